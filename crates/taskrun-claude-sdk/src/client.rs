@@ -2,10 +2,15 @@
 //!
 //! This module provides ready-to-use implementations of the `ControlHandler` trait.
 
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
 use async_trait::async_trait;
 use serde_json::{json, Value};
 use tokio::sync::mpsc;
-use tracing::trace;
+use tokio::time::Instant;
+use tracing::{debug, trace};
 
 use crate::error::SdkError;
 use crate::protocol::ControlHandler;
@@ -36,6 +41,7 @@ use crate::types::{ClaudeMessage, PermissionResult};
 /// ```
 pub struct AutoApproveHandler {
     message_tx: mpsc::UnboundedSender<ClaudeMessage>,
+    thinking: bool,
 }
 
 impl AutoApproveHandler {
@@ -44,7 +50,19 @@ impl AutoApproveHandler {
     /// Returns the handler and a receiver for streaming messages.
     pub fn new() -> (Self, mpsc::UnboundedReceiver<ClaudeMessage>) {
         let (tx, rx) = mpsc::unbounded_channel();
-        (Self { message_tx: tx }, rx)
+        (
+            Self {
+                message_tx: tx,
+                thinking: false,
+            },
+            rx,
+        )
+    }
+
+    /// Also forward thinking blocks and deltas.
+    pub fn with_thinking(mut self, enabled: bool) -> Self {
+        self.thinking = enabled;
+        self
     }
 
     /// Create a handler with a bounded channel.
@@ -54,7 +72,17 @@ impl AutoApproveHandler {
         capacity: usize,
     ) -> (BoundedAutoApproveHandler, mpsc::Receiver<ClaudeMessage>) {
         let (tx, rx) = mpsc::channel(capacity);
-        (BoundedAutoApproveHandler { message_tx: tx }, rx)
+        (
+            BoundedAutoApproveHandler {
+                message_tx: tx,
+                thinking: false,
+                budget: ApprovalBudget::default(),
+                used: Mutex::new(HashMap::new()),
+                started: Instant::now(),
+                on_exhausted: None,
+            },
+            rx,
+        )
     }
 }
 
@@ -91,14 +119,156 @@ impl ControlHandler for AutoApproveHandler {
         self.message_tx.send(message).ok();
         Ok(())
     }
+
+    fn wants_thinking(&self) -> bool {
+        self.thinking
+    }
+}
+
+/// How many uses of each tool may be auto-approved, and for how long.
+///
+/// By default every tool is approved without limit and without expiry.
+///
+/// # Example
+///
+/// ```rust
+/// use std::time::Duration;
+/// use taskrun_claude_sdk::ApprovalBudget;
+///
+/// // Up to 5 Bash calls, any number of Reads, 2 uses of any other tool,
+/// // nothing auto-approved after 10 minutes
+/// let budget = ApprovalBudget::default()
+///     .with_tool_limit("Bash", 5)
+///     .with_unlimited_tool("Read")
+///     .with_default_limit(2)
+///     .with_expiry(Duration::from_secs(600));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ApprovalBudget {
+    /// Limits of specific tools (None = unlimited).
+    tools: HashMap<String, Option<u32>>,
+    /// Limit of tools without their own (None = unlimited).
+    default_limit: Option<u32>,
+    /// Time after which nothing is auto-approved.
+    expires_after: Option<Duration>,
+}
+
+impl ApprovalBudget {
+    /// Auto-approve at most `limit` uses of `tool`.
+    pub fn with_tool_limit(mut self, tool: impl Into<String>, limit: u32) -> Self {
+        self.tools.insert(tool.into(), Some(limit));
+        self
+    }
+
+    /// Auto-approve every use of `tool`, even with a default limit.
+    pub fn with_unlimited_tool(mut self, tool: impl Into<String>) -> Self {
+        self.tools.insert(tool.into(), None);
+        self
+    }
+
+    /// Auto-approve at most `limit` uses of each tool without its own limit.
+    pub fn with_default_limit(mut self, limit: u32) -> Self {
+        self.default_limit = Some(limit);
+        self
+    }
+
+    /// Stop auto-approving `after` the handler was created.
+    pub fn with_expiry(mut self, after: Duration) -> Self {
+        self.expires_after = Some(after);
+        self
+    }
+
+    /// The limit that applies to `tool` (None = unlimited).
+    pub fn limit(&self, tool: &str) -> Option<u32> {
+        match self.tools.get(tool) {
+            Some(limit) => *limit,
+            None => self.default_limit,
+        }
+    }
+}
+
+/// Why a tool use was not auto-approved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BudgetExhaustion {
+    /// The tool was already approved `limit` times.
+    Limit { limit: u32 },
+    /// The budget's expiry passed.
+    Expired,
+}
+
+/// A tool use past the approval budget.
+#[derive(Debug, Clone)]
+pub struct ExhaustedToolUse {
+    pub tool_name: String,
+    pub input: Value,
+    pub reason: BudgetExhaustion,
+}
+
+/// Decides tool uses past the approval budget, e.g. by asking a human.
+#[async_trait]
+pub trait BudgetExhaustedHandler: Send + Sync {
+    async fn on_budget_exhausted(
+        &self,
+        tool_use: ExhaustedToolUse,
+    ) -> Result<PermissionResult, SdkError>;
 }
 
 /// A bounded variant of AutoApproveHandler.
 ///
 /// Uses a bounded channel which provides backpressure when the receiver
-/// is slow to process messages.
+/// is slow to process messages. Auto-approvals can be limited with an
+/// `ApprovalBudget`; tool uses past it go to the `BudgetExhaustedHandler`,
+/// or are denied if there is none. The budget applies to `can_use_tool`
+/// requests; hook callbacks are always approved.
 pub struct BoundedAutoApproveHandler {
     message_tx: mpsc::Sender<ClaudeMessage>,
+    thinking: bool,
+    budget: ApprovalBudget,
+    /// Auto-approved uses per tool.
+    used: Mutex<HashMap<String, u32>>,
+    started: Instant,
+    on_exhausted: Option<Arc<dyn BudgetExhaustedHandler>>,
+}
+
+impl BoundedAutoApproveHandler {
+    /// Also forward thinking blocks and deltas.
+    pub fn with_thinking(mut self, enabled: bool) -> Self {
+        self.thinking = enabled;
+        self
+    }
+
+    /// Limit auto-approvals. The expiry counts from when the handler was created.
+    pub fn with_budget(mut self, budget: ApprovalBudget) -> Self {
+        self.budget = budget;
+        self
+    }
+
+    /// Let `handler` decide tool uses past the budget instead of denying them.
+    pub fn with_exhausted_handler(mut self, handler: Arc<dyn BudgetExhaustedHandler>) -> Self {
+        self.on_exhausted = Some(handler);
+        self
+    }
+
+    /// Count a use of `tool` against the budget. Returns why it is past the
+    /// budget, or None if it is approved.
+    fn take(&self, tool: &str) -> Option<BudgetExhaustion> {
+        if self
+            .budget
+            .expires_after
+            .is_some_and(|after| self.started.elapsed() >= after)
+        {
+            return Some(BudgetExhaustion::Expired);
+        }
+        let mut used = self.used.lock().unwrap();
+        let count = used.entry(tool.to_string()).or_default();
+        match self.budget.limit(tool) {
+            Some(limit) if *count >= limit => Some(BudgetExhaustion::Limit { limit }),
+            _ => {
+                *count += 1;
+                None
+            }
+        }
+    }
 }
 
 #[async_trait]
@@ -108,10 +278,34 @@ impl ControlHandler for BoundedAutoApproveHandler {
         tool_name: String,
         input: Value,
     ) -> Result<PermissionResult, SdkError> {
-        trace!("Auto-approving tool: {}", tool_name);
-        Ok(PermissionResult::Allow {
-            updated_input: input,
-            updated_permissions: None,
+        let Some(reason) = self.take(&tool_name) else {
+            trace!("Auto-approving tool: {}", tool_name);
+            return Ok(PermissionResult::Allow {
+                updated_input: input,
+                updated_permissions: None,
+            });
+        };
+
+        debug!(tool = %tool_name, reason = ?reason, "Approval budget exhausted");
+        if let Some(handler) = &self.on_exhausted {
+            return handler
+                .on_budget_exhausted(ExhaustedToolUse {
+                    tool_name,
+                    input,
+                    reason,
+                })
+                .await;
+        }
+        let message = match reason {
+            BudgetExhaustion::Limit { limit } => format!(
+                "Approval budget for {} exhausted ({} uses)",
+                tool_name, limit
+            ),
+            BudgetExhaustion::Expired => "Auto-approval expired".to_string(),
+        };
+        Ok(PermissionResult::Deny {
+            message,
+            interrupt: Some(false),
         })
     }
 
@@ -137,6 +331,10 @@ impl ControlHandler for BoundedAutoApproveHandler {
             .map_err(|_| SdkError::ChannelClosed)?;
         Ok(())
     }
+
+    fn wants_thinking(&self) -> bool {
+        self.thinking
+    }
 }
 
 /// A handler that denies all tool uses.
@@ -232,6 +430,74 @@ mod tests {
         assert!(matches!(received, ClaudeMessage::System { .. }));
     }
 
+    fn allowed(result: &PermissionResult) -> bool {
+        matches!(result, PermissionResult::Allow { .. })
+    }
+
+    #[tokio::test]
+    async fn test_bounded_handler_budget_per_tool() {
+        let budget = ApprovalBudget::default()
+            .with_tool_limit("Bash", 2)
+            .with_unlimited_tool("Read")
+            .with_default_limit(0);
+        let (handler, _rx) = AutoApproveHandler::with_capacity(8);
+        let handler = handler.with_budget(budget);
+
+        for _ in 0..5 {
+            let read = handler.on_can_use_tool("Read".into(), json!({})).await;
+            assert!(allowed(&read.unwrap()));
+        }
+        for expected in [true, true, false] {
+            let bash = handler.on_can_use_tool("Bash".into(), json!({})).await;
+            assert_eq!(allowed(&bash.unwrap()), expected);
+        }
+        match handler.on_can_use_tool("Write".into(), json!({})).await {
+            Ok(PermissionResult::Deny { message, .. }) => {
+                assert_eq!(message, "Approval budget for Write exhausted (0 uses)");
+            }
+            _ => panic!("Expected Deny"),
+        }
+    }
+
+    struct Escalate(Mutex<Vec<ExhaustedToolUse>>);
+
+    #[async_trait]
+    impl BudgetExhaustedHandler for Escalate {
+        async fn on_budget_exhausted(
+            &self,
+            tool_use: ExhaustedToolUse,
+        ) -> Result<PermissionResult, SdkError> {
+            let input = tool_use.input.clone();
+            self.0.lock().unwrap().push(tool_use);
+            Ok(PermissionResult::Allow {
+                updated_input: input,
+                updated_permissions: None,
+            })
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_bounded_handler_expiry_escalates() {
+        let escalate = Arc::new(Escalate(Mutex::new(Vec::new())));
+        let (handler, _rx) = AutoApproveHandler::with_capacity(8);
+        let handler = handler
+            .with_budget(ApprovalBudget::default().with_expiry(Duration::from_secs(60)))
+            .with_exhausted_handler(escalate.clone());
+
+        let before = handler.on_can_use_tool("Bash".into(), json!({})).await;
+        assert!(allowed(&before.unwrap()));
+        assert!(escalate.0.lock().unwrap().is_empty());
+
+        tokio::time::advance(Duration::from_secs(61)).await;
+        let after = handler
+            .on_can_use_tool("Bash".into(), json!({"command": "ls"}))
+            .await;
+        assert!(allowed(&after.unwrap()));
+        let escalated = escalate.0.lock().unwrap();
+        assert_eq!(escalated[0].reason, BudgetExhaustion::Expired);
+        assert_eq!(escalated[0].input["command"], "ls");
+    }
+
     #[tokio::test]
     async fn test_deny_all_handler() {
         let (handler, _rx) = DenyAllHandler::new("Not allowed in test mode");
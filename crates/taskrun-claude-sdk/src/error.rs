@@ -2,6 +2,8 @@
 
 use thiserror::Error;
 
+use crate::limits::LimitKind;
+
 /// Errors that can occur during Claude Code SDK operations.
 #[derive(Debug, Error)]
 pub enum SdkError {
@@ -29,6 +31,14 @@ pub enum SdkError {
     #[error("Channel closed")]
     ChannelClosed,
 
+    /// The Claude process was stopped by one of its resource limits.
+    #[error("Claude process hit its {0} limit")]
+    ResourceLimit(LimitKind),
+
+    /// Resource limits could not be set up.
+    #[error("Failed to set up resource limits: {0}")]
+    LimitSetup(String),
+
     /// Timeout waiting for response.
     #[error("Timeout waiting for response")]
     Timeout,
@@ -5,16 +5,31 @@
 
 use std::path::Path;
 use std::process::Stdio;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 
 use tokio::io::{AsyncBufReadExt, BufReader};
-use tokio::process::Command;
-use tracing::{debug, error, info, warn};
+use tokio::process::{Child, ChildStdout, Command};
+use tracing::{debug, error, info, warn, Instrument};
 
 use crate::error::SdkError;
-use crate::protocol::ControlHandler;
+use crate::limits::{Confinement, LimitKind, ResourceLimits};
+use crate::protocol::{self, ControlHandler, ProtocolPeer};
+use crate::sandbox::Sandbox;
 use crate::types::PermissionMode;
 
+/// Callback ID of the PreToolUse hook, as passed to
+/// `ControlHandler::on_hook_callback`.
+pub const PRE_TOOL_USE_HOOK: &str = "pre_tool_use";
+
+/// How long a failed execution waits for the rest of its stderr.
+const STDERR_DRAIN_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// How long an execution waits for the handler to see Claude's last
+/// messages after it exited.
+const STDOUT_DRAIN_TIMEOUT: Duration = Duration::from_secs(5);
+
 /// Result of a Claude Code execution.
 #[derive(Debug, Clone)]
 pub struct ExecutionResult {
@@ -73,17 +88,39 @@ pub struct ClaudeExecutor {
     /// Maximum thinking tokens (optional).
     max_thinking_tokens: Option<u32>,
 
+    /// Maximum agentic turns (optional).
+    max_turns: Option<u32>,
+
     /// System prompt (optional).
     system_prompt: Option<String>,
 
     /// Additional environment variables.
     env_vars: Vec<(String, String)>,
 
+    /// Inherited environment variables to leave out.
+    env_removed: Vec<String>,
+
     /// Tools to allow (--allowedTools).
     allowed_tools: Vec<String>,
 
     /// Tools to disallow (--disallowedTools).
     disallowed_tools: Vec<String>,
+
+    /// Route permission prompts to the handler's `on_can_use_tool`.
+    permission_prompts: bool,
+
+    /// Tools (a matcher such as `Read|Bash`) whose every use is first
+    /// passed to the handler's `on_hook_callback`.
+    pre_tool_use_hook: Option<String>,
+
+    /// MCP server configuration JSON (--mcp-config).
+    mcp_config: Option<String>,
+
+    /// Limits on the process and its children.
+    limits: ResourceLimits,
+
+    /// Container to run the process in (optional).
+    sandbox: Option<Sandbox>,
 }
 
 impl ClaudeExecutor {
@@ -96,10 +133,17 @@ impl ClaudeExecutor {
             permission_mode: PermissionMode::Default,
             model: None,
             max_thinking_tokens: None,
+            max_turns: None,
             system_prompt: None,
             env_vars: Vec::new(),
+            env_removed: Vec::new(),
             allowed_tools: Vec::new(),
             disallowed_tools: Vec::new(),
+            permission_prompts: false,
+            pre_tool_use_hook: None,
+            mcp_config: None,
+            limits: ResourceLimits::default(),
+            sandbox: None,
         }
     }
 
@@ -121,6 +165,12 @@ impl ClaudeExecutor {
         self
     }
 
+    /// Stop after this many agentic turns.
+    pub fn with_max_turns(mut self, turns: u32) -> Self {
+        self.max_turns = Some(turns);
+        self
+    }
+
     /// Set a system prompt.
     pub fn with_system_prompt(mut self, prompt: impl Into<String>) -> Self {
         self.system_prompt = Some(prompt.into());
@@ -133,6 +183,12 @@ impl ClaudeExecutor {
         self
     }
 
+    /// Leave an inherited environment variable out of the process's environment.
+    pub fn without_env(mut self, key: impl Into<String>) -> Self {
+        self.env_removed.push(key.into());
+        self
+    }
+
     /// Set allowed tools.
     pub fn with_allowed_tools(mut self, tools: Vec<String>) -> Self {
         self.allowed_tools = tools;
@@ -145,6 +201,53 @@ impl ClaudeExecutor {
         self
     }
 
+    /// Ask the handler before tool uses that need permission.
+    ///
+    /// Runs Claude with the control protocol on stdin (`--input-format=stream-json
+    /// --permission-prompt-tool stdio`), so each permission prompt reaches
+    /// `ControlHandler::on_can_use_tool`. Has no effect with
+    /// `PermissionMode::BypassPermissions`, which never prompts.
+    pub fn with_permission_prompts(mut self, enabled: bool) -> Self {
+        self.permission_prompts = enabled;
+        self
+    }
+
+    /// Pass each use of the tools `matcher` names (`Read|Bash`, or `*` for
+    /// all) to `ControlHandler::on_hook_callback` with callback ID
+    /// [`PRE_TOOL_USE_HOOK`] before it runs, in any permission mode.
+    ///
+    /// The handler answers with hook output: `{}` lets the tool use go on
+    /// as usual, and a `permissionDecision` of `deny` in
+    /// `hookSpecificOutput` refuses it. Runs Claude with the control
+    /// protocol on stdin, as permission prompts do.
+    pub fn with_pre_tool_use_hook(mut self, matcher: impl Into<String>) -> Self {
+        self.pre_tool_use_hook = Some(matcher.into());
+        self
+    }
+
+    /// Load MCP servers from a JSON config (`--mcp-config`), either a file
+    /// path or the JSON itself.
+    pub fn with_mcp_config(mut self, config: impl Into<String>) -> Self {
+        self.mcp_config = Some(config.into());
+        self
+    }
+
+    /// Limit the memory, CPU and processes of each execution, and how long
+    /// it runs.
+    ///
+    /// An execution stopped by a limit fails with `SdkError::ResourceLimit`.
+    pub fn with_resource_limits(mut self, limits: ResourceLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Run each execution in a container. The resource limits are then
+    /// applied to the container.
+    pub fn with_sandbox(mut self, sandbox: Sandbox) -> Self {
+        self.sandbox = Some(sandbox);
+        self
+    }
+
     /// Execute a prompt with Claude Code.
     ///
     /// This spawns a new Claude process and runs the given prompt.
@@ -205,6 +308,10 @@ impl ClaudeExecutor {
             cmd.arg("--max-thinking-tokens").arg(tokens.to_string());
         }
 
+        if let Some(turns) = self.max_turns {
+            cmd.arg("--max-turns").arg(turns.to_string());
+        }
+
         if let Some(system) = &self.system_prompt {
             cmd.arg("--system-prompt").arg(system);
         }
@@ -217,13 +324,31 @@ impl ClaudeExecutor {
             cmd.arg("--disallowedTools").arg(tool);
         }
 
+        // Extra MCP servers
+        if let Some(config) = &self.mcp_config {
+            cmd.arg("--mcp-config").arg(config);
+        }
+
         // Permission mode
-        if self.permission_mode == PermissionMode::BypassPermissions {
+        let bypass = self.permission_mode == PermissionMode::BypassPermissions;
+        if bypass {
             cmd.arg("--dangerously-skip-permissions");
             cmd.env("CLAUDE_CODE_ALLOW_DANGEROUSLY_SKIP_PERMISSIONS", "true");
             info!("Using --dangerously-skip-permissions flag");
         }
 
+        // Permission prompts and hooks go over the control protocol, so the
+        // prompt is sent on stdin rather than as an argument
+        let prompts = self.permission_prompts && !bypass;
+        let control = prompts || self.pre_tool_use_hook.is_some();
+        if control {
+            cmd.arg("--input-format=stream-json");
+        }
+        if prompts {
+            cmd.arg("--permission-prompt-tool").arg("stdio");
+            info!("Routing permission prompts over the control protocol");
+        }
+
         // Session continuation (--resume for specific session ID)
         if let Some(sid) = session_id {
             cmd.arg("--resume").arg(sid);
@@ -231,19 +356,55 @@ impl ClaudeExecutor {
         }
 
         // The prompt itself
-        cmd.arg("--print").arg(prompt);
-
-        // Configure stdio - no stdin needed for one-shot mode
-        cmd.stdin(Stdio::null())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .current_dir(working_dir);
+        cmd.arg("--print");
+        if !control {
+            cmd.arg(prompt);
+        }
 
         // Add environment variables
+        for key in &self.env_removed {
+            cmd.env_remove(key);
+        }
         for (key, value) in &self.env_vars {
             cmd.env(key, value);
         }
 
+        // In a sandbox the container gets the limits; either is kept until the
+        // process exits and dropping it cleans up after the tree
+        let (mut cmd, container) = match &self.sandbox {
+            Some(sandbox) => {
+                let (cmd, container) = sandbox.wrap(&cmd, working_dir, &self.limits)?;
+                (cmd, Some(container))
+            }
+            None => (cmd, None),
+        };
+        let confinement = if container.is_some() {
+            Confinement::default()
+        } else {
+            self.limits.apply(&mut cmd)?
+        };
+
+        // Configure stdio - stdin only carries the control protocol
+        cmd.stdin(if control {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .current_dir(working_dir);
+
+        // Dropping the execution future (e.g. on run cancellation) kills the process
+        cmd.kill_on_drop(true);
+
+        // Out of time, the whole process group is killed: whatever the
+        // process started would otherwise hold its output open
+        let own_group = cfg!(unix) && self.limits.max_wall_time().is_some() && container.is_none();
+        #[cfg(unix)]
+        if own_group {
+            cmd.process_group(0);
+        }
+
         info!("Spawning Claude process with args: --output-format=stream-json --print <prompt>");
         debug!("Full command: {:?}", cmd);
 
@@ -266,81 +427,75 @@ impl ClaudeExecutor {
         info!("Got stdout/stderr handles");
 
         // Spawn stderr reader for logging
-        tokio::spawn(async move {
-            let mut reader = BufReader::new(stderr);
-            let mut line = String::new();
-            loop {
-                line.clear();
-                match reader.read_line(&mut line).await {
-                    Ok(0) => break, // EOF
-                    Ok(_) => {
-                        let trimmed = line.trim();
-                        if !trimmed.is_empty() {
-                            warn!(stderr = %trimmed, "Claude stderr");
-                        }
-                    }
-                    Err(e) => {
-                        error!(error = %e, "Error reading Claude stderr");
-                        break;
-                    }
-                }
-            }
-        });
-
-        // Spawn stdout reader that forwards messages to handler
-        info!("Starting stdout reader for JSON messages");
-        let handler_clone = handler.clone();
-        tokio::spawn(async move {
-            let mut reader = BufReader::new(stdout);
-            let mut line = String::new();
-            let mut message_count = 0u64;
-
-            loop {
-                line.clear();
-                match reader.read_line(&mut line).await {
-                    Ok(0) => {
-                        info!(total_messages = message_count, "Claude stdout closed (EOF)");
-                        break;
-                    }
-                    Ok(bytes) => {
-                        let trimmed = line.trim();
-                        if trimmed.is_empty() {
-                            continue;
-                        }
-
-                        message_count += 1;
-                        info!(
-                            message_num = message_count,
-                            bytes = bytes,
-                            "Received message from Claude"
-                        );
-
-                        match serde_json::from_str::<crate::types::ClaudeMessage>(trimmed) {
-                            Ok(message) => {
-                                if let Err(e) = handler_clone.on_message(message).await {
-                                    warn!(error = %e, "Handler error processing message");
-                                }
+        let out_of_memory = Arc::new(AtomicBool::new(false));
+        let stderr_out_of_memory = out_of_memory.clone();
+        let stderr_reader = tokio::spawn(
+            async move {
+                let mut reader = BufReader::new(stderr);
+                let mut line = String::new();
+                loop {
+                    line.clear();
+                    match reader.read_line(&mut line).await {
+                        Ok(0) => break, // EOF
+                        Ok(_) => {
+                            let trimmed = line.trim();
+                            if !trimmed.is_empty() {
+                                warn!(stderr = %trimmed, "Claude stderr");
                             }
-                            Err(e) => {
-                                warn!(error = %e, "Failed to parse Claude message");
-                                // Log first 200 chars for debugging
-                                let preview: String = trimmed.chars().take(200).collect();
-                                warn!(preview = %preview, "Message preview");
+                            if trimmed.to_lowercase().contains("out of memory") {
+                                stderr_out_of_memory.store(true, Ordering::Relaxed);
                             }
                         }
-                    }
-                    Err(e) => {
-                        error!(error = %e, "Error reading Claude stdout");
-                        break;
+                        Err(e) => {
+                            error!(error = %e, "Error reading Claude stderr");
+                            break;
+                        }
                     }
                 }
             }
-        });
+            .in_current_span(),
+        );
+
+        // Over the control protocol, the protocol peer reads stdout and
+        // answers control requests; keep it alive until the process exits
+        let mut stdout_reader = None;
+        let peer = if control {
+            let stdin = child
+                .stdin
+                .take()
+                .ok_or_else(|| SdkError::ProtocolError("Failed to get stdin".to_string()))?;
+            let peer = ProtocolPeer::spawn(stdin, stdout, handler.clone());
+            if let Some(matcher) = &self.pre_tool_use_hook {
+                peer.initialize(Some(pre_tool_use_hooks(matcher))).await?;
+                info!(matcher = %matcher, "Registered PreToolUse hook");
+            }
+            peer.send_user_message(prompt).await?;
+            Some(peer)
+        } else {
+            stdout_reader = Some(Self::spawn_stdout_reader(stdout, handler.clone()));
+            None
+        };
 
         info!("Waiting for Claude process to complete...");
 
-        // Wait for process to complete
-        let status = child.wait().await?;
+        // Wait for process to complete, killing it once out of time
+        let status = match self.limits.max_wall_time() {
+            Some(limit) => match tokio::time::timeout(limit, child.wait()).await {
+                Ok(status) => status?,
+                Err(_) => {
+                    warn!(
+                        limit_secs = limit.as_secs_f64(),
+                        "Claude process ran out of time, killing it"
+                    );
+                    if own_group {
+                        kill_process_group(&child);
+                    }
+                    let _ = child.kill().await;
+                    return Err(SdkError::ResourceLimit(LimitKind::WallClock));
+                }
+            },
+            None => child.wait().await?,
+        };
 
         let exit_code = status.code().unwrap_or(-1);
         info!(
@@ -350,12 +505,33 @@ impl ClaudeExecutor {
         );
 
         if !status.success() {
+            // Let the reader see the last lines before looking for an OOM report
+            let _ = tokio::time::timeout(STDERR_DRAIN_TIMEOUT, stderr_reader).await;
+            let limit = match &container {
+                Some(container) => container.limit_hit(exit_code),
+                None => confinement
+                    .limit_hit(exit_signal(&status), out_of_memory.load(Ordering::Relaxed)),
+            };
+            if let Some(limit) = limit {
+                warn!(limit = %limit, "Claude process hit a resource limit");
+                return Err(SdkError::ResourceLimit(limit));
+            }
             return Err(SdkError::ProcessError(format!(
                 "Claude exited with code {}",
                 exit_code
             )));
         }
 
+        // Messages are handled off this task; let the handler see the last
+        // ones (the result) before returning
+        match (&peer, stdout_reader) {
+            (Some(peer), _) => peer.drain(STDOUT_DRAIN_TIMEOUT).await,
+            (None, Some(reader)) => {
+                let _ = tokio::time::timeout(STDOUT_DRAIN_TIMEOUT, reader).await;
+            }
+            (None, None) => {}
+        }
+
         // Note: In a real implementation, we'd capture session_id and model from
         // the messages received by the handler. For now, return placeholder values.
         Ok(ExecutionResult {
@@ -366,6 +542,63 @@ impl ClaudeExecutor {
             error_message: None,
         })
     }
+
+    /// Spawn a stdout reader that forwards messages to the handler.
+    fn spawn_stdout_reader(
+        stdout: ChildStdout,
+        handler: Arc<dyn ControlHandler>,
+    ) -> tokio::task::JoinHandle<()> {
+        info!("Starting stdout reader for JSON messages");
+        tokio::spawn(
+            async move {
+                let mut reader = BufReader::new(stdout);
+                let mut line = String::new();
+                let mut message_count = 0u64;
+
+                loop {
+                    line.clear();
+                    match reader.read_line(&mut line).await {
+                        Ok(0) => {
+                            info!(total_messages = message_count, "Claude stdout closed (EOF)");
+                            break;
+                        }
+                        Ok(bytes) => {
+                            let trimmed = line.trim();
+                            if trimmed.is_empty() {
+                                continue;
+                            }
+
+                            message_count += 1;
+                            info!(
+                                message_num = message_count,
+                                bytes = bytes,
+                                "Received message from Claude"
+                            );
+
+                            match serde_json::from_str::<crate::types::ClaudeMessage>(trimmed) {
+                                Ok(message) => {
+                                    if let Err(e) = protocol::deliver(&handler, message).await {
+                                        warn!(error = %e, "Handler error processing message");
+                                    }
+                                }
+                                Err(e) => {
+                                    warn!(error = %e, "Failed to parse Claude message");
+                                    // Log first 200 chars for debugging
+                                    let preview: String = trimmed.chars().take(200).collect();
+                                    warn!(preview = %preview, "Message preview");
+                                }
+                            }
+                        }
+                        Err(e) => {
+                            error!(error = %e, "Error reading Claude stdout");
+                            break;
+                        }
+                    }
+                }
+            }
+            .in_current_span(),
+        )
+    }
 }
 
 /// Builder for creating ClaudeExecutor with additional configuration.
@@ -375,9 +608,43 @@ impl Default for ClaudeExecutor {
     }
 }
 
+/// Hooks of the initialize request for a PreToolUse hook on `matcher`.
+fn pre_tool_use_hooks(matcher: &str) -> serde_json::Value {
+    serde_json::json!({
+        "PreToolUse": [{ "matcher": matcher, "hookCallbackIds": [PRE_TOOL_USE_HOOK] }]
+    })
+}
+
+/// Kill the process group `child` leads.
+#[cfg(unix)]
+fn kill_process_group(child: &Child) {
+    if let Some(pid) = child.id() {
+        // SAFETY: killpg has no memory safety requirements
+        unsafe { libc::killpg(pid as libc::pid_t, libc::SIGKILL) };
+    }
+}
+
+#[cfg(not(unix))]
+fn kill_process_group(_child: &Child) {}
+
+/// The signal that ended a process, if one did.
+#[cfg(unix)]
+fn exit_signal(status: &std::process::ExitStatus) -> Option<i32> {
+    use std::os::unix::process::ExitStatusExt;
+    status.signal()
+}
+
+#[cfg(not(unix))]
+fn exit_signal(_status: &std::process::ExitStatus) -> Option<i32> {
+    None
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::types::{ClaudeMessage, PermissionResult};
+    use async_trait::async_trait;
+    use serde_json::Value;
 
     #[test]
     fn test_executor_builder() {
@@ -385,6 +652,7 @@ mod tests {
             .with_model("claude-sonnet-4-20250514")
             .with_permission_mode(PermissionMode::BypassPermissions)
             .with_max_thinking_tokens(10000)
+            .with_max_turns(1)
             .with_system_prompt("You are a helpful assistant.")
             .with_env("ANTHROPIC_API_KEY", "test-key");
 
@@ -392,6 +660,7 @@ mod tests {
         assert_eq!(executor.model, Some("claude-sonnet-4-20250514".to_string()));
         assert_eq!(executor.permission_mode, PermissionMode::BypassPermissions);
         assert_eq!(executor.max_thinking_tokens, Some(10000));
+        assert_eq!(executor.max_turns, Some(1));
         assert_eq!(
             executor.system_prompt,
             Some("You are a helpful assistant.".to_string())
@@ -399,11 +668,88 @@ mod tests {
         assert_eq!(executor.env_vars.len(), 1);
     }
 
+    /// Records messages, slowly, like a handler forwarding them elsewhere.
+    struct SlowHandler {
+        messages: std::sync::Mutex<Vec<ClaudeMessage>>,
+    }
+
+    #[async_trait]
+    impl ControlHandler for SlowHandler {
+        async fn on_can_use_tool(
+            &self,
+            _tool_name: String,
+            input: Value,
+        ) -> Result<PermissionResult, SdkError> {
+            Ok(PermissionResult::Allow {
+                updated_input: input,
+                updated_permissions: None,
+            })
+        }
+
+        async fn on_hook_callback(
+            &self,
+            _callback_id: String,
+            _input: Value,
+            _tool_use_id: Option<String>,
+        ) -> Result<Value, SdkError> {
+            Ok(Value::Null)
+        }
+
+        async fn on_message(&self, message: ClaudeMessage) -> Result<(), SdkError> {
+            tokio::time::sleep(Duration::from_millis(200)).await;
+            self.messages.lock().unwrap().push(message);
+            Ok(())
+        }
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_result_reaches_handler_before_execute_returns() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = std::env::temp_dir().join(format!("taskrun-sdk-drain-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        // Answers the prompt with a result and exits at once
+        let claude = dir.join("claude");
+        std::fs::write(
+            &claude,
+            "#!/bin/sh\nread -r prompt\necho '{\"type\":\"result\",\"is_error\":false,\"usage\":{\"input_tokens\":12,\"output_tokens\":85}}'\n",
+        )
+        .unwrap();
+        std::fs::set_permissions(&claude, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        // Both with stdout read directly and over the control protocol
+        for prompts in [false, true] {
+            let handler = Arc::new(SlowHandler {
+                messages: std::sync::Mutex::new(Vec::new()),
+            });
+            ClaudeExecutor::new(claude.display().to_string())
+                .with_permission_prompts(prompts)
+                .execute(&dir, "Summarize", handler.clone())
+                .await
+                .unwrap();
+
+            let messages = handler.messages.lock().unwrap();
+            assert!(
+                matches!(
+                    messages.as_slice(),
+                    [ClaudeMessage::Result { usage: Some(_), .. }]
+                ),
+                "prompts={}: {:?}",
+                prompts,
+                messages
+            );
+        }
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
     #[test]
     fn test_default_executor() {
         let executor = ClaudeExecutor::default();
         assert_eq!(executor.claude_path, "claude");
         assert_eq!(executor.permission_mode, PermissionMode::Default);
         assert!(executor.model.is_none());
+        assert!(!executor.permission_prompts);
     }
 }
@@ -30,17 +30,24 @@
 mod client;
 mod error;
 mod executor;
+mod limits;
 mod protocol;
+mod sandbox;
 mod types;
 
 // Re-export main types
-pub use client::{AutoApproveHandler, BoundedAutoApproveHandler, DenyAllHandler};
+pub use client::{
+    ApprovalBudget, AutoApproveHandler, BoundedAutoApproveHandler, BudgetExhaustedHandler,
+    BudgetExhaustion, DenyAllHandler, ExhaustedToolUse,
+};
 pub use error::SdkError;
-pub use executor::{ClaudeExecutor, ExecutionResult};
+pub use executor::{ClaudeExecutor, ExecutionResult, PRE_TOOL_USE_HOOK};
+pub use limits::{LimitKind, ResourceLimits};
 pub use protocol::ControlHandler;
+pub use sandbox::{Sandbox, SandboxRuntime};
 pub use types::{
     AssistantMessage, ClaudeMessage, ContentDelta, ContentItem, ControlRequest, ControlResponse,
     MessageDelta, PermissionMode, PermissionResult, PermissionUpdate, PermissionUpdateDestination,
-    PermissionUpdateType, SdkControlRequest, SdkControlRequestType, StreamEvent, ToolData,
-    UserMessage,
+    PermissionUpdateType, ResultUsage, SdkControlRequest, SdkControlRequestType, StreamEvent,
+    ToolData, UserMessage,
 };
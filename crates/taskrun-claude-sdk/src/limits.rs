@@ -0,0 +1,409 @@
+//! Resource limits for the Claude process and the processes it starts.
+//!
+//! With a cgroup parent (a cgroup v2 directory delegated to the worker, with
+//! the `memory`, `cpu` and `pids` controllers enabled for its children), each
+//! execution runs in a cgroup of its own below it. Memory is capped on
+//! resident memory (`memory.max`), CPU is shared by weight (`cpu.weight`) and
+//! capped in CPUs (`cpu.max`), and processes are capped in number
+//! (`pids.max`), all across the whole process tree. Whatever is left in the
+//! cgroup when the execution ends is killed.
+//!
+//! Without a cgroup parent, rlimits are set instead. They are weaker: memory
+//! caps each process's data segment (`RLIMIT_DATA`), the process cap counts
+//! every process of the user (`RLIMIT_NPROC`), and CPU limits can't be set.
+//!
+//! CPU time is capped per process (`RLIMIT_CPU`) either way; a process over
+//! it gets `SIGXCPU`. Wall-clock time is enforced by the executor, which
+//! kills the process and what it started once it runs out.
+//!
+//! In a sandbox (see `Sandbox`) the limits are the container's instead.
+//!
+//! On Linux the process can also get a network namespace of its own, with no
+//! interface but loopback. That cuts off the Anthropic API as well, so it is
+//! only useful when the CLI reaches its model without the network.
+
+use std::fmt;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+
+use std::time::Duration;
+
+use tokio::process::Command;
+use tracing::warn;
+
+use crate::error::SdkError;
+
+/// CPU seconds past the CPU time limit before a process ignoring `SIGXCPU`
+/// is killed.
+pub(crate) const CPU_KILL_GRACE_SECS: u64 = 5;
+
+/// A limit the Claude process ran into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LimitKind {
+    Memory,
+    Processes,
+    CpuTime,
+    WallClock,
+}
+
+impl fmt::Display for LimitKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LimitKind::Memory => write!(f, "memory"),
+            LimitKind::Processes => write!(f, "process"),
+            LimitKind::CpuTime => write!(f, "CPU time"),
+            LimitKind::WallClock => write!(f, "wall-clock time"),
+        }
+    }
+}
+
+/// Limits applied to each execution. None are set by default.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ResourceLimits {
+    pub(crate) max_memory_bytes: Option<u64>,
+    pub(crate) cpu_weight: Option<u32>,
+    /// CPU cap in thousandths of a CPU.
+    pub(crate) max_millicpus: Option<u64>,
+    pub(crate) max_processes: Option<u64>,
+    pub(crate) max_cpu_seconds: Option<u64>,
+    pub(crate) max_wall_time: Option<Duration>,
+    pub(crate) no_network: bool,
+    cgroup_parent: Option<PathBuf>,
+}
+
+impl ResourceLimits {
+    /// Cap memory at `bytes`.
+    pub fn with_max_memory(mut self, bytes: u64) -> Self {
+        self.max_memory_bytes = Some(bytes);
+        self
+    }
+
+    /// Share CPU by `weight` (1 to 10000, 100 being the default share).
+    /// Needs a cgroup parent.
+    pub fn with_cpu_weight(mut self, weight: u32) -> Self {
+        self.cpu_weight = Some(weight.clamp(1, 10_000));
+        self
+    }
+
+    /// Cap CPU time at `cpus` CPUs (e.g. 1.5). Needs a cgroup parent.
+    pub fn with_max_cpus(mut self, cpus: f64) -> Self {
+        self.max_millicpus = Some((cpus * 1000.0).round().max(1.0) as u64);
+        self
+    }
+
+    /// Cap the number of processes.
+    pub fn with_max_processes(mut self, processes: u64) -> Self {
+        self.max_processes = Some(processes);
+        self
+    }
+
+    /// Cap the CPU time of each process at `time` (rounded up to a second).
+    pub fn with_max_cpu_time(mut self, time: Duration) -> Self {
+        let secs = time.as_secs() + u64::from(time.subsec_nanos() > 0);
+        self.max_cpu_seconds = Some(secs.max(1));
+        self
+    }
+
+    /// Kill the process once it has run for `time`.
+    pub fn with_max_wall_time(mut self, time: Duration) -> Self {
+        self.max_wall_time = Some(time);
+        self
+    }
+
+    /// Run without network access (Linux only).
+    pub fn with_no_network(mut self, no_network: bool) -> Self {
+        self.no_network = no_network;
+        self
+    }
+
+    /// Create execution cgroups below `path`.
+    pub fn with_cgroup_parent(mut self, path: impl Into<PathBuf>) -> Self {
+        self.cgroup_parent = Some(path.into());
+        self
+    }
+
+    /// Whether the process runs without network access.
+    pub fn no_network(&self) -> bool {
+        self.no_network
+    }
+
+    /// The memory cap in bytes, if set.
+    pub fn max_memory(&self) -> Option<u64> {
+        self.max_memory_bytes
+    }
+
+    /// The CPU time cap of each process, if set.
+    pub fn max_cpu_time(&self) -> Option<Duration> {
+        self.max_cpu_seconds.map(Duration::from_secs)
+    }
+
+    /// The wall-clock time limit, if set.
+    pub fn max_wall_time(&self) -> Option<Duration> {
+        self.max_wall_time
+    }
+
+    /// Whether no limit is set.
+    pub fn is_empty(&self) -> bool {
+        !self.confines() && self.max_wall_time.is_none()
+    }
+
+    /// Whether a limit is set on the process itself.
+    fn confines(&self) -> bool {
+        self.max_memory_bytes.is_some()
+            || self.cpu_weight.is_some()
+            || self.max_millicpus.is_some()
+            || self.max_processes.is_some()
+            || self.max_cpu_seconds.is_some()
+            || self.no_network
+    }
+
+    /// Set up the limits for `cmd`, before it is spawned.
+    pub(crate) fn apply(&self, cmd: &mut Command) -> Result<Confinement, SdkError> {
+        if !self.confines() {
+            return Ok(Confinement::default());
+        }
+        self.apply_limits(cmd)
+    }
+
+    #[cfg(unix)]
+    fn apply_limits(&self, cmd: &mut Command) -> Result<Confinement, SdkError> {
+        use std::os::fd::AsRawFd;
+
+        let mut confinement = Confinement::default();
+        if let Some(parent) = &self.cgroup_parent {
+            let dir = parent.join(format!("taskrun-{}", uuid::Uuid::new_v4()));
+            fs::create_dir(&dir).map_err(|e| setup_error(&dir, e))?;
+            confinement.cgroup = Some(dir.clone());
+            let settings = [
+                ("memory.max", self.max_memory_bytes.map(|b| b.to_string())),
+                ("cpu.weight", self.cpu_weight.map(|w| w.to_string())),
+                (
+                    "cpu.max",
+                    self.max_millicpus.map(|m| format!("{} 100000", m * 100)),
+                ),
+                ("pids.max", self.max_processes.map(|n| n.to_string())),
+            ];
+            for (file, value) in settings {
+                if let Some(value) = value {
+                    let path = dir.join(file);
+                    fs::write(&path, value).map_err(|e| setup_error(&path, e))?;
+                }
+            }
+            let procs = dir.join("cgroup.procs");
+            confinement.procs = Some(
+                File::options()
+                    .write(true)
+                    .open(&procs)
+                    .map_err(|e| setup_error(&procs, e))?,
+            );
+        } else if self.cpu_weight.is_some() || self.max_millicpus.is_some() {
+            warn!("CPU limits need a cgroup parent; they are not applied");
+        }
+        if self.no_network && !cfg!(target_os = "linux") {
+            return Err(SdkError::LimitSetup(
+                "network isolation is only supported on Linux".to_string(),
+            ));
+        }
+
+        let procs_fd = confinement.procs.as_ref().map(|f| f.as_raw_fd());
+        let rlimits = confinement.cgroup.is_none();
+        let max_data = self.max_memory_bytes.filter(|_| rlimits);
+        let max_processes = self.max_processes.filter(|_| rlimits);
+        let max_cpu_seconds = self.max_cpu_seconds;
+        let no_network = self.no_network;
+        confinement.memory_rlimit = max_data.is_some();
+        confinement.cpu_rlimit = max_cpu_seconds.is_some();
+
+        // SAFETY: the closure only makes async-signal-safe system calls
+        unsafe {
+            cmd.pre_exec(move || {
+                // Joining the cgroup before exec puts everything the process
+                // starts in it too
+                if let Some(fd) = procs_fd {
+                    if libc::write(fd, b"0".as_ptr().cast(), 1) < 0 {
+                        return Err(std::io::Error::last_os_error());
+                    }
+                }
+                if let Some(bytes) = max_data {
+                    if libc::setrlimit(libc::RLIMIT_DATA, &rlimit(bytes)) != 0 {
+                        return Err(std::io::Error::last_os_error());
+                    }
+                }
+                if let Some(processes) = max_processes {
+                    if libc::setrlimit(libc::RLIMIT_NPROC, &rlimit(processes)) != 0 {
+                        return Err(std::io::Error::last_os_error());
+                    }
+                }
+                if let Some(secs) = max_cpu_seconds {
+                    if libc::setrlimit(libc::RLIMIT_CPU, &cpu_rlimit(secs)) != 0 {
+                        return Err(std::io::Error::last_os_error());
+                    }
+                }
+                #[cfg(target_os = "linux")]
+                if no_network
+                    && libc::unshare(libc::CLONE_NEWNET) != 0
+                    // Unprivileged: a user namespace grants the right
+                    && libc::unshare(libc::CLONE_NEWUSER | libc::CLONE_NEWNET) != 0
+                {
+                    return Err(std::io::Error::last_os_error());
+                }
+                #[cfg(not(target_os = "linux"))]
+                let _ = no_network;
+                Ok(())
+            });
+        }
+        Ok(confinement)
+    }
+
+    #[cfg(not(unix))]
+    fn apply_limits(&self, _cmd: &mut Command) -> Result<Confinement, SdkError> {
+        Err(SdkError::LimitSetup(
+            "resource limits are only supported on Unix".to_string(),
+        ))
+    }
+}
+
+#[cfg(unix)]
+fn rlimit(value: u64) -> libc::rlimit {
+    libc::rlimit {
+        rlim_cur: value as libc::rlim_t,
+        rlim_max: value as libc::rlim_t,
+    }
+}
+
+/// SIGXCPU at `secs` of CPU time, SIGKILL a little later if it is ignored.
+#[cfg(unix)]
+fn cpu_rlimit(secs: u64) -> libc::rlimit {
+    libc::rlimit {
+        rlim_cur: secs as libc::rlim_t,
+        rlim_max: secs.saturating_add(CPU_KILL_GRACE_SECS) as libc::rlim_t,
+    }
+}
+
+fn setup_error(path: &Path, error: std::io::Error) -> SdkError {
+    SdkError::LimitSetup(format!("{}: {}", path.display(), error))
+}
+
+/// Limits applied to one execution. Dropping it kills what is left of the
+/// process tree and removes its cgroup.
+#[derive(Debug, Default)]
+pub(crate) struct Confinement {
+    cgroup: Option<PathBuf>,
+    /// `cgroup.procs` of the cgroup, open for the child to join it.
+    procs: Option<File>,
+    memory_rlimit: bool,
+    cpu_rlimit: bool,
+}
+
+impl Confinement {
+    /// The limit behind a failed exit, if one was hit. `signal` is the
+    /// signal that ended the process, and `out_of_memory` whether it
+    /// reported running out of memory.
+    pub(crate) fn limit_hit(&self, signal: Option<i32>, out_of_memory: bool) -> Option<LimitKind> {
+        if self.cpu_rlimit && signal.is_some_and(is_cpu_limit_signal) {
+            return Some(LimitKind::CpuTime);
+        }
+        match &self.cgroup {
+            Some(dir) => {
+                if event_count(&dir.join("memory.events"), "oom_kill") > 0 {
+                    Some(LimitKind::Memory)
+                } else if event_count(&dir.join("pids.events"), "max") > 0 {
+                    Some(LimitKind::Processes)
+                } else {
+                    None
+                }
+            }
+            None => (self.memory_rlimit && out_of_memory).then_some(LimitKind::Memory),
+        }
+    }
+}
+
+impl Drop for Confinement {
+    fn drop(&mut self) {
+        let Some(dir) = self.cgroup.take() else {
+            return;
+        };
+        // Killed processes leave the cgroup asynchronously
+        std::thread::spawn(move || {
+            let _ = fs::write(dir.join("cgroup.kill"), "1");
+            for _ in 0..50 {
+                if fs::remove_dir(&dir).is_ok() {
+                    return;
+                }
+                std::thread::sleep(std::time::Duration::from_millis(20));
+            }
+            warn!(cgroup = %dir.display(), "Failed to remove execution cgroup");
+        });
+    }
+}
+
+/// Whether `signal` is sent for going over `RLIMIT_CPU`.
+#[cfg(unix)]
+fn is_cpu_limit_signal(signal: i32) -> bool {
+    signal == libc::SIGXCPU
+}
+
+#[cfg(not(unix))]
+fn is_cpu_limit_signal(_signal: i32) -> bool {
+    false
+}
+
+/// Value of `key` in a cgroup events file (0 if missing).
+fn event_count(path: &Path, key: &str) -> u64 {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|events| {
+            events.lines().find_map(|line| {
+                let (name, count) = line.split_once(' ')?;
+                (name == key).then(|| count.trim().parse().ok())?
+            })
+        })
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_count() {
+        let path = std::env::temp_dir().join(format!("taskrun-events-{}", std::process::id()));
+        fs::write(&path, "low 0\nhigh 2\nmax 0\noom 1\noom_kill 1\n").unwrap();
+        assert_eq!(event_count(&path, "oom_kill"), 1);
+        assert_eq!(event_count(&path, "max"), 0);
+        assert_eq!(event_count(&path, "missing"), 0);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_rlimits_apply_without_a_cgroup() {
+        let limits = ResourceLimits::default().with_max_memory(64 * 1024 * 1024);
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c").arg("ulimit -d");
+        let confinement = limits.apply(&mut cmd).unwrap();
+
+        let output = cmd.output().await.unwrap();
+        assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "65536");
+        assert_eq!(confinement.limit_hit(None, true), Some(LimitKind::Memory));
+        assert_eq!(confinement.limit_hit(None, false), None);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_cpu_time_limit_stops_a_busy_process() {
+        use std::os::unix::process::ExitStatusExt;
+
+        let limits = ResourceLimits::default().with_max_cpu_time(Duration::from_millis(500));
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c").arg("ulimit -t; while :; do :; done");
+        let confinement = limits.apply(&mut cmd).unwrap();
+
+        let output = cmd.output().await.unwrap();
+        assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "1");
+        assert_eq!(
+            confinement.limit_hit(output.status.signal(), false),
+            Some(LimitKind::CpuTime)
+        );
+    }
+}
@@ -4,13 +4,15 @@
 //! with the Claude Code CLI over stdin/stdout.
 
 use std::sync::Arc;
+use std::time::Duration;
 
 use async_trait::async_trait;
 use serde_json::Value;
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
 use tokio::process::{ChildStdin, ChildStdout};
 use tokio::sync::Mutex;
-use tracing::{debug, error, info, trace, warn};
+use tokio::task::JoinHandle;
+use tracing::{debug, error, info, trace, warn, Instrument};
 
 use crate::error::SdkError;
 use crate::types::{
@@ -47,16 +49,42 @@ pub trait ControlHandler: Send + Sync {
     /// This is called for all message types (assistant, tool use, etc.)
     /// and can be used to stream output or track progress.
     async fn on_message(&self, message: ClaudeMessage) -> Result<(), SdkError>;
+
+    /// Whether `on_message` receives thinking blocks and deltas.
+    ///
+    /// Off by default: thinking is stripped from messages before they reach
+    /// the handler, and stream events that only carry thinking are skipped.
+    fn wants_thinking(&self) -> bool {
+        false
+    }
+}
+
+/// Pass a message to the handler, stripping thinking unless it opted in.
+pub(crate) async fn deliver(
+    handler: &Arc<dyn ControlHandler>,
+    message: ClaudeMessage,
+) -> Result<(), SdkError> {
+    let message = if handler.wants_thinking() {
+        Some(message)
+    } else {
+        message.without_thinking()
+    };
+    match message {
+        Some(message) => handler.on_message(message).await,
+        None => Ok(()),
+    }
 }
 
 /// Protocol peer for bidirectional communication with Claude Code.
 ///
 /// Manages the stdin/stdout streams and handles the control protocol
 /// message exchange.
-#[allow(dead_code)]
 pub struct ProtocolPeer {
-    stdin: Arc<Mutex<ChildStdin>>,
+    /// Closed (set to `None`) once Claude reports a result.
+    stdin: Arc<Mutex<Option<ChildStdin>>>,
     initialized: Arc<Mutex<bool>>,
+    /// The read loop, until `drain` waits for it.
+    read_loop: Mutex<Option<JoinHandle<()>>>,
 }
 
 #[allow(dead_code)]
@@ -69,21 +97,33 @@ impl ProtocolPeer {
     /// Returns the peer for sending messages.
     pub fn spawn(stdin: ChildStdin, stdout: ChildStdout, handler: Arc<dyn ControlHandler>) -> Self {
         info!("ProtocolPeer::spawn - starting read loop");
-        let stdin = Arc::new(Mutex::new(stdin));
+        let stdin = Arc::new(Mutex::new(Some(stdin)));
         let stdin_clone = Arc::clone(&stdin);
 
         // Spawn the read loop
-        tokio::spawn(async move {
-            info!("Protocol read loop task started");
-            if let Err(e) = Self::read_loop(stdout, handler, stdin_clone).await {
-                error!("Protocol read loop error: {}", e);
+        let read_loop = tokio::spawn(
+            async move {
+                info!("Protocol read loop task started");
+                if let Err(e) = Self::read_loop(stdout, handler, stdin_clone).await {
+                    error!("Protocol read loop error: {}", e);
+                }
+                info!("Protocol read loop task ended");
             }
-            info!("Protocol read loop task ended");
-        });
+            .in_current_span(),
+        );
 
         Self {
             stdin,
             initialized: Arc::new(Mutex::new(false)),
+            read_loop: Mutex::new(Some(read_loop)),
+        }
+    }
+
+    /// Wait up to `timeout` for the read loop to hand the rest of Claude's
+    /// output to the handler. The loop ends at EOF, once Claude has exited.
+    pub async fn drain(&self, timeout: Duration) {
+        if let Some(read_loop) = self.read_loop.lock().await.take() {
+            let _ = tokio::time::timeout(timeout, read_loop).await;
         }
     }
 
@@ -106,6 +146,18 @@ impl ProtocolPeer {
         Ok(())
     }
 
+    /// Send the prompt as a user message (`--input-format=stream-json`).
+    pub async fn send_user_message(&self, prompt: &str) -> Result<(), SdkError> {
+        let message = serde_json::json!({
+            "type": "user",
+            "message": {
+                "role": "user",
+                "content": prompt,
+            },
+        });
+        self.send_json(&message).await
+    }
+
     /// Send a JSON message to Claude's stdin.
     async fn send_json<T: serde::Serialize>(&self, message: &T) -> Result<(), SdkError> {
         let json = serde_json::to_string(message)?;
@@ -115,11 +167,20 @@ impl ProtocolPeer {
         );
         trace!("Sending to stdin: {}", json);
 
-        let mut stdin = self.stdin.lock().await;
+        Self::write_line(&self.stdin, &json).await?;
+        info!("Control message sent and flushed");
+        Ok(())
+    }
+
+    /// Write one line to Claude's stdin, failing if it has been closed.
+    async fn write_line(stdin: &Mutex<Option<ChildStdin>>, json: &str) -> Result<(), SdkError> {
+        let mut guard = stdin.lock().await;
+        let stdin = guard
+            .as_mut()
+            .ok_or_else(|| SdkError::ProtocolError("Claude stdin is closed".to_string()))?;
         stdin.write_all(json.as_bytes()).await?;
         stdin.write_all(b"\n").await?;
         stdin.flush().await?;
-        info!("Control message sent and flushed");
         Ok(())
     }
 
@@ -127,7 +188,7 @@ impl ProtocolPeer {
     async fn read_loop(
         stdout: ChildStdout,
         handler: Arc<dyn ControlHandler>,
-        stdin: Arc<Mutex<ChildStdin>>,
+        stdin: Arc<Mutex<Option<ChildStdin>>>,
     ) -> Result<(), SdkError> {
         info!("Read loop started, waiting for Claude stdout...");
         let mut reader = BufReader::new(stdout);
@@ -190,10 +251,18 @@ impl ProtocolPeer {
                         )
                         .await?;
                     } else {
+                        // Claude waits for further input after a result;
+                        // closing stdin ends the session.
+                        let is_result = matches!(message, ClaudeMessage::Result { .. });
+
                         // Forward other messages to the handler
-                        if let Err(e) = handler.on_message(message).await {
+                        if let Err(e) = deliver(&handler, message).await {
                             warn!("Handler error processing message: {}", e);
                         }
+
+                        if is_result {
+                            stdin.lock().await.take();
+                        }
                     }
                 }
                 Err(e) => {
@@ -217,7 +286,7 @@ impl ProtocolPeer {
         request_id: String,
         request: ControlRequest,
         handler: &Arc<dyn ControlHandler>,
-        stdin: &Arc<Mutex<ChildStdin>>,
+        stdin: &Arc<Mutex<Option<ChildStdin>>>,
     ) -> Result<(), SdkError> {
         debug!("Handling control request: {:?}", request);
 
@@ -257,12 +326,7 @@ impl ProtocolPeer {
         let json = serde_json::to_string(&response)?;
         trace!("Sending control response: {}", json);
 
-        let mut stdin_guard = stdin.lock().await;
-        stdin_guard.write_all(json.as_bytes()).await?;
-        stdin_guard.write_all(b"\n").await?;
-        stdin_guard.flush().await?;
-
-        Ok(())
+        Self::write_line(stdin, &json).await
     }
 }
 
@@ -0,0 +1,291 @@
+//! Container sandbox for the Claude process.
+//!
+//! With a sandbox, each execution runs the Claude CLI in a fresh container
+//! (`docker run --rm` or `podman run --rm`) from an image that has it
+//! installed. The working directory is bind-mounted at the same path, so the
+//! agent sees and edits the same files, and the container runs as the
+//! worker's user so what it writes stays owned by it.
+//!
+//! The executor's resource limits become the container's: `--memory`,
+//! `--cpus`, `--cpu-shares` and `--pids-limit`, with `--network none` for
+//! network isolation. The container is removed when the execution ends,
+//! including when it is cancelled.
+
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+use tokio::process::Command;
+use tracing::{info, warn};
+
+use crate::error::SdkError;
+use crate::limits::{LimitKind, ResourceLimits, CPU_KILL_GRACE_SECS};
+
+/// Environment variables passed into the container when the worker has them.
+const PASSTHROUGH_ENV: [&str; 3] = [
+    "ANTHROPIC_API_KEY",
+    "ANTHROPIC_BASE_URL",
+    "CLAUDE_CODE_OAUTH_TOKEN",
+];
+
+/// Exit code of a process killed with SIGKILL, as the runtimes report it.
+const SIGKILL_EXIT_CODE: i32 = 137;
+
+/// Exit code of a container whose process got SIGXCPU (128 + 24).
+const SIGXCPU_EXIT_CODE: i32 = 152;
+
+/// Container runtime a sandbox runs with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SandboxRuntime {
+    Docker,
+    Podman,
+}
+
+impl SandboxRuntime {
+    /// Name of the runtime's CLI.
+    pub fn program(&self) -> &'static str {
+        match self {
+            SandboxRuntime::Docker => "docker",
+            SandboxRuntime::Podman => "podman",
+        }
+    }
+}
+
+impl fmt::Display for SandboxRuntime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.program())
+    }
+}
+
+impl FromStr for SandboxRuntime {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "docker" => Ok(SandboxRuntime::Docker),
+            "podman" => Ok(SandboxRuntime::Podman),
+            other => Err(format!(
+                "unknown sandbox runtime '{}' (expected docker or podman)",
+                other
+            )),
+        }
+    }
+}
+
+/// Container the Claude process runs in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sandbox {
+    runtime: SandboxRuntime,
+    image: String,
+    /// Path of the Claude CLI in the image.
+    claude_path: String,
+}
+
+impl Sandbox {
+    /// Run in containers of `image` with `runtime`.
+    pub fn new(runtime: SandboxRuntime, image: impl Into<String>) -> Self {
+        Self {
+            runtime,
+            image: image.into(),
+            claude_path: "claude".to_string(),
+        }
+    }
+
+    /// Set the path of the Claude CLI in the image (default: `claude`).
+    pub fn with_claude_path(mut self, path: impl Into<String>) -> Self {
+        self.claude_path = path.into();
+        self
+    }
+
+    pub fn runtime(&self) -> SandboxRuntime {
+        self.runtime
+    }
+
+    pub fn image(&self) -> &str {
+        &self.image
+    }
+
+    /// The command running `cmd`'s arguments and environment in a container
+    /// with `working_dir` mounted and `limits` applied.
+    pub(crate) fn wrap(
+        &self,
+        cmd: &Command,
+        working_dir: &Path,
+        limits: &ResourceLimits,
+    ) -> Result<(Command, Container), SdkError> {
+        let working_dir = working_dir
+            .canonicalize()
+            .map_err(|e| SdkError::LimitSetup(format!("{}: {}", working_dir.display(), e)))?;
+        let dir = working_dir.to_string_lossy();
+        let name = format!("taskrun-{}", uuid::Uuid::new_v4());
+
+        let mut wrapped = Command::new(self.runtime.program());
+        wrapped
+            .args(["run", "--rm", "-i", "--init", "--name", &name])
+            .arg("--volume")
+            .arg(format!("{}:{}", dir, dir))
+            .arg("--workdir")
+            .arg(&*dir);
+
+        if limits.no_network {
+            wrapped.arg("--network").arg("none");
+        }
+
+        if let Some(bytes) = limits.max_memory_bytes {
+            wrapped.arg("--memory").arg(bytes.to_string());
+        }
+        if let Some(millicpus) = limits.max_millicpus {
+            wrapped
+                .arg("--cpus")
+                .arg(format!("{}.{:03}", millicpus / 1000, millicpus % 1000));
+        }
+        if let Some(weight) = limits.cpu_weight {
+            // cpu.weight 100 is the default share, as 1024 CPU shares are
+            let shares = (u64::from(weight) * 1024 / 100).max(2);
+            wrapped.arg("--cpu-shares").arg(shares.to_string());
+        }
+        if let Some(processes) = limits.max_processes {
+            wrapped.arg("--pids-limit").arg(processes.to_string());
+        }
+        if let Some(secs) = limits.max_cpu_seconds {
+            wrapped.arg("--ulimit").arg(format!(
+                "cpu={}:{}",
+                secs,
+                secs.saturating_add(CPU_KILL_GRACE_SECS)
+            ));
+        }
+
+        #[cfg(unix)]
+        {
+            // SAFETY: getuid and getgid can't fail
+            let (uid, gid) = unsafe { (libc::getuid(), libc::getgid()) };
+            wrapped.arg("--user").arg(format!("{}:{}", uid, gid));
+            // The worker's user has no home in the image
+            wrapped.arg("--env").arg("HOME=/tmp");
+        }
+        for key in PASSTHROUGH_ENV {
+            if std::env::var_os(key).is_some() {
+                // Without a value, the runtime takes it from its own environment
+                wrapped.arg("--env").arg(key);
+            }
+        }
+        for (key, value) in cmd.as_std().get_envs() {
+            if let Some(value) = value {
+                // Passed through the runtime's environment, so values such
+                // as secrets stay off its command line
+                wrapped.env(key, value);
+                wrapped.arg("--env").arg(key);
+            }
+        }
+
+        wrapped
+            .arg(&self.image)
+            .arg(&self.claude_path)
+            .args(cmd.as_std().get_args());
+
+        info!(
+            runtime = %self.runtime,
+            image = %self.image,
+            container = %name,
+            "Running Claude in a sandbox container"
+        );
+        Ok((
+            wrapped,
+            Container {
+                runtime: self.runtime,
+                name,
+                memory_limit: limits.max_memory_bytes.is_some(),
+                cpu_limit: limits.max_cpu_seconds.is_some(),
+            },
+        ))
+    }
+}
+
+/// The container of one execution. Dropping it removes the container, which
+/// stops it if the execution was cancelled.
+#[derive(Debug)]
+pub(crate) struct Container {
+    runtime: SandboxRuntime,
+    name: String,
+    memory_limit: bool,
+    cpu_limit: bool,
+}
+
+impl Container {
+    /// The limit behind a failed exit, if one was hit. The runtime kills a
+    /// container over its memory limit, which is reported as a SIGKILL.
+    pub(crate) fn limit_hit(&self, exit_code: i32) -> Option<LimitKind> {
+        if self.cpu_limit && exit_code == SIGXCPU_EXIT_CODE {
+            return Some(LimitKind::CpuTime);
+        }
+        (self.memory_limit && exit_code == SIGKILL_EXIT_CODE).then_some(LimitKind::Memory)
+    }
+}
+
+impl Drop for Container {
+    fn drop(&mut self) {
+        let program = self.runtime.program();
+        let name = std::mem::take(&mut self.name);
+        // Not awaited: this may run outside of a runtime or while one shuts down
+        std::thread::spawn(move || {
+            let removed = std::process::Command::new(program)
+                .args(["rm", "--force", &name])
+                .stdout(std::process::Stdio::null())
+                .stderr(std::process::Stdio::null())
+                .status();
+            if let Err(e) = removed {
+                warn!(container = %name, error = %e, "Failed to remove sandbox container");
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_wrap_applies_limits_to_the_container() {
+        let dir = std::env::temp_dir();
+        let mut cmd = Command::new("claude");
+        cmd.arg("--print").arg("hi").env("FOO", "bar");
+        let limits = ResourceLimits::default()
+            .with_max_memory(512 * 1024 * 1024)
+            .with_max_cpus(1.5)
+            .with_max_cpu_time(Duration::from_secs(60))
+            .with_no_network(true);
+        let sandbox = Sandbox::new(SandboxRuntime::Podman, "claude:latest");
+
+        let (wrapped, container) = sandbox.wrap(&cmd, &dir, &limits).unwrap();
+        let std = wrapped.as_std();
+        assert_eq!(std.get_program(), "podman");
+        let args: Vec<_> = std
+            .get_args()
+            .map(|a| a.to_string_lossy().into_owned())
+            .collect();
+        let after = |flag: &str| {
+            let i = args.iter().position(|a| a == flag).unwrap();
+            args[i + 1].clone()
+        };
+        assert_eq!(after("--memory"), "536870912");
+        assert_eq!(after("--cpus"), "1.500");
+        assert_eq!(after("--network"), "none");
+        assert_eq!(after("--ulimit"), "cpu=60:65");
+        let dir = dir.canonicalize().unwrap();
+        assert_eq!(after("--workdir"), dir.to_string_lossy());
+        assert!(args.contains(&"FOO".to_string()));
+        assert!(!args.iter().any(|a| a.contains("bar")));
+        assert!(std
+            .get_envs()
+            .any(|(k, v)| k == "FOO" && v == Some("bar".as_ref())));
+        assert_eq!(
+            &args[args.len() - 4..],
+            ["claude:latest", "claude", "--print", "hi"]
+        );
+
+        assert_eq!(container.limit_hit(137), Some(LimitKind::Memory));
+        assert_eq!(container.limit_hit(152), Some(LimitKind::CpuTime));
+        assert_eq!(container.limit_hit(1), None);
+    }
+}
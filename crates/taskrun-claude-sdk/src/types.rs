@@ -66,10 +66,22 @@ pub enum ClaudeMessage {
         is_error: Option<bool>,
         #[serde(default, alias = "durationMs")]
         duration_ms: Option<u64>,
+        /// What the execution cost, in USD, as reported by Claude Code.
+        #[serde(default, alias = "totalCostUsd")]
+        total_cost_usd: Option<f64>,
+        /// Tokens consumed by the execution, as reported by Claude Code.
+        #[serde(default)]
+        usage: Option<ResultUsage>,
         #[serde(default)]
         result: Option<Value>,
         #[serde(default)]
         error: Option<String>,
+        /// Result kind, e.g. `success` or `error_max_turns`.
+        #[serde(default)]
+        subtype: Option<String>,
+        /// Tool uses that were denied during the execution.
+        #[serde(default, alias = "permissionDenials")]
+        permission_denials: Vec<Value>,
         #[serde(default, alias = "sessionId")]
         session_id: Option<String>,
     },
@@ -99,6 +111,37 @@ impl ClaudeMessage {
             Self::ControlRequest { .. } | Self::Unknown(_) => None,
         }
     }
+
+    /// Drop thinking from the message, for handlers that do not want it.
+    ///
+    /// Thinking blocks are removed from assistant and user content; stream
+    /// events that only carry thinking are dropped entirely (`None`).
+    pub fn without_thinking(self) -> Option<Self> {
+        match self {
+            Self::Assistant {
+                mut message,
+                session_id,
+            } => {
+                message.content.retain(|item| !item.is_thinking());
+                Some(Self::Assistant {
+                    message,
+                    session_id,
+                })
+            }
+            Self::User {
+                mut message,
+                session_id,
+            } => {
+                message.content.retain(|item| !item.is_thinking());
+                Some(Self::User {
+                    message,
+                    session_id,
+                })
+            }
+            Self::StreamEvent { ref event, .. } if event.is_thinking() => None,
+            other => Some(other),
+        }
+    }
 }
 
 /// Assistant message content.
@@ -131,7 +174,14 @@ pub enum ContentItem {
     Text { text: String },
 
     /// Thinking/reasoning content.
-    Thinking { thinking: String },
+    Thinking {
+        thinking: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        signature: Option<String>,
+    },
+
+    /// Thinking the API returned encrypted.
+    RedactedThinking { data: String },
 
     /// Tool use request.
     ToolUse {
@@ -149,6 +199,13 @@ pub enum ContentItem {
     },
 }
 
+impl ContentItem {
+    /// Whether this is a (possibly redacted) thinking block.
+    pub fn is_thinking(&self) -> bool {
+        matches!(self, Self::Thinking { .. } | Self::RedactedThinking { .. })
+    }
+}
+
 /// Structured tool data for Claude tools.
 #[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(tag = "name", content = "input")]
@@ -230,8 +287,50 @@ impl ToolData {
                 .unwrap_or("unknown"),
         }
     }
+
+    /// The tool input as JSON.
+    pub fn input(&self) -> Value {
+        serde_json::to_value(self)
+            .ok()
+            .and_then(|mut data| data.get_mut("input").map(Value::take))
+            .unwrap_or(Value::Null)
+    }
+
+    /// A one-line summary of the input (file path, command, pattern), cut
+    /// to 120 characters; None for tools without a telling field.
+    pub fn summary(&self) -> Option<String> {
+        let summary = match self {
+            Self::Read { file_path }
+            | Self::Write { file_path, .. }
+            | Self::Edit { file_path, .. } => file_path.clone(),
+            Self::Bash { command, .. } => command.clone(),
+            Self::Grep { pattern, path } | Self::Glob { pattern, path } => match path {
+                Some(path) => format!("{} in {}", pattern, path),
+                None => pattern.clone(),
+            },
+            Self::Task {
+                description,
+                subagent_type,
+                ..
+            } => description.clone().or_else(|| subagent_type.clone())?,
+            Self::Unknown(_) => return None,
+        };
+        let line = summary.lines().next().unwrap_or_default().trim();
+        if line.is_empty() {
+            return None;
+        }
+        let cut = line.chars().count() > SUMMARY_MAX_CHARS || summary.trim().lines().count() > 1;
+        let mut line: String = line.chars().take(SUMMARY_MAX_CHARS).collect();
+        if cut {
+            line.push('…');
+        }
+        Some(line)
+    }
 }
 
+/// Longest [`ToolData::summary`], in characters.
+const SUMMARY_MAX_CHARS: usize = 120;
+
 /// Streaming event types.
 #[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
@@ -265,6 +364,17 @@ pub enum StreamEvent {
     Unknown,
 }
 
+impl StreamEvent {
+    /// Whether the event starts or continues a thinking block.
+    pub fn is_thinking(&self) -> bool {
+        match self {
+            Self::ContentBlockStart { content_block, .. } => content_block.is_thinking(),
+            Self::ContentBlockDelta { delta, .. } => delta.is_thinking(),
+            _ => false,
+        }
+    }
+}
+
 /// Content delta for streaming.
 #[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
@@ -275,11 +385,24 @@ pub enum ContentDelta {
     /// Thinking delta.
     ThinkingDelta { thinking: String },
 
+    /// Signature closing a thinking block.
+    SignatureDelta { signature: String },
+
     /// Unknown delta.
     #[serde(other)]
     Unknown,
 }
 
+impl ContentDelta {
+    /// Whether the delta belongs to a thinking block.
+    pub fn is_thinking(&self) -> bool {
+        matches!(
+            self,
+            Self::ThinkingDelta { .. } | Self::SignatureDelta { .. }
+        )
+    }
+}
+
 /// Message delta for streaming.
 #[derive(Debug, Clone, Default, Deserialize, Serialize)]
 pub struct MessageDelta {
@@ -287,6 +410,26 @@ pub struct MessageDelta {
     pub stop_reason: Option<String>,
 }
 
+/// Token counts of an execution result.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ResultUsage {
+    #[serde(default)]
+    pub input_tokens: u64,
+    #[serde(default)]
+    pub output_tokens: u64,
+    #[serde(default)]
+    pub cache_creation_input_tokens: u64,
+    #[serde(default)]
+    pub cache_read_input_tokens: u64,
+}
+
+impl ResultUsage {
+    /// Prompt tokens, cached or not.
+    pub fn total_input_tokens(&self) -> u64 {
+        self.input_tokens + self.cache_creation_input_tokens + self.cache_read_input_tokens
+    }
+}
+
 /// Control request from CLI to SDK.
 #[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(tag = "subtype", rename_all = "snake_case")]
@@ -486,6 +629,41 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_thinking_blocks_parsing_and_stripping() {
+        let json = r#"{"type":"assistant","message":{"role":"assistant","content":[{"type":"thinking","thinking":"2 + 2 is 4","signature":"sig"},{"type":"redacted_thinking","data":"abc"},{"type":"text","text":"4"}]}}"#;
+        let msg: ClaudeMessage = serde_json::from_str(json).unwrap();
+
+        if let ClaudeMessage::Assistant { message, .. } = &msg {
+            assert_eq!(message.content.len(), 3);
+            assert!(matches!(
+                &message.content[0],
+                ContentItem::Thinking { thinking, signature: Some(_) } if thinking == "2 + 2 is 4"
+            ));
+            assert!(matches!(
+                message.content[1],
+                ContentItem::RedactedThinking { .. }
+            ));
+        } else {
+            panic!("Expected assistant message");
+        }
+
+        if let Some(ClaudeMessage::Assistant { message, .. }) = msg.without_thinking() {
+            assert_eq!(message.content.len(), 1);
+            assert!(matches!(message.content[0], ContentItem::Text { .. }));
+        } else {
+            panic!("Expected assistant message");
+        }
+
+        let json = r#"{"type":"stream_event","event":{"type":"content_block_delta","index":0,"delta":{"type":"thinking_delta","thinking":"hmm"}}}"#;
+        let msg: ClaudeMessage = serde_json::from_str(json).unwrap();
+        assert!(msg.without_thinking().is_none());
+
+        let json = r#"{"type":"stream_event","event":{"type":"content_block_delta","index":1,"delta":{"type":"text_delta","text":"4"}}}"#;
+        let msg: ClaudeMessage = serde_json::from_str(json).unwrap();
+        assert!(msg.without_thinking().is_some());
+    }
+
     #[test]
     fn test_tool_data_parsing() {
         let json = r#"{"name":"Read","input":{"file_path":"/tmp/test.rs"}}"#;
@@ -515,6 +693,35 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_tool_data_summary() {
+        let grep: ToolData =
+            serde_json::from_str(r#"{"name":"Grep","input":{"pattern":"fn main","path":"src"}}"#)
+                .unwrap();
+        assert_eq!(grep.summary().as_deref(), Some("fn main in src"));
+        assert_eq!(
+            grep.input(),
+            serde_json::json!({"pattern": "fn main", "path": "src"})
+        );
+
+        let script = format!("cat <<EOF\n{}\nEOF", "x".repeat(200));
+        let bash = ToolData::Bash {
+            command: script,
+            description: None,
+        };
+        assert_eq!(bash.summary().as_deref(), Some("cat <<EOF…"));
+
+        let long = ToolData::Bash {
+            command: "y".repeat(200),
+            description: None,
+        };
+        assert_eq!(
+            long.summary().unwrap().chars().count(),
+            SUMMARY_MAX_CHARS + 1
+        );
+        assert_eq!(ToolData::Unknown(HashMap::new()).summary(), None);
+    }
+
     #[test]
     fn test_permission_mode_serialization() {
         assert_eq!(
@@ -554,18 +761,24 @@ mod tests {
 
     #[test]
     fn test_result_message_parsing() {
-        let json = r#"{"type":"result","isError":false,"durationMs":1234,"sessionId":"sess-abc"}"#;
+        let json = r#"{"type":"result","isError":false,"durationMs":1234,"total_cost_usd":0.0421,"usage":{"input_tokens":12,"cache_read_input_tokens":300,"output_tokens":85},"sessionId":"sess-abc"}"#;
         let msg: ClaudeMessage = serde_json::from_str(json).unwrap();
 
         if let ClaudeMessage::Result {
             is_error,
             duration_ms,
+            total_cost_usd,
+            usage,
             session_id,
             ..
         } = msg
         {
             assert_eq!(is_error, Some(false));
             assert_eq!(duration_ms, Some(1234));
+            assert_eq!(total_cost_usd, Some(0.0421));
+            let usage = usage.unwrap();
+            assert_eq!(usage.total_input_tokens(), 312);
+            assert_eq!(usage.output_tokens, 85);
             assert_eq!(session_id, Some("sess-abc".to_string()));
         } else {
             panic!("Expected result message");
@@ -1,12 +1,28 @@
 //! TaskRun CLI - Command line interface for TaskRun control plane.
 
+use std::sync::OnceLock;
+
 use clap::{Parser, Subcommand};
-use tonic::transport::{Certificate, Channel, ClientTlsConfig};
+use taskrun_tui_components::{DisplayLocale, DisplayZone, TimeFormat};
+use tonic::transport::{Certificate, Channel, ClientTlsConfig, Identity};
 
 use taskrun_proto::pb::{
-    CancelTaskRequest, CreateTaskRequest, GetTaskRequest, ListTasksRequest, ListWorkersRequest,
+    BulkTaskRequest, BulkTaskResponse, CancelTaskRequest, CreateApiKeyRequest,
+    CreateBootstrapTokenRequest, CreateScheduleRequest, CreateTaskRequest, CreateWorkflowRequest,
+    DecideApprovalRequest, DeleteScheduleRequest, DisconnectWorkerRequest, DrainWorkerRequest,
+    ExportStateRequest, FailureReason, GetGroupSummaryRequest, GetTaskRequest, GetTogglesRequest,
+    GetWorkerRequest, GetWorkflowRequest, GroupChild, GroupSummary, ListApiKeysRequest,
+    ListApprovalsRequest, ListBootstrapTokensRequest, ListRevokedCertificatesRequest,
+    ListSchedulesRequest, ListTasksRequest, ListWorkersRequest, ListWorkflowTemplatesRequest,
+    ListWorkflowsRequest, PauseScheduleRequest, ResumeScheduleRequest, RevokeApiKeyRequest,
+    RevokeBootstrapTokenRequest, RevokeCertificateRequest, RunLimits, SearchTasksRequest,
+    StateChunk, StreamRunOutputRequest, TaskFilter, TaskPriority, TaskSortOrder, TaskStatus,
+    UndrainWorkerRequest, UpdateTogglesRequest, ValidationSeverity, WatchTasksRequest,
+};
+use taskrun_proto::{
+    AdminServiceClient, ApiKeyServiceClient, ApprovalServiceClient, ScheduleServiceClient,
+    TaskServiceClient, WorkerServiceClient, WorkflowServiceClient,
 };
-use taskrun_proto::{TaskServiceClient, WorkerServiceClient};
 
 /// TaskRun CLI - Control plane management tool
 #[derive(Parser)]
@@ -21,6 +37,31 @@ struct Cli {
     #[arg(long, default_value = "certs/ca.crt")]
     ca_cert: String,
 
+    /// Client certificate for mTLS; its CN ("<role>:<name>") sets what the CLI may do
+    #[arg(long, default_value = "certs/client.crt")]
+    client_cert: String,
+
+    /// Private key of the client certificate
+    #[arg(long, default_value = "certs/client.key")]
+    client_key: String,
+
+    /// Namespace to create tasks and API keys in, and to narrow listings to
+    #[arg(short, long, global = true)]
+    namespace: Option<String>,
+
+    /// Time zone of printed timestamps: utc, local or an IANA name (Europe/Berlin)
+    #[arg(long, global = true, default_value = "utc")]
+    tz: DisplayZone,
+
+    /// Date order and clock of printed timestamps: iso, a locale such as
+    /// en_US or de_DE, or auto to follow LC_TIME/LANG
+    #[arg(long, global = true, default_value = "iso")]
+    locale: DisplayLocale,
+
+    /// Print timestamps relative to now ("5m ago")
+    #[arg(long, global = true)]
+    relative_times: bool,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -37,27 +78,413 @@ enum Commands {
         /// Input JSON for the agent
         #[arg(short, long)]
         input: String,
+
+        /// Fail the run if it takes longer than this many seconds
+        #[arg(long)]
+        timeout: Option<u32>,
+
+        /// Schedule in the interactive lane, ahead of batch tasks (default:
+        /// the server's default priority)
+        #[arg(long)]
+        interactive: bool,
+
+        /// Fail the task if no worker picks it up within this many seconds
+        #[arg(long)]
+        pending_ttl: Option<u32>,
+
+        /// Add the task to a group (batch or fan-out), see group-summary
+        #[arg(long)]
+        group: Option<String>,
+
+        /// ID of an uploaded file (POST /v1/files) to stage in the run's
+        /// workspace; repeat for several files
+        #[arg(long = "input-file", value_name = "FILE_ID")]
+        input_files: Vec<String>,
+
+        /// Kill a run whose Claude process uses more than this much memory
+        #[arg(long, value_name = "MB")]
+        max_memory_mb: Option<u64>,
+
+        /// Kill a run's process once it has used this much CPU time
+        #[arg(long, value_name = "SECONDS")]
+        max_cpu_seconds: Option<u64>,
+
+        /// Kill a run on the worker once it has run this long
+        #[arg(long, value_name = "SECONDS")]
+        max_wall_seconds: Option<u64>,
+
+        /// Only check the task (agent, input, quotas, capacity); create nothing
+        #[arg(long)]
+        dry_run: bool,
     },
 
     /// Get task status
     #[command(name = "get-task")]
     GetTask {
-        /// Task ID
+        /// Task ID (or unique prefix)
         id: String,
     },
 
-    /// List all tasks
+    /// List tasks, newest first
     #[command(name = "list-tasks")]
-    ListTasks,
+    ListTasks {
+        /// Only tasks in this status (pending, running, completed, failed, cancelled)
+        #[arg(short, long, value_parser = parse_status)]
+        status: Option<TaskStatus>,
+
+        /// Only tasks for this agent
+        #[arg(short, long)]
+        agent: Option<String>,
+
+        /// Only tasks that failed for this reason (e.g. timeout, worker_lost)
+        #[arg(long, value_parser = parse_failure_reason)]
+        failure_reason: Option<FailureReason>,
+
+        /// Only tasks with this label, as key=value (repeatable)
+        #[arg(short, long = "label", value_parser = parse_param)]
+        labels: Vec<(String, String)>,
+
+        /// Only tasks created at or after this time (RFC 3339)
+        #[arg(long, value_parser = parse_time)]
+        created_after: Option<i64>,
+
+        /// Only tasks created before this time (RFC 3339)
+        #[arg(long, value_parser = parse_time)]
+        created_before: Option<i64>,
+
+        /// List oldest first
+        #[arg(long)]
+        oldest_first: bool,
+
+        /// Tasks per page (max 1000)
+        #[arg(long, default_value = "100")]
+        limit: i32,
+
+        /// Continue from a previous page
+        #[arg(long, conflicts_with = "all")]
+        page_token: Option<String>,
+
+        /// Fetch every page
+        #[arg(long)]
+        all: bool,
+    },
+
+    /// Search task input, labels, agent names and run output
+    Search {
+        /// Words that must all occur; a trailing * matches a prefix (e.g. "deploy*")
+        query: String,
+
+        /// Maximum number of results
+        #[arg(long, default_value = "50")]
+        limit: i32,
+    },
+
+    /// Progress of a group: a workflow, or tasks created with --group
+    #[command(name = "group-summary")]
+    GroupSummary {
+        /// Group ID (or workflow ID)
+        id: String,
+    },
+
+    /// Follow task and run changes as they happen
+    #[command(name = "watch-tasks")]
+    WatchTasks {
+        /// Only tasks for this agent
+        #[arg(short, long)]
+        agent: Option<String>,
+
+        /// Only tasks with this label, as key=value (repeatable)
+        #[arg(short, long = "label", value_parser = parse_param)]
+        labels: Vec<(String, String)>,
+
+        /// Only these tasks (repeatable)
+        #[arg(short, long = "task")]
+        tasks: Vec<String>,
+
+        /// Also print run output
+        #[arg(long)]
+        output: bool,
+    },
+
+    /// Print a run's output, then follow it until the run finishes
+    #[command(name = "tail-run")]
+    TailRun {
+        /// Run ID (or unique prefix)
+        id: String,
+
+        /// Start at this output chunk (e.g. the last one seen + 1)
+        #[arg(long, default_value = "0")]
+        from_seq: u64,
+    },
+
+    /// Cancel or retry every task matching a filter (admin)
+    Bulk {
+        /// Operation to apply
+        #[arg(value_parser = ["cancel", "retry"])]
+        operation: String,
+
+        /// Filter as key=value: status=<status>, reason=<failure reason>, or a label to match (repeatable)
+        #[arg(short, long = "filter", value_parser = parse_param)]
+        filters: Vec<(String, String)>,
+
+        /// Only tasks for this agent
+        #[arg(short, long)]
+        agent: Option<String>,
+
+        /// Only show the tasks that would be affected
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Skip the confirmation prompt
+        #[arg(short, long)]
+        yes: bool,
+    },
 
     /// List connected workers
     #[command(name = "list-workers")]
     ListWorkers,
 
+    /// Stop assigning runs to a worker; its active runs finish
+    #[command(name = "drain-worker")]
+    DrainWorker {
+        /// Worker ID (or unique prefix)
+        id: String,
+
+        /// Wait until the worker has no run left
+        #[arg(long)]
+        wait: bool,
+    },
+
+    /// Let a draining worker receive runs again
+    #[command(name = "undrain-worker")]
+    UndrainWorker {
+        /// Worker ID (or unique prefix)
+        id: String,
+    },
+
+    /// Close a worker's stream; its running tasks fail
+    #[command(name = "disconnect-worker")]
+    DisconnectWorker {
+        /// Worker ID (or unique prefix)
+        id: String,
+
+        /// Why (audited and shown to the worker)
+        #[arg(long)]
+        reason: Option<String>,
+
+        /// Put the worker's running tasks back in the queue
+        #[arg(long)]
+        reschedule: bool,
+    },
+
     /// Cancel a task
     #[command(name = "cancel-task")]
     CancelTask {
-        /// Task ID to cancel
+        /// Task ID (or unique prefix) to cancel
+        id: String,
+    },
+
+    /// Create a recurring schedule
+    #[command(name = "create-schedule")]
+    CreateSchedule {
+        /// Cron expression, evaluated in UTC (e.g. "0 9 * * 1-5")
+        #[arg(short, long)]
+        cron: String,
+
+        /// Agent name to run
+        #[arg(short, long)]
+        agent: String,
+
+        /// Input JSON template ({{schedule_id}}, {{fire_time}}, {{fire_time_ms}} are filled in)
+        #[arg(short, long)]
+        input: String,
+
+        /// Fail each run if it takes longer than this many seconds
+        #[arg(long)]
+        timeout: Option<u32>,
+    },
+
+    /// List schedules
+    #[command(name = "list-schedules")]
+    ListSchedules,
+
+    /// Pause a schedule
+    #[command(name = "pause-schedule")]
+    PauseSchedule {
+        /// Schedule ID (or unique prefix)
+        id: String,
+    },
+
+    /// Resume a paused schedule
+    #[command(name = "resume-schedule")]
+    ResumeSchedule {
+        /// Schedule ID (or unique prefix)
+        id: String,
+    },
+
+    /// Delete a schedule
+    #[command(name = "delete-schedule")]
+    DeleteSchedule {
+        /// Schedule ID (or unique prefix)
+        id: String,
+    },
+
+    /// Create a workflow from a template
+    #[command(name = "create-workflow")]
+    CreateWorkflow {
+        /// Template name
+        template: String,
+
+        /// Template parameter as key=value (repeatable)
+        #[arg(short, long = "param", value_parser = parse_param)]
+        params: Vec<(String, String)>,
+    },
+
+    /// Get workflow status
+    #[command(name = "get-workflow")]
+    GetWorkflow {
+        /// Workflow ID (or unique prefix)
+        id: String,
+    },
+
+    /// List workflows
+    #[command(name = "list-workflows")]
+    ListWorkflows,
+
+    /// List workflow templates
+    #[command(name = "list-workflow-templates")]
+    ListWorkflowTemplates,
+
+    /// Create an API key for the HTTP API
+    #[command(name = "create-api-key")]
+    CreateApiKey {
+        /// Name of who or what uses the key
+        name: String,
+
+        /// Role of the key: readonly, operator or admin (admin keys manage other keys)
+        #[arg(long, default_value = "operator", value_parser = ["readonly", "operator", "admin"])]
+        role: String,
+    },
+
+    /// List API keys
+    #[command(name = "list-api-keys")]
+    ListApiKeys,
+
+    /// Revoke an API key
+    #[command(name = "revoke-api-key")]
+    RevokeApiKey {
+        /// API key ID (or unique prefix)
+        id: String,
+    },
+
+    /// Manage bootstrap tokens for worker enrollment (admin)
+    Token {
+        #[command(subcommand)]
+        command: TokenCommands,
+    },
+
+    /// Revoke a worker certificate by serial number, or every certificate of a worker (admin)
+    #[command(name = "revoke-cert")]
+    RevokeCert {
+        /// Certificate serial number in hex, as logged at enrollment
+        #[arg(long, required_unless_present = "worker_id")]
+        serial: Option<String>,
+
+        /// Worker ID; also disconnects the worker
+        #[arg(long)]
+        worker_id: Option<String>,
+
+        /// Why the certificate is revoked
+        #[arg(long, default_value = "")]
+        reason: String,
+    },
+
+    /// List revoked certificates (admin)
+    #[command(name = "list-revoked-certs")]
+    ListRevokedCerts,
+
+    /// List tool uses waiting for approval
+    #[command(name = "list-approvals")]
+    ListApprovals,
+
+    /// Let a pending tool use proceed
+    Approve {
+        /// Approval ID (or unique prefix)
+        id: String,
+
+        /// Reason recorded with the decision
+        #[arg(long, default_value = "")]
+        reason: String,
+
+        /// Also allow later uses of this tool by the task's agent
+        #[arg(long)]
+        always: bool,
+    },
+
+    /// Deny a pending tool use
+    Deny {
+        /// Approval ID (or unique prefix)
+        id: String,
+
+        /// Reason shown to Claude
+        #[arg(long, default_value = "")]
+        reason: String,
+    },
+
+    /// Save the control plane state (tasks, runs, events, enrollment records) to a file
+    #[command(name = "export-state")]
+    ExportState {
+        /// Archive file to write
+        file: String,
+    },
+
+    /// Load a state archive written by export-state; existing tasks are kept
+    #[command(name = "import-state")]
+    ImportState {
+        /// Archive file to read
+        file: String,
+    },
+
+    /// Show or change runtime toggles (admin)
+    Toggles {
+        /// Refuse new tasks (queued and running work continues)
+        #[arg(long, value_parser = ["on", "off"])]
+        maintenance: Option<String>,
+
+        /// Keep queued tasks pending instead of assigning them to workers
+        #[arg(long, value_parser = ["on", "off"])]
+        freeze_scheduling: Option<String>,
+
+        /// Lane of tasks created without a priority
+        #[arg(long, value_parser = ["batch", "interactive"])]
+        default_priority: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum TokenCommands {
+    /// Create a single-use token a worker enrolls with (--enroll-token).
+    /// The worker is bound to --namespace (default: default)
+    Create {
+        /// What the token is for, e.g. the host it is handed to
+        #[arg(long, default_value = "")]
+        description: String,
+
+        /// Hours until the token expires
+        #[arg(long, default_value = "1")]
+        validity_hours: u32,
+
+        /// Only enroll the worker with this ID
+        #[arg(long, default_value = "")]
+        worker_id: String,
+    },
+
+    /// List bootstrap tokens
+    List,
+
+    /// Revoke a bootstrap token
+    Revoke {
+        /// Token ID (or unique prefix)
         id: String,
     },
 }
@@ -65,6 +492,7 @@ enum Commands {
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let cli = Cli::parse();
+    let _ = TIME_FORMAT.set(TimeFormat::new(cli.tz, cli.locale).with_relative(cli.relative_times));
 
     // Load CA certificate for TLS
     let ca_cert = std::fs::read(&cli.ca_cert).map_err(|e| {
@@ -74,31 +502,352 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         )
     })?;
 
-    let tls_config = ClientTlsConfig::new()
+    let mut tls_config = ClientTlsConfig::new()
         .ca_certificate(Certificate::from_pem(ca_cert))
         .domain_name("localhost");
 
+    // Present a client certificate when one is available
+    if let (Ok(cert), Ok(key)) = (
+        std::fs::read(&cli.client_cert),
+        std::fs::read(&cli.client_key),
+    ) {
+        tls_config = tls_config.identity(Identity::from_pem(cert, key));
+    }
+
     let channel = Channel::from_shared(cli.addr)?
         .tls_config(tls_config)?
         .connect()
         .await?;
 
     match cli.command {
-        Commands::CreateTask { agent, input } => {
-            create_task(channel, agent, input).await?;
+        Commands::CreateTask {
+            agent,
+            input,
+            timeout,
+            interactive,
+            pending_ttl,
+            group,
+            input_files,
+            max_memory_mb,
+            max_cpu_seconds,
+            max_wall_seconds,
+            dry_run,
+        } => {
+            let limits = RunLimits {
+                max_memory_mb: max_memory_mb.unwrap_or(0),
+                max_cpu_seconds: max_cpu_seconds.unwrap_or(0),
+                max_wall_seconds: max_wall_seconds.unwrap_or(0),
+            };
+            let request = CreateTaskRequest {
+                agent_name: agent,
+                input_json: input,
+                created_by: String::new(),
+                labels: group
+                    .map(|group| ("group".to_string(), group))
+                    .into_iter()
+                    .collect(),
+                timeout_seconds: timeout.unwrap_or(0),
+                priority: if interactive {
+                    TaskPriority::Interactive
+                } else {
+                    TaskPriority::Unspecified
+                }
+                .into(),
+                pending_ttl_seconds: pending_ttl.unwrap_or(0),
+                namespace: cli.namespace.unwrap_or_default(),
+                input_files,
+                limits: (limits != RunLimits::default()).then_some(limits),
+            };
+            if dry_run {
+                validate_task(channel, request).await?;
+            } else {
+                create_task(channel, request).await?;
+            }
         }
         Commands::GetTask { id } => {
             get_task(channel, id).await?;
         }
-        Commands::ListTasks => {
-            list_tasks(channel).await?;
+        Commands::ListTasks {
+            status,
+            agent,
+            failure_reason,
+            labels,
+            created_after,
+            created_before,
+            oldest_first,
+            limit,
+            page_token,
+            all,
+        } => {
+            let request = ListTasksRequest {
+                status_filter: status.map_or(0, |s| s as i32),
+                agent_filter: agent.unwrap_or_default(),
+                limit,
+                label_selector: labels.into_iter().collect(),
+                created_after_ms: created_after.unwrap_or(0),
+                created_before_ms: created_before.unwrap_or(0),
+                sort: if oldest_first {
+                    TaskSortOrder::CreatedAsc
+                } else {
+                    TaskSortOrder::CreatedDesc
+                } as i32,
+                page_token: page_token.unwrap_or_default(),
+                failure_reason_filter: failure_reason.map_or(0, |r| r as i32),
+                namespace_filter: cli.namespace.unwrap_or_default(),
+            };
+            list_tasks(channel, request, all).await?;
+        }
+        Commands::GroupSummary { id } => {
+            let mut client = TaskServiceClient::new(channel);
+            let summary = client
+                .get_group_summary(GetGroupSummaryRequest {
+                    group_id: id,
+                    namespace: cli.namespace.unwrap_or_default(),
+                })
+                .await?
+                .into_inner();
+            print_group_summary(&summary);
+        }
+        Commands::Search { query, limit } => {
+            let request = SearchTasksRequest {
+                query,
+                limit,
+                namespace: cli.namespace.unwrap_or_default(),
+            };
+            search_tasks(channel, request).await?;
+        }
+        Commands::WatchTasks {
+            agent,
+            labels,
+            tasks,
+            output,
+        } => {
+            let request = WatchTasksRequest {
+                namespace: cli.namespace.unwrap_or_default(),
+                agent_name: agent.unwrap_or_default(),
+                labels: labels.into_iter().collect(),
+                task_ids: tasks,
+                include_output: output,
+            };
+            watch_tasks(channel, request).await?;
+        }
+        Commands::TailRun { id, from_seq } => {
+            tail_run(channel, id, from_seq).await?;
+        }
+        Commands::Bulk {
+            operation,
+            filters,
+            agent,
+            dry_run,
+            yes,
+        } => {
+            let filter = TaskFilter {
+                agent_name: agent.unwrap_or_default(),
+                namespace: cli.namespace.unwrap_or_default(),
+                ..TaskFilter::default()
+            };
+            bulk_tasks(channel, operation, filter, filters, dry_run, yes).await?;
         }
         Commands::ListWorkers => {
-            list_workers(channel).await?;
+            list_workers(channel, cli.namespace).await?;
+        }
+        Commands::DrainWorker { id, wait } => {
+            drain_worker(channel, id, wait).await?;
+        }
+        Commands::UndrainWorker { id } => {
+            let mut client = WorkerServiceClient::new(channel);
+            let worker = client
+                .undrain_worker(UndrainWorkerRequest { worker_id: id })
+                .await?
+                .into_inner();
+            println!(
+                "Worker {} undrained ({})",
+                worker.worker_id,
+                worker_status_name(worker.status)
+            );
+        }
+        Commands::DisconnectWorker {
+            id,
+            reason,
+            reschedule,
+        } => {
+            let mut client = WorkerServiceClient::new(channel);
+            let response = client
+                .disconnect_worker(DisconnectWorkerRequest {
+                    worker_id: id,
+                    reason: reason.unwrap_or_default(),
+                    reschedule,
+                })
+                .await?
+                .into_inner();
+            println!("Worker {} disconnected", response.worker_id);
+            for run_id in &response.failed_run_ids {
+                println!("  Failed run: {}", run_id);
+            }
+            for task_id in &response.rescheduled_task_ids {
+                println!("  Rescheduled task: {}", task_id);
+            }
         }
         Commands::CancelTask { id } => {
             cancel_task(channel, id).await?;
         }
+        Commands::CreateSchedule {
+            cron,
+            agent,
+            input,
+            timeout,
+        } => {
+            create_schedule(channel, cron, agent, input, timeout).await?;
+        }
+        Commands::ListSchedules => {
+            list_schedules(channel).await?;
+        }
+        Commands::PauseSchedule { id } => {
+            let mut client = ScheduleServiceClient::new(channel);
+            let schedule = client
+                .pause_schedule(PauseScheduleRequest { id })
+                .await?
+                .into_inner();
+            println!("Schedule paused:");
+            print_schedule(&schedule);
+        }
+        Commands::ResumeSchedule { id } => {
+            let mut client = ScheduleServiceClient::new(channel);
+            let schedule = client
+                .resume_schedule(ResumeScheduleRequest { id })
+                .await?
+                .into_inner();
+            println!("Schedule resumed:");
+            print_schedule(&schedule);
+        }
+        Commands::DeleteSchedule { id } => {
+            let mut client = ScheduleServiceClient::new(channel);
+            let resp = client
+                .delete_schedule(DeleteScheduleRequest { id })
+                .await?
+                .into_inner();
+            println!("Schedule deleted: {}", resp.id);
+        }
+        Commands::CreateWorkflow { template, params } => {
+            let mut client = WorkflowServiceClient::new(channel);
+            let workflow = client
+                .create_workflow(CreateWorkflowRequest {
+                    template_name: template,
+                    params: params.into_iter().collect(),
+                    created_by: String::new(),
+                })
+                .await?
+                .into_inner();
+            println!("Workflow created:");
+            print_workflow(&workflow);
+        }
+        Commands::GetWorkflow { id } => {
+            let mut client = WorkflowServiceClient::new(channel);
+            let workflow = client
+                .get_workflow(GetWorkflowRequest { id })
+                .await?
+                .into_inner();
+            println!("Workflow:");
+            print_workflow(&workflow);
+        }
+        Commands::ListWorkflows => {
+            list_workflows(channel).await?;
+        }
+        Commands::ListWorkflowTemplates => {
+            list_workflow_templates(channel).await?;
+        }
+        Commands::CreateApiKey { name, role } => {
+            let mut client = ApiKeyServiceClient::new(channel);
+            let resp = client
+                .create_api_key(CreateApiKeyRequest {
+                    name,
+                    role,
+                    namespace: cli.namespace.unwrap_or_default(),
+                })
+                .await?
+                .into_inner();
+            let api_key = resp.api_key.unwrap_or_default();
+            println!("API key created:");
+            println!("  ID:    {}", api_key.id);
+            println!("  Name:  {}", api_key.name);
+            println!("  Role:  {}", api_key.role);
+            if !api_key.namespace.is_empty() {
+                println!("  Namespace: {}", api_key.namespace);
+            }
+            println!("  Key:   {}", resp.key);
+            println!();
+            println!("Store the key now; it cannot be shown again.");
+        }
+        Commands::ListApiKeys => {
+            list_api_keys(channel).await?;
+        }
+        Commands::RevokeApiKey { id } => {
+            let mut client = ApiKeyServiceClient::new(channel);
+            let api_key = client
+                .revoke_api_key(RevokeApiKeyRequest { id })
+                .await?
+                .into_inner();
+            println!("API key revoked: {} ({})", api_key.id, api_key.name);
+        }
+        Commands::Token { command } => {
+            bootstrap_token(channel, command, cli.namespace.unwrap_or_default()).await?;
+        }
+        Commands::RevokeCert {
+            serial,
+            worker_id,
+            reason,
+        } => {
+            let mut client = AdminServiceClient::new(channel);
+            let revoked = client
+                .revoke_certificate(RevokeCertificateRequest {
+                    serial: serial.unwrap_or_default(),
+                    worker_id: worker_id.unwrap_or_default(),
+                    reason,
+                })
+                .await?
+                .into_inner();
+            if !revoked.serial.is_empty() {
+                println!("Certificate revoked: serial {}", revoked.serial);
+            }
+            if !revoked.worker_id.is_empty() {
+                println!("Certificates of worker {} revoked", revoked.worker_id);
+            }
+        }
+        Commands::ListRevokedCerts => {
+            list_revoked_certs(channel).await?;
+        }
+        Commands::ListApprovals => {
+            list_approvals(channel).await?;
+        }
+        Commands::Approve { id, reason, always } => {
+            decide_approval(channel, id, true, always, reason).await?;
+        }
+        Commands::Deny { id, reason } => {
+            decide_approval(channel, id, false, false, reason).await?;
+        }
+        Commands::ExportState { file } => {
+            export_state(channel, file).await?;
+        }
+        Commands::ImportState { file } => {
+            import_state(channel, file).await?;
+        }
+        Commands::Toggles {
+            maintenance,
+            freeze_scheduling,
+            default_priority,
+        } => {
+            let request = UpdateTogglesRequest {
+                maintenance: maintenance.map(|v| v == "on"),
+                scheduling_frozen: freeze_scheduling.map(|v| v == "on"),
+                default_priority: match default_priority.as_deref() {
+                    Some("interactive") => TaskPriority::Interactive,
+                    Some(_) => TaskPriority::Batch,
+                    None => TaskPriority::Unspecified,
+                }
+                .into(),
+            };
+            toggles(channel, request).await?;
+        }
     }
 
     Ok(())
@@ -106,18 +855,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
 async fn create_task(
     channel: Channel,
-    agent_name: String,
-    input_json: String,
+    request: CreateTaskRequest,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let mut client = TaskServiceClient::new(channel);
 
-    let request = CreateTaskRequest {
-        agent_name,
-        input_json,
-        created_by: String::new(),
-        labels: std::collections::HashMap::new(),
-    };
-
     let response = client.create_task(request).await?;
     let task = response.into_inner();
 
@@ -127,6 +868,29 @@ async fn create_task(
     Ok(())
 }
 
+async fn validate_task(
+    channel: Channel,
+    request: CreateTaskRequest,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut client = TaskServiceClient::new(channel);
+    let report = client.validate_task(request).await?.into_inner();
+
+    for finding in &report.findings {
+        let severity = match finding.severity() {
+            ValidationSeverity::Error => "error",
+            _ => "warning",
+        };
+        println!("{}: {} [{}]", severity, finding.message, finding.code);
+    }
+    if !report.valid {
+        return Err("the task would be rejected".into());
+    }
+    if report.findings.is_empty() {
+        println!("OK: the task can be created and scheduled now");
+    }
+    Ok(())
+}
+
 async fn get_task(channel: Channel, id: String) -> Result<(), Box<dyn std::error::Error>> {
     let mut client = TaskServiceClient::new(channel);
 
@@ -140,23 +904,28 @@ async fn get_task(channel: Channel, id: String) -> Result<(), Box<dyn std::error
     Ok(())
 }
 
-async fn list_tasks(channel: Channel) -> Result<(), Box<dyn std::error::Error>> {
+async fn list_tasks(
+    channel: Channel,
+    mut request: ListTasksRequest,
+    all: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
     let mut client = TaskServiceClient::new(channel);
 
-    let request = ListTasksRequest {
-        status_filter: 0, // 0 = no filter
-        agent_filter: String::new(),
-        limit: 100,
+    let mut tasks = Vec::new();
+    let next_page_token = loop {
+        let resp = client.list_tasks(request.clone()).await?.into_inner();
+        tasks.extend(resp.tasks);
+        if !all || resp.next_page_token.is_empty() {
+            break resp.next_page_token;
+        }
+        request.page_token = resp.next_page_token;
     };
 
-    let response = client.list_tasks(request).await?;
-    let resp = response.into_inner();
-
-    println!("Tasks ({}):", resp.tasks.len());
+    println!("Tasks ({}):", tasks.len());
     println!("{:<36}  {:<10}  {:<16}  CREATED", "ID", "STATUS", "AGENT");
     println!("{}", "-".repeat(80));
 
-    for task in resp.tasks {
+    for task in tasks {
         let status = status_name(task.status);
         let created = format_timestamp(task.created_at_ms);
         println!(
@@ -165,15 +934,191 @@ async fn list_tasks(channel: Channel) -> Result<(), Box<dyn std::error::Error>>
         );
     }
 
+    if !next_page_token.is_empty() {
+        println!();
+        println!("More tasks: --page-token {}", next_page_token);
+    }
+
+    Ok(())
+}
+
+async fn search_tasks(
+    channel: Channel,
+    request: SearchTasksRequest,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut client = TaskServiceClient::new(channel);
+
+    let resp = client.search_tasks(request).await?.into_inner();
+
+    println!("Matches ({}):", resp.hits.len());
+    println!(
+        "{:<36}  {:<10}  {:<16}  {:<22}  MATCHED",
+        "ID", "STATUS", "AGENT", "CREATED"
+    );
+    println!("{}", "-".repeat(100));
+
+    for hit in resp.hits {
+        let Some(task) = hit.task else { continue };
+        println!(
+            "{:<36}  {:<10}  {:<16}  {:<22}  {}",
+            task.id,
+            status_name(task.status),
+            task.agent_name,
+            format_timestamp(task.created_at_ms),
+            hit.matched_fields.join(",")
+        );
+    }
+
+    Ok(())
+}
+
+async fn watch_tasks(
+    channel: Channel,
+    request: WatchTasksRequest,
+) -> Result<(), Box<dyn std::error::Error>> {
+    use taskrun_proto::pb::task_watch_event::Event;
+
+    let mut client = TaskServiceClient::new(channel);
+    let mut stream = client.watch_tasks(request).await?.into_inner();
+
+    while let Some(update) = stream.message().await? {
+        let time = format_timestamp(update.timestamp_ms);
+        let line = match update.event {
+            Some(Event::Created(task)) => format!("created ({})", task.agent_name),
+            Some(Event::TaskStatus(change)) => {
+                let mut line = format!("task {}", status_name(change.status));
+                if let Ok(reason) = FailureReason::try_from(change.failure_reason) {
+                    if reason != FailureReason::Unspecified {
+                        line.push_str(&format!(" ({})", failure_reason_name(reason)));
+                    }
+                }
+                if !change.failure_message.is_empty() {
+                    line.push_str(&format!(": {}", change.failure_message));
+                }
+                line
+            }
+            Some(Event::RunStatus(change)) => format!(
+                "run {} {} on {}",
+                change.run_id,
+                run_status_name(change.status),
+                change.worker_id
+            ),
+            Some(Event::RunEvent(event)) => format!(
+                "run {} event {}",
+                event.run_id,
+                taskrun_proto::pb::RunEventType::try_from(event.event_type)
+                    .map(|t| t.as_str_name().trim_start_matches("RUN_EVENT_TYPE_"))
+                    .unwrap_or("UNKNOWN")
+            ),
+            Some(Event::Output(chunk)) => {
+                print!("{}", chunk.content);
+                continue;
+            }
+            Some(Event::Lagged(lagged)) => {
+                eprintln!(
+                    "{}  missed {} update(s); re-list tasks to catch up",
+                    time, lagged.missed
+                );
+                continue;
+            }
+            None => continue,
+        };
+        println!("{}  {:<36}  {}", time, update.task_id, line);
+    }
+
     Ok(())
 }
 
-async fn list_workers(channel: Channel) -> Result<(), Box<dyn std::error::Error>> {
+async fn tail_run(
+    channel: Channel,
+    run_id: String,
+    from_seq: u64,
+) -> Result<(), Box<dyn std::error::Error>> {
+    use std::io::Write;
+
+    let mut client = TaskServiceClient::new(channel);
+    let mut stream = client
+        .stream_run_output(StreamRunOutputRequest { run_id, from_seq })
+        .await?
+        .into_inner();
+
+    while let Some(chunk) = stream.message().await? {
+        if chunk.is_final {
+            println!();
+            eprintln!(
+                "Run {} {} (next seq {})",
+                chunk.run_id,
+                run_status_name(chunk.status),
+                chunk.seq
+            );
+            break;
+        }
+        print!("{}", chunk.content);
+        std::io::stdout().flush()?;
+    }
+
+    Ok(())
+}
+
+fn print_group_summary(summary: &GroupSummary) {
+    const BAR_WIDTH: u32 = 40;
+    let finished = summary.completed + summary.failed + summary.cancelled;
+    let filled = (finished * BAR_WIDTH)
+        .checked_div(summary.total)
+        .unwrap_or(0);
+
+    println!("Group {}:", summary.group_id);
+    println!(
+        "  [{}{}] {}/{} finished",
+        "#".repeat(filled as usize),
+        "-".repeat((BAR_WIDTH - filled) as usize),
+        finished,
+        summary.total
+    );
+    println!(
+        "  Pending {}, running {}, completed {}, failed {}, cancelled {}",
+        summary.pending, summary.running, summary.completed, summary.failed, summary.cancelled
+    );
+    println!(
+        "  Run time:   {}s (elapsed {}s)",
+        summary.total_run_seconds, summary.elapsed_seconds
+    );
+    if summary.total_cost_usd > 0.0 {
+        println!("  Cost:       ${:.4}", summary.total_cost_usd);
+    }
+
+    let print_child = |child: &GroupChild| {
+        println!(
+            "    - {} {} {} ({}s)",
+            child.task_id,
+            child.agent_name,
+            status_name(child.status),
+            child.duration_seconds
+        );
+        if !child.failure_message.is_empty() {
+            println!("      {}", child.failure_message);
+        }
+    };
+    if !summary.slowest.is_empty() {
+        println!("  Slowest:");
+        summary.slowest.iter().for_each(print_child);
+    }
+    if !summary.failed_children.is_empty() {
+        println!("  Failed:");
+        summary.failed_children.iter().for_each(print_child);
+    }
+}
+
+async fn list_workers(
+    channel: Channel,
+    namespace: Option<String>,
+) -> Result<(), Box<dyn std::error::Error>> {
     let mut client = WorkerServiceClient::new(channel);
 
     let request = ListWorkersRequest {
         agent_name: None,
         status: None,
+        namespace,
     };
 
     let response = client.list_workers(request).await?;
@@ -197,6 +1142,56 @@ async fn list_workers(channel: Channel) -> Result<(), Box<dyn std::error::Error>
     Ok(())
 }
 
+async fn drain_worker(
+    channel: Channel,
+    id: String,
+    wait: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut client = WorkerServiceClient::new(channel);
+
+    let resp = client
+        .drain_worker(DrainWorkerRequest { worker_id: id })
+        .await?
+        .into_inner();
+    let worker_id = resp.worker.map(|w| w.worker_id).unwrap_or_default();
+
+    if resp.active_run_ids.is_empty() {
+        println!("Worker {} drained, safe to stop", worker_id);
+        return Ok(());
+    }
+    println!(
+        "Draining worker {}: {} run(s) left",
+        worker_id,
+        resp.active_run_ids.len()
+    );
+    for run_id in &resp.active_run_ids {
+        println!("  - {}", run_id);
+    }
+    if !wait {
+        return Ok(());
+    }
+
+    // Active runs come from heartbeats, so poll at about their pace
+    let mut left = resp.active_run_ids.len() as u32;
+    loop {
+        tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+        let worker = client
+            .get_worker(GetWorkerRequest {
+                worker_id: worker_id.clone(),
+            })
+            .await?
+            .into_inner();
+        if worker.active_runs == 0 {
+            println!("Worker {} drained, safe to stop", worker_id);
+            return Ok(());
+        }
+        if worker.active_runs != left {
+            left = worker.active_runs;
+            println!("  {} run(s) left", left);
+        }
+    }
+}
+
 async fn cancel_task(channel: Channel, id: String) -> Result<(), Box<dyn std::error::Error>> {
     let mut client = TaskServiceClient::new(channel);
 
@@ -211,20 +1206,334 @@ async fn cancel_task(channel: Channel, id: String) -> Result<(), Box<dyn std::er
     Ok(())
 }
 
+async fn bulk_tasks(
+    channel: Channel,
+    operation: String,
+    mut filter: TaskFilter,
+    filters: Vec<(String, String)>,
+    dry_run: bool,
+    yes: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    for (key, value) in filters {
+        if key == "status" {
+            filter.set_status(parse_status(&value)?);
+        } else if key == "reason" {
+            filter.set_failure_reason(parse_failure_reason(&value)?);
+        } else {
+            filter.labels.insert(key, value);
+        }
+    }
+
+    let mut client = TaskServiceClient::new(channel);
+    let request = |dry_run| BulkTaskRequest {
+        filter: Some(filter.clone()),
+        dry_run,
+    };
+    // Show what would be affected before changing anything
+    let preview = send_bulk(&mut client, &operation, request(true)).await?;
+    println!("{} task(s) to {}:", preview.task_ids.len(), operation);
+    for id in &preview.task_ids {
+        println!("  {}", id);
+    }
+    if dry_run || preview.task_ids.is_empty() {
+        return Ok(());
+    }
+
+    if !yes {
+        print!("Proceed? [y/N] ");
+        std::io::Write::flush(&mut std::io::stdout())?;
+        let mut answer = String::new();
+        std::io::stdin().read_line(&mut answer)?;
+        if !matches!(answer.trim(), "y" | "Y" | "yes") {
+            println!("Aborted.");
+            return Ok(());
+        }
+    }
+
+    let resp = send_bulk(&mut client, &operation, request(false)).await?;
+    println!(
+        "Bulk {}: {} task(s), {} failed",
+        operation,
+        resp.task_ids.len(),
+        resp.failed_task_ids.len()
+    );
+    for id in &resp.failed_task_ids {
+        println!("  failed: {}", id);
+    }
+
+    Ok(())
+}
+
+async fn send_bulk(
+    client: &mut TaskServiceClient<Channel>,
+    operation: &str,
+    request: BulkTaskRequest,
+) -> Result<BulkTaskResponse, tonic::Status> {
+    let response = if operation == "cancel" {
+        client.bulk_cancel_tasks(request).await?
+    } else {
+        client.bulk_retry_tasks(request).await?
+    };
+    Ok(response.into_inner())
+}
+
+async fn create_schedule(
+    channel: Channel,
+    cron_expr: String,
+    agent_name: String,
+    input_template: String,
+    timeout_seconds: Option<u32>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut client = ScheduleServiceClient::new(channel);
+
+    let request = CreateScheduleRequest {
+        cron_expr,
+        agent_name,
+        input_template,
+        created_by: String::new(),
+        labels: std::collections::HashMap::new(),
+        timeout_seconds: timeout_seconds.unwrap_or(0),
+    };
+
+    let schedule = client.create_schedule(request).await?.into_inner();
+
+    println!("Schedule created:");
+    print_schedule(&schedule);
+
+    Ok(())
+}
+
+async fn list_schedules(channel: Channel) -> Result<(), Box<dyn std::error::Error>> {
+    let mut client = ScheduleServiceClient::new(channel);
+
+    let resp = client
+        .list_schedules(ListSchedulesRequest {})
+        .await?
+        .into_inner();
+
+    println!("Schedules ({}):", resp.schedules.len());
+    println!(
+        "{:<36}  {:<16}  {:<16}  {:<8}  NEXT FIRE",
+        "ID", "CRON", "AGENT", "STATUS"
+    );
+    println!("{}", "-".repeat(100));
+
+    for schedule in resp.schedules {
+        let status = if schedule.paused { "PAUSED" } else { "ACTIVE" };
+        let next = if schedule.next_fire_at_ms > 0 {
+            format_timestamp(schedule.next_fire_at_ms)
+        } else {
+            "-".to_string()
+        };
+        println!(
+            "{:<36}  {:<16}  {:<16}  {:<8}  {}",
+            schedule.id, schedule.cron_expr, schedule.agent_name, status, next
+        );
+    }
+
+    Ok(())
+}
+
+fn print_schedule(schedule: &taskrun_proto::pb::Schedule) {
+    println!("  ID:         {}", schedule.id);
+    println!("  Cron:       {}", schedule.cron_expr);
+    println!("  Agent:      {}", schedule.agent_name);
+    println!(
+        "  Status:     {}",
+        if schedule.paused { "PAUSED" } else { "ACTIVE" }
+    );
+    if schedule.next_fire_at_ms > 0 {
+        println!(
+            "  Next fire:  {}",
+            format_timestamp(schedule.next_fire_at_ms)
+        );
+    }
+    if schedule.last_fired_at_ms > 0 {
+        println!(
+            "  Last fired: {} (task {})",
+            format_timestamp(schedule.last_fired_at_ms),
+            schedule.last_task_id
+        );
+    }
+}
+
+async fn list_workflows(channel: Channel) -> Result<(), Box<dyn std::error::Error>> {
+    let mut client = WorkflowServiceClient::new(channel);
+
+    let resp = client
+        .list_workflows(ListWorkflowsRequest { limit: 0 })
+        .await?
+        .into_inner();
+
+    println!("Workflows ({}):", resp.workflows.len());
+    println!(
+        "{:<36}  {:<20}  {:<10}  {:<6}  CREATED",
+        "ID", "TEMPLATE", "STATUS", "STEPS"
+    );
+    println!("{}", "-".repeat(100));
+
+    for workflow in resp.workflows {
+        let done = workflow
+            .steps
+            .iter()
+            .filter(|s| s.status == TaskStatus::Completed as i32)
+            .count();
+        println!(
+            "{:<36}  {:<20}  {:<10}  {:<6}  {}",
+            workflow.id,
+            workflow.template_name,
+            workflow_status_name(workflow.status),
+            format!("{}/{}", done, workflow.steps.len()),
+            format_timestamp(workflow.created_at_ms)
+        );
+    }
+
+    Ok(())
+}
+
+async fn list_workflow_templates(channel: Channel) -> Result<(), Box<dyn std::error::Error>> {
+    let mut client = WorkflowServiceClient::new(channel);
+
+    let resp = client
+        .list_workflow_templates(ListWorkflowTemplatesRequest {})
+        .await?
+        .into_inner();
+
+    println!("Workflow templates ({}):", resp.templates.len());
+    for template in resp.templates {
+        println!();
+        println!("  {}", template.name);
+        if !template.description.is_empty() {
+            println!("    {}", template.description);
+        }
+        if !template.params.is_empty() {
+            println!("    Params: {}", template.params.join(", "));
+        }
+        for step in &template.steps {
+            if step.depends_on.is_empty() {
+                println!("    - {} ({})", step.name, step.agent_name);
+            } else {
+                println!(
+                    "    - {} ({}) after {}",
+                    step.name,
+                    step.agent_name,
+                    step.depends_on.join(", ")
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn print_workflow(workflow: &taskrun_proto::pb::Workflow) {
+    println!("  ID:         {}", workflow.id);
+    println!("  Template:   {}", workflow.template_name);
+    println!("  Status:     {}", workflow_status_name(workflow.status));
+    println!("  Created:    {}", format_timestamp(workflow.created_at_ms));
+    println!("  Steps:");
+    for step in &workflow.steps {
+        println!(
+            "    - {:<16} {:<10} task {} ({})",
+            step.step_name,
+            status_name(step.status),
+            step.task_id,
+            step.agent_name
+        );
+    }
+}
+
 fn print_task(task: &taskrun_proto::pb::Task) {
     println!("  ID:         {}", task.id);
     println!("  Agent:      {}", task.agent_name);
+    println!("  Namespace:  {}", task.namespace);
     println!("  Status:     {}", status_name(task.status));
     println!("  Created:    {}", format_timestamp(task.created_at_ms));
+    if !task.created_by.is_empty() {
+        if task.client_ip.is_empty() {
+            println!("  Created by: {}", task.created_by);
+        } else {
+            println!("  Created by: {} from {}", task.created_by, task.client_ip);
+        }
+    }
+    if task.timeout_seconds > 0 {
+        println!("  Timeout:    {}s", task.timeout_seconds);
+    }
+    if task.priority() == TaskPriority::Interactive {
+        println!("  Priority:   INTERACTIVE");
+    }
+    if !task.depends_on.is_empty() {
+        println!("  Depends on: {}", task.depends_on.join(", "));
+    }
+    if task.pending_ttl_seconds > 0 {
+        println!("  Expires:    after {}s pending", task.pending_ttl_seconds);
+    }
+    if let Some(limits) = &task.limits {
+        let mut parts = Vec::new();
+        if limits.max_memory_mb > 0 {
+            parts.push(format!("{} MiB memory", limits.max_memory_mb));
+        }
+        if limits.max_cpu_seconds > 0 {
+            parts.push(format!("{}s CPU", limits.max_cpu_seconds));
+        }
+        if limits.max_wall_seconds > 0 {
+            parts.push(format!("{}s wall-clock", limits.max_wall_seconds));
+        }
+        if !parts.is_empty() {
+            println!("  Limits:     {}", parts.join(", "));
+        }
+    }
+    if let Some(queue) = &task.queue {
+        if queue.eta_seconds > 0 {
+            println!(
+                "  Queue:      position {} (ETA ~{}s)",
+                queue.position, queue.eta_seconds
+            );
+        } else {
+            println!("  Queue:      position {}", queue.position);
+        }
+    }
+    if task.failure_reason() != FailureReason::Unspecified {
+        println!(
+            "  Reason:     {}",
+            failure_reason_name(task.failure_reason())
+        );
+    }
+    if !task.failure_message.is_empty() {
+        println!("  Message:    {}", task.failure_message);
+    }
 
     if !task.runs.is_empty() {
         println!("  Runs:");
         for run in &task.runs {
             let run_status = run_status_name(run.status);
             println!("    - {} ({})", run.run_id, run_status);
+            if run.failure_reason() != FailureReason::Unspecified {
+                println!(
+                    "      Reason:  {}",
+                    failure_reason_name(run.failure_reason())
+                );
+            }
             if let Some(backend) = &run.backend_used {
                 println!("      Backend: {}/{}", backend.provider, backend.model_name);
             }
+            if let Some(result) = &run.result {
+                let mut parts = vec![
+                    format!("{} files changed", result.files_changed),
+                    format!("{} tool calls", result.tools_used),
+                ];
+                if let Some(ms) = result.duration_ms {
+                    parts.push(format!("{}s", ms / 1000));
+                }
+                let tokens = result.input_tokens.unwrap_or(0) + result.output_tokens.unwrap_or(0);
+                if tokens > 0 {
+                    parts.push(format!("{} tokens", tokens));
+                }
+                println!("      Result:  {}", parts.join(", "));
+                if !result.answer.is_empty() {
+                    println!("      Answer:  {}", result.answer);
+                }
+            }
         }
     }
 }
@@ -241,6 +1550,16 @@ fn status_name(status: i32) -> &'static str {
     }
 }
 
+fn workflow_status_name(status: i32) -> &'static str {
+    match status {
+        0 => "UNSPECIFIED",
+        1 => "RUNNING",
+        2 => "COMPLETED",
+        3 => "FAILED",
+        _ => "UNKNOWN",
+    }
+}
+
 fn run_status_name(status: i32) -> &'static str {
     match status {
         0 => "UNSPECIFIED",
@@ -261,14 +1580,344 @@ fn worker_status_name(status: i32) -> &'static str {
         2 => "BUSY",
         3 => "DRAINING",
         4 => "ERROR",
+        5 => "DEGRADED",
+        6 => "OFFLINE",
         _ => "UNKNOWN",
     }
 }
 
+/// Parse a `key=value` template parameter.
+fn parse_param(s: &str) -> Result<(String, String), String> {
+    s.split_once('=')
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .ok_or_else(|| format!("expected key=value, got '{}'", s))
+}
+
+fn parse_status(s: &str) -> Result<TaskStatus, String> {
+    TaskStatus::from_str_name(&format!("TASK_STATUS_{}", s.to_uppercase()))
+        .ok_or_else(|| format!("unknown task status '{}'", s))
+}
+
+fn parse_failure_reason(s: &str) -> Result<FailureReason, String> {
+    FailureReason::from_str_name(&format!("FAILURE_REASON_{}", s.to_uppercase()))
+        .filter(|r| *r != FailureReason::Unspecified)
+        .ok_or_else(|| format!("unknown failure reason '{}'", s))
+}
+
+/// Lowercase name of a failure reason, e.g. `worker_lost`.
+fn failure_reason_name(reason: FailureReason) -> String {
+    reason
+        .as_str_name()
+        .trim_start_matches("FAILURE_REASON_")
+        .to_lowercase()
+}
+
+/// Parse an RFC 3339 time into a Unix timestamp in ms.
+fn parse_time(s: &str) -> Result<i64, String> {
+    chrono::DateTime::parse_from_rfc3339(s)
+        .map(|t| t.timestamp_millis())
+        .map_err(|e| format!("invalid time '{}': {}", s, e))
+}
+
+async fn list_api_keys(channel: Channel) -> Result<(), Box<dyn std::error::Error>> {
+    let mut client = ApiKeyServiceClient::new(channel);
+
+    let resp = client
+        .list_api_keys(ListApiKeysRequest {})
+        .await?
+        .into_inner();
+
+    println!("API keys ({}):", resp.api_keys.len());
+    println!(
+        "{:<8}  {:<24}  {:<12}  {:<8}  {:<16}  CREATED",
+        "ID", "NAME", "PREFIX", "ROLE", "NAMESPACE"
+    );
+    println!("{}", "-".repeat(98));
+
+    for api_key in resp.api_keys {
+        let namespace = if api_key.namespace.is_empty() {
+            "*"
+        } else {
+            &api_key.namespace
+        };
+        println!(
+            "{:<8}  {:<24}  {:<12}  {:<8}  {:<16}  {}",
+            api_key.id,
+            api_key.name,
+            api_key.prefix,
+            api_key.role,
+            namespace,
+            format_timestamp(api_key.created_at_ms)
+        );
+    }
+
+    Ok(())
+}
+
+async fn bootstrap_token(
+    channel: Channel,
+    command: TokenCommands,
+    namespace: String,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut client = AdminServiceClient::new(channel);
+    match command {
+        TokenCommands::Create {
+            description,
+            validity_hours,
+            worker_id,
+        } => {
+            let resp = client
+                .create_bootstrap_token(CreateBootstrapTokenRequest {
+                    description,
+                    validity_hours,
+                    namespace,
+                    worker_id,
+                })
+                .await?
+                .into_inner();
+            let token = resp.token.unwrap_or_default();
+            println!("Bootstrap token created:");
+            println!("  ID:      {}", token.id);
+            if !token.description.is_empty() {
+                println!("  For:     {}", token.description);
+            }
+            let worker = match token.bound_worker_id.as_str() {
+                "" => "any worker".to_string(),
+                id => format!("worker {}", id),
+            };
+            println!("  Enrolls: {} in namespace {}", worker, token.namespace);
+            println!("  Expires: {}", format_timestamp(token.expires_at_ms));
+            println!("  Token:   {}", resp.bootstrap_token);
+            println!();
+            println!("Start the worker with --enroll-token <token>; it can be used once.");
+        }
+        TokenCommands::List => {
+            let resp = client
+                .list_bootstrap_tokens(ListBootstrapTokensRequest {})
+                .await?
+                .into_inner();
+            println!("Bootstrap tokens ({}):", resp.tokens.len());
+            println!(
+                "{:<12}  {:<24}  {:<10}  {:<36}  EXPIRES",
+                "ID", "DESCRIPTION", "STATUS", "WORKER"
+            );
+            println!("{}", "-".repeat(110));
+            let now_ms = chrono::Utc::now().timestamp_millis();
+            for token in resp.tokens {
+                let status = if token.consumed {
+                    "used"
+                } else if token.expires_at_ms <= now_ms {
+                    "expired"
+                } else {
+                    "unused"
+                };
+                println!(
+                    "{:<12}  {:<24}  {:<10}  {:<36}  {}",
+                    token.id,
+                    token.description,
+                    status,
+                    token.worker_id,
+                    format_timestamp(token.expires_at_ms)
+                );
+            }
+        }
+        TokenCommands::Revoke { id } => {
+            let token = client
+                .revoke_bootstrap_token(RevokeBootstrapTokenRequest { id })
+                .await?
+                .into_inner();
+            println!("Bootstrap token revoked: {}", token.id);
+        }
+    }
+    Ok(())
+}
+
+async fn list_revoked_certs(channel: Channel) -> Result<(), Box<dyn std::error::Error>> {
+    let mut client = AdminServiceClient::new(channel);
+    let resp = client
+        .list_revoked_certificates(ListRevokedCertificatesRequest {})
+        .await?
+        .into_inner();
+
+    println!("Revoked certificates ({}):", resp.certificates.len());
+    println!(
+        "{:<34}  {:<36}  {:<22}  REASON",
+        "SERIAL", "WORKER", "REVOKED"
+    );
+    println!("{}", "-".repeat(110));
+    for cert in resp.certificates {
+        println!(
+            "{:<34}  {:<36}  {:<22}  {}",
+            cert.serial,
+            cert.worker_id,
+            format_timestamp(cert.revoked_at_ms),
+            cert.reason
+        );
+    }
+    Ok(())
+}
+
+async fn list_approvals(channel: Channel) -> Result<(), Box<dyn std::error::Error>> {
+    let mut client = ApprovalServiceClient::new(channel);
+
+    let resp = client
+        .list_approvals(ListApprovalsRequest {})
+        .await?
+        .into_inner();
+
+    println!("Pending approvals ({}):", resp.approvals.len());
+    println!(
+        "{:<40}  {:<10}  {:<16}  {:<8}  {:<22}  DEFAULT",
+        "ID", "TOOL", "AGENT", "RUN", "EXPIRES"
+    );
+    println!("{}", "-".repeat(118));
+
+    for approval in resp.approvals {
+        println!(
+            "{:<40}  {:<10}  {:<16}  {:<8}  {:<22}  {}",
+            approval.id,
+            approval.tool_name,
+            approval.agent_name,
+            &approval.run_id[..8.min(approval.run_id.len())],
+            format_timestamp(approval.expires_at_ms),
+            if approval.default_allow {
+                "allow"
+            } else {
+                "deny"
+            }
+        );
+        println!("  {}", approval.input_json);
+    }
+
+    Ok(())
+}
+
+async fn decide_approval(
+    channel: Channel,
+    id: String,
+    allowed: bool,
+    always_allow: bool,
+    reason: String,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut client = ApprovalServiceClient::new(channel);
+
+    let approval = client
+        .decide_approval(DecideApprovalRequest {
+            id,
+            allowed,
+            reason,
+            always_allow,
+        })
+        .await?
+        .into_inner();
+
+    println!(
+        "{} {} for run {} ({})",
+        match (allowed, always_allow) {
+            (true, true) => "Always allowed",
+            (true, false) => "Approved",
+            (false, _) => "Denied",
+        },
+        approval.tool_name,
+        approval.run_id,
+        approval.id
+    );
+
+    Ok(())
+}
+
+/// Size of the chunks an archive is uploaded in.
+const STATE_CHUNK_SIZE: usize = 64 * 1024;
+
+async fn export_state(channel: Channel, file: String) -> Result<(), Box<dyn std::error::Error>> {
+    let mut client = AdminServiceClient::new(channel);
+    let mut stream = client
+        .export_state(ExportStateRequest {})
+        .await?
+        .into_inner();
+
+    let mut bytes = Vec::new();
+    while let Some(chunk) = stream.message().await? {
+        bytes.extend_from_slice(&chunk.data);
+    }
+    std::fs::write(&file, &bytes)
+        .map_err(|e| format!("Failed to write state archive to '{}': {}", file, e))?;
+    println!("State exported to {} ({} bytes)", file, bytes.len());
+    Ok(())
+}
+
+async fn import_state(channel: Channel, file: String) -> Result<(), Box<dyn std::error::Error>> {
+    let bytes = std::fs::read(&file)
+        .map_err(|e| format!("Failed to read state archive from '{}': {}", file, e))?;
+    let chunks: Vec<StateChunk> = bytes
+        .chunks(STATE_CHUNK_SIZE)
+        .map(|data| StateChunk {
+            data: data.to_vec(),
+        })
+        .collect();
+
+    let mut client = AdminServiceClient::new(channel);
+    let resp = client
+        .import_state(tokio_stream::iter(chunks))
+        .await?
+        .into_inner();
+    println!("State imported from {} (version {}):", file, resp.version);
+    println!("  Tasks imported:   {}", resp.tasks_imported);
+    println!(
+        "  Tasks skipped:    {} (already present)",
+        resp.tasks_skipped
+    );
+    println!("  Events imported:  {}", resp.events_imported);
+    println!("  Tokens imported:  {}", resp.tokens_imported);
+    if resp.runs_interrupted > 0 {
+        println!(
+            "  Runs interrupted: {} (failed as worker_lost; their tasks are pending again)",
+            resp.runs_interrupted
+        );
+    }
+    Ok(())
+}
+
+async fn toggles(
+    channel: Channel,
+    request: UpdateTogglesRequest,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut client = AdminServiceClient::new(channel);
+    let unchanged = request.maintenance.is_none()
+        && request.scheduling_frozen.is_none()
+        && request.default_priority() == TaskPriority::Unspecified;
+    let toggles = if unchanged {
+        client.get_toggles(GetTogglesRequest {}).await?
+    } else {
+        client.update_toggles(request).await?
+    }
+    .into_inner();
+
+    let on_off = |on: bool| if on { "on" } else { "off" };
+    println!("Maintenance mode:  {}", on_off(toggles.maintenance));
+    println!("Scheduling frozen: {}", on_off(toggles.scheduling_frozen));
+    println!(
+        "Default priority:  {}",
+        if toggles.default_priority() == TaskPriority::Interactive {
+            "interactive"
+        } else {
+            "batch"
+        }
+    );
+    Ok(())
+}
+
+/// How timestamps are printed, from `--tz`, `--locale` and `--relative-times`.
+static TIME_FORMAT: OnceLock<TimeFormat> = OnceLock::new();
+
 fn format_timestamp(ms: i64) -> String {
     use std::time::{Duration, UNIX_EPOCH};
     let d = Duration::from_millis(ms as u64);
     let dt = UNIX_EPOCH + d;
     let datetime: chrono::DateTime<chrono::Utc> = dt.into();
-    datetime.format("%Y-%m-%d %H:%M:%S").to_string()
+    TIME_FORMAT
+        .get()
+        .copied()
+        .unwrap_or_default()
+        .date_time(datetime)
 }
@@ -0,0 +1,121 @@
+//! Artifact references attached to chat messages and run events.
+
+use serde::{Deserialize, Serialize};
+
+/// A file produced or referenced during a run (screenshot, generated report, ...).
+///
+/// Artifacts are references only: the file stays where the agent wrote it and
+/// `uri` points at it (`file://` on the worker host, or an `http(s)` URL).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Artifact {
+    /// Display name (usually the file name).
+    pub name: String,
+    /// Location of the artifact.
+    pub uri: String,
+    /// Size in bytes, if known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size_bytes: Option<u64>,
+    /// MIME type, if known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mime_type: Option<String>,
+}
+
+impl Artifact {
+    /// Create an artifact reference.
+    pub fn new(name: impl Into<String>, uri: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            uri: uri.into(),
+            size_bytes: None,
+            mime_type: None,
+        }
+    }
+
+    /// Set the size in bytes.
+    pub fn with_size(mut self, size_bytes: u64) -> Self {
+        self.size_bytes = Some(size_bytes);
+        self
+    }
+
+    /// Set the MIME type.
+    pub fn with_mime_type(mut self, mime_type: impl Into<String>) -> Self {
+        self.mime_type = Some(mime_type.into());
+        self
+    }
+
+    /// Whether the artifact is an image that can be previewed inline.
+    pub fn is_image(&self) -> bool {
+        self.mime_type
+            .as_deref()
+            .is_some_and(|m| m.starts_with("image/"))
+    }
+
+    /// Transcript marker, e.g. `[attachment: shot.png (12.3 KB)]`.
+    pub fn marker(&self) -> String {
+        match self.size_bytes {
+            Some(size) => format!("[attachment: {} ({})]", self.name, format_size(size)),
+            None => format!("[attachment: {}]", self.name),
+        }
+    }
+}
+
+/// MIME type for a file name, for the file types treated as artifacts.
+pub fn mime_type_for(file_name: &str) -> Option<&'static str> {
+    let (_, ext) = file_name.rsplit_once('.')?;
+    let mime = match ext.to_ascii_lowercase().as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "svg" => "image/svg+xml",
+        "pdf" => "application/pdf",
+        "html" | "htm" => "text/html",
+        "csv" => "text/csv",
+        "md" => "text/markdown",
+        "txt" | "log" => "text/plain",
+        "json" => "application/json",
+        "zip" => "application/zip",
+        _ => return None,
+    };
+    Some(mime)
+}
+
+/// Format a byte count for display (`512 B`, `12.3 KB`, `4.0 MB`).
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_marker() {
+        let artifact = Artifact::new("shot.png", "file:///tmp/shot.png").with_size(12_595);
+        assert_eq!(artifact.marker(), "[attachment: shot.png (12.3 KB)]");
+        assert_eq!(
+            Artifact::new("report.pdf", "https://example.com/report.pdf").marker(),
+            "[attachment: report.pdf]"
+        );
+        assert_eq!(format_size(512), "512 B");
+        assert_eq!(format_size(4 * 1024 * 1024), "4.0 MB");
+    }
+
+    #[test]
+    fn test_mime_type_for() {
+        assert_eq!(mime_type_for("Screenshot.PNG"), Some("image/png"));
+        assert_eq!(mime_type_for("report.pdf"), Some("application/pdf"));
+        assert_eq!(mime_type_for("main.rs"), None);
+        assert_eq!(mime_type_for("Makefile"), None);
+    }
+}
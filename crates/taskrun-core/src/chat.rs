@@ -1,5 +1,7 @@
 //! Chat message types for conversation history.
 
+use crate::artifact::Artifact;
+
 /// Role of a message in the conversation.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ChatRole {
@@ -20,6 +22,8 @@ pub struct ChatMessage {
     pub content: String,
     /// Unix timestamp (milliseconds) when message was created.
     pub timestamp_ms: i64,
+    /// Files referenced by this message (screenshots, generated reports).
+    pub attachments: Vec<Artifact>,
 }
 
 impl ChatMessage {
@@ -29,9 +33,16 @@ impl ChatMessage {
             role,
             content: content.into(),
             timestamp_ms: chrono::Utc::now().timestamp_millis(),
+            attachments: Vec::new(),
         }
     }
 
+    /// Attach artifact references to the message.
+    pub fn with_attachments(mut self, attachments: Vec<Artifact>) -> Self {
+        self.attachments = attachments;
+        self
+    }
+
     /// Create a user message.
     pub fn user(content: impl Into<String>) -> Self {
         Self::new(ChatRole::User, content)
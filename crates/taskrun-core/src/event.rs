@@ -1,8 +1,10 @@
 //! Run execution events for tracking execution stages.
 
+use crate::artifact::Artifact;
 use crate::ids::{EventId, RunId, TaskId};
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 /// A run execution event for tracking execution stages.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -19,6 +21,9 @@ pub struct RunEvent {
     pub timestamp_ms: i64,
     /// Event-specific metadata (tool_name, model, error, etc.).
     pub metadata: HashMap<String, String>,
+    /// Files produced or referenced by this step.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub artifacts: Vec<Artifact>,
 }
 
 impl RunEvent {
@@ -39,9 +44,16 @@ impl RunEvent {
                 .unwrap_or_default()
                 .as_millis() as i64,
             metadata,
+            artifacts: Vec::new(),
         }
     }
 
+    /// Attach artifact references to the event.
+    pub fn with_artifacts(mut self, artifacts: Vec<Artifact>) -> Self {
+        self.artifacts = artifacts;
+        self
+    }
+
     /// Create an ExecutionStarted event.
     pub fn execution_started(run_id: RunId, task_id: TaskId) -> Self {
         Self::new(
@@ -69,10 +81,19 @@ impl RunEvent {
         Self::new(run_id, task_id, RunEventType::SessionInitialized, metadata)
     }
 
-    /// Create a ToolRequested event.
-    pub fn tool_requested(run_id: RunId, task_id: TaskId, tool_name: &str) -> Self {
+    /// Create a ToolRequested event, with a short summary of the tool input
+    /// (file path, command, pattern) if there is one.
+    pub fn tool_requested(
+        run_id: RunId,
+        task_id: TaskId,
+        tool_name: &str,
+        input_summary: Option<String>,
+    ) -> Self {
         let mut metadata = HashMap::new();
         metadata.insert("tool_name".to_string(), tool_name.to_string());
+        if let Some(summary) = input_summary {
+            metadata.insert("input_summary".to_string(), summary);
+        }
         Self::new(run_id, task_id, RunEventType::ToolRequested, metadata)
     }
 
@@ -83,6 +104,17 @@ impl RunEvent {
         Self::new(run_id, task_id, RunEventType::ToolCompleted, metadata)
     }
 
+    /// Tie a ToolCompleted event to the ToolRequested event of its call:
+    /// copies `tool_name` and sets `call_id` to the request's event ID.
+    pub fn for_call(mut self, requested: &RunEvent) -> Self {
+        if let Some(name) = requested.metadata.get("tool_name") {
+            self.metadata.insert("tool_name".to_string(), name.clone());
+        }
+        self.metadata
+            .insert("call_id".to_string(), requested.id.to_string());
+        self
+    }
+
     /// Create an OutputGenerated event.
     pub fn output_generated(run_id: RunId, task_id: TaskId, summary: Option<String>) -> Self {
         let mut metadata = HashMap::new();
@@ -92,6 +124,53 @@ impl RunEvent {
         Self::new(run_id, task_id, RunEventType::OutputGenerated, metadata)
     }
 
+    /// Create an OutputGenerated event recording spans masked by output filters.
+    ///
+    /// Metadata holds `masked_spans` (the total) and `masked.<filter>` per filter.
+    pub fn output_filtered(
+        run_id: RunId,
+        task_id: TaskId,
+        masked: &BTreeMap<String, usize>,
+    ) -> Self {
+        let mut metadata: HashMap<String, String> = masked
+            .iter()
+            .map(|(filter, n)| (format!("masked.{}", filter), n.to_string()))
+            .collect();
+        metadata.insert(
+            "masked_spans".to_string(),
+            masked.values().sum::<usize>().to_string(),
+        );
+        Self::new(run_id, task_id, RunEventType::OutputGenerated, metadata)
+    }
+
+    /// Create an OutputGenerated event recording the git branch a run's
+    /// changes were committed to.
+    ///
+    /// Metadata holds `branch`, `commit` and `diff_stat` (against the commit
+    /// the branch started from).
+    pub fn branch_updated(
+        run_id: RunId,
+        task_id: TaskId,
+        branch: &str,
+        commit: &str,
+        diff_stat: &str,
+    ) -> Self {
+        let mut metadata = HashMap::new();
+        metadata.insert("branch".to_string(), branch.to_string());
+        metadata.insert("commit".to_string(), commit.to_string());
+        metadata.insert("diff_stat".to_string(), diff_stat.to_string());
+        Self::new(run_id, task_id, RunEventType::OutputGenerated, metadata)
+    }
+
+    /// Create an OutputGenerated event carrying artifacts not tied to a
+    /// single tool result. Metadata holds the `artifacts` count.
+    pub fn artifacts_attached(run_id: RunId, task_id: TaskId, artifacts: Vec<Artifact>) -> Self {
+        let mut metadata = HashMap::new();
+        metadata.insert("artifacts".to_string(), artifacts.len().to_string());
+        Self::new(run_id, task_id, RunEventType::OutputGenerated, metadata)
+            .with_artifacts(artifacts)
+    }
+
     /// Create an ExecutionCompleted event.
     pub fn execution_completed(run_id: RunId, task_id: TaskId, duration_ms: Option<i64>) -> Self {
         let mut metadata = HashMap::new();
@@ -109,6 +188,76 @@ impl RunEvent {
         }
         Self::new(run_id, task_id, RunEventType::ExecutionFailed, metadata)
     }
+
+    /// Create a Milestone event. Metadata holds `milestone`, and `percent`
+    /// and `note` when set.
+    pub fn milestone(run_id: RunId, task_id: TaskId, milestone: &RunMilestone) -> Self {
+        let mut metadata = HashMap::new();
+        metadata.insert("milestone".to_string(), milestone.name.clone());
+        if let Some(percent) = milestone.percent {
+            metadata.insert("percent".to_string(), percent.to_string());
+        }
+        if let Some(note) = &milestone.note {
+            metadata.insert("note".to_string(), note.clone());
+        }
+        Self::new(run_id, task_id, RunEventType::Milestone, metadata)
+    }
+
+    /// Create a RunSummary event. Metadata holds `tools_used`,
+    /// `files_changed` (the count) and, when known, `answer`, `files`
+    /// (newline-separated), `duration_ms`, `input_tokens` and `output_tokens`.
+    pub fn run_summary(run_id: RunId, task_id: TaskId, summary: &RunResultSummary) -> Self {
+        let mut metadata = HashMap::new();
+        metadata.insert("tools_used".to_string(), summary.tools_used.to_string());
+        metadata.insert(
+            "files_changed".to_string(),
+            summary.files_changed.to_string(),
+        );
+        if let Some(answer) = &summary.answer {
+            metadata.insert("answer".to_string(), answer.clone());
+        }
+        if !summary.files.is_empty() {
+            metadata.insert("files".to_string(), summary.files.join("\n"));
+        }
+        let counts = [
+            ("duration_ms", summary.duration_ms),
+            ("input_tokens", summary.input_tokens),
+            ("output_tokens", summary.output_tokens),
+        ];
+        for (key, value) in counts {
+            if let Some(value) = value {
+                metadata.insert(key.to_string(), value.to_string());
+            }
+        }
+        Self::new(run_id, task_id, RunEventType::RunSummary, metadata)
+    }
+
+    /// Create a Log event. Metadata holds `level`, `target` and `message`.
+    pub fn log(run_id: RunId, task_id: TaskId, level: &str, target: &str, message: String) -> Self {
+        let mut metadata = HashMap::new();
+        metadata.insert("level".to_string(), level.to_string());
+        metadata.insert("target".to_string(), target.to_string());
+        metadata.insert("message".to_string(), message);
+        Self::new(run_id, task_id, RunEventType::Log, metadata)
+    }
+
+    /// Record what the execution cost (USD), if the model reported it.
+    pub fn with_cost_usd(mut self, cost_usd: Option<f64>) -> Self {
+        if let Some(cost) = cost_usd {
+            self.metadata
+                .insert("cost_usd".to_string(), cost.to_string());
+        }
+        self
+    }
+
+    /// Record the tokens the execution consumed, if the model reported them.
+    pub fn with_token_usage(mut self, input_tokens: u64, output_tokens: u64) -> Self {
+        self.metadata
+            .insert("input_tokens".to_string(), input_tokens.to_string());
+        self.metadata
+            .insert("output_tokens".to_string(), output_tokens.to_string());
+        self
+    }
 }
 
 /// Type of run execution event.
@@ -129,6 +278,188 @@ pub enum RunEventType {
     ExecutionCompleted,
     /// Execution failed with error.
     ExecutionFailed,
+    /// The agent reported reaching a milestone.
+    Milestone,
+    /// A worker log record of the run.
+    Log,
+    /// Compact result of a finished run, for previews.
+    RunSummary,
+}
+
+/// A step an agent reported reaching during a run.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RunMilestone {
+    /// Short name of the step.
+    pub name: String,
+    /// Overall progress of the run (0-100), if the agent estimated it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub percent: Option<u8>,
+    /// Free-form detail.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    /// When the milestone was reported.
+    pub reached_at: DateTime<Utc>,
+}
+
+impl RunMilestone {
+    /// Create a milestone reached now.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            percent: None,
+            note: None,
+            reached_at: Utc::now(),
+        }
+    }
+
+    /// Builder method to set the progress estimate, capped at 100.
+    pub fn with_percent(mut self, percent: Option<u8>) -> Self {
+        self.percent = percent.map(|p| p.min(100));
+        self
+    }
+
+    /// Builder method to set the note.
+    pub fn with_note(mut self, note: Option<String>) -> Self {
+        self.note = note.filter(|n| !n.is_empty());
+        self
+    }
+
+    /// Add to `milestones`, replacing an earlier report of the same name.
+    pub fn record(self, milestones: &mut Vec<RunMilestone>) {
+        match milestones.iter_mut().find(|m| m.name == self.name) {
+            Some(existing) => *existing = self,
+            None => milestones.push(self),
+        }
+    }
+
+    /// The milestone a Milestone event carries.
+    pub fn from_event(event: &RunEvent) -> Option<Self> {
+        if event.event_type != RunEventType::Milestone {
+            return None;
+        }
+        let name = event.metadata.get("milestone")?;
+        let mut milestone = Self::new(name.clone())
+            .with_percent(event.metadata.get("percent").and_then(|p| p.parse().ok()))
+            .with_note(event.metadata.get("note").cloned());
+        if let Some(at) = DateTime::from_timestamp_millis(event.timestamp_ms) {
+            milestone.reached_at = at;
+        }
+        Some(milestone)
+    }
+}
+
+/// Longest answer excerpt of a [`RunResultSummary`], in characters.
+pub const MAX_ANSWER_EXCERPT_CHARS: usize = 280;
+
+/// Most changed files a [`RunResultSummary`] lists.
+pub const MAX_SUMMARY_FILES: usize = 20;
+
+/// What a finished run produced, compact enough to preview in run lists.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RunResultSummary {
+    /// Start of the agent's final answer.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub answer: Option<String>,
+    /// Number of files the agent wrote or edited.
+    pub files_changed: u32,
+    /// Paths of the first changed files, relative to the workspace.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub files: Vec<String>,
+    /// Number of tool calls.
+    pub tools_used: u32,
+    /// Wall-clock duration of the run.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u64>,
+    /// Prompt tokens, cached or not.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input_tokens: Option<u64>,
+    /// Generated tokens.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_tokens: Option<u64>,
+}
+
+impl RunResultSummary {
+    /// Builder method to set the answer, cut to an excerpt on one line.
+    pub fn with_answer(mut self, answer: &str) -> Self {
+        let line = answer.split_whitespace().collect::<Vec<_>>().join(" ");
+        self.answer = (!line.is_empty()).then(|| {
+            if line.chars().count() > MAX_ANSWER_EXCERPT_CHARS {
+                let mut excerpt: String = line.chars().take(MAX_ANSWER_EXCERPT_CHARS).collect();
+                excerpt.push('…');
+                excerpt
+            } else {
+                line
+            }
+        });
+        self
+    }
+
+    /// Builder method to set the changed files, listing at most
+    /// [`MAX_SUMMARY_FILES`].
+    pub fn with_files(mut self, mut files: Vec<String>) -> Self {
+        self.files_changed = files.len() as u32;
+        files.truncate(MAX_SUMMARY_FILES);
+        self.files = files;
+        self
+    }
+
+    /// One-line preview: counts, then the answer excerpt.
+    ///
+    /// `3 files, 12 tools, 1m 5s, 15.2k tokens: Fixed the flaky test by…`
+    pub fn preview(&self) -> String {
+        let plural = |n: u32, what: &str| match n {
+            1 => format!("1 {}", what),
+            n => format!("{} {}s", n, what),
+        };
+        let mut parts = vec![
+            plural(self.files_changed, "file"),
+            plural(self.tools_used, "tool"),
+        ];
+        if let Some(ms) = self.duration_ms {
+            let secs = ms / 1000;
+            parts.push(match secs {
+                0..=59 => format!("{}s", secs),
+                _ => format!("{}m {}s", secs / 60, secs % 60),
+            });
+        }
+        let tokens = self.input_tokens.unwrap_or(0) + self.output_tokens.unwrap_or(0);
+        if tokens > 0 {
+            parts.push(match tokens {
+                0..=999 => format!("{} tokens", tokens),
+                _ => format!("{:.1}k tokens", tokens as f64 / 1000.0),
+            });
+        }
+        let counts = parts.join(", ");
+        match &self.answer {
+            Some(answer) => format!("{}: {}", counts, answer),
+            None => counts,
+        }
+    }
+
+    /// The summary a RunSummary event carries.
+    pub fn from_event(event: &RunEvent) -> Option<Self> {
+        if event.event_type != RunEventType::RunSummary {
+            return None;
+        }
+        let metadata = &event.metadata;
+        Some(Self {
+            answer: metadata.get("answer").cloned(),
+            files_changed: number(metadata, "files_changed").unwrap_or(0),
+            files: metadata
+                .get("files")
+                .map(|f| f.lines().map(str::to_string).collect())
+                .unwrap_or_default(),
+            tools_used: number(metadata, "tools_used").unwrap_or(0),
+            duration_ms: number(metadata, "duration_ms"),
+            input_tokens: number(metadata, "input_tokens"),
+            output_tokens: number(metadata, "output_tokens"),
+        })
+    }
+}
+
+/// The number under `key` in event metadata.
+fn number<T: std::str::FromStr>(metadata: &HashMap<String, String>, key: &str) -> Option<T> {
+    metadata.get(key).and_then(|v| v.parse().ok())
 }
 
 #[cfg(test)]
@@ -151,10 +482,84 @@ mod tests {
     fn test_tool_requested() {
         let run_id = RunId::generate();
         let task_id = TaskId::generate();
-        let event = RunEvent::tool_requested(run_id, task_id, "Read");
+        let event = RunEvent::tool_requested(
+            run_id.clone(),
+            task_id.clone(),
+            "Read",
+            Some("src/main.rs".to_string()),
+        );
 
         assert_eq!(event.event_type, RunEventType::ToolRequested);
         assert_eq!(event.metadata.get("tool_name"), Some(&"Read".to_string()));
+        assert_eq!(
+            event.metadata.get("input_summary"),
+            Some(&"src/main.rs".to_string())
+        );
+
+        let completed = RunEvent::tool_completed(run_id, task_id, false).for_call(&event);
+        assert_eq!(
+            completed.metadata.get("tool_name"),
+            Some(&"Read".to_string())
+        );
+        assert_eq!(
+            completed.metadata.get("call_id"),
+            Some(&event.id.to_string())
+        );
+    }
+
+    #[test]
+    fn test_milestone_round_trips_through_event() {
+        let milestone = RunMilestone::new("tests passing")
+            .with_percent(Some(150))
+            .with_note(Some("42 of 42".to_string()));
+        let event = RunEvent::milestone(RunId::generate(), TaskId::generate(), &milestone);
+
+        let parsed = RunMilestone::from_event(&event).unwrap();
+        assert_eq!(parsed.name, "tests passing");
+        assert_eq!(parsed.percent, Some(100));
+        assert_eq!(parsed.note.as_deref(), Some("42 of 42"));
+        assert_eq!(parsed.reached_at.timestamp_millis(), event.timestamp_ms);
+
+        let other = RunEvent::execution_started(RunId::generate(), TaskId::generate());
+        assert_eq!(RunMilestone::from_event(&other), None);
+
+        // Reporting a milestone again updates it in place
+        let mut milestones = vec![parsed, RunMilestone::new("docs")];
+        RunMilestone::new("tests passing")
+            .with_percent(Some(90))
+            .record(&mut milestones);
+        assert_eq!(milestones.len(), 2);
+        assert_eq!(milestones[0].percent, Some(90));
+    }
+
+    #[test]
+    fn test_run_summary_round_trips_through_event() {
+        let files = (0..25).map(|i| format!("src/f{}.rs", i)).collect();
+        let summary = RunResultSummary {
+            tools_used: 12,
+            duration_ms: Some(65_000),
+            input_tokens: Some(15_000),
+            output_tokens: Some(200),
+            ..Default::default()
+        }
+        .with_answer("Fixed the\n\nflaky   test.")
+        .with_files(files);
+        assert_eq!(summary.files_changed, 25);
+        assert_eq!(summary.files.len(), MAX_SUMMARY_FILES);
+        assert_eq!(
+            summary.preview(),
+            "25 files, 12 tools, 1m 5s, 15.2k tokens: Fixed the flaky test."
+        );
+
+        let event = RunEvent::run_summary(RunId::generate(), TaskId::generate(), &summary);
+        assert_eq!(RunResultSummary::from_event(&event), Some(summary));
+
+        let long = RunResultSummary::default().with_answer(&"x".repeat(1000));
+        assert_eq!(
+            long.answer.unwrap().chars().count(),
+            MAX_ANSWER_EXCERPT_CHARS + 1
+        );
+        assert_eq!(RunResultSummary::default().preview(), "0 files, 0 tools");
     }
 
     #[test]
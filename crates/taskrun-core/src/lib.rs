@@ -7,21 +7,33 @@
 //!
 //! All types here represent the core business domain of TaskRun.
 
+pub mod artifact;
 pub mod chat;
 pub mod error;
 pub mod event;
 pub mod ids;
+pub mod manifest;
 pub mod model;
+pub mod namespace;
+pub mod schedule;
+pub mod spiffe;
 pub mod status;
 pub mod task;
 pub mod worker;
+pub mod workflow;
 
 // Re-export commonly used types
+pub use artifact::Artifact;
 pub use chat::{ChatMessage, ChatRole};
 pub use error::CoreError;
-pub use event::{RunEvent, RunEventType};
-pub use ids::{EventId, RunId, TaskId, WorkerId};
+pub use event::{RunEvent, RunEventType, RunMilestone, RunResultSummary};
+pub use ids::{EventId, RunId, ScheduleId, TaskId, WorkerId, WorkflowId};
+pub use manifest::RunManifest;
 pub use model::{AgentSpec, ModelBackend};
-pub use status::{RunStatus, TaskStatus, WorkerStatus};
-pub use task::{RunSummary, Task};
+pub use namespace::{validate_namespace, DEFAULT_NAMESPACE};
+pub use schedule::Schedule;
+pub use spiffe::SpiffeId;
+pub use status::{FailureReason, RunStatus, TaskStatus, WorkerStatus};
+pub use task::{RunLimits, RunSummary, Task, TaskPriority};
 pub use worker::WorkerInfo;
+pub use workflow::{Workflow, WorkflowStatus, WorkflowStep, WorkflowStepTask, WorkflowTemplate};
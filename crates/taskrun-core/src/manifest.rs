@@ -0,0 +1,106 @@
+//! Run reproducibility manifest.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Everything that determined how a run executed, captured by the worker
+/// when the run starts.
+///
+/// Two runs with equal manifests got the same input, through the same agent
+/// template, model, tool policy and binaries. A task pinned to a manifest
+/// (see `Task::pinned_manifest`) is run with its model and tool policy.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct RunManifest {
+    /// Agent that executed the run.
+    pub agent_name: String,
+
+    /// SHA-256 of the agent specification the worker advertised (hex).
+    pub agent_template_hash: String,
+
+    /// Model provider (e.g., "anthropic").
+    pub model_provider: String,
+
+    /// Model requested for the run (e.g., "claude-sonnet-4-5").
+    pub model_name: String,
+
+    /// Tools the agent was restricted to (None = all tools).
+    #[serde(default)]
+    pub allowed_tools: Option<Vec<String>>,
+
+    /// Tools the agent was denied (None = none).
+    #[serde(default)]
+    pub denied_tools: Option<Vec<String>>,
+
+    /// `claude --version` on the worker, if it could be determined.
+    #[serde(default)]
+    pub claude_cli_version: Option<String>,
+
+    /// Worker binary version.
+    pub worker_version: String,
+
+    /// Worker features that affect execution (e.g., permission mode, output filters).
+    #[serde(default)]
+    pub feature_flags: BTreeMap<String, String>,
+
+    /// SHA-256 of the run's input JSON (hex).
+    pub input_hash: String,
+}
+
+impl RunManifest {
+    /// Names of the fields in which `other` differs from this manifest.
+    pub fn diff(&self, other: &RunManifest) -> Vec<&'static str> {
+        let mut fields = Vec::new();
+        let mut check = |name, differs: bool| {
+            if differs {
+                fields.push(name);
+            }
+        };
+        check("agent_name", self.agent_name != other.agent_name);
+        check(
+            "agent_template_hash",
+            self.agent_template_hash != other.agent_template_hash,
+        );
+        check(
+            "model_provider",
+            self.model_provider != other.model_provider,
+        );
+        check("model_name", self.model_name != other.model_name);
+        check("allowed_tools", self.allowed_tools != other.allowed_tools);
+        check("denied_tools", self.denied_tools != other.denied_tools);
+        check(
+            "claude_cli_version",
+            self.claude_cli_version != other.claude_cli_version,
+        );
+        check(
+            "worker_version",
+            self.worker_version != other.worker_version,
+        );
+        check("feature_flags", self.feature_flags != other.feature_flags);
+        check("input_hash", self.input_hash != other.input_hash);
+        fields
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_manifest_diff() {
+        let manifest = RunManifest {
+            agent_name: "general".to_string(),
+            model_name: "claude-sonnet-4-5".to_string(),
+            worker_version: "0.1.0".to_string(),
+            ..Default::default()
+        };
+        assert!(manifest.diff(&manifest.clone()).is_empty());
+
+        let mut other = manifest.clone();
+        other.worker_version = "0.2.0".to_string();
+        other.claude_cli_version = Some("2.0.1".to_string());
+        assert_eq!(
+            manifest.diff(&other),
+            vec!["claude_cli_version", "worker_version"]
+        );
+    }
+}
@@ -72,6 +72,10 @@ pub struct AgentSpec {
 
     /// Model backends this agent can use.
     pub backends: Vec<ModelBackend>,
+
+    /// Why the agent failed the worker's smoke test (None = ready).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub degraded: Option<String>,
 }
 
 impl AgentSpec {
@@ -82,6 +86,7 @@ impl AgentSpec {
             description: String::new(),
             labels: HashMap::new(),
             backends: Vec::new(),
+            degraded: None,
         }
     }
 
@@ -96,4 +101,15 @@ impl AgentSpec {
         self.backends.push(backend);
         self
     }
+
+    /// Builder method to mark the agent degraded, or ready with None.
+    pub fn with_degraded(mut self, reason: Option<String>) -> Self {
+        self.degraded = reason;
+        self
+    }
+
+    /// Whether the agent can be given runs.
+    pub fn is_ready(&self) -> bool {
+        self.degraded.is_none()
+    }
 }
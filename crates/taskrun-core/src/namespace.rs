@@ -0,0 +1,55 @@
+//! Namespaces for multi-tenancy.
+//!
+//! Every task, worker and API key belongs to a namespace. Tasks only run on
+//! workers of their own namespace, and callers bound to a namespace only see
+//! and create tasks and workers in it. Everything created before namespaces
+//! existed lives in `DEFAULT_NAMESPACE`.
+
+use crate::CoreError;
+
+/// Namespace of tasks and workers that do not name one.
+pub const DEFAULT_NAMESPACE: &str = "default";
+
+/// Longest allowed namespace name.
+pub const MAX_NAMESPACE_LEN: usize = 63;
+
+/// `DEFAULT_NAMESPACE` as an owned string (for `#[serde(default)]`).
+pub fn default_namespace() -> String {
+    DEFAULT_NAMESPACE.to_string()
+}
+
+/// Check that `namespace` is 1-63 lowercase letters, digits and dashes, not
+/// starting or ending with a dash.
+pub fn validate_namespace(namespace: &str) -> Result<(), CoreError> {
+    let valid = !namespace.is_empty()
+        && namespace.len() <= MAX_NAMESPACE_LEN
+        && namespace
+            .bytes()
+            .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'-')
+        && !namespace.starts_with('-')
+        && !namespace.ends_with('-');
+    if valid {
+        Ok(())
+    } else {
+        Err(CoreError::InvalidInput(format!(
+            "invalid namespace '{}' (expected 1-{} lowercase letters, digits and dashes)",
+            namespace, MAX_NAMESPACE_LEN
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_namespace() {
+        assert!(validate_namespace(DEFAULT_NAMESPACE).is_ok());
+        assert!(validate_namespace("team-a2").is_ok());
+        assert!(validate_namespace("").is_err());
+        assert!(validate_namespace("Team").is_err());
+        assert!(validate_namespace("-a").is_err());
+        assert!(validate_namespace("a_b").is_err());
+        assert!(validate_namespace(&"a".repeat(64)).is_err());
+    }
+}
@@ -0,0 +1,127 @@
+//! Recurring task schedules.
+
+use crate::{ScheduleId, TaskId};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// A Schedule creates a new Task for an agent every time its cron expression fires.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Schedule {
+    /// Unique schedule identifier.
+    pub id: ScheduleId,
+
+    /// Cron expression (5 fields, or 6 with leading seconds).
+    pub cron_expr: String,
+
+    /// Name of the agent to execute.
+    pub agent_name: String,
+
+    /// Input template; placeholders are filled in at fire time (see `render_input`).
+    pub input_template: String,
+
+    /// Who created this schedule.
+    pub created_by: String,
+
+    /// When the schedule was created.
+    pub created_at: DateTime<Utc>,
+
+    /// Labels copied onto every task this schedule creates.
+    #[serde(default)]
+    pub labels: HashMap<String, String>,
+
+    /// Run timeout applied to every task this schedule creates.
+    #[serde(default)]
+    pub timeout_seconds: Option<u32>,
+
+    /// Paused schedules do not fire.
+    #[serde(default)]
+    pub paused: bool,
+
+    /// When the schedule last fired.
+    #[serde(default)]
+    pub last_fired_at: Option<DateTime<Utc>>,
+
+    /// Task created by the most recent firing.
+    #[serde(default)]
+    pub last_task_id: Option<TaskId>,
+
+    /// When the schedule will fire next (None while paused).
+    #[serde(default)]
+    pub next_fire_at: Option<DateTime<Utc>>,
+}
+
+impl Schedule {
+    /// Create a new Schedule.
+    pub fn new(
+        cron_expr: impl Into<String>,
+        agent_name: impl Into<String>,
+        input_template: impl Into<String>,
+        created_by: impl Into<String>,
+    ) -> Self {
+        Self {
+            id: ScheduleId::generate(),
+            cron_expr: cron_expr.into(),
+            agent_name: agent_name.into(),
+            input_template: input_template.into(),
+            created_by: created_by.into(),
+            created_at: Utc::now(),
+            labels: HashMap::new(),
+            timeout_seconds: None,
+            paused: false,
+            last_fired_at: None,
+            last_task_id: None,
+            next_fire_at: None,
+        }
+    }
+
+    /// Builder method to add a label.
+    pub fn with_label(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.labels.insert(key.into(), value.into());
+        self
+    }
+
+    /// Builder method to set a run timeout in seconds.
+    pub fn with_timeout_seconds(mut self, seconds: u32) -> Self {
+        self.timeout_seconds = Some(seconds);
+        self
+    }
+
+    /// Render the input template for a firing at `fire_time`.
+    ///
+    /// Supported placeholders:
+    /// - `{{schedule_id}}`: this schedule's ID
+    /// - `{{fire_time}}`: fire time as RFC 3339
+    /// - `{{fire_time_ms}}`: fire time in milliseconds since epoch
+    pub fn render_input(&self, fire_time: DateTime<Utc>) -> String {
+        self.input_template
+            .replace("{{schedule_id}}", self.id.as_str())
+            .replace("{{fire_time}}", &fire_time.to_rfc3339())
+            .replace(
+                "{{fire_time_ms}}",
+                &fire_time.timestamp_millis().to_string(),
+            )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_input_fills_placeholders() {
+        let mut schedule = Schedule::new(
+            "0 * * * *",
+            "general",
+            r#"{"at":"{{fire_time}}","ms":{{fire_time_ms}},"s":"{{schedule_id}}"}"#,
+            "test",
+        );
+        schedule.id = ScheduleId::new("sched-1");
+        let fire_time = DateTime::from_timestamp_millis(1_700_000_000_000).unwrap();
+
+        assert_eq!(
+            schedule.render_input(fire_time),
+            r#"{"at":"2023-11-14T22:13:20+00:00","ms":1700000000000,"s":"sched-1"}"#
+        );
+    }
+}
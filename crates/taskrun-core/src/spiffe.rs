@@ -0,0 +1,117 @@
+//! SPIFFE IDs of workers.
+//!
+//! Worker certificates carry a SPIFFE ID as their URI subject alternative
+//! name, so standard workload identity tooling can tell TaskRun workers
+//! apart: `spiffe://taskrun/worker/<worker_id>`, or
+//! `spiffe://taskrun/worker/<worker_id>/agent/<agent_name>` for a worker
+//! enrolled to serve one agent.
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::CoreError;
+
+/// Trust domain of TaskRun SPIFFE IDs.
+pub const TRUST_DOMAIN: &str = "taskrun";
+
+/// The identity in a worker certificate's SPIFFE URI.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpiffeId {
+    pub worker_id: String,
+    pub agent: Option<String>,
+}
+
+impl SpiffeId {
+    /// The SPIFFE ID of `worker_id`, for any agent.
+    pub fn worker(worker_id: impl Into<String>) -> Self {
+        Self {
+            worker_id: worker_id.into(),
+            agent: None,
+        }
+    }
+
+    /// Narrow the ID to one agent.
+    pub fn with_agent(mut self, agent: impl Into<String>) -> Self {
+        self.agent = Some(agent.into());
+        self
+    }
+}
+
+impl fmt::Display for SpiffeId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "spiffe://{}/worker/{}", TRUST_DOMAIN, self.worker_id)?;
+        if let Some(agent) = &self.agent {
+            write!(f, "/agent/{}", agent)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for SpiffeId {
+    type Err = CoreError;
+
+    fn from_str(uri: &str) -> Result<Self, Self::Err> {
+        let invalid = |why: &str| {
+            CoreError::InvalidInput(format!("invalid TaskRun SPIFFE ID '{}': {}", uri, why))
+        };
+        let path = uri
+            .strip_prefix("spiffe://")
+            .ok_or_else(|| invalid("not a spiffe:// URI"))?
+            .strip_prefix(TRUST_DOMAIN)
+            .and_then(|rest| rest.strip_prefix('/'))
+            .ok_or_else(|| invalid("wrong trust domain"))?;
+        let segments: Vec<&str> = path.split('/').collect();
+        if !segments.iter().copied().all(valid_segment) {
+            return Err(invalid(
+                "path segments must be letters, digits, '-', '_' or '.'",
+            ));
+        }
+        match segments.as_slice() {
+            ["worker", worker_id] => Ok(Self::worker(*worker_id)),
+            ["worker", worker_id, "agent", agent] => {
+                Ok(Self::worker(*worker_id).with_agent(*agent))
+            }
+            _ => Err(invalid(
+                "expected /worker/<id> or /worker/<id>/agent/<name>",
+            )),
+        }
+    }
+}
+
+/// A non-empty path segment of SPIFFE's allowed characters, not `.` or `..`.
+fn valid_segment(segment: &str) -> bool {
+    !segment.is_empty()
+        && segment != "."
+        && segment != ".."
+        && segment
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'.'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spiffe_id_round_trip() {
+        let id = SpiffeId::worker("w-1").with_agent("support_triage");
+        let uri = id.to_string();
+        assert_eq!(uri, "spiffe://taskrun/worker/w-1/agent/support_triage");
+        assert_eq!(uri.parse::<SpiffeId>().unwrap(), id);
+        assert_eq!(
+            "spiffe://taskrun/worker/w-1".parse::<SpiffeId>().unwrap(),
+            SpiffeId::worker("w-1")
+        );
+
+        for bad in [
+            "https://taskrun/worker/w-1",
+            "spiffe://other/worker/w-1",
+            "spiffe://taskrun/worker/",
+            "spiffe://taskrun/worker/w-1/agent",
+            "spiffe://taskrun/worker/../agent/x",
+            "spiffe://taskrun/worker/w:1",
+        ] {
+            assert!(bad.parse::<SpiffeId>().is_err(), "{}", bad);
+        }
+    }
+}
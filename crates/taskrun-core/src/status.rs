@@ -50,6 +50,65 @@ impl RunStatus {
     }
 }
 
+/// Why a run or task ended without completing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FailureReason {
+    /// The worker stopped heartbeating or disconnected mid-run.
+    WorkerLost,
+    /// The run exceeded its time limit.
+    Timeout,
+    /// The agent hit its turn or spend limit.
+    BudgetExceeded,
+    /// The agent failed after being denied a tool.
+    ToolDenied,
+    /// The agent process could not start or exited abnormally.
+    ProcessCrash,
+    /// The agent reported an error.
+    SdkError,
+    /// A user or operator cancelled the task.
+    CancelledByUser,
+    /// The task waited in Pending past its TTL.
+    Expired,
+    /// The agent process was stopped by a memory or process limit.
+    ResourceLimit,
+}
+
+impl FailureReason {
+    /// Every reason, in declaration order.
+    pub const ALL: [FailureReason; 9] = [
+        Self::WorkerLost,
+        Self::Timeout,
+        Self::BudgetExceeded,
+        Self::ToolDenied,
+        Self::ProcessCrash,
+        Self::SdkError,
+        Self::CancelledByUser,
+        Self::Expired,
+        Self::ResourceLimit,
+    ];
+
+    /// Snake-case name, as used in APIs and metrics.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::WorkerLost => "worker_lost",
+            Self::Timeout => "timeout",
+            Self::BudgetExceeded => "budget_exceeded",
+            Self::ToolDenied => "tool_denied",
+            Self::ProcessCrash => "process_crash",
+            Self::SdkError => "sdk_error",
+            Self::CancelledByUser => "cancelled_by_user",
+            Self::Expired => "expired",
+            Self::ResourceLimit => "resource_limit",
+        }
+    }
+
+    /// Parse a snake-case name.
+    pub fn parse(s: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|r| r.as_str() == s)
+    }
+}
+
 /// Status of a Worker connection.
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
@@ -63,6 +122,10 @@ pub enum WorkerStatus {
     Draining,
     /// Worker is in an error state.
     Error,
+    /// Worker has missed heartbeats and may be unhealthy.
+    Degraded,
+    /// Worker has stopped heartbeating and is presumed dead.
+    Offline,
 }
 
 impl WorkerStatus {
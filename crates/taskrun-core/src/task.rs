@@ -1,6 +1,10 @@
 //! Task and Run types.
 
-use crate::{ModelBackend, RunId, RunStatus, TaskId, TaskStatus, WorkerId};
+use crate::namespace::default_namespace;
+use crate::{
+    FailureReason, ModelBackend, RunId, RunManifest, RunMilestone, RunResultSummary, RunStatus,
+    TaskId, TaskStatus, WorkerId,
+};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -14,6 +18,10 @@ pub struct Task {
     /// Name of the agent to execute.
     pub agent_name: String,
 
+    /// Namespace the task belongs to; only workers of the same namespace run it.
+    #[serde(default = "default_namespace")]
+    pub namespace: String,
+
     /// Input payload as JSON string.
     pub input_json: String,
 
@@ -31,6 +39,102 @@ pub struct Task {
 
     /// Runs associated with this task.
     pub runs: Vec<RunSummary>,
+
+    /// Maximum run duration in seconds before the run is failed.
+    #[serde(default)]
+    pub timeout_seconds: Option<u32>,
+
+    /// Tasks that must complete before this one is dispatched.
+    #[serde(default)]
+    pub depends_on: Vec<TaskId>,
+
+    /// Scheduling lane for this task's runs.
+    #[serde(default)]
+    pub priority: TaskPriority,
+
+    /// Environment to reproduce: runs use this manifest's model and tool policy.
+    #[serde(default)]
+    pub pinned_manifest: Option<RunManifest>,
+
+    /// Seconds the task may wait in Pending before it expires (None = server default).
+    #[serde(default)]
+    pub pending_ttl_seconds: Option<u32>,
+
+    /// Why the task last failed or was cancelled.
+    #[serde(default)]
+    pub failure_reason: Option<FailureReason>,
+
+    /// Details of the failure, when no run explains it (e.g., it expired in Pending).
+    #[serde(default)]
+    pub failure_message: Option<String>,
+
+    /// Address of the client that created the task over HTTP, resolved
+    /// through trusted proxies (None = not created over HTTP).
+    #[serde(default)]
+    pub client_ip: Option<String>,
+
+    /// W3C trace context of the span that created the task. Runs of the task
+    /// are traced as part of this trace (empty = not traced).
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub trace_context: HashMap<String, String>,
+
+    /// IDs of uploaded files staged in the workspace of the task's runs.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub input_files: Vec<String>,
+
+    /// Limits the worker enforces on each run, on top of the agent's.
+    #[serde(default, skip_serializing_if = "RunLimits::is_empty")]
+    pub limits: RunLimits,
+}
+
+/// Limits on each run of a task, enforced by the worker.
+///
+/// They only tighten the limits a worker configures for the agent: the lower
+/// of the two applies. A run stopped by one fails with reason
+/// `resource_limit`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RunLimits {
+    /// Memory cap of the Claude process in MiB.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_memory_mb: Option<u64>,
+
+    /// CPU time cap of each process of the run in seconds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_cpu_seconds: Option<u64>,
+
+    /// Wall-clock time after which the run is killed, in seconds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_wall_seconds: Option<u64>,
+}
+
+impl RunLimits {
+    /// Whether no limit is set.
+    pub fn is_empty(&self) -> bool {
+        self.max_memory_mb.is_none()
+            && self.max_cpu_seconds.is_none()
+            && self.max_wall_seconds.is_none()
+    }
+}
+
+/// Scheduling lane for a task.
+///
+/// Interactive work (streaming chat, session continuation) is assigned and
+/// executed ahead of batch work so chat latency stays low under load.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum TaskPriority {
+    /// Queued background work.
+    #[default]
+    Batch,
+    /// A user is waiting on the output.
+    Interactive,
+}
+
+impl TaskPriority {
+    /// Returns true for the interactive lane.
+    pub fn is_interactive(&self) -> bool {
+        matches!(self, Self::Interactive)
+    }
 }
 
 impl Task {
@@ -43,12 +147,24 @@ impl Task {
         Self {
             id: TaskId::generate(),
             agent_name: agent_name.into(),
+            namespace: default_namespace(),
             input_json: input_json.into(),
             status: TaskStatus::Pending,
             created_by: created_by.into(),
             created_at: Utc::now(),
             labels: HashMap::new(),
             runs: Vec::new(),
+            timeout_seconds: None,
+            depends_on: Vec::new(),
+            priority: TaskPriority::Batch,
+            pinned_manifest: None,
+            pending_ttl_seconds: None,
+            failure_reason: None,
+            failure_message: None,
+            client_ip: None,
+            trace_context: HashMap::new(),
+            input_files: Vec::new(),
+            limits: RunLimits::default(),
         }
     }
 
@@ -58,6 +174,66 @@ impl Task {
         self
     }
 
+    /// Builder method to set the namespace.
+    pub fn with_namespace(mut self, namespace: impl Into<String>) -> Self {
+        self.namespace = namespace.into();
+        self
+    }
+
+    /// Builder method to set a run timeout in seconds.
+    pub fn with_timeout_seconds(mut self, seconds: u32) -> Self {
+        self.timeout_seconds = Some(seconds);
+        self
+    }
+
+    /// Builder method to add a dependency on another task.
+    pub fn with_dependency(mut self, task_id: TaskId) -> Self {
+        self.depends_on.push(task_id);
+        self
+    }
+
+    /// Builder method to set the scheduling lane.
+    pub fn with_priority(mut self, priority: TaskPriority) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Builder method to pin runs to a previous run's environment.
+    pub fn with_pinned_manifest(mut self, manifest: RunManifest) -> Self {
+        self.pinned_manifest = Some(manifest);
+        self
+    }
+
+    /// Builder method to set how long the task may wait in Pending.
+    pub fn with_pending_ttl_seconds(mut self, seconds: u32) -> Self {
+        self.pending_ttl_seconds = Some(seconds);
+        self
+    }
+
+    /// Builder method to set the trace context the task's runs are traced in.
+    pub fn with_trace_context(mut self, trace_context: HashMap<String, String>) -> Self {
+        self.trace_context = trace_context;
+        self
+    }
+
+    /// Builder method to record the address of the creating client.
+    pub fn with_client_ip(mut self, client_ip: Option<String>) -> Self {
+        self.client_ip = client_ip;
+        self
+    }
+
+    /// Builder method to set the uploaded files staged for the task's runs.
+    pub fn with_input_files(mut self, file_ids: Vec<String>) -> Self {
+        self.input_files = file_ids;
+        self
+    }
+
+    /// Builder method to set the limits on each run.
+    pub fn with_limits(mut self, limits: RunLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
     /// Builder method to set a specific ID (useful for testing).
     pub fn with_id(mut self, id: TaskId) -> Self {
         self.id = id;
@@ -69,6 +245,16 @@ impl Task {
         self.runs.push(run);
     }
 
+    /// When the task last started waiting for a worker: its creation, or the
+    /// end of its latest run if it was retried.
+    pub fn pending_since(&self) -> DateTime<Utc> {
+        self.runs
+            .iter()
+            .filter_map(|r| r.finished_at)
+            .max()
+            .map_or(self.created_at, |finished| finished.max(self.created_at))
+    }
+
     /// Get the most recent run, if any.
     pub fn latest_run(&self) -> Option<&RunSummary> {
         self.runs.last()
@@ -106,6 +292,22 @@ pub struct RunSummary {
 
     /// Error message if run failed.
     pub error_message: Option<String>,
+
+    /// Why the run failed or was cancelled.
+    #[serde(default)]
+    pub failure_reason: Option<FailureReason>,
+
+    /// Environment the run executed in, reported by the worker when it starts.
+    #[serde(default)]
+    pub manifest: Option<RunManifest>,
+
+    /// Milestones the agent reported, in the order first reached.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub milestones: Vec<RunMilestone>,
+
+    /// What the run produced, reported by the worker when it finishes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<RunResultSummary>,
 }
 
 impl RunSummary {
@@ -119,6 +321,10 @@ impl RunSummary {
             finished_at: None,
             backend_used: None,
             error_message: None,
+            failure_reason: None,
+            manifest: None,
+            milestones: Vec::new(),
+            result: None,
         }
     }
 
@@ -136,15 +342,23 @@ impl RunSummary {
     }
 
     /// Mark the run as failed.
-    pub fn fail(&mut self, error: impl Into<String>) {
+    pub fn fail(&mut self, reason: FailureReason, error: impl Into<String>) {
         self.status = RunStatus::Failed;
         self.finished_at = Some(Utc::now());
         self.error_message = Some(error.into());
+        self.failure_reason = Some(reason);
     }
 
     /// Mark the run as cancelled.
     pub fn cancel(&mut self) {
         self.status = RunStatus::Cancelled;
         self.finished_at = Some(Utc::now());
+        self.failure_reason = Some(FailureReason::CancelledByUser);
+    }
+
+    /// Record a reported milestone. Reporting a milestone again updates it in
+    /// place.
+    pub fn record_milestone(&mut self, milestone: RunMilestone) {
+        milestone.record(&mut self.milestones);
     }
 }
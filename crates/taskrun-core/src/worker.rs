@@ -1,5 +1,6 @@
 //! Worker information types.
 
+use crate::namespace::default_namespace;
 use crate::{AgentSpec, WorkerId};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -21,6 +22,10 @@ pub struct WorkerInfo {
 
     /// Worker-level labels (region, hardware, tenant, etc.).
     pub labels: HashMap<String, String>,
+
+    /// Namespace whose tasks this worker runs.
+    #[serde(default = "default_namespace")]
+    pub namespace: String,
 }
 
 impl WorkerInfo {
@@ -32,6 +37,7 @@ impl WorkerInfo {
             version: env!("CARGO_PKG_VERSION").to_string(),
             agents: Vec::new(),
             labels: HashMap::new(),
+            namespace: default_namespace(),
         }
     }
 
@@ -40,6 +46,11 @@ impl WorkerInfo {
         self.agents.iter().any(|a| a.name == agent_name)
     }
 
+    /// Check if this worker supports a given agent and it is not degraded.
+    pub fn can_run_agent(&self, agent_name: &str) -> bool {
+        self.get_agent(agent_name).is_some_and(AgentSpec::is_ready)
+    }
+
     /// Get an agent by name.
     pub fn get_agent(&self, agent_name: &str) -> Option<&AgentSpec> {
         self.agents.iter().find(|a| a.name == agent_name)
@@ -51,6 +62,12 @@ impl WorkerInfo {
         self
     }
 
+    /// Builder method to set the namespace.
+    pub fn with_namespace(mut self, namespace: impl Into<String>) -> Self {
+        self.namespace = namespace.into();
+        self
+    }
+
     /// Builder method to add a label.
     pub fn with_label(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
         self.labels.insert(key.into(), value.into());
@@ -0,0 +1,185 @@
+//! Workflow templates and instances.
+
+use crate::{TaskId, TaskStatus, WorkflowId};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// A named, reusable multi-step pipeline.
+///
+/// Step input templates may reference:
+/// - `{{<param>}}`: a workflow parameter declared in `params`
+/// - `{{steps.<step>.output}}`: the output of a step this step depends on
+///
+/// Substituted values are JSON-string escaped, so placeholders belong inside
+/// string literals of the input JSON.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorkflowTemplate {
+    /// Unique template name.
+    pub name: String,
+
+    /// Human-readable description.
+    #[serde(default)]
+    pub description: String,
+
+    /// Parameters that must be supplied when the workflow is created.
+    #[serde(default)]
+    pub params: Vec<String>,
+
+    /// Steps, each expanded into one task.
+    pub steps: Vec<WorkflowStep>,
+}
+
+/// A single step of a workflow template.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorkflowStep {
+    /// Step name, unique within the template.
+    pub name: String,
+
+    /// Name of the agent to execute.
+    pub agent_name: String,
+
+    /// Input JSON template.
+    pub input_template: String,
+
+    /// Steps that must complete before this one is dispatched.
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+
+    /// Run timeout for this step's task.
+    #[serde(default)]
+    pub timeout_seconds: Option<u32>,
+}
+
+/// A workflow created from a template.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Workflow {
+    /// Unique workflow identifier.
+    pub id: WorkflowId,
+
+    /// Template this workflow was expanded from.
+    pub template_name: String,
+
+    /// Parameters supplied at creation.
+    pub params: HashMap<String, String>,
+
+    /// Who created this workflow.
+    pub created_by: String,
+
+    /// When the workflow was created.
+    pub created_at: DateTime<Utc>,
+
+    /// Task created for each step, in template order.
+    pub steps: Vec<WorkflowStepTask>,
+}
+
+/// The task a workflow step was expanded into.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorkflowStepTask {
+    /// Step name from the template.
+    pub step_name: String,
+
+    /// Task executing this step.
+    pub task_id: TaskId,
+}
+
+/// Overall status of a workflow, derived from its tasks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WorkflowStatus {
+    /// Some steps have not finished yet.
+    Running,
+    /// Every step completed.
+    Completed,
+    /// A step failed or was cancelled.
+    Failed,
+}
+
+impl WorkflowStatus {
+    /// Derive the workflow status from its step task statuses.
+    pub fn from_tasks(statuses: impl IntoIterator<Item = TaskStatus>) -> Self {
+        let mut status = WorkflowStatus::Completed;
+        for s in statuses {
+            match s {
+                TaskStatus::Failed | TaskStatus::Cancelled => return WorkflowStatus::Failed,
+                TaskStatus::Completed => {}
+                _ => status = WorkflowStatus::Running,
+            }
+        }
+        status
+    }
+}
+
+/// Placeholder names (`{{name}}`, trimmed) appearing in `template`.
+pub fn placeholders(template: &str) -> Vec<&str> {
+    let mut names = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        let after = &rest[start + 2..];
+        match after.find("}}") {
+            Some(end) => {
+                names.push(after[..end].trim());
+                rest = &after[end + 2..];
+            }
+            None => break,
+        }
+    }
+    names
+}
+
+/// Replace placeholders for which `lookup` returns a value, JSON-string
+/// escaping the value. Other placeholders are left untouched.
+pub fn fill_placeholders(template: &str, lookup: impl Fn(&str) -> Option<String>) -> String {
+    let mut output = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("}}") else {
+            break;
+        };
+        output.push_str(&rest[..start]);
+        match lookup(after[..end].trim()) {
+            Some(value) => output.push_str(&json_escape(&value)),
+            None => output.push_str(&rest[start..start + 2 + end + 2]),
+        }
+        rest = &after[end + 2..];
+    }
+    output.push_str(rest);
+    output
+}
+
+/// Escape `value` for inclusion inside a JSON string literal.
+fn json_escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fill_placeholders_escapes_and_keeps_unknown() {
+        let template = r#"{"prompt":"Review {{ repo }}: {{steps.plan.output}}"}"#;
+        assert_eq!(placeholders(template), vec!["repo", "steps.plan.output"]);
+
+        let filled = fill_placeholders(template, |name| {
+            (name == "repo").then(|| "acme/\"web\"".to_string())
+        });
+        assert_eq!(
+            filled,
+            r#"{"prompt":"Review acme/\"web\": {{steps.plan.output}}"}"#
+        );
+    }
+}
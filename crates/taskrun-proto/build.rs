@@ -9,10 +9,15 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // Proto files to compile
     let proto_files = [
+        proto_dir.join("taskrun/v1/admin_service.proto"),
+        proto_dir.join("taskrun/v1/api_key_service.proto"),
+        proto_dir.join("taskrun/v1/approval_service.proto"),
         proto_dir.join("taskrun/v1/common.proto"),
         proto_dir.join("taskrun/v1/run_service.proto"),
+        proto_dir.join("taskrun/v1/schedule_service.proto"),
         proto_dir.join("taskrun/v1/task_service.proto"),
         proto_dir.join("taskrun/v1/worker_service.proto"),
+        proto_dir.join("taskrun/v1/workflow_service.proto"),
     ];
 
     // Tell Cargo to rerun if proto files change
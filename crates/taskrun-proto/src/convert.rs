@@ -3,8 +3,10 @@
 use crate::pb;
 use chrono::{TimeZone, Utc};
 use taskrun_core::{
-    AgentSpec, ModelBackend, RunId, RunStatus, RunSummary, Task, TaskId, TaskStatus, WorkerId,
-    WorkerInfo, WorkerStatus,
+    AgentSpec, Artifact, FailureReason, ModelBackend, RunEventType, RunId, RunLimits, RunManifest,
+    RunMilestone, RunResultSummary, RunStatus, RunSummary, Schedule, ScheduleId, Task, TaskId,
+    TaskPriority, TaskStatus, WorkerId, WorkerInfo, WorkerStatus, WorkflowStatus, WorkflowStep,
+    WorkflowTemplate, DEFAULT_NAMESPACE,
 };
 
 // ============================================================================
@@ -36,6 +38,71 @@ impl From<pb::TaskStatus> for TaskStatus {
     }
 }
 
+// ============================================================================
+// TaskPriority conversions
+// ============================================================================
+
+impl From<TaskPriority> for pb::TaskPriority {
+    fn from(priority: TaskPriority) -> Self {
+        match priority {
+            TaskPriority::Batch => pb::TaskPriority::Batch,
+            TaskPriority::Interactive => pb::TaskPriority::Interactive,
+        }
+    }
+}
+
+impl From<pb::TaskPriority> for TaskPriority {
+    fn from(priority: pb::TaskPriority) -> Self {
+        match priority {
+            pb::TaskPriority::Unspecified => TaskPriority::Batch,
+            pb::TaskPriority::Batch => TaskPriority::Batch,
+            pb::TaskPriority::Interactive => TaskPriority::Interactive,
+        }
+    }
+}
+
+// ============================================================================
+// FailureReason conversions
+// ============================================================================
+
+impl From<FailureReason> for pb::FailureReason {
+    fn from(reason: FailureReason) -> Self {
+        match reason {
+            FailureReason::WorkerLost => pb::FailureReason::WorkerLost,
+            FailureReason::Timeout => pb::FailureReason::Timeout,
+            FailureReason::BudgetExceeded => pb::FailureReason::BudgetExceeded,
+            FailureReason::ToolDenied => pb::FailureReason::ToolDenied,
+            FailureReason::ProcessCrash => pb::FailureReason::ProcessCrash,
+            FailureReason::SdkError => pb::FailureReason::SdkError,
+            FailureReason::CancelledByUser => pb::FailureReason::CancelledByUser,
+            FailureReason::Expired => pb::FailureReason::Expired,
+            FailureReason::ResourceLimit => pb::FailureReason::ResourceLimit,
+        }
+    }
+}
+
+impl From<pb::FailureReason> for Option<FailureReason> {
+    fn from(reason: pb::FailureReason) -> Self {
+        match reason {
+            pb::FailureReason::Unspecified => None,
+            pb::FailureReason::WorkerLost => Some(FailureReason::WorkerLost),
+            pb::FailureReason::Timeout => Some(FailureReason::Timeout),
+            pb::FailureReason::BudgetExceeded => Some(FailureReason::BudgetExceeded),
+            pb::FailureReason::ToolDenied => Some(FailureReason::ToolDenied),
+            pb::FailureReason::ProcessCrash => Some(FailureReason::ProcessCrash),
+            pb::FailureReason::SdkError => Some(FailureReason::SdkError),
+            pb::FailureReason::CancelledByUser => Some(FailureReason::CancelledByUser),
+            pb::FailureReason::Expired => Some(FailureReason::Expired),
+            pb::FailureReason::ResourceLimit => Some(FailureReason::ResourceLimit),
+        }
+    }
+}
+
+/// Encode an optional failure reason (None = unspecified).
+fn failure_reason_to_proto(reason: Option<FailureReason>) -> i32 {
+    reason.map_or(0, |r| pb::FailureReason::from(r).into())
+}
+
 // ============================================================================
 // RunStatus conversions
 // ============================================================================
@@ -67,6 +134,27 @@ impl From<pb::RunStatus> for RunStatus {
     }
 }
 
+// ============================================================================
+// RunEventType conversions
+// ============================================================================
+
+impl From<RunEventType> for pb::RunEventType {
+    fn from(event_type: RunEventType) -> Self {
+        match event_type {
+            RunEventType::ExecutionStarted => pb::RunEventType::ExecutionStarted,
+            RunEventType::SessionInitialized => pb::RunEventType::SessionInitialized,
+            RunEventType::ToolRequested => pb::RunEventType::ToolRequested,
+            RunEventType::ToolCompleted => pb::RunEventType::ToolCompleted,
+            RunEventType::OutputGenerated => pb::RunEventType::OutputGenerated,
+            RunEventType::ExecutionCompleted => pb::RunEventType::ExecutionCompleted,
+            RunEventType::ExecutionFailed => pb::RunEventType::ExecutionFailed,
+            RunEventType::Milestone => pb::RunEventType::Milestone,
+            RunEventType::Log => pb::RunEventType::Log,
+            RunEventType::RunSummary => pb::RunEventType::RunSummary,
+        }
+    }
+}
+
 // ============================================================================
 // WorkerStatus conversions
 // ============================================================================
@@ -78,6 +166,8 @@ impl From<WorkerStatus> for pb::WorkerStatus {
             WorkerStatus::Busy => pb::WorkerStatus::Busy,
             WorkerStatus::Draining => pb::WorkerStatus::Draining,
             WorkerStatus::Error => pb::WorkerStatus::Error,
+            WorkerStatus::Degraded => pb::WorkerStatus::Degraded,
+            WorkerStatus::Offline => pb::WorkerStatus::Offline,
         }
     }
 }
@@ -90,6 +180,8 @@ impl From<pb::WorkerStatus> for WorkerStatus {
             pb::WorkerStatus::Busy => WorkerStatus::Busy,
             pb::WorkerStatus::Draining => WorkerStatus::Draining,
             pb::WorkerStatus::Error => WorkerStatus::Error,
+            pb::WorkerStatus::Degraded => WorkerStatus::Degraded,
+            pb::WorkerStatus::Offline => WorkerStatus::Offline,
         }
     }
 }
@@ -137,6 +229,8 @@ impl From<AgentSpec> for pb::AgentSpec {
             description: agent.description,
             labels: agent.labels,
             backends: agent.backends.into_iter().map(Into::into).collect(),
+            degraded: agent.degraded.is_some(),
+            degraded_reason: agent.degraded.unwrap_or_default(),
         }
     }
 }
@@ -148,10 +242,20 @@ impl From<pb::AgentSpec> for AgentSpec {
             description: proto.description,
             labels: proto.labels,
             backends: proto.backends.into_iter().map(Into::into).collect(),
+            degraded: proto.degraded.then_some(proto.degraded_reason),
         }
     }
 }
 
+/// Empty namespaces (from peers that predate namespaces) are the default one.
+fn namespace_or_default(namespace: String) -> String {
+    if namespace.is_empty() {
+        DEFAULT_NAMESPACE.to_string()
+    } else {
+        namespace
+    }
+}
+
 // ============================================================================
 // WorkerInfo conversions
 // ============================================================================
@@ -164,6 +268,7 @@ impl From<WorkerInfo> for pb::WorkerInfo {
             version: info.version,
             agents: info.agents.into_iter().map(Into::into).collect(),
             labels: info.labels,
+            namespace: info.namespace,
         }
     }
 }
@@ -176,6 +281,7 @@ impl From<pb::WorkerInfo> for WorkerInfo {
             version: proto.version,
             agents: proto.agents.into_iter().map(Into::into).collect(),
             labels: proto.labels,
+            namespace: namespace_or_default(proto.namespace),
         }
     }
 }
@@ -189,12 +295,29 @@ impl From<Task> for pb::Task {
         pb::Task {
             id: task.id.into_inner(),
             agent_name: task.agent_name,
+            namespace: task.namespace,
             input_json: task.input_json,
             status: pb::TaskStatus::from(task.status).into(),
             created_by: task.created_by,
             created_at_ms: task.created_at.timestamp_millis(),
             labels: task.labels,
             runs: task.runs.into_iter().map(Into::into).collect(),
+            timeout_seconds: task.timeout_seconds.unwrap_or(0),
+            depends_on: task
+                .depends_on
+                .into_iter()
+                .map(TaskId::into_inner)
+                .collect(),
+            priority: pb::TaskPriority::from(task.priority).into(),
+            pinned_manifest: task.pinned_manifest.map(Into::into),
+            pending_ttl_seconds: task.pending_ttl_seconds.unwrap_or(0),
+            failure_message: task.failure_message.unwrap_or_default(),
+            failure_reason: failure_reason_to_proto(task.failure_reason),
+            client_ip: task.client_ip.unwrap_or_default(),
+            input_files: task.input_files,
+            limits: (!task.limits.is_empty()).then(|| task.limits.into()),
+            // Filled in by the server, which owns the queue
+            queue: None,
         }
     }
 }
@@ -204,10 +327,12 @@ impl From<pb::Task> for Task {
         let status = pb::TaskStatus::try_from(proto.status)
             .unwrap_or(pb::TaskStatus::Unspecified)
             .into();
+        let failure_reason = proto.failure_reason().into();
 
         Task {
             id: TaskId::new(proto.id),
             agent_name: proto.agent_name,
+            namespace: namespace_or_default(proto.namespace),
             input_json: proto.input_json,
             status,
             created_by: proto.created_by,
@@ -217,6 +342,44 @@ impl From<pb::Task> for Task {
                 .unwrap_or_else(Utc::now),
             labels: proto.labels,
             runs: proto.runs.into_iter().map(Into::into).collect(),
+            timeout_seconds: (proto.timeout_seconds > 0).then_some(proto.timeout_seconds),
+            depends_on: proto.depends_on.into_iter().map(TaskId::new).collect(),
+            priority: pb::TaskPriority::try_from(proto.priority)
+                .unwrap_or(pb::TaskPriority::Unspecified)
+                .into(),
+            pinned_manifest: proto.pinned_manifest.map(Into::into),
+            pending_ttl_seconds: (proto.pending_ttl_seconds > 0)
+                .then_some(proto.pending_ttl_seconds),
+            failure_reason,
+            failure_message: (!proto.failure_message.is_empty()).then_some(proto.failure_message),
+            client_ip: (!proto.client_ip.is_empty()).then_some(proto.client_ip),
+            trace_context: Default::default(),
+            input_files: proto.input_files,
+            limits: proto.limits.map(Into::into).unwrap_or_default(),
+        }
+    }
+}
+
+// ============================================================================
+// RunLimits conversions
+// ============================================================================
+
+impl From<RunLimits> for pb::RunLimits {
+    fn from(limits: RunLimits) -> Self {
+        pb::RunLimits {
+            max_memory_mb: limits.max_memory_mb.unwrap_or(0),
+            max_cpu_seconds: limits.max_cpu_seconds.unwrap_or(0),
+            max_wall_seconds: limits.max_wall_seconds.unwrap_or(0),
+        }
+    }
+}
+
+impl From<pb::RunLimits> for RunLimits {
+    fn from(proto: pb::RunLimits) -> Self {
+        RunLimits {
+            max_memory_mb: (proto.max_memory_mb > 0).then_some(proto.max_memory_mb),
+            max_cpu_seconds: (proto.max_cpu_seconds > 0).then_some(proto.max_cpu_seconds),
+            max_wall_seconds: (proto.max_wall_seconds > 0).then_some(proto.max_wall_seconds),
         }
     }
 }
@@ -235,6 +398,10 @@ impl From<RunSummary> for pb::RunSummary {
             finished_at_ms: run.finished_at.map(|t| t.timestamp_millis()).unwrap_or(0),
             backend_used: run.backend_used.map(Into::into),
             error_message: run.error_message.unwrap_or_default(),
+            manifest: run.manifest.map(Into::into),
+            failure_reason: failure_reason_to_proto(run.failure_reason),
+            milestones: run.milestones.into_iter().map(Into::into).collect(),
+            result: run.result.map(Into::into),
         }
     }
 }
@@ -244,6 +411,7 @@ impl From<pb::RunSummary> for RunSummary {
         let status = pb::RunStatus::try_from(proto.status)
             .unwrap_or(pb::RunStatus::Unspecified)
             .into();
+        let failure_reason = proto.failure_reason().into();
 
         RunSummary {
             run_id: RunId::new(proto.run_id),
@@ -265,6 +433,265 @@ impl From<pb::RunSummary> for RunSummary {
             } else {
                 Some(proto.error_message)
             },
+            failure_reason,
+            manifest: proto.manifest.map(Into::into),
+            milestones: proto.milestones.into_iter().map(Into::into).collect(),
+            result: proto.result.map(Into::into),
+        }
+    }
+}
+
+// ============================================================================
+// RunManifest conversions
+// ============================================================================
+
+impl From<RunManifest> for pb::RunManifest {
+    fn from(manifest: RunManifest) -> Self {
+        let tool_list = |tools: Vec<String>| pb::ToolList { tools };
+        pb::RunManifest {
+            agent_name: manifest.agent_name,
+            agent_template_hash: manifest.agent_template_hash,
+            model_provider: manifest.model_provider,
+            model_name: manifest.model_name,
+            allowed_tools: manifest.allowed_tools.map(tool_list),
+            denied_tools: manifest.denied_tools.map(tool_list),
+            claude_cli_version: manifest.claude_cli_version.unwrap_or_default(),
+            worker_version: manifest.worker_version,
+            feature_flags: manifest.feature_flags.into_iter().collect(),
+            input_hash: manifest.input_hash,
+        }
+    }
+}
+
+impl From<pb::RunManifest> for RunManifest {
+    fn from(proto: pb::RunManifest) -> Self {
+        RunManifest {
+            agent_name: proto.agent_name,
+            agent_template_hash: proto.agent_template_hash,
+            model_provider: proto.model_provider,
+            model_name: proto.model_name,
+            allowed_tools: proto.allowed_tools.map(|list| list.tools),
+            denied_tools: proto.denied_tools.map(|list| list.tools),
+            claude_cli_version: (!proto.claude_cli_version.is_empty())
+                .then_some(proto.claude_cli_version),
+            worker_version: proto.worker_version,
+            feature_flags: proto.feature_flags.into_iter().collect(),
+            input_hash: proto.input_hash,
+        }
+    }
+}
+
+// ============================================================================
+// Schedule conversions
+// ============================================================================
+
+impl From<Schedule> for pb::Schedule {
+    fn from(schedule: Schedule) -> Self {
+        pb::Schedule {
+            id: schedule.id.into_inner(),
+            cron_expr: schedule.cron_expr,
+            agent_name: schedule.agent_name,
+            input_template: schedule.input_template,
+            created_by: schedule.created_by,
+            created_at_ms: schedule.created_at.timestamp_millis(),
+            labels: schedule.labels,
+            timeout_seconds: schedule.timeout_seconds.unwrap_or(0),
+            paused: schedule.paused,
+            last_fired_at_ms: schedule
+                .last_fired_at
+                .map(|t| t.timestamp_millis())
+                .unwrap_or(0),
+            last_task_id: schedule
+                .last_task_id
+                .map(TaskId::into_inner)
+                .unwrap_or_default(),
+            next_fire_at_ms: schedule
+                .next_fire_at
+                .map(|t| t.timestamp_millis())
+                .unwrap_or(0),
+        }
+    }
+}
+
+impl From<pb::Schedule> for Schedule {
+    fn from(proto: pb::Schedule) -> Self {
+        let millis = |ms: i64| {
+            if ms > 0 {
+                Utc.timestamp_millis_opt(ms).single()
+            } else {
+                None
+            }
+        };
+
+        Schedule {
+            id: ScheduleId::new(proto.id),
+            cron_expr: proto.cron_expr,
+            agent_name: proto.agent_name,
+            input_template: proto.input_template,
+            created_by: proto.created_by,
+            created_at: Utc
+                .timestamp_millis_opt(proto.created_at_ms)
+                .single()
+                .unwrap_or_else(Utc::now),
+            labels: proto.labels,
+            timeout_seconds: (proto.timeout_seconds > 0).then_some(proto.timeout_seconds),
+            paused: proto.paused,
+            last_fired_at: millis(proto.last_fired_at_ms),
+            last_task_id: (!proto.last_task_id.is_empty()).then(|| TaskId::new(proto.last_task_id)),
+            next_fire_at: millis(proto.next_fire_at_ms),
+        }
+    }
+}
+
+// ============================================================================
+// Workflow conversions
+// ============================================================================
+
+impl From<WorkflowStatus> for pb::WorkflowStatus {
+    fn from(status: WorkflowStatus) -> Self {
+        match status {
+            WorkflowStatus::Running => pb::WorkflowStatus::Running,
+            WorkflowStatus::Completed => pb::WorkflowStatus::Completed,
+            WorkflowStatus::Failed => pb::WorkflowStatus::Failed,
+        }
+    }
+}
+
+impl From<pb::WorkflowStatus> for WorkflowStatus {
+    fn from(status: pb::WorkflowStatus) -> Self {
+        match status {
+            pb::WorkflowStatus::Unspecified | pb::WorkflowStatus::Running => {
+                WorkflowStatus::Running
+            }
+            pb::WorkflowStatus::Completed => WorkflowStatus::Completed,
+            pb::WorkflowStatus::Failed => WorkflowStatus::Failed,
+        }
+    }
+}
+
+impl From<WorkflowTemplate> for pb::WorkflowTemplate {
+    fn from(template: WorkflowTemplate) -> Self {
+        pb::WorkflowTemplate {
+            name: template.name,
+            description: template.description,
+            params: template.params,
+            steps: template
+                .steps
+                .into_iter()
+                .map(|step| pb::WorkflowTemplateStep {
+                    name: step.name,
+                    agent_name: step.agent_name,
+                    input_template: step.input_template,
+                    depends_on: step.depends_on,
+                    timeout_seconds: step.timeout_seconds.unwrap_or(0),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<pb::WorkflowTemplate> for WorkflowTemplate {
+    fn from(proto: pb::WorkflowTemplate) -> Self {
+        WorkflowTemplate {
+            name: proto.name,
+            description: proto.description,
+            params: proto.params,
+            steps: proto
+                .steps
+                .into_iter()
+                .map(|step| WorkflowStep {
+                    name: step.name,
+                    agent_name: step.agent_name,
+                    input_template: step.input_template,
+                    depends_on: step.depends_on,
+                    timeout_seconds: (step.timeout_seconds > 0).then_some(step.timeout_seconds),
+                })
+                .collect(),
+        }
+    }
+}
+
+// ============================================================================
+// Artifact conversions
+// ============================================================================
+
+impl From<Artifact> for pb::Artifact {
+    fn from(artifact: Artifact) -> Self {
+        pb::Artifact {
+            name: artifact.name,
+            uri: artifact.uri,
+            size_bytes: artifact.size_bytes.unwrap_or(0),
+            mime_type: artifact.mime_type.unwrap_or_default(),
+        }
+    }
+}
+
+impl From<pb::Artifact> for Artifact {
+    fn from(proto: pb::Artifact) -> Self {
+        Artifact {
+            name: proto.name,
+            uri: proto.uri,
+            size_bytes: (proto.size_bytes > 0).then_some(proto.size_bytes),
+            mime_type: (!proto.mime_type.is_empty()).then_some(proto.mime_type),
+        }
+    }
+}
+
+// ============================================================================
+// RunMilestone conversions
+// ============================================================================
+
+impl From<RunMilestone> for pb::RunMilestone {
+    fn from(milestone: RunMilestone) -> Self {
+        pb::RunMilestone {
+            name: milestone.name,
+            percent: milestone.percent.map(u32::from),
+            note: milestone.note.unwrap_or_default(),
+            reached_at_ms: milestone.reached_at.timestamp_millis(),
+        }
+    }
+}
+
+impl From<pb::RunMilestone> for RunMilestone {
+    fn from(proto: pb::RunMilestone) -> Self {
+        let mut milestone = RunMilestone::new(proto.name)
+            .with_percent(proto.percent.map(|p| p.min(100) as u8))
+            .with_note(Some(proto.note));
+        if let Some(at) = Utc.timestamp_millis_opt(proto.reached_at_ms).single() {
+            milestone.reached_at = at;
+        }
+        milestone
+    }
+}
+
+// ============================================================================
+// RunResultSummary conversions
+// ============================================================================
+
+impl From<RunResultSummary> for pb::RunResultSummary {
+    fn from(summary: RunResultSummary) -> Self {
+        pb::RunResultSummary {
+            answer: summary.answer.unwrap_or_default(),
+            files_changed: summary.files_changed,
+            files: summary.files,
+            tools_used: summary.tools_used,
+            duration_ms: summary.duration_ms,
+            input_tokens: summary.input_tokens,
+            output_tokens: summary.output_tokens,
+        }
+    }
+}
+
+impl From<pb::RunResultSummary> for RunResultSummary {
+    fn from(proto: pb::RunResultSummary) -> Self {
+        RunResultSummary {
+            answer: (!proto.answer.is_empty()).then_some(proto.answer),
+            files_changed: proto.files_changed,
+            files: proto.files,
+            tools_used: proto.tools_used,
+            duration_ms: proto.duration_ms,
+            input_tokens: proto.input_tokens,
+            output_tokens: proto.output_tokens,
         }
     }
 }
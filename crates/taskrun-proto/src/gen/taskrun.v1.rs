@@ -27,6 +27,62 @@ pub struct ModelBackend {
         ::prost::alloc::string::String,
     >,
 }
+/// A list of tool names (message so that "unset" differs from "empty")
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ToolList {
+    #[prost(string, repeated, tag = "1")]
+    pub tools: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+}
+/// Everything that determined how a run executed, captured when it starts
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RunManifest {
+    /// Agent that executed the run
+    #[prost(string, tag = "1")]
+    pub agent_name: ::prost::alloc::string::String,
+    /// SHA-256 of the agent specification the worker advertised (hex)
+    #[prost(string, tag = "2")]
+    pub agent_template_hash: ::prost::alloc::string::String,
+    /// Model provider (e.g., "anthropic")
+    #[prost(string, tag = "3")]
+    pub model_provider: ::prost::alloc::string::String,
+    /// Model requested for the run
+    #[prost(string, tag = "4")]
+    pub model_name: ::prost::alloc::string::String,
+    /// Tools the agent was restricted to (unset = all tools)
+    #[prost(message, optional, tag = "5")]
+    pub allowed_tools: ::core::option::Option<ToolList>,
+    /// Tools the agent was denied (unset = none)
+    #[prost(message, optional, tag = "6")]
+    pub denied_tools: ::core::option::Option<ToolList>,
+    /// `claude --version` on the worker (empty = unknown)
+    #[prost(string, tag = "7")]
+    pub claude_cli_version: ::prost::alloc::string::String,
+    /// Worker binary version
+    #[prost(string, tag = "8")]
+    pub worker_version: ::prost::alloc::string::String,
+    /// Worker features that affect execution
+    #[prost(map = "string, string", tag = "9")]
+    pub feature_flags: ::std::collections::HashMap<
+        ::prost::alloc::string::String,
+        ::prost::alloc::string::String,
+    >,
+    /// SHA-256 of the run's input JSON (hex)
+    #[prost(string, tag = "10")]
+    pub input_hash: ::prost::alloc::string::String,
+}
+/// Limits the worker enforces on each run of a task (0 = no limit)
+#[derive(Clone, Copy, PartialEq, ::prost::Message)]
+pub struct RunLimits {
+    /// Memory cap of the Claude process in MiB
+    #[prost(uint64, tag = "1")]
+    pub max_memory_mb: u64,
+    /// CPU time cap of each process of the run in seconds
+    #[prost(uint64, tag = "2")]
+    pub max_cpu_seconds: u64,
+    /// Wall-clock time after which the worker kills the run, in seconds
+    #[prost(uint64, tag = "3")]
+    pub max_wall_seconds: u64,
+}
 /// Specification of an agent available on a worker
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct AgentSpec {
@@ -45,6 +101,13 @@ pub struct AgentSpec {
     /// Model backends this agent can use
     #[prost(message, repeated, tag = "4")]
     pub backends: ::prost::alloc::vec::Vec<ModelBackend>,
+    /// Set when the agent failed the worker's startup smoke test; degraded
+    /// agents are advertised but not scheduled
+    #[prost(bool, tag = "5")]
+    pub degraded: bool,
+    /// Why the agent is degraded
+    #[prost(string, tag = "6")]
+    pub degraded_reason: ::prost::alloc::string::String,
 }
 /// A message in the conversation history
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -58,6 +121,66 @@ pub struct ChatMessage {
     /// Unix timestamp (milliseconds) when message was created
     #[prost(int64, tag = "3")]
     pub timestamp_ms: i64,
+    /// Files referenced by this message (screenshots, generated reports)
+    #[prost(message, repeated, tag = "4")]
+    pub attachments: ::prost::alloc::vec::Vec<Artifact>,
+}
+/// Reference to a file produced or referenced during a run
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Artifact {
+    /// Display name (usually the file name)
+    #[prost(string, tag = "1")]
+    pub name: ::prost::alloc::string::String,
+    /// Location of the artifact (file:// on the worker host, or http(s))
+    #[prost(string, tag = "2")]
+    pub uri: ::prost::alloc::string::String,
+    /// Size in bytes (0 = unknown)
+    #[prost(uint64, tag = "3")]
+    pub size_bytes: u64,
+    /// MIME type (empty = unknown)
+    #[prost(string, tag = "4")]
+    pub mime_type: ::prost::alloc::string::String,
+}
+/// A step the agent reported reaching through the report_progress tool
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RunMilestone {
+    /// Short name of the step, e.g. "tests passing"
+    #[prost(string, tag = "1")]
+    pub name: ::prost::alloc::string::String,
+    /// Overall progress of the run (0-100), if the agent estimated it
+    #[prost(uint32, optional, tag = "2")]
+    pub percent: ::core::option::Option<u32>,
+    /// Free-form detail (empty = none)
+    #[prost(string, tag = "3")]
+    pub note: ::prost::alloc::string::String,
+    /// Unix timestamp (milliseconds) when the milestone was reported
+    #[prost(int64, tag = "4")]
+    pub reached_at_ms: i64,
+}
+/// What a finished run produced, compact enough to preview in run lists
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RunResultSummary {
+    /// Start of the agent's final answer (empty = none)
+    #[prost(string, tag = "1")]
+    pub answer: ::prost::alloc::string::String,
+    /// Number of files the agent wrote or edited
+    #[prost(uint32, tag = "2")]
+    pub files_changed: u32,
+    /// Paths of the first changed files, relative to the workspace
+    #[prost(string, repeated, tag = "3")]
+    pub files: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    /// Number of tool calls
+    #[prost(uint32, tag = "4")]
+    pub tools_used: u32,
+    /// Wall-clock duration of the run, if known
+    #[prost(uint64, optional, tag = "5")]
+    pub duration_ms: ::core::option::Option<u64>,
+    /// Prompt tokens, cached or not, if reported
+    #[prost(uint64, optional, tag = "6")]
+    pub input_tokens: ::core::option::Option<u64>,
+    /// Generated tokens, if reported
+    #[prost(uint64, optional, tag = "7")]
+    pub output_tokens: ::core::option::Option<u64>,
 }
 /// Run execution event for tracking execution stages
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -83,6 +206,9 @@ pub struct RunEvent {
         ::prost::alloc::string::String,
         ::prost::alloc::string::String,
     >,
+    /// Files produced or referenced by this step
+    #[prost(message, repeated, tag = "7")]
+    pub artifacts: ::prost::alloc::vec::Vec<Artifact>,
 }
 /// Information about a worker's capabilities
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -105,6 +231,9 @@ pub struct WorkerInfo {
         ::prost::alloc::string::String,
         ::prost::alloc::string::String,
     >,
+    /// Namespace whose tasks this worker runs (empty = "default")
+    #[prost(string, tag = "6")]
+    pub namespace: ::prost::alloc::string::String,
 }
 /// Status of a Task in the control plane
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
@@ -196,6 +325,8 @@ pub enum WorkerStatus {
     Busy = 2,
     Draining = 3,
     Error = 4,
+    Degraded = 5,
+    Offline = 6,
 }
 impl WorkerStatus {
     /// String value of the enum field names used in the ProtoBuf definition.
@@ -209,6 +340,8 @@ impl WorkerStatus {
             Self::Busy => "WORKER_STATUS_BUSY",
             Self::Draining => "WORKER_STATUS_DRAINING",
             Self::Error => "WORKER_STATUS_ERROR",
+            Self::Degraded => "WORKER_STATUS_DEGRADED",
+            Self::Offline => "WORKER_STATUS_OFFLINE",
         }
     }
     /// Creates an enum from field names used in the ProtoBuf definition.
@@ -219,6 +352,100 @@ impl WorkerStatus {
             "WORKER_STATUS_BUSY" => Some(Self::Busy),
             "WORKER_STATUS_DRAINING" => Some(Self::Draining),
             "WORKER_STATUS_ERROR" => Some(Self::Error),
+            "WORKER_STATUS_DEGRADED" => Some(Self::Degraded),
+            "WORKER_STATUS_OFFLINE" => Some(Self::Offline),
+            _ => None,
+        }
+    }
+}
+/// Scheduling lane of a Task
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum TaskPriority {
+    Unspecified = 0,
+    /// Queued background work
+    Batch = 1,
+    /// A user is waiting on the output (streaming chat, continuation)
+    Interactive = 2,
+}
+impl TaskPriority {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            Self::Unspecified => "TASK_PRIORITY_UNSPECIFIED",
+            Self::Batch => "TASK_PRIORITY_BATCH",
+            Self::Interactive => "TASK_PRIORITY_INTERACTIVE",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "TASK_PRIORITY_UNSPECIFIED" => Some(Self::Unspecified),
+            "TASK_PRIORITY_BATCH" => Some(Self::Batch),
+            "TASK_PRIORITY_INTERACTIVE" => Some(Self::Interactive),
+            _ => None,
+        }
+    }
+}
+/// Why a run or task ended without completing
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum FailureReason {
+    Unspecified = 0,
+    /// The worker stopped heartbeating or disconnected mid-run
+    WorkerLost = 1,
+    /// The run exceeded its time limit
+    Timeout = 2,
+    /// The agent hit its turn or spend limit
+    BudgetExceeded = 3,
+    /// The agent failed after being denied a tool
+    ToolDenied = 4,
+    /// The agent process could not start or exited abnormally
+    ProcessCrash = 5,
+    /// The agent reported an error
+    SdkError = 6,
+    /// A user or operator cancelled the task
+    CancelledByUser = 7,
+    /// The task waited in PENDING past its TTL
+    Expired = 8,
+    /// The agent process was stopped by a memory or process limit
+    ResourceLimit = 9,
+}
+impl FailureReason {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            Self::Unspecified => "FAILURE_REASON_UNSPECIFIED",
+            Self::WorkerLost => "FAILURE_REASON_WORKER_LOST",
+            Self::Timeout => "FAILURE_REASON_TIMEOUT",
+            Self::BudgetExceeded => "FAILURE_REASON_BUDGET_EXCEEDED",
+            Self::ToolDenied => "FAILURE_REASON_TOOL_DENIED",
+            Self::ProcessCrash => "FAILURE_REASON_PROCESS_CRASH",
+            Self::SdkError => "FAILURE_REASON_SDK_ERROR",
+            Self::CancelledByUser => "FAILURE_REASON_CANCELLED_BY_USER",
+            Self::Expired => "FAILURE_REASON_EXPIRED",
+            Self::ResourceLimit => "FAILURE_REASON_RESOURCE_LIMIT",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "FAILURE_REASON_UNSPECIFIED" => Some(Self::Unspecified),
+            "FAILURE_REASON_WORKER_LOST" => Some(Self::WorkerLost),
+            "FAILURE_REASON_TIMEOUT" => Some(Self::Timeout),
+            "FAILURE_REASON_BUDGET_EXCEEDED" => Some(Self::BudgetExceeded),
+            "FAILURE_REASON_TOOL_DENIED" => Some(Self::ToolDenied),
+            "FAILURE_REASON_PROCESS_CRASH" => Some(Self::ProcessCrash),
+            "FAILURE_REASON_SDK_ERROR" => Some(Self::SdkError),
+            "FAILURE_REASON_CANCELLED_BY_USER" => Some(Self::CancelledByUser),
+            "FAILURE_REASON_EXPIRED" => Some(Self::Expired),
+            "FAILURE_REASON_RESOURCE_LIMIT" => Some(Self::ResourceLimit),
             _ => None,
         }
     }
@@ -268,6 +495,13 @@ pub enum RunEventType {
     OutputGenerated = 5,
     ExecutionCompleted = 6,
     ExecutionFailed = 7,
+    /// The agent reported a milestone (metadata: milestone, percent, note)
+    Milestone = 8,
+    /// A worker log record of the run (metadata: level, target, message)
+    Log = 9,
+    /// Compact result of a finished run (metadata: answer, files_changed, files,
+    /// tools_used, duration_ms, input_tokens, output_tokens)
+    RunSummary = 10,
 }
 impl RunEventType {
     /// String value of the enum field names used in the ProtoBuf definition.
@@ -284,6 +518,9 @@ impl RunEventType {
             Self::OutputGenerated => "RUN_EVENT_TYPE_OUTPUT_GENERATED",
             Self::ExecutionCompleted => "RUN_EVENT_TYPE_EXECUTION_COMPLETED",
             Self::ExecutionFailed => "RUN_EVENT_TYPE_EXECUTION_FAILED",
+            Self::Milestone => "RUN_EVENT_TYPE_MILESTONE",
+            Self::Log => "RUN_EVENT_TYPE_LOG",
+            Self::RunSummary => "RUN_EVENT_TYPE_RUN_SUMMARY",
         }
     }
     /// Creates an enum from field names used in the ProtoBuf definition.
@@ -297,203 +534,185 @@ impl RunEventType {
             "RUN_EVENT_TYPE_OUTPUT_GENERATED" => Some(Self::OutputGenerated),
             "RUN_EVENT_TYPE_EXECUTION_COMPLETED" => Some(Self::ExecutionCompleted),
             "RUN_EVENT_TYPE_EXECUTION_FAILED" => Some(Self::ExecutionFailed),
+            "RUN_EVENT_TYPE_MILESTONE" => Some(Self::Milestone),
+            "RUN_EVENT_TYPE_LOG" => Some(Self::Log),
+            "RUN_EVENT_TYPE_RUN_SUMMARY" => Some(Self::RunSummary),
             _ => None,
         }
     }
 }
-/// Wrapper for all messages from worker to control plane
+/// Request to export the control plane state.
+#[derive(Clone, Copy, PartialEq, ::prost::Message)]
+pub struct ExportStateRequest {}
+/// A piece of a state archive. The archive is the concatenation of the
+/// chunks, in order.
 #[derive(Clone, PartialEq, ::prost::Message)]
-pub struct RunClientMessage {
-    #[prost(oneof = "run_client_message::Payload", tags = "1, 2, 3, 4, 5, 6")]
-    pub payload: ::core::option::Option<run_client_message::Payload>,
+pub struct StateChunk {
+    #[prost(bytes = "vec", tag = "1")]
+    pub data: ::prost::alloc::vec::Vec<u8>,
 }
-/// Nested message and enum types in `RunClientMessage`.
-pub mod run_client_message {
-    #[derive(Clone, PartialEq, ::prost::Oneof)]
-    pub enum Payload {
-        #[prost(message, tag = "1")]
-        Hello(super::WorkerHello),
-        #[prost(message, tag = "2")]
-        Heartbeat(super::WorkerHeartbeat),
-        #[prost(message, tag = "3")]
-        StatusUpdate(super::RunStatusUpdate),
-        #[prost(message, tag = "4")]
-        OutputChunk(super::RunOutputChunk),
-        #[prost(message, tag = "5")]
-        Event(super::RunEvent),
-        #[prost(message, tag = "6")]
-        ChatMessage(super::RunChatMessage),
-    }
+/// Result of an import.
+#[derive(Clone, Copy, PartialEq, ::prost::Message)]
+pub struct ImportStateResponse {
+    /// Archive format version that was read.
+    #[prost(uint32, tag = "1")]
+    pub version: u32,
+    /// Tasks added.
+    #[prost(uint32, tag = "2")]
+    pub tasks_imported: u32,
+    /// Tasks left out because a task with the same ID exists.
+    #[prost(uint32, tag = "3")]
+    pub tasks_skipped: u32,
+    /// Run events added (only those of imported tasks).
+    #[prost(uint32, tag = "4")]
+    pub events_imported: u32,
+    /// Enrollment token records added.
+    #[prost(uint32, tag = "5")]
+    pub tokens_imported: u32,
+    /// Runs that were active in the archive, failed as worker_lost; their
+    /// tasks went back to Pending.
+    #[prost(uint32, tag = "6")]
+    pub runs_interrupted: u32,
 }
-/// A chat message for a run (user or assistant message in the conversation)
-#[derive(Clone, PartialEq, ::prost::Message)]
-pub struct RunChatMessage {
-    /// Run identifier
-    #[prost(string, tag = "1")]
-    pub run_id: ::prost::alloc::string::String,
-    /// The chat message
-    #[prost(message, optional, tag = "2")]
-    pub message: ::core::option::Option<ChatMessage>,
+/// Request for the runtime toggles.
+#[derive(Clone, Copy, PartialEq, ::prost::Message)]
+pub struct GetTogglesRequest {}
+/// Switches changed at runtime.
+#[derive(Clone, Copy, PartialEq, ::prost::Message)]
+pub struct RuntimeToggles {
+    /// New tasks are refused; queued and running work continues.
+    #[prost(bool, tag = "1")]
+    pub maintenance: bool,
+    /// Queued tasks stay pending instead of being assigned to workers.
+    #[prost(bool, tag = "2")]
+    pub scheduling_frozen: bool,
+    /// Lane of tasks created without a priority.
+    #[prost(enumeration = "TaskPriority", tag = "3")]
+    pub default_priority: i32,
 }
-/// Initial message from worker announcing its capabilities
-#[derive(Clone, PartialEq, ::prost::Message)]
-pub struct WorkerHello {
-    /// Worker information and capabilities
-    #[prost(message, optional, tag = "1")]
-    pub info: ::core::option::Option<WorkerInfo>,
+/// Change to the runtime toggles. Unset fields keep their value.
+#[derive(Clone, Copy, PartialEq, ::prost::Message)]
+pub struct UpdateTogglesRequest {
+    #[prost(bool, optional, tag = "1")]
+    pub maintenance: ::core::option::Option<bool>,
+    #[prost(bool, optional, tag = "2")]
+    pub scheduling_frozen: ::core::option::Option<bool>,
+    /// UNSPECIFIED keeps the current default.
+    #[prost(enumeration = "TaskPriority", tag = "3")]
+    pub default_priority: i32,
 }
-/// Periodic heartbeat from worker
+/// A stored bootstrap token.
 #[derive(Clone, PartialEq, ::prost::Message)]
-pub struct WorkerHeartbeat {
-    /// Worker identifier
+pub struct BootstrapToken {
+    /// Token ID: the start of the token's SHA-256 hash.
     #[prost(string, tag = "1")]
+    pub id: ::prost::alloc::string::String,
+    /// What the token is for.
+    #[prost(string, tag = "2")]
+    pub description: ::prost::alloc::string::String,
+    /// Creation timestamp in milliseconds since epoch.
+    #[prost(int64, tag = "3")]
+    pub created_at_ms: i64,
+    /// Expiry timestamp in milliseconds since epoch.
+    #[prost(int64, tag = "4")]
+    pub expires_at_ms: i64,
+    /// The token was used to enroll a worker.
+    #[prost(bool, tag = "5")]
+    pub consumed: bool,
+    /// Worker that enrolled with the token (empty if unused).
+    #[prost(string, tag = "6")]
     pub worker_id: ::prost::alloc::string::String,
-    /// Current worker status
-    #[prost(enumeration = "WorkerStatus", tag = "2")]
-    pub status: i32,
-    /// Number of active runs on this worker
-    #[prost(uint32, tag = "3")]
-    pub active_runs: u32,
-    /// Maximum concurrent runs this worker can handle
-    #[prost(uint32, tag = "4")]
-    pub max_concurrent_runs: u32,
-    /// Custom metrics (cpu_usage, memory_mb, etc.)
-    #[prost(map = "string, string", tag = "5")]
-    pub metrics: ::std::collections::HashMap<
-        ::prost::alloc::string::String,
-        ::prost::alloc::string::String,
-    >,
-    /// Unix timestamp (milliseconds) when heartbeat was sent
-    #[prost(int64, tag = "6")]
-    pub timestamp_ms: i64,
+    /// Namespace the enrolled worker is bound to.
+    #[prost(string, tag = "7")]
+    pub namespace: ::prost::alloc::string::String,
+    /// The only worker ID the token enrolls (empty = any).
+    #[prost(string, tag = "8")]
+    pub bound_worker_id: ::prost::alloc::string::String,
 }
-/// Status update for a run
+/// Request to create a bootstrap token.
 #[derive(Clone, PartialEq, ::prost::Message)]
-pub struct RunStatusUpdate {
-    /// Run identifier
+pub struct CreateBootstrapTokenRequest {
+    /// What the token is for (e.g. the host it is handed to).
     #[prost(string, tag = "1")]
-    pub run_id: ::prost::alloc::string::String,
-    /// New status
-    #[prost(enumeration = "RunStatus", tag = "2")]
-    pub status: i32,
-    /// Error message (populated when status is FAILED)
+    pub description: ::prost::alloc::string::String,
+    /// Hours until the token expires (0 = 1 hour).
+    #[prost(uint32, tag = "2")]
+    pub validity_hours: u32,
+    /// Namespace the enrolled worker is bound to (empty = default).
     #[prost(string, tag = "3")]
-    pub error_message: ::prost::alloc::string::String,
-    /// Model backend actually used for this run
-    #[prost(message, optional, tag = "4")]
-    pub backend_used: ::core::option::Option<ModelBackend>,
-    /// Unix timestamp (milliseconds) of this update
-    #[prost(int64, tag = "5")]
-    pub timestamp_ms: i64,
+    pub namespace: ::prost::alloc::string::String,
+    /// The only worker ID the token enrolls (empty = any).
+    #[prost(string, tag = "4")]
+    pub worker_id: ::prost::alloc::string::String,
 }
-/// Streaming output chunk from a run
+/// Response containing a newly created bootstrap token.
 #[derive(Clone, PartialEq, ::prost::Message)]
-pub struct RunOutputChunk {
-    /// Run identifier
-    #[prost(string, tag = "1")]
-    pub run_id: ::prost::alloc::string::String,
-    /// Sequence number for ordering (0-indexed)
-    #[prost(uint64, tag = "2")]
-    pub seq: u64,
-    /// Content of this chunk (text tokens, etc.)
-    #[prost(string, tag = "3")]
-    pub content: ::prost::alloc::string::String,
-    /// Whether this is the final chunk
-    #[prost(bool, tag = "4")]
-    pub is_final: bool,
-    /// Chunk metadata (role, content_type, etc.)
-    #[prost(map = "string, string", tag = "5")]
-    pub metadata: ::std::collections::HashMap<
-        ::prost::alloc::string::String,
-        ::prost::alloc::string::String,
-    >,
-    /// Unix timestamp (milliseconds) when chunk was produced
-    #[prost(int64, tag = "6")]
-    pub timestamp_ms: i64,
+pub struct CreateBootstrapTokenResponse {
+    /// The stored token.
+    #[prost(message, optional, tag = "1")]
+    pub token: ::core::option::Option<BootstrapToken>,
+    /// Plaintext token to pass to the worker (`--enroll-token`).
+    #[prost(string, tag = "2")]
+    pub bootstrap_token: ::prost::alloc::string::String,
 }
-/// Wrapper for all messages from control plane to worker
+/// Request to list bootstrap tokens.
+#[derive(Clone, Copy, PartialEq, ::prost::Message)]
+pub struct ListBootstrapTokensRequest {}
+/// Response containing bootstrap tokens.
 #[derive(Clone, PartialEq, ::prost::Message)]
-pub struct RunServerMessage {
-    #[prost(oneof = "run_server_message::Payload", tags = "1, 2, 3, 4")]
-    pub payload: ::core::option::Option<run_server_message::Payload>,
-}
-/// Nested message and enum types in `RunServerMessage`.
-pub mod run_server_message {
-    #[derive(Clone, PartialEq, ::prost::Oneof)]
-    pub enum Payload {
-        #[prost(message, tag = "1")]
-        AssignRun(super::RunAssignment),
-        #[prost(message, tag = "2")]
-        CancelRun(super::CancelRun),
-        #[prost(message, tag = "3")]
-        Ack(super::ServerAck),
-        #[prost(message, tag = "4")]
-        ContinueRun(super::ContinueRun),
-    }
+pub struct ListBootstrapTokensResponse {
+    /// Tokens, oldest first.
+    #[prost(message, repeated, tag = "1")]
+    pub tokens: ::prost::alloc::vec::Vec<BootstrapToken>,
 }
-/// Assignment of a run to a worker
+/// Request to revoke a bootstrap token.
 #[derive(Clone, PartialEq, ::prost::Message)]
-pub struct RunAssignment {
-    /// Unique run identifier
+pub struct RevokeBootstrapTokenRequest {
+    /// Token ID (or unique prefix).
     #[prost(string, tag = "1")]
-    pub run_id: ::prost::alloc::string::String,
-    /// Associated task identifier
-    #[prost(string, tag = "2")]
-    pub task_id: ::prost::alloc::string::String,
-    /// Name of agent to execute
-    #[prost(string, tag = "3")]
-    pub agent_name: ::prost::alloc::string::String,
-    /// Input payload as JSON string
-    #[prost(string, tag = "4")]
-    pub input_json: ::prost::alloc::string::String,
-    /// Task/run labels
-    #[prost(map = "string, string", tag = "5")]
-    pub labels: ::std::collections::HashMap<
-        ::prost::alloc::string::String,
-        ::prost::alloc::string::String,
-    >,
-    /// Unix timestamp (milliseconds) when assignment was issued
-    #[prost(int64, tag = "6")]
-    pub issued_at_ms: i64,
-    /// Optional deadline for the run (Unix timestamp ms, 0 = no deadline)
-    #[prost(int64, tag = "7")]
-    pub deadline_ms: i64,
+    pub id: ::prost::alloc::string::String,
 }
-/// Request to cancel an in-progress run
+/// A revoked certificate, or all certificates of a worker.
 #[derive(Clone, PartialEq, ::prost::Message)]
-pub struct CancelRun {
-    /// Run identifier to cancel
+pub struct RevokedCertificate {
+    /// Serial number in hex (empty when revoking by worker).
     #[prost(string, tag = "1")]
-    pub run_id: ::prost::alloc::string::String,
-    /// Reason for cancellation
+    pub serial: ::prost::alloc::string::String,
+    /// Worker whose certificates are all revoked (empty when revoking by serial).
     #[prost(string, tag = "2")]
+    pub worker_id: ::prost::alloc::string::String,
+    /// Revocation timestamp in milliseconds since epoch.
+    #[prost(int64, tag = "3")]
+    pub revoked_at_ms: i64,
+    /// Why the certificate was revoked.
+    #[prost(string, tag = "4")]
     pub reason: ::prost::alloc::string::String,
 }
-/// Server acknowledgment (optional, for protocol reliability)
+/// Request to revoke a certificate. Set serial, worker_id or both.
 #[derive(Clone, PartialEq, ::prost::Message)]
-pub struct ServerAck {
-    /// Type of message being acknowledged
+pub struct RevokeCertificateRequest {
+    /// Certificate serial number in hex (colons allowed).
     #[prost(string, tag = "1")]
-    pub ack_type: ::prost::alloc::string::String,
-    /// Relevant identifier (run_id, etc.)
+    pub serial: ::prost::alloc::string::String,
+    /// Worker ID, revoking all of its certificates.
     #[prost(string, tag = "2")]
-    pub ref_id: ::prost::alloc::string::String,
+    pub worker_id: ::prost::alloc::string::String,
+    /// Why the certificate is revoked.
+    #[prost(string, tag = "3")]
+    pub reason: ::prost::alloc::string::String,
 }
-/// Request to continue an in-progress run with a follow-up message
+/// Request to list revoked certificates.
+#[derive(Clone, Copy, PartialEq, ::prost::Message)]
+pub struct ListRevokedCertificatesRequest {}
+/// Response containing revoked certificates.
 #[derive(Clone, PartialEq, ::prost::Message)]
-pub struct ContinueRun {
-    /// Run identifier to continue
-    #[prost(string, tag = "1")]
-    pub run_id: ::prost::alloc::string::String,
-    /// The follow-up message content
-    #[prost(string, tag = "2")]
-    pub message: ::prost::alloc::string::String,
-    /// Unix timestamp (milliseconds) when request was sent
-    #[prost(int64, tag = "3")]
-    pub timestamp_ms: i64,
+pub struct ListRevokedCertificatesResponse {
+    /// Revocations, oldest first.
+    #[prost(message, repeated, tag = "1")]
+    pub certificates: ::prost::alloc::vec::Vec<RevokedCertificate>,
 }
 /// Generated client implementations.
-pub mod run_service_client {
+pub mod admin_service_client {
     #![allow(
         unused_variables,
         dead_code,
@@ -503,11 +722,16 @@ pub mod run_service_client {
     )]
     use tonic::codegen::*;
     use tonic::codegen::http::Uri;
+    /// AdminService exports and imports control plane state: tasks with their
+    /// runs, run events, and worker enrollment (bootstrap token) records. It also
+    /// reads and changes the runtime toggles (maintenance mode, scheduling
+    /// freeze, default priority) and manages worker bootstrap tokens.
+    /// Every method requires an admin client certificate.
     #[derive(Debug, Clone)]
-    pub struct RunServiceClient<T> {
+    pub struct AdminServiceClient<T> {
         inner: tonic::client::Grpc<T>,
     }
-    impl RunServiceClient<tonic::transport::Channel> {
+    impl AdminServiceClient<tonic::transport::Channel> {
         /// Attempt to create a new client by connecting to a given endpoint.
         pub async fn connect<D>(dst: D) -> Result<Self, tonic::transport::Error>
         where
@@ -518,7 +742,7 @@ pub mod run_service_client {
             Ok(Self::new(conn))
         }
     }
-    impl<T> RunServiceClient<T>
+    impl<T> AdminServiceClient<T>
     where
         T: tonic::client::GrpcService<tonic::body::BoxBody>,
         T::Error: Into<StdError>,
@@ -536,7 +760,7 @@ pub mod run_service_client {
         pub fn with_interceptor<F>(
             inner: T,
             interceptor: F,
-        ) -> RunServiceClient<InterceptedService<T, F>>
+        ) -> AdminServiceClient<InterceptedService<T, F>>
         where
             F: tonic::service::Interceptor,
             T::ResponseBody: Default,
@@ -550,7 +774,7 @@ pub mod run_service_client {
                 http::Request<tonic::body::BoxBody>,
             >>::Error: Into<StdError> + std::marker::Send + std::marker::Sync,
         {
-            RunServiceClient::new(InterceptedService::new(inner, interceptor))
+            AdminServiceClient::new(InterceptedService::new(inner, interceptor))
         }
         /// Compress requests with the given encoding.
         ///
@@ -583,18 +807,12 @@ pub mod run_service_client {
             self.inner = self.inner.max_encoding_message_size(limit);
             self
         }
-        /// Bidirectional streaming connection for worker communication
-        /// Workers connect and maintain a persistent stream for:
-        /// - Announcing capabilities (WorkerHello)
-        /// - Receiving run assignments (RunAssignment)
-        /// - Streaming output (RunOutputChunk)
-        /// - Reporting status (RunStatusUpdate)
-        /// - Health checks (WorkerHeartbeat)
-        pub async fn stream_connect(
+        /// Export the state as a versioned JSON archive, streamed in chunks.
+        pub async fn export_state(
             &mut self,
-            request: impl tonic::IntoStreamingRequest<Message = super::RunClientMessage>,
+            request: impl tonic::IntoRequest<super::ExportStateRequest>,
         ) -> std::result::Result<
-            tonic::Response<tonic::codec::Streaming<super::RunServerMessage>>,
+            tonic::Response<tonic::codec::Streaming<super::StateChunk>>,
             tonic::Status,
         > {
             self.inner
@@ -607,17 +825,222 @@ pub mod run_service_client {
                 })?;
             let codec = tonic::codec::ProstCodec::default();
             let path = http::uri::PathAndQuery::from_static(
-                "/taskrun.v1.RunService/StreamConnect",
+                "/taskrun.v1.AdminService/ExportState",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("taskrun.v1.AdminService", "ExportState"));
+            self.inner.server_streaming(req, path, codec).await
+        }
+        /// Import an archive produced by ExportState, streamed in chunks.
+        /// Tasks that already exist are skipped.
+        pub async fn import_state(
+            &mut self,
+            request: impl tonic::IntoStreamingRequest<Message = super::StateChunk>,
+        ) -> std::result::Result<
+            tonic::Response<super::ImportStateResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/taskrun.v1.AdminService/ImportState",
             );
             let mut req = request.into_streaming_request();
             req.extensions_mut()
-                .insert(GrpcMethod::new("taskrun.v1.RunService", "StreamConnect"));
-            self.inner.streaming(req, path, codec).await
+                .insert(GrpcMethod::new("taskrun.v1.AdminService", "ImportState"));
+            self.inner.client_streaming(req, path, codec).await
+        }
+        /// Get the runtime toggles.
+        pub async fn get_toggles(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetTogglesRequest>,
+        ) -> std::result::Result<tonic::Response<super::RuntimeToggles>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/taskrun.v1.AdminService/GetToggles",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("taskrun.v1.AdminService", "GetToggles"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// Change runtime toggles. Takes effect immediately and is kept across
+        /// restarts when the server has a toggles file.
+        pub async fn update_toggles(
+            &mut self,
+            request: impl tonic::IntoRequest<super::UpdateTogglesRequest>,
+        ) -> std::result::Result<tonic::Response<super::RuntimeToggles>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/taskrun.v1.AdminService/UpdateToggles",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("taskrun.v1.AdminService", "UpdateToggles"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// Create a single-use bootstrap token for enrolling a worker at
+        /// POST /v1/enroll. The plaintext token is only returned here.
+        pub async fn create_bootstrap_token(
+            &mut self,
+            request: impl tonic::IntoRequest<super::CreateBootstrapTokenRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::CreateBootstrapTokenResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/taskrun.v1.AdminService/CreateBootstrapToken",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new("taskrun.v1.AdminService", "CreateBootstrapToken"),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        /// List bootstrap tokens. Tokens themselves are never returned.
+        pub async fn list_bootstrap_tokens(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ListBootstrapTokensRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ListBootstrapTokensResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/taskrun.v1.AdminService/ListBootstrapTokens",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new("taskrun.v1.AdminService", "ListBootstrapTokens"),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        /// Revoke a bootstrap token. Workers already enrolled with it are not
+        /// affected.
+        pub async fn revoke_bootstrap_token(
+            &mut self,
+            request: impl tonic::IntoRequest<super::RevokeBootstrapTokenRequest>,
+        ) -> std::result::Result<tonic::Response<super::BootstrapToken>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/taskrun.v1.AdminService/RevokeBootstrapToken",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new("taskrun.v1.AdminService", "RevokeBootstrapToken"),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        /// Revoke a worker certificate by serial number, or all certificates of a
+        /// worker by its ID. Revoked certificates are refused on every call and
+        /// listed in the CRL at GET /v1/crl; a revoked worker is disconnected.
+        pub async fn revoke_certificate(
+            &mut self,
+            request: impl tonic::IntoRequest<super::RevokeCertificateRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::RevokedCertificate>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/taskrun.v1.AdminService/RevokeCertificate",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("taskrun.v1.AdminService", "RevokeCertificate"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// List revoked certificates.
+        pub async fn list_revoked_certificates(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ListRevokedCertificatesRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ListRevokedCertificatesResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/taskrun.v1.AdminService/ListRevokedCertificates",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new("taskrun.v1.AdminService", "ListRevokedCertificates"),
+                );
+            self.inner.unary(req, path, codec).await
         }
     }
 }
 /// Generated server implementations.
-pub mod run_service_server {
+pub mod admin_service_server {
     #![allow(
         unused_variables,
         dead_code,
@@ -626,39 +1049,99 @@ pub mod run_service_server {
         clippy::let_unit_value,
     )]
     use tonic::codegen::*;
-    /// Generated trait containing gRPC methods that should be implemented for use with RunServiceServer.
+    /// Generated trait containing gRPC methods that should be implemented for use with AdminServiceServer.
     #[async_trait]
-    pub trait RunService: std::marker::Send + std::marker::Sync + 'static {
-        /// Server streaming response type for the StreamConnect method.
-        type StreamConnectStream: tonic::codegen::tokio_stream::Stream<
-                Item = std::result::Result<super::RunServerMessage, tonic::Status>,
+    pub trait AdminService: std::marker::Send + std::marker::Sync + 'static {
+        /// Server streaming response type for the ExportState method.
+        type ExportStateStream: tonic::codegen::tokio_stream::Stream<
+                Item = std::result::Result<super::StateChunk, tonic::Status>,
             >
             + std::marker::Send
             + 'static;
-        /// Bidirectional streaming connection for worker communication
-        /// Workers connect and maintain a persistent stream for:
-        /// - Announcing capabilities (WorkerHello)
-        /// - Receiving run assignments (RunAssignment)
-        /// - Streaming output (RunOutputChunk)
-        /// - Reporting status (RunStatusUpdate)
-        /// - Health checks (WorkerHeartbeat)
-        async fn stream_connect(
+        /// Export the state as a versioned JSON archive, streamed in chunks.
+        async fn export_state(
             &self,
-            request: tonic::Request<tonic::Streaming<super::RunClientMessage>>,
+            request: tonic::Request<super::ExportStateRequest>,
         ) -> std::result::Result<
-            tonic::Response<Self::StreamConnectStream>,
+            tonic::Response<Self::ExportStateStream>,
+            tonic::Status,
+        >;
+        /// Import an archive produced by ExportState, streamed in chunks.
+        /// Tasks that already exist are skipped.
+        async fn import_state(
+            &self,
+            request: tonic::Request<tonic::Streaming<super::StateChunk>>,
+        ) -> std::result::Result<
+            tonic::Response<super::ImportStateResponse>,
+            tonic::Status,
+        >;
+        /// Get the runtime toggles.
+        async fn get_toggles(
+            &self,
+            request: tonic::Request<super::GetTogglesRequest>,
+        ) -> std::result::Result<tonic::Response<super::RuntimeToggles>, tonic::Status>;
+        /// Change runtime toggles. Takes effect immediately and is kept across
+        /// restarts when the server has a toggles file.
+        async fn update_toggles(
+            &self,
+            request: tonic::Request<super::UpdateTogglesRequest>,
+        ) -> std::result::Result<tonic::Response<super::RuntimeToggles>, tonic::Status>;
+        /// Create a single-use bootstrap token for enrolling a worker at
+        /// POST /v1/enroll. The plaintext token is only returned here.
+        async fn create_bootstrap_token(
+            &self,
+            request: tonic::Request<super::CreateBootstrapTokenRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::CreateBootstrapTokenResponse>,
+            tonic::Status,
+        >;
+        /// List bootstrap tokens. Tokens themselves are never returned.
+        async fn list_bootstrap_tokens(
+            &self,
+            request: tonic::Request<super::ListBootstrapTokensRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ListBootstrapTokensResponse>,
+            tonic::Status,
+        >;
+        /// Revoke a bootstrap token. Workers already enrolled with it are not
+        /// affected.
+        async fn revoke_bootstrap_token(
+            &self,
+            request: tonic::Request<super::RevokeBootstrapTokenRequest>,
+        ) -> std::result::Result<tonic::Response<super::BootstrapToken>, tonic::Status>;
+        /// Revoke a worker certificate by serial number, or all certificates of a
+        /// worker by its ID. Revoked certificates are refused on every call and
+        /// listed in the CRL at GET /v1/crl; a revoked worker is disconnected.
+        async fn revoke_certificate(
+            &self,
+            request: tonic::Request<super::RevokeCertificateRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::RevokedCertificate>,
+            tonic::Status,
+        >;
+        /// List revoked certificates.
+        async fn list_revoked_certificates(
+            &self,
+            request: tonic::Request<super::ListRevokedCertificatesRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ListRevokedCertificatesResponse>,
             tonic::Status,
         >;
     }
+    /// AdminService exports and imports control plane state: tasks with their
+    /// runs, run events, and worker enrollment (bootstrap token) records. It also
+    /// reads and changes the runtime toggles (maintenance mode, scheduling
+    /// freeze, default priority) and manages worker bootstrap tokens.
+    /// Every method requires an admin client certificate.
     #[derive(Debug)]
-    pub struct RunServiceServer<T> {
+    pub struct AdminServiceServer<T> {
         inner: Arc<T>,
         accept_compression_encodings: EnabledCompressionEncodings,
         send_compression_encodings: EnabledCompressionEncodings,
         max_decoding_message_size: Option<usize>,
         max_encoding_message_size: Option<usize>,
     }
-    impl<T> RunServiceServer<T> {
+    impl<T> AdminServiceServer<T> {
         pub fn new(inner: T) -> Self {
             Self::from_arc(Arc::new(inner))
         }
@@ -709,9 +1192,9 @@ pub mod run_service_server {
             self
         }
     }
-    impl<T, B> tonic::codegen::Service<http::Request<B>> for RunServiceServer<T>
+    impl<T, B> tonic::codegen::Service<http::Request<B>> for AdminServiceServer<T>
     where
-        T: RunService,
+        T: AdminService,
         B: Body + std::marker::Send + 'static,
         B::Error: Into<StdError> + std::marker::Send + 'static,
     {
@@ -726,28 +1209,26 @@ pub mod run_service_server {
         }
         fn call(&mut self, req: http::Request<B>) -> Self::Future {
             match req.uri().path() {
-                "/taskrun.v1.RunService/StreamConnect" => {
+                "/taskrun.v1.AdminService/ExportState" => {
                     #[allow(non_camel_case_types)]
-                    struct StreamConnectSvc<T: RunService>(pub Arc<T>);
+                    struct ExportStateSvc<T: AdminService>(pub Arc<T>);
                     impl<
-                        T: RunService,
-                    > tonic::server::StreamingService<super::RunClientMessage>
-                    for StreamConnectSvc<T> {
-                        type Response = super::RunServerMessage;
-                        type ResponseStream = T::StreamConnectStream;
+                        T: AdminService,
+                    > tonic::server::ServerStreamingService<super::ExportStateRequest>
+                    for ExportStateSvc<T> {
+                        type Response = super::StateChunk;
+                        type ResponseStream = T::ExportStateStream;
                         type Future = BoxFuture<
                             tonic::Response<Self::ResponseStream>,
                             tonic::Status,
                         >;
                         fn call(
                             &mut self,
-                            request: tonic::Request<
-                                tonic::Streaming<super::RunClientMessage>,
-                            >,
+                            request: tonic::Request<super::ExportStateRequest>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
                             let fut = async move {
-                                <T as RunService>::stream_connect(&inner, request).await
+                                <T as AdminService>::export_state(&inner, request).await
                             };
                             Box::pin(fut)
                         }
@@ -758,7 +1239,7 @@ pub mod run_service_server {
                     let max_encoding_message_size = self.max_encoding_message_size;
                     let inner = self.inner.clone();
                     let fut = async move {
-                        let method = StreamConnectSvc(inner);
+                        let method = ExportStateSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec)
                             .apply_compression_config(
@@ -769,22 +1250,4362 @@ pub mod run_service_server {
                                 max_decoding_message_size,
                                 max_encoding_message_size,
                             );
-                        let res = grpc.streaming(method, req).await;
+                        let res = grpc.server_streaming(method, req).await;
                         Ok(res)
                     };
                     Box::pin(fut)
                 }
-                _ => {
-                    Box::pin(async move {
-                        let mut response = http::Response::new(empty_body());
-                        let headers = response.headers_mut();
-                        headers
-                            .insert(
-                                tonic::Status::GRPC_STATUS,
-                                (tonic::Code::Unimplemented as i32).into(),
+                "/taskrun.v1.AdminService/ImportState" => {
+                    #[allow(non_camel_case_types)]
+                    struct ImportStateSvc<T: AdminService>(pub Arc<T>);
+                    impl<
+                        T: AdminService,
+                    > tonic::server::ClientStreamingService<super::StateChunk>
+                    for ImportStateSvc<T> {
+                        type Response = super::ImportStateResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<tonic::Streaming<super::StateChunk>>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as AdminService>::import_state(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = ImportStateSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
                             );
-                        headers
-                            .insert(
+                        let res = grpc.client_streaming(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/taskrun.v1.AdminService/GetToggles" => {
+                    #[allow(non_camel_case_types)]
+                    struct GetTogglesSvc<T: AdminService>(pub Arc<T>);
+                    impl<
+                        T: AdminService,
+                    > tonic::server::UnaryService<super::GetTogglesRequest>
+                    for GetTogglesSvc<T> {
+                        type Response = super::RuntimeToggles;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::GetTogglesRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as AdminService>::get_toggles(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = GetTogglesSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/taskrun.v1.AdminService/UpdateToggles" => {
+                    #[allow(non_camel_case_types)]
+                    struct UpdateTogglesSvc<T: AdminService>(pub Arc<T>);
+                    impl<
+                        T: AdminService,
+                    > tonic::server::UnaryService<super::UpdateTogglesRequest>
+                    for UpdateTogglesSvc<T> {
+                        type Response = super::RuntimeToggles;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::UpdateTogglesRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as AdminService>::update_toggles(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = UpdateTogglesSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/taskrun.v1.AdminService/CreateBootstrapToken" => {
+                    #[allow(non_camel_case_types)]
+                    struct CreateBootstrapTokenSvc<T: AdminService>(pub Arc<T>);
+                    impl<
+                        T: AdminService,
+                    > tonic::server::UnaryService<super::CreateBootstrapTokenRequest>
+                    for CreateBootstrapTokenSvc<T> {
+                        type Response = super::CreateBootstrapTokenResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::CreateBootstrapTokenRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as AdminService>::create_bootstrap_token(&inner, request)
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = CreateBootstrapTokenSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/taskrun.v1.AdminService/ListBootstrapTokens" => {
+                    #[allow(non_camel_case_types)]
+                    struct ListBootstrapTokensSvc<T: AdminService>(pub Arc<T>);
+                    impl<
+                        T: AdminService,
+                    > tonic::server::UnaryService<super::ListBootstrapTokensRequest>
+                    for ListBootstrapTokensSvc<T> {
+                        type Response = super::ListBootstrapTokensResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ListBootstrapTokensRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as AdminService>::list_bootstrap_tokens(&inner, request)
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = ListBootstrapTokensSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/taskrun.v1.AdminService/RevokeBootstrapToken" => {
+                    #[allow(non_camel_case_types)]
+                    struct RevokeBootstrapTokenSvc<T: AdminService>(pub Arc<T>);
+                    impl<
+                        T: AdminService,
+                    > tonic::server::UnaryService<super::RevokeBootstrapTokenRequest>
+                    for RevokeBootstrapTokenSvc<T> {
+                        type Response = super::BootstrapToken;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::RevokeBootstrapTokenRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as AdminService>::revoke_bootstrap_token(&inner, request)
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = RevokeBootstrapTokenSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/taskrun.v1.AdminService/RevokeCertificate" => {
+                    #[allow(non_camel_case_types)]
+                    struct RevokeCertificateSvc<T: AdminService>(pub Arc<T>);
+                    impl<
+                        T: AdminService,
+                    > tonic::server::UnaryService<super::RevokeCertificateRequest>
+                    for RevokeCertificateSvc<T> {
+                        type Response = super::RevokedCertificate;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::RevokeCertificateRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as AdminService>::revoke_certificate(&inner, request)
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = RevokeCertificateSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/taskrun.v1.AdminService/ListRevokedCertificates" => {
+                    #[allow(non_camel_case_types)]
+                    struct ListRevokedCertificatesSvc<T: AdminService>(pub Arc<T>);
+                    impl<
+                        T: AdminService,
+                    > tonic::server::UnaryService<super::ListRevokedCertificatesRequest>
+                    for ListRevokedCertificatesSvc<T> {
+                        type Response = super::ListRevokedCertificatesResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<
+                                super::ListRevokedCertificatesRequest,
+                            >,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as AdminService>::list_revoked_certificates(
+                                        &inner,
+                                        request,
+                                    )
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = ListRevokedCertificatesSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                _ => {
+                    Box::pin(async move {
+                        let mut response = http::Response::new(empty_body());
+                        let headers = response.headers_mut();
+                        headers
+                            .insert(
+                                tonic::Status::GRPC_STATUS,
+                                (tonic::Code::Unimplemented as i32).into(),
+                            );
+                        headers
+                            .insert(
+                                http::header::CONTENT_TYPE,
+                                tonic::metadata::GRPC_CONTENT_TYPE,
+                            );
+                        Ok(response)
+                    })
+                }
+            }
+        }
+    }
+    impl<T> Clone for AdminServiceServer<T> {
+        fn clone(&self) -> Self {
+            let inner = self.inner.clone();
+            Self {
+                inner,
+                accept_compression_encodings: self.accept_compression_encodings,
+                send_compression_encodings: self.send_compression_encodings,
+                max_decoding_message_size: self.max_decoding_message_size,
+                max_encoding_message_size: self.max_encoding_message_size,
+            }
+        }
+    }
+    /// Generated gRPC service name
+    pub const SERVICE_NAME: &str = "taskrun.v1.AdminService";
+    impl<T> tonic::server::NamedService for AdminServiceServer<T> {
+        const NAME: &'static str = SERVICE_NAME;
+    }
+}
+/// A stored API key.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ApiKey {
+    /// Unique key identifier.
+    #[prost(string, tag = "1")]
+    pub id: ::prost::alloc::string::String,
+    /// Human-readable name (who or what uses the key).
+    #[prost(string, tag = "2")]
+    pub name: ::prost::alloc::string::String,
+    /// First characters of the plaintext key, for recognizing it.
+    #[prost(string, tag = "3")]
+    pub prefix: ::prost::alloc::string::String,
+    /// Creation timestamp in milliseconds since epoch.
+    #[prost(int64, tag = "5")]
+    pub created_at_ms: i64,
+    /// Role of the key: "readonly", "operator" or "admin".
+    #[prost(string, tag = "6")]
+    pub role: ::prost::alloc::string::String,
+    /// Namespace the key is bound to (empty = all namespaces).
+    #[prost(string, tag = "7")]
+    pub namespace: ::prost::alloc::string::String,
+}
+/// Request to create an API key.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CreateApiKeyRequest {
+    /// Human-readable name.
+    #[prost(string, tag = "1")]
+    pub name: ::prost::alloc::string::String,
+    /// Role of the key: "readonly", "operator" or "admin" (empty = operator).
+    #[prost(string, tag = "3")]
+    pub role: ::prost::alloc::string::String,
+    /// Bind the key to a namespace (empty = all namespaces).
+    #[prost(string, tag = "4")]
+    pub namespace: ::prost::alloc::string::String,
+}
+/// Response containing a newly created API key.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CreateApiKeyResponse {
+    /// The stored key.
+    #[prost(message, optional, tag = "1")]
+    pub api_key: ::core::option::Option<ApiKey>,
+    /// Plaintext key to send as `Authorization: Bearer <key>`.
+    #[prost(string, tag = "2")]
+    pub key: ::prost::alloc::string::String,
+}
+/// Request to list API keys.
+#[derive(Clone, Copy, PartialEq, ::prost::Message)]
+pub struct ListApiKeysRequest {}
+/// Response containing API keys.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListApiKeysResponse {
+    /// Keys, oldest first.
+    #[prost(message, repeated, tag = "1")]
+    pub api_keys: ::prost::alloc::vec::Vec<ApiKey>,
+}
+/// Request to revoke an API key.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RevokeApiKeyRequest {
+    /// Key ID (or unique prefix).
+    #[prost(string, tag = "1")]
+    pub id: ::prost::alloc::string::String,
+}
+/// Generated client implementations.
+pub mod api_key_service_client {
+    #![allow(
+        unused_variables,
+        dead_code,
+        missing_docs,
+        clippy::wildcard_imports,
+        clippy::let_unit_value,
+    )]
+    use tonic::codegen::*;
+    use tonic::codegen::http::Uri;
+    /// ApiKeyService manages the keys that authenticate HTTP API callers.
+    /// It is only reachable over mTLS with an admin client certificate, so it can
+    /// create the first admin key.
+    #[derive(Debug, Clone)]
+    pub struct ApiKeyServiceClient<T> {
+        inner: tonic::client::Grpc<T>,
+    }
+    impl ApiKeyServiceClient<tonic::transport::Channel> {
+        /// Attempt to create a new client by connecting to a given endpoint.
+        pub async fn connect<D>(dst: D) -> Result<Self, tonic::transport::Error>
+        where
+            D: TryInto<tonic::transport::Endpoint>,
+            D::Error: Into<StdError>,
+        {
+            let conn = tonic::transport::Endpoint::new(dst)?.connect().await?;
+            Ok(Self::new(conn))
+        }
+    }
+    impl<T> ApiKeyServiceClient<T>
+    where
+        T: tonic::client::GrpcService<tonic::body::BoxBody>,
+        T::Error: Into<StdError>,
+        T::ResponseBody: Body<Data = Bytes> + std::marker::Send + 'static,
+        <T::ResponseBody as Body>::Error: Into<StdError> + std::marker::Send,
+    {
+        pub fn new(inner: T) -> Self {
+            let inner = tonic::client::Grpc::new(inner);
+            Self { inner }
+        }
+        pub fn with_origin(inner: T, origin: Uri) -> Self {
+            let inner = tonic::client::Grpc::with_origin(inner, origin);
+            Self { inner }
+        }
+        pub fn with_interceptor<F>(
+            inner: T,
+            interceptor: F,
+        ) -> ApiKeyServiceClient<InterceptedService<T, F>>
+        where
+            F: tonic::service::Interceptor,
+            T::ResponseBody: Default,
+            T: tonic::codegen::Service<
+                http::Request<tonic::body::BoxBody>,
+                Response = http::Response<
+                    <T as tonic::client::GrpcService<tonic::body::BoxBody>>::ResponseBody,
+                >,
+            >,
+            <T as tonic::codegen::Service<
+                http::Request<tonic::body::BoxBody>,
+            >>::Error: Into<StdError> + std::marker::Send + std::marker::Sync,
+        {
+            ApiKeyServiceClient::new(InterceptedService::new(inner, interceptor))
+        }
+        /// Compress requests with the given encoding.
+        ///
+        /// This requires the server to support it otherwise it might respond with an
+        /// error.
+        #[must_use]
+        pub fn send_compressed(mut self, encoding: CompressionEncoding) -> Self {
+            self.inner = self.inner.send_compressed(encoding);
+            self
+        }
+        /// Enable decompressing responses.
+        #[must_use]
+        pub fn accept_compressed(mut self, encoding: CompressionEncoding) -> Self {
+            self.inner = self.inner.accept_compressed(encoding);
+            self
+        }
+        /// Limits the maximum size of a decoded message.
+        ///
+        /// Default: `4MB`
+        #[must_use]
+        pub fn max_decoding_message_size(mut self, limit: usize) -> Self {
+            self.inner = self.inner.max_decoding_message_size(limit);
+            self
+        }
+        /// Limits the maximum size of an encoded message.
+        ///
+        /// Default: `usize::MAX`
+        #[must_use]
+        pub fn max_encoding_message_size(mut self, limit: usize) -> Self {
+            self.inner = self.inner.max_encoding_message_size(limit);
+            self
+        }
+        /// Create an API key. The plaintext key is only returned here.
+        pub async fn create_api_key(
+            &mut self,
+            request: impl tonic::IntoRequest<super::CreateApiKeyRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::CreateApiKeyResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/taskrun.v1.ApiKeyService/CreateApiKey",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("taskrun.v1.ApiKeyService", "CreateApiKey"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// List API keys. Keys themselves are never returned.
+        pub async fn list_api_keys(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ListApiKeysRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ListApiKeysResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/taskrun.v1.ApiKeyService/ListApiKeys",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("taskrun.v1.ApiKeyService", "ListApiKeys"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// Revoke an API key.
+        pub async fn revoke_api_key(
+            &mut self,
+            request: impl tonic::IntoRequest<super::RevokeApiKeyRequest>,
+        ) -> std::result::Result<tonic::Response<super::ApiKey>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/taskrun.v1.ApiKeyService/RevokeApiKey",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("taskrun.v1.ApiKeyService", "RevokeApiKey"));
+            self.inner.unary(req, path, codec).await
+        }
+    }
+}
+/// Generated server implementations.
+pub mod api_key_service_server {
+    #![allow(
+        unused_variables,
+        dead_code,
+        missing_docs,
+        clippy::wildcard_imports,
+        clippy::let_unit_value,
+    )]
+    use tonic::codegen::*;
+    /// Generated trait containing gRPC methods that should be implemented for use with ApiKeyServiceServer.
+    #[async_trait]
+    pub trait ApiKeyService: std::marker::Send + std::marker::Sync + 'static {
+        /// Create an API key. The plaintext key is only returned here.
+        async fn create_api_key(
+            &self,
+            request: tonic::Request<super::CreateApiKeyRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::CreateApiKeyResponse>,
+            tonic::Status,
+        >;
+        /// List API keys. Keys themselves are never returned.
+        async fn list_api_keys(
+            &self,
+            request: tonic::Request<super::ListApiKeysRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ListApiKeysResponse>,
+            tonic::Status,
+        >;
+        /// Revoke an API key.
+        async fn revoke_api_key(
+            &self,
+            request: tonic::Request<super::RevokeApiKeyRequest>,
+        ) -> std::result::Result<tonic::Response<super::ApiKey>, tonic::Status>;
+    }
+    /// ApiKeyService manages the keys that authenticate HTTP API callers.
+    /// It is only reachable over mTLS with an admin client certificate, so it can
+    /// create the first admin key.
+    #[derive(Debug)]
+    pub struct ApiKeyServiceServer<T> {
+        inner: Arc<T>,
+        accept_compression_encodings: EnabledCompressionEncodings,
+        send_compression_encodings: EnabledCompressionEncodings,
+        max_decoding_message_size: Option<usize>,
+        max_encoding_message_size: Option<usize>,
+    }
+    impl<T> ApiKeyServiceServer<T> {
+        pub fn new(inner: T) -> Self {
+            Self::from_arc(Arc::new(inner))
+        }
+        pub fn from_arc(inner: Arc<T>) -> Self {
+            Self {
+                inner,
+                accept_compression_encodings: Default::default(),
+                send_compression_encodings: Default::default(),
+                max_decoding_message_size: None,
+                max_encoding_message_size: None,
+            }
+        }
+        pub fn with_interceptor<F>(
+            inner: T,
+            interceptor: F,
+        ) -> InterceptedService<Self, F>
+        where
+            F: tonic::service::Interceptor,
+        {
+            InterceptedService::new(Self::new(inner), interceptor)
+        }
+        /// Enable decompressing requests with the given encoding.
+        #[must_use]
+        pub fn accept_compressed(mut self, encoding: CompressionEncoding) -> Self {
+            self.accept_compression_encodings.enable(encoding);
+            self
+        }
+        /// Compress responses with the given encoding, if the client supports it.
+        #[must_use]
+        pub fn send_compressed(mut self, encoding: CompressionEncoding) -> Self {
+            self.send_compression_encodings.enable(encoding);
+            self
+        }
+        /// Limits the maximum size of a decoded message.
+        ///
+        /// Default: `4MB`
+        #[must_use]
+        pub fn max_decoding_message_size(mut self, limit: usize) -> Self {
+            self.max_decoding_message_size = Some(limit);
+            self
+        }
+        /// Limits the maximum size of an encoded message.
+        ///
+        /// Default: `usize::MAX`
+        #[must_use]
+        pub fn max_encoding_message_size(mut self, limit: usize) -> Self {
+            self.max_encoding_message_size = Some(limit);
+            self
+        }
+    }
+    impl<T, B> tonic::codegen::Service<http::Request<B>> for ApiKeyServiceServer<T>
+    where
+        T: ApiKeyService,
+        B: Body + std::marker::Send + 'static,
+        B::Error: Into<StdError> + std::marker::Send + 'static,
+    {
+        type Response = http::Response<tonic::body::BoxBody>;
+        type Error = std::convert::Infallible;
+        type Future = BoxFuture<Self::Response, Self::Error>;
+        fn poll_ready(
+            &mut self,
+            _cx: &mut Context<'_>,
+        ) -> Poll<std::result::Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+        fn call(&mut self, req: http::Request<B>) -> Self::Future {
+            match req.uri().path() {
+                "/taskrun.v1.ApiKeyService/CreateApiKey" => {
+                    #[allow(non_camel_case_types)]
+                    struct CreateApiKeySvc<T: ApiKeyService>(pub Arc<T>);
+                    impl<
+                        T: ApiKeyService,
+                    > tonic::server::UnaryService<super::CreateApiKeyRequest>
+                    for CreateApiKeySvc<T> {
+                        type Response = super::CreateApiKeyResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::CreateApiKeyRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as ApiKeyService>::create_api_key(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = CreateApiKeySvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/taskrun.v1.ApiKeyService/ListApiKeys" => {
+                    #[allow(non_camel_case_types)]
+                    struct ListApiKeysSvc<T: ApiKeyService>(pub Arc<T>);
+                    impl<
+                        T: ApiKeyService,
+                    > tonic::server::UnaryService<super::ListApiKeysRequest>
+                    for ListApiKeysSvc<T> {
+                        type Response = super::ListApiKeysResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ListApiKeysRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as ApiKeyService>::list_api_keys(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = ListApiKeysSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/taskrun.v1.ApiKeyService/RevokeApiKey" => {
+                    #[allow(non_camel_case_types)]
+                    struct RevokeApiKeySvc<T: ApiKeyService>(pub Arc<T>);
+                    impl<
+                        T: ApiKeyService,
+                    > tonic::server::UnaryService<super::RevokeApiKeyRequest>
+                    for RevokeApiKeySvc<T> {
+                        type Response = super::ApiKey;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::RevokeApiKeyRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as ApiKeyService>::revoke_api_key(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = RevokeApiKeySvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                _ => {
+                    Box::pin(async move {
+                        let mut response = http::Response::new(empty_body());
+                        let headers = response.headers_mut();
+                        headers
+                            .insert(
+                                tonic::Status::GRPC_STATUS,
+                                (tonic::Code::Unimplemented as i32).into(),
+                            );
+                        headers
+                            .insert(
+                                http::header::CONTENT_TYPE,
+                                tonic::metadata::GRPC_CONTENT_TYPE,
+                            );
+                        Ok(response)
+                    })
+                }
+            }
+        }
+    }
+    impl<T> Clone for ApiKeyServiceServer<T> {
+        fn clone(&self) -> Self {
+            let inner = self.inner.clone();
+            Self {
+                inner,
+                accept_compression_encodings: self.accept_compression_encodings,
+                send_compression_encodings: self.send_compression_encodings,
+                max_decoding_message_size: self.max_decoding_message_size,
+                max_encoding_message_size: self.max_encoding_message_size,
+            }
+        }
+    }
+    /// Generated gRPC service name
+    pub const SERVICE_NAME: &str = "taskrun.v1.ApiKeyService";
+    impl<T> tonic::server::NamedService for ApiKeyServiceServer<T> {
+        const NAME: &'static str = SERVICE_NAME;
+    }
+}
+/// A tool use waiting for an operator.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Approval {
+    /// Unique approval identifier.
+    #[prost(string, tag = "1")]
+    pub id: ::prost::alloc::string::String,
+    /// Run that wants to use the tool.
+    #[prost(string, tag = "2")]
+    pub run_id: ::prost::alloc::string::String,
+    /// Task the run belongs to.
+    #[prost(string, tag = "3")]
+    pub task_id: ::prost::alloc::string::String,
+    /// Worker executing the run.
+    #[prost(string, tag = "4")]
+    pub worker_id: ::prost::alloc::string::String,
+    /// Tool Claude wants to use.
+    #[prost(string, tag = "5")]
+    pub tool_name: ::prost::alloc::string::String,
+    /// Tool input as JSON string.
+    #[prost(string, tag = "6")]
+    pub input_json: ::prost::alloc::string::String,
+    /// When the worker asked, in milliseconds since epoch.
+    #[prost(int64, tag = "7")]
+    pub requested_at_ms: i64,
+    /// When the worker stops waiting and applies the default, in milliseconds since epoch.
+    #[prost(int64, tag = "8")]
+    pub expires_at_ms: i64,
+    /// Whether the tool use is allowed if nobody decides in time.
+    #[prost(bool, tag = "9")]
+    pub default_allow: bool,
+    /// Namespace of the task.
+    #[prost(string, tag = "10")]
+    pub namespace: ::prost::alloc::string::String,
+    /// Agent of the task.
+    #[prost(string, tag = "11")]
+    pub agent_name: ::prost::alloc::string::String,
+}
+/// Request to list pending approvals.
+#[derive(Clone, Copy, PartialEq, ::prost::Message)]
+pub struct ListApprovalsRequest {}
+/// Response containing pending approvals.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListApprovalsResponse {
+    /// Pending approvals, oldest first.
+    #[prost(message, repeated, tag = "1")]
+    pub approvals: ::prost::alloc::vec::Vec<Approval>,
+}
+/// Request to decide a pending approval.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DecideApprovalRequest {
+    /// Approval ID (or unique prefix).
+    #[prost(string, tag = "1")]
+    pub id: ::prost::alloc::string::String,
+    /// Whether the tool use may proceed.
+    #[prost(bool, tag = "2")]
+    pub allowed: bool,
+    /// Optional reason, shown to Claude when denied.
+    #[prost(string, tag = "3")]
+    pub reason: ::prost::alloc::string::String,
+    /// When allowed, also allow later uses of the tool by the task's agent in
+    /// its namespace, until the control plane restarts.
+    #[prost(bool, tag = "4")]
+    pub always_allow: bool,
+}
+/// Generated client implementations.
+pub mod approval_service_client {
+    #![allow(
+        unused_variables,
+        dead_code,
+        missing_docs,
+        clippy::wildcard_imports,
+        clippy::let_unit_value,
+    )]
+    use tonic::codegen::*;
+    use tonic::codegen::http::Uri;
+    /// ApprovalService lists and decides tool uses that workers escalated because
+    /// their policy asks before running the tool.
+    #[derive(Debug, Clone)]
+    pub struct ApprovalServiceClient<T> {
+        inner: tonic::client::Grpc<T>,
+    }
+    impl ApprovalServiceClient<tonic::transport::Channel> {
+        /// Attempt to create a new client by connecting to a given endpoint.
+        pub async fn connect<D>(dst: D) -> Result<Self, tonic::transport::Error>
+        where
+            D: TryInto<tonic::transport::Endpoint>,
+            D::Error: Into<StdError>,
+        {
+            let conn = tonic::transport::Endpoint::new(dst)?.connect().await?;
+            Ok(Self::new(conn))
+        }
+    }
+    impl<T> ApprovalServiceClient<T>
+    where
+        T: tonic::client::GrpcService<tonic::body::BoxBody>,
+        T::Error: Into<StdError>,
+        T::ResponseBody: Body<Data = Bytes> + std::marker::Send + 'static,
+        <T::ResponseBody as Body>::Error: Into<StdError> + std::marker::Send,
+    {
+        pub fn new(inner: T) -> Self {
+            let inner = tonic::client::Grpc::new(inner);
+            Self { inner }
+        }
+        pub fn with_origin(inner: T, origin: Uri) -> Self {
+            let inner = tonic::client::Grpc::with_origin(inner, origin);
+            Self { inner }
+        }
+        pub fn with_interceptor<F>(
+            inner: T,
+            interceptor: F,
+        ) -> ApprovalServiceClient<InterceptedService<T, F>>
+        where
+            F: tonic::service::Interceptor,
+            T::ResponseBody: Default,
+            T: tonic::codegen::Service<
+                http::Request<tonic::body::BoxBody>,
+                Response = http::Response<
+                    <T as tonic::client::GrpcService<tonic::body::BoxBody>>::ResponseBody,
+                >,
+            >,
+            <T as tonic::codegen::Service<
+                http::Request<tonic::body::BoxBody>,
+            >>::Error: Into<StdError> + std::marker::Send + std::marker::Sync,
+        {
+            ApprovalServiceClient::new(InterceptedService::new(inner, interceptor))
+        }
+        /// Compress requests with the given encoding.
+        ///
+        /// This requires the server to support it otherwise it might respond with an
+        /// error.
+        #[must_use]
+        pub fn send_compressed(mut self, encoding: CompressionEncoding) -> Self {
+            self.inner = self.inner.send_compressed(encoding);
+            self
+        }
+        /// Enable decompressing responses.
+        #[must_use]
+        pub fn accept_compressed(mut self, encoding: CompressionEncoding) -> Self {
+            self.inner = self.inner.accept_compressed(encoding);
+            self
+        }
+        /// Limits the maximum size of a decoded message.
+        ///
+        /// Default: `4MB`
+        #[must_use]
+        pub fn max_decoding_message_size(mut self, limit: usize) -> Self {
+            self.inner = self.inner.max_decoding_message_size(limit);
+            self
+        }
+        /// Limits the maximum size of an encoded message.
+        ///
+        /// Default: `usize::MAX`
+        #[must_use]
+        pub fn max_encoding_message_size(mut self, limit: usize) -> Self {
+            self.inner = self.inner.max_encoding_message_size(limit);
+            self
+        }
+        /// List pending approvals, oldest first.
+        pub async fn list_approvals(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ListApprovalsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ListApprovalsResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/taskrun.v1.ApprovalService/ListApprovals",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("taskrun.v1.ApprovalService", "ListApprovals"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// Approve or deny a pending tool use.
+        pub async fn decide_approval(
+            &mut self,
+            request: impl tonic::IntoRequest<super::DecideApprovalRequest>,
+        ) -> std::result::Result<tonic::Response<super::Approval>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/taskrun.v1.ApprovalService/DecideApproval",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("taskrun.v1.ApprovalService", "DecideApproval"));
+            self.inner.unary(req, path, codec).await
+        }
+    }
+}
+/// Generated server implementations.
+pub mod approval_service_server {
+    #![allow(
+        unused_variables,
+        dead_code,
+        missing_docs,
+        clippy::wildcard_imports,
+        clippy::let_unit_value,
+    )]
+    use tonic::codegen::*;
+    /// Generated trait containing gRPC methods that should be implemented for use with ApprovalServiceServer.
+    #[async_trait]
+    pub trait ApprovalService: std::marker::Send + std::marker::Sync + 'static {
+        /// List pending approvals, oldest first.
+        async fn list_approvals(
+            &self,
+            request: tonic::Request<super::ListApprovalsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ListApprovalsResponse>,
+            tonic::Status,
+        >;
+        /// Approve or deny a pending tool use.
+        async fn decide_approval(
+            &self,
+            request: tonic::Request<super::DecideApprovalRequest>,
+        ) -> std::result::Result<tonic::Response<super::Approval>, tonic::Status>;
+    }
+    /// ApprovalService lists and decides tool uses that workers escalated because
+    /// their policy asks before running the tool.
+    #[derive(Debug)]
+    pub struct ApprovalServiceServer<T> {
+        inner: Arc<T>,
+        accept_compression_encodings: EnabledCompressionEncodings,
+        send_compression_encodings: EnabledCompressionEncodings,
+        max_decoding_message_size: Option<usize>,
+        max_encoding_message_size: Option<usize>,
+    }
+    impl<T> ApprovalServiceServer<T> {
+        pub fn new(inner: T) -> Self {
+            Self::from_arc(Arc::new(inner))
+        }
+        pub fn from_arc(inner: Arc<T>) -> Self {
+            Self {
+                inner,
+                accept_compression_encodings: Default::default(),
+                send_compression_encodings: Default::default(),
+                max_decoding_message_size: None,
+                max_encoding_message_size: None,
+            }
+        }
+        pub fn with_interceptor<F>(
+            inner: T,
+            interceptor: F,
+        ) -> InterceptedService<Self, F>
+        where
+            F: tonic::service::Interceptor,
+        {
+            InterceptedService::new(Self::new(inner), interceptor)
+        }
+        /// Enable decompressing requests with the given encoding.
+        #[must_use]
+        pub fn accept_compressed(mut self, encoding: CompressionEncoding) -> Self {
+            self.accept_compression_encodings.enable(encoding);
+            self
+        }
+        /// Compress responses with the given encoding, if the client supports it.
+        #[must_use]
+        pub fn send_compressed(mut self, encoding: CompressionEncoding) -> Self {
+            self.send_compression_encodings.enable(encoding);
+            self
+        }
+        /// Limits the maximum size of a decoded message.
+        ///
+        /// Default: `4MB`
+        #[must_use]
+        pub fn max_decoding_message_size(mut self, limit: usize) -> Self {
+            self.max_decoding_message_size = Some(limit);
+            self
+        }
+        /// Limits the maximum size of an encoded message.
+        ///
+        /// Default: `usize::MAX`
+        #[must_use]
+        pub fn max_encoding_message_size(mut self, limit: usize) -> Self {
+            self.max_encoding_message_size = Some(limit);
+            self
+        }
+    }
+    impl<T, B> tonic::codegen::Service<http::Request<B>> for ApprovalServiceServer<T>
+    where
+        T: ApprovalService,
+        B: Body + std::marker::Send + 'static,
+        B::Error: Into<StdError> + std::marker::Send + 'static,
+    {
+        type Response = http::Response<tonic::body::BoxBody>;
+        type Error = std::convert::Infallible;
+        type Future = BoxFuture<Self::Response, Self::Error>;
+        fn poll_ready(
+            &mut self,
+            _cx: &mut Context<'_>,
+        ) -> Poll<std::result::Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+        fn call(&mut self, req: http::Request<B>) -> Self::Future {
+            match req.uri().path() {
+                "/taskrun.v1.ApprovalService/ListApprovals" => {
+                    #[allow(non_camel_case_types)]
+                    struct ListApprovalsSvc<T: ApprovalService>(pub Arc<T>);
+                    impl<
+                        T: ApprovalService,
+                    > tonic::server::UnaryService<super::ListApprovalsRequest>
+                    for ListApprovalsSvc<T> {
+                        type Response = super::ListApprovalsResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ListApprovalsRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as ApprovalService>::list_approvals(&inner, request)
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = ListApprovalsSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/taskrun.v1.ApprovalService/DecideApproval" => {
+                    #[allow(non_camel_case_types)]
+                    struct DecideApprovalSvc<T: ApprovalService>(pub Arc<T>);
+                    impl<
+                        T: ApprovalService,
+                    > tonic::server::UnaryService<super::DecideApprovalRequest>
+                    for DecideApprovalSvc<T> {
+                        type Response = super::Approval;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::DecideApprovalRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as ApprovalService>::decide_approval(&inner, request)
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = DecideApprovalSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                _ => {
+                    Box::pin(async move {
+                        let mut response = http::Response::new(empty_body());
+                        let headers = response.headers_mut();
+                        headers
+                            .insert(
+                                tonic::Status::GRPC_STATUS,
+                                (tonic::Code::Unimplemented as i32).into(),
+                            );
+                        headers
+                            .insert(
+                                http::header::CONTENT_TYPE,
+                                tonic::metadata::GRPC_CONTENT_TYPE,
+                            );
+                        Ok(response)
+                    })
+                }
+            }
+        }
+    }
+    impl<T> Clone for ApprovalServiceServer<T> {
+        fn clone(&self) -> Self {
+            let inner = self.inner.clone();
+            Self {
+                inner,
+                accept_compression_encodings: self.accept_compression_encodings,
+                send_compression_encodings: self.send_compression_encodings,
+                max_decoding_message_size: self.max_decoding_message_size,
+                max_encoding_message_size: self.max_encoding_message_size,
+            }
+        }
+    }
+    /// Generated gRPC service name
+    pub const SERVICE_NAME: &str = "taskrun.v1.ApprovalService";
+    impl<T> tonic::server::NamedService for ApprovalServiceServer<T> {
+        const NAME: &'static str = SERVICE_NAME;
+    }
+}
+/// Wrapper for all messages from worker to control plane
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RunClientMessage {
+    #[prost(
+        oneof = "run_client_message::Payload",
+        tags = "1, 2, 3, 4, 5, 6, 7, 8, 9, 10"
+    )]
+    pub payload: ::core::option::Option<run_client_message::Payload>,
+}
+/// Nested message and enum types in `RunClientMessage`.
+pub mod run_client_message {
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum Payload {
+        #[prost(message, tag = "1")]
+        Hello(super::WorkerHello),
+        #[prost(message, tag = "2")]
+        Heartbeat(super::WorkerHeartbeat),
+        #[prost(message, tag = "3")]
+        StatusUpdate(super::RunStatusUpdate),
+        #[prost(message, tag = "4")]
+        OutputChunk(super::RunOutputChunk),
+        #[prost(message, tag = "5")]
+        Event(super::RunEvent),
+        #[prost(message, tag = "6")]
+        ChatMessage(super::RunChatMessage),
+        #[prost(message, tag = "7")]
+        ToolApproval(super::ToolApprovalRequest),
+        #[prost(message, tag = "8")]
+        Update(super::WorkerUpdate),
+        #[prost(message, tag = "9")]
+        ArtifactUpload(super::ArtifactUpload),
+        #[prost(message, tag = "10")]
+        AssignmentAck(super::AssignmentAck),
+    }
+}
+/// A chat message for a run (user or assistant message in the conversation)
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RunChatMessage {
+    /// Run identifier
+    #[prost(string, tag = "1")]
+    pub run_id: ::prost::alloc::string::String,
+    /// The chat message
+    #[prost(message, optional, tag = "2")]
+    pub message: ::core::option::Option<ChatMessage>,
+}
+/// Initial message from worker announcing its capabilities
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct WorkerHello {
+    /// Worker information and capabilities
+    #[prost(message, optional, tag = "1")]
+    pub info: ::core::option::Option<WorkerInfo>,
+    /// The worker acknowledges each RunAssignment with an AssignmentAck; the
+    /// control plane reassigns assignments left unacknowledged
+    #[prost(bool, tag = "2")]
+    pub acks_assignments: bool,
+}
+/// Receipt of a RunAssignment, sent as soon as it arrives
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AssignmentAck {
+    /// Run identifier of the assignment
+    #[prost(string, tag = "1")]
+    pub run_id: ::prost::alloc::string::String,
+}
+/// Change to the capabilities a connected worker advertised, applied in
+/// place without a reconnect. Unset fields keep their value.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct WorkerUpdate {
+    /// Agents the worker now serves (replaces the advertised list)
+    #[prost(message, optional, tag = "1")]
+    pub agents: ::core::option::Option<WorkerAgents>,
+    /// Worker-level labels (replace the advertised labels)
+    #[prost(message, optional, tag = "2")]
+    pub labels: ::core::option::Option<WorkerLabels>,
+    /// New limit on concurrent batch runs
+    #[prost(uint32, optional, tag = "3")]
+    pub max_concurrent_runs: ::core::option::Option<u32>,
+}
+/// Agent list of a WorkerUpdate
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct WorkerAgents {
+    #[prost(message, repeated, tag = "1")]
+    pub agents: ::prost::alloc::vec::Vec<AgentSpec>,
+}
+/// Label set of a WorkerUpdate
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct WorkerLabels {
+    #[prost(map = "string, string", tag = "1")]
+    pub labels: ::std::collections::HashMap<
+        ::prost::alloc::string::String,
+        ::prost::alloc::string::String,
+    >,
+}
+/// Periodic heartbeat from worker
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct WorkerHeartbeat {
+    /// Worker identifier
+    #[prost(string, tag = "1")]
+    pub worker_id: ::prost::alloc::string::String,
+    /// Current worker status
+    #[prost(enumeration = "WorkerStatus", tag = "2")]
+    pub status: i32,
+    /// Number of active runs on this worker
+    #[prost(uint32, tag = "3")]
+    pub active_runs: u32,
+    /// Maximum concurrent batch runs this worker can handle
+    #[prost(uint32, tag = "4")]
+    pub max_concurrent_runs: u32,
+    /// Host and run metrics the worker chose to send (load_1m,
+    /// memory_used_bytes, memory_total_bytes, disk_free_bytes, runs_completed,
+    /// runs_failed, avg_run_ms), as decimal strings
+    #[prost(map = "string, string", tag = "5")]
+    pub metrics: ::std::collections::HashMap<
+        ::prost::alloc::string::String,
+        ::prost::alloc::string::String,
+    >,
+    /// Unix timestamp (milliseconds) when heartbeat was sent
+    #[prost(int64, tag = "6")]
+    pub timestamp_ms: i64,
+    /// Extra runs reserved for interactive work on top of max_concurrent_runs
+    #[prost(uint32, tag = "7")]
+    pub interactive_slots: u32,
+}
+/// Status update for a run
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RunStatusUpdate {
+    /// Run identifier
+    #[prost(string, tag = "1")]
+    pub run_id: ::prost::alloc::string::String,
+    /// New status
+    #[prost(enumeration = "RunStatus", tag = "2")]
+    pub status: i32,
+    /// Error message (populated when status is FAILED)
+    #[prost(string, tag = "3")]
+    pub error_message: ::prost::alloc::string::String,
+    /// Model backend actually used for this run
+    #[prost(message, optional, tag = "4")]
+    pub backend_used: ::core::option::Option<ModelBackend>,
+    /// Unix timestamp (milliseconds) of this update
+    #[prost(int64, tag = "5")]
+    pub timestamp_ms: i64,
+    /// Environment the run executes in (sent with RUNNING)
+    #[prost(message, optional, tag = "6")]
+    pub manifest: ::core::option::Option<RunManifest>,
+    /// Why the run failed or was cancelled (with FAILED or CANCELLED)
+    #[prost(enumeration = "FailureReason", tag = "7")]
+    pub failure_reason: i32,
+}
+/// Streaming output chunk from a run
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RunOutputChunk {
+    /// Run identifier
+    #[prost(string, tag = "1")]
+    pub run_id: ::prost::alloc::string::String,
+    /// Sequence number for ordering (0-indexed)
+    #[prost(uint64, tag = "2")]
+    pub seq: u64,
+    /// Content of this chunk (text tokens, etc.)
+    #[prost(string, tag = "3")]
+    pub content: ::prost::alloc::string::String,
+    /// Whether this is the final chunk
+    #[prost(bool, tag = "4")]
+    pub is_final: bool,
+    /// Chunk metadata (role, content_type, etc.)
+    #[prost(map = "string, string", tag = "5")]
+    pub metadata: ::std::collections::HashMap<
+        ::prost::alloc::string::String,
+        ::prost::alloc::string::String,
+    >,
+    /// Unix timestamp (milliseconds) when chunk was produced
+    #[prost(int64, tag = "6")]
+    pub timestamp_ms: i64,
+}
+/// Request for an operator to approve a tool use the worker's policy asks about
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ToolApprovalRequest {
+    /// Approval identifier (unique per worker)
+    #[prost(string, tag = "1")]
+    pub approval_id: ::prost::alloc::string::String,
+    /// Run identifier
+    #[prost(string, tag = "2")]
+    pub run_id: ::prost::alloc::string::String,
+    /// Tool Claude wants to use
+    #[prost(string, tag = "3")]
+    pub tool_name: ::prost::alloc::string::String,
+    /// Tool input as JSON string
+    #[prost(string, tag = "4")]
+    pub input_json: ::prost::alloc::string::String,
+    /// How long the worker waits before applying the default (milliseconds)
+    #[prost(int64, tag = "5")]
+    pub timeout_ms: i64,
+    /// Whether the tool use is allowed if nobody decides in time
+    #[prost(bool, tag = "6")]
+    pub default_allow: bool,
+}
+/// Wrapper for all messages from control plane to worker
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RunServerMessage {
+    #[prost(oneof = "run_server_message::Payload", tags = "1, 2, 3, 4, 5, 6")]
+    pub payload: ::core::option::Option<run_server_message::Payload>,
+}
+/// Nested message and enum types in `RunServerMessage`.
+pub mod run_server_message {
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum Payload {
+        #[prost(message, tag = "1")]
+        AssignRun(super::RunAssignment),
+        #[prost(message, tag = "2")]
+        CancelRun(super::CancelRun),
+        #[prost(message, tag = "3")]
+        Ack(super::ServerAck),
+        #[prost(message, tag = "4")]
+        ContinueRun(super::ContinueRun),
+        #[prost(message, tag = "5")]
+        ApprovalDecision(super::ToolApprovalDecision),
+        #[prost(message, tag = "6")]
+        Disconnect(super::WorkerDisconnect),
+    }
+}
+/// Assignment of a run to a worker
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RunAssignment {
+    /// Unique run identifier
+    #[prost(string, tag = "1")]
+    pub run_id: ::prost::alloc::string::String,
+    /// Associated task identifier
+    #[prost(string, tag = "2")]
+    pub task_id: ::prost::alloc::string::String,
+    /// Name of agent to execute
+    #[prost(string, tag = "3")]
+    pub agent_name: ::prost::alloc::string::String,
+    /// Input payload as JSON string
+    #[prost(string, tag = "4")]
+    pub input_json: ::prost::alloc::string::String,
+    /// Task/run labels
+    #[prost(map = "string, string", tag = "5")]
+    pub labels: ::std::collections::HashMap<
+        ::prost::alloc::string::String,
+        ::prost::alloc::string::String,
+    >,
+    /// Unix timestamp (milliseconds) when assignment was issued
+    #[prost(int64, tag = "6")]
+    pub issued_at_ms: i64,
+    /// Optional deadline for the run (Unix timestamp ms, 0 = no deadline)
+    #[prost(int64, tag = "7")]
+    pub deadline_ms: i64,
+    /// Scheduling lane the worker should execute the run in
+    #[prost(enumeration = "TaskPriority", tag = "8")]
+    pub priority: i32,
+    /// Environment to reproduce: run with this manifest's model and tool policy
+    #[prost(message, optional, tag = "9")]
+    pub pinned_manifest: ::core::option::Option<RunManifest>,
+    /// W3C trace context of the scheduling span; the worker's spans join its trace
+    #[prost(map = "string, string", tag = "10")]
+    pub trace_context: ::std::collections::HashMap<
+        ::prost::alloc::string::String,
+        ::prost::alloc::string::String,
+    >,
+    /// Uploaded files the worker stages in the run's workspace before it starts
+    #[prost(message, repeated, tag = "11")]
+    pub input_files: ::prost::alloc::vec::Vec<InputFile>,
+    /// Limits on the run, tightening the agent's limits on the worker
+    #[prost(message, optional, tag = "12")]
+    pub limits: ::core::option::Option<RunLimits>,
+}
+/// A file uploaded for a task, sent with its run assignment
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct InputFile {
+    /// File name (no directories)
+    #[prost(string, tag = "1")]
+    pub name: ::prost::alloc::string::String,
+    /// File contents
+    #[prost(bytes = "vec", tag = "2")]
+    pub content: ::prost::alloc::vec::Vec<u8>,
+}
+/// Contents of an artifact the run produced, uploaded before the run reports
+/// its final status. Files too large for one message are sent in chunks, in
+/// order, each with the same artifact
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ArtifactUpload {
+    /// Run identifier
+    #[prost(string, tag = "1")]
+    pub run_id: ::prost::alloc::string::String,
+    /// The artifact, as reported on the run's events
+    #[prost(message, optional, tag = "2")]
+    pub artifact: ::core::option::Option<Artifact>,
+    /// File contents (this chunk's, for chunked uploads)
+    #[prost(bytes = "vec", tag = "3")]
+    pub content: ::prost::alloc::vec::Vec<u8>,
+    /// Position of this chunk in the file
+    #[prost(uint64, tag = "4")]
+    pub offset: u64,
+    /// More chunks of the file follow
+    #[prost(bool, tag = "5")]
+    pub more: bool,
+}
+/// Request to cancel an in-progress run
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CancelRun {
+    /// Run identifier to cancel
+    #[prost(string, tag = "1")]
+    pub run_id: ::prost::alloc::string::String,
+    /// Reason for cancellation
+    #[prost(string, tag = "2")]
+    pub reason: ::prost::alloc::string::String,
+}
+/// Server acknowledgment (optional, for protocol reliability)
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ServerAck {
+    /// Type of message being acknowledged
+    #[prost(string, tag = "1")]
+    pub ack_type: ::prost::alloc::string::String,
+    /// Relevant identifier (run_id, etc.)
+    #[prost(string, tag = "2")]
+    pub ref_id: ::prost::alloc::string::String,
+}
+/// Request to continue an in-progress run with a follow-up message
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ContinueRun {
+    /// Run identifier to continue
+    #[prost(string, tag = "1")]
+    pub run_id: ::prost::alloc::string::String,
+    /// The follow-up message content
+    #[prost(string, tag = "2")]
+    pub message: ::prost::alloc::string::String,
+    /// Unix timestamp (milliseconds) when request was sent
+    #[prost(int64, tag = "3")]
+    pub timestamp_ms: i64,
+}
+/// Operator decision on a pending tool approval
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ToolApprovalDecision {
+    /// Approval identifier from ToolApprovalRequest
+    #[prost(string, tag = "1")]
+    pub approval_id: ::prost::alloc::string::String,
+    /// Run identifier
+    #[prost(string, tag = "2")]
+    pub run_id: ::prost::alloc::string::String,
+    /// Whether the tool use may proceed
+    #[prost(bool, tag = "3")]
+    pub allowed: bool,
+    /// Reason given by the operator (shown to Claude on deny)
+    #[prost(string, tag = "4")]
+    pub reason: ::prost::alloc::string::String,
+    /// Who decided (API key name or client certificate subject)
+    #[prost(string, tag = "5")]
+    pub decided_by: ::prost::alloc::string::String,
+}
+/// The control plane is closing the stream; sent last, before it ends
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct WorkerDisconnect {
+    /// Why the worker was disconnected (from the operator)
+    #[prost(string, tag = "1")]
+    pub reason: ::prost::alloc::string::String,
+}
+/// Generated client implementations.
+pub mod run_service_client {
+    #![allow(
+        unused_variables,
+        dead_code,
+        missing_docs,
+        clippy::wildcard_imports,
+        clippy::let_unit_value,
+    )]
+    use tonic::codegen::*;
+    use tonic::codegen::http::Uri;
+    #[derive(Debug, Clone)]
+    pub struct RunServiceClient<T> {
+        inner: tonic::client::Grpc<T>,
+    }
+    impl RunServiceClient<tonic::transport::Channel> {
+        /// Attempt to create a new client by connecting to a given endpoint.
+        pub async fn connect<D>(dst: D) -> Result<Self, tonic::transport::Error>
+        where
+            D: TryInto<tonic::transport::Endpoint>,
+            D::Error: Into<StdError>,
+        {
+            let conn = tonic::transport::Endpoint::new(dst)?.connect().await?;
+            Ok(Self::new(conn))
+        }
+    }
+    impl<T> RunServiceClient<T>
+    where
+        T: tonic::client::GrpcService<tonic::body::BoxBody>,
+        T::Error: Into<StdError>,
+        T::ResponseBody: Body<Data = Bytes> + std::marker::Send + 'static,
+        <T::ResponseBody as Body>::Error: Into<StdError> + std::marker::Send,
+    {
+        pub fn new(inner: T) -> Self {
+            let inner = tonic::client::Grpc::new(inner);
+            Self { inner }
+        }
+        pub fn with_origin(inner: T, origin: Uri) -> Self {
+            let inner = tonic::client::Grpc::with_origin(inner, origin);
+            Self { inner }
+        }
+        pub fn with_interceptor<F>(
+            inner: T,
+            interceptor: F,
+        ) -> RunServiceClient<InterceptedService<T, F>>
+        where
+            F: tonic::service::Interceptor,
+            T::ResponseBody: Default,
+            T: tonic::codegen::Service<
+                http::Request<tonic::body::BoxBody>,
+                Response = http::Response<
+                    <T as tonic::client::GrpcService<tonic::body::BoxBody>>::ResponseBody,
+                >,
+            >,
+            <T as tonic::codegen::Service<
+                http::Request<tonic::body::BoxBody>,
+            >>::Error: Into<StdError> + std::marker::Send + std::marker::Sync,
+        {
+            RunServiceClient::new(InterceptedService::new(inner, interceptor))
+        }
+        /// Compress requests with the given encoding.
+        ///
+        /// This requires the server to support it otherwise it might respond with an
+        /// error.
+        #[must_use]
+        pub fn send_compressed(mut self, encoding: CompressionEncoding) -> Self {
+            self.inner = self.inner.send_compressed(encoding);
+            self
+        }
+        /// Enable decompressing responses.
+        #[must_use]
+        pub fn accept_compressed(mut self, encoding: CompressionEncoding) -> Self {
+            self.inner = self.inner.accept_compressed(encoding);
+            self
+        }
+        /// Limits the maximum size of a decoded message.
+        ///
+        /// Default: `4MB`
+        #[must_use]
+        pub fn max_decoding_message_size(mut self, limit: usize) -> Self {
+            self.inner = self.inner.max_decoding_message_size(limit);
+            self
+        }
+        /// Limits the maximum size of an encoded message.
+        ///
+        /// Default: `usize::MAX`
+        #[must_use]
+        pub fn max_encoding_message_size(mut self, limit: usize) -> Self {
+            self.inner = self.inner.max_encoding_message_size(limit);
+            self
+        }
+        /// Bidirectional streaming connection for worker communication
+        /// Workers connect and maintain a persistent stream for:
+        /// - Announcing capabilities (WorkerHello)
+        /// - Receiving run assignments (RunAssignment)
+        /// - Streaming output (RunOutputChunk)
+        /// - Reporting status (RunStatusUpdate)
+        /// - Health checks (WorkerHeartbeat)
+        pub async fn stream_connect(
+            &mut self,
+            request: impl tonic::IntoStreamingRequest<Message = super::RunClientMessage>,
+        ) -> std::result::Result<
+            tonic::Response<tonic::codec::Streaming<super::RunServerMessage>>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/taskrun.v1.RunService/StreamConnect",
+            );
+            let mut req = request.into_streaming_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("taskrun.v1.RunService", "StreamConnect"));
+            self.inner.streaming(req, path, codec).await
+        }
+    }
+}
+/// Generated server implementations.
+pub mod run_service_server {
+    #![allow(
+        unused_variables,
+        dead_code,
+        missing_docs,
+        clippy::wildcard_imports,
+        clippy::let_unit_value,
+    )]
+    use tonic::codegen::*;
+    /// Generated trait containing gRPC methods that should be implemented for use with RunServiceServer.
+    #[async_trait]
+    pub trait RunService: std::marker::Send + std::marker::Sync + 'static {
+        /// Server streaming response type for the StreamConnect method.
+        type StreamConnectStream: tonic::codegen::tokio_stream::Stream<
+                Item = std::result::Result<super::RunServerMessage, tonic::Status>,
+            >
+            + std::marker::Send
+            + 'static;
+        /// Bidirectional streaming connection for worker communication
+        /// Workers connect and maintain a persistent stream for:
+        /// - Announcing capabilities (WorkerHello)
+        /// - Receiving run assignments (RunAssignment)
+        /// - Streaming output (RunOutputChunk)
+        /// - Reporting status (RunStatusUpdate)
+        /// - Health checks (WorkerHeartbeat)
+        async fn stream_connect(
+            &self,
+            request: tonic::Request<tonic::Streaming<super::RunClientMessage>>,
+        ) -> std::result::Result<
+            tonic::Response<Self::StreamConnectStream>,
+            tonic::Status,
+        >;
+    }
+    #[derive(Debug)]
+    pub struct RunServiceServer<T> {
+        inner: Arc<T>,
+        accept_compression_encodings: EnabledCompressionEncodings,
+        send_compression_encodings: EnabledCompressionEncodings,
+        max_decoding_message_size: Option<usize>,
+        max_encoding_message_size: Option<usize>,
+    }
+    impl<T> RunServiceServer<T> {
+        pub fn new(inner: T) -> Self {
+            Self::from_arc(Arc::new(inner))
+        }
+        pub fn from_arc(inner: Arc<T>) -> Self {
+            Self {
+                inner,
+                accept_compression_encodings: Default::default(),
+                send_compression_encodings: Default::default(),
+                max_decoding_message_size: None,
+                max_encoding_message_size: None,
+            }
+        }
+        pub fn with_interceptor<F>(
+            inner: T,
+            interceptor: F,
+        ) -> InterceptedService<Self, F>
+        where
+            F: tonic::service::Interceptor,
+        {
+            InterceptedService::new(Self::new(inner), interceptor)
+        }
+        /// Enable decompressing requests with the given encoding.
+        #[must_use]
+        pub fn accept_compressed(mut self, encoding: CompressionEncoding) -> Self {
+            self.accept_compression_encodings.enable(encoding);
+            self
+        }
+        /// Compress responses with the given encoding, if the client supports it.
+        #[must_use]
+        pub fn send_compressed(mut self, encoding: CompressionEncoding) -> Self {
+            self.send_compression_encodings.enable(encoding);
+            self
+        }
+        /// Limits the maximum size of a decoded message.
+        ///
+        /// Default: `4MB`
+        #[must_use]
+        pub fn max_decoding_message_size(mut self, limit: usize) -> Self {
+            self.max_decoding_message_size = Some(limit);
+            self
+        }
+        /// Limits the maximum size of an encoded message.
+        ///
+        /// Default: `usize::MAX`
+        #[must_use]
+        pub fn max_encoding_message_size(mut self, limit: usize) -> Self {
+            self.max_encoding_message_size = Some(limit);
+            self
+        }
+    }
+    impl<T, B> tonic::codegen::Service<http::Request<B>> for RunServiceServer<T>
+    where
+        T: RunService,
+        B: Body + std::marker::Send + 'static,
+        B::Error: Into<StdError> + std::marker::Send + 'static,
+    {
+        type Response = http::Response<tonic::body::BoxBody>;
+        type Error = std::convert::Infallible;
+        type Future = BoxFuture<Self::Response, Self::Error>;
+        fn poll_ready(
+            &mut self,
+            _cx: &mut Context<'_>,
+        ) -> Poll<std::result::Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+        fn call(&mut self, req: http::Request<B>) -> Self::Future {
+            match req.uri().path() {
+                "/taskrun.v1.RunService/StreamConnect" => {
+                    #[allow(non_camel_case_types)]
+                    struct StreamConnectSvc<T: RunService>(pub Arc<T>);
+                    impl<
+                        T: RunService,
+                    > tonic::server::StreamingService<super::RunClientMessage>
+                    for StreamConnectSvc<T> {
+                        type Response = super::RunServerMessage;
+                        type ResponseStream = T::StreamConnectStream;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::ResponseStream>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<
+                                tonic::Streaming<super::RunClientMessage>,
+                            >,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as RunService>::stream_connect(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = StreamConnectSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.streaming(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                _ => {
+                    Box::pin(async move {
+                        let mut response = http::Response::new(empty_body());
+                        let headers = response.headers_mut();
+                        headers
+                            .insert(
+                                tonic::Status::GRPC_STATUS,
+                                (tonic::Code::Unimplemented as i32).into(),
+                            );
+                        headers
+                            .insert(
+                                http::header::CONTENT_TYPE,
+                                tonic::metadata::GRPC_CONTENT_TYPE,
+                            );
+                        Ok(response)
+                    })
+                }
+            }
+        }
+    }
+    impl<T> Clone for RunServiceServer<T> {
+        fn clone(&self) -> Self {
+            let inner = self.inner.clone();
+            Self {
+                inner,
+                accept_compression_encodings: self.accept_compression_encodings,
+                send_compression_encodings: self.send_compression_encodings,
+                max_decoding_message_size: self.max_decoding_message_size,
+                max_encoding_message_size: self.max_encoding_message_size,
+            }
+        }
+    }
+    /// Generated gRPC service name
+    pub const SERVICE_NAME: &str = "taskrun.v1.RunService";
+    impl<T> tonic::server::NamedService for RunServiceServer<T> {
+        const NAME: &'static str = SERVICE_NAME;
+    }
+}
+/// Schedule creates a task for an agent every time its cron expression fires.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Schedule {
+    /// Unique schedule identifier.
+    #[prost(string, tag = "1")]
+    pub id: ::prost::alloc::string::String,
+    /// Cron expression (5 fields, or 6 with leading seconds), evaluated in UTC.
+    #[prost(string, tag = "2")]
+    pub cron_expr: ::prost::alloc::string::String,
+    /// Name of the agent to execute.
+    #[prost(string, tag = "3")]
+    pub agent_name: ::prost::alloc::string::String,
+    /// Input JSON template. {{schedule_id}}, {{fire_time}} and {{fire_time_ms}}
+    /// are replaced at fire time.
+    #[prost(string, tag = "4")]
+    pub input_template: ::prost::alloc::string::String,
+    /// Who created this schedule.
+    #[prost(string, tag = "5")]
+    pub created_by: ::prost::alloc::string::String,
+    /// Creation timestamp in milliseconds since epoch.
+    #[prost(int64, tag = "6")]
+    pub created_at_ms: i64,
+    /// Labels copied onto every task this schedule creates.
+    #[prost(map = "string, string", tag = "7")]
+    pub labels: ::std::collections::HashMap<
+        ::prost::alloc::string::String,
+        ::prost::alloc::string::String,
+    >,
+    /// Run timeout in seconds for created tasks (0 = no timeout).
+    #[prost(uint32, tag = "8")]
+    pub timeout_seconds: u32,
+    /// Whether the schedule is paused.
+    #[prost(bool, tag = "9")]
+    pub paused: bool,
+    /// When the schedule last fired (milliseconds since epoch, 0 = never).
+    #[prost(int64, tag = "10")]
+    pub last_fired_at_ms: i64,
+    /// Task created by the most recent firing (empty = none).
+    #[prost(string, tag = "11")]
+    pub last_task_id: ::prost::alloc::string::String,
+    /// When the schedule fires next (milliseconds since epoch, 0 = paused).
+    #[prost(int64, tag = "12")]
+    pub next_fire_at_ms: i64,
+}
+/// Request to create a schedule.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CreateScheduleRequest {
+    /// Cron expression (5 fields, or 6 with leading seconds), evaluated in UTC.
+    #[prost(string, tag = "1")]
+    pub cron_expr: ::prost::alloc::string::String,
+    /// Name of the agent to execute.
+    #[prost(string, tag = "2")]
+    pub agent_name: ::prost::alloc::string::String,
+    /// Input JSON template.
+    #[prost(string, tag = "3")]
+    pub input_template: ::prost::alloc::string::String,
+    /// Who is creating this schedule.
+    #[prost(string, tag = "4")]
+    pub created_by: ::prost::alloc::string::String,
+    /// Labels copied onto every task this schedule creates.
+    #[prost(map = "string, string", tag = "5")]
+    pub labels: ::std::collections::HashMap<
+        ::prost::alloc::string::String,
+        ::prost::alloc::string::String,
+    >,
+    /// Run timeout in seconds for created tasks (0 = no timeout).
+    #[prost(uint32, tag = "6")]
+    pub timeout_seconds: u32,
+}
+/// Request to list schedules.
+#[derive(Clone, Copy, PartialEq, ::prost::Message)]
+pub struct ListSchedulesRequest {}
+/// Response containing all schedules.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListSchedulesResponse {
+    /// Schedules ordered by next fire time.
+    #[prost(message, repeated, tag = "1")]
+    pub schedules: ::prost::alloc::vec::Vec<Schedule>,
+}
+/// Request to pause a schedule.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PauseScheduleRequest {
+    /// Schedule ID (or unique prefix).
+    #[prost(string, tag = "1")]
+    pub id: ::prost::alloc::string::String,
+}
+/// Request to resume a schedule.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ResumeScheduleRequest {
+    /// Schedule ID (or unique prefix).
+    #[prost(string, tag = "1")]
+    pub id: ::prost::alloc::string::String,
+}
+/// Request to delete a schedule.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DeleteScheduleRequest {
+    /// Schedule ID (or unique prefix).
+    #[prost(string, tag = "1")]
+    pub id: ::prost::alloc::string::String,
+}
+/// Response to a delete request.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DeleteScheduleResponse {
+    /// ID of the deleted schedule.
+    #[prost(string, tag = "1")]
+    pub id: ::prost::alloc::string::String,
+}
+/// Generated client implementations.
+pub mod schedule_service_client {
+    #![allow(
+        unused_variables,
+        dead_code,
+        missing_docs,
+        clippy::wildcard_imports,
+        clippy::let_unit_value,
+    )]
+    use tonic::codegen::*;
+    use tonic::codegen::http::Uri;
+    /// ScheduleService creates tasks on a cron schedule.
+    #[derive(Debug, Clone)]
+    pub struct ScheduleServiceClient<T> {
+        inner: tonic::client::Grpc<T>,
+    }
+    impl ScheduleServiceClient<tonic::transport::Channel> {
+        /// Attempt to create a new client by connecting to a given endpoint.
+        pub async fn connect<D>(dst: D) -> Result<Self, tonic::transport::Error>
+        where
+            D: TryInto<tonic::transport::Endpoint>,
+            D::Error: Into<StdError>,
+        {
+            let conn = tonic::transport::Endpoint::new(dst)?.connect().await?;
+            Ok(Self::new(conn))
+        }
+    }
+    impl<T> ScheduleServiceClient<T>
+    where
+        T: tonic::client::GrpcService<tonic::body::BoxBody>,
+        T::Error: Into<StdError>,
+        T::ResponseBody: Body<Data = Bytes> + std::marker::Send + 'static,
+        <T::ResponseBody as Body>::Error: Into<StdError> + std::marker::Send,
+    {
+        pub fn new(inner: T) -> Self {
+            let inner = tonic::client::Grpc::new(inner);
+            Self { inner }
+        }
+        pub fn with_origin(inner: T, origin: Uri) -> Self {
+            let inner = tonic::client::Grpc::with_origin(inner, origin);
+            Self { inner }
+        }
+        pub fn with_interceptor<F>(
+            inner: T,
+            interceptor: F,
+        ) -> ScheduleServiceClient<InterceptedService<T, F>>
+        where
+            F: tonic::service::Interceptor,
+            T::ResponseBody: Default,
+            T: tonic::codegen::Service<
+                http::Request<tonic::body::BoxBody>,
+                Response = http::Response<
+                    <T as tonic::client::GrpcService<tonic::body::BoxBody>>::ResponseBody,
+                >,
+            >,
+            <T as tonic::codegen::Service<
+                http::Request<tonic::body::BoxBody>,
+            >>::Error: Into<StdError> + std::marker::Send + std::marker::Sync,
+        {
+            ScheduleServiceClient::new(InterceptedService::new(inner, interceptor))
+        }
+        /// Compress requests with the given encoding.
+        ///
+        /// This requires the server to support it otherwise it might respond with an
+        /// error.
+        #[must_use]
+        pub fn send_compressed(mut self, encoding: CompressionEncoding) -> Self {
+            self.inner = self.inner.send_compressed(encoding);
+            self
+        }
+        /// Enable decompressing responses.
+        #[must_use]
+        pub fn accept_compressed(mut self, encoding: CompressionEncoding) -> Self {
+            self.inner = self.inner.accept_compressed(encoding);
+            self
+        }
+        /// Limits the maximum size of a decoded message.
+        ///
+        /// Default: `4MB`
+        #[must_use]
+        pub fn max_decoding_message_size(mut self, limit: usize) -> Self {
+            self.inner = self.inner.max_decoding_message_size(limit);
+            self
+        }
+        /// Limits the maximum size of an encoded message.
+        ///
+        /// Default: `usize::MAX`
+        #[must_use]
+        pub fn max_encoding_message_size(mut self, limit: usize) -> Self {
+            self.inner = self.inner.max_encoding_message_size(limit);
+            self
+        }
+        /// Create a new recurring schedule.
+        pub async fn create_schedule(
+            &mut self,
+            request: impl tonic::IntoRequest<super::CreateScheduleRequest>,
+        ) -> std::result::Result<tonic::Response<super::Schedule>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/taskrun.v1.ScheduleService/CreateSchedule",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("taskrun.v1.ScheduleService", "CreateSchedule"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// List all schedules.
+        pub async fn list_schedules(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ListSchedulesRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ListSchedulesResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/taskrun.v1.ScheduleService/ListSchedules",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("taskrun.v1.ScheduleService", "ListSchedules"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// Pause a schedule so it stops firing.
+        pub async fn pause_schedule(
+            &mut self,
+            request: impl tonic::IntoRequest<super::PauseScheduleRequest>,
+        ) -> std::result::Result<tonic::Response<super::Schedule>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/taskrun.v1.ScheduleService/PauseSchedule",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("taskrun.v1.ScheduleService", "PauseSchedule"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// Resume a paused schedule.
+        pub async fn resume_schedule(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ResumeScheduleRequest>,
+        ) -> std::result::Result<tonic::Response<super::Schedule>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/taskrun.v1.ScheduleService/ResumeSchedule",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("taskrun.v1.ScheduleService", "ResumeSchedule"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// Delete a schedule. Tasks it already created are kept.
+        pub async fn delete_schedule(
+            &mut self,
+            request: impl tonic::IntoRequest<super::DeleteScheduleRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::DeleteScheduleResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/taskrun.v1.ScheduleService/DeleteSchedule",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("taskrun.v1.ScheduleService", "DeleteSchedule"));
+            self.inner.unary(req, path, codec).await
+        }
+    }
+}
+/// Generated server implementations.
+pub mod schedule_service_server {
+    #![allow(
+        unused_variables,
+        dead_code,
+        missing_docs,
+        clippy::wildcard_imports,
+        clippy::let_unit_value,
+    )]
+    use tonic::codegen::*;
+    /// Generated trait containing gRPC methods that should be implemented for use with ScheduleServiceServer.
+    #[async_trait]
+    pub trait ScheduleService: std::marker::Send + std::marker::Sync + 'static {
+        /// Create a new recurring schedule.
+        async fn create_schedule(
+            &self,
+            request: tonic::Request<super::CreateScheduleRequest>,
+        ) -> std::result::Result<tonic::Response<super::Schedule>, tonic::Status>;
+        /// List all schedules.
+        async fn list_schedules(
+            &self,
+            request: tonic::Request<super::ListSchedulesRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ListSchedulesResponse>,
+            tonic::Status,
+        >;
+        /// Pause a schedule so it stops firing.
+        async fn pause_schedule(
+            &self,
+            request: tonic::Request<super::PauseScheduleRequest>,
+        ) -> std::result::Result<tonic::Response<super::Schedule>, tonic::Status>;
+        /// Resume a paused schedule.
+        async fn resume_schedule(
+            &self,
+            request: tonic::Request<super::ResumeScheduleRequest>,
+        ) -> std::result::Result<tonic::Response<super::Schedule>, tonic::Status>;
+        /// Delete a schedule. Tasks it already created are kept.
+        async fn delete_schedule(
+            &self,
+            request: tonic::Request<super::DeleteScheduleRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::DeleteScheduleResponse>,
+            tonic::Status,
+        >;
+    }
+    /// ScheduleService creates tasks on a cron schedule.
+    #[derive(Debug)]
+    pub struct ScheduleServiceServer<T> {
+        inner: Arc<T>,
+        accept_compression_encodings: EnabledCompressionEncodings,
+        send_compression_encodings: EnabledCompressionEncodings,
+        max_decoding_message_size: Option<usize>,
+        max_encoding_message_size: Option<usize>,
+    }
+    impl<T> ScheduleServiceServer<T> {
+        pub fn new(inner: T) -> Self {
+            Self::from_arc(Arc::new(inner))
+        }
+        pub fn from_arc(inner: Arc<T>) -> Self {
+            Self {
+                inner,
+                accept_compression_encodings: Default::default(),
+                send_compression_encodings: Default::default(),
+                max_decoding_message_size: None,
+                max_encoding_message_size: None,
+            }
+        }
+        pub fn with_interceptor<F>(
+            inner: T,
+            interceptor: F,
+        ) -> InterceptedService<Self, F>
+        where
+            F: tonic::service::Interceptor,
+        {
+            InterceptedService::new(Self::new(inner), interceptor)
+        }
+        /// Enable decompressing requests with the given encoding.
+        #[must_use]
+        pub fn accept_compressed(mut self, encoding: CompressionEncoding) -> Self {
+            self.accept_compression_encodings.enable(encoding);
+            self
+        }
+        /// Compress responses with the given encoding, if the client supports it.
+        #[must_use]
+        pub fn send_compressed(mut self, encoding: CompressionEncoding) -> Self {
+            self.send_compression_encodings.enable(encoding);
+            self
+        }
+        /// Limits the maximum size of a decoded message.
+        ///
+        /// Default: `4MB`
+        #[must_use]
+        pub fn max_decoding_message_size(mut self, limit: usize) -> Self {
+            self.max_decoding_message_size = Some(limit);
+            self
+        }
+        /// Limits the maximum size of an encoded message.
+        ///
+        /// Default: `usize::MAX`
+        #[must_use]
+        pub fn max_encoding_message_size(mut self, limit: usize) -> Self {
+            self.max_encoding_message_size = Some(limit);
+            self
+        }
+    }
+    impl<T, B> tonic::codegen::Service<http::Request<B>> for ScheduleServiceServer<T>
+    where
+        T: ScheduleService,
+        B: Body + std::marker::Send + 'static,
+        B::Error: Into<StdError> + std::marker::Send + 'static,
+    {
+        type Response = http::Response<tonic::body::BoxBody>;
+        type Error = std::convert::Infallible;
+        type Future = BoxFuture<Self::Response, Self::Error>;
+        fn poll_ready(
+            &mut self,
+            _cx: &mut Context<'_>,
+        ) -> Poll<std::result::Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+        fn call(&mut self, req: http::Request<B>) -> Self::Future {
+            match req.uri().path() {
+                "/taskrun.v1.ScheduleService/CreateSchedule" => {
+                    #[allow(non_camel_case_types)]
+                    struct CreateScheduleSvc<T: ScheduleService>(pub Arc<T>);
+                    impl<
+                        T: ScheduleService,
+                    > tonic::server::UnaryService<super::CreateScheduleRequest>
+                    for CreateScheduleSvc<T> {
+                        type Response = super::Schedule;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::CreateScheduleRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as ScheduleService>::create_schedule(&inner, request)
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = CreateScheduleSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/taskrun.v1.ScheduleService/ListSchedules" => {
+                    #[allow(non_camel_case_types)]
+                    struct ListSchedulesSvc<T: ScheduleService>(pub Arc<T>);
+                    impl<
+                        T: ScheduleService,
+                    > tonic::server::UnaryService<super::ListSchedulesRequest>
+                    for ListSchedulesSvc<T> {
+                        type Response = super::ListSchedulesResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ListSchedulesRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as ScheduleService>::list_schedules(&inner, request)
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = ListSchedulesSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/taskrun.v1.ScheduleService/PauseSchedule" => {
+                    #[allow(non_camel_case_types)]
+                    struct PauseScheduleSvc<T: ScheduleService>(pub Arc<T>);
+                    impl<
+                        T: ScheduleService,
+                    > tonic::server::UnaryService<super::PauseScheduleRequest>
+                    for PauseScheduleSvc<T> {
+                        type Response = super::Schedule;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::PauseScheduleRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as ScheduleService>::pause_schedule(&inner, request)
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = PauseScheduleSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/taskrun.v1.ScheduleService/ResumeSchedule" => {
+                    #[allow(non_camel_case_types)]
+                    struct ResumeScheduleSvc<T: ScheduleService>(pub Arc<T>);
+                    impl<
+                        T: ScheduleService,
+                    > tonic::server::UnaryService<super::ResumeScheduleRequest>
+                    for ResumeScheduleSvc<T> {
+                        type Response = super::Schedule;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ResumeScheduleRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as ScheduleService>::resume_schedule(&inner, request)
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = ResumeScheduleSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/taskrun.v1.ScheduleService/DeleteSchedule" => {
+                    #[allow(non_camel_case_types)]
+                    struct DeleteScheduleSvc<T: ScheduleService>(pub Arc<T>);
+                    impl<
+                        T: ScheduleService,
+                    > tonic::server::UnaryService<super::DeleteScheduleRequest>
+                    for DeleteScheduleSvc<T> {
+                        type Response = super::DeleteScheduleResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::DeleteScheduleRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as ScheduleService>::delete_schedule(&inner, request)
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = DeleteScheduleSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                _ => {
+                    Box::pin(async move {
+                        let mut response = http::Response::new(empty_body());
+                        let headers = response.headers_mut();
+                        headers
+                            .insert(
+                                tonic::Status::GRPC_STATUS,
+                                (tonic::Code::Unimplemented as i32).into(),
+                            );
+                        headers
+                            .insert(
+                                http::header::CONTENT_TYPE,
+                                tonic::metadata::GRPC_CONTENT_TYPE,
+                            );
+                        Ok(response)
+                    })
+                }
+            }
+        }
+    }
+    impl<T> Clone for ScheduleServiceServer<T> {
+        fn clone(&self) -> Self {
+            let inner = self.inner.clone();
+            Self {
+                inner,
+                accept_compression_encodings: self.accept_compression_encodings,
+                send_compression_encodings: self.send_compression_encodings,
+                max_decoding_message_size: self.max_decoding_message_size,
+                max_encoding_message_size: self.max_encoding_message_size,
+            }
+        }
+    }
+    /// Generated gRPC service name
+    pub const SERVICE_NAME: &str = "taskrun.v1.ScheduleService";
+    impl<T> tonic::server::NamedService for ScheduleServiceServer<T> {
+        const NAME: &'static str = SERVICE_NAME;
+    }
+}
+/// Task represents a unit of work to be executed by an agent.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Task {
+    /// Unique task identifier.
+    #[prost(string, tag = "1")]
+    pub id: ::prost::alloc::string::String,
+    /// Name of the agent to execute this task.
+    #[prost(string, tag = "2")]
+    pub agent_name: ::prost::alloc::string::String,
+    /// Input data as JSON string.
+    #[prost(string, tag = "3")]
+    pub input_json: ::prost::alloc::string::String,
+    /// Current status of the task.
+    #[prost(enumeration = "TaskStatus", tag = "4")]
+    pub status: i32,
+    /// Who created this task.
+    #[prost(string, tag = "5")]
+    pub created_by: ::prost::alloc::string::String,
+    /// Creation timestamp in milliseconds since epoch.
+    #[prost(int64, tag = "6")]
+    pub created_at_ms: i64,
+    /// Optional labels for filtering/routing.
+    #[prost(map = "string, string", tag = "7")]
+    pub labels: ::std::collections::HashMap<
+        ::prost::alloc::string::String,
+        ::prost::alloc::string::String,
+    >,
+    /// Runs associated with this task.
+    #[prost(message, repeated, tag = "8")]
+    pub runs: ::prost::alloc::vec::Vec<RunSummary>,
+    /// Maximum run duration in seconds before the run is failed (0 = no timeout).
+    #[prost(uint32, tag = "9")]
+    pub timeout_seconds: u32,
+    /// Tasks that must complete before this one is dispatched.
+    #[prost(string, repeated, tag = "10")]
+    pub depends_on: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    /// Scheduling lane (unspecified = batch).
+    #[prost(enumeration = "TaskPriority", tag = "11")]
+    pub priority: i32,
+    /// Environment runs of this task reproduce (unset = worker defaults).
+    #[prost(message, optional, tag = "12")]
+    pub pinned_manifest: ::core::option::Option<RunManifest>,
+    /// Seconds the task may wait in PENDING before it expires (0 = server default).
+    #[prost(uint32, tag = "13")]
+    pub pending_ttl_seconds: u32,
+    /// Details of the failure when no run explains it (e.g., it expired).
+    #[prost(string, tag = "14")]
+    pub failure_message: ::prost::alloc::string::String,
+    /// Why the task last failed or was cancelled.
+    #[prost(enumeration = "FailureReason", tag = "15")]
+    pub failure_reason: i32,
+    /// Namespace the task belongs to; only workers of the same namespace run it.
+    #[prost(string, tag = "16")]
+    pub namespace: ::prost::alloc::string::String,
+    /// Where the task stands while it waits for a worker (unset = not queued).
+    #[prost(message, optional, tag = "17")]
+    pub queue: ::core::option::Option<QueuePosition>,
+    /// Address of the HTTP client that created the task, resolved through
+    /// trusted proxies (empty = not created over HTTP).
+    #[prost(string, tag = "18")]
+    pub client_ip: ::prost::alloc::string::String,
+    /// IDs of uploaded files (POST /v1/files) staged in the run's workspace.
+    #[prost(string, repeated, tag = "19")]
+    pub input_files: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    /// Limits the worker enforces on each run (unset = the agent's limits).
+    #[prost(message, optional, tag = "20")]
+    pub limits: ::core::option::Option<RunLimits>,
+}
+/// QueuePosition tells a waiting client how long it may wait.
+#[derive(Clone, Copy, PartialEq, ::prost::Message)]
+pub struct QueuePosition {
+    /// 1 = next to be assigned among queued tasks of the same agent and namespace.
+    #[prost(uint32, tag = "1")]
+    pub position: u32,
+    /// Estimated seconds until the task is assigned (0 = no estimate).
+    #[prost(uint64, tag = "2")]
+    pub eta_seconds: u64,
+}
+/// RunSummary provides a summary of a run's execution.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RunSummary {
+    /// Unique run identifier.
+    #[prost(string, tag = "1")]
+    pub run_id: ::prost::alloc::string::String,
+    /// Worker that executed/is executing this run.
+    #[prost(string, tag = "2")]
+    pub worker_id: ::prost::alloc::string::String,
+    /// Current status of the run.
+    #[prost(enumeration = "RunStatus", tag = "3")]
+    pub status: i32,
+    /// When the run started (milliseconds since epoch).
+    #[prost(int64, tag = "4")]
+    pub started_at_ms: i64,
+    /// When the run finished (milliseconds since epoch).
+    #[prost(int64, tag = "5")]
+    pub finished_at_ms: i64,
+    /// Model backend used for execution.
+    #[prost(message, optional, tag = "6")]
+    pub backend_used: ::core::option::Option<ModelBackend>,
+    /// Error message if the run failed.
+    #[prost(string, tag = "7")]
+    pub error_message: ::prost::alloc::string::String,
+    /// Environment the run executed in (unset until the run starts).
+    #[prost(message, optional, tag = "8")]
+    pub manifest: ::core::option::Option<RunManifest>,
+    /// Why the run failed or was cancelled.
+    #[prost(enumeration = "FailureReason", tag = "9")]
+    pub failure_reason: i32,
+    /// Milestones the agent reported, in the order first reached.
+    #[prost(message, repeated, tag = "10")]
+    pub milestones: ::prost::alloc::vec::Vec<RunMilestone>,
+    /// What the run produced (unset until the worker reports it).
+    #[prost(message, optional, tag = "11")]
+    pub result: ::core::option::Option<RunResultSummary>,
+}
+/// Request to create a new task.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CreateTaskRequest {
+    /// Name of the agent to execute this task.
+    #[prost(string, tag = "1")]
+    pub agent_name: ::prost::alloc::string::String,
+    /// Input data as JSON string.
+    #[prost(string, tag = "2")]
+    pub input_json: ::prost::alloc::string::String,
+    /// Who is creating this task.
+    #[prost(string, tag = "3")]
+    pub created_by: ::prost::alloc::string::String,
+    /// Optional labels for filtering/routing.
+    #[prost(map = "string, string", tag = "4")]
+    pub labels: ::std::collections::HashMap<
+        ::prost::alloc::string::String,
+        ::prost::alloc::string::String,
+    >,
+    /// Maximum run duration in seconds before the run is failed (0 = no timeout).
+    #[prost(uint32, tag = "5")]
+    pub timeout_seconds: u32,
+    /// Scheduling lane (unspecified = batch).
+    #[prost(enumeration = "TaskPriority", tag = "6")]
+    pub priority: i32,
+    /// Seconds the task may wait in PENDING before it expires (0 = server default).
+    #[prost(uint32, tag = "7")]
+    pub pending_ttl_seconds: u32,
+    /// Namespace for the task (empty = the caller's, or "default").
+    #[prost(string, tag = "8")]
+    pub namespace: ::prost::alloc::string::String,
+    /// IDs of uploaded files (POST /v1/files) to stage in the run's workspace.
+    #[prost(string, repeated, tag = "9")]
+    pub input_files: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    /// Limits the worker enforces on each run (unset = the agent's limits).
+    #[prost(message, optional, tag = "10")]
+    pub limits: ::core::option::Option<RunLimits>,
+}
+/// Request to get a task by ID.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetTaskRequest {
+    /// Task ID (or unique prefix) to retrieve.
+    #[prost(string, tag = "1")]
+    pub id: ::prost::alloc::string::String,
+}
+/// Request to list tasks.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListTasksRequest {
+    /// Filter by status (optional, 0 = no filter).
+    #[prost(enumeration = "TaskStatus", tag = "1")]
+    pub status_filter: i32,
+    /// Filter by agent name (optional, empty = no filter).
+    #[prost(string, tag = "2")]
+    pub agent_filter: ::prost::alloc::string::String,
+    /// Maximum number of tasks to return (0 = 100, capped at 1000).
+    #[prost(int32, tag = "3")]
+    pub limit: i32,
+    /// Only tasks carrying all of these labels.
+    #[prost(map = "string, string", tag = "4")]
+    pub label_selector: ::std::collections::HashMap<
+        ::prost::alloc::string::String,
+        ::prost::alloc::string::String,
+    >,
+    /// Only tasks created at or after this Unix timestamp in ms (0 = no bound).
+    #[prost(int64, tag = "5")]
+    pub created_after_ms: i64,
+    /// Only tasks created before this Unix timestamp in ms (0 = no bound).
+    #[prost(int64, tag = "6")]
+    pub created_before_ms: i64,
+    /// Order of the returned tasks.
+    #[prost(enumeration = "TaskSortOrder", tag = "7")]
+    pub sort: i32,
+    /// `next_page_token` from a previous response with the same filters.
+    #[prost(string, tag = "8")]
+    pub page_token: ::prost::alloc::string::String,
+    /// Only tasks that failed or were cancelled for this reason (0 = any).
+    #[prost(enumeration = "FailureReason", tag = "9")]
+    pub failure_reason_filter: i32,
+    /// Only tasks in this namespace (empty = any). Callers bound to a namespace only see theirs.
+    #[prost(string, tag = "10")]
+    pub namespace_filter: ::prost::alloc::string::String,
+}
+/// Response containing a list of tasks.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListTasksResponse {
+    /// List of tasks matching the filter criteria.
+    #[prost(message, repeated, tag = "1")]
+    pub tasks: ::prost::alloc::vec::Vec<Task>,
+    /// Token for the next page (empty = no more tasks).
+    #[prost(string, tag = "2")]
+    pub next_page_token: ::prost::alloc::string::String,
+}
+/// Request to search tasks.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SearchTasksRequest {
+    /// Words that must all occur, case-insensitive. A trailing * matches a prefix.
+    #[prost(string, tag = "1")]
+    pub query: ::prost::alloc::string::String,
+    /// Maximum number of results (0 = server default of 50, at most 500).
+    #[prost(int32, tag = "2")]
+    pub limit: i32,
+    /// Only tasks in this namespace (empty = any). Callers bound to a namespace only see theirs.
+    #[prost(string, tag = "3")]
+    pub namespace: ::prost::alloc::string::String,
+}
+/// A task matching a search.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SearchTaskHit {
+    /// The matching task.
+    #[prost(message, optional, tag = "1")]
+    pub task: ::core::option::Option<Task>,
+    /// Fields a query word was found in: input, labels, agent, output.
+    #[prost(string, repeated, tag = "2")]
+    pub matched_fields: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+}
+/// Search results, newest task first.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SearchTasksResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub hits: ::prost::alloc::vec::Vec<SearchTaskHit>,
+}
+/// One problem found by ValidateTask.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ValidationFinding {
+    #[prost(enumeration = "ValidationSeverity", tag = "1")]
+    pub severity: i32,
+    /// Stable identifier, e.g. "no_worker_for_agent".
+    #[prost(string, tag = "2")]
+    pub code: ::prost::alloc::string::String,
+    /// Request field the finding is about (empty = the task as a whole).
+    #[prost(string, tag = "3")]
+    pub field: ::prost::alloc::string::String,
+    /// Human-readable explanation.
+    #[prost(string, tag = "4")]
+    pub message: ::prost::alloc::string::String,
+}
+/// Result of ValidateTask.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ValidateTaskResponse {
+    /// No errors (warnings allowed).
+    #[prost(bool, tag = "1")]
+    pub valid: bool,
+    #[prost(message, repeated, tag = "2")]
+    pub findings: ::prost::alloc::vec::Vec<ValidationFinding>,
+}
+/// Request for a group's summary.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetGroupSummaryRequest {
+    /// Group ID (value of the group or workflow_id label).
+    #[prost(string, tag = "1")]
+    pub group_id: ::prost::alloc::string::String,
+    /// Namespace of the group's tasks (empty = all visible to the caller).
+    #[prost(string, tag = "2")]
+    pub namespace: ::prost::alloc::string::String,
+}
+/// Aggregate progress of a group of tasks.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GroupSummary {
+    #[prost(string, tag = "1")]
+    pub group_id: ::prost::alloc::string::String,
+    /// Number of tasks in the group, and how many are in each status.
+    #[prost(uint32, tag = "2")]
+    pub total: u32,
+    #[prost(uint32, tag = "3")]
+    pub pending: u32,
+    #[prost(uint32, tag = "4")]
+    pub running: u32,
+    #[prost(uint32, tag = "5")]
+    pub completed: u32,
+    #[prost(uint32, tag = "6")]
+    pub failed: u32,
+    #[prost(uint32, tag = "7")]
+    pub cancelled: u32,
+    /// Run time summed over every run of every task.
+    #[prost(uint64, tag = "8")]
+    pub total_run_seconds: u64,
+    /// From the first task's creation to the last run's end (or now).
+    #[prost(uint64, tag = "9")]
+    pub elapsed_seconds: u64,
+    /// Cost reported by the runs, in USD (0 = none reported).
+    #[prost(double, tag = "10")]
+    pub total_cost_usd: f64,
+    /// Tasks with the longest run time, slowest first.
+    #[prost(message, repeated, tag = "11")]
+    pub slowest: ::prost::alloc::vec::Vec<GroupChild>,
+    /// Tasks that failed.
+    #[prost(message, repeated, tag = "12")]
+    pub failed_children: ::prost::alloc::vec::Vec<GroupChild>,
+}
+/// A task of a group, as listed in its summary.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GroupChild {
+    #[prost(string, tag = "1")]
+    pub task_id: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub agent_name: ::prost::alloc::string::String,
+    #[prost(enumeration = "TaskStatus", tag = "3")]
+    pub status: i32,
+    #[prost(uint64, tag = "4")]
+    pub duration_seconds: u64,
+    #[prost(string, tag = "5")]
+    pub failure_message: ::prost::alloc::string::String,
+}
+/// Request to cancel a task.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CancelTaskRequest {
+    /// Task ID (or unique prefix) to cancel.
+    #[prost(string, tag = "1")]
+    pub id: ::prost::alloc::string::String,
+}
+/// Selects tasks for a bulk operation. Unset fields match every task.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TaskFilter {
+    /// Only tasks in this status (0 = any).
+    #[prost(enumeration = "TaskStatus", tag = "1")]
+    pub status: i32,
+    /// Only tasks for this agent (empty = any).
+    #[prost(string, tag = "2")]
+    pub agent_name: ::prost::alloc::string::String,
+    /// Only tasks carrying all of these labels.
+    #[prost(map = "string, string", tag = "3")]
+    pub labels: ::std::collections::HashMap<
+        ::prost::alloc::string::String,
+        ::prost::alloc::string::String,
+    >,
+    /// Only tasks that failed or were cancelled for this reason (0 = any).
+    #[prost(enumeration = "FailureReason", tag = "4")]
+    pub failure_reason: i32,
+    /// Only tasks in this namespace (empty = any). Callers bound to a namespace only reach theirs.
+    #[prost(string, tag = "5")]
+    pub namespace: ::prost::alloc::string::String,
+}
+/// Request to cancel or retry the tasks matching a filter.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BulkTaskRequest {
+    /// Tasks to act on.
+    #[prost(message, optional, tag = "1")]
+    pub filter: ::core::option::Option<TaskFilter>,
+    /// Report the affected tasks without changing them.
+    #[prost(bool, tag = "2")]
+    pub dry_run: bool,
+}
+/// Result of a bulk operation.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BulkTaskResponse {
+    /// Tasks the operation applies to (matched by the filter and in a state it can act on).
+    #[prost(string, repeated, tag = "1")]
+    pub task_ids: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    /// Tasks the operation failed for (empty on a dry run).
+    #[prost(string, repeated, tag = "2")]
+    pub failed_task_ids: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    /// Whether this was a dry run.
+    #[prost(bool, tag = "3")]
+    pub dry_run: bool,
+}
+/// Selects the tasks a WatchTasks stream reports on. Unset fields match every task.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct WatchTasksRequest {
+    /// Only tasks in this namespace (empty = any). Callers bound to a namespace only see theirs.
+    #[prost(string, tag = "1")]
+    pub namespace: ::prost::alloc::string::String,
+    /// Only tasks for this agent (empty = any).
+    #[prost(string, tag = "2")]
+    pub agent_name: ::prost::alloc::string::String,
+    /// Only tasks carrying all of these labels.
+    #[prost(map = "string, string", tag = "3")]
+    pub labels: ::std::collections::HashMap<
+        ::prost::alloc::string::String,
+        ::prost::alloc::string::String,
+    >,
+    /// Only these tasks (full IDs; empty = any).
+    #[prost(string, repeated, tag = "4")]
+    pub task_ids: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    /// Also send run output chunks (frequent while runs stream output).
+    #[prost(bool, tag = "5")]
+    pub include_output: bool,
+}
+/// One change to a watched task.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TaskWatchEvent {
+    /// Task the change is about (empty for `lagged`).
+    #[prost(string, tag = "1")]
+    pub task_id: ::prost::alloc::string::String,
+    /// When the change was sent, in milliseconds since epoch.
+    #[prost(int64, tag = "2")]
+    pub timestamp_ms: i64,
+    #[prost(oneof = "task_watch_event::Event", tags = "3, 4, 5, 6, 7, 8")]
+    pub event: ::core::option::Option<task_watch_event::Event>,
+}
+/// Nested message and enum types in `TaskWatchEvent`.
+pub mod task_watch_event {
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum Event {
+        /// The task was created.
+        #[prost(message, tag = "3")]
+        Created(super::Task),
+        /// The task changed status.
+        #[prost(message, tag = "4")]
+        TaskStatus(super::TaskStatusChange),
+        /// One of the task's runs changed status.
+        #[prost(message, tag = "5")]
+        RunStatus(super::RunStatusChange),
+        /// A run produced output (only with include_output).
+        #[prost(message, tag = "6")]
+        Output(super::RunOutputChunkNotice),
+        /// A run reached an execution stage (tool use, session start, ...).
+        #[prost(message, tag = "7")]
+        RunEvent(super::RunEvent),
+        /// The stream fell behind and skipped changes; re-list tasks to catch up.
+        #[prost(message, tag = "8")]
+        Lagged(super::WatchLagged),
+    }
+}
+/// New status of a task.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TaskStatusChange {
+    #[prost(enumeration = "TaskStatus", tag = "1")]
+    pub status: i32,
+    /// Why the task failed or was cancelled (unset otherwise).
+    #[prost(enumeration = "FailureReason", tag = "2")]
+    pub failure_reason: i32,
+    /// Details of the failure when no run explains it.
+    #[prost(string, tag = "3")]
+    pub failure_message: ::prost::alloc::string::String,
+}
+/// New status of a run.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RunStatusChange {
+    #[prost(string, tag = "1")]
+    pub run_id: ::prost::alloc::string::String,
+    /// Worker running it (empty if unknown).
+    #[prost(string, tag = "2")]
+    pub worker_id: ::prost::alloc::string::String,
+    #[prost(enumeration = "RunStatus", tag = "3")]
+    pub status: i32,
+}
+/// Output produced by a run.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RunOutputChunkNotice {
+    #[prost(string, tag = "1")]
+    pub run_id: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub content: ::prost::alloc::string::String,
+}
+/// Changes a WatchTasks stream skipped because it fell behind.
+#[derive(Clone, Copy, PartialEq, ::prost::Message)]
+pub struct WatchLagged {
+    #[prost(uint64, tag = "1")]
+    pub missed: u64,
+}
+/// Request to tail a run's output.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct StreamRunOutputRequest {
+    /// Run ID (or unique prefix).
+    #[prost(string, tag = "1")]
+    pub run_id: ::prost::alloc::string::String,
+    /// First chunk to send. Chunks the control plane still stores are replayed
+    /// before live ones, so a client can resume from the last seq it saw + 1.
+    #[prost(uint64, tag = "2")]
+    pub from_seq: u64,
+}
+/// A chunk of a run's output.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RunOutputStreamChunk {
+    #[prost(string, tag = "1")]
+    pub run_id: ::prost::alloc::string::String,
+    /// Sequence number of the chunk (0-indexed, assigned by the worker). For the
+    /// final message, the next unused sequence number.
+    #[prost(uint64, tag = "2")]
+    pub seq: u64,
+    #[prost(string, tag = "3")]
+    pub content: ::prost::alloc::string::String,
+    /// Last message of the stream: the run finished. Carries no content.
+    #[prost(bool, tag = "4")]
+    pub is_final: bool,
+    /// When the worker produced the chunk (0 for replayed chunks).
+    #[prost(int64, tag = "5")]
+    pub timestamp_ms: i64,
+    /// Replayed from stored output rather than received live.
+    #[prost(bool, tag = "6")]
+    pub replayed: bool,
+    /// Status of the run (set on the final message).
+    #[prost(enumeration = "RunStatus", tag = "7")]
+    pub status: i32,
+}
+/// Order of tasks returned by ListTasks.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum TaskSortOrder {
+    /// Newest first.
+    Unspecified = 0,
+    CreatedDesc = 1,
+    CreatedAsc = 2,
+}
+impl TaskSortOrder {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            Self::Unspecified => "TASK_SORT_ORDER_UNSPECIFIED",
+            Self::CreatedDesc => "TASK_SORT_ORDER_CREATED_DESC",
+            Self::CreatedAsc => "TASK_SORT_ORDER_CREATED_ASC",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "TASK_SORT_ORDER_UNSPECIFIED" => Some(Self::Unspecified),
+            "TASK_SORT_ORDER_CREATED_DESC" => Some(Self::CreatedDesc),
+            "TASK_SORT_ORDER_CREATED_ASC" => Some(Self::CreatedAsc),
+            _ => None,
+        }
+    }
+}
+/// How bad a validation finding is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum ValidationSeverity {
+    Unspecified = 0,
+    /// CreateTask would fail.
+    Error = 1,
+    /// The task would be created, but may wait, expire or misbehave.
+    Warning = 2,
+}
+impl ValidationSeverity {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            Self::Unspecified => "VALIDATION_SEVERITY_UNSPECIFIED",
+            Self::Error => "VALIDATION_SEVERITY_ERROR",
+            Self::Warning => "VALIDATION_SEVERITY_WARNING",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "VALIDATION_SEVERITY_UNSPECIFIED" => Some(Self::Unspecified),
+            "VALIDATION_SEVERITY_ERROR" => Some(Self::Error),
+            "VALIDATION_SEVERITY_WARNING" => Some(Self::Warning),
+            _ => None,
+        }
+    }
+}
+/// Generated client implementations.
+pub mod task_service_client {
+    #![allow(
+        unused_variables,
+        dead_code,
+        missing_docs,
+        clippy::wildcard_imports,
+        clippy::let_unit_value,
+    )]
+    use tonic::codegen::*;
+    use tonic::codegen::http::Uri;
+    /// TaskService provides the client-facing API for creating and managing tasks.
+    #[derive(Debug, Clone)]
+    pub struct TaskServiceClient<T> {
+        inner: tonic::client::Grpc<T>,
+    }
+    impl TaskServiceClient<tonic::transport::Channel> {
+        /// Attempt to create a new client by connecting to a given endpoint.
+        pub async fn connect<D>(dst: D) -> Result<Self, tonic::transport::Error>
+        where
+            D: TryInto<tonic::transport::Endpoint>,
+            D::Error: Into<StdError>,
+        {
+            let conn = tonic::transport::Endpoint::new(dst)?.connect().await?;
+            Ok(Self::new(conn))
+        }
+    }
+    impl<T> TaskServiceClient<T>
+    where
+        T: tonic::client::GrpcService<tonic::body::BoxBody>,
+        T::Error: Into<StdError>,
+        T::ResponseBody: Body<Data = Bytes> + std::marker::Send + 'static,
+        <T::ResponseBody as Body>::Error: Into<StdError> + std::marker::Send,
+    {
+        pub fn new(inner: T) -> Self {
+            let inner = tonic::client::Grpc::new(inner);
+            Self { inner }
+        }
+        pub fn with_origin(inner: T, origin: Uri) -> Self {
+            let inner = tonic::client::Grpc::with_origin(inner, origin);
+            Self { inner }
+        }
+        pub fn with_interceptor<F>(
+            inner: T,
+            interceptor: F,
+        ) -> TaskServiceClient<InterceptedService<T, F>>
+        where
+            F: tonic::service::Interceptor,
+            T::ResponseBody: Default,
+            T: tonic::codegen::Service<
+                http::Request<tonic::body::BoxBody>,
+                Response = http::Response<
+                    <T as tonic::client::GrpcService<tonic::body::BoxBody>>::ResponseBody,
+                >,
+            >,
+            <T as tonic::codegen::Service<
+                http::Request<tonic::body::BoxBody>,
+            >>::Error: Into<StdError> + std::marker::Send + std::marker::Sync,
+        {
+            TaskServiceClient::new(InterceptedService::new(inner, interceptor))
+        }
+        /// Compress requests with the given encoding.
+        ///
+        /// This requires the server to support it otherwise it might respond with an
+        /// error.
+        #[must_use]
+        pub fn send_compressed(mut self, encoding: CompressionEncoding) -> Self {
+            self.inner = self.inner.send_compressed(encoding);
+            self
+        }
+        /// Enable decompressing responses.
+        #[must_use]
+        pub fn accept_compressed(mut self, encoding: CompressionEncoding) -> Self {
+            self.inner = self.inner.accept_compressed(encoding);
+            self
+        }
+        /// Limits the maximum size of a decoded message.
+        ///
+        /// Default: `4MB`
+        #[must_use]
+        pub fn max_decoding_message_size(mut self, limit: usize) -> Self {
+            self.inner = self.inner.max_decoding_message_size(limit);
+            self
+        }
+        /// Limits the maximum size of an encoded message.
+        ///
+        /// Default: `usize::MAX`
+        #[must_use]
+        pub fn max_encoding_message_size(mut self, limit: usize) -> Self {
+            self.inner = self.inner.max_encoding_message_size(limit);
+            self
+        }
+        /// Create a new task to be executed by an agent.
+        pub async fn create_task(
+            &mut self,
+            request: impl tonic::IntoRequest<super::CreateTaskRequest>,
+        ) -> std::result::Result<tonic::Response<super::Task>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/taskrun.v1.TaskService/CreateTask",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("taskrun.v1.TaskService", "CreateTask"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// Check a task spec (agent, input, quotas, scheduling) without creating it.
+        pub async fn validate_task(
+            &mut self,
+            request: impl tonic::IntoRequest<super::CreateTaskRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ValidateTaskResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/taskrun.v1.TaskService/ValidateTask",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("taskrun.v1.TaskService", "ValidateTask"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// Get a task by ID.
+        pub async fn get_task(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetTaskRequest>,
+        ) -> std::result::Result<tonic::Response<super::Task>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/taskrun.v1.TaskService/GetTask",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("taskrun.v1.TaskService", "GetTask"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// List tasks with optional filtering.
+        pub async fn list_tasks(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ListTasksRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ListTasksResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/taskrun.v1.TaskService/ListTasks",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("taskrun.v1.TaskService", "ListTasks"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// Full-text search over task input, labels, agent name and run output.
+        pub async fn search_tasks(
+            &mut self,
+            request: impl tonic::IntoRequest<super::SearchTasksRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::SearchTasksResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/taskrun.v1.TaskService/SearchTasks",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("taskrun.v1.TaskService", "SearchTasks"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// Aggregate progress of a group (tasks labeled group=<id> or workflow_id=<id>).
+        pub async fn get_group_summary(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetGroupSummaryRequest>,
+        ) -> std::result::Result<tonic::Response<super::GroupSummary>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/taskrun.v1.TaskService/GetGroupSummary",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("taskrun.v1.TaskService", "GetGroupSummary"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// Cancel a running or pending task.
+        pub async fn cancel_task(
+            &mut self,
+            request: impl tonic::IntoRequest<super::CancelTaskRequest>,
+        ) -> std::result::Result<tonic::Response<super::Task>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/taskrun.v1.TaskService/CancelTask",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("taskrun.v1.TaskService", "CancelTask"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// Cancel every active task matching a filter (admin only).
+        pub async fn bulk_cancel_tasks(
+            &mut self,
+            request: impl tonic::IntoRequest<super::BulkTaskRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::BulkTaskResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/taskrun.v1.TaskService/BulkCancelTasks",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("taskrun.v1.TaskService", "BulkCancelTasks"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// Retry every failed or cancelled task matching a filter (admin only).
+        pub async fn bulk_retry_tasks(
+            &mut self,
+            request: impl tonic::IntoRequest<super::BulkTaskRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::BulkTaskResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/taskrun.v1.TaskService/BulkRetryTasks",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("taskrun.v1.TaskService", "BulkRetryTasks"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// Stream task and run changes as they happen, instead of polling.
+        pub async fn watch_tasks(
+            &mut self,
+            request: impl tonic::IntoRequest<super::WatchTasksRequest>,
+        ) -> std::result::Result<
+            tonic::Response<tonic::codec::Streaming<super::TaskWatchEvent>>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/taskrun.v1.TaskService/WatchTasks",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("taskrun.v1.TaskService", "WatchTasks"));
+            self.inner.server_streaming(req, path, codec).await
+        }
+        /// Tail a run's output: stored chunks from a sequence number, then live ones.
+        pub async fn stream_run_output(
+            &mut self,
+            request: impl tonic::IntoRequest<super::StreamRunOutputRequest>,
+        ) -> std::result::Result<
+            tonic::Response<tonic::codec::Streaming<super::RunOutputStreamChunk>>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/taskrun.v1.TaskService/StreamRunOutput",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("taskrun.v1.TaskService", "StreamRunOutput"));
+            self.inner.server_streaming(req, path, codec).await
+        }
+    }
+}
+/// Generated server implementations.
+pub mod task_service_server {
+    #![allow(
+        unused_variables,
+        dead_code,
+        missing_docs,
+        clippy::wildcard_imports,
+        clippy::let_unit_value,
+    )]
+    use tonic::codegen::*;
+    /// Generated trait containing gRPC methods that should be implemented for use with TaskServiceServer.
+    #[async_trait]
+    pub trait TaskService: std::marker::Send + std::marker::Sync + 'static {
+        /// Create a new task to be executed by an agent.
+        async fn create_task(
+            &self,
+            request: tonic::Request<super::CreateTaskRequest>,
+        ) -> std::result::Result<tonic::Response<super::Task>, tonic::Status>;
+        /// Check a task spec (agent, input, quotas, scheduling) without creating it.
+        async fn validate_task(
+            &self,
+            request: tonic::Request<super::CreateTaskRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ValidateTaskResponse>,
+            tonic::Status,
+        >;
+        /// Get a task by ID.
+        async fn get_task(
+            &self,
+            request: tonic::Request<super::GetTaskRequest>,
+        ) -> std::result::Result<tonic::Response<super::Task>, tonic::Status>;
+        /// List tasks with optional filtering.
+        async fn list_tasks(
+            &self,
+            request: tonic::Request<super::ListTasksRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ListTasksResponse>,
+            tonic::Status,
+        >;
+        /// Full-text search over task input, labels, agent name and run output.
+        async fn search_tasks(
+            &self,
+            request: tonic::Request<super::SearchTasksRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::SearchTasksResponse>,
+            tonic::Status,
+        >;
+        /// Aggregate progress of a group (tasks labeled group=<id> or workflow_id=<id>).
+        async fn get_group_summary(
+            &self,
+            request: tonic::Request<super::GetGroupSummaryRequest>,
+        ) -> std::result::Result<tonic::Response<super::GroupSummary>, tonic::Status>;
+        /// Cancel a running or pending task.
+        async fn cancel_task(
+            &self,
+            request: tonic::Request<super::CancelTaskRequest>,
+        ) -> std::result::Result<tonic::Response<super::Task>, tonic::Status>;
+        /// Cancel every active task matching a filter (admin only).
+        async fn bulk_cancel_tasks(
+            &self,
+            request: tonic::Request<super::BulkTaskRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::BulkTaskResponse>,
+            tonic::Status,
+        >;
+        /// Retry every failed or cancelled task matching a filter (admin only).
+        async fn bulk_retry_tasks(
+            &self,
+            request: tonic::Request<super::BulkTaskRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::BulkTaskResponse>,
+            tonic::Status,
+        >;
+        /// Server streaming response type for the WatchTasks method.
+        type WatchTasksStream: tonic::codegen::tokio_stream::Stream<
+                Item = std::result::Result<super::TaskWatchEvent, tonic::Status>,
+            >
+            + std::marker::Send
+            + 'static;
+        /// Stream task and run changes as they happen, instead of polling.
+        async fn watch_tasks(
+            &self,
+            request: tonic::Request<super::WatchTasksRequest>,
+        ) -> std::result::Result<tonic::Response<Self::WatchTasksStream>, tonic::Status>;
+        /// Server streaming response type for the StreamRunOutput method.
+        type StreamRunOutputStream: tonic::codegen::tokio_stream::Stream<
+                Item = std::result::Result<super::RunOutputStreamChunk, tonic::Status>,
+            >
+            + std::marker::Send
+            + 'static;
+        /// Tail a run's output: stored chunks from a sequence number, then live ones.
+        async fn stream_run_output(
+            &self,
+            request: tonic::Request<super::StreamRunOutputRequest>,
+        ) -> std::result::Result<
+            tonic::Response<Self::StreamRunOutputStream>,
+            tonic::Status,
+        >;
+    }
+    /// TaskService provides the client-facing API for creating and managing tasks.
+    #[derive(Debug)]
+    pub struct TaskServiceServer<T> {
+        inner: Arc<T>,
+        accept_compression_encodings: EnabledCompressionEncodings,
+        send_compression_encodings: EnabledCompressionEncodings,
+        max_decoding_message_size: Option<usize>,
+        max_encoding_message_size: Option<usize>,
+    }
+    impl<T> TaskServiceServer<T> {
+        pub fn new(inner: T) -> Self {
+            Self::from_arc(Arc::new(inner))
+        }
+        pub fn from_arc(inner: Arc<T>) -> Self {
+            Self {
+                inner,
+                accept_compression_encodings: Default::default(),
+                send_compression_encodings: Default::default(),
+                max_decoding_message_size: None,
+                max_encoding_message_size: None,
+            }
+        }
+        pub fn with_interceptor<F>(
+            inner: T,
+            interceptor: F,
+        ) -> InterceptedService<Self, F>
+        where
+            F: tonic::service::Interceptor,
+        {
+            InterceptedService::new(Self::new(inner), interceptor)
+        }
+        /// Enable decompressing requests with the given encoding.
+        #[must_use]
+        pub fn accept_compressed(mut self, encoding: CompressionEncoding) -> Self {
+            self.accept_compression_encodings.enable(encoding);
+            self
+        }
+        /// Compress responses with the given encoding, if the client supports it.
+        #[must_use]
+        pub fn send_compressed(mut self, encoding: CompressionEncoding) -> Self {
+            self.send_compression_encodings.enable(encoding);
+            self
+        }
+        /// Limits the maximum size of a decoded message.
+        ///
+        /// Default: `4MB`
+        #[must_use]
+        pub fn max_decoding_message_size(mut self, limit: usize) -> Self {
+            self.max_decoding_message_size = Some(limit);
+            self
+        }
+        /// Limits the maximum size of an encoded message.
+        ///
+        /// Default: `usize::MAX`
+        #[must_use]
+        pub fn max_encoding_message_size(mut self, limit: usize) -> Self {
+            self.max_encoding_message_size = Some(limit);
+            self
+        }
+    }
+    impl<T, B> tonic::codegen::Service<http::Request<B>> for TaskServiceServer<T>
+    where
+        T: TaskService,
+        B: Body + std::marker::Send + 'static,
+        B::Error: Into<StdError> + std::marker::Send + 'static,
+    {
+        type Response = http::Response<tonic::body::BoxBody>;
+        type Error = std::convert::Infallible;
+        type Future = BoxFuture<Self::Response, Self::Error>;
+        fn poll_ready(
+            &mut self,
+            _cx: &mut Context<'_>,
+        ) -> Poll<std::result::Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+        fn call(&mut self, req: http::Request<B>) -> Self::Future {
+            match req.uri().path() {
+                "/taskrun.v1.TaskService/CreateTask" => {
+                    #[allow(non_camel_case_types)]
+                    struct CreateTaskSvc<T: TaskService>(pub Arc<T>);
+                    impl<
+                        T: TaskService,
+                    > tonic::server::UnaryService<super::CreateTaskRequest>
+                    for CreateTaskSvc<T> {
+                        type Response = super::Task;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::CreateTaskRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as TaskService>::create_task(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = CreateTaskSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/taskrun.v1.TaskService/ValidateTask" => {
+                    #[allow(non_camel_case_types)]
+                    struct ValidateTaskSvc<T: TaskService>(pub Arc<T>);
+                    impl<
+                        T: TaskService,
+                    > tonic::server::UnaryService<super::CreateTaskRequest>
+                    for ValidateTaskSvc<T> {
+                        type Response = super::ValidateTaskResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::CreateTaskRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as TaskService>::validate_task(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = ValidateTaskSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/taskrun.v1.TaskService/GetTask" => {
+                    #[allow(non_camel_case_types)]
+                    struct GetTaskSvc<T: TaskService>(pub Arc<T>);
+                    impl<
+                        T: TaskService,
+                    > tonic::server::UnaryService<super::GetTaskRequest>
+                    for GetTaskSvc<T> {
+                        type Response = super::Task;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::GetTaskRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as TaskService>::get_task(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = GetTaskSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/taskrun.v1.TaskService/ListTasks" => {
+                    #[allow(non_camel_case_types)]
+                    struct ListTasksSvc<T: TaskService>(pub Arc<T>);
+                    impl<
+                        T: TaskService,
+                    > tonic::server::UnaryService<super::ListTasksRequest>
+                    for ListTasksSvc<T> {
+                        type Response = super::ListTasksResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ListTasksRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as TaskService>::list_tasks(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = ListTasksSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/taskrun.v1.TaskService/SearchTasks" => {
+                    #[allow(non_camel_case_types)]
+                    struct SearchTasksSvc<T: TaskService>(pub Arc<T>);
+                    impl<
+                        T: TaskService,
+                    > tonic::server::UnaryService<super::SearchTasksRequest>
+                    for SearchTasksSvc<T> {
+                        type Response = super::SearchTasksResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::SearchTasksRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as TaskService>::search_tasks(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = SearchTasksSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/taskrun.v1.TaskService/GetGroupSummary" => {
+                    #[allow(non_camel_case_types)]
+                    struct GetGroupSummarySvc<T: TaskService>(pub Arc<T>);
+                    impl<
+                        T: TaskService,
+                    > tonic::server::UnaryService<super::GetGroupSummaryRequest>
+                    for GetGroupSummarySvc<T> {
+                        type Response = super::GroupSummary;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::GetGroupSummaryRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as TaskService>::get_group_summary(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = GetGroupSummarySvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/taskrun.v1.TaskService/CancelTask" => {
+                    #[allow(non_camel_case_types)]
+                    struct CancelTaskSvc<T: TaskService>(pub Arc<T>);
+                    impl<
+                        T: TaskService,
+                    > tonic::server::UnaryService<super::CancelTaskRequest>
+                    for CancelTaskSvc<T> {
+                        type Response = super::Task;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::CancelTaskRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as TaskService>::cancel_task(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = CancelTaskSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/taskrun.v1.TaskService/BulkCancelTasks" => {
+                    #[allow(non_camel_case_types)]
+                    struct BulkCancelTasksSvc<T: TaskService>(pub Arc<T>);
+                    impl<
+                        T: TaskService,
+                    > tonic::server::UnaryService<super::BulkTaskRequest>
+                    for BulkCancelTasksSvc<T> {
+                        type Response = super::BulkTaskResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::BulkTaskRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as TaskService>::bulk_cancel_tasks(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = BulkCancelTasksSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/taskrun.v1.TaskService/BulkRetryTasks" => {
+                    #[allow(non_camel_case_types)]
+                    struct BulkRetryTasksSvc<T: TaskService>(pub Arc<T>);
+                    impl<
+                        T: TaskService,
+                    > tonic::server::UnaryService<super::BulkTaskRequest>
+                    for BulkRetryTasksSvc<T> {
+                        type Response = super::BulkTaskResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::BulkTaskRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as TaskService>::bulk_retry_tasks(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = BulkRetryTasksSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/taskrun.v1.TaskService/WatchTasks" => {
+                    #[allow(non_camel_case_types)]
+                    struct WatchTasksSvc<T: TaskService>(pub Arc<T>);
+                    impl<
+                        T: TaskService,
+                    > tonic::server::ServerStreamingService<super::WatchTasksRequest>
+                    for WatchTasksSvc<T> {
+                        type Response = super::TaskWatchEvent;
+                        type ResponseStream = T::WatchTasksStream;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::ResponseStream>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::WatchTasksRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as TaskService>::watch_tasks(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = WatchTasksSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.server_streaming(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/taskrun.v1.TaskService/StreamRunOutput" => {
+                    #[allow(non_camel_case_types)]
+                    struct StreamRunOutputSvc<T: TaskService>(pub Arc<T>);
+                    impl<
+                        T: TaskService,
+                    > tonic::server::ServerStreamingService<
+                        super::StreamRunOutputRequest,
+                    > for StreamRunOutputSvc<T> {
+                        type Response = super::RunOutputStreamChunk;
+                        type ResponseStream = T::StreamRunOutputStream;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::ResponseStream>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::StreamRunOutputRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as TaskService>::stream_run_output(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = StreamRunOutputSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.server_streaming(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                _ => {
+                    Box::pin(async move {
+                        let mut response = http::Response::new(empty_body());
+                        let headers = response.headers_mut();
+                        headers
+                            .insert(
+                                tonic::Status::GRPC_STATUS,
+                                (tonic::Code::Unimplemented as i32).into(),
+                            );
+                        headers
+                            .insert(
                                 http::header::CONTENT_TYPE,
                                 tonic::metadata::GRPC_CONTENT_TYPE,
                             );
@@ -794,7 +5615,7 @@ pub mod run_service_server {
             }
         }
     }
-    impl<T> Clone for RunServiceServer<T> {
+    impl<T> Clone for TaskServiceServer<T> {
         fn clone(&self) -> Self {
             let inner = self.inner.clone();
             Self {
@@ -807,122 +5628,122 @@ pub mod run_service_server {
         }
     }
     /// Generated gRPC service name
-    pub const SERVICE_NAME: &str = "taskrun.v1.RunService";
-    impl<T> tonic::server::NamedService for RunServiceServer<T> {
+    pub const SERVICE_NAME: &str = "taskrun.v1.TaskService";
+    impl<T> tonic::server::NamedService for TaskServiceServer<T> {
         const NAME: &'static str = SERVICE_NAME;
     }
 }
-/// Task represents a unit of work to be executed by an agent.
+/// A connected worker with its current state
 #[derive(Clone, PartialEq, ::prost::Message)]
-pub struct Task {
-    /// Unique task identifier.
+pub struct Worker {
+    /// Unique worker identifier
     #[prost(string, tag = "1")]
-    pub id: ::prost::alloc::string::String,
-    /// Name of the agent to execute this task.
+    pub worker_id: ::prost::alloc::string::String,
+    /// Hostname of the worker machine
     #[prost(string, tag = "2")]
-    pub agent_name: ::prost::alloc::string::String,
-    /// Input data as JSON string.
+    pub hostname: ::prost::alloc::string::String,
+    /// Worker binary version
     #[prost(string, tag = "3")]
-    pub input_json: ::prost::alloc::string::String,
-    /// Current status of the task.
-    #[prost(enumeration = "TaskStatus", tag = "4")]
+    pub version: ::prost::alloc::string::String,
+    /// Current status
+    #[prost(enumeration = "WorkerStatus", tag = "4")]
     pub status: i32,
-    /// Who created this task.
-    #[prost(string, tag = "5")]
-    pub created_by: ::prost::alloc::string::String,
-    /// Creation timestamp in milliseconds since epoch.
-    #[prost(int64, tag = "6")]
-    pub created_at_ms: i64,
-    /// Optional labels for filtering/routing.
-    #[prost(map = "string, string", tag = "7")]
+    /// Agents available on this worker
+    #[prost(message, repeated, tag = "5")]
+    pub agents: ::prost::alloc::vec::Vec<AgentSpec>,
+    /// Worker-level labels
+    #[prost(map = "string, string", tag = "6")]
     pub labels: ::std::collections::HashMap<
         ::prost::alloc::string::String,
         ::prost::alloc::string::String,
     >,
-    /// Runs associated with this task.
-    #[prost(message, repeated, tag = "8")]
-    pub runs: ::prost::alloc::vec::Vec<RunSummary>,
+    /// Number of currently active runs
+    #[prost(uint32, tag = "7")]
+    pub active_runs: u32,
+    /// Maximum concurrent runs this worker can handle
+    #[prost(uint32, tag = "8")]
+    pub max_concurrent_runs: u32,
+    /// Timestamp of last heartbeat (Unix millis)
+    #[prost(int64, tag = "9")]
+    pub last_heartbeat_ms: i64,
+    /// Namespace whose tasks this worker runs
+    #[prost(string, tag = "10")]
+    pub namespace: ::prost::alloc::string::String,
+    /// When draining started (Unix millis, 0 = not draining)
+    #[prost(int64, tag = "11")]
+    pub draining_since_ms: i64,
 }
-/// RunSummary provides a summary of a run's execution.
 #[derive(Clone, PartialEq, ::prost::Message)]
-pub struct RunSummary {
-    /// Unique run identifier.
+pub struct ListWorkersRequest {
+    /// Filter by agent name (optional)
+    #[prost(string, optional, tag = "1")]
+    pub agent_name: ::core::option::Option<::prost::alloc::string::String>,
+    /// Filter by worker status (optional)
+    #[prost(enumeration = "WorkerStatus", optional, tag = "2")]
+    pub status: ::core::option::Option<i32>,
+    /// Filter by namespace (optional; callers bound to a namespace only see theirs)
+    #[prost(string, optional, tag = "3")]
+    pub namespace: ::core::option::Option<::prost::alloc::string::String>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListWorkersResponse {
+    /// List of workers matching the filter
+    #[prost(message, repeated, tag = "1")]
+    pub workers: ::prost::alloc::vec::Vec<Worker>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetWorkerRequest {
+    /// Worker ID to retrieve
     #[prost(string, tag = "1")]
-    pub run_id: ::prost::alloc::string::String,
-    /// Worker that executed/is executing this run.
-    #[prost(string, tag = "2")]
     pub worker_id: ::prost::alloc::string::String,
-    /// Current status of the run.
-    #[prost(enumeration = "RunStatus", tag = "3")]
-    pub status: i32,
-    /// When the run started (milliseconds since epoch).
-    #[prost(int64, tag = "4")]
-    pub started_at_ms: i64,
-    /// When the run finished (milliseconds since epoch).
-    #[prost(int64, tag = "5")]
-    pub finished_at_ms: i64,
-    /// Model backend used for execution.
-    #[prost(message, optional, tag = "6")]
-    pub backend_used: ::core::option::Option<ModelBackend>,
-    /// Error message if the run failed.
-    #[prost(string, tag = "7")]
-    pub error_message: ::prost::alloc::string::String,
 }
-/// Request to create a new task.
 #[derive(Clone, PartialEq, ::prost::Message)]
-pub struct CreateTaskRequest {
-    /// Name of the agent to execute this task.
+pub struct DrainWorkerRequest {
+    /// Worker ID (or unique prefix) to drain
     #[prost(string, tag = "1")]
-    pub agent_name: ::prost::alloc::string::String,
-    /// Input data as JSON string.
-    #[prost(string, tag = "2")]
-    pub input_json: ::prost::alloc::string::String,
-    /// Who is creating this task.
-    #[prost(string, tag = "3")]
-    pub created_by: ::prost::alloc::string::String,
-    /// Optional labels for filtering/routing.
-    #[prost(map = "string, string", tag = "4")]
-    pub labels: ::std::collections::HashMap<
-        ::prost::alloc::string::String,
-        ::prost::alloc::string::String,
-    >,
+    pub worker_id: ::prost::alloc::string::String,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DrainWorkerResponse {
+    /// The worker, with status DRAINING
+    #[prost(message, optional, tag = "1")]
+    pub worker: ::core::option::Option<Worker>,
+    /// Runs still executing on the worker; drained when empty
+    #[prost(string, repeated, tag = "2")]
+    pub active_run_ids: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
 }
-/// Request to get a task by ID.
 #[derive(Clone, PartialEq, ::prost::Message)]
-pub struct GetTaskRequest {
-    /// Task ID to retrieve.
+pub struct UndrainWorkerRequest {
+    /// Worker ID (or unique prefix) to undrain
     #[prost(string, tag = "1")]
-    pub id: ::prost::alloc::string::String,
+    pub worker_id: ::prost::alloc::string::String,
 }
-/// Request to list tasks.
 #[derive(Clone, PartialEq, ::prost::Message)]
-pub struct ListTasksRequest {
-    /// Filter by status (optional, 0 = no filter).
-    #[prost(enumeration = "TaskStatus", tag = "1")]
-    pub status_filter: i32,
-    /// Filter by agent name (optional, empty = no filter).
+pub struct DisconnectWorkerRequest {
+    /// Worker ID (or unique prefix) to disconnect
+    #[prost(string, tag = "1")]
+    pub worker_id: ::prost::alloc::string::String,
+    /// Why, recorded in the audit log and sent to the worker
     #[prost(string, tag = "2")]
-    pub agent_filter: ::prost::alloc::string::String,
-    /// Maximum number of tasks to return.
-    #[prost(int32, tag = "3")]
-    pub limit: i32,
-}
-/// Response containing a list of tasks.
-#[derive(Clone, PartialEq, ::prost::Message)]
-pub struct ListTasksResponse {
-    /// List of tasks matching the filter criteria.
-    #[prost(message, repeated, tag = "1")]
-    pub tasks: ::prost::alloc::vec::Vec<Task>,
+    pub reason: ::prost::alloc::string::String,
+    /// Put the worker's running tasks back in the queue for other workers
+    #[prost(bool, tag = "3")]
+    pub reschedule: bool,
 }
-/// Request to cancel a task.
 #[derive(Clone, PartialEq, ::prost::Message)]
-pub struct CancelTaskRequest {
-    /// Task ID to cancel.
+pub struct DisconnectWorkerResponse {
+    /// Worker that was disconnected
     #[prost(string, tag = "1")]
-    pub id: ::prost::alloc::string::String,
+    pub worker_id: ::prost::alloc::string::String,
+    /// Runs that were executing on the worker, now failed
+    #[prost(string, repeated, tag = "2")]
+    pub failed_run_ids: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    /// Tasks of those runs that went back to Pending (reschedule only)
+    #[prost(string, repeated, tag = "3")]
+    pub rescheduled_task_ids: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
 }
 /// Generated client implementations.
-pub mod task_service_client {
+pub mod worker_service_client {
     #![allow(
         unused_variables,
         dead_code,
@@ -932,12 +5753,11 @@ pub mod task_service_client {
     )]
     use tonic::codegen::*;
     use tonic::codegen::http::Uri;
-    /// TaskService provides the client-facing API for creating and managing tasks.
     #[derive(Debug, Clone)]
-    pub struct TaskServiceClient<T> {
+    pub struct WorkerServiceClient<T> {
         inner: tonic::client::Grpc<T>,
     }
-    impl TaskServiceClient<tonic::transport::Channel> {
+    impl WorkerServiceClient<tonic::transport::Channel> {
         /// Attempt to create a new client by connecting to a given endpoint.
         pub async fn connect<D>(dst: D) -> Result<Self, tonic::transport::Error>
         where
@@ -948,7 +5768,7 @@ pub mod task_service_client {
             Ok(Self::new(conn))
         }
     }
-    impl<T> TaskServiceClient<T>
+    impl<T> WorkerServiceClient<T>
     where
         T: tonic::client::GrpcService<tonic::body::BoxBody>,
         T::Error: Into<StdError>,
@@ -966,7 +5786,7 @@ pub mod task_service_client {
         pub fn with_interceptor<F>(
             inner: T,
             interceptor: F,
-        ) -> TaskServiceClient<InterceptedService<T, F>>
+        ) -> WorkerServiceClient<InterceptedService<T, F>>
         where
             F: tonic::service::Interceptor,
             T::ResponseBody: Default,
@@ -980,7 +5800,7 @@ pub mod task_service_client {
                 http::Request<tonic::body::BoxBody>,
             >>::Error: Into<StdError> + std::marker::Send + std::marker::Sync,
         {
-            TaskServiceClient::new(InterceptedService::new(inner, interceptor))
+            WorkerServiceClient::new(InterceptedService::new(inner, interceptor))
         }
         /// Compress requests with the given encoding.
         ///
@@ -1013,11 +5833,14 @@ pub mod task_service_client {
             self.inner = self.inner.max_encoding_message_size(limit);
             self
         }
-        /// Create a new task to be executed by an agent.
-        pub async fn create_task(
+        /// List all connected workers with optional filtering
+        pub async fn list_workers(
             &mut self,
-            request: impl tonic::IntoRequest<super::CreateTaskRequest>,
-        ) -> std::result::Result<tonic::Response<super::Task>, tonic::Status> {
+            request: impl tonic::IntoRequest<super::ListWorkersRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ListWorkersResponse>,
+            tonic::Status,
+        > {
             self.inner
                 .ready()
                 .await
@@ -1028,18 +5851,18 @@ pub mod task_service_client {
                 })?;
             let codec = tonic::codec::ProstCodec::default();
             let path = http::uri::PathAndQuery::from_static(
-                "/taskrun.v1.TaskService/CreateTask",
+                "/taskrun.v1.WorkerService/ListWorkers",
             );
             let mut req = request.into_request();
             req.extensions_mut()
-                .insert(GrpcMethod::new("taskrun.v1.TaskService", "CreateTask"));
+                .insert(GrpcMethod::new("taskrun.v1.WorkerService", "ListWorkers"));
             self.inner.unary(req, path, codec).await
         }
-        /// Get a task by ID.
-        pub async fn get_task(
+        /// Get details for a specific worker
+        pub async fn get_worker(
             &mut self,
-            request: impl tonic::IntoRequest<super::GetTaskRequest>,
-        ) -> std::result::Result<tonic::Response<super::Task>, tonic::Status> {
+            request: impl tonic::IntoRequest<super::GetWorkerRequest>,
+        ) -> std::result::Result<tonic::Response<super::Worker>, tonic::Status> {
             self.inner
                 .ready()
                 .await
@@ -1050,19 +5873,19 @@ pub mod task_service_client {
                 })?;
             let codec = tonic::codec::ProstCodec::default();
             let path = http::uri::PathAndQuery::from_static(
-                "/taskrun.v1.TaskService/GetTask",
+                "/taskrun.v1.WorkerService/GetWorker",
             );
             let mut req = request.into_request();
             req.extensions_mut()
-                .insert(GrpcMethod::new("taskrun.v1.TaskService", "GetTask"));
+                .insert(GrpcMethod::new("taskrun.v1.WorkerService", "GetWorker"));
             self.inner.unary(req, path, codec).await
         }
-        /// List tasks with optional filtering.
-        pub async fn list_tasks(
+        /// Stop assigning new runs to a worker; its active runs finish normally
+        pub async fn drain_worker(
             &mut self,
-            request: impl tonic::IntoRequest<super::ListTasksRequest>,
+            request: impl tonic::IntoRequest<super::DrainWorkerRequest>,
         ) -> std::result::Result<
-            tonic::Response<super::ListTasksResponse>,
+            tonic::Response<super::DrainWorkerResponse>,
             tonic::Status,
         > {
             self.inner
@@ -1075,18 +5898,18 @@ pub mod task_service_client {
                 })?;
             let codec = tonic::codec::ProstCodec::default();
             let path = http::uri::PathAndQuery::from_static(
-                "/taskrun.v1.TaskService/ListTasks",
+                "/taskrun.v1.WorkerService/DrainWorker",
             );
             let mut req = request.into_request();
             req.extensions_mut()
-                .insert(GrpcMethod::new("taskrun.v1.TaskService", "ListTasks"));
+                .insert(GrpcMethod::new("taskrun.v1.WorkerService", "DrainWorker"));
             self.inner.unary(req, path, codec).await
         }
-        /// Cancel a running or pending task.
-        pub async fn cancel_task(
+        /// Let a draining worker receive runs again
+        pub async fn undrain_worker(
             &mut self,
-            request: impl tonic::IntoRequest<super::CancelTaskRequest>,
-        ) -> std::result::Result<tonic::Response<super::Task>, tonic::Status> {
+            request: impl tonic::IntoRequest<super::UndrainWorkerRequest>,
+        ) -> std::result::Result<tonic::Response<super::Worker>, tonic::Status> {
             self.inner
                 .ready()
                 .await
@@ -1097,17 +5920,42 @@ pub mod task_service_client {
                 })?;
             let codec = tonic::codec::ProstCodec::default();
             let path = http::uri::PathAndQuery::from_static(
-                "/taskrun.v1.TaskService/CancelTask",
+                "/taskrun.v1.WorkerService/UndrainWorker",
             );
             let mut req = request.into_request();
             req.extensions_mut()
-                .insert(GrpcMethod::new("taskrun.v1.TaskService", "CancelTask"));
+                .insert(GrpcMethod::new("taskrun.v1.WorkerService", "UndrainWorker"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// Close a worker's stream, failing (and optionally rescheduling) its running tasks
+        pub async fn disconnect_worker(
+            &mut self,
+            request: impl tonic::IntoRequest<super::DisconnectWorkerRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::DisconnectWorkerResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/taskrun.v1.WorkerService/DisconnectWorker",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("taskrun.v1.WorkerService", "DisconnectWorker"));
             self.inner.unary(req, path, codec).await
         }
     }
 }
 /// Generated server implementations.
-pub mod task_service_server {
+pub mod worker_service_server {
     #![allow(
         unused_variables,
         dead_code,
@@ -1116,43 +5964,53 @@ pub mod task_service_server {
         clippy::let_unit_value,
     )]
     use tonic::codegen::*;
-    /// Generated trait containing gRPC methods that should be implemented for use with TaskServiceServer.
+    /// Generated trait containing gRPC methods that should be implemented for use with WorkerServiceServer.
     #[async_trait]
-    pub trait TaskService: std::marker::Send + std::marker::Sync + 'static {
-        /// Create a new task to be executed by an agent.
-        async fn create_task(
+    pub trait WorkerService: std::marker::Send + std::marker::Sync + 'static {
+        /// List all connected workers with optional filtering
+        async fn list_workers(
             &self,
-            request: tonic::Request<super::CreateTaskRequest>,
-        ) -> std::result::Result<tonic::Response<super::Task>, tonic::Status>;
-        /// Get a task by ID.
-        async fn get_task(
+            request: tonic::Request<super::ListWorkersRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ListWorkersResponse>,
+            tonic::Status,
+        >;
+        /// Get details for a specific worker
+        async fn get_worker(
             &self,
-            request: tonic::Request<super::GetTaskRequest>,
-        ) -> std::result::Result<tonic::Response<super::Task>, tonic::Status>;
-        /// List tasks with optional filtering.
-        async fn list_tasks(
+            request: tonic::Request<super::GetWorkerRequest>,
+        ) -> std::result::Result<tonic::Response<super::Worker>, tonic::Status>;
+        /// Stop assigning new runs to a worker; its active runs finish normally
+        async fn drain_worker(
             &self,
-            request: tonic::Request<super::ListTasksRequest>,
+            request: tonic::Request<super::DrainWorkerRequest>,
         ) -> std::result::Result<
-            tonic::Response<super::ListTasksResponse>,
+            tonic::Response<super::DrainWorkerResponse>,
             tonic::Status,
         >;
-        /// Cancel a running or pending task.
-        async fn cancel_task(
+        /// Let a draining worker receive runs again
+        async fn undrain_worker(
             &self,
-            request: tonic::Request<super::CancelTaskRequest>,
-        ) -> std::result::Result<tonic::Response<super::Task>, tonic::Status>;
+            request: tonic::Request<super::UndrainWorkerRequest>,
+        ) -> std::result::Result<tonic::Response<super::Worker>, tonic::Status>;
+        /// Close a worker's stream, failing (and optionally rescheduling) its running tasks
+        async fn disconnect_worker(
+            &self,
+            request: tonic::Request<super::DisconnectWorkerRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::DisconnectWorkerResponse>,
+            tonic::Status,
+        >;
     }
-    /// TaskService provides the client-facing API for creating and managing tasks.
     #[derive(Debug)]
-    pub struct TaskServiceServer<T> {
+    pub struct WorkerServiceServer<T> {
         inner: Arc<T>,
         accept_compression_encodings: EnabledCompressionEncodings,
         send_compression_encodings: EnabledCompressionEncodings,
         max_decoding_message_size: Option<usize>,
         max_encoding_message_size: Option<usize>,
     }
-    impl<T> TaskServiceServer<T> {
+    impl<T> WorkerServiceServer<T> {
         pub fn new(inner: T) -> Self {
             Self::from_arc(Arc::new(inner))
         }
@@ -1203,9 +6061,9 @@ pub mod task_service_server {
             self
         }
     }
-    impl<T, B> tonic::codegen::Service<http::Request<B>> for TaskServiceServer<T>
+    impl<T, B> tonic::codegen::Service<http::Request<B>> for WorkerServiceServer<T>
     where
-        T: TaskService,
+        T: WorkerService,
         B: Body + std::marker::Send + 'static,
         B::Error: Into<StdError> + std::marker::Send + 'static,
     {
@@ -1220,25 +6078,25 @@ pub mod task_service_server {
         }
         fn call(&mut self, req: http::Request<B>) -> Self::Future {
             match req.uri().path() {
-                "/taskrun.v1.TaskService/CreateTask" => {
+                "/taskrun.v1.WorkerService/ListWorkers" => {
                     #[allow(non_camel_case_types)]
-                    struct CreateTaskSvc<T: TaskService>(pub Arc<T>);
+                    struct ListWorkersSvc<T: WorkerService>(pub Arc<T>);
                     impl<
-                        T: TaskService,
-                    > tonic::server::UnaryService<super::CreateTaskRequest>
-                    for CreateTaskSvc<T> {
-                        type Response = super::Task;
+                        T: WorkerService,
+                    > tonic::server::UnaryService<super::ListWorkersRequest>
+                    for ListWorkersSvc<T> {
+                        type Response = super::ListWorkersResponse;
                         type Future = BoxFuture<
                             tonic::Response<Self::Response>,
                             tonic::Status,
                         >;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::CreateTaskRequest>,
+                            request: tonic::Request<super::ListWorkersRequest>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
                             let fut = async move {
-                                <T as TaskService>::create_task(&inner, request).await
+                                <T as WorkerService>::list_workers(&inner, request).await
                             };
                             Box::pin(fut)
                         }
@@ -1249,7 +6107,7 @@ pub mod task_service_server {
                     let max_encoding_message_size = self.max_encoding_message_size;
                     let inner = self.inner.clone();
                     let fut = async move {
-                        let method = CreateTaskSvc(inner);
+                        let method = ListWorkersSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec)
                             .apply_compression_config(
@@ -1265,25 +6123,25 @@ pub mod task_service_server {
                     };
                     Box::pin(fut)
                 }
-                "/taskrun.v1.TaskService/GetTask" => {
+                "/taskrun.v1.WorkerService/GetWorker" => {
                     #[allow(non_camel_case_types)]
-                    struct GetTaskSvc<T: TaskService>(pub Arc<T>);
+                    struct GetWorkerSvc<T: WorkerService>(pub Arc<T>);
                     impl<
-                        T: TaskService,
-                    > tonic::server::UnaryService<super::GetTaskRequest>
-                    for GetTaskSvc<T> {
-                        type Response = super::Task;
+                        T: WorkerService,
+                    > tonic::server::UnaryService<super::GetWorkerRequest>
+                    for GetWorkerSvc<T> {
+                        type Response = super::Worker;
                         type Future = BoxFuture<
                             tonic::Response<Self::Response>,
                             tonic::Status,
                         >;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::GetTaskRequest>,
+                            request: tonic::Request<super::GetWorkerRequest>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
                             let fut = async move {
-                                <T as TaskService>::get_task(&inner, request).await
+                                <T as WorkerService>::get_worker(&inner, request).await
                             };
                             Box::pin(fut)
                         }
@@ -1294,7 +6152,7 @@ pub mod task_service_server {
                     let max_encoding_message_size = self.max_encoding_message_size;
                     let inner = self.inner.clone();
                     let fut = async move {
-                        let method = GetTaskSvc(inner);
+                        let method = GetWorkerSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec)
                             .apply_compression_config(
@@ -1310,25 +6168,25 @@ pub mod task_service_server {
                     };
                     Box::pin(fut)
                 }
-                "/taskrun.v1.TaskService/ListTasks" => {
+                "/taskrun.v1.WorkerService/DrainWorker" => {
                     #[allow(non_camel_case_types)]
-                    struct ListTasksSvc<T: TaskService>(pub Arc<T>);
+                    struct DrainWorkerSvc<T: WorkerService>(pub Arc<T>);
                     impl<
-                        T: TaskService,
-                    > tonic::server::UnaryService<super::ListTasksRequest>
-                    for ListTasksSvc<T> {
-                        type Response = super::ListTasksResponse;
+                        T: WorkerService,
+                    > tonic::server::UnaryService<super::DrainWorkerRequest>
+                    for DrainWorkerSvc<T> {
+                        type Response = super::DrainWorkerResponse;
                         type Future = BoxFuture<
                             tonic::Response<Self::Response>,
                             tonic::Status,
                         >;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::ListTasksRequest>,
+                            request: tonic::Request<super::DrainWorkerRequest>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
                             let fut = async move {
-                                <T as TaskService>::list_tasks(&inner, request).await
+                                <T as WorkerService>::drain_worker(&inner, request).await
                             };
                             Box::pin(fut)
                         }
@@ -1339,7 +6197,7 @@ pub mod task_service_server {
                     let max_encoding_message_size = self.max_encoding_message_size;
                     let inner = self.inner.clone();
                     let fut = async move {
-                        let method = ListTasksSvc(inner);
+                        let method = DrainWorkerSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec)
                             .apply_compression_config(
@@ -1355,25 +6213,25 @@ pub mod task_service_server {
                     };
                     Box::pin(fut)
                 }
-                "/taskrun.v1.TaskService/CancelTask" => {
+                "/taskrun.v1.WorkerService/UndrainWorker" => {
                     #[allow(non_camel_case_types)]
-                    struct CancelTaskSvc<T: TaskService>(pub Arc<T>);
+                    struct UndrainWorkerSvc<T: WorkerService>(pub Arc<T>);
                     impl<
-                        T: TaskService,
-                    > tonic::server::UnaryService<super::CancelTaskRequest>
-                    for CancelTaskSvc<T> {
-                        type Response = super::Task;
+                        T: WorkerService,
+                    > tonic::server::UnaryService<super::UndrainWorkerRequest>
+                    for UndrainWorkerSvc<T> {
+                        type Response = super::Worker;
                         type Future = BoxFuture<
                             tonic::Response<Self::Response>,
                             tonic::Status,
                         >;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::CancelTaskRequest>,
+                            request: tonic::Request<super::UndrainWorkerRequest>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
                             let fut = async move {
-                                <T as TaskService>::cancel_task(&inner, request).await
+                                <T as WorkerService>::undrain_worker(&inner, request).await
                             };
                             Box::pin(fut)
                         }
@@ -1384,7 +6242,53 @@ pub mod task_service_server {
                     let max_encoding_message_size = self.max_encoding_message_size;
                     let inner = self.inner.clone();
                     let fut = async move {
-                        let method = CancelTaskSvc(inner);
+                        let method = UndrainWorkerSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/taskrun.v1.WorkerService/DisconnectWorker" => {
+                    #[allow(non_camel_case_types)]
+                    struct DisconnectWorkerSvc<T: WorkerService>(pub Arc<T>);
+                    impl<
+                        T: WorkerService,
+                    > tonic::server::UnaryService<super::DisconnectWorkerRequest>
+                    for DisconnectWorkerSvc<T> {
+                        type Response = super::DisconnectWorkerResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::DisconnectWorkerRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as WorkerService>::disconnect_worker(&inner, request)
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = DisconnectWorkerSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec)
                             .apply_compression_config(
@@ -1420,7 +6324,7 @@ pub mod task_service_server {
             }
         }
     }
-    impl<T> Clone for TaskServiceServer<T> {
+    impl<T> Clone for WorkerServiceServer<T> {
         fn clone(&self) -> Self {
             let inner = self.inner.clone();
             Self {
@@ -1433,68 +6337,175 @@ pub mod task_service_server {
         }
     }
     /// Generated gRPC service name
-    pub const SERVICE_NAME: &str = "taskrun.v1.TaskService";
-    impl<T> tonic::server::NamedService for TaskServiceServer<T> {
+    pub const SERVICE_NAME: &str = "taskrun.v1.WorkerService";
+    impl<T> tonic::server::NamedService for WorkerServiceServer<T> {
         const NAME: &'static str = SERVICE_NAME;
     }
 }
-/// A connected worker with its current state
+/// A step of a workflow template.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct WorkflowTemplateStep {
+    /// Step name, unique within the template.
+    #[prost(string, tag = "1")]
+    pub name: ::prost::alloc::string::String,
+    /// Name of the agent to execute.
+    #[prost(string, tag = "2")]
+    pub agent_name: ::prost::alloc::string::String,
+    /// Input JSON template. {{<param>}} and {{steps.<step>.output}} are replaced.
+    #[prost(string, tag = "3")]
+    pub input_template: ::prost::alloc::string::String,
+    /// Steps that must complete first.
+    #[prost(string, repeated, tag = "4")]
+    pub depends_on: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    /// Run timeout in seconds (0 = no timeout).
+    #[prost(uint32, tag = "5")]
+    pub timeout_seconds: u32,
+}
+/// A named multi-step pipeline.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct WorkflowTemplate {
+    /// Unique template name.
+    #[prost(string, tag = "1")]
+    pub name: ::prost::alloc::string::String,
+    /// Human-readable description.
+    #[prost(string, tag = "2")]
+    pub description: ::prost::alloc::string::String,
+    /// Parameters required by CreateWorkflow.
+    #[prost(string, repeated, tag = "3")]
+    pub params: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    /// Steps of the pipeline.
+    #[prost(message, repeated, tag = "4")]
+    pub steps: ::prost::alloc::vec::Vec<WorkflowTemplateStep>,
+}
+/// The task a workflow step was expanded into.
 #[derive(Clone, PartialEq, ::prost::Message)]
-pub struct Worker {
-    /// Unique worker identifier
+pub struct WorkflowStepTask {
+    /// Step name from the template.
     #[prost(string, tag = "1")]
-    pub worker_id: ::prost::alloc::string::String,
-    /// Hostname of the worker machine
+    pub step_name: ::prost::alloc::string::String,
+    /// Task executing this step.
     #[prost(string, tag = "2")]
-    pub hostname: ::prost::alloc::string::String,
-    /// Worker binary version
+    pub task_id: ::prost::alloc::string::String,
+    /// Agent executing this step.
     #[prost(string, tag = "3")]
-    pub version: ::prost::alloc::string::String,
-    /// Current status
-    #[prost(enumeration = "WorkerStatus", tag = "4")]
+    pub agent_name: ::prost::alloc::string::String,
+    /// Current status of the step's task.
+    #[prost(enumeration = "TaskStatus", tag = "4")]
     pub status: i32,
-    /// Agents available on this worker
-    #[prost(message, repeated, tag = "5")]
-    pub agents: ::prost::alloc::vec::Vec<AgentSpec>,
-    /// Worker-level labels
-    #[prost(map = "string, string", tag = "6")]
-    pub labels: ::std::collections::HashMap<
+    /// Task IDs this step waits for.
+    #[prost(string, repeated, tag = "5")]
+    pub depends_on: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+}
+/// A workflow created from a template.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Workflow {
+    /// Unique workflow identifier.
+    #[prost(string, tag = "1")]
+    pub id: ::prost::alloc::string::String,
+    /// Template this workflow was expanded from.
+    #[prost(string, tag = "2")]
+    pub template_name: ::prost::alloc::string::String,
+    /// Parameters supplied at creation.
+    #[prost(map = "string, string", tag = "3")]
+    pub params: ::std::collections::HashMap<
         ::prost::alloc::string::String,
         ::prost::alloc::string::String,
     >,
-    /// Number of currently active runs
-    #[prost(uint32, tag = "7")]
-    pub active_runs: u32,
-    /// Maximum concurrent runs this worker can handle
-    #[prost(uint32, tag = "8")]
-    pub max_concurrent_runs: u32,
-    /// Timestamp of last heartbeat (Unix millis)
-    #[prost(int64, tag = "9")]
-    pub last_heartbeat_ms: i64,
+    /// Who created this workflow.
+    #[prost(string, tag = "4")]
+    pub created_by: ::prost::alloc::string::String,
+    /// Creation timestamp in milliseconds since epoch.
+    #[prost(int64, tag = "5")]
+    pub created_at_ms: i64,
+    /// Overall status derived from the step tasks.
+    #[prost(enumeration = "WorkflowStatus", tag = "6")]
+    pub status: i32,
+    /// Steps in template order.
+    #[prost(message, repeated, tag = "7")]
+    pub steps: ::prost::alloc::vec::Vec<WorkflowStepTask>,
 }
+/// Request to create a workflow.
 #[derive(Clone, PartialEq, ::prost::Message)]
-pub struct ListWorkersRequest {
-    /// Filter by agent name (optional)
-    #[prost(string, optional, tag = "1")]
-    pub agent_name: ::core::option::Option<::prost::alloc::string::String>,
-    /// Filter by worker status (optional)
-    #[prost(enumeration = "WorkerStatus", optional, tag = "2")]
-    pub status: ::core::option::Option<i32>,
+pub struct CreateWorkflowRequest {
+    /// Template name.
+    #[prost(string, tag = "1")]
+    pub template_name: ::prost::alloc::string::String,
+    /// Template parameters.
+    #[prost(map = "string, string", tag = "2")]
+    pub params: ::std::collections::HashMap<
+        ::prost::alloc::string::String,
+        ::prost::alloc::string::String,
+    >,
+    /// Who is creating this workflow.
+    #[prost(string, tag = "3")]
+    pub created_by: ::prost::alloc::string::String,
 }
+/// Request to get a workflow.
 #[derive(Clone, PartialEq, ::prost::Message)]
-pub struct ListWorkersResponse {
-    /// List of workers matching the filter
+pub struct GetWorkflowRequest {
+    /// Workflow ID (or unique prefix).
+    #[prost(string, tag = "1")]
+    pub id: ::prost::alloc::string::String,
+}
+/// Request to list workflows.
+#[derive(Clone, Copy, PartialEq, ::prost::Message)]
+pub struct ListWorkflowsRequest {
+    /// Maximum number of workflows to return (0 = all).
+    #[prost(uint32, tag = "1")]
+    pub limit: u32,
+}
+/// Response containing workflows.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListWorkflowsResponse {
+    /// Workflows, newest first.
     #[prost(message, repeated, tag = "1")]
-    pub workers: ::prost::alloc::vec::Vec<Worker>,
+    pub workflows: ::prost::alloc::vec::Vec<Workflow>,
 }
+/// Request to list workflow templates.
+#[derive(Clone, Copy, PartialEq, ::prost::Message)]
+pub struct ListWorkflowTemplatesRequest {}
+/// Response containing workflow templates.
 #[derive(Clone, PartialEq, ::prost::Message)]
-pub struct GetWorkerRequest {
-    /// Worker ID to retrieve
-    #[prost(string, tag = "1")]
-    pub worker_id: ::prost::alloc::string::String,
+pub struct ListWorkflowTemplatesResponse {
+    /// Templates ordered by name.
+    #[prost(message, repeated, tag = "1")]
+    pub templates: ::prost::alloc::vec::Vec<WorkflowTemplate>,
+}
+/// Overall status of a workflow.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum WorkflowStatus {
+    Unspecified = 0,
+    Running = 1,
+    Completed = 2,
+    Failed = 3,
+}
+impl WorkflowStatus {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            Self::Unspecified => "WORKFLOW_STATUS_UNSPECIFIED",
+            Self::Running => "WORKFLOW_STATUS_RUNNING",
+            Self::Completed => "WORKFLOW_STATUS_COMPLETED",
+            Self::Failed => "WORKFLOW_STATUS_FAILED",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "WORKFLOW_STATUS_UNSPECIFIED" => Some(Self::Unspecified),
+            "WORKFLOW_STATUS_RUNNING" => Some(Self::Running),
+            "WORKFLOW_STATUS_COMPLETED" => Some(Self::Completed),
+            "WORKFLOW_STATUS_FAILED" => Some(Self::Failed),
+            _ => None,
+        }
+    }
 }
 /// Generated client implementations.
-pub mod worker_service_client {
+pub mod workflow_service_client {
     #![allow(
         unused_variables,
         dead_code,
@@ -1504,11 +6515,12 @@ pub mod worker_service_client {
     )]
     use tonic::codegen::*;
     use tonic::codegen::http::Uri;
+    /// WorkflowService expands named templates into linked tasks.
     #[derive(Debug, Clone)]
-    pub struct WorkerServiceClient<T> {
+    pub struct WorkflowServiceClient<T> {
         inner: tonic::client::Grpc<T>,
     }
-    impl WorkerServiceClient<tonic::transport::Channel> {
+    impl WorkflowServiceClient<tonic::transport::Channel> {
         /// Attempt to create a new client by connecting to a given endpoint.
         pub async fn connect<D>(dst: D) -> Result<Self, tonic::transport::Error>
         where
@@ -1519,7 +6531,7 @@ pub mod worker_service_client {
             Ok(Self::new(conn))
         }
     }
-    impl<T> WorkerServiceClient<T>
+    impl<T> WorkflowServiceClient<T>
     where
         T: tonic::client::GrpcService<tonic::body::BoxBody>,
         T::Error: Into<StdError>,
@@ -1537,7 +6549,7 @@ pub mod worker_service_client {
         pub fn with_interceptor<F>(
             inner: T,
             interceptor: F,
-        ) -> WorkerServiceClient<InterceptedService<T, F>>
+        ) -> WorkflowServiceClient<InterceptedService<T, F>>
         where
             F: tonic::service::Interceptor,
             T::ResponseBody: Default,
@@ -1551,7 +6563,7 @@ pub mod worker_service_client {
                 http::Request<tonic::body::BoxBody>,
             >>::Error: Into<StdError> + std::marker::Send + std::marker::Sync,
         {
-            WorkerServiceClient::new(InterceptedService::new(inner, interceptor))
+            WorkflowServiceClient::new(InterceptedService::new(inner, interceptor))
         }
         /// Compress requests with the given encoding.
         ///
@@ -1584,12 +6596,56 @@ pub mod worker_service_client {
             self.inner = self.inner.max_encoding_message_size(limit);
             self
         }
-        /// List all connected workers with optional filtering
-        pub async fn list_workers(
+        /// Expand a template into a workflow of linked tasks.
+        pub async fn create_workflow(
             &mut self,
-            request: impl tonic::IntoRequest<super::ListWorkersRequest>,
+            request: impl tonic::IntoRequest<super::CreateWorkflowRequest>,
+        ) -> std::result::Result<tonic::Response<super::Workflow>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/taskrun.v1.WorkflowService/CreateWorkflow",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("taskrun.v1.WorkflowService", "CreateWorkflow"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// Get a workflow by ID.
+        pub async fn get_workflow(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetWorkflowRequest>,
+        ) -> std::result::Result<tonic::Response<super::Workflow>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/taskrun.v1.WorkflowService/GetWorkflow",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("taskrun.v1.WorkflowService", "GetWorkflow"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// List workflows, newest first.
+        pub async fn list_workflows(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ListWorkflowsRequest>,
         ) -> std::result::Result<
-            tonic::Response<super::ListWorkersResponse>,
+            tonic::Response<super::ListWorkflowsResponse>,
             tonic::Status,
         > {
             self.inner
@@ -1602,18 +6658,21 @@ pub mod worker_service_client {
                 })?;
             let codec = tonic::codec::ProstCodec::default();
             let path = http::uri::PathAndQuery::from_static(
-                "/taskrun.v1.WorkerService/ListWorkers",
+                "/taskrun.v1.WorkflowService/ListWorkflows",
             );
             let mut req = request.into_request();
             req.extensions_mut()
-                .insert(GrpcMethod::new("taskrun.v1.WorkerService", "ListWorkers"));
+                .insert(GrpcMethod::new("taskrun.v1.WorkflowService", "ListWorkflows"));
             self.inner.unary(req, path, codec).await
         }
-        /// Get details for a specific worker
-        pub async fn get_worker(
+        /// List the templates workflows can be created from.
+        pub async fn list_workflow_templates(
             &mut self,
-            request: impl tonic::IntoRequest<super::GetWorkerRequest>,
-        ) -> std::result::Result<tonic::Response<super::Worker>, tonic::Status> {
+            request: impl tonic::IntoRequest<super::ListWorkflowTemplatesRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ListWorkflowTemplatesResponse>,
+            tonic::Status,
+        > {
             self.inner
                 .ready()
                 .await
@@ -1624,17 +6683,22 @@ pub mod worker_service_client {
                 })?;
             let codec = tonic::codec::ProstCodec::default();
             let path = http::uri::PathAndQuery::from_static(
-                "/taskrun.v1.WorkerService/GetWorker",
+                "/taskrun.v1.WorkflowService/ListWorkflowTemplates",
             );
             let mut req = request.into_request();
             req.extensions_mut()
-                .insert(GrpcMethod::new("taskrun.v1.WorkerService", "GetWorker"));
+                .insert(
+                    GrpcMethod::new(
+                        "taskrun.v1.WorkflowService",
+                        "ListWorkflowTemplates",
+                    ),
+                );
             self.inner.unary(req, path, codec).await
         }
     }
 }
 /// Generated server implementations.
-pub mod worker_service_server {
+pub mod workflow_service_server {
     #![allow(
         unused_variables,
         dead_code,
@@ -1643,32 +6707,46 @@ pub mod worker_service_server {
         clippy::let_unit_value,
     )]
     use tonic::codegen::*;
-    /// Generated trait containing gRPC methods that should be implemented for use with WorkerServiceServer.
+    /// Generated trait containing gRPC methods that should be implemented for use with WorkflowServiceServer.
     #[async_trait]
-    pub trait WorkerService: std::marker::Send + std::marker::Sync + 'static {
-        /// List all connected workers with optional filtering
-        async fn list_workers(
+    pub trait WorkflowService: std::marker::Send + std::marker::Sync + 'static {
+        /// Expand a template into a workflow of linked tasks.
+        async fn create_workflow(
             &self,
-            request: tonic::Request<super::ListWorkersRequest>,
+            request: tonic::Request<super::CreateWorkflowRequest>,
+        ) -> std::result::Result<tonic::Response<super::Workflow>, tonic::Status>;
+        /// Get a workflow by ID.
+        async fn get_workflow(
+            &self,
+            request: tonic::Request<super::GetWorkflowRequest>,
+        ) -> std::result::Result<tonic::Response<super::Workflow>, tonic::Status>;
+        /// List workflows, newest first.
+        async fn list_workflows(
+            &self,
+            request: tonic::Request<super::ListWorkflowsRequest>,
         ) -> std::result::Result<
-            tonic::Response<super::ListWorkersResponse>,
+            tonic::Response<super::ListWorkflowsResponse>,
             tonic::Status,
         >;
-        /// Get details for a specific worker
-        async fn get_worker(
+        /// List the templates workflows can be created from.
+        async fn list_workflow_templates(
             &self,
-            request: tonic::Request<super::GetWorkerRequest>,
-        ) -> std::result::Result<tonic::Response<super::Worker>, tonic::Status>;
+            request: tonic::Request<super::ListWorkflowTemplatesRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ListWorkflowTemplatesResponse>,
+            tonic::Status,
+        >;
     }
+    /// WorkflowService expands named templates into linked tasks.
     #[derive(Debug)]
-    pub struct WorkerServiceServer<T> {
+    pub struct WorkflowServiceServer<T> {
         inner: Arc<T>,
         accept_compression_encodings: EnabledCompressionEncodings,
         send_compression_encodings: EnabledCompressionEncodings,
         max_decoding_message_size: Option<usize>,
         max_encoding_message_size: Option<usize>,
     }
-    impl<T> WorkerServiceServer<T> {
+    impl<T> WorkflowServiceServer<T> {
         pub fn new(inner: T) -> Self {
             Self::from_arc(Arc::new(inner))
         }
@@ -1719,9 +6797,9 @@ pub mod worker_service_server {
             self
         }
     }
-    impl<T, B> tonic::codegen::Service<http::Request<B>> for WorkerServiceServer<T>
+    impl<T, B> tonic::codegen::Service<http::Request<B>> for WorkflowServiceServer<T>
     where
-        T: WorkerService,
+        T: WorkflowService,
         B: Body + std::marker::Send + 'static,
         B::Error: Into<StdError> + std::marker::Send + 'static,
     {
@@ -1736,25 +6814,26 @@ pub mod worker_service_server {
         }
         fn call(&mut self, req: http::Request<B>) -> Self::Future {
             match req.uri().path() {
-                "/taskrun.v1.WorkerService/ListWorkers" => {
+                "/taskrun.v1.WorkflowService/CreateWorkflow" => {
                     #[allow(non_camel_case_types)]
-                    struct ListWorkersSvc<T: WorkerService>(pub Arc<T>);
+                    struct CreateWorkflowSvc<T: WorkflowService>(pub Arc<T>);
                     impl<
-                        T: WorkerService,
-                    > tonic::server::UnaryService<super::ListWorkersRequest>
-                    for ListWorkersSvc<T> {
-                        type Response = super::ListWorkersResponse;
+                        T: WorkflowService,
+                    > tonic::server::UnaryService<super::CreateWorkflowRequest>
+                    for CreateWorkflowSvc<T> {
+                        type Response = super::Workflow;
                         type Future = BoxFuture<
                             tonic::Response<Self::Response>,
                             tonic::Status,
                         >;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::ListWorkersRequest>,
+                            request: tonic::Request<super::CreateWorkflowRequest>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
                             let fut = async move {
-                                <T as WorkerService>::list_workers(&inner, request).await
+                                <T as WorkflowService>::create_workflow(&inner, request)
+                                    .await
                             };
                             Box::pin(fut)
                         }
@@ -1765,7 +6844,7 @@ pub mod worker_service_server {
                     let max_encoding_message_size = self.max_encoding_message_size;
                     let inner = self.inner.clone();
                     let fut = async move {
-                        let method = ListWorkersSvc(inner);
+                        let method = CreateWorkflowSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec)
                             .apply_compression_config(
@@ -1781,25 +6860,25 @@ pub mod worker_service_server {
                     };
                     Box::pin(fut)
                 }
-                "/taskrun.v1.WorkerService/GetWorker" => {
+                "/taskrun.v1.WorkflowService/GetWorkflow" => {
                     #[allow(non_camel_case_types)]
-                    struct GetWorkerSvc<T: WorkerService>(pub Arc<T>);
+                    struct GetWorkflowSvc<T: WorkflowService>(pub Arc<T>);
                     impl<
-                        T: WorkerService,
-                    > tonic::server::UnaryService<super::GetWorkerRequest>
-                    for GetWorkerSvc<T> {
-                        type Response = super::Worker;
+                        T: WorkflowService,
+                    > tonic::server::UnaryService<super::GetWorkflowRequest>
+                    for GetWorkflowSvc<T> {
+                        type Response = super::Workflow;
                         type Future = BoxFuture<
                             tonic::Response<Self::Response>,
                             tonic::Status,
                         >;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::GetWorkerRequest>,
+                            request: tonic::Request<super::GetWorkflowRequest>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
                             let fut = async move {
-                                <T as WorkerService>::get_worker(&inner, request).await
+                                <T as WorkflowService>::get_workflow(&inner, request).await
                             };
                             Box::pin(fut)
                         }
@@ -1810,7 +6889,102 @@ pub mod worker_service_server {
                     let max_encoding_message_size = self.max_encoding_message_size;
                     let inner = self.inner.clone();
                     let fut = async move {
-                        let method = GetWorkerSvc(inner);
+                        let method = GetWorkflowSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/taskrun.v1.WorkflowService/ListWorkflows" => {
+                    #[allow(non_camel_case_types)]
+                    struct ListWorkflowsSvc<T: WorkflowService>(pub Arc<T>);
+                    impl<
+                        T: WorkflowService,
+                    > tonic::server::UnaryService<super::ListWorkflowsRequest>
+                    for ListWorkflowsSvc<T> {
+                        type Response = super::ListWorkflowsResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ListWorkflowsRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as WorkflowService>::list_workflows(&inner, request)
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = ListWorkflowsSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/taskrun.v1.WorkflowService/ListWorkflowTemplates" => {
+                    #[allow(non_camel_case_types)]
+                    struct ListWorkflowTemplatesSvc<T: WorkflowService>(pub Arc<T>);
+                    impl<
+                        T: WorkflowService,
+                    > tonic::server::UnaryService<super::ListWorkflowTemplatesRequest>
+                    for ListWorkflowTemplatesSvc<T> {
+                        type Response = super::ListWorkflowTemplatesResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ListWorkflowTemplatesRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as WorkflowService>::list_workflow_templates(
+                                        &inner,
+                                        request,
+                                    )
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = ListWorkflowTemplatesSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec)
                             .apply_compression_config(
@@ -1846,7 +7020,7 @@ pub mod worker_service_server {
             }
         }
     }
-    impl<T> Clone for WorkerServiceServer<T> {
+    impl<T> Clone for WorkflowServiceServer<T> {
         fn clone(&self) -> Self {
             let inner = self.inner.clone();
             Self {
@@ -1859,8 +7033,8 @@ pub mod worker_service_server {
         }
     }
     /// Generated gRPC service name
-    pub const SERVICE_NAME: &str = "taskrun.v1.WorkerService";
-    impl<T> tonic::server::NamedService for WorkerServiceServer<T> {
+    pub const SERVICE_NAME: &str = "taskrun.v1.WorkflowService";
+    impl<T> tonic::server::NamedService for WorkflowServiceServer<T> {
         const NAME: &'static str = SERVICE_NAME;
     }
 }
@@ -4,10 +4,13 @@
 //! - Generated protobuf message types
 //! - Generated gRPC service stubs (client and server)
 //! - Converters between proto types and domain types
+//! - Trace context propagation and OTLP export
 
 pub mod convert;
+pub mod trace;
 
 /// Generated protobuf types and services.
+#[allow(clippy::large_enum_variant)] // Stream payloads carry run manifests
 pub mod pb {
     // Include the generated code
     // The path matches the proto package: taskrun.v1
@@ -15,9 +18,19 @@ pub mod pb {
 }
 
 // Re-export commonly used types
+pub use pb::admin_service_client::AdminServiceClient;
+pub use pb::admin_service_server::{AdminService, AdminServiceServer};
+pub use pb::api_key_service_client::ApiKeyServiceClient;
+pub use pb::api_key_service_server::{ApiKeyService, ApiKeyServiceServer};
+pub use pb::approval_service_client::ApprovalServiceClient;
+pub use pb::approval_service_server::{ApprovalService, ApprovalServiceServer};
 pub use pb::run_service_client::RunServiceClient;
 pub use pb::run_service_server::{RunService, RunServiceServer};
+pub use pb::schedule_service_client::ScheduleServiceClient;
+pub use pb::schedule_service_server::{ScheduleService, ScheduleServiceServer};
 pub use pb::task_service_client::TaskServiceClient;
 pub use pb::task_service_server::{TaskService, TaskServiceServer};
 pub use pb::worker_service_client::WorkerServiceClient;
 pub use pb::worker_service_server::{WorkerService, WorkerServiceServer};
+pub use pb::workflow_service_client::WorkflowServiceClient;
+pub use pb::workflow_service_server::{WorkflowService, WorkflowServiceServer};
@@ -0,0 +1,167 @@
+//! Trace context propagation and OTLP export.
+//!
+//! A task's trace starts where it is created and follows it through
+//! scheduling, delivery to a worker and execution. The context travels as
+//! W3C trace context (`traceparent`/`tracestate`) in string maps: gRPC
+//! request metadata, `Task.trace_context` and `RunAssignment.trace_context`.
+//!
+//! Until a binary calls [`OtlpExporter::start`] (feature `otlp`) no
+//! propagator is installed, so every helper here is a no-op and the maps stay
+//! empty.
+
+use std::collections::HashMap;
+
+use opentelemetry::trace::TraceContextExt;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+/// Trace context of a span, as propagation headers.
+pub type TraceContext = HashMap<String, String>;
+
+/// Context of `span`, ready to send along with the work it started.
+pub fn inject(span: &tracing::Span) -> TraceContext {
+    let mut carrier = TraceContext::new();
+    let cx = span.context();
+    opentelemetry::global::get_text_map_propagator(|propagator| {
+        propagator.inject_context(&cx, &mut carrier)
+    });
+    carrier
+}
+
+/// Make `span` a child of the span that sent `carrier`. An empty carrier
+/// leaves it a root span.
+pub fn set_parent(span: &tracing::Span, carrier: &TraceContext) {
+    if carrier.is_empty() {
+        return;
+    }
+    let cx =
+        opentelemetry::global::get_text_map_propagator(|propagator| propagator.extract(carrier));
+    span.set_parent(cx);
+}
+
+/// Trace context headers of a gRPC request.
+pub fn from_metadata(metadata: &tonic::metadata::MetadataMap) -> TraceContext {
+    let fields: Vec<String> = opentelemetry::global::get_text_map_propagator(|propagator| {
+        propagator.fields().map(str::to_string).collect()
+    });
+    fields
+        .into_iter()
+        .filter_map(|field| {
+            let value = metadata.get(field.as_str())?.to_str().ok()?.to_string();
+            Some((field, value))
+        })
+        .collect()
+}
+
+/// ID of the trace `span` belongs to, if it is being traced.
+pub fn trace_id(span: &tracing::Span) -> Option<String> {
+    let cx = span.context();
+    let span_context = cx.span().span_context().clone();
+    span_context
+        .is_valid()
+        .then(|| span_context.trace_id().to_string())
+}
+
+#[cfg(feature = "otlp")]
+pub use otlp::OtlpExporter;
+
+#[cfg(feature = "otlp")]
+mod otlp {
+    use opentelemetry::trace::{TraceError, TracerProvider as _};
+    use opentelemetry::KeyValue;
+    use opentelemetry_otlp::{SpanExporter, WithExportConfig};
+    use opentelemetry_sdk::propagation::TraceContextPropagator;
+    use opentelemetry_sdk::trace::{Tracer, TracerProvider};
+    use opentelemetry_sdk::{runtime, Resource};
+    use tracing_opentelemetry::OpenTelemetryLayer;
+    use tracing_subscriber::registry::LookupSpan;
+
+    /// Exports spans to an OTLP collector over gRPC.
+    ///
+    /// Export runs on a small runtime of its own, so the exporter can be
+    /// started before the binary's runtime and outlives it. Dropping the
+    /// exporter flushes the spans not sent yet.
+    pub struct OtlpExporter {
+        provider: TracerProvider,
+        tracer: Tracer,
+        _runtime: tokio::runtime::Runtime,
+    }
+
+    impl OtlpExporter {
+        /// Start exporting spans of `service_name` to `endpoint`
+        /// (e.g. `http://localhost:4317`) and install the W3C trace context
+        /// propagator.
+        pub fn start(service_name: &str, endpoint: &str) -> Result<Self, TraceError> {
+            let rt = tokio::runtime::Builder::new_multi_thread()
+                .worker_threads(1)
+                .thread_name("otlp-export")
+                .enable_all()
+                .build()
+                .map_err(|e| TraceError::Other(Box::new(e)))?;
+            let provider = {
+                let _guard = rt.enter();
+                let exporter = SpanExporter::builder()
+                    .with_tonic()
+                    .with_endpoint(endpoint)
+                    .build()?;
+                TracerProvider::builder()
+                    .with_batch_exporter(exporter, runtime::Tokio)
+                    .with_resource(Resource::new([KeyValue::new(
+                        "service.name",
+                        service_name.to_string(),
+                    )]))
+                    .build()
+            };
+            opentelemetry::global::set_text_map_propagator(TraceContextPropagator::new());
+            let tracer = provider.tracer(service_name.to_string());
+            Ok(Self {
+                provider,
+                tracer,
+                _runtime: rt,
+            })
+        }
+
+        /// Layer that records `tracing` spans as OpenTelemetry spans.
+        pub fn layer<S>(&self) -> OpenTelemetryLayer<S, Tracer>
+        where
+            S: tracing::Subscriber + for<'span> LookupSpan<'span>,
+        {
+            tracing_opentelemetry::layer().with_tracer(self.tracer.clone())
+        }
+    }
+
+    impl Drop for OtlpExporter {
+        fn drop(&mut self) {
+            if let Err(e) = self.provider.shutdown() {
+                eprintln!("Failed to flush traces: {e}");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opentelemetry::trace::TracerProvider as _;
+    use opentelemetry_sdk::propagation::TraceContextPropagator;
+    use opentelemetry_sdk::trace::TracerProvider;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    #[test]
+    fn test_context_round_trip_keeps_the_trace() {
+        opentelemetry::global::set_text_map_propagator(TraceContextPropagator::new());
+        let provider = TracerProvider::builder().build();
+        let subscriber = tracing_subscriber::registry()
+            .with(tracing_opentelemetry::layer().with_tracer(provider.tracer("test")));
+
+        tracing::subscriber::with_default(subscriber, || {
+            let sender = tracing::info_span!("task.create");
+            let carrier = inject(&sender);
+            assert!(carrier.contains_key("traceparent"));
+
+            let receiver = tracing::info_span!("run.execute");
+            set_parent(&receiver, &carrier);
+            assert_eq!(trace_id(&receiver), trace_id(&sender));
+            assert!(trace_id(&receiver).is_some());
+        });
+    }
+}
@@ -1,16 +1,22 @@
 //! Server TUI application.
 
-use std::io;
+use std::collections::HashMap;
+use std::io::{self, Write};
 use std::time::Duration;
 
 use crossterm::event::{self, Event, KeyCode, KeyModifiers};
 use ratatui::backend::Backend;
 use ratatui::Terminal;
+use taskrun_tui_components::{GroupProgress, TimeFormat};
 use tokio::sync::mpsc;
 
+use crate::control_plane::bulk::{BulkOperation, TaskFilter};
 use crate::event::{LogLevel, ServerCommand, ServerUiEvent};
 use crate::render::render;
-use crate::state::{ServerStatus, ServerUiState, ServerView, TaskDisplayInfo, WorkerDisplayInfo};
+use crate::state::{
+    BulkConfirm, ServerStatus, ServerUiState, ServerView, TaskDisplayInfo, TaskSearch, Toast,
+    WorkerDisplayInfo, SETTING_ROWS,
+};
 
 /// Server TUI application.
 pub struct ServerApp {
@@ -30,6 +36,12 @@ impl ServerApp {
         }
     }
 
+    /// Show timestamps with `time_format` until the settings file changes it.
+    pub fn with_time_format(mut self, time_format: TimeFormat) -> Self {
+        self.state.time_format = time_format;
+        self
+    }
+
     pub fn run<B: Backend>(&mut self, terminal: &mut Terminal<B>) -> io::Result<()> {
         while !self.should_quit {
             // Process backend events (non-blocking)
@@ -54,6 +66,9 @@ impl ServerApp {
         while let Ok(event) = self.ui_rx.try_recv() {
             self.apply_event(event);
         }
+        if self.state.toast.as_ref().is_some_and(Toast::is_expired) {
+            self.state.toast = None;
+        }
     }
 
     fn apply_event(&mut self, event: ServerUiEvent) {
@@ -75,16 +90,19 @@ impl ServerApp {
                 worker_id,
                 hostname,
                 agents,
+                labels,
             } => {
                 let info = WorkerDisplayInfo {
                     worker_id: worker_id.clone(),
                     hostname,
                     agents,
+                    labels,
                     status: taskrun_core::WorkerStatus::Idle,
                     active_runs: 0,
                     max_concurrent_runs: 0,
                     connected_at: chrono::Utc::now(),
                     last_heartbeat: chrono::Utc::now(),
+                    metrics: HashMap::new(),
                 };
                 self.state.workers.insert(worker_id.clone(), info);
                 self.state
@@ -102,18 +120,59 @@ impl ServerApp {
                 status,
                 active_runs,
                 max_concurrent_runs,
+                metrics,
             } => {
                 if let Some(worker) = self.state.workers.get_mut(&worker_id) {
                     worker.status = status;
                     worker.active_runs = active_runs;
                     worker.max_concurrent_runs = max_concurrent_runs;
                     worker.last_heartbeat = chrono::Utc::now();
+                    worker.metrics = metrics;
+                }
+            }
+            ServerUiEvent::WorkerStatusChanged { worker_id, status } => {
+                if let Some(worker) = self.state.workers.get_mut(&worker_id) {
+                    worker.status = status;
+                }
+                self.state.add_log(
+                    LogLevel::Warn,
+                    format!("Worker {} is {:?}", worker_id, status),
+                );
+            }
+            ServerUiEvent::WorkerUpdated {
+                worker_id,
+                agents,
+                labels,
+                max_concurrent_runs,
+            } => {
+                if let Some(worker) = self.state.workers.get_mut(&worker_id) {
+                    worker.agents = agents;
+                    worker.labels = labels;
+                    worker.max_concurrent_runs = max_concurrent_runs;
                 }
+                self.state.add_log(
+                    LogLevel::Info,
+                    format!("Worker {} updated its capabilities", worker_id),
+                );
+            }
+            ServerUiEvent::WorkerDrained { worker_id } => {
+                self.state.add_log(
+                    LogLevel::Info,
+                    format!("Worker {} drained, safe to stop", worker_id),
+                );
+                self.state.toast = Some(Toast::new(format!("Worker {} drained", worker_id)));
             }
-            ServerUiEvent::TaskCreated { task_id, agent } => {
+            ServerUiEvent::TaskCreated {
+                task_id,
+                agent,
+                created_by,
+                client_ip,
+            } => {
                 let info = TaskDisplayInfo {
                     task_id: task_id.clone(),
                     agent_name: agent,
+                    created_by,
+                    client_ip,
                     status: taskrun_core::TaskStatus::Pending,
                     created_at: chrono::Utc::now(),
                     run_count: 0,
@@ -147,17 +206,49 @@ impl ServerApp {
                     task.latest_run_status = Some(status);
                 }
             }
-            ServerUiEvent::RunOutputChunk { run_id, content } => {
+            ServerUiEvent::RunOutputChunk {
+                run_id,
+                seq,
+                content,
+            } => {
+                let next_seq = self
+                    .state
+                    .run_output_next_seq
+                    .entry(run_id.clone())
+                    .or_default();
+                // Already part of a loaded backlog
+                if seq < *next_seq {
+                    return;
+                }
+                *next_seq = seq + 1;
                 self.state
                     .run_output
                     .entry(run_id)
                     .or_default()
                     .push_str(&content);
             }
+            ServerUiEvent::RunOutputBacklog {
+                run_id,
+                content,
+                next_seq,
+            } => {
+                // The backlog has every chunk before next_seq; keep live
+                // output that is newer than it
+                let seen = self
+                    .state
+                    .run_output_next_seq
+                    .entry(run_id.clone())
+                    .or_default();
+                if next_seq >= *seen {
+                    *seen = next_seq;
+                    self.state.run_output.insert(run_id, content);
+                }
+            }
             ServerUiEvent::ChatMessage {
                 run_id,
                 role,
                 content,
+                attachments,
                 ..
             } => {
                 use crate::state::ChatEntry;
@@ -169,6 +260,7 @@ impl ServerApp {
                         timestamp: chrono::Utc::now(),
                         role,
                         content,
+                        attachments,
                     });
             }
             ServerUiEvent::RunEvent {
@@ -188,6 +280,137 @@ impl ServerApp {
                         details,
                     });
             }
+            ServerUiEvent::RunMilestone { run_id, milestone } => {
+                milestone.record(self.state.run_milestones.entry(run_id).or_default());
+            }
+            ServerUiEvent::RunResult { run_id, result } => {
+                self.state.run_results.insert(run_id, result);
+            }
+            ServerUiEvent::ScheduleUpdated { schedule } => {
+                self.state.schedules.insert(schedule.id.clone(), schedule);
+            }
+            ServerUiEvent::ScheduleDeleted { schedule_id } => {
+                self.state.schedules.remove(&schedule_id);
+                self.state.selected_schedule_index = self
+                    .state
+                    .selected_schedule_index
+                    .min(self.state.schedules.len().saturating_sub(1));
+            }
+            ServerUiEvent::AuditRecorded { record } => {
+                self.state.add_audit_record(record);
+            }
+            ServerUiEvent::BulkPreview {
+                op,
+                filter,
+                task_count,
+            } => {
+                self.state.bulk_confirm = Some(BulkConfirm {
+                    op,
+                    filter,
+                    task_count,
+                });
+            }
+            ServerUiEvent::TaskValidated {
+                agent_name,
+                input_json,
+                messages,
+            } => {
+                // Ignore answers for what has been edited since
+                if self.state.show_new_task_dialog
+                    && agent_name == self.state.new_task_agent
+                    && input_json == self.state.new_task_input_json()
+                {
+                    self.state.new_task_validation = messages;
+                }
+            }
+            ServerUiEvent::SearchResults { query, task_ids } => {
+                self.state.add_log(
+                    LogLevel::Info,
+                    format!("Search '{}': {} task(s)", query, task_ids.len()),
+                );
+                self.state.task_search = Some(TaskSearch { query, task_ids });
+                self.state.selected_task_index = 0;
+            }
+            ServerUiEvent::ApprovalRequested { approval } => {
+                self.state.add_log(
+                    LogLevel::Warn,
+                    format!(
+                        "Run {} asks to use {} (press a to review)",
+                        &approval.run_id.as_str()[..8.min(approval.run_id.as_str().len())],
+                        approval.tool_name
+                    ),
+                );
+                self.state.toast = Some(Toast::new(format!(
+                    "{} wants to use {} (6: Approvals)",
+                    approval.agent_name, approval.tool_name
+                )));
+                self.state.pending_approvals.push_back(approval);
+                // Terminal bell, so the request is noticed from another window
+                let mut stdout = io::stdout();
+                let _ = stdout.write_all(b"\x07").and_then(|()| stdout.flush());
+            }
+            ServerUiEvent::ApprovalResolved { approval_id } => {
+                self.state
+                    .pending_approvals
+                    .retain(|approval| approval.id != approval_id);
+                if self.state.pending_approvals.is_empty() {
+                    self.state.show_approval_dialog = false;
+                }
+                self.state.selected_approval_index = self
+                    .state
+                    .selected_approval_index
+                    .min(self.state.pending_approvals.len().saturating_sub(1));
+            }
+            ServerUiEvent::GroupsUpdated { groups } => {
+                self.state.groups = groups
+                    .into_iter()
+                    .map(|(label, counts)| GroupProgress {
+                        label,
+                        completed: counts.completed,
+                        failed: counts.failed,
+                        cancelled: counts.cancelled,
+                        running: counts.running,
+                        pending: counts.pending,
+                    })
+                    .collect();
+            }
+            ServerUiEvent::QueueUpdated { entries } => {
+                self.state.queue_positions = entries
+                    .iter()
+                    .map(|e| (e.task_id.clone(), e.queue))
+                    .collect();
+                self.state.queue = entries;
+                self.state.selected_queue_index = self
+                    .state
+                    .selected_queue_index
+                    .min(self.state.queue.len().saturating_sub(1));
+            }
+            ServerUiEvent::TogglesChanged { toggles } => {
+                if toggles.maintenance != self.state.toggles.maintenance {
+                    let message = if toggles.maintenance {
+                        "Maintenance mode on: new tasks are refused"
+                    } else {
+                        "Maintenance mode off"
+                    };
+                    self.state.add_log(LogLevel::Warn, message.to_string());
+                }
+                if toggles.scheduling_frozen != self.state.toggles.scheduling_frozen {
+                    let message = if toggles.scheduling_frozen {
+                        "Scheduling frozen: queued tasks are held"
+                    } else {
+                        "Scheduling resumed"
+                    };
+                    self.state.add_log(LogLevel::Warn, message.to_string());
+                }
+                self.state.toggles = toggles;
+            }
+            ServerUiEvent::DebugStats { stats } => {
+                self.state.debug_stats = Some(stats);
+            }
+            ServerUiEvent::TimeFormatChanged { time_format } => {
+                // Keep the absolute/relative choice made with `T`
+                self.state.time_format = time_format.with_relative(self.state.time_format.relative);
+            }
             ServerUiEvent::LogMessage { level, message } => {
                 self.state.add_log(level, message);
             }
@@ -212,6 +435,22 @@ impl ServerApp {
             self.handle_disconnect_confirm(code);
             return;
         }
+        if self.state.bulk_confirm.is_some() {
+            self.handle_bulk_confirm(code);
+            return;
+        }
+        if self.state.search_input.is_some() {
+            self.handle_search_input(code);
+            return;
+        }
+        if self.state.show_approval_dialog {
+            self.handle_approval_dialog(code);
+            return;
+        }
+        if self.state.show_settings_dialog {
+            self.handle_settings_dialog(code);
+            return;
+        }
 
         // Run detail view has special handling - chat input is always active
         if self.state.current_view == ServerView::RunDetail {
@@ -221,15 +460,41 @@ impl ServerApp {
 
         // Global keys for other views
         match code {
+            KeyCode::Esc
+                if self.state.current_view == ServerView::Tasks
+                    && self.state.task_search.is_some() =>
+            {
+                self.state.task_search = None;
+                self.state.selected_task_index = 0;
+            }
+            KeyCode::Esc
+                if self.state.current_view == ServerView::Fleet
+                    && self.state.fleet_group.is_some() =>
+            {
+                self.state.fleet_group = None;
+            }
             KeyCode::Char('q') | KeyCode::Esc => {
                 self.state.show_quit_confirm = true;
             }
             KeyCode::Char('c') if modifiers.contains(KeyModifiers::CONTROL) => {
                 self.state.show_quit_confirm = true;
             }
+            KeyCode::Char('a') if !self.state.pending_approvals.is_empty() => {
+                self.state.show_approval_dialog = true;
+            }
+            KeyCode::Char('S') => {
+                self.state.show_settings_dialog = true;
+                self.state.selected_setting_index = 0;
+            }
+            KeyCode::Char('T') => self.state.time_format.toggle_relative(),
             KeyCode::Char('1') => self.state.current_view = ServerView::Workers,
             KeyCode::Char('2') => self.state.current_view = ServerView::Tasks,
             KeyCode::Char('3') => self.state.current_view = ServerView::Logs,
+            KeyCode::Char('4') => self.state.current_view = ServerView::Schedules,
+            KeyCode::Char('5') => self.state.current_view = ServerView::Audit,
+            KeyCode::Char('6') => self.state.current_view = ServerView::Approvals,
+            KeyCode::Char('7') => self.state.current_view = ServerView::Fleet,
+            KeyCode::Char('8') => self.state.current_view = ServerView::Queue,
             KeyCode::Tab => {
                 self.state.current_view = self.state.current_view.next();
             }
@@ -242,6 +507,11 @@ impl ServerApp {
                     ServerView::Workers => self.handle_workers_key(code),
                     ServerView::Tasks => self.handle_tasks_key(code),
                     ServerView::Logs => self.handle_logs_key(code),
+                    ServerView::Schedules => self.handle_schedules_key(code),
+                    ServerView::Audit => self.handle_audit_key(code),
+                    ServerView::Approvals => self.handle_approvals_key(code),
+                    ServerView::Fleet => self.handle_fleet_key(code),
+                    ServerView::Queue => self.handle_queue_key(code),
                     ServerView::RunDetail => unreachable!(),
                 }
             }
@@ -251,26 +521,102 @@ impl ServerApp {
     fn handle_workers_key(&mut self, code: KeyCode) {
         let worker_count = self.state.workers.len();
         match code {
-            KeyCode::Char('j') | KeyCode::Down => {
-                if worker_count > 0 {
-                    self.state.selected_worker_index =
-                        (self.state.selected_worker_index + 1).min(worker_count - 1);
+            KeyCode::Char('j') | KeyCode::Down if worker_count > 0 => {
+                self.state.selected_worker_index =
+                    (self.state.selected_worker_index + 1).min(worker_count - 1);
+            }
+            KeyCode::Char('k') | KeyCode::Up if self.state.selected_worker_index > 0 => {
+                self.state.selected_worker_index -= 1;
+            }
+            KeyCode::Char('d') if self.state.get_selected_worker().is_some() => {
+                self.state.show_disconnect_confirm = true;
+            }
+            KeyCode::Char('D') => {
+                if let Some(worker) = self.state.get_selected_worker() {
+                    let _ = self.cmd_tx.blocking_send(ServerCommand::SetWorkerDraining {
+                        worker_id: worker.worker_id.clone(),
+                        draining: worker.status != taskrun_core::WorkerStatus::Draining,
+                    });
                 }
             }
+            KeyCode::Char('g') => self.state.selected_worker_index = 0,
+            KeyCode::Char('G') if worker_count > 0 => {
+                self.state.selected_worker_index = worker_count - 1;
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_settings_dialog(&mut self, code: KeyCode) {
+        match code {
+            KeyCode::Char('j') | KeyCode::Down => {
+                self.state.selected_setting_index =
+                    (self.state.selected_setting_index + 1).min(SETTING_ROWS - 1);
+            }
             KeyCode::Char('k') | KeyCode::Up => {
-                if self.state.selected_worker_index > 0 {
-                    self.state.selected_worker_index -= 1;
+                self.state.selected_setting_index =
+                    self.state.selected_setting_index.saturating_sub(1);
+            }
+            KeyCode::Char(' ') | KeyCode::Enter => {
+                if let Some(update) = self.state.flip_setting(self.state.selected_setting_index) {
+                    let _ = self
+                        .cmd_tx
+                        .blocking_send(ServerCommand::UpdateToggles { update });
                 }
             }
-            KeyCode::Char('d') => {
-                if self.state.get_selected_worker().is_some() {
-                    self.state.show_disconnect_confirm = true;
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('S') => {
+                self.state.show_settings_dialog = false;
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_queue_key(&mut self, code: KeyCode) {
+        let queue_len = self.state.queue.len();
+        match code {
+            KeyCode::Char('j') | KeyCode::Down if queue_len > 0 => {
+                self.state.selected_queue_index =
+                    (self.state.selected_queue_index + 1).min(queue_len - 1);
+            }
+            KeyCode::Char('k') | KeyCode::Up if self.state.selected_queue_index > 0 => {
+                self.state.selected_queue_index -= 1;
+            }
+            KeyCode::Char('g') => self.state.selected_queue_index = 0,
+            KeyCode::Char('G') if queue_len > 0 => self.state.selected_queue_index = queue_len - 1,
+            _ => {}
+        }
+    }
+
+    fn handle_fleet_key(&mut self, code: KeyCode) {
+        if self.state.fleet_group.is_some() {
+            let worker_count = self.state.fleet_group_workers().len();
+            match code {
+                KeyCode::Char('j') | KeyCode::Down if worker_count > 0 => {
+                    self.state.selected_fleet_worker_index =
+                        (self.state.selected_fleet_worker_index + 1).min(worker_count - 1);
                 }
+                KeyCode::Char('k') | KeyCode::Up if self.state.selected_fleet_worker_index > 0 => {
+                    self.state.selected_fleet_worker_index -= 1;
+                }
+                _ => {}
             }
-            KeyCode::Char('g') => self.state.selected_worker_index = 0,
-            KeyCode::Char('G') => {
-                if worker_count > 0 {
-                    self.state.selected_worker_index = worker_count - 1;
+            return;
+        }
+
+        let group_count = self.state.fleet_groups().len();
+        match code {
+            KeyCode::Char('j') | KeyCode::Down if group_count > 0 => {
+                self.state.selected_fleet_group_index =
+                    (self.state.selected_fleet_group_index + 1).min(group_count - 1);
+            }
+            KeyCode::Char('k') | KeyCode::Up if self.state.selected_fleet_group_index > 0 => {
+                self.state.selected_fleet_group_index -= 1;
+            }
+            KeyCode::Char('l') => self.state.cycle_fleet_label_key(),
+            KeyCode::Enter => {
+                if let Some(group) = self.state.get_selected_fleet_group() {
+                    self.state.fleet_group = Some(group.name);
+                    self.state.selected_fleet_worker_index = 0;
                 }
             }
             _ => {}
@@ -278,18 +624,14 @@ impl ServerApp {
     }
 
     fn handle_tasks_key(&mut self, code: KeyCode) {
-        let task_count = self.state.task_list.len();
+        let task_count = self.state.task_display_list().len();
         match code {
-            KeyCode::Char('j') | KeyCode::Down => {
-                if task_count > 0 {
-                    self.state.selected_task_index =
-                        (self.state.selected_task_index + 1).min(task_count - 1);
-                }
+            KeyCode::Char('j') | KeyCode::Down if task_count > 0 => {
+                self.state.selected_task_index =
+                    (self.state.selected_task_index + 1).min(task_count - 1);
             }
-            KeyCode::Char('k') | KeyCode::Up => {
-                if self.state.selected_task_index > 0 {
-                    self.state.selected_task_index -= 1;
-                }
+            KeyCode::Char('k') | KeyCode::Up if self.state.selected_task_index > 0 => {
+                self.state.selected_task_index -= 1;
             }
             KeyCode::Char('n') => {
                 self.state.show_new_task_dialog = true;
@@ -297,24 +639,35 @@ impl ServerApp {
                 self.state.new_task_input.clear();
                 self.state.new_task_cursor = 0;
                 self.state.new_task_field = 0;
+                self.state.new_task_validation.clear();
+                self.validate_new_task();
             }
-            KeyCode::Char('c') => {
-                if self.state.get_selected_task().is_some() {
-                    self.state.show_cancel_confirm = true;
-                }
+            KeyCode::Char('c') if self.state.get_selected_task().is_some() => {
+                self.state.show_cancel_confirm = true;
             }
+            KeyCode::Char('/') => {
+                let query = self.state.task_search.as_ref().map(|s| s.query.clone());
+                self.state.search_input = Some(query.unwrap_or_default());
+            }
+            KeyCode::Char('C') => self.preview_bulk(BulkOperation::Cancel),
+            KeyCode::Char('R') => self.preview_bulk(BulkOperation::Retry),
             KeyCode::Enter => {
                 if let Some(task) = self.state.get_selected_task() {
+                    let run_id = task.latest_run_id.clone();
                     self.state.viewing_task_id = Some(task.task_id.clone());
                     self.state.current_view = ServerView::RunDetail;
                     self.state.run_scroll = usize::MAX; // Auto-scroll to bottom
+                                                        // Catch up on output streamed before the TUI saw it
+                    if let Some(run_id) = run_id {
+                        let _ = self
+                            .cmd_tx
+                            .blocking_send(ServerCommand::LoadRunOutput { run_id });
+                    }
                 }
             }
             KeyCode::Char('g') => self.state.selected_task_index = 0,
-            KeyCode::Char('G') => {
-                if task_count > 0 {
-                    self.state.selected_task_index = task_count - 1;
-                }
+            KeyCode::Char('G') if task_count > 0 => {
+                self.state.selected_task_index = task_count - 1;
             }
             _ => {}
         }
@@ -323,27 +676,96 @@ impl ServerApp {
     fn handle_logs_key(&mut self, code: KeyCode) {
         let log_count = self.state.log_messages.len();
         match code {
-            KeyCode::Char('j') | KeyCode::Down => {
-                if log_count > 0 {
-                    self.state.log_scroll =
-                        (self.state.log_scroll + 1).min(log_count.saturating_sub(1));
-                }
+            KeyCode::Char('j') | KeyCode::Down if log_count > 0 => {
+                self.state.log_scroll =
+                    (self.state.log_scroll + 1).min(log_count.saturating_sub(1));
             }
-            KeyCode::Char('k') | KeyCode::Up => {
-                if self.state.log_scroll > 0 {
-                    self.state.log_scroll -= 1;
-                }
+            KeyCode::Char('k') | KeyCode::Up if self.state.log_scroll > 0 => {
+                self.state.log_scroll -= 1;
             }
             KeyCode::Char('g') => self.state.log_scroll = 0,
-            KeyCode::Char('G') => {
-                if log_count > 0 {
-                    self.state.log_scroll = log_count - 1;
+            KeyCode::Char('G') if log_count > 0 => {
+                self.state.log_scroll = log_count - 1;
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_schedules_key(&mut self, code: KeyCode) {
+        let schedule_count = self.state.schedules.len();
+        match code {
+            KeyCode::Char('j') | KeyCode::Down if schedule_count > 0 => {
+                self.state.selected_schedule_index =
+                    (self.state.selected_schedule_index + 1).min(schedule_count - 1);
+            }
+            KeyCode::Char('k') | KeyCode::Up if self.state.selected_schedule_index > 0 => {
+                self.state.selected_schedule_index -= 1;
+            }
+            KeyCode::Char('p') => {
+                if let Some(schedule) = self.state.get_selected_schedule() {
+                    let _ = self.cmd_tx.blocking_send(ServerCommand::SetSchedulePaused {
+                        schedule_id: schedule.id.clone(),
+                        paused: !schedule.paused,
+                    });
                 }
             }
+            KeyCode::Char('g') => self.state.selected_schedule_index = 0,
+            KeyCode::Char('G') if schedule_count > 0 => {
+                self.state.selected_schedule_index = schedule_count - 1;
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_audit_key(&mut self, code: KeyCode) {
+        let record_count = self.state.audit_records.len();
+        match code {
+            KeyCode::Char('j') | KeyCode::Down if record_count > 0 => {
+                self.state.selected_audit_index =
+                    (self.state.selected_audit_index + 1).min(record_count - 1);
+            }
+            KeyCode::Char('k') | KeyCode::Up if self.state.selected_audit_index > 0 => {
+                self.state.selected_audit_index -= 1;
+            }
+            KeyCode::Char('g') => self.state.selected_audit_index = 0,
+            KeyCode::Char('G') if record_count > 0 => {
+                self.state.selected_audit_index = record_count - 1;
+            }
             _ => {}
         }
     }
 
+    fn handle_approvals_key(&mut self, code: KeyCode) {
+        let approval_count = self.state.pending_approvals.len();
+        let decision = match code {
+            KeyCode::Char('j') | KeyCode::Down if approval_count > 0 => {
+                self.state.selected_approval_index =
+                    (self.state.selected_approval_index + 1).min(approval_count - 1);
+                return;
+            }
+            KeyCode::Char('k') | KeyCode::Up if self.state.selected_approval_index > 0 => {
+                self.state.selected_approval_index -= 1;
+                return;
+            }
+            KeyCode::Char('g') => {
+                self.state.selected_approval_index = 0;
+                return;
+            }
+            KeyCode::Char('G') if approval_count > 0 => {
+                self.state.selected_approval_index = approval_count - 1;
+                return;
+            }
+            KeyCode::Char('y') => (true, false),
+            KeyCode::Char('n') => (false, false),
+            KeyCode::Char('A') => (true, true),
+            _ => return,
+        };
+        if let Some(approval) = self.state.get_selected_approval() {
+            let approval_id = approval.id.clone();
+            self.decide_approval(approval_id, decision.0, decision.1);
+        }
+    }
+
     fn handle_run_detail_key(&mut self, code: KeyCode, modifiers: KeyModifiers) {
         // Chat input is always active in run detail view
         match code {
@@ -355,19 +777,25 @@ impl ServerApp {
                 self.state.chat_input_cursor = 0;
             }
             // Enter sends the message
-            KeyCode::Enter => {
-                if !self.state.chat_input.is_empty() {
-                    if let Some(task) = self.state.get_viewing_task() {
-                        if let Some(run_id) = &task.latest_run_id {
-                            let _ = self.cmd_tx.blocking_send(ServerCommand::SendChatMessage {
-                                run_id: run_id.clone(),
-                                message: self.state.chat_input.clone(),
-                            });
-                        }
+            KeyCode::Enter if !self.state.chat_input.is_empty() => {
+                if let Some(task) = self.state.get_viewing_task() {
+                    if let Some(run_id) = &task.latest_run_id {
+                        let _ = self.cmd_tx.blocking_send(ServerCommand::SendChatMessage {
+                            run_id: run_id.clone(),
+                            message: self.state.chat_input.clone(),
+                        });
                     }
-                    self.state.chat_input.clear();
-                    self.state.chat_input_cursor = 0;
                 }
+                self.state.chat_input.clear();
+                self.state.chat_input_cursor = 0;
+            }
+            // Ctrl+O opens the latest attachment externally
+            KeyCode::Char('o') if modifiers.contains(KeyModifiers::CONTROL) => {
+                self.open_latest_attachment();
+            }
+            // Ctrl+R switches between rendered markdown and raw text
+            KeyCode::Char('r') if modifiers.contains(KeyModifiers::CONTROL) => {
+                self.state.raw_chat = !self.state.raw_chat;
             }
             // Navigation with Ctrl modifier for scrolling
             KeyCode::Up if modifiers.contains(KeyModifiers::CONTROL) => {
@@ -417,34 +845,30 @@ impl ServerApp {
                 self.state.chat_input.insert(byte_pos, c);
                 self.state.chat_input_cursor += 1;
             }
-            KeyCode::Backspace => {
-                if self.state.chat_input_cursor > 0 {
-                    let char_count = self.state.chat_input.chars().count();
-                    if self.state.chat_input_cursor <= char_count {
-                        let byte_pos = self
+            KeyCode::Backspace if self.state.chat_input_cursor > 0 => {
+                let char_count = self.state.chat_input.chars().count();
+                if self.state.chat_input_cursor <= char_count {
+                    let byte_pos = self
+                        .state
+                        .chat_input
+                        .char_indices()
+                        .nth(self.state.chat_input_cursor - 1)
+                        .map(|(i, _)| i);
+                    if let Some(start) = byte_pos {
+                        let end = self
                             .state
                             .chat_input
                             .char_indices()
-                            .nth(self.state.chat_input_cursor - 1)
-                            .map(|(i, _)| i);
-                        if let Some(start) = byte_pos {
-                            let end = self
-                                .state
-                                .chat_input
-                                .char_indices()
-                                .nth(self.state.chat_input_cursor)
-                                .map(|(i, _)| i)
-                                .unwrap_or(self.state.chat_input.len());
-                            self.state.chat_input.replace_range(start..end, "");
-                            self.state.chat_input_cursor -= 1;
-                        }
+                            .nth(self.state.chat_input_cursor)
+                            .map(|(i, _)| i)
+                            .unwrap_or(self.state.chat_input.len());
+                        self.state.chat_input.replace_range(start..end, "");
+                        self.state.chat_input_cursor -= 1;
                     }
                 }
             }
-            KeyCode::Left => {
-                if self.state.chat_input_cursor > 0 {
-                    self.state.chat_input_cursor -= 1;
-                }
+            KeyCode::Left if self.state.chat_input_cursor > 0 => {
+                self.state.chat_input_cursor -= 1;
             }
             KeyCode::Right => {
                 let char_count = self.state.chat_input.chars().count();
@@ -456,6 +880,40 @@ impl ServerApp {
         }
     }
 
+    fn open_latest_attachment(&mut self) {
+        let Some(artifact) = self.state.latest_viewing_attachment().cloned() else {
+            self.state
+                .add_log(LogLevel::Warn, "No attachment to open".to_string());
+            return;
+        };
+        // A worker's file:// path means nothing on this host; the control
+        // plane serves the uploaded copy instead
+        if !taskrun_tui_components::is_web_url(&artifact.uri) {
+            let task_id = self
+                .state
+                .get_viewing_task()
+                .map(|t| t.task_id.to_string())
+                .unwrap_or_default();
+            self.state.add_log(
+                LogLevel::Warn,
+                format!(
+                    "{} is on the worker host; download it from /v1/tasks/{}/artifacts/{}",
+                    artifact.name, task_id, artifact.name
+                ),
+            );
+            return;
+        }
+        match taskrun_tui_components::open_external(&artifact.uri) {
+            Ok(()) => self
+                .state
+                .add_log(LogLevel::Info, format!("Opened {}", artifact.name)),
+            Err(e) => self.state.add_log(
+                LogLevel::Error,
+                format!("Failed to open {}: {}", artifact.name, e),
+            ),
+        }
+    }
+
     fn handle_quit_confirm(&mut self, code: KeyCode) {
         match code {
             KeyCode::Char('y') | KeyCode::Char('Y') => {
@@ -484,20 +942,16 @@ impl ServerApp {
                     self.state.new_task_input.len()
                 };
             }
-            KeyCode::Enter => {
-                // Submit task
-                if !self.state.new_task_agent.is_empty() {
-                    let input = if self.state.new_task_input.is_empty() {
-                        "{}".to_string()
-                    } else {
-                        self.state.new_task_input.clone()
-                    };
-                    let _ = self.cmd_tx.blocking_send(ServerCommand::CreateTask {
-                        agent_name: self.state.new_task_agent.clone(),
-                        input_json: input,
-                    });
-                    self.state.show_new_task_dialog = false;
-                }
+            // Submit task, unless validation found errors
+            KeyCode::Enter
+                if !self.state.new_task_agent.is_empty()
+                    && !self.state.new_task_validation.iter().any(|m| m.error) =>
+            {
+                let _ = self.cmd_tx.blocking_send(ServerCommand::CreateTask {
+                    agent_name: self.state.new_task_agent.clone(),
+                    input_json: self.state.new_task_input_json(),
+                });
+                self.state.show_new_task_dialog = false;
             }
             KeyCode::Char(c) => {
                 let field = if self.state.new_task_field == 0 {
@@ -540,10 +994,8 @@ impl ServerApp {
                     }
                 }
             }
-            KeyCode::Left => {
-                if self.state.new_task_cursor > 0 {
-                    self.state.new_task_cursor -= 1;
-                }
+            KeyCode::Left if self.state.new_task_cursor > 0 => {
+                self.state.new_task_cursor -= 1;
             }
             KeyCode::Right => {
                 let field = if self.state.new_task_field == 0 {
@@ -558,6 +1010,48 @@ impl ServerApp {
             }
             _ => {}
         }
+        if self.state.show_new_task_dialog
+            && matches!(
+                code,
+                KeyCode::Char(_) | KeyCode::Backspace | KeyCode::Delete
+            )
+        {
+            self.validate_new_task();
+        }
+    }
+
+    /// Lint the new-task dialog's contents (answered with `TaskValidated`).
+    fn validate_new_task(&self) {
+        let _ = self.cmd_tx.blocking_send(ServerCommand::ValidateTask {
+            agent_name: self.state.new_task_agent.clone(),
+            input_json: self.state.new_task_input_json(),
+        });
+    }
+
+    fn handle_search_input(&mut self, code: KeyCode) {
+        let Some(input) = self.state.search_input.as_mut() else {
+            return;
+        };
+        match code {
+            KeyCode::Esc => {
+                self.state.search_input = None;
+            }
+            KeyCode::Enter => {
+                let query = input.trim().to_string();
+                self.state.search_input = None;
+                if query.is_empty() {
+                    self.state.task_search = None;
+                    self.state.selected_task_index = 0;
+                } else {
+                    let _ = self.cmd_tx.blocking_send(ServerCommand::Search { query });
+                }
+            }
+            KeyCode::Char(c) => input.push(c),
+            KeyCode::Backspace => {
+                input.pop();
+            }
+            _ => {}
+        }
     }
 
     fn handle_cancel_confirm(&mut self, code: KeyCode) {
@@ -593,4 +1087,63 @@ impl ServerApp {
             _ => {}
         }
     }
+
+    /// Ask the backend for a dry run of `op` over tasks with the selected
+    /// task's agent and status.
+    fn preview_bulk(&mut self, op: BulkOperation) {
+        if let Some(task) = self.state.get_selected_task() {
+            let filter = TaskFilter {
+                status: Some(task.status),
+                agent_name: Some(task.agent_name.clone()),
+                ..TaskFilter::default()
+            };
+            let _ = self
+                .cmd_tx
+                .blocking_send(ServerCommand::PreviewBulk { op, filter });
+        }
+    }
+
+    /// Decide the oldest pending approval.
+    fn handle_approval_dialog(&mut self, code: KeyCode) {
+        let (allowed, always_allow) = match code {
+            KeyCode::Char('y') | KeyCode::Char('Y') => (true, false),
+            KeyCode::Char('n') | KeyCode::Char('N') => (false, false),
+            KeyCode::Char('A') => (true, true),
+            KeyCode::Esc => {
+                self.state.show_approval_dialog = false;
+                return;
+            }
+            _ => return,
+        };
+        if let Some(approval) = self.state.pending_approvals.front() {
+            let approval_id = approval.id.clone();
+            self.decide_approval(approval_id, allowed, always_allow);
+        }
+    }
+
+    /// Send a decision; the approval is removed when the backend reports it resolved.
+    fn decide_approval(&self, approval_id: String, allowed: bool, always_allow: bool) {
+        let _ = self.cmd_tx.blocking_send(ServerCommand::DecideApproval {
+            approval_id,
+            allowed,
+            always_allow,
+        });
+    }
+
+    fn handle_bulk_confirm(&mut self, code: KeyCode) {
+        match code {
+            KeyCode::Char('y') | KeyCode::Char('Y') => {
+                if let Some(bulk) = self.state.bulk_confirm.take() {
+                    let _ = self.cmd_tx.blocking_send(ServerCommand::RunBulk {
+                        op: bulk.op,
+                        filter: bulk.filter,
+                    });
+                }
+            }
+            KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
+                self.state.bulk_confirm = None;
+            }
+            _ => {}
+        }
+    }
 }
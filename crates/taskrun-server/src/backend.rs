@@ -2,23 +2,67 @@
 //!
 //! Runs gRPC and HTTP servers and forwards events to the UI.
 
+use std::future::IntoFuture;
 use std::net::SocketAddr;
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 
+use chrono::{DateTime, Utc};
+use futures_util::future::BoxFuture;
 use tokio::sync::mpsc;
+use tokio_rustls::TlsAcceptor;
 use tonic::transport::{Certificate, Identity, Server, ServerTlsConfig};
 use tracing::{error, info, warn};
 
+use taskrun_tui_components::TimeFormat;
 use tokio_util::sync::CancellationToken;
 
-use crate::control_plane::crypto::CertificateAuthority;
+use crate::control_plane::api_keys::ApiKeyManager;
+use crate::control_plane::approvals;
+use crate::control_plane::assignments::{RedeliveryConfig, RedeliveryTimer};
+use crate::control_plane::audit::{AuditAction, AuditLog};
+use crate::control_plane::bulk::{self, BulkOperation, TaskFilter};
+use crate::control_plane::crypto::{CertificateAuthority, PreviousCa, RevocationList};
+use crate::control_plane::debug_stats;
+use crate::control_plane::disconnect;
+use crate::control_plane::drain;
+use crate::control_plane::event_store::EventStoreConfig;
+use crate::control_plane::grafana;
+use crate::control_plane::groups;
+use crate::control_plane::leader::{self, LeaseConfig, LeaseElector};
+use crate::control_plane::liveness::{LivenessConfig, LivenessMonitor};
+use crate::control_plane::output_store::{OutputStoreConfig, RunOutputs, DEFAULT_SPILL_THRESHOLD};
+use crate::control_plane::queue::Dispatcher;
+use crate::control_plane::quotas;
+use crate::control_plane::rbac::Role;
+use crate::control_plane::reaper::{Reaper, ReaperConfig};
+use crate::control_plane::reload::{ConfigReloader, LogFilterHandle};
+use crate::control_plane::retention::{Compactor, RetentionConfig};
+use crate::control_plane::scheduler::trace_created;
+use crate::control_plane::schedules::{ScheduleManager, ScheduleStoreConfig, ScheduleTicker};
+use crate::control_plane::search::{self, MAX_SEARCH_LIMIT};
+use crate::control_plane::service::mtls;
 use crate::control_plane::state::{AppState, UiNotification};
-use crate::control_plane::{http, RunServiceImpl, Scheduler, TaskServiceImpl, WorkerServiceImpl};
-use taskrun_core::{RunId, Task, TaskId, TaskStatus};
+use crate::control_plane::toggles::{self, ToggleUpdate};
+use crate::control_plane::validation;
+use crate::control_plane::workflows::{self, WorkflowAdvancer, WorkflowManager};
+use crate::control_plane::{
+    http, AdminServiceImpl, ApiKeyServiceImpl, ApprovalServiceImpl, RunServiceImpl,
+    ScheduleServiceImpl, Scheduler, TaskServiceImpl, WorkerServiceImpl, WorkflowServiceImpl,
+};
+use taskrun_core::{
+    RunEventType, RunId, ScheduleId, Task, TaskId, TaskPriority, DEFAULT_NAMESPACE,
+};
+use taskrun_proto::trace::TraceContext;
 
 use crate::mcp;
 
-use crate::event::{LogLevel, ServerCommand, ServerUiEvent};
+use crate::event::{LogLevel, ServerCommand, ServerUiEvent, ValidationMessage};
+use crate::state::MAX_AUDIT_RECORDS;
+
+/// Actor recorded for actions taken in the server TUI.
+const TUI_ACTOR: &str = "server-tui";
 
 /// Server configuration.
 #[derive(Debug, Clone)]
@@ -30,6 +74,68 @@ pub struct ServerConfig {
     pub ca_cert_path: String,
     pub ca_key_path: String,
     pub worker_cert_validity_days: u32,
+    /// CA certificate being rotated out, still trusted for client certificates.
+    pub previous_ca_cert_path: Option<String>,
+    /// End of the rotation window (None = one worker certificate validity
+    /// after the new CA's start).
+    pub previous_ca_until: Option<DateTime<Utc>>,
+    /// JSON file revoked certificates are persisted to (None = in-memory only).
+    pub revocations_file: Option<PathBuf>,
+    pub event_store: EventStoreConfig,
+    /// How long events, chat messages, and output of finished runs are kept.
+    pub retention: RetentionConfig,
+    /// Expected heartbeat interval from workers (seconds).
+    pub heartbeat_interval_secs: u64,
+    /// Missed heartbeats before a worker's runs are reaped.
+    pub max_missed_heartbeats: u32,
+    /// Heartbeat age (seconds) after which a worker is marked Degraded.
+    pub worker_stale_secs: u64,
+    pub schedule_store: ScheduleStoreConfig,
+    /// JSON file of workflow templates (None = no templates).
+    pub workflows_file: Option<PathBuf>,
+    /// JSON file of namespace and API key quotas (None = unlimited).
+    pub quotas_file: Option<PathBuf>,
+    /// JSON file runtime toggles are persisted to (None = in-memory only).
+    pub toggles_file: Option<PathBuf>,
+    /// Allow chaos flags to be set (testing and staging only).
+    pub enable_chaos: bool,
+    /// JSON file HTTP API keys are persisted to (None = in-memory only).
+    pub api_keys_file: Option<PathBuf>,
+    /// Reject HTTP API calls without a valid API key.
+    pub require_api_key: bool,
+    /// Serve the HTTP API over TLS with the server certificate.
+    pub http_tls: bool,
+    /// Origins allowed to make cross-origin HTTP requests (empty = any).
+    pub cors_origins: Vec<String>,
+    /// JSONL file audit records are appended to (None = in-memory only).
+    pub audit_file: Option<PathBuf>,
+    /// Cancel a streamed `/v1/responses` task when its client disconnects.
+    pub cancel_on_disconnect: bool,
+    /// Proxies (addresses or CIDR ranges) whose forwarding headers are believed.
+    pub trusted_proxies: Vec<String>,
+    /// Fraction of HTTP requests that are logged (server errors always are).
+    pub http_log_sample_rate: f64,
+    /// Write an access log line for every HTTP API request.
+    pub access_log: bool,
+    /// File the access log is written to (None = the application log).
+    pub access_log_file: Option<http::AccessLogFile>,
+    /// Seconds a task may wait in Pending before it is failed (None = forever).
+    pub pending_ttl_secs: Option<u64>,
+    /// Times a task is rescheduled after its worker is lost mid-run (0 = fail it).
+    pub lost_run_retries: u32,
+    /// Seconds a worker has to acknowledge an assignment before it is reassigned.
+    pub assignment_ack_timeout_secs: u64,
+    pub output_store: OutputStoreConfig,
+    /// In-memory output (bytes) after which a run spills to the output store.
+    pub output_spill_bytes: usize,
+    /// Leader election for HA mode (None = standalone, always the leader).
+    pub ha: Option<LeaseConfig>,
+    /// JSON file of settings reloaded while running (None = command line only).
+    pub config_file: Option<PathBuf>,
+    /// Swaps the log filter when `log_level` is reloaded.
+    pub log_filter: Option<LogFilterHandle>,
+    /// Time zone and locale of TUI timestamps, unless the settings file sets them.
+    pub time_format: TimeFormat,
 }
 
 impl Default for ServerConfig {
@@ -42,6 +148,38 @@ impl Default for ServerConfig {
             ca_cert_path: "certs/ca.crt".to_string(),
             ca_key_path: "certs/ca.key".to_string(),
             worker_cert_validity_days: 7,
+            previous_ca_cert_path: None,
+            previous_ca_until: None,
+            revocations_file: None,
+            event_store: EventStoreConfig::Memory,
+            retention: RetentionConfig::default(),
+            heartbeat_interval_secs: 15,
+            max_missed_heartbeats: 3,
+            worker_stale_secs: 30,
+            schedule_store: ScheduleStoreConfig::Memory,
+            workflows_file: None,
+            quotas_file: None,
+            toggles_file: None,
+            enable_chaos: false,
+            api_keys_file: None,
+            require_api_key: false,
+            http_tls: false,
+            cors_origins: Vec::new(),
+            audit_file: None,
+            cancel_on_disconnect: false,
+            trusted_proxies: Vec::new(),
+            http_log_sample_rate: 1.0,
+            access_log: false,
+            access_log_file: None,
+            pending_ttl_secs: None,
+            lost_run_retries: 0,
+            assignment_ack_timeout_secs: 10,
+            output_store: OutputStoreConfig::Memory,
+            output_spill_bytes: DEFAULT_SPILL_THRESHOLD,
+            ha: None,
+            config_file: None,
+            log_filter: None,
+            time_format: TimeFormat::default(),
         }
     }
 }
@@ -58,11 +196,326 @@ pub async fn run_server_backend(
         None => return,
     };
 
+    // Load revoked certificates; an unreadable list must not let them back in
+    let revocations = match RevocationList::open(config.revocations_file.clone()) {
+        Ok(revocations) => revocations,
+        Err(e) => {
+            let _ = ui_tx
+                .send(ServerUiEvent::ServerError {
+                    message: format!("Failed to load revoked certificates: {}", e),
+                })
+                .await;
+            return;
+        }
+    };
+
     // Load CA for certificate signing
-    let ca = load_ca(&config, &ui_tx).await;
+    let ca = load_ca(&config, revocations, &ui_tx).await;
+
+    // Open event store
+    let event_store = match config.event_store.build().await {
+        Ok(store) => store,
+        Err(e) => {
+            let _ = ui_tx
+                .send(ServerUiEvent::ServerError {
+                    message: format!("Failed to open event store: {}", e),
+                })
+                .await;
+            return;
+        }
+    };
+    if let EventStoreConfig::File { path } = &config.event_store {
+        log_to_ui(
+            &ui_tx,
+            LogLevel::Info,
+            format!("Persisting run events to {}", path.display()),
+        )
+        .await;
+    }
+
+    // Open audit log
+    let audit = match AuditLog::open(config.audit_file.clone()).await {
+        Ok(audit) => audit,
+        Err(e) => {
+            let _ = ui_tx
+                .send(ServerUiEvent::ServerError {
+                    message: format!("Failed to open audit log: {}", e),
+                })
+                .await;
+            return;
+        }
+    };
+    if let Some(path) = &config.audit_file {
+        log_to_ui(
+            &ui_tx,
+            LogLevel::Info,
+            format!("Appending audit records to {}", path.display()),
+        )
+        .await;
+    }
+
+    // Open output store
+    let output_store = match config.output_store.build().await {
+        Ok(store) => store,
+        Err(e) => {
+            let _ = ui_tx
+                .send(ServerUiEvent::ServerError {
+                    message: format!("Failed to open output store: {}", e),
+                })
+                .await;
+            return;
+        }
+    };
+    match &config.output_store {
+        OutputStoreConfig::Memory => {}
+        OutputStoreConfig::Disk { dir } => {
+            log_to_ui(
+                &ui_tx,
+                LogLevel::Info,
+                format!("Spilling run output to {}", dir.display()),
+            )
+            .await;
+        }
+        OutputStoreConfig::S3(s3) => {
+            log_to_ui(
+                &ui_tx,
+                LogLevel::Info,
+                format!("Spilling run output to s3://{}/{}", s3.bucket, s3.prefix),
+            )
+            .await;
+        }
+    }
+    let outputs = RunOutputs::new(output_store, config.output_spill_bytes);
 
     // Create shared state with UI notification channel
-    let (state, ui_rx) = AppState::with_ui_channel(ca);
+    let (state, ui_rx) = AppState::with_ui_channel(ca, event_store, outputs, audit);
+
+    // Stuck-run reaper and worker liveness monitor settings
+    let reaper_config = ReaperConfig {
+        heartbeat_interval: Duration::from_secs(config.heartbeat_interval_secs),
+        max_missed_heartbeats: config.max_missed_heartbeats,
+        pending_ttl: config.pending_ttl_secs.map(Duration::from_secs),
+        lost_run_retries: config.lost_run_retries,
+        ..ReaperConfig::default()
+    };
+    let redelivery_config = RedeliveryConfig {
+        ack_timeout: Duration::from_secs(config.assignment_ack_timeout_secs.max(1)),
+        ..RedeliveryConfig::default()
+    };
+    let liveness_config = LivenessConfig {
+        degraded_after: Duration::from_secs(config.worker_stale_secs),
+        offline_after: reaper_config.heartbeat_timeout(),
+        ..LivenessConfig::default()
+    };
+    tokio::spawn(groups::publish_loop(state.clone()));
+    tokio::spawn(debug_stats::publish_loop(state.clone()));
+    tokio::spawn(grafana::sample_workers_loop(state.clone()));
+
+    // Load schedules and start firing them
+    let schedules = match ScheduleManager::open(state.clone(), config.schedule_store.build()).await
+    {
+        Ok(manager) => manager,
+        Err(e) => {
+            let _ = ui_tx
+                .send(ServerUiEvent::ServerError {
+                    message: format!("Failed to load schedules: {}", e),
+                })
+                .await;
+            return;
+        }
+    };
+    if let ScheduleStoreConfig::File { path } = &config.schedule_store {
+        log_to_ui(
+            &ui_tx,
+            LogLevel::Info,
+            format!("Persisting schedules to {}", path.display()),
+        )
+        .await;
+    }
+
+    // Load quotas
+    if let Some(path) = &config.quotas_file {
+        match quotas::load_quotas(path) {
+            Ok(quota_config) => {
+                state.quotas.configure(quota_config).await;
+                log_to_ui(
+                    &ui_tx,
+                    LogLevel::Info,
+                    format!("Loaded quotas from {}", path.display()),
+                )
+                .await;
+            }
+            Err(e) => {
+                let _ = ui_tx
+                    .send(ServerUiEvent::ServerError {
+                        message: e.to_string(),
+                    })
+                    .await;
+                return;
+            }
+        }
+    }
+
+    // Load runtime toggles
+    if let Some(path) = &config.toggles_file {
+        match state.toggles.open(path.clone()).await {
+            Ok(toggles) => {
+                log_to_ui(
+                    &ui_tx,
+                    LogLevel::Info,
+                    format!("Persisting runtime toggles to {}", path.display()),
+                )
+                .await;
+                let _ = ui_tx.send(ServerUiEvent::TogglesChanged { toggles }).await;
+            }
+            Err(e) => {
+                let _ = ui_tx
+                    .send(ServerUiEvent::ServerError {
+                        message: e.to_string(),
+                    })
+                    .await;
+                return;
+            }
+        }
+    }
+
+    if config.enable_chaos {
+        state.chaos.enable();
+        log_to_ui(
+            &ui_tx,
+            LogLevel::Warn,
+            "Chaos flags enabled: faults can be injected via /v1/admin/chaos".to_string(),
+        )
+        .await;
+    }
+
+    // Load the settings file and reload it when it changes
+    let reloader = match &config.config_file {
+        Some(path) => {
+            let reloader = Arc::new(
+                ConfigReloader::new(
+                    state.clone(),
+                    path.clone(),
+                    config.retention.clone(),
+                    config.quotas_file.clone(),
+                    config.log_filter.clone(),
+                )
+                .with_time_format(config.time_format),
+            );
+            if let Err(e) = reloader.load().await {
+                let _ = ui_tx
+                    .send(ServerUiEvent::ServerError {
+                        message: e.to_string(),
+                    })
+                    .await;
+                return;
+            }
+            log_to_ui(
+                &ui_tx,
+                LogLevel::Info,
+                format!("Loaded settings from {}", path.display()),
+            )
+            .await;
+            tokio::spawn(reloader.clone().run());
+            Some(reloader)
+        }
+        None => None,
+    };
+    let retention = match &reloader {
+        Some(reloader) => reloader.retention(),
+        None => tokio::sync::watch::channel(config.retention.clone()).1,
+    };
+
+    // Load workflow templates and start advancing workflows
+    let templates = match &config.workflows_file {
+        Some(path) => match workflows::load_templates(path) {
+            Ok(templates) => templates,
+            Err(e) => {
+                let _ = ui_tx
+                    .send(ServerUiEvent::ServerError {
+                        message: e.to_string(),
+                    })
+                    .await;
+                return;
+            }
+        },
+        None => Vec::new(),
+    };
+    let workflows = match WorkflowManager::new(state.clone(), templates) {
+        Ok(manager) => manager,
+        Err(e) => {
+            let _ = ui_tx
+                .send(ServerUiEvent::ServerError {
+                    message: e.to_string(),
+                })
+                .await;
+            return;
+        }
+    };
+
+    // Elect a leader among instances sharing the lease file, if HA is on
+    if let Some(lease) = &config.ha {
+        log_to_ui(
+            &ui_tx,
+            LogLevel::Info,
+            format!(
+                "HA mode: electing a leader through {} as {}",
+                lease.path.display(),
+                lease.advertise_addr
+            ),
+        )
+        .await;
+        tokio::spawn(LeaseElector::new(state.clone(), lease.clone()).run());
+    }
+
+    // Loops that change tasks run on the leader only
+    let leader_loops = {
+        let state = state.clone();
+        let reloadable = reloader.is_some();
+        let schedules = schedules.clone();
+        let workflows = workflows.clone();
+        move || {
+            let mut handles = vec![
+                tokio::spawn(Reaper::new(state.clone(), reaper_config.clone()).run()),
+                tokio::spawn(RedeliveryTimer::new(state.clone(), redelivery_config.clone()).run()),
+                tokio::spawn(Dispatcher::new(state.clone()).run()),
+                tokio::spawn(LivenessMonitor::new(state.clone(), liveness_config.clone()).run()),
+                tokio::spawn(ScheduleTicker::new(schedules.clone()).run()),
+                tokio::spawn(WorkflowAdvancer::new(workflows.clone()).run()),
+            ];
+            // Periodically drop run data past retention, if any is configured
+            // or may be configured by a reload
+            if reloadable || retention.borrow().is_enabled() {
+                handles.push(tokio::spawn(
+                    Compactor::watching(state.clone(), retention.clone()).run(),
+                ));
+            }
+            handles
+        }
+    };
+    tokio::spawn(leader::while_leader(state.clone(), leader_loops));
+
+    // Load HTTP API keys
+    let api_keys = match ApiKeyManager::open(config.api_keys_file.clone()).await {
+        Ok(manager) => manager,
+        Err(e) => {
+            let _ = ui_tx
+                .send(ServerUiEvent::ServerError {
+                    message: format!("Failed to load API keys: {}", e),
+                })
+                .await;
+            return;
+        }
+    };
+    if config.require_api_key {
+        log_to_ui(
+            &ui_tx,
+            LogLevel::Info,
+            "HTTP API requires an API key".to_string(),
+        )
+        .await;
+    }
+    let auth = http::ApiKeyAuth::new(api_keys.clone(), config.require_api_key);
 
     // Clone state for servers
     let state_for_grpc = state.clone();
@@ -79,14 +532,87 @@ pub async fn run_server_backend(
     let run_service = RunServiceImpl::new(state_for_grpc.clone()).into_server();
     let task_service = TaskServiceImpl::new(state_for_grpc.clone()).into_server();
     let worker_service = WorkerServiceImpl::new(state_for_grpc).into_server();
+    let schedule_service = ScheduleServiceImpl::new(schedules.clone()).into_server();
+    let workflow_service = WorkflowServiceImpl::new(workflows).into_server();
+    let api_key_service = ApiKeyServiceImpl::new(api_keys, state.clone()).into_server();
+    let approval_service = ApprovalServiceImpl::new(state.clone()).into_server();
+    let admin_service = AdminServiceImpl::new(state.clone()).into_server();
 
     // Create cancellation token for MCP
     let mcp_ct = CancellationToken::new();
 
+    let access_log = match config.access_log_file.clone() {
+        Some(file) => match http::AccessLog::to_file(file) {
+            Ok(log) => Some(log),
+            Err(e) => {
+                let _ = ui_tx
+                    .send(ServerUiEvent::ServerError {
+                        message: e.to_string(),
+                    })
+                    .await;
+                return;
+            }
+        },
+        None => config.access_log.then(http::AccessLog::to_log),
+    };
+
     // Create HTTP router with MCP support
-    let http_router = http::create_router(state_for_http.clone());
-    let mcp_router = mcp::create_mcp_router(state_for_http, mcp_ct.clone());
-    let http_router = http_router.merge(mcp_router);
+    let http_router = http::create_router(
+        state_for_http.clone(),
+        auth.clone(),
+        http::ResponsesConfig {
+            cancel_on_disconnect: config.cancel_on_disconnect,
+        },
+        reloader,
+        access_log,
+    );
+    let mcp_router =
+        mcp::create_mcp_router(state_for_http.clone(), schedules.clone(), mcp_ct.clone())
+            .route_layer(
+                // Tools that change anything check the caller's role themselves
+                axum::middleware::from_fn_with_state(
+                    auth.require(Role::Readonly),
+                    http::require_role,
+                ),
+            );
+    let trusted_proxies = match http::TrustedProxies::parse(&config.trusted_proxies) {
+        Ok(trusted) => Arc::new(trusted),
+        Err(e) => {
+            let _ = ui_tx
+                .send(ServerUiEvent::ServerError {
+                    message: e.to_string(),
+                })
+                .await;
+            return;
+        }
+    };
+    let cors = match http::cors_layer(&config.cors_origins) {
+        Ok(cors) => cors,
+        Err(e) => {
+            let _ = ui_tx
+                .send(ServerUiEvent::ServerError {
+                    message: format!("Invalid CORS origin: {}", e),
+                })
+                .await;
+            return;
+        }
+    };
+    let http_router = http::with_request_log(
+        http_router.merge(mcp_router),
+        state_for_http,
+        http::RequestLogConfig {
+            sample_rate: config.http_log_sample_rate,
+        },
+    )
+    .layer(axum::middleware::from_fn_with_state(
+        state.clone(),
+        leader::require_leader_http,
+    ))
+    .layer(axum::middleware::from_fn_with_state(
+        trusted_proxies,
+        http::resolve_client_ip,
+    ))
+    .layer(cors);
 
     // Parse addresses
     let grpc_addr: SocketAddr = match config.grpc_addr.parse() {
@@ -130,18 +656,28 @@ pub async fn run_server_backend(
         &ui_tx,
         LogLevel::Info,
         format!(
-            "HTTP server listening on {} (MCP at /mcp)",
-            config.http_addr
+            "HTTP server listening on {}{} (MCP at /mcp)",
+            config.http_addr,
+            if config.http_tls { " (TLS)" } else { "" }
         ),
     )
     .await;
 
     // Build gRPC server
     let grpc_server = match Server::builder().tls_config(tls_config) {
-        Ok(mut builder) => builder
+        Ok(builder) => builder
+            .layer(leader::RequireLeaderLayer::new(state.clone()))
+            .layer(tonic::service::interceptor(mtls::reject_untrusted(
+                state.clone(),
+            )))
             .add_service(run_service)
             .add_service(task_service)
             .add_service(worker_service)
+            .add_service(schedule_service)
+            .add_service(workflow_service)
+            .add_service(api_key_service)
+            .add_service(approval_service)
+            .add_service(admin_service)
             .serve(grpc_addr),
         Err(e) => {
             let _ = ui_tx
@@ -165,13 +701,42 @@ pub async fn run_server_backend(
             return;
         }
     };
-    let http_server = axum::serve(http_listener, http_router);
+    let http_server: BoxFuture<'static, std::io::Result<()>> = if config.http_tls {
+        let acceptor = match http_tls_acceptor(&config) {
+            Ok(acceptor) => acceptor,
+            Err(message) => {
+                let _ = ui_tx.send(ServerUiEvent::ServerError { message }).await;
+                return;
+            }
+        };
+        Box::pin(http::tls::serve(
+            http_listener,
+            acceptor,
+            http_router,
+            state.clone(),
+        ))
+    } else {
+        Box::pin(
+            axum::serve(
+                http_listener,
+                http_router.into_make_service_with_connect_info::<SocketAddr>(),
+            )
+            .into_future(),
+        )
+    };
 
     // Spawn a task to handle commands
     let cmd_ui_tx = ui_tx.clone();
     let mcp_ct_for_cmds = mcp_ct.clone();
     tokio::spawn(async move {
-        handle_commands(cmd_rx, state_for_commands, cmd_ui_tx, mcp_ct_for_cmds).await;
+        handle_commands(
+            cmd_rx,
+            state_for_commands,
+            schedules,
+            cmd_ui_tx,
+            mcp_ct_for_cmds,
+        )
+        .await;
     });
 
     // Run servers concurrently - when one exits or shuts down, the function returns
@@ -207,9 +772,16 @@ pub async fn run_server_backend(
 async fn handle_commands(
     mut cmd_rx: mpsc::Receiver<ServerCommand>,
     state: Arc<AppState>,
+    schedules: Arc<ScheduleManager>,
     ui_tx: mpsc::Sender<ServerUiEvent>,
     mcp_ct: CancellationToken,
 ) {
+    // The forwarder is running by now, so the UI receives the loaded schedules
+    schedules.notify_all().await;
+    for record in state.audit.recent(MAX_AUDIT_RECORDS).await {
+        let _ = ui_tx.send(ServerUiEvent::AuditRecorded { record }).await;
+    }
+
     while let Some(cmd) = cmd_rx.recv().await {
         match cmd {
             ServerCommand::Shutdown => {
@@ -225,19 +797,76 @@ async fn handle_commands(
             } => {
                 handle_create_task(&state, &ui_tx, agent_name, input_json).await;
             }
+            ServerCommand::ValidateTask {
+                agent_name,
+                input_json,
+            } => {
+                handle_validate_task(&state, &ui_tx, agent_name, input_json).await;
+            }
             ServerCommand::CancelTask { task_id } => {
                 handle_cancel_task(&state, &ui_tx, task_id).await;
             }
+            ServerCommand::PreviewBulk { op, filter } => {
+                handle_preview_bulk(&state, &ui_tx, op, filter).await;
+            }
+            ServerCommand::RunBulk { op, filter } => {
+                handle_run_bulk(&state, &ui_tx, op, filter).await;
+            }
             ServerCommand::DisconnectWorker { worker_id } => {
                 handle_disconnect_worker(&state, &ui_tx, worker_id).await;
             }
+            ServerCommand::UpdateToggles { update } => {
+                handle_update_toggles(&state, &ui_tx, update).await;
+            }
+            ServerCommand::SetWorkerDraining {
+                worker_id,
+                draining,
+            } => {
+                handle_set_worker_draining(&state, &ui_tx, worker_id, draining).await;
+            }
             ServerCommand::SendChatMessage { run_id, message } => {
                 handle_send_chat_message(&state, &ui_tx, run_id, message).await;
             }
+            ServerCommand::Search { query } => {
+                handle_search(&state, &ui_tx, query).await;
+            }
+            ServerCommand::LoadRunOutput { run_id } => {
+                handle_load_run_output(&state, &ui_tx, run_id).await;
+            }
+            ServerCommand::DecideApproval {
+                approval_id,
+                allowed,
+                always_allow,
+            } => {
+                handle_decide_approval(&state, &ui_tx, approval_id, allowed, always_allow).await;
+            }
+            ServerCommand::SetSchedulePaused {
+                schedule_id,
+                paused,
+            } => {
+                handle_set_schedule_paused(&schedules, &ui_tx, schedule_id, paused).await;
+            }
         }
     }
 }
 
+/// TLS acceptor for the HTTP listener, from the gRPC server's certificate and CA.
+fn http_tls_acceptor(config: &ServerConfig) -> Result<TlsAcceptor, String> {
+    let read = |path: &str| {
+        std::fs::read(path).map_err(|e| format!("Failed to read '{}' for HTTP TLS: {}", path, e))
+    };
+    let mut client_ca = read(&config.ca_cert_path)?;
+    if let Some(path) = &config.previous_ca_cert_path {
+        client_ca.extend(read(path)?);
+    }
+    http::tls::acceptor(
+        &read(&config.tls_cert_path)?,
+        &read(&config.tls_key_path)?,
+        &client_ca,
+    )
+    .map_err(|e| format!("Failed to configure HTTP TLS: {}", e))
+}
+
 async fn load_tls(
     config: &ServerConfig,
     ui_tx: &mpsc::Sender<ServerUiEvent>,
@@ -274,7 +903,7 @@ async fn load_tls(
     let identity = Identity::from_pem(cert, key);
 
     // Load CA certificate for client verification (mTLS)
-    let ca_cert = match std::fs::read(&config.ca_cert_path) {
+    let mut ca_cert = match std::fs::read(&config.ca_cert_path) {
         Ok(ca) => ca,
         Err(e) => {
             let msg = format!(
@@ -288,6 +917,23 @@ async fn load_tls(
         }
     };
 
+    // During a CA rotation, client certificates of the previous CA verify too
+    if let Some(path) = &config.previous_ca_cert_path {
+        match std::fs::read(path) {
+            Ok(previous) => ca_cert.extend(previous),
+            Err(e) => {
+                let msg = format!(
+                    "Failed to read previous CA certificate from '{}': {}",
+                    path, e
+                );
+                let _ = ui_tx
+                    .send(ServerUiEvent::ServerError { message: msg })
+                    .await;
+                return None;
+            }
+        }
+    }
+
     let tls_config = ServerTlsConfig::new()
         .identity(identity.clone())
         .client_ca_root(Certificate::from_pem(ca_cert));
@@ -297,6 +943,7 @@ async fn load_tls(
 
 async fn load_ca(
     config: &ServerConfig,
+    revocations: RevocationList,
     ui_tx: &mpsc::Sender<ServerUiEvent>,
 ) -> Option<CertificateAuthority> {
     match CertificateAuthority::from_files(
@@ -309,12 +956,42 @@ async fn load_ca(
                 ui_tx,
                 LogLevel::Info,
                 format!(
-                    "Certificate Authority loaded (validity: {} days)",
-                    config.worker_cert_validity_days
+                    "Certificate Authority loaded (validity: {} days, {} revoked)",
+                    config.worker_cert_validity_days,
+                    revocations.list().len()
                 ),
             )
             .await;
-            Some(ca)
+            let ca = ca.with_revocations(revocations);
+            let Some(path) = &config.previous_ca_cert_path else {
+                return Some(ca);
+            };
+            let until = config
+                .previous_ca_until
+                .unwrap_or_else(|| ca.default_rotation_end());
+            match PreviousCa::load(path, until) {
+                Ok(previous) => {
+                    log_to_ui(
+                        ui_tx,
+                        LogLevel::Info,
+                        format!(
+                            "Rotating CA: certificates of '{}' trusted until {}",
+                            path,
+                            until.to_rfc3339()
+                        ),
+                    )
+                    .await;
+                    Some(ca.with_previous(previous))
+                }
+                Err(e) => {
+                    let _ = ui_tx
+                        .send(ServerUiEvent::ServerError {
+                            message: format!("Failed to load previous CA '{}': {}", path, e),
+                        })
+                        .await;
+                    None
+                }
+            }
         }
         Err(e) => {
             log_to_ui(
@@ -340,12 +1017,14 @@ async fn forward_notifications(
                         worker_id,
                         hostname,
                         agents,
+                        labels,
                     } => ServerUiEvent::WorkerConnected {
                         worker_id,
                         hostname,
                         agents,
+                        labels,
                     },
-                    UiNotification::WorkerDisconnected { worker_id } => {
+                    UiNotification::WorkerDisconnected { worker_id, .. } => {
                         ServerUiEvent::WorkerDisconnected { worker_id }
                     }
                     UiNotification::WorkerHeartbeat {
@@ -353,18 +1032,49 @@ async fn forward_notifications(
                         status,
                         active_runs,
                         max_concurrent_runs,
+                        metrics,
                     } => ServerUiEvent::WorkerHeartbeat {
                         worker_id,
                         status,
                         active_runs,
                         max_concurrent_runs,
+                        metrics,
                     },
-                    UiNotification::TaskCreated { task_id, agent } => {
-                        ServerUiEvent::TaskCreated { task_id, agent }
+                    UiNotification::WorkerStatusChanged { worker_id, status } => {
+                        ServerUiEvent::WorkerStatusChanged { worker_id, status }
                     }
+                    UiNotification::WorkerDrained { worker_id } => {
+                        ServerUiEvent::WorkerDrained { worker_id }
+                    }
+                    UiNotification::WorkerUpdated {
+                        worker_id,
+                        agents,
+                        labels,
+                        max_concurrent_runs,
+                    } => ServerUiEvent::WorkerUpdated {
+                        worker_id,
+                        agents,
+                        labels,
+                        max_concurrent_runs,
+                    },
+                    UiNotification::TaskCreated {
+                        task_id,
+                        agent,
+                        created_by,
+                        client_ip,
+                    } => ServerUiEvent::TaskCreated {
+                        task_id,
+                        agent,
+                        created_by,
+                        client_ip,
+                    },
                     UiNotification::TaskStatusChanged { task_id, status } => {
                         ServerUiEvent::TaskStatusChanged { task_id, status }
                     }
+                    UiNotification::TaskExpired { task_id, reason } => ServerUiEvent::LogMessage {
+                        level: LogLevel::Warn,
+                        message: format!("Task {} expired: {}", task_id, reason),
+                    },
                     UiNotification::RunStatusChanged {
                         run_id,
                         task_id,
@@ -376,8 +1086,15 @@ async fn forward_notifications(
                         status,
                     },
                     UiNotification::RunOutputChunk {
-                        run_id, content, ..
-                    } => ServerUiEvent::RunOutputChunk { run_id, content },
+                        run_id,
+                        seq,
+                        content,
+                        ..
+                    } => ServerUiEvent::RunOutputChunk {
+                        run_id,
+                        seq,
+                        content,
+                    },
                     UiNotification::RunEvent {
                         run_id,
                         event_type,
@@ -386,7 +1103,18 @@ async fn forward_notifications(
                         ..
                     } => {
                         // Extract details from metadata (e.g., tool name)
-                        let details = metadata.get("tool_name").cloned();
+                        let details = if event_type == RunEventType::Log {
+                            Some(format!(
+                                "{} {}",
+                                metadata.get("level").map_or("", String::as_str),
+                                metadata.get("message").map_or("", String::as_str)
+                            ))
+                        } else {
+                            metadata
+                                .get("tool_name")
+                                .or_else(|| metadata.get("milestone"))
+                                .cloned()
+                        };
                         ServerUiEvent::RunEvent {
                             run_id,
                             event_type,
@@ -399,12 +1127,56 @@ async fn forward_notifications(
                         task_id,
                         role,
                         content,
+                        attachments,
                     } => ServerUiEvent::ChatMessage {
                         run_id,
                         task_id,
                         role,
                         content,
+                        attachments,
                     },
+                    UiNotification::ScheduleUpdated { schedule } => {
+                        ServerUiEvent::ScheduleUpdated { schedule }
+                    }
+                    UiNotification::ScheduleDeleted { schedule_id } => {
+                        ServerUiEvent::ScheduleDeleted { schedule_id }
+                    }
+                    UiNotification::AuditRecorded { record } => {
+                        ServerUiEvent::AuditRecorded { record }
+                    }
+                    UiNotification::ApprovalRequested { approval } => {
+                        ServerUiEvent::ApprovalRequested { approval }
+                    }
+                    UiNotification::ApprovalResolved { approval_id } => {
+                        ServerUiEvent::ApprovalResolved { approval_id }
+                    }
+                    UiNotification::GroupsUpdated { groups } => {
+                        ServerUiEvent::GroupsUpdated { groups }
+                    }
+                    UiNotification::QueueUpdated { entries } => {
+                        ServerUiEvent::QueueUpdated { entries }
+                    }
+                    UiNotification::DebugStats { stats } => ServerUiEvent::DebugStats { stats },
+                    UiNotification::RunMilestone { run_id, milestone } => {
+                        ServerUiEvent::RunMilestone { run_id, milestone }
+                    }
+                    UiNotification::RunResult { run_id, result } => {
+                        ServerUiEvent::RunResult { run_id, result }
+                    }
+                    UiNotification::ConfigReloaded { changed } => ServerUiEvent::LogMessage {
+                        level: LogLevel::Info,
+                        message: if changed.is_empty() {
+                            "Settings reloaded, nothing changed".to_string()
+                        } else {
+                            format!("Settings reloaded: {}", changed.join(", "))
+                        },
+                    },
+                    UiNotification::TogglesChanged { toggles } => {
+                        ServerUiEvent::TogglesChanged { toggles }
+                    }
+                    UiNotification::TimeFormatChanged { time_format } => {
+                        ServerUiEvent::TimeFormatChanged { time_format }
+                    }
                 };
 
                 if tx.send(event).await.is_err() {
@@ -428,7 +1200,7 @@ async fn handle_create_task(
     input_json: String,
 ) {
     // Validate agent exists on a worker
-    if !state.has_agent(&agent_name).await {
+    if !state.has_agent(&agent_name, DEFAULT_NAMESPACE).await {
         log_to_ui(
             ui_tx,
             LogLevel::Error,
@@ -439,7 +1211,9 @@ async fn handle_create_task(
     }
 
     // Create task
-    let task = Task::new(&agent_name, &input_json, "server-tui");
+    let task = Task::new(&agent_name, &input_json, TUI_ACTOR)
+        .with_priority(state.toggles.default_priority().await);
+    let task = trace_created(task, &TraceContext::new());
     let task_id = task.id.clone();
 
     log_to_ui(
@@ -450,13 +1224,19 @@ async fn handle_create_task(
     .await;
 
     // Store task
+    state
+        .audit(
+            &task.created_by,
+            AuditAction::TaskCreated,
+            task_id.as_str(),
+            &task,
+        )
+        .await;
+    let created = UiNotification::task_created(&task);
     state.tasks.write().await.insert(task_id.clone(), task);
 
     // Notify UI (the notification from TaskService won't fire since we're bypassing it)
-    state.notify_ui(UiNotification::TaskCreated {
-        task_id: task_id.clone(),
-        agent: agent_name.clone(),
-    });
+    state.notify_ui(created);
 
     // Schedule immediately
     let scheduler = Scheduler::new(state.clone());
@@ -485,89 +1265,250 @@ async fn handle_cancel_task(
     ui_tx: &mpsc::Sender<ServerUiEvent>,
     task_id: TaskId,
 ) {
-    use taskrun_core::RunStatus;
-    use taskrun_proto::pb::run_server_message::Payload as ServerPayload;
-    use taskrun_proto::pb::{CancelRun, RunServerMessage};
+    log_to_ui(
+        ui_tx,
+        LogLevel::Info,
+        format!("Cancelling task {}", task_id),
+    )
+    .await;
 
-    // Collect runs to cancel
-    let runs_to_cancel: Vec<_>;
-    {
-        let mut tasks = state.tasks.write().await;
-        let task = match tasks.get_mut(&task_id) {
-            Some(task) => task,
-            None => {
+    // Cancellation waits for worker acknowledgement, so don't block the command loop
+    let state = state.clone();
+    let scheduler = Scheduler::new(state.clone());
+    let ui_tx = ui_tx.clone();
+    tokio::spawn(async move {
+        match scheduler
+            .cancel_task(&task_id, "Task cancelled by user")
+            .await
+        {
+            Ok(task) => {
+                state
+                    .audit(
+                        TUI_ACTOR,
+                        AuditAction::TaskCancelled,
+                        task_id.as_str(),
+                        &task,
+                    )
+                    .await;
                 log_to_ui(
-                    ui_tx,
-                    LogLevel::Error,
-                    format!("Task not found: {}", task_id),
+                    &ui_tx,
+                    LogLevel::Info,
+                    format!("Task {} finished as {:?}", task_id, task.status),
                 )
                 .await;
-                return;
             }
-        };
+            Err(e) => {
+                log_to_ui(&ui_tx, LogLevel::Warn, format!("Cancel failed: {}", e)).await;
+            }
+        }
+    });
+}
 
-        if task.is_terminal() {
+async fn handle_preview_bulk(
+    state: &Arc<AppState>,
+    ui_tx: &mpsc::Sender<ServerUiEvent>,
+    op: BulkOperation,
+    filter: TaskFilter,
+) {
+    let task_count = bulk::affected_tasks(state, op, &filter).await.len();
+    if task_count == 0 {
+        log_to_ui(
+            ui_tx,
+            LogLevel::Info,
+            format!("No tasks to {}", op.as_str()),
+        )
+        .await;
+        return;
+    }
+    let _ = ui_tx
+        .send(ServerUiEvent::BulkPreview {
+            op,
+            filter,
+            task_count,
+        })
+        .await;
+}
+
+async fn handle_validate_task(
+    state: &Arc<AppState>,
+    ui_tx: &mpsc::Sender<ServerUiEvent>,
+    agent_name: String,
+    input_json: String,
+) {
+    // Same namespace, creator and lane as handle_create_task
+    let report = validation::validate(
+        state,
+        &validation::TaskSpec {
+            agent_name: &agent_name,
+            input_json: &input_json,
+            namespace: DEFAULT_NAMESPACE,
+            created_by: TUI_ACTOR,
+            priority: TaskPriority::Batch,
+        },
+    )
+    .await;
+    let messages = report
+        .findings
+        .into_iter()
+        .map(|finding| ValidationMessage {
+            error: finding.severity == validation::Severity::Error,
+            text: finding.message,
+        })
+        .collect();
+    let _ = ui_tx
+        .send(ServerUiEvent::TaskValidated {
+            agent_name,
+            input_json,
+            messages,
+        })
+        .await;
+}
+
+async fn handle_load_run_output(
+    state: &Arc<AppState>,
+    ui_tx: &mpsc::Sender<ServerUiEvent>,
+    run_id: RunId,
+) {
+    let chunks = match state.outputs.chunks_from(&run_id, 0).await {
+        Ok(chunks) => chunks,
+        Err(e) => {
             log_to_ui(
                 ui_tx,
                 LogLevel::Warn,
-                format!("Task {} is already terminal: {:?}", task_id, task.status),
+                format!("Failed to load output of run {}: {}", run_id, e),
             )
             .await;
             return;
         }
-
-        log_to_ui(
-            ui_tx,
-            LogLevel::Info,
-            format!("Cancelling task {}", task_id),
-        )
+    };
+    let next_seq = chunks.last().map_or(0, |chunk| chunk.seq + 1);
+    let content = chunks.into_iter().map(|chunk| chunk.content).collect();
+    let _ = ui_tx
+        .send(ServerUiEvent::RunOutputBacklog {
+            run_id,
+            content,
+            next_seq,
+        })
         .await;
+}
 
-        // Collect active runs
-        runs_to_cancel = task
-            .runs
-            .iter()
-            .filter(|r| r.status.is_active())
-            .map(|r| (r.worker_id.clone(), r.run_id.clone()))
-            .collect();
-
-        // Mark task and runs as cancelled
-        task.status = TaskStatus::Cancelled;
-        for run in &mut task.runs {
-            if run.status.is_active() {
-                run.status = RunStatus::Cancelled;
-                run.finished_at = Some(chrono::Utc::now());
-            }
+async fn handle_search(state: &Arc<AppState>, ui_tx: &mpsc::Sender<ServerUiEvent>, query: String) {
+    match search::search(state, &query, None, MAX_SEARCH_LIMIT).await {
+        Ok(hits) => {
+            let task_ids = hits.into_iter().map(|hit| hit.task.id).collect();
+            let _ = ui_tx
+                .send(ServerUiEvent::SearchResults { query, task_ids })
+                .await;
+        }
+        Err(e) => {
+            log_to_ui(ui_tx, LogLevel::Warn, format!("Search failed: {}", e)).await;
         }
     }
+}
 
-    // Notify UI
-    state.notify_ui(UiNotification::TaskStatusChanged {
-        task_id: task_id.clone(),
-        status: TaskStatus::Cancelled,
+async fn handle_run_bulk(
+    state: &Arc<AppState>,
+    ui_tx: &mpsc::Sender<ServerUiEvent>,
+    op: BulkOperation,
+    filter: TaskFilter,
+) {
+    // Cancellation waits for worker acknowledgement, so don't block the command loop
+    let state = state.clone();
+    let ui_tx = ui_tx.clone();
+    tokio::spawn(async move {
+        let outcome = bulk::run(&state, op, &filter, TUI_ACTOR, false).await;
+        let (level, message) = if outcome.failed_task_ids.is_empty() {
+            (
+                LogLevel::Info,
+                format!("Bulk {}: {} task(s)", op.as_str(), outcome.task_ids.len()),
+            )
+        } else {
+            (
+                LogLevel::Warn,
+                format!(
+                    "Bulk {}: {} task(s), {} failed",
+                    op.as_str(),
+                    outcome.task_ids.len(),
+                    outcome.failed_task_ids.len()
+                ),
+            )
+        };
+        log_to_ui(&ui_tx, level, message).await;
     });
+}
 
-    // Send CancelRun to workers
-    if !runs_to_cancel.is_empty() {
-        let workers = state.workers.read().await;
-        for (worker_id, run_id) in &runs_to_cancel {
-            if let Some(worker) = workers.get(worker_id) {
-                let cancel_msg = RunServerMessage {
-                    payload: Some(ServerPayload::CancelRun(CancelRun {
-                        run_id: run_id.to_string(),
-                        reason: "Task cancelled by user".to_string(),
-                    })),
-                };
+async fn handle_set_schedule_paused(
+    schedules: &ScheduleManager,
+    ui_tx: &mpsc::Sender<ServerUiEvent>,
+    schedule_id: ScheduleId,
+    paused: bool,
+) {
+    match schedules.set_paused(&schedule_id, paused).await {
+        Ok(schedule) => {
+            let audit_action = if paused {
+                AuditAction::SchedulePaused
+            } else {
+                AuditAction::ScheduleResumed
+            };
+            schedules
+                .state()
+                .audit(TUI_ACTOR, audit_action, schedule.id.as_str(), &schedule)
+                .await;
+            let action = if paused { "Paused" } else { "Resumed" };
+            log_to_ui(
+                ui_tx,
+                LogLevel::Info,
+                format!("{} schedule {}", action, schedule_id),
+            )
+            .await;
+        }
+        Err(e) => {
+            log_to_ui(
+                ui_tx,
+                LogLevel::Error,
+                format!("Failed to update schedule: {}", e),
+            )
+            .await;
+        }
+    }
+}
 
-                if let Err(e) = worker.tx.send(cancel_msg).await {
-                    log_to_ui(
-                        ui_tx,
-                        LogLevel::Warn,
-                        format!("Failed to send cancel to worker {}: {}", worker_id, e),
-                    )
-                    .await;
-                }
-            }
+async fn handle_decide_approval(
+    state: &Arc<AppState>,
+    ui_tx: &mpsc::Sender<ServerUiEvent>,
+    approval_id: String,
+    allowed: bool,
+    always_allow: bool,
+) {
+    match approvals::decide(
+        state,
+        &approval_id,
+        None,
+        allowed,
+        always_allow,
+        "",
+        TUI_ACTOR,
+    )
+    .await
+    {
+        Ok(approval) => {
+            let verb = match (allowed, always_allow) {
+                (true, true) => "Always allowed",
+                (true, false) => "Approved",
+                (false, _) => "Denied",
+            };
+            log_to_ui(
+                ui_tx,
+                LogLevel::Info,
+                format!(
+                    "{} {} for run {}",
+                    verb, approval.tool_name, approval.run_id
+                ),
+            )
+            .await;
+        }
+        Err(e) => {
+            log_to_ui(ui_tx, LogLevel::Error, format!("Approval failed: {}", e)).await;
         }
     }
 }
@@ -577,19 +1518,29 @@ async fn handle_disconnect_worker(
     ui_tx: &mpsc::Sender<ServerUiEvent>,
     worker_id: taskrun_core::WorkerId,
 ) {
-    let mut workers = state.workers.write().await;
-    if workers.remove(&worker_id).is_some() {
+    let outcome =
+        disconnect::disconnect_worker(state, &worker_id, "Disconnected from the server TUI", true)
+            .await;
+    if let Some(outcome) = outcome {
+        state
+            .audit(
+                TUI_ACTOR,
+                AuditAction::WorkerDisconnected,
+                worker_id.as_str(),
+                &outcome,
+            )
+            .await;
         log_to_ui(
             ui_tx,
             LogLevel::Info,
-            format!("Disconnected worker: {}", worker_id),
+            format!(
+                "Disconnected worker: {} ({} runs failed, {} tasks rescheduled)",
+                worker_id,
+                outcome.failed_run_ids.len(),
+                outcome.rescheduled_task_ids.len()
+            ),
         )
         .await;
-
-        // Notify UI
-        state.notify_ui(UiNotification::WorkerDisconnected {
-            worker_id: worker_id.clone(),
-        });
     } else {
         log_to_ui(
             ui_tx,
@@ -600,6 +1551,59 @@ async fn handle_disconnect_worker(
     }
 }
 
+async fn handle_set_worker_draining(
+    state: &Arc<AppState>,
+    ui_tx: &mpsc::Sender<ServerUiEvent>,
+    worker_id: taskrun_core::WorkerId,
+    draining: bool,
+) {
+    let Some(progress) = drain::set_draining(state, &worker_id, draining).await else {
+        log_to_ui(
+            ui_tx,
+            LogLevel::Warn,
+            format!("Worker not found: {}", worker_id),
+        )
+        .await;
+        return;
+    };
+    let action = if draining {
+        AuditAction::WorkerDrained
+    } else {
+        AuditAction::WorkerUndrained
+    };
+    state
+        .audit(TUI_ACTOR, action, worker_id.as_str(), &progress)
+        .await;
+
+    let message = if !draining {
+        format!("Undrained worker {}", worker_id)
+    } else if progress.drained() {
+        format!("Worker {} drained, safe to stop", worker_id)
+    } else {
+        format!(
+            "Draining worker {}: {} run(s) left",
+            worker_id,
+            progress.active_run_ids.len()
+        )
+    };
+    log_to_ui(ui_tx, LogLevel::Info, message).await;
+}
+
+async fn handle_update_toggles(
+    state: &Arc<AppState>,
+    ui_tx: &mpsc::Sender<ServerUiEvent>,
+    update: ToggleUpdate,
+) {
+    if let Err(e) = toggles::update(state, TUI_ACTOR, update).await {
+        log_to_ui(
+            ui_tx,
+            LogLevel::Error,
+            format!("Failed to update runtime toggles: {}", e),
+        )
+        .await;
+    }
+}
+
 async fn handle_send_chat_message(
     state: &Arc<AppState>,
     ui_tx: &mpsc::Sender<ServerUiEvent>,
@@ -0,0 +1,292 @@
+//! API keys for the HTTP API.
+//!
+//! Keys are handed out once in plaintext and only their SHA-256 hash is kept,
+//! like bootstrap tokens. The `ApiKeyManager` owns the key set and, when
+//! configured with a file, rewrites it on every change.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, Utc};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio::sync::{Mutex, RwLock};
+use tracing::info;
+
+use taskrun_core::validate_namespace;
+
+use crate::control_plane::crypto::hash_token;
+use crate::control_plane::rbac::{NamespaceError, Role};
+use crate::control_plane::state::{resolve_prefix, IdLookupError};
+
+/// Prefix of every plaintext API key, so leaked keys are easy to recognize.
+pub const API_KEY_PREFIX: &str = "trk_";
+
+/// API key errors.
+#[derive(Debug, Error)]
+pub enum ApiKeyError {
+    #[error("API key name is required")]
+    MissingName,
+
+    #[error(transparent)]
+    Namespace(#[from] NamespaceError),
+
+    #[error(transparent)]
+    Lookup(#[from] IdLookupError),
+
+    #[error("I/O error on API key file '{path}': {source}")]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+
+    #[error("Failed to (de)serialize API keys: {0}")]
+    Serialization(#[from] serde_json::Error),
+}
+
+/// A stored API key. The plaintext key is never stored.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ApiKey {
+    /// Short identifier used to list and revoke the key.
+    pub id: String,
+
+    /// Human-readable name (who or what uses the key).
+    pub name: String,
+
+    /// First characters of the plaintext key, for recognizing it.
+    pub prefix: String,
+
+    /// SHA-256 hash of the plaintext key (hex encoded).
+    pub key_hash: String,
+
+    /// What the key may do.
+    pub role: Role,
+
+    /// Namespace the key is bound to (None = all namespaces).
+    #[serde(default)]
+    pub namespace: Option<String>,
+
+    /// When the key was created.
+    pub created_at: DateTime<Utc>,
+}
+
+/// Owns all API keys and keeps the key file in sync.
+pub struct ApiKeyManager {
+    /// JSON file the keys are persisted to (None = in memory only).
+    path: Option<PathBuf>,
+    keys: RwLock<Vec<ApiKey>>,
+    /// Serializes writers so the temp file is never shared.
+    write_lock: Mutex<()>,
+}
+
+impl ApiKeyManager {
+    /// Create a manager, loading existing keys from `path` if given.
+    pub async fn open(path: Option<PathBuf>) -> Result<Arc<Self>, ApiKeyError> {
+        let keys = match &path {
+            Some(path) => match tokio::fs::read_to_string(path).await {
+                Ok(contents) if contents.trim().is_empty() => Vec::new(),
+                Ok(contents) => serde_json::from_str(&contents)?,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+                Err(source) => {
+                    return Err(ApiKeyError::Io {
+                        path: path.clone(),
+                        source,
+                    })
+                }
+            },
+            None => Vec::new(),
+        };
+
+        Ok(Arc::new(Self {
+            path,
+            keys: RwLock::new(keys),
+            write_lock: Mutex::new(()),
+        }))
+    }
+
+    /// Create a key, bound to `namespace` if given. Returns the stored key
+    /// and the plaintext, which is not recoverable afterwards.
+    pub async fn create(
+        &self,
+        name: &str,
+        role: Role,
+        namespace: Option<String>,
+    ) -> Result<(ApiKey, String), ApiKeyError> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(ApiKeyError::MissingName);
+        }
+        if let Some(namespace) = &namespace {
+            validate_namespace(namespace).map_err(|e| NamespaceError::Invalid(e.to_string()))?;
+        }
+
+        let mut secret = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut secret);
+        let plaintext = format!("{}{}", API_KEY_PREFIX, URL_SAFE_NO_PAD.encode(secret));
+
+        let mut id = [0u8; 4];
+        rand::thread_rng().fill_bytes(&mut id);
+
+        let key = ApiKey {
+            id: hex::encode(id),
+            name: name.to_string(),
+            prefix: plaintext[..API_KEY_PREFIX.len() + 6].to_string(),
+            key_hash: hash_token(&plaintext),
+            role,
+            namespace,
+            created_at: Utc::now(),
+        };
+
+        // Only keep the key if it was persisted
+        let mut keys = self.keys.write().await;
+        keys.push(key.clone());
+        if let Err(e) = self.save(&keys).await {
+            keys.pop();
+            return Err(e);
+        }
+
+        info!(key_id = %key.id, name = %key.name, role = %role, "API key created");
+        Ok((key, plaintext))
+    }
+
+    /// Keys bound to `namespace` (None = all keys), oldest first.
+    pub async fn list(&self, namespace: Option<&str>) -> Vec<ApiKey> {
+        self.keys
+            .read()
+            .await
+            .iter()
+            .filter(|k| namespace.map_or(true, |ns| k.namespace.as_deref() == Some(ns)))
+            .cloned()
+            .collect()
+    }
+
+    /// Revoke the key whose ID is `id` or starts with it, among the keys
+    /// bound to `namespace` (None = all keys).
+    pub async fn revoke(&self, id: &str, namespace: Option<&str>) -> Result<ApiKey, ApiKeyError> {
+        let mut keys = self.keys.write().await;
+        let candidates = keys
+            .iter()
+            .filter(|k| namespace.map_or(true, |ns| k.namespace.as_deref() == Some(ns)))
+            .map(|k| k.id.as_str());
+        let id = resolve_prefix("API key", id, candidates)?;
+        let index = keys.iter().position(|k| k.id == id).expect("resolved ID");
+        let key = keys.remove(index);
+        if let Err(e) = self.save(&keys).await {
+            keys.insert(index, key);
+            return Err(e);
+        }
+
+        info!(key_id = %key.id, name = %key.name, "API key revoked");
+        Ok(key)
+    }
+
+    /// Look up the key matching a plaintext bearer token.
+    pub async fn authenticate(&self, token: &str) -> Option<ApiKey> {
+        if !token.starts_with(API_KEY_PREFIX) {
+            return None;
+        }
+        let hash = hash_token(token);
+        self.keys
+            .read()
+            .await
+            .iter()
+            .find(|k| k.key_hash == hash)
+            .cloned()
+    }
+
+    async fn save(&self, keys: &[ApiKey]) -> Result<(), ApiKeyError> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let io_err = |source| ApiKeyError::Io {
+            path: path.clone(),
+            source,
+        };
+
+        let contents = serde_json::to_string_pretty(keys)?;
+        let _guard = self.write_lock.lock().await;
+
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            tokio::fs::create_dir_all(parent).await.map_err(io_err)?;
+        }
+
+        let tmp_path = path.with_extension("json.tmp");
+        tokio::fs::write(&tmp_path, contents)
+            .await
+            .map_err(io_err)?;
+        tokio::fs::rename(&tmp_path, path).await.map_err(io_err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_create_authenticate_revoke() {
+        let manager = ApiKeyManager::open(None).await.unwrap();
+        let (key, plaintext) = manager
+            .create("ci", Role::Operator, Some("team-a".to_string()))
+            .await
+            .unwrap();
+
+        assert!(plaintext.starts_with(API_KEY_PREFIX));
+        assert!(plaintext.starts_with(&key.prefix));
+        assert_ne!(key.key_hash, plaintext);
+        assert_eq!(manager.authenticate(&plaintext).await, Some(key.clone()));
+        assert_eq!(manager.authenticate("trk_wrong").await, None);
+
+        assert!(manager.list(Some("team-b")).await.is_empty());
+        assert!(manager.revoke(&key.id, Some("team-b")).await.is_err());
+        manager.revoke(&key.id[..4], Some("team-a")).await.unwrap();
+        assert_eq!(manager.authenticate(&plaintext).await, None);
+    }
+
+    #[tokio::test]
+    async fn test_keys_persist_hashed() {
+        let dir = std::env::temp_dir().join(format!("taskrun-api-keys-{}", std::process::id()));
+        let path = dir.join("keys.json");
+
+        let manager = ApiKeyManager::open(Some(path.clone())).await.unwrap();
+        let (_, plaintext) = manager.create("admin", Role::Admin, None).await.unwrap();
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        assert!(!contents.contains(&plaintext));
+
+        let reopened = ApiKeyManager::open(Some(path)).await.unwrap();
+        assert_eq!(
+            reopened.authenticate(&plaintext).await.unwrap().role,
+            Role::Admin
+        );
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_failed_save_leaves_keys_unchanged() {
+        let dir =
+            std::env::temp_dir().join(format!("taskrun-api-keys-fail-{}", std::process::id()));
+        let path = dir.join("keys.json");
+
+        let manager = ApiKeyManager::open(Some(path.clone())).await.unwrap();
+        let (key, plaintext) = manager.create("ci", Role::Operator, None).await.unwrap();
+
+        // A directory in place of the temp file makes every save fail
+        std::fs::create_dir_all(path.with_extension("json.tmp")).unwrap();
+        assert!(matches!(
+            manager.create("ops", Role::Admin, None).await,
+            Err(ApiKeyError::Io { .. })
+        ));
+        assert_eq!(manager.list(None).await, vec![key.clone()]);
+        assert!(matches!(
+            manager.revoke(&key.id, None).await,
+            Err(ApiKeyError::Io { .. })
+        ));
+        assert_eq!(manager.authenticate(&plaintext).await, Some(key));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
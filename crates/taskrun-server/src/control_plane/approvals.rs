@@ -0,0 +1,484 @@
+//! Tool-use approvals escalated by workers.
+//!
+//! A worker whose policy asks about a tool sends a `ToolApprovalRequest` and
+//! holds the run until it hears back. The request waits here until an
+//! operator decides it over gRPC, HTTP, or the TUI, and the decision goes to
+//! the worker on its stream. Once the request's timeout passes the worker
+//! applies its default on its own, so expired approvals can no longer be
+//! decided and are dropped.
+//!
+//! Approving with "always allow" also approves later uses of the same tool
+//! by the same agent in the same namespace, until the control plane restarts.
+
+use std::collections::{HashMap, HashSet};
+
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use thiserror::Error;
+use tokio::sync::RwLock;
+use tracing::{info, warn};
+
+use taskrun_core::{RunId, TaskId, WorkerId};
+use taskrun_proto::pb::run_server_message::Payload as ServerPayload;
+use taskrun_proto::pb::{RunServerMessage, ToolApprovalDecision, ToolApprovalRequest};
+
+use crate::control_plane::audit::AuditAction;
+use crate::control_plane::state::{resolve_prefix, AppState, IdLookupError, UiNotification};
+
+/// Approval errors.
+#[derive(Debug, Error)]
+pub enum ApprovalError {
+    #[error(transparent)]
+    Lookup(#[from] IdLookupError),
+
+    #[error("Approval {0} expired; the worker applied its default")]
+    Expired(String),
+
+    #[error("Worker {0} is no longer connected")]
+    WorkerGone(WorkerId),
+}
+
+/// Actor recorded for tool uses approved by an "always allow" rule.
+pub const ALWAYS_ALLOW_ACTOR: &str = "always-allow";
+
+/// A tool use waiting for an operator.
+#[derive(Debug, Clone, Serialize)]
+pub struct Approval {
+    pub id: String,
+    pub run_id: RunId,
+    pub task_id: TaskId,
+    /// Namespace of the task; only callers that can access it decide.
+    pub namespace: String,
+    /// Agent of the task.
+    pub agent_name: String,
+    pub worker_id: WorkerId,
+    pub tool_name: String,
+    /// Tool input as JSON string.
+    pub input_json: String,
+    pub requested_at: DateTime<Utc>,
+    /// When the worker stops waiting and applies `default_allow`.
+    pub expires_at: DateTime<Utc>,
+    pub default_allow: bool,
+}
+
+impl Approval {
+    fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        now >= self.expires_at
+    }
+
+    fn rule(&self) -> AllowRule {
+        AllowRule {
+            namespace: self.namespace.clone(),
+            agent_name: self.agent_name.clone(),
+            tool_name: self.tool_name.clone(),
+        }
+    }
+}
+
+/// A tool that is always allowed for an agent in a namespace.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct AllowRule {
+    namespace: String,
+    agent_name: String,
+    tool_name: String,
+}
+
+/// What gets audited for a decision.
+#[derive(Serialize)]
+struct DecisionRecord<'a> {
+    approval: &'a Approval,
+    allowed: bool,
+    always_allow: bool,
+    reason: &'a str,
+}
+
+/// Pending approvals, keyed by approval ID.
+#[derive(Debug, Default)]
+pub struct ApprovalQueue {
+    pending: RwLock<HashMap<String, Approval>>,
+    always_allowed: RwLock<HashSet<AllowRule>>,
+}
+
+impl ApprovalQueue {
+    /// Pending approvals in `namespace` (None = all), oldest first. Drops
+    /// the ones that expired.
+    pub async fn list(&self, namespace: Option<&str>) -> Vec<Approval> {
+        let now = Utc::now();
+        let mut pending = self.pending.write().await;
+        pending.retain(|_, approval| !approval.is_expired(now));
+        let mut approvals: Vec<Approval> = pending
+            .values()
+            .filter(|a| namespace.map_or(true, |ns| a.namespace == ns))
+            .cloned()
+            .collect();
+        approvals.sort_by_key(|a| a.requested_at);
+        approvals
+    }
+
+    /// Forget the approvals of a run (e.g. once it finished). Returns their IDs.
+    pub async fn remove_run(&self, run_id: &RunId) -> Vec<String> {
+        self.remove_where(|a| &a.run_id == run_id).await
+    }
+
+    /// Forget the approvals of a worker that disconnected. Returns their IDs.
+    pub async fn remove_worker(&self, worker_id: &WorkerId) -> Vec<String> {
+        self.remove_where(|a| &a.worker_id == worker_id).await
+    }
+
+    async fn remove_where(&self, matches: impl Fn(&Approval) -> bool) -> Vec<String> {
+        let mut pending = self.pending.write().await;
+        let ids: Vec<String> = pending
+            .values()
+            .filter(|a| matches(a))
+            .map(|a| a.id.clone())
+            .collect();
+        for id in &ids {
+            pending.remove(id);
+        }
+        ids
+    }
+
+    /// Remove and return the approval a full or unique-prefix ID names,
+    /// among those in `namespace` (None = all).
+    async fn take(&self, prefix: &str, namespace: Option<&str>) -> Result<Approval, ApprovalError> {
+        let mut pending = self.pending.write().await;
+        let candidates = pending
+            .values()
+            .filter(|a| namespace.map_or(true, |ns| a.namespace == ns))
+            .map(|a| a.id.as_str());
+        let id = resolve_prefix("Approval", prefix, candidates)?;
+        let approval = pending.remove(&id).expect("resolved ID is pending");
+        if approval.is_expired(Utc::now()) {
+            return Err(ApprovalError::Expired(approval.id));
+        }
+        Ok(approval)
+    }
+}
+
+/// Queue a worker's approval request and show it to operators.
+pub async fn request(state: &AppState, worker_id: &WorkerId, request: ToolApprovalRequest) {
+    let run_id = RunId::new(&request.run_id);
+    let task = {
+        let tasks = state.tasks.read().await;
+        tasks
+            .values()
+            .find(|task| task.runs.iter().any(|r| r.run_id == run_id))
+            .map(|task| {
+                (
+                    task.id.clone(),
+                    task.namespace.clone(),
+                    task.agent_name.clone(),
+                )
+            })
+    };
+    let Some((task_id, namespace, agent_name)) = task else {
+        // The worker applies its default when nobody answers
+        warn!(run_id = %run_id, approval_id = %request.approval_id, "Approval request for unknown run");
+        return;
+    };
+
+    let requested_at = Utc::now();
+    let approval = Approval {
+        id: request.approval_id,
+        run_id,
+        task_id,
+        namespace,
+        agent_name,
+        worker_id: worker_id.clone(),
+        tool_name: request.tool_name,
+        input_json: request.input_json,
+        requested_at,
+        expires_at: requested_at + Duration::milliseconds(request.timeout_ms),
+        default_allow: request.default_allow,
+    };
+    info!(
+        approval_id = %approval.id,
+        run_id = %approval.run_id,
+        tool = %approval.tool_name,
+        "Tool approval requested"
+    );
+
+    let always_allowed = state
+        .approvals
+        .always_allowed
+        .read()
+        .await
+        .contains(&approval.rule());
+    if always_allowed {
+        if let Err(e) = send_decision(state, &approval, true, false, "", ALWAYS_ALLOW_ACTOR).await {
+            warn!(approval_id = %approval.id, error = %e, "Failed to send approval decision");
+        }
+        return;
+    }
+
+    state
+        .approvals
+        .pending
+        .write()
+        .await
+        .insert(approval.id.clone(), approval.clone());
+    state.notify_ui(UiNotification::ApprovalRequested { approval });
+}
+
+/// Approve or deny a pending tool use in `namespace` (None = any) and send
+/// the decision to its worker. With `always_allow`, an approval also covers
+/// later uses of the tool by the agent, including ones already waiting.
+pub async fn decide(
+    state: &AppState,
+    prefix: &str,
+    namespace: Option<&str>,
+    allowed: bool,
+    always_allow: bool,
+    reason: &str,
+    actor: &str,
+) -> Result<Approval, ApprovalError> {
+    let approval = state.approvals.take(prefix, namespace).await;
+    if let Err(ApprovalError::Expired(ref id)) = approval {
+        state.notify_ui(UiNotification::ApprovalResolved {
+            approval_id: id.clone(),
+        });
+    }
+    let approval = approval?;
+
+    // Resolved either way: the worker can no longer be told otherwise
+    state.notify_ui(UiNotification::ApprovalResolved {
+        approval_id: approval.id.clone(),
+    });
+
+    let always_allow = allowed && always_allow;
+    send_decision(state, &approval, allowed, always_allow, reason, actor).await?;
+    if always_allow {
+        allow_always(state, approval.rule()).await;
+    }
+    Ok(approval)
+}
+
+/// Remember `rule` and approve the pending tool uses it covers.
+async fn allow_always(state: &AppState, rule: AllowRule) {
+    info!(
+        namespace = %rule.namespace,
+        agent = %rule.agent_name,
+        tool = %rule.tool_name,
+        "Tool always allowed"
+    );
+    let covered = {
+        let mut pending = state.approvals.pending.write().await;
+        let ids: Vec<String> = pending
+            .values()
+            .filter(|a| a.rule() == rule)
+            .map(|a| a.id.clone())
+            .collect();
+        ids.iter()
+            .filter_map(|id| pending.remove(id))
+            .collect::<Vec<_>>()
+    };
+    state.approvals.always_allowed.write().await.insert(rule);
+
+    for approval in covered {
+        state.notify_ui(UiNotification::ApprovalResolved {
+            approval_id: approval.id.clone(),
+        });
+        if let Err(e) = send_decision(state, &approval, true, false, "", ALWAYS_ALLOW_ACTOR).await {
+            warn!(approval_id = %approval.id, error = %e, "Failed to send approval decision");
+        }
+    }
+}
+
+/// Send a decision to the approval's worker and audit it.
+async fn send_decision(
+    state: &AppState,
+    approval: &Approval,
+    allowed: bool,
+    always_allow: bool,
+    reason: &str,
+    actor: &str,
+) -> Result<(), ApprovalError> {
+    let tx = {
+        let workers = state.workers.read().await;
+        workers.get(&approval.worker_id).map(|w| w.tx.clone())
+    };
+    let msg = RunServerMessage {
+        payload: Some(ServerPayload::ApprovalDecision(ToolApprovalDecision {
+            approval_id: approval.id.clone(),
+            run_id: approval.run_id.as_str().to_string(),
+            allowed,
+            reason: reason.to_string(),
+            decided_by: actor.to_string(),
+        })),
+    };
+    match tx {
+        Some(tx) if tx.send(msg).await.is_ok() => {}
+        _ => return Err(ApprovalError::WorkerGone(approval.worker_id.clone())),
+    }
+
+    info!(
+        approval_id = %approval.id,
+        run_id = %approval.run_id,
+        allowed,
+        decided_by = %actor,
+        "Tool approval decided"
+    );
+    let action = if allowed {
+        AuditAction::ToolApproved
+    } else {
+        AuditAction::ToolDenied
+    };
+    state
+        .audit(
+            actor,
+            action,
+            &approval.id,
+            &DecisionRecord {
+                approval,
+                allowed,
+                always_allow,
+                reason,
+            },
+        )
+        .await;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use taskrun_core::{RunSummary, Task, WorkerInfo, WorkerStatus};
+    use tokio::sync::mpsc;
+
+    use crate::control_plane::state::ConnectedWorker;
+
+    #[tokio::test]
+    async fn test_decision_is_sent_to_worker() {
+        let state = AppState::new();
+        let worker_id = WorkerId::new("worker-1");
+        let (tx, mut rx) = mpsc::channel(8);
+        state.workers.write().await.insert(
+            worker_id.clone(),
+            ConnectedWorker {
+                info: WorkerInfo::new(worker_id.clone(), "host"),
+                status: WorkerStatus::Busy,
+                active_runs: 1,
+                max_concurrent_runs: 10,
+                interactive_slots: 0,
+                last_heartbeat: Utc::now(),
+                draining_since: None,
+                tx,
+                shutdown: tokio_util::sync::CancellationToken::new(),
+            },
+        );
+        let mut task = Task::new("general", "{}", "test");
+        let run = RunSummary::new(worker_id.clone());
+        let run_id = run.run_id.clone();
+        task.add_run(run);
+        state.tasks.write().await.insert(task.id.clone(), task);
+
+        let ask = |approval_id: &str, timeout_ms| ToolApprovalRequest {
+            approval_id: approval_id.to_string(),
+            run_id: run_id.as_str().to_string(),
+            tool_name: "Bash".to_string(),
+            input_json: r#"{"command":"rm -rf build"}"#.to_string(),
+            timeout_ms,
+            default_allow: false,
+        };
+        request(&state, &worker_id, ask("apr-1", 60_000)).await;
+        request(&state, &worker_id, ask("apr-2", 0)).await;
+
+        // The second one expired as soon as it arrived
+        let pending = state.approvals.list(None).await;
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].tool_name, "Bash");
+        assert!(state.approvals.list(Some("team-a")).await.is_empty());
+
+        let approval = decide(
+            &state,
+            "apr",
+            Some("default"),
+            false,
+            false,
+            "not on prod",
+            "ops",
+        )
+        .await
+        .unwrap();
+        assert_eq!(approval.id, "apr-1");
+        let Some(ServerPayload::ApprovalDecision(decision)) = rx.recv().await.unwrap().payload
+        else {
+            panic!("expected an approval decision");
+        };
+        assert!(!decision.allowed);
+        assert_eq!(decision.reason, "not on prod");
+        assert_eq!(decision.decided_by, "ops");
+
+        assert!(state.approvals.list(None).await.is_empty());
+        assert!(matches!(
+            decide(&state, "apr-1", None, true, false, "", "ops").await,
+            Err(ApprovalError::Lookup(IdLookupError::NotFound { .. }))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_always_allow_covers_later_uses() {
+        let state = AppState::new();
+        let worker_id = WorkerId::new("worker-1");
+        let (tx, mut rx) = mpsc::channel(8);
+        state.workers.write().await.insert(
+            worker_id.clone(),
+            ConnectedWorker {
+                info: WorkerInfo::new(worker_id.clone(), "host"),
+                status: WorkerStatus::Busy,
+                active_runs: 1,
+                max_concurrent_runs: 10,
+                interactive_slots: 0,
+                last_heartbeat: Utc::now(),
+                draining_since: None,
+                tx,
+                shutdown: tokio_util::sync::CancellationToken::new(),
+            },
+        );
+        let mut task = Task::new("general", "{}", "test");
+        let run = RunSummary::new(worker_id.clone());
+        let run_id = run.run_id.clone();
+        task.add_run(run);
+        state.tasks.write().await.insert(task.id.clone(), task);
+
+        let ask = |approval_id: &str, tool_name: &str| ToolApprovalRequest {
+            approval_id: approval_id.to_string(),
+            run_id: run_id.as_str().to_string(),
+            tool_name: tool_name.to_string(),
+            input_json: "{}".to_string(),
+            timeout_ms: 60_000,
+            default_allow: false,
+        };
+        request(&state, &worker_id, ask("apr-1", "Bash")).await;
+        request(&state, &worker_id, ask("apr-2", "Bash")).await;
+        request(&state, &worker_id, ask("apr-3", "Write")).await;
+
+        decide(&state, "apr-1", None, true, true, "", "ops")
+            .await
+            .unwrap();
+        // The other pending Bash use is approved by the rule
+        let mut decided = Vec::new();
+        for _ in 0..2 {
+            let Some(ServerPayload::ApprovalDecision(decision)) = rx.recv().await.unwrap().payload
+            else {
+                panic!("expected an approval decision");
+            };
+            assert!(decision.allowed);
+            decided.push(decision.approval_id);
+        }
+        decided.sort();
+        assert_eq!(decided, vec!["apr-1", "apr-2"]);
+
+        // Later Bash uses never wait; other tools still do
+        request(&state, &worker_id, ask("apr-4", "Bash")).await;
+        let Some(ServerPayload::ApprovalDecision(decision)) = rx.recv().await.unwrap().payload
+        else {
+            panic!("expected an approval decision");
+        };
+        assert_eq!(decision.approval_id, "apr-4");
+        assert_eq!(decision.decided_by, ALWAYS_ALLOW_ACTOR);
+        let pending = state.approvals.list(None).await;
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].id, "apr-3");
+    }
+}
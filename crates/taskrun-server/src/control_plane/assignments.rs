@@ -0,0 +1,325 @@
+//! Assignment acknowledgement and redelivery.
+//!
+//! A `RunAssignment` can be lost on its way: the worker's stream may break
+//! after the assignment left the control plane but before the worker read
+//! it, leaving the run Assigned with nobody working on it. Workers that set
+//! `acks_assignments` in their `WorkerHello` answer each assignment with an
+//! `AssignmentAck`. The redelivery timer fails assignments left unacknowledged
+//! for `ack_timeout` as `worker_lost`, sends the worker a `CancelRun` in case
+//! the assignment arrives late, and puts the task back in Pending so another
+//! worker gets it. A worker that missed an ack gets no new assignments until
+//! it acknowledges one again or reconnects.
+//!
+//! Assignments to workers that don't acknowledge them (older workers) are not
+//! tracked.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use tracing::{info, warn};
+
+use taskrun_core::{FailureReason, RunId, RunStatus, TaskId, WorkerId};
+use taskrun_proto::pb::run_server_message::Payload as ServerPayload;
+use taskrun_proto::pb::{CancelRun, RunServerMessage};
+
+use crate::control_plane::scheduler::Scheduler;
+use crate::control_plane::state::AppState;
+
+/// An assignment waiting for its acknowledgement.
+#[derive(Debug, Clone)]
+pub struct PendingAssignment {
+    pub task_id: TaskId,
+    pub worker_id: WorkerId,
+    pub sent_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Default)]
+struct Tracked {
+    /// Connected workers that acknowledge assignments.
+    acking: HashSet<WorkerId>,
+    /// Unacknowledged assignments by run.
+    pending: HashMap<RunId, PendingAssignment>,
+    /// Workers that let an assignment time out.
+    unresponsive: HashSet<WorkerId>,
+}
+
+/// Assignments waiting for their worker's acknowledgement.
+#[derive(Debug, Default)]
+pub struct Assignments {
+    tracked: Mutex<Tracked>,
+}
+
+impl Assignments {
+    fn tracked(&self) -> std::sync::MutexGuard<'_, Tracked> {
+        self.tracked.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Record a worker's hello, and whether it acknowledges assignments.
+    pub fn worker_connected(&self, worker_id: &WorkerId, acks_assignments: bool) {
+        let mut tracked = self.tracked();
+        tracked.unresponsive.remove(worker_id);
+        if acks_assignments {
+            tracked.acking.insert(worker_id.clone());
+        } else {
+            tracked.acking.remove(worker_id);
+        }
+    }
+
+    /// Forget a disconnected worker. Its unacknowledged assignments still
+    /// time out.
+    pub fn worker_disconnected(&self, worker_id: &WorkerId) {
+        let mut tracked = self.tracked();
+        tracked.acking.remove(worker_id);
+        tracked.unresponsive.remove(worker_id);
+    }
+
+    /// Track an assignment about to be sent, if its worker acknowledges
+    /// assignments.
+    pub fn sent(&self, run_id: &RunId, task_id: &TaskId, worker_id: &WorkerId) {
+        let mut tracked = self.tracked();
+        if tracked.acking.contains(worker_id) {
+            tracked.pending.insert(
+                run_id.clone(),
+                PendingAssignment {
+                    task_id: task_id.clone(),
+                    worker_id: worker_id.clone(),
+                    sent_at: Utc::now(),
+                },
+            );
+        }
+    }
+
+    /// Record the acknowledgement of `run_id`'s assignment by `worker_id`.
+    /// Returns false if the assignment was not waiting for one (untracked,
+    /// or already redelivered).
+    pub fn acknowledged(&self, run_id: &RunId, worker_id: &WorkerId) -> bool {
+        let mut tracked = self.tracked();
+        tracked.unresponsive.remove(worker_id);
+        tracked.pending.remove(run_id).is_some()
+    }
+
+    /// Whether `worker_id` let an assignment time out since it last
+    /// acknowledged one.
+    pub fn is_unresponsive(&self, worker_id: &WorkerId) -> bool {
+        self.tracked().unresponsive.contains(worker_id)
+    }
+
+    /// Number of assignments waiting for their acknowledgement.
+    pub fn pending_count(&self) -> usize {
+        self.tracked().pending.len()
+    }
+
+    /// Remove the assignments sent before `deadline`, marking their workers
+    /// unresponsive.
+    fn take_overdue(&self, deadline: DateTime<Utc>) -> Vec<(RunId, PendingAssignment)> {
+        let mut tracked = self.tracked();
+        let mut overdue = Vec::new();
+        tracked.pending.retain(|run_id, assignment| {
+            if assignment.sent_at >= deadline {
+                return true;
+            }
+            overdue.push((run_id.clone(), assignment.clone()));
+            false
+        });
+        for (_, assignment) in &overdue {
+            tracked.unresponsive.insert(assignment.worker_id.clone());
+        }
+        overdue
+    }
+}
+
+/// Redelivery timer configuration.
+#[derive(Debug, Clone)]
+pub struct RedeliveryConfig {
+    /// How often to look for unacknowledged assignments.
+    pub interval: Duration,
+
+    /// How long a worker has to acknowledge an assignment.
+    pub ack_timeout: Duration,
+}
+
+impl Default for RedeliveryConfig {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(1),
+            ack_timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+/// Periodically reassigns assignments their worker did not acknowledge.
+pub struct RedeliveryTimer {
+    state: Arc<AppState>,
+    config: RedeliveryConfig,
+}
+
+impl RedeliveryTimer {
+    /// Create a new RedeliveryTimer.
+    pub fn new(state: Arc<AppState>, config: RedeliveryConfig) -> Self {
+        Self { state, config }
+    }
+
+    /// Run the timer loop forever.
+    pub async fn run(self) {
+        info!(
+            ack_timeout_secs = self.config.ack_timeout.as_secs(),
+            "Assignment redelivery timer started"
+        );
+
+        let mut interval = tokio::time::interval(self.config.interval);
+        loop {
+            interval.tick().await;
+            self.redeliver(Utc::now()).await;
+        }
+    }
+
+    /// Reassign the assignments unacknowledged for `ack_timeout` as of `now`.
+    /// Returns the number of assignments given up on.
+    pub async fn redeliver(&self, now: DateTime<Utc>) -> usize {
+        let ack_timeout =
+            chrono::Duration::from_std(self.config.ack_timeout).unwrap_or(chrono::Duration::MAX);
+        let overdue = self.state.assignments.take_overdue(
+            now.checked_sub_signed(ack_timeout)
+                .unwrap_or(DateTime::<Utc>::MIN_UTC),
+        );
+        let count = overdue.len();
+
+        for (run_id, assignment) in overdue {
+            let reason = format!(
+                "Worker {} did not acknowledge the assignment",
+                assignment.worker_id
+            );
+            // Finished or cancelled meanwhile
+            if !self
+                .state
+                .finalize_run(
+                    &run_id,
+                    RunStatus::Failed,
+                    Some(FailureReason::WorkerLost),
+                    Some(reason.clone()),
+                )
+                .await
+            {
+                continue;
+            }
+            warn!(
+                task_id = %assignment.task_id,
+                run_id = %run_id,
+                worker_id = %assignment.worker_id,
+                "Assignment not acknowledged, reassigning task"
+            );
+
+            // The assignment may still arrive; the worker must not run it
+            {
+                let workers = self.state.workers.read().await;
+                if let Some(worker) = workers.get(&assignment.worker_id) {
+                    let cancel = RunServerMessage {
+                        payload: Some(ServerPayload::CancelRun(CancelRun {
+                            run_id: run_id.to_string(),
+                            reason,
+                        })),
+                    };
+                    let _ = worker.tx.send(cancel).await;
+                }
+            }
+
+            match Scheduler::new(self.state.clone())
+                .retry_task(&assignment.task_id)
+                .await
+            {
+                Ok(run_id) => info!(
+                    task_id = %assignment.task_id,
+                    run_id = ?run_id.map(|id| id.to_string()),
+                    "Reassigned task after an unacknowledged assignment"
+                ),
+                Err(e) => {
+                    warn!(task_id = %assignment.task_id, error = %e, "Failed to reassign task")
+                }
+            }
+        }
+
+        count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use taskrun_core::{AgentSpec, Task, TaskStatus, WorkerInfo, WorkerStatus};
+    use tokio::sync::mpsc;
+
+    use crate::control_plane::state::ConnectedWorker;
+
+    fn connect(state: &AppState, worker_id: &WorkerId) -> mpsc::Receiver<RunServerMessage> {
+        let (tx, rx) = mpsc::channel(8);
+        let connected = ConnectedWorker {
+            info: WorkerInfo::new(worker_id.clone(), "host").with_agent(AgentSpec::new("general")),
+            status: WorkerStatus::Idle,
+            active_runs: 0,
+            max_concurrent_runs: 10,
+            interactive_slots: 0,
+            last_heartbeat: Utc::now(),
+            draining_since: None,
+            tx,
+            shutdown: tokio_util::sync::CancellationToken::new(),
+        };
+        state
+            .workers
+            .try_write()
+            .unwrap()
+            .insert(worker_id.clone(), connected);
+        state.assignments.worker_connected(worker_id, true);
+        rx
+    }
+
+    #[tokio::test]
+    async fn test_unacknowledged_assignment_goes_to_another_worker() {
+        let state = AppState::new();
+        let silent = WorkerId::new("worker-silent");
+        let mut silent_rx = connect(&state, &silent);
+        let task = Task::new("general", "{}", "test");
+        let task_id = task.id.clone();
+        state.tasks.write().await.insert(task_id.clone(), task);
+
+        let scheduler = Scheduler::new(state.clone());
+        let first = scheduler.assign_task(&task_id).await.unwrap();
+        assert!(matches!(
+            silent_rx.recv().await.and_then(|m| m.payload),
+            Some(ServerPayload::AssignRun(_))
+        ));
+        assert_eq!(state.assignments.pending_count(), 1);
+
+        // Not overdue yet
+        let timer = RedeliveryTimer::new(state.clone(), RedeliveryConfig::default());
+        assert_eq!(timer.redeliver(Utc::now()).await, 0);
+
+        // A second worker comes up; the first never acknowledges
+        let other = WorkerId::new("worker-other");
+        let mut other_rx = connect(&state, &other);
+        let later = Utc::now() + chrono::Duration::seconds(11);
+        assert_eq!(timer.redeliver(later).await, 1);
+
+        assert!(matches!(
+            silent_rx.recv().await.and_then(|m| m.payload),
+            Some(ServerPayload::CancelRun(cancel)) if cancel.run_id == first.as_str()
+        ));
+        let Some(ServerPayload::AssignRun(reassigned)) =
+            other_rx.recv().await.and_then(|m| m.payload)
+        else {
+            panic!("task not reassigned");
+        };
+        assert!(state
+            .assignments
+            .acknowledged(&RunId::new(&reassigned.run_id), &other));
+
+        let tasks = state.tasks.read().await;
+        let task = &tasks[&task_id];
+        assert_eq!(task.status, TaskStatus::Running);
+        assert_eq!(task.runs.len(), 2);
+        assert_eq!(task.runs[0].status, RunStatus::Failed);
+        assert_eq!(task.runs[1].worker_id, other);
+        assert!(state.assignments.is_unresponsive(&silent));
+    }
+}
@@ -0,0 +1,372 @@
+//! Append-only audit log of mutating actions.
+//!
+//! Every change made through the control plane (tasks created or cancelled,
+//! workers enrolled or disconnected, schedules and API keys changed) is
+//! recorded with the actor that made it, when, the client address for HTTP
+//! calls, and a SHA-256 digest of the action's payload. Records are never modified or removed. With a file
+//! configured, each record is appended as one JSON line and the file is
+//! replayed on startup, like the JSONL event store.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+use tokio::fs::{File, OpenOptions};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::{Mutex, RwLock};
+use tracing::{info, warn};
+
+/// Audit log errors.
+#[derive(Debug, Error)]
+pub enum AuditError {
+    #[error("I/O error on audit log '{path}': {source}")]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+}
+
+/// Kind of audited action.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditAction {
+    TaskCreated,
+    TaskCancelled,
+    TaskRetried,
+    WorkerEnrolled,
+    WorkerDisconnected,
+    WorkerDrained,
+    WorkerUndrained,
+    ScheduleCreated,
+    SchedulePaused,
+    ScheduleResumed,
+    ScheduleDeleted,
+    WorkflowCreated,
+    ApiKeyCreated,
+    ApiKeyRevoked,
+    ToolApproved,
+    ToolDenied,
+    StateExported,
+    StateImported,
+    ConfigReloaded,
+    TogglesUpdated,
+    ChaosUpdated,
+    FileUploaded,
+    FileDeleted,
+    BootstrapTokenCreated,
+    BootstrapTokenRevoked,
+    CertificateRevoked,
+}
+
+impl AuditAction {
+    /// snake_case name, as serialized and used in `/v1/audit?action=`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditAction::TaskCreated => "task_created",
+            AuditAction::TaskCancelled => "task_cancelled",
+            AuditAction::TaskRetried => "task_retried",
+            AuditAction::WorkerEnrolled => "worker_enrolled",
+            AuditAction::WorkerDisconnected => "worker_disconnected",
+            AuditAction::WorkerDrained => "worker_drained",
+            AuditAction::WorkerUndrained => "worker_undrained",
+            AuditAction::ScheduleCreated => "schedule_created",
+            AuditAction::SchedulePaused => "schedule_paused",
+            AuditAction::ScheduleResumed => "schedule_resumed",
+            AuditAction::ScheduleDeleted => "schedule_deleted",
+            AuditAction::WorkflowCreated => "workflow_created",
+            AuditAction::ApiKeyCreated => "api_key_created",
+            AuditAction::ApiKeyRevoked => "api_key_revoked",
+            AuditAction::ToolApproved => "tool_approved",
+            AuditAction::ToolDenied => "tool_denied",
+            AuditAction::StateExported => "state_exported",
+            AuditAction::StateImported => "state_imported",
+            AuditAction::ConfigReloaded => "config_reloaded",
+            AuditAction::TogglesUpdated => "toggles_updated",
+            AuditAction::ChaosUpdated => "chaos_updated",
+            AuditAction::FileUploaded => "file_uploaded",
+            AuditAction::FileDeleted => "file_deleted",
+            AuditAction::BootstrapTokenCreated => "bootstrap_token_created",
+            AuditAction::BootstrapTokenRevoked => "bootstrap_token_revoked",
+            AuditAction::CertificateRevoked => "certificate_revoked",
+        }
+    }
+}
+
+/// One audited action.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditRecord {
+    /// Position in the log, starting at 1.
+    pub seq: u64,
+    /// When the action happened.
+    pub timestamp: DateTime<Utc>,
+    /// Who did it: a caller identity (`api-key:<name>`, certificate CN),
+    /// `server-tui`, or the schedule/workflow acting on someone's behalf.
+    pub actor: String,
+    /// Address of the HTTP client, resolved through trusted proxies.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_ip: Option<String>,
+    pub action: AuditAction,
+    /// ID of the task, worker, schedule, workflow, key or file acted on.
+    pub target: String,
+    /// SHA-256 of the action's JSON payload (hex encoded).
+    pub payload_digest: String,
+}
+
+/// Filter for `AuditLog::query`. Empty fields match everything.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct AuditQuery {
+    pub action: Option<AuditAction>,
+    pub actor: Option<String>,
+    /// Target ID or prefix.
+    pub target: Option<String>,
+    /// Only records at or after this Unix timestamp (milliseconds).
+    pub since_ms: Option<i64>,
+    /// Maximum number of records (newest first).
+    pub limit: Option<usize>,
+}
+
+/// Default and maximum number of records returned by a query.
+const DEFAULT_QUERY_LIMIT: usize = 100;
+const MAX_QUERY_LIMIT: usize = 1000;
+
+/// The audit log, optionally persisted to a JSONL file.
+pub struct AuditLog {
+    /// JSONL file records are appended to (None = in memory only).
+    path: Option<PathBuf>,
+    records: RwLock<Vec<AuditRecord>>,
+    file: Mutex<Option<File>>,
+}
+
+impl AuditLog {
+    /// Create an in-memory audit log.
+    pub fn in_memory() -> Arc<Self> {
+        Arc::new(Self {
+            path: None,
+            records: RwLock::new(Vec::new()),
+            file: Mutex::new(None),
+        })
+    }
+
+    /// Open (or create) an audit log, replaying `path` if given.
+    pub async fn open(path: Option<PathBuf>) -> Result<Arc<Self>, AuditError> {
+        let Some(path) = path else {
+            return Ok(Self::in_memory());
+        };
+        let io_err = |source| AuditError::Io {
+            path: path.clone(),
+            source,
+        };
+
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            tokio::fs::create_dir_all(parent).await.map_err(io_err)?;
+        }
+
+        let mut records = Vec::new();
+        match tokio::fs::read_to_string(&path).await {
+            Ok(contents) => {
+                for (line_no, line) in contents.lines().enumerate() {
+                    if line.trim().is_empty() {
+                        continue;
+                    }
+                    match serde_json::from_str::<AuditRecord>(line) {
+                        Ok(record) => records.push(record),
+                        Err(e) => warn!(
+                            path = %path.display(),
+                            line = line_no + 1,
+                            error = %e,
+                            "Skipping malformed audit line"
+                        ),
+                    }
+                }
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(io_err(e)),
+        }
+
+        let file = open_for_append(&path).await?;
+
+        Ok(Arc::new(Self {
+            path: Some(path),
+            records: RwLock::new(records),
+            file: Mutex::new(Some(file)),
+        }))
+    }
+
+    /// Record an action. Failing to persist the record is logged, not
+    /// returned: the action has already happened.
+    pub async fn record(
+        &self,
+        actor: &str,
+        action: AuditAction,
+        target: &str,
+        payload: &impl Serialize,
+    ) -> AuditRecord {
+        self.record_from(actor, None, action, target, payload).await
+    }
+
+    /// Record an action taken by an HTTP client at `client_ip`.
+    pub async fn record_from(
+        &self,
+        actor: &str,
+        client_ip: Option<&str>,
+        action: AuditAction,
+        target: &str,
+        payload: &impl Serialize,
+    ) -> AuditRecord {
+        let payload = serde_json::to_vec(payload).unwrap_or_default();
+
+        // Hold the file lock so sequence numbers follow file order
+        let mut file = self.file.lock().await;
+        let mut records = self.records.write().await;
+        let record = AuditRecord {
+            seq: records.last().map_or(1, |r| r.seq + 1),
+            timestamp: Utc::now(),
+            actor: actor.to_string(),
+            client_ip: client_ip.map(str::to_string),
+            action,
+            target: target.to_string(),
+            payload_digest: hex::encode(Sha256::digest(&payload)),
+        };
+
+        if let (Some(file), Some(path)) = (file.as_mut(), &self.path) {
+            if let Err(e) = append_line(file, &record).await {
+                warn!(path = %path.display(), error = %e, "Failed to write audit record");
+            }
+        }
+        records.push(record.clone());
+
+        info!(
+            actor = %record.actor,
+            client_ip = record.client_ip.as_deref(),
+            action = record.action.as_str(),
+            target = %record.target,
+            "Audit"
+        );
+        record
+    }
+
+    /// Records matching `query`, newest first.
+    pub async fn query(&self, query: &AuditQuery) -> Vec<AuditRecord> {
+        let limit = query
+            .limit
+            .unwrap_or(DEFAULT_QUERY_LIMIT)
+            .min(MAX_QUERY_LIMIT);
+        self.records
+            .read()
+            .await
+            .iter()
+            .rev()
+            .filter(|r| query.action.map_or(true, |a| r.action == a))
+            .filter(|r| query.actor.as_ref().map_or(true, |a| &r.actor == a))
+            .filter(|r| {
+                query
+                    .target
+                    .as_ref()
+                    .map_or(true, |t| r.target.starts_with(t.as_str()))
+            })
+            .filter(|r| {
+                query
+                    .since_ms
+                    .map_or(true, |ms| r.timestamp.timestamp_millis() >= ms)
+            })
+            .take(limit)
+            .cloned()
+            .collect()
+    }
+
+    /// The latest `n` records, oldest first.
+    pub async fn recent(&self, n: usize) -> Vec<AuditRecord> {
+        let records = self.records.read().await;
+        records[records.len().saturating_sub(n)..].to_vec()
+    }
+}
+
+async fn open_for_append(path: &Path) -> Result<File, AuditError> {
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .await
+        .map_err(|source| AuditError::Io {
+            path: path.to_path_buf(),
+            source,
+        })
+}
+
+async fn append_line(file: &mut File, record: &AuditRecord) -> std::io::Result<()> {
+    let mut line = serde_json::to_string(record)?;
+    line.push('\n');
+    file.write_all(line.as_bytes()).await?;
+    file.flush().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_record_query_and_replay() {
+        let path = std::env::temp_dir().join(format!(
+            "taskrun-audit-{}-{}.jsonl",
+            std::process::id(),
+            Utc::now().timestamp_nanos_opt().unwrap_or_default()
+        ));
+
+        {
+            let log = AuditLog::open(Some(path.clone())).await.unwrap();
+            let created = log
+                .record(
+                    "api-key:ci",
+                    AuditAction::TaskCreated,
+                    "task-1",
+                    &serde_json::json!({"agent": "general"}),
+                )
+                .await;
+            assert_eq!(created.seq, 1);
+            assert_eq!(created.payload_digest.len(), 64);
+            log.record("server-tui", AuditAction::TaskCancelled, "task-1", &())
+                .await;
+        }
+
+        let log = AuditLog::open(Some(path.clone())).await.unwrap();
+        let all = log.query(&AuditQuery::default()).await;
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[0].action, AuditAction::TaskCancelled);
+
+        let third = log
+            .record_from(
+                "worker:dev",
+                Some("203.0.113.7"),
+                AuditAction::WorkerEnrolled,
+                "dev",
+                &(),
+            )
+            .await;
+        assert_eq!(third.seq, 3);
+        assert_eq!(third.client_ip.as_deref(), Some("203.0.113.7"));
+        assert_eq!(all[0].client_ip, None);
+
+        let by_actor = log
+            .query(&AuditQuery {
+                actor: Some("api-key:ci".to_string()),
+                ..AuditQuery::default()
+            })
+            .await;
+        assert_eq!(by_actor.len(), 1);
+        assert_eq!(by_actor[0].target, "task-1");
+
+        let cancels = log
+            .query(&AuditQuery {
+                action: Some(AuditAction::TaskCancelled),
+                ..AuditQuery::default()
+            })
+            .await;
+        assert_eq!(cancels.len(), 1);
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
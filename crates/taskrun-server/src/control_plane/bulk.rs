@@ -0,0 +1,218 @@
+//! Bulk task operations - cancel or retry every task matching a filter.
+//!
+//! Shared by the `BulkCancelTasks`/`BulkRetryTasks` RPCs and the TUI. A dry
+//! run reports which tasks an operation would affect without touching them,
+//! so callers can show the count and ask for confirmation first.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use tracing::{info, warn};
+
+use taskrun_core::{FailureReason, Task, TaskId, TaskStatus};
+
+use crate::control_plane::audit::AuditAction;
+use crate::control_plane::scheduler::{Scheduler, SchedulerError};
+use crate::control_plane::state::AppState;
+
+/// Selects tasks for a bulk operation. Empty fields match every task.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TaskFilter {
+    pub status: Option<TaskStatus>,
+    pub agent_name: Option<String>,
+    pub failure_reason: Option<FailureReason>,
+    /// Labels a task must all carry.
+    pub labels: HashMap<String, String>,
+    pub namespace: Option<String>,
+}
+
+impl TaskFilter {
+    /// Check whether a task matches this filter.
+    pub fn matches(&self, task: &Task) -> bool {
+        self.status.map_or(true, |s| task.status == s)
+            && self
+                .agent_name
+                .as_ref()
+                .map_or(true, |a| &task.agent_name == a)
+            && self
+                .failure_reason
+                .map_or(true, |r| task.failure_reason == Some(r))
+            && self
+                .namespace
+                .as_ref()
+                .map_or(true, |ns| &task.namespace == ns)
+            && self
+                .labels
+                .iter()
+                .all(|(k, v)| task.labels.get(k) == Some(v))
+    }
+}
+
+/// Operation applied to every matching task.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BulkOperation {
+    /// Cancel active (pending or running) tasks.
+    Cancel,
+    /// Retry failed or cancelled tasks with a new run.
+    Retry,
+}
+
+impl BulkOperation {
+    /// Whether the operation can act on a task in `status`.
+    pub fn applies_to(&self, status: TaskStatus) -> bool {
+        match self {
+            BulkOperation::Cancel => {
+                matches!(status, TaskStatus::Pending | TaskStatus::Running)
+            }
+            BulkOperation::Retry => {
+                matches!(status, TaskStatus::Failed | TaskStatus::Cancelled)
+            }
+        }
+    }
+
+    /// Lowercase verb for logs and dialogs.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BulkOperation::Cancel => "cancel",
+            BulkOperation::Retry => "retry",
+        }
+    }
+}
+
+/// Result of a bulk operation.
+#[derive(Debug, Clone, Default)]
+pub struct BulkOutcome {
+    /// Tasks the operation applied to, oldest first.
+    pub task_ids: Vec<TaskId>,
+    /// Tasks the operation failed for.
+    pub failed_task_ids: Vec<TaskId>,
+}
+
+/// Tasks matching `filter` that `op` can act on, oldest first.
+pub async fn affected_tasks(
+    state: &AppState,
+    op: BulkOperation,
+    filter: &TaskFilter,
+) -> Vec<TaskId> {
+    let tasks = state.tasks.read().await;
+    let mut affected: Vec<&Task> = tasks
+        .values()
+        .filter(|t| op.applies_to(t.status) && filter.matches(t))
+        .collect();
+    affected.sort_by_key(|t| t.created_at);
+    affected.into_iter().map(|t| t.id.clone()).collect()
+}
+
+/// Apply `op` to every task matching `filter`, auditing each one as `actor`.
+///
+/// With `dry_run`, only reports the tasks that would be affected.
+pub async fn run(
+    state: &Arc<AppState>,
+    op: BulkOperation,
+    filter: &TaskFilter,
+    actor: &str,
+    dry_run: bool,
+) -> BulkOutcome {
+    let task_ids = affected_tasks(state, op, filter).await;
+    if dry_run {
+        return BulkOutcome {
+            task_ids,
+            failed_task_ids: Vec::new(),
+        };
+    }
+
+    info!(operation = op.as_str(), tasks = task_ids.len(), actor = %actor, "Bulk task operation");
+
+    // Cancellation waits for worker acknowledgement, so act on all tasks at once
+    let scheduler = Scheduler::new(state.clone());
+    let reason = format!("Task cancelled in bulk by {}", actor);
+    let results = futures_util::future::join_all(task_ids.iter().map(|task_id| {
+        let scheduler = &scheduler;
+        let reason = &reason;
+        async move {
+            match op {
+                BulkOperation::Cancel => {
+                    let task = scheduler.cancel_task(task_id, reason).await?;
+                    state
+                        .audit(actor, AuditAction::TaskCancelled, task_id.as_str(), &task)
+                        .await;
+                }
+                BulkOperation::Retry => {
+                    let run_id = scheduler.retry_task(task_id).await?;
+                    state
+                        .audit(
+                            actor,
+                            AuditAction::TaskRetried,
+                            task_id.as_str(),
+                            &serde_json::json!({ "run_id": run_id }),
+                        )
+                        .await;
+                }
+            }
+            Ok::<_, SchedulerError>(())
+        }
+    }))
+    .await;
+
+    let mut failed_task_ids = Vec::new();
+    for (task_id, result) in task_ids.iter().zip(results) {
+        if let Err(e) = result {
+            warn!(task_id = %task_id, operation = op.as_str(), error = %e, "Bulk operation failed for task");
+            failed_task_ids.push(task_id.clone());
+        }
+    }
+
+    BulkOutcome {
+        task_ids,
+        failed_task_ids,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_bulk_retry_respects_filter_and_dry_run() {
+        let state = AppState::new();
+        let mut failed = Task::new("general", "{}", "test");
+        failed.status = TaskStatus::Failed;
+        failed.labels.insert("team".to_string(), "ops".to_string());
+        let mut other_agent = Task::new("support_triage", "{}", "test");
+        other_agent.status = TaskStatus::Failed;
+        let pending = Task::new("general", "{}", "test");
+        let failed_id = failed.id.clone();
+        {
+            let mut tasks = state.tasks.write().await;
+            for task in [failed, other_agent, pending] {
+                tasks.insert(task.id.clone(), task);
+            }
+        }
+
+        let filter = TaskFilter {
+            agent_name: Some("general".to_string()),
+            labels: HashMap::from([("team".to_string(), "ops".to_string())]),
+            ..TaskFilter::default()
+        };
+        let dry = run(&state, BulkOperation::Retry, &filter, "test", true).await;
+        assert_eq!(dry.task_ids, vec![failed_id.clone()]);
+        assert_eq!(
+            state.tasks.read().await[&failed_id].status,
+            TaskStatus::Failed
+        );
+
+        // No workers: the retried task is queued as pending
+        let outcome = run(&state, BulkOperation::Retry, &filter, "test", false).await;
+        assert!(outcome.failed_task_ids.is_empty());
+        assert_eq!(
+            state.tasks.read().await[&failed_id].status,
+            TaskStatus::Pending
+        );
+        assert_eq!(
+            affected_tasks(&state, BulkOperation::Cancel, &TaskFilter::default())
+                .await
+                .len(),
+            2
+        );
+    }
+}
@@ -0,0 +1,215 @@
+//! Fault injection for resilience testing.
+//!
+//! Chaos flags make the control plane misbehave on purpose, so retries,
+//! leases and reconnects can be exercised in integration tests and staging:
+//!
+//! - `drop_output_pct`: share of worker output chunks silently dropped.
+//! - `status_delay_ms`: delay before a run status update is processed. The
+//!   worker's stream is not read meanwhile, so its later messages wait too.
+//! - `close_stream_pct`: chance that a worker stream is closed when a
+//!   heartbeat arrives, as if the connection was lost.
+//! - `scheduler_error_pct`: share of task assignments that fail with an
+//!   injected error; tasks stay Pending and are picked up again.
+//!
+//! Flags can only be set on a server started with `--enable-chaos`, through
+//! `PUT /v1/admin/chaos`. They start at zero and are not persisted.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::warn;
+
+use crate::control_plane::audit::AuditAction;
+use crate::control_plane::state::AppState;
+
+/// Chaos flags were set on a server started without `--enable-chaos`.
+#[derive(Debug, Clone, Error)]
+#[error("Chaos flags are disabled; start the server with --enable-chaos")]
+pub struct ChaosDisabled;
+
+/// Invalid chaos flag values.
+#[derive(Debug, Clone, Error)]
+pub enum ChaosError {
+    #[error(transparent)]
+    Disabled(#[from] ChaosDisabled),
+
+    #[error("{field} must be between 0 and 100, got {value}")]
+    InvalidPercentage { field: &'static str, value: f64 },
+}
+
+/// Current chaos flags.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct ChaosSettings {
+    #[serde(default)]
+    pub drop_output_pct: f64,
+    #[serde(default)]
+    pub status_delay_ms: u64,
+    #[serde(default)]
+    pub close_stream_pct: f64,
+    #[serde(default)]
+    pub scheduler_error_pct: f64,
+}
+
+/// A change to some chaos flags. Unset fields keep their value.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ChaosUpdate {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub drop_output_pct: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status_delay_ms: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub close_stream_pct: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scheduler_error_pct: Option<f64>,
+}
+
+impl ChaosSettings {
+    /// These flags with `update` applied.
+    pub fn apply(mut self, update: ChaosUpdate) -> Result<Self, ChaosError> {
+        let pct = |field, value: Option<f64>, current: f64| match value {
+            Some(value) if !(0.0..=100.0).contains(&value) => {
+                Err(ChaosError::InvalidPercentage { field, value })
+            }
+            Some(value) => Ok(value),
+            None => Ok(current),
+        };
+        self.drop_output_pct = pct(
+            "drop_output_pct",
+            update.drop_output_pct,
+            self.drop_output_pct,
+        )?;
+        self.close_stream_pct = pct(
+            "close_stream_pct",
+            update.close_stream_pct,
+            self.close_stream_pct,
+        )?;
+        self.scheduler_error_pct = pct(
+            "scheduler_error_pct",
+            update.scheduler_error_pct,
+            self.scheduler_error_pct,
+        )?;
+        if let Some(delay) = update.status_delay_ms {
+            self.status_delay_ms = delay;
+        }
+        Ok(self)
+    }
+}
+
+/// The chaos flags of a control plane.
+#[derive(Debug, Default)]
+pub struct Chaos {
+    enabled: AtomicBool,
+    settings: Mutex<ChaosSettings>,
+}
+
+impl Chaos {
+    /// Allow chaos flags to be set.
+    pub fn enable(&self) {
+        self.enabled.store(true, Ordering::SeqCst);
+    }
+
+    /// Whether chaos flags can be set.
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::SeqCst)
+    }
+
+    /// Current flags.
+    pub fn get(&self) -> ChaosSettings {
+        *self.settings.lock().unwrap()
+    }
+
+    /// Apply `update`. Fails if chaos is not enabled or a value is invalid.
+    pub fn update(&self, update: ChaosUpdate) -> Result<ChaosSettings, ChaosError> {
+        if !self.is_enabled() {
+            return Err(ChaosDisabled.into());
+        }
+        let mut settings = self.settings.lock().unwrap();
+        *settings = settings.apply(update)?;
+        Ok(*settings)
+    }
+
+    /// Whether to drop this output chunk.
+    pub fn drop_output(&self) -> bool {
+        roll(self.get().drop_output_pct)
+    }
+
+    /// How long to hold this status update, if at all.
+    pub fn status_delay(&self) -> Option<Duration> {
+        let delay_ms = self.get().status_delay_ms;
+        (delay_ms > 0).then(|| Duration::from_millis(delay_ms))
+    }
+
+    /// Whether to close the worker stream this heartbeat arrived on.
+    pub fn close_stream(&self) -> bool {
+        roll(self.get().close_stream_pct)
+    }
+
+    /// Whether to fail this task assignment.
+    pub fn scheduler_error(&self) -> bool {
+        roll(self.get().scheduler_error_pct)
+    }
+}
+
+/// True with a chance of `pct` percent.
+fn roll(pct: f64) -> bool {
+    pct > 0.0 && rand::random::<f64>() * 100.0 < pct
+}
+
+/// Change chaos flags on behalf of `actor`, auditing the change.
+pub async fn update(
+    state: &AppState,
+    actor: &str,
+    update: ChaosUpdate,
+) -> Result<ChaosSettings, ChaosError> {
+    let settings = state.chaos.update(update)?;
+    warn!(
+        drop_output_pct = settings.drop_output_pct,
+        status_delay_ms = settings.status_delay_ms,
+        close_stream_pct = settings.close_stream_pct,
+        scheduler_error_pct = settings.scheduler_error_pct,
+        changed_by = actor,
+        "Chaos flags updated"
+    );
+    state
+        .audit(actor, AuditAction::ChaosUpdated, "chaos", &update)
+        .await;
+    Ok(settings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flags_need_enabling_and_valid_percentages() {
+        let chaos = Chaos::default();
+        let update = ChaosUpdate {
+            scheduler_error_pct: Some(100.0),
+            ..Default::default()
+        };
+        assert!(matches!(chaos.update(update), Err(ChaosError::Disabled(_))));
+        assert!(!chaos.scheduler_error());
+
+        chaos.enable();
+        chaos.update(update).unwrap();
+        assert!(chaos.scheduler_error());
+        assert!(!chaos.drop_output());
+
+        // A bad value leaves every flag as it was
+        let bad = ChaosUpdate {
+            status_delay_ms: Some(500),
+            drop_output_pct: Some(150.0),
+            ..Default::default()
+        };
+        assert!(matches!(
+            chaos.update(bad),
+            Err(ChaosError::InvalidPercentage { .. })
+        ));
+        assert_eq!(chaos.status_delay(), None);
+        assert_eq!(chaos.get().scheduler_error_pct, 100.0);
+    }
+}
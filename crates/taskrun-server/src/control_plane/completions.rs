@@ -0,0 +1,112 @@
+//! Waiting for tasks to finish.
+//!
+//! Requests that answer once a task finished (non-streaming `/v1/responses`)
+//! register here instead of polling the task table. Every task status change
+//! goes out as a `TaskStatusChanged` notification; `AppState::notify_ui`
+//! also wakes the task's waiters, which then look at the task again.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use tokio::sync::oneshot;
+
+use taskrun_core::{Task, TaskId};
+
+use crate::control_plane::state::AppState;
+
+/// Waiters by task, woken on each status change of their task.
+#[derive(Debug, Default)]
+pub struct TaskCompletions {
+    waiters: Mutex<HashMap<TaskId, Vec<oneshot::Sender<()>>>>,
+}
+
+impl TaskCompletions {
+    /// A receiver that fires on the next status change of `task_id`.
+    fn subscribe(&self, task_id: &TaskId) -> oneshot::Receiver<()> {
+        let (tx, rx) = oneshot::channel();
+        let mut waiters = self.waiters.lock().unwrap_or_else(|e| e.into_inner());
+        // Drop waiters that gave up (timed out or disconnected)
+        waiters.retain(|_, senders| {
+            senders.retain(|s| !s.is_closed());
+            !senders.is_empty()
+        });
+        waiters.entry(task_id.clone()).or_default().push(tx);
+        rx
+    }
+
+    /// Wake the waiters of `task_id`.
+    pub fn notify(&self, task_id: &TaskId) {
+        let senders = self
+            .waiters
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(task_id);
+        for tx in senders.into_iter().flatten() {
+            let _ = tx.send(());
+        }
+    }
+}
+
+/// Wait up to `timeout` for `task_id` to finish.
+///
+/// Returns the task once it finished, or as it is when the time is up;
+/// None if the task is gone.
+pub async fn wait_for_task(state: &AppState, task_id: &TaskId, timeout: Duration) -> Option<Task> {
+    let deadline = tokio::time::Instant::now() + timeout;
+    loop {
+        // Subscribe before looking so a change in between is not missed
+        let changed = state.completions.subscribe(task_id);
+        let task = state.tasks.read().await.get(task_id).cloned()?;
+        if task.is_terminal() || tokio::time::timeout_at(deadline, changed).await.is_err() {
+            return Some(task);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use taskrun_core::TaskStatus;
+
+    use crate::control_plane::state::UiNotification;
+
+    #[tokio::test]
+    async fn test_wait_wakes_on_status_change() {
+        let state = AppState::new();
+        let task = taskrun_core::Task::new("general", "{}", "test");
+        let task_id = task.id.clone();
+        state.tasks.write().await.insert(task_id.clone(), task);
+
+        let waiting = {
+            let state = state.clone();
+            let task_id = task_id.clone();
+            tokio::spawn(
+                async move { wait_for_task(&state, &task_id, Duration::from_secs(5)).await },
+            )
+        };
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        state.tasks.write().await.get_mut(&task_id).unwrap().status = TaskStatus::Completed;
+        state.notify_ui(UiNotification::TaskStatusChanged {
+            task_id: task_id.clone(),
+            status: TaskStatus::Completed,
+        });
+
+        let task = waiting.await.unwrap().unwrap();
+        assert_eq!(task.status, TaskStatus::Completed);
+        // Nobody is left waiting; closed senders are pruned on the next wait
+        assert!(state
+            .completions
+            .waiters
+            .lock()
+            .unwrap()
+            .values()
+            .flatten()
+            .all(|tx| tx.is_closed()));
+
+        // A task that does not finish in time
+        state.tasks.write().await.get_mut(&task_id).unwrap().status = TaskStatus::Running;
+        let task = wait_for_task(&state, &task_id, Duration::from_millis(20)).await;
+        assert_eq!(task.unwrap().status, TaskStatus::Running);
+    }
+}
@@ -0,0 +1,328 @@
+//! Multi-turn conversations over `/v1/responses`.
+//!
+//! A response is named after the run it came from: `resp_<run_id>` for the
+//! run's own answer and `resp_<run_id>.<turn>` for the follow-ups. Passing
+//! one as `previous_response_id` sends the new input to the worker that ran
+//! it as a `ContinueRun`, which resumes the run's Claude session.
+//!
+//! The run itself has finished by then, so the worker's status updates for
+//! the follow-up do not change it; they are routed to the run's stream,
+//! where the waiting request picks them up. A session only moves forward:
+//! only the latest response of a conversation can be continued, one
+//! follow-up at a time.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use thiserror::Error;
+use tokio::sync::{broadcast, RwLock};
+use tracing::info;
+
+use taskrun_core::{RunId, RunStatus, TaskId, WorkerId};
+use taskrun_proto::pb::run_server_message::Payload as ServerPayload;
+use taskrun_proto::pb::{ContinueRun, RunServerMessage};
+
+use crate::control_plane::state::{AppState, StreamEvent};
+
+/// How long a finished stream channel is kept for late subscribers.
+const STREAM_LINGER: Duration = Duration::from_secs(5);
+
+/// Conversation errors.
+#[derive(Debug, Error)]
+pub enum ConversationError {
+    #[error("'{0}' is not a response ID")]
+    InvalidId(String),
+
+    #[error("Response {0} not found")]
+    NotFound(String),
+
+    #[error("Response {0} did not complete")]
+    NotCompleted(String),
+
+    #[error("Response {id} was answered by agent '{agent}'")]
+    OtherAgent { id: String, agent: String },
+
+    #[error("Response {id} is not the latest of its conversation; continue {latest}")]
+    NotLatest { id: String, latest: String },
+
+    #[error("A follow-up of response {0} is still in progress")]
+    Busy(String),
+
+    #[error("Worker {0} is no longer connected")]
+    WorkerGone(WorkerId),
+}
+
+/// ID of a response: the run it came from and the turn of the conversation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResponseId {
+    pub run_id: RunId,
+    /// 0 for the run's own answer, then 1, 2, ... for follow-ups.
+    pub turn: u32,
+}
+
+impl ResponseId {
+    pub fn new(run_id: RunId, turn: u32) -> Self {
+        Self { run_id, turn }
+    }
+
+    /// The response after this one.
+    fn next(&self) -> Self {
+        Self::new(self.run_id.clone(), self.turn + 1)
+    }
+}
+
+impl fmt::Display for ResponseId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.turn {
+            0 => write!(f, "resp_{}", self.run_id),
+            turn => write!(f, "resp_{}.{}", self.run_id, turn),
+        }
+    }
+}
+
+impl FromStr for ResponseId {
+    type Err = ConversationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || ConversationError::InvalidId(s.to_string());
+        let id = s.strip_prefix("resp_").ok_or_else(invalid)?;
+        let (run_id, turn) = match id.rsplit_once('.') {
+            Some((run_id, turn)) => (run_id, turn.parse().map_err(|_| invalid())?),
+            None => (id, 0),
+        };
+        if run_id.is_empty() {
+            return Err(invalid());
+        }
+        Ok(Self::new(RunId::new(run_id), turn))
+    }
+}
+
+/// Where a conversation stands.
+#[derive(Debug, Default)]
+struct Conversation {
+    /// Turn of the latest response.
+    latest: u32,
+    /// A follow-up was sent and has not finished.
+    in_progress: bool,
+}
+
+/// Conversations continued past their run, keyed by run ID.
+#[derive(Debug, Default)]
+pub struct Conversations {
+    runs: RwLock<HashMap<RunId, Conversation>>,
+}
+
+impl Conversations {
+    /// Whether a follow-up of the run is waiting on the worker.
+    pub async fn in_progress(&self, run_id: &RunId) -> bool {
+        self.runs
+            .read()
+            .await
+            .get(run_id)
+            .is_some_and(|c| c.in_progress)
+    }
+
+    /// Start the follow-up of `previous`, returning the ID of its response.
+    async fn begin(&self, previous: &ResponseId) -> Result<ResponseId, ConversationError> {
+        let mut runs = self.runs.write().await;
+        let conversation = runs.entry(previous.run_id.clone()).or_default();
+        if conversation.in_progress {
+            return Err(ConversationError::Busy(previous.to_string()));
+        }
+        if conversation.latest != previous.turn {
+            return Err(ConversationError::NotLatest {
+                id: previous.to_string(),
+                latest: ResponseId::new(previous.run_id.clone(), conversation.latest).to_string(),
+            });
+        }
+        conversation.latest += 1;
+        conversation.in_progress = true;
+        Ok(previous.next())
+    }
+
+    /// The follow-up of the run finished.
+    async fn finish(&self, run_id: &RunId) {
+        if let Some(conversation) = self.runs.write().await.get_mut(run_id) {
+            conversation.in_progress = false;
+        }
+    }
+
+    /// Take back a follow-up that never reached the worker.
+    async fn abandon(&self, run_id: &RunId) {
+        if let Some(conversation) = self.runs.write().await.get_mut(run_id) {
+            conversation.latest -= 1;
+            conversation.in_progress = false;
+        }
+    }
+
+    /// Forget the conversations of runs whose chat history expired.
+    pub async fn forget<'a>(&self, run_ids: impl IntoIterator<Item = &'a RunId>) {
+        let mut runs = self.runs.write().await;
+        for run_id in run_ids {
+            runs.remove(run_id);
+        }
+    }
+}
+
+/// A follow-up sent to the worker.
+pub struct FollowUp {
+    pub task_id: TaskId,
+    pub worker_id: WorkerId,
+    /// ID of the response the follow-up produces.
+    pub response_id: ResponseId,
+    /// Events of the run's stream, from before the follow-up was sent.
+    pub receiver: broadcast::Receiver<StreamEvent>,
+}
+
+/// Continue the conversation of `previous` with `message`.
+///
+/// The response must be in `namespace` (None = any) and answered by
+/// `agent_name`.
+pub async fn continue_response(
+    state: &AppState,
+    previous: &str,
+    namespace: Option<&str>,
+    agent_name: &str,
+    message: String,
+) -> Result<FollowUp, ConversationError> {
+    let previous: ResponseId = previous.parse()?;
+    let not_found = || ConversationError::NotFound(previous.to_string());
+
+    let (task_id, worker_id) = {
+        let tasks = state.tasks.read().await;
+        let (task, run) = tasks
+            .values()
+            .filter(|t| namespace.map_or(true, |ns| t.namespace == ns))
+            .find_map(|t| {
+                t.runs
+                    .iter()
+                    .find(|r| r.run_id == previous.run_id)
+                    .map(|r| (t, r))
+            })
+            .ok_or_else(not_found)?;
+        if task.agent_name != agent_name {
+            return Err(ConversationError::OtherAgent {
+                id: previous.to_string(),
+                agent: task.agent_name.clone(),
+            });
+        }
+        if run.status != RunStatus::Completed {
+            return Err(ConversationError::NotCompleted(previous.to_string()));
+        }
+        (task.id.clone(), run.worker_id.clone())
+    };
+
+    let worker_tx = state
+        .workers
+        .read()
+        .await
+        .get(&worker_id)
+        .map(|w| w.tx.clone())
+        .ok_or_else(|| ConversationError::WorkerGone(worker_id.clone()))?;
+
+    let response_id = state.conversations.begin(&previous).await?;
+    // Subscribe before the worker can answer
+    let receiver = state
+        .get_or_create_stream_channel(&previous.run_id)
+        .await
+        .subscribe();
+    let message = RunServerMessage {
+        payload: Some(ServerPayload::ContinueRun(ContinueRun {
+            run_id: previous.run_id.to_string(),
+            message,
+            timestamp_ms: chrono::Utc::now().timestamp_millis(),
+        })),
+    };
+    if worker_tx.send(message).await.is_err() {
+        state.conversations.abandon(&previous.run_id).await;
+        return Err(ConversationError::WorkerGone(worker_id));
+    }
+
+    info!(
+        task_id = %task_id,
+        run_id = %previous.run_id,
+        response_id = %response_id,
+        "Sent follow-up to worker"
+    );
+    Ok(FollowUp {
+        task_id,
+        worker_id,
+        response_id,
+        receiver,
+    })
+}
+
+/// Pass a status update of a finished run on to the follow-up in progress.
+pub async fn follow_up_status(
+    state: &Arc<AppState>,
+    run_id: &RunId,
+    status: RunStatus,
+    error_message: Option<String>,
+    timestamp_ms: i64,
+) {
+    state
+        .publish_stream_event(
+            run_id,
+            StreamEvent::StatusUpdate {
+                status,
+                error_message,
+                timestamp_ms,
+            },
+        )
+        .await;
+    if status.is_terminal() {
+        state.conversations.finish(run_id).await;
+        remove_stream_later(state, run_id);
+    }
+}
+
+/// Drop a run's stream channel shortly after it finished, unless a
+/// follow-up is using it by then.
+pub fn remove_stream_later(state: &Arc<AppState>, run_id: &RunId) {
+    let state = state.clone();
+    let run_id = run_id.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(STREAM_LINGER).await;
+        if !state.conversations.in_progress(&run_id).await {
+            state.remove_stream_channel(&run_id).await;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_response_id_round_trip() {
+        let first: ResponseId = "resp_8f2c-41".parse().unwrap();
+        assert_eq!(first, ResponseId::new(RunId::new("8f2c-41"), 0));
+        assert_eq!(first.next().to_string(), "resp_8f2c-41.1");
+        assert_eq!("resp_8f2c-41.3".parse::<ResponseId>().unwrap().turn, 3);
+        assert!("run_8f2c".parse::<ResponseId>().is_err());
+        assert!("resp_8f2c.x".parse::<ResponseId>().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_only_latest_response_continues() {
+        let conversations = Conversations::default();
+        let first = ResponseId::new(RunId::new("run-1"), 0);
+
+        let second = conversations.begin(&first).await.unwrap();
+        assert_eq!(second.turn, 1);
+        assert!(matches!(
+            conversations.begin(&second).await,
+            Err(ConversationError::Busy(_))
+        ));
+
+        conversations.finish(&first.run_id).await;
+        assert!(matches!(
+            conversations.begin(&first).await,
+            Err(ConversationError::NotLatest { .. })
+        ));
+        assert_eq!(conversations.begin(&second).await.unwrap().turn, 2);
+    }
+}
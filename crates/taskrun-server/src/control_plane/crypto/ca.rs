@@ -1,17 +1,30 @@
 //! Certificate Authority for signing worker CSRs.
 //!
-//! Uses x509-parser to parse CSRs and rcgen to generate certificates.
+//! Uses x509-parser to parse CSRs and rcgen to generate certificates. While
+//! the CA is rotated, a previous CA certificate is trusted alongside it (see
+//! `rotation`).
 
 use std::path::Path;
+use std::time::{Duration, SystemTime};
 
 use chrono::{DateTime, Datelike, Utc};
+use rand::RngCore;
 use rcgen::{
-    Certificate, CertificateParams, DistinguishedName, DnType, ExtendedKeyUsagePurpose, IsCa,
-    KeyPair, KeyUsagePurpose,
+    Certificate, CertificateParams, CertificateRevocationListParams,
+    CertificateSigningRequestParams, DistinguishedName, DnType, ExtendedKeyUsagePurpose,
+    KeyIdMethod, KeyPair, KeyUsagePurpose, RevokedCertParams, SanType, SerialNumber,
 };
+use taskrun_core::SpiffeId;
 use thiserror::Error;
 use x509_parser::prelude::*;
 
+use super::cert_extractor::CertExtractError;
+use super::revocation::{format_serial, serial_bytes, RevocationList};
+use super::rotation::{CaInfo, PreviousCa, RotationStatus};
+
+/// How long a published CRL is valid; it is re-signed on every request.
+const CRL_VALIDITY: Duration = Duration::from_secs(24 * 60 * 60);
+
 /// Errors that can occur during CA operations.
 #[derive(Debug, Error)]
 pub enum CaError {
@@ -35,6 +48,9 @@ pub enum CaError {
 
     #[error("failed to sign certificate: {0}")]
     SignError(String),
+
+    #[error("failed to sign CRL: {0}")]
+    CrlError(String),
 }
 
 /// Certificate Authority that signs worker certificates.
@@ -42,15 +58,24 @@ pub struct CertificateAuthority {
     /// CA certificate (PEM).
     ca_cert_pem: String,
 
-    /// CA certificate (parsed rcgen).
+    /// CA certificate, re-signed from the file's parameters so that issued
+    /// certificates name the same issuer.
     ca_cert: Certificate,
 
     /// CA key pair.
     ca_key_pair: KeyPair,
 
     /// Certificate validity in days.
-    #[allow(dead_code)]
     validity_days: u64,
+
+    /// Revoked certificates and workers.
+    revocations: RevocationList,
+
+    /// Details of the CA certificate.
+    info: CaInfo,
+
+    /// CA being rotated out (None = no rotation).
+    previous: Option<PreviousCa>,
 }
 
 impl CertificateAuthority {
@@ -66,31 +91,88 @@ impl CertificateAuthority {
         let ca_key_pair =
             KeyPair::from_pem(&ca_key_pem).map_err(|e| CaError::ParseKey(e.to_string()))?;
 
-        // Create CA cert params for signing
-        let mut ca_params = CertificateParams::default();
-        let mut dn = DistinguishedName::new();
-        dn.push(DnType::CommonName, "TaskRun CA");
-        dn.push(DnType::OrganizationName, "TaskRun");
-        ca_params.distinguished_name = dn;
-        ca_params.is_ca = IsCa::Ca(rcgen::BasicConstraints::Unconstrained);
-        ca_params.key_usages = vec![
-            KeyUsagePurpose::KeyCertSign,
-            KeyUsagePurpose::CrlSign,
-            KeyUsagePurpose::DigitalSignature,
-        ];
-
+        let ca_params = CertificateParams::from_ca_cert_pem(&ca_cert_pem)
+            .map_err(|e| CaError::ParseCert(e.to_string()))?;
         let ca_cert = ca_params
             .self_signed(&ca_key_pair)
             .map_err(|e| CaError::ParseCert(e.to_string()))?;
+        let info = CaInfo::from_der(
+            ::pem::parse(&ca_cert_pem)
+                .map_err(|e| CaError::ParseCert(e.to_string()))?
+                .contents(),
+        )?;
 
         Ok(Self {
             ca_cert_pem,
             ca_cert,
             ca_key_pair,
             validity_days,
+            revocations: RevocationList::default(),
+            info,
+            previous: None,
         })
     }
 
+    /// Also trust `previous` during its dual-trust window.
+    pub fn with_previous(mut self, previous: PreviousCa) -> Self {
+        self.previous = Some(previous);
+        self
+    }
+
+    /// The CA being rotated out, if any.
+    pub fn previous(&self) -> Option<&PreviousCa> {
+        self.previous.as_ref()
+    }
+
+    /// Default end of a rotation to this CA: when the last certificate the
+    /// previous CA could have issued before this one existed expires.
+    pub fn default_rotation_end(&self) -> DateTime<Utc> {
+        self.info.not_before + chrono::Duration::days(self.validity_days as i64)
+    }
+
+    /// CA certificates workers should trust (PEM): this one, followed by the
+    /// previous one during its window.
+    pub fn trust_bundle_pem(&self) -> String {
+        match self.previous.as_ref().filter(|p| p.is_trusted()) {
+            Some(previous) => format!(
+                "{}\n{}\n",
+                self.ca_cert_pem.trim_end(),
+                previous.cert_pem().trim_end()
+            ),
+            None => self.ca_cert_pem.clone(),
+        }
+    }
+
+    /// Fails for a DER-encoded client certificate that is revoked or was
+    /// issued by the previous CA after its window.
+    pub fn check_client_cert(&self, cert_der: &[u8]) -> Result<(), CertExtractError> {
+        if let Some(previous) = &self.previous {
+            previous.check(cert_der)?;
+        }
+        self.revocations.check(cert_der)
+    }
+
+    /// Where a CA rotation stands.
+    pub fn rotation_status(&self) -> RotationStatus {
+        let previous = self.previous.as_ref().map(PreviousCa::status);
+        RotationStatus {
+            current: self.info.clone(),
+            rotating: previous.as_ref().is_some_and(|p| p.remaining_secs > 0),
+            previous,
+        }
+    }
+
+    /// Use `revocations` as the revocation list.
+    pub fn with_revocations(mut self, revocations: RevocationList) -> Self {
+        self.revocations = revocations;
+        self
+    }
+
+    /// Revoked certificates and workers.
+    pub fn revocations(&self) -> &RevocationList {
+        &self.revocations
+    }
+
     /// Get the CA certificate in PEM format.
     pub fn ca_cert_pem(&self) -> &str {
         &self.ca_cert_pem
@@ -98,8 +180,9 @@ impl CertificateAuthority {
 
     /// Sign a Certificate Signing Request (CSR).
     ///
-    /// Extracts the worker ID from the CSR's Common Name and issues a certificate.
-    /// Returns the signed certificate in PEM format and its expiration time.
+    /// Extracts the worker ID from the CSR's Common Name and issues a certificate
+    /// for the CSR's public key. Returns the signed certificate in PEM format and
+    /// its expiration time.
     pub fn sign_csr(&self, csr_pem: &str) -> Result<SignedCertificate, CaError> {
         // Parse the CSR using x509-parser
         let pem = ::pem::parse(csr_pem).map_err(|e| CaError::ParseCsr(e.to_string()))?;
@@ -126,12 +209,23 @@ impl CertificateAuthority {
             ));
         }
 
+        // The SPIFFE ID the worker asked for (it may narrow itself to an
+        // agent), or the one its CN implies
+        let csr_params = CertificateSigningRequestParams::from_pem(csr_pem)
+            .map_err(|e| CaError::ParseCsr(e.to_string()))?;
+        let spiffe_id = requested_spiffe_id(&csr_params, worker_id)?;
+
         // Calculate validity
         let not_before = Utc::now();
         let not_after = not_before + chrono::Duration::days(self.validity_days as i64);
 
-        // Create certificate parameters for the worker
+        // Create certificate parameters for the worker, with a random
+        // positive serial so it can be revoked on its own
         let mut params = CertificateParams::default();
+        let mut serial = [0u8; 16];
+        rand::thread_rng().fill_bytes(&mut serial);
+        serial[0] &= 0x7f;
+        params.serial_number = Some(SerialNumber::from_slice(&serial));
 
         // Set subject name
         let mut dn = DistinguishedName::new();
@@ -159,24 +253,56 @@ impl CertificateAuthority {
         ];
 
         params.extended_key_usages = vec![ExtendedKeyUsagePurpose::ClientAuth];
+        params.subject_alt_names =
+            vec![SanType::URI(spiffe_id.to_string().try_into().map_err(
+                |e: rcgen::Error| CaError::SignError(e.to_string()),
+            )?)];
 
-        // Generate a key pair for the worker cert (in real impl, we'd use CSR's public key)
-        // For now, generate a new key pair since rcgen CSR signing is complex
-        let worker_key_pair = KeyPair::generate().map_err(|e| CaError::SignError(e.to_string()))?;
-
-        // Sign the certificate with CA
+        // Sign the CSR's public key; the private key never leaves the worker
         let worker_cert = params
-            .signed_by(&worker_key_pair, &self.ca_cert, &self.ca_key_pair)
+            .signed_by(&csr_params.public_key, &self.ca_cert, &self.ca_key_pair)
             .map_err(|e| CaError::SignError(e.to_string()))?;
 
-        // For a real implementation, we'd need to return the private key too
-        // or use the CSR's public key directly. For now, return just the cert.
         Ok(SignedCertificate {
             cert_pem: worker_cert.pem(),
             expires_at: not_after,
             worker_id: worker_id.to_string(),
+            serial: format_serial(&serial),
+            spiffe_id: spiffe_id.to_string(),
         })
     }
+
+    /// A CRL of the certificates revoked by serial number, DER encoded and
+    /// signed by the CA. Worker-wide revocations have no serial and are only
+    /// enforced by the control plane itself.
+    pub fn crl_der(&self) -> Result<Vec<u8>, CaError> {
+        let revocations = self.revocations.list();
+        let revoked_certs = revocations
+            .iter()
+            .filter_map(|r| {
+                Some(RevokedCertParams {
+                    serial_number: SerialNumber::from(serial_bytes(r.serial.as_deref()?)?),
+                    revocation_time: SystemTime::from(r.revoked_at).into(),
+                    reason_code: None,
+                    invalidity_date: None,
+                })
+            })
+            .collect();
+        let now = SystemTime::now();
+        let params = CertificateRevocationListParams {
+            this_update: now.into(),
+            next_update: (now + CRL_VALIDITY).into(),
+            // Entries are never removed, so their count only grows
+            crl_number: SerialNumber::from(revocations.len() as u64),
+            issuing_distribution_point: None,
+            revoked_certs,
+            key_identifier_method: KeyIdMethod::Sha256,
+        };
+        let crl = params
+            .signed_by(&self.ca_cert, &self.ca_key_pair)
+            .map_err(|e| CaError::CrlError(e.to_string()))?;
+        Ok(crl.der().to_vec())
+    }
 }
 
 /// A signed certificate returned by the CA.
@@ -190,6 +316,55 @@ pub struct SignedCertificate {
 
     /// The worker ID extracted from the CN.
     pub worker_id: String,
+
+    /// Serial number of the certificate (hex).
+    pub serial: String,
+
+    /// SPIFFE ID in the certificate's URI SAN.
+    pub spiffe_id: String,
+}
+
+/// The SPIFFE ID for a worker certificate: the CSR's URI SAN if it has one,
+/// which must name the worker of the CN (`worker_id`, without a namespace),
+/// else `spiffe://taskrun/worker/<id>`.
+fn requested_spiffe_id(
+    csr: &CertificateSigningRequestParams,
+    worker_id: &str,
+) -> Result<SpiffeId, CaError> {
+    let worker_id = worker_id.split_once('@').map_or(worker_id, |(id, _)| id);
+    let uris: Vec<&str> = csr
+        .params
+        .subject_alt_names
+        .iter()
+        .filter_map(|san| match san {
+            SanType::URI(uri) => Some(uri.as_str()),
+            _ => None,
+        })
+        .collect();
+    match uris.as_slice() {
+        [] => {
+            let id = SpiffeId::worker(worker_id);
+            // Also validates the worker ID as a SPIFFE path segment
+            id.to_string()
+                .parse()
+                .map_err(|e: taskrun_core::CoreError| CaError::InvalidCsr(e.to_string()))
+        }
+        [uri] => {
+            let id: SpiffeId = uri
+                .parse()
+                .map_err(|e: taskrun_core::CoreError| CaError::InvalidCsr(e.to_string()))?;
+            if id.worker_id != worker_id {
+                return Err(CaError::InvalidCsr(format!(
+                    "SPIFFE ID '{}' does not match worker '{}' of the CN",
+                    uri, worker_id
+                )));
+            }
+            Ok(id)
+        }
+        _ => Err(CaError::InvalidCsr(
+            "at most one URI SAN (the SPIFFE ID) is allowed".to_string(),
+        )),
+    }
 }
 
 /// Extract Common Name from X.509 CSR.
@@ -214,6 +389,7 @@ fn extract_cn_from_x509_csr(csr: &X509CertificationRequest<'_>) -> Result<String
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rcgen::IsCa;
 
     #[test]
     fn test_generate_test_ca_and_sign() {
@@ -241,4 +417,118 @@ mod tests {
         // Verify signed cert is valid PEM
         assert!(worker_cert.pem().starts_with("-----BEGIN CERTIFICATE-----"));
     }
+
+    #[test]
+    fn test_sign_csr_certifies_worker_key() {
+        let mut ca_params = CertificateParams::default();
+        ca_params
+            .distinguished_name
+            .push(DnType::CommonName, "Test CA");
+        ca_params.is_ca = IsCa::Ca(rcgen::BasicConstraints::Unconstrained);
+        let ca_key_pair = KeyPair::generate().unwrap();
+        let ca_cert = ca_params.self_signed(&ca_key_pair).unwrap();
+
+        let dir = std::env::temp_dir().join(format!("taskrun-ca-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("ca.crt"), ca_cert.pem()).unwrap();
+        std::fs::write(dir.join("ca.key"), ca_key_pair.serialize_pem()).unwrap();
+        let ca =
+            CertificateAuthority::from_files(dir.join("ca.crt"), dir.join("ca.key"), 7).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let worker_key = KeyPair::generate().unwrap();
+        let mut params = CertificateParams::default();
+        params
+            .distinguished_name
+            .push(DnType::CommonName, "worker:w-1");
+        let csr = params.serialize_request(&worker_key).unwrap();
+
+        let signed = ca.sign_csr(&csr.pem().unwrap()).unwrap();
+        assert_eq!(signed.worker_id, "w-1");
+        assert_eq!(signed.spiffe_id, "spiffe://taskrun/worker/w-1");
+        let der = ::pem::parse(&signed.cert_pem).unwrap();
+        let (_, cert) = X509Certificate::from_der(der.contents()).unwrap();
+        assert_eq!(
+            cert.public_key().raw,
+            worker_key.public_key_der().as_slice()
+        );
+        assert_eq!(cert.issuer().to_string(), "CN=Test CA");
+
+        // A requested SPIFFE ID is kept if it names the CN's worker
+        params.subject_alt_names = vec![SanType::URI(
+            "spiffe://taskrun/worker/w-1/agent/general"
+                .try_into()
+                .unwrap(),
+        )];
+        let csr = params.serialize_request(&worker_key).unwrap();
+        let signed = ca.sign_csr(&csr.pem().unwrap()).unwrap();
+        assert_eq!(
+            signed.spiffe_id,
+            "spiffe://taskrun/worker/w-1/agent/general"
+        );
+        params.subject_alt_names = vec![SanType::URI(
+            "spiffe://taskrun/worker/w-2".try_into().unwrap(),
+        )];
+        let csr = params.serialize_request(&worker_key).unwrap();
+        assert!(matches!(
+            ca.sign_csr(&csr.pem().unwrap()),
+            Err(CaError::InvalidCsr(_))
+        ));
+
+        let mut params = CertificateParams::default();
+        params
+            .distinguished_name
+            .push(DnType::CommonName, "admin:eve");
+        let csr = params.serialize_request(&worker_key).unwrap();
+        assert!(matches!(
+            ca.sign_csr(&csr.pem().unwrap()),
+            Err(CaError::InvalidCsr(_))
+        ));
+    }
+    #[tokio::test]
+    async fn test_crl_lists_revoked_serials() {
+        let mut ca_params = CertificateParams::default();
+        ca_params
+            .distinguished_name
+            .push(DnType::CommonName, "Test CA");
+        ca_params.is_ca = IsCa::Ca(rcgen::BasicConstraints::Unconstrained);
+        let ca_key_pair = KeyPair::generate().unwrap();
+        let ca_cert = ca_params.self_signed(&ca_key_pair).unwrap();
+        let ca = CertificateAuthority {
+            ca_cert_pem: ca_cert.pem(),
+            info: CaInfo::from_der(ca_cert.der()).unwrap(),
+            ca_cert,
+            ca_key_pair,
+            validity_days: 7,
+            revocations: RevocationList::default(),
+            previous: None,
+        };
+
+        let worker_key = KeyPair::generate().unwrap();
+        let mut params = CertificateParams::default();
+        params
+            .distinguished_name
+            .push(DnType::CommonName, "worker:w-1");
+        let csr = params.serialize_request(&worker_key).unwrap();
+        let signed = ca.sign_csr(&csr.pem().unwrap()).unwrap();
+
+        ca.revocations()
+            .revoke(Some(&signed.serial), None, None)
+            .await
+            .unwrap();
+        // Worker-wide revocations have no serial to list
+        ca.revocations()
+            .revoke(None, Some("w-2"), None)
+            .await
+            .unwrap();
+
+        let der = ca.crl_der().unwrap();
+        let (_, crl) = CertificateRevocationList::from_der(&der).unwrap();
+        let serials: Vec<String> = crl
+            .iter_revoked_certificates()
+            .map(|r| format_serial(r.raw_serial()))
+            .collect();
+        assert_eq!(serials, vec![signed.serial]);
+        assert_eq!(crl.issuer().to_string(), "CN=Test CA");
+    }
 }
@@ -1,11 +1,18 @@
 //! Certificate parsing and worker ID extraction.
 //!
-//! Extracts worker_id from X.509 certificate CN field.
-//! Expected format: "worker:<worker_id>"
+//! Extracts worker_id from the SPIFFE ID in an X.509 certificate's URI SAN
+//! (`spiffe://taskrun/worker/<worker_id>[/agent/<name>]`). Certificates
+//! without one, issued before SPIFFE IDs or by hand, fall back to the CN
+//! field, expected as "worker:<worker_id>".
+//!
+//! The raw CN is also exposed for role-based access control of other callers.
 
+use taskrun_core::SpiffeId;
 use thiserror::Error;
 use x509_parser::prelude::*;
 
+use super::revocation::RevocationList;
+
 /// Errors that can occur during certificate extraction.
 #[derive(Debug, Error)]
 #[allow(dead_code)]
@@ -21,25 +28,44 @@ pub enum CertExtractError {
 
     #[error("worker_id in CN cannot be empty")]
     EmptyWorkerId,
+
+    #[error("certificate '{0}' has been revoked")]
+    Revoked(String),
+
+    #[error("invalid SPIFFE ID in certificate: {0}")]
+    InvalidSpiffeId(String),
+
+    #[error("certificate was issued by the previous CA, trusted only until {0}")]
+    PreviousCa(String),
 }
 
 /// Extract worker_id from a DER-encoded X.509 certificate.
 ///
-/// The certificate's Common Name (CN) must be in the format "worker:<worker_id>".
+/// The worker is named by the certificate's SPIFFE ID, or without one by its
+/// Common Name (CN) in the format "worker:<worker_id>". The certificate must
+/// not be revoked.
 ///
 /// # Arguments
 /// * `cert_der` - DER-encoded X.509 certificate bytes
+/// * `revocations` - Revoked certificates and workers
 ///
 /// # Returns
-/// The worker_id extracted from the CN (without the "worker:" prefix).
-#[allow(dead_code)]
-pub fn extract_worker_id_from_cert(cert_der: &[u8]) -> Result<String, CertExtractError> {
-    // Parse the X.509 certificate
-    let (_, cert) = X509Certificate::from_der(cert_der)
-        .map_err(|e| CertExtractError::ParseError(format!("{:?}", e)))?;
+/// The worker_id from the SPIFFE ID, or from the CN without the "worker:" prefix.
+pub fn extract_worker_id_from_cert(
+    cert_der: &[u8],
+    revocations: &RevocationList,
+) -> Result<String, CertExtractError> {
+    let worker_id = cert_worker_id(cert_der)?;
+    revocations.check(cert_der)?;
+    Ok(worker_id)
+}
 
-    // Extract CN from subject
-    let cn = extract_cn_from_subject(&cert)?;
+/// The worker named by a certificate's SPIFFE ID, else by its CN.
+pub(crate) fn cert_worker_id(cert_der: &[u8]) -> Result<String, CertExtractError> {
+    if let Some(spiffe_id) = extract_spiffe_id(cert_der)? {
+        return Ok(spiffe_id.worker_id);
+    }
+    let cn = extract_common_name(cert_der)?;
 
     // Validate format and extract worker_id
     if !cn.starts_with("worker:") {
@@ -50,12 +76,51 @@ pub fn extract_worker_id_from_cert(cert_der: &[u8]) -> Result<String, CertExtrac
     if worker_id.is_empty() {
         return Err(CertExtractError::EmptyWorkerId);
     }
-
     Ok(worker_id.to_string())
 }
 
+/// The TaskRun SPIFFE ID in a DER-encoded certificate's URI SANs, if it has
+/// one. Fails on a malformed ID or on more than one `spiffe://` URI.
+pub fn extract_spiffe_id(cert_der: &[u8]) -> Result<Option<SpiffeId>, CertExtractError> {
+    let (_, cert) = X509Certificate::from_der(cert_der)
+        .map_err(|e| CertExtractError::ParseError(format!("{:?}", e)))?;
+    let Some(san) = cert
+        .subject_alternative_name()
+        .map_err(|e| CertExtractError::ParseError(format!("{:?}", e)))?
+    else {
+        return Ok(None);
+    };
+
+    let uris: Vec<&str> = san
+        .value
+        .general_names
+        .iter()
+        .filter_map(|name| match name {
+            GeneralName::URI(uri) if uri.starts_with("spiffe://") => Some(*uri),
+            _ => None,
+        })
+        .collect();
+    match uris.as_slice() {
+        [] => Ok(None),
+        [uri] => uri
+            .parse()
+            .map(Some)
+            .map_err(|e: taskrun_core::CoreError| CertExtractError::InvalidSpiffeId(e.to_string())),
+        _ => Err(CertExtractError::InvalidSpiffeId(
+            "more than one SPIFFE ID".to_string(),
+        )),
+    }
+}
+
+/// Extract the subject Common Name from a DER-encoded X.509 certificate.
+pub fn extract_common_name(cert_der: &[u8]) -> Result<String, CertExtractError> {
+    let (_, cert) = X509Certificate::from_der(cert_der)
+        .map_err(|e| CertExtractError::ParseError(format!("{:?}", e)))?;
+
+    extract_cn_from_subject(&cert)
+}
+
 /// Extract Common Name from certificate subject.
-#[allow(dead_code)]
 fn extract_cn_from_subject(cert: &X509Certificate<'_>) -> Result<String, CertExtractError> {
     for rdn in cert.subject().iter() {
         for attr in rdn.iter() {
@@ -93,7 +158,7 @@ mod tests {
     #[test]
     fn test_extract_valid_worker_id() {
         let cert_der = generate_test_cert("worker:test-worker-123");
-        let result = extract_worker_id_from_cert(&cert_der);
+        let result = extract_worker_id_from_cert(&cert_der, &RevocationList::default());
         assert!(result.is_ok());
         assert_eq!(result.unwrap(), "test-worker-123");
     }
@@ -101,14 +166,58 @@ mod tests {
     #[test]
     fn test_extract_invalid_cn_format() {
         let cert_der = generate_test_cert("not-a-worker");
-        let result = extract_worker_id_from_cert(&cert_der);
+        let result = extract_worker_id_from_cert(&cert_der, &RevocationList::default());
         assert!(matches!(result, Err(CertExtractError::InvalidCnFormat(_))));
     }
 
     #[test]
     fn test_extract_empty_worker_id() {
         let cert_der = generate_test_cert("worker:");
-        let result = extract_worker_id_from_cert(&cert_der);
+        let result = extract_worker_id_from_cert(&cert_der, &RevocationList::default());
         assert!(matches!(result, Err(CertExtractError::EmptyWorkerId)));
     }
+
+    #[test]
+    fn test_extract_worker_id_from_spiffe_san() {
+        let mut params = CertificateParams::default();
+        params
+            .distinguished_name
+            .push(DnType::CommonName, "worker:legacy-name");
+        params.subject_alt_names = vec![rcgen::SanType::URI(
+            "spiffe://taskrun/worker/w-1/agent/general"
+                .try_into()
+                .unwrap(),
+        )];
+        let key_pair = KeyPair::generate().unwrap();
+        let cert_der = params
+            .clone()
+            .self_signed(&key_pair)
+            .unwrap()
+            .der()
+            .to_vec();
+
+        let spiffe_id = extract_spiffe_id(&cert_der).unwrap().unwrap();
+        assert_eq!(spiffe_id.agent.as_deref(), Some("general"));
+        let result = extract_worker_id_from_cert(&cert_der, &RevocationList::default());
+        assert_eq!(result.unwrap(), "w-1");
+
+        params.subject_alt_names = vec![rcgen::SanType::URI(
+            "spiffe://other/worker/w-1".try_into().unwrap(),
+        )];
+        let cert_der = params.self_signed(&key_pair).unwrap().der().to_vec();
+        let result = extract_worker_id_from_cert(&cert_der, &RevocationList::default());
+        assert!(matches!(result, Err(CertExtractError::InvalidSpiffeId(_))));
+    }
+
+    #[tokio::test]
+    async fn test_extract_revoked_worker_id() {
+        let cert_der = generate_test_cert("worker:test-worker-123");
+        let revocations = RevocationList::default();
+        revocations
+            .revoke(None, Some("test-worker-123"), None)
+            .await
+            .unwrap();
+        let result = extract_worker_id_from_cert(&cert_der, &revocations);
+        assert!(matches!(result, Err(CertExtractError::Revoked(_))));
+    }
 }
@@ -2,13 +2,14 @@
 
 mod ca;
 mod cert_extractor;
+mod revocation;
+mod rotation;
 mod token;
 
-pub use ca::CertificateAuthority;
-#[allow(unused_imports)]
-pub use cert_extractor::{extract_worker_id_from_cert, CertExtractError};
-pub use token::{hash_token, BootstrapToken};
-
-// Re-export for use in CLI token generation command
-#[allow(unused_imports)]
-pub use token::generate_bootstrap_token;
+pub use ca::{CaError, CertificateAuthority, SignedCertificate};
+pub use cert_extractor::{
+    extract_common_name, extract_spiffe_id, extract_worker_id_from_cert, CertExtractError,
+};
+pub use revocation::{RevocationError, RevocationList, RevokedCertificate};
+pub use rotation::PreviousCa;
+pub use token::{generate_bootstrap_token, hash_token, BootstrapToken, DEFAULT_VALIDITY_HOURS};
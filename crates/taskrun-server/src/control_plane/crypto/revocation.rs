@@ -0,0 +1,290 @@
+//! Revoked worker certificates.
+//!
+//! A certificate is revoked by its serial number, or every certificate of a
+//! worker by its worker ID. The list is checked on each gRPC call and when
+//! worker IDs are taken from certificates, so revoked workers cannot
+//! reconnect, and open worker streams are told of every change so they can
+//! close. The CA publishes the serial revocations as an X.509 CRL.
+//! Entries are never removed. With a file configured, the list is persisted
+//! as JSON and reloaded on startup.
+
+use std::path::PathBuf;
+use std::sync::RwLock;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio::sync::{watch, Mutex};
+use x509_parser::prelude::*;
+
+use super::cert_extractor::{cert_worker_id, extract_common_name, CertExtractError};
+
+/// Revocation list errors.
+#[derive(Debug, Error)]
+pub enum RevocationError {
+    #[error("Give a certificate serial number or a worker ID to revoke")]
+    MissingTarget,
+
+    #[error("Invalid serial number '{0}': expected hex digits")]
+    InvalidSerial(String),
+
+    #[error("I/O error on revocation file '{path}': {source}")]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+
+    #[error("Invalid revocation file: {0}")]
+    Serialization(#[from] serde_json::Error),
+}
+
+/// A revocation: one certificate, or all certificates of a worker.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RevokedCertificate {
+    /// Serial number (lowercase hex, no leading zeros).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub serial: Option<String>,
+    /// Worker whose certificates are all revoked.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub worker_id: Option<String>,
+    pub revoked_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+impl RevokedCertificate {
+    fn matches(&self, serial: &str, worker_id: Option<&str>) -> bool {
+        self.serial.as_deref() == Some(serial)
+            || self
+                .worker_id
+                .as_deref()
+                .is_some_and(|id| Some(id) == worker_id)
+    }
+}
+
+/// Revoked certificates, optionally persisted to a JSON file.
+#[derive(Debug)]
+pub struct RevocationList {
+    /// JSON file the list is persisted to (None = in memory only).
+    path: Option<PathBuf>,
+    /// Read on every gRPC call, from synchronous interceptors.
+    entries: RwLock<Vec<RevokedCertificate>>,
+    /// Serializes writers so the temp file is never shared.
+    write_lock: Mutex<()>,
+    /// Bumped on every revocation.
+    changed: watch::Sender<()>,
+}
+
+impl Default for RevocationList {
+    fn default() -> Self {
+        Self {
+            path: None,
+            entries: RwLock::default(),
+            write_lock: Mutex::default(),
+            changed: watch::channel(()).0,
+        }
+    }
+}
+
+impl RevocationList {
+    /// Load the list from `path` if given.
+    pub fn open(path: Option<PathBuf>) -> Result<Self, RevocationError> {
+        let entries = match &path {
+            Some(path) => match std::fs::read_to_string(path) {
+                Ok(contents) if contents.trim().is_empty() => Vec::new(),
+                Ok(contents) => serde_json::from_str(&contents)?,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+                Err(source) => {
+                    return Err(RevocationError::Io {
+                        path: path.clone(),
+                        source,
+                    })
+                }
+            },
+            None => Vec::new(),
+        };
+        Ok(Self {
+            path,
+            entries: RwLock::new(entries),
+            ..Self::default()
+        })
+    }
+
+    /// Revoke the certificate with `serial` (hex, colons allowed) or all
+    /// certificates of `worker_id`.
+    pub async fn revoke(
+        &self,
+        serial: Option<&str>,
+        worker_id: Option<&str>,
+        reason: Option<String>,
+    ) -> Result<RevokedCertificate, RevocationError> {
+        let serial = serial.map(normalize_serial).transpose()?;
+        let worker_id = worker_id
+            .map(|id| id.strip_prefix("worker:").unwrap_or(id).to_string())
+            .filter(|id| !id.is_empty());
+        if serial.is_none() && worker_id.is_none() {
+            return Err(RevocationError::MissingTarget);
+        }
+        let revoked = RevokedCertificate {
+            serial,
+            worker_id,
+            revoked_at: Utc::now(),
+            reason,
+        };
+
+        let _guard = self.write_lock.lock().await;
+        let entries = {
+            let mut entries = self.entries.write().unwrap_or_else(|e| e.into_inner());
+            entries.push(revoked.clone());
+            entries.clone()
+        };
+        self.changed.send_replace(());
+        self.save(&entries).await?;
+        Ok(revoked)
+    }
+
+    /// Revocations, oldest first.
+    pub fn list(&self) -> Vec<RevokedCertificate> {
+        self.entries
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Notified after each revocation.
+    pub fn subscribe(&self) -> watch::Receiver<()> {
+        self.changed.subscribe()
+    }
+
+    /// Fails if the DER-encoded certificate is revoked, by serial number or
+    /// because its SPIFFE ID or CN names a revoked worker.
+    pub fn check(&self, cert_der: &[u8]) -> Result<(), CertExtractError> {
+        let serial = certificate_serial(cert_der)?;
+        let cn = extract_common_name(cert_der).ok();
+        let worker_id = cert_worker_id(cert_der).ok();
+        let worker_id = worker_id.as_deref().map(without_namespace);
+        let revoked = self
+            .entries
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .any(|r| r.matches(&serial, worker_id));
+        if revoked {
+            return Err(CertExtractError::Revoked(
+                cn.unwrap_or_else(|| format!("serial {}", serial)),
+            ));
+        }
+        Ok(())
+    }
+
+    async fn save(&self, entries: &[RevokedCertificate]) -> Result<(), RevocationError> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let io_err = |source| RevocationError::Io {
+            path: path.clone(),
+            source,
+        };
+
+        let contents = serde_json::to_string_pretty(entries)?;
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            tokio::fs::create_dir_all(parent).await.map_err(io_err)?;
+        }
+        let tmp_path = path.with_extension("json.tmp");
+        tokio::fs::write(&tmp_path, contents)
+            .await
+            .map_err(io_err)?;
+        tokio::fs::rename(&tmp_path, path).await.map_err(io_err)
+    }
+}
+
+/// Serial number of a DER-encoded certificate, as stored in revocations.
+fn certificate_serial(cert_der: &[u8]) -> Result<String, CertExtractError> {
+    let (_, cert) = X509Certificate::from_der(cert_der)
+        .map_err(|e| CertExtractError::ParseError(format!("{:?}", e)))?;
+    Ok(format_serial(cert.raw_serial()))
+}
+
+/// `bytes` as lowercase hex without leading zeros.
+pub(crate) fn format_serial(bytes: &[u8]) -> String {
+    let hex = hex::encode(bytes);
+    match hex.trim_start_matches('0') {
+        "" => "0".to_string(),
+        trimmed => trimmed.to_string(),
+    }
+}
+
+/// Bytes of a stored serial number.
+pub(crate) fn serial_bytes(serial: &str) -> Option<Vec<u8>> {
+    let padded = if serial.len() % 2 == 1 {
+        format!("0{}", serial)
+    } else {
+        serial.to_string()
+    };
+    hex::decode(padded).ok()
+}
+
+fn normalize_serial(serial: &str) -> Result<String, RevocationError> {
+    let hex: String = serial
+        .trim()
+        .trim_start_matches("0x")
+        .chars()
+        .filter(|c| *c != ':')
+        .collect::<String>()
+        .to_lowercase();
+    let bytes = serial_bytes(&hex)
+        .filter(|b| !b.is_empty())
+        .ok_or_else(|| RevocationError::InvalidSerial(serial.to_string()))?;
+    Ok(format_serial(&bytes))
+}
+
+/// `<id>` of a `<id>@<namespace>` worker ID from a CN.
+fn without_namespace(worker_id: &str) -> &str {
+    worker_id.split_once('@').map_or(worker_id, |(id, _)| id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rcgen::{CertificateParams, DnType, KeyPair, SerialNumber};
+
+    fn cert(cn: &str, serial: &[u8]) -> Vec<u8> {
+        let mut params = CertificateParams::default();
+        params.distinguished_name.push(DnType::CommonName, cn);
+        params.serial_number = Some(SerialNumber::from_slice(serial));
+        let key = KeyPair::generate().unwrap();
+        params.self_signed(&key).unwrap().der().to_vec()
+    }
+
+    #[tokio::test]
+    async fn test_revoke_by_serial_and_worker_id() {
+        let path = std::env::temp_dir().join(format!("taskrun-crl-{}.json", std::process::id()));
+        let list = RevocationList::open(Some(path.clone())).unwrap();
+        let first = cert("worker:w-1", &[0x0a, 0xbc]);
+        let second = cert("worker:w-2@team-a", &[0x01]);
+        list.check(&first).unwrap();
+
+        assert!(matches!(
+            list.revoke(None, Some(""), None).await,
+            Err(RevocationError::MissingTarget)
+        ));
+        let revoked = list.revoke(Some("0A:BC"), None, None).await.unwrap();
+        assert_eq!(revoked.serial.as_deref(), Some("abc"));
+        assert!(matches!(
+            list.check(&first),
+            Err(CertExtractError::Revoked(_))
+        ));
+        list.check(&second).unwrap();
+
+        list.revoke(None, Some("worker:w-2"), Some("host retired".into()))
+            .await
+            .unwrap();
+        assert!(list.check(&second).is_err());
+
+        // Kept across restarts
+        let reopened = RevocationList::open(Some(path.clone())).unwrap();
+        assert_eq!(reopened.list(), list.list());
+        std::fs::remove_file(&path).unwrap();
+    }
+}
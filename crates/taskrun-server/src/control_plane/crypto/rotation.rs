@@ -0,0 +1,189 @@
+//! CA rotation with a dual-trust window.
+//!
+//! To rotate the CA, the new CA takes over `--ca-cert`/`--ca-key` and the old
+//! certificate is passed as `--previous-ca-cert`. Until `--previous-ca-until`
+//! client certificates from either CA are accepted, while enrollment signs
+//! with the new one and hands workers both CA certificates to trust. Once the
+//! window ends, certificates issued by the previous CA are refused, and
+//! streams that still use one are closed.
+
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use x509_parser::prelude::*;
+
+use super::ca::CaError;
+use super::cert_extractor::CertExtractError;
+
+/// A CA certificate that is trusted, but no longer signs.
+#[derive(Debug)]
+pub struct PreviousCa {
+    cert_pem: String,
+    cert_der: Vec<u8>,
+    info: CaInfo,
+    trusted_until: DateTime<Utc>,
+}
+
+/// Identifying details of a CA certificate.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CaInfo {
+    pub subject: String,
+    /// SHA-256 of the DER certificate, hex.
+    pub fingerprint: String,
+    pub not_before: DateTime<Utc>,
+    pub not_after: DateTime<Utc>,
+}
+
+/// Where a rotation stands, for `GET /v1/admin/ca`.
+#[derive(Debug, Clone, Serialize)]
+pub struct RotationStatus {
+    /// The CA that signs new worker certificates.
+    pub current: CaInfo,
+    /// The CA being rotated out, if any.
+    pub previous: Option<PreviousCaStatus>,
+    /// Certificates of both CAs are accepted.
+    pub rotating: bool,
+}
+
+/// The CA being rotated out.
+#[derive(Debug, Clone, Serialize)]
+pub struct PreviousCaStatus {
+    #[serde(flatten)]
+    pub info: CaInfo,
+    pub trusted_until: DateTime<Utc>,
+    /// Seconds until its certificates are refused (0 once they are).
+    pub remaining_secs: i64,
+}
+
+impl PreviousCa {
+    /// Load the previous CA certificate, trusted until `trusted_until`.
+    pub fn load(
+        cert_path: impl AsRef<Path>,
+        trusted_until: DateTime<Utc>,
+    ) -> Result<Self, CaError> {
+        let cert_pem = std::fs::read_to_string(cert_path).map_err(CaError::ReadCert)?;
+        let cert_der = ::pem::parse(&cert_pem)
+            .map_err(|e| CaError::ParseCert(e.to_string()))?
+            .into_contents();
+        let info = CaInfo::from_der(&cert_der)?;
+        Ok(Self {
+            cert_pem,
+            cert_der,
+            info,
+            trusted_until,
+        })
+    }
+
+    /// The previous CA certificate in PEM format.
+    pub fn cert_pem(&self) -> &str {
+        &self.cert_pem
+    }
+
+    /// End of the dual-trust window.
+    pub fn trusted_until(&self) -> DateTime<Utc> {
+        self.trusted_until
+    }
+
+    /// Whether its certificates are still accepted.
+    pub fn is_trusted(&self) -> bool {
+        Utc::now() < self.trusted_until
+    }
+
+    /// Whether the DER-encoded certificate was signed by this CA.
+    pub fn issued(&self, cert_der: &[u8]) -> bool {
+        let (Ok((_, ca)), Ok((_, cert))) = (
+            X509Certificate::from_der(&self.cert_der),
+            X509Certificate::from_der(cert_der),
+        ) else {
+            return false;
+        };
+        cert.issuer() == ca.subject() && cert.verify_signature(Some(ca.public_key())).is_ok()
+    }
+
+    /// Fails for a certificate of this CA once the window has ended.
+    pub fn check(&self, cert_der: &[u8]) -> Result<(), CertExtractError> {
+        if !self.is_trusted() && self.issued(cert_der) {
+            return Err(CertExtractError::PreviousCa(
+                self.trusted_until.to_rfc3339(),
+            ));
+        }
+        Ok(())
+    }
+
+    pub(crate) fn status(&self) -> PreviousCaStatus {
+        PreviousCaStatus {
+            info: self.info.clone(),
+            trusted_until: self.trusted_until,
+            remaining_secs: (self.trusted_until - Utc::now()).num_seconds().max(0),
+        }
+    }
+}
+
+impl CaInfo {
+    /// Details of a DER-encoded CA certificate.
+    pub fn from_der(cert_der: &[u8]) -> Result<Self, CaError> {
+        let (_, cert) =
+            X509Certificate::from_der(cert_der).map_err(|e| CaError::ParseCert(e.to_string()))?;
+        let validity = cert.validity();
+        let timestamp = |t: ASN1Time| {
+            DateTime::from_timestamp(t.timestamp(), 0)
+                .ok_or_else(|| CaError::ParseCert("validity out of range".to_string()))
+        };
+        Ok(Self {
+            subject: cert.subject().to_string(),
+            fingerprint: hex::encode(Sha256::digest(cert_der)),
+            not_before: timestamp(validity.not_before)?,
+            not_after: timestamp(validity.not_after)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rcgen::{BasicConstraints, CertificateParams, DnType, IsCa, KeyPair};
+
+    fn ca(cn: &str) -> (rcgen::Certificate, KeyPair) {
+        let mut params = CertificateParams::default();
+        params.distinguished_name.push(DnType::CommonName, cn);
+        params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+        let key = KeyPair::generate().unwrap();
+        (params.self_signed(&key).unwrap(), key)
+    }
+
+    #[test]
+    fn test_previous_ca_refused_after_window() {
+        // Same name, as when a CA is regenerated with the same script
+        let (old_ca, old_key) = ca("TaskRun Dev CA");
+        let (new_ca, new_key) = ca("TaskRun Dev CA");
+        let leaf = |ca: &rcgen::Certificate, key: &KeyPair| {
+            let mut params = CertificateParams::default();
+            params
+                .distinguished_name
+                .push(DnType::CommonName, "worker:w-1");
+            let leaf_key = KeyPair::generate().unwrap();
+            params.signed_by(&leaf_key, ca, key).unwrap().der().to_vec()
+        };
+        let old_leaf = leaf(&old_ca, &old_key);
+        let new_leaf = leaf(&new_ca, &new_key);
+
+        let path = std::env::temp_dir().join(format!("taskrun-prev-ca-{}.crt", std::process::id()));
+        std::fs::write(&path, old_ca.pem()).unwrap();
+        let open = PreviousCa::load(&path, Utc::now() + chrono::Duration::hours(1)).unwrap();
+        let closed = PreviousCa::load(&path, Utc::now() - chrono::Duration::hours(1)).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(closed.issued(&old_leaf));
+        assert!(!closed.issued(&new_leaf));
+        open.check(&old_leaf).unwrap();
+        assert!(matches!(
+            closed.check(&old_leaf),
+            Err(CertExtractError::PreviousCa(_))
+        ));
+        closed.check(&new_leaf).unwrap();
+        assert_eq!(closed.status().remaining_secs, 0);
+        assert_eq!(open.status().info.subject, "CN=TaskRun Dev CA");
+    }
+}
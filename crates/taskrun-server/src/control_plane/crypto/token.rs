@@ -2,17 +2,25 @@
 //!
 //! Bootstrap tokens are used for initial worker enrollment.
 //! They are single-use, time-limited tokens that allow workers
-//! to request a certificate via CSR.
+//! to request a certificate via CSR. Each token enrolls workers of one
+//! namespace, and optionally only one worker ID.
 
 use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
 use chrono::{DateTime, Duration, Utc};
 use rand::RngCore;
+use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
+use taskrun_core::DEFAULT_NAMESPACE;
+
+/// Validity of a bootstrap token when none is requested.
+pub const DEFAULT_VALIDITY_HOURS: u64 = 1;
+
+/// Length of the hash prefix shown as a token's ID.
+const ID_LEN: usize = 12;
 
 /// A bootstrap token stored in the control plane.
 /// We never store the plaintext token - only its SHA-256 hash.
-#[derive(Debug, Clone)]
-#[allow(dead_code)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BootstrapToken {
     /// SHA-256 hash of the token (hex encoded).
     pub token_hash: String,
@@ -25,9 +33,28 @@ pub struct BootstrapToken {
 
     /// Whether the token has been consumed.
     pub consumed: bool,
+
+    /// What the token is for (e.g. the host it was handed to).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    /// Worker that enrolled with the token.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub worker_id: Option<String>,
+
+    /// Namespace the enrolled worker's certificate is bound to.
+    #[serde(default = "default_namespace")]
+    pub namespace: String,
+
+    /// The only worker ID the token enrolls (None = any).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bound_worker_id: Option<String>,
+}
+
+fn default_namespace() -> String {
+    DEFAULT_NAMESPACE.to_string()
 }
 
-#[allow(dead_code)]
 impl BootstrapToken {
     /// Create a new bootstrap token entry from a token hash.
     pub fn new(token_hash: String, validity_hours: u64) -> Self {
@@ -37,14 +64,32 @@ impl BootstrapToken {
             created_at: now,
             expires_at: now + Duration::hours(validity_hours as i64),
             consumed: false,
+            description: None,
+            worker_id: None,
+            namespace: default_namespace(),
+            bound_worker_id: None,
         }
     }
 
+    /// Short ID of the token: the start of its hash.
+    pub fn id(&self) -> &str {
+        &self.token_hash[..ID_LEN.min(self.token_hash.len())]
+    }
+
     /// Check if the token is valid (not expired and not consumed).
     pub fn is_valid(&self) -> bool {
         !self.consumed && Utc::now() < self.expires_at
     }
 
+    /// Whether the token may enroll `worker_id` as named in a certificate
+    /// CN (`<id>` or `<id>@<namespace>`; no namespace means the default one).
+    pub fn admits(&self, worker_id: &str) -> bool {
+        let (id, namespace) = worker_id
+            .rsplit_once('@')
+            .unwrap_or((worker_id, DEFAULT_NAMESPACE));
+        namespace == self.namespace && self.bound_worker_id.as_deref().map_or(true, |b| b == id)
+    }
+
     /// Mark the token as consumed.
     pub fn consume(&mut self) {
         self.consumed = true;
@@ -56,7 +101,6 @@ impl BootstrapToken {
 /// Returns a tuple of (plaintext_token, token_hash).
 /// The plaintext token should be given to the worker admin.
 /// The token_hash should be stored in the control plane.
-#[allow(dead_code)]
 pub fn generate_bootstrap_token() -> (String, String) {
     // Generate 256 bits (32 bytes) of random data
     let mut token_bytes = [0u8; 32];
@@ -106,6 +150,20 @@ mod tests {
         assert!(!token.consumed);
     }
 
+    #[test]
+    fn test_token_admits_only_its_namespace_and_worker() {
+        let mut token = BootstrapToken::new("test_hash".to_string(), 1);
+        assert!(token.admits("w-1"));
+        assert!(token.admits("w-1@default"));
+        assert!(!token.admits("w-1@team-a"));
+
+        token.namespace = "team-a".to_string();
+        token.bound_worker_id = Some("w-1".to_string());
+        assert!(token.admits("w-1@team-a"));
+        assert!(!token.admits("w-1"));
+        assert!(!token.admits("w-2@team-a"));
+    }
+
     #[test]
     fn test_token_consume() {
         let hash = "test_hash".to_string();
@@ -0,0 +1,187 @@
+//! Process and state counters for diagnosing leaks.
+//!
+//! `GET /v1/debug/stats` reports the resident memory of the process, the
+//! tokio task counts and the size of everything the control plane keeps in
+//! memory: tasks, runs, workers, stored output, run stream channels and
+//! their subscribers. A long-running deployment whose numbers keep growing
+//! while its load does not is leaking. The server TUI footer shows a compact
+//! readout, refreshed every few seconds.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::Serialize;
+
+use crate::control_plane::state::{AppState, UiNotification};
+
+/// How often the server TUI's readout is refreshed.
+const PUBLISH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Tokio runtime counters.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct RuntimeStats {
+    /// Worker threads of the runtime.
+    pub workers: usize,
+    /// Tasks spawned and not yet finished.
+    pub alive_tasks: usize,
+    /// Tasks waiting in the runtime's global queue.
+    pub global_queue_depth: usize,
+}
+
+impl RuntimeStats {
+    /// Counters of the current runtime, if any.
+    pub fn current() -> Option<Self> {
+        let metrics = tokio::runtime::Handle::try_current().ok()?.metrics();
+        Some(Self {
+            workers: metrics.num_workers(),
+            alive_tasks: metrics.num_alive_tasks(),
+            global_queue_depth: metrics.global_queue_depth(),
+        })
+    }
+}
+
+/// Snapshot of the process and control plane state.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct DebugStats {
+    /// Resident set size (None where it cannot be read).
+    pub rss_bytes: Option<u64>,
+    pub runtime: Option<RuntimeStats>,
+    pub tasks: usize,
+    pub runs: usize,
+    pub workers: usize,
+    /// Runs with stored output.
+    pub output_runs: usize,
+    pub output_bytes: u64,
+    /// Runs with chat history.
+    pub chat_runs: usize,
+    /// Open run stream channels.
+    pub stream_channels: usize,
+    /// Receivers subscribed to the run stream channels.
+    pub stream_subscribers: usize,
+    /// Receivers of UI notifications (server TUI, `/v1/watch` clients).
+    pub ui_subscribers: usize,
+}
+
+impl DebugStats {
+    /// One-line readout for the server TUI footer.
+    pub fn summary(&self) -> String {
+        let rss = self.rss_bytes.map_or_else(|| "?".to_string(), format_mib);
+        let tokio_tasks = self
+            .runtime
+            .map_or_else(|| "?".to_string(), |r| r.alive_tasks.to_string());
+        format!(
+            "RSS {} | tokio {} | runs {} | out {} | streams {}/{}",
+            rss,
+            tokio_tasks,
+            self.runs,
+            format_mib(self.output_bytes),
+            self.stream_channels,
+            self.stream_subscribers
+        )
+    }
+}
+
+fn format_mib(bytes: u64) -> String {
+    format!("{:.1}M", bytes as f64 / (1024.0 * 1024.0))
+}
+
+/// Collect the current stats.
+pub async fn collect(state: &AppState) -> DebugStats {
+    let (tasks, runs) = {
+        let tasks = state.tasks.read().await;
+        (tasks.len(), tasks.values().map(|t| t.runs.len()).sum())
+    };
+    let workers = state.workers.read().await.len();
+    let run_ids = state.outputs.run_ids().await;
+    let mut output_bytes = 0;
+    for run_id in &run_ids {
+        output_bytes += state.outputs.len(run_id).await.unwrap_or(0);
+    }
+    let chat_runs = state.chat_messages.read().await.len();
+    let (stream_channels, stream_subscribers) = {
+        let channels = state.stream_channels.read().await;
+        (
+            channels.len(),
+            channels.values().map(|tx| tx.receiver_count()).sum(),
+        )
+    };
+
+    DebugStats {
+        rss_bytes: rss_bytes(),
+        runtime: RuntimeStats::current(),
+        tasks,
+        runs,
+        workers,
+        output_runs: run_ids.len(),
+        output_bytes,
+        chat_runs,
+        stream_channels,
+        stream_subscribers,
+        ui_subscribers: state.ui_tx.as_ref().map_or(0, |tx| tx.receiver_count()),
+    }
+}
+
+/// Resident set size of this process, from `/proc/self/status`.
+#[cfg(target_os = "linux")]
+fn rss_bytes() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    parse_vm_rss(&status)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn rss_bytes() -> Option<u64> {
+    None
+}
+
+#[cfg_attr(not(any(target_os = "linux", test)), allow(dead_code))]
+fn parse_vm_rss(status: &str) -> Option<u64> {
+    let line = status.lines().find(|l| l.starts_with("VmRSS:"))?;
+    let kib: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kib * 1024)
+}
+
+/// Send the stats to the UI periodically.
+pub async fn publish_loop(state: Arc<AppState>) {
+    let mut interval = tokio::time::interval(PUBLISH_INTERVAL);
+    loop {
+        interval.tick().await;
+        let stats = collect(&state).await;
+        state.notify_ui(UiNotification::DebugStats { stats });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use taskrun_core::{RunId, RunSummary, Task, WorkerId};
+
+    #[tokio::test]
+    async fn test_stats_count_state() {
+        let state = AppState::new();
+        let mut task = Task::new("general", "{}", "test");
+        task.runs.push(RunSummary::new(WorkerId::generate()));
+        let run_id = task.runs[0].run_id.clone();
+        state.tasks.write().await.insert(task.id.clone(), task);
+        state.outputs.append(&run_id, 0, "hello").await;
+        let _receiver = state
+            .get_or_create_stream_channel(&RunId::generate())
+            .await
+            .subscribe();
+
+        let stats = collect(&state).await;
+        assert_eq!(stats.tasks, 1);
+        assert_eq!(stats.runs, 1);
+        assert_eq!(stats.output_runs, 1);
+        assert_eq!(stats.output_bytes, 5);
+        assert_eq!(stats.stream_channels, 1);
+        assert_eq!(stats.stream_subscribers, 1);
+        assert!(stats.runtime.is_some());
+    }
+
+    #[test]
+    fn test_rss_is_parsed_from_proc_status() {
+        let status = "Name:\ttaskrun-server\nVmPeak:\t  300000 kB\nVmRSS:\t   51200 kB\n";
+        assert_eq!(parse_vm_rss(status), Some(50 * 1024 * 1024));
+        assert_eq!(parse_vm_rss("Name:\tx\n"), None);
+    }
+}
@@ -0,0 +1,175 @@
+//! Server-initiated worker disconnect.
+//!
+//! The worker is removed from the pool, sent a `CancelRun` for each active
+//! run and a final `WorkerDisconnect` with the reason, then its stream is
+//! closed. Its active runs fail as `worker_lost`; with `reschedule`, their
+//! tasks go back to Pending for other workers. Workers reconnect on their
+//! own, so this is not a ban - revoke the worker's certificate for that.
+
+use std::sync::Arc;
+
+use serde::Serialize;
+use tracing::{info, warn};
+
+use taskrun_core::{FailureReason, RunId, RunStatus, TaskId, WorkerId};
+use taskrun_proto::pb::run_server_message::Payload as ServerPayload;
+use taskrun_proto::pb::{CancelRun, RunServerMessage, WorkerDisconnect};
+
+use crate::control_plane::scheduler::Scheduler;
+use crate::control_plane::state::AppState;
+
+/// What a disconnect did, as returned to the caller and audited.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DisconnectOutcome {
+    pub worker_id: WorkerId,
+    pub reason: String,
+    /// Runs that were executing on the worker, now failed.
+    pub failed_run_ids: Vec<RunId>,
+    /// Tasks of those runs that went back to Pending.
+    pub rescheduled_task_ids: Vec<TaskId>,
+}
+
+/// Disconnect a worker. Returns None if it is not connected.
+pub async fn disconnect_worker(
+    state: &Arc<AppState>,
+    worker_id: &WorkerId,
+    reason: &str,
+    reschedule: bool,
+) -> Option<DisconnectOutcome> {
+    // Out of the pool first, so rescheduled tasks go elsewhere
+    let worker = state.workers.write().await.remove(worker_id)?;
+
+    let active_runs: Vec<(TaskId, RunId)> = state
+        .tasks
+        .read()
+        .await
+        .values()
+        .flat_map(|t| t.runs.iter().map(move |r| (t, r)))
+        .filter(|(_, r)| &r.worker_id == worker_id && r.status.is_active())
+        .map(|(t, r)| (t.id.clone(), r.run_id.clone()))
+        .collect();
+
+    let mut messages: Vec<RunServerMessage> = active_runs
+        .iter()
+        .map(|(_, run_id)| RunServerMessage {
+            payload: Some(ServerPayload::CancelRun(CancelRun {
+                run_id: run_id.to_string(),
+                reason: format!("Worker disconnected: {}", reason),
+            })),
+        })
+        .collect();
+    messages.push(RunServerMessage {
+        payload: Some(ServerPayload::Disconnect(WorkerDisconnect {
+            reason: reason.to_string(),
+        })),
+    });
+    for message in messages {
+        if let Err(e) = worker.tx.send(message).await {
+            warn!(worker_id = %worker_id, error = %e, "Worker stream already closed");
+            break;
+        }
+    }
+    // Queued messages are still delivered before the stream ends
+    worker.shutdown.cancel();
+
+    let mut outcome = DisconnectOutcome {
+        worker_id: worker_id.clone(),
+        reason: reason.to_string(),
+        failed_run_ids: Vec::new(),
+        rescheduled_task_ids: Vec::new(),
+    };
+    let scheduler = Scheduler::new(state.clone());
+    for (task_id, run_id) in active_runs {
+        if !state
+            .finalize_run(
+                &run_id,
+                RunStatus::Failed,
+                Some(FailureReason::WorkerLost),
+                Some(format!("Worker {} disconnected: {}", worker_id, reason)),
+            )
+            .await
+        {
+            continue;
+        }
+        outcome.failed_run_ids.push(run_id);
+
+        if reschedule {
+            match scheduler.retry_task(&task_id).await {
+                Ok(_) => outcome.rescheduled_task_ids.push(task_id),
+                Err(e) => warn!(task_id = %task_id, error = %e, "Failed to reschedule task"),
+            }
+        }
+    }
+
+    info!(
+        worker_id = %worker_id,
+        reason,
+        failed_runs = outcome.failed_run_ids.len(),
+        rescheduled = outcome.rescheduled_task_ids.len(),
+        "Worker disconnected by the control plane"
+    );
+    Some(outcome)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::control_plane::state::ConnectedWorker;
+    use chrono::Utc;
+    use taskrun_core::{AgentSpec, Task, TaskStatus, WorkerInfo, WorkerStatus};
+    use tokio::sync::mpsc;
+    use tokio_util::sync::CancellationToken;
+
+    #[tokio::test]
+    async fn test_disconnect_fails_and_reschedules_runs() {
+        let state = AppState::new();
+        let worker_id = WorkerId::generate();
+        let (tx, mut rx) = mpsc::channel(8);
+        let shutdown = CancellationToken::new();
+        state.workers.write().await.insert(
+            worker_id.clone(),
+            ConnectedWorker {
+                info: WorkerInfo::new(worker_id.clone(), "host")
+                    .with_agent(AgentSpec::new("general")),
+                status: WorkerStatus::Idle,
+                active_runs: 0,
+                max_concurrent_runs: 4,
+                interactive_slots: 0,
+                last_heartbeat: Utc::now(),
+                draining_since: None,
+                tx,
+                shutdown: shutdown.clone(),
+            },
+        );
+
+        let task = Task::new("general", "{}", "test");
+        let task_id = task.id.clone();
+        state.tasks.write().await.insert(task_id.clone(), task);
+        let run_id = Scheduler::new(state.clone())
+            .assign_task(&task_id)
+            .await
+            .unwrap();
+        rx.recv().await.unwrap(); // the assignment
+
+        let outcome = disconnect_worker(&state, &worker_id, "maintenance", true)
+            .await
+            .unwrap();
+        assert_eq!(outcome.failed_run_ids, vec![run_id]);
+        assert_eq!(outcome.rescheduled_task_ids, vec![task_id.clone()]);
+        assert!(shutdown.is_cancelled());
+        assert!(state.workers.read().await.is_empty());
+
+        // No other worker: the task waits in the queue
+        let tasks = state.tasks.read().await;
+        assert_eq!(tasks[&task_id].status, TaskStatus::Pending);
+
+        assert!(matches!(
+            rx.recv().await.unwrap().payload,
+            Some(ServerPayload::CancelRun(_))
+        ));
+        let Some(ServerPayload::Disconnect(disconnect)) = rx.recv().await.unwrap().payload else {
+            panic!("expected WorkerDisconnect");
+        };
+        assert_eq!(disconnect.reason, "maintenance");
+    }
+}
@@ -0,0 +1,147 @@
+//! Worker draining (maintenance mode).
+//!
+//! A draining worker is assigned no new runs but finishes the ones it has.
+//! The flag belongs to the control plane: heartbeats reporting Idle or Busy
+//! do not clear it. It ends with `UndrainWorker`, or when the worker
+//! reconnects (e.g. after the maintenance it was drained for).
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tracing::info;
+
+use taskrun_core::{RunId, WorkerId, WorkerStatus};
+
+use crate::control_plane::state::{AppState, UiNotification};
+
+/// How far a worker is with draining.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DrainProgress {
+    pub worker_id: WorkerId,
+    /// When draining started (None = not draining).
+    pub draining_since: Option<DateTime<Utc>>,
+    /// Runs the worker is still executing.
+    pub active_run_ids: Vec<RunId>,
+}
+
+impl DrainProgress {
+    /// Draining and no run left: safe to take the worker down.
+    pub fn drained(&self) -> bool {
+        self.draining_since.is_some() && self.active_run_ids.is_empty()
+    }
+}
+
+/// Start or stop draining a worker. Returns None if it is not connected.
+pub async fn set_draining(
+    state: &AppState,
+    worker_id: &WorkerId,
+    draining: bool,
+) -> Option<DrainProgress> {
+    let status = {
+        let mut workers = state.workers.write().await;
+        let worker = workers.get_mut(worker_id)?;
+        if draining {
+            worker.draining_since.get_or_insert_with(Utc::now);
+            if worker.status.can_accept_runs() {
+                worker.status = WorkerStatus::Draining;
+            }
+        } else {
+            worker.draining_since = None;
+            if worker.status == WorkerStatus::Draining {
+                // The next heartbeat reports the real one
+                worker.status = if worker.active_runs > 0 {
+                    WorkerStatus::Busy
+                } else {
+                    WorkerStatus::Idle
+                };
+            }
+        }
+        worker.status
+    };
+
+    info!(worker_id = %worker_id, draining, "Worker drain state changed");
+    state.notify_ui(UiNotification::WorkerStatusChanged {
+        worker_id: worker_id.clone(),
+        status,
+    });
+    progress(state, worker_id).await
+}
+
+/// Drain progress of a worker. Returns None if it is not connected.
+pub async fn progress(state: &AppState, worker_id: &WorkerId) -> Option<DrainProgress> {
+    let draining_since = state.workers.read().await.get(worker_id)?.draining_since;
+    let active_run_ids = state
+        .tasks
+        .read()
+        .await
+        .values()
+        .flat_map(|t| &t.runs)
+        .filter(|r| &r.worker_id == worker_id && !r.status.is_terminal())
+        .map(|r| r.run_id.clone())
+        .collect();
+    Some(DrainProgress {
+        worker_id: worker_id.clone(),
+        draining_since,
+        active_run_ids,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::control_plane::scheduler::{Scheduler, SchedulerError};
+    use crate::control_plane::state::ConnectedWorker;
+    use taskrun_core::{AgentSpec, RunStatus, Task, WorkerInfo};
+    use tokio::sync::mpsc;
+
+    #[tokio::test]
+    async fn test_draining_worker_gets_no_runs() {
+        let state = AppState::new();
+        let worker_id = WorkerId::generate();
+        let (tx, _rx) = mpsc::channel(8);
+        state.workers.write().await.insert(
+            worker_id.clone(),
+            ConnectedWorker {
+                info: WorkerInfo::new(worker_id.clone(), "host")
+                    .with_agent(AgentSpec::new("general")),
+                status: WorkerStatus::Idle,
+                active_runs: 0,
+                max_concurrent_runs: 4,
+                interactive_slots: 0,
+                last_heartbeat: Utc::now(),
+                draining_since: None,
+                tx,
+                shutdown: tokio_util::sync::CancellationToken::new(),
+            },
+        );
+        let scheduler = Scheduler::new(state.clone());
+
+        let running = Task::new("general", "{}", "test");
+        let running_id = running.id.clone();
+        state
+            .tasks
+            .write()
+            .await
+            .insert(running_id.clone(), running);
+        let run_id = scheduler.assign_task(&running_id).await.unwrap();
+
+        let started = set_draining(&state, &worker_id, true).await.unwrap();
+        assert_eq!(started.active_run_ids, vec![run_id]);
+        assert!(!started.drained());
+
+        let queued = Task::new("general", "{}", "test");
+        let queued_id = queued.id.clone();
+        state.tasks.write().await.insert(queued_id.clone(), queued);
+        assert!(matches!(
+            scheduler.assign_task(&queued_id).await,
+            Err(SchedulerError::NoWorkersAvailable(_))
+        ));
+
+        // The active run finishes: drained
+        state.tasks.write().await.get_mut(&running_id).unwrap().runs[0].status =
+            RunStatus::Completed;
+        assert!(progress(&state, &worker_id).await.unwrap().drained());
+
+        set_draining(&state, &worker_id, false).await.unwrap();
+        assert!(scheduler.assign_task(&queued_id).await.is_ok());
+    }
+}
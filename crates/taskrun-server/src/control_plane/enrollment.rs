@@ -0,0 +1,211 @@
+//! Worker enrollment with bootstrap tokens.
+//!
+//! An admin creates a single-use bootstrap token (`taskrun token create`)
+//! and hands it to the worker out of band. The worker posts it with a CSR to
+//! `/v1/enroll`; the control plane checks the token's hash, signs the CSR
+//! with its CA and marks the token used. A token enrolls workers of one
+//! namespace (and, if given, one worker ID): the CSR's CN must match them.
+//! Only token hashes are stored.
+
+use std::sync::Arc;
+
+use thiserror::Error;
+use tracing::{info, warn};
+
+use crate::control_plane::crypto::{
+    generate_bootstrap_token, hash_token, BootstrapToken, CaError, SignedCertificate,
+    DEFAULT_VALIDITY_HOURS,
+};
+use crate::control_plane::state::{resolve_prefix, AppState, IdLookupError};
+
+/// Enrollment errors.
+#[derive(Debug, Error)]
+pub enum EnrollError {
+    #[error("Certificate authority not configured")]
+    NoCa,
+
+    #[error("Invalid or expired bootstrap token")]
+    InvalidToken,
+
+    #[error("Bootstrap token already used")]
+    TokenUsed,
+
+    #[error("Failed to sign CSR: {0}")]
+    Csr(#[from] CaError),
+
+    #[error("Bootstrap token does not enroll worker '{0}'")]
+    IdentityMismatch(String),
+}
+
+/// Create a bootstrap token valid for `validity_hours` (default: one hour)
+/// that enrolls workers of `namespace`, or only `worker_id` if given.
+/// Returns the plaintext token, which is not stored, and its record.
+pub async fn create_token(
+    state: &AppState,
+    description: Option<String>,
+    validity_hours: Option<u64>,
+    namespace: String,
+    worker_id: Option<String>,
+) -> (String, BootstrapToken) {
+    let (plaintext, token_hash) = generate_bootstrap_token();
+    let mut token = BootstrapToken::new(
+        token_hash.clone(),
+        validity_hours.unwrap_or(DEFAULT_VALIDITY_HOURS),
+    );
+    token.description = description;
+    token.namespace = namespace;
+    token.bound_worker_id = worker_id;
+    state
+        .bootstrap_tokens
+        .write()
+        .await
+        .insert(token_hash, token.clone());
+    info!(token_id = %token.id(), expires_at = %token.expires_at, "Bootstrap token created");
+    (plaintext, token)
+}
+
+/// Bootstrap tokens, oldest first.
+pub async fn list_tokens(state: &AppState) -> Vec<BootstrapToken> {
+    let mut tokens: Vec<BootstrapToken> = state
+        .bootstrap_tokens
+        .read()
+        .await
+        .values()
+        .cloned()
+        .collect();
+    tokens.sort_by_key(|t| t.created_at);
+    tokens
+}
+
+/// Revoke a bootstrap token by ID (or unique prefix). Workers that already
+/// enrolled with it keep their certificates.
+pub async fn revoke_token(state: &AppState, id: &str) -> Result<BootstrapToken, IdLookupError> {
+    let mut tokens = state.bootstrap_tokens.write().await;
+    let hash = resolve_prefix("Bootstrap token", id, tokens.keys().map(String::as_str))?;
+    let token = tokens.remove(&hash).expect("resolved token exists");
+    info!(token_id = %token.id(), "Bootstrap token revoked");
+    Ok(token)
+}
+
+/// Sign `csr` for the holder of `bootstrap_token`, consuming the token.
+/// A token is only used up by a successful enrollment.
+pub async fn enroll(
+    state: &Arc<AppState>,
+    bootstrap_token: &str,
+    csr: &str,
+) -> Result<(SignedCertificate, BootstrapToken), EnrollError> {
+    let ca = state.ca.as_ref().ok_or(EnrollError::NoCa)?;
+    let token_hash = hash_token(bootstrap_token);
+
+    let mut tokens = state.bootstrap_tokens.write().await;
+    let token = tokens
+        .get_mut(&token_hash)
+        .ok_or(EnrollError::InvalidToken)?;
+    if token.consumed {
+        warn!(token_id = %token.id(), "Bootstrap token reused");
+        return Err(EnrollError::TokenUsed);
+    }
+    if !token.is_valid() {
+        warn!(token_id = %token.id(), "Expired bootstrap token");
+        return Err(EnrollError::InvalidToken);
+    }
+
+    // Signing stores nothing, so a certificate for another worker is
+    // simply dropped
+    let signed = ca.sign_csr(csr)?;
+    if !token.admits(&signed.worker_id) {
+        warn!(token_id = %token.id(), worker_id = %signed.worker_id, "Bootstrap token used for another worker");
+        return Err(EnrollError::IdentityMismatch(signed.worker_id));
+    }
+    token.consume();
+    token.worker_id = Some(signed.worker_id.clone());
+    info!(token_id = %token.id(), worker_id = %signed.worker_id, "Bootstrap token consumed");
+    Ok((signed, token.clone()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_tokens_are_listed_and_revoked_by_id() {
+        let state = AppState::new();
+        let (plaintext, first) = create_token(
+            &state,
+            Some("build-01".into()),
+            None,
+            "default".into(),
+            None,
+        )
+        .await;
+        let (_, second) = create_token(&state, None, Some(24), "default".into(), None).await;
+        assert_eq!(hash_token(&plaintext), first.token_hash);
+        assert_eq!(first.id().len(), 12);
+
+        let tokens = list_tokens(&state).await;
+        assert_eq!(tokens.len(), 2);
+        assert!(tokens
+            .iter()
+            .any(|t| t.description.as_deref() == Some("build-01")));
+
+        let revoked = revoke_token(&state, second.id()).await.unwrap();
+        assert_eq!(revoked.token_hash, second.token_hash);
+        assert!(revoke_token(&state, second.id()).await.is_err());
+        assert_eq!(list_tokens(&state).await.len(), 1);
+
+        // Without a CA nothing can be signed, and the token is kept
+        assert!(matches!(
+            enroll(&state, &plaintext, "").await,
+            Err(EnrollError::NoCa)
+        ));
+        assert!(!list_tokens(&state).await[0].consumed);
+    }
+
+    #[tokio::test]
+    async fn test_token_only_enrolls_its_namespace_and_worker() {
+        use crate::control_plane::crypto::CertificateAuthority;
+        use rcgen::{CertificateParams, DnType, IsCa, KeyPair};
+
+        let mut ca_params = CertificateParams::default();
+        ca_params
+            .distinguished_name
+            .push(DnType::CommonName, "Test CA");
+        ca_params.is_ca = IsCa::Ca(rcgen::BasicConstraints::Unconstrained);
+        let ca_key_pair = KeyPair::generate().unwrap();
+        let ca_cert = ca_params.self_signed(&ca_key_pair).unwrap();
+        let dir = std::env::temp_dir().join(format!("taskrun-enrollment-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("ca.crt"), ca_cert.pem()).unwrap();
+        std::fs::write(dir.join("ca.key"), ca_key_pair.serialize_pem()).unwrap();
+        let ca =
+            CertificateAuthority::from_files(dir.join("ca.crt"), dir.join("ca.key"), 7).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        let state = AppState::with_ca(ca);
+
+        let csr = |cn: &str| {
+            let mut params = CertificateParams::default();
+            params.distinguished_name.push(DnType::CommonName, cn);
+            params
+                .serialize_request(&KeyPair::generate().unwrap())
+                .unwrap()
+                .pem()
+                .unwrap()
+        };
+        let (plaintext, _) =
+            create_token(&state, None, None, "team-a".into(), Some("w-1".into())).await;
+
+        for cn in ["worker:w-2@team-a", "worker:w-1", "worker:w-1@team-b"] {
+            assert!(matches!(
+                enroll(&state, &plaintext, &csr(cn)).await,
+                Err(EnrollError::IdentityMismatch(_))
+            ));
+        }
+        assert!(!list_tokens(&state).await[0].consumed);
+
+        let (signed, token) = enroll(&state, &plaintext, &csr("worker:w-1@team-a"))
+            .await
+            .unwrap();
+        assert_eq!(signed.worker_id, "w-1@team-a");
+        assert!(token.consumed);
+    }
+}
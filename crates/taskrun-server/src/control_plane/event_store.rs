@@ -0,0 +1,437 @@
+//! Pluggable storage for run events.
+//!
+//! The control plane records every `RunEvent` it receives from workers. The
+//! `EventStore` trait abstracts where those events live so that single-node
+//! deployments can keep history across restarts without pulling in a database:
+//!
+//! - `InMemoryEventStore`: reference implementation, lost on restart.
+//! - `JsonlEventStore`: append-only JSON Lines file, replayed on startup.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use thiserror::Error;
+use tokio::fs::{File, OpenOptions};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::{Mutex, RwLock};
+use tracing::warn;
+
+use taskrun_core::{RunEvent, RunId, TaskId};
+
+/// Event store errors.
+#[derive(Debug, Error)]
+pub enum EventStoreError {
+    #[error("I/O error on event store '{path}': {source}")]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+
+    #[error("Failed to serialize event: {0}")]
+    Serialization(#[from] serde_json::Error),
+}
+
+pub type Result<T> = std::result::Result<T, EventStoreError>;
+
+/// Storage backend for run events.
+#[async_trait]
+pub trait EventStore: Send + Sync {
+    /// Append an event to the store.
+    async fn append(&self, event: RunEvent) -> Result<()>;
+
+    /// List all events for a run, in insertion order.
+    async fn list_by_run(&self, run_id: &RunId) -> Result<Vec<RunEvent>>;
+
+    /// List all events for a task (across all runs), sorted by timestamp.
+    async fn list_by_task(&self, task_id: &TaskId) -> Result<Vec<RunEvent>>;
+
+    /// Remove events older than the given Unix timestamp (milliseconds).
+    /// Returns the number of events removed.
+    async fn prune(&self, older_than_ms: i64) -> Result<usize>;
+
+    /// Remove every event of the given runs.
+    /// Returns the number of events removed.
+    async fn remove_runs(&self, run_ids: &HashSet<RunId>) -> Result<usize>;
+}
+
+/// Which event store backend to use.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum EventStoreConfig {
+    /// Keep events in memory only.
+    #[default]
+    Memory,
+    /// Persist events to an append-only JSONL file.
+    File { path: PathBuf },
+}
+
+impl EventStoreConfig {
+    /// Build the configured event store.
+    pub async fn build(&self) -> Result<Arc<dyn EventStore>> {
+        match self {
+            EventStoreConfig::Memory => Ok(Arc::new(InMemoryEventStore::new())),
+            EventStoreConfig::File { path } => Ok(Arc::new(JsonlEventStore::open(path).await?)),
+        }
+    }
+}
+
+// ============================================================================
+// In-memory store
+// ============================================================================
+
+/// Event store backed by a `HashMap` indexed by RunId.
+#[derive(Default)]
+pub struct InMemoryEventStore {
+    events: RwLock<HashMap<RunId, Vec<RunEvent>>>,
+}
+
+impl InMemoryEventStore {
+    /// Create an empty in-memory store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Snapshot all events, ordered by timestamp.
+    async fn all(&self) -> Vec<RunEvent> {
+        let events = self.events.read().await;
+        let mut result: Vec<RunEvent> = events.values().flatten().cloned().collect();
+        result.sort_by_key(|e| e.timestamp_ms);
+        result
+    }
+}
+
+#[async_trait]
+impl EventStore for InMemoryEventStore {
+    async fn append(&self, event: RunEvent) -> Result<()> {
+        let mut events = self.events.write().await;
+        events.entry(event.run_id.clone()).or_default().push(event);
+        Ok(())
+    }
+
+    async fn list_by_run(&self, run_id: &RunId) -> Result<Vec<RunEvent>> {
+        let events = self.events.read().await;
+        Ok(events.get(run_id).cloned().unwrap_or_default())
+    }
+
+    async fn list_by_task(&self, task_id: &TaskId) -> Result<Vec<RunEvent>> {
+        let events = self.events.read().await;
+        let mut result: Vec<RunEvent> = events
+            .values()
+            .flatten()
+            .filter(|e| &e.task_id == task_id)
+            .cloned()
+            .collect();
+        result.sort_by_key(|e| e.timestamp_ms);
+        Ok(result)
+    }
+
+    async fn prune(&self, older_than_ms: i64) -> Result<usize> {
+        let mut events = self.events.write().await;
+        let mut removed = 0;
+        events.retain(|_, run_events| {
+            let before = run_events.len();
+            run_events.retain(|e| e.timestamp_ms >= older_than_ms);
+            removed += before - run_events.len();
+            !run_events.is_empty()
+        });
+        Ok(removed)
+    }
+
+    async fn remove_runs(&self, run_ids: &HashSet<RunId>) -> Result<usize> {
+        let mut events = self.events.write().await;
+        Ok(run_ids
+            .iter()
+            .filter_map(|run_id| events.remove(run_id))
+            .map(|run_events| run_events.len())
+            .sum())
+    }
+}
+
+// ============================================================================
+// JSONL file store
+// ============================================================================
+
+/// Event store that appends each event as one JSON line to a file.
+///
+/// The file is replayed into an in-memory index on open, so reads never touch
+/// disk. Pruning rewrites the file atomically (write to a temp file, then rename).
+pub struct JsonlEventStore {
+    path: PathBuf,
+    index: InMemoryEventStore,
+    file: Mutex<File>,
+}
+
+impl JsonlEventStore {
+    /// Open (or create) a JSONL event store at `path`, loading existing events.
+    pub async fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let io_err = |source| EventStoreError::Io {
+            path: path.clone(),
+            source,
+        };
+
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            tokio::fs::create_dir_all(parent).await.map_err(io_err)?;
+        }
+
+        let index = InMemoryEventStore::new();
+        match tokio::fs::read_to_string(&path).await {
+            Ok(contents) => {
+                for (line_no, line) in contents.lines().enumerate() {
+                    if line.trim().is_empty() {
+                        continue;
+                    }
+                    match serde_json::from_str::<RunEvent>(line) {
+                        Ok(event) => index.append(event).await?,
+                        Err(e) => warn!(
+                            path = %path.display(),
+                            line = line_no + 1,
+                            error = %e,
+                            "Skipping malformed event line"
+                        ),
+                    }
+                }
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(io_err(e)),
+        }
+
+        let file = Self::open_for_append(&path).await?;
+
+        Ok(Self {
+            path,
+            index,
+            file: Mutex::new(file),
+        })
+    }
+
+    async fn open_for_append(path: &Path) -> Result<File> {
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .await
+            .map_err(|source| EventStoreError::Io {
+                path: path.to_path_buf(),
+                source,
+            })
+    }
+
+    /// Rewrite the file from the index, returning a fresh append handle.
+    /// Callers must hold the file lock.
+    async fn rewrite(&self) -> Result<File> {
+        let mut contents = String::new();
+        for event in self.index.all().await {
+            contents.push_str(&serde_json::to_string(&event)?);
+            contents.push('\n');
+        }
+
+        let tmp_path = self.path.with_extension("jsonl.tmp");
+        tokio::fs::write(&tmp_path, contents)
+            .await
+            .map_err(|e| self.io_err(e))?;
+        tokio::fs::rename(&tmp_path, &self.path)
+            .await
+            .map_err(|e| self.io_err(e))?;
+
+        Self::open_for_append(&self.path).await
+    }
+
+    fn io_err(&self, source: std::io::Error) -> EventStoreError {
+        EventStoreError::Io {
+            path: self.path.clone(),
+            source,
+        }
+    }
+}
+
+#[async_trait]
+impl EventStore for JsonlEventStore {
+    async fn append(&self, event: RunEvent) -> Result<()> {
+        let mut line = serde_json::to_string(&event)?;
+        line.push('\n');
+
+        // Index the event before releasing the file lock: a prune or
+        // remove_runs in between would rewrite the file without it
+        let mut file = self.file.lock().await;
+        file.write_all(line.as_bytes())
+            .await
+            .map_err(|e| self.io_err(e))?;
+        file.flush().await.map_err(|e| self.io_err(e))?;
+        self.index.append(event).await
+    }
+
+    async fn list_by_run(&self, run_id: &RunId) -> Result<Vec<RunEvent>> {
+        self.index.list_by_run(run_id).await
+    }
+
+    async fn list_by_task(&self, task_id: &TaskId) -> Result<Vec<RunEvent>> {
+        self.index.list_by_task(task_id).await
+    }
+
+    async fn prune(&self, older_than_ms: i64) -> Result<usize> {
+        // Hold the file lock for the whole rewrite so no append is lost
+        let mut file = self.file.lock().await;
+
+        let removed = self.index.prune(older_than_ms).await?;
+        if removed > 0 {
+            *file = self.rewrite().await?;
+        }
+        Ok(removed)
+    }
+
+    async fn remove_runs(&self, run_ids: &HashSet<RunId>) -> Result<usize> {
+        let mut file = self.file.lock().await;
+
+        let removed = self.index.remove_runs(run_ids).await?;
+        if removed > 0 {
+            *file = self.rewrite().await?;
+        }
+        Ok(removed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use taskrun_core::EventId;
+
+    fn event_at(run_id: &str, task_id: &str, timestamp_ms: i64) -> RunEvent {
+        let mut event = RunEvent::execution_started(RunId::new(run_id), TaskId::new(task_id));
+        event.timestamp_ms = timestamp_ms;
+        event
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_append_list_prune() {
+        let store = InMemoryEventStore::new();
+        store
+            .append(event_at("run-1", "task-1", 100))
+            .await
+            .unwrap();
+        store
+            .append(event_at("run-1", "task-1", 200))
+            .await
+            .unwrap();
+        store
+            .append(event_at("run-2", "task-1", 150))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            store.list_by_run(&RunId::new("run-1")).await.unwrap().len(),
+            2
+        );
+
+        let by_task = store.list_by_task(&TaskId::new("task-1")).await.unwrap();
+        let timestamps: Vec<i64> = by_task.iter().map(|e| e.timestamp_ms).collect();
+        assert_eq!(timestamps, vec![100, 150, 200]);
+
+        assert_eq!(store.prune(160).await.unwrap(), 2);
+        assert!(store
+            .list_by_run(&RunId::new("run-2"))
+            .await
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            store.list_by_run(&RunId::new("run-1")).await.unwrap().len(),
+            1
+        );
+
+        let run_ids = HashSet::from([RunId::new("run-1"), RunId::new("run-3")]);
+        assert_eq!(store.remove_runs(&run_ids).await.unwrap(), 1);
+        assert!(store.all().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_jsonl_survives_reopen_and_prune() {
+        let path =
+            std::env::temp_dir().join(format!("taskrun-events-{}.jsonl", EventId::generate()));
+
+        {
+            let store = JsonlEventStore::open(&path).await.unwrap();
+            store
+                .append(event_at("run-1", "task-1", 100))
+                .await
+                .unwrap();
+            store
+                .append(event_at("run-1", "task-1", 200))
+                .await
+                .unwrap();
+        }
+
+        let store = JsonlEventStore::open(&path).await.unwrap();
+        assert_eq!(
+            store.list_by_run(&RunId::new("run-1")).await.unwrap().len(),
+            2
+        );
+
+        assert_eq!(store.prune(150).await.unwrap(), 1);
+        store
+            .append(event_at("run-1", "task-1", 300))
+            .await
+            .unwrap();
+        drop(store);
+
+        let store = JsonlEventStore::open(&path).await.unwrap();
+        let timestamps: Vec<i64> = store
+            .list_by_run(&RunId::new("run-1"))
+            .await
+            .unwrap()
+            .iter()
+            .map(|e| e.timestamp_ms)
+            .collect();
+        assert_eq!(timestamps, vec![200, 300]);
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn test_jsonl_append_racing_prune_is_kept() {
+        let path =
+            std::env::temp_dir().join(format!("taskrun-events-{}.jsonl", EventId::generate()));
+        let store = Arc::new(JsonlEventStore::open(&path).await.unwrap());
+        store.append(event_at("old", "task-1", 1)).await.unwrap();
+
+        // Park the append on the index once its line is written
+        let index = store.index.events.read().await;
+        let append = tokio::spawn({
+            let store = store.clone();
+            async move { store.append(event_at("run-1", "task-1", 1000)).await }
+        });
+        while !tokio::fs::read_to_string(&path)
+            .await
+            .unwrap()
+            .contains("run-1")
+        {
+            tokio::task::yield_now().await;
+        }
+        // A prune now must wait for the append, or it would rewrite the
+        // file without the event
+        assert!(store.file.try_lock().is_err());
+        let prune = tokio::spawn({
+            let store = store.clone();
+            async move { store.prune(500).await }
+        });
+        tokio::task::yield_now().await;
+        drop(index);
+        append.await.unwrap().unwrap();
+        assert_eq!(prune.await.unwrap().unwrap(), 1);
+        drop(store);
+
+        let store = JsonlEventStore::open(&path).await.unwrap();
+        assert_eq!(
+            store.list_by_run(&RunId::new("run-1")).await.unwrap().len(),
+            1
+        );
+        assert!(store
+            .list_by_run(&RunId::new("old"))
+            .await
+            .unwrap()
+            .is_empty());
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,602 @@
+//! Uploaded input files and run artifacts.
+//!
+//! `POST /v1/files` stores a file for later tasks. A task naming it in
+//! `input_files` gets it sent along with each run assignment, and the worker
+//! stages it in the run's workspace. When a run finishes, the worker uploads
+//! the contents of the artifacts it reported, in chunks if they do not fit in
+//! one message; `GET /v1/tasks/:id/artifacts` lists and serves them.
+//!
+//! Uploads, and chunked artifact uploads in progress, are kept in memory.
+//! Finished artifacts are written to the `OutputStore` run output spills to.
+//! All of them count toward a total capacity. A chunked upload that sees no
+//! new chunk for `PARTIAL_TIMEOUT` is dropped. Artifacts are dropped together
+//! with their run's output (see `retention`); uploads stay until they are
+//! deleted.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use thiserror::Error;
+use tokio::sync::RwLock;
+use tracing::warn;
+
+use taskrun_core::{Artifact, RunId, TaskId};
+
+use crate::control_plane::output_store::{InMemoryOutputStore, OutputStore, OutputStoreError};
+
+/// Largest file, and largest total of a task's input files, in bytes. Keeps
+/// a run assignment or an artifact upload within one gRPC message.
+pub const MAX_FILE_BYTES: usize = 3 * 1024 * 1024;
+
+/// Largest artifact, in bytes. Artifacts larger than one message are
+/// uploaded in chunks.
+pub const MAX_ARTIFACT_BYTES: usize = 64 * 1024 * 1024;
+
+/// Default total size of stored files, in bytes.
+pub const DEFAULT_CAPACITY: usize = 256 * 1024 * 1024;
+
+/// How long a chunked artifact upload may go without a chunk before it is
+/// dropped.
+pub const PARTIAL_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
+/// File store errors.
+#[derive(Debug, Error)]
+pub enum FileError {
+    #[error("File {0} not found")]
+    NotFound(String),
+
+    #[error("File '{name}' is {size} bytes; files may be at most {max} bytes")]
+    TooLarge {
+        name: String,
+        size: usize,
+        max: usize,
+    },
+
+    #[error("Chunk of artifact '{name}' at offset {offset}; expected offset {expected}")]
+    OutOfOrder {
+        name: String,
+        offset: u64,
+        expected: u64,
+    },
+
+    #[error("Input files total {size} bytes; a task's input files may total at most {max} bytes")]
+    InputsTooLarge { size: usize, max: usize },
+
+    #[error("File storage is full ({capacity} bytes)")]
+    Full { capacity: usize },
+
+    #[error("Artifact storage error: {0}")]
+    Store(#[from] OutputStoreError),
+}
+
+/// A stored file: an upload or an artifact.
+#[derive(Debug, Clone, Serialize)]
+pub struct StoredFile {
+    pub id: String,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mime_type: Option<String>,
+    pub size_bytes: u64,
+    pub created_at: DateTime<Utc>,
+    /// Namespace of the uploader, or of the task the artifact belongs to.
+    pub namespace: String,
+    /// Contents of an upload. Empty for artifacts, whose contents are in the
+    /// output store (see `FileStore::read_artifact`).
+    #[serde(skip)]
+    pub content: Arc<Vec<u8>>,
+}
+
+impl StoredFile {
+    fn new(namespace: &str, name: &str, mime_type: Option<String>, size: usize) -> Self {
+        Self {
+            id: format!("file_{}", uuid::Uuid::new_v4().simple()),
+            name: name.to_string(),
+            mime_type,
+            size_bytes: size as u64,
+            created_at: Utc::now(),
+            namespace: namespace.to_string(),
+            content: Arc::default(),
+        }
+    }
+}
+
+/// An artifact of one of a task's runs.
+#[derive(Debug, Clone, Serialize)]
+pub struct RunArtifact {
+    pub run_id: RunId,
+    #[serde(flatten)]
+    pub file: StoredFile,
+}
+
+/// A chunked artifact upload in progress.
+#[derive(Debug)]
+struct Partial {
+    content: Vec<u8>,
+    updated_at: Instant,
+}
+
+#[derive(Debug, Default)]
+struct Files {
+    uploads: HashMap<String, StoredFile>,
+    /// Artifacts by task, in upload order.
+    artifacts: HashMap<TaskId, Vec<RunArtifact>>,
+    /// Chunked artifact uploads in progress, by run and name.
+    partial: HashMap<(RunId, String), Partial>,
+    /// Bytes stored.
+    used: usize,
+}
+
+impl Files {
+    fn reserve(&mut self, size: usize, capacity: usize) -> Result<(), FileError> {
+        if self.used + size > capacity {
+            return Err(FileError::Full { capacity });
+        }
+        self.used += size;
+        Ok(())
+    }
+
+    /// Drop the chunked upload of `key`, if any, releasing its bytes.
+    fn drop_partial(&mut self, key: &(RunId, String)) -> Option<Vec<u8>> {
+        let partial = self.partial.remove(key)?;
+        self.used -= partial.content.len();
+        Some(partial.content)
+    }
+
+    /// Drop chunked uploads that got no chunk within `timeout`.
+    fn drop_stale_partials(&mut self, timeout: Duration) {
+        let stale: Vec<(RunId, String)> = self
+            .partial
+            .iter()
+            .filter(|(_, p)| p.updated_at.elapsed() >= timeout)
+            .map(|(key, _)| key.clone())
+            .collect();
+        for key in stale {
+            self.drop_partial(&key);
+        }
+    }
+}
+
+/// Uploaded files and run artifacts.
+pub struct FileStore {
+    capacity: usize,
+    partial_timeout: Duration,
+    store: Arc<dyn OutputStore>,
+    files: RwLock<Files>,
+}
+
+impl std::fmt::Debug for FileStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FileStore")
+            .field("capacity", &self.capacity)
+            .field("partial_timeout", &self.partial_timeout)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for FileStore {
+    fn default() -> Self {
+        Self::new(DEFAULT_CAPACITY)
+    }
+}
+
+impl FileStore {
+    /// A store holding at most `capacity` bytes, with artifacts in memory.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            partial_timeout: PARTIAL_TIMEOUT,
+            store: Arc::new(InMemoryOutputStore::default()),
+            files: RwLock::default(),
+        }
+    }
+
+    /// Keep artifact contents in `store`.
+    pub fn with_store(mut self, store: Arc<dyn OutputStore>) -> Self {
+        self.store = store;
+        self
+    }
+
+    /// Drop chunked uploads that get no chunk for `timeout`.
+    pub fn with_partial_timeout(mut self, timeout: Duration) -> Self {
+        self.partial_timeout = timeout;
+        self
+    }
+
+    /// Store an uploaded file.
+    pub async fn upload(
+        &self,
+        namespace: &str,
+        name: &str,
+        mime_type: Option<String>,
+        content: Vec<u8>,
+    ) -> Result<StoredFile, FileError> {
+        check_size(name, content.len(), MAX_FILE_BYTES)?;
+        let size = content.len();
+        let file = StoredFile {
+            content: Arc::new(content),
+            ..StoredFile::new(namespace, name, mime_type, size)
+        };
+        let mut files = self.files.write().await;
+        files.drop_stale_partials(self.partial_timeout);
+        files.reserve(file.content.len(), self.capacity)?;
+        files.uploads.insert(file.id.clone(), file.clone());
+        Ok(file)
+    }
+
+    /// Delete an uploaded file. Tasks that named it run without it.
+    pub async fn delete(&self, id: &str, namespace: Option<&str>) -> Result<StoredFile, FileError> {
+        let mut files = self.files.write().await;
+        let visible = files
+            .uploads
+            .get(id)
+            .is_some_and(|f| namespace.map_or(true, |ns| f.namespace == ns));
+        let file = visible
+            .then(|| files.uploads.remove(id))
+            .flatten()
+            .ok_or_else(|| FileError::NotFound(id.to_string()))?;
+        files.used -= file.content.len();
+        Ok(file)
+    }
+
+    /// Check that a new task in `namespace` can use these uploads.
+    pub async fn check_inputs(&self, ids: &[String], namespace: &str) -> Result<(), FileError> {
+        let files = self.files.read().await;
+        let mut size = 0;
+        for id in ids {
+            let file = files
+                .uploads
+                .get(id)
+                .filter(|f| f.namespace == namespace)
+                .ok_or_else(|| FileError::NotFound(id.clone()))?;
+            size += file.content.len();
+        }
+        if size > MAX_FILE_BYTES {
+            return Err(FileError::InputsTooLarge {
+                size,
+                max: MAX_FILE_BYTES,
+            });
+        }
+        Ok(())
+    }
+
+    /// The uploads of `ids` that still exist, in order.
+    pub async fn inputs(&self, ids: &[String]) -> Vec<StoredFile> {
+        let files = self.files.read().await;
+        ids.iter()
+            .filter_map(|id| files.uploads.get(id).cloned())
+            .collect()
+    }
+
+    /// Store the contents of an artifact of `run_id`. A run reporting the
+    /// same name again replaces the earlier contents.
+    pub async fn add_artifact(
+        &self,
+        task_id: &TaskId,
+        run_id: &RunId,
+        namespace: &str,
+        artifact: &Artifact,
+        content: Vec<u8>,
+    ) -> Result<StoredFile, FileError> {
+        check_size(&artifact.name, content.len(), MAX_ARTIFACT_BYTES)?;
+        let size = content.len();
+        let file = StoredFile::new(namespace, &artifact.name, artifact.mime_type.clone(), size);
+        {
+            let mut files = self.files.write().await;
+            files.drop_stale_partials(self.partial_timeout);
+            files.reserve(size, self.capacity)?;
+        }
+        if let Err(e) = self.store.put_artifact(run_id, &file.id, content).await {
+            self.files.write().await.used -= size;
+            return Err(e.into());
+        }
+
+        let replaced = {
+            let mut files = self.files.write().await;
+            let replaced = files.artifacts.get_mut(task_id).and_then(|artifacts| {
+                let position = artifacts
+                    .iter()
+                    .position(|a| &a.run_id == run_id && a.file.name == artifact.name)?;
+                Some(artifacts.remove(position).file)
+            });
+            files.used -= replaced.as_ref().map_or(0, |f| f.size_bytes as usize);
+            files
+                .artifacts
+                .entry(task_id.clone())
+                .or_default()
+                .push(RunArtifact {
+                    run_id: run_id.clone(),
+                    file: file.clone(),
+                });
+            replaced
+        };
+        if let Some(replaced) = replaced {
+            self.delete_from_store(run_id, &replaced.id).await;
+        }
+        Ok(file)
+    }
+
+    /// Add a chunk of an artifact of `run_id`, starting at `offset`. Returns
+    /// the stored artifact once its last chunk is in, or `None` while `more`
+    /// are to come. A chunk at offset 0 starts the upload over.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn add_artifact_chunk(
+        &self,
+        task_id: &TaskId,
+        run_id: &RunId,
+        namespace: &str,
+        artifact: &Artifact,
+        offset: u64,
+        content: Vec<u8>,
+        more: bool,
+    ) -> Result<Option<StoredFile>, FileError> {
+        let content = {
+            let mut files = self.files.write().await;
+            files.drop_stale_partials(self.partial_timeout);
+            let key = (run_id.clone(), artifact.name.clone());
+            let mut partial = files.drop_partial(&key).unwrap_or_default();
+            if offset == 0 {
+                partial.clear();
+            }
+            if offset != partial.len() as u64 {
+                return Err(FileError::OutOfOrder {
+                    name: artifact.name.clone(),
+                    offset,
+                    expected: partial.len() as u64,
+                });
+            }
+            partial.extend_from_slice(&content);
+            check_size(&artifact.name, partial.len(), MAX_ARTIFACT_BYTES)?;
+            if more {
+                files.reserve(partial.len(), self.capacity)?;
+                files.partial.insert(
+                    key,
+                    Partial {
+                        content: partial,
+                        updated_at: Instant::now(),
+                    },
+                );
+                return Ok(None);
+            }
+            partial
+        };
+        self.add_artifact(task_id, run_id, namespace, artifact, content)
+            .await
+            .map(Some)
+    }
+
+    /// Read the contents of an artifact.
+    pub async fn read_artifact(&self, artifact: &RunArtifact) -> Result<Vec<u8>, FileError> {
+        Ok(self
+            .store
+            .read_artifact(&artifact.run_id, &artifact.file.id)
+            .await?)
+    }
+
+    async fn delete_from_store(&self, run_id: &RunId, file_id: &str) {
+        if let Err(e) = self.store.delete_artifact(run_id, file_id).await {
+            warn!(run_id = %run_id, file_id = %file_id, error = %e, "Failed to delete artifact");
+        }
+    }
+
+    /// Artifacts of a task's runs, in upload order.
+    pub async fn artifacts(&self, task_id: &TaskId) -> Vec<RunArtifact> {
+        self.files
+            .read()
+            .await
+            .artifacts
+            .get(task_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// The artifact named `name` of `run_id`, or of the task's latest run
+    /// that produced one.
+    pub async fn artifact(
+        &self,
+        task_id: &TaskId,
+        name: &str,
+        run_id: Option<&RunId>,
+    ) -> Option<RunArtifact> {
+        self.files
+            .read()
+            .await
+            .artifacts
+            .get(task_id)?
+            .iter()
+            .rev()
+            .find(|a| a.file.name == name && run_id.map_or(true, |id| &a.run_id == id))
+            .cloned()
+    }
+
+    /// Drop the artifacts of expired runs. Returns the number removed.
+    pub async fn remove_runs(&self, run_ids: &HashSet<RunId>) -> usize {
+        let removed = {
+            let mut files = self.files.write().await;
+            let mut removed = Vec::new();
+            files.artifacts.retain(|_, artifacts| {
+                artifacts.retain(|a| {
+                    let expired = run_ids.contains(&a.run_id);
+                    if expired {
+                        removed.push(a.clone());
+                    }
+                    !expired
+                });
+                !artifacts.is_empty()
+            });
+            files.used -= removed
+                .iter()
+                .map(|a| a.file.size_bytes as usize)
+                .sum::<usize>();
+            let partial: Vec<(RunId, String)> = files
+                .partial
+                .keys()
+                .filter(|(run_id, _)| run_ids.contains(run_id))
+                .cloned()
+                .collect();
+            for key in partial {
+                files.drop_partial(&key);
+            }
+            removed
+        };
+        for artifact in &removed {
+            self.delete_from_store(&artifact.run_id, &artifact.file.id)
+                .await;
+        }
+        removed.len()
+    }
+}
+
+fn check_size(name: &str, size: usize, max: usize) -> Result<(), FileError> {
+    if size > max {
+        return Err(FileError::TooLarge {
+            name: name.to_string(),
+            size,
+            max,
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_uploads_are_scoped_and_bounded() {
+        let store = FileStore::new(10);
+        let file = store
+            .upload("team-a", "notes.txt", None, b"hello".to_vec())
+            .await
+            .unwrap();
+        assert!(file.id.starts_with("file_"));
+        assert!(store.delete(&file.id, Some("team-b")).await.is_err());
+        assert!(store
+            .check_inputs(std::slice::from_ref(&file.id), "team-b")
+            .await
+            .is_err());
+        store
+            .check_inputs(std::slice::from_ref(&file.id), "team-a")
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            store.upload("team-a", "big.txt", None, vec![0; 6]).await,
+            Err(FileError::Full { capacity: 10 })
+        ));
+        store.delete(&file.id, Some("team-a")).await.unwrap();
+        store
+            .upload("team-a", "big.txt", None, vec![0; 6])
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_artifacts_replace_and_expire_by_run() {
+        let store = FileStore::default();
+        let task_id = TaskId::new("task-1");
+        let (first, second) = (RunId::new("run-1"), RunId::new("run-2"));
+        let report = Artifact::new("report.md", "file:///tmp/report.md");
+
+        for (run_id, content) in [(&first, "v1"), (&first, "v2"), (&second, "v3")] {
+            store
+                .add_artifact(&task_id, run_id, "default", &report, content.into())
+                .await
+                .unwrap();
+        }
+        assert_eq!(store.artifacts(&task_id).await.len(), 2);
+        let latest = store.artifact(&task_id, "report.md", None).await.unwrap();
+        assert_eq!(latest.run_id, second);
+        let earlier = store
+            .artifact(&task_id, "report.md", Some(&first))
+            .await
+            .unwrap();
+        assert_eq!(store.read_artifact(&earlier).await.unwrap(), b"v2");
+
+        assert_eq!(store.remove_runs(&HashSet::from([first])).await, 1);
+        assert_eq!(store.files.read().await.used, 2);
+        assert!(matches!(
+            store.read_artifact(&earlier).await,
+            Err(FileError::Store(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_chunked_artifacts_are_assembled_in_order() {
+        let store = FileStore::default();
+        let task_id = TaskId::new("task-1");
+        let run_id = RunId::new("run-1");
+        let log = Artifact::new("build.log", "file:///tmp/build.log");
+        let chunk = |offset, content: &str, more| {
+            store.add_artifact_chunk(
+                &task_id,
+                &run_id,
+                "default",
+                &log,
+                offset,
+                content.into(),
+                more,
+            )
+        };
+
+        assert!(chunk(0, "abc", true).await.unwrap().is_none());
+        assert!(matches!(
+            chunk(2, "x", true).await,
+            Err(FileError::OutOfOrder { expected: 3, .. })
+        ));
+        // The out-of-order chunk dropped the upload; it starts over
+        assert!(chunk(0, "abc", true).await.unwrap().is_none());
+        chunk(3, "def", false).await.unwrap().unwrap();
+        let artifact = store.artifact(&task_id, "build.log", None).await.unwrap();
+        assert_eq!(store.read_artifact(&artifact).await.unwrap(), b"abcdef");
+        assert!(store.files.read().await.partial.is_empty());
+    }
+
+    async fn add_log_chunk(
+        store: &FileStore,
+        run_id: &str,
+        content: &str,
+    ) -> Result<Option<StoredFile>, FileError> {
+        store
+            .add_artifact_chunk(
+                &TaskId::new("task-1"),
+                &RunId::new(run_id),
+                "default",
+                &Artifact::new("build.log", "file:///tmp/build.log"),
+                0,
+                content.into(),
+                true,
+            )
+            .await
+    }
+
+    #[tokio::test]
+    async fn test_partial_artifacts_count_toward_capacity_until_stale() {
+        let store = FileStore::new(8);
+        assert!(add_log_chunk(&store, "run-1", "abcdef")
+            .await
+            .unwrap()
+            .is_none());
+        assert_eq!(store.files.read().await.used, 6);
+        assert!(matches!(
+            add_log_chunk(&store, "run-2", "abc").await,
+            Err(FileError::Full { capacity: 8 })
+        ));
+
+        // A stale upload is dropped and its bytes released
+        let store = FileStore::new(8).with_partial_timeout(Duration::ZERO);
+        assert!(add_log_chunk(&store, "run-1", "abcdef")
+            .await
+            .unwrap()
+            .is_none());
+        assert!(add_log_chunk(&store, "run-2", "abc")
+            .await
+            .unwrap()
+            .is_none());
+        let files = store.files.read().await;
+        assert_eq!(files.partial.len(), 1);
+        assert_eq!(files.used, 3);
+    }
+}
@@ -0,0 +1,339 @@
+//! Time series for the Grafana SimpleJSON datasource.
+//!
+//! Grafana's SimpleJSON (and the compatible Infinity/JSON) datasources call
+//! `/search` to list metric names and `/query` to fetch datapoints. Series are
+//! computed from the task and run history held in `AppState`:
+//!
+//! - `tasks_created`, `tasks_completed`, `tasks_failed`: counts per bucket
+//! - `run_duration_avg_seconds`, `run_duration_max_seconds`: over runs finished in the bucket
+//! - `workers_connected`, `workers_busy`: from periodic samples (see `sample_workers_loop`)
+
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use taskrun_core::{RunStatus, WorkerStatus};
+
+use crate::control_plane::state::AppState;
+
+/// How often worker counts are sampled.
+pub const WORKER_SAMPLE_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Number of worker samples kept (24h at the default interval).
+pub const MAX_WORKER_SAMPLES: usize = 5760;
+
+/// Metric names served by `/search`.
+pub const METRICS: &[&str] = &[
+    "tasks_created",
+    "tasks_completed",
+    "tasks_failed",
+    "run_duration_avg_seconds",
+    "run_duration_max_seconds",
+    "workers_connected",
+    "workers_busy",
+];
+
+/// Worker counts at a point in time.
+#[derive(Debug, Clone, Copy)]
+pub struct WorkerSample {
+    pub timestamp_ms: i64,
+    pub connected: u32,
+    pub busy: u32,
+}
+
+/// Ring buffer of worker samples stored in `AppState`.
+pub type WorkerSamples = VecDeque<WorkerSample>;
+
+/// Time range of a query.
+#[derive(Debug, Deserialize)]
+pub struct QueryRange {
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+}
+
+/// A requested series.
+#[derive(Debug, Deserialize)]
+pub struct QueryTarget {
+    pub target: String,
+}
+
+/// Body of a `/query` request.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryRequest {
+    pub range: QueryRange,
+    #[serde(default)]
+    pub interval_ms: Option<i64>,
+    #[serde(default)]
+    pub max_data_points: Option<i64>,
+    #[serde(default)]
+    pub targets: Vec<QueryTarget>,
+}
+
+/// A series in a `/query` response. Datapoints are `[value, timestamp_ms]`.
+#[derive(Debug, Serialize)]
+pub struct TimeSeries {
+    pub target: String,
+    pub datapoints: Vec<(f64, i64)>,
+}
+
+/// Fixed-width buckets covering a query range.
+struct Buckets {
+    from_ms: i64,
+    step_ms: i64,
+    count: usize,
+}
+
+impl Buckets {
+    fn new(request: &QueryRequest) -> Self {
+        let from_ms = request.range.from.timestamp_millis();
+        let span_ms = (request.range.to.timestamp_millis() - from_ms).max(1);
+        let max_points = request.max_data_points.filter(|&n| n > 0).unwrap_or(1000);
+
+        // Honor Grafana's interval, but never return more points than it asked for
+        let step_ms = request
+            .interval_ms
+            .filter(|&ms| ms > 0)
+            .unwrap_or(60_000)
+            .max(span_ms / max_points)
+            .max(1000);
+        let count = ((span_ms + step_ms - 1) / step_ms) as usize;
+
+        Self {
+            from_ms,
+            step_ms,
+            count,
+        }
+    }
+
+    /// Bucket index for a timestamp, if it falls in range.
+    fn index(&self, timestamp_ms: i64) -> Option<usize> {
+        if timestamp_ms < self.from_ms {
+            return None;
+        }
+        let i = ((timestamp_ms - self.from_ms) / self.step_ms) as usize;
+        (i < self.count).then_some(i)
+    }
+
+    fn timestamp(&self, index: usize) -> i64 {
+        self.from_ms + index as i64 * self.step_ms
+    }
+
+    fn series(&self, target: &str, values: Vec<Option<f64>>) -> TimeSeries {
+        TimeSeries {
+            target: target.to_string(),
+            datapoints: values
+                .into_iter()
+                .enumerate()
+                .filter_map(|(i, v)| v.map(|v| (v, self.timestamp(i))))
+                .collect(),
+        }
+    }
+}
+
+/// Answer a `/search` request: every metric containing `filter`.
+pub fn search(filter: &str) -> Vec<&'static str> {
+    METRICS
+        .iter()
+        .copied()
+        .filter(|m| m.contains(filter))
+        .collect()
+}
+
+/// Answer a `/query` request. Unknown targets are skipped.
+pub async fn query(state: &AppState, request: &QueryRequest) -> Vec<TimeSeries> {
+    let buckets = Buckets::new(request);
+    let mut result = Vec::new();
+
+    for target in &request.targets {
+        let values = match target.target.as_str() {
+            "tasks_created" => task_counts(state, &buckets, None).await,
+            "tasks_completed" => task_counts(state, &buckets, Some(RunStatus::Completed)).await,
+            "tasks_failed" => task_counts(state, &buckets, Some(RunStatus::Failed)).await,
+            "run_duration_avg_seconds" => run_durations(state, &buckets, false).await,
+            "run_duration_max_seconds" => run_durations(state, &buckets, true).await,
+            "workers_connected" => worker_counts(state, &buckets, |s| s.connected).await,
+            "workers_busy" => worker_counts(state, &buckets, |s| s.busy).await,
+            _ => continue,
+        };
+        result.push(buckets.series(&target.target, values));
+    }
+
+    result
+}
+
+/// Count tasks created per bucket, or runs that finished with `finished` per bucket.
+async fn task_counts(
+    state: &AppState,
+    buckets: &Buckets,
+    finished: Option<RunStatus>,
+) -> Vec<Option<f64>> {
+    let mut counts = vec![0.0; buckets.count];
+    let tasks = state.tasks.read().await;
+
+    for task in tasks.values() {
+        let timestamp = match finished {
+            None => Some(task.created_at),
+            Some(status) => task
+                .latest_run()
+                .filter(|r| r.status == status)
+                .and_then(|r| r.finished_at),
+        };
+        if let Some(i) = timestamp.and_then(|t| buckets.index(t.timestamp_millis())) {
+            counts[i] += 1.0;
+        }
+    }
+
+    counts.into_iter().map(Some).collect()
+}
+
+/// Average (or max) duration of runs finished in each bucket.
+async fn run_durations(state: &AppState, buckets: &Buckets, max: bool) -> Vec<Option<f64>> {
+    let mut sums = vec![(0.0, 0u32, 0.0f64); buckets.count];
+    let tasks = state.tasks.read().await;
+
+    for run in tasks.values().flat_map(|t| t.runs.iter()) {
+        let (Some(started), Some(finished)) = (run.started_at, run.finished_at) else {
+            continue;
+        };
+        let Some(i) = buckets.index(finished.timestamp_millis()) else {
+            continue;
+        };
+        let secs = finished.signed_duration_since(started).num_milliseconds() as f64 / 1000.0;
+        let entry = &mut sums[i];
+        entry.0 += secs;
+        entry.1 += 1;
+        entry.2 = entry.2.max(secs);
+    }
+
+    sums.into_iter()
+        .map(|(sum, n, peak)| match n {
+            0 => None,
+            _ if max => Some(peak),
+            _ => Some(sum / n as f64),
+        })
+        .collect()
+}
+
+/// Latest worker sample in each bucket.
+async fn worker_counts(
+    state: &AppState,
+    buckets: &Buckets,
+    value: impl Fn(&WorkerSample) -> u32,
+) -> Vec<Option<f64>> {
+    let mut values = vec![None; buckets.count];
+    let samples = state.worker_samples.read().await;
+
+    for sample in samples.iter() {
+        if let Some(i) = buckets.index(sample.timestamp_ms) {
+            values[i] = Some(value(sample) as f64);
+        }
+    }
+
+    values
+}
+
+/// Record the current worker counts.
+pub async fn record_worker_sample(state: &AppState, now: DateTime<Utc>) {
+    let sample = {
+        let workers = state.workers.read().await;
+        WorkerSample {
+            timestamp_ms: now.timestamp_millis(),
+            connected: workers.len() as u32,
+            busy: workers
+                .values()
+                .filter(|w| w.status == WorkerStatus::Busy)
+                .count() as u32,
+        }
+    };
+
+    let mut samples = state.worker_samples.write().await;
+    samples.push_back(sample);
+    while samples.len() > MAX_WORKER_SAMPLES {
+        samples.pop_front();
+    }
+}
+
+/// Sample worker counts forever.
+pub async fn sample_workers_loop(state: Arc<AppState>) {
+    let mut interval = tokio::time::interval(WORKER_SAMPLE_INTERVAL);
+    loop {
+        interval.tick().await;
+        record_worker_sample(&state, Utc::now()).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use taskrun_core::{RunSummary, Task, WorkerId};
+
+    fn request(from: DateTime<Utc>, targets: &[&str]) -> QueryRequest {
+        QueryRequest {
+            range: QueryRange {
+                from,
+                to: from + chrono::Duration::minutes(3),
+            },
+            interval_ms: Some(60_000),
+            max_data_points: None,
+            targets: targets
+                .iter()
+                .map(|t| QueryTarget {
+                    target: t.to_string(),
+                })
+                .collect(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_query_buckets_throughput_and_durations() {
+        let state = AppState::new();
+        let from = DateTime::parse_from_rfc3339("2024-01-01T10:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        for (created_min, duration_secs) in [(0, 10), (0, 30), (2, 20)] {
+            let mut task = Task::new("general", "{}", "test");
+            task.created_at = from + chrono::Duration::minutes(created_min);
+            let mut run = RunSummary::new(WorkerId::new("w"));
+            run.status = RunStatus::Completed;
+            run.started_at = Some(task.created_at);
+            run.finished_at = Some(task.created_at + chrono::Duration::seconds(duration_secs));
+            task.add_run(run);
+            state.tasks.write().await.insert(task.id.clone(), task);
+        }
+
+        let series = query(
+            &state,
+            &request(
+                from,
+                &[
+                    "tasks_created",
+                    "run_duration_avg_seconds",
+                    "run_duration_max_seconds",
+                    "unknown",
+                ],
+            ),
+        )
+        .await;
+        assert_eq!(series.len(), 3);
+
+        let created = from.timestamp_millis();
+        assert_eq!(
+            series[0].datapoints,
+            vec![
+                (2.0, created),
+                (0.0, created + 60_000),
+                (1.0, created + 120_000)
+            ]
+        );
+        assert_eq!(
+            series[1].datapoints,
+            vec![(20.0, created), (20.0, created + 120_000)]
+        );
+        assert_eq!(series[2].datapoints[0], (30.0, created));
+    }
+}
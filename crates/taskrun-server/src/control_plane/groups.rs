@@ -0,0 +1,329 @@
+//! Task groups - workflows, batch submissions and sub-task fan-outs.
+//!
+//! A group is every task labeled `group=<id>` (set by whoever submits a batch
+//! or fans out sub-tasks) or `workflow_id=<id>` (set on workflow steps). The
+//! summary aggregates the children's statuses, run time and cost, and points
+//! at the slowest and failed ones.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use taskrun_core::{Task, TaskId, TaskStatus};
+use taskrun_proto::pb;
+
+use crate::control_plane::state::{AppState, UiNotification};
+use crate::control_plane::usage;
+use crate::control_plane::workflows::WORKFLOW_ID_LABEL;
+
+/// Label putting a task in a group.
+pub const GROUP_LABEL: &str = "group";
+
+/// Children listed as the slowest of a group.
+const SLOWEST_CHILDREN: usize = 5;
+
+/// How often the server TUI's group bars are refreshed.
+const PUBLISH_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Finished groups stay in the server TUI this long after their last task.
+const FINISHED_GROUP_TTL: chrono::Duration = chrono::Duration::minutes(10);
+
+/// Number of children per status.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct GroupCounts {
+    pub pending: u32,
+    pub running: u32,
+    pub completed: u32,
+    pub failed: u32,
+    pub cancelled: u32,
+}
+
+impl GroupCounts {
+    fn add(&mut self, status: TaskStatus) {
+        match status {
+            TaskStatus::Pending => self.pending += 1,
+            TaskStatus::Running => self.running += 1,
+            TaskStatus::Completed => self.completed += 1,
+            TaskStatus::Failed => self.failed += 1,
+            TaskStatus::Cancelled => self.cancelled += 1,
+        }
+    }
+
+    /// Whether every child reached a terminal status.
+    pub fn finished(&self) -> bool {
+        self.pending == 0 && self.running == 0
+    }
+}
+
+/// A child task, as listed in a summary.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GroupChild {
+    pub task_id: TaskId,
+    pub agent_name: String,
+    pub status: TaskStatus,
+    /// Run time of the child's runs so far.
+    pub duration_secs: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub failure_message: Option<String>,
+}
+
+/// Aggregate view of a group.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GroupSummary {
+    pub group_id: String,
+    pub total: u32,
+    pub counts: GroupCounts,
+    /// Run time summed over every run of every child.
+    pub total_run_secs: u64,
+    /// Wall-clock time from the first child's creation to the last run's end
+    /// (or now, while children are unfinished).
+    pub elapsed_secs: u64,
+    /// Cost reported by the children's runs (None = no run reported one).
+    pub total_cost_usd: Option<f64>,
+    /// Children with the longest run time, slowest first.
+    pub slowest: Vec<GroupChild>,
+    /// Children that failed.
+    pub failed: Vec<GroupChild>,
+}
+
+/// The group `task` belongs to, if any. An explicit `group` label wins over
+/// the workflow.
+pub fn group_of(task: &Task) -> Option<&str> {
+    task.labels
+        .get(GROUP_LABEL)
+        .or_else(|| task.labels.get(WORKFLOW_ID_LABEL))
+        .map(String::as_str)
+}
+
+/// Summarize group `group_id` among the tasks in `namespace` (None = all).
+/// Returns None if the group has no task.
+pub async fn summarize(
+    state: &AppState,
+    group_id: &str,
+    namespace: Option<&str>,
+) -> Option<GroupSummary> {
+    let children: Vec<Task> = state
+        .tasks
+        .read()
+        .await
+        .values()
+        .filter(|t| group_of(t) == Some(group_id))
+        .filter(|t| namespace.map_or(true, |ns| t.namespace == ns))
+        .cloned()
+        .collect();
+    if children.is_empty() {
+        return None;
+    }
+
+    let mut total_cost_usd = None;
+    for run in children.iter().flat_map(|t| &t.runs) {
+        if let Some(cost) = usage::run_usage(state, &run.run_id)
+            .await
+            .and_then(|u| u.cost_usd)
+        {
+            *total_cost_usd.get_or_insert(0.0) += cost;
+        }
+    }
+
+    let mut summary = aggregate(group_id, &children, Utc::now());
+    summary.total_cost_usd = total_cost_usd;
+    Some(summary)
+}
+
+/// Everything but the cost, which needs the run events.
+fn aggregate(group_id: &str, children: &[Task], now: DateTime<Utc>) -> GroupSummary {
+    let mut counts = GroupCounts::default();
+    let mut listed: Vec<GroupChild> = Vec::with_capacity(children.len());
+    let mut total_run_secs = 0;
+    let mut first_created = now;
+    let mut last_finished = None;
+
+    for task in children {
+        counts.add(task.status);
+        first_created = first_created.min(task.created_at);
+
+        let mut duration_secs = 0;
+        for run in &task.runs {
+            if let Some(started) = run.started_at {
+                let end = run.finished_at.unwrap_or(now);
+                duration_secs += (end - started).num_seconds().max(0) as u64;
+            }
+            if let Some(finished) = run.finished_at {
+                last_finished = last_finished.max(Some(finished));
+            }
+        }
+        total_run_secs += duration_secs;
+
+        listed.push(GroupChild {
+            task_id: task.id.clone(),
+            agent_name: task.agent_name.clone(),
+            status: task.status,
+            duration_secs,
+            failure_message: task
+                .latest_run()
+                .and_then(|r| r.error_message.clone())
+                .or_else(|| task.failure_message.clone()),
+        });
+    }
+
+    let end = if counts.finished() {
+        last_finished.unwrap_or(now)
+    } else {
+        now
+    };
+
+    let failed = listed
+        .iter()
+        .filter(|c| c.status == TaskStatus::Failed)
+        .cloned()
+        .collect();
+    listed.sort_by_key(|c| std::cmp::Reverse(c.duration_secs));
+    listed.truncate(SLOWEST_CHILDREN);
+
+    GroupSummary {
+        group_id: group_id.to_string(),
+        total: children.len() as u32,
+        counts,
+        total_run_secs,
+        elapsed_secs: (end - first_created).num_seconds().max(0) as u64,
+        total_cost_usd: None,
+        slowest: listed,
+        failed,
+    }
+}
+
+/// Counts of the groups worth showing: unfinished, or finished recently.
+pub fn active_groups(
+    tasks: &HashMap<TaskId, Task>,
+    now: DateTime<Utc>,
+) -> Vec<(String, GroupCounts)> {
+    let mut groups: HashMap<&str, (GroupCounts, DateTime<Utc>)> = HashMap::new();
+    for task in tasks.values() {
+        let Some(group_id) = group_of(task) else {
+            continue;
+        };
+        let last_activity = task
+            .runs
+            .iter()
+            .filter_map(|r| r.finished_at)
+            .max()
+            .unwrap_or(task.created_at);
+        let (counts, latest) = groups
+            .entry(group_id)
+            .or_insert_with(|| (GroupCounts::default(), last_activity));
+        counts.add(task.status);
+        *latest = (*latest).max(last_activity);
+    }
+
+    let mut active: Vec<(String, GroupCounts)> = groups
+        .into_iter()
+        .filter(|(_, (counts, latest))| !counts.finished() || now - *latest < FINISHED_GROUP_TTL)
+        .map(|(group_id, (counts, _))| (group_id.to_string(), counts))
+        .collect();
+    active.sort_by(|a, b| a.0.cmp(&b.0));
+    active
+}
+
+/// Send the active groups to the UI whenever they change.
+pub async fn publish_loop(state: Arc<AppState>) {
+    let mut interval = tokio::time::interval(PUBLISH_INTERVAL);
+    let mut last = Vec::new();
+    loop {
+        interval.tick().await;
+        let groups = active_groups(&*state.tasks.read().await, Utc::now());
+        if groups != last {
+            last = groups.clone();
+            state.notify_ui(UiNotification::GroupsUpdated { groups });
+        }
+    }
+}
+
+impl From<GroupChild> for pb::GroupChild {
+    fn from(child: GroupChild) -> Self {
+        pb::GroupChild {
+            task_id: child.task_id.into_inner(),
+            agent_name: child.agent_name,
+            status: pb::TaskStatus::from(child.status).into(),
+            duration_seconds: child.duration_secs,
+            failure_message: child.failure_message.unwrap_or_default(),
+        }
+    }
+}
+
+impl From<GroupSummary> for pb::GroupSummary {
+    fn from(summary: GroupSummary) -> Self {
+        pb::GroupSummary {
+            group_id: summary.group_id,
+            total: summary.total,
+            pending: summary.counts.pending,
+            running: summary.counts.running,
+            completed: summary.counts.completed,
+            failed: summary.counts.failed,
+            cancelled: summary.counts.cancelled,
+            total_run_seconds: summary.total_run_secs,
+            elapsed_seconds: summary.elapsed_secs,
+            total_cost_usd: summary.total_cost_usd.unwrap_or(0.0),
+            slowest: summary.slowest.into_iter().map(Into::into).collect(),
+            failed_children: summary.failed.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration as ChronoDuration;
+    use taskrun_core::{RunStatus, RunSummary, WorkerId};
+
+    fn child(status: TaskStatus, run_secs: i64, now: DateTime<Utc>) -> Task {
+        let mut task = Task::new("general", "{}", "test");
+        task.labels.insert(GROUP_LABEL.into(), "nightly".into());
+        task.created_at = now - ChronoDuration::seconds(100);
+        let mut run = RunSummary::new(WorkerId::generate());
+        run.started_at = Some(now - ChronoDuration::seconds(run_secs));
+        if status != TaskStatus::Running {
+            run.finished_at = Some(now);
+        }
+        run.status = match status {
+            TaskStatus::Failed => RunStatus::Failed,
+            TaskStatus::Running => RunStatus::Running,
+            _ => RunStatus::Completed,
+        };
+        task.runs.push(run);
+        task.status = status;
+        task
+    }
+
+    #[test]
+    fn test_aggregate_counts_slowest_and_failed() {
+        let now = Utc::now();
+        let children = vec![
+            child(TaskStatus::Completed, 10, now),
+            child(TaskStatus::Failed, 30, now),
+            child(TaskStatus::Running, 50, now),
+        ];
+        let summary = aggregate("nightly", &children, now);
+
+        assert_eq!(summary.total, 3);
+        assert_eq!(summary.counts.completed, 1);
+        assert_eq!(summary.counts.running, 1);
+        assert_eq!(summary.total_run_secs, 90);
+        assert_eq!(summary.elapsed_secs, 100);
+        assert_eq!(summary.slowest[0].task_id, children[2].id);
+        assert_eq!(summary.failed.len(), 1);
+        assert_eq!(summary.failed[0].task_id, children[1].id);
+    }
+
+    #[test]
+    fn test_workflow_tasks_form_a_group() {
+        let mut task = Task::new("general", "{}", "test");
+        task.labels.insert(WORKFLOW_ID_LABEL.into(), "wf-1".into());
+        assert_eq!(group_of(&task), Some("wf-1"));
+
+        task.labels.insert(GROUP_LABEL.into(), "batch-7".into());
+        assert_eq!(group_of(&task), Some("batch-7"));
+    }
+}
@@ -0,0 +1,346 @@
+//! Structured access log for the HTTP API.
+//!
+//! With `--access-log`, every request to the API routes is written as one
+//! JSON line:
+//!
+//! ```json
+//! {"ts":"2025-01-01T12:00:00Z","method":"GET","path":"/v1/tasks/abc/events",
+//!  "route":"/v1/tasks/:task_id/events","status":200,"latency_ms":1.8,
+//!  "caller":"api-key:ci","task_id":"abc","request_id":"…","client_ip":"10.0.0.7"}
+//! ```
+//!
+//! `caller` is the authenticated identity (absent for anonymous calls) and
+//! `task_id` the task the request named in its path or created. Unlike the
+//! `request_log` events, access lines are never sampled.
+//!
+//! Lines go to the application log under the `access` target, or with
+//! `--access-log-file` to a file of their own, rotated by size: the full
+//! file is renamed to `<file>.1`, older ones shift up and the oldest past
+//! `--access-log-max-files` is removed.
+
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use axum::{
+    extract::{MatchedPath, RawPathParams, Request, State},
+    middleware::Next,
+    response::Response,
+};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use thiserror::Error;
+use tower_http::request_id::RequestId;
+use tracing::{info, warn};
+
+use taskrun_core::TaskId;
+
+use crate::control_plane::http::client_ip::ClientIp;
+use crate::control_plane::rbac::Caller;
+
+/// Errors opening the access log file.
+#[derive(Debug, Error)]
+pub enum AccessLogError {
+    #[error("I/O error on access log '{path}': {source}")]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+}
+
+/// Rotation settings of the access log file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AccessLogFile {
+    pub path: PathBuf,
+    /// Size (bytes) at which the file is rotated.
+    pub max_bytes: u64,
+    /// Rotated files kept next to the current one.
+    pub max_files: usize,
+}
+
+/// One access log line.
+#[derive(Debug, Clone, Serialize)]
+pub struct AccessEntry {
+    pub ts: DateTime<Utc>,
+    pub method: String,
+    pub path: String,
+    /// Route pattern the request matched, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub route: Option<String>,
+    pub status: u16,
+    pub latency_ms: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub caller: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub task_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_ip: Option<String>,
+}
+
+/// Task a request created, for handlers whose path names none. The
+/// middleware adds an empty one to every request.
+#[derive(Debug, Clone, Default)]
+pub struct AccessTaskId(Arc<Mutex<Option<String>>>);
+
+impl AccessTaskId {
+    /// Record `task_id` as the request's task.
+    pub fn set(&self, task_id: &TaskId) {
+        *self.0.lock().unwrap() = Some(task_id.as_str().to_string());
+    }
+
+    fn get(&self) -> Option<String> {
+        self.0.lock().unwrap().clone()
+    }
+}
+
+/// Where access lines are written.
+#[derive(Debug, Clone)]
+pub struct AccessLog {
+    file: Option<Arc<Mutex<RotatingFile>>>,
+}
+
+impl AccessLog {
+    /// Write access lines to the application log.
+    pub fn to_log() -> Self {
+        Self { file: None }
+    }
+
+    /// Write access lines to a rotating file.
+    pub fn to_file(config: AccessLogFile) -> Result<Self, AccessLogError> {
+        let file = RotatingFile::open(config)?;
+        Ok(Self {
+            file: Some(Arc::new(Mutex::new(file))),
+        })
+    }
+
+    fn write(&self, entry: &AccessEntry) {
+        let line = match serde_json::to_string(entry) {
+            Ok(line) => line,
+            Err(e) => {
+                warn!(error = %e, "Failed to serialize access log entry");
+                return;
+            }
+        };
+        match &self.file {
+            Some(file) => {
+                if let Err(e) = file.lock().unwrap().write_line(&line) {
+                    warn!(error = %e, "Failed to write access log");
+                }
+            }
+            None => info!(target: "access", "{}", line),
+        }
+    }
+}
+
+/// Append-only file rotated by size.
+#[derive(Debug)]
+struct RotatingFile {
+    config: AccessLogFile,
+    file: File,
+    size: u64,
+}
+
+impl RotatingFile {
+    fn open(config: AccessLogFile) -> Result<Self, AccessLogError> {
+        let file = open_for_append(&config.path)?;
+        let size = file
+            .metadata()
+            .map_err(|source| AccessLogError::Io {
+                path: config.path.clone(),
+                source,
+            })?
+            .len();
+        Ok(Self { config, file, size })
+    }
+
+    fn write_line(&mut self, line: &str) -> Result<(), AccessLogError> {
+        let len = line.len() as u64 + 1;
+        if self.size > 0 && self.size + len > self.config.max_bytes {
+            self.rotate()?;
+        }
+        writeln!(self.file, "{}", line).map_err(|source| AccessLogError::Io {
+            path: self.config.path.clone(),
+            source,
+        })?;
+        self.size += len;
+        Ok(())
+    }
+
+    fn rotate(&mut self) -> Result<(), AccessLogError> {
+        let path = &self.config.path;
+        let rotated = |n: usize| PathBuf::from(format!("{}.{}", path.display(), n));
+        let io_err = |source| AccessLogError::Io {
+            path: path.clone(),
+            source,
+        };
+
+        if self.config.max_files == 0 {
+            std::fs::remove_file(path).map_err(io_err)?;
+        } else {
+            // Missing files are fine: there may be fewer than max_files so far
+            let _ = std::fs::remove_file(rotated(self.config.max_files));
+            for n in (1..self.config.max_files).rev() {
+                let _ = std::fs::rename(rotated(n), rotated(n + 1));
+            }
+            std::fs::rename(path, rotated(1)).map_err(io_err)?;
+        }
+        self.file = open_for_append(path)?;
+        self.size = 0;
+        Ok(())
+    }
+}
+
+fn open_for_append(path: &Path) -> Result<File, AccessLogError> {
+    let io_err = |source| AccessLogError::Io {
+        path: path.to_path_buf(),
+        source,
+    };
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent).map_err(io_err)?;
+    }
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(io_err)
+}
+
+/// Write one access line per request.
+pub async fn log_access(
+    State(log): State<AccessLog>,
+    params: Option<RawPathParams>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let started = Instant::now();
+    let ts = Utc::now();
+    let method = request.method().to_string();
+    let path = request.uri().path().to_string();
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|p| p.as_str().to_string());
+    let request_id = request
+        .extensions()
+        .get::<RequestId>()
+        .and_then(|id| id.header_value().to_str().ok())
+        .map(str::to_string);
+    let client_ip = request
+        .extensions()
+        .get::<ClientIp>()
+        .map(|ip| ip.0.to_string());
+    let path_task_id = params.and_then(|params| {
+        params
+            .iter()
+            .find(|(key, _)| *key == "task_id")
+            .map(|(_, value)| value.to_string())
+    });
+    let created = AccessTaskId::default();
+    request.extensions_mut().insert(created.clone());
+
+    let response = next.run(request).await;
+
+    log.write(&AccessEntry {
+        ts,
+        method,
+        path,
+        route,
+        status: response.status().as_u16(),
+        latency_ms: started.elapsed().as_secs_f64() * 1000.0,
+        caller: response
+            .extensions()
+            .get::<Caller>()
+            .map(|c| c.identity.clone()),
+        task_id: path_task_id.or_else(|| created.get()),
+        request_id,
+        client_ip,
+    });
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        body::Body,
+        middleware,
+        routing::{get, post},
+        Extension, Router,
+    };
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn test_lines_name_the_task_of_the_request() {
+        let path =
+            std::env::temp_dir().join(format!("taskrun-access-{}-task.log", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let log = AccessLog::to_file(AccessLogFile {
+            path: path.clone(),
+            max_bytes: 1024 * 1024,
+            max_files: 1,
+        })
+        .unwrap();
+        let router = Router::new()
+            .route("/v1/tasks/:task_id/events", get(|| async { "[]" }))
+            .route(
+                "/v1/responses",
+                post(|Extension(task): Extension<AccessTaskId>| async move {
+                    task.set(&TaskId::new("created"));
+                    "ok"
+                }),
+            )
+            .layer(middleware::from_fn_with_state(log, log_access));
+
+        for request in [
+            Request::get("/v1/tasks/abc/events").body(Body::empty()),
+            Request::post("/v1/responses").body(Body::empty()),
+        ] {
+            router.clone().oneshot(request.unwrap()).await.unwrap();
+        }
+
+        let lines: Vec<serde_json::Value> = std::fs::read_to_string(&path)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["task_id"], "abc");
+        assert_eq!(lines[0]["route"], "/v1/tasks/:task_id/events");
+        assert_eq!(lines[0]["status"], 200);
+        assert_eq!(lines[1]["task_id"], "created");
+        assert_eq!(lines[1]["method"], "POST");
+        assert!(lines[1].get("caller").is_none());
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_file_rotates_by_size() {
+        let dir = std::env::temp_dir().join(format!("taskrun-access-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let path = dir.join("access.log");
+        let mut file = RotatingFile::open(AccessLogFile {
+            path: path.clone(),
+            max_bytes: 10,
+            max_files: 2,
+        })
+        .unwrap();
+
+        for line in ["first", "second", "third", "fourth"] {
+            file.write_line(line).unwrap();
+        }
+
+        let read = |p: &Path| std::fs::read_to_string(p).unwrap();
+        assert_eq!(read(&path), "fourth\n");
+        assert_eq!(read(&dir.join("access.log.1")), "third\n");
+        assert_eq!(read(&dir.join("access.log.2")), "second\n");
+        assert!(!dir.join("access.log.3").exists());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
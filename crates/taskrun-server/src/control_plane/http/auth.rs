@@ -0,0 +1,275 @@
+//! API key authentication middleware.
+//!
+//! Callers authenticate with `Authorization: Bearer <api key>`, or over
+//! `--http-tls` with a client certificate from the TaskRun CA (see `tls`).
+//! Each route group requires a minimum role; the authenticated `Caller` is
+//! added to the request extensions. Rejections use the OpenAI error shape so SDK clients
+//! surface them like any other API error.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Request, State},
+    http::{header, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+
+use serde_json::json;
+
+use super::tls::ClientCertCaller;
+use crate::control_plane::api_keys::{ApiKey, ApiKeyManager};
+use crate::control_plane::rbac::{Caller, Role};
+
+/// Authentication settings shared by the HTTP middleware.
+#[derive(Clone)]
+pub struct ApiKeyAuth {
+    pub keys: Arc<ApiKeyManager>,
+    /// Reject protected routes without a valid key. Admin routes always
+    /// require an admin key.
+    pub required: bool,
+}
+
+impl ApiKeyAuth {
+    /// Create auth settings over `keys`.
+    pub fn new(keys: Arc<ApiKeyManager>, required: bool) -> Self {
+        Self { keys, required }
+    }
+
+    /// Middleware state requiring at least `role`.
+    pub fn require(&self, role: Role) -> RoleGate {
+        RoleGate {
+            auth: self.clone(),
+            role,
+        }
+    }
+
+    async fn authenticate(&self, token: Option<String>) -> Option<ApiKey> {
+        self.keys.authenticate(&token?).await
+    }
+}
+
+/// The bearer token of `request`, copied out so the (non-Sync) request is
+/// not borrowed across an await.
+fn bearer_token(request: &Request) -> Option<String> {
+    let value = request
+        .headers()
+        .get(header::AUTHORIZATION)?
+        .to_str()
+        .ok()?;
+    Some(value.strip_prefix("Bearer ")?.trim().to_string())
+}
+
+/// Minimum role for a group of routes (see `require_role`).
+#[derive(Clone)]
+pub struct RoleGate {
+    auth: ApiKeyAuth,
+    role: Role,
+}
+
+fn reject(status: StatusCode, error_type: &str, code: &str, message: &str) -> Response {
+    (
+        status,
+        [(header::WWW_AUTHENTICATE, "Bearer")],
+        Json(json!({
+            "error": {
+                "message": message,
+                "type": error_type,
+                "code": code,
+            }
+        })),
+    )
+        .into_response()
+}
+
+fn unauthorized() -> Response {
+    reject(
+        StatusCode::UNAUTHORIZED,
+        "authentication_error",
+        "invalid_api_key",
+        "Missing or invalid API key",
+    )
+}
+
+/// Require an API key with at least the gate's role.
+///
+/// A request without a key is authenticated by its client certificate, if it
+/// came with one. The `Caller` is also added to the response, for the access
+/// log.
+///
+/// Without `--require-api-key`, calls without a key are let through to all
+/// but admin routes; a key that is presented is still checked, and an
+/// invalid or revoked one is rejected rather than ignored.
+pub async fn require_role(
+    State(gate): State<RoleGate>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let token = bearer_token(&request);
+    let presented = token.is_some();
+    let cert_caller = match &token {
+        Some(_) => None,
+        None => request.extensions().get::<ClientCertCaller>().cloned(),
+    };
+    let caller = match (gate.auth.authenticate(token).await, cert_caller) {
+        (Some(key), _) if key.role >= gate.role => {
+            Caller::api_key(&key.name, key.role).with_namespace(key.namespace)
+        }
+        (Some(key), _) => {
+            return reject(
+                StatusCode::FORBIDDEN,
+                "permission_error",
+                "insufficient_role",
+                &format!("API key has role {}, {} required", key.role, gate.role),
+            )
+        }
+        (None, Some(ClientCertCaller(caller))) if caller.has_role(gate.role) => caller,
+        (None, Some(ClientCertCaller(caller))) => {
+            return reject(
+                StatusCode::FORBIDDEN,
+                "permission_error",
+                "insufficient_role",
+                &format!(
+                    "'{}' has role {}, {} required",
+                    caller.identity, caller.role, gate.role
+                ),
+            )
+        }
+        (None, None) if !presented && !gate.auth.required && gate.role < Role::Admin => {
+            return next.run(request).await
+        }
+        (None, None) => return unauthorized(),
+    };
+    request.extensions_mut().insert(caller.clone());
+    let mut response = next.run(request).await;
+    response.extensions_mut().insert(caller);
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, middleware, routing::get, Router};
+    use tower::ServiceExt;
+
+    async fn status(router: Router, token: Option<&str>) -> StatusCode {
+        let mut request = Request::builder().uri("/");
+        if let Some(token) = token {
+            request = request.header(header::AUTHORIZATION, format!("Bearer {}", token));
+        }
+        router
+            .oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+            .status()
+    }
+
+    #[tokio::test]
+    async fn test_required_and_admin_keys() {
+        let keys = ApiKeyManager::open(None).await.unwrap();
+        let (_, user) = keys.create("user", Role::Operator, None).await.unwrap();
+        let (_, admin) = keys.create("admin", Role::Admin, None).await.unwrap();
+        let auth = ApiKeyAuth::new(keys, true);
+
+        let protected = Router::new()
+            .route("/", get(|| async { "ok" }))
+            .route_layer(middleware::from_fn_with_state(
+                auth.require(Role::Readonly),
+                require_role,
+            ));
+        assert_eq!(
+            status(protected.clone(), None).await,
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            status(protected.clone(), Some("trk_nope")).await,
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(status(protected, Some(&user)).await, StatusCode::OK);
+
+        let admin_only = Router::new()
+            .route("/", get(|| async { "ok" }))
+            .route_layer(middleware::from_fn_with_state(
+                auth.require(Role::Admin),
+                require_role,
+            ));
+        assert_eq!(
+            status(admin_only.clone(), Some(&user)).await,
+            StatusCode::FORBIDDEN
+        );
+        assert_eq!(status(admin_only, Some(&admin)).await, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_readonly_key_cannot_operate() {
+        let keys = ApiKeyManager::open(None).await.unwrap();
+        let (_, viewer) = keys.create("viewer", Role::Readonly, None).await.unwrap();
+        // Open mode: anonymous calls pass, but a presented key is checked
+        let auth = ApiKeyAuth::new(keys, false);
+
+        let operate = Router::new()
+            .route("/", get(|| async { "ok" }))
+            .route_layer(middleware::from_fn_with_state(
+                auth.require(Role::Operator),
+                require_role,
+            ));
+        assert_eq!(status(operate.clone(), None).await, StatusCode::OK);
+        assert_eq!(
+            status(operate.clone(), Some(&viewer)).await,
+            StatusCode::FORBIDDEN
+        );
+        // An unknown key is not taken for an anonymous call
+        assert_eq!(
+            status(operate, Some("trk_nope")).await,
+            StatusCode::UNAUTHORIZED
+        );
+    }
+
+    #[tokio::test]
+    async fn test_open_mode_rejects_revoked_key() {
+        let keys = ApiKeyManager::open(None).await.unwrap();
+        let (revoked, key) = keys.create("ci", Role::Operator, None).await.unwrap();
+        let auth = ApiKeyAuth::new(keys.clone(), false);
+        let operate = Router::new()
+            .route("/", get(|| async { "ok" }))
+            .route_layer(middleware::from_fn_with_state(
+                auth.require(Role::Operator),
+                require_role,
+            ));
+        assert_eq!(status(operate.clone(), Some(&key)).await, StatusCode::OK);
+
+        keys.revoke(&revoked.id, None).await.unwrap();
+        assert_eq!(
+            status(operate.clone(), Some(&key)).await,
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(status(operate, None).await, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_client_certificate_stands_in_for_key() {
+        let auth = ApiKeyAuth::new(ApiKeyManager::open(None).await.unwrap(), true);
+        let operate = |cn: &str| {
+            let caller = ClientCertCaller(Caller::from_common_name(cn).unwrap());
+            Router::new()
+                .route("/", get(|| async { "ok" }))
+                .route_layer(middleware::from_fn_with_state(
+                    auth.require(Role::Operator),
+                    require_role,
+                ))
+                .layer(axum::Extension(caller))
+        };
+
+        assert_eq!(status(operate("worker:dev"), None).await, StatusCode::OK);
+        assert_eq!(
+            status(operate("readonly:dashboard"), None).await,
+            StatusCode::FORBIDDEN
+        );
+        // A presented key is what counts
+        assert_eq!(
+            status(operate("worker:dev"), Some("trk_nope")).await,
+            StatusCode::UNAUTHORIZED
+        );
+    }
+}
@@ -0,0 +1,266 @@
+//! Client address resolution behind reverse proxies.
+//!
+//! Behind a proxy every request comes from the proxy's address. When the
+//! connecting peer is a trusted proxy, the client is taken from the
+//! `Forwarded` header (RFC 7239), or `X-Forwarded-For` if there is none:
+//! the chain is walked from the right, skipping trusted proxies, and the
+//! first untrusted address is the client. Headers from untrusted peers are
+//! ignored, since anyone can send them.
+//!
+//! The resolved address is added to the request extensions as `ClientIp`
+//! and recorded on audit records and created tasks.
+
+use std::fmt;
+use std::net::{IpAddr, SocketAddr};
+use std::str::FromStr;
+use std::sync::Arc;
+
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    http::HeaderMap,
+    middleware::Next,
+    response::Response,
+    Extension,
+};
+use thiserror::Error;
+
+/// Errors parsing trusted proxy addresses.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum TrustedProxyError {
+    #[error("invalid trusted proxy '{0}': expected an IP address or CIDR range")]
+    Invalid(String),
+}
+
+/// An IP address range in CIDR notation (a bare address is a /32 or /128).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IpRange {
+    addr: IpAddr,
+    prefix: u8,
+}
+
+impl IpRange {
+    /// Returns true if `ip` is in the range.
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        match (self.addr, ip.to_canonical()) {
+            (IpAddr::V4(net), IpAddr::V4(ip)) => {
+                prefix_matches(u32::from(net).into(), u32::from(ip).into(), self.prefix, 32)
+            }
+            (IpAddr::V6(net), IpAddr::V6(ip)) => {
+                prefix_matches(u128::from(net), u128::from(ip), self.prefix, 128)
+            }
+            _ => false,
+        }
+    }
+}
+
+fn prefix_matches(net: u128, ip: u128, prefix: u8, bits: u8) -> bool {
+    let shift = u32::from(bits - prefix);
+    net.checked_shr(shift).unwrap_or(0) == ip.checked_shr(shift).unwrap_or(0)
+}
+
+impl FromStr for IpRange {
+    type Err = TrustedProxyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || TrustedProxyError::Invalid(s.to_string());
+        let (addr, prefix) = match s.trim().split_once('/') {
+            Some((addr, prefix)) => (addr, Some(prefix)),
+            None => (s.trim(), None),
+        };
+        let addr = IpAddr::from_str(addr)
+            .map_err(|_| invalid())?
+            .to_canonical();
+        let bits = if addr.is_ipv4() { 32 } else { 128 };
+        let prefix = match prefix {
+            Some(prefix) => prefix.parse::<u8>().map_err(|_| invalid())?,
+            None => bits,
+        };
+        if prefix > bits {
+            return Err(invalid());
+        }
+        Ok(Self { addr, prefix })
+    }
+}
+
+/// Proxies whose forwarding headers are believed.
+#[derive(Debug, Clone, Default)]
+pub struct TrustedProxies {
+    ranges: Vec<IpRange>,
+}
+
+impl TrustedProxies {
+    /// Parse a list of addresses and CIDR ranges.
+    pub fn parse<S: AsRef<str>>(entries: &[S]) -> Result<Self, TrustedProxyError> {
+        let ranges = entries
+            .iter()
+            .map(|e| e.as_ref().parse())
+            .collect::<Result<_, _>>()?;
+        Ok(Self { ranges })
+    }
+
+    fn is_trusted(&self, ip: IpAddr) -> bool {
+        self.ranges.iter().any(|r| r.contains(ip))
+    }
+
+    /// The client address of a request from `peer` with `headers`.
+    pub fn resolve(&self, peer: IpAddr, headers: &HeaderMap) -> IpAddr {
+        let peer = peer.to_canonical();
+        if !self.is_trusted(peer) {
+            return peer;
+        }
+
+        let chain = forwarded_chain(headers);
+        let mut client = peer;
+        for hop in chain.iter().rev() {
+            // An unknown or obfuscated hop ends what can be trusted
+            let Some(ip) = hop else {
+                break;
+            };
+            client = *ip;
+            if !self.is_trusted(client) {
+                break;
+            }
+        }
+        client
+    }
+}
+
+/// Addresses from `Forwarded` (or `X-Forwarded-For`), client first.
+/// Entries that are not an IP address are None.
+fn forwarded_chain(headers: &HeaderMap) -> Vec<Option<IpAddr>> {
+    let forwarded: Vec<&str> = headers
+        .get_all("forwarded")
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .collect();
+    if !forwarded.is_empty() {
+        return forwarded
+            .iter()
+            .flat_map(|v| v.split(','))
+            .map(|element| {
+                element
+                    .split(';')
+                    .filter_map(|pair| pair.split_once('='))
+                    .find(|(key, _)| key.trim().eq_ignore_ascii_case("for"))
+                    .and_then(|(_, value)| parse_node(value.trim().trim_matches('"')))
+            })
+            .collect();
+    }
+
+    headers
+        .get_all("x-forwarded-for")
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .map(|hop| parse_node(hop.trim()))
+        .collect()
+}
+
+/// Parse `1.2.3.4`, `1.2.3.4:80`, `2001:db8::1` or `[2001:db8::1]:80`.
+fn parse_node(node: &str) -> Option<IpAddr> {
+    let ip = match node.parse::<IpAddr>() {
+        Ok(ip) => ip,
+        Err(_) => match node.parse::<SocketAddr>() {
+            Ok(addr) => addr.ip(),
+            Err(_) => node
+                .strip_prefix('[')
+                .and_then(|n| n.strip_suffix(']'))?
+                .parse()
+                .ok()?,
+        },
+    };
+    Some(ip.to_canonical())
+}
+
+/// Resolved address of the HTTP client, in the request extensions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClientIp(pub IpAddr);
+
+impl fmt::Display for ClientIp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// The client address of an optional `ClientIp` extension, as recorded.
+pub fn client_ip_string(client_ip: Option<Extension<ClientIp>>) -> Option<String> {
+    client_ip.map(|Extension(ip)| ip.to_string())
+}
+
+/// Add the `ClientIp` of the request to its extensions.
+///
+/// Needs the server to be run with connect info; without it (as in tests)
+/// requests pass through without a `ClientIp`.
+pub async fn resolve_client_ip(
+    State(trusted): State<Arc<TrustedProxies>>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    if let Some(ConnectInfo(peer)) = request.extensions().get::<ConnectInfo<SocketAddr>>() {
+        let client = trusted.resolve(peer.ip(), request.headers());
+        request.extensions_mut().insert(ClientIp(client));
+    }
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    fn ip(s: &str) -> IpAddr {
+        s.parse().unwrap()
+    }
+
+    fn headers(pairs: &[(&'static str, &'static str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.append(*name, HeaderValue::from_static(value));
+        }
+        headers
+    }
+
+    #[test]
+    fn test_ranges() {
+        let net: IpRange = "10.0.0.0/8".parse().unwrap();
+        assert!(net.contains(ip("10.1.2.3")));
+        assert!(net.contains(ip("::ffff:10.1.2.3")));
+        assert!(!net.contains(ip("11.0.0.1")));
+        let any: IpRange = "::/0".parse().unwrap();
+        assert!(any.contains(ip("2001:db8::1")));
+        assert!("10.0.0.0/33".parse::<IpRange>().is_err());
+        assert!("proxy.local".parse::<IpRange>().is_err());
+    }
+
+    #[test]
+    fn test_resolve_through_trusted_proxies() {
+        let trusted = TrustedProxies::parse(&["10.0.0.0/8", "::1"]).unwrap();
+        let xff = headers(&[("x-forwarded-for", "1.1.1.1, 203.0.113.7, 10.0.0.2")]);
+
+        // The rightmost untrusted hop; anything left of it could be forged
+        assert_eq!(trusted.resolve(ip("10.0.0.1"), &xff), ip("203.0.113.7"));
+        // Headers from untrusted peers are ignored
+        assert_eq!(
+            trusted.resolve(ip("198.51.100.1"), &xff),
+            ip("198.51.100.1")
+        );
+        assert_eq!(
+            TrustedProxies::default().resolve(ip("10.0.0.1"), &xff),
+            ip("10.0.0.1")
+        );
+
+        // Forwarded wins over X-Forwarded-For
+        let forwarded = headers(&[
+            (
+                "forwarded",
+                r#"for="[2001:db8::7]:4711";proto=https, for=10.0.0.3"#,
+            ),
+            ("x-forwarded-for", "203.0.113.7"),
+        ]);
+        assert_eq!(trusted.resolve(ip("::1"), &forwarded), ip("2001:db8::7"));
+
+        // An obfuscated hop stops the walk at the proxy that reported it
+        let unknown = headers(&[("forwarded", "for=unknown, for=10.0.0.3")]);
+        assert_eq!(trusted.resolve(ip("10.0.0.1"), &unknown), ip("10.0.0.3"));
+    }
+}
@@ -0,0 +1,141 @@
+//! API key admin handlers.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+use tracing::info;
+
+use crate::control_plane::api_keys::{ApiKey, ApiKeyError, ApiKeyManager};
+use crate::control_plane::audit::AuditAction;
+use crate::control_plane::http::client_ip::{client_ip_string, ClientIp};
+use crate::control_plane::http::responses::{
+    ApiKeyResponse, CreateApiKeyRequest, CreateApiKeyResponse, ErrorResponse,
+};
+use crate::control_plane::rbac::Caller;
+use crate::control_plane::reload::ConfigReloader;
+use crate::control_plane::state::AppState;
+
+/// State of the admin routes: the API keys, the app state for auditing and
+/// the settings reloader, if a settings file is configured.
+#[derive(Clone)]
+pub struct AdminState {
+    pub keys: Arc<ApiKeyManager>,
+    pub app: Arc<AppState>,
+    pub reloader: Option<Arc<ConfigReloader>>,
+}
+
+impl From<ApiKey> for ApiKeyResponse {
+    fn from(key: ApiKey) -> Self {
+        Self {
+            id: key.id,
+            name: key.name,
+            prefix: key.prefix,
+            role: key.role,
+            namespace: key.namespace,
+            created_at: key.created_at.to_rfc3339(),
+        }
+    }
+}
+
+impl IntoResponse for ApiKeyError {
+    fn into_response(self) -> Response {
+        match self {
+            ApiKeyError::Lookup(e) => e.into_response(),
+            ApiKeyError::Namespace(e) => e.into_response(),
+            ApiKeyError::MissingName => (
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse {
+                    error: self.to_string(),
+                }),
+            )
+                .into_response(),
+            ApiKeyError::Io { .. } | ApiKeyError::Serialization(_) => (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: self.to_string(),
+                }),
+            )
+                .into_response(),
+        }
+    }
+}
+
+/// List API keys.
+///
+/// GET /v1/admin/api-keys
+pub async fn list_api_keys(
+    State(admin): State<AdminState>,
+    Extension(caller): Extension<Caller>,
+) -> impl IntoResponse {
+    let response: Vec<ApiKeyResponse> = admin
+        .keys
+        .list(caller.namespace.as_deref())
+        .await
+        .into_iter()
+        .map(Into::into)
+        .collect();
+    Json(response)
+}
+
+/// Create an API key. The plaintext key is only returned here.
+///
+/// POST /v1/admin/api-keys
+pub async fn create_api_key(
+    State(admin): State<AdminState>,
+    Extension(caller): Extension<Caller>,
+    client_ip: Option<Extension<ClientIp>>,
+    Json(req): Json<CreateApiKeyRequest>,
+) -> Result<impl IntoResponse, ApiKeyError> {
+    // Admins bound to a namespace only hand out keys for it
+    let namespace = caller.scope(req.namespace.as_deref())?;
+    let (api_key, key) = admin.keys.create(&req.name, req.role, namespace).await?;
+    info!(key_id = %api_key.id, created_by = %caller.identity, "API key created via HTTP");
+    admin
+        .app
+        .audit_from(
+            &caller.identity,
+            client_ip_string(client_ip).as_deref(),
+            AuditAction::ApiKeyCreated,
+            &api_key.id,
+            &api_key,
+        )
+        .await;
+    Ok((
+        StatusCode::CREATED,
+        Json(CreateApiKeyResponse {
+            api_key: api_key.into(),
+            key,
+        }),
+    ))
+}
+
+/// Revoke an API key.
+///
+/// DELETE /v1/admin/api-keys/:id
+///
+/// `id` may be a unique prefix of the full ID.
+pub async fn revoke_api_key(
+    State(admin): State<AdminState>,
+    Extension(caller): Extension<Caller>,
+    client_ip: Option<Extension<ClientIp>>,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse, ApiKeyError> {
+    let key = admin.keys.revoke(&id, caller.namespace.as_deref()).await?;
+    info!(key_id = %key.id, revoked_by = %caller.identity, "API key revoked via HTTP");
+    admin
+        .app
+        .audit_from(
+            &caller.identity,
+            client_ip_string(client_ip).as_deref(),
+            AuditAction::ApiKeyRevoked,
+            &key.id,
+            &key,
+        )
+        .await;
+    Ok(Json(ApiKeyResponse::from(key)))
+}
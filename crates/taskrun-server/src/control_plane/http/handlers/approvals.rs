@@ -0,0 +1,99 @@
+//! HTTP handlers for tool-use approvals.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+use tracing::info;
+
+use crate::control_plane::approvals::{self, Approval, ApprovalError};
+use crate::control_plane::http::responses::{DecideApprovalRequest, ErrorResponse};
+use crate::control_plane::rbac::Caller;
+use crate::control_plane::state::AppState;
+
+impl IntoResponse for ApprovalError {
+    fn into_response(self) -> Response {
+        let status = match self {
+            ApprovalError::Lookup(e) => return e.into_response(),
+            ApprovalError::Expired(_) => StatusCode::CONFLICT,
+            ApprovalError::WorkerGone(_) => StatusCode::SERVICE_UNAVAILABLE,
+        };
+        (
+            status,
+            Json(ErrorResponse {
+                error: self.to_string(),
+            }),
+        )
+            .into_response()
+    }
+}
+
+/// List pending tool approvals, oldest first.
+///
+/// GET /v1/approvals
+pub async fn list_approvals(
+    State(state): State<Arc<AppState>>,
+    caller: Option<Extension<Caller>>,
+) -> Json<Vec<Approval>> {
+    let namespace = caller.and_then(|c| c.0.namespace);
+    Json(state.approvals.list(namespace.as_deref()).await)
+}
+
+/// Let a pending tool use proceed.
+///
+/// POST /v1/approvals/:id/approve
+///
+/// `id` may be a unique prefix of the full ID. The body `{"reason": ...,
+/// "always_allow": true}` is optional.
+pub async fn approve_approval(
+    State(state): State<Arc<AppState>>,
+    caller: Option<Extension<Caller>>,
+    Path(id): Path<String>,
+    body: Option<Json<DecideApprovalRequest>>,
+) -> Result<Json<Approval>, ApprovalError> {
+    decide(&state, caller, &id, true, body).await
+}
+
+/// Deny a pending tool use. Claude is told the reason.
+///
+/// POST /v1/approvals/:id/deny
+///
+/// `id` may be a unique prefix of the full ID. The body `{"reason": ...}` is optional.
+pub async fn deny_approval(
+    State(state): State<Arc<AppState>>,
+    caller: Option<Extension<Caller>>,
+    Path(id): Path<String>,
+    body: Option<Json<DecideApprovalRequest>>,
+) -> Result<Json<Approval>, ApprovalError> {
+    decide(&state, caller, &id, false, body).await
+}
+
+async fn decide(
+    state: &AppState,
+    caller: Option<Extension<Caller>>,
+    id: &str,
+    allowed: bool,
+    body: Option<Json<DecideApprovalRequest>>,
+) -> Result<Json<Approval>, ApprovalError> {
+    let (actor, namespace) = caller.map_or_else(
+        || ("http-api".to_string(), None),
+        |c| (c.0.identity, c.0.namespace),
+    );
+    let Json(req) = body.unwrap_or_default();
+    let approval = approvals::decide(
+        state,
+        id,
+        namespace.as_deref(),
+        allowed,
+        req.always_allow,
+        &req.reason,
+        &actor,
+    )
+    .await?;
+    info!(approval_id = %approval.id, allowed, decided_by = %actor, "Tool approval decided via HTTP");
+    Ok(Json(approval))
+}
@@ -0,0 +1,24 @@
+//! Audit log handler.
+
+use axum::{
+    extract::{Query, State},
+    Extension, Json,
+};
+
+use crate::control_plane::audit::{AuditQuery, AuditRecord};
+use crate::control_plane::http::handlers::AdminState;
+use crate::control_plane::rbac::{Caller, NamespaceError};
+
+/// Query the audit log, newest first.
+///
+/// GET /v1/audit?action=&actor=&target=&since_ms=&limit=
+///
+/// The log spans every namespace, so admins bound to one cannot read it.
+pub async fn list_audit(
+    State(admin): State<AdminState>,
+    Extension(caller): Extension<Caller>,
+    Query(query): Query<AuditQuery>,
+) -> Result<Json<Vec<AuditRecord>>, NamespaceError> {
+    caller.require_unbound()?;
+    Ok(Json(admin.app.audit.query(&query).await))
+}
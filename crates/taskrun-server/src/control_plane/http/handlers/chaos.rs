@@ -0,0 +1,51 @@
+//! Chaos flag handlers.
+
+use axum::{
+    extract::{rejection::JsonRejection, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+
+use crate::control_plane::chaos::{self, ChaosError, ChaosUpdate};
+use crate::control_plane::http::handlers::AdminState;
+use crate::control_plane::http::responses::ErrorResponse;
+use crate::control_plane::rbac::Caller;
+
+fn error(status: StatusCode, error: String) -> Response {
+    (status, Json(ErrorResponse { error })).into_response()
+}
+
+/// Current chaos flags.
+///
+/// GET /v1/admin/chaos
+pub async fn get_chaos(State(admin): State<AdminState>) -> Response {
+    Json(admin.app.chaos.get()).into_response()
+}
+
+/// Change chaos flags. Fields left out keep their value.
+///
+/// PUT /v1/admin/chaos
+///
+/// Faults hit every namespace, so admins bound to a namespace can't set them.
+pub async fn update_chaos(
+    State(admin): State<AdminState>,
+    Extension(caller): Extension<Caller>,
+    body: Result<Json<ChaosUpdate>, JsonRejection>,
+) -> Response {
+    if caller.namespace.is_some() {
+        return error(
+            StatusCode::FORBIDDEN,
+            "Chaos flags need an admin key not bound to a namespace".to_string(),
+        );
+    }
+    let update = match body {
+        Ok(Json(update)) => update,
+        Err(rejection) => return error(StatusCode::BAD_REQUEST, rejection.body_text()),
+    };
+    match chaos::update(&admin.app, &caller.identity, update).await {
+        Ok(settings) => Json(settings).into_response(),
+        Err(e @ ChaosError::Disabled(_)) => error(StatusCode::NOT_FOUND, e.to_string()),
+        Err(e) => error(StatusCode::UNPROCESSABLE_ENTITY, e.to_string()),
+    }
+}
@@ -0,0 +1,20 @@
+//! Debug stats handler.
+
+use axum::{extract::State, Extension, Json};
+
+use crate::control_plane::debug_stats::{self, DebugStats};
+use crate::control_plane::http::handlers::AdminState;
+use crate::control_plane::rbac::{Caller, NamespaceError};
+
+/// Process memory, tokio task counts and in-memory state sizes.
+///
+/// GET /v1/debug/stats
+///
+/// The stats span every namespace, so admins bound to one cannot read them.
+pub async fn get_debug_stats(
+    State(admin): State<AdminState>,
+    Extension(caller): Extension<Caller>,
+) -> Result<Json<DebugStats>, NamespaceError> {
+    caller.require_unbound()?;
+    Ok(Json(debug_stats::collect(&admin.app).await))
+}
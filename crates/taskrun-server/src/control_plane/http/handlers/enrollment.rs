@@ -1,11 +1,19 @@
-//! Worker enrollment handler.
+//! Worker enrollment, certificate revocation list and CA rotation handlers.
 
 use std::sync::Arc;
 
-use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
+use axum::{
+    extract::State,
+    http::{header, StatusCode},
+    response::IntoResponse,
+    Extension, Json,
+};
 use tracing::{error, info, warn};
 
-use crate::control_plane::crypto::hash_token;
+use crate::control_plane::audit::AuditAction;
+use crate::control_plane::enrollment::{self, EnrollError};
+use crate::control_plane::http::client_ip::{client_ip_string, ClientIp};
+use crate::control_plane::http::handlers::AdminState;
 use crate::control_plane::http::responses::{EnrollRequest, EnrollResponse, ErrorResponse};
 use crate::control_plane::state::AppState;
 
@@ -14,81 +22,112 @@ use crate::control_plane::state::AppState;
 /// Validates bootstrap token, signs CSR, and returns worker certificate.
 pub async fn enroll(
     State(state): State<Arc<AppState>>,
+    client_ip: Option<Extension<ClientIp>>,
     Json(req): Json<EnrollRequest>,
 ) -> impl IntoResponse {
-    // Check if CA is configured
-    let ca = match &state.ca {
-        Some(ca) => ca,
-        None => {
-            error!("Enrollment requested but CA is not configured");
-            return (
-                StatusCode::SERVICE_UNAVAILABLE,
-                Json(ErrorResponse {
-                    error: "Certificate authority not configured".to_string(),
-                }),
-            )
-                .into_response();
-        }
-    };
-
-    // Validate bootstrap token
-    let token_hash = hash_token(&req.bootstrap_token);
-
-    let token_valid = {
-        let mut tokens = state.bootstrap_tokens.write().await;
-        if let Some(token) = tokens.get_mut(&token_hash) {
-            if token.is_valid() {
-                token.consume();
-                info!(token_hash = %token_hash, "Bootstrap token consumed");
-                true
-            } else {
-                warn!(token_hash = %token_hash, consumed = token.consumed, "Invalid or expired token");
-                false
-            }
-        } else {
-            warn!("Unknown bootstrap token attempted");
-            false
-        }
-    };
-
-    if !token_valid {
-        return (
-            StatusCode::UNAUTHORIZED,
-            Json(ErrorResponse {
-                error: "Invalid or expired bootstrap token".to_string(),
-            }),
-        )
-            .into_response();
-    }
-
-    // Sign the CSR
-    match ca.sign_csr(&req.csr) {
-        Ok(signed) => {
+    let client_ip = client_ip_string(client_ip);
+    match enrollment::enroll(&state, &req.bootstrap_token, &req.csr).await {
+        Ok((signed, token)) => {
             info!(
                 worker_id = %signed.worker_id,
                 expires_at = %signed.expires_at,
                 "Worker certificate issued"
             );
+            state
+                .audit_from(
+                    &format!("bootstrap-token:{}", token.id()),
+                    client_ip.as_deref(),
+                    AuditAction::WorkerEnrolled,
+                    &signed.worker_id,
+                    &signed.cert_pem,
+                )
+                .await;
 
+            let ca_cert = state
+                .ca
+                .as_ref()
+                .map(|ca| ca.trust_bundle_pem())
+                .unwrap_or_default();
             (
                 StatusCode::OK,
                 Json(EnrollResponse {
                     worker_cert: signed.cert_pem,
-                    ca_cert: ca.ca_cert_pem().to_string(),
+                    ca_cert,
                     expires_at: signed.expires_at.to_rfc3339(),
+                    serial_number: signed.serial,
+                    spiffe_id: signed.spiffe_id,
                 }),
             )
                 .into_response()
         }
         Err(e) => {
-            error!(error = %e, "Failed to sign CSR");
+            let status = match e {
+                EnrollError::NoCa => {
+                    error!("Enrollment requested but CA is not configured");
+                    StatusCode::SERVICE_UNAVAILABLE
+                }
+                EnrollError::InvalidToken => {
+                    warn!(
+                        client_ip = client_ip.as_deref(),
+                        "Invalid bootstrap token attempted"
+                    );
+                    StatusCode::UNAUTHORIZED
+                }
+                EnrollError::TokenUsed => StatusCode::CONFLICT,
+                EnrollError::IdentityMismatch(_) => StatusCode::FORBIDDEN,
+                EnrollError::Csr(ref e) => {
+                    error!(error = %e, "Failed to sign CSR");
+                    StatusCode::BAD_REQUEST
+                }
+            };
             (
-                StatusCode::BAD_REQUEST,
+                status,
                 Json(ErrorResponse {
-                    error: format!("Failed to sign CSR: {}", e),
+                    error: e.to_string(),
                 }),
             )
                 .into_response()
         }
     }
 }
+
+/// Certificate revocation list of the CA, DER encoded. Public, like the CA
+/// certificate itself.
+pub async fn get_crl(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    let Some(ca) = state.ca.as_ref() else {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(ErrorResponse {
+                error: EnrollError::NoCa.to_string(),
+            }),
+        )
+            .into_response();
+    };
+    match ca.crl_der() {
+        Ok(crl) => ([(header::CONTENT_TYPE, "application/pkix-crl")], crl).into_response(),
+        Err(e) => {
+            error!(error = %e, "Failed to sign CRL");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: e.to_string(),
+                }),
+            )
+                .into_response()
+        }
+    }
+}
+
+/// Current CA and the state of a CA rotation.
+pub async fn get_ca_rotation(State(admin): State<AdminState>) -> impl IntoResponse {
+    match admin.app.ca.as_ref() {
+        Some(ca) => Json(ca.rotation_status()).into_response(),
+        None => (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(ErrorResponse {
+                error: EnrollError::NoCa.to_string(),
+            }),
+        )
+            .into_response(),
+    }
+}
@@ -2,17 +2,22 @@
 
 use std::sync::Arc;
 
+use std::ops::Range;
+
 use axum::{
-    extract::{Path, State},
-    http::StatusCode,
-    response::IntoResponse,
-    Json,
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Extension, Json,
 };
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
 
-use taskrun_core::{RunEventType, TaskId};
+use taskrun_core::{Artifact, RunEventType, RunId};
 
-use crate::control_plane::state::AppState;
+use crate::control_plane::http::responses::ErrorResponse;
+use crate::control_plane::rbac::Caller;
+use crate::control_plane::state::{AppState, IdLookupError};
 
 /// Response structure for a run event.
 #[derive(Serialize)]
@@ -23,6 +28,8 @@ pub struct EventResponse {
     pub event_type: String,
     pub timestamp_ms: i64,
     pub metadata: std::collections::HashMap<String, String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub artifacts: Vec<Artifact>,
 }
 
 impl EventResponse {
@@ -35,6 +42,9 @@ impl EventResponse {
             RunEventType::OutputGenerated => "output_generated",
             RunEventType::ExecutionCompleted => "execution_completed",
             RunEventType::ExecutionFailed => "execution_failed",
+            RunEventType::Milestone => "milestone",
+            RunEventType::Log => "log",
+            RunEventType::RunSummary => "run_summary",
         };
 
         Self {
@@ -44,6 +54,7 @@ impl EventResponse {
             event_type: event_type_str.to_string(),
             timestamp_ms: event.timestamp_ms,
             metadata: event.metadata.clone(),
+            artifacts: event.artifacts.clone(),
         }
     }
 }
@@ -51,16 +62,22 @@ impl EventResponse {
 /// Get events for a specific task.
 ///
 /// GET /v1/tasks/:task_id/events
+///
+/// `task_id` may be a unique prefix of the full ID.
 pub async fn get_task_events(
     State(state): State<Arc<AppState>>,
+    caller: Option<Extension<Caller>>,
     Path(task_id): Path<String>,
-) -> impl IntoResponse {
-    let task_id = TaskId::new(&task_id);
+) -> Result<impl IntoResponse, IdLookupError> {
+    let namespace = caller.and_then(|c| c.0.namespace);
+    let task_id = state
+        .resolve_task_id(&task_id, namespace.as_deref())
+        .await?;
     let events = state.get_events_by_task(&task_id).await;
 
     let response: Vec<EventResponse> = events.iter().map(EventResponse::from_domain).collect();
 
-    (StatusCode::OK, Json(response))
+    Ok((StatusCode::OK, Json(response)))
 }
 
 /// Response structure for task output.
@@ -68,22 +85,149 @@ pub async fn get_task_events(
 pub struct OutputResponse {
     pub task_id: String,
     pub output: Option<String>,
+    /// Run the output belongs to.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub run_id: Option<String>,
+    /// Byte offset of `output` within the full output.
+    pub offset: u64,
+    /// Size of the full output in bytes.
+    pub total_bytes: u64,
+    /// Offset of the next page, if there is more output.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_offset: Option<u64>,
+}
+
+/// Pagination for task output.
+#[derive(Debug, Default, Deserialize)]
+pub struct OutputQuery {
+    /// Byte offset to start from.
+    pub offset: Option<u64>,
+    /// Maximum number of bytes to return.
+    pub limit: Option<u64>,
 }
 
 /// Get output for a specific task.
 ///
 /// GET /v1/tasks/:task_id/output
+///
+/// `task_id` may be a unique prefix of the full ID. Returns JSON, paginated
+/// with `?offset=&limit=` (in bytes). With a `Range: bytes=...` header, returns
+/// the raw bytes instead (206 Partial Content).
 pub async fn get_task_output(
     State(state): State<Arc<AppState>>,
+    caller: Option<Extension<Caller>>,
     Path(task_id): Path<String>,
-) -> impl IntoResponse {
-    let task_id = TaskId::new(&task_id);
-    let output = state.get_output_by_task(&task_id).await;
+    Query(query): Query<OutputQuery>,
+    headers: HeaderMap,
+) -> Result<Response, IdLookupError> {
+    let namespace = caller.and_then(|c| c.0.namespace);
+    let task_id = state
+        .resolve_task_id(&task_id, namespace.as_deref())
+        .await?;
+    let run_id = state.output_run_for_task(&task_id).await;
+    let total = match &run_id {
+        Some(run_id) => state.outputs.len(run_id).await.unwrap_or(0),
+        None => 0,
+    };
+
+    if let Some(range) = headers.get(header::RANGE) {
+        let Some(range) = range.to_str().ok().and_then(|r| parse_range(r, total)) else {
+            return Ok((
+                StatusCode::RANGE_NOT_SATISFIABLE,
+                [(header::CONTENT_RANGE, format!("bytes */{}", total))],
+            )
+                .into_response());
+        };
+        let data = match read_output(&state, run_id.as_ref(), range.clone()).await {
+            Ok(data) => data,
+            Err(response) => return Ok(response),
+        };
+        return Ok((
+            StatusCode::PARTIAL_CONTENT,
+            [
+                (
+                    header::CONTENT_TYPE,
+                    "text/plain; charset=utf-8".to_string(),
+                ),
+                (header::ACCEPT_RANGES, "bytes".to_string()),
+                (
+                    header::CONTENT_RANGE,
+                    format!("bytes {}-{}/{}", range.start, range.end - 1, total),
+                ),
+            ],
+            data,
+        )
+            .into_response());
+    }
+
+    let offset = query.offset.unwrap_or(0).min(total);
+    let end = query
+        .limit
+        .map_or(total, |limit| offset.saturating_add(limit).min(total));
+    let output = match &run_id {
+        Some(_) => match read_output(&state, run_id.as_ref(), offset..end).await {
+            Ok(data) => Some(String::from_utf8_lossy(&data).into_owned()),
+            Err(response) => return Ok(response),
+        },
+        None => None,
+    };
 
     let response = OutputResponse {
         task_id: task_id.as_str().to_string(),
         output,
+        run_id: run_id.map(|r| r.as_str().to_string()),
+        offset,
+        total_bytes: total,
+        next_offset: (end < total).then_some(end),
+    };
+
+    Ok((StatusCode::OK, Json(response)).into_response())
+}
+
+/// Read a byte range of a run's output, or an error response.
+async fn read_output(
+    state: &AppState,
+    run_id: Option<&RunId>,
+    range: Range<u64>,
+) -> Result<Vec<u8>, Response> {
+    let Some(run_id) = run_id else {
+        return Ok(Vec::new());
     };
+    state.outputs.read_range(run_id, range).await.map_err(|e| {
+        warn!(run_id = %run_id, error = %e, "Failed to read run output");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse {
+                error: format!("Failed to read output: {}", e),
+            }),
+        )
+            .into_response()
+    })
+}
 
-    (StatusCode::OK, Json(response))
+/// Parse a single-range `Range` header (`bytes=a-b`, `bytes=a-`, `bytes=-n`)
+/// against an output of `total` bytes. None if malformed or unsatisfiable.
+fn parse_range(header: &str, total: u64) -> Option<Range<u64>> {
+    let (start, end) = header.strip_prefix("bytes=")?.split_once('-')?;
+    let range = match (start.trim(), end.trim()) {
+        ("", suffix) => total.saturating_sub(suffix.parse().ok()?)..total,
+        (start, "") => start.parse().ok()?..total,
+        (start, end) => start.parse().ok()?..end.parse::<u64>().ok()?.saturating_add(1).min(total),
+    };
+    (range.start < range.end).then_some(range)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_range() {
+        assert_eq!(parse_range("bytes=0-9", 100), Some(0..10));
+        assert_eq!(parse_range("bytes=90-", 100), Some(90..100));
+        assert_eq!(parse_range("bytes=-5", 100), Some(95..100));
+        assert_eq!(parse_range("bytes=50-500", 100), Some(50..100));
+        assert_eq!(parse_range("bytes=100-", 100), None);
+        assert_eq!(parse_range("items=0-9", 100), None);
+    }
 }
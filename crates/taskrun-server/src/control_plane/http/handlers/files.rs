@@ -0,0 +1,299 @@
+//! HTTP handlers for uploaded files and run artifacts.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Multipart, Path, Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use taskrun_core::artifact::mime_type_for;
+use taskrun_core::{RunId, TaskId};
+
+use crate::control_plane::audit::AuditAction;
+use crate::control_plane::files::{FileError, RunArtifact, StoredFile};
+use crate::control_plane::http::client_ip::{client_ip_string, ClientIp};
+use crate::control_plane::http::responses::ErrorResponse;
+use crate::control_plane::rbac::{self, Caller};
+use crate::control_plane::state::{AppState, IdLookupError};
+
+/// Query parameters for `POST /v1/files`.
+#[derive(Debug, Deserialize)]
+pub struct UploadFileParams {
+    /// Namespace of the tasks that may use the file (default: the caller's).
+    pub namespace: Option<String>,
+}
+
+/// An uploaded file.
+#[derive(Debug, Serialize)]
+pub struct FileObject {
+    pub id: String,
+    pub object: &'static str,
+    pub filename: String,
+    pub bytes: u64,
+    /// Unix timestamp (seconds).
+    pub created_at: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mime_type: Option<String>,
+    pub namespace: String,
+}
+
+impl From<StoredFile> for FileObject {
+    fn from(file: StoredFile) -> Self {
+        Self {
+            id: file.id,
+            object: "file",
+            filename: file.name,
+            bytes: file.size_bytes,
+            created_at: file.created_at.timestamp(),
+            mime_type: file.mime_type,
+            namespace: file.namespace,
+        }
+    }
+}
+
+/// Upload a file to stage in the workspace of later tasks.
+///
+/// POST /v1/files (multipart, with the file in a `file` field)
+///
+/// Tasks name the returned ID in `input_files` (gRPC `CreateTask`, CLI
+/// `--input-file`) or as an `input_file` content part of `/v1/responses`.
+pub async fn upload_file(
+    State(state): State<Arc<AppState>>,
+    caller: Option<Extension<Caller>>,
+    client_ip: Option<Extension<ClientIp>>,
+    Query(params): Query<UploadFileParams>,
+    mut multipart: Multipart,
+) -> Response {
+    let caller = caller.map(|c| c.0);
+    let namespace = match rbac::namespace_for_new(caller.as_ref(), params.namespace.as_deref()) {
+        Ok(namespace) => namespace,
+        Err(e) => return error(StatusCode::FORBIDDEN, e.to_string()),
+    };
+
+    let field = loop {
+        match multipart.next_field().await {
+            Ok(Some(field)) if field.name() == Some("file") => break field,
+            Ok(Some(_)) => continue,
+            Ok(None) => return error(StatusCode::BAD_REQUEST, "missing 'file' field".into()),
+            Err(e) => return error(e.status(), e.body_text()),
+        }
+    };
+    // Only the file name; the worker decides where it goes
+    let name = field
+        .file_name()
+        .and_then(|name| name.rsplit(['/', '\\']).next())
+        .map(str::trim)
+        .unwrap_or_default()
+        .to_string();
+    if name.is_empty() || name == "." || name == ".." {
+        return error(StatusCode::BAD_REQUEST, "the file needs a name".into());
+    }
+    let mime_type = field
+        .content_type()
+        .filter(|m| *m != "application/octet-stream")
+        .map(str::to_string)
+        .or_else(|| mime_type_for(&name).map(str::to_string));
+    let content = match field.bytes().await {
+        Ok(content) => content.to_vec(),
+        Err(e) => return error(e.status(), e.body_text()),
+    };
+
+    let file = match state
+        .files
+        .upload(&namespace, &name, mime_type, content)
+        .await
+    {
+        Ok(file) => file,
+        Err(e) => return file_error(e),
+    };
+    let actor = caller.map_or_else(|| "http-api".to_string(), |c| c.identity);
+    info!(file_id = %file.id, name = %file.name, size_bytes = file.size_bytes, uploaded_by = %actor, "File uploaded");
+    state
+        .audit_from(
+            &actor,
+            client_ip_string(client_ip).as_deref(),
+            AuditAction::FileUploaded,
+            &file.id,
+            &file,
+        )
+        .await;
+    (StatusCode::CREATED, Json(FileObject::from(file))).into_response()
+}
+
+/// Delete an uploaded file.
+///
+/// DELETE /v1/files/:file_id
+///
+/// Tasks that named the file and have not started run without it.
+pub async fn delete_file(
+    State(state): State<Arc<AppState>>,
+    caller: Option<Extension<Caller>>,
+    client_ip: Option<Extension<ClientIp>>,
+    Path(file_id): Path<String>,
+) -> Response {
+    let (actor, namespace) = caller.map_or_else(
+        || ("http-api".to_string(), None),
+        |c| (c.0.identity, c.0.namespace),
+    );
+    let file = match state.files.delete(&file_id, namespace.as_deref()).await {
+        Ok(file) => file,
+        Err(e) => return file_error(e),
+    };
+    state
+        .audit_from(
+            &actor,
+            client_ip_string(client_ip).as_deref(),
+            AuditAction::FileDeleted,
+            &file.id,
+            &file,
+        )
+        .await;
+    Json(FileObject::from(file)).into_response()
+}
+
+/// An artifact of a task's run.
+#[derive(Debug, Serialize)]
+pub struct ArtifactResponse {
+    pub run_id: RunId,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mime_type: Option<String>,
+    pub size_bytes: u64,
+    /// Unix timestamp (milliseconds) of the upload.
+    pub uploaded_at_ms: i64,
+    /// Where to download the artifact.
+    pub url: String,
+}
+
+impl ArtifactResponse {
+    fn new(task_id: &TaskId, artifact: RunArtifact) -> Self {
+        let url = format!(
+            "/v1/tasks/{}/artifacts/{}?run_id={}",
+            task_id,
+            path_segment(&artifact.file.name),
+            artifact.run_id
+        );
+        Self {
+            run_id: artifact.run_id,
+            name: artifact.file.name,
+            mime_type: artifact.file.mime_type,
+            size_bytes: artifact.file.size_bytes,
+            uploaded_at_ms: artifact.file.created_at.timestamp_millis(),
+            url,
+        }
+    }
+}
+
+/// Artifacts of a task.
+#[derive(Debug, Serialize)]
+pub struct ArtifactListResponse {
+    pub task_id: TaskId,
+    pub artifacts: Vec<ArtifactResponse>,
+}
+
+/// List the artifacts the task's runs uploaded.
+///
+/// GET /v1/tasks/:task_id/artifacts
+///
+/// `task_id` may be a unique prefix of the full ID.
+pub async fn list_task_artifacts(
+    State(state): State<Arc<AppState>>,
+    caller: Option<Extension<Caller>>,
+    Path(task_id): Path<String>,
+) -> Result<Json<ArtifactListResponse>, IdLookupError> {
+    let namespace = caller.and_then(|c| c.0.namespace);
+    let task_id = state
+        .resolve_task_id(&task_id, namespace.as_deref())
+        .await?;
+    let artifacts = state
+        .files
+        .artifacts(&task_id)
+        .await
+        .into_iter()
+        .map(|artifact| ArtifactResponse::new(&task_id, artifact))
+        .collect();
+    Ok(Json(ArtifactListResponse { task_id, artifacts }))
+}
+
+/// Query parameters for downloading an artifact.
+#[derive(Debug, Deserialize)]
+pub struct DownloadArtifactParams {
+    /// Run that produced the artifact (default: the latest run with one of
+    /// that name).
+    pub run_id: Option<String>,
+}
+
+/// Download an artifact of a task.
+///
+/// GET /v1/tasks/:task_id/artifacts/:name
+pub async fn download_task_artifact(
+    State(state): State<Arc<AppState>>,
+    caller: Option<Extension<Caller>>,
+    Path((task_id, name)): Path<(String, String)>,
+    Query(params): Query<DownloadArtifactParams>,
+) -> Response {
+    let namespace = caller.and_then(|c| c.0.namespace);
+    let task_id = match state.resolve_task_id(&task_id, namespace.as_deref()).await {
+        Ok(task_id) => task_id,
+        Err(e) => return e.into_response(),
+    };
+    let run_id = params.run_id.map(RunId::new);
+    let Some(artifact) = state.files.artifact(&task_id, &name, run_id.as_ref()).await else {
+        return error(
+            StatusCode::NOT_FOUND,
+            format!("Task {} has no artifact '{}'", task_id, name),
+        );
+    };
+    let content = match state.files.read_artifact(&artifact).await {
+        Ok(content) => content,
+        Err(e) => return file_error(e),
+    };
+    let file = artifact.file;
+    let content_type = file
+        .mime_type
+        .unwrap_or_else(|| "application/octet-stream".to_string());
+    let disposition = format!(
+        "attachment; filename=\"{}\"",
+        file.name.replace(['"', '\\'], "_")
+    );
+    (
+        [
+            (header::CONTENT_TYPE, content_type),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        content,
+    )
+        .into_response()
+}
+
+/// `name` percent-encoded for use as a URL path segment.
+fn path_segment(name: &str) -> String {
+    name.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+fn file_error(e: FileError) -> Response {
+    let status = match e {
+        FileError::NotFound(_) => StatusCode::NOT_FOUND,
+        FileError::TooLarge { .. } => StatusCode::PAYLOAD_TOO_LARGE,
+        FileError::InputsTooLarge { .. } | FileError::OutOfOrder { .. } => StatusCode::BAD_REQUEST,
+        FileError::Full { .. } => StatusCode::INSUFFICIENT_STORAGE,
+        FileError::Store(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    error(status, e.to_string())
+}
+
+fn error(status: StatusCode, error: String) -> Response {
+    (status, Json(ErrorResponse { error })).into_response()
+}
@@ -0,0 +1,37 @@
+//! Grafana SimpleJSON datasource handlers.
+//!
+//! Point a SimpleJSON datasource at `http://<host>:<port>/v1/grafana`.
+
+use std::sync::Arc;
+
+use axum::{extract::State, response::IntoResponse, Json};
+use serde::Deserialize;
+
+use crate::control_plane::grafana::{self, QueryRequest};
+use crate::control_plane::state::AppState;
+
+/// Body of a `/search` request.
+#[derive(Debug, Default, Deserialize)]
+pub struct SearchRequest {
+    #[serde(default)]
+    pub target: String,
+}
+
+/// Connection test used by Grafana's "Save & test".
+pub async fn grafana_health() -> impl IntoResponse {
+    Json(serde_json::json!({ "status": "ok" }))
+}
+
+/// List available metric names.
+pub async fn grafana_search(body: Option<Json<SearchRequest>>) -> impl IntoResponse {
+    let filter = body.map(|Json(b)| b.target).unwrap_or_default();
+    Json(grafana::search(&filter))
+}
+
+/// Return datapoints for the requested metrics.
+pub async fn grafana_query(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<QueryRequest>,
+) -> impl IntoResponse {
+    Json(grafana::query(&state, &request).await)
+}
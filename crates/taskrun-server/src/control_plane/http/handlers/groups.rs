@@ -0,0 +1,49 @@
+//! HTTP handler for group summaries.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+use serde::Deserialize;
+
+use crate::control_plane::groups;
+use crate::control_plane::http::responses::ErrorResponse;
+use crate::control_plane::rbac::{self, Caller};
+use crate::control_plane::state::AppState;
+
+/// Query parameters for `GET /v1/groups/:id/summary`.
+#[derive(Debug, Deserialize)]
+pub struct GroupSummaryParams {
+    pub namespace: Option<String>,
+}
+
+/// Aggregate progress of a workflow, batch or fan-out.
+///
+/// GET /v1/groups/:id/summary?namespace=
+///
+/// Callers bound to a namespace only see its tasks.
+pub async fn get_group_summary(
+    State(state): State<Arc<AppState>>,
+    caller: Option<Extension<Caller>>,
+    Path(group_id): Path<String>,
+    Query(params): Query<GroupSummaryParams>,
+) -> Response {
+    let namespace = match rbac::scope(caller.as_ref().map(|c| &c.0), params.namespace.as_deref()) {
+        Ok(namespace) => namespace,
+        Err(e) => return e.into_response(),
+    };
+    match groups::summarize(&state, &group_id, namespace.as_deref()).await {
+        Some(summary) => Json(summary).into_response(),
+        None => (
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: format!("Group not found: {}", group_id),
+            }),
+        )
+            .into_response(),
+    }
+}
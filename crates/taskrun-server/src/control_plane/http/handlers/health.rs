@@ -6,9 +6,14 @@ use axum::{extract::State, http::header, response::IntoResponse, Json};
 
 use crate::control_plane::state::AppState;
 
-/// Health check endpoint.
-pub async fn health_check() -> impl IntoResponse {
-    Json(serde_json::json!({ "status": "ok" }))
+/// Health check endpoint. Also reports whether this instance leads.
+pub async fn health_check(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    let leader = state.leadership.current();
+    Json(serde_json::json!({
+        "status": "ok",
+        "role": if leader.is_leader { "leader" } else { "follower" },
+        "leader": leader.leader_addr,
+    }))
 }
 
 /// Prometheus metrics endpoint.
@@ -1,13 +1,49 @@
 //! HTTP request handlers.
 
+mod api_keys;
+mod approvals;
+mod audit;
+mod chaos;
+mod debug_stats;
 mod enrollment;
 mod events;
+mod files;
+mod grafana;
+mod groups;
 mod health;
+mod models;
+mod queue;
+mod quotas;
+mod reload;
 mod responses_openai;
+mod runs;
+mod search;
+mod task_page;
+mod tasks;
+mod usage;
+mod watch;
 mod workers;
 
-pub use enrollment::enroll;
+pub use api_keys::{create_api_key, list_api_keys, revoke_api_key, AdminState};
+pub use approvals::{approve_approval, deny_approval, list_approvals};
+pub use audit::list_audit;
+pub use chaos::{get_chaos, update_chaos};
+pub use debug_stats::get_debug_stats;
+pub use enrollment::{enroll, get_ca_rotation, get_crl};
 pub use events::{get_task_events, get_task_output};
+pub use files::{delete_file, download_task_artifact, list_task_artifacts, upload_file};
+pub use grafana::{grafana_health, grafana_query, grafana_search};
+pub use groups::get_group_summary;
 pub use health::{health_check, metrics_handler};
-pub use responses_openai::create_response;
-pub use workers::{list_workers_html, list_workers_json};
+pub use models::list_models;
+pub use queue::list_queue;
+pub use quotas::list_quotas;
+pub use reload::reload_config;
+pub use responses_openai::{cancel_response, create_response};
+pub use runs::{get_run_manifest, reproduce_run};
+pub use search::search_tasks;
+pub use task_page::task_page_html;
+pub use tasks::{cancel_task, list_tasks, validate_task};
+pub use usage::get_usage;
+pub use watch::watch;
+pub use workers::{disconnect_worker, list_workers_html, list_workers_json};
@@ -0,0 +1,39 @@
+//! HTTP handler for the OpenAI-compatible model list.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Query, State},
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+use serde::Deserialize;
+
+use crate::control_plane::models;
+use crate::control_plane::rbac::{self, Caller};
+use crate::control_plane::state::AppState;
+
+/// Query parameters for `GET /v1/models`.
+#[derive(Debug, Deserialize)]
+pub struct ModelsParams {
+    pub namespace: Option<String>,
+}
+
+/// Agents advertised by connected workers, as OpenAI models.
+///
+/// GET /v1/models?namespace=
+///
+/// Lists the namespace `/v1/responses` would create the caller's tasks in:
+/// a bound caller's own, otherwise `namespace` or the default one.
+pub async fn list_models(
+    State(state): State<Arc<AppState>>,
+    caller: Option<Extension<Caller>>,
+    Query(params): Query<ModelsParams>,
+) -> Response {
+    let namespace =
+        match rbac::namespace_for_new(caller.as_ref().map(|c| &c.0), params.namespace.as_deref()) {
+            Ok(namespace) => namespace,
+            Err(e) => return e.into_response(),
+        };
+    Json(models::list(&state, &namespace).await).into_response()
+}
@@ -0,0 +1,76 @@
+//! Run queue handler.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Query, State},
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+use crate::control_plane::queue::{self, QueueEntry};
+use crate::control_plane::rbac::{self, Caller};
+use crate::control_plane::state::AppState;
+
+/// Query parameters for `GET /v1/queue`.
+#[derive(Debug, Deserialize)]
+pub struct QueueParams {
+    pub namespace: Option<String>,
+    pub agent: Option<String>,
+}
+
+/// A queued task with how long it has waited.
+#[derive(Serialize)]
+pub struct QueuedTaskResponse {
+    #[serde(flatten)]
+    pub entry: QueueEntry,
+    pub wait_secs: u64,
+}
+
+/// Queued tasks in dispatch order.
+#[derive(Serialize)]
+pub struct QueueResponse {
+    pub depth: usize,
+    /// Wait of the longest-waiting task (absent when the queue is empty).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub oldest_wait_secs: Option<u64>,
+    pub tasks: Vec<QueuedTaskResponse>,
+}
+
+/// List tasks waiting for a worker, with their position, wait, ETA and what
+/// blocks them.
+///
+/// GET /v1/queue?namespace=&agent=
+///
+/// Callers bound to a namespace only see its tasks.
+pub async fn list_queue(
+    State(state): State<Arc<AppState>>,
+    caller: Option<Extension<Caller>>,
+    Query(params): Query<QueueParams>,
+) -> Response {
+    let namespace = match rbac::scope(caller.as_ref().map(|c| &c.0), params.namespace.as_deref()) {
+        Ok(namespace) => namespace,
+        Err(e) => return e.into_response(),
+    };
+
+    let now = Utc::now();
+    let tasks: Vec<QueuedTaskResponse> = queue::entries(&state)
+        .await
+        .into_iter()
+        .filter(|e| namespace.as_deref().map_or(true, |ns| e.namespace == ns))
+        .filter(|e| params.agent.as_deref().map_or(true, |a| e.agent_name == a))
+        .map(|entry| QueuedTaskResponse {
+            wait_secs: (now - entry.queued_at).num_seconds().max(0) as u64,
+            entry,
+        })
+        .collect();
+
+    Json(QueueResponse {
+        depth: tasks.len(),
+        oldest_wait_secs: tasks.iter().map(|t| t.wait_secs).max(),
+        tasks,
+    })
+    .into_response()
+}
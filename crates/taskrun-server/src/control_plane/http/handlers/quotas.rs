@@ -0,0 +1,29 @@
+//! Quota usage handler.
+
+use axum::{extract::State, Extension, Json};
+
+use crate::control_plane::http::handlers::AdminState;
+use crate::control_plane::quotas::{QuotaScope, QuotaUsage};
+use crate::control_plane::rbac::Caller;
+
+/// Configured quotas with their current consumption.
+///
+/// GET /v1/admin/quotas
+///
+/// Admins bound to a namespace only see that namespace's quota.
+pub async fn list_quotas(
+    State(admin): State<AdminState>,
+    Extension(caller): Extension<Caller>,
+) -> Json<Vec<QuotaUsage>> {
+    let usage = {
+        let tasks = admin.app.tasks.read().await;
+        admin.app.quotas.usage(&tasks).await
+    };
+    Json(match caller.namespace {
+        Some(namespace) => usage
+            .into_iter()
+            .filter(|u| matches!(&u.scope, QuotaScope::Namespace(name) if *name == namespace))
+            .collect(),
+        None => usage,
+    })
+}
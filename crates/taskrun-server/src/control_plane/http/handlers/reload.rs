@@ -0,0 +1,46 @@
+//! Settings reload handler.
+
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+
+use crate::control_plane::http::client_ip::{client_ip_string, ClientIp};
+use crate::control_plane::http::handlers::AdminState;
+use crate::control_plane::http::responses::ErrorResponse;
+use crate::control_plane::rbac::Caller;
+
+/// Re-read the settings file and apply what changed.
+///
+/// POST /v1/admin/reload
+///
+/// Settings are server-wide, so admins bound to a namespace can't reload them.
+pub async fn reload_config(
+    State(admin): State<AdminState>,
+    Extension(caller): Extension<Caller>,
+    client_ip: Option<Extension<ClientIp>>,
+) -> Response {
+    let error =
+        |status: StatusCode, error: String| (status, Json(ErrorResponse { error })).into_response();
+    if caller.namespace.is_some() {
+        return error(
+            StatusCode::FORBIDDEN,
+            "Reloading settings needs an admin key not bound to a namespace".to_string(),
+        );
+    }
+    let Some(reloader) = &admin.reloader else {
+        return error(
+            StatusCode::NOT_FOUND,
+            "No settings file configured (start the server with --config)".to_string(),
+        );
+    };
+    match reloader
+        .reload(&caller.identity, client_ip_string(client_ip).as_deref())
+        .await
+    {
+        Ok(summary) => Json(summary).into_response(),
+        Err(e) => error(StatusCode::UNPROCESSABLE_ENTITY, e.to_string()),
+    }
+}
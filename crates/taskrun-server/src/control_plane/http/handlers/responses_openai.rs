@@ -7,16 +7,16 @@ use std::collections::HashMap;
 use std::convert::Infallible;
 use std::pin::Pin;
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use axum::{
-    extract::{rejection::JsonRejection, State},
-    http::StatusCode,
+    extract::{rejection::JsonRejection, Path, State},
+    http::{header, StatusCode},
     response::{
         sse::{Event, KeepAlive, Sse},
         IntoResponse, Response,
     },
-    Json,
+    Extension, Json,
 };
 use futures_util::stream::{self, Stream, StreamExt};
 use serde::{Deserialize, Serialize};
@@ -24,10 +24,24 @@ use serde_json::Value;
 use tokio::sync::broadcast;
 use tracing::{info, warn};
 
-use taskrun_core::{RunStatus, Task, TaskStatus};
-
-use crate::control_plane::scheduler::Scheduler;
-use crate::control_plane::state::{AppState, StreamEvent};
+use taskrun_core::{
+    ChatRole, RunStatus, Task, TaskId, TaskPriority, TaskStatus, DEFAULT_NAMESPACE,
+};
+use taskrun_proto::trace::TraceContext;
+
+use crate::control_plane::audit::AuditAction;
+use crate::control_plane::completions;
+use crate::control_plane::conversations::{self, ConversationError, FollowUp, ResponseId};
+use crate::control_plane::http::access_log::AccessTaskId;
+use crate::control_plane::http::client_ip::{client_ip_string, ClientIp};
+use crate::control_plane::http::ResponsesConfig;
+use crate::control_plane::queue::{self, QueuePosition};
+use crate::control_plane::quotas::{self, QuotaExceeded};
+use crate::control_plane::rbac::Caller;
+use crate::control_plane::scheduler::{trace_created, Scheduler, SchedulerError};
+use crate::control_plane::state::{AppState, StreamEvent, UiNotification};
+use crate::control_plane::toggles::MaintenanceMode;
+use crate::control_plane::usage::{self, RunUsage};
 
 // ============================================================================
 // Request Types
@@ -61,8 +75,24 @@ pub struct CreateResponseRequest {
     /// Arbitrary metadata.
     #[serde(default)]
     pub metadata: HashMap<String, String>,
+
+    /// Response to follow up on; the input continues its agent session
+    /// instead of starting a new task.
+    #[serde(default)]
+    pub previous_response_id: Option<String>,
+
+    /// How long a non-streaming request waits for the answer (default 300s,
+    /// at most 3600s). The task keeps running when the wait times out.
+    #[serde(default)]
+    pub timeout_seconds: Option<u64>,
 }
 
+/// Default wait of a non-streaming request.
+const DEFAULT_WAIT_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Longest wait a request may ask for.
+const MAX_WAIT_TIMEOUT_SECS: u64 = 3600;
+
 // ============================================================================
 // Response Types
 // ============================================================================
@@ -98,6 +128,10 @@ pub struct ResponseObject {
 
     /// Metadata including internal IDs.
     pub metadata: HashMap<String, String>,
+
+    /// Response this one follows up on.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub previous_response_id: Option<String>,
 }
 
 /// Output item in the response.
@@ -137,9 +171,19 @@ pub struct ContentBlock {
 /// Token usage statistics.
 #[derive(Debug, Serialize)]
 pub struct Usage {
-    pub input_tokens: u32,
-    pub output_tokens: u32,
-    pub total_tokens: u32,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub total_tokens: u64,
+}
+
+impl From<RunUsage> for Usage {
+    fn from(usage: RunUsage) -> Self {
+        Self {
+            input_tokens: usage.input_tokens,
+            output_tokens: usage.output_tokens,
+            total_tokens: usage.total_tokens(),
+        }
+    }
 }
 
 /// Error details for failed responses.
@@ -183,8 +227,18 @@ pub enum ApiError {
     },
     /// Model/agent not found.
     ModelNotFound { model: String },
+    /// No response with this ID.
+    ResponseNotFound { id: String },
+    /// The response cannot be cancelled, e.g. because it finished.
+    NotCancellable { message: String },
+    /// A namespace or API key quota would be exceeded.
+    QuotaExceeded(QuotaExceeded),
+    /// `previous_response_id` cannot be continued.
+    Conversation(ConversationError),
 
     // Server errors (5xx)
+    /// The control plane is in maintenance mode.
+    Maintenance(MaintenanceMode),
     /// No workers available for the requested agent.
     NoWorkersAvailable { agent: String },
     /// Worker disconnected during execution.
@@ -199,6 +253,10 @@ pub enum ApiError {
 
 impl IntoResponse for ApiError {
     fn into_response(self) -> axum::response::Response {
+        let retry_after = match &self {
+            ApiError::QuotaExceeded(e) => e.retry_after_secs,
+            _ => None,
+        };
         let (status, error_type, code, message, param) = match self {
             ApiError::InvalidJson { message } => (
                 StatusCode::BAD_REQUEST,
@@ -228,6 +286,57 @@ impl IntoResponse for ApiError {
                 format!("Model '{}' not found", model),
                 Some("model".to_string()),
             ),
+            ApiError::ResponseNotFound { id } => (
+                StatusCode::NOT_FOUND,
+                "invalid_request_error",
+                "response_not_found",
+                format!("Response '{}' not found", id),
+                None,
+            ),
+            ApiError::NotCancellable { message } => (
+                StatusCode::CONFLICT,
+                "invalid_request_error",
+                "response_not_cancellable",
+                message,
+                None,
+            ),
+            ApiError::QuotaExceeded(e) => (
+                StatusCode::TOO_MANY_REQUESTS,
+                "rate_limit_error",
+                "quota_exceeded",
+                e.to_string(),
+                None,
+            ),
+            ApiError::Conversation(e @ ConversationError::WorkerGone(_)) => (
+                StatusCode::SERVICE_UNAVAILABLE,
+                "server_error",
+                "worker_disconnected",
+                e.to_string(),
+                Some("previous_response_id".to_string()),
+            ),
+            ApiError::Conversation(e) => {
+                let (status, code) = match e {
+                    ConversationError::InvalidId(_) | ConversationError::NotFound(_) => {
+                        (StatusCode::NOT_FOUND, "response_not_found")
+                    }
+                    ConversationError::Busy(_) => (StatusCode::CONFLICT, "response_in_progress"),
+                    _ => (StatusCode::BAD_REQUEST, "invalid_previous_response"),
+                };
+                (
+                    status,
+                    "invalid_request_error",
+                    code,
+                    e.to_string(),
+                    Some("previous_response_id".to_string()),
+                )
+            }
+            ApiError::Maintenance(e) => (
+                StatusCode::SERVICE_UNAVAILABLE,
+                "server_error",
+                "maintenance",
+                e.to_string(),
+                None,
+            ),
             ApiError::NoWorkersAvailable { agent } => (
                 StatusCode::SERVICE_UNAVAILABLE,
                 "server_error",
@@ -274,7 +383,15 @@ impl IntoResponse for ApiError {
             },
         };
 
-        (status, Json(body)).into_response()
+        match retry_after {
+            Some(secs) => (
+                status,
+                [(header::RETRY_AFTER, secs.to_string())],
+                Json(body),
+            )
+                .into_response(),
+            None => (status, Json(body)).into_response(),
+        }
     }
 }
 
@@ -292,6 +409,8 @@ struct ResponseCreatedEvent {
     object: &'static str,
     model: String,
     created_at: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    previous_response_id: Option<String>,
 }
 
 /// SSE event: response.output_text.delta
@@ -302,6 +421,39 @@ struct OutputTextDeltaEvent {
     delta: DeltaContent,
 }
 
+/// SSE event: response.progress
+#[derive(Debug, Serialize)]
+struct ResponseProgressEvent {
+    response_id: String,
+    name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    percent: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    note: Option<String>,
+}
+
+/// SSE event: response.tool_call.started
+#[derive(Debug, Serialize)]
+struct ToolCallStartedEvent {
+    response_id: String,
+    call_id: String,
+    tool_name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    input_summary: Option<String>,
+}
+
+/// SSE event: response.tool_call.completed
+#[derive(Debug, Serialize)]
+struct ToolCallCompletedEvent {
+    response_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    call_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_name: Option<String>,
+    /// "completed", or "failed" when the tool reported an error.
+    status: &'static str,
+}
+
 /// Delta content for streaming.
 #[derive(Debug, Serialize)]
 struct DeltaContent {
@@ -326,6 +478,15 @@ struct ResponseFailedEvent {
     error: ErrorObject,
 }
 
+/// SSE event: response.cancelled
+#[derive(Debug, Serialize)]
+struct ResponseCancelledEvent {
+    id: String,
+    status: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reason: Option<String>,
+}
+
 // ============================================================================
 // Handler
 // ============================================================================
@@ -333,6 +494,10 @@ struct ResponseFailedEvent {
 /// POST /v1/responses - Create a response (OpenAI-compatible).
 pub async fn create_response(
     State(state): State<Arc<AppState>>,
+    Extension(config): Extension<ResponsesConfig>,
+    caller: Option<Extension<Caller>>,
+    client_ip: Option<Extension<ClientIp>>,
+    access_task: Option<Extension<AccessTaskId>>,
     json_result: Result<Json<CreateResponseRequest>, JsonRejection>,
 ) -> Response {
     // Handle JSON parsing errors
@@ -358,9 +523,16 @@ pub async fn create_response(
         return e.into_response();
     }
 
+    // Bound callers create tasks in their namespace
+    let caller = caller.map(|c| c.0);
+    let namespace = caller
+        .as_ref()
+        .and_then(|c| c.namespace.clone())
+        .unwrap_or_else(|| DEFAULT_NAMESPACE.to_string());
+
     // Validate model/agent exists
     let agent_name = resolve_agent_name(&req.model);
-    if !state.has_agent(&agent_name).await {
+    if !state.has_agent(&agent_name, &namespace).await {
         warn!(model = %req.model, agent = %agent_name, "Model not found");
         return ApiError::ModelNotFound {
             model: req.model.clone(),
@@ -368,10 +540,48 @@ pub async fn create_response(
         .into_response();
     }
 
+    if let Err(e) = state.toggles.check_accepting().await {
+        warn!(error = %e, "Task refused");
+        return ApiError::Maintenance(e).into_response();
+    }
+    let access_task = access_task.map(|a| a.0);
+    if let Some(previous) = req.previous_response_id.clone() {
+        // Unbound callers may continue any conversation
+        let namespace = caller.as_ref().and_then(|c| c.namespace.as_deref());
+        return create_follow_up_response(state, req, previous, namespace, access_task).await;
+    }
+
+    let created_by = caller.map_or_else(|| "http-api".to_string(), |c| c.identity);
+    if let Err(e) = quotas::check_create(&state, &namespace, &created_by).await {
+        warn!(error = %e, "Quota exceeded");
+        return ApiError::QuotaExceeded(e).into_response();
+    }
+    if let Err(e) = state
+        .files
+        .check_inputs(&input_file_ids(&req.input), &namespace)
+        .await
+    {
+        return ApiError::InvalidField {
+            field: "input",
+            message: e.to_string(),
+        }
+        .into_response();
+    }
+    let client_ip = client_ip_string(client_ip);
     if req.stream {
-        create_streaming_response(state, req).await.into_response()
+        create_streaming_response(
+            state,
+            req,
+            created_by,
+            client_ip,
+            namespace,
+            config.cancel_on_disconnect,
+            access_task,
+        )
+        .await
+        .into_response()
     } else {
-        create_non_streaming_response(state, req)
+        create_non_streaming_response(state, req, created_by, client_ip, namespace, access_task)
             .await
             .into_response()
     }
@@ -399,6 +609,16 @@ fn validate_request(req: &CreateResponseRequest) -> Result<(), ApiError> {
         }
     }
 
+    // timeout_seconds must be 1-3600 if present
+    if let Some(secs) = req.timeout_seconds {
+        if !(1..=MAX_WAIT_TIMEOUT_SECS).contains(&secs) {
+            return Err(ApiError::InvalidField {
+                field: "timeout_seconds",
+                message: format!("must be between 1 and {}", MAX_WAIT_TIMEOUT_SECS),
+            });
+        }
+    }
+
     // temperature must be 0.0-2.0 if present
     if let Some(temp) = req.temperature {
         if !(0.0..=2.0).contains(&temp) {
@@ -416,13 +636,22 @@ fn validate_request(req: &CreateResponseRequest) -> Result<(), ApiError> {
 async fn create_streaming_response(
     state: Arc<AppState>,
     req: CreateResponseRequest,
+    created_by: String,
+    client_ip: Option<String>,
+    namespace: String,
+    cancel_on_disconnect: bool,
+    access_task: Option<AccessTaskId>,
 ) -> Sse<SseEventStream> {
     // Map model to agent_name
     let agent_name = resolve_agent_name(&req.model);
     let input_json = build_input_json(&req);
 
-    // Create task
-    let mut task = Task::new(&agent_name, &input_json, "http-api");
+    // Create task; a client is watching the stream, so it runs in the interactive lane
+    let mut task = Task::new(&agent_name, &input_json, created_by)
+        .with_namespace(namespace)
+        .with_priority(TaskPriority::Interactive)
+        .with_client_ip(client_ip)
+        .with_input_files(input_file_ids(&req.input));
     for (k, v) in &req.metadata {
         task.labels.insert(k.clone(), v.clone());
     }
@@ -431,8 +660,20 @@ async fn create_streaming_response(
     task.labels
         .insert("streaming".to_string(), "true".to_string());
 
+    let task = trace_created(task, &TraceContext::new());
     let task_id = task.id.clone();
+    if let Some(access_task) = &access_task {
+        access_task.set(&task_id);
+    }
     let created_at = task.created_at.timestamp();
+    let guard = cancel_on_disconnect.then(|| {
+        DisconnectGuard::new(
+            state.clone(),
+            task_id.clone(),
+            task.created_by.clone(),
+            task.client_ip.clone(),
+        )
+    });
 
     info!(
         task_id = %task_id,
@@ -441,7 +682,18 @@ async fn create_streaming_response(
     );
 
     // Store task
+    state
+        .audit_from(
+            &task.created_by,
+            task.client_ip.as_deref(),
+            AuditAction::TaskCreated,
+            task_id.as_str(),
+            &task,
+        )
+        .await;
+    let created = UiNotification::task_created(&task);
     state.tasks.write().await.insert(task_id.clone(), task);
+    state.notify_ui(created);
 
     // Schedule task
     let scheduler = Scheduler::new(state.clone());
@@ -450,6 +702,17 @@ async fn create_streaming_response(
             info!(task_id = %task_id, run_id = %run_id, "Task assigned to worker (streaming)");
             run_id
         }
+        Err(e) if is_queueable(&e) => {
+            info!(task_id = %task_id, reason = %e, "Task queued (streaming)");
+            let queued_stream: SseEventStream = Box::pin(create_queued_stream(
+                state,
+                task_id,
+                req.model.clone(),
+                created_at,
+                guard,
+            ));
+            return Sse::new(queued_stream).keep_alive(KeepAlive::default());
+        }
         Err(e) => {
             warn!(task_id = %task_id, error = %e, "Failed to assign task");
             // Return error as SSE stream with single error event
@@ -475,36 +738,252 @@ async fn create_streaming_response(
         }
     };
 
-    let response_id = format!("resp_{}", run_id.as_str());
-
     // Subscribe to stream channel BEFORE any events might be published
     let sender = state.get_or_create_stream_channel(&run_id).await;
     let receiver = sender.subscribe();
 
     // Create the SSE stream
     let sse_stream: SseEventStream = Box::pin(create_sse_stream(
+        state,
+        ResponseId::new(run_id, 0),
+        None,
         receiver,
-        response_id,
         req.model.clone(),
         created_at,
+        guard,
     ));
 
     Sse::new(sse_stream).keep_alive(KeepAlive::default())
 }
 
+/// How often a queued streaming client is told its position.
+const QUEUE_REPORT_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How often a queued task is checked for assignment.
+const QUEUE_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Whether a scheduling failure only means the task has to wait its turn.
+fn is_queueable(err: &SchedulerError) -> bool {
+    match err {
+        SchedulerError::NoWorkersAvailable(_)
+        | SchedulerError::SchedulingFrozen(_)
+        | SchedulerError::ChaosInjected(_) => true,
+        // Concurrency quotas free up as runs finish; daily quotas do not
+        SchedulerError::QuotaExceeded(e) => e.retry_after_secs.is_none(),
+        _ => false,
+    }
+}
+
+/// Where a queued streaming response is.
+enum QueuedStream {
+    /// Waiting for the dispatcher; `reported` is when position was last sent.
+    Waiting {
+        guard: Option<DisconnectGuard>,
+        reported: Option<Instant>,
+    },
+    /// The task got a run and its events are streamed.
+    Streaming(SseEventStream),
+    Done,
+}
+
+/// Stream for a task that could not be assigned right away.
+///
+/// Until the dispatcher assigns the task, emits `queued position=N
+/// eta_seconds=M` comments so clients can decide whether to keep waiting;
+/// then continues like `create_sse_stream`.
+fn create_queued_stream(
+    state: Arc<AppState>,
+    task_id: TaskId,
+    model: String,
+    created_at: i64,
+    guard: Option<DisconnectGuard>,
+) -> impl Stream<Item = Result<Event, Infallible>> + Send {
+    let initial = QueuedStream::Waiting {
+        guard,
+        reported: None,
+    };
+    stream::unfold(initial, move |mut current| {
+        let state = state.clone();
+        let task_id = task_id.clone();
+        let model = model.clone();
+        async move {
+            loop {
+                match current {
+                    QueuedStream::Waiting {
+                        mut guard,
+                        reported,
+                    } => {
+                        let task = state.tasks.read().await.get(&task_id).cloned()?;
+                        if task.is_terminal() {
+                            // Expired or cancelled while queued, or finished
+                            // between two polls
+                            if let Some(guard) = guard.take() {
+                                guard.disarm();
+                            }
+                            let run = task.latest_run();
+                            let event = StreamEvent::StatusUpdate {
+                                status: run.map_or(RunStatus::Cancelled, |r| r.status),
+                                error_message: run
+                                    .and_then(|r| r.error_message.clone())
+                                    .or_else(|| task.failure_message.clone()),
+                                timestamp_ms: 0,
+                            };
+                            let id = run.map_or_else(
+                                || format!("resp_{}", task_id.as_str()),
+                                |r| format!("resp_{}", r.run_id.as_str()),
+                            );
+                            let usage = match run {
+                                Some(run) => usage::run_usage(&state, &run.run_id).await,
+                                None => None,
+                            };
+                            return Some((
+                                stream_event_to_sse(event, &id, usage.map(Usage::from)),
+                                QueuedStream::Done,
+                            ));
+                        }
+                        let assigned = task
+                            .latest_run()
+                            .filter(|_| task.status == TaskStatus::Running);
+                        if let Some(run) = assigned {
+                            let receiver = state
+                                .get_or_create_stream_channel(&run.run_id)
+                                .await
+                                .subscribe();
+                            current = QueuedStream::Streaming(Box::pin(create_sse_stream(
+                                state.clone(),
+                                ResponseId::new(run.run_id.clone(), 0),
+                                None,
+                                receiver,
+                                model.clone(),
+                                created_at,
+                                guard,
+                            )));
+                            continue;
+                        }
+                        if reported.map_or(true, |at| at.elapsed() >= QUEUE_REPORT_INTERVAL) {
+                            let comment = match queue::position(&state, &task_id).await {
+                                Some(QueuePosition {
+                                    position,
+                                    eta_secs: Some(eta),
+                                }) => format!("queued position={} eta_seconds={}", position, eta),
+                                Some(queue) => format!("queued position={}", queue.position),
+                                // Waiting on dependencies or just assigned
+                                None => "queued".to_string(),
+                            };
+                            return Some((
+                                Ok(Event::default().comment(comment)),
+                                QueuedStream::Waiting {
+                                    guard,
+                                    reported: Some(Instant::now()),
+                                },
+                            ));
+                        }
+                        tokio::time::sleep(QUEUE_POLL_INTERVAL).await;
+                        current = QueuedStream::Waiting { guard, reported };
+                    }
+                    QueuedStream::Streaming(mut inner) => {
+                        let event = inner.next().await?;
+                        return Some((event, QueuedStream::Streaming(inner)));
+                    }
+                    QueuedStream::Done => return None,
+                }
+            }
+        }
+    })
+}
+
+/// Run cancellation reason recorded when a streaming client goes away.
+const CLIENT_DISCONNECTED: &str = "client_disconnected";
+
+/// Cancels a streamed task if dropped before the run finished, i.e. when the
+/// SSE client disconnected mid-stream and axum dropped the response stream.
+struct DisconnectGuard {
+    state: Arc<AppState>,
+    task_id: TaskId,
+    /// Who created the task; recorded as the actor of the cancellation.
+    actor: String,
+    client_ip: Option<String>,
+    armed: bool,
+}
+
+impl DisconnectGuard {
+    fn new(
+        state: Arc<AppState>,
+        task_id: TaskId,
+        actor: String,
+        client_ip: Option<String>,
+    ) -> Self {
+        Self {
+            state,
+            task_id,
+            actor,
+            client_ip,
+            armed: true,
+        }
+    }
+
+    /// The stream ended normally: nothing to cancel.
+    fn disarm(mut self) {
+        self.armed = false;
+    }
+}
+
+impl Drop for DisconnectGuard {
+    fn drop(&mut self) {
+        if !self.armed {
+            return;
+        }
+        let state = self.state.clone();
+        let task_id = self.task_id.clone();
+        let actor = std::mem::take(&mut self.actor);
+        let client_ip = self.client_ip.take();
+        tokio::spawn(async move {
+            info!(task_id = %task_id, "Streaming client disconnected, cancelling task");
+            match Scheduler::new(state.clone())
+                .cancel_task(&task_id, CLIENT_DISCONNECTED)
+                .await
+            {
+                Ok(task) => {
+                    state
+                        .audit_from(
+                            &actor,
+                            client_ip.as_deref(),
+                            AuditAction::TaskCancelled,
+                            task_id.as_str(),
+                            &task,
+                        )
+                        .await;
+                }
+                // Finished between the last event and the disconnect
+                Err(SchedulerError::TaskAlreadyTerminal(..)) => {}
+                Err(e) => warn!(task_id = %task_id, error = %e, "Failed to cancel task"),
+            }
+        });
+    }
+}
+
 /// Create the SSE stream from broadcast receiver.
+///
+/// With a `guard`, dropping the stream before the run reaches a terminal
+/// status cancels the task.
 fn create_sse_stream(
+    state: Arc<AppState>,
+    response_id: ResponseId,
+    previous_response_id: Option<String>,
     receiver: broadcast::Receiver<StreamEvent>,
-    response_id: String,
     model: String,
     created_at: i64,
+    guard: Option<DisconnectGuard>,
 ) -> impl Stream<Item = Result<Event, Infallible>> + Send {
+    let (run_id, response_id) = (response_id.run_id.clone(), response_id.to_string());
+
     // First, emit the response.created event
     let created_event = ResponseCreatedEvent {
         id: response_id.clone(),
         object: "response",
         model: model.clone(),
         created_at,
+        previous_response_id,
     };
 
     let initial = stream::once(async move {
@@ -516,39 +995,58 @@ fn create_sse_stream(
         )
     });
 
-    // State for unfold: (receiver, response_id, terminated)
-    let state = (receiver, response_id, false);
+    // State for unfold: (receiver, response_id, guard, terminated)
+    let initial_state = (receiver, response_id, guard, false);
 
     // Use unfold to properly manage async state with termination
     let event_stream = stream::unfold(
-        state,
-        |(mut receiver, response_id, terminated)| async move {
-            if terminated {
-                return None;
-            }
-
-            // Use the receiver directly instead of BroadcastStream
-            match receiver.recv().await {
-                Ok(event) => {
-                    let is_terminal = matches!(
-                        &event,
-                        StreamEvent::StatusUpdate { status, .. }
-                            if status.is_terminal()
-                    );
-                    let sse_event = stream_event_to_sse(event, &response_id);
-                    Some((sse_event, (receiver, response_id, is_terminal)))
-                }
-                Err(broadcast::error::RecvError::Lagged(n)) => {
-                    warn!(skipped = n, "Broadcast stream lagged, skipping events");
-                    // Continue receiving after lag
-                    Some((
-                        Ok(Event::default().comment(format!("skipped {} events", n))),
-                        (receiver, response_id, false),
-                    ))
+        initial_state,
+        move |(mut receiver, response_id, mut guard, terminated)| {
+            let state = state.clone();
+            let run_id = run_id.clone();
+            async move {
+                if terminated {
+                    return None;
                 }
-                Err(broadcast::error::RecvError::Closed) => {
-                    // Channel closed, stream ends
-                    None
+
+                // Use the receiver directly instead of BroadcastStream
+                match receiver.recv().await {
+                    Ok(event) => {
+                        let is_terminal = matches!(
+                            &event,
+                            StreamEvent::StatusUpdate { status, .. }
+                                if status.is_terminal()
+                        );
+                        if is_terminal {
+                            if let Some(guard) = guard.take() {
+                                guard.disarm();
+                            }
+                        }
+                        let usage = match &event {
+                            StreamEvent::StatusUpdate {
+                                status: RunStatus::Completed,
+                                ..
+                            } => usage::run_usage(&state, &run_id).await.map(Usage::from),
+                            _ => None,
+                        };
+                        let sse_event = stream_event_to_sse(event, &response_id, usage);
+                        Some((sse_event, (receiver, response_id, guard, is_terminal)))
+                    }
+                    Err(broadcast::error::RecvError::Lagged(n)) => {
+                        warn!(skipped = n, "Broadcast stream lagged, skipping events");
+                        // Continue receiving after lag
+                        Some((
+                            Ok(Event::default().comment(format!("skipped {} events", n))),
+                            (receiver, response_id, guard, false),
+                        ))
+                    }
+                    Err(broadcast::error::RecvError::Closed) => {
+                        // Channel closed, stream ends
+                        if let Some(guard) = guard.take() {
+                            guard.disarm();
+                        }
+                        None
+                    }
                 }
             }
         },
@@ -559,7 +1057,13 @@ fn create_sse_stream(
 }
 
 /// Convert a StreamEvent to an SSE Event.
-fn stream_event_to_sse(event: StreamEvent, response_id: &str) -> Result<Event, Infallible> {
+///
+/// `usage` is reported on `response.completed`.
+fn stream_event_to_sse(
+    event: StreamEvent,
+    response_id: &str,
+    usage: Option<Usage>,
+) -> Result<Event, Infallible> {
     match event {
         StreamEvent::OutputChunk {
             seq: _,
@@ -580,6 +1084,52 @@ fn stream_event_to_sse(event: StreamEvent, response_id: &str) -> Result<Event, I
                 .json_data(delta_event)
                 .unwrap())
         }
+        StreamEvent::Milestone { milestone } => {
+            let progress_event = ResponseProgressEvent {
+                response_id: response_id.to_string(),
+                name: milestone.name,
+                percent: milestone.percent,
+                note: milestone.note,
+            };
+            Ok(Event::default()
+                .event("response.progress")
+                .json_data(progress_event)
+                .unwrap())
+        }
+        StreamEvent::ToolCall {
+            call_id,
+            tool_name,
+            input_summary,
+            timestamp_ms: _,
+        } => {
+            let started_event = ToolCallStartedEvent {
+                response_id: response_id.to_string(),
+                call_id,
+                tool_name,
+                input_summary,
+            };
+            Ok(Event::default()
+                .event("response.tool_call.started")
+                .json_data(started_event)
+                .unwrap())
+        }
+        StreamEvent::ToolResult {
+            call_id,
+            tool_name,
+            is_error,
+            timestamp_ms: _,
+        } => {
+            let completed_event = ToolCallCompletedEvent {
+                response_id: response_id.to_string(),
+                call_id,
+                tool_name,
+                status: if is_error { "failed" } else { "completed" },
+            };
+            Ok(Event::default()
+                .event("response.tool_call.completed")
+                .json_data(completed_event)
+                .unwrap())
+        }
         StreamEvent::StatusUpdate {
             status,
             error_message,
@@ -590,21 +1140,26 @@ fn stream_event_to_sse(event: StreamEvent, response_id: &str) -> Result<Event, I
                     id: response_id.to_string(),
                     status: "completed".to_string(),
                     output: vec![], // Output already streamed via deltas
-                    usage: None,    // TODO: Track token usage
+                    usage,
                 };
                 Ok(Event::default()
                     .event("response.completed")
                     .json_data(completed_event)
                     .unwrap())
-            } else if status == RunStatus::Failed || status == RunStatus::Cancelled {
-                let status_str = if status == RunStatus::Failed {
-                    "failed"
-                } else {
-                    "cancelled"
+            } else if status == RunStatus::Cancelled {
+                let cancelled_event = ResponseCancelledEvent {
+                    id: response_id.to_string(),
+                    status: "cancelled",
+                    reason: error_message,
                 };
+                Ok(Event::default()
+                    .event("response.cancelled")
+                    .json_data(cancelled_event)
+                    .unwrap())
+            } else if status == RunStatus::Failed {
                 let failed_event = ResponseFailedEvent {
                     id: response_id.to_string(),
-                    status: status_str.to_string(),
+                    status: "failed".to_string(),
                     error: ErrorObject {
                         message: error_message.unwrap_or_else(|| "Unknown error".to_string()),
                         error_type: "agent_error".to_string(),
@@ -628,6 +1183,10 @@ fn stream_event_to_sse(event: StreamEvent, response_id: &str) -> Result<Event, I
 async fn create_non_streaming_response(
     state: Arc<AppState>,
     req: CreateResponseRequest,
+    created_by: String,
+    client_ip: Option<String>,
+    namespace: String,
+    access_task: Option<AccessTaskId>,
 ) -> impl IntoResponse {
     // Map model to agent_name (direct mapping for MVP)
     let agent_name = resolve_agent_name(&req.model);
@@ -636,14 +1195,22 @@ async fn create_non_streaming_response(
     let input_json = build_input_json(&req);
 
     // Create task
-    let mut task = Task::new(&agent_name, &input_json, "http-api");
+    let mut task = Task::new(&agent_name, &input_json, created_by)
+        .with_namespace(namespace)
+        .with_priority(state.toggles.default_priority().await)
+        .with_client_ip(client_ip)
+        .with_input_files(input_file_ids(&req.input));
     for (k, v) in &req.metadata {
         task.labels.insert(k.clone(), v.clone());
     }
     task.labels
         .insert("source".to_string(), "openai_api".to_string());
 
+    let task = trace_created(task, &TraceContext::new());
     let task_id = task.id.clone();
+    if let Some(access_task) = &access_task {
+        access_task.set(&task_id);
+    }
 
     info!(
         task_id = %task_id,
@@ -652,59 +1219,300 @@ async fn create_non_streaming_response(
     );
 
     // Store task
+    state
+        .audit_from(
+            &task.created_by,
+            task.client_ip.as_deref(),
+            AuditAction::TaskCreated,
+            task_id.as_str(),
+            &task,
+        )
+        .await;
+    let created = UiNotification::task_created(&task);
     state.tasks.write().await.insert(task_id.clone(), task);
+    state.notify_ui(created);
 
     // Schedule task
     let scheduler = Scheduler::new(state.clone());
-    let run_id = match scheduler.assign_task(&task_id).await {
+    match scheduler.assign_task(&task_id).await {
         Ok(run_id) => {
             info!(task_id = %task_id, run_id = %run_id, "Task assigned to worker");
-            run_id
+        }
+        Err(e) if is_queueable(&e) => {
+            // The dispatcher assigns it once a worker frees up
+            info!(task_id = %task_id, reason = %e, "Task queued");
         }
         Err(e) => {
             warn!(task_id = %task_id, error = %e, "Failed to assign task");
+            if let SchedulerError::QuotaExceeded(e) = e {
+                return ApiError::QuotaExceeded(e).into_response();
+            }
             return ApiError::NoWorkersAvailable { agent: agent_name }.into_response();
         }
+    }
+
+    // Woken by the task's status changes
+    let task = match completions::wait_for_task(&state, &task_id, wait_timeout(&req)).await {
+        Some(task) => task,
+        None => {
+            return ApiError::Internal {
+                message: "Task disappeared".to_string(),
+            }
+            .into_response();
+        }
     };
+    if !task.is_terminal() {
+        warn!(task_id = %task_id, "Task timed out");
+        return ApiError::TaskTimeout.into_response();
+    }
 
-    // Wait for task completion (poll with timeout)
-    let timeout = Duration::from_secs(300); // 5 minute timeout
-    let poll_interval = Duration::from_millis(100);
-    let start = std::time::Instant::now();
+    // Latest run: the task may have been queued before it got one
+    let response = build_response(&state, &task, &req.model, None).await;
+    (StatusCode::OK, Json(response)).into_response()
+}
 
-    loop {
-        if start.elapsed() > timeout {
-            warn!(task_id = %task_id, "Task timed out");
-            return ApiError::TaskTimeout.into_response();
+/// Continue the conversation of `previous` with the request input.
+///
+/// The input goes to the worker that produced `previous` and resumes its
+/// agent session; no task is created. Instructions and sampling options of
+/// the request are not applied to a session that is already running.
+async fn create_follow_up_response(
+    state: Arc<AppState>,
+    req: CreateResponseRequest,
+    previous: String,
+    namespace: Option<&str>,
+    access_task: Option<AccessTaskId>,
+) -> Response {
+    let agent_name = resolve_agent_name(&req.model);
+    let follow_up = match conversations::continue_response(
+        &state,
+        &previous,
+        namespace,
+        &agent_name,
+        input_text(&req.input),
+    )
+    .await
+    {
+        Ok(follow_up) => follow_up,
+        Err(e) => {
+            warn!(previous_response_id = %previous, error = %e, "Cannot continue response");
+            return ApiError::Conversation(e).into_response();
         }
+    };
+    if let Some(access_task) = &access_task {
+        access_task.set(&follow_up.task_id);
+    }
+    let created_at = chrono::Utc::now().timestamp();
 
-        // Check task status
-        let task_opt = state.tasks.read().await.get(&task_id).cloned();
-        let task = match task_opt {
-            Some(t) => t,
-            None => {
-                return ApiError::Internal {
-                    message: "Task disappeared".to_string(),
-                }
-                .into_response();
+    if req.stream {
+        let sse_stream: SseEventStream = Box::pin(create_sse_stream(
+            state,
+            follow_up.response_id,
+            Some(previous),
+            follow_up.receiver,
+            req.model,
+            created_at,
+            None,
+        ));
+        return Sse::new(sse_stream)
+            .keep_alive(KeepAlive::default())
+            .into_response();
+    }
+
+    let FollowUp {
+        task_id,
+        worker_id,
+        response_id,
+        mut receiver,
+    } = follow_up;
+    let finished = tokio::time::timeout(wait_timeout(&req), async {
+        loop {
+            match receiver.recv().await {
+                Ok(StreamEvent::StatusUpdate {
+                    status,
+                    error_message,
+                    ..
+                }) if status.is_terminal() => return Some((status, error_message)),
+                Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => {}
+                Err(broadcast::error::RecvError::Closed) => return None,
             }
-        };
+        }
+    })
+    .await;
+    let (status, error_message) = match finished {
+        Ok(Some(finished)) => finished,
+        Ok(None) => return ApiError::WorkerDisconnected.into_response(),
+        Err(_) => {
+            warn!(response_id = %response_id, "Follow-up timed out");
+            return ApiError::TaskTimeout.into_response();
+        }
+    };
 
-        if task.is_terminal() {
-            // Task completed, build response
-            let response = build_response(&state, &task, &req.model, Some(&run_id)).await;
-            return (StatusCode::OK, Json(response)).into_response();
+    // The worker sends the whole answer as a chat message before it reports
+    // the follow-up completed
+    let run_id = &response_id.run_id;
+    let (output, usage) = if status == RunStatus::Completed {
+        let answer = state
+            .get_chat_messages(run_id)
+            .await
+            .into_iter()
+            .rev()
+            .find(|m| m.role == ChatRole::Assistant)
+            .map(|m| m.content)
+            .unwrap_or_default();
+        let usage = usage::run_usage(&state, run_id).await.map(Usage::from);
+        (message_output(&response_id.to_string(), answer), usage)
+    } else {
+        (Vec::new(), None)
+    };
+    let error = (status != RunStatus::Completed).then(|| ErrorObject {
+        message: error_message.unwrap_or_else(|| "Unknown error".to_string()),
+        error_type: "agent_error".to_string(),
+        code: "execution_failed".to_string(),
+        param: None,
+    });
+
+    let mut metadata = HashMap::new();
+    metadata.insert("task_id".to_string(), task_id.as_str().to_string());
+    metadata.insert("run_id".to_string(), run_id.as_str().to_string());
+    metadata.insert("worker_id".to_string(), worker_id.as_str().to_string());
+
+    let response = ResponseObject {
+        id: response_id.to_string(),
+        object: "response",
+        created_at,
+        status: response_status(status).to_string(),
+        model: req.model,
+        output,
+        usage,
+        error,
+        metadata,
+        previous_response_id: Some(previous),
+    };
+    (StatusCode::OK, Json(response)).into_response()
+}
+
+/// POST /v1/responses/:response_id/cancel - Cancel a response.
+///
+/// Cancels the task behind the response: its run is stopped on the worker,
+/// which frees the slot, and a client streaming it gets `response.cancelled`.
+/// Follow-ups of a conversation run in a finished task and cannot be
+/// cancelled.
+pub async fn cancel_response(
+    State(state): State<Arc<AppState>>,
+    caller: Option<Extension<Caller>>,
+    client_ip: Option<Extension<ClientIp>>,
+    access_task: Option<Extension<AccessTaskId>>,
+    Path(response_id): Path<String>,
+) -> Response {
+    let (actor, namespace) = caller.map_or_else(
+        || ("http-api".to_string(), None),
+        |c| (c.0.identity, c.0.namespace),
+    );
+    let not_found = || ApiError::ResponseNotFound {
+        id: response_id.clone(),
+    };
+    let Ok(id) = response_id.parse::<ResponseId>() else {
+        return not_found().into_response();
+    };
+    if id.turn > 0 {
+        return ApiError::NotCancellable {
+            message: format!("Response '{}' is a follow-up and cannot be cancelled", id),
         }
+        .into_response();
+    }
 
-        // Still running, wait and poll again
-        tokio::time::sleep(poll_interval).await;
+    // Queued responses are named after their task until they get a run
+    let task_id = {
+        let tasks = state.tasks.read().await;
+        tasks
+            .values()
+            .filter(|t| namespace.as_deref().map_or(true, |ns| t.namespace == ns))
+            .find(|t| {
+                t.id.as_str() == id.run_id.as_str() || t.runs.iter().any(|r| r.run_id == id.run_id)
+            })
+            .map(|t| t.id.clone())
+    };
+    let Some(task_id) = task_id else {
+        return not_found().into_response();
+    };
+    if let Some(Extension(access_task)) = &access_task {
+        access_task.set(&task_id);
     }
+
+    let reason = format!("Response cancelled by {}", actor);
+    let task = match Scheduler::new(state.clone())
+        .cancel_task(&task_id, &reason)
+        .await
+    {
+        Ok(task) => task,
+        Err(SchedulerError::TaskAlreadyTerminal(_, status)) => {
+            return ApiError::NotCancellable {
+                message: format!("Response '{}' already finished ({:?})", response_id, status),
+            }
+            .into_response();
+        }
+        Err(e) => {
+            return ApiError::Internal {
+                message: e.to_string(),
+            }
+            .into_response();
+        }
+    };
+    info!(task_id = %task_id, response_id = %response_id, cancelled_by = %actor, "Response cancelled");
+    state
+        .audit_from(
+            &actor,
+            client_ip_string(client_ip).as_deref(),
+            AuditAction::TaskCancelled,
+            task_id.as_str(),
+            &task,
+        )
+        .await;
+
+    let response = build_response(&state, &task, &task.agent_name, None).await;
+    (StatusCode::OK, Json(response)).into_response()
 }
 
 // ============================================================================
 // Helper Functions
 // ============================================================================
 
+/// Response status for a finished run.
+fn response_status(status: RunStatus) -> &'static str {
+    match status {
+        RunStatus::Completed => "completed",
+        RunStatus::Failed => "failed",
+        RunStatus::Cancelled => "cancelled",
+        _ => "in_progress",
+    }
+}
+
+/// The assistant message of a response, or nothing if `text` is empty.
+fn message_output(response_id: &str, text: String) -> Vec<OutputItem> {
+    if text.is_empty() {
+        return Vec::new();
+    }
+    let id = response_id.strip_prefix("resp_").unwrap_or(response_id);
+    vec![OutputItem {
+        item_type: "message".to_string(),
+        id: format!("msg_{}", id),
+        role: "assistant".to_string(),
+        status: "completed".to_string(),
+        content: vec![ContentBlock {
+            block_type: "output_text".to_string(),
+            content_type: "text/plain".to_string(),
+            text,
+        }],
+    }]
+}
+
+/// How long a non-streaming request waits for its answer.
+fn wait_timeout(req: &CreateResponseRequest) -> Duration {
+    req.timeout_seconds
+        .map_or(DEFAULT_WAIT_TIMEOUT, Duration::from_secs)
+}
+
 /// Resolve model name to agent name.
 /// For MVP: direct mapping (strip "taskrun:" prefix if present).
 fn resolve_agent_name(model: &str) -> String {
@@ -715,29 +1523,54 @@ fn resolve_agent_name(model: &str) -> String {
     }
 }
 
-/// Build input_json from the OpenAI request.
-fn build_input_json(req: &CreateResponseRequest) -> String {
-    // Extract the actual input text
-    let input_text = match &req.input {
+/// Extract the text of the request input.
+fn input_text(input: &Value) -> String {
+    match input {
         Value::String(s) => s.clone(),
         Value::Array(arr) => {
             // Handle structured input (messages array)
             // For MVP, just extract text from the last user message
             arr.iter()
                 .rev()
-                .find_map(|item| {
-                    item.get("content")
-                        .and_then(|c| c.as_str())
-                        .map(String::from)
+                .find_map(|item| match item.get("content")? {
+                    Value::String(text) => Some(text.clone()),
+                    // Content parts: the text parts, without the files
+                    Value::Array(parts) => {
+                        let texts: Vec<&str> = parts
+                            .iter()
+                            .filter(|p| p.get("type").and_then(Value::as_str) != Some("input_file"))
+                            .filter_map(|p| p.get("text").and_then(Value::as_str))
+                            .collect();
+                        (!texts.is_empty()).then(|| texts.join("\n"))
+                    }
+                    _ => None,
                 })
                 .unwrap_or_default()
         }
-        _ => serde_json::to_string(&req.input).unwrap_or_default(),
+        _ => serde_json::to_string(input).unwrap_or_default(),
+    }
+}
+
+/// IDs of uploaded files given as `input_file` content parts.
+fn input_file_ids(input: &Value) -> Vec<String> {
+    let Value::Array(messages) = input else {
+        return Vec::new();
     };
+    messages
+        .iter()
+        .filter_map(|m| m.get("content").and_then(Value::as_array))
+        .flatten()
+        .filter(|p| p.get("type").and_then(Value::as_str) == Some("input_file"))
+        .filter_map(|p| p.get("file_id").and_then(Value::as_str))
+        .map(str::to_string)
+        .collect()
+}
 
+/// Build input_json from the OpenAI request.
+fn build_input_json(req: &CreateResponseRequest) -> String {
     // Build the input object for the agent
     let mut input = serde_json::json!({
-        "task": input_text,
+        "task": input_text(&req.input),
     });
 
     // Add optional fields
@@ -764,13 +1597,14 @@ async fn build_response(
     model: &str,
     run_id: Option<&taskrun_core::RunId>,
 ) -> ResponseObject {
-    // Get output from the run
-    let output_text = if let Some(rid) = run_id {
-        state.get_output(rid).await.unwrap_or_default()
-    } else if let Some(run) = task.latest_run() {
-        state.get_output(&run.run_id).await.unwrap_or_default()
-    } else {
-        String::new()
+    // Get output and token usage from the run
+    let run_id = run_id.or_else(|| task.latest_run().map(|r| &r.run_id));
+    let (output_text, usage) = match run_id {
+        Some(rid) => (
+            state.get_output(rid).await.unwrap_or_default(),
+            usage::run_usage(state, rid).await.map(Usage::from),
+        ),
+        None => (String::new(), None),
     };
 
     // Map task status to response status
@@ -838,8 +1672,281 @@ async fn build_response(
         status: status.to_string(),
         model: model.to_string(),
         output,
-        usage: None, // TODO: Track token usage
+        usage,
         error,
         metadata,
+        previous_response_id: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use taskrun_core::{
+        ChatMessage, RunEvent, RunId, RunSummary, WorkerId, WorkerInfo, WorkerStatus,
+    };
+    use taskrun_proto::pb::run_server_message::Payload as ServerPayload;
+
+    use crate::control_plane::state::ConnectedWorker;
+
+    #[tokio::test]
+    async fn test_dropped_stream_cancels_task() {
+        let state = AppState::new();
+        let task = Task::new("general", "{}", "api-key:ci");
+        let task_id = task.id.clone();
+        state.tasks.write().await.insert(task_id.clone(), task);
+
+        let (_tx, rx) = broadcast::channel(8);
+        let guard = DisconnectGuard::new(state.clone(), task_id.clone(), "api-key:ci".into(), None);
+        let stream = create_sse_stream(
+            state.clone(),
+            ResponseId::new(RunId::new("1"), 0),
+            None,
+            rx,
+            "general".into(),
+            0,
+            Some(guard),
+        );
+        drop(stream);
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let status = state.tasks.read().await[&task_id].status;
+        assert_eq!(status, TaskStatus::Cancelled);
+        let audit = state.audit.recent(1).await;
+        assert_eq!(audit[0].action, AuditAction::TaskCancelled);
+        assert_eq!(audit[0].actor, "api-key:ci");
+    }
+
+    #[tokio::test]
+    async fn test_completed_stream_reports_token_usage() {
+        let state = AppState::new();
+        let run_id = RunId::new("run-1");
+        state
+            .store_event(
+                RunEvent::execution_completed(run_id.clone(), TaskId::new("task-1"), None)
+                    .with_token_usage(120, 30),
+            )
+            .await;
+
+        let (tx, rx) = broadcast::channel(8);
+        let stream = create_sse_stream(
+            state,
+            ResponseId::new(run_id, 0),
+            None,
+            rx,
+            "general".into(),
+            0,
+            None,
+        );
+        tx.send(StreamEvent::StatusUpdate {
+            status: RunStatus::Completed,
+            error_message: None,
+            timestamp_ms: 0,
+        })
+        .unwrap();
+
+        let events: Vec<_> = stream.collect().await;
+        assert_eq!(events.len(), 2);
+        let completed = format!("{:?}", events[1].as_ref().unwrap());
+        assert!(completed.contains("response.completed"));
+        assert!(completed.contains(r#"\"total_tokens\":150"#));
+    }
+
+    #[tokio::test]
+    async fn test_previous_response_id_continues_session_on_same_worker() {
+        let state = AppState::new();
+        let worker_id = WorkerId::new("worker-1");
+        let (tx, mut worker_rx) = tokio::sync::mpsc::channel(8);
+        state.workers.write().await.insert(
+            worker_id.clone(),
+            ConnectedWorker {
+                info: WorkerInfo::new(worker_id.clone(), "host"),
+                status: WorkerStatus::Idle,
+                active_runs: 0,
+                max_concurrent_runs: 4,
+                interactive_slots: 0,
+                last_heartbeat: Utc::now(),
+                draining_since: None,
+                tx,
+                shutdown: tokio_util::sync::CancellationToken::new(),
+            },
+        );
+        let mut task = Task::new("general", "{}", "api-key:ci");
+        let mut run = RunSummary::new(worker_id);
+        run.complete(None);
+        let run_id = run.run_id.clone();
+        task.add_run(run);
+        state.tasks.write().await.insert(task.id.clone(), task);
+
+        let request = |previous: &str| CreateResponseRequest {
+            model: "general".to_string(),
+            input: Value::String("And in French?".to_string()),
+            instructions: None,
+            stream: false,
+            max_output_tokens: None,
+            temperature: None,
+            metadata: HashMap::new(),
+            previous_response_id: Some(previous.to_string()),
+            timeout_seconds: None,
+        };
+        let previous = format!("resp_{}", run_id);
+        let follow_up = tokio::spawn(create_follow_up_response(
+            state.clone(),
+            request(&previous),
+            previous.clone(),
+            None,
+            None,
+        ));
+
+        let sent = worker_rx.recv().await.unwrap();
+        let Some(ServerPayload::ContinueRun(continue_run)) = sent.payload else {
+            panic!("expected ContinueRun");
+        };
+        assert_eq!(continue_run.run_id, run_id.as_str());
+        assert_eq!(continue_run.message, "And in French?");
+
+        // The first response cannot be continued twice
+        let again = create_follow_up_response(
+            state.clone(),
+            request(&previous),
+            previous.clone(),
+            None,
+            None,
+        )
+        .await;
+        assert_eq!(again.status(), StatusCode::CONFLICT);
+
+        state
+            .store_chat_message(&run_id, ChatMessage::new(ChatRole::Assistant, "Bonjour"))
+            .await;
+        conversations::follow_up_status(&state, &run_id, RunStatus::Completed, None, 0).await;
+
+        let response = follow_up.await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["id"], format!("resp_{}.1", run_id));
+        assert_eq!(body["previous_response_id"], previous);
+        assert_eq!(body["output"][0]["content"][0]["text"], "Bonjour");
+        assert!(!state.conversations.in_progress(&run_id).await);
+    }
+
+    #[tokio::test]
+    async fn test_cancel_queued_response() {
+        let state = AppState::new();
+        let task = Task::new("general", "{}", "api-key:ci");
+        let task_id = task.id.clone();
+        state.tasks.write().await.insert(task_id.clone(), task);
+
+        let cancel = |id: String| cancel_response(State(state.clone()), None, None, None, Path(id));
+        let response = cancel(format!("resp_{}", task_id)).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["status"], "cancelled");
+        assert_eq!(
+            state.tasks.read().await[&task_id].status,
+            TaskStatus::Cancelled
+        );
+        assert_eq!(
+            state.audit.recent(1).await[0].action,
+            AuditAction::TaskCancelled
+        );
+
+        let again = cancel(format!("resp_{}", task_id)).await;
+        assert_eq!(again.status(), StatusCode::CONFLICT);
+        assert_eq!(
+            cancel("resp_unknown".into()).await.status(),
+            StatusCode::NOT_FOUND
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_run_ends_stream_with_cancelled_event() {
+        let (tx, rx) = broadcast::channel(8);
+        let stream = create_sse_stream(
+            AppState::new(),
+            ResponseId::new(RunId::new("run-1"), 0),
+            None,
+            rx,
+            "general".into(),
+            0,
+            None,
+        );
+        tx.send(StreamEvent::StatusUpdate {
+            status: RunStatus::Cancelled,
+            error_message: Some("Response cancelled by api-key:ci".to_string()),
+            timestamp_ms: 0,
+        })
+        .unwrap();
+
+        let events: Vec<_> = stream.collect().await;
+        assert_eq!(events.len(), 2);
+        let cancelled = format!("{:?}", events[1].as_ref().unwrap());
+        assert!(cancelled.contains("response.cancelled"));
+        assert!(cancelled.contains("Response cancelled by api-key:ci"));
+    }
+
+    #[test]
+    fn test_input_file_parts() {
+        let input = serde_json::json!([{
+            "role": "user",
+            "content": [
+                {"type": "input_text", "text": "Summarize the attached log"},
+                {"type": "input_file", "file_id": "file_1"},
+            ],
+        }]);
+        assert_eq!(input_text(&input), "Summarize the attached log");
+        assert_eq!(input_file_ids(&input), vec!["file_1".to_string()]);
+        assert!(input_file_ids(&Value::String("hi".into())).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_tool_calls_stream_as_tool_call_events() {
+        let (tx, rx) = broadcast::channel(8);
+        let stream = create_sse_stream(
+            AppState::new(),
+            ResponseId::new(RunId::new("run-1"), 0),
+            None,
+            rx,
+            "general".into(),
+            0,
+            None,
+        );
+        tx.send(StreamEvent::ToolCall {
+            call_id: "evt-1".to_string(),
+            tool_name: "Bash".to_string(),
+            input_summary: Some("cargo test".to_string()),
+            timestamp_ms: 0,
+        })
+        .unwrap();
+        tx.send(StreamEvent::ToolResult {
+            call_id: Some("evt-1".to_string()),
+            tool_name: Some("Bash".to_string()),
+            is_error: true,
+            timestamp_ms: 0,
+        })
+        .unwrap();
+        tx.send(StreamEvent::StatusUpdate {
+            status: RunStatus::Failed,
+            error_message: None,
+            timestamp_ms: 0,
+        })
+        .unwrap();
+
+        let events: Vec<_> = stream.collect().await;
+        assert_eq!(events.len(), 4);
+        let started = format!("{:?}", events[1].as_ref().unwrap());
+        assert!(started.contains("response.tool_call.started"));
+        assert!(started.contains(r#"\"input_summary\":\"cargo test\""#));
+        let completed = format!("{:?}", events[2].as_ref().unwrap());
+        assert!(completed.contains("response.tool_call.completed"));
+        assert!(completed.contains(r#"\"call_id\":\"evt-1\""#));
+        assert!(completed.contains(r#"\"status\":\"failed\""#));
     }
 }
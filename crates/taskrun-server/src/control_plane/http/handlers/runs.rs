@@ -0,0 +1,135 @@
+//! HTTP handlers for runs.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+use tracing::{info, warn};
+
+use taskrun_core::{RunId, RunManifest, Task};
+use taskrun_proto::trace::TraceContext;
+
+use crate::control_plane::audit::AuditAction;
+use crate::control_plane::http::access_log::AccessTaskId;
+use crate::control_plane::http::client_ip::{client_ip_string, ClientIp};
+use crate::control_plane::http::responses::{ErrorResponse, ReproduceResponse};
+use crate::control_plane::quotas;
+use crate::control_plane::rbac::Caller;
+use crate::control_plane::scheduler::{trace_created, Scheduler};
+use crate::control_plane::state::{AppState, IdLookupError, UiNotification};
+
+/// Get the reproducibility manifest of a run.
+///
+/// GET /v1/runs/:run_id/manifest
+///
+/// `run_id` may be a unique prefix of the full ID.
+pub async fn get_run_manifest(
+    State(state): State<Arc<AppState>>,
+    caller: Option<Extension<Caller>>,
+    Path(run_id): Path<String>,
+) -> Result<Response, IdLookupError> {
+    let namespace = caller.and_then(|c| c.0.namespace);
+    let run_id = state.resolve_run_id(&run_id, namespace.as_deref()).await?;
+    Ok(match find_manifest(&state, &run_id).await {
+        Some((_, manifest)) => Json(manifest).into_response(),
+        None => no_manifest(&run_id),
+    })
+}
+
+/// Create a new task configured like a previous run: same agent, input,
+/// labels and lane, pinned to the run's model and tool policy.
+///
+/// POST /v1/runs/:run_id/reproduce
+pub async fn reproduce_run(
+    State(state): State<Arc<AppState>>,
+    caller: Option<Extension<Caller>>,
+    client_ip: Option<Extension<ClientIp>>,
+    access_task: Option<Extension<AccessTaskId>>,
+    Path(run_id): Path<String>,
+) -> Result<Response, IdLookupError> {
+    let caller = caller.map(|c| c.0);
+    let namespace = caller.as_ref().and_then(|c| c.namespace.as_deref());
+    let run_id = state.resolve_run_id(&run_id, namespace).await?;
+    let Some((original, manifest)) = find_manifest(&state, &run_id).await else {
+        return Ok(no_manifest(&run_id));
+    };
+
+    let created_by = caller.map_or_else(|| "http-api".to_string(), |c| c.identity);
+    if let Err(e) = state.toggles.check_accepting().await {
+        return Ok(e.into_response());
+    }
+    if let Err(e) = quotas::check_create(&state, &original.namespace, &created_by).await {
+        return Ok(e.into_response());
+    }
+    let mut task = Task::new(&original.agent_name, &original.input_json, created_by)
+        .with_namespace(original.namespace)
+        .with_priority(original.priority)
+        .with_pinned_manifest(manifest)
+        .with_client_ip(client_ip_string(client_ip));
+    task.labels = original.labels;
+    task.labels
+        .insert("reproduces".to_string(), run_id.as_str().to_string());
+    task.timeout_seconds = original.timeout_seconds;
+    task.limits = original.limits;
+
+    let task = trace_created(task, &TraceContext::new());
+    let task_id = task.id.clone();
+    if let Some(Extension(access_task)) = &access_task {
+        access_task.set(&task_id);
+    }
+    info!(task_id = %task_id, run_id = %run_id, "Reproducing run");
+
+    state
+        .audit_from(
+            &task.created_by,
+            task.client_ip.as_deref(),
+            AuditAction::TaskCreated,
+            task_id.as_str(),
+            &task,
+        )
+        .await;
+    let created = UiNotification::task_created(&task);
+    state.tasks.write().await.insert(task_id.clone(), task);
+    state.notify_ui(created);
+
+    let new_run_id = match Scheduler::new(state.clone()).assign_task(&task_id).await {
+        Ok(new_run_id) => Some(new_run_id.into_inner()),
+        Err(e) => {
+            warn!(task_id = %task_id, error = %e, "Failed to assign task (no workers available?)");
+            None
+        }
+    };
+
+    Ok((
+        StatusCode::CREATED,
+        Json(ReproduceResponse {
+            task_id: task_id.into_inner(),
+            run_id: new_run_id,
+            reproduces: run_id.into_inner(),
+        }),
+    )
+        .into_response())
+}
+
+/// The task a run belongs to and the run's manifest, if it reported one.
+async fn find_manifest(state: &AppState, run_id: &RunId) -> Option<(Task, RunManifest)> {
+    let tasks = state.tasks.read().await;
+    tasks.values().find_map(|task| {
+        let run = task.runs.iter().find(|r| &r.run_id == run_id)?;
+        Some((task.clone(), run.manifest.clone()?))
+    })
+}
+
+fn no_manifest(run_id: &RunId) -> Response {
+    (
+        StatusCode::CONFLICT,
+        Json(ErrorResponse {
+            error: format!("Run {} has not reported a manifest", run_id),
+        }),
+    )
+        .into_response()
+}
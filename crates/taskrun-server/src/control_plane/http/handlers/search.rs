@@ -0,0 +1,59 @@
+//! HTTP handler for task search.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::control_plane::http::responses::ErrorResponse;
+use crate::control_plane::rbac::{self, Caller};
+use crate::control_plane::search::{self, SearchHit};
+use crate::control_plane::state::AppState;
+
+/// Query parameters for `GET /v1/search`.
+#[derive(Debug, Deserialize)]
+pub struct SearchParams {
+    /// Words that must all occur; a trailing `*` matches a prefix.
+    #[serde(default)]
+    pub q: String,
+    pub namespace: Option<String>,
+    #[serde(default)]
+    pub limit: usize,
+}
+
+/// Search results, newest task first.
+#[derive(Serialize)]
+pub struct SearchResponse {
+    pub hits: Vec<SearchHit>,
+}
+
+/// Full-text search over task input, labels, agent name and run output.
+///
+/// GET /v1/search?q=&namespace=&limit=
+///
+/// Callers bound to a namespace only see its tasks.
+pub async fn search_tasks(
+    State(state): State<Arc<AppState>>,
+    caller: Option<Extension<Caller>>,
+    Query(params): Query<SearchParams>,
+) -> Response {
+    let namespace = match rbac::scope(caller.as_ref().map(|c| &c.0), params.namespace.as_deref()) {
+        Ok(namespace) => namespace,
+        Err(e) => return e.into_response(),
+    };
+    match search::search(&state, &params.q, namespace.as_deref(), params.limit).await {
+        Ok(hits) => Json(SearchResponse { hits }).into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: e.to_string(),
+            }),
+        )
+            .into_response(),
+    }
+}
@@ -0,0 +1,217 @@
+//! Task transcript page handler.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    http::header,
+    response::IntoResponse,
+    Extension,
+};
+
+use taskrun_core::artifact::format_size;
+use taskrun_core::{Artifact, ChatRole};
+
+use crate::control_plane::rbac::Caller;
+use crate::control_plane::state::{AppState, IdLookupError};
+
+/// Show a task's chat transcript and artifacts as an HTML page.
+///
+/// GET /ui/tasks/:task_id
+///
+/// `task_id` may be a unique prefix of the full ID. Image artifacts are
+/// previewed inline; other artifacts are linked.
+pub async fn task_page_html(
+    State(state): State<Arc<AppState>>,
+    caller: Option<Extension<Caller>>,
+    Path(task_id): Path<String>,
+) -> Result<impl IntoResponse, IdLookupError> {
+    let namespace = caller.and_then(|c| c.0.namespace);
+    let task_id = state
+        .resolve_task_id(&task_id, namespace.as_deref())
+        .await?;
+    let task = state.tasks.read().await.get(&task_id).cloned();
+    let Some(task) = task else {
+        return Err(IdLookupError::NotFound {
+            kind: "Task",
+            prefix: task_id.as_str().to_string(),
+        });
+    };
+
+    let messages = state.get_chat_messages_by_task(&task_id).await;
+    let events = state.get_events_by_task(&task_id).await;
+
+    let mut transcript = String::new();
+    for message in &messages {
+        let (role, class) = match message.role {
+            ChatRole::User => ("You", "user"),
+            ChatRole::Assistant => ("AI", "assistant"),
+            ChatRole::System => ("System", "system"),
+        };
+        let time = chrono::DateTime::from_timestamp_millis(message.timestamp_ms)
+            .map(|t| t.format("%H:%M:%S").to_string())
+            .unwrap_or_default();
+        transcript.push_str(&format!(
+            r#"<div class="msg {}"><div class="meta"><strong>{}</strong> <small>{}</small></div><div class="content">{}</div>{}</div>"#,
+            class,
+            role,
+            time,
+            escape_html(&message.content),
+            artifacts_html(&message.attachments)
+        ));
+    }
+
+    // Output stored for runs without a chat transcript (e.g. batch runs)
+    if transcript.is_empty() {
+        transcript = match state.get_output_by_task(&task_id).await {
+            Some(output) => format!(
+                r#"<div class="msg assistant"><div class="content">{}</div></div>"#,
+                escape_html(&output)
+            ),
+            None => r#"<p style="color:#666;">No messages yet</p>"#.to_string(),
+        };
+    }
+
+    // Every artifact reported by the task's events, latest version of each
+    let mut artifacts: Vec<Artifact> = Vec::new();
+    for artifact in events.into_iter().flat_map(|e| e.artifacts) {
+        match artifacts.iter_mut().find(|a| a.uri == artifact.uri) {
+            Some(existing) => *existing = artifact,
+            None => artifacts.push(artifact),
+        }
+    }
+    let artifacts_section = if artifacts.is_empty() {
+        r#"<p style="color:#666;">No artifacts</p>"#.to_string()
+    } else {
+        artifacts_html(&artifacts)
+    };
+
+    let html = format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+    <title>TaskRun Task {id}</title>
+    <meta charset="utf-8">
+    <meta name="viewport" content="width=device-width, initial-scale=1">
+    <style>
+        body {{ font-family: -apple-system, BlinkMacSystemFont, 'Segoe UI', Roboto, sans-serif; margin: 20px; background: #f5f5f5; }}
+        h1 {{ color: #333; }}
+        h2 {{ color: #555; font-size: 18px; margin-top: 28px; }}
+        small {{ color: #888; }}
+        .msg {{ background: white; box-shadow: 0 1px 3px rgba(0,0,0,0.1); padding: 12px; margin-bottom: 10px; border-left: 4px solid #6b7280; }}
+        .msg.user {{ border-left-color: #22c55e; }}
+        .msg.assistant {{ border-left-color: #0ea5e9; }}
+        .content {{ white-space: pre-wrap; margin-top: 6px; }}
+        .artifacts {{ list-style: none; padding: 0; margin: 8px 0 0; }}
+        .artifacts li {{ margin: 4px 0; }}
+        .artifacts img {{ display: block; max-width: 480px; max-height: 320px; margin-top: 4px; border: 1px solid #eee; }}
+        .refresh {{ color: #0066cc; text-decoration: none; margin-left: 20px; }}
+        .refresh:hover {{ text-decoration: underline; }}
+    </style>
+</head>
+<body>
+    <h1>Task {short_id} <a href="/ui/tasks/{id}" class="refresh">↻ Refresh</a></h1>
+    <p>Agent: <strong>{agent}</strong> | Status: <strong>{status:?}</strong> | Created: {created}</p>
+    <h2>Transcript</h2>
+    {transcript}
+    <h2>Artifacts</h2>
+    {artifacts_section}
+    <p style="margin-top:20px;color:#888;font-size:12px;">
+        JSON API: <a href="/v1/tasks/{id}/events">/v1/tasks/{short_id}/events</a> |
+        <a href="/v1/tasks/{id}/output">/v1/tasks/{short_id}/output</a>
+    </p>
+</body>
+</html>"#,
+        id = task.id.as_str(),
+        short_id = &task.id.as_str()[..8.min(task.id.as_str().len())],
+        agent = escape_html(&task.agent_name),
+        status = task.status,
+        created = task.created_at.format("%Y-%m-%d %H:%M:%S UTC"),
+        transcript = transcript,
+        artifacts_section = artifacts_section,
+    );
+
+    Ok(([(header::CONTENT_TYPE, "text/html; charset=utf-8")], html))
+}
+
+/// URI schemes rendered as links.
+const LINKABLE_SCHEMES: [&str; 3] = ["file://", "http://", "https://"];
+
+/// Artifact links, with an inline preview for images.
+fn artifacts_html(artifacts: &[Artifact]) -> String {
+    if artifacts.is_empty() {
+        return String::new();
+    }
+
+    let items: Vec<String> = artifacts
+        .iter()
+        .map(|artifact| {
+            let name = escape_html(&artifact.name);
+            let size = artifact
+                .size_bytes
+                .map(|s| format!(" <small>({})</small>", format_size(s)))
+                .unwrap_or_default();
+
+            // URIs come from workers: only link schemes that cannot run script
+            if !LINKABLE_SCHEMES.iter().any(|s| artifact.uri.starts_with(s)) {
+                return format!("<li>📎 {}{}</li>", name, size);
+            }
+
+            let uri = escape_html(&artifact.uri);
+            let preview = if artifact.is_image() {
+                format!(
+                    r#"<a href="{0}"><img src="{0}" alt="{1}" loading="lazy"></a>"#,
+                    uri, name
+                )
+            } else {
+                String::new()
+            };
+            format!(
+                r#"<li>📎 <a href="{}">{}</a>{}{}</li>"#,
+                uri, name, size, preview
+            )
+        })
+        .collect();
+
+    format!(r#"<ul class="artifacts">{}</ul>"#, items.join(""))
+}
+
+/// Escape text for inclusion in HTML content and attribute values.
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_artifacts_html_previews_images() {
+        let html = artifacts_html(&[
+            Artifact::new("shot.png", "file:///tmp/shot.png")
+                .with_size(2048)
+                .with_mime_type("image/png"),
+            Artifact::new("<report>.pdf", "file:///tmp/report.pdf")
+                .with_mime_type("application/pdf"),
+        ]);
+
+        assert!(html.contains(r#"<img src="file:///tmp/shot.png""#));
+        assert!(html.contains("(2.0 KB)"));
+        assert!(html.contains("&lt;report&gt;.pdf"));
+        assert_eq!(html.matches("<img").count(), 1);
+
+        let html = artifacts_html(&[Artifact::new("x.html", "javascript:alert(1)")]);
+        assert!(!html.contains("href"));
+    }
+}
@@ -0,0 +1,252 @@
+//! HTTP handlers for listing, validating and cancelling tasks.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+use chrono::DateTime;
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use taskrun_core::{FailureReason, Task, TaskPriority, TaskStatus};
+
+use crate::control_plane::audit::AuditAction;
+use crate::control_plane::bulk::TaskFilter;
+use crate::control_plane::http::access_log::AccessTaskId;
+use crate::control_plane::http::client_ip::{client_ip_string, ClientIp};
+use crate::control_plane::http::responses::ErrorResponse;
+use crate::control_plane::rbac::{self, Caller};
+use crate::control_plane::scheduler::{Scheduler, SchedulerError};
+use crate::control_plane::state::AppState;
+use crate::control_plane::task_query::{self, TaskQuery, TaskSort};
+use crate::control_plane::validation::{self, TaskSpec, ValidationReport};
+
+/// Query parameters for `GET /v1/tasks`.
+#[derive(Debug, Deserialize)]
+pub struct ListTasksParams {
+    /// Task status, e.g. `failed`.
+    pub status: Option<String>,
+    pub agent: Option<String>,
+    /// Failure reason, e.g. `timeout`.
+    pub failure_reason: Option<String>,
+    /// Comma-separated label selector, e.g. `team=ops,env=prod`.
+    pub labels: Option<String>,
+    pub namespace: Option<String>,
+    pub created_after_ms: Option<i64>,
+    pub created_before_ms: Option<i64>,
+    #[serde(default)]
+    pub sort: TaskSort,
+    #[serde(default)]
+    pub limit: usize,
+    pub page_token: Option<String>,
+}
+
+/// One page of tasks.
+#[derive(Serialize)]
+pub struct TaskListResponse {
+    pub tasks: Vec<Task>,
+    /// Pass as `page_token` to get the next page; absent on the last page.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_page_token: Option<String>,
+}
+
+/// List tasks, newest first unless `sort=created_asc`.
+///
+/// GET /v1/tasks?status=&agent=&failure_reason=&labels=&namespace=&created_after_ms=&created_before_ms=&sort=&limit=&page_token=
+///
+/// Callers bound to a namespace only see its tasks.
+pub async fn list_tasks(
+    State(state): State<Arc<AppState>>,
+    caller: Option<Extension<Caller>>,
+    Query(params): Query<ListTasksParams>,
+) -> Response {
+    let mut query = match to_query(params) {
+        Ok(query) => query,
+        Err(message) => return bad_request(message),
+    };
+    query.filter.namespace = match rbac::scope(
+        caller.as_ref().map(|c| &c.0),
+        query.filter.namespace.as_deref(),
+    ) {
+        Ok(namespace) => namespace,
+        Err(e) => return e.into_response(),
+    };
+    match task_query::list(&state, &query).await {
+        Ok(page) => Json(TaskListResponse {
+            tasks: page.tasks,
+            next_page_token: page.next_page_token,
+        })
+        .into_response(),
+        Err(e) => bad_request(e.to_string()),
+    }
+}
+
+fn to_query(params: ListTasksParams) -> Result<TaskQuery, String> {
+    let status = params
+        .status
+        .map(|s| parse_status(&s).ok_or_else(|| format!("Unknown task status '{}'", s)))
+        .transpose()?;
+    let failure_reason = params
+        .failure_reason
+        .map(|r| FailureReason::parse(&r).ok_or_else(|| format!("Unknown failure reason '{}'", r)))
+        .transpose()?;
+
+    let mut labels = HashMap::new();
+    for pair in params.labels.iter().flat_map(|l| l.split(',')) {
+        let (key, value) = pair
+            .split_once('=')
+            .ok_or_else(|| format!("Expected key=value in labels, got '{}'", pair))?;
+        labels.insert(key.to_string(), value.to_string());
+    }
+
+    let timestamp = |ms: Option<i64>| {
+        ms.map(|ms| {
+            DateTime::from_timestamp_millis(ms).ok_or_else(|| format!("Invalid timestamp {}", ms))
+        })
+        .transpose()
+    };
+
+    Ok(TaskQuery {
+        filter: TaskFilter {
+            status,
+            agent_name: params.agent,
+            failure_reason,
+            labels,
+            namespace: params.namespace,
+        },
+        created_after: timestamp(params.created_after_ms)?,
+        created_before: timestamp(params.created_before_ms)?,
+        sort: params.sort,
+        limit: params.limit,
+        page_token: params.page_token,
+    })
+}
+
+/// Body of `POST /v1/tasks/validate`: the task as it would be created.
+#[derive(Debug, Deserialize)]
+pub struct ValidateTaskRequest {
+    #[serde(default)]
+    pub agent_name: String,
+    /// Task input: a JSON value, or a string sent as is.
+    #[serde(default)]
+    pub input: serde_json::Value,
+    #[serde(default)]
+    pub namespace: Option<String>,
+    /// `BATCH` (default) or `INTERACTIVE`.
+    #[serde(default)]
+    pub priority: TaskPriority,
+}
+
+/// Check a task spec against the agent catalog, quotas and the scheduler
+/// without creating anything.
+///
+/// POST /v1/tasks/validate
+///
+/// Always 200 with the findings, unless the namespace is not accessible.
+pub async fn validate_task(
+    State(state): State<Arc<AppState>>,
+    caller: Option<Extension<Caller>>,
+    Json(req): Json<ValidateTaskRequest>,
+) -> Result<Json<ValidationReport>, rbac::NamespaceError> {
+    let caller = caller.map(|c| c.0);
+    let namespace = rbac::namespace_for_new(caller.as_ref(), req.namespace.as_deref())?;
+    let input_json = match req.input {
+        serde_json::Value::Null => String::new(),
+        serde_json::Value::String(s) => s,
+        value => value.to_string(),
+    };
+    let created_by = caller.map_or_else(|| "http-api".to_string(), |c| c.identity);
+
+    let report = validation::validate(
+        &state,
+        &TaskSpec {
+            agent_name: &req.agent_name,
+            input_json: &input_json,
+            namespace: &namespace,
+            created_by: &created_by,
+            priority: req.priority,
+        },
+    )
+    .await;
+    Ok(Json(report))
+}
+
+/// Cancel a task, stopping its run on the worker.
+///
+/// DELETE /v1/tasks/:task_id
+///
+/// `task_id` may be a unique prefix of the full ID. Returns the cancelled
+/// task once the worker acknowledged (or the control plane gave up waiting
+/// and finalized the run); 409 if the task already finished.
+pub async fn cancel_task(
+    State(state): State<Arc<AppState>>,
+    caller: Option<Extension<Caller>>,
+    client_ip: Option<Extension<ClientIp>>,
+    access_task: Option<Extension<AccessTaskId>>,
+    Path(task_id): Path<String>,
+) -> Response {
+    let (actor, namespace) = caller.map_or_else(
+        || ("http-api".to_string(), None),
+        |c| (c.0.identity, c.0.namespace),
+    );
+    let task_id = match state.resolve_task_id(&task_id, namespace.as_deref()).await {
+        Ok(task_id) => task_id,
+        Err(e) => return e.into_response(),
+    };
+    if let Some(Extension(access_task)) = &access_task {
+        access_task.set(&task_id);
+    }
+
+    let reason = format!("Task cancelled by {}", actor);
+    let task = match Scheduler::new(state.clone())
+        .cancel_task(&task_id, &reason)
+        .await
+    {
+        Ok(task) => task,
+        Err(e) => {
+            let status = match e {
+                SchedulerError::TaskNotFound(_) => StatusCode::NOT_FOUND,
+                SchedulerError::TaskAlreadyTerminal(..) => StatusCode::CONFLICT,
+                _ => StatusCode::INTERNAL_SERVER_ERROR,
+            };
+            return (
+                status,
+                Json(ErrorResponse {
+                    error: e.to_string(),
+                }),
+            )
+                .into_response();
+        }
+    };
+    info!(task_id = %task_id, cancelled_by = %actor, "Task cancelled via HTTP");
+    state
+        .audit_from(
+            &actor,
+            client_ip_string(client_ip).as_deref(),
+            AuditAction::TaskCancelled,
+            task_id.as_str(),
+            &task,
+        )
+        .await;
+    Json(task).into_response()
+}
+
+fn parse_status(s: &str) -> Option<TaskStatus> {
+    match s.to_ascii_lowercase().as_str() {
+        "pending" => Some(TaskStatus::Pending),
+        "running" => Some(TaskStatus::Running),
+        "completed" => Some(TaskStatus::Completed),
+        "failed" => Some(TaskStatus::Failed),
+        "cancelled" => Some(TaskStatus::Cancelled),
+        _ => None,
+    }
+}
+
+fn bad_request(error: String) -> Response {
+    (StatusCode::BAD_REQUEST, Json(ErrorResponse { error })).into_response()
+}
@@ -0,0 +1,40 @@
+//! HTTP handler for token usage and cost reports.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Query, State},
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+
+use crate::control_plane::rbac::{self, Caller};
+use crate::control_plane::state::AppState;
+use crate::control_plane::usage;
+
+/// Query parameters for `GET /v1/usage`.
+#[derive(Debug, Deserialize)]
+pub struct UsageParams {
+    /// Only count runs finished at or after this time (RFC 3339).
+    pub since: Option<DateTime<Utc>>,
+    pub namespace: Option<String>,
+}
+
+/// Tokens and cost reported by runs, per task, agent and namespace.
+///
+/// GET /v1/usage?since=&namespace=
+///
+/// Callers bound to a namespace only see its tasks.
+pub async fn get_usage(
+    State(state): State<Arc<AppState>>,
+    caller: Option<Extension<Caller>>,
+    Query(params): Query<UsageParams>,
+) -> Response {
+    let namespace = match rbac::scope(caller.as_ref().map(|c| &c.0), params.namespace.as_deref()) {
+        Ok(namespace) => namespace,
+        Err(e) => return e.into_response(),
+    };
+    Json(usage::report(&state, params.since, namespace.as_deref()).await).into_response()
+}
@@ -0,0 +1,235 @@
+//! Live task and worker updates over SSE.
+//!
+//! `GET /v1/watch` pushes changes to dashboards and desktop clients instead
+//! of having them poll `/v1/tasks` and `/v1/workers`:
+//!
+//! - `ready` once subscribed: list tasks and workers now, then apply events
+//! - `task-updated`: the task, as in `/v1/tasks`, on creation and on task or
+//!   run status changes
+//! - `worker-updated`: the worker, as in `/v1/workers`, on connect, heartbeat
+//!   and status changes
+//! - `worker-removed`: `{"worker_id"}` when a worker disconnects
+//! - `lagged`: `{"missed"}` when the client fell behind; list again
+//!
+//! A keep-alive comment is sent every `KEEP_ALIVE_INTERVAL`, so a client that
+//! hears nothing for twice that long can mark its data stale and reconnect.
+
+use std::convert::Infallible;
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
+    Extension, Json,
+};
+use serde::Deserialize;
+use tokio::sync::{broadcast, mpsc};
+use tokio_stream::wrappers::ReceiverStream;
+use tokio_stream::StreamExt;
+use tracing::debug;
+
+use taskrun_core::{TaskId, WorkerId};
+
+use super::workers::worker_response;
+use crate::control_plane::http::responses::ErrorResponse;
+use crate::control_plane::rbac::{self, Caller};
+use crate::control_plane::state::{AppState, UiNotification};
+
+/// Interval between keep-alive comments.
+pub const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Buffered events per client before the stream applies backpressure.
+const WATCH_BUFFER: usize = 64;
+
+/// Query parameters for `GET /v1/watch`.
+#[derive(Debug, Deserialize)]
+pub struct WatchParams {
+    /// Only tasks and workers of this namespace.
+    pub namespace: Option<String>,
+}
+
+/// Stream task and worker updates as server-sent events.
+///
+/// GET /v1/watch?namespace=
+///
+/// Callers bound to a namespace only see its tasks and workers.
+pub async fn watch(
+    State(state): State<Arc<AppState>>,
+    caller: Option<Extension<Caller>>,
+    Query(params): Query<WatchParams>,
+) -> Response {
+    let namespace = match rbac::scope(caller.as_ref().map(|c| &c.0), params.namespace.as_deref()) {
+        Ok(namespace) => namespace,
+        Err(e) => return e.into_response(),
+    };
+    let Some(feed) = state.subscribe_ui() else {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(ErrorResponse {
+                error: "Live updates are not available on this server".to_string(),
+            }),
+        )
+            .into_response();
+    };
+
+    let (tx, rx) = mpsc::channel(WATCH_BUFFER);
+    let _ = tx.try_send(Event::default().event("ready").data("{}"));
+    tokio::spawn(forward(state, namespace, feed, tx));
+
+    let stream = ReceiverStream::new(rx).map(Ok::<_, Infallible>);
+    Sse::new(stream)
+        .keep_alive(KeepAlive::new().interval(KEEP_ALIVE_INTERVAL))
+        .into_response()
+}
+
+/// Turn notifications into events until the client goes away.
+async fn forward(
+    state: Arc<AppState>,
+    namespace: Option<String>,
+    mut feed: broadcast::Receiver<UiNotification>,
+    tx: mpsc::Sender<Event>,
+) {
+    loop {
+        let event = tokio::select! {
+            received = feed.recv() => match received {
+                Ok(notification) => {
+                    match to_event(&state, namespace.as_deref(), notification).await {
+                        Some(event) => event,
+                        None => continue,
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(missed)) => {
+                    json_event("lagged", &serde_json::json!({ "missed": missed }))
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+            _ = tx.closed() => break,
+        };
+        if tx.send(event).await.is_err() {
+            break;
+        }
+    }
+    debug!("HTTP watch ended");
+}
+
+/// The event for `notification`, if it is about a task or worker in
+/// `namespace` (None = any).
+async fn to_event(
+    state: &AppState,
+    namespace: Option<&str>,
+    notification: UiNotification,
+) -> Option<Event> {
+    match notification {
+        UiNotification::TaskCreated { task_id, .. }
+        | UiNotification::TaskStatusChanged { task_id, .. }
+        | UiNotification::RunStatusChanged { task_id, .. } => {
+            task_event(state, namespace, &task_id).await
+        }
+        UiNotification::WorkerConnected { worker_id, .. }
+        | UiNotification::WorkerHeartbeat { worker_id, .. }
+        | UiNotification::WorkerStatusChanged { worker_id, .. }
+        | UiNotification::WorkerUpdated { worker_id, .. }
+        | UiNotification::WorkerDrained { worker_id } => {
+            worker_event(state, namespace, &worker_id).await
+        }
+        // The worker is gone, so the notification carries its namespace
+        UiNotification::WorkerDisconnected {
+            worker_id,
+            namespace: worker_namespace,
+        } => {
+            if namespace.is_some_and(|ns| ns != worker_namespace) {
+                return None;
+            }
+            Some(json_event(
+                "worker-removed",
+                &serde_json::json!({ "worker_id": worker_id }),
+            ))
+        }
+        _ => None,
+    }
+}
+
+async fn task_event(state: &AppState, namespace: Option<&str>, task_id: &TaskId) -> Option<Event> {
+    let tasks = state.tasks.read().await;
+    let task = tasks.get(task_id)?;
+    if namespace.is_some_and(|ns| ns != task.namespace) {
+        return None;
+    }
+    Some(json_event("task-updated", task))
+}
+
+async fn worker_event(
+    state: &AppState,
+    namespace: Option<&str>,
+    worker_id: &WorkerId,
+) -> Option<Event> {
+    let workers = state.workers.read().await;
+    let worker = workers.get(worker_id)?;
+    if namespace.is_some_and(|ns| ns != worker.info.namespace) {
+        return None;
+    }
+    Some(json_event("worker-updated", &worker_response(worker)))
+}
+
+fn json_event(name: &str, data: &impl serde::Serialize) -> Event {
+    Event::default()
+        .event(name)
+        .json_data(data)
+        .unwrap_or_else(|_| Event::default().event(name).data("{}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::control_plane::audit::AuditLog;
+    use crate::control_plane::event_store::InMemoryEventStore;
+    use crate::control_plane::output_store::RunOutputs;
+    use taskrun_core::{Task, TaskStatus};
+
+    #[tokio::test]
+    async fn test_task_events_are_scoped_to_the_namespace() {
+        let (state, _ui_rx) = AppState::with_ui_channel(
+            None,
+            Arc::new(InMemoryEventStore::new()),
+            RunOutputs::in_memory(),
+            AuditLog::in_memory(),
+        );
+        let task = Task::new("general", "{}", "test").with_namespace("team-a");
+        state
+            .tasks
+            .write()
+            .await
+            .insert(task.id.clone(), task.clone());
+        let changed = || UiNotification::TaskStatusChanged {
+            task_id: task.id.clone(),
+            status: TaskStatus::Running,
+        };
+
+        assert!(to_event(&state, Some("team-a"), changed()).await.is_some());
+        assert!(to_event(&state, None, changed()).await.is_some());
+        assert!(to_event(&state, Some("team-b"), changed()).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_worker_removed_is_scoped_to_the_namespace() {
+        let (state, _ui_rx) = AppState::with_ui_channel(
+            None,
+            Arc::new(InMemoryEventStore::new()),
+            RunOutputs::in_memory(),
+            AuditLog::in_memory(),
+        );
+        let removed = || UiNotification::WorkerDisconnected {
+            worker_id: WorkerId::generate(),
+            namespace: "team-a".to_string(),
+        };
+
+        assert!(to_event(&state, Some("team-a"), removed()).await.is_some());
+        assert!(to_event(&state, None, removed()).await.is_some());
+        assert!(to_event(&state, Some("team-b"), removed()).await.is_none());
+    }
+}
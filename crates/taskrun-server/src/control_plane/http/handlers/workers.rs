@@ -2,58 +2,145 @@
 
 use std::sync::Arc;
 
-use axum::{extract::State, http::header, response::IntoResponse, Json};
+use axum::{
+    extract::{Path, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
 
-use crate::control_plane::http::responses::{AgentResponse, BackendResponse, WorkerResponse};
-use crate::control_plane::state::AppState;
+use crate::control_plane::audit::AuditAction;
+use crate::control_plane::disconnect;
+use crate::control_plane::http::client_ip::{client_ip_string, ClientIp};
+use crate::control_plane::http::handlers::AdminState;
+use crate::control_plane::http::responses::{
+    AgentResponse, BackendResponse, DisconnectWorkerRequest, ErrorResponse, WorkerResponse,
+};
+use crate::control_plane::rbac::Caller;
+use crate::control_plane::state::{AppState, ConnectedWorker};
 
 /// List workers as JSON.
-pub async fn list_workers_json(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+///
+/// Callers bound to a namespace only see its workers.
+pub async fn list_workers_json(
+    State(state): State<Arc<AppState>>,
+    caller: Option<Extension<Caller>>,
+) -> impl IntoResponse {
     let workers = state.workers.read().await;
     let response: Vec<WorkerResponse> = workers
         .values()
-        .map(|w| WorkerResponse {
-            worker_id: w.info.worker_id.as_str().to_string(),
-            hostname: w.info.hostname.clone(),
-            version: w.info.version.clone(),
-            status: format!("{:?}", w.status).to_uppercase(),
-            active_runs: w.active_runs,
-            max_concurrent_runs: w.max_concurrent_runs,
-            last_heartbeat: w.last_heartbeat.to_rfc3339(),
-            agents: w
-                .info
-                .agents
-                .iter()
-                .map(|a| AgentResponse {
-                    name: a.name.clone(),
-                    description: a.description.clone(),
-                    backends: a
-                        .backends
-                        .iter()
-                        .map(|b| BackendResponse {
-                            provider: b.provider.clone(),
-                            model_name: b.model_name.clone(),
-                        })
-                        .collect(),
-                })
-                .collect(),
-        })
+        .filter(|w| visible(caller.as_deref(), w))
+        .map(worker_response)
         .collect();
     Json(response)
 }
 
+/// Whether `caller` may see `worker`.
+fn visible(caller: Option<&Caller>, worker: &ConnectedWorker) -> bool {
+    caller.map_or(true, |c| c.can_access(&worker.info.namespace))
+}
+
+/// JSON view of a connected worker.
+pub(super) fn worker_response(w: &ConnectedWorker) -> WorkerResponse {
+    WorkerResponse {
+        worker_id: w.info.worker_id.as_str().to_string(),
+        hostname: w.info.hostname.clone(),
+        version: w.info.version.clone(),
+        status: format!("{:?}", w.status).to_uppercase(),
+        active_runs: w.active_runs,
+        max_concurrent_runs: w.max_concurrent_runs,
+        last_heartbeat: w.last_heartbeat.to_rfc3339(),
+        agents: w
+            .info
+            .agents
+            .iter()
+            .map(|a| AgentResponse {
+                name: a.name.clone(),
+                description: a.description.clone(),
+                backends: a
+                    .backends
+                    .iter()
+                    .map(|b| BackendResponse {
+                        provider: b.provider.clone(),
+                        model_name: b.model_name.clone(),
+                    })
+                    .collect(),
+            })
+            .collect(),
+    }
+}
+
+/// Close a worker's stream, failing (and optionally rescheduling) its runs.
+///
+/// POST /v1/admin/workers/:id/disconnect
+///
+/// `id` may be a unique prefix of the full ID. The body is optional.
+pub async fn disconnect_worker(
+    State(admin): State<AdminState>,
+    Extension(caller): Extension<Caller>,
+    client_ip: Option<Extension<ClientIp>>,
+    Path(id): Path<String>,
+    body: Option<Json<DisconnectWorkerRequest>>,
+) -> Response {
+    let Json(req) = body.unwrap_or_default();
+    let worker_id = match admin
+        .app
+        .resolve_worker_id(&id, caller.namespace.as_deref())
+        .await
+    {
+        Ok(worker_id) => worker_id,
+        Err(e) => return e.into_response(),
+    };
+    let reason = req
+        .reason
+        .filter(|r| !r.is_empty())
+        .unwrap_or_else(|| format!("Disconnected by {}", caller.identity));
+
+    match disconnect::disconnect_worker(&admin.app, &worker_id, &reason, req.reschedule).await {
+        Some(outcome) => {
+            admin
+                .app
+                .audit_from(
+                    &caller.identity,
+                    client_ip_string(client_ip).as_deref(),
+                    AuditAction::WorkerDisconnected,
+                    worker_id.as_str(),
+                    &outcome,
+                )
+                .await;
+            Json(outcome).into_response()
+        }
+        None => (
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: format!("Worker {} not found", worker_id),
+            }),
+        )
+            .into_response(),
+    }
+}
+
 /// List workers as HTML page.
-pub async fn list_workers_html(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+pub async fn list_workers_html(
+    State(state): State<Arc<AppState>>,
+    caller: Option<Extension<Caller>>,
+) -> impl IntoResponse {
     let workers = state.workers.read().await;
+    let workers: Vec<&ConnectedWorker> = workers
+        .values()
+        .filter(|w| visible(caller.as_deref(), w))
+        .collect();
     let now = chrono::Utc::now();
 
     let mut rows = String::new();
-    for worker in workers.values() {
+    for worker in &workers {
         let status_color = match worker.status {
             taskrun_core::WorkerStatus::Idle => "#22c55e",
             taskrun_core::WorkerStatus::Busy => "#eab308",
             taskrun_core::WorkerStatus::Draining => "#f97316",
             taskrun_core::WorkerStatus::Error => "#ef4444",
+            taskrun_core::WorkerStatus::Degraded => "#fb7185",
+            taskrun_core::WorkerStatus::Offline => "#6b7280",
         };
 
         let heartbeat_ago = format_relative_time(now, worker.last_heartbeat);
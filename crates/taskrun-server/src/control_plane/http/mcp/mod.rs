@@ -1,12 +1,46 @@
-//! MCP (Model Context Protocol) HTTP tools.
+//! Deprecated REST routes for the MCP tools.
 //!
-//! Provides HTTP endpoints for MCP tools:
+//! The MCP server at `/mcp` (see `crate::mcp`) speaks the protocol and is
+//! what MCP clients connect to. These routes predate it and stay as an alias
+//! of its tools for existing callers:
 //! - `list_workers` - List connected workers
 //! - `start_new_task` - Create and start a new task
 //! - `read_task` - Get task status, output, events, and chat
 //! - `continue_task` - Send a follow-up message
+//!
+//! Responses carry `Deprecation` and `Link` headers pointing at `/mcp`.
 
 mod tools;
 mod types;
 
+use std::sync::Once;
+
+use axum::{
+    extract::Request,
+    http::{header, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+use tracing::warn;
+
 pub use tools::{continue_task, list_workers, read_task, start_new_task};
+
+/// Mark a response of a `/mcp/tools/*` route as deprecated.
+pub async fn deprecated(request: Request, next: Next) -> Response {
+    static WARNED: Once = Once::new();
+    WARNED.call_once(|| {
+        warn!(
+            path = %request.uri().path(),
+            "The /mcp/tools/* routes are deprecated; connect an MCP client to /mcp instead"
+        );
+    });
+
+    let mut response = next.run(request).await;
+    let headers = response.headers_mut();
+    headers.insert("deprecation", HeaderValue::from_static("true"));
+    headers.insert(
+        header::LINK,
+        HeaderValue::from_static("</mcp>; rel=\"successor-version\""),
+    );
+    response
+}
@@ -2,15 +2,21 @@
 
 use std::sync::Arc;
 
-use axum::{extract::State, Json};
+use axum::{extract::State, Extension, Json};
 use tracing::{info, warn};
 
-use taskrun_core::{ChatRole, RunEventType, Task, TaskId};
+use taskrun_core::{ChatRole, RunEventType, Task, DEFAULT_NAMESPACE};
 use taskrun_proto::pb::run_server_message::Payload as ServerPayload;
 use taskrun_proto::pb::{ContinueRun, RunServerMessage};
+use taskrun_proto::trace::TraceContext;
 
-use crate::control_plane::scheduler::Scheduler;
-use crate::control_plane::state::AppState;
+use crate::control_plane::audit::AuditAction;
+use crate::control_plane::http::access_log::AccessTaskId;
+use crate::control_plane::http::client_ip::{client_ip_string, ClientIp};
+use crate::control_plane::quotas;
+use crate::control_plane::rbac::Caller;
+use crate::control_plane::scheduler::{trace_created, Scheduler};
+use crate::control_plane::state::{AppState, IdLookupError, UiNotification};
 
 use super::types::*;
 
@@ -21,12 +27,19 @@ use super::types::*;
 /// List all connected workers.
 pub async fn list_workers(
     State(state): State<Arc<AppState>>,
+    caller: Option<Extension<Caller>>,
     Json(request): Json<McpRequest<ListWorkersParams>>,
 ) -> Json<McpResponse<ListWorkersResult>> {
+    let namespace = caller.and_then(|c| c.0.namespace);
     let workers = state.workers.read().await;
 
     let workers_list: Vec<WorkerInfo> = workers
         .values()
+        .filter(|w| {
+            namespace
+                .as_ref()
+                .map_or(true, |ns| &w.info.namespace == ns)
+        })
         .filter(|w| {
             // Apply optional agent filter
             if let Some(ref agent) = request.params.agent {
@@ -57,6 +70,9 @@ pub async fn list_workers(
 /// Start a new task on an available worker.
 pub async fn start_new_task(
     State(state): State<Arc<AppState>>,
+    caller: Option<Extension<Caller>>,
+    client_ip: Option<Extension<ClientIp>>,
+    access_task: Option<Extension<AccessTaskId>>,
     Json(request): Json<McpRequest<StartNewTaskParams>>,
 ) -> Json<McpResponse<StartNewTaskResult>> {
     let params = request.params;
@@ -67,8 +83,14 @@ pub async fn start_new_task(
         other => other.to_string(),
     };
 
+    let caller = caller.map(|c| c.0);
+    let namespace = caller
+        .as_ref()
+        .and_then(|c| c.namespace.clone())
+        .unwrap_or_else(|| DEFAULT_NAMESPACE.to_string());
+
     // Check if any worker supports this agent
-    if !state.has_agent(&params.agent_name).await {
+    if !state.has_agent(&params.agent_name, &namespace).await {
         return Json(McpResponse::err(
             "NO_AGENT",
             &format!("No worker supports agent: {}", params.agent_name),
@@ -76,7 +98,17 @@ pub async fn start_new_task(
     }
 
     // Create task
-    let mut task = Task::new(&params.agent_name, &input_json, "mcp");
+    let created_by = caller.map_or_else(|| "mcp".to_string(), |c| c.identity);
+    if let Err(e) = state.toggles.check_accepting().await {
+        return Json(McpResponse::err("MAINTENANCE", &e.to_string()));
+    }
+    if let Err(e) = quotas::check_create(&state, &namespace, &created_by).await {
+        return Json(McpResponse::err("QUOTA_EXCEEDED", &e.to_string()));
+    }
+    let mut task = Task::new(&params.agent_name, &input_json, created_by)
+        .with_namespace(namespace)
+        .with_priority(state.toggles.default_priority().await)
+        .with_client_ip(client_ip_string(client_ip));
 
     // Add metadata
     task.labels.insert("source".to_string(), "mcp".to_string());
@@ -84,10 +116,25 @@ pub async fn start_new_task(
         task.labels.insert(key, value);
     }
 
+    let task = trace_created(task, &TraceContext::new());
     let task_id = task.id.clone();
+    if let Some(Extension(access_task)) = &access_task {
+        access_task.set(&task_id);
+    }
 
     // Store task
+    state
+        .audit_from(
+            &task.created_by,
+            task.client_ip.as_deref(),
+            AuditAction::TaskCreated,
+            task_id.as_str(),
+            &task,
+        )
+        .await;
+    let created = UiNotification::task_created(&task);
     state.tasks.write().await.insert(task_id.clone(), task);
+    state.notify_ui(created);
 
     info!(
         task_id = %task_id,
@@ -124,20 +171,17 @@ pub async fn start_new_task(
 /// Read task status, output, events, and chat history.
 pub async fn read_task(
     State(state): State<Arc<AppState>>,
+    caller: Option<Extension<Caller>>,
     Json(request): Json<McpRequest<ReadTaskParams>>,
 ) -> Json<McpResponse<ReadTaskResult>> {
-    let task_id = TaskId::new(&request.params.task_id);
-
-    // Verify task exists
+    let namespace = caller.and_then(|c| c.0.namespace);
+    let task_id = match state
+        .resolve_task_id(&request.params.task_id, namespace.as_deref())
+        .await
     {
-        let tasks = state.tasks.read().await;
-        if !tasks.contains_key(&task_id) {
-            return Json(McpResponse::err(
-                "NOT_FOUND",
-                &format!("Task not found: {}", request.params.task_id),
-            ));
-        }
-    }
+        Ok(id) => id,
+        Err(e) => return Json(lookup_error(e)),
+    };
 
     // Get output
     let output = state.get_output_by_task(&task_id).await;
@@ -198,9 +242,17 @@ pub async fn read_task(
 /// Continue a task with a follow-up message.
 pub async fn continue_task(
     State(state): State<Arc<AppState>>,
+    caller: Option<Extension<Caller>>,
     Json(request): Json<McpRequest<ContinueTaskParams>>,
 ) -> Json<McpResponse<ContinueTaskResult>> {
-    let task_id = TaskId::new(&request.params.task_id);
+    let namespace = caller.and_then(|c| c.0.namespace);
+    let task_id = match state
+        .resolve_task_id(&request.params.task_id, namespace.as_deref())
+        .await
+    {
+        Ok(id) => id,
+        Err(e) => return Json(lookup_error(e)),
+    };
 
     // Get task and its latest run
     let (run_id, worker_id) = {
@@ -270,6 +322,14 @@ pub async fn continue_task(
 // Helpers
 // ============================================================================
 
+fn lookup_error<T>(err: IdLookupError) -> McpResponse<T> {
+    let code = match err {
+        IdLookupError::NotFound { .. } => "NOT_FOUND",
+        IdLookupError::Ambiguous { .. } => "AMBIGUOUS",
+    };
+    McpResponse::err(code, &err.to_string())
+}
+
 fn event_type_to_string(event_type: &RunEventType) -> String {
     match event_type {
         RunEventType::ExecutionStarted => "execution_started",
@@ -279,6 +339,9 @@ fn event_type_to_string(event_type: &RunEventType) -> String {
         RunEventType::OutputGenerated => "output_generated",
         RunEventType::ExecutionCompleted => "execution_completed",
         RunEventType::ExecutionFailed => "execution_failed",
+        RunEventType::Milestone => "milestone",
+        RunEventType::Log => "log",
+        RunEventType::RunSummary => "run_summary",
     }
     .to_string()
 }
@@ -119,7 +119,7 @@ pub struct StartNewTaskResult {
 /// Parameters for read_task.
 #[derive(Debug, Deserialize)]
 pub struct ReadTaskParams {
-    /// Task ID to read.
+    /// Task ID (or unique prefix) to read.
     pub task_id: String,
 }
 
@@ -173,7 +173,7 @@ pub struct ChatMessageInfo {
 /// Parameters for continue_task.
 #[derive(Debug, Deserialize)]
 pub struct ContinueTaskParams {
-    /// Task ID to continue.
+    /// Task ID (or unique prefix) to continue.
     pub task_id: String,
 
     /// Follow-up message.
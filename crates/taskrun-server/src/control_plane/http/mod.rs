@@ -1,54 +1,316 @@
 //! HTTP server for the control plane.
 //!
 //! Provides endpoints for:
-//! - OpenAI-compatible responses API (`/v1/responses`)
-//! - Worker enrollment (`/v1/enroll`)
-//! - Worker list API (`/v1/workers`)
+//! - OpenAI-compatible responses API (`/v1/responses`) and model list (`/v1/models`)
+//! - Worker enrollment (`/v1/enroll`) and certificate revocation list (`/v1/crl`)
+//! - Worker list API (`/v1/workers`) and disconnect (`/v1/admin/workers/:id/disconnect`)
+//! - Task list and pre-flight validation API (`/v1/tasks`, `/v1/tasks/validate`)
+//! - File uploads (`/v1/files`) and run artifacts (`/v1/tasks/:task_id/artifacts`)
+//! - Task search (`/v1/search`)
+//! - Run queue (`/v1/queue`)
+//! - Group summaries (`/v1/groups/:id/summary`)
+//! - Token usage and cost (`/v1/usage`)
+//! - Run manifests and reproduction (`/v1/runs/:run_id/*`)
 //! - Workers UI (`/ui/workers`)
 //! - Health check (`/health`)
 //! - Prometheus metrics (`/metrics`)
-//! - MCP tools (`/mcp/tools/*`)
+//! - Grafana SimpleJSON datasource (`/v1/grafana/*`)
+//! - Deprecated REST alias of the MCP tools (`/mcp/tools/*`; the MCP server
+//!   itself is mounted at `/mcp`, see `crate::mcp`)
+//! - API key management (`/v1/admin/api-keys`)
+//! - Quota usage (`/v1/admin/quotas`)
+//! - CA rotation state (`/v1/admin/ca`)
+//! - Settings reload (`/v1/admin/reload`)
+//! - Chaos flags for fault injection (`/v1/admin/chaos`)
+//! - Audit log (`/v1/audit`)
+//! - Memory and state counters for leak diagnosis (`/v1/debug/stats`)
+//!
+//! The responses API, task and worker endpoints and MCP tools require an API
+//! key when authentication is enabled; admin endpoints always require an admin
+//! key. Reading needs a `readonly` key, creating or continuing tasks an
+//! `operator` key (see `rbac`). With `--http-tls` the API is served over TLS,
+//! and a client certificate from the TaskRun CA may stand in for a key (see
+//! `tls`). Cross-origin requests are allowed from the `--cors-origin` origins
+//! (any origin if none are given). Behind a reverse proxy, client addresses
+//! are resolved from forwarding headers of trusted proxies (see `client_ip`).
+//! Requests are given a correlation ID, logged and counted (see
+//! `request_log`), and can be written to a structured access log (see
+//! `access_log`).
 
 use std::sync::Arc;
 
 use axum::{
-    routing::{get, post},
-    Router,
+    extract::DefaultBodyLimit,
+    http::{header::InvalidHeaderValue, HeaderValue},
+    middleware,
+    routing::{delete, get, post},
+    Extension, Router,
 };
-use tower_http::cors::{Any, CorsLayer};
+use tower_http::cors::{AllowOrigin, Any, CorsLayer};
 
+use crate::control_plane::files::MAX_FILE_BYTES;
+use crate::control_plane::rbac::Role;
+use crate::control_plane::reload::ConfigReloader;
 use crate::control_plane::state::AppState;
 
+pub mod access_log;
+pub mod auth;
+pub mod client_ip;
 mod handlers;
 mod mcp;
+pub mod request_log;
 pub mod responses;
+pub mod tls;
 
-/// Create the HTTP router.
-pub fn create_router(state: Arc<AppState>) -> Router {
-    // CORS layer for devtools access
-    let cors = CorsLayer::new()
-        .allow_origin(Any)
+pub use access_log::{AccessLog, AccessLogFile};
+pub use auth::{require_role, ApiKeyAuth};
+pub use client_ip::{resolve_client_ip, TrustedProxies};
+pub use request_log::{with_request_log, RequestLogConfig};
+
+/// Body limit of `POST /v1/files`: the largest file plus multipart framing.
+const UPLOAD_BODY_LIMIT: usize = MAX_FILE_BYTES + 64 * 1024;
+
+/// Options for the `/v1/responses` endpoint.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ResponsesConfig {
+    /// Cancel a streamed response's task when the client disconnects mid-stream.
+    pub cancel_on_disconnect: bool,
+}
+
+/// CORS layer allowing requests from `origins` (any origin if empty).
+pub fn cors_layer(origins: &[String]) -> Result<CorsLayer, InvalidHeaderValue> {
+    let allow_origin = if origins.is_empty() {
+        AllowOrigin::any()
+    } else {
+        let origins = origins
+            .iter()
+            .map(|origin| HeaderValue::from_str(origin))
+            .collect::<Result<Vec<_>, _>>()?;
+        AllowOrigin::list(origins)
+    };
+    Ok(CorsLayer::new()
+        .allow_origin(allow_origin)
         .allow_methods(Any)
-        .allow_headers(Any);
+        .allow_headers(Any))
+}
 
-    Router::new()
-        // OpenAI-compatible API
-        .route("/v1/responses", post(handlers::create_response))
-        // API routes
-        .route("/v1/enroll", post(handlers::enroll))
+/// Create the HTTP router.
+pub fn create_router(
+    state: Arc<AppState>,
+    auth: ApiKeyAuth,
+    responses: ResponsesConfig,
+    reloader: Option<Arc<ConfigReloader>>,
+    access_log: Option<AccessLog>,
+) -> Router {
+    // Deprecated alias of the MCP server's tools
+    let deprecated = middleware::from_fn(mcp::deprecated);
+
+    // Routes that require an API key when authentication is enabled
+    let readonly = Router::new()
+        .route("/v1/models", get(handlers::list_models))
         .route("/v1/workers", get(handlers::list_workers_json))
+        .route("/ui/workers", get(handlers::list_workers_html))
+        .route("/v1/tasks", get(handlers::list_tasks))
+        .route("/v1/search", get(handlers::search_tasks))
+        .route("/v1/queue", get(handlers::list_queue))
+        .route("/v1/groups/:id/summary", get(handlers::get_group_summary))
+        .route("/v1/usage", get(handlers::get_usage))
         .route("/v1/tasks/:task_id/events", get(handlers::get_task_events))
         .route("/v1/tasks/:task_id/output", get(handlers::get_task_output))
-        // MCP tools
-        .route("/mcp/tools/list_workers", post(mcp::list_workers))
-        .route("/mcp/tools/start_new_task", post(mcp::start_new_task))
-        .route("/mcp/tools/read_task", post(mcp::read_task))
-        .route("/mcp/tools/continue_task", post(mcp::continue_task))
-        // UI routes
-        .route("/ui/workers", get(handlers::list_workers_html))
+        .route(
+            "/v1/tasks/:task_id/artifacts",
+            get(handlers::list_task_artifacts),
+        )
+        .route(
+            "/v1/tasks/:task_id/artifacts/:name",
+            get(handlers::download_task_artifact),
+        )
+        .route("/v1/runs/:run_id/manifest", get(handlers::get_run_manifest))
+        .route("/v1/approvals", get(handlers::list_approvals))
+        .route("/v1/watch", get(handlers::watch))
+        // Task transcript page (holds the same data as the task endpoints)
+        .route("/ui/tasks/:task_id", get(handlers::task_page_html))
+        // MCP tools (deprecated)
+        .route(
+            "/mcp/tools/list_workers",
+            post(mcp::list_workers).layer(deprecated.clone()),
+        )
+        .route(
+            "/mcp/tools/read_task",
+            post(mcp::read_task).layer(deprecated.clone()),
+        )
+        .route_layer(middleware::from_fn_with_state(
+            auth.require(Role::Readonly),
+            require_role,
+        ));
+
+    let operator = Router::new()
+        // OpenAI-compatible API
+        .route("/v1/responses", post(handlers::create_response))
+        .route(
+            "/v1/responses/:response_id/cancel",
+            post(handlers::cancel_response),
+        )
+        .route("/v1/tasks/:task_id", delete(handlers::cancel_task))
+        .route(
+            "/v1/files",
+            post(handlers::upload_file).layer(DefaultBodyLimit::max(UPLOAD_BODY_LIMIT)),
+        )
+        .route("/v1/files/:file_id", delete(handlers::delete_file))
+        .route("/v1/tasks/validate", post(handlers::validate_task))
+        .route("/v1/runs/:run_id/reproduce", post(handlers::reproduce_run))
+        .route(
+            "/v1/approvals/:id/approve",
+            post(handlers::approve_approval),
+        )
+        .route("/v1/approvals/:id/deny", post(handlers::deny_approval))
+        // MCP tools (deprecated)
+        .route(
+            "/mcp/tools/start_new_task",
+            post(mcp::start_new_task).layer(deprecated.clone()),
+        )
+        .route(
+            "/mcp/tools/continue_task",
+            post(mcp::continue_task).layer(deprecated),
+        )
+        .layer(Extension(responses))
+        .route_layer(middleware::from_fn_with_state(
+            auth.require(Role::Operator),
+            require_role,
+        ));
+
+    let admin = Router::new()
+        .route(
+            "/v1/admin/api-keys",
+            get(handlers::list_api_keys).post(handlers::create_api_key),
+        )
+        .route("/v1/admin/api-keys/:id", delete(handlers::revoke_api_key))
+        .route("/v1/admin/quotas", get(handlers::list_quotas))
+        .route("/v1/admin/ca", get(handlers::get_ca_rotation))
+        .route("/v1/admin/reload", post(handlers::reload_config))
+        .route(
+            "/v1/admin/chaos",
+            get(handlers::get_chaos).put(handlers::update_chaos),
+        )
+        .route(
+            "/v1/admin/workers/:id/disconnect",
+            post(handlers::disconnect_worker),
+        )
+        .route("/v1/audit", get(handlers::list_audit))
+        .route("/v1/debug/stats", get(handlers::get_debug_stats))
+        .route_layer(middleware::from_fn_with_state(
+            auth.require(Role::Admin),
+            require_role,
+        ))
+        .with_state(handlers::AdminState {
+            keys: auth.keys,
+            app: state.clone(),
+            reloader,
+        });
+
+    let router = Router::new()
+        .merge(readonly)
+        .merge(operator)
+        .merge(admin)
+        // Worker enrollment (authenticated by its bootstrap token)
+        .route("/v1/enroll", post(handlers::enroll))
+        .route("/v1/crl", get(handlers::get_crl))
         // Observability routes
         .route("/health", get(handlers::health_check))
         .route("/metrics", get(handlers::metrics_handler))
-        .layer(cors)
-        .with_state(state)
+        .route("/v1/grafana", get(handlers::grafana_health))
+        .route("/v1/grafana/", get(handlers::grafana_health))
+        .route("/v1/grafana/search", post(handlers::grafana_search))
+        .route("/v1/grafana/query", post(handlers::grafana_query));
+    let router = match access_log {
+        Some(log) => router.layer(middleware::from_fn_with_state(log, access_log::log_access)),
+        None => router,
+    };
+    router.with_state(state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::{to_bytes, Body};
+    use axum::http::Request;
+    use chrono::{Duration as ChronoDuration, Utc};
+    use tower::ServiceExt;
+
+    use taskrun_core::{RunSummary, Task, TaskStatus, WorkerId};
+
+    use crate::control_plane::api_keys::ApiKeyManager;
+
+    #[tokio::test]
+    async fn test_metrics_endpoint_exposes_expected_families() {
+        let state = AppState::new();
+        let mut task = Task::new("general", "{}", "test");
+        let mut run = RunSummary::new(WorkerId::generate());
+        run.start();
+        run.complete(None);
+        run.started_at = Some(Utc::now() - ChronoDuration::seconds(42));
+        task.add_run(run);
+        task.status = TaskStatus::Completed;
+        state.tasks.write().await.insert(task.id.clone(), task);
+        state
+            .scheduling_latency
+            .observe(std::time::Duration::from_millis(300));
+
+        let auth = ApiKeyAuth::new(ApiKeyManager::open(None).await.unwrap(), false);
+        let router = create_router(state, auth, ResponsesConfig::default(), None, None);
+        let response = router
+            .oneshot(Request::get("/metrics").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body = String::from_utf8(body.to_vec()).unwrap();
+
+        for (family, kind) in [
+            ("taskrun_workers_connected", "gauge"),
+            ("taskrun_worker_heartbeat_age_seconds", "gauge"),
+            ("taskrun_worker_active_runs", "gauge"),
+            ("taskrun_tasks_total", "gauge"),
+            ("taskrun_runs_total", "gauge"),
+            ("taskrun_run_duration_seconds", "histogram"),
+            ("taskrun_output_bytes", "gauge"),
+            ("taskrun_queue_depth", "gauge"),
+            ("taskrun_scheduling_latency_seconds", "histogram"),
+            ("taskrun_stream_channels", "gauge"),
+            ("taskrun_http_requests_total", "counter"),
+        ] {
+            assert!(
+                body.contains(&format!("# TYPE {family} {kind}\n")),
+                "missing {family}"
+            );
+        }
+        assert!(body.contains("taskrun_runs_total{agent=\"general\",status=\"completed\"} 1"));
+        assert!(body.contains("taskrun_run_duration_seconds_bucket{agent=\"general\",le=\"60\"} 1"));
+        assert!(body.contains("taskrun_run_duration_seconds_bucket{agent=\"general\",le=\"30\"} 0"));
+        assert!(body.contains("taskrun_scheduling_latency_seconds_bucket{le=\"0.5\"} 1"));
+        assert!(body.contains("taskrun_scheduling_latency_seconds_count 1"));
+    }
+
+    #[tokio::test]
+    async fn test_mcp_tool_routes_are_deprecated() {
+        let auth = ApiKeyAuth::new(ApiKeyManager::open(None).await.unwrap(), false);
+        let router = create_router(
+            AppState::new(),
+            auth,
+            ResponsesConfig::default(),
+            None,
+            None,
+        );
+        let request = Request::post("/mcp/tools/list_workers")
+            .header("content-type", "application/json")
+            .body(Body::from(r#"{"params": {}}"#))
+            .unwrap();
+        let response = router.oneshot(request).await.unwrap();
+
+        assert_eq!(response.status(), axum::http::StatusCode::OK);
+        assert_eq!(response.headers()["deprecation"], "true");
+        assert_eq!(
+            response.headers()["link"],
+            "</mcp>; rel=\"successor-version\""
+        );
+    }
 }
@@ -0,0 +1,260 @@
+//! Request logging for the HTTP API.
+//!
+//! Every request gets a correlation ID: the `X-Request-Id` the client sent,
+//! or a generated one. It is echoed on the response and recorded on the
+//! `http_request` span, so anything a handler logs can be traced back to the
+//! request. When a request finishes, one `http_request` event is emitted with
+//! its method, path, status, latency and body sizes. Events are sampled by
+//! `RequestLogConfig::sample_rate`; server errors are always logged. Every
+//! request is counted in `HttpStats` regardless of sampling and exported by
+//! the metrics endpoint.
+//!
+//! Latency is measured until the response head is ready, so for streamed
+//! responses (SSE) it does not include the stream itself, and their
+//! response size is not known.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use axum::{
+    body::HttpBody,
+    extract::{MatchedPath, Request, State},
+    middleware::{self, Next},
+    response::Response,
+    Router,
+};
+use tower_http::request_id::{
+    MakeRequestUuid, PropagateRequestIdLayer, RequestId, SetRequestIdLayer,
+};
+use tower_http::trace::TraceLayer;
+use tracing::{info, info_span, warn};
+
+use crate::control_plane::http::client_ip::ClientIp;
+use crate::control_plane::state::AppState;
+
+/// Route label for requests that matched no route.
+const UNMATCHED_ROUTE: &str = "unmatched";
+
+/// Request logging options.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RequestLogConfig {
+    /// Fraction of requests (0.0 to 1.0) that are logged. Server errors are
+    /// logged regardless.
+    pub sample_rate: f64,
+}
+
+impl Default for RequestLogConfig {
+    fn default() -> Self {
+        Self { sample_rate: 1.0 }
+    }
+}
+
+impl RequestLogConfig {
+    /// Whether a request that got `status` is logged, given a roll in [0, 1).
+    fn should_log(&self, status: u16, roll: f64) -> bool {
+        status >= 500 || roll < self.sample_rate
+    }
+}
+
+/// Key of a group of counted requests.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct RouteKey {
+    pub method: String,
+    /// Route pattern (e.g. `/v1/tasks/:task_id/events`), not the raw path.
+    pub route: String,
+    pub status: u16,
+}
+
+/// Totals for one `RouteKey`.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct RouteStats {
+    pub requests: u64,
+    pub latency_secs: f64,
+    pub request_bytes: u64,
+    pub response_bytes: u64,
+}
+
+/// Request totals by method, route and status.
+#[derive(Debug, Default)]
+pub struct HttpStats {
+    routes: Mutex<HashMap<RouteKey, RouteStats>>,
+}
+
+impl HttpStats {
+    fn record(&self, key: RouteKey, latency: Duration, request_bytes: u64, response_bytes: u64) {
+        let mut routes = self.routes.lock().unwrap();
+        let stats = routes.entry(key).or_default();
+        stats.requests += 1;
+        stats.latency_secs += latency.as_secs_f64();
+        stats.request_bytes += request_bytes;
+        stats.response_bytes += response_bytes;
+    }
+
+    /// Current totals, sorted by key.
+    pub fn snapshot(&self) -> Vec<(RouteKey, RouteStats)> {
+        let mut routes: Vec<_> = self
+            .routes
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(key, stats)| (key.clone(), *stats))
+            .collect();
+        routes.sort_by(|a, b| a.0.cmp(&b.0));
+        routes
+    }
+}
+
+/// State of the logging middleware.
+#[derive(Clone)]
+struct RequestLog {
+    config: RequestLogConfig,
+    state: Arc<AppState>,
+}
+
+/// Add correlation IDs, the request span and request logging to `router`.
+pub fn with_request_log(router: Router, state: Arc<AppState>, config: RequestLogConfig) -> Router {
+    let trace = TraceLayer::new_for_http()
+        .make_span_with(|request: &Request| {
+            info_span!(
+                "http_request",
+                method = %request.method(),
+                path = %request.uri().path(),
+                request_id = request_id(request).unwrap_or_default(),
+            )
+        })
+        // The middleware below logs the outcome
+        .on_request(())
+        .on_response(())
+        .on_failure(());
+
+    // Layers run outermost (last added) first
+    router
+        .layer(middleware::from_fn_with_state(
+            RequestLog { config, state },
+            log_request,
+        ))
+        .layer(trace)
+        .layer(PropagateRequestIdLayer::x_request_id())
+        .layer(SetRequestIdLayer::x_request_id(MakeRequestUuid))
+}
+
+fn request_id(request: &Request) -> Option<&str> {
+    request
+        .extensions()
+        .get::<RequestId>()
+        .and_then(|id| id.header_value().to_str().ok())
+}
+
+async fn log_request(State(log): State<RequestLog>, request: Request, next: Next) -> Response {
+    let started = Instant::now();
+    let method = request.method().clone();
+    let path = request.uri().path().to_string();
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map_or(UNMATCHED_ROUTE, |p| p.as_str())
+        .to_string();
+    let request_id = request_id(&request).unwrap_or_default().to_string();
+    let client_ip = request.extensions().get::<ClientIp>().map(|ip| ip.0);
+    let request_bytes = request.body().size_hint().exact();
+
+    let response = next.run(request).await;
+
+    let latency = started.elapsed();
+    let status = response.status().as_u16();
+    let response_bytes = response.body().size_hint().exact();
+    log.state.http.record(
+        RouteKey {
+            method: method.to_string(),
+            route,
+            status,
+        },
+        latency,
+        request_bytes.unwrap_or(0),
+        response_bytes.unwrap_or(0),
+    );
+
+    if !log.config.should_log(status, rand::random()) {
+        return response;
+    }
+    let latency_ms = latency.as_secs_f64() * 1000.0;
+    let client_ip = client_ip.map(|ip| ip.to_string());
+    if status >= 500 {
+        warn!(
+            method = %method,
+            path,
+            status,
+            latency_ms,
+            request_id,
+            client_ip,
+            request_bytes,
+            response_bytes,
+            "HTTP request failed"
+        );
+    } else {
+        info!(
+            method = %method,
+            path,
+            status,
+            latency_ms,
+            request_id,
+            client_ip,
+            request_bytes,
+            response_bytes,
+            "HTTP request"
+        );
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, routing::get};
+    use tower::ServiceExt;
+
+    #[test]
+    fn test_sampling_keeps_server_errors() {
+        let config = RequestLogConfig { sample_rate: 0.25 };
+        assert!(config.should_log(200, 0.1));
+        assert!(!config.should_log(200, 0.5));
+        assert!(config.should_log(503, 0.99));
+        let off = RequestLogConfig { sample_rate: 0.0 };
+        assert!(!off.should_log(404, 0.0));
+    }
+
+    #[tokio::test]
+    async fn test_requests_get_an_id_and_are_counted() {
+        let state = AppState::new();
+        let router = Router::new().route("/v1/items/:id", get(|| async { "hello" }));
+        let router = with_request_log(router, state.clone(), RequestLogConfig { sample_rate: 0.0 });
+
+        let response = router
+            .clone()
+            .oneshot(Request::get("/v1/items/7").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert!(response.headers().contains_key("x-request-id"));
+
+        // A client's ID is kept
+        let response = router
+            .oneshot(
+                Request::get("/v1/items/8")
+                    .header("x-request-id", "abc")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.headers()["x-request-id"], "abc");
+
+        let snapshot = state.http.snapshot();
+        assert_eq!(snapshot.len(), 1);
+        let (key, stats) = &snapshot[0];
+        assert_eq!(key.route, "/v1/items/:id");
+        assert_eq!((key.method.as_str(), key.status), ("GET", 200));
+        assert_eq!(stats.requests, 2);
+        assert_eq!(stats.response_bytes, 10);
+    }
+}
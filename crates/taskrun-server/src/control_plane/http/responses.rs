@@ -1,7 +1,15 @@
 //! HTTP request and response types.
 
+use axum::http::{header, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::Json;
 use serde::{Deserialize, Serialize};
 
+use crate::control_plane::quotas::{QuotaExceeded, QuotaResource, QuotaScope};
+use crate::control_plane::rbac::{NamespaceError, Role};
+use crate::control_plane::state::IdLookupError;
+use crate::control_plane::toggles::MaintenanceMode;
+
 // ============================================================================
 // Enrollment types
 // ============================================================================
@@ -27,6 +35,12 @@ pub struct EnrollResponse {
 
     /// Certificate expiration time (ISO 8601).
     pub expires_at: String,
+
+    /// Certificate serial number (hex), for revoking it.
+    pub serial_number: String,
+
+    /// SPIFFE ID in the certificate's URI SAN.
+    pub spiffe_id: String,
 }
 
 // ============================================================================
@@ -39,6 +53,84 @@ pub struct ErrorResponse {
     pub error: String,
 }
 
+impl IntoResponse for IdLookupError {
+    fn into_response(self) -> Response {
+        let status = match self {
+            IdLookupError::NotFound { .. } => StatusCode::NOT_FOUND,
+            IdLookupError::Ambiguous { .. } => StatusCode::CONFLICT,
+        };
+        (
+            status,
+            Json(ErrorResponse {
+                error: self.to_string(),
+            }),
+        )
+            .into_response()
+    }
+}
+
+/// Body of a 429 returned when a quota would be exceeded.
+#[derive(Debug, Serialize)]
+pub struct QuotaErrorResponse {
+    pub error: String,
+    pub scope: QuotaScope,
+    pub resource: QuotaResource,
+    pub used: u64,
+    pub limit: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retry_after_secs: Option<u64>,
+}
+
+impl IntoResponse for QuotaExceeded {
+    fn into_response(self) -> Response {
+        let body = Json(QuotaErrorResponse {
+            error: self.to_string(),
+            scope: self.scope,
+            resource: self.resource,
+            used: self.used,
+            limit: self.limit,
+            retry_after_secs: self.retry_after_secs,
+        });
+        match self.retry_after_secs {
+            Some(secs) => (
+                StatusCode::TOO_MANY_REQUESTS,
+                [(header::RETRY_AFTER, secs.to_string())],
+                body,
+            )
+                .into_response(),
+            None => (StatusCode::TOO_MANY_REQUESTS, body).into_response(),
+        }
+    }
+}
+
+impl IntoResponse for MaintenanceMode {
+    fn into_response(self) -> Response {
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(ErrorResponse {
+                error: self.to_string(),
+            }),
+        )
+            .into_response()
+    }
+}
+
+impl IntoResponse for NamespaceError {
+    fn into_response(self) -> Response {
+        let status = match self {
+            NamespaceError::Denied { .. } => StatusCode::FORBIDDEN,
+            NamespaceError::Invalid(_) => StatusCode::BAD_REQUEST,
+        };
+        (
+            status,
+            Json(ErrorResponse {
+                error: self.to_string(),
+            }),
+        )
+            .into_response()
+    }
+}
+
 // ============================================================================
 // Worker list types
 // ============================================================================
@@ -70,3 +162,90 @@ pub struct BackendResponse {
     pub provider: String,
     pub model_name: String,
 }
+
+/// Request body for disconnecting a worker.
+#[derive(Debug, Default, Deserialize)]
+pub struct DisconnectWorkerRequest {
+    /// Why, recorded in the audit log and sent to the worker.
+    #[serde(default)]
+    pub reason: Option<String>,
+
+    /// Put the worker's running tasks back in the queue.
+    #[serde(default)]
+    pub reschedule: bool,
+}
+
+// ============================================================================
+// API key types
+// ============================================================================
+
+/// Request body for creating an API key.
+#[derive(Debug, Deserialize)]
+pub struct CreateApiKeyRequest {
+    /// Human-readable name (who or what uses the key).
+    pub name: String,
+
+    /// Role of the key (defaults to operator).
+    #[serde(default)]
+    pub role: Role,
+
+    /// Namespace to bind the key to (defaults to all namespaces, or the
+    /// caller's own if it is bound to one).
+    #[serde(default)]
+    pub namespace: Option<String>,
+}
+
+/// Response for a single API key. Never includes the key itself.
+#[derive(Debug, Serialize)]
+pub struct ApiKeyResponse {
+    pub id: String,
+    pub name: String,
+    pub prefix: String,
+    pub role: Role,
+    pub namespace: Option<String>,
+    pub created_at: String,
+}
+
+/// Response for a newly created API key.
+#[derive(Debug, Serialize)]
+pub struct CreateApiKeyResponse {
+    #[serde(flatten)]
+    pub api_key: ApiKeyResponse,
+
+    /// Plaintext key. Only returned once.
+    pub key: String,
+}
+
+// ============================================================================
+// Run reproduction types
+// ============================================================================
+
+/// Response for `POST /v1/runs/:run_id/reproduce`.
+#[derive(Debug, Serialize)]
+pub struct ReproduceResponse {
+    /// The new task, pinned to the original run's manifest.
+    pub task_id: String,
+
+    /// Run of the new task, if a worker was available.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub run_id: Option<String>,
+
+    /// The run being reproduced.
+    pub reproduces: String,
+}
+
+// ============================================================================
+// Approval types
+// ============================================================================
+
+/// Optional request body for approving or denying a tool use.
+#[derive(Debug, Default, Deserialize)]
+pub struct DecideApprovalRequest {
+    /// Why, shown to Claude when the tool use is denied.
+    #[serde(default)]
+    pub reason: String,
+
+    /// Also allow later uses of the tool by the same agent (approve only).
+    #[serde(default)]
+    pub always_allow: bool,
+}
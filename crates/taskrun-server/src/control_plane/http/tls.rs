@@ -0,0 +1,199 @@
+//! TLS for the HTTP listener.
+//!
+//! With `--http-tls` the HTTP API is served over TLS with the gRPC server's
+//! certificate. Clients may present a certificate signed by the TaskRun CA;
+//! its common name names the caller as on the gRPC side (`role:name`,
+//! `worker:<id>`) and stands in for an API key (see `auth`). Clients without
+//! one, such as workers enrolling or callers using API keys, are still
+//! accepted. A revoked certificate, or one of a previous CA after its
+//! rotation window, names no caller.
+
+use std::io;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::extract::ConnectInfo;
+use axum::Router;
+use hyper::body::Incoming;
+use hyper_util::rt::{TokioExecutor, TokioIo};
+use hyper_util::server::conn::auto::Builder;
+use thiserror::Error;
+use tokio::net::TcpListener;
+use tokio_rustls::rustls::crypto::{ring, CryptoProvider};
+use tokio_rustls::rustls::pki_types::CertificateDer;
+use tokio_rustls::rustls::server::WebPkiClientVerifier;
+use tokio_rustls::rustls::{self, RootCertStore, ServerConfig};
+use tokio_rustls::TlsAcceptor;
+use tower::ServiceExt;
+use tracing::{debug, warn};
+
+use crate::control_plane::crypto::{extract_common_name, CertificateAuthority};
+use crate::control_plane::rbac::Caller;
+use crate::control_plane::state::AppState;
+
+/// Caller named by a verified TLS client certificate.
+#[derive(Debug, Clone)]
+pub struct ClientCertCaller(pub Caller);
+
+/// Errors setting up HTTP TLS.
+#[derive(Debug, Error)]
+pub enum HttpTlsError {
+    #[error("Invalid PEM: {0}")]
+    Pem(#[from] io::Error),
+
+    #[error("No private key in the TLS key file")]
+    NoPrivateKey,
+
+    #[error("No certificate in the CA file")]
+    NoCaCertificate,
+
+    #[error("TLS configuration failed: {0}")]
+    Rustls(#[from] rustls::Error),
+
+    #[error("Client certificate verification failed to build: {0}")]
+    Verifier(#[from] rustls::server::VerifierBuilderError),
+}
+
+/// A TLS acceptor serving `cert_pem` with `key_pem`, verifying client
+/// certificates against `ca_pem` when clients present one.
+pub fn acceptor(
+    cert_pem: &[u8],
+    key_pem: &[u8],
+    ca_pem: &[u8],
+) -> Result<TlsAcceptor, HttpTlsError> {
+    let certs = rustls_pemfile::certs(&mut &cert_pem[..]).collect::<Result<Vec<_>, _>>()?;
+    let key = rustls_pemfile::private_key(&mut &key_pem[..])?.ok_or(HttpTlsError::NoPrivateKey)?;
+    let mut roots = RootCertStore::empty();
+    for ca in rustls_pemfile::certs(&mut &ca_pem[..]) {
+        roots.add(ca?)?;
+    }
+    if roots.is_empty() {
+        return Err(HttpTlsError::NoCaCertificate);
+    }
+
+    let provider: Arc<CryptoProvider> = Arc::new(ring::default_provider());
+    let verifier = WebPkiClientVerifier::builder_with_provider(Arc::new(roots), provider.clone())
+        .allow_unauthenticated()
+        .build()?;
+    let mut config = ServerConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()?
+        .with_client_cert_verifier(verifier)
+        .with_single_cert(certs, key)?;
+    config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+    Ok(TlsAcceptor::from(Arc::new(config)))
+}
+
+/// The caller named by a verified client certificate, if any and still
+/// trusted by `ca`.
+fn client_cert_caller(
+    certs: Option<&[CertificateDer<'_>]>,
+    ca: Option<&CertificateAuthority>,
+) -> Option<ClientCertCaller> {
+    let cert = certs?.first()?.as_ref();
+    if let Some(Err(e)) = ca.map(|ca| ca.check_client_cert(cert)) {
+        warn!(error = %e, "Ignoring untrusted client certificate");
+        return None;
+    }
+    let cn = extract_common_name(cert).ok()?;
+    let caller = Caller::from_common_name(&cn);
+    if caller.is_none() {
+        debug!(cn = %cn, "Client certificate does not name a role");
+    }
+    caller.map(ClientCertCaller)
+}
+
+/// Serve `router` over TLS on `listener`. Like `axum::serve`, requests carry
+/// the peer address as `ConnectInfo<SocketAddr>`.
+pub async fn serve(
+    listener: TcpListener,
+    acceptor: TlsAcceptor,
+    router: Router,
+    state: Arc<AppState>,
+) -> io::Result<()> {
+    loop {
+        let (stream, addr) = match listener.accept().await {
+            Ok(conn) => conn,
+            Err(e) => {
+                // Usually out of file descriptors; give connections time to close
+                warn!(error = %e, "Failed to accept HTTP connection");
+                tokio::time::sleep(Duration::from_millis(100)).await;
+                continue;
+            }
+        };
+        let acceptor = acceptor.clone();
+        let router = router.clone();
+        let state = state.clone();
+
+        tokio::spawn(async move {
+            let stream = match acceptor.accept(stream).await {
+                Ok(stream) => stream,
+                Err(e) => {
+                    debug!(client = %addr, error = %e, "TLS handshake failed");
+                    return;
+                }
+            };
+            let cert_caller =
+                client_cert_caller(stream.get_ref().1.peer_certificates(), state.ca.as_ref());
+
+            let service =
+                hyper::service::service_fn(move |mut request: hyper::Request<Incoming>| {
+                    request
+                        .extensions_mut()
+                        .insert(ConnectInfo::<SocketAddr>(addr));
+                    if let Some(caller) = &cert_caller {
+                        request.extensions_mut().insert(caller.clone());
+                    }
+                    router.clone().oneshot(request)
+                });
+            if let Err(e) = Builder::new(TokioExecutor::new())
+                .serve_connection_with_upgrades(TokioIo::new(stream), service)
+                .await
+            {
+                debug!(client = %addr, error = %e, "HTTP connection ended with an error");
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::control_plane::rbac::Role;
+    use rcgen::{BasicConstraints, CertificateParams, DnType, IsCa, KeyPair};
+
+    #[test]
+    fn test_acceptor_and_client_certificate_caller() {
+        let mut ca_params = CertificateParams::default();
+        ca_params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+        let ca_key = KeyPair::generate().unwrap();
+        let ca = ca_params.self_signed(&ca_key).unwrap();
+
+        let signed = |cn: &str| {
+            let mut params = CertificateParams::new(vec!["localhost".to_string()]).unwrap();
+            params.distinguished_name.push(DnType::CommonName, cn);
+            let key = KeyPair::generate().unwrap();
+            (params.signed_by(&key, &ca, &ca_key).unwrap(), key)
+        };
+
+        let (server, server_key) = signed("localhost");
+        acceptor(
+            server.pem().as_bytes(),
+            server_key.serialize_pem().as_bytes(),
+            ca.pem().as_bytes(),
+        )
+        .unwrap();
+        assert!(matches!(
+            acceptor(server.pem().as_bytes(), b"", ca.pem().as_bytes()),
+            Err(HttpTlsError::NoPrivateKey)
+        ));
+
+        let (client, _) = signed("readonly:dashboard");
+        let ClientCertCaller(caller) =
+            client_cert_caller(Some(&[client.der().clone()]), None).unwrap();
+        assert_eq!(caller.identity, "readonly:dashboard");
+        assert_eq!(caller.role, Role::Readonly);
+        assert!(client_cert_caller(Some(&[server.der().clone()]), None).is_none());
+        assert!(client_cert_caller(None, None).is_none());
+    }
+}
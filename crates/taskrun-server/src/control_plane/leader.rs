@@ -0,0 +1,838 @@
+//! Leader election for running several control planes (HA mode).
+//!
+//! Instances share a lease file name on storage they can all reach (e.g. an
+//! NFS mount). Each leadership term has its own file next to it
+//! (`<lease-file>.<term>`), and the file of the highest term is the lease in
+//! force. Its holder renews it every third of its TTL. Once it has expired,
+//! an instance takes over by creating the next term's file with a hard link,
+//! which fails if the file exists, so exactly one instance wins a term.
+//!
+//! Only the leader accepts workers and changes, and only it runs the
+//! background loops (dispatcher, reaper, liveness, schedules, workflows,
+//! compaction). Followers serve reads (HTTP `GET` and the gRPC Get, List,
+//! Search and Watch methods) and refuse the rest with gRPC `UNAVAILABLE` or
+//! HTTP 503, naming the leader in the `x-taskrun-leader` header, which
+//! workers follow to reconnect.
+//!
+//! The leader writes its tasks as a state snapshot to
+//! `<lease-file>.state.<term>` when they change. The term is a fencing
+//! token: a write goes ahead only while the writer's term is the highest and
+//! its lease has not expired, and a leader can only write its own term's
+//! file. Followers mirror the latest snapshot to serve reads, and a new
+//! leader loads it before starting its loops: runs that were active fail as
+//! `worker_lost` and their tasks go back to Pending.
+
+use std::future::{ready, Ready};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use axum::{
+    extract::{Request, State},
+    http::{self, HeaderValue, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use futures_util::future::Either;
+use serde::{Deserialize, Serialize};
+use tokio::sync::{broadcast, watch};
+use tokio::task::JoinHandle;
+use tonic::metadata::MetadataValue;
+use tonic::Status;
+use tracing::{info, warn};
+
+use taskrun_core::WorkerId;
+
+use crate::control_plane::disconnect;
+use crate::control_plane::snapshot::{self, Snapshot};
+use crate::control_plane::state::{AppState, UiNotification};
+
+/// Header (and gRPC metadata key) naming the current leader.
+pub const LEADER_HEADER: &str = "x-taskrun-leader";
+
+/// Default lease TTL.
+pub const DEFAULT_LEASE_TTL: Duration = Duration::from_secs(15);
+
+/// HTTP paths served by followers for any method.
+const FOLLOWER_PATHS: &[&str] = &["/health", "/metrics"];
+
+/// Prefixes of the gRPC methods followers serve.
+const FOLLOWER_METHOD_PREFIXES: &[&str] = &["Get", "List", "Search", "Watch"];
+
+/// How often the leader writes changed tasks to the shared state.
+const STATE_SYNC_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Digits of the term in lease and state file names.
+const TERM_DIGITS: usize = 20;
+
+/// This instance's view of who leads.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LeaderState {
+    pub is_leader: bool,
+    /// Advertised address of the leader, if known.
+    pub leader_addr: Option<String>,
+}
+/// Whether this instance leads. Without HA it always does.
+#[derive(Debug)]
+pub struct Leadership {
+    tx: watch::Sender<LeaderState>,
+}
+
+impl Default for Leadership {
+    fn default() -> Self {
+        let (tx, _) = watch::channel(LeaderState {
+            is_leader: true,
+            leader_addr: None,
+        });
+        Self { tx }
+    }
+}
+
+impl Leadership {
+    pub fn is_leader(&self) -> bool {
+        self.tx.borrow().is_leader
+    }
+
+    pub fn current(&self) -> LeaderState {
+        self.tx.borrow().clone()
+    }
+
+    pub fn subscribe(&self) -> watch::Receiver<LeaderState> {
+        self.tx.subscribe()
+    }
+
+    fn set(&self, state: LeaderState) {
+        self.tx.send_if_modified(|current| {
+            if *current == state {
+                return false;
+            }
+            match (current.is_leader, state.is_leader) {
+                (false, true) => info!("This instance is now the leader"),
+                (true, false) => {
+                    warn!(leader = ?state.leader_addr, "This instance is no longer the leader")
+                }
+                _ => info!(leader = ?state.leader_addr, "Leader changed"),
+            }
+            *current = state;
+            true
+        });
+    }
+
+    /// The error returned to gRPC callers by a follower, or None when leading.
+    pub fn not_leader_status(&self) -> Option<Status> {
+        let state = self.current();
+        if state.is_leader {
+            return None;
+        }
+        let mut status = match &state.leader_addr {
+            Some(addr) => Status::unavailable(format!("not the leader; the leader is {}", addr)),
+            None => Status::unavailable("not the leader; no leader is elected"),
+        };
+        if let Some(value) = state
+            .leader_addr
+            .and_then(|addr| MetadataValue::try_from(addr).ok())
+        {
+            status.metadata_mut().insert(LEADER_HEADER, value);
+        }
+        Some(status)
+    }
+}
+
+/// Lease election options.
+#[derive(Debug, Clone)]
+pub struct LeaseConfig {
+    /// Lease file name, on storage shared by all instances. Term and state
+    /// files are created next to it.
+    pub path: PathBuf,
+    /// gRPC address workers use to reach this instance. Identifies the
+    /// holder, so it must differ between instances.
+    pub advertise_addr: String,
+    /// How long a lease lasts without renewal.
+    pub ttl: Duration,
+}
+
+/// Contents of a term's lease file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct Lease {
+    holder: String,
+    expires_at_ms: i64,
+    term: u64,
+}
+
+/// Takes and renews the lease, updating `AppState::leadership`, and keeps
+/// the shared state in step with it.
+pub struct LeaseElector {
+    state: Arc<AppState>,
+    config: LeaseConfig,
+    /// The lease this instance holds, while it leads.
+    held: Option<Lease>,
+    /// Term and export time of the state snapshot last mirrored.
+    mirrored: Option<(u64, i64)>,
+}
+
+impl LeaseElector {
+    /// Create an elector. The instance follows until it holds the lease.
+    pub fn new(state: Arc<AppState>, config: LeaseConfig) -> Self {
+        state.leadership.set(LeaderState {
+            is_leader: false,
+            leader_addr: None,
+        });
+        Self {
+            state,
+            config,
+            held: None,
+            mirrored: None,
+        }
+    }
+
+    pub async fn run(mut self) {
+        let mut lease_interval = tokio::time::interval(self.config.ttl / 3);
+        let mut sync_interval = tokio::time::interval(STATE_SYNC_INTERVAL);
+        let mut notifications = self.state.subscribe_ui();
+        let mut changed = false;
+        loop {
+            tokio::select! {
+                _ = lease_interval.tick() => {
+                    self.elect().await;
+                    changed = false;
+                }
+                _ = sync_interval.tick() => {
+                    if changed && self.held.is_some() {
+                        self.save_state().await;
+                        changed = false;
+                    }
+                }
+                task_changed = next_task_change(&mut notifications) => changed |= task_changed,
+            }
+        }
+    }
+
+    /// Take or renew the lease, then write the state if leading or mirror
+    /// it if following.
+    async fn elect(&mut self) {
+        let now = now_ms();
+        match self.try_acquire(now).await {
+            Ok(lease) if lease.holder == self.config.advertise_addr => {
+                let took_over = self.held.as_ref().map_or(true, |h| h.term != lease.term);
+                self.held = Some(lease);
+                if took_over {
+                    if let Err(e) = self.take_over_state().await {
+                        // Leading without the shared tasks would lose them;
+                        // the lease stays ours, so try again next time
+                        warn!(error = %e, "Failed to load the shared state");
+                        self.held = None;
+                        self.set_follower(None);
+                        return;
+                    }
+                }
+                self.state.leadership.set(LeaderState {
+                    is_leader: true,
+                    leader_addr: Some(self.config.advertise_addr.clone()),
+                });
+                self.save_state().await;
+            }
+            Ok(lease) => {
+                self.held = None;
+                self.set_follower(Some(lease.holder));
+                self.mirror_state().await;
+            }
+            Err(e) => {
+                warn!(path = %self.config.path.display(), error = %e, "Failed to access leader lease");
+                // Keep leading on what we hold; nobody else can take it sooner
+                if self.held.as_ref().is_some_and(|h| now >= h.expires_at_ms) {
+                    self.held = None;
+                }
+                if self.held.is_none() {
+                    self.set_follower(None);
+                }
+            }
+        }
+    }
+
+    fn set_follower(&self, leader_addr: Option<String>) {
+        self.state.leadership.set(LeaderState {
+            is_leader: false,
+            leader_addr,
+        });
+    }
+
+    /// Take or renew the lease if it is free or ours. Returns the lease in
+    /// force afterwards.
+    async fn try_acquire(&self, now_ms: i64) -> io::Result<Lease> {
+        let ttl_ms = self.config.ttl.as_millis() as i64;
+        let term = self.highest_term(".").await?;
+        if let Some(term) = term {
+            if let Some(lease) = self.read_lease(term).await? {
+                if lease.expires_at_ms > now_ms {
+                    if lease.holder != self.config.advertise_addr {
+                        return Ok(lease);
+                    }
+                    let renewed = Lease {
+                        expires_at_ms: now_ms + ttl_ms,
+                        ..lease
+                    };
+                    write_atomically(&self.term_path(term), &serde_json::to_vec(&renewed)?).await?;
+                    // A renewal that came too late loses to a newer term
+                    if self.highest_term(".").await? == Some(term) {
+                        return Ok(renewed);
+                    }
+                    return self.current_lease().await;
+                }
+            }
+        }
+
+        let lease = Lease {
+            holder: self.config.advertise_addr.clone(),
+            expires_at_ms: now_ms + ttl_ms,
+            term: term.unwrap_or(0) + 1,
+        };
+        if self.claim(&lease).await? {
+            self.prune(".", lease.term).await;
+            return Ok(lease);
+        }
+        // Another instance claimed the term first
+        self.current_lease().await
+    }
+
+    /// Create the file of `lease.term`, unless it exists. The hard link is
+    /// what makes the takeover atomic: of the instances linking the same
+    /// term, only one succeeds.
+    async fn claim(&self, lease: &Lease) -> io::Result<bool> {
+        let tmp = self.sibling(&format!(".{:016x}.tmp", rand::random::<u64>()));
+        tokio::fs::write(&tmp, serde_json::to_vec(lease)?).await?;
+        let linked = tokio::fs::hard_link(&tmp, self.term_path(lease.term)).await;
+        let _ = tokio::fs::remove_file(&tmp).await;
+        match linked {
+            Ok(()) => Ok(true),
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    async fn current_lease(&self) -> io::Result<Lease> {
+        if let Some(term) = self.highest_term(".").await? {
+            if let Some(lease) = self.read_lease(term).await? {
+                return Ok(lease);
+            }
+        }
+        Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            "no readable leader lease",
+        ))
+    }
+
+    async fn read_lease(&self, term: u64) -> io::Result<Option<Lease>> {
+        let path = self.term_path(term);
+        let bytes = match tokio::fs::read(&path).await {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        match serde_json::from_slice(&bytes) {
+            Ok(lease) => Ok(Some(lease)),
+            Err(e) => {
+                warn!(path = %path.display(), error = %e, "Ignoring corrupt leader lease");
+                Ok(None)
+            }
+        }
+    }
+
+    /// Write the tasks to the shared state, unless this instance no longer
+    /// holds the lease, in which case it steps down.
+    async fn save_state(&mut self) {
+        let Some(lease) = self.held.clone() else {
+            return;
+        };
+        let bytes = match snapshot::export(&self.state)
+            .await
+            .and_then(|s| snapshot::encode(&s))
+        {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                warn!(error = %e, "Failed to snapshot the shared state");
+                return;
+            }
+        };
+        match self.write_state(&lease, &bytes, now_ms()).await {
+            Ok(true) => {}
+            Ok(false) => {
+                warn!(
+                    term = lease.term,
+                    "Leader lease lost; not writing the shared state"
+                );
+                self.held = None;
+                self.set_follower(None);
+            }
+            Err(e) => warn!(error = %e, "Failed to write the shared state"),
+        }
+    }
+
+    /// Write `bytes` as the state of `lease`'s term, fenced by the term:
+    /// returns false without writing if the lease has expired or a newer
+    /// term exists.
+    async fn write_state(&self, lease: &Lease, bytes: &[u8], now_ms: i64) -> io::Result<bool> {
+        if now_ms >= lease.expires_at_ms || self.highest_term(".").await? != Some(lease.term) {
+            return Ok(false);
+        }
+        write_atomically(&self.state_path(lease.term), bytes).await?;
+        self.prune(".state.", lease.term).await;
+        Ok(true)
+    }
+
+    /// The latest shared state, with its term.
+    async fn latest_state(&self) -> io::Result<Option<(u64, Snapshot)>> {
+        let Some(term) = self.highest_term(".state.").await? else {
+            return Ok(None);
+        };
+        let bytes = match tokio::fs::read(self.state_path(term)).await {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        let snapshot = snapshot::decode(&bytes)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+        Ok(Some((term, snapshot)))
+    }
+
+    /// Load the shared state on becoming leader.
+    async fn take_over_state(&mut self) -> io::Result<()> {
+        let Some((term, snapshot)) = self.latest_state().await? else {
+            return Ok(());
+        };
+        self.mirrored = Some((term, snapshot.exported_at_ms));
+        let tasks = snapshot.tasks.len();
+        let interrupted = snapshot::replace(&self.state, snapshot, true)
+            .await
+            .map_err(|e| io::Error::other(e.to_string()))?;
+        info!(
+            term,
+            tasks,
+            interrupted_runs = interrupted,
+            "Took over the shared state"
+        );
+        Ok(())
+    }
+
+    /// Replace the tasks with the shared state if it changed.
+    async fn mirror_state(&mut self) {
+        let (term, snapshot) = match self.latest_state().await {
+            Ok(Some(latest)) => latest,
+            Ok(None) => return,
+            Err(e) => {
+                warn!(error = %e, "Failed to read the shared state");
+                return;
+            }
+        };
+        let version = (term, snapshot.exported_at_ms);
+        if self.mirrored == Some(version) {
+            return;
+        }
+        match snapshot::replace(&self.state, snapshot, false).await {
+            Ok(_) => self.mirrored = Some(version),
+            Err(e) => warn!(error = %e, "Failed to mirror the shared state"),
+        }
+    }
+
+    /// `<lease-file><suffix>`.
+    fn sibling(&self, suffix: &str) -> PathBuf {
+        let mut name = self
+            .config
+            .path
+            .file_name()
+            .unwrap_or_default()
+            .to_os_string();
+        name.push(suffix);
+        self.config.path.with_file_name(name)
+    }
+
+    fn term_path(&self, term: u64) -> PathBuf {
+        self.sibling(&format!(".{:0width$}", term, width = TERM_DIGITS))
+    }
+
+    fn state_path(&self, term: u64) -> PathBuf {
+        self.sibling(&format!(".state.{:0width$}", term, width = TERM_DIGITS))
+    }
+
+    /// Terms of the files named `<lease-file><prefix><term>`.
+    async fn terms(&self, prefix: &str) -> io::Result<Vec<u64>> {
+        let dir = match self.config.path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new("."),
+        };
+        let name = format!(
+            "{}{}",
+            self.config
+                .path
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy(),
+            prefix
+        );
+        let mut terms = Vec::new();
+        let mut entries = tokio::fs::read_dir(dir).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let file_name = entry.file_name();
+            let Some(digits) = file_name.to_str().and_then(|n| n.strip_prefix(&name)) else {
+                continue;
+            };
+            if digits.len() == TERM_DIGITS && digits.bytes().all(|b| b.is_ascii_digit()) {
+                terms.extend(digits.parse::<u64>().ok());
+            }
+        }
+        Ok(terms)
+    }
+
+    async fn highest_term(&self, prefix: &str) -> io::Result<Option<u64>> {
+        Ok(self.terms(prefix).await?.into_iter().max())
+    }
+
+    /// Remove the files of terms before `term`.
+    async fn prune(&self, prefix: &str, term: u64) {
+        let Ok(terms) = self.terms(prefix).await else {
+            return;
+        };
+        for old in terms.into_iter().filter(|t| *t < term) {
+            let path = self.sibling(&format!("{}{:0width$}", prefix, old, width = TERM_DIGITS));
+            let _ = tokio::fs::remove_file(path).await;
+        }
+    }
+}
+
+/// Replace `path` with `bytes` so readers never see a partial file.
+async fn write_atomically(path: &Path, bytes: &[u8]) -> io::Result<()> {
+    let mut tmp = path.as_os_str().to_os_string();
+    tmp.push(format!(".{:016x}.tmp", rand::random::<u64>()));
+    tokio::fs::write(&tmp, bytes).await?;
+    if let Err(e) = tokio::fs::rename(&tmp, path).await {
+        let _ = tokio::fs::remove_file(&tmp).await;
+        return Err(e);
+    }
+    Ok(())
+}
+
+/// Wait for the next notification, returning whether it changed tasks.
+async fn next_task_change(notifications: &mut Option<broadcast::Receiver<UiNotification>>) -> bool {
+    let Some(receiver) = notifications else {
+        return std::future::pending().await;
+    };
+    match receiver.recv().await {
+        Ok(notification) => matches!(
+            notification,
+            UiNotification::TaskCreated { .. }
+                | UiNotification::TaskStatusChanged { .. }
+                | UiNotification::TaskExpired { .. }
+                | UiNotification::RunStatusChanged { .. }
+                | UiNotification::RunEvent { .. }
+                | UiNotification::RunMilestone { .. }
+                | UiNotification::RunResult { .. }
+        ),
+        Err(broadcast::error::RecvError::Lagged(_)) => true,
+        Err(broadcast::error::RecvError::Closed) => {
+            *notifications = None;
+            false
+        }
+    }
+}
+
+/// Run the loops `start` spawns while this instance leads.
+///
+/// They are aborted when it stops leading, and connected workers are sent
+/// away so they reconnect to the new leader.
+pub async fn while_leader<F>(state: Arc<AppState>, mut start: F)
+where
+    F: FnMut() -> Vec<JoinHandle<()>>,
+{
+    let mut leadership = state.leadership.subscribe();
+    loop {
+        if leadership.wait_for(|s| s.is_leader).await.is_err() {
+            return;
+        }
+        let handles = start();
+
+        if leadership.wait_for(|s| !s.is_leader).await.is_err() {
+            return;
+        }
+        for handle in handles {
+            handle.abort();
+        }
+        let worker_ids: Vec<WorkerId> = state.workers.read().await.keys().cloned().collect();
+        for worker_id in worker_ids {
+            disconnect::disconnect_worker(
+                &state,
+                &worker_id,
+                "control plane is no longer the leader",
+                false,
+            )
+            .await;
+        }
+    }
+}
+
+/// Whether followers serve the gRPC method at `path` (`/package.Service/Method`).
+fn is_follower_method(path: &str) -> bool {
+    let method = path.rsplit('/').next().unwrap_or_default();
+    FOLLOWER_METHOD_PREFIXES
+        .iter()
+        .any(|prefix| method.starts_with(prefix))
+}
+
+/// Tower layer refusing gRPC calls on followers, except reads.
+#[derive(Clone)]
+pub struct RequireLeaderLayer {
+    state: Arc<AppState>,
+}
+
+impl RequireLeaderLayer {
+    pub fn new(state: Arc<AppState>) -> Self {
+        Self { state }
+    }
+}
+
+impl<S> tower::Layer<S> for RequireLeaderLayer {
+    type Service = RequireLeader<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RequireLeader {
+            inner,
+            state: self.state.clone(),
+        }
+    }
+}
+
+/// Service of `RequireLeaderLayer`.
+#[derive(Clone)]
+pub struct RequireLeader<S> {
+    inner: S,
+    state: Arc<AppState>,
+}
+
+impl<S, B> tower::Service<http::Request<B>> for RequireLeader<S>
+where
+    S: tower::Service<http::Request<B>, Response = http::Response<tonic::body::BoxBody>>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Either<S::Future, Ready<Result<S::Response, S::Error>>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: http::Request<B>) -> Self::Future {
+        if !is_follower_method(request.uri().path()) {
+            if let Some(status) = self.state.leadership.not_leader_status() {
+                return Either::Right(ready(Ok(status.into_http())));
+            }
+        }
+        Either::Left(self.inner.call(request))
+    }
+}
+
+/// HTTP middleware refusing requests on followers, except reads (`GET` and
+/// `HEAD`), health and metrics.
+pub async fn require_leader_http(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let leader = state.leadership.current();
+    if leader.is_leader
+        || matches!(*request.method(), Method::GET | Method::HEAD)
+        || FOLLOWER_PATHS.contains(&request.uri().path())
+    {
+        return next.run(request).await;
+    }
+
+    let mut response = (
+        StatusCode::SERVICE_UNAVAILABLE,
+        Json(serde_json::json!({
+            "error": "not the leader",
+            "leader": leader.leader_addr,
+        })),
+    )
+        .into_response();
+    if let Some(value) = leader
+        .leader_addr
+        .and_then(|addr| HeaderValue::try_from(addr).ok())
+    {
+        response.headers_mut().insert(LEADER_HEADER, value);
+    }
+    response
+}
+
+fn now_ms() -> i64 {
+    chrono::Utc::now().timestamp_millis()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, middleware, routing::get, Router};
+    use taskrun_core::{RunSummary, Task, TaskStatus};
+    use tower::ServiceExt;
+
+    fn lease_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("taskrun-lease-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn elector(state: &Arc<AppState>, dir: &Path, addr: &str) -> LeaseElector {
+        LeaseElector::new(
+            state.clone(),
+            LeaseConfig {
+                path: dir.join("taskrun.lease"),
+                advertise_addr: addr.to_string(),
+                ttl: Duration::from_secs(10),
+            },
+        )
+    }
+
+    #[tokio::test]
+    async fn test_lease_is_taken_over_once_expired() {
+        let dir = lease_dir("takeover");
+        let a = elector(&AppState::new(), &dir, "https://cp-a:50051");
+        let b = elector(&AppState::new(), &dir, "https://cp-b:50051");
+
+        let lease = a.try_acquire(1_000).await.unwrap();
+        assert_eq!(lease.holder, "https://cp-a:50051");
+        assert_eq!(lease.term, 1);
+        assert_eq!(b.try_acquire(2_000).await.unwrap(), lease);
+        // The holder renews
+        assert_eq!(a.try_acquire(5_000).await.unwrap().expires_at_ms, 15_000);
+
+        let lease = b.try_acquire(15_000).await.unwrap();
+        assert_eq!(lease.holder, "https://cp-b:50051");
+        assert_eq!(lease.term, 2);
+        // The old holder follows the new term
+        assert_eq!(a.try_acquire(16_000).await.unwrap(), lease);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_only_one_instance_claims_a_term() {
+        let dir = lease_dir("claim");
+        let a = elector(&AppState::new(), &dir, "https://cp-a:50051");
+        let b = elector(&AppState::new(), &dir, "https://cp-b:50051");
+
+        let (lease_a, lease_b) = tokio::join!(a.try_acquire(1_000), b.try_acquire(1_000));
+        assert_eq!(lease_a.unwrap(), lease_b.unwrap());
+
+        let lease = |holder: &str| Lease {
+            holder: holder.to_string(),
+            expires_at_ms: 30_000,
+            term: 7,
+        };
+        assert!(a.claim(&lease("https://cp-a:50051")).await.unwrap());
+        assert!(!b.claim(&lease("https://cp-b:50051")).await.unwrap());
+        assert_eq!(
+            b.read_lease(7).await.unwrap().unwrap().holder,
+            "https://cp-a:50051"
+        );
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_new_leader_takes_over_tasks_and_fences_the_old_one() {
+        let dir = lease_dir("state");
+        let state_a = AppState::new();
+        let mut task = Task::new("general", "{}", "test");
+        let mut run = RunSummary::new(WorkerId::generate());
+        run.start();
+        task.add_run(run);
+        task.status = TaskStatus::Running;
+        let task_id = task.id.clone();
+        state_a.tasks.write().await.insert(task_id.clone(), task);
+
+        let mut a = elector(&state_a, &dir, "https://cp-a:50051");
+        a.held = Some(a.try_acquire(now_ms()).await.unwrap());
+        a.save_state().await;
+        assert!(a.held.is_some());
+
+        // A follower mirrors the leader's tasks as they are
+        let state_b = AppState::new();
+        let mut b = elector(&state_b, &dir, "https://cp-b:50051");
+        b.mirror_state().await;
+        assert_eq!(
+            state_b.tasks.read().await[&task_id].status,
+            TaskStatus::Running
+        );
+
+        // Once the lease expires, b takes over and the run is interrupted
+        let lease = b.try_acquire(now_ms() + 20_000).await.unwrap();
+        assert_eq!(lease.term, 2);
+        b.held = Some(lease);
+        b.take_over_state().await.unwrap();
+        assert_eq!(
+            state_b.tasks.read().await[&task_id].status,
+            TaskStatus::Pending
+        );
+
+        // The old leader's writes are refused, and it steps down
+        state_a.tasks.write().await.clear();
+        a.save_state().await;
+        assert!(a.held.is_none());
+        assert!(!state_a.leadership.is_leader());
+        let (_, snapshot) = b.latest_state().await.unwrap().unwrap();
+        assert_eq!(snapshot.tasks.len(), 1);
+        b.save_state().await;
+        let (term, snapshot) = b.latest_state().await.unwrap().unwrap();
+        assert_eq!(term, 2);
+        assert_eq!(snapshot.tasks.len(), 1);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_followers_name_the_leader() {
+        let leadership = Leadership::default();
+        assert!(leadership.not_leader_status().is_none());
+
+        leadership.set(LeaderState {
+            is_leader: false,
+            leader_addr: Some("https://cp-b:50051".to_string()),
+        });
+        let status = leadership.not_leader_status().unwrap();
+        assert_eq!(status.code(), tonic::Code::Unavailable);
+        assert_eq!(
+            status.metadata().get(LEADER_HEADER).unwrap(),
+            "https://cp-b:50051"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_followers_serve_reads_only() {
+        let state = AppState::new();
+        state.leadership.set(LeaderState {
+            is_leader: false,
+            leader_addr: Some("https://cp-b:50051".to_string()),
+        });
+        let router = Router::new()
+            .route(
+                "/v1/tasks",
+                get(|| async { "tasks" }).post(|| async { "created" }),
+            )
+            .layer(middleware::from_fn_with_state(state, require_leader_http));
+
+        let status = |method: &str| {
+            let request = Request::builder()
+                .method(method)
+                .uri("/v1/tasks")
+                .body(Body::empty())
+                .unwrap();
+            let router = router.clone();
+            async move { router.oneshot(request).await.unwrap().status() }
+        };
+        assert_eq!(status("GET").await, StatusCode::OK);
+        assert_eq!(status("POST").await, StatusCode::SERVICE_UNAVAILABLE);
+
+        assert!(is_follower_method("/taskrun.v1.TaskService/ListTasks"));
+        assert!(is_follower_method("/taskrun.v1.TaskService/WatchTasks"));
+        assert!(!is_follower_method("/taskrun.v1.TaskService/CreateTask"));
+        assert!(!is_follower_method("/taskrun.v1.RunService/StreamConnect"));
+    }
+}
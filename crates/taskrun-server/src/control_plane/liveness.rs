@@ -0,0 +1,169 @@
+//! Worker liveness tracking based on heartbeats.
+//!
+//! A worker whose stream hangs without closing (network partition, frozen
+//! process) would otherwise stay schedulable forever. The monitor compares each
+//! worker's `last_heartbeat` against two thresholds:
+//!
+//! - older than `degraded_after`: status becomes Degraded
+//! - older than `offline_after`: status becomes Offline
+//!
+//! Neither status accepts runs, so both exclude the worker from scheduling. The
+//! next heartbeat restores the status the worker reports.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use tracing::{info, warn};
+
+use taskrun_core::{WorkerId, WorkerStatus};
+
+use crate::control_plane::state::{AppState, UiNotification};
+
+/// Liveness monitor configuration.
+#[derive(Debug, Clone)]
+pub struct LivenessConfig {
+    /// How often to check worker heartbeats.
+    pub interval: Duration,
+
+    /// Heartbeat age after which a worker is marked Degraded.
+    pub degraded_after: Duration,
+
+    /// Heartbeat age after which a worker is marked Offline.
+    pub offline_after: Duration,
+}
+
+impl Default for LivenessConfig {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(5),
+            degraded_after: Duration::from_secs(30),
+            offline_after: Duration::from_secs(45),
+        }
+    }
+}
+
+/// Periodically downgrades workers that stopped heartbeating.
+pub struct LivenessMonitor {
+    state: Arc<AppState>,
+    config: LivenessConfig,
+}
+
+impl LivenessMonitor {
+    /// Create a new LivenessMonitor.
+    pub fn new(state: Arc<AppState>, config: LivenessConfig) -> Self {
+        Self { state, config }
+    }
+
+    /// Run the monitor loop forever.
+    pub async fn run(self) {
+        info!(
+            degraded_after_secs = self.config.degraded_after.as_secs(),
+            offline_after_secs = self.config.offline_after.as_secs(),
+            "Worker liveness monitor started"
+        );
+
+        let mut interval = tokio::time::interval(self.config.interval);
+        loop {
+            interval.tick().await;
+            self.check(Utc::now()).await;
+        }
+    }
+
+    /// Check all workers once, returning the ones whose status changed.
+    pub async fn check(&self, now: DateTime<Utc>) -> Vec<(WorkerId, WorkerStatus)> {
+        let degraded_after =
+            chrono::Duration::from_std(self.config.degraded_after).unwrap_or(chrono::Duration::MAX);
+        let offline_after =
+            chrono::Duration::from_std(self.config.offline_after).unwrap_or(chrono::Duration::MAX);
+
+        let mut changed = Vec::new();
+        {
+            let mut workers = self.state.workers.write().await;
+            for (worker_id, worker) in workers.iter_mut() {
+                let age = now.signed_duration_since(worker.last_heartbeat);
+                let new_status = if age > offline_after {
+                    WorkerStatus::Offline
+                } else if age > degraded_after {
+                    WorkerStatus::Degraded
+                } else {
+                    continue;
+                };
+
+                if worker.status != new_status {
+                    warn!(
+                        worker_id = %worker_id,
+                        status = ?new_status,
+                        last_heartbeat_secs = age.num_seconds(),
+                        "Worker heartbeat is stale"
+                    );
+                    worker.status = new_status;
+                    changed.push((worker_id.clone(), new_status));
+                }
+            }
+        }
+
+        for (worker_id, status) in &changed {
+            self.state.notify_ui(UiNotification::WorkerStatusChanged {
+                worker_id: worker_id.clone(),
+                status: *status,
+            });
+        }
+
+        changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use taskrun_core::WorkerInfo;
+    use tokio::sync::mpsc;
+
+    use crate::control_plane::state::ConnectedWorker;
+
+    #[tokio::test]
+    async fn test_stale_worker_degrades_then_goes_offline() {
+        let state = AppState::new();
+        let worker_id = WorkerId::new("worker-1");
+        let (tx, _rx) = mpsc::channel(1);
+        let heartbeat = Utc::now();
+        state.workers.write().await.insert(
+            worker_id.clone(),
+            ConnectedWorker {
+                info: WorkerInfo::new(worker_id.clone(), "host"),
+                status: WorkerStatus::Idle,
+                active_runs: 0,
+                max_concurrent_runs: 10,
+                interactive_slots: 0,
+                last_heartbeat: heartbeat,
+                draining_since: None,
+                tx,
+                shutdown: tokio_util::sync::CancellationToken::new(),
+            },
+        );
+
+        let monitor = LivenessMonitor::new(state.clone(), LivenessConfig::default());
+
+        assert!(monitor.check(heartbeat).await.is_empty());
+
+        let changed = monitor
+            .check(heartbeat + chrono::Duration::seconds(31))
+            .await;
+        assert_eq!(changed, vec![(worker_id.clone(), WorkerStatus::Degraded)]);
+
+        // No repeated notification while the status is unchanged
+        assert!(monitor
+            .check(heartbeat + chrono::Duration::seconds(32))
+            .await
+            .is_empty());
+
+        let changed = monitor
+            .check(heartbeat + chrono::Duration::seconds(46))
+            .await;
+        assert_eq!(changed, vec![(worker_id.clone(), WorkerStatus::Offline)]);
+        assert!(!state.workers.read().await[&worker_id]
+            .status
+            .can_accept_runs());
+    }
+}
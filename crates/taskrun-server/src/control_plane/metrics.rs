@@ -1,20 +1,125 @@
 //! Prometheus metrics collection and formatting.
 //!
-//! This module provides metrics in Prometheus text exposition format.
+//! This module provides metrics in Prometheus text exposition format. Most
+//! series are derived from the state at scrape time; scheduling latency is
+//! observed as tasks are assigned and kept in a `Histogram`.
 
+use std::collections::{BTreeMap, HashMap};
 use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 
-use taskrun_core::{TaskStatus, WorkerStatus};
+use chrono::Utc;
+use taskrun_core::{FailureReason, RunStatus, TaskStatus, WorkerStatus};
 
+use crate::control_plane::queue;
+use crate::control_plane::quotas::QuotaResource;
 use crate::control_plane::state::AppState;
 
+/// Bucket bounds (seconds) of the run duration histogram.
+const RUN_DURATION_BUCKETS: &[f64] = &[
+    5.0, 15.0, 30.0, 60.0, 120.0, 300.0, 600.0, 1800.0, 3600.0, 7200.0,
+];
+
+/// Bucket bounds (seconds) of the scheduling latency histogram.
+pub const SCHEDULING_LATENCY_BUCKETS: &[f64] =
+    &[0.1, 0.5, 1.0, 5.0, 15.0, 30.0, 60.0, 300.0, 900.0, 3600.0];
+
+/// A Prometheus histogram of durations, safe to observe concurrently.
+#[derive(Debug)]
+pub struct Histogram {
+    bounds: &'static [f64],
+    /// Observations per bucket (not cumulative); the last one is `+Inf`.
+    buckets: Vec<AtomicU64>,
+    sum_micros: AtomicU64,
+}
+
+impl Histogram {
+    pub fn new(bounds: &'static [f64]) -> Self {
+        Self {
+            bounds,
+            buckets: (0..=bounds.len()).map(|_| AtomicU64::new(0)).collect(),
+            sum_micros: AtomicU64::new(0),
+        }
+    }
+
+    /// Record one duration.
+    pub fn observe(&self, duration: Duration) {
+        let secs = duration.as_secs_f64();
+        let bucket = self
+            .bounds
+            .iter()
+            .position(|bound| secs <= *bound)
+            .unwrap_or(self.bounds.len());
+        self.buckets[bucket].fetch_add(1, Ordering::Relaxed);
+        self.sum_micros
+            .fetch_add(duration.as_micros() as u64, Ordering::Relaxed);
+    }
+
+    fn write(&self, output: &mut String, name: &str) {
+        let counts: Vec<u64> = self
+            .buckets
+            .iter()
+            .map(|b| b.load(Ordering::Relaxed))
+            .collect();
+        let sum = self.sum_micros.load(Ordering::Relaxed) as f64 / 1_000_000.0;
+        write_histogram(output, name, "", self.bounds, &counts, sum);
+    }
+}
+
+/// Write the `_bucket`, `_sum` and `_count` series of a histogram from
+/// per-bucket counts (`bounds.len() + 1` of them, the last being `+Inf`).
+fn write_histogram(
+    output: &mut String,
+    name: &str,
+    labels: &str,
+    bounds: &[f64],
+    counts: &[u64],
+    sum: f64,
+) {
+    let sep = if labels.is_empty() { "" } else { "," };
+    let mut cumulative = 0;
+    for (i, count) in counts.iter().enumerate() {
+        cumulative += count;
+        let le = bounds
+            .get(i)
+            .map_or_else(|| "+Inf".to_string(), |b| b.to_string());
+        writeln!(
+            output,
+            "{name}_bucket{{{labels}{sep}le=\"{le}\"}} {cumulative}"
+        )
+        .ok();
+    }
+    let braces = if labels.is_empty() {
+        String::new()
+    } else {
+        format!("{{{labels}}}")
+    };
+    writeln!(output, "{name}_sum{braces} {sum}").ok();
+    writeln!(output, "{name}_count{braces} {cumulative}").ok();
+}
+
+/// Escape a label value.
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
 /// Collect all metrics from AppState and format as Prometheus text.
 pub async fn collect_metrics(state: &Arc<AppState>) -> String {
     let mut output = String::new();
 
     collect_worker_metrics(state, &mut output).await;
     collect_task_metrics(state, &mut output).await;
+    collect_run_metrics(state, &mut output).await;
+    collect_queue_metrics(state, &mut output).await;
+    collect_stream_metrics(state, &mut output).await;
+    collect_retention_metrics(state, &mut output);
+    collect_quota_metrics(state, &mut output).await;
+    collect_http_metrics(state, &mut output);
 
     output
 }
@@ -28,6 +133,8 @@ async fn collect_worker_metrics(state: &Arc<AppState>, output: &mut String) {
     let mut busy = 0u64;
     let mut draining = 0u64;
     let mut error = 0u64;
+    let mut degraded = 0u64;
+    let mut offline = 0u64;
 
     for worker in workers.values() {
         match worker.status {
@@ -35,6 +142,8 @@ async fn collect_worker_metrics(state: &Arc<AppState>, output: &mut String) {
             WorkerStatus::Busy => busy += 1,
             WorkerStatus::Draining => draining += 1,
             WorkerStatus::Error => error += 1,
+            WorkerStatus::Degraded => degraded += 1,
+            WorkerStatus::Offline => offline += 1,
         }
     }
 
@@ -65,6 +174,75 @@ async fn collect_worker_metrics(state: &Arc<AppState>, output: &mut String) {
         "taskrun_workers_connected{{status=\"error\"}} {error}"
     )
     .ok();
+    writeln!(
+        output,
+        "taskrun_workers_connected{{status=\"degraded\"}} {degraded}"
+    )
+    .ok();
+    writeln!(
+        output,
+        "taskrun_workers_connected{{status=\"offline\"}} {offline}"
+    )
+    .ok();
+
+    // Per-worker series, in a stable order
+    let mut workers: Vec<_> = workers.values().collect();
+    workers.sort_by(|a, b| a.info.worker_id.as_str().cmp(b.info.worker_id.as_str()));
+    let now = Utc::now();
+
+    writeln!(output).ok();
+    writeln!(
+        output,
+        "# HELP taskrun_worker_heartbeat_age_seconds Seconds since each worker's last heartbeat"
+    )
+    .ok();
+    writeln!(output, "# TYPE taskrun_worker_heartbeat_age_seconds gauge").ok();
+    for worker in &workers {
+        let age = (now - worker.last_heartbeat).num_milliseconds().max(0) as f64 / 1000.0;
+        writeln!(
+            output,
+            "taskrun_worker_heartbeat_age_seconds{{worker_id=\"{}\",hostname=\"{}\"}} {age}",
+            worker.info.worker_id,
+            escape(&worker.info.hostname)
+        )
+        .ok();
+    }
+
+    writeln!(output).ok();
+    writeln!(
+        output,
+        "# HELP taskrun_worker_active_runs Active runs on each worker"
+    )
+    .ok();
+    writeln!(output, "# TYPE taskrun_worker_active_runs gauge").ok();
+    for worker in &workers {
+        writeln!(
+            output,
+            "taskrun_worker_active_runs{{worker_id=\"{}\",hostname=\"{}\"}} {}",
+            worker.info.worker_id,
+            escape(&worker.info.hostname),
+            worker.active_runs
+        )
+        .ok();
+    }
+
+    writeln!(output).ok();
+    writeln!(
+        output,
+        "# HELP taskrun_worker_max_concurrent_runs Run slots of each worker"
+    )
+    .ok();
+    writeln!(output, "# TYPE taskrun_worker_max_concurrent_runs gauge").ok();
+    for worker in &workers {
+        writeln!(
+            output,
+            "taskrun_worker_max_concurrent_runs{{worker_id=\"{}\",hostname=\"{}\"}} {}",
+            worker.info.worker_id,
+            escape(&worker.info.hostname),
+            worker.max_concurrent_runs
+        )
+        .ok();
+    }
 }
 
 /// Collect task metrics by status.
@@ -117,6 +295,364 @@ async fn collect_task_metrics(state: &Arc<AppState>, output: &mut String) {
         "taskrun_tasks_total{{status=\"cancelled\"}} {cancelled}"
     )
     .ok();
+
+    // Count failed and cancelled runs by reason
+    let mut failures: HashMap<FailureReason, u64> = HashMap::new();
+    for reason in tasks
+        .values()
+        .flat_map(|task| &task.runs)
+        .filter_map(|run| run.failure_reason)
+    {
+        *failures.entry(reason).or_default() += 1;
+    }
+
+    writeln!(output).ok();
+    writeln!(
+        output,
+        "# HELP taskrun_run_failures_total Failed and cancelled runs by failure reason"
+    )
+    .ok();
+    writeln!(output, "# TYPE taskrun_run_failures_total gauge").ok();
+    for reason in FailureReason::ALL {
+        writeln!(
+            output,
+            "taskrun_run_failures_total{{reason=\"{}\"}} {}",
+            reason.as_str(),
+            failures.get(&reason).copied().unwrap_or(0)
+        )
+        .ok();
+    }
+}
+
+/// Collect run counts, durations and output size by agent.
+async fn collect_run_metrics(state: &Arc<AppState>, output: &mut String) {
+    // (agent, status) -> runs; agent -> finished durations; agent -> run IDs
+    let mut counts: BTreeMap<(String, &'static str), u64> = BTreeMap::new();
+    let mut durations: BTreeMap<String, Vec<f64>> = BTreeMap::new();
+    let mut run_ids: BTreeMap<String, Vec<taskrun_core::RunId>> = BTreeMap::new();
+    {
+        let tasks = state.tasks.read().await;
+        for task in tasks.values() {
+            for run in &task.runs {
+                *counts
+                    .entry((task.agent_name.clone(), run_status_label(run.status)))
+                    .or_default() += 1;
+                if let (Some(started), Some(finished)) = (run.started_at, run.finished_at) {
+                    let secs = (finished - started).num_milliseconds().max(0) as f64 / 1000.0;
+                    durations
+                        .entry(task.agent_name.clone())
+                        .or_default()
+                        .push(secs);
+                }
+                run_ids
+                    .entry(task.agent_name.clone())
+                    .or_default()
+                    .push(run.run_id.clone());
+            }
+        }
+    }
+
+    writeln!(output).ok();
+    writeln!(output, "# HELP taskrun_runs_total Runs by agent and status").ok();
+    writeln!(output, "# TYPE taskrun_runs_total gauge").ok();
+    for ((agent, status), count) in &counts {
+        writeln!(
+            output,
+            "taskrun_runs_total{{agent=\"{}\",status=\"{status}\"}} {count}",
+            escape(agent)
+        )
+        .ok();
+    }
+
+    writeln!(output).ok();
+    writeln!(
+        output,
+        "# HELP taskrun_run_duration_seconds Duration of finished runs by agent"
+    )
+    .ok();
+    writeln!(output, "# TYPE taskrun_run_duration_seconds histogram").ok();
+    for (agent, secs) in &durations {
+        let mut buckets = vec![0u64; RUN_DURATION_BUCKETS.len() + 1];
+        for duration in secs {
+            let bucket = RUN_DURATION_BUCKETS
+                .iter()
+                .position(|bound| duration <= bound)
+                .unwrap_or(RUN_DURATION_BUCKETS.len());
+            buckets[bucket] += 1;
+        }
+        write_histogram(
+            output,
+            "taskrun_run_duration_seconds",
+            &format!("agent=\"{}\"", escape(agent)),
+            RUN_DURATION_BUCKETS,
+            &buckets,
+            secs.iter().sum(),
+        );
+    }
+
+    writeln!(output).ok();
+    writeln!(
+        output,
+        "# HELP taskrun_output_bytes Stored run output by agent"
+    )
+    .ok();
+    writeln!(output, "# TYPE taskrun_output_bytes gauge").ok();
+    for (agent, ids) in &run_ids {
+        let mut bytes = 0;
+        for run_id in ids {
+            bytes += state.outputs.len(run_id).await.unwrap_or(0);
+        }
+        writeln!(
+            output,
+            "taskrun_output_bytes{{agent=\"{}\"}} {bytes}",
+            escape(agent)
+        )
+        .ok();
+    }
+}
+
+fn run_status_label(status: RunStatus) -> &'static str {
+    match status {
+        RunStatus::Pending => "pending",
+        RunStatus::Assigned => "assigned",
+        RunStatus::Running => "running",
+        RunStatus::Completed => "completed",
+        RunStatus::Failed => "failed",
+        RunStatus::Cancelled => "cancelled",
+    }
+}
+
+/// Collect queue depth by agent and scheduling latency.
+async fn collect_queue_metrics(state: &Arc<AppState>, output: &mut String) {
+    let mut depth: BTreeMap<String, u64> = BTreeMap::new();
+    {
+        let tasks = state.tasks.read().await;
+        for task in queue::queued_tasks(&tasks) {
+            *depth.entry(task.agent_name.clone()).or_default() += 1;
+        }
+    }
+
+    writeln!(output).ok();
+    writeln!(
+        output,
+        "# HELP taskrun_queue_depth Tasks waiting for a worker by agent"
+    )
+    .ok();
+    writeln!(output, "# TYPE taskrun_queue_depth gauge").ok();
+    for (agent, count) in &depth {
+        writeln!(
+            output,
+            "taskrun_queue_depth{{agent=\"{}\"}} {count}",
+            escape(agent)
+        )
+        .ok();
+    }
+
+    writeln!(output).ok();
+    writeln!(
+        output,
+        "# HELP taskrun_scheduling_latency_seconds Time tasks waited in Pending before a worker was assigned"
+    )
+    .ok();
+    writeln!(
+        output,
+        "# TYPE taskrun_scheduling_latency_seconds histogram"
+    )
+    .ok();
+    state
+        .scheduling_latency
+        .write(output, "taskrun_scheduling_latency_seconds");
+}
+
+/// Collect the number of open output stream channels.
+async fn collect_stream_metrics(state: &Arc<AppState>, output: &mut String) {
+    let channels = state.stream_channels.read().await.len();
+
+    writeln!(output).ok();
+    writeln!(
+        output,
+        "# HELP taskrun_stream_channels Open run output stream channels"
+    )
+    .ok();
+    writeln!(output, "# TYPE taskrun_stream_channels gauge").ok();
+    writeln!(output, "taskrun_stream_channels {channels}").ok();
+}
+
+/// Collect totals reclaimed by the run data compactor.
+fn collect_retention_metrics(state: &Arc<AppState>, output: &mut String) {
+    let stats = &state.retention;
+    let compactions = stats.compactions.load(Ordering::Relaxed);
+    let events = stats.events_removed.load(Ordering::Relaxed);
+    let chat_messages = stats.chat_messages_removed.load(Ordering::Relaxed);
+    let outputs = stats.outputs_removed.load(Ordering::Relaxed);
+    let chat_bytes = stats.chat_bytes_reclaimed.load(Ordering::Relaxed);
+    let output_bytes = stats.output_bytes_reclaimed.load(Ordering::Relaxed);
+
+    writeln!(output).ok();
+    writeln!(
+        output,
+        "# HELP taskrun_compactions_total Number of run data compaction passes"
+    )
+    .ok();
+    writeln!(output, "# TYPE taskrun_compactions_total counter").ok();
+    writeln!(output, "taskrun_compactions_total {compactions}").ok();
+
+    writeln!(output).ok();
+    writeln!(
+        output,
+        "# HELP taskrun_retention_removed_total Items dropped by retention by kind"
+    )
+    .ok();
+    writeln!(output, "# TYPE taskrun_retention_removed_total counter").ok();
+    writeln!(
+        output,
+        "taskrun_retention_removed_total{{kind=\"events\"}} {events}"
+    )
+    .ok();
+    writeln!(
+        output,
+        "taskrun_retention_removed_total{{kind=\"chat_messages\"}} {chat_messages}"
+    )
+    .ok();
+    writeln!(
+        output,
+        "taskrun_retention_removed_total{{kind=\"outputs\"}} {outputs}"
+    )
+    .ok();
+
+    writeln!(output).ok();
+    writeln!(
+        output,
+        "# HELP taskrun_retention_reclaimed_bytes_total Bytes reclaimed by retention by kind"
+    )
+    .ok();
+    writeln!(
+        output,
+        "# TYPE taskrun_retention_reclaimed_bytes_total counter"
+    )
+    .ok();
+    writeln!(
+        output,
+        "taskrun_retention_reclaimed_bytes_total{{kind=\"chat_messages\"}} {chat_bytes}"
+    )
+    .ok();
+    writeln!(
+        output,
+        "taskrun_retention_reclaimed_bytes_total{{kind=\"outputs\"}} {output_bytes}"
+    )
+    .ok();
+}
+
+/// Collect quota consumption, limits, and rejections.
+async fn collect_quota_metrics(state: &Arc<AppState>, output: &mut String) {
+    let usage = {
+        let tasks = state.tasks.read().await;
+        state.quotas.usage(&tasks).await
+    };
+
+    writeln!(output).ok();
+    writeln!(
+        output,
+        "# HELP taskrun_quota_usage Quota consumption by scope and resource"
+    )
+    .ok();
+    writeln!(output, "# TYPE taskrun_quota_usage gauge").ok();
+    for entry in &usage {
+        for resource in QuotaResource::ALL {
+            writeln!(
+                output,
+                "taskrun_quota_usage{{scope=\"{}\",name=\"{}\",resource=\"{}\"}} {}",
+                entry.scope.kind(),
+                entry.scope.name(),
+                resource,
+                entry.usage.get(resource)
+            )
+            .ok();
+        }
+    }
+
+    writeln!(output).ok();
+    writeln!(
+        output,
+        "# HELP taskrun_quota_limit Configured quota limit by scope and resource"
+    )
+    .ok();
+    writeln!(output, "# TYPE taskrun_quota_limit gauge").ok();
+    for entry in &usage {
+        for resource in QuotaResource::ALL {
+            if let Some(limit) = entry.limits.get(resource) {
+                writeln!(
+                    output,
+                    "taskrun_quota_limit{{scope=\"{}\",name=\"{}\",resource=\"{}\"}} {}",
+                    entry.scope.kind(),
+                    entry.scope.name(),
+                    resource,
+                    limit
+                )
+                .ok();
+            }
+        }
+    }
+
+    writeln!(output).ok();
+    writeln!(
+        output,
+        "# HELP taskrun_quota_rejected_total Requests refused by a quota by resource"
+    )
+    .ok();
+    writeln!(output, "# TYPE taskrun_quota_rejected_total counter").ok();
+    for resource in QuotaResource::ALL {
+        writeln!(
+            output,
+            "taskrun_quota_rejected_total{{resource=\"{}\"}} {}",
+            resource,
+            state.quotas.rejected(resource)
+        )
+        .ok();
+    }
+}
+
+/// Collect HTTP request totals by method, route and status.
+fn collect_http_metrics(state: &Arc<AppState>, output: &mut String) {
+    let routes = state.http.snapshot();
+
+    let families = [
+        (
+            "taskrun_http_requests_total",
+            "HTTP requests by method, route and status",
+        ),
+        (
+            "taskrun_http_request_duration_seconds_total",
+            "Time spent answering HTTP requests until the response head",
+        ),
+        (
+            "taskrun_http_request_bytes_total",
+            "HTTP request body bytes (when known)",
+        ),
+        (
+            "taskrun_http_response_bytes_total",
+            "HTTP response body bytes (when known)",
+        ),
+    ];
+    for (name, help) in families {
+        writeln!(output).ok();
+        writeln!(output, "# HELP {name} {help}").ok();
+        writeln!(output, "# TYPE {name} counter").ok();
+        for (key, stats) in &routes {
+            let value = match name {
+                "taskrun_http_requests_total" => stats.requests.to_string(),
+                "taskrun_http_request_duration_seconds_total" => stats.latency_secs.to_string(),
+                "taskrun_http_request_bytes_total" => stats.request_bytes.to_string(),
+                _ => stats.response_bytes.to_string(),
+            };
+            writeln!(
+                output,
+                "{name}{{method=\"{}\",route=\"{}\",status=\"{}\"}} {value}",
+                key.method, key.route, key.status
+            )
+            .ok();
+        }
+    }
 }
 
 #[cfg(test)]
@@ -139,5 +675,9 @@ mod tests {
         // All counts should be 0
         assert!(output.contains("taskrun_workers_connected{status=\"idle\"} 0"));
         assert!(output.contains("taskrun_tasks_total{status=\"pending\"} 0"));
+        assert!(output.contains("taskrun_retention_reclaimed_bytes_total{kind=\"outputs\"} 0"));
+        assert!(output.contains("taskrun_run_failures_total{reason=\"worker_lost\"} 0"));
+        assert!(output.contains("taskrun_quota_rejected_total{resource=\"tasks_per_day\"} 0"));
+        assert!(output.contains("# TYPE taskrun_http_requests_total counter"));
     }
 }
@@ -3,13 +3,52 @@
 //! This module provides the core control plane functionality for TaskRun,
 //! including gRPC services, scheduling, and state management.
 
+pub mod api_keys;
+pub mod approvals;
+pub mod assignments;
+pub mod audit;
+pub mod bulk;
+pub mod chaos;
+pub mod completions;
 pub mod config;
+pub mod conversations;
 pub mod crypto;
+pub mod debug_stats;
+pub mod disconnect;
+pub mod drain;
+pub mod enrollment;
+pub mod event_store;
+pub mod files;
+pub mod grafana;
+pub mod groups;
 pub mod http;
+pub mod leader;
+pub mod liveness;
 pub mod metrics;
+pub mod models;
+pub mod output_store;
+pub mod output_stream;
+pub mod queue;
+pub mod quotas;
+pub mod rbac;
+pub mod reaper;
+pub mod reload;
+pub mod retention;
 pub mod scheduler;
+pub mod schedules;
+pub mod search;
 pub mod service;
+pub mod snapshot;
 pub mod state;
+pub mod task_query;
+pub mod toggles;
+pub mod usage;
+pub mod validation;
+pub mod watch;
+pub mod workflows;
 
 pub use scheduler::Scheduler;
-pub use service::{RunServiceImpl, TaskServiceImpl, WorkerServiceImpl};
+pub use service::{
+    AdminServiceImpl, ApiKeyServiceImpl, ApprovalServiceImpl, RunServiceImpl, ScheduleServiceImpl,
+    TaskServiceImpl, WorkerServiceImpl, WorkflowServiceImpl,
+};
@@ -0,0 +1,198 @@
+//! Model listing for OpenAI-compatible clients.
+//!
+//! `GET /v1/models` lists the agents advertised by the connected workers of a
+//! namespace, in the shape of OpenAI's model list, so clients can discover
+//! what they may pass as `model` to `/v1/responses`. Each entry adds the
+//! model backends behind the agent, with their context window and
+//! modalities, and how many workers serve it.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::Serialize;
+
+use taskrun_core::ModelBackend;
+
+use crate::control_plane::state::{AppState, ConnectedWorker};
+
+/// A model backend behind an agent.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BackendInfo {
+    pub provider: String,
+    pub model_name: String,
+    pub context_window: u32,
+    pub supports_streaming: bool,
+    pub modalities: Vec<String>,
+}
+
+impl From<&ModelBackend> for BackendInfo {
+    fn from(backend: &ModelBackend) -> Self {
+        Self {
+            provider: backend.provider.clone(),
+            model_name: backend.model_name.clone(),
+            context_window: backend.context_window,
+            supports_streaming: backend.supports_streaming,
+            modalities: backend.modalities.clone(),
+        }
+    }
+}
+
+/// An agent callable as a model.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ModelInfo {
+    /// Agent name, the `model` of a `/v1/responses` request.
+    pub id: String,
+    pub object: &'static str,
+    /// Workers do not report when an agent appeared; always 0.
+    pub created: i64,
+    pub owned_by: &'static str,
+    pub description: String,
+    /// Largest context window of the agent's backends (0 = unknown).
+    pub context_window: u32,
+    /// Modalities supported by any of the agent's backends.
+    pub modalities: Vec<String>,
+    pub backends: Vec<BackendInfo>,
+    /// Connected workers advertising the agent.
+    pub workers: usize,
+    /// Those that can take a run of it now: not draining, degraded or
+    /// failing the agent's smoke test.
+    pub ready_workers: usize,
+}
+
+/// OpenAI-style model list.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ModelList {
+    pub object: &'static str,
+    pub data: Vec<ModelInfo>,
+}
+
+/// Models served by the connected workers of `namespace`, by name.
+pub async fn list(state: &AppState, namespace: &str) -> ModelList {
+    let workers = state.workers.read().await;
+    collect(workers.values().filter(|w| w.info.namespace == namespace))
+}
+
+fn collect<'a>(workers: impl Iterator<Item = &'a ConnectedWorker>) -> ModelList {
+    let mut models: BTreeMap<String, ModelInfo> = BTreeMap::new();
+    for worker in workers {
+        for agent in &worker.info.agents {
+            let model = models
+                .entry(agent.name.clone())
+                .or_insert_with(|| ModelInfo {
+                    id: agent.name.clone(),
+                    object: "model",
+                    created: 0,
+                    owned_by: "taskrun",
+                    description: agent.description.clone(),
+                    context_window: 0,
+                    modalities: Vec::new(),
+                    backends: Vec::new(),
+                    workers: 0,
+                    ready_workers: 0,
+                });
+            model.workers += 1;
+            if worker.status.can_accept_runs() && agent.is_ready() {
+                model.ready_workers += 1;
+            }
+            for backend in &agent.backends {
+                let backend = BackendInfo::from(backend);
+                if !model.backends.contains(&backend) {
+                    model.backends.push(backend);
+                }
+            }
+        }
+    }
+
+    let data = models
+        .into_values()
+        .map(|mut model| {
+            model.context_window = model
+                .backends
+                .iter()
+                .map(|b| b.context_window)
+                .max()
+                .unwrap_or(0);
+            model.modalities = model
+                .backends
+                .iter()
+                .flat_map(|b| b.modalities.iter().cloned())
+                .collect::<BTreeSet<_>>()
+                .into_iter()
+                .collect();
+            model
+        })
+        .collect();
+    ModelList {
+        object: "list",
+        data,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use taskrun_core::{AgentSpec, WorkerId, WorkerInfo, WorkerStatus};
+
+    fn worker(namespace: &str, agents: Vec<AgentSpec>) -> ConnectedWorker {
+        let mut info = WorkerInfo::new(WorkerId::generate(), "host").with_namespace(namespace);
+        info.agents = agents;
+        ConnectedWorker {
+            info,
+            status: WorkerStatus::Idle,
+            active_runs: 0,
+            max_concurrent_runs: 4,
+            interactive_slots: 0,
+            last_heartbeat: Utc::now(),
+            draining_since: None,
+            tx: tokio::sync::mpsc::channel(1).0,
+            shutdown: tokio_util::sync::CancellationToken::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_models_merge_agents_across_workers() {
+        let state = AppState::new();
+        let sonnet = ModelBackend::new("anthropic", "claude-sonnet").with_context_window(200_000);
+        let vision = ModelBackend::new("anthropic", "claude-opus")
+            .with_context_window(100_000)
+            .with_modalities(vec!["text".to_string(), "vision".to_string()]);
+        let mut draining = worker(
+            "default",
+            vec![AgentSpec::new("general").with_backend(sonnet.clone())],
+        );
+        draining.status = WorkerStatus::Draining;
+        let workers = [
+            worker(
+                "default",
+                vec![
+                    AgentSpec::new("general").with_backend(sonnet),
+                    AgentSpec::new("reviewer")
+                        .with_backend(vision)
+                        .with_degraded(Some("no answer within 60s".to_string())),
+                ],
+            ),
+            draining,
+            worker("team-a", vec![AgentSpec::new("support")]),
+        ];
+        for w in workers {
+            state
+                .workers
+                .write()
+                .await
+                .insert(w.info.worker_id.clone(), w);
+        }
+
+        let models = list(&state, "default").await;
+        let ids: Vec<&str> = models.data.iter().map(|m| m.id.as_str()).collect();
+        assert_eq!(ids, ["general", "reviewer"]);
+
+        let general = &models.data[0];
+        assert_eq!((general.workers, general.ready_workers), (2, 1));
+        assert_eq!(general.backends.len(), 1);
+        assert_eq!(general.context_window, 200_000);
+
+        let reviewer = &models.data[1];
+        assert_eq!(reviewer.ready_workers, 0);
+        assert_eq!(reviewer.modalities, ["text", "vision"]);
+    }
+}
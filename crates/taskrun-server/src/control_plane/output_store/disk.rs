@@ -0,0 +1,165 @@
+//! Local-disk output store.
+
+use std::io::SeekFrom;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use async_trait::async_trait;
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+
+use taskrun_core::RunId;
+
+use super::{OutputStore, OutputStoreError, Result};
+
+/// Output store writing each segment to `<dir>/<run_id>/<index>.out`, and
+/// each artifact to `<dir>/<run_id>/artifacts/<file_id>`.
+pub struct DiskOutputStore {
+    dir: PathBuf,
+}
+
+impl DiskOutputStore {
+    /// Open (or create) an output directory.
+    pub async fn open(dir: &Path) -> Result<Self> {
+        tokio::fs::create_dir_all(dir)
+            .await
+            .map_err(|source| OutputStoreError::Io {
+                path: dir.to_path_buf(),
+                source,
+            })?;
+        Ok(Self {
+            dir: dir.to_path_buf(),
+        })
+    }
+
+    fn segment_path(&self, run_id: &RunId, index: usize) -> PathBuf {
+        self.dir
+            .join(run_id.as_str())
+            .join(format!("{:06}.out", index))
+    }
+
+    fn artifact_path(&self, run_id: &RunId, file_id: &str) -> PathBuf {
+        self.dir
+            .join(run_id.as_str())
+            .join("artifacts")
+            .join(file_id)
+    }
+}
+
+#[async_trait]
+impl OutputStore for DiskOutputStore {
+    async fn put_segment(&self, run_id: &RunId, index: usize, data: Vec<u8>) -> Result<()> {
+        let path = self.segment_path(run_id, index);
+        let io_err = |source| OutputStoreError::Io {
+            path: path.clone(),
+            source,
+        };
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await.map_err(io_err)?;
+        }
+        tokio::fs::write(&path, data).await.map_err(io_err)
+    }
+
+    async fn read_segment(
+        &self,
+        run_id: &RunId,
+        index: usize,
+        range: Range<u64>,
+    ) -> Result<Vec<u8>> {
+        let path = self.segment_path(run_id, index);
+        let io_err = |source| OutputStoreError::Io {
+            path: path.clone(),
+            source,
+        };
+        let mut file = tokio::fs::File::open(&path).await.map_err(io_err)?;
+        file.seek(SeekFrom::Start(range.start))
+            .await
+            .map_err(io_err)?;
+        let mut data = Vec::new();
+        file.take(range.end.saturating_sub(range.start))
+            .read_to_end(&mut data)
+            .await
+            .map_err(io_err)?;
+        Ok(data)
+    }
+
+    async fn delete_run(&self, run_id: &RunId, _segments: usize) -> Result<()> {
+        let path = self.dir.join(run_id.as_str());
+        match tokio::fs::remove_dir_all(&path).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(source) => Err(OutputStoreError::Io { path, source }),
+        }
+    }
+
+    async fn put_artifact(&self, run_id: &RunId, file_id: &str, data: Vec<u8>) -> Result<()> {
+        let path = self.artifact_path(run_id, file_id);
+        let io_err = |source| OutputStoreError::Io {
+            path: path.clone(),
+            source,
+        };
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await.map_err(io_err)?;
+        }
+        tokio::fs::write(&path, data).await.map_err(io_err)
+    }
+
+    async fn read_artifact(&self, run_id: &RunId, file_id: &str) -> Result<Vec<u8>> {
+        let path = self.artifact_path(run_id, file_id);
+        match tokio::fs::read(&path).await {
+            Ok(data) => Ok(data),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                Err(OutputStoreError::ArtifactNotFound {
+                    run_id: run_id.clone(),
+                    file_id: file_id.to_string(),
+                })
+            }
+            Err(source) => Err(OutputStoreError::Io { path, source }),
+        }
+    }
+
+    async fn delete_artifact(&self, run_id: &RunId, file_id: &str) -> Result<()> {
+        let path = self.artifact_path(run_id, file_id);
+        match tokio::fs::remove_file(&path).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(source) => Err(OutputStoreError::Io { path, source }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_disk_segments_round_trip() {
+        let dir = std::env::temp_dir().join(format!("taskrun-output-{}", std::process::id()));
+        let store = DiskOutputStore::open(&dir).await.unwrap();
+        let run_id = RunId::new("run-1");
+
+        store
+            .put_segment(&run_id, 0, b"0123456789".to_vec())
+            .await
+            .unwrap();
+        assert_eq!(store.read_segment(&run_id, 0, 2..5).await.unwrap(), b"234");
+        assert!(store.read_segment(&run_id, 1, 0..5).await.is_err());
+
+        store
+            .put_artifact(&run_id, "file_1", b"report".to_vec())
+            .await
+            .unwrap();
+        assert_eq!(
+            store.read_artifact(&run_id, "file_1").await.unwrap(),
+            b"report"
+        );
+
+        store.delete_run(&run_id, 1).await.unwrap();
+        assert!(store.read_segment(&run_id, 0, 0..5).await.is_err());
+        assert!(matches!(
+            store.read_artifact(&run_id, "file_1").await,
+            Err(OutputStoreError::ArtifactNotFound { .. })
+        ));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,444 @@
+//! Pluggable storage for run output.
+//!
+//! Output chunks stream in from workers and are buffered in memory per run.
+//! Once a run's buffer passes the spill threshold it is written to the
+//! `OutputStore` as a numbered segment and dropped from memory, so long
+//! transcripts do not grow the control plane's heap without bound:
+//!
+//! - `InMemoryOutputStore`: reference implementation, lost on restart.
+//! - `DiskOutputStore`: one file per segment under a local directory.
+//! - `S3OutputStore`: one object per segment in an S3-compatible bucket.
+//!
+//! `RunOutputs` keeps the segment index and serves byte ranges across the
+//! spilled segments and the in-memory tail. It also remembers where each
+//! chunk starts, so output can be replayed chunk by chunk from a sequence
+//! number.
+//!
+//! The same store keeps the contents of run artifacts (see `files`), one
+//! object per artifact next to the run's segments.
+
+mod disk;
+mod s3;
+
+use std::collections::HashMap;
+use std::ops::Range;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use thiserror::Error;
+use tokio::sync::{Mutex, RwLock};
+use tracing::warn;
+
+use taskrun_core::RunId;
+
+pub use disk::DiskOutputStore;
+pub use s3::{S3Config, S3OutputStore};
+
+/// Default size (bytes) of in-memory output after which a run spills to the store.
+pub const DEFAULT_SPILL_THRESHOLD: usize = 1024 * 1024;
+
+/// Output store errors.
+#[derive(Debug, Error)]
+pub enum OutputStoreError {
+    #[error("I/O error on output store '{path}': {source}")]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+
+    #[error("S3 request failed: {0}")]
+    Http(#[from] reqwest::Error),
+
+    #[error("S3 returned {status} for '{key}'")]
+    S3Status {
+        key: String,
+        status: reqwest::StatusCode,
+    },
+
+    #[error("Segment {index} of run {run_id} not found")]
+    SegmentNotFound { run_id: RunId, index: usize },
+
+    #[error("Artifact {file_id} of run {run_id} not found")]
+    ArtifactNotFound { run_id: RunId, file_id: String },
+
+    #[error("Invalid output store configuration: {0}")]
+    Config(String),
+}
+
+pub type Result<T> = std::result::Result<T, OutputStoreError>;
+
+/// Storage backend for spilled run output segments.
+#[async_trait]
+pub trait OutputStore: Send + Sync {
+    /// Store segment `index` of a run's output.
+    async fn put_segment(&self, run_id: &RunId, index: usize, data: Vec<u8>) -> Result<()>;
+
+    /// Read `range` (relative to the segment start) of segment `index`.
+    async fn read_segment(
+        &self,
+        run_id: &RunId,
+        index: usize,
+        range: Range<u64>,
+    ) -> Result<Vec<u8>>;
+
+    /// Delete the first `segments` segments of a run.
+    async fn delete_run(&self, run_id: &RunId, segments: usize) -> Result<()>;
+
+    /// Store the contents of artifact `file_id` of a run.
+    async fn put_artifact(&self, run_id: &RunId, file_id: &str, data: Vec<u8>) -> Result<()>;
+
+    /// Read the contents of artifact `file_id` of a run.
+    async fn read_artifact(&self, run_id: &RunId, file_id: &str) -> Result<Vec<u8>>;
+
+    /// Delete artifact `file_id` of a run.
+    async fn delete_artifact(&self, run_id: &RunId, file_id: &str) -> Result<()>;
+}
+
+/// Which output store backend to use.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum OutputStoreConfig {
+    /// Keep spilled output in memory.
+    #[default]
+    Memory,
+    /// Write spilled output to files under a directory.
+    Disk { dir: PathBuf },
+    /// Write spilled output to an S3-compatible bucket.
+    S3(S3Config),
+}
+
+impl OutputStoreConfig {
+    /// Build the configured output store.
+    pub async fn build(&self) -> Result<Arc<dyn OutputStore>> {
+        match self {
+            OutputStoreConfig::Memory => Ok(Arc::new(InMemoryOutputStore::default())),
+            OutputStoreConfig::Disk { dir } => Ok(Arc::new(DiskOutputStore::open(dir).await?)),
+            OutputStoreConfig::S3(config) => Ok(Arc::new(S3OutputStore::new(config.clone())?)),
+        }
+    }
+}
+
+// ============================================================================
+// In-memory store
+// ============================================================================
+
+/// Output store backed by `HashMap`s of segments and artifacts.
+#[derive(Default)]
+pub struct InMemoryOutputStore {
+    segments: RwLock<HashMap<(RunId, usize), Vec<u8>>>,
+    artifacts: RwLock<HashMap<(RunId, String), Vec<u8>>>,
+}
+
+#[async_trait]
+impl OutputStore for InMemoryOutputStore {
+    async fn put_segment(&self, run_id: &RunId, index: usize, data: Vec<u8>) -> Result<()> {
+        self.segments
+            .write()
+            .await
+            .insert((run_id.clone(), index), data);
+        Ok(())
+    }
+
+    async fn read_segment(
+        &self,
+        run_id: &RunId,
+        index: usize,
+        range: Range<u64>,
+    ) -> Result<Vec<u8>> {
+        let segments = self.segments.read().await;
+        let data = segments.get(&(run_id.clone(), index)).ok_or_else(|| {
+            OutputStoreError::SegmentNotFound {
+                run_id: run_id.clone(),
+                index,
+            }
+        })?;
+        let end = (range.end as usize).min(data.len());
+        let start = (range.start as usize).min(end);
+        Ok(data[start..end].to_vec())
+    }
+
+    async fn delete_run(&self, run_id: &RunId, segments: usize) -> Result<()> {
+        let mut stored = self.segments.write().await;
+        for index in 0..segments {
+            stored.remove(&(run_id.clone(), index));
+        }
+        Ok(())
+    }
+
+    async fn put_artifact(&self, run_id: &RunId, file_id: &str, data: Vec<u8>) -> Result<()> {
+        self.artifacts
+            .write()
+            .await
+            .insert((run_id.clone(), file_id.to_string()), data);
+        Ok(())
+    }
+
+    async fn read_artifact(&self, run_id: &RunId, file_id: &str) -> Result<Vec<u8>> {
+        self.artifacts
+            .read()
+            .await
+            .get(&(run_id.clone(), file_id.to_string()))
+            .cloned()
+            .ok_or_else(|| OutputStoreError::ArtifactNotFound {
+                run_id: run_id.clone(),
+                file_id: file_id.to_string(),
+            })
+    }
+
+    async fn delete_artifact(&self, run_id: &RunId, file_id: &str) -> Result<()> {
+        self.artifacts
+            .write()
+            .await
+            .remove(&(run_id.clone(), file_id.to_string()));
+        Ok(())
+    }
+}
+
+// ============================================================================
+// Run output index
+// ============================================================================
+
+/// Output of one run: sizes of the spilled segments plus the unspilled tail,
+/// and where each chunk starts.
+#[derive(Default)]
+struct RunOutput {
+    segments: Vec<u64>,
+    tail: Vec<u8>,
+    chunks: Vec<ChunkMark>,
+}
+
+/// Start of a chunk within a run's output.
+#[derive(Debug, Clone, Copy)]
+struct ChunkMark {
+    seq: u64,
+    start: u64,
+}
+
+/// A chunk of stored run output.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StoredChunk {
+    /// Sequence number the worker gave the chunk.
+    pub seq: u64,
+    pub content: String,
+}
+
+impl RunOutput {
+    fn len(&self) -> u64 {
+        self.segments.iter().sum::<u64>() + self.tail.len() as u64
+    }
+}
+
+/// Run output, buffered in memory and spilled to an `OutputStore`.
+pub struct RunOutputs {
+    store: Arc<dyn OutputStore>,
+    spill_threshold: usize,
+    runs: RwLock<HashMap<RunId, Arc<Mutex<RunOutput>>>>,
+}
+
+impl RunOutputs {
+    /// Create run outputs that spill to `store` past `spill_threshold` bytes.
+    pub fn new(store: Arc<dyn OutputStore>, spill_threshold: usize) -> Self {
+        Self {
+            store,
+            spill_threshold,
+            runs: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Create run outputs backed by an in-memory store.
+    pub fn in_memory() -> Self {
+        Self::new(
+            Arc::new(InMemoryOutputStore::default()),
+            DEFAULT_SPILL_THRESHOLD,
+        )
+    }
+
+    /// The store output is spilled to.
+    pub fn store(&self) -> Arc<dyn OutputStore> {
+        self.store.clone()
+    }
+
+    async fn get(&self, run_id: &RunId) -> Option<Arc<Mutex<RunOutput>>> {
+        self.runs.read().await.get(run_id).cloned()
+    }
+
+    /// Append chunk `seq` to a run's output, spilling the buffer if it is full.
+    ///
+    /// A failed spill is logged and retried on the next append; the content
+    /// stays in memory meanwhile.
+    pub async fn append(&self, run_id: &RunId, seq: u64, content: &str) {
+        let output = self
+            .runs
+            .write()
+            .await
+            .entry(run_id.clone())
+            .or_default()
+            .clone();
+
+        let mut output = output.lock().await;
+        let start = output.len();
+        output.chunks.push(ChunkMark { seq, start });
+        output.tail.extend_from_slice(content.as_bytes());
+        if output.tail.len() < self.spill_threshold {
+            return;
+        }
+
+        let index = output.segments.len();
+        let size = output.tail.len() as u64;
+        match self
+            .store
+            .put_segment(run_id, index, output.tail.clone())
+            .await
+        {
+            Ok(()) => {
+                output.segments.push(size);
+                output.tail.clear();
+            }
+            Err(e) => {
+                warn!(run_id = %run_id, segment = index, error = %e, "Failed to spill run output");
+            }
+        }
+    }
+
+    /// Total size of a run's output in bytes, or None if it has none.
+    pub async fn len(&self, run_id: &RunId) -> Option<u64> {
+        Some(self.get(run_id).await?.lock().await.len())
+    }
+
+    /// Read `range` of a run's output, clamped to its length.
+    pub async fn read_range(&self, run_id: &RunId, range: Range<u64>) -> Result<Vec<u8>> {
+        let Some(output) = self.get(run_id).await else {
+            return Ok(Vec::new());
+        };
+        let output = output.lock().await;
+
+        let mut data = Vec::new();
+        let mut segment_start = 0;
+        for (index, &size) in output.segments.iter().enumerate() {
+            let segment_end = segment_start + size;
+            if range.start < segment_end && range.end > segment_start {
+                let relative = range.start.saturating_sub(segment_start)
+                    ..range.end.min(segment_end) - segment_start;
+                data.extend(self.store.read_segment(run_id, index, relative).await?);
+            }
+            segment_start = segment_end;
+        }
+
+        let tail_end = segment_start + output.tail.len() as u64;
+        if range.end > segment_start && range.start < tail_end {
+            let start = range.start.saturating_sub(segment_start) as usize;
+            let end = (range.end.min(tail_end) - segment_start) as usize;
+            data.extend_from_slice(&output.tail[start..end]);
+        }
+        Ok(data)
+    }
+
+    /// Stored chunks of a run with a sequence number of at least `from_seq`,
+    /// in the order they were appended.
+    pub async fn chunks_from(&self, run_id: &RunId, from_seq: u64) -> Result<Vec<StoredChunk>> {
+        let Some(output) = self.get(run_id).await else {
+            return Ok(Vec::new());
+        };
+        let (spans, end) = {
+            let output = output.lock().await;
+            let end = output.len();
+            let spans: Vec<(u64, Range<u64>)> = output
+                .chunks
+                .iter()
+                .enumerate()
+                .filter(|(_, mark)| mark.seq >= from_seq)
+                .map(|(i, mark)| {
+                    let next = output.chunks.get(i + 1).map_or(end, |m| m.start);
+                    (mark.seq, mark.start..next)
+                })
+                .collect();
+            (spans, end)
+        };
+        let Some(first) = spans.first().map(|(_, range)| range.start) else {
+            return Ok(Vec::new());
+        };
+
+        let data = self.read_range(run_id, first..end).await?;
+        Ok(spans
+            .into_iter()
+            .map(|(seq, range)| {
+                let bytes = &data[(range.start - first) as usize..(range.end - first) as usize];
+                StoredChunk {
+                    seq,
+                    content: String::from_utf8_lossy(bytes).into_owned(),
+                }
+            })
+            .collect())
+    }
+
+    /// Drop a run's output from memory and the store.
+    /// Returns the number of bytes removed, or None if the run had no output.
+    ///
+    /// A failed store delete is logged; the run is dropped from the index anyway.
+    pub async fn remove(&self, run_id: &RunId) -> Option<u64> {
+        let output = self.runs.write().await.remove(run_id)?;
+        let output = output.lock().await;
+        if !output.segments.is_empty() {
+            if let Err(e) = self.store.delete_run(run_id, output.segments.len()).await {
+                warn!(run_id = %run_id, error = %e, "Failed to delete spilled run output");
+            }
+        }
+        Some(output.len())
+    }
+
+    /// IDs of all runs with output.
+    pub async fn run_ids(&self) -> Vec<RunId> {
+        self.runs.read().await.keys().cloned().collect()
+    }
+
+    /// A run's full output, or None if it has none (or it could not be read).
+    pub async fn read_all(&self, run_id: &RunId) -> Option<String> {
+        let len = self.len(run_id).await?;
+        match self.read_range(run_id, 0..len).await {
+            Ok(data) => Some(String::from_utf8_lossy(&data).into_owned()),
+            Err(e) => {
+                warn!(run_id = %run_id, error = %e, "Failed to read run output");
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_spilled_output_reads_across_segments() {
+        let outputs = RunOutputs::new(Arc::new(InMemoryOutputStore::default()), 4);
+        let run_id = RunId::new("run-1");
+
+        assert_eq!(outputs.len(&run_id).await, None);
+        outputs.append(&run_id, 0, "hello ").await; // spilled as segment 0
+        outputs.append(&run_id, 1, "wor").await; // still in memory
+        outputs.append(&run_id, 2, "ld!").await; // "world!" spilled as segment 1
+        outputs.append(&run_id, 3, "ok").await;
+
+        assert_eq!(outputs.len(&run_id).await, Some(14));
+        assert_eq!(outputs.read_all(&run_id).await.unwrap(), "hello world!ok");
+        assert_eq!(
+            outputs.read_range(&run_id, 4..13).await.unwrap(),
+            b"o world!o"
+        );
+        assert_eq!(outputs.read_range(&run_id, 12..100).await.unwrap(), b"ok");
+        let chunks = outputs.chunks_from(&run_id, 1).await.unwrap();
+        let chunks: Vec<(u64, &str)> = chunks.iter().map(|c| (c.seq, c.content.as_str())).collect();
+        assert_eq!(chunks, vec![(1, "wor"), (2, "ld!"), (3, "ok")]);
+        assert!(outputs.chunks_from(&run_id, 4).await.unwrap().is_empty());
+        assert!(outputs
+            .read_range(&run_id, 20..30)
+            .await
+            .unwrap()
+            .is_empty());
+
+        assert_eq!(outputs.remove(&run_id).await, Some(14));
+        assert_eq!(outputs.len(&run_id).await, None);
+        assert!(outputs.store.read_segment(&run_id, 0, 0..1).await.is_err());
+    }
+}
@@ -0,0 +1,214 @@
+//! Tailing a run's output for `StreamRunOutput` subscribers.
+//!
+//! The subscriber joins the run's stream channel first, then the chunks
+//! already stored from `from_seq` on are replayed, then live chunks follow.
+//! A chunk that is both stored and received live is sent once (by sequence
+//! number). A subscriber that falls behind the live channel catches up from
+//! storage again. Once the run finishes, a final message with its status
+//! ends the stream.
+
+use std::sync::Arc;
+
+use tokio::sync::{broadcast, mpsc};
+use tracing::warn;
+
+use taskrun_core::{RunId, RunStatus};
+use taskrun_proto::pb;
+
+use crate::control_plane::state::{AppState, StreamEvent};
+
+/// Buffered chunks per subscriber before the stream applies backpressure.
+const OUTPUT_BUFFER: usize = 64;
+
+/// Start tailing `run_id` from chunk `from_seq`.
+///
+/// Chunks are sent until the run finishes or the receiver is dropped.
+pub fn subscribe(
+    state: Arc<AppState>,
+    run_id: RunId,
+    from_seq: u64,
+) -> mpsc::Receiver<pb::RunOutputStreamChunk> {
+    let (tx, rx) = mpsc::channel(OUTPUT_BUFFER);
+
+    tokio::spawn(async move {
+        let mut tail = Tail {
+            state,
+            run_id,
+            next_seq: from_seq,
+            tx,
+        };
+        // Join before replaying, so no chunk falls between the two
+        let mut live = match tail.status().await {
+            Some(status) if status.is_active() => Some(
+                tail.state
+                    .get_or_create_stream_channel(&tail.run_id)
+                    .await
+                    .subscribe(),
+            ),
+            _ => None,
+        };
+        if !tail.replay().await {
+            return;
+        }
+
+        if let Some(live) = live.as_mut() {
+            loop {
+                let event = tokio::select! {
+                    event = live.recv() => event,
+                    _ = tail.tx.closed() => return,
+                };
+                match event {
+                    Ok(StreamEvent::OutputChunk {
+                        seq,
+                        content,
+                        timestamp_ms,
+                        ..
+                    }) => {
+                        if seq < tail.next_seq || content.is_empty() {
+                            continue;
+                        }
+                        if !tail.send(seq, content, timestamp_ms, false).await {
+                            return;
+                        }
+                    }
+                    Ok(StreamEvent::StatusUpdate { status, .. }) if status.is_terminal() => break,
+                    Ok(
+                        StreamEvent::StatusUpdate { .. }
+                        | StreamEvent::Milestone { .. }
+                        | StreamEvent::ToolCall { .. }
+                        | StreamEvent::ToolResult { .. },
+                    ) => {}
+                    Err(broadcast::error::RecvError::Lagged(_)) => {
+                        if !tail.replay().await {
+                            return;
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        }
+
+        let status = tail.status().await.unwrap_or(RunStatus::Pending);
+        let _ = tail
+            .tx
+            .send(pb::RunOutputStreamChunk {
+                run_id: tail.run_id.to_string(),
+                seq: tail.next_seq,
+                is_final: true,
+                status: pb::RunStatus::from(status).into(),
+                ..pb::RunOutputStreamChunk::default()
+            })
+            .await;
+    });
+
+    rx
+}
+
+/// State of one subscriber.
+struct Tail {
+    state: Arc<AppState>,
+    run_id: RunId,
+    /// Lowest sequence number not sent yet.
+    next_seq: u64,
+    tx: mpsc::Sender<pb::RunOutputStreamChunk>,
+}
+
+impl Tail {
+    async fn status(&self) -> Option<RunStatus> {
+        let tasks = self.state.tasks.read().await;
+        tasks
+            .values()
+            .flat_map(|t| &t.runs)
+            .find(|r| r.run_id == self.run_id)
+            .map(|r| r.status)
+    }
+
+    /// Send the stored chunks from `next_seq` on. Returns false if the
+    /// subscriber is gone.
+    async fn replay(&mut self) -> bool {
+        let chunks = match self
+            .state
+            .outputs
+            .chunks_from(&self.run_id, self.next_seq)
+            .await
+        {
+            Ok(chunks) => chunks,
+            Err(e) => {
+                warn!(run_id = %self.run_id, error = %e, "Failed to read run output");
+                return true;
+            }
+        };
+        for chunk in chunks {
+            if !self.send(chunk.seq, chunk.content, 0, true).await {
+                return false;
+            }
+        }
+        true
+    }
+
+    async fn send(&mut self, seq: u64, content: String, timestamp_ms: i64, replayed: bool) -> bool {
+        self.next_seq = seq + 1;
+        self.tx
+            .send(pb::RunOutputStreamChunk {
+                run_id: self.run_id.to_string(),
+                seq,
+                content,
+                timestamp_ms,
+                replayed,
+                ..pb::RunOutputStreamChunk::default()
+            })
+            .await
+            .is_ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use taskrun_core::{RunSummary, Task, WorkerId};
+
+    #[tokio::test]
+    async fn test_replays_stored_chunks_then_follows_live() {
+        let state = AppState::new();
+        let mut run = RunSummary::new(WorkerId::generate());
+        run.status = RunStatus::Running;
+        let run_id = run.run_id.clone();
+        let mut task = Task::new("general", "{}", "test");
+        task.runs.push(run);
+        state.tasks.write().await.insert(task.id.clone(), task);
+        state.append_output(&run_id, 0, "one ").await;
+        state.append_output(&run_id, 1, "two ").await;
+
+        let mut rx = subscribe(state.clone(), run_id.clone(), 1);
+        let replayed = rx.recv().await.unwrap();
+        assert_eq!((replayed.seq, replayed.content.as_str()), (1, "two "));
+        assert!(replayed.replayed);
+
+        // A chunk already replayed is not sent again
+        state.append_output(&run_id, 2, "three").await;
+        for (seq, content) in [(1, "two "), (2, "three")] {
+            state
+                .publish_stream_event(
+                    &run_id,
+                    StreamEvent::OutputChunk {
+                        seq,
+                        content: content.into(),
+                        is_final: false,
+                        timestamp_ms: 1,
+                    },
+                )
+                .await;
+        }
+        let live = rx.recv().await.unwrap();
+        assert_eq!((live.seq, live.content.as_str()), (2, "three"));
+        assert!(!live.replayed);
+
+        state
+            .finalize_run(&run_id, RunStatus::Completed, None, None)
+            .await;
+        let last = rx.recv().await.unwrap();
+        assert!(last.is_final);
+        assert_eq!(last.seq, 3);
+        assert_eq!(last.status, pb::RunStatus::Completed as i32);
+    }
+}
@@ -0,0 +1,475 @@
+//! Run queue - pending tasks waiting for a worker.
+//!
+//! A task that cannot be assigned when it is created (no free worker, or its
+//! concurrency quota is used up) stays Pending. The dispatcher retries queued
+//! tasks in order: interactive before batch, then oldest first, and stops
+//! trying an agent for the tick once it has no free worker, so later tasks do
+//! not overtake earlier ones.
+//!
+//! Waiting clients are told their position among queued tasks of the same
+//! agent and namespace, and an ETA: the number of "waves" ahead of them
+//! (position divided by the capacity of the workers serving the agent) times
+//! the mean duration of the agent's recent runs. There is no ETA without a
+//! capable worker or without finished runs to learn from.
+//!
+//! Queue entries (`GET /v1/queue`, the server TUI's Queue view) also say why
+//! a task is still waiting: no worker serves its agent, the workers that do
+//! are draining or unhealthy, they are busy, a quota is used up, or
+//! scheduling is frozen. Nothing is dispatched while scheduling is frozen.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tracing::{info, warn};
+
+use taskrun_core::{RunStatus, Task, TaskId, TaskPriority, TaskStatus, WorkerId};
+use taskrun_proto::pb;
+
+use crate::control_plane::scheduler::{Scheduler, SchedulerError};
+use crate::control_plane::state::{AppState, ConnectedWorker, UiNotification};
+
+/// How often the dispatcher retries queued tasks.
+pub const DISPATCH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Finished runs of an agent used to estimate how long a run takes.
+const RECENT_RUNS: usize = 20;
+
+/// Where a queued task stands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct QueuePosition {
+    /// 1 = next to be assigned among tasks of its agent and namespace.
+    pub position: u32,
+    /// Estimated seconds until it is assigned (None = no estimate).
+    pub eta_secs: Option<u64>,
+}
+
+impl From<QueuePosition> for pb::QueuePosition {
+    fn from(queue: QueuePosition) -> Self {
+        pb::QueuePosition {
+            position: queue.position,
+            eta_seconds: queue.eta_secs.unwrap_or(0),
+        }
+    }
+}
+
+/// Why a queued task has not been assigned yet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BlockReason {
+    /// No connected worker of its namespace serves its agent.
+    AgentMissing,
+    /// The workers serving its agent are draining, degraded or offline.
+    WorkersUnavailable,
+    /// The workers serving its agent are full, or their free slots go to
+    /// tasks ahead of it.
+    NoCapacity,
+    /// A concurrency or output quota is used up.
+    QuotaExceeded,
+    /// Scheduling is frozen by an operator.
+    SchedulingFrozen,
+}
+
+impl BlockReason {
+    /// snake_case name, as serialized.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BlockReason::AgentMissing => "agent_missing",
+            BlockReason::WorkersUnavailable => "workers_unavailable",
+            BlockReason::NoCapacity => "no_capacity",
+            BlockReason::QuotaExceeded => "quota_exceeded",
+            BlockReason::SchedulingFrozen => "scheduling_frozen",
+        }
+    }
+}
+
+/// A queued task and where it stands.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct QueueEntry {
+    pub task_id: TaskId,
+    pub namespace: String,
+    pub agent_name: String,
+    pub priority: TaskPriority,
+    /// When the task started waiting for a worker.
+    pub queued_at: DateTime<Utc>,
+    #[serde(flatten)]
+    pub queue: QueuePosition,
+    /// Why it is still waiting (None = assigned on the next dispatch).
+    pub blocked_by: Option<BlockReason>,
+}
+
+/// Pending tasks that can run, in the order the dispatcher assigns them.
+pub fn queued_tasks(tasks: &HashMap<TaskId, Task>) -> Vec<&Task> {
+    let mut queued: Vec<&Task> = tasks
+        .values()
+        .filter(|task| {
+            task.status == TaskStatus::Pending
+                && task.depends_on.iter().all(|dep| {
+                    tasks
+                        .get(dep)
+                        .is_some_and(|t| t.status == TaskStatus::Completed)
+                })
+        })
+        .collect();
+    queued.sort_by(|a, b| {
+        let key = |t: &Task| (t.priority == TaskPriority::Batch, t.pending_since());
+        key(a)
+            .cmp(&key(b))
+            .then_with(|| a.id.as_str().cmp(b.id.as_str()))
+    });
+    queued
+}
+
+/// Queue position of every queued task.
+pub fn positions(
+    tasks: &HashMap<TaskId, Task>,
+    workers: &HashMap<WorkerId, ConnectedWorker>,
+) -> HashMap<TaskId, QueuePosition> {
+    let mut ahead: HashMap<(&str, &str), u32> = HashMap::new();
+    let mut durations: HashMap<(&str, &str), Option<u64>> = HashMap::new();
+    let mut positions = HashMap::new();
+
+    for task in queued_tasks(tasks) {
+        let key = (task.namespace.as_str(), task.agent_name.as_str());
+        let position = ahead.entry(key).or_insert(0);
+        *position += 1;
+
+        let mean = *durations
+            .entry(key)
+            .or_insert_with(|| mean_run_secs(tasks, &task.namespace, &task.agent_name));
+        let capacity = capacity(workers, task);
+        let eta_secs = mean
+            .filter(|_| capacity > 0)
+            .map(|mean| u64::from(position.div_ceil(capacity)) * mean);
+
+        positions.insert(
+            task.id.clone(),
+            QueuePosition {
+                position: *position,
+                eta_secs,
+            },
+        );
+    }
+    positions
+}
+
+/// Queue position of one task (None = not queued).
+pub async fn position(state: &AppState, task_id: &TaskId) -> Option<QueuePosition> {
+    let tasks = state.tasks.read().await;
+    if tasks.get(task_id)?.status != TaskStatus::Pending {
+        return None;
+    }
+    let workers = state.workers.read().await;
+    positions(&tasks, &workers).remove(task_id)
+}
+
+/// Every queued task in dispatch order, with the reason it is waiting.
+pub async fn entries(state: &AppState) -> Vec<QueueEntry> {
+    let tasks = state.tasks.read().await;
+    let workers = state.workers.read().await;
+    let mut positions = positions(&tasks, &workers);
+    let frozen = state.toggles.is_frozen().await;
+
+    let mut entries = Vec::new();
+    for task in queued_tasks(&tasks) {
+        let Some(queue) = positions.remove(&task.id) else {
+            continue;
+        };
+        let blocked_by = if frozen {
+            Some(BlockReason::SchedulingFrozen)
+        } else {
+            match state.quotas.check_run(&tasks, task).await {
+                Ok(()) => block_reason(&workers, task, queue.position),
+                Err(_) => Some(BlockReason::QuotaExceeded),
+            }
+        };
+        entries.push(QueueEntry {
+            task_id: task.id.clone(),
+            namespace: task.namespace.clone(),
+            agent_name: task.agent_name.clone(),
+            priority: task.priority,
+            queued_at: task.pending_since(),
+            queue,
+            blocked_by,
+        });
+    }
+    entries
+}
+
+/// Why the task at `position` among its agent's queued tasks waits, if the
+/// workers are the reason.
+fn block_reason(
+    workers: &HashMap<WorkerId, ConnectedWorker>,
+    task: &Task,
+    position: u32,
+) -> Option<BlockReason> {
+    let serving: Vec<&ConnectedWorker> = workers
+        .values()
+        .filter(|w| w.info.supports_agent(&task.agent_name) && w.info.namespace == task.namespace)
+        .collect();
+    if serving.is_empty() {
+        return Some(BlockReason::AgentMissing);
+    }
+    let accepting: Vec<&ConnectedWorker> = serving
+        .into_iter()
+        .filter(|w| w.status.can_accept_runs() && w.info.can_run_agent(&task.agent_name))
+        .collect();
+    if accepting.is_empty() {
+        return Some(BlockReason::WorkersUnavailable);
+    }
+    let free: u32 = accepting
+        .iter()
+        .map(|w| slots(w, task.priority).saturating_sub(w.active_runs))
+        .sum();
+    (position > free).then_some(BlockReason::NoCapacity)
+}
+
+/// Run slots of the workers that could take `task`.
+fn capacity(workers: &HashMap<WorkerId, ConnectedWorker>, task: &Task) -> u32 {
+    workers
+        .values()
+        .filter(|w| {
+            w.info.can_run_agent(&task.agent_name)
+                && w.info.namespace == task.namespace
+                && w.status.can_accept_runs()
+        })
+        .map(|w| slots(w, task.priority))
+        .sum()
+}
+
+/// Run slots a worker offers to tasks of `priority`.
+fn slots(worker: &ConnectedWorker, priority: TaskPriority) -> u32 {
+    match priority {
+        TaskPriority::Batch => worker.max_concurrent_runs,
+        TaskPriority::Interactive => worker.max_concurrent_runs + worker.interactive_slots,
+    }
+}
+
+/// Mean duration (seconds, at least 1) of the agent's last completed runs.
+fn mean_run_secs(tasks: &HashMap<TaskId, Task>, namespace: &str, agent_name: &str) -> Option<u64> {
+    let mut runs: Vec<_> = tasks
+        .values()
+        .filter(|t| t.namespace == namespace && t.agent_name == agent_name)
+        .flat_map(|t| &t.runs)
+        .filter(|r| r.status == RunStatus::Completed)
+        .filter_map(|r| Some((r.finished_at?, r.started_at?)))
+        .collect();
+    if runs.is_empty() {
+        return None;
+    }
+    runs.sort_by_key(|(finished, _)| std::cmp::Reverse(*finished));
+    runs.truncate(RECENT_RUNS);
+
+    let total: i64 = runs
+        .iter()
+        .map(|(finished, started)| (*finished - *started).num_seconds().max(0))
+        .sum();
+    Some((total as u64 / runs.len() as u64).max(1))
+}
+
+/// Assigns queued tasks as workers free up.
+pub struct Dispatcher {
+    state: Arc<AppState>,
+    scheduler: Scheduler,
+    /// Queue last sent to the UI, to only notify on change.
+    last_entries: Vec<QueueEntry>,
+}
+
+impl Dispatcher {
+    pub fn new(state: Arc<AppState>) -> Self {
+        Self {
+            scheduler: Scheduler::new(state.clone()),
+            state,
+            last_entries: Vec::new(),
+        }
+    }
+
+    /// Run forever, dispatching every `DISPATCH_INTERVAL`.
+    pub async fn run(mut self) {
+        let mut interval = tokio::time::interval(DISPATCH_INTERVAL);
+        loop {
+            interval.tick().await;
+            self.dispatch().await;
+            self.publish_queue().await;
+        }
+    }
+
+    /// Try to assign every queued task once. Returns the number assigned.
+    pub async fn dispatch(&self) -> usize {
+        if self.state.toggles.is_frozen().await {
+            return 0;
+        }
+        let queued: Vec<(TaskId, String, String)> = {
+            let tasks = self.state.tasks.read().await;
+            queued_tasks(&tasks)
+                .into_iter()
+                .map(|t| (t.id.clone(), t.namespace.clone(), t.agent_name.clone()))
+                .collect()
+        };
+
+        let mut full: HashSet<(String, String)> = HashSet::new();
+        let mut assigned = 0;
+        for (task_id, namespace, agent_name) in queued {
+            let key = (namespace, agent_name);
+            if full.contains(&key) {
+                continue;
+            }
+            match self.scheduler.assign_task(&task_id).await {
+                Ok(run_id) => {
+                    info!(task_id = %task_id, run_id = %run_id, "Assigned queued task");
+                    self.state.notify_ui(UiNotification::TaskStatusChanged {
+                        task_id,
+                        status: TaskStatus::Running,
+                    });
+                    assigned += 1;
+                }
+                Err(SchedulerError::NoWorkersAvailable(_)) => {
+                    full.insert(key);
+                }
+                // Over quota, or assigned/cancelled since the queue was read
+                Err(
+                    SchedulerError::QuotaExceeded(_)
+                    | SchedulerError::TaskNotPending(..)
+                    | SchedulerError::TaskNotFound(_)
+                    | SchedulerError::DependenciesPending(_)
+                    | SchedulerError::SchedulingFrozen(_),
+                ) => {}
+                Err(e) => warn!(task_id = %task_id, error = %e, "Failed to assign queued task"),
+            }
+        }
+        assigned
+    }
+
+    /// Send the queue to the UI when it changed.
+    async fn publish_queue(&mut self) {
+        let entries = entries(&self.state).await;
+        if entries != self.last_entries {
+            self.last_entries = entries.clone();
+            self.state
+                .notify_ui(UiNotification::QueueUpdated { entries });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::control_plane::toggles::ToggleUpdate;
+    use chrono::{Duration as ChronoDuration, Utc};
+    use taskrun_core::{AgentSpec, RunSummary, WorkerInfo, WorkerStatus};
+    use tokio::sync::mpsc;
+
+    fn worker(agent: &str, max_concurrent_runs: u32) -> ConnectedWorker {
+        let (tx, _rx) = mpsc::channel(1);
+        ConnectedWorker {
+            info: WorkerInfo::new(WorkerId::generate(), "host").with_agent(AgentSpec::new(agent)),
+            status: WorkerStatus::Idle,
+            active_runs: 0,
+            max_concurrent_runs,
+            interactive_slots: 1,
+            last_heartbeat: Utc::now(),
+            draining_since: None,
+            tx,
+            shutdown: tokio_util::sync::CancellationToken::new(),
+        }
+    }
+
+    #[test]
+    fn test_positions_order_and_eta() {
+        let now = Utc::now();
+        let mut tasks = HashMap::new();
+
+        // A finished run of 60s to learn from
+        let mut done = Task::new("general", "{}", "test");
+        let mut run = RunSummary::new(WorkerId::generate());
+        run.status = RunStatus::Completed;
+        run.started_at = Some(now - ChronoDuration::seconds(100));
+        run.finished_at = Some(now - ChronoDuration::seconds(40));
+        done.runs.push(run);
+        done.status = TaskStatus::Completed;
+        tasks.insert(done.id.clone(), done);
+
+        let mut ids = Vec::new();
+        for i in 0..3 {
+            let mut task = Task::new("general", "{}", "test");
+            task.created_at = now - ChronoDuration::seconds(30 - i);
+            ids.push(task.id.clone());
+            tasks.insert(task.id.clone(), task);
+        }
+        // Interactive work goes first even though it is the newest
+        let urgent = Task::new("general", "{}", "test").with_priority(TaskPriority::Interactive);
+        let urgent_id = urgent.id.clone();
+        tasks.insert(urgent_id.clone(), urgent);
+
+        let w = worker("general", 2);
+        let workers = HashMap::from([(w.info.worker_id.clone(), w)]);
+        let positions = positions(&tasks, &workers);
+
+        assert_eq!(positions.len(), 4);
+        assert_eq!(positions[&urgent_id].position, 1);
+        assert_eq!(positions[&ids[0]].position, 2);
+        assert_eq!(positions[&ids[2]].position, 4);
+        // Two slots: positions 1-2 start in the first wave, 3-4 in the second
+        assert_eq!(positions[&ids[0]].eta_secs, Some(60));
+        assert_eq!(positions[&ids[2]].eta_secs, Some(120));
+
+        // No capable worker, no estimate
+        let positions = super::positions(&tasks, &HashMap::new());
+        assert_eq!(positions[&ids[0]].eta_secs, None);
+    }
+
+    #[tokio::test]
+    async fn test_entries_explain_why_tasks_wait() {
+        let state = AppState::new();
+        let reasons = |entries: Vec<QueueEntry>| -> Vec<Option<BlockReason>> {
+            entries.into_iter().map(|e| e.blocked_by).collect()
+        };
+        for _ in 0..2 {
+            let task = Task::new("general", "{}", "test");
+            state.tasks.write().await.insert(task.id.clone(), task);
+        }
+        assert_eq!(
+            reasons(entries(&state).await),
+            vec![Some(BlockReason::AgentMissing); 2]
+        );
+
+        let mut w = worker("general", 1);
+        w.status = WorkerStatus::Draining;
+        let worker_id = w.info.worker_id.clone();
+        state.workers.write().await.insert(worker_id.clone(), w);
+        assert_eq!(
+            reasons(entries(&state).await),
+            vec![Some(BlockReason::WorkersUnavailable); 2]
+        );
+
+        // One free slot: the first task goes next, the second waits for it
+        state
+            .workers
+            .write()
+            .await
+            .get_mut(&worker_id)
+            .unwrap()
+            .status = WorkerStatus::Idle;
+        assert_eq!(
+            reasons(entries(&state).await),
+            vec![None, Some(BlockReason::NoCapacity)]
+        );
+
+        // A frozen scheduler holds every task back
+        state
+            .toggles
+            .update(ToggleUpdate {
+                scheduling_frozen: Some(true),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(
+            reasons(entries(&state).await),
+            vec![Some(BlockReason::SchedulingFrozen); 2]
+        );
+        assert_eq!(Dispatcher::new(state.clone()).dispatch().await, 0);
+    }
+}
@@ -0,0 +1,508 @@
+//! Per-tenant quotas.
+//!
+//! Limits are set per namespace or per API key (by key name) in a JSON file.
+//! Tasks per day and output bytes per day are checked when a task is
+//! created; concurrent runs and output bytes when a task is scheduled, so a
+//! task over its concurrency limit waits in Pending like one without a
+//! worker. Days are UTC calendar days. Usage is derived from the tasks in
+//! memory, except output bytes, which are counted as chunks arrive.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio::sync::RwLock;
+
+use taskrun_core::{Task, TaskId};
+
+use crate::control_plane::state::AppState;
+
+/// Prefix of the `created_by` of tasks created with an API key.
+const API_KEY_ACTOR_PREFIX: &str = "api-key:";
+
+/// Errors loading quotas.
+#[derive(Debug, Error)]
+pub enum QuotaError {
+    #[error("Failed to read quotas from '{path}': {source}")]
+    Io {
+        path: String,
+        source: std::io::Error,
+    },
+
+    #[error("Invalid quotas file: {0}")]
+    Parse(#[from] serde_json::Error),
+}
+
+/// A request that would go over a quota.
+#[derive(Debug, Clone, Error, Serialize)]
+#[error("Quota exceeded for {scope}: {resource} at {used} of {limit}")]
+pub struct QuotaExceeded {
+    pub scope: QuotaScope,
+    pub resource: QuotaResource,
+    pub used: u64,
+    pub limit: u64,
+    /// Seconds until the quota resets (None = when runs finish).
+    pub retry_after_secs: Option<u64>,
+}
+
+/// Limits for one namespace or API key. Unset limits are unlimited.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuotaLimits {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_concurrent_runs: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_tasks_per_day: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_output_bytes_per_day: Option<u64>,
+}
+
+impl QuotaLimits {
+    pub fn get(&self, resource: QuotaResource) -> Option<u64> {
+        match resource {
+            QuotaResource::ConcurrentRuns => self.max_concurrent_runs,
+            QuotaResource::TasksPerDay => self.max_tasks_per_day,
+            QuotaResource::OutputBytesPerDay => self.max_output_bytes_per_day,
+        }
+    }
+}
+
+/// Quotas file: limits by namespace and by API key name.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct QuotaConfig {
+    #[serde(default)]
+    pub namespaces: HashMap<String, QuotaLimits>,
+    #[serde(default)]
+    pub api_keys: HashMap<String, QuotaLimits>,
+}
+
+impl QuotaConfig {
+    fn limits(&self, scope: &QuotaScope) -> Option<&QuotaLimits> {
+        match scope {
+            QuotaScope::Namespace(name) => self.namespaces.get(name),
+            QuotaScope::ApiKey(name) => self.api_keys.get(name),
+        }
+    }
+
+    fn scopes(&self) -> Vec<QuotaScope> {
+        let namespaces = self.namespaces.keys().cloned().map(QuotaScope::Namespace);
+        let api_keys = self.api_keys.keys().cloned().map(QuotaScope::ApiKey);
+        let mut scopes: Vec<QuotaScope> = namespaces.chain(api_keys).collect();
+        scopes.sort();
+        scopes
+    }
+}
+
+/// Load quotas from a JSON file.
+pub fn load_quotas(path: &Path) -> Result<QuotaConfig, QuotaError> {
+    let json = std::fs::read_to_string(path).map_err(|source| QuotaError::Io {
+        path: path.display().to_string(),
+        source,
+    })?;
+    Ok(serde_json::from_str(&json)?)
+}
+
+/// What a quota applies to.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(tag = "kind", content = "name", rename_all = "snake_case")]
+pub enum QuotaScope {
+    Namespace(String),
+    ApiKey(String),
+}
+
+impl QuotaScope {
+    /// Scopes a task counts against: its namespace, and the API key that
+    /// created it, if any.
+    fn of(namespace: &str, created_by: &str) -> Vec<QuotaScope> {
+        let mut scopes = vec![QuotaScope::Namespace(namespace.to_string())];
+        if let Some(name) = created_by.strip_prefix(API_KEY_ACTOR_PREFIX) {
+            scopes.push(QuotaScope::ApiKey(name.to_string()));
+        }
+        scopes
+    }
+
+    fn matches(&self, task: &Task) -> bool {
+        match self {
+            QuotaScope::Namespace(name) => &task.namespace == name,
+            QuotaScope::ApiKey(name) => task
+                .created_by
+                .strip_prefix(API_KEY_ACTOR_PREFIX)
+                .is_some_and(|key| key == name),
+        }
+    }
+
+    pub fn kind(&self) -> &'static str {
+        match self {
+            QuotaScope::Namespace(_) => "namespace",
+            QuotaScope::ApiKey(_) => "api_key",
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            QuotaScope::Namespace(name) | QuotaScope::ApiKey(name) => name,
+        }
+    }
+}
+
+impl fmt::Display for QuotaScope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} '{}'", self.kind(), self.name())
+    }
+}
+
+/// A limited resource.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QuotaResource {
+    ConcurrentRuns,
+    TasksPerDay,
+    OutputBytesPerDay,
+}
+
+impl QuotaResource {
+    pub const ALL: [QuotaResource; 3] = [
+        QuotaResource::ConcurrentRuns,
+        QuotaResource::TasksPerDay,
+        QuotaResource::OutputBytesPerDay,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            QuotaResource::ConcurrentRuns => "concurrent_runs",
+            QuotaResource::TasksPerDay => "tasks_per_day",
+            QuotaResource::OutputBytesPerDay => "output_bytes_per_day",
+        }
+    }
+}
+
+impl fmt::Display for QuotaResource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Current consumption of a scope with limits.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Usage {
+    pub concurrent_runs: u64,
+    pub tasks_per_day: u64,
+    pub output_bytes_per_day: u64,
+}
+
+impl Usage {
+    pub fn get(&self, resource: QuotaResource) -> u64 {
+        match resource {
+            QuotaResource::ConcurrentRuns => self.concurrent_runs,
+            QuotaResource::TasksPerDay => self.tasks_per_day,
+            QuotaResource::OutputBytesPerDay => self.output_bytes_per_day,
+        }
+    }
+}
+
+/// A scope's limits and what it has used.
+#[derive(Debug, Clone, Serialize)]
+pub struct QuotaUsage {
+    pub scope: QuotaScope,
+    pub limits: QuotaLimits,
+    pub usage: Usage,
+}
+
+/// Output bytes counted for one UTC day.
+#[derive(Debug, Clone, Copy)]
+struct DailyBytes {
+    day: NaiveDate,
+    bytes: u64,
+}
+
+/// Configured limits, output byte counters, and rejection totals.
+#[derive(Debug, Default)]
+pub struct Quotas {
+    config: RwLock<QuotaConfig>,
+    output_bytes: RwLock<HashMap<QuotaScope, DailyBytes>>,
+    rejected_concurrent_runs: AtomicU64,
+    rejected_tasks_per_day: AtomicU64,
+    rejected_output_bytes_per_day: AtomicU64,
+}
+
+impl Quotas {
+    /// Replace the configured limits.
+    pub async fn configure(&self, config: QuotaConfig) {
+        *self.config.write().await = config;
+    }
+
+    /// Whether any limits are configured.
+    pub async fn is_enabled(&self) -> bool {
+        let config = self.config.read().await;
+        !config.namespaces.is_empty() || !config.api_keys.is_empty()
+    }
+
+    /// Count output produced by a task of `namespace` created by `created_by`.
+    pub async fn record_output(&self, namespace: &str, created_by: &str, bytes: u64) {
+        let today = Utc::now().date_naive();
+        let config = self.config.read().await;
+        let mut counters = self.output_bytes.write().await;
+        for scope in QuotaScope::of(namespace, created_by) {
+            if config.limits(&scope).is_none() {
+                continue;
+            }
+            let counter = counters.entry(scope).or_insert(DailyBytes {
+                day: today,
+                bytes: 0,
+            });
+            if counter.day != today {
+                *counter = DailyBytes {
+                    day: today,
+                    bytes: 0,
+                };
+            }
+            counter.bytes += bytes;
+        }
+    }
+
+    /// Check the quotas a new task counts against.
+    pub async fn check_create(
+        &self,
+        tasks: &HashMap<TaskId, Task>,
+        namespace: &str,
+        created_by: &str,
+    ) -> Result<(), QuotaExceeded> {
+        self.check(
+            tasks,
+            namespace,
+            created_by,
+            &[QuotaResource::TasksPerDay, QuotaResource::OutputBytesPerDay],
+        )
+        .await
+    }
+
+    /// Check the quotas a task counts against before it gets a run.
+    pub async fn check_run(
+        &self,
+        tasks: &HashMap<TaskId, Task>,
+        task: &Task,
+    ) -> Result<(), QuotaExceeded> {
+        self.check(
+            tasks,
+            &task.namespace,
+            &task.created_by,
+            &[
+                QuotaResource::ConcurrentRuns,
+                QuotaResource::OutputBytesPerDay,
+            ],
+        )
+        .await
+    }
+
+    async fn check(
+        &self,
+        tasks: &HashMap<TaskId, Task>,
+        namespace: &str,
+        created_by: &str,
+        resources: &[QuotaResource],
+    ) -> Result<(), QuotaExceeded> {
+        match self.exceeded(tasks, namespace, created_by, resources).await {
+            Some(exceeded) => {
+                self.rejections(exceeded.resource)
+                    .fetch_add(1, Ordering::Relaxed);
+                Err(exceeded)
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// The first of `resources` a task of `namespace` created by `created_by`
+    /// is over, without counting a rejection.
+    pub async fn exceeded(
+        &self,
+        tasks: &HashMap<TaskId, Task>,
+        namespace: &str,
+        created_by: &str,
+        resources: &[QuotaResource],
+    ) -> Option<QuotaExceeded> {
+        let now = Utc::now();
+        let config = self.config.read().await;
+        for scope in QuotaScope::of(namespace, created_by) {
+            let Some(limits) = config.limits(&scope) else {
+                continue;
+            };
+            let usage = self.usage_of(tasks, &scope, now).await;
+            for &resource in resources {
+                let Some(limit) = limits.get(resource) else {
+                    continue;
+                };
+                let used = usage.get(resource);
+                if used >= limit {
+                    let retry_after_secs = match resource {
+                        QuotaResource::ConcurrentRuns => None,
+                        _ => Some(secs_until_tomorrow(now)),
+                    };
+                    return Some(QuotaExceeded {
+                        scope,
+                        resource,
+                        used,
+                        limit,
+                        retry_after_secs,
+                    });
+                }
+            }
+        }
+        None
+    }
+
+    /// Limits and usage of every configured scope.
+    pub async fn usage(&self, tasks: &HashMap<TaskId, Task>) -> Vec<QuotaUsage> {
+        let now = Utc::now();
+        let config = self.config.read().await;
+        let mut all = Vec::new();
+        for scope in config.scopes() {
+            let limits = config.limits(&scope).cloned().unwrap_or_default();
+            let usage = self.usage_of(tasks, &scope, now).await;
+            all.push(QuotaUsage {
+                scope,
+                limits,
+                usage,
+            });
+        }
+        all
+    }
+
+    async fn usage_of(
+        &self,
+        tasks: &HashMap<TaskId, Task>,
+        scope: &QuotaScope,
+        now: DateTime<Utc>,
+    ) -> Usage {
+        let today = now.date_naive();
+        let mut usage = Usage::default();
+        for task in tasks.values().filter(|t| scope.matches(t)) {
+            if task.runs.iter().any(|r| !r.status.is_terminal()) {
+                usage.concurrent_runs += 1;
+            }
+            if task.created_at.date_naive() == today {
+                usage.tasks_per_day += 1;
+            }
+        }
+        usage.output_bytes_per_day = self
+            .output_bytes
+            .read()
+            .await
+            .get(scope)
+            .filter(|counter| counter.day == today)
+            .map_or(0, |counter| counter.bytes);
+        usage
+    }
+
+    fn rejections(&self, resource: QuotaResource) -> &AtomicU64 {
+        match resource {
+            QuotaResource::ConcurrentRuns => &self.rejected_concurrent_runs,
+            QuotaResource::TasksPerDay => &self.rejected_tasks_per_day,
+            QuotaResource::OutputBytesPerDay => &self.rejected_output_bytes_per_day,
+        }
+    }
+
+    /// Requests refused for `resource` since startup.
+    pub fn rejected(&self, resource: QuotaResource) -> u64 {
+        self.rejections(resource).load(Ordering::Relaxed)
+    }
+}
+
+/// Check the quotas for a task about to be created.
+pub async fn check_create(
+    state: &AppState,
+    namespace: &str,
+    created_by: &str,
+) -> Result<(), QuotaExceeded> {
+    let tasks = state.tasks.read().await;
+    state
+        .quotas
+        .check_create(&tasks, namespace, created_by)
+        .await
+}
+
+fn secs_until_tomorrow(now: DateTime<Utc>) -> u64 {
+    let tomorrow = (now.date_naive() + chrono::Days::new(1))
+        .and_hms_opt(0, 0, 0)
+        .expect("midnight is valid")
+        .and_utc();
+    (tomorrow - now).num_seconds().max(1) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use taskrun_core::{RunSummary, WorkerId};
+
+    fn config(json: &str) -> QuotaConfig {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_tasks_per_day_by_namespace_and_api_key() {
+        let quotas = Quotas::default();
+        quotas
+            .configure(config(
+                r#"{"namespaces": {"team-a": {"max_tasks_per_day": 2}},
+                    "api_keys": {"ci": {"max_tasks_per_day": 1}}}"#,
+            ))
+            .await;
+
+        let mut tasks = HashMap::new();
+        let task = Task::new("general", "{}", "api-key:ci").with_namespace("team-a");
+        tasks.insert(task.id.clone(), task);
+
+        // The ci key used its one task; other callers in team-a have one left
+        let err = quotas
+            .check_create(&tasks, "team-a", "api-key:ci")
+            .await
+            .unwrap_err();
+        assert_eq!(err.scope, QuotaScope::ApiKey("ci".to_string()));
+        assert_eq!(err.resource, QuotaResource::TasksPerDay);
+        assert!(err.retry_after_secs.is_some());
+        assert!(quotas
+            .check_create(&tasks, "team-a", "operator:alice")
+            .await
+            .is_ok());
+        assert!(quotas
+            .check_create(&tasks, "default", "api-key:other")
+            .await
+            .is_ok());
+        assert_eq!(quotas.rejected(QuotaResource::TasksPerDay), 1);
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_runs_and_output_bytes() {
+        let quotas = Quotas::default();
+        quotas
+            .configure(config(
+                r#"{"namespaces": {"team-a": {"max_concurrent_runs": 1, "max_output_bytes_per_day": 10}}}"#,
+            ))
+            .await;
+
+        let mut tasks = HashMap::new();
+        let mut running = Task::new("general", "{}", "test").with_namespace("team-a");
+        running.add_run(RunSummary::new(WorkerId::new("worker-1")));
+        let pending = Task::new("general", "{}", "test").with_namespace("team-a");
+        tasks.insert(running.id.clone(), running);
+        tasks.insert(pending.id.clone(), pending.clone());
+
+        let err = quotas.check_run(&tasks, &pending).await.unwrap_err();
+        assert_eq!(err.resource, QuotaResource::ConcurrentRuns);
+        assert_eq!(err.retry_after_secs, None);
+
+        tasks.retain(|id, _| id == &pending.id);
+        assert!(quotas.check_run(&tasks, &pending).await.is_ok());
+
+        quotas.record_output("team-a", "test", 10).await;
+        let err = quotas.check_run(&tasks, &pending).await.unwrap_err();
+        assert_eq!(err.resource, QuotaResource::OutputBytesPerDay);
+
+        let usage = quotas.usage(&tasks).await;
+        assert_eq!(usage.len(), 1);
+        assert_eq!(usage[0].usage.output_bytes_per_day, 10);
+        assert_eq!(usage[0].usage.tasks_per_day, 1);
+    }
+}
@@ -0,0 +1,284 @@
+//! Role-based access control.
+//!
+//! Every caller of the gRPC services and the protected HTTP routes has a
+//! role. Roles are ordered, each one including the permissions of the ones
+//! below it:
+//!
+//! - `readonly`: list and get tasks, runs, workers, schedules and workflows
+//! - `operator`: also create and cancel tasks, schedules and workflows
+//! - `admin`: also manage API keys
+//!
+//! HTTP callers get the role of their API key. gRPC callers get it from the
+//! Common Name of their client certificate: `<role>:<name>` (e.g.
+//! `operator:alice`). Worker certificates (`worker:<id>`) act as operators so
+//! the worker TUI can start tasks.
+//!
+//! Callers may also be bound to a namespace: API keys created with one, and
+//! certificates whose CN ends in `@<namespace>` (e.g. `operator:alice@team-a`).
+//! A bound caller only sees and creates tasks and workers in its namespace;
+//! an unbound caller reaches every namespace. Worker certificates are always
+//! bound: `worker:<id>` without `@<namespace>` serves the default namespace
+//! only.
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tonic::{Request, Status};
+
+use taskrun_core::{validate_namespace, DEFAULT_NAMESPACE};
+
+use crate::control_plane::crypto::extract_common_name;
+
+/// Access level of a caller.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    /// Read tasks, runs, workers, schedules and workflows.
+    Readonly,
+    /// Also create and cancel tasks, schedules and workflows.
+    #[default]
+    Operator,
+    /// Also manage API keys.
+    Admin,
+}
+
+impl Role {
+    /// Lowercase name, as used in certificates, key files and the CLI.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::Readonly => "readonly",
+            Role::Operator => "operator",
+            Role::Admin => "admin",
+        }
+    }
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Role {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "readonly" => Ok(Role::Readonly),
+            "operator" => Ok(Role::Operator),
+            "admin" => Ok(Role::Admin),
+            other => Err(format!(
+                "unknown role '{}' (expected readonly, operator or admin)",
+                other
+            )),
+        }
+    }
+}
+
+/// A caller asked for a namespace it may not use.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum NamespaceError {
+    #[error("'{identity}' is bound to namespace '{namespace}'")]
+    Denied { identity: String, namespace: String },
+
+    #[error("{0}")]
+    Invalid(String),
+}
+
+/// An authenticated caller, recorded as `created_by` on what it creates.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Caller {
+    /// Who is calling: `api-key:<name>` or the certificate CN.
+    pub identity: String,
+    /// What the caller may do.
+    pub role: Role,
+    /// Namespace the caller is bound to (None = all namespaces).
+    pub namespace: Option<String>,
+}
+
+impl Caller {
+    /// Caller authenticated by an API key.
+    pub fn api_key(name: &str, role: Role) -> Self {
+        Self {
+            identity: format!("api-key:{}", name),
+            role,
+            namespace: None,
+        }
+    }
+
+    /// Builder method to bind the caller to a namespace.
+    pub fn with_namespace(mut self, namespace: Option<String>) -> Self {
+        self.namespace = namespace;
+        self
+    }
+
+    /// Caller authenticated by a client certificate with Common Name `cn`.
+    ///
+    /// Returns None if the CN names no role or an invalid namespace.
+    pub fn from_common_name(cn: &str) -> Option<Self> {
+        let (prefix, name) = cn.split_once(':')?;
+        let (name, namespace) = match name.rsplit_once('@') {
+            Some((name, namespace)) => {
+                validate_namespace(namespace).ok()?;
+                (name, Some(namespace.to_string()))
+            }
+            // A worker never reaches other tenants' namespaces
+            None if prefix == "worker" => (name, Some(DEFAULT_NAMESPACE.to_string())),
+            None => (name, None),
+        };
+        if name.is_empty() {
+            return None;
+        }
+        let role = match prefix {
+            "worker" => Role::Operator,
+            prefix => prefix.parse().ok()?,
+        };
+        Some(Self {
+            identity: cn.to_string(),
+            role,
+            namespace,
+        })
+    }
+
+    /// Whether the caller has at least `role`.
+    pub fn has_role(&self, role: Role) -> bool {
+        self.role >= role
+    }
+
+    /// Whether the caller may see things in `namespace`.
+    pub fn can_access(&self, namespace: &str) -> bool {
+        self.namespace
+            .as_deref()
+            .map_or(true, |own| own == namespace)
+    }
+
+    /// Fails for callers bound to a namespace, for actions that span them all.
+    pub fn require_unbound(&self) -> Result<(), NamespaceError> {
+        match &self.namespace {
+            Some(namespace) => Err(NamespaceError::Denied {
+                identity: self.identity.clone(),
+                namespace: namespace.clone(),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// The namespaces a listing may cover: `requested` (None = all), narrowed
+    /// to the caller's own. Fails if a bound caller asks for another one.
+    pub fn scope(&self, requested: Option<&str>) -> Result<Option<String>, NamespaceError> {
+        match (self.namespace.as_deref(), requested) {
+            (Some(own), Some(requested)) if own != requested => Err(NamespaceError::Denied {
+                identity: self.identity.clone(),
+                namespace: own.to_string(),
+            }),
+            (Some(own), _) => Ok(Some(own.to_string())),
+            (None, requested) => Ok(requested.map(str::to_string)),
+        }
+    }
+
+    /// Namespace for something the caller creates: `requested`, or the
+    /// caller's own namespace, or the default one.
+    pub fn namespace_for_new(&self, requested: Option<&str>) -> Result<String, NamespaceError> {
+        namespace_for_new(Some(self), requested)
+    }
+}
+
+/// `Caller::scope` for an optional caller. Unauthenticated callers (HTTP
+/// without `--require-api-key`) reach all namespaces.
+pub fn scope(
+    caller: Option<&Caller>,
+    requested: Option<&str>,
+) -> Result<Option<String>, NamespaceError> {
+    match caller {
+        Some(caller) => caller.scope(requested),
+        None => Ok(requested.map(str::to_string)),
+    }
+}
+
+/// `Caller::namespace_for_new` for an optional caller.
+pub fn namespace_for_new(
+    caller: Option<&Caller>,
+    requested: Option<&str>,
+) -> Result<String, NamespaceError> {
+    let namespace = scope(caller, requested)?.unwrap_or_else(|| DEFAULT_NAMESPACE.to_string());
+    validate_namespace(&namespace).map_err(|e| NamespaceError::Invalid(e.to_string()))?;
+    Ok(namespace)
+}
+
+/// Authenticate the client certificate of a gRPC request and check that the
+/// caller has at least `required`.
+#[allow(clippy::result_large_err)]
+pub fn authorize<T>(request: &Request<T>, required: Role) -> Result<Caller, Status> {
+    let certs = request.peer_certs().unwrap_or_default();
+    let cert = certs
+        .first()
+        .ok_or_else(|| Status::unauthenticated("client certificate required"))?;
+    let cn =
+        extract_common_name(cert.as_ref()).map_err(|e| Status::unauthenticated(e.to_string()))?;
+    let caller = Caller::from_common_name(&cn).ok_or_else(|| {
+        Status::permission_denied(format!("certificate '{}' does not name a role", cn))
+    })?;
+
+    if !caller.has_role(required) {
+        return Err(Status::permission_denied(format!(
+            "'{}' has role {}, {} required",
+            caller.identity, caller.role, required
+        )));
+    }
+    Ok(caller)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_caller_from_common_name() {
+        let caller = Caller::from_common_name("readonly:dashboard").unwrap();
+        assert_eq!(caller.role, Role::Readonly);
+        assert!(caller.has_role(Role::Readonly));
+        assert!(!caller.has_role(Role::Operator));
+
+        let worker = Caller::from_common_name("worker:dev").unwrap();
+        assert_eq!(worker.identity, "worker:dev");
+        assert!(worker.has_role(Role::Operator));
+        assert!(!worker.has_role(Role::Admin));
+        assert_eq!(worker.namespace.as_deref(), Some(DEFAULT_NAMESPACE));
+        assert!(!worker.can_access("team-a"));
+
+        assert!(Caller::from_common_name("admin:ops")
+            .unwrap()
+            .has_role(Role::Admin));
+        assert_eq!(Caller::from_common_name("localhost"), None);
+        assert_eq!(Caller::from_common_name("root:x"), None);
+        assert_eq!(Caller::from_common_name("admin:"), None);
+    }
+
+    #[test]
+    fn test_caller_namespace() {
+        let caller = Caller::from_common_name("operator:alice@team-a").unwrap();
+        assert_eq!(caller.namespace.as_deref(), Some("team-a"));
+        assert!(caller.can_access("team-a"));
+        assert!(!caller.can_access("default"));
+        assert_eq!(caller.scope(None).unwrap().as_deref(), Some("team-a"));
+        assert!(caller.scope(Some("team-b")).is_err());
+        assert_eq!(caller.namespace_for_new(None).unwrap(), "team-a");
+        assert_eq!(Caller::from_common_name("operator:alice@Team"), None);
+
+        let admin = Caller::from_common_name("admin:ops").unwrap();
+        assert!(admin.can_access("team-a"));
+        assert_eq!(admin.scope(None).unwrap(), None);
+        assert_eq!(admin.namespace_for_new(None).unwrap(), DEFAULT_NAMESPACE);
+        assert!(admin.namespace_for_new(Some("Bad NS")).is_err());
+    }
+
+    #[test]
+    fn test_authorize_requires_certificate() {
+        let status = authorize(&Request::new(()), Role::Readonly).unwrap_err();
+        assert_eq!(status.code(), tonic::Code::Unauthenticated);
+    }
+}
@@ -0,0 +1,611 @@
+//! Stuck-run reaper - fails runs that time out or lose their worker.
+//!
+//! Without this, a crashed worker leaves its tasks in Running forever. The
+//! reaper periodically scans active runs and marks them Failed when:
+//!
+//! - the run exceeds its task's `timeout_seconds`,
+//! - the owning worker has missed too many heartbeats, or
+//! - the owning worker disconnected and did not come back within the same window.
+//!
+//! If the worker is still connected, it is sent a `CancelRun` so it stops work.
+//! With `lost_run_retries` set, a task whose run failed because its worker was
+//! lost goes back to Pending for another worker, up to that many times. The
+//! task keeps its input and its earlier runs, and goes straight back without
+//! being marked Failed, so workflow steps and requests waiting on it don't see
+//! it finish.
+//!
+//! With a pending TTL configured (or set on the task), it also fails tasks that
+//! waited in Pending that long without a worker picking them up. Tasks held
+//! back by unfinished dependencies are not expired.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use tracing::{info, warn};
+
+use taskrun_core::{FailureReason, RunId, RunStatus, TaskId, TaskStatus, WorkerId};
+use taskrun_proto::pb::run_server_message::Payload as ServerPayload;
+use taskrun_proto::pb::{CancelRun, RunServerMessage};
+
+use crate::control_plane::scheduler::Scheduler;
+use crate::control_plane::state::{AppState, UiNotification};
+
+/// Reaper configuration.
+#[derive(Debug, Clone)]
+pub struct ReaperConfig {
+    /// How often to scan for stuck runs.
+    pub interval: Duration,
+
+    /// Expected heartbeat interval from workers.
+    pub heartbeat_interval: Duration,
+
+    /// Number of missed heartbeats before a worker's runs are failed.
+    pub max_missed_heartbeats: u32,
+
+    /// How long a task may wait in Pending (None = forever, unless the task sets a TTL).
+    pub pending_ttl: Option<Duration>,
+
+    /// Times a task is rescheduled after losing its worker mid-run (0 = fail it).
+    pub lost_run_retries: u32,
+}
+
+impl ReaperConfig {
+    /// Time without a heartbeat after which a worker is considered dead.
+    pub fn heartbeat_timeout(&self) -> Duration {
+        self.heartbeat_interval * self.max_missed_heartbeats
+    }
+}
+
+impl Default for ReaperConfig {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(5),
+            heartbeat_interval: Duration::from_secs(15),
+            max_missed_heartbeats: 3,
+            pending_ttl: None,
+            lost_run_retries: 0,
+        }
+    }
+}
+
+/// A run selected for reaping.
+struct ReapTarget {
+    task_id: TaskId,
+    run_id: RunId,
+    worker_id: WorkerId,
+    failure_reason: FailureReason,
+    reason: String,
+    /// Whether the worker is still connected and should be told to stop.
+    send_cancel: bool,
+}
+
+/// Periodically fails runs that exceeded their timeout or lost their worker.
+pub struct Reaper {
+    state: Arc<AppState>,
+    config: ReaperConfig,
+    /// When each run was first seen without a connected worker.
+    orphaned_since: HashMap<RunId, DateTime<Utc>>,
+}
+
+impl Reaper {
+    /// Create a new Reaper.
+    pub fn new(state: Arc<AppState>, config: ReaperConfig) -> Self {
+        Self {
+            state,
+            config,
+            orphaned_since: HashMap::new(),
+        }
+    }
+
+    /// Run the reaper loop forever.
+    pub async fn run(mut self) {
+        info!(
+            interval_secs = self.config.interval.as_secs(),
+            heartbeat_timeout_secs = self.config.heartbeat_timeout().as_secs(),
+            "Run reaper started"
+        );
+
+        let mut interval = tokio::time::interval(self.config.interval);
+        loop {
+            interval.tick().await;
+            let now = Utc::now();
+            self.reap(now).await;
+            self.expire_pending(now).await;
+        }
+    }
+
+    /// Fail tasks that have waited in Pending past their TTL.
+    /// Returns the number of tasks expired.
+    pub async fn expire_pending(&self, now: DateTime<Utc>) -> usize {
+        let mut expired = Vec::new();
+        {
+            let mut tasks = self.state.tasks.write().await;
+            let blocked: Vec<bool> = tasks
+                .values()
+                .map(|task| {
+                    task.depends_on.iter().any(|dep| {
+                        tasks
+                            .get(dep)
+                            .is_some_and(|t| t.status != TaskStatus::Completed)
+                    })
+                })
+                .collect();
+
+            for (task, blocked) in tasks.values_mut().zip(blocked) {
+                if task.status != TaskStatus::Pending || blocked {
+                    continue;
+                }
+                let ttl = match task.pending_ttl_seconds {
+                    Some(secs) => chrono::Duration::seconds(secs.into()),
+                    None => match self.config.pending_ttl {
+                        Some(ttl) => {
+                            chrono::Duration::from_std(ttl).unwrap_or(chrono::Duration::MAX)
+                        }
+                        None => continue,
+                    },
+                };
+                if now.signed_duration_since(task.pending_since()) <= ttl {
+                    continue;
+                }
+
+                let reason = format!(
+                    "Expired after {}s in Pending without a worker",
+                    ttl.num_seconds()
+                );
+                task.status = TaskStatus::Failed;
+                task.failure_reason = Some(FailureReason::Expired);
+                task.failure_message = Some(reason.clone());
+                expired.push((task.id.clone(), reason));
+            }
+        }
+
+        let count = expired.len();
+        for (task_id, reason) in expired {
+            warn!(task_id = %task_id, reason = %reason, "Expired pending task");
+            self.state.notify_ui(UiNotification::TaskStatusChanged {
+                task_id: task_id.clone(),
+                status: TaskStatus::Failed,
+            });
+            self.state
+                .notify_ui(UiNotification::TaskExpired { task_id, reason });
+        }
+        count
+    }
+
+    /// Scan active runs once and fail those that are stuck.
+    /// Returns the number of runs reaped.
+    pub async fn reap(&mut self, now: DateTime<Utc>) -> usize {
+        let targets = self.find_targets(now).await;
+        let count = targets.len();
+
+        for target in targets {
+            self.orphaned_since.remove(&target.run_id);
+            fail_run(&self.state, target, self.config.lost_run_retries).await;
+        }
+
+        count
+    }
+
+    async fn find_targets(&mut self, now: DateTime<Utc>) -> Vec<ReapTarget> {
+        let heartbeat_timeout = chrono::Duration::from_std(self.config.heartbeat_timeout())
+            .unwrap_or(chrono::Duration::MAX);
+
+        let last_heartbeats: HashMap<WorkerId, DateTime<Utc>> = {
+            let workers = self.state.workers.read().await;
+            workers
+                .iter()
+                .map(|(id, w)| (id.clone(), w.last_heartbeat))
+                .collect()
+        };
+
+        let tasks = self.state.tasks.read().await;
+        let mut targets = Vec::new();
+        let mut still_orphaned = HashMap::new();
+
+        for task in tasks.values().filter(|t| !t.is_terminal()) {
+            for run in task.runs.iter().filter(|r| r.status.is_active()) {
+                let started_at = run.started_at.unwrap_or(task.created_at);
+
+                let timed_out = task.timeout_seconds.filter(|&secs| {
+                    now.signed_duration_since(started_at) > chrono::Duration::seconds(secs.into())
+                });
+
+                let last_heartbeat = last_heartbeats.get(&run.worker_id);
+
+                let (failure_reason, reason, send_cancel) = if let Some(secs) = timed_out {
+                    (
+                        FailureReason::Timeout,
+                        format!("Run timed out after {}s", secs),
+                        last_heartbeat.is_some(),
+                    )
+                } else if let Some(last) = last_heartbeat {
+                    if now.signed_duration_since(*last) <= heartbeat_timeout {
+                        continue;
+                    }
+                    (
+                        FailureReason::WorkerLost,
+                        format!(
+                            "Worker {} missed {} heartbeats",
+                            run.worker_id, self.config.max_missed_heartbeats
+                        ),
+                        true,
+                    )
+                } else {
+                    let since = *self.orphaned_since.get(&run.run_id).unwrap_or(&now);
+                    if now.signed_duration_since(since) <= heartbeat_timeout {
+                        still_orphaned.insert(run.run_id.clone(), since);
+                        continue;
+                    }
+                    (
+                        FailureReason::WorkerLost,
+                        format!("Worker {} disconnected", run.worker_id),
+                        false,
+                    )
+                };
+
+                targets.push(ReapTarget {
+                    task_id: task.id.clone(),
+                    run_id: run.run_id.clone(),
+                    worker_id: run.worker_id.clone(),
+                    failure_reason,
+                    reason,
+                    send_cancel,
+                });
+            }
+        }
+
+        // Forget runs that finished or whose worker came back
+        self.orphaned_since = still_orphaned;
+        targets
+    }
+}
+
+/// Mark a run as Failed and tell the worker to stop.
+///
+/// A task that lost its worker with retries left goes straight back to
+/// Pending and is rescheduled; otherwise it fails with the run.
+async fn fail_run(state: &Arc<AppState>, target: ReapTarget, lost_run_retries: u32) {
+    let ReapTarget {
+        task_id,
+        run_id,
+        worker_id,
+        failure_reason,
+        reason,
+        send_cancel,
+    } = target;
+
+    // Decided before finalizing, so a retried task is never seen as Failed
+    let lost = lost_runs(state, &task_id).await;
+    let retry = failure_reason == FailureReason::WorkerLost && lost < lost_run_retries as usize;
+
+    // The worker may have reported a final status since we scanned
+    let finalized = if retry {
+        state.requeue_lost_run(&run_id, reason.clone()).await
+    } else {
+        state
+            .finalize_run(
+                &run_id,
+                RunStatus::Failed,
+                Some(failure_reason),
+                Some(reason.clone()),
+            )
+            .await
+    };
+    if !finalized {
+        return;
+    }
+
+    warn!(
+        task_id = %task_id,
+        run_id = %run_id,
+        worker_id = %worker_id,
+        failure_reason = failure_reason.as_str(),
+        reason = %reason,
+        "Reaped stuck run"
+    );
+
+    if send_cancel {
+        let workers = state.workers.read().await;
+        if let Some(worker) = workers.get(&worker_id) {
+            let cancel_msg = RunServerMessage {
+                payload: Some(ServerPayload::CancelRun(CancelRun {
+                    run_id: run_id.to_string(),
+                    reason,
+                })),
+            };
+            if let Err(e) = worker.tx.send(cancel_msg).await {
+                warn!(run_id = %run_id, worker_id = %worker_id, error = %e, "Failed to send CancelRun to worker");
+            }
+        }
+    }
+
+    if retry {
+        match Scheduler::new(state.clone()).assign_task(&task_id).await {
+            Ok(run_id) => info!(
+                task_id = %task_id,
+                run_id = %run_id,
+                attempt = lost + 2,
+                "Rescheduled task after losing its worker"
+            ),
+            Err(e) => info!(
+                task_id = %task_id,
+                error = %e,
+                "Task that lost its worker left pending"
+            ),
+        }
+    }
+}
+
+/// How many runs of a task have lost their worker.
+async fn lost_runs(state: &AppState, task_id: &TaskId) -> usize {
+    state.tasks.read().await.get(task_id).map_or(0, |task| {
+        task.runs
+            .iter()
+            .filter(|r| r.failure_reason == Some(FailureReason::WorkerLost))
+            .count()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use taskrun_core::{
+        RunSummary, Task, WorkerInfo, WorkerStatus, WorkflowStep, WorkflowTemplate,
+    };
+    use tokio::sync::mpsc;
+
+    use crate::control_plane::audit::AuditLog;
+    use crate::control_plane::event_store::InMemoryEventStore;
+    use crate::control_plane::output_store::RunOutputs;
+    use crate::control_plane::state::ConnectedWorker;
+    use crate::control_plane::workflows::WorkflowManager;
+
+    async fn add_running_task(
+        state: &AppState,
+        worker_id: &WorkerId,
+        timeout: Option<u32>,
+    ) -> RunId {
+        let mut task = Task::new("general", "{}", "test");
+        task.timeout_seconds = timeout;
+        task.status = TaskStatus::Running;
+        let mut run = RunSummary::new(worker_id.clone());
+        run.start();
+        let run_id = run.run_id.clone();
+        task.add_run(run);
+        state.tasks.write().await.insert(task.id.clone(), task);
+        run_id
+    }
+
+    fn connect_worker(state: &AppState, worker_id: &WorkerId) -> mpsc::Receiver<RunServerMessage> {
+        let (tx, rx) = mpsc::channel(8);
+        let worker = ConnectedWorker {
+            info: WorkerInfo::new(worker_id.clone(), "host"),
+            status: WorkerStatus::Busy,
+            active_runs: 1,
+            max_concurrent_runs: 10,
+            interactive_slots: 0,
+            last_heartbeat: Utc::now(),
+            draining_since: None,
+            tx,
+            shutdown: tokio_util::sync::CancellationToken::new(),
+        };
+        state
+            .workers
+            .try_write()
+            .unwrap()
+            .insert(worker_id.clone(), worker);
+        rx
+    }
+
+    async fn run_status(state: &AppState, run_id: &RunId) -> RunStatus {
+        let tasks = state.tasks.read().await;
+        tasks
+            .values()
+            .flat_map(|t| t.runs.iter())
+            .find(|r| &r.run_id == run_id)
+            .unwrap()
+            .status
+    }
+
+    #[tokio::test]
+    async fn test_reaps_timed_out_run_and_sends_cancel() {
+        let state = AppState::new();
+        let worker_id = WorkerId::new("worker-1");
+        let mut rx = connect_worker(&state, &worker_id);
+        let run_id = add_running_task(&state, &worker_id, Some(10)).await;
+
+        let mut reaper = Reaper::new(state.clone(), ReaperConfig::default());
+        assert_eq!(reaper.reap(Utc::now()).await, 0);
+
+        let later = Utc::now() + chrono::Duration::seconds(11);
+        assert_eq!(reaper.reap(later).await, 1);
+        assert_eq!(run_status(&state, &run_id).await, RunStatus::Failed);
+
+        match rx.try_recv().unwrap().payload {
+            Some(ServerPayload::CancelRun(cancel)) => assert_eq!(cancel.run_id, run_id.as_str()),
+            other => panic!("expected CancelRun, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reaps_orphaned_run_after_heartbeat_timeout() {
+        let state = AppState::new();
+        let worker_id = WorkerId::new("worker-gone");
+        let run_id = add_running_task(&state, &worker_id, None).await;
+
+        let mut reaper = Reaper::new(state.clone(), ReaperConfig::default());
+        let now = Utc::now();
+        assert_eq!(reaper.reap(now).await, 0);
+        assert_eq!(reaper.reap(now + chrono::Duration::seconds(30)).await, 0);
+        assert_eq!(reaper.reap(now + chrono::Duration::seconds(46)).await, 1);
+        assert_eq!(run_status(&state, &run_id).await, RunStatus::Failed);
+    }
+
+    #[tokio::test]
+    async fn test_reschedules_lost_run_within_retries() {
+        let state = AppState::new();
+        let lost_worker = WorkerId::new("worker-gone");
+        let run_id = add_running_task(&state, &lost_worker, None).await;
+
+        let other = WorkerId::new("worker-2");
+        let mut rx = connect_worker(&state, &other);
+        state
+            .workers
+            .write()
+            .await
+            .get_mut(&other)
+            .unwrap()
+            .info
+            .agents
+            .push(taskrun_core::AgentSpec::new("general"));
+
+        let config = ReaperConfig {
+            lost_run_retries: 1,
+            ..ReaperConfig::default()
+        };
+        let mut reaper = Reaper::new(state.clone(), config);
+        let now = Utc::now();
+        assert_eq!(reaper.reap(now).await, 0);
+        assert_eq!(reaper.reap(now + chrono::Duration::seconds(46)).await, 1);
+        assert_eq!(run_status(&state, &run_id).await, RunStatus::Failed);
+
+        // A second run on the other worker, first attempt kept
+        {
+            let tasks = state.tasks.read().await;
+            let task = tasks.values().next().unwrap();
+            assert_eq!(task.status, TaskStatus::Running);
+            assert_eq!(task.runs.len(), 2);
+            assert_eq!(task.runs[1].worker_id, other);
+        }
+        assert!(matches!(
+            rx.try_recv().unwrap().payload,
+            Some(ServerPayload::AssignRun(_))
+        ));
+
+        // Out of retries: losing the second worker fails the task
+        state.workers.write().await.remove(&other);
+        assert_eq!(reaper.reap(now + chrono::Duration::seconds(50)).await, 0);
+        assert_eq!(reaper.reap(now + chrono::Duration::seconds(96)).await, 1);
+        let tasks = state.tasks.read().await;
+        assert_eq!(tasks.values().next().unwrap().status, TaskStatus::Failed);
+    }
+
+    #[tokio::test]
+    async fn test_lost_workflow_step_keeps_its_dependents() {
+        let (state, mut notifications) = AppState::with_ui_channel(
+            None,
+            Arc::new(InMemoryEventStore::new()),
+            RunOutputs::in_memory(),
+            AuditLog::in_memory(),
+        );
+        let step = |name: &str, deps: &[&str]| WorkflowStep {
+            name: name.to_string(),
+            agent_name: "general".to_string(),
+            input_template: "{}".to_string(),
+            depends_on: deps.iter().map(|d| d.to_string()).collect(),
+            timeout_seconds: None,
+        };
+        let manager = WorkflowManager::new(
+            state.clone(),
+            vec![WorkflowTemplate {
+                name: "chain".to_string(),
+                description: String::new(),
+                params: Vec::new(),
+                steps: vec![step("a", &[]), step("b", &["a"])],
+            }],
+        )
+        .unwrap();
+        let workflow = manager
+            .create("chain", HashMap::new(), "test")
+            .await
+            .unwrap();
+        let (first, second) = (&workflow.steps[0].task_id, &workflow.steps[1].task_id);
+        let lost_worker = WorkerId::new("worker-gone");
+        {
+            let mut tasks = state.tasks.write().await;
+            let task = tasks.get_mut(first).unwrap();
+            let mut run = RunSummary::new(lost_worker.clone());
+            run.start();
+            task.add_run(run);
+            task.status = TaskStatus::Running;
+        }
+        while notifications.try_recv().is_ok() {}
+
+        let config = ReaperConfig {
+            lost_run_retries: 1,
+            ..ReaperConfig::default()
+        };
+        let mut reaper = Reaper::new(state.clone(), config);
+        let now = Utc::now();
+        assert_eq!(reaper.reap(now).await, 0);
+        assert_eq!(reaper.reap(now + chrono::Duration::seconds(46)).await, 1);
+
+        // The step never failed, so the next step is not cancelled
+        while let Ok(notification) = notifications.try_recv() {
+            assert!(!matches!(
+                notification,
+                UiNotification::TaskStatusChanged {
+                    status: TaskStatus::Failed,
+                    ..
+                }
+            ));
+        }
+        manager.advance().await;
+        let tasks = state.tasks.read().await;
+        assert_eq!(tasks[first].status, TaskStatus::Pending);
+        assert_eq!(
+            tasks[first].runs[0].failure_reason,
+            Some(FailureReason::WorkerLost)
+        );
+        assert_eq!(tasks[second].status, TaskStatus::Pending);
+    }
+
+    #[tokio::test]
+    async fn test_expires_pending_task_past_ttl() {
+        let state = AppState::new();
+        let default_ttl = Task::new("general", "{}", "test");
+        let short_ttl = Task::new("general", "{}", "test").with_pending_ttl_seconds(5);
+        let (default_id, short_id) = (default_ttl.id.clone(), short_ttl.id.clone());
+        {
+            let mut tasks = state.tasks.write().await;
+            tasks.insert(default_id.clone(), default_ttl);
+            tasks.insert(short_id.clone(), short_ttl);
+        }
+
+        let reaper = Reaper::new(
+            state.clone(),
+            ReaperConfig {
+                pending_ttl: Some(Duration::from_secs(60)),
+                ..ReaperConfig::default()
+            },
+        );
+        let now = Utc::now();
+        assert_eq!(reaper.expire_pending(now).await, 0);
+        assert_eq!(
+            reaper
+                .expire_pending(now + chrono::Duration::seconds(6))
+                .await,
+            1
+        );
+        assert_eq!(
+            reaper
+                .expire_pending(now + chrono::Duration::seconds(61))
+                .await,
+            1
+        );
+
+        let tasks = state.tasks.read().await;
+        assert_eq!(tasks[&short_id].status, TaskStatus::Failed);
+        assert_eq!(
+            tasks[&short_id].failure_reason,
+            Some(FailureReason::Expired)
+        );
+        assert_eq!(
+            tasks[&short_id].failure_message.as_deref(),
+            Some("Expired after 5s in Pending without a worker")
+        );
+        assert_eq!(tasks[&default_id].status, TaskStatus::Failed);
+    }
+}
@@ -0,0 +1,465 @@
+//! Hot reload of control plane settings.
+//!
+//! Settings that can change without a restart live in a JSON file passed
+//! with `--config`:
+//!
+//! ```json
+//! {
+//!   "log_level": "taskrun=debug,info",
+//!   "retention": {
+//!     "interval_secs": 300,
+//!     "events": { "max_age_hours": 72 },
+//!     "outputs": { "max_runs": 1000 }
+//!   },
+//!   "timezone": "Europe/Berlin",
+//!   "locale": "de-DE"
+//! }
+//! ```
+//!
+//! The file is read at startup and again when it changes, on SIGHUP, or on
+//! `POST /v1/admin/reload`. A reload also re-reads the `--quotas-file`.
+//! Settings missing from the file fall back to the command line. A file
+//! that fails to parse or validate is rejected as a whole and the running
+//! settings stay in place. Worker streams are not touched.
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio::sync::{watch, Mutex};
+use tracing::{info, warn};
+use tracing_subscriber::EnvFilter;
+
+use crate::control_plane::audit::AuditAction;
+use crate::control_plane::quotas::{self, QuotaConfig, QuotaError};
+use crate::control_plane::retention::{RetentionConfig, RetentionPolicy};
+use crate::control_plane::state::{AppState, UiNotification};
+use taskrun_tui_components::{DisplayLocale, DisplayZone, TimeFormat, UnknownZone};
+
+/// How often the settings file is checked for changes.
+const WATCH_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Log filter used when the settings file sets none.
+pub fn default_log_filter() -> EnvFilter {
+    EnvFilter::from_default_env().add_directive("taskrun=info".parse().unwrap())
+}
+
+/// Replaces the log filter of the running subscriber.
+#[derive(Clone)]
+pub struct LogFilterHandle(Arc<dyn Fn(EnvFilter) -> Result<(), String> + Send + Sync>);
+
+impl LogFilterHandle {
+    pub fn new(set: impl Fn(EnvFilter) -> Result<(), String> + Send + Sync + 'static) -> Self {
+        Self(Arc::new(set))
+    }
+
+    fn set(&self, filter: EnvFilter) -> Result<(), String> {
+        (self.0)(filter)
+    }
+}
+
+impl fmt::Debug for LogFilterHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("LogFilterHandle")
+    }
+}
+
+/// Reload errors. The running settings are kept on any of them.
+#[derive(Debug, Error)]
+pub enum ReloadError {
+    #[error("Failed to read settings from '{path}': {source}")]
+    Io {
+        path: String,
+        source: std::io::Error,
+    },
+
+    #[error("Invalid settings file: {0}")]
+    Parse(#[from] serde_json::Error),
+
+    #[error("Invalid log_level '{level}': {message}")]
+    LogLevel { level: String, message: String },
+
+    #[error(transparent)]
+    Quotas(#[from] QuotaError),
+
+    #[error("Invalid timezone: {0}")]
+    TimeZone(#[from] UnknownZone),
+}
+
+/// Contents of the settings file.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Settings {
+    /// Log filter directives, as in `RUST_LOG`.
+    #[serde(default)]
+    pub log_level: Option<String>,
+    #[serde(default)]
+    pub retention: Option<RetentionSettings>,
+    /// Time zone of server TUI timestamps: `utc`, `local` or an IANA name.
+    #[serde(default)]
+    pub timezone: Option<String>,
+    /// Locale of server TUI timestamps, e.g. `en_US` or `auto`.
+    #[serde(default)]
+    pub locale: Option<String>,
+}
+
+/// Retention section of the settings file. Unset fields keep the value
+/// given on the command line.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RetentionSettings {
+    #[serde(default)]
+    pub interval_secs: Option<u64>,
+    #[serde(default)]
+    pub events: Option<PolicySettings>,
+    #[serde(default)]
+    pub chat_messages: Option<PolicySettings>,
+    #[serde(default)]
+    pub outputs: Option<PolicySettings>,
+}
+
+/// Retention limits for one kind of run data. Unset limits keep data forever.
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PolicySettings {
+    #[serde(default)]
+    pub max_age_hours: Option<u64>,
+    #[serde(default)]
+    pub max_runs: Option<usize>,
+}
+
+impl From<PolicySettings> for RetentionPolicy {
+    fn from(policy: PolicySettings) -> Self {
+        RetentionPolicy {
+            max_age: policy.max_age_hours.map(|h| Duration::from_secs(h * 3600)),
+            max_runs: policy.max_runs,
+        }
+    }
+}
+
+impl RetentionSettings {
+    /// `base` with the settings of this section applied.
+    fn apply(&self, base: &RetentionConfig) -> RetentionConfig {
+        RetentionConfig {
+            interval: self
+                .interval_secs
+                .map_or(base.interval, |secs| Duration::from_secs(secs.max(1))),
+            events: self.events.map_or(base.events, Into::into),
+            chat_messages: self.chat_messages.map_or(base.chat_messages, Into::into),
+            outputs: self.outputs.map_or(base.outputs, Into::into),
+        }
+    }
+}
+
+/// Load the settings file.
+pub fn load_settings(path: &Path) -> Result<Settings, ReloadError> {
+    let json = std::fs::read_to_string(path).map_err(|source| ReloadError::Io {
+        path: path.display().to_string(),
+        source,
+    })?;
+    Ok(serde_json::from_str(&json)?)
+}
+
+/// What a reload changed.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ReloadSummary {
+    /// Names of the settings whose value changed.
+    pub changed: Vec<String>,
+}
+
+/// Settings as last applied.
+#[derive(Default)]
+struct Applied {
+    log_level: Option<String>,
+    quotas: Option<QuotaConfig>,
+    time_format: TimeFormat,
+}
+
+/// Reads the settings file and applies it to the running server.
+pub struct ConfigReloader {
+    state: Arc<AppState>,
+    path: PathBuf,
+    quotas_file: Option<PathBuf>,
+    /// Retention from the command line.
+    base_retention: RetentionConfig,
+    retention: watch::Sender<RetentionConfig>,
+    log_filter: Option<LogFilterHandle>,
+    /// Timestamp display from the command line.
+    base_time_format: TimeFormat,
+    applied: Mutex<Applied>,
+}
+
+impl ConfigReloader {
+    /// Create a reloader for the settings file at `path`.
+    pub fn new(
+        state: Arc<AppState>,
+        path: PathBuf,
+        base_retention: RetentionConfig,
+        quotas_file: Option<PathBuf>,
+        log_filter: Option<LogFilterHandle>,
+    ) -> Self {
+        let (retention, _) = watch::channel(base_retention.clone());
+        Self {
+            state,
+            path,
+            quotas_file,
+            base_retention,
+            retention,
+            log_filter,
+            base_time_format: TimeFormat::default(),
+            applied: Mutex::new(Applied::default()),
+        }
+    }
+
+    /// Timestamp display to fall back to when the file sets none.
+    pub fn with_time_format(mut self, time_format: TimeFormat) -> Self {
+        self.base_time_format = time_format;
+        self.applied.get_mut().time_format = time_format;
+        self
+    }
+
+    /// Current retention, updated on every reload that changes it.
+    pub fn retention(&self) -> watch::Receiver<RetentionConfig> {
+        self.retention.subscribe()
+    }
+
+    /// Read the settings file and apply what changed.
+    pub async fn load(&self) -> Result<ReloadSummary, ReloadError> {
+        let settings = load_settings(&self.path)?;
+        let log_filter = match &settings.log_level {
+            Some(level) => EnvFilter::try_new(level).map_err(|e| ReloadError::LogLevel {
+                level: level.clone(),
+                message: e.to_string(),
+            })?,
+            None => default_log_filter(),
+        };
+        let quotas = match &self.quotas_file {
+            Some(path) => Some(quotas::load_quotas(path)?),
+            None => None,
+        };
+        let retention = match &settings.retention {
+            Some(section) => section.apply(&self.base_retention),
+            None => self.base_retention.clone(),
+        };
+        let zone = match &settings.timezone {
+            Some(zone) => zone.parse::<DisplayZone>()?,
+            None => self.base_time_format.zone,
+        };
+        let locale = settings
+            .locale
+            .as_deref()
+            .map_or(self.base_time_format.locale, |tag| {
+                tag.parse::<DisplayLocale>().unwrap_or_default()
+            });
+
+        // Everything parsed; apply it
+        let mut summary = ReloadSummary::default();
+        let mut applied = self.applied.lock().await;
+        if applied.log_level != settings.log_level {
+            if let Some(handle) = &self.log_filter {
+                if let Err(e) = handle.set(log_filter) {
+                    warn!(error = %e, "Failed to replace the log filter");
+                }
+            }
+            applied.log_level = settings.log_level;
+            summary.changed.push("log_level".to_string());
+        }
+        let retention_changed = self.retention.send_if_modified(|current| {
+            let changed = *current != retention;
+            *current = retention;
+            changed
+        });
+        if retention_changed {
+            summary.changed.push("retention".to_string());
+        }
+        if quotas.is_some() && applied.quotas != quotas {
+            self.state
+                .quotas
+                .configure(quotas.clone().unwrap_or_default())
+                .await;
+            applied.quotas = quotas;
+            summary.changed.push("quotas".to_string());
+        }
+        let time_format = TimeFormat::new(zone, locale);
+        if applied.time_format != time_format {
+            if applied.time_format.zone != zone {
+                summary.changed.push("timezone".to_string());
+            }
+            if applied.time_format.locale != locale {
+                summary.changed.push("locale".to_string());
+            }
+            applied.time_format = time_format;
+            self.state
+                .notify_ui(UiNotification::TimeFormatChanged { time_format });
+        }
+        Ok(summary)
+    }
+
+    /// Reload on behalf of `actor`, notifying the UI and auditing the reload.
+    pub async fn reload(
+        &self,
+        actor: &str,
+        client_ip: Option<&str>,
+    ) -> Result<ReloadSummary, ReloadError> {
+        let summary = match self.load().await {
+            Ok(summary) => summary,
+            Err(e) => {
+                warn!(path = %self.path.display(), error = %e, "Settings reload rejected");
+                return Err(e);
+            }
+        };
+        info!(
+            path = %self.path.display(),
+            changed = ?summary.changed,
+            reloaded_by = actor,
+            "Settings reloaded"
+        );
+        self.state.notify_ui(UiNotification::ConfigReloaded {
+            changed: summary.changed.clone(),
+        });
+        self.state
+            .audit_from(
+                actor,
+                client_ip,
+                AuditAction::ConfigReloaded,
+                &self.path.display().to_string(),
+                &summary,
+            )
+            .await;
+        Ok(summary)
+    }
+
+    /// Reload whenever the settings file changes or SIGHUP arrives.
+    pub async fn run(self: Arc<Self>) {
+        info!(path = %self.path.display(), "Watching settings file");
+        let mut hangups = hangup_signals();
+        let mut last_modified = modified(&self.path);
+        let mut interval = tokio::time::interval(WATCH_INTERVAL);
+        loop {
+            let actor = tokio::select! {
+                _ = interval.tick() => {
+                    let now = modified(&self.path);
+                    if now == last_modified {
+                        continue;
+                    }
+                    last_modified = now;
+                    "settings-file"
+                }
+                Some(()) = recv_hangup(&mut hangups) => "sighup",
+            };
+            // Errors are logged by reload
+            let _ = self.reload(actor, None).await;
+        }
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+#[cfg(unix)]
+type Hangups = Option<tokio::signal::unix::Signal>;
+#[cfg(not(unix))]
+type Hangups = ();
+
+#[cfg(unix)]
+fn hangup_signals() -> Hangups {
+    use tokio::signal::unix::{signal, SignalKind};
+    match signal(SignalKind::hangup()) {
+        Ok(signals) => Some(signals),
+        Err(e) => {
+            warn!(error = %e, "Failed to listen for SIGHUP");
+            None
+        }
+    }
+}
+
+#[cfg(not(unix))]
+fn hangup_signals() -> Hangups {}
+
+#[cfg(unix)]
+async fn recv_hangup(hangups: &mut Hangups) -> Option<()> {
+    match hangups {
+        Some(signals) => signals.recv().await,
+        None => std::future::pending().await,
+    }
+}
+
+#[cfg(not(unix))]
+async fn recv_hangup(_: &mut Hangups) -> Option<()> {
+    std::future::pending().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_settings(path: &Path, json: &str) {
+        std::fs::write(path, json).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_reload_applies_changes_and_keeps_settings_on_error() {
+        let path =
+            std::env::temp_dir().join(format!("taskrun-settings-{}.json", std::process::id()));
+        write_settings(&path, r#"{"retention": {"events": {"max_runs": 10}}}"#);
+        let base = RetentionConfig::default();
+        let reloader = ConfigReloader::new(AppState::new(), path.clone(), base.clone(), None, None);
+        let retention = reloader.retention();
+
+        let summary = reloader.load().await.unwrap();
+        assert_eq!(summary.changed, vec!["retention".to_string()]);
+        assert_eq!(retention.borrow().events.max_runs, Some(10));
+        assert_eq!(retention.borrow().outputs, base.outputs);
+
+        // Unchanged file: nothing to apply
+        assert!(reloader.load().await.unwrap().changed.is_empty());
+
+        // Invalid settings are rejected as a whole
+        write_settings(&path, r#"{"log_level": "taskrun=loud", "retention": {}}"#);
+        assert!(matches!(
+            reloader.load().await,
+            Err(ReloadError::LogLevel { .. })
+        ));
+        assert_eq!(retention.borrow().events.max_runs, Some(10));
+
+        // Dropping the section falls back to the command line
+        write_settings(&path, "{}");
+        reloader.load().await.unwrap();
+        assert_eq!(*retention.borrow(), base);
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn test_reload_time_format_falls_back_to_command_line() {
+        let path = std::env::temp_dir().join(format!("taskrun-tz-{}.json", std::process::id()));
+        let base = TimeFormat::new(DisplayZone::Local, DisplayLocale::Iso);
+        let reloader = ConfigReloader::new(
+            AppState::new(),
+            path.clone(),
+            RetentionConfig::default(),
+            None,
+            None,
+        )
+        .with_time_format(base);
+
+        write_settings(&path, r#"{"timezone": "Asia/Tokyo"}"#);
+        assert_eq!(reloader.load().await.unwrap().changed, ["timezone"]);
+
+        write_settings(&path, r#"{"timezone": "Mars/Olympus"}"#);
+        assert!(matches!(
+            reloader.load().await,
+            Err(ReloadError::TimeZone(_))
+        ));
+
+        write_settings(&path, "{}");
+        assert_eq!(reloader.load().await.unwrap().changed, ["timezone"]);
+        assert!(reloader.load().await.unwrap().changed.is_empty());
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
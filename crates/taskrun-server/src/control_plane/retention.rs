@@ -0,0 +1,331 @@
+//! Retention and compaction of run data.
+//!
+//! Run events, chat messages, and output (with artifacts) are kept for every
+//! run the control plane has seen, so a long-running server grows without
+//! bound. The compactor periodically drops the data of finished runs that
+//! fall outside the configured retention:
+//!
+//! - by age: the run finished longer ago than `max_age`,
+//! - by count: the run is not among the `max_runs` most recently finished.
+//!
+//! Active runs are never compacted. Event age is the exception: events are
+//! pruned by their own timestamp, which also covers events replayed from a
+//! file for runs this server no longer knows about.
+//!
+//! Totals of what was reclaimed are kept in `RetentionStats` and exported by
+//! the metrics endpoint. The configuration can be swapped while the
+//! compactor runs (see `reload`).
+
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use tokio::sync::watch;
+use tracing::{info, warn};
+
+use taskrun_core::RunId;
+
+use crate::control_plane::state::AppState;
+
+/// Retention limits for one kind of run data. Unset limits keep data forever.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RetentionPolicy {
+    /// Drop data of runs that finished longer ago than this.
+    pub max_age: Option<Duration>,
+    /// Keep data for at most this many of the most recently finished runs.
+    pub max_runs: Option<usize>,
+}
+
+impl RetentionPolicy {
+    /// Whether any limit is set.
+    pub fn is_bounded(&self) -> bool {
+        self.max_age.is_some() || self.max_runs.is_some()
+    }
+
+    /// Runs past this policy. `finished` must be sorted newest first.
+    fn expired(&self, finished: &[(RunId, DateTime<Utc>)], now: DateTime<Utc>) -> HashSet<RunId> {
+        finished
+            .iter()
+            .enumerate()
+            .filter(|(position, (_, finished_at))| {
+                self.max_runs.is_some_and(|max| *position >= max)
+                    || self.max_age.is_some_and(|max| {
+                        (now - *finished_at)
+                            .to_std()
+                            .is_ok_and(|elapsed| elapsed > max)
+                    })
+            })
+            .map(|(_, (run_id, _))| run_id.clone())
+            .collect()
+    }
+}
+
+/// Retention configuration for the compactor.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetentionConfig {
+    /// How often to compact.
+    pub interval: Duration,
+    pub events: RetentionPolicy,
+    pub chat_messages: RetentionPolicy,
+    pub outputs: RetentionPolicy,
+}
+
+impl RetentionConfig {
+    /// Whether any kind of data has a limit, i.e. the compactor has work to do.
+    pub fn is_enabled(&self) -> bool {
+        self.events.is_bounded() || self.chat_messages.is_bounded() || self.outputs.is_bounded()
+    }
+}
+
+impl Default for RetentionConfig {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(600),
+            events: RetentionPolicy::default(),
+            chat_messages: RetentionPolicy::default(),
+            outputs: RetentionPolicy::default(),
+        }
+    }
+}
+
+/// Totals reclaimed by the compactor since startup.
+#[derive(Debug, Default)]
+pub struct RetentionStats {
+    pub compactions: AtomicU64,
+    pub events_removed: AtomicU64,
+    pub chat_messages_removed: AtomicU64,
+    pub chat_bytes_reclaimed: AtomicU64,
+    pub outputs_removed: AtomicU64,
+    pub output_bytes_reclaimed: AtomicU64,
+}
+
+impl RetentionStats {
+    fn record(&self, report: &CompactionReport) {
+        self.compactions.fetch_add(1, Ordering::Relaxed);
+        self.events_removed
+            .fetch_add(report.events_removed as u64, Ordering::Relaxed);
+        self.chat_messages_removed
+            .fetch_add(report.chat_messages_removed as u64, Ordering::Relaxed);
+        self.chat_bytes_reclaimed
+            .fetch_add(report.chat_bytes_reclaimed, Ordering::Relaxed);
+        self.outputs_removed
+            .fetch_add(report.outputs_removed as u64, Ordering::Relaxed);
+        self.output_bytes_reclaimed
+            .fetch_add(report.output_bytes_reclaimed, Ordering::Relaxed);
+    }
+}
+
+/// What a single compaction pass removed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CompactionReport {
+    pub events_removed: usize,
+    pub chat_messages_removed: usize,
+    pub chat_bytes_reclaimed: u64,
+    pub outputs_removed: usize,
+    pub output_bytes_reclaimed: u64,
+}
+
+impl CompactionReport {
+    /// Whether nothing was removed.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Periodically drops run data that is past its retention.
+pub struct Compactor {
+    state: Arc<AppState>,
+    config: watch::Receiver<RetentionConfig>,
+}
+
+impl Compactor {
+    /// Create a new Compactor with a fixed configuration.
+    #[cfg(test)]
+    pub fn new(state: Arc<AppState>, config: RetentionConfig) -> Self {
+        Self::watching(state, watch::channel(config).1)
+    }
+
+    /// Create a new Compactor that follows configuration updates.
+    pub fn watching(state: Arc<AppState>, config: watch::Receiver<RetentionConfig>) -> Self {
+        Self { state, config }
+    }
+
+    /// Run the compaction loop forever. Passes are skipped while no
+    /// retention is configured.
+    pub async fn run(mut self) {
+        let mut period = self.config.borrow().interval;
+        info!(
+            interval_secs = period.as_secs(),
+            "Run data compactor started"
+        );
+
+        let mut interval = tokio::time::interval(period);
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {}
+                Ok(()) = self.config.changed() => {
+                    // Apply the new limits right away, then on the new period
+                    let updated = self.config.borrow_and_update().interval;
+                    if updated != period {
+                        period = updated;
+                        interval = tokio::time::interval(period);
+                        interval.tick().await;
+                    }
+                }
+            }
+            if !self.config.borrow().is_enabled() {
+                continue;
+            }
+            let report = self.compact(Utc::now()).await;
+            if !report.is_empty() {
+                info!(
+                    events = report.events_removed,
+                    chat_messages = report.chat_messages_removed,
+                    outputs = report.outputs_removed,
+                    reclaimed_bytes = report.chat_bytes_reclaimed + report.output_bytes_reclaimed,
+                    "Compacted run data"
+                );
+            }
+        }
+    }
+
+    /// Drop everything past retention as of `now`.
+    pub async fn compact(&self, now: DateTime<Utc>) -> CompactionReport {
+        let config = self.config.borrow().clone();
+        let finished = self.finished_runs().await;
+        let mut report = CompactionReport::default();
+
+        let event_cutoff = config
+            .events
+            .max_age
+            .and_then(|age| chrono::Duration::from_std(age).ok())
+            .and_then(|age| now.checked_sub_signed(age));
+        if let Some(cutoff) = event_cutoff {
+            match self.state.events.prune(cutoff.timestamp_millis()).await {
+                Ok(removed) => report.events_removed += removed,
+                Err(e) => warn!(error = %e, "Failed to prune run events"),
+            }
+        }
+        if config.events.max_runs.is_some() {
+            let expired = RetentionPolicy {
+                max_age: None,
+                ..config.events
+            }
+            .expired(&finished, now);
+            if !expired.is_empty() {
+                match self.state.events.remove_runs(&expired).await {
+                    Ok(removed) => report.events_removed += removed,
+                    Err(e) => warn!(error = %e, "Failed to remove run events"),
+                }
+            }
+        }
+
+        let expired = config.chat_messages.expired(&finished, now);
+        if !expired.is_empty() {
+            let mut messages = self.state.chat_messages.write().await;
+            for run_messages in expired.iter().filter_map(|id| messages.remove(id)) {
+                report.chat_messages_removed += run_messages.len();
+                report.chat_bytes_reclaimed += run_messages
+                    .iter()
+                    .map(|m| m.content.len() as u64)
+                    .sum::<u64>();
+            }
+            drop(messages);
+            self.state.conversations.forget(&expired).await;
+        }
+
+        let expired = config.outputs.expired(&finished, now);
+        // Artifacts are output too
+        self.state.files.remove_runs(&expired).await;
+        for run_id in &expired {
+            self.state.output_index.remove(run_id).await;
+            if let Some(bytes) = self.state.outputs.remove(run_id).await {
+                report.outputs_removed += 1;
+                report.output_bytes_reclaimed += bytes;
+            }
+        }
+
+        self.state.retention.record(&report);
+        report
+    }
+
+    /// Finished runs with their finish time, newest first.
+    async fn finished_runs(&self) -> Vec<(RunId, DateTime<Utc>)> {
+        let tasks = self.state.tasks.read().await;
+        let mut finished: Vec<(RunId, DateTime<Utc>)> = tasks
+            .values()
+            .flat_map(|task| &task.runs)
+            .filter_map(|run| Some((run.run_id.clone(), run.finished_at?)))
+            .collect();
+        finished.sort_by_key(|(_, finished_at)| std::cmp::Reverse(*finished_at));
+        finished
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use taskrun_core::{ChatMessage, ChatRole, RunSummary, Task, WorkerId};
+
+    #[tokio::test]
+    async fn test_compact_by_count_and_age() {
+        let state = AppState::new();
+        let now = Utc::now();
+        let mut run_ids = Vec::new();
+        {
+            let mut tasks = state.tasks.write().await;
+            // Finished 3h, 2h and 1h ago, plus one still running
+            for hours_ago in [3, 2, 1, 0] {
+                let mut task = Task::new("general", "{}", "test");
+                let mut run = RunSummary::new(WorkerId::new("worker-1"));
+                run.start();
+                if hours_ago > 0 {
+                    run.complete(None);
+                    run.finished_at = Some(now - chrono::Duration::hours(hours_ago));
+                }
+                run_ids.push(run.run_id.clone());
+                task.add_run(run);
+                tasks.insert(task.id.clone(), task);
+            }
+        }
+        for run_id in &run_ids {
+            state.append_output(run_id, 0, "output").await;
+            state
+                .store_chat_message(run_id, ChatMessage::new(ChatRole::User, "hi"))
+                .await;
+        }
+
+        let config = RetentionConfig {
+            chat_messages: RetentionPolicy {
+                max_age: Some(Duration::from_secs(90 * 60)),
+                max_runs: None,
+            },
+            outputs: RetentionPolicy {
+                max_age: None,
+                max_runs: Some(1),
+            },
+            ..RetentionConfig::default()
+        };
+        let report = Compactor::new(state.clone(), config).compact(now).await;
+
+        assert_eq!(report.chat_messages_removed, 2);
+        assert_eq!(report.chat_bytes_reclaimed, 4);
+        assert_eq!(report.outputs_removed, 2);
+        assert_eq!(report.output_bytes_reclaimed, 12);
+        // The newest finished run and the running one keep their data
+        assert!(state.get_output(&run_ids[2]).await.is_some());
+        assert!(state.get_output(&run_ids[3]).await.is_some());
+        assert!(state.get_output(&run_ids[1]).await.is_none());
+        assert_eq!(state.get_chat_messages(&run_ids[1]).await.len(), 0);
+        assert_eq!(state.get_chat_messages(&run_ids[2]).await.len(), 1);
+        assert_eq!(
+            state
+                .retention
+                .output_bytes_reclaimed
+                .load(Ordering::Relaxed),
+            12
+        );
+    }
+}
@@ -1,15 +1,20 @@
 //! Task scheduler - routes tasks to workers.
 
 use std::sync::Arc;
+use std::time::Duration;
 
 use thiserror::Error;
-use tracing::{info, warn};
+use tracing::{info, info_span, warn, Instrument};
 
-use taskrun_core::{RunId, RunSummary, TaskId, TaskStatus, WorkerId};
+use taskrun_core::{
+    FailureReason, RunId, RunStatus, RunSummary, Task, TaskId, TaskPriority, TaskStatus, WorkerId,
+};
 use taskrun_proto::pb::run_server_message::Payload as ServerPayload;
-use taskrun_proto::pb::{RunAssignment, RunServerMessage};
+use taskrun_proto::pb::{CancelRun, InputFile, RunAssignment, RunServerMessage};
+use taskrun_proto::trace::{self, TraceContext};
 
-use crate::control_plane::state::AppState;
+use crate::control_plane::quotas::QuotaExceeded;
+use crate::control_plane::state::{AppState, StreamEvent, UiNotification};
 
 /// Scheduler errors.
 #[derive(Debug, Error)]
@@ -22,8 +27,33 @@ pub enum SchedulerError {
 
     #[error("Failed to send assignment to worker: {0}")]
     SendFailed(String),
+
+    #[error("Task {0} is already in terminal state: {1:?}")]
+    TaskAlreadyTerminal(TaskId, TaskStatus),
+
+    #[error("Task {0} is not pending: {1:?}")]
+    TaskNotPending(TaskId, TaskStatus),
+
+    #[error("Task {0} is waiting for its dependencies to complete")]
+    DependenciesPending(TaskId),
+
+    #[error("Scheduling is frozen, task {0} stays queued")]
+    SchedulingFrozen(TaskId),
+
+    #[error("Task {0} cannot be retried from state: {1:?}")]
+    TaskNotRetryable(TaskId, TaskStatus),
+
+    #[error(transparent)]
+    QuotaExceeded(#[from] QuotaExceeded),
+
+    #[error("Injected scheduler fault for task {0} (chaos)")]
+    ChaosInjected(TaskId),
 }
 
+/// How long to wait for workers to acknowledge a CancelRun before the
+/// control plane finalizes the run itself.
+const CANCEL_ACK_TIMEOUT: Duration = Duration::from_secs(10);
+
 /// Task scheduler.
 pub struct Scheduler {
     state: Arc<AppState>,
@@ -35,58 +65,90 @@ impl Scheduler {
         Self { state }
     }
 
-    /// Select a worker that supports the given agent and has capacity.
-    #[allow(dead_code)]
-    pub async fn select_worker(&self, agent_name: &str) -> Option<WorkerId> {
+    /// Select a worker in `namespace` that supports the given agent and has capacity.
+    /// Workers whose agent failed its smoke test are skipped.
+    ///
+    /// Batch work may only fill `max_concurrent_runs`, which leaves each
+    /// worker's `interactive_slots` free for interactive work. Interactive
+    /// work goes to the least loaded worker so it starts as soon as possible.
+    pub async fn select_worker(
+        &self,
+        agent_name: &str,
+        namespace: &str,
+        priority: TaskPriority,
+    ) -> Option<WorkerId> {
         let workers = self.state.workers.read().await;
 
-        for (worker_id, worker) in workers.iter() {
-            // Check if worker supports this agent
-            if !worker.info.supports_agent(agent_name) {
-                continue;
-            }
-
-            // Check if worker has capacity
-            if worker.active_runs >= worker.max_concurrent_runs {
-                continue;
-            }
-
-            // Check if worker can accept runs
-            if !worker.status.can_accept_runs() {
-                continue;
-            }
-
-            return Some(worker_id.clone());
-        }
+        let mut candidates = workers.iter().filter(|(worker_id, worker)| {
+            let capacity = match priority {
+                TaskPriority::Batch => worker.max_concurrent_runs,
+                TaskPriority::Interactive => worker.max_concurrent_runs + worker.interactive_slots,
+            };
+            worker.info.can_run_agent(agent_name)
+                && worker.info.namespace == namespace
+                && !self.state.assignments.is_unresponsive(worker_id)
+                && worker.active_runs < capacity
+                && worker.status.can_accept_runs()
+        });
 
-        None
+        let selected = match priority {
+            TaskPriority::Batch => candidates.next(),
+            TaskPriority::Interactive => candidates.min_by_key(|(_, worker)| worker.active_runs),
+        };
+        selected.map(|(worker_id, _)| worker_id.clone())
     }
 
     /// Assign a task to a worker, creating a Run.
     pub async fn assign_task(&self, task_id: &TaskId) -> Result<RunId, SchedulerError> {
         // Get task
         let mut tasks = self.state.tasks.write().await;
+        let task = tasks
+            .get(task_id)
+            .ok_or_else(|| SchedulerError::TaskNotFound(task_id.clone()))?;
+
+        // Already assigned (e.g. by the dispatcher) or finished
+        if task.status != TaskStatus::Pending {
+            return Err(SchedulerError::TaskNotPending(task_id.clone(), task.status));
+        }
+
+        // While scheduling is frozen every task waits in Pending
+        if self.state.toggles.is_frozen().await {
+            return Err(SchedulerError::SchedulingFrozen(task_id.clone()));
+        }
+
+        if self.state.chaos.scheduler_error() {
+            return Err(SchedulerError::ChaosInjected(task_id.clone()));
+        }
+
+        // Dependencies must have completed (a missing dependency never will)
+        let blocked = task.depends_on.iter().any(|dep| {
+            tasks
+                .get(dep)
+                .map_or(true, |t| t.status != TaskStatus::Completed)
+        });
+        if blocked {
+            return Err(SchedulerError::DependenciesPending(task_id.clone()));
+        }
+
+        // Over its concurrency or output quota, the task waits in Pending
+        if let Some(task) = tasks.get(task_id) {
+            self.state.quotas.check_run(&tasks, task).await?;
+        }
+
         let task = tasks
             .get_mut(task_id)
             .ok_or_else(|| SchedulerError::TaskNotFound(task_id.clone()))?;
 
         // Find a suitable worker
-        let worker_id = {
-            let workers = self.state.workers.read().await;
-
-            let mut selected: Option<WorkerId> = None;
-            for (wid, worker) in workers.iter() {
-                if worker.info.supports_agent(&task.agent_name)
-                    && worker.active_runs < worker.max_concurrent_runs
-                    && worker.status.can_accept_runs()
-                {
-                    selected = Some(wid.clone());
-                    break;
-                }
-            }
-            selected
-        }
-        .ok_or_else(|| SchedulerError::NoWorkersAvailable(task.agent_name.clone()))?;
+        let worker_id = self
+            .select_worker(&task.agent_name, &task.namespace, task.priority)
+            .await
+            .ok_or_else(|| SchedulerError::NoWorkersAvailable(task.agent_name.clone()))?;
+
+        // How long the task waited, for the scheduling latency histogram
+        let waited = (chrono::Utc::now() - task.pending_since())
+            .to_std()
+            .unwrap_or_default();
 
         // Create run summary
         let mut run = RunSummary::new(worker_id.clone());
@@ -104,18 +166,51 @@ impl Scheduler {
             run_id = %run_id,
             worker_id = %worker_id,
             agent = %task.agent_name,
+            priority = ?task.priority,
             "Assigning task to worker"
         );
 
+        // Scheduling span, in the trace the task was created in; the worker
+        // continues the trace from its context
+        let span = info_span!(
+            "task.schedule",
+            task_id = %task_id,
+            run_id = %run_id,
+            worker_id = %worker_id,
+            agent = %task.agent_name,
+            waited_ms = waited.as_millis() as u64,
+        );
+        trace::set_parent(&span, &task.trace_context);
+
         // Build assignment message
+        let issued_at_ms = chrono::Utc::now().timestamp_millis();
+        let deadline_ms = task
+            .timeout_seconds
+            .map(|secs| issued_at_ms + i64::from(secs) * 1000)
+            .unwrap_or(0);
         let assignment = RunAssignment {
             run_id: run_id.as_str().to_string(),
             task_id: task_id.as_str().to_string(),
             agent_name: task.agent_name.clone(),
             input_json: task.input_json.clone(),
             labels: task.labels.clone(),
-            issued_at_ms: chrono::Utc::now().timestamp_millis(),
-            deadline_ms: 0, // No deadline for now
+            issued_at_ms,
+            deadline_ms,
+            priority: taskrun_proto::pb::TaskPriority::from(task.priority).into(),
+            pinned_manifest: task.pinned_manifest.clone().map(Into::into),
+            trace_context: trace::inject(&span),
+            input_files: self
+                .state
+                .files
+                .inputs(&task.input_files)
+                .await
+                .into_iter()
+                .map(|file| InputFile {
+                    name: file.name,
+                    content: file.content.to_vec(),
+                })
+                .collect(),
+            limits: (!task.limits.is_empty()).then(|| task.limits.into()),
         };
 
         let msg = RunServerMessage {
@@ -126,23 +221,378 @@ impl Scheduler {
         drop(tasks);
 
         // Send to worker
-        {
+        let delivery = info_span!(parent: &span, "assignment.deliver", worker_id = %worker_id);
+        async {
             let mut workers = self.state.workers.write().await;
             if let Some(worker) = workers.get_mut(&worker_id) {
                 worker.active_runs += 1;
+                // Redelivered unless the worker acknowledges it in time
+                self.state.assignments.sent(&run_id, task_id, &worker_id);
 
                 if worker.tx.send(msg).await.is_err() {
                     warn!(worker_id = %worker_id, "Failed to send assignment - worker disconnected");
                     return Err(SchedulerError::SendFailed(worker_id.to_string()));
                 }
+                Ok(())
             } else {
-                return Err(SchedulerError::SendFailed(format!(
+                Err(SchedulerError::SendFailed(format!(
                     "Worker {} disappeared",
                     worker_id
-                )));
+                )))
             }
         }
+        .instrument(delivery)
+        .await?;
 
+        self.state.scheduling_latency.observe(waited);
         Ok(run_id)
     }
+
+    /// Retry a failed or cancelled task with a new run.
+    ///
+    /// Earlier runs are kept. The task goes back to Pending and is assigned
+    /// if a worker is available; otherwise it stays Pending, like a new task.
+    /// Returns the new run's ID when the task was assigned.
+    pub async fn retry_task(&self, task_id: &TaskId) -> Result<Option<RunId>, SchedulerError> {
+        {
+            let mut tasks = self.state.tasks.write().await;
+            let task = tasks
+                .get_mut(task_id)
+                .ok_or_else(|| SchedulerError::TaskNotFound(task_id.clone()))?;
+            if !matches!(task.status, TaskStatus::Failed | TaskStatus::Cancelled) {
+                return Err(SchedulerError::TaskNotRetryable(
+                    task_id.clone(),
+                    task.status,
+                ));
+            }
+            task.status = TaskStatus::Pending;
+            task.failure_reason = None;
+            task.failure_message = None;
+        }
+        self.state.notify_ui(UiNotification::TaskStatusChanged {
+            task_id: task_id.clone(),
+            status: TaskStatus::Pending,
+        });
+
+        match self.assign_task(task_id).await {
+            Ok(run_id) => Ok(Some(run_id)),
+            Err(e) => {
+                warn!(task_id = %task_id, error = %e, "Retried task not assigned, left pending");
+                Ok(None)
+            }
+        }
+    }
+
+    /// Cancel a task end to end.
+    ///
+    /// Sends `CancelRun` for every active run, waits (up to `CANCEL_ACK_TIMEOUT`)
+    /// for the workers to report a terminal status, then finalizes any runs that
+    /// were not acknowledged. Returns the task in its final state.
+    pub async fn cancel_task(
+        &self,
+        task_id: &TaskId,
+        reason: &str,
+    ) -> Result<Task, SchedulerError> {
+        let active_runs: Vec<(WorkerId, RunId)> = {
+            let mut tasks = self.state.tasks.write().await;
+            let task = tasks
+                .get_mut(task_id)
+                .ok_or_else(|| SchedulerError::TaskNotFound(task_id.clone()))?;
+
+            if task.is_terminal() {
+                return Err(SchedulerError::TaskAlreadyTerminal(
+                    task_id.clone(),
+                    task.status,
+                ));
+            }
+
+            let active: Vec<_> = task
+                .runs
+                .iter()
+                .filter(|r| r.status.is_active())
+                .map(|r| (r.worker_id.clone(), r.run_id.clone()))
+                .collect();
+
+            // Nothing running yet: the task can be cancelled immediately
+            if active.is_empty() {
+                task.status = TaskStatus::Cancelled;
+                let task = task.clone();
+                drop(tasks);
+                self.state.notify_ui(UiNotification::TaskStatusChanged {
+                    task_id: task_id.clone(),
+                    status: TaskStatus::Cancelled,
+                });
+                return Ok(task);
+            }
+            active
+        };
+
+        info!(task_id = %task_id, runs = active_runs.len(), "Cancelling task");
+
+        // Subscribe before sending so a fast acknowledgement is not missed
+        let mut pending = Vec::new();
+        for (worker_id, run_id) in active_runs {
+            let rx = self
+                .state
+                .get_or_create_stream_channel(&run_id)
+                .await
+                .subscribe();
+
+            let sent = {
+                let workers = self.state.workers.read().await;
+                match workers.get(&worker_id) {
+                    Some(worker) => worker
+                        .tx
+                        .send(RunServerMessage {
+                            payload: Some(ServerPayload::CancelRun(CancelRun {
+                                run_id: run_id.to_string(),
+                                reason: reason.to_string(),
+                            })),
+                        })
+                        .await
+                        .is_ok(),
+                    None => false,
+                }
+            };
+
+            if sent {
+                info!(task_id = %task_id, run_id = %run_id, worker_id = %worker_id, "Sent CancelRun to worker");
+                pending.push((run_id, rx));
+            } else {
+                warn!(task_id = %task_id, run_id = %run_id, worker_id = %worker_id, "Worker not reachable, cancelling run locally");
+                self.state
+                    .finalize_run(
+                        &run_id,
+                        RunStatus::Cancelled,
+                        Some(FailureReason::CancelledByUser),
+                        Some(reason.to_string()),
+                    )
+                    .await;
+            }
+        }
+
+        // Wait for workers to report a terminal status
+        let waits = pending.into_iter().map(|(run_id, mut rx)| async move {
+            let acked = tokio::time::timeout(CANCEL_ACK_TIMEOUT, async {
+                loop {
+                    match rx.recv().await {
+                        Ok(StreamEvent::StatusUpdate { status, .. }) if status.is_terminal() => {
+                            return true
+                        }
+                        Ok(_) | Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => {}
+                        Err(tokio::sync::broadcast::error::RecvError::Closed) => return false,
+                    }
+                }
+            })
+            .await
+            .unwrap_or(false);
+            (run_id, acked)
+        });
+
+        for (run_id, acked) in futures_util::future::join_all(waits).await {
+            if !acked
+                && self
+                    .state
+                    .finalize_run(
+                        &run_id,
+                        RunStatus::Cancelled,
+                        Some(FailureReason::CancelledByUser),
+                        Some(reason.to_string()),
+                    )
+                    .await
+            {
+                warn!(task_id = %task_id, run_id = %run_id, "Worker did not acknowledge cancel, run finalized by control plane");
+            }
+        }
+
+        // Runs are now terminal; settle the task if no run update already did
+        let (task, changed) = {
+            let mut tasks = self.state.tasks.write().await;
+            let task = tasks
+                .get_mut(task_id)
+                .ok_or_else(|| SchedulerError::TaskNotFound(task_id.clone()))?;
+            let changed = !task.is_terminal();
+            if changed {
+                task.status = TaskStatus::Cancelled;
+                task.failure_reason = Some(FailureReason::CancelledByUser);
+            }
+            (task.clone(), changed)
+        };
+        if changed {
+            self.state.notify_ui(UiNotification::TaskStatusChanged {
+                task_id: task_id.clone(),
+                status: TaskStatus::Cancelled,
+            });
+        }
+
+        Ok(task)
+    }
+}
+
+/// Start the trace of a new task: a `task.create` span, child of `parent`
+/// (the caller's trace context, if it sent one), whose context the task
+/// keeps for its runs.
+pub fn trace_created(task: Task, parent: &TraceContext) -> Task {
+    let span = info_span!(
+        "task.create",
+        task_id = %task.id,
+        agent = %task.agent_name,
+        created_by = %task.created_by,
+    );
+    trace::set_parent(&span, parent);
+    let trace_context = trace::inject(&span);
+    task.with_trace_context(trace_context)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use taskrun_core::{WorkerInfo, WorkerStatus, DEFAULT_NAMESPACE};
+    use tokio::sync::mpsc;
+
+    use crate::control_plane::state::ConnectedWorker;
+
+    #[tokio::test]
+    async fn test_cancel_task_waits_for_worker_ack() {
+        let state = AppState::new();
+        let worker_id = WorkerId::new("worker-1");
+        let (tx, mut rx) = mpsc::channel(8);
+        state.workers.write().await.insert(
+            worker_id.clone(),
+            ConnectedWorker {
+                info: WorkerInfo::new(worker_id.clone(), "host"),
+                status: WorkerStatus::Busy,
+                active_runs: 1,
+                max_concurrent_runs: 10,
+                interactive_slots: 0,
+                last_heartbeat: chrono::Utc::now(),
+                draining_since: None,
+                tx,
+                shutdown: tokio_util::sync::CancellationToken::new(),
+            },
+        );
+
+        let mut task = Task::new("general", "{}", "test");
+        let mut run = RunSummary::new(worker_id.clone());
+        run.start();
+        task.add_run(run);
+        task.status = TaskStatus::Running;
+        let task_id = task.id.clone();
+        state.tasks.write().await.insert(task_id.clone(), task);
+
+        // Simulated worker: acknowledge the cancel with a terminal status
+        let worker_state = state.clone();
+        tokio::spawn(async move {
+            if let Some(RunServerMessage {
+                payload: Some(ServerPayload::CancelRun(cancel)),
+            }) = rx.recv().await
+            {
+                worker_state
+                    .finalize_run(
+                        &RunId::new(&cancel.run_id),
+                        RunStatus::Cancelled,
+                        Some(FailureReason::CancelledByUser),
+                        None,
+                    )
+                    .await;
+            }
+        });
+
+        let scheduler = Scheduler::new(state.clone());
+        let task = scheduler.cancel_task(&task_id, "test").await.unwrap();
+
+        assert_eq!(task.status, TaskStatus::Cancelled);
+        assert_eq!(task.runs[0].status, RunStatus::Cancelled);
+        assert!(matches!(
+            scheduler.cancel_task(&task_id, "again").await,
+            Err(SchedulerError::TaskAlreadyTerminal(..))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_interactive_slots_reserved_from_batch() {
+        let state = AppState::new();
+        let worker_id = WorkerId::new("worker-1");
+        let (tx, _rx) = mpsc::channel(8);
+        state.workers.write().await.insert(
+            worker_id.clone(),
+            ConnectedWorker {
+                info: WorkerInfo::new(worker_id.clone(), "host")
+                    .with_agent(taskrun_core::AgentSpec::new("general")),
+                status: WorkerStatus::Busy,
+                active_runs: 2,
+                max_concurrent_runs: 2,
+                interactive_slots: 1,
+                last_heartbeat: chrono::Utc::now(),
+                draining_since: None,
+                tx,
+                shutdown: tokio_util::sync::CancellationToken::new(),
+            },
+        );
+
+        let scheduler = Scheduler::new(state.clone());
+        assert_eq!(
+            scheduler
+                .select_worker("general", DEFAULT_NAMESPACE, TaskPriority::Batch)
+                .await,
+            None
+        );
+        assert_eq!(
+            scheduler
+                .select_worker("general", DEFAULT_NAMESPACE, TaskPriority::Interactive)
+                .await,
+            Some(worker_id.clone())
+        );
+
+        state
+            .workers
+            .write()
+            .await
+            .get_mut(&worker_id)
+            .unwrap()
+            .active_runs = 3;
+        assert_eq!(
+            scheduler
+                .select_worker("general", DEFAULT_NAMESPACE, TaskPriority::Interactive)
+                .await,
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn test_select_worker_stays_in_namespace() {
+        let state = AppState::new();
+        let worker_id = WorkerId::new("worker-1");
+        let (tx, _rx) = mpsc::channel(8);
+        state.workers.write().await.insert(
+            worker_id.clone(),
+            ConnectedWorker {
+                info: WorkerInfo::new(worker_id.clone(), "host")
+                    .with_namespace("team-a")
+                    .with_agent(taskrun_core::AgentSpec::new("general")),
+                status: WorkerStatus::Idle,
+                active_runs: 0,
+                max_concurrent_runs: 2,
+                interactive_slots: 1,
+                last_heartbeat: chrono::Utc::now(),
+                draining_since: None,
+                tx,
+                shutdown: tokio_util::sync::CancellationToken::new(),
+            },
+        );
+
+        let scheduler = Scheduler::new(state);
+        assert_eq!(
+            scheduler
+                .select_worker("general", DEFAULT_NAMESPACE, TaskPriority::Batch)
+                .await,
+            None
+        );
+        assert_eq!(
+            scheduler
+                .select_worker("general", "team-a", TaskPriority::Batch)
+                .await,
+            Some(worker_id)
+        );
+    }
 }
@@ -0,0 +1,267 @@
+//! Recurring task schedules.
+//!
+//! A schedule pairs a cron expression with an agent and an input template.
+//! The `ScheduleManager` owns the set of schedules and persists every change
+//! through a `ScheduleStore`; the `ScheduleTicker` materializes a task each
+//! time a schedule fires.
+//!
+//! Cron expressions are evaluated in UTC and accept 5 fields, or 6 with a
+//! leading seconds field. If the server was down when a schedule was due, it
+//! fires once on startup and then resumes its normal cadence.
+
+pub mod store;
+mod ticker;
+
+pub use store::{ScheduleStore, ScheduleStoreConfig, ScheduleStoreError};
+pub use ticker::ScheduleTicker;
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use croner::Cron;
+use thiserror::Error;
+use tokio::sync::RwLock;
+use tracing::info;
+
+use taskrun_core::{Schedule, ScheduleId};
+
+use crate::control_plane::state::{resolve_prefix, AppState, IdLookupError, UiNotification};
+
+/// Schedule errors.
+#[derive(Debug, Error)]
+pub enum ScheduleError {
+    #[error("Invalid cron expression '{expr}': {reason}")]
+    InvalidCron { expr: String, reason: String },
+
+    #[error("agent_name is required")]
+    MissingAgent,
+
+    #[error(transparent)]
+    Lookup(#[from] IdLookupError),
+
+    #[error("Failed to persist schedules: {0}")]
+    Store(#[from] ScheduleStoreError),
+}
+
+/// Parse a cron expression (5 fields, or 6 with leading seconds).
+pub fn parse_cron(expr: &str) -> Result<Cron, ScheduleError> {
+    Cron::new(expr)
+        .with_seconds_optional()
+        .parse()
+        .map_err(|e| ScheduleError::InvalidCron {
+            expr: expr.to_string(),
+            reason: e.to_string(),
+        })
+}
+
+/// Next time `expr` fires strictly after `after`.
+pub fn next_fire_after(expr: &str, after: DateTime<Utc>) -> Result<DateTime<Utc>, ScheduleError> {
+    parse_cron(expr)?
+        .find_next_occurrence(&after, false)
+        .map_err(|e| ScheduleError::InvalidCron {
+            expr: expr.to_string(),
+            reason: e.to_string(),
+        })
+}
+
+/// Owns all schedules and keeps the store in sync.
+pub struct ScheduleManager {
+    state: Arc<AppState>,
+    store: Arc<dyn ScheduleStore>,
+    schedules: RwLock<HashMap<ScheduleId, Schedule>>,
+}
+
+impl ScheduleManager {
+    /// Create a manager, loading existing schedules from `store`.
+    pub async fn open(
+        state: Arc<AppState>,
+        store: Arc<dyn ScheduleStore>,
+    ) -> Result<Arc<Self>, ScheduleError> {
+        let schedules = store
+            .load()
+            .await?
+            .into_iter()
+            .map(|s| (s.id.clone(), s))
+            .collect::<HashMap<_, _>>();
+
+        if !schedules.is_empty() {
+            info!(count = schedules.len(), "Loaded schedules");
+        }
+
+        Ok(Arc::new(Self {
+            state,
+            store,
+            schedules: RwLock::new(schedules),
+        }))
+    }
+
+    /// Shared application state used to create tasks.
+    pub(crate) fn state(&self) -> &Arc<AppState> {
+        &self.state
+    }
+
+    /// Validate and add a new schedule.
+    pub async fn create(&self, mut schedule: Schedule) -> Result<Schedule, ScheduleError> {
+        if schedule.agent_name.is_empty() {
+            return Err(ScheduleError::MissingAgent);
+        }
+        schedule.next_fire_at = Some(next_fire_after(&schedule.cron_expr, Utc::now())?);
+
+        info!(
+            schedule_id = %schedule.id,
+            cron = %schedule.cron_expr,
+            agent = %schedule.agent_name,
+            "Creating schedule"
+        );
+
+        let mut schedules = self.schedules.write().await;
+        schedules.insert(schedule.id.clone(), schedule.clone());
+        self.persist(&schedules).await?;
+        drop(schedules);
+
+        self.notify_updated(&schedule);
+        Ok(schedule)
+    }
+
+    /// All schedules, soonest next fire first (paused schedules last).
+    pub async fn list(&self) -> Vec<Schedule> {
+        let mut list: Vec<Schedule> = self.schedules.read().await.values().cloned().collect();
+        list.sort_by_key(|s| (s.next_fire_at.is_none(), s.next_fire_at, s.created_at));
+        list
+    }
+
+    /// Resolve a full or unique-prefix schedule ID.
+    pub async fn resolve_id(&self, prefix: &str) -> Result<ScheduleId, IdLookupError> {
+        let schedules = self.schedules.read().await;
+        resolve_prefix("Schedule", prefix, schedules.keys().map(|id| id.as_str()))
+            .map(ScheduleId::new)
+    }
+
+    /// Pause or resume a schedule. Resuming recomputes the next fire time
+    /// from now, so firings missed while paused are skipped.
+    pub async fn set_paused(
+        &self,
+        schedule_id: &ScheduleId,
+        paused: bool,
+    ) -> Result<Schedule, ScheduleError> {
+        let mut schedules = self.schedules.write().await;
+        let schedule = schedules
+            .get_mut(schedule_id)
+            .ok_or_else(|| IdLookupError::NotFound {
+                kind: "Schedule",
+                prefix: schedule_id.to_string(),
+            })?;
+
+        if schedule.paused != paused {
+            schedule.paused = paused;
+            schedule.next_fire_at = if paused {
+                None
+            } else {
+                Some(next_fire_after(&schedule.cron_expr, Utc::now())?)
+            };
+            info!(schedule_id = %schedule_id, paused, "Schedule pause state changed");
+        }
+
+        let schedule = schedule.clone();
+        self.persist(&schedules).await?;
+        drop(schedules);
+
+        self.notify_updated(&schedule);
+        Ok(schedule)
+    }
+
+    /// Delete a schedule. Tasks it already created are kept.
+    pub async fn delete(&self, schedule_id: &ScheduleId) -> Result<Schedule, ScheduleError> {
+        let mut schedules = self.schedules.write().await;
+        let schedule = schedules
+            .remove(schedule_id)
+            .ok_or_else(|| IdLookupError::NotFound {
+                kind: "Schedule",
+                prefix: schedule_id.to_string(),
+            })?;
+        self.persist(&schedules).await?;
+        drop(schedules);
+
+        info!(schedule_id = %schedule_id, "Deleted schedule");
+        self.state.notify_ui(UiNotification::ScheduleDeleted {
+            schedule_id: schedule_id.clone(),
+        });
+        Ok(schedule)
+    }
+
+    /// Announce every schedule to the UI (used once at startup).
+    pub async fn notify_all(&self) {
+        for schedule in self.list().await {
+            self.notify_updated(&schedule);
+        }
+    }
+
+    fn notify_updated(&self, schedule: &Schedule) {
+        self.state.notify_ui(UiNotification::ScheduleUpdated {
+            schedule: schedule.clone(),
+        });
+    }
+
+    async fn persist(
+        &self,
+        schedules: &HashMap<ScheduleId, Schedule>,
+    ) -> Result<(), ScheduleError> {
+        let all: Vec<Schedule> = schedules.values().cloned().collect();
+        self.store.save(&all).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::control_plane::schedules::store::InMemoryScheduleStore;
+
+    #[test]
+    fn test_next_fire_accepts_five_and_six_fields() {
+        let after = DateTime::parse_from_rfc3339("2024-01-01T10:07:30Z")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        let next = next_fire_after("*/15 * * * *", after).unwrap();
+        assert_eq!(next.to_rfc3339(), "2024-01-01T10:15:00+00:00");
+
+        let next = next_fire_after("0 0 12 * * *", after).unwrap();
+        assert_eq!(next.to_rfc3339(), "2024-01-01T12:00:00+00:00");
+
+        assert!(matches!(
+            next_fire_after("not a cron", after),
+            Err(ScheduleError::InvalidCron { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_pause_resume_delete_persist() {
+        let store: Arc<dyn ScheduleStore> = Arc::new(InMemoryScheduleStore::new());
+        let manager = ScheduleManager::open(AppState::new(), store.clone())
+            .await
+            .unwrap();
+
+        let created = manager
+            .create(Schedule::new("0 * * * *", "general", "{}", "test"))
+            .await
+            .unwrap();
+        assert!(created.next_fire_at.is_some());
+
+        let prefix = &created.id.as_str()[..8];
+        let id = manager.resolve_id(prefix).await.unwrap();
+
+        let paused = manager.set_paused(&id, true).await.unwrap();
+        assert!(paused.paused);
+        assert!(paused.next_fire_at.is_none());
+        assert!(store.load().await.unwrap()[0].paused);
+
+        let resumed = manager.set_paused(&id, false).await.unwrap();
+        assert!(resumed.next_fire_at.is_some());
+
+        manager.delete(&id).await.unwrap();
+        assert!(store.load().await.unwrap().is_empty());
+        assert!(manager.list().await.is_empty());
+    }
+}
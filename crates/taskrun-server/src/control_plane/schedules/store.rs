@@ -0,0 +1,139 @@
+//! Persistence for schedules.
+//!
+//! Schedules are few and change rarely, so stores save the full set on every
+//! change instead of keeping an append log like the event store.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use thiserror::Error;
+use tokio::sync::{Mutex, RwLock};
+
+use taskrun_core::Schedule;
+
+/// Schedule store errors.
+#[derive(Debug, Error)]
+pub enum ScheduleStoreError {
+    #[error("I/O error on schedule store '{path}': {source}")]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+
+    #[error("Failed to (de)serialize schedules: {0}")]
+    Serialization(#[from] serde_json::Error),
+}
+
+pub type Result<T> = std::result::Result<T, ScheduleStoreError>;
+
+/// Storage backend for schedules.
+#[async_trait]
+pub trait ScheduleStore: Send + Sync {
+    /// Load all stored schedules.
+    async fn load(&self) -> Result<Vec<Schedule>>;
+
+    /// Replace the stored schedules with `schedules`.
+    async fn save(&self, schedules: &[Schedule]) -> Result<()>;
+}
+
+/// Which schedule store backend to use.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum ScheduleStoreConfig {
+    /// Keep schedules in memory only.
+    #[default]
+    Memory,
+    /// Persist schedules to a JSON file.
+    File { path: PathBuf },
+}
+
+impl ScheduleStoreConfig {
+    /// Build the configured schedule store.
+    pub fn build(&self) -> Arc<dyn ScheduleStore> {
+        match self {
+            ScheduleStoreConfig::Memory => Arc::new(InMemoryScheduleStore::new()),
+            ScheduleStoreConfig::File { path } => Arc::new(JsonScheduleStore::new(path)),
+        }
+    }
+}
+
+/// Schedule store that keeps the last saved set in memory.
+#[derive(Default)]
+pub struct InMemoryScheduleStore {
+    schedules: RwLock<Vec<Schedule>>,
+}
+
+impl InMemoryScheduleStore {
+    /// Create an empty in-memory store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl ScheduleStore for InMemoryScheduleStore {
+    async fn load(&self) -> Result<Vec<Schedule>> {
+        Ok(self.schedules.read().await.clone())
+    }
+
+    async fn save(&self, schedules: &[Schedule]) -> Result<()> {
+        *self.schedules.write().await = schedules.to_vec();
+        Ok(())
+    }
+}
+
+/// Schedule store backed by a single JSON file, rewritten atomically on save.
+pub struct JsonScheduleStore {
+    path: PathBuf,
+    /// Serializes writers so the temp file is never shared.
+    write_lock: Mutex<()>,
+}
+
+impl JsonScheduleStore {
+    /// Create a store at `path`. The file is created on first save.
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            write_lock: Mutex::new(()),
+        }
+    }
+
+    fn io_err(&self, source: std::io::Error) -> ScheduleStoreError {
+        ScheduleStoreError::Io {
+            path: self.path.clone(),
+            source,
+        }
+    }
+}
+
+#[async_trait]
+impl ScheduleStore for JsonScheduleStore {
+    async fn load(&self) -> Result<Vec<Schedule>> {
+        match tokio::fs::read_to_string(&self.path).await {
+            Ok(contents) if contents.trim().is_empty() => Ok(Vec::new()),
+            Ok(contents) => Ok(serde_json::from_str(&contents)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+            Err(e) => Err(self.io_err(e)),
+        }
+    }
+
+    async fn save(&self, schedules: &[Schedule]) -> Result<()> {
+        let contents = serde_json::to_string_pretty(schedules)?;
+        let _guard = self.write_lock.lock().await;
+
+        if let Some(parent) = self.path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .map_err(|e| self.io_err(e))?;
+        }
+
+        let tmp_path = self.path.with_extension("json.tmp");
+        tokio::fs::write(&tmp_path, contents)
+            .await
+            .map_err(|e| self.io_err(e))?;
+        tokio::fs::rename(&tmp_path, &self.path)
+            .await
+            .map_err(|e| self.io_err(e))
+    }
+}
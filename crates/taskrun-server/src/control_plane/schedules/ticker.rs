@@ -0,0 +1,195 @@
+//! Schedule ticker - creates tasks when schedules fire.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use tracing::{info, warn};
+
+use taskrun_core::{Schedule, ScheduleId, Task, TaskId};
+use taskrun_proto::trace::TraceContext;
+
+use super::{next_fire_after, ScheduleManager};
+use crate::control_plane::audit::AuditAction;
+use crate::control_plane::quotas;
+use crate::control_plane::scheduler::{trace_created, Scheduler};
+use crate::control_plane::state::UiNotification;
+
+/// Label set on every task created by a schedule.
+pub const SCHEDULE_ID_LABEL: &str = "schedule_id";
+
+/// Periodically fires due schedules.
+pub struct ScheduleTicker {
+    manager: Arc<ScheduleManager>,
+    interval: Duration,
+}
+
+impl ScheduleTicker {
+    /// Create a ticker that checks schedules once per second.
+    pub fn new(manager: Arc<ScheduleManager>) -> Self {
+        Self {
+            manager,
+            interval: Duration::from_secs(1),
+        }
+    }
+
+    /// Run the ticker loop forever.
+    pub async fn run(self) {
+        info!("Schedule ticker started");
+
+        let mut interval = tokio::time::interval(self.interval);
+        loop {
+            interval.tick().await;
+            self.tick(Utc::now()).await;
+        }
+    }
+
+    /// Fire every schedule that is due at `now`, returning the tasks created.
+    pub async fn tick(&self, now: DateTime<Utc>) -> Vec<(ScheduleId, TaskId)> {
+        let mut fired: Vec<(Schedule, Task)> = Vec::new();
+        {
+            let mut schedules = self.manager.schedules.write().await;
+            for schedule in schedules.values_mut() {
+                let fire_time = match schedule.next_fire_at {
+                    Some(at) if !schedule.paused && at <= now => at,
+                    _ => continue,
+                };
+
+                let task = build_task(schedule, fire_time);
+                schedule.last_fired_at = Some(fire_time);
+                schedule.last_task_id = Some(task.id.clone());
+                // Compute from `now` so a long outage fires once, not once per missed slot
+                schedule.next_fire_at = match next_fire_after(&schedule.cron_expr, now) {
+                    Ok(next) => Some(next),
+                    Err(e) => {
+                        warn!(schedule_id = %schedule.id, error = %e, "Schedule has no next fire time");
+                        None
+                    }
+                };
+
+                fired.push((schedule.clone(), task));
+            }
+
+            if fired.is_empty() {
+                return Vec::new();
+            }
+
+            if let Err(e) = self.manager.persist(&schedules).await {
+                warn!(error = %e, "Failed to persist schedules after firing");
+            }
+        }
+
+        let state = self.manager.state();
+        let scheduler = Scheduler::new(state.clone());
+        let mut created = Vec::with_capacity(fired.len());
+
+        for (schedule, task) in fired {
+            let task = trace_created(task, &TraceContext::new());
+            let task_id = task.id.clone();
+            let agent = task.agent_name.clone();
+
+            if let Err(e) = state.toggles.check_accepting().await {
+                warn!(schedule_id = %schedule.id, error = %e, "Skipping scheduled task");
+                continue;
+            }
+            if let Err(e) = quotas::check_create(state, &task.namespace, &task.created_by).await {
+                warn!(schedule_id = %schedule.id, error = %e, "Skipping scheduled task");
+                continue;
+            }
+
+            info!(
+                schedule_id = %schedule.id,
+                task_id = %task_id,
+                agent = %agent,
+                "Schedule fired"
+            );
+
+            state
+                .audit(
+                    &task.created_by,
+                    AuditAction::TaskCreated,
+                    task_id.as_str(),
+                    &task,
+                )
+                .await;
+            let notification = UiNotification::task_created(&task);
+            state.tasks.write().await.insert(task_id.clone(), task);
+            state.notify_ui(notification);
+
+            if let Err(e) = scheduler.assign_task(&task_id).await {
+                warn!(
+                    schedule_id = %schedule.id,
+                    task_id = %task_id,
+                    error = %e,
+                    "Scheduled task created but not assigned"
+                );
+            }
+
+            self.manager.notify_updated(&schedule);
+            created.push((schedule.id, task_id));
+        }
+
+        created
+    }
+}
+
+/// Build the task a schedule creates when it fires at `fire_time`.
+fn build_task(schedule: &Schedule, fire_time: DateTime<Utc>) -> Task {
+    let mut task = Task::new(
+        &schedule.agent_name,
+        schedule.render_input(fire_time),
+        format!("schedule:{}", schedule.id),
+    )
+    .with_label(SCHEDULE_ID_LABEL, schedule.id.as_str());
+    for (k, v) in &schedule.labels {
+        task.labels.insert(k.clone(), v.clone());
+    }
+    if let Some(secs) = schedule.timeout_seconds {
+        task = task.with_timeout_seconds(secs);
+    }
+    task
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::control_plane::schedules::store::InMemoryScheduleStore;
+    use crate::control_plane::state::AppState;
+
+    #[tokio::test]
+    async fn test_tick_creates_task_once_per_fire() {
+        let state = AppState::new();
+        let manager = ScheduleManager::open(state.clone(), Arc::new(InMemoryScheduleStore::new()))
+            .await
+            .unwrap();
+        let schedule = manager
+            .create(Schedule::new(
+                "* * * * *",
+                "general",
+                r#"{"id":"{{schedule_id}}"}"#,
+                "test",
+            ))
+            .await
+            .unwrap();
+        let first_fire = schedule.next_fire_at.unwrap();
+
+        let ticker = ScheduleTicker::new(manager.clone());
+        assert!(ticker
+            .tick(first_fire - chrono::Duration::seconds(1))
+            .await
+            .is_empty());
+
+        let created = ticker.tick(first_fire).await;
+        assert_eq!(created.len(), 1);
+        assert!(ticker.tick(first_fire).await.is_empty());
+
+        let tasks = state.tasks.read().await;
+        let task = &tasks[&created[0].1];
+        assert_eq!(task.input_json, format!(r#"{{"id":"{}"}}"#, schedule.id));
+        assert_eq!(task.labels[SCHEDULE_ID_LABEL], schedule.id.as_str());
+
+        let updated = &manager.list().await[0];
+        assert_eq!(updated.last_fired_at, Some(first_fire));
+        assert!(updated.next_fire_at.unwrap() > first_fire);
+    }
+}
@@ -0,0 +1,315 @@
+//! Full-text search over tasks and run output.
+//!
+//! Shared by the `SearchTasks` RPC, `GET /v1/search` and the TUI. A query is a
+//! list of words, all of which must occur in a task's input, labels, agent
+//! name, or the output of one of its runs. Words match case-insensitively on
+//! whole words; a trailing `*` matches a prefix (`deploy*`).
+//!
+//! Task fields are scanned directly, since tasks are held in memory. Output
+//! can be spilled to disk or S3, so its words are indexed as chunks arrive and
+//! dropped when retention removes the output.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use serde::Serialize;
+use thiserror::Error;
+use tokio::sync::RwLock;
+
+use taskrun_core::{RunId, Task};
+
+use crate::control_plane::state::AppState;
+
+/// Results returned when the caller does not ask for a number.
+pub const DEFAULT_SEARCH_LIMIT: usize = 50;
+
+/// Most results a caller can ask for.
+pub const MAX_SEARCH_LIMIT: usize = 500;
+
+/// Search errors.
+#[derive(Debug, Error)]
+pub enum SearchError {
+    #[error("Search query has no words")]
+    EmptyQuery,
+}
+
+/// Where a query word was found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchField {
+    Input,
+    Labels,
+    Agent,
+    Output,
+}
+
+impl SearchField {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SearchField::Input => "input",
+            SearchField::Labels => "labels",
+            SearchField::Agent => "agent",
+            SearchField::Output => "output",
+        }
+    }
+}
+
+/// A task matching a search.
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchHit {
+    pub task: Task,
+    /// Fields any query word was found in.
+    pub matched_fields: Vec<SearchField>,
+}
+
+/// One query word.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Term {
+    word: String,
+    prefix: bool,
+}
+
+impl Term {
+    fn matches(&self, word: &str) -> bool {
+        if self.prefix {
+            word.starts_with(&self.word)
+        } else {
+            word == self.word
+        }
+    }
+}
+
+/// Split text into lowercase words.
+fn words(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+}
+
+fn parse_query(query: &str) -> Vec<Term> {
+    query
+        .split_whitespace()
+        .flat_map(|token| {
+            let prefix = token.ends_with('*');
+            let mut terms: Vec<Term> = words(token)
+                .map(|word| Term {
+                    word,
+                    prefix: false,
+                })
+                .collect();
+            if let Some(last) = terms.last_mut() {
+                last.prefix = prefix;
+            }
+            terms
+        })
+        .collect()
+}
+
+#[derive(Debug, Default)]
+struct IndexInner {
+    /// Runs whose output contains each word.
+    runs_by_word: BTreeMap<String, HashSet<RunId>>,
+    /// Words indexed for each run, to drop them with the output.
+    words_by_run: HashMap<RunId, HashSet<String>>,
+    /// Trailing word of the last chunk, which the next chunk may continue.
+    tails: HashMap<RunId, String>,
+}
+
+/// Word index over run output.
+#[derive(Debug, Default)]
+pub struct OutputIndex {
+    inner: RwLock<IndexInner>,
+}
+
+impl OutputIndex {
+    /// Index a chunk of a run's output.
+    ///
+    /// A word split across chunks is indexed whole, and also by the part in
+    /// the first chunk in case the output ends there.
+    pub async fn add(&self, run_id: &RunId, content: &str) {
+        let mut inner = self.inner.write().await;
+        let tail = inner.tails.remove(run_id).unwrap_or_default();
+        let text = tail + content;
+
+        let continues = text
+            .chars()
+            .last()
+            .is_some_and(|c| c.is_alphanumeric() || c == '_');
+        let found: Vec<String> = words(&text).collect();
+        if continues {
+            if let Some(last) = found.last() {
+                inner.tails.insert(run_id.clone(), last.clone());
+            }
+        }
+
+        let IndexInner {
+            runs_by_word,
+            words_by_run,
+            ..
+        } = &mut *inner;
+        let run_words = words_by_run.entry(run_id.clone()).or_default();
+        for word in found {
+            if run_words.insert(word.clone()) {
+                runs_by_word.entry(word).or_default().insert(run_id.clone());
+            }
+        }
+    }
+
+    /// Drop a run's output from the index.
+    pub async fn remove(&self, run_id: &RunId) {
+        let mut inner = self.inner.write().await;
+        inner.tails.remove(run_id);
+        for word in inner.words_by_run.remove(run_id).unwrap_or_default() {
+            if let Some(runs) = inner.runs_by_word.get_mut(&word) {
+                runs.remove(run_id);
+                if runs.is_empty() {
+                    inner.runs_by_word.remove(&word);
+                }
+            }
+        }
+    }
+
+    /// Runs whose output contains a word matching `term`.
+    async fn runs_matching(&self, term: &Term) -> HashSet<RunId> {
+        let inner = self.inner.read().await;
+        if term.prefix {
+            inner
+                .runs_by_word
+                .range(term.word.clone()..)
+                .take_while(|(word, _)| word.starts_with(&term.word))
+                .flat_map(|(_, runs)| runs.iter().cloned())
+                .collect()
+        } else {
+            inner
+                .runs_by_word
+                .get(&term.word)
+                .cloned()
+                .unwrap_or_default()
+        }
+    }
+}
+
+/// Search tasks, newest first. `limit` of 0 means `DEFAULT_SEARCH_LIMIT`.
+pub async fn search(
+    state: &AppState,
+    query: &str,
+    namespace: Option<&str>,
+    limit: usize,
+) -> Result<Vec<SearchHit>, SearchError> {
+    let terms = parse_query(query);
+    if terms.is_empty() {
+        return Err(SearchError::EmptyQuery);
+    }
+    let limit = match limit {
+        0 => DEFAULT_SEARCH_LIMIT,
+        n => n.min(MAX_SEARCH_LIMIT),
+    };
+
+    let mut output_runs = Vec::with_capacity(terms.len());
+    for term in &terms {
+        output_runs.push(state.output_index.runs_matching(term).await);
+    }
+
+    let tasks = state.tasks.read().await;
+    let mut hits: Vec<SearchHit> = tasks
+        .values()
+        .filter(|task| namespace.map_or(true, |ns| task.namespace == ns))
+        .filter_map(|task| {
+            let fields: [(SearchField, Vec<String>); 3] = [
+                (SearchField::Input, words(&task.input_json).collect()),
+                (
+                    SearchField::Labels,
+                    task.labels
+                        .iter()
+                        .flat_map(|(k, v)| words(k).chain(words(v)))
+                        .collect(),
+                ),
+                (SearchField::Agent, words(&task.agent_name).collect()),
+            ];
+
+            let mut matched = Vec::new();
+            for (term, runs) in terms.iter().zip(&output_runs) {
+                let before = matched.len();
+                for (field, field_words) in &fields {
+                    if field_words.iter().any(|w| term.matches(w)) {
+                        matched.push(*field);
+                    }
+                }
+                if task.runs.iter().any(|run| runs.contains(&run.run_id)) {
+                    matched.push(SearchField::Output);
+                }
+                if matched.len() == before {
+                    return None;
+                }
+            }
+
+            matched.sort();
+            matched.dedup();
+            Some(SearchHit {
+                task: task.clone(),
+                matched_fields: matched,
+            })
+        })
+        .collect();
+
+    hits.sort_by_key(|hit| std::cmp::Reverse(hit.task.created_at));
+    hits.truncate(limit);
+    Ok(hits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use taskrun_core::{RunSummary, WorkerId};
+
+    #[tokio::test]
+    async fn test_search_tasks_and_output() {
+        let state = AppState::new();
+        let run = RunSummary::new(WorkerId::new("worker-1"));
+        let run_id = run.run_id.clone();
+        {
+            let mut tasks = state.tasks.write().await;
+            let mut task = Task::new("support_triage", r#"{"prompt":"Refund order"}"#, "test");
+            task.labels
+                .insert("team".to_string(), "billing".to_string());
+            task.add_run(run);
+            tasks.insert(task.id.clone(), task);
+
+            let other = Task::new("general", r#"{"prompt":"Say hi"}"#, "test");
+            tasks.insert(other.id.clone(), other);
+        }
+        // "deployment" arrives split across two chunks
+        state.append_output(&run_id, 0, "Starting deploy").await;
+        state.append_output(&run_id, 1, "ment now").await;
+
+        let hits = search(&state, "refund BILLING", None, 0).await.unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(
+            hits[0].matched_fields,
+            vec![SearchField::Input, SearchField::Labels]
+        );
+
+        let hits = search(&state, "deployment support*", None, 0)
+            .await
+            .unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(
+            hits[0].matched_fields,
+            vec![SearchField::Agent, SearchField::Output]
+        );
+
+        assert!(search(&state, "refund hi", None, 0)
+            .await
+            .unwrap()
+            .is_empty());
+        assert!(matches!(
+            search(&state, " * ", None, 0).await,
+            Err(SearchError::EmptyQuery)
+        ));
+
+        state.output_index.remove(&run_id).await;
+        assert!(search(&state, "deployment", None, 0)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -0,0 +1,321 @@
+//! AdminService implementation - export and import control plane state,
+//! runtime toggles, bootstrap tokens, certificate revocation.
+//!
+//! Every method requires an admin client certificate.
+
+use std::pin::Pin;
+use std::sync::Arc;
+
+use tonic::{Request, Response, Status, Streaming};
+use tracing::info;
+
+use taskrun_core::WorkerId;
+use taskrun_proto::pb::{
+    CreateBootstrapTokenRequest, CreateBootstrapTokenResponse, ExportStateRequest,
+    GetTogglesRequest, ImportStateResponse, ListBootstrapTokensRequest,
+    ListBootstrapTokensResponse, ListRevokedCertificatesRequest, ListRevokedCertificatesResponse,
+    RevokeBootstrapTokenRequest, RevokeCertificateRequest, RuntimeToggles, StateChunk,
+    UpdateTogglesRequest,
+};
+use taskrun_proto::{AdminService, AdminServiceServer};
+
+use crate::control_plane::audit::AuditAction;
+use crate::control_plane::crypto::{BootstrapToken, RevocationError, RevokedCertificate};
+use crate::control_plane::enrollment;
+use crate::control_plane::rbac::{authorize, Role};
+use crate::control_plane::service::mtls::validate_worker_id_format;
+use crate::control_plane::snapshot::{self, SnapshotError};
+use crate::control_plane::state::AppState;
+use crate::control_plane::toggles::{self, ToggleError};
+
+/// Size of the chunks an archive is streamed in.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// gRPC AdminService implementation.
+pub struct AdminServiceImpl {
+    state: Arc<AppState>,
+}
+
+impl AdminServiceImpl {
+    /// Create a new AdminServiceImpl.
+    pub fn new(state: Arc<AppState>) -> Self {
+        Self { state }
+    }
+
+    /// Convert into a tonic server.
+    pub fn into_server(self) -> AdminServiceServer<Self> {
+        AdminServiceServer::new(self)
+    }
+}
+
+impl From<SnapshotError> for Status {
+    fn from(err: SnapshotError) -> Self {
+        match err {
+            SnapshotError::Parse(_)
+            | SnapshotError::UnknownFormat(_)
+            | SnapshotError::UnsupportedVersion(_) => Status::invalid_argument(err.to_string()),
+            SnapshotError::Store(_) => Status::internal(err.to_string()),
+        }
+    }
+}
+
+fn to_proto(token: BootstrapToken) -> taskrun_proto::pb::BootstrapToken {
+    taskrun_proto::pb::BootstrapToken {
+        id: token.id().to_string(),
+        description: token.description.unwrap_or_default(),
+        created_at_ms: token.created_at.timestamp_millis(),
+        expires_at_ms: token.expires_at.timestamp_millis(),
+        consumed: token.consumed,
+        worker_id: token.worker_id.unwrap_or_default(),
+        namespace: token.namespace,
+        bound_worker_id: token.bound_worker_id.unwrap_or_default(),
+    }
+}
+
+fn revoked_to_proto(revoked: RevokedCertificate) -> taskrun_proto::pb::RevokedCertificate {
+    taskrun_proto::pb::RevokedCertificate {
+        serial: revoked.serial.unwrap_or_default(),
+        worker_id: revoked.worker_id.unwrap_or_default(),
+        revoked_at_ms: revoked.revoked_at.timestamp_millis(),
+        reason: revoked.reason.unwrap_or_default(),
+    }
+}
+
+impl From<RevocationError> for Status {
+    fn from(err: RevocationError) -> Self {
+        match err {
+            RevocationError::MissingTarget | RevocationError::InvalidSerial(_) => {
+                Status::invalid_argument(err.to_string())
+            }
+            RevocationError::Io { .. } | RevocationError::Serialization(_) => {
+                Status::internal(err.to_string())
+            }
+        }
+    }
+}
+
+impl From<ToggleError> for Status {
+    fn from(err: ToggleError) -> Self {
+        Status::internal(err.to_string())
+    }
+}
+
+#[tonic::async_trait]
+impl AdminService for AdminServiceImpl {
+    type ExportStateStream =
+        Pin<Box<dyn tokio_stream::Stream<Item = Result<StateChunk, Status>> + Send>>;
+
+    async fn export_state(
+        &self,
+        request: Request<ExportStateRequest>,
+    ) -> Result<Response<Self::ExportStateStream>, Status> {
+        let caller = authorize(&request, Role::Admin)?;
+        let snapshot = snapshot::export(&self.state).await?;
+        let bytes = snapshot::encode(&snapshot)?;
+        info!(
+            tasks = snapshot.tasks.len(),
+            events = snapshot.events.len(),
+            bytes = bytes.len(),
+            exported_by = %caller.identity,
+            "State exported via gRPC"
+        );
+        self.state
+            .audit(
+                &caller.identity,
+                AuditAction::StateExported,
+                "state",
+                &serde_json::json!({
+                    "tasks": snapshot.tasks.len(),
+                    "events": snapshot.events.len(),
+                    "enrollment_tokens": snapshot.enrollment_tokens.len(),
+                }),
+            )
+            .await;
+
+        let chunks: Vec<StateChunk> = bytes
+            .chunks(CHUNK_SIZE)
+            .map(|data| StateChunk {
+                data: data.to_vec(),
+            })
+            .collect();
+        Ok(Response::new(Box::pin(tokio_stream::iter(
+            chunks.into_iter().map(Ok),
+        ))))
+    }
+
+    async fn import_state(
+        &self,
+        request: Request<Streaming<StateChunk>>,
+    ) -> Result<Response<ImportStateResponse>, Status> {
+        let caller = authorize(&request, Role::Admin)?;
+        let mut stream = request.into_inner();
+        let mut bytes = Vec::new();
+        while let Some(chunk) = stream.message().await? {
+            bytes.extend_from_slice(&chunk.data);
+        }
+
+        let summary = snapshot::import(&self.state, snapshot::decode(&bytes)?).await?;
+        info!(imported_by = %caller.identity, "State imported via gRPC");
+        self.state
+            .audit(
+                &caller.identity,
+                AuditAction::StateImported,
+                "state",
+                &summary,
+            )
+            .await;
+
+        Ok(Response::new(ImportStateResponse {
+            version: summary.version,
+            tasks_imported: summary.tasks_imported as u32,
+            tasks_skipped: summary.tasks_skipped as u32,
+            events_imported: summary.events_imported as u32,
+            tokens_imported: summary.tokens_imported as u32,
+            runs_interrupted: summary.runs_interrupted as u32,
+        }))
+    }
+
+    async fn get_toggles(
+        &self,
+        request: Request<GetTogglesRequest>,
+    ) -> Result<Response<RuntimeToggles>, Status> {
+        authorize(&request, Role::Admin)?;
+        Ok(Response::new(self.state.toggles.get().await.into()))
+    }
+
+    async fn update_toggles(
+        &self,
+        request: Request<UpdateTogglesRequest>,
+    ) -> Result<Response<RuntimeToggles>, Status> {
+        let caller = authorize(&request, Role::Admin)?;
+        let update = request.into_inner().into();
+        let toggles = toggles::update(&self.state, &caller.identity, update).await?;
+        Ok(Response::new(toggles.into()))
+    }
+
+    async fn create_bootstrap_token(
+        &self,
+        request: Request<CreateBootstrapTokenRequest>,
+    ) -> Result<Response<CreateBootstrapTokenResponse>, Status> {
+        let caller = authorize(&request, Role::Admin)?;
+        // Tokens are minted by admins of every namespace
+        caller.require_unbound()?;
+        let req = request.into_inner();
+        let namespace =
+            caller.namespace_for_new((!req.namespace.is_empty()).then_some(&req.namespace))?;
+        let worker_id = (!req.worker_id.is_empty()).then_some(req.worker_id);
+        if let Some(worker_id) = &worker_id {
+            validate_worker_id_format(&WorkerId::new(worker_id.as_str()))?;
+        }
+        let (bootstrap_token, token) = enrollment::create_token(
+            &self.state,
+            (!req.description.is_empty()).then_some(req.description),
+            (req.validity_hours > 0).then_some(req.validity_hours as u64),
+            namespace,
+            worker_id,
+        )
+        .await;
+        info!(token_id = %token.id(), created_by = %caller.identity, "Bootstrap token created via gRPC");
+        self.state
+            .audit(
+                &caller.identity,
+                AuditAction::BootstrapTokenCreated,
+                token.id(),
+                &token,
+            )
+            .await;
+        Ok(Response::new(CreateBootstrapTokenResponse {
+            token: Some(to_proto(token)),
+            bootstrap_token,
+        }))
+    }
+
+    async fn list_bootstrap_tokens(
+        &self,
+        request: Request<ListBootstrapTokensRequest>,
+    ) -> Result<Response<ListBootstrapTokensResponse>, Status> {
+        authorize(&request, Role::Admin)?;
+        let tokens = enrollment::list_tokens(&self.state)
+            .await
+            .into_iter()
+            .map(to_proto)
+            .collect();
+        Ok(Response::new(ListBootstrapTokensResponse { tokens }))
+    }
+
+    async fn revoke_bootstrap_token(
+        &self,
+        request: Request<RevokeBootstrapTokenRequest>,
+    ) -> Result<Response<taskrun_proto::pb::BootstrapToken>, Status> {
+        let caller = authorize(&request, Role::Admin)?;
+        caller.require_unbound()?;
+        let token = enrollment::revoke_token(&self.state, &request.into_inner().id).await?;
+        info!(token_id = %token.id(), revoked_by = %caller.identity, "Bootstrap token revoked via gRPC");
+        self.state
+            .audit(
+                &caller.identity,
+                AuditAction::BootstrapTokenRevoked,
+                token.id(),
+                &token,
+            )
+            .await;
+        Ok(Response::new(to_proto(token)))
+    }
+
+    async fn revoke_certificate(
+        &self,
+        request: Request<RevokeCertificateRequest>,
+    ) -> Result<Response<taskrun_proto::pb::RevokedCertificate>, Status> {
+        let caller = authorize(&request, Role::Admin)?;
+        caller.require_unbound()?;
+        let ca = self
+            .state
+            .ca
+            .as_ref()
+            .ok_or_else(|| Status::unavailable("Certificate authority not configured"))?;
+        let req = request.into_inner();
+        let revoked = ca
+            .revocations()
+            .revoke(
+                (!req.serial.is_empty()).then_some(req.serial.as_str()),
+                (!req.worker_id.is_empty()).then_some(req.worker_id.as_str()),
+                (!req.reason.is_empty()).then_some(req.reason),
+            )
+            .await?;
+        let target = revoked
+            .serial
+            .clone()
+            .or_else(|| revoked.worker_id.clone())
+            .unwrap_or_default();
+        info!(target = %target, revoked_by = %caller.identity, "Certificate revoked via gRPC");
+        self.state
+            .audit(
+                &caller.identity,
+                AuditAction::CertificateRevoked,
+                &target,
+                &revoked,
+            )
+            .await;
+        // Streams of revoked workers close themselves (see run_service)
+        Ok(Response::new(revoked_to_proto(revoked)))
+    }
+
+    async fn list_revoked_certificates(
+        &self,
+        request: Request<ListRevokedCertificatesRequest>,
+    ) -> Result<Response<ListRevokedCertificatesResponse>, Status> {
+        authorize(&request, Role::Admin)?;
+        let certificates = self
+            .state
+            .ca
+            .as_ref()
+            .map(|ca| ca.revocations().list())
+            .unwrap_or_default()
+            .into_iter()
+            .map(revoked_to_proto)
+            .collect();
+        Ok(Response::new(ListRevokedCertificatesResponse {
+            certificates,
+        }))
+    }
+}
@@ -0,0 +1,161 @@
+//! ApiKeyService implementation - manage HTTP API keys over mTLS.
+//!
+//! Every method requires an admin client certificate.
+
+use std::sync::Arc;
+
+use tonic::{Request, Response, Status};
+use tracing::info;
+
+use taskrun_proto::pb::{
+    CreateApiKeyRequest, CreateApiKeyResponse, ListApiKeysRequest, ListApiKeysResponse,
+    RevokeApiKeyRequest,
+};
+use taskrun_proto::{ApiKeyService, ApiKeyServiceServer};
+
+use crate::control_plane::api_keys::{ApiKey, ApiKeyError, ApiKeyManager};
+use crate::control_plane::audit::AuditAction;
+use crate::control_plane::rbac::{authorize, Role};
+use crate::control_plane::state::AppState;
+
+/// gRPC ApiKeyService implementation.
+pub struct ApiKeyServiceImpl {
+    keys: Arc<ApiKeyManager>,
+    state: Arc<AppState>,
+}
+
+impl ApiKeyServiceImpl {
+    /// Create a new ApiKeyServiceImpl.
+    pub fn new(keys: Arc<ApiKeyManager>, state: Arc<AppState>) -> Self {
+        Self { keys, state }
+    }
+
+    /// Convert into a tonic server.
+    pub fn into_server(self) -> ApiKeyServiceServer<Self> {
+        ApiKeyServiceServer::new(self)
+    }
+}
+
+fn to_proto(key: ApiKey) -> taskrun_proto::pb::ApiKey {
+    taskrun_proto::pb::ApiKey {
+        id: key.id,
+        name: key.name,
+        prefix: key.prefix,
+        namespace: key.namespace.unwrap_or_default(),
+        created_at_ms: key.created_at.timestamp_millis(),
+        role: key.role.to_string(),
+    }
+}
+
+impl From<ApiKeyError> for Status {
+    fn from(err: ApiKeyError) -> Self {
+        match err {
+            ApiKeyError::MissingName => Status::invalid_argument(err.to_string()),
+            ApiKeyError::Lookup(e) => e.into(),
+            ApiKeyError::Namespace(e) => e.into(),
+            ApiKeyError::Io { .. } | ApiKeyError::Serialization(_) => {
+                Status::internal(err.to_string())
+            }
+        }
+    }
+}
+
+#[tonic::async_trait]
+impl ApiKeyService for ApiKeyServiceImpl {
+    async fn create_api_key(
+        &self,
+        request: Request<CreateApiKeyRequest>,
+    ) -> Result<Response<CreateApiKeyResponse>, Status> {
+        let caller = authorize(&request, Role::Admin)?;
+        let req = request.into_inner();
+        let role = if req.role.is_empty() {
+            Role::default()
+        } else {
+            req.role.parse().map_err(Status::invalid_argument)?
+        };
+        // Admins bound to a namespace only hand out keys for it
+        let namespace =
+            caller.scope((!req.namespace.is_empty()).then_some(req.namespace.as_str()))?;
+        let (api_key, key) = self.keys.create(&req.name, role, namespace).await?;
+        info!(key_id = %api_key.id, created_by = %caller.identity, "API key created via gRPC");
+        self.state
+            .audit(
+                &caller.identity,
+                AuditAction::ApiKeyCreated,
+                &api_key.id,
+                &api_key,
+            )
+            .await;
+        Ok(Response::new(CreateApiKeyResponse {
+            api_key: Some(to_proto(api_key)),
+            key,
+        }))
+    }
+
+    async fn list_api_keys(
+        &self,
+        request: Request<ListApiKeysRequest>,
+    ) -> Result<Response<ListApiKeysResponse>, Status> {
+        let caller = authorize(&request, Role::Admin)?;
+        let api_keys = self
+            .keys
+            .list(caller.namespace.as_deref())
+            .await
+            .into_iter()
+            .map(to_proto)
+            .collect();
+        Ok(Response::new(ListApiKeysResponse { api_keys }))
+    }
+
+    async fn revoke_api_key(
+        &self,
+        request: Request<RevokeApiKeyRequest>,
+    ) -> Result<Response<taskrun_proto::pb::ApiKey>, Status> {
+        let caller = authorize(&request, Role::Admin)?;
+        let key = self
+            .keys
+            .revoke(&request.into_inner().id, caller.namespace.as_deref())
+            .await?;
+        info!(key_id = %key.id, revoked_by = %caller.identity, "API key revoked via gRPC");
+        self.state
+            .audit(&caller.identity, AuditAction::ApiKeyRevoked, &key.id, &key)
+            .await;
+        Ok(Response::new(to_proto(key)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_api_key_methods_require_an_admin_certificate() {
+        let keys = ApiKeyManager::open(None).await.unwrap();
+        let (existing, _) = keys.create("ci", Role::Admin, None).await.unwrap();
+        let service = ApiKeyServiceImpl::new(keys.clone(), AppState::new());
+
+        let created = service
+            .create_api_key(Request::new(CreateApiKeyRequest {
+                name: "intruder".to_string(),
+                role: "admin".to_string(),
+                namespace: String::new(),
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(created.code(), tonic::Code::Unauthenticated);
+        let listed = service
+            .list_api_keys(Request::new(ListApiKeysRequest {}))
+            .await
+            .unwrap_err();
+        assert_eq!(listed.code(), tonic::Code::Unauthenticated);
+        let revoked = service
+            .revoke_api_key(Request::new(RevokeApiKeyRequest {
+                id: existing.id.clone(),
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(revoked.code(), tonic::Code::Unauthenticated);
+
+        assert_eq!(keys.list(None).await, vec![existing]);
+    }
+}
@@ -0,0 +1,102 @@
+//! ApprovalService implementation - decide tool uses escalated by workers.
+//!
+//! Listing needs a readonly certificate, deciding needs an operator.
+
+use std::sync::Arc;
+
+use tonic::{Request, Response, Status};
+use tracing::info;
+
+use taskrun_proto::pb::{DecideApprovalRequest, ListApprovalsRequest, ListApprovalsResponse};
+use taskrun_proto::{ApprovalService, ApprovalServiceServer};
+
+use crate::control_plane::approvals::{self, Approval, ApprovalError};
+use crate::control_plane::rbac::{authorize, Role};
+use crate::control_plane::state::AppState;
+
+/// gRPC ApprovalService implementation.
+pub struct ApprovalServiceImpl {
+    state: Arc<AppState>,
+}
+
+impl ApprovalServiceImpl {
+    /// Create a new ApprovalServiceImpl.
+    pub fn new(state: Arc<AppState>) -> Self {
+        Self { state }
+    }
+
+    /// Convert into a tonic server.
+    pub fn into_server(self) -> ApprovalServiceServer<Self> {
+        ApprovalServiceServer::new(self)
+    }
+}
+
+fn to_proto(approval: Approval) -> taskrun_proto::pb::Approval {
+    taskrun_proto::pb::Approval {
+        id: approval.id,
+        run_id: approval.run_id.into_inner(),
+        task_id: approval.task_id.into_inner(),
+        namespace: approval.namespace,
+        agent_name: approval.agent_name,
+        worker_id: approval.worker_id.into_inner(),
+        tool_name: approval.tool_name,
+        input_json: approval.input_json,
+        requested_at_ms: approval.requested_at.timestamp_millis(),
+        expires_at_ms: approval.expires_at.timestamp_millis(),
+        default_allow: approval.default_allow,
+    }
+}
+
+impl From<ApprovalError> for Status {
+    fn from(err: ApprovalError) -> Self {
+        match err {
+            ApprovalError::Lookup(e) => e.into(),
+            ApprovalError::Expired(_) => Status::failed_precondition(err.to_string()),
+            ApprovalError::WorkerGone(_) => Status::unavailable(err.to_string()),
+        }
+    }
+}
+
+#[tonic::async_trait]
+impl ApprovalService for ApprovalServiceImpl {
+    async fn list_approvals(
+        &self,
+        request: Request<ListApprovalsRequest>,
+    ) -> Result<Response<ListApprovalsResponse>, Status> {
+        let caller = authorize(&request, Role::Readonly)?;
+        let approvals = self
+            .state
+            .approvals
+            .list(caller.namespace.as_deref())
+            .await
+            .into_iter()
+            .map(to_proto)
+            .collect();
+        Ok(Response::new(ListApprovalsResponse { approvals }))
+    }
+
+    async fn decide_approval(
+        &self,
+        request: Request<DecideApprovalRequest>,
+    ) -> Result<Response<taskrun_proto::pb::Approval>, Status> {
+        let caller = authorize(&request, Role::Operator)?;
+        let req = request.into_inner();
+        let approval = approvals::decide(
+            &self.state,
+            &req.id,
+            caller.namespace.as_deref(),
+            req.allowed,
+            req.always_allow,
+            &req.reason,
+            &caller.identity,
+        )
+        .await?;
+        info!(
+            approval_id = %approval.id,
+            allowed = req.allowed,
+            decided_by = %caller.identity,
+            "Tool approval decided via gRPC"
+        );
+        Ok(Response::new(to_proto(approval)))
+    }
+}
@@ -1,10 +1,63 @@
 //! gRPC service implementations.
 
+pub mod admin_service;
+pub mod api_key_service;
+pub mod approval_service;
 pub mod mtls;
 pub mod run_service;
+pub mod schedule_service;
 pub mod task_service;
 pub mod worker_service;
+pub mod workflow_service;
 
+pub use admin_service::AdminServiceImpl;
+pub use api_key_service::ApiKeyServiceImpl;
+pub use approval_service::ApprovalServiceImpl;
 pub use run_service::RunServiceImpl;
+pub use schedule_service::ScheduleServiceImpl;
 pub use task_service::TaskServiceImpl;
 pub use worker_service::WorkerServiceImpl;
+pub use workflow_service::WorkflowServiceImpl;
+
+use tonic::Status;
+
+use crate::control_plane::quotas::QuotaExceeded;
+use crate::control_plane::rbac::NamespaceError;
+use crate::control_plane::state::IdLookupError;
+use crate::control_plane::toggles::MaintenanceMode;
+
+impl From<IdLookupError> for Status {
+    fn from(err: IdLookupError) -> Self {
+        match err {
+            IdLookupError::NotFound { .. } => Status::not_found(err.to_string()),
+            IdLookupError::Ambiguous { .. } => Status::invalid_argument(err.to_string()),
+        }
+    }
+}
+
+impl From<NamespaceError> for Status {
+    fn from(err: NamespaceError) -> Self {
+        match err {
+            NamespaceError::Denied { .. } => Status::permission_denied(err.to_string()),
+            NamespaceError::Invalid(_) => Status::invalid_argument(err.to_string()),
+        }
+    }
+}
+
+impl From<QuotaExceeded> for Status {
+    fn from(err: QuotaExceeded) -> Self {
+        let mut status = Status::resource_exhausted(err.to_string());
+        if let Some(secs) = err.retry_after_secs {
+            if let Ok(value) = secs.to_string().parse() {
+                status.metadata_mut().insert("retry-after", value);
+            }
+        }
+        status
+    }
+}
+
+impl From<MaintenanceMode> for Status {
+    fn from(err: MaintenanceMode) -> Self {
+        Status::unavailable(err.to_string())
+    }
+}
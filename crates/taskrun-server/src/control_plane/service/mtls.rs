@@ -7,10 +7,35 @@
 //!
 //! Our CA only signs certificates with CN="worker:<worker_id>", so any
 //! connected worker is authenticated by virtue of having a valid certificate.
+//! Certificates revoked through the admin API, and those of a previous CA
+//! after its rotation window, are refused on every call by the
+//! `reject_untrusted` interceptor.
+
+use std::sync::Arc;
 
 use taskrun_core::WorkerId;
 use tonic::Status;
 
+use crate::control_plane::state::AppState;
+
+/// gRPC interceptor refusing calls made with a client certificate that is
+/// revoked or no longer trusted.
+#[allow(clippy::result_large_err)]
+pub fn reject_untrusted(
+    state: Arc<AppState>,
+) -> impl FnMut(tonic::Request<()>) -> Result<tonic::Request<()>, Status> + Clone {
+    move |request| {
+        if let Some(ca) = &state.ca {
+            let certs = request.peer_certs().unwrap_or_default();
+            if let Some(cert) = certs.first() {
+                ca.check_client_cert(cert.as_ref())
+                    .map_err(|e| Status::unauthenticated(e.to_string()))?;
+            }
+        }
+        Ok(request)
+    }
+}
+
 /// Validate that a worker_id follows the expected mTLS certificate format.
 ///
 /// With mTLS, workers must have certificates signed by our CA, which only
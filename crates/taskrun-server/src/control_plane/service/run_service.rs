@@ -2,25 +2,35 @@
 
 use std::pin::Pin;
 use std::sync::Arc;
-use std::time::Duration;
 
-use tokio::sync::{mpsc, Mutex};
+use chrono::Utc;
+use thiserror::Error;
+use tokio::sync::{mpsc, watch, Mutex};
 use tokio_stream::wrappers::ReceiverStream;
 use tokio_stream::StreamExt;
+use tokio_util::sync::CancellationToken;
 use tonic::{Request, Response, Status, Streaming};
 use tracing::{error, info, warn};
 
 use taskrun_core::{
-    ChatMessage, ChatRole, RunEvent, RunEventType, RunId, RunStatus, TaskId, TaskStatus, WorkerId,
-    WorkerInfo, WorkerStatus,
+    validate_namespace, Artifact, ChatMessage, ChatRole, FailureReason, RunEvent, RunEventType,
+    RunId, RunMilestone, RunResultSummary, RunStatus, TaskId, TaskStatus, WorkerId, WorkerInfo,
+    WorkerStatus, DEFAULT_NAMESPACE,
 };
 use taskrun_proto::pb::run_client_message::Payload as ClientPayload;
 use taskrun_proto::pb::{
-    RunChatMessage, RunClientMessage, RunEvent as ProtoRunEvent, RunOutputChunk, RunServerMessage,
-    RunStatusUpdate, WorkerHeartbeat, WorkerHello,
+    ArtifactUpload, RunChatMessage, RunClientMessage, RunEvent as ProtoRunEvent, RunOutputChunk,
+    RunServerMessage, RunStatusUpdate, WorkerHeartbeat, WorkerHello, WorkerUpdate,
 };
 use taskrun_proto::{RunService, RunServiceServer};
 
+use crate::control_plane::approvals;
+use crate::control_plane::conversations;
+use crate::control_plane::crypto::{
+    extract_spiffe_id, extract_worker_id_from_cert, CertExtractError, RevocationList,
+};
+use crate::control_plane::disconnect;
+use crate::control_plane::rbac::{self, Role};
 use crate::control_plane::service::mtls::validate_worker_id_format;
 use crate::control_plane::state::{AppState, ConnectedWorker, StreamEvent, UiNotification};
 
@@ -50,6 +60,22 @@ impl RunService for RunServiceImpl {
         &self,
         request: Request<Streaming<RunClientMessage>>,
     ) -> Result<Response<Self::StreamConnectStream>, Status> {
+        // A "worker:<name>@<namespace>" certificate pins the worker to that
+        // namespace; "worker:<name>" and certificates naming no role to the
+        // default one
+        let cert_namespace = rbac::authorize(&request, Role::Operator).map_or_else(
+            |_| Some(DEFAULT_NAMESPACE.to_string()),
+            |caller| caller.namespace,
+        );
+        // Kept to close the stream if the certificate is revoked while open
+        let peer_cert = request
+            .peer_certs()
+            .and_then(|certs| certs.first().map(|cert| cert.as_ref().to_vec()));
+        let mut revocations = self
+            .state
+            .ca
+            .as_ref()
+            .map(|ca| ca.revocations().subscribe());
         let mut inbound = request.into_inner();
         let state = self.state.clone();
 
@@ -62,9 +88,25 @@ impl RunService for RunServiceImpl {
         let state_clone = state.clone();
         let tx_clone = tx.clone();
 
+        // Cancelled by DisconnectWorker to end the stream from this side
+        let shutdown = CancellationToken::new();
+
         // Spawn task to process incoming messages
         tokio::spawn(async move {
-            while let Some(result) = inbound.next().await {
+            // Kept for the disconnect event, after the worker may be gone
+            let mut namespace = None;
+            while let Some(result) = tokio::select! {
+                result = inbound.next() => result,
+                _ = shutdown.cancelled() => None,
+                e = until_untrusted(&state_clone, peer_cert.as_deref(), &mut revocations) => {
+                    warn!(error = %e, "Closing stream of worker whose certificate is no longer trusted");
+                    let id = worker_id_clone.lock().await.clone();
+                    if let Some(id) = id {
+                        disconnect::disconnect_worker(&state_clone, &id, &e.to_string(), true).await;
+                    }
+                    None
+                }
+            } {
                 match result {
                     Ok(msg) => {
                         if let Some(payload) = msg.payload {
@@ -73,18 +115,38 @@ impl RunService for RunServiceImpl {
                                     handle_worker_hello(
                                         &state_clone,
                                         &worker_id_clone,
+                                        peer_cert.as_deref(),
+                                        cert_namespace.as_deref(),
                                         hello,
                                         tx_clone.clone(),
+                                        shutdown.clone(),
                                     )
                                     .await;
+                                    let id = worker_id_clone.lock().await.clone();
+                                    if let Some(id) = id {
+                                        let workers = state_clone.workers.read().await;
+                                        if let Some(worker) = workers.get(&id) {
+                                            namespace = Some(worker.info.namespace.clone());
+                                        }
+                                    }
                                 }
                                 ClientPayload::Heartbeat(hb) => {
+                                    if state_clone.chaos.close_stream() {
+                                        warn!(worker_id = %hb.worker_id, "Chaos: closing worker stream");
+                                        break;
+                                    }
                                     handle_heartbeat(&state_clone, hb).await;
                                 }
                                 ClientPayload::StatusUpdate(update) => {
+                                    if let Some(delay) = state_clone.chaos.status_delay() {
+                                        tokio::time::sleep(delay).await;
+                                    }
                                     handle_status_update(&state_clone, update).await;
                                 }
                                 ClientPayload::OutputChunk(chunk) => {
+                                    if state_clone.chaos.drop_output() {
+                                        continue;
+                                    }
                                     handle_output_chunk(&state_clone, chunk).await;
                                 }
                                 ClientPayload::Event(event) => {
@@ -93,6 +155,44 @@ impl RunService for RunServiceImpl {
                                 ClientPayload::ChatMessage(chat_msg) => {
                                     handle_chat_message(&state_clone, chat_msg).await;
                                 }
+                                ClientPayload::Update(update) => {
+                                    let id = worker_id_clone.lock().await.clone();
+                                    match id {
+                                        Some(id) => {
+                                            handle_worker_update(&state_clone, &id, update).await
+                                        }
+                                        None => warn!("Worker update before WorkerHello"),
+                                    }
+                                }
+                                ClientPayload::ArtifactUpload(upload) => {
+                                    let id = worker_id_clone.lock().await.clone();
+                                    match id {
+                                        Some(id) => {
+                                            handle_artifact_upload(&state_clone, &id, upload).await
+                                        }
+                                        None => warn!("Artifact upload before WorkerHello"),
+                                    }
+                                }
+                                ClientPayload::AssignmentAck(ack) => {
+                                    let id = worker_id_clone.lock().await.clone();
+                                    match id {
+                                        Some(id) => {
+                                            state_clone
+                                                .assignments
+                                                .acknowledged(&RunId::new(&ack.run_id), &id);
+                                        }
+                                        None => warn!("Assignment ack before WorkerHello"),
+                                    }
+                                }
+                                ClientPayload::ToolApproval(request) => {
+                                    let id = worker_id_clone.lock().await.clone();
+                                    match id {
+                                        Some(id) => {
+                                            approvals::request(&state_clone, &id, request).await
+                                        }
+                                        None => warn!("Tool approval request before WorkerHello"),
+                                    }
+                                }
                             }
                         }
                     }
@@ -106,10 +206,30 @@ impl RunService for RunServiceImpl {
             // Worker disconnected - clean up
             if let Some(id) = worker_id_clone.lock().await.take() {
                 info!(worker_id = %id, "Worker disconnected");
-                state_clone.workers.write().await.remove(&id);
+                {
+                    // Unless it already reconnected on another stream
+                    let mut workers = state_clone.workers.write().await;
+                    if workers
+                        .get(&id)
+                        .is_some_and(|w| w.tx.same_channel(&tx_clone))
+                    {
+                        workers.remove(&id);
+                        state_clone.assignments.worker_disconnected(&id);
+                    }
+                }
+
+                // Its runs can no longer be told a decision
+                for approval_id in state_clone.approvals.remove_worker(&id).await {
+                    state_clone.notify_ui(UiNotification::ApprovalResolved { approval_id });
+                }
 
                 // Notify UI
-                state_clone.notify_ui(UiNotification::WorkerDisconnected { worker_id: id });
+                if let Some(namespace) = namespace {
+                    state_clone.notify_ui(UiNotification::WorkerDisconnected {
+                        worker_id: id,
+                        namespace,
+                    });
+                }
             }
         });
 
@@ -120,17 +240,103 @@ impl RunService for RunServiceImpl {
     }
 }
 
+/// Resolves once `cert` is revoked, or its CA is rotated out; never without
+/// a CA or a certificate.
+async fn until_untrusted(
+    state: &AppState,
+    cert: Option<&[u8]>,
+    changes: &mut Option<watch::Receiver<()>>,
+) -> CertExtractError {
+    if let (Some(ca), Some(cert), Some(changes)) = (&state.ca, cert, changes.as_mut()) {
+        let window_end = ca
+            .previous()
+            .filter(|previous| previous.issued(cert))
+            .map(|previous| previous.trusted_until());
+        loop {
+            let window_closed = async {
+                match window_end {
+                    Some(end) => {
+                        tokio::time::sleep((end - Utc::now()).to_std().unwrap_or_default()).await
+                    }
+                    None => std::future::pending().await,
+                }
+            };
+            tokio::select! {
+                changed = changes.changed() => {
+                    // The sender lives as long as the CA
+                    if changed.is_err() {
+                        break;
+                    }
+                }
+                _ = window_closed => {}
+            }
+            if let Err(e) = ca.check_client_cert(cert) {
+                return e;
+            }
+        }
+    }
+    std::future::pending().await
+}
+
+/// Why a worker hello does not match the worker's certificate.
+#[derive(Debug, Error)]
+enum HelloCertError {
+    #[error("no client certificate")]
+    MissingCert,
+
+    #[error(transparent)]
+    Cert(#[from] CertExtractError),
+
+    #[error("certificate is for worker '{0}'")]
+    OtherWorker(String),
+
+    #[error("certificate is for agent '{cert}', not '{advertised}'")]
+    OtherAgent { cert: String, advertised: String },
+}
+
+/// Check that the certificate names the worker of `info`, by its SPIFFE ID
+/// or CN, and that an `/agent/<name>` segment in the SPIFFE ID matches every
+/// agent advertised.
+fn check_hello_cert(
+    state: &AppState,
+    cert: Option<&[u8]>,
+    info: &WorkerInfo,
+) -> Result<(), HelloCertError> {
+    let cert = cert.ok_or(HelloCertError::MissingCert)?;
+    let no_revocations = RevocationList::default();
+    let revocations = state
+        .ca
+        .as_ref()
+        .map_or(&no_revocations, |ca| ca.revocations());
+    let cert_worker_id = extract_worker_id_from_cert(cert, revocations)?;
+    if cert_worker_id != info.worker_id.as_str() {
+        return Err(HelloCertError::OtherWorker(cert_worker_id));
+    }
+    if let Some(agent) = extract_spiffe_id(cert)?.and_then(|id| id.agent) {
+        if let Some(other) = info.agents.iter().find(|a| a.name != agent) {
+            return Err(HelloCertError::OtherAgent {
+                cert: agent,
+                advertised: other.name.clone(),
+            });
+        }
+    }
+    Ok(())
+}
+
 async fn handle_worker_hello(
     state: &Arc<AppState>,
     worker_id_holder: &Arc<Mutex<Option<WorkerId>>>,
+    peer_cert: Option<&[u8]>,
+    cert_namespace: Option<&str>,
     hello: WorkerHello,
     tx: mpsc::Sender<RunServerMessage>,
+    shutdown: CancellationToken,
 ) {
     if let Some(info_proto) = hello.info {
         let info: WorkerInfo = info_proto.into();
         let worker_id = info.worker_id.clone();
 
-        // Validate worker_id format (mTLS ensures the worker has a valid cert)
+        // Validate worker_id format
         if let Err(e) = validate_worker_id_format(&worker_id) {
             error!(
                 worker_id = %worker_id,
@@ -140,11 +346,35 @@ async fn handle_worker_hello(
             return;
         }
 
+        // The hello is trusted only as far as the certificate vouches for it
+        if let Err(e) = check_hello_cert(state, peer_cert, &info) {
+            error!(
+                worker_id = %worker_id,
+                error = %e,
+                "Worker hello does not match its certificate"
+            );
+            return;
+        }
+
+        if let Err(e) = validate_namespace(&info.namespace) {
+            error!(worker_id = %worker_id, error = %e, "Worker namespace is invalid");
+            return;
+        }
+        if cert_namespace.is_some_and(|ns| ns != info.namespace) {
+            error!(
+                worker_id = %worker_id,
+                namespace = %info.namespace,
+                "Worker certificate is bound to another namespace"
+            );
+            return;
+        }
+
         let agent_names: Vec<&str> = info.agents.iter().map(|a| a.name.as_str()).collect();
         info!(
             worker_id = %worker_id,
             hostname = %info.hostname,
             version = %info.version,
+            namespace = %info.namespace,
             agents = ?agent_names,
             "Worker authenticated via mTLS"
         );
@@ -155,6 +385,7 @@ async fn handle_worker_hello(
         // Capture info for notification before move
         let hostname = info.hostname.clone();
         let agents: Vec<String> = info.agents.iter().map(|a| a.name.clone()).collect();
+        let labels = info.labels.clone();
 
         // Register worker in state
         let connected = ConnectedWorker {
@@ -162,8 +393,11 @@ async fn handle_worker_hello(
             status: WorkerStatus::Idle,
             active_runs: 0,
             max_concurrent_runs: 10,
+            interactive_slots: 0,
             last_heartbeat: chrono::Utc::now(),
+            draining_since: None,
             tx,
+            shutdown,
         };
 
         state
@@ -171,12 +405,16 @@ async fn handle_worker_hello(
             .write()
             .await
             .insert(worker_id.clone(), connected);
+        state
+            .assignments
+            .worker_connected(&worker_id, hello.acks_assignments);
 
         // Notify UI
         state.notify_ui(UiNotification::WorkerConnected {
             worker_id,
             hostname,
             agents,
+            labels,
         });
     } else {
         error!("WorkerHello received without WorkerInfo");
@@ -197,9 +435,22 @@ async fn handle_heartbeat(state: &Arc<AppState>, hb: WorkerHeartbeat) {
             _ => WorkerStatus::Idle,
         };
 
+        let recovered = matches!(
+            worker.status,
+            WorkerStatus::Degraded | WorkerStatus::Offline
+        );
+        // A drain ends only when the control plane says so
+        let status = if worker.draining_since.is_some() && status.can_accept_runs() {
+            WorkerStatus::Draining
+        } else {
+            status
+        };
+        let drained =
+            worker.draining_since.is_some() && worker.active_runs > 0 && hb.active_runs == 0;
         worker.status = status;
         worker.active_runs = hb.active_runs;
         worker.max_concurrent_runs = hb.max_concurrent_runs;
+        worker.interactive_slots = hb.interactive_slots;
         worker.last_heartbeat = chrono::Utc::now();
 
         info!(
@@ -209,19 +460,74 @@ async fn handle_heartbeat(state: &Arc<AppState>, hb: WorkerHeartbeat) {
             "Heartbeat received"
         );
 
+        if recovered {
+            info!(worker_id = %worker_id, "Worker heartbeat resumed");
+        }
+        if drained {
+            info!(worker_id = %worker_id, "Worker drained, no runs left");
+        }
+
         // Notify UI
         drop(workers); // Release lock before notification
+        if recovered {
+            state.notify_ui(UiNotification::WorkerStatusChanged {
+                worker_id: worker_id.clone(),
+                status,
+            });
+        }
+        if drained {
+            state.notify_ui(UiNotification::WorkerDrained {
+                worker_id: worker_id.clone(),
+            });
+        }
         state.notify_ui(UiNotification::WorkerHeartbeat {
             worker_id,
             status,
             active_runs: hb.active_runs,
             max_concurrent_runs: hb.max_concurrent_runs,
+            metrics: hb.metrics,
         });
     } else {
         warn!(worker_id = %hb.worker_id, "Heartbeat from unknown worker");
     }
 }
 
+/// Apply a worker's capability change to its registration in place.
+async fn handle_worker_update(state: &Arc<AppState>, worker_id: &WorkerId, update: WorkerUpdate) {
+    let notification = {
+        let mut workers = state.workers.write().await;
+        let Some(worker) = workers.get_mut(worker_id) else {
+            warn!(worker_id = %worker_id, "Update from unknown worker");
+            return;
+        };
+        if let Some(agents) = update.agents {
+            worker.info.agents = agents.agents.into_iter().map(Into::into).collect();
+        }
+        if let Some(labels) = update.labels {
+            worker.info.labels = labels.labels;
+        }
+        if let Some(max) = update.max_concurrent_runs {
+            worker.max_concurrent_runs = max;
+        }
+
+        let agents: Vec<String> = worker.info.agents.iter().map(|a| a.name.clone()).collect();
+        info!(
+            worker_id = %worker_id,
+            agents = ?agents,
+            labels = ?worker.info.labels,
+            max_concurrent_runs = worker.max_concurrent_runs,
+            "Worker capabilities updated"
+        );
+        UiNotification::WorkerUpdated {
+            worker_id: worker_id.clone(),
+            agents,
+            labels: worker.info.labels.clone(),
+            max_concurrent_runs: worker.max_concurrent_runs,
+        }
+    };
+    state.notify_ui(notification);
+}
+
 async fn handle_status_update(state: &Arc<AppState>, update: RunStatusUpdate) {
     let run_id = RunId::new(&update.run_id);
 
@@ -235,6 +541,34 @@ async fn handle_status_update(state: &Arc<AppState>, update: RunStatusUpdate) {
     for task in tasks.values_mut() {
         for run in &mut task.runs {
             if run.run_id == run_id {
+                // Ignore late updates for runs already finalized (e.g. reaped),
+                // unless they are about a follow-up of the conversation
+                if run.status.is_terminal() {
+                    let (task_id, current) = (task.id.clone(), run.status);
+                    drop(tasks);
+                    if state.conversations.in_progress(&run_id).await {
+                        let error_message =
+                            (!update.error_message.is_empty()).then_some(update.error_message);
+                        conversations::follow_up_status(
+                            state,
+                            &run_id,
+                            run_status,
+                            error_message,
+                            update.timestamp_ms,
+                        )
+                        .await;
+                        return;
+                    }
+                    warn!(
+                        task_id = %task_id,
+                        run_id = %run_id,
+                        current = ?current,
+                        reported = ?run_status,
+                        "Ignoring status update for finished run"
+                    );
+                    return;
+                }
+
                 // Log with full correlation
                 info!(
                     task_id = %task.id,
@@ -258,21 +592,38 @@ async fn handle_status_update(state: &Arc<AppState>, update: RunStatusUpdate) {
                     run.error_message = Some(update.error_message.clone());
                 }
 
+                // Record why the run did not complete. Workers that predate
+                // failure reasons only report a cancel as such.
+                let failure_reason: Option<FailureReason> = update.failure_reason().into();
+                let failure_reason = match run_status {
+                    RunStatus::Failed => failure_reason,
+                    RunStatus::Cancelled => {
+                        Some(failure_reason.unwrap_or(FailureReason::CancelledByUser))
+                    }
+                    _ => None,
+                };
+                if failure_reason.is_some() {
+                    run.failure_reason = failure_reason;
+                }
+
                 // Update backend_used if present
                 if let Some(backend) = &update.backend_used {
                     run.backend_used = Some(backend.clone().into());
                 }
 
+                // Record the run's environment if reported
+                if let Some(manifest) = &update.manifest {
+                    run.manifest = Some(manifest.clone().into());
+                }
+
                 // Capture worker_id before we might need to drop the lock
                 let worker_id = run.worker_id.clone();
                 let task_id = task.id.clone();
 
                 // Update task status based on run status
                 match run_status {
-                    RunStatus::Running => {
-                        if task.status == TaskStatus::Pending {
-                            task.status = TaskStatus::Running;
-                        }
+                    RunStatus::Running if task.status == TaskStatus::Pending => {
+                        task.status = TaskStatus::Running;
                     }
                     RunStatus::Completed => {
                         task.status = TaskStatus::Completed;
@@ -280,10 +631,12 @@ async fn handle_status_update(state: &Arc<AppState>, update: RunStatusUpdate) {
                     }
                     RunStatus::Failed => {
                         task.status = TaskStatus::Failed;
-                        info!(task_id = %task_id, run_id = %run_id, "Task failed");
+                        task.failure_reason = failure_reason;
+                        info!(task_id = %task_id, run_id = %run_id, reason = ?failure_reason, "Task failed");
                     }
                     RunStatus::Cancelled => {
                         task.status = TaskStatus::Cancelled;
+                        task.failure_reason = failure_reason;
                         info!(task_id = %task_id, run_id = %run_id, "Task cancelled");
                     }
                     _ => {}
@@ -308,6 +661,11 @@ async fn handle_status_update(state: &Arc<AppState>, update: RunStatusUpdate) {
                         }
                     }
                     drop(workers);
+
+                    // A finished run is no longer waiting on any tool use
+                    for approval_id in state.approvals.remove_run(&run_id).await {
+                        state.notify_ui(UiNotification::ApprovalResolved { approval_id });
+                    }
                 } else {
                     drop(tasks);
                 }
@@ -349,12 +707,7 @@ async fn handle_status_update(state: &Arc<AppState>, update: RunStatusUpdate) {
 
                 // Schedule cleanup for terminal status
                 if is_terminal {
-                    let state_clone = state.clone();
-                    let run_id_clone = run_id.clone();
-                    tokio::spawn(async move {
-                        tokio::time::sleep(Duration::from_secs(5)).await;
-                        state_clone.remove_stream_channel(&run_id_clone).await;
-                    });
+                    conversations::remove_stream_later(state, &run_id);
                 }
 
                 return;
@@ -368,14 +721,21 @@ async fn handle_status_update(state: &Arc<AppState>, update: RunStatusUpdate) {
 async fn handle_output_chunk(state: &Arc<AppState>, chunk: RunOutputChunk) {
     let run_id = RunId::new(&chunk.run_id);
 
-    // Find task_id for correlation
-    let task_id = {
+    // Find the task for correlation and quota accounting
+    let task = {
         let tasks = state.tasks.read().await;
         tasks
             .values()
             .find(|t| t.runs.iter().any(|r| r.run_id == run_id))
-            .map(|t| t.id.clone())
+            .map(|t| (t.id.clone(), t.namespace.clone(), t.created_by.clone()))
     };
+    if let Some((_, namespace, created_by)) = &task {
+        state
+            .quotas
+            .record_output(namespace, created_by, chunk.content.len() as u64)
+            .await;
+    }
+    let task_id = task.map(|(task_id, ..)| task_id);
 
     if let Some(ref task_id) = task_id {
         info!(
@@ -396,7 +756,9 @@ async fn handle_output_chunk(state: &Arc<AppState>, chunk: RunOutputChunk) {
 
     // Store output content (append to existing output for this run)
     if !chunk.content.is_empty() {
-        state.append_output(&run_id, &chunk.content).await;
+        state
+            .append_output(&run_id, chunk.seq, &chunk.content)
+            .await;
     }
 
     // Publish to stream channel for SSE subscribers
@@ -418,6 +780,7 @@ async fn handle_output_chunk(state: &Arc<AppState>, chunk: RunOutputChunk) {
         state.notify_ui(UiNotification::RunOutputChunk {
             run_id,
             task_id,
+            seq: chunk.seq,
             content: content_for_ui,
         });
     }
@@ -433,6 +796,9 @@ async fn handle_event(state: &Arc<AppState>, proto_event: ProtoRunEvent) {
         Ok(taskrun_proto::pb::RunEventType::OutputGenerated) => RunEventType::OutputGenerated,
         Ok(taskrun_proto::pb::RunEventType::ExecutionCompleted) => RunEventType::ExecutionCompleted,
         Ok(taskrun_proto::pb::RunEventType::ExecutionFailed) => RunEventType::ExecutionFailed,
+        Ok(taskrun_proto::pb::RunEventType::Milestone) => RunEventType::Milestone,
+        Ok(taskrun_proto::pb::RunEventType::Log) => RunEventType::Log,
+        Ok(taskrun_proto::pb::RunEventType::RunSummary) => RunEventType::RunSummary,
         _ => {
             warn!(event_id = %proto_event.id, "Unknown event type");
             return;
@@ -447,6 +813,7 @@ async fn handle_event(state: &Arc<AppState>, proto_event: ProtoRunEvent) {
         event_type,
         timestamp_ms: proto_event.timestamp_ms,
         metadata: proto_event.metadata.clone(),
+        artifacts: proto_event.artifacts.into_iter().map(Into::into).collect(),
     };
 
     info!(
@@ -470,8 +837,147 @@ async fn handle_event(state: &Arc<AppState>, proto_event: ProtoRunEvent) {
         metadata: proto_event.metadata.clone(),
     });
 
+    let milestone = RunMilestone::from_event(&event);
+    let result = RunResultSummary::from_event(&event);
+    let tool_event = tool_stream_event(&event);
+
     // Store the event
     state.store_event(event).await;
+
+    let run_id = RunId::new(&proto_event.run_id);
+    if let Some(milestone) = milestone {
+        record_milestone(state, &run_id, milestone).await;
+    }
+    if let Some(result) = result {
+        record_result(state, &run_id, result).await;
+    }
+    if let Some(tool_event) = tool_event {
+        state.publish_stream_event(&run_id, tool_event).await;
+    }
+}
+
+/// The stream event of a tool call or result, for SSE subscribers.
+fn tool_stream_event(event: &RunEvent) -> Option<StreamEvent> {
+    let metadata = &event.metadata;
+    match event.event_type {
+        RunEventType::ToolRequested => Some(StreamEvent::ToolCall {
+            call_id: event.id.to_string(),
+            tool_name: metadata.get("tool_name").cloned().unwrap_or_default(),
+            input_summary: metadata.get("input_summary").cloned(),
+            timestamp_ms: event.timestamp_ms,
+        }),
+        RunEventType::ToolCompleted => Some(StreamEvent::ToolResult {
+            call_id: metadata.get("call_id").cloned(),
+            tool_name: metadata.get("tool_name").cloned(),
+            is_error: metadata.get("is_error").is_some_and(|e| e == "true"),
+            timestamp_ms: event.timestamp_ms,
+        }),
+        _ => None,
+    }
+}
+
+/// Store a milestone on its run and stream it to SSE subscribers.
+async fn record_milestone(state: &Arc<AppState>, run_id: &RunId, milestone: RunMilestone) {
+    let recorded = {
+        let mut tasks = state.tasks.write().await;
+        match tasks
+            .values_mut()
+            .flat_map(|t| t.runs.iter_mut())
+            .find(|r| &r.run_id == run_id)
+        {
+            Some(run) => {
+                run.record_milestone(milestone.clone());
+                true
+            }
+            None => false,
+        }
+    };
+    if !recorded {
+        warn!(run_id = %run_id, milestone = %milestone.name, "Milestone for unknown run");
+        return;
+    }
+    state.notify_ui(UiNotification::RunMilestone {
+        run_id: run_id.clone(),
+        milestone: milestone.clone(),
+    });
+    state
+        .publish_stream_event(run_id, StreamEvent::Milestone { milestone })
+        .await;
+}
+
+/// Store a result summary on its run, for run list previews.
+async fn record_result(state: &Arc<AppState>, run_id: &RunId, result: RunResultSummary) {
+    let recorded = {
+        let mut tasks = state.tasks.write().await;
+        match tasks
+            .values_mut()
+            .flat_map(|t| t.runs.iter_mut())
+            .find(|r| &r.run_id == run_id)
+        {
+            Some(run) => {
+                run.result = Some(result.clone());
+                true
+            }
+            None => false,
+        }
+    };
+    if !recorded {
+        warn!(run_id = %run_id, "Result summary for unknown run");
+        return;
+    }
+    state.notify_ui(UiNotification::RunResult {
+        run_id: run_id.clone(),
+        result,
+    });
+}
+
+/// Store the contents of an artifact a worker uploaded for one of its runs.
+async fn handle_artifact_upload(
+    state: &Arc<AppState>,
+    worker_id: &WorkerId,
+    upload: ArtifactUpload,
+) {
+    let run_id = RunId::new(&upload.run_id);
+    let Some(artifact) = upload.artifact.map(Artifact::from) else {
+        warn!(run_id = %run_id, "Artifact upload without an artifact");
+        return;
+    };
+    let owner = {
+        let tasks = state.tasks.read().await;
+        tasks.values().find_map(|t| {
+            t.runs
+                .iter()
+                .find(|r| r.run_id == run_id && &r.worker_id == worker_id)
+                .map(|_| (t.id.clone(), t.namespace.clone()))
+        })
+    };
+    let Some((task_id, namespace)) = owner else {
+        warn!(run_id = %run_id, worker_id = %worker_id, "Artifact upload for a run of another worker");
+        return;
+    };
+    match state
+        .files
+        .add_artifact_chunk(
+            &task_id,
+            &run_id,
+            &namespace,
+            &artifact,
+            upload.offset,
+            upload.content,
+            upload.more,
+        )
+        .await
+    {
+        Ok(None) => {}
+        Ok(Some(file)) => info!(
+            task_id = %task_id,
+            run_id = %run_id,
+            name = %file.name,
+            size_bytes = file.size_bytes,
+            "Stored artifact"
+        ),
+        Err(e) => warn!(run_id = %run_id, name = %artifact.name, error = %e, "Artifact not stored"),
+    }
 }
 
 async fn handle_chat_message(state: &Arc<AppState>, chat_msg: RunChatMessage) {
@@ -523,10 +1029,12 @@ async fn handle_chat_message(state: &Arc<AppState>, chat_msg: RunChatMessage) {
     );
 
     // Create domain chat message
+    let attachments: Vec<Artifact> = proto_msg.attachments.into_iter().map(Into::into).collect();
     let message = ChatMessage {
         role,
         content: proto_msg.content.clone(),
         timestamp_ms: proto_msg.timestamp_ms,
+        attachments: attachments.clone(),
     };
 
     // Store the message
@@ -538,5 +1046,180 @@ async fn handle_chat_message(state: &Arc<AppState>, chat_msg: RunChatMessage) {
         task_id,
         role,
         content: proto_msg.content,
+        attachments,
     });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use std::collections::HashMap;
+    use taskrun_core::AgentSpec;
+    use taskrun_proto::pb;
+
+    /// A self-signed client certificate with `cn` and, if given, a SPIFFE ID.
+    fn client_cert(cn: &str, spiffe_id: Option<&str>) -> Vec<u8> {
+        let mut params = rcgen::CertificateParams::default();
+        params
+            .distinguished_name
+            .push(rcgen::DnType::CommonName, cn);
+        if let Some(uri) = spiffe_id {
+            params.subject_alt_names = vec![rcgen::SanType::URI(uri.try_into().unwrap())];
+        }
+        let key_pair = rcgen::KeyPair::generate().unwrap();
+        params.self_signed(&key_pair).unwrap().der().to_vec()
+    }
+
+    async fn hello_registers(state: &Arc<AppState>, cert: &[u8], info: WorkerInfo) -> bool {
+        let worker_id = info.worker_id.clone();
+        let (tx, _rx) = mpsc::channel(1);
+        handle_worker_hello(
+            state,
+            &Arc::new(Mutex::new(None)),
+            Some(cert),
+            Some(DEFAULT_NAMESPACE),
+            WorkerHello {
+                info: Some(info.into()),
+                acks_assignments: false,
+            },
+            tx,
+            CancellationToken::new(),
+        )
+        .await;
+        state.workers.read().await.contains_key(&worker_id)
+    }
+
+    #[tokio::test]
+    async fn test_hello_must_match_the_certificate() {
+        let state = AppState::new();
+
+        // A certificate for worker A can't say hello as worker B
+        let cert = client_cert("worker:a", Some("spiffe://taskrun/worker/a"));
+        let b = WorkerInfo::new(WorkerId::new("b"), "host");
+        assert!(!hello_registers(&state, &cert, b).await);
+        let b = WorkerInfo::new(WorkerId::new("b"), "host");
+        assert!(!hello_registers(&state, &client_cert("worker:a", None), b).await);
+
+        // A certificate bound to an agent can't advertise others
+        let cert = client_cert("worker:a", Some("spiffe://taskrun/worker/a/agent/general"));
+        let a = WorkerInfo::new(WorkerId::new("a"), "host")
+            .with_agent(AgentSpec::new("general"))
+            .with_agent(AgentSpec::new("deploy"));
+        assert!(!hello_registers(&state, &cert, a).await);
+
+        let a = WorkerInfo::new(WorkerId::new("a"), "host").with_agent(AgentSpec::new("general"));
+        assert!(hello_registers(&state, &cert, a).await);
+    }
+
+    #[tokio::test]
+    async fn test_unbound_worker_certificate_cannot_claim_another_namespace() {
+        let state = AppState::new();
+        let holder = Arc::new(Mutex::new(None));
+        let cert = client_cert("worker:dev", None);
+        let cert_namespace = rbac::Caller::from_common_name("worker:dev")
+            .unwrap()
+            .namespace;
+        let hello = |namespace: &str| WorkerHello {
+            info: Some(
+                WorkerInfo::new(WorkerId::new("dev"), "host")
+                    .with_namespace(namespace)
+                    .into(),
+            ),
+            acks_assignments: false,
+        };
+
+        let (tx, _rx) = mpsc::channel(1);
+        handle_worker_hello(
+            &state,
+            &holder,
+            Some(&cert),
+            cert_namespace.as_deref(),
+            hello("team-b"),
+            tx,
+            CancellationToken::new(),
+        )
+        .await;
+        assert!(state.workers.read().await.is_empty());
+        assert!(holder.lock().await.is_none());
+
+        let (tx, _rx) = mpsc::channel(1);
+        handle_worker_hello(
+            &state,
+            &holder,
+            Some(&cert),
+            cert_namespace.as_deref(),
+            hello(DEFAULT_NAMESPACE),
+            tx,
+            CancellationToken::new(),
+        )
+        .await;
+        assert!(state
+            .workers
+            .read()
+            .await
+            .contains_key(&WorkerId::new("dev")));
+    }
+
+    #[tokio::test]
+    async fn test_worker_update_is_applied_in_place() {
+        let state = AppState::new();
+        let worker_id = WorkerId::generate();
+        let (tx, _rx) = mpsc::channel(1);
+        state.workers.write().await.insert(
+            worker_id.clone(),
+            ConnectedWorker {
+                info: WorkerInfo::new(worker_id.clone(), "host")
+                    .with_agent(AgentSpec::new("general"))
+                    .with_label("zone", "a"),
+                status: WorkerStatus::Idle,
+                active_runs: 0,
+                max_concurrent_runs: 1,
+                interactive_slots: 1,
+                last_heartbeat: Utc::now(),
+                draining_since: None,
+                tx,
+                shutdown: CancellationToken::new(),
+            },
+        );
+
+        // Only the limit changes; agents and labels are kept
+        handle_worker_update(
+            &state,
+            &worker_id,
+            WorkerUpdate {
+                max_concurrent_runs: Some(3),
+                ..Default::default()
+            },
+        )
+        .await;
+        {
+            let workers = state.workers.read().await;
+            let worker = &workers[&worker_id];
+            assert_eq!(worker.max_concurrent_runs, 3);
+            assert_eq!(worker.info.agents[0].name, "general");
+            assert_eq!(worker.info.labels["zone"], "a");
+        }
+
+        handle_worker_update(
+            &state,
+            &worker_id,
+            WorkerUpdate {
+                agents: Some(pb::WorkerAgents {
+                    agents: vec![AgentSpec::new("reviewer").into()],
+                }),
+                labels: Some(pb::WorkerLabels {
+                    labels: HashMap::new(),
+                }),
+                max_concurrent_runs: None,
+            },
+        )
+        .await;
+        let workers = state.workers.read().await;
+        let worker = &workers[&worker_id];
+        assert!(worker.info.supports_agent("reviewer"));
+        assert!(!worker.info.supports_agent("general"));
+        assert!(worker.info.labels.is_empty());
+        assert_eq!(worker.max_concurrent_runs, 3);
+    }
+}
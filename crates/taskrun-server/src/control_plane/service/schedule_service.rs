@@ -0,0 +1,141 @@
+//! ScheduleService implementation - manage recurring tasks.
+
+use std::sync::Arc;
+
+use tonic::{Request, Response, Status};
+
+use taskrun_core::Schedule;
+use taskrun_proto::pb::{
+    CreateScheduleRequest, DeleteScheduleRequest, DeleteScheduleResponse, ListSchedulesRequest,
+    ListSchedulesResponse, PauseScheduleRequest, ResumeScheduleRequest,
+};
+use taskrun_proto::{ScheduleService, ScheduleServiceServer};
+
+use crate::control_plane::audit::AuditAction;
+use crate::control_plane::rbac::{authorize, Caller, Role};
+use crate::control_plane::schedules::{ScheduleError, ScheduleManager};
+
+/// gRPC ScheduleService implementation.
+pub struct ScheduleServiceImpl {
+    manager: Arc<ScheduleManager>,
+}
+
+impl ScheduleServiceImpl {
+    /// Create a new ScheduleServiceImpl.
+    pub fn new(manager: Arc<ScheduleManager>) -> Self {
+        Self { manager }
+    }
+
+    /// Convert into a tonic server.
+    pub fn into_server(self) -> ScheduleServiceServer<Self> {
+        ScheduleServiceServer::new(self)
+    }
+
+    async fn audit(&self, caller: &Caller, action: AuditAction, schedule: &Schedule) {
+        self.manager
+            .state()
+            .audit(&caller.identity, action, schedule.id.as_str(), schedule)
+            .await;
+    }
+}
+
+impl From<ScheduleError> for Status {
+    fn from(err: ScheduleError) -> Self {
+        match err {
+            ScheduleError::InvalidCron { .. } | ScheduleError::MissingAgent => {
+                Status::invalid_argument(err.to_string())
+            }
+            ScheduleError::Lookup(e) => e.into(),
+            ScheduleError::Store(_) => Status::internal(err.to_string()),
+        }
+    }
+}
+
+#[tonic::async_trait]
+impl ScheduleService for ScheduleServiceImpl {
+    async fn create_schedule(
+        &self,
+        request: Request<CreateScheduleRequest>,
+    ) -> Result<Response<taskrun_proto::pb::Schedule>, Status> {
+        let caller = authorize(&request, Role::Operator)?;
+        // Scheduled tasks are created in the default namespace
+        caller.require_unbound()?;
+        let req = request.into_inner();
+
+        let mut schedule = Schedule::new(
+            req.cron_expr,
+            req.agent_name,
+            req.input_template,
+            caller.identity,
+        );
+        schedule.labels = req.labels;
+        if req.timeout_seconds > 0 {
+            schedule = schedule.with_timeout_seconds(req.timeout_seconds);
+        }
+
+        let schedule = self.manager.create(schedule).await?;
+        self.manager
+            .state()
+            .audit(
+                &schedule.created_by,
+                AuditAction::ScheduleCreated,
+                schedule.id.as_str(),
+                &schedule,
+            )
+            .await;
+        Ok(Response::new(schedule.into()))
+    }
+
+    async fn list_schedules(
+        &self,
+        request: Request<ListSchedulesRequest>,
+    ) -> Result<Response<ListSchedulesResponse>, Status> {
+        authorize(&request, Role::Readonly)?;
+        let schedules = self
+            .manager
+            .list()
+            .await
+            .into_iter()
+            .map(Into::into)
+            .collect();
+        Ok(Response::new(ListSchedulesResponse { schedules }))
+    }
+
+    async fn pause_schedule(
+        &self,
+        request: Request<PauseScheduleRequest>,
+    ) -> Result<Response<taskrun_proto::pb::Schedule>, Status> {
+        let caller = authorize(&request, Role::Operator)?;
+        let id = self.manager.resolve_id(&request.into_inner().id).await?;
+        let schedule = self.manager.set_paused(&id, true).await?;
+        self.audit(&caller, AuditAction::SchedulePaused, &schedule)
+            .await;
+        Ok(Response::new(schedule.into()))
+    }
+
+    async fn resume_schedule(
+        &self,
+        request: Request<ResumeScheduleRequest>,
+    ) -> Result<Response<taskrun_proto::pb::Schedule>, Status> {
+        let caller = authorize(&request, Role::Operator)?;
+        let id = self.manager.resolve_id(&request.into_inner().id).await?;
+        let schedule = self.manager.set_paused(&id, false).await?;
+        self.audit(&caller, AuditAction::ScheduleResumed, &schedule)
+            .await;
+        Ok(Response::new(schedule.into()))
+    }
+
+    async fn delete_schedule(
+        &self,
+        request: Request<DeleteScheduleRequest>,
+    ) -> Result<Response<DeleteScheduleResponse>, Status> {
+        let caller = authorize(&request, Role::Operator)?;
+        let id = self.manager.resolve_id(&request.into_inner().id).await?;
+        let schedule = self.manager.delete(&id).await?;
+        self.audit(&caller, AuditAction::ScheduleDeleted, &schedule)
+            .await;
+        Ok(Response::new(DeleteScheduleResponse {
+            id: schedule.id.into_inner(),
+        }))
+    }
+}
@@ -1,20 +1,62 @@
 //! TaskService implementation for the control plane.
 
+use std::pin::Pin;
 use std::sync::Arc;
 
+use chrono::{DateTime, Utc};
+use tokio_stream::wrappers::ReceiverStream;
+use tokio_stream::StreamExt;
 use tonic::{Request, Response, Status};
 use tracing::{info, warn};
 
-use taskrun_core::{RunStatus, Task, TaskId, TaskStatus};
-use taskrun_proto::pb::run_server_message::Payload as ServerPayload;
+use taskrun_core::{Task, TaskId};
 use taskrun_proto::pb::{
-    CancelRun, CancelTaskRequest, CreateTaskRequest, GetTaskRequest, ListTasksRequest,
-    ListTasksResponse, RunServerMessage,
+    BulkTaskRequest, BulkTaskResponse, CancelTaskRequest, CreateTaskRequest,
+    GetGroupSummaryRequest, GetTaskRequest, GroupSummary, ListTasksRequest, ListTasksResponse,
+    RunOutputStreamChunk, SearchTaskHit, SearchTasksRequest, SearchTasksResponse,
+    StreamRunOutputRequest, TaskPriority, TaskSortOrder, TaskWatchEvent, ValidateTaskResponse,
+    WatchTasksRequest,
 };
+use taskrun_proto::trace;
 use taskrun_proto::{TaskService, TaskServiceServer};
 
-use crate::control_plane::scheduler::Scheduler;
+use crate::control_plane::audit::AuditAction;
+use crate::control_plane::bulk::{self, BulkOperation, TaskFilter};
+use crate::control_plane::groups;
+use crate::control_plane::output_stream;
+use crate::control_plane::queue;
+use crate::control_plane::quotas;
+use crate::control_plane::rbac::{authorize, Role};
+use crate::control_plane::scheduler::{trace_created, Scheduler, SchedulerError};
+use crate::control_plane::search::{self, SearchError};
 use crate::control_plane::state::{AppState, UiNotification};
+use crate::control_plane::task_query::{self, TaskQuery, TaskQueryError, TaskSort};
+use crate::control_plane::validation;
+use crate::control_plane::watch::{self, WatchFilter};
+
+impl From<TaskQueryError> for Status {
+    fn from(err: TaskQueryError) -> Self {
+        Status::invalid_argument(err.to_string())
+    }
+}
+
+impl From<SearchError> for Status {
+    fn from(err: SearchError) -> Self {
+        Status::invalid_argument(err.to_string())
+    }
+}
+
+/// An optional string field (empty = unset).
+fn non_empty(s: &str) -> Option<&str> {
+    (!s.is_empty()).then_some(s)
+}
+
+/// Convert a Unix timestamp in ms to a time (0 = unset).
+fn timestamp(ms: i64) -> Option<DateTime<Utc>> {
+    (ms != 0)
+        .then(|| DateTime::from_timestamp_millis(ms))
+        .flatten()
+}
 
 /// TaskService implementation.
 pub struct TaskServiceImpl {
@@ -33,14 +75,52 @@ impl TaskServiceImpl {
     pub fn into_server(self) -> TaskServiceServer<Self> {
         TaskServiceServer::new(self)
     }
+
+    /// Run a bulk operation for an admin caller.
+    async fn bulk(
+        &self,
+        request: Request<BulkTaskRequest>,
+        op: BulkOperation,
+    ) -> Result<Response<BulkTaskResponse>, Status> {
+        let caller = authorize(&request, Role::Admin)?;
+        let req = request.into_inner();
+        let filter = req.filter.unwrap_or_default();
+        let filter = TaskFilter {
+            status: (filter.status != 0).then(|| filter.status().into()),
+            agent_name: (!filter.agent_name.is_empty()).then_some(filter.agent_name.clone()),
+            failure_reason: filter.failure_reason().into(),
+            namespace: caller.scope(non_empty(&filter.namespace))?,
+            labels: filter.labels,
+        };
+
+        let outcome = bulk::run(&self.state, op, &filter, &caller.identity, req.dry_run).await;
+
+        Ok(Response::new(BulkTaskResponse {
+            task_ids: outcome.task_ids.iter().map(ToString::to_string).collect(),
+            failed_task_ids: outcome
+                .failed_task_ids
+                .iter()
+                .map(ToString::to_string)
+                .collect(),
+            dry_run: req.dry_run,
+        }))
+    }
 }
 
 #[tonic::async_trait]
 impl TaskService for TaskServiceImpl {
+    type WatchTasksStream =
+        Pin<Box<dyn tokio_stream::Stream<Item = Result<TaskWatchEvent, Status>> + Send>>;
+    type StreamRunOutputStream =
+        Pin<Box<dyn tokio_stream::Stream<Item = Result<RunOutputStreamChunk, Status>> + Send>>;
+
     async fn create_task(
         &self,
         request: Request<CreateTaskRequest>,
     ) -> Result<Response<taskrun_proto::pb::Task>, Status> {
+        let caller = authorize(&request, Role::Operator)?;
+        // Continue the caller's trace, if it sent one
+        let parent = trace::from_metadata(request.metadata());
         let req = request.into_inner();
 
         // Validate request
@@ -48,29 +128,62 @@ impl TaskService for TaskServiceImpl {
             return Err(Status::invalid_argument("agent_name is required"));
         }
 
+        let namespace = caller.namespace_for_new(non_empty(&req.namespace))?;
+        self.state.toggles.check_accepting().await?;
+        quotas::check_create(&self.state, &namespace, &caller.identity).await?;
+        self.state
+            .files
+            .check_inputs(&req.input_files, &namespace)
+            .await
+            .map_err(|e| Status::invalid_argument(e.to_string()))?;
+
         // Create task
-        let mut task = Task::new(&req.agent_name, &req.input_json, &req.created_by);
+        // Record who created the task, not who the client claims to be
+        let priority = match req.priority() {
+            TaskPriority::Unspecified => self.state.toggles.default_priority().await,
+            priority => priority.into(),
+        };
+        let mut task = Task::new(&req.agent_name, &req.input_json, &caller.identity)
+            .with_namespace(namespace)
+            .with_priority(priority)
+            .with_input_files(req.input_files);
         for (k, v) in req.labels {
             task.labels.insert(k, v);
         }
+        if req.timeout_seconds > 0 {
+            task = task.with_timeout_seconds(req.timeout_seconds);
+        }
+        if req.pending_ttl_seconds > 0 {
+            task = task.with_pending_ttl_seconds(req.pending_ttl_seconds);
+        }
+        if let Some(limits) = req.limits {
+            task = task.with_limits(limits.into());
+        }
 
+        let task = trace_created(task, &parent);
         let task_id = task.id.clone();
 
         info!(
             task_id = %task_id,
             agent = %req.agent_name,
-            created_by = %req.created_by,
+            created_by = %caller.identity,
             "Creating task"
         );
 
         // Store task
+        self.state
+            .audit(
+                &caller.identity,
+                AuditAction::TaskCreated,
+                task_id.as_str(),
+                &task,
+            )
+            .await;
+        let created = UiNotification::task_created(&task);
         self.state.tasks.write().await.insert(task_id.clone(), task);
 
         // Notify UI
-        self.state.notify_ui(UiNotification::TaskCreated {
-            task_id: task_id.clone(),
-            agent: req.agent_name.clone(),
-        });
+        self.state.notify_ui(created);
 
         // Try to schedule immediately
         match self.scheduler.assign_task(&task_id).await {
@@ -93,15 +206,43 @@ impl TaskService for TaskServiceImpl {
             .cloned()
             .ok_or_else(|| Status::internal("Task disappeared after creation"))?;
 
-        Ok(Response::new(task.into()))
+        let mut task = taskrun_proto::pb::Task::from(task);
+        task.queue = queue::position(&self.state, &task_id).await.map(Into::into);
+        Ok(Response::new(task))
+    }
+
+    async fn validate_task(
+        &self,
+        request: Request<CreateTaskRequest>,
+    ) -> Result<Response<ValidateTaskResponse>, Status> {
+        let caller = authorize(&request, Role::Operator)?;
+        let req = request.into_inner();
+        let namespace = caller.namespace_for_new(non_empty(&req.namespace))?;
+
+        let report = validation::validate(
+            &self.state,
+            &validation::TaskSpec {
+                agent_name: &req.agent_name,
+                input_json: &req.input_json,
+                namespace: &namespace,
+                created_by: &caller.identity,
+                priority: req.priority().into(),
+            },
+        )
+        .await;
+        Ok(Response::new(report.into()))
     }
 
     async fn get_task(
         &self,
         request: Request<GetTaskRequest>,
     ) -> Result<Response<taskrun_proto::pb::Task>, Status> {
+        let caller = authorize(&request, Role::Readonly)?;
         let req = request.into_inner();
-        let task_id = TaskId::new(&req.id);
+        let task_id = self
+            .state
+            .resolve_task_id(&req.id, caller.namespace.as_deref())
+            .await?;
 
         let task = self
             .state
@@ -112,141 +253,178 @@ impl TaskService for TaskServiceImpl {
             .cloned()
             .ok_or_else(|| Status::not_found(format!("Task not found: {}", req.id)))?;
 
-        Ok(Response::new(task.into()))
+        let mut task = taskrun_proto::pb::Task::from(task);
+        task.queue = queue::position(&self.state, &task_id).await.map(Into::into);
+        Ok(Response::new(task))
     }
 
     async fn list_tasks(
         &self,
         request: Request<ListTasksRequest>,
     ) -> Result<Response<ListTasksResponse>, Status> {
+        let caller = authorize(&request, Role::Readonly)?;
         let req = request.into_inner();
-        let limit = if req.limit > 0 {
-            req.limit as usize
-        } else {
-            100
+        let query = TaskQuery {
+            filter: TaskFilter {
+                status: (req.status_filter != 0).then(|| req.status_filter().into()),
+                agent_name: (!req.agent_filter.is_empty()).then_some(req.agent_filter.clone()),
+                failure_reason: req.failure_reason_filter().into(),
+                labels: req.label_selector.clone(),
+                namespace: caller.scope(non_empty(&req.namespace_filter))?,
+            },
+            created_after: timestamp(req.created_after_ms),
+            created_before: timestamp(req.created_before_ms),
+            sort: match req.sort() {
+                TaskSortOrder::CreatedAsc => TaskSort::CreatedAsc,
+                TaskSortOrder::Unspecified | TaskSortOrder::CreatedDesc => TaskSort::CreatedDesc,
+            },
+            limit: req.limit.max(0) as usize,
+            page_token: (!req.page_token.is_empty()).then_some(req.page_token),
         };
 
-        let tasks = self.state.tasks.read().await;
-
-        let filtered: Vec<taskrun_proto::pb::Task> = tasks
-            .values()
-            .filter(|task| {
-                // Status filter
-                if req.status_filter != 0 {
-                    let filter_status: TaskStatus =
-                        taskrun_proto::pb::TaskStatus::try_from(req.status_filter)
-                            .unwrap_or(taskrun_proto::pb::TaskStatus::Unspecified)
-                            .into();
-                    if task.status != filter_status {
-                        return false;
-                    }
-                }
-                // Agent filter
-                if !req.agent_filter.is_empty() && task.agent_name != req.agent_filter {
-                    return false;
-                }
-                true
-            })
-            .take(limit)
-            .cloned()
-            .map(Into::into)
-            .collect();
+        let page = task_query::list(&self.state, &query).await?;
+
+        Ok(Response::new(ListTasksResponse {
+            tasks: page.tasks.into_iter().map(Into::into).collect(),
+            next_page_token: page.next_page_token.unwrap_or_default(),
+        }))
+    }
 
-        Ok(Response::new(ListTasksResponse { tasks: filtered }))
+    async fn search_tasks(
+        &self,
+        request: Request<SearchTasksRequest>,
+    ) -> Result<Response<SearchTasksResponse>, Status> {
+        let caller = authorize(&request, Role::Readonly)?;
+        let req = request.into_inner();
+        let namespace = caller.scope(non_empty(&req.namespace))?;
+
+        let hits = search::search(
+            &self.state,
+            &req.query,
+            namespace.as_deref(),
+            req.limit.max(0) as usize,
+        )
+        .await?;
+
+        Ok(Response::new(SearchTasksResponse {
+            hits: hits
+                .into_iter()
+                .map(|hit| SearchTaskHit {
+                    task: Some(hit.task.into()),
+                    matched_fields: hit
+                        .matched_fields
+                        .iter()
+                        .map(|f| f.as_str().to_string())
+                        .collect(),
+                })
+                .collect(),
+        }))
+    }
+
+    async fn get_group_summary(
+        &self,
+        request: Request<GetGroupSummaryRequest>,
+    ) -> Result<Response<GroupSummary>, Status> {
+        let caller = authorize(&request, Role::Readonly)?;
+        let req = request.into_inner();
+        let namespace = caller.scope(non_empty(&req.namespace))?;
+
+        let summary = groups::summarize(&self.state, &req.group_id, namespace.as_deref())
+            .await
+            .ok_or_else(|| Status::not_found(format!("Group not found: {}", req.group_id)))?;
+
+        Ok(Response::new(summary.into()))
     }
 
     async fn cancel_task(
         &self,
         request: Request<CancelTaskRequest>,
     ) -> Result<Response<taskrun_proto::pb::Task>, Status> {
+        let caller = authorize(&request, Role::Operator)?;
         let req = request.into_inner();
-        let task_id = TaskId::new(&req.id);
-
-        // Collect runs to cancel (worker_id, run_id pairs)
-        let runs_to_cancel: Vec<_>;
-        let result_task: Task;
-
-        {
-            let mut tasks = self.state.tasks.write().await;
-            let task = tasks
-                .get_mut(&task_id)
-                .ok_or_else(|| Status::not_found(format!("Task not found: {}", req.id)))?;
-
-            // Check if cancellable
-            if task.is_terminal() {
-                return Err(Status::failed_precondition(format!(
-                    "Task {} is already in terminal state: {:?}",
-                    req.id, task.status
-                )));
-            }
+        let task_id = self
+            .state
+            .resolve_task_id(&req.id, caller.namespace.as_deref())
+            .await?;
 
-            info!(task_id = %task_id, "Cancelling task");
+        let task = self
+            .scheduler
+            .cancel_task(&task_id, &format!("Task cancelled by {}", caller.identity))
+            .await
+            .map_err(|e| match e {
+                SchedulerError::TaskNotFound(_) => Status::not_found(e.to_string()),
+                SchedulerError::TaskAlreadyTerminal(..) => {
+                    Status::failed_precondition(e.to_string())
+                }
+                _ => Status::internal(e.to_string()),
+            })?;
+        self.state
+            .audit(
+                &caller.identity,
+                AuditAction::TaskCancelled,
+                task_id.as_str(),
+                &task,
+            )
+            .await;
 
-            // Notify UI of task status change
-            self.state.notify_ui(UiNotification::TaskStatusChanged {
-                task_id: task_id.clone(),
-                status: TaskStatus::Cancelled,
-            });
+        Ok(Response::new(task.into()))
+    }
 
-            // Collect active runs
-            runs_to_cancel = task
-                .runs
-                .iter()
-                .filter(|r| r.status.is_active())
-                .map(|r| (r.worker_id.clone(), r.run_id.clone()))
-                .collect();
-
-            // Mark task and its runs as cancelled
-            task.status = TaskStatus::Cancelled;
-            for run in &mut task.runs {
-                if run.status.is_active() {
-                    run.status = RunStatus::Cancelled;
-                    run.finished_at = Some(chrono::Utc::now());
-                }
-            }
+    async fn bulk_cancel_tasks(
+        &self,
+        request: Request<BulkTaskRequest>,
+    ) -> Result<Response<BulkTaskResponse>, Status> {
+        self.bulk(request, BulkOperation::Cancel).await
+    }
 
-            result_task = task.clone();
-        }
+    async fn bulk_retry_tasks(
+        &self,
+        request: Request<BulkTaskRequest>,
+    ) -> Result<Response<BulkTaskResponse>, Status> {
+        self.bulk(request, BulkOperation::Retry).await
+    }
 
-        // Send CancelRun to workers (outside the tasks lock)
-        if !runs_to_cancel.is_empty() {
-            let workers = self.state.workers.read().await;
-            for (worker_id, run_id) in &runs_to_cancel {
-                if let Some(worker) = workers.get(worker_id) {
-                    let cancel_msg = RunServerMessage {
-                        payload: Some(ServerPayload::CancelRun(CancelRun {
-                            run_id: run_id.to_string(),
-                            reason: "Task cancelled by user".to_string(),
-                        })),
-                    };
-
-                    if let Err(e) = worker.tx.send(cancel_msg).await {
-                        warn!(
-                            task_id = %task_id,
-                            run_id = %run_id,
-                            worker_id = %worker_id,
-                            error = %e,
-                            "Failed to send CancelRun to worker"
-                        );
-                    } else {
-                        info!(
-                            task_id = %task_id,
-                            run_id = %run_id,
-                            worker_id = %worker_id,
-                            "Sent CancelRun to worker"
-                        );
-                    }
-                } else {
-                    warn!(
-                        task_id = %task_id,
-                        run_id = %run_id,
-                        worker_id = %worker_id,
-                        "Worker not connected, cannot send CancelRun"
-                    );
-                }
-            }
-        }
+    async fn watch_tasks(
+        &self,
+        request: Request<WatchTasksRequest>,
+    ) -> Result<Response<Self::WatchTasksStream>, Status> {
+        let caller = authorize(&request, Role::Readonly)?;
+        let req = request.into_inner();
+        let filter = WatchFilter {
+            tasks: TaskFilter {
+                agent_name: non_empty(&req.agent_name).map(str::to_string),
+                namespace: caller.scope(non_empty(&req.namespace))?,
+                labels: req.labels,
+                ..TaskFilter::default()
+            },
+            task_ids: req.task_ids.into_iter().map(TaskId::new).collect(),
+            include_output: req.include_output,
+        };
+
+        let events = watch::subscribe(self.state.clone(), filter)
+            .ok_or_else(|| Status::unavailable("Task updates are not available"))?;
+        info!(caller = %caller.identity, "Task watch started");
+        Ok(Response::new(Box::pin(ReceiverStream::new(events).map(Ok))))
+    }
 
-        Ok(Response::new(result_task.into()))
+    async fn stream_run_output(
+        &self,
+        request: Request<StreamRunOutputRequest>,
+    ) -> Result<Response<Self::StreamRunOutputStream>, Status> {
+        let caller = authorize(&request, Role::Readonly)?;
+        let req = request.into_inner();
+        let run_id = self
+            .state
+            .resolve_run_id(&req.run_id, caller.namespace.as_deref())
+            .await?;
+
+        info!(
+            caller = %caller.identity,
+            run_id = %run_id,
+            from_seq = req.from_seq,
+            "Run output stream started"
+        );
+        let chunks = output_stream::subscribe(self.state.clone(), run_id, req.from_seq);
+        Ok(Response::new(Box::pin(ReceiverStream::new(chunks).map(Ok))))
     }
 }
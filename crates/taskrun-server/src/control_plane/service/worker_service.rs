@@ -4,9 +4,16 @@ use std::sync::Arc;
 
 use tonic::{Request, Response, Status};
 
-use taskrun_proto::pb::{GetWorkerRequest, ListWorkersRequest, ListWorkersResponse, Worker};
+use taskrun_proto::pb::{
+    DisconnectWorkerRequest, DisconnectWorkerResponse, DrainWorkerRequest, DrainWorkerResponse,
+    GetWorkerRequest, ListWorkersRequest, ListWorkersResponse, UndrainWorkerRequest, Worker,
+};
 use taskrun_proto::{WorkerService, WorkerServiceServer};
 
+use crate::control_plane::audit::AuditAction;
+use crate::control_plane::disconnect;
+use crate::control_plane::drain;
+use crate::control_plane::rbac::{authorize, Caller, Role};
 use crate::control_plane::state::{AppState, ConnectedWorker};
 
 /// gRPC WorkerService implementation.
@@ -24,6 +31,37 @@ impl WorkerServiceImpl {
     pub fn into_server(self) -> WorkerServiceServer<Self> {
         WorkerServiceServer::new(self)
     }
+
+    /// Drain or undrain a worker visible to `caller`, auditing the change.
+    async fn set_draining(
+        &self,
+        caller: &Caller,
+        worker_id: &str,
+        draining: bool,
+    ) -> Result<(Worker, drain::DrainProgress), Status> {
+        let worker_id = self
+            .state
+            .resolve_worker_id(worker_id, caller.namespace.as_deref())
+            .await?;
+        let progress = drain::set_draining(&self.state, &worker_id, draining)
+            .await
+            .ok_or_else(|| Status::not_found(format!("Worker {} not found", worker_id)))?;
+        let action = if draining {
+            AuditAction::WorkerDrained
+        } else {
+            AuditAction::WorkerUndrained
+        };
+        self.state
+            .audit(&caller.identity, action, worker_id.as_str(), &progress)
+            .await;
+
+        let workers = self.state.workers.read().await;
+        let worker = workers
+            .get(&worker_id)
+            .map(connected_worker_to_proto)
+            .ok_or_else(|| Status::not_found(format!("Worker {} not found", worker_id)))?;
+        Ok((worker, progress))
+    }
 }
 
 /// Convert ConnectedWorker to proto Worker message.
@@ -35,9 +73,13 @@ fn connected_worker_to_proto(worker: &ConnectedWorker) -> Worker {
         status: taskrun_proto::pb::WorkerStatus::from(worker.status) as i32,
         agents: worker.info.agents.iter().cloned().map(Into::into).collect(),
         labels: worker.info.labels.clone(),
+        namespace: worker.info.namespace.clone(),
         active_runs: worker.active_runs,
         max_concurrent_runs: worker.max_concurrent_runs,
         last_heartbeat_ms: worker.last_heartbeat.timestamp_millis(),
+        draining_since_ms: worker
+            .draining_since
+            .map_or(0, |since| since.timestamp_millis()),
     }
 }
 
@@ -47,12 +89,21 @@ impl WorkerService for WorkerServiceImpl {
         &self,
         request: Request<ListWorkersRequest>,
     ) -> Result<Response<ListWorkersResponse>, Status> {
+        let caller = authorize(&request, Role::Readonly)?;
         let req = request.into_inner();
+        let namespace = caller.scope(req.namespace.as_deref())?;
         let workers = self.state.workers.read().await;
 
         let mut result: Vec<Worker> = Vec::new();
 
         for worker in workers.values() {
+            if namespace
+                .as_ref()
+                .is_some_and(|ns| &worker.info.namespace != ns)
+            {
+                continue;
+            }
+
             // Filter by agent_name if specified
             if let Some(ref agent_name) = req.agent_name {
                 if !worker.info.supports_agent(agent_name) {
@@ -78,12 +129,17 @@ impl WorkerService for WorkerServiceImpl {
         &self,
         request: Request<GetWorkerRequest>,
     ) -> Result<Response<Worker>, Status> {
+        let caller = authorize(&request, Role::Readonly)?;
         let req = request.into_inner();
         let worker_id = taskrun_core::WorkerId::new(req.worker_id.clone());
 
         let workers = self.state.workers.read().await;
 
-        match workers.get(&worker_id) {
+        // Workers of other namespaces are not found, rather than forbidden
+        match workers
+            .get(&worker_id)
+            .filter(|w| caller.can_access(&w.info.namespace))
+        {
             Some(worker) => Ok(Response::new(connected_worker_to_proto(worker))),
             None => Err(Status::not_found(format!(
                 "Worker {} not found",
@@ -91,4 +147,77 @@ impl WorkerService for WorkerServiceImpl {
             ))),
         }
     }
+
+    async fn drain_worker(
+        &self,
+        request: Request<DrainWorkerRequest>,
+    ) -> Result<Response<DrainWorkerResponse>, Status> {
+        let caller = authorize(&request, Role::Admin)?;
+        let req = request.into_inner();
+        let (worker, progress) = self.set_draining(&caller, &req.worker_id, true).await?;
+
+        Ok(Response::new(DrainWorkerResponse {
+            worker: Some(worker),
+            active_run_ids: progress
+                .active_run_ids
+                .into_iter()
+                .map(|id| id.into_inner())
+                .collect(),
+        }))
+    }
+
+    async fn undrain_worker(
+        &self,
+        request: Request<UndrainWorkerRequest>,
+    ) -> Result<Response<Worker>, Status> {
+        let caller = authorize(&request, Role::Admin)?;
+        let req = request.into_inner();
+        let (worker, _) = self.set_draining(&caller, &req.worker_id, false).await?;
+
+        Ok(Response::new(worker))
+    }
+
+    async fn disconnect_worker(
+        &self,
+        request: Request<DisconnectWorkerRequest>,
+    ) -> Result<Response<DisconnectWorkerResponse>, Status> {
+        let caller = authorize(&request, Role::Admin)?;
+        let req = request.into_inner();
+        let worker_id = self
+            .state
+            .resolve_worker_id(&req.worker_id, caller.namespace.as_deref())
+            .await?;
+        let reason = if req.reason.is_empty() {
+            format!("Disconnected by {}", caller.identity)
+        } else {
+            req.reason
+        };
+
+        let outcome =
+            disconnect::disconnect_worker(&self.state, &worker_id, &reason, req.reschedule)
+                .await
+                .ok_or_else(|| Status::not_found(format!("Worker {} not found", worker_id)))?;
+        self.state
+            .audit(
+                &caller.identity,
+                AuditAction::WorkerDisconnected,
+                worker_id.as_str(),
+                &outcome,
+            )
+            .await;
+
+        Ok(Response::new(DisconnectWorkerResponse {
+            worker_id: outcome.worker_id.into_inner(),
+            failed_run_ids: outcome
+                .failed_run_ids
+                .into_iter()
+                .map(|id| id.into_inner())
+                .collect(),
+            rescheduled_task_ids: outcome
+                .rescheduled_task_ids
+                .into_iter()
+                .map(|id| id.into_inner())
+                .collect(),
+        }))
+    }
 }
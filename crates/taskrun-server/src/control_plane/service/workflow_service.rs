@@ -0,0 +1,143 @@
+//! WorkflowService implementation - expand templates into linked tasks.
+
+use std::sync::Arc;
+
+use tonic::{Request, Response, Status};
+
+use taskrun_core::{TaskId, TaskStatus, Workflow};
+use taskrun_proto::pb::{
+    CreateWorkflowRequest, GetWorkflowRequest, ListWorkflowTemplatesRequest,
+    ListWorkflowTemplatesResponse, ListWorkflowsRequest, ListWorkflowsResponse, WorkflowStepTask,
+};
+use taskrun_proto::{WorkflowService, WorkflowServiceServer};
+
+use crate::control_plane::rbac::{authorize, Role};
+use crate::control_plane::workflows::{WorkflowError, WorkflowManager};
+
+/// gRPC WorkflowService implementation.
+pub struct WorkflowServiceImpl {
+    manager: Arc<WorkflowManager>,
+}
+
+impl WorkflowServiceImpl {
+    /// Create a new WorkflowServiceImpl.
+    pub fn new(manager: Arc<WorkflowManager>) -> Self {
+        Self { manager }
+    }
+
+    /// Convert into a tonic server.
+    pub fn into_server(self) -> WorkflowServiceServer<Self> {
+        WorkflowServiceServer::new(self)
+    }
+
+    /// Build the proto workflow, including each step's current task status.
+    async fn to_proto(&self, workflow: Workflow) -> taskrun_proto::pb::Workflow {
+        let status = self.manager.status(&workflow).await;
+        let tasks = self.manager.state().tasks.read().await;
+
+        let steps = workflow
+            .steps
+            .into_iter()
+            .map(|step| {
+                let task = tasks.get(&step.task_id);
+                WorkflowStepTask {
+                    step_name: step.step_name,
+                    agent_name: task.map(|t| t.agent_name.clone()).unwrap_or_default(),
+                    status: taskrun_proto::pb::TaskStatus::from(
+                        task.map_or(TaskStatus::Pending, |t| t.status),
+                    )
+                    .into(),
+                    depends_on: task
+                        .map(|t| t.depends_on.iter().map(TaskId::to_string).collect())
+                        .unwrap_or_default(),
+                    task_id: step.task_id.into_inner(),
+                }
+            })
+            .collect();
+
+        taskrun_proto::pb::Workflow {
+            id: workflow.id.into_inner(),
+            template_name: workflow.template_name,
+            params: workflow.params,
+            created_by: workflow.created_by,
+            created_at_ms: workflow.created_at.timestamp_millis(),
+            status: taskrun_proto::pb::WorkflowStatus::from(status).into(),
+            steps,
+        }
+    }
+}
+
+impl From<WorkflowError> for Status {
+    fn from(err: WorkflowError) -> Self {
+        match err {
+            WorkflowError::UnknownTemplate(_) => Status::not_found(err.to_string()),
+            WorkflowError::MissingParam { .. } | WorkflowError::UnknownParam { .. } => {
+                Status::invalid_argument(err.to_string())
+            }
+            WorkflowError::Lookup(e) => e.into(),
+            WorkflowError::Maintenance(e) => e.into(),
+            WorkflowError::Io { .. }
+            | WorkflowError::Parse(_)
+            | WorkflowError::InvalidTemplate { .. } => Status::internal(err.to_string()),
+        }
+    }
+}
+
+#[tonic::async_trait]
+impl WorkflowService for WorkflowServiceImpl {
+    async fn create_workflow(
+        &self,
+        request: Request<CreateWorkflowRequest>,
+    ) -> Result<Response<taskrun_proto::pb::Workflow>, Status> {
+        let caller = authorize(&request, Role::Operator)?;
+        // Workflow steps are created in the default namespace
+        caller.require_unbound()?;
+        let req = request.into_inner();
+        let workflow = self
+            .manager
+            .create(&req.template_name, req.params, &caller.identity)
+            .await?;
+        Ok(Response::new(self.to_proto(workflow).await))
+    }
+
+    async fn get_workflow(
+        &self,
+        request: Request<GetWorkflowRequest>,
+    ) -> Result<Response<taskrun_proto::pb::Workflow>, Status> {
+        authorize(&request, Role::Readonly)?;
+        let id = self.manager.resolve_id(&request.into_inner().id).await?;
+        let workflow = self.manager.get(&id).await?;
+        Ok(Response::new(self.to_proto(workflow).await))
+    }
+
+    async fn list_workflows(
+        &self,
+        request: Request<ListWorkflowsRequest>,
+    ) -> Result<Response<ListWorkflowsResponse>, Status> {
+        authorize(&request, Role::Readonly)?;
+        let limit = match request.into_inner().limit {
+            0 => usize::MAX,
+            n => n as usize,
+        };
+
+        let mut workflows = Vec::new();
+        for workflow in self.manager.list().await.into_iter().take(limit) {
+            workflows.push(self.to_proto(workflow).await);
+        }
+        Ok(Response::new(ListWorkflowsResponse { workflows }))
+    }
+
+    async fn list_workflow_templates(
+        &self,
+        request: Request<ListWorkflowTemplatesRequest>,
+    ) -> Result<Response<ListWorkflowTemplatesResponse>, Status> {
+        authorize(&request, Role::Readonly)?;
+        let templates = self
+            .manager
+            .templates()
+            .into_iter()
+            .map(Into::into)
+            .collect();
+        Ok(Response::new(ListWorkflowTemplatesResponse { templates }))
+    }
+}
@@ -0,0 +1,374 @@
+//! Snapshot and restore of control plane state.
+//!
+//! A snapshot is a JSON archive of the tasks (with their runs), their run
+//! events and the worker enrollment (bootstrap token) records. It is used
+//! for backups and to move between event store backends: export from one
+//! instance, start the new one with the other backend, import.
+//!
+//! Run output, chat history, audit records, schedules and workflows are not
+//! included; each has its own store.
+//!
+//! Importing merges into the current state: tasks whose ID already exists
+//! are skipped along with their events. Runs that were active when the
+//! snapshot was taken can't continue, since their workers aren't connected
+//! to this instance, so they fail as `worker_lost` and their tasks go back
+//! to Pending.
+//!
+//! In HA mode the leader writes snapshots to shared storage, and other
+//! instances replace their tasks with the latest one: followers mirror it to
+//! serve reads, and a new leader takes it over, interrupting active runs as
+//! an import does.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::info;
+
+use taskrun_core::{FailureReason, RunEvent, Task, TaskId, TaskStatus};
+
+use crate::control_plane::crypto::BootstrapToken;
+use crate::control_plane::event_store::EventStoreError;
+use crate::control_plane::state::{AppState, UiNotification};
+
+/// Value of the `format` field of every snapshot.
+pub const SNAPSHOT_FORMAT: &str = "taskrun-state";
+
+/// Version written by this build; older versions are read as well.
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+/// Snapshot errors.
+#[derive(Debug, Error)]
+pub enum SnapshotError {
+    #[error("Invalid snapshot: {0}")]
+    Parse(#[from] serde_json::Error),
+
+    #[error("Not a TaskRun state snapshot (format '{0}')")]
+    UnknownFormat(String),
+
+    #[error("Unsupported snapshot version {0} (this server reads up to {SNAPSHOT_VERSION})")]
+    UnsupportedVersion(u32),
+
+    #[error("Event store error: {0}")]
+    Store(#[from] EventStoreError),
+}
+
+/// Format and version, read before the rest of the archive.
+#[derive(Debug, Deserialize)]
+struct SnapshotHeader {
+    format: String,
+    version: u32,
+}
+
+/// A versioned archive of control plane state.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub format: String,
+    pub version: u32,
+    /// When the snapshot was taken (Unix milliseconds).
+    pub exported_at_ms: i64,
+    pub tasks: Vec<Task>,
+    /// Run events of the tasks, in task order.
+    #[serde(default)]
+    pub events: Vec<RunEvent>,
+    /// Bootstrap token records (hashes only, never plaintext tokens).
+    #[serde(default)]
+    pub enrollment_tokens: Vec<BootstrapToken>,
+}
+
+/// What an import did.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ImportSummary {
+    pub version: u32,
+    pub tasks_imported: usize,
+    pub tasks_skipped: usize,
+    pub events_imported: usize,
+    pub tokens_imported: usize,
+    pub runs_interrupted: usize,
+}
+
+/// Take a snapshot of `state`.
+pub async fn export(state: &AppState) -> Result<Snapshot, SnapshotError> {
+    let mut tasks: Vec<Task> = state.tasks.read().await.values().cloned().collect();
+    tasks.sort_by(|a, b| {
+        a.created_at
+            .cmp(&b.created_at)
+            .then_with(|| a.id.as_str().cmp(b.id.as_str()))
+    });
+
+    let mut events = Vec::new();
+    for task in &tasks {
+        events.extend(state.events.list_by_task(&task.id).await?);
+    }
+
+    let mut enrollment_tokens: Vec<BootstrapToken> = state
+        .bootstrap_tokens
+        .read()
+        .await
+        .values()
+        .cloned()
+        .collect();
+    enrollment_tokens.sort_by_key(|t| t.created_at);
+
+    Ok(Snapshot {
+        format: SNAPSHOT_FORMAT.to_string(),
+        version: SNAPSHOT_VERSION,
+        exported_at_ms: Utc::now().timestamp_millis(),
+        tasks,
+        events,
+        enrollment_tokens,
+    })
+}
+
+/// Serialize a snapshot.
+pub fn encode(snapshot: &Snapshot) -> Result<Vec<u8>, SnapshotError> {
+    Ok(serde_json::to_vec(snapshot)?)
+}
+
+/// Parse a snapshot, checking its format and version first.
+pub fn decode(bytes: &[u8]) -> Result<Snapshot, SnapshotError> {
+    let header: SnapshotHeader = serde_json::from_slice(bytes)?;
+    if header.format != SNAPSHOT_FORMAT {
+        return Err(SnapshotError::UnknownFormat(header.format));
+    }
+    if header.version == 0 || header.version > SNAPSHOT_VERSION {
+        return Err(SnapshotError::UnsupportedVersion(header.version));
+    }
+    Ok(serde_json::from_slice(bytes)?)
+}
+
+/// Merge a snapshot into `state`.
+pub async fn import(
+    state: &Arc<AppState>,
+    snapshot: Snapshot,
+) -> Result<ImportSummary, SnapshotError> {
+    let mut summary = ImportSummary {
+        version: snapshot.version,
+        ..Default::default()
+    };
+
+    let mut imported = Vec::new();
+    {
+        let mut tasks = state.tasks.write().await;
+        for mut task in snapshot.tasks {
+            if tasks.contains_key(&task.id) {
+                summary.tasks_skipped += 1;
+                continue;
+            }
+            summary.runs_interrupted += interrupt_runs(&mut task);
+            imported.push(task.clone());
+            tasks.insert(task.id.clone(), task);
+        }
+    }
+    summary.tasks_imported = imported.len();
+
+    let imported_ids: HashSet<&TaskId> = imported.iter().map(|t| &t.id).collect();
+    for event in snapshot.events {
+        if imported_ids.contains(&event.task_id) {
+            state.events.append(event).await?;
+            summary.events_imported += 1;
+        }
+    }
+
+    {
+        let mut tokens = state.bootstrap_tokens.write().await;
+        for token in snapshot.enrollment_tokens {
+            if !tokens.contains_key(&token.token_hash) {
+                tokens.insert(token.token_hash.clone(), token);
+                summary.tokens_imported += 1;
+            }
+        }
+    }
+
+    for task in &imported {
+        state.notify_ui(UiNotification::task_created(task));
+    }
+    info!(
+        tasks = summary.tasks_imported,
+        skipped = summary.tasks_skipped,
+        events = summary.events_imported,
+        tokens = summary.tokens_imported,
+        interrupted_runs = summary.runs_interrupted,
+        "State snapshot imported"
+    );
+    Ok(summary)
+}
+
+/// Replace the tasks and enrollment tokens of `state` with those of a
+/// snapshot, adding the events `state` lacks. With `interrupt`, active runs
+/// fail as on import. Returns the number of runs interrupted.
+pub async fn replace(
+    state: &Arc<AppState>,
+    snapshot: Snapshot,
+    interrupt: bool,
+) -> Result<usize, SnapshotError> {
+    let mut interrupted = 0;
+    let mut tasks: HashMap<TaskId, Task> = HashMap::with_capacity(snapshot.tasks.len());
+    for mut task in snapshot.tasks {
+        if interrupt {
+            interrupted += interrupt_runs(&mut task);
+        }
+        tasks.insert(task.id.clone(), task);
+    }
+
+    let mut known = HashMap::new();
+    for event in snapshot.events {
+        if !known.contains_key(&event.task_id) {
+            let ids: HashSet<_> = state
+                .events
+                .list_by_task(&event.task_id)
+                .await?
+                .into_iter()
+                .map(|e| e.id)
+                .collect();
+            known.insert(event.task_id.clone(), ids);
+        }
+        if known[&event.task_id].contains(&event.id) {
+            continue;
+        }
+        state.events.append(event).await?;
+    }
+
+    let previous = std::mem::replace(&mut *state.tasks.write().await, tasks.clone());
+    *state.bootstrap_tokens.write().await = snapshot
+        .enrollment_tokens
+        .into_iter()
+        .map(|t| (t.token_hash.clone(), t))
+        .collect();
+
+    for task in tasks.values() {
+        match previous.get(&task.id) {
+            None => state.notify_ui(UiNotification::task_created(task)),
+            Some(old) if old.status != task.status => {
+                state.notify_ui(UiNotification::TaskStatusChanged {
+                    task_id: task.id.clone(),
+                    status: task.status,
+                })
+            }
+            Some(_) => {}
+        }
+    }
+    Ok(interrupted)
+}
+
+/// Fail the active runs of an imported task and put it back in Pending.
+/// Returns the number of runs failed.
+fn interrupt_runs(task: &mut Task) -> usize {
+    let mut interrupted = 0;
+    for run in task.runs.iter_mut().filter(|r| r.status.is_active()) {
+        run.fail(
+            FailureReason::WorkerLost,
+            "Run was active when the state snapshot was taken",
+        );
+        interrupted += 1;
+    }
+    if task.status == TaskStatus::Running {
+        task.status = TaskStatus::Pending;
+    }
+    interrupted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use taskrun_core::{RunStatus, RunSummary, WorkerId};
+
+    #[tokio::test]
+    async fn test_round_trip_interrupts_active_runs() {
+        let source = AppState::new();
+        let mut task = Task::new("general", "{}", "test");
+        let mut run = RunSummary::new(WorkerId::generate());
+        run.start();
+        let run_id = run.run_id.clone();
+        task.add_run(run);
+        task.status = TaskStatus::Running;
+        let task_id = task.id.clone();
+        source.tasks.write().await.insert(task_id.clone(), task);
+        source
+            .events
+            .append(RunEvent::execution_started(run_id.clone(), task_id.clone()))
+            .await
+            .unwrap();
+        source.bootstrap_tokens.write().await.insert(
+            "hash".to_string(),
+            BootstrapToken::new("hash".to_string(), 1),
+        );
+
+        let bytes = encode(&export(&source).await.unwrap()).unwrap();
+        let target = AppState::new();
+        let summary = import(&target, decode(&bytes).unwrap()).await.unwrap();
+        assert_eq!(
+            summary,
+            ImportSummary {
+                version: SNAPSHOT_VERSION,
+                tasks_imported: 1,
+                tasks_skipped: 0,
+                events_imported: 1,
+                tokens_imported: 1,
+                runs_interrupted: 1,
+            }
+        );
+        let task = target.tasks.read().await[&task_id].clone();
+        assert_eq!(task.status, TaskStatus::Pending);
+        assert_eq!(task.runs[0].status, RunStatus::Failed);
+        assert_eq!(target.events.list_by_run(&run_id).await.unwrap().len(), 1);
+
+        // Importing again changes nothing
+        let summary = import(&target, decode(&bytes).unwrap()).await.unwrap();
+        assert_eq!(summary.tasks_skipped, 1);
+        assert_eq!(summary.events_imported, 0);
+        assert_eq!(summary.tokens_imported, 0);
+    }
+
+    #[tokio::test]
+    async fn test_replace_mirrors_tasks_and_adds_missing_events() {
+        let source = AppState::new();
+        let mut task = Task::new("general", "{}", "test");
+        let mut run = RunSummary::new(WorkerId::generate());
+        run.start();
+        let run_id = run.run_id.clone();
+        task.add_run(run);
+        task.status = TaskStatus::Running;
+        let task_id = task.id.clone();
+        source.tasks.write().await.insert(task_id.clone(), task);
+        source
+            .events
+            .append(RunEvent::execution_started(run_id.clone(), task_id.clone()))
+            .await
+            .unwrap();
+
+        // A follower keeps active runs as they are
+        let target = AppState::new();
+        let stale = Task::new("general", "{}", "test");
+        target.tasks.write().await.insert(stale.id.clone(), stale);
+        let snapshot = export(&source).await.unwrap();
+        assert_eq!(replace(&target, snapshot.clone(), false).await.unwrap(), 0);
+        assert_eq!(target.tasks.read().await.len(), 1);
+        assert_eq!(
+            target.tasks.read().await[&task_id].status,
+            TaskStatus::Running
+        );
+
+        // A new leader interrupts them; events are not added twice
+        assert_eq!(replace(&target, snapshot, true).await.unwrap(), 1);
+        assert_eq!(
+            target.tasks.read().await[&task_id].status,
+            TaskStatus::Pending
+        );
+        assert_eq!(target.events.list_by_run(&run_id).await.unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_decode_rejects_other_formats_and_versions() {
+        assert!(matches!(
+            decode(br#"{"format":"other","version":1}"#),
+            Err(SnapshotError::UnknownFormat(_))
+        ));
+        assert!(matches!(
+            decode(br#"{"format":"taskrun-state","version":99,"tasks":[]}"#),
+            Err(SnapshotError::UnsupportedVersion(99))
+        ));
+    }
+}
@@ -4,15 +4,40 @@ use std::collections::HashMap;
 use std::sync::Arc;
 
 use chrono::{DateTime, Utc};
+use thiserror::Error;
 use tokio::sync::{broadcast, mpsc, RwLock};
+use tokio_util::sync::CancellationToken;
+use tracing::warn;
 
 use taskrun_core::{
-    ChatMessage, ChatRole, RunEvent, RunEventType, RunId, RunStatus, Task, TaskId, TaskStatus,
-    WorkerId, WorkerInfo, WorkerStatus,
+    Artifact, ChatMessage, ChatRole, FailureReason, RunEvent, RunEventType, RunId, RunMilestone,
+    RunResultSummary, RunStatus, Schedule, ScheduleId, Task, TaskId, TaskStatus, WorkerId,
+    WorkerInfo, WorkerStatus,
 };
 use taskrun_proto::pb::RunServerMessage;
-
+use taskrun_tui_components::TimeFormat;
+
+use crate::control_plane::approvals::{Approval, ApprovalQueue};
+use crate::control_plane::assignments::Assignments;
+use crate::control_plane::audit::{AuditAction, AuditLog, AuditRecord};
+use crate::control_plane::chaos::Chaos;
+use crate::control_plane::completions::TaskCompletions;
+use crate::control_plane::conversations::Conversations;
 use crate::control_plane::crypto::{BootstrapToken, CertificateAuthority};
+use crate::control_plane::debug_stats::DebugStats;
+use crate::control_plane::event_store::{EventStore, InMemoryEventStore};
+use crate::control_plane::files::FileStore;
+use crate::control_plane::grafana::WorkerSamples;
+use crate::control_plane::groups::GroupCounts;
+use crate::control_plane::http::request_log::HttpStats;
+use crate::control_plane::leader::Leadership;
+use crate::control_plane::metrics::{Histogram, SCHEDULING_LATENCY_BUCKETS};
+use crate::control_plane::output_store::RunOutputs;
+use crate::control_plane::queue::QueueEntry;
+use crate::control_plane::quotas::Quotas;
+use crate::control_plane::retention::RetentionStats;
+use crate::control_plane::search::OutputIndex;
+use crate::control_plane::toggles::{RuntimeToggles, Toggles};
 
 // ============================================================================
 // UI Notification Types
@@ -27,20 +52,46 @@ pub enum UiNotification {
         worker_id: WorkerId,
         hostname: String,
         agents: Vec<String>,
+        labels: HashMap<String, String>,
     },
     /// A worker disconnected from the control plane.
-    WorkerDisconnected { worker_id: WorkerId },
+    WorkerDisconnected {
+        worker_id: WorkerId,
+        namespace: String,
+    },
     /// A worker sent a heartbeat.
     WorkerHeartbeat {
         worker_id: WorkerId,
         status: WorkerStatus,
         active_runs: u32,
         max_concurrent_runs: u32,
+        metrics: HashMap<String, String>,
+    },
+    /// A worker's liveness status changed (e.g. stale heartbeats).
+    WorkerStatusChanged {
+        worker_id: WorkerId,
+        status: WorkerStatus,
+    },
+    /// A draining worker finished its last run.
+    WorkerDrained { worker_id: WorkerId },
+    /// A worker changed its agents, labels or run limit without reconnecting.
+    WorkerUpdated {
+        worker_id: WorkerId,
+        agents: Vec<String>,
+        labels: HashMap<String, String>,
+        max_concurrent_runs: u32,
     },
     /// A new task was created.
-    TaskCreated { task_id: TaskId, agent: String },
+    TaskCreated {
+        task_id: TaskId,
+        agent: String,
+        created_by: String,
+        client_ip: Option<String>,
+    },
     /// Task status changed.
     TaskStatusChanged { task_id: TaskId, status: TaskStatus },
+    /// A task waited in Pending past its TTL and was failed.
+    TaskExpired { task_id: TaskId, reason: String },
     /// Run status changed.
     RunStatusChanged {
         run_id: RunId,
@@ -52,8 +103,19 @@ pub enum UiNotification {
     RunOutputChunk {
         run_id: RunId,
         task_id: TaskId,
+        seq: u64,
         content: String,
     },
+    /// The agent of a run reported a milestone.
+    RunMilestone {
+        run_id: RunId,
+        milestone: RunMilestone,
+    },
+    /// The worker of a finished run reported its result summary.
+    RunResult {
+        run_id: RunId,
+        result: RunResultSummary,
+    },
     /// Run event occurred.
     RunEvent {
         run_id: RunId,
@@ -68,7 +130,42 @@ pub enum UiNotification {
         task_id: TaskId,
         role: ChatRole,
         content: String,
+        attachments: Vec<Artifact>,
     },
+    /// A schedule was created, fired, or paused/resumed.
+    ScheduleUpdated { schedule: Schedule },
+    /// A schedule was deleted.
+    ScheduleDeleted { schedule_id: ScheduleId },
+    /// An action was recorded in the audit log.
+    AuditRecorded { record: AuditRecord },
+    /// A worker asked an operator to approve a tool use.
+    ApprovalRequested { approval: Approval },
+    /// A pending approval was decided, expired, or dropped with its run.
+    ApprovalResolved { approval_id: String },
+    /// Progress of unfinished or recently finished groups changed.
+    GroupsUpdated { groups: Vec<(String, GroupCounts)> },
+    /// Positions of queued tasks changed.
+    QueueUpdated { entries: Vec<QueueEntry> },
+    /// The settings file was reloaded.
+    ConfigReloaded { changed: Vec<String> },
+    /// Runtime toggles changed.
+    TogglesChanged { toggles: RuntimeToggles },
+    /// The settings file changed the time zone or locale of timestamps.
+    TimeFormatChanged { time_format: TimeFormat },
+    /// Periodic process and state counters.
+    DebugStats { stats: DebugStats },
+}
+
+impl UiNotification {
+    /// Notification for a newly created `task`.
+    pub fn task_created(task: &Task) -> Self {
+        Self::TaskCreated {
+            task_id: task.id.clone(),
+            agent: task.agent_name.clone(),
+            created_by: task.created_by.clone(),
+            client_ip: task.client_ip.clone(),
+        }
+    }
 }
 
 /// Type alias for UI notification sender.
@@ -95,6 +192,25 @@ pub enum StreamEvent {
         error_message: Option<String>,
         timestamp_ms: i64,
     },
+    /// The agent reported a milestone.
+    Milestone { milestone: RunMilestone },
+    /// The agent called a tool.
+    ToolCall {
+        /// ID of the ToolRequested event, repeated on the result.
+        call_id: String,
+        tool_name: String,
+        /// Short summary of the input (file path, command, pattern).
+        input_summary: Option<String>,
+        timestamp_ms: i64,
+    },
+    /// A tool call returned.
+    ToolResult {
+        /// None if the worker could not tie the result to its call.
+        call_id: Option<String>,
+        tool_name: Option<String>,
+        is_error: bool,
+        timestamp_ms: i64,
+    },
 }
 
 /// Type alias for broadcast sender of stream events.
@@ -119,11 +235,77 @@ pub struct ConnectedWorker {
     /// Maximum concurrent runs.
     pub max_concurrent_runs: u32,
 
+    /// Extra runs reserved for interactive work on top of `max_concurrent_runs`.
+    pub interactive_slots: u32,
+
     /// Timestamp of last heartbeat.
     pub last_heartbeat: DateTime<Utc>,
 
+    /// When `DrainWorker` was called (None = not draining).
+    pub draining_since: Option<DateTime<Utc>>,
+
     /// Channel to send messages to this worker.
     pub tx: mpsc::Sender<RunServerMessage>,
+
+    /// Cancelled to close the worker's stream from the control plane.
+    pub shutdown: CancellationToken,
+}
+
+// ============================================================================
+// ID Prefix Lookup
+// ============================================================================
+
+/// Errors from resolving a full or abbreviated ID.
+#[derive(Debug, Error)]
+pub enum IdLookupError {
+    #[error("{kind} not found: {prefix}")]
+    NotFound { kind: &'static str, prefix: String },
+
+    #[error("Ambiguous {kind} ID prefix '{prefix}' matches: {}", .matches.join(", "))]
+    Ambiguous {
+        kind: &'static str,
+        prefix: String,
+        matches: Vec<String>,
+    },
+}
+
+/// Resolve `prefix` against `ids` like git short hashes.
+/// An exact match always wins; otherwise the prefix must match exactly one ID.
+pub(crate) fn resolve_prefix<'a>(
+    kind: &'static str,
+    prefix: &str,
+    ids: impl Iterator<Item = &'a str>,
+) -> Result<String, IdLookupError> {
+    let not_found = || IdLookupError::NotFound {
+        kind,
+        prefix: prefix.to_string(),
+    };
+    if prefix.is_empty() {
+        return Err(not_found());
+    }
+
+    let mut matches: Vec<String> = Vec::new();
+    for id in ids {
+        if id == prefix {
+            return Ok(id.to_string());
+        }
+        if id.starts_with(prefix) {
+            matches.push(id.to_string());
+        }
+    }
+
+    match matches.len() {
+        0 => Err(not_found()),
+        1 => Ok(matches.remove(0)),
+        _ => {
+            matches.sort();
+            Err(IdLookupError::Ambiguous {
+                kind,
+                prefix: prefix.to_string(),
+                matches,
+            })
+        }
+    }
 }
 
 /// Shared application state.
@@ -134,11 +316,15 @@ pub struct AppState {
     /// Tasks indexed by TaskId.
     pub tasks: RwLock<HashMap<TaskId, Task>>,
 
-    /// Run events indexed by RunId (each run can have multiple events).
-    pub events: RwLock<HashMap<RunId, Vec<RunEvent>>>,
+    /// Run event storage (in-memory or file-backed).
+    pub events: Arc<dyn EventStore>,
+
+    /// Run output (accumulated content from output chunks), spilled to storage
+    /// past a size threshold.
+    pub outputs: RunOutputs,
 
-    /// Run output indexed by RunId (accumulated content from output chunks).
-    pub outputs: RwLock<HashMap<RunId, String>>,
+    /// Word index over run output, for search.
+    pub output_index: OutputIndex,
 
     /// Chat messages indexed by RunId (conversation history).
     pub chat_messages: RwLock<HashMap<RunId, Vec<ChatMessage>>>,
@@ -155,6 +341,48 @@ pub struct AppState {
 
     /// Optional channel for sending notifications to the TUI.
     pub ui_tx: Option<UiNotificationSender>,
+
+    /// Periodic worker count samples for the Grafana datasource.
+    pub worker_samples: RwLock<WorkerSamples>,
+
+    /// Append-only log of mutating actions.
+    pub audit: Arc<AuditLog>,
+
+    /// Totals reclaimed by the run data compactor.
+    pub retention: RetentionStats,
+
+    /// Tool uses waiting for an operator's approval.
+    pub approvals: ApprovalQueue,
+
+    /// Run assignments waiting for their worker's acknowledgement.
+    pub assignments: Assignments,
+
+    /// `/v1/responses` conversations continued past their run.
+    pub conversations: Conversations,
+
+    /// Requests waiting for tasks to finish.
+    pub completions: TaskCompletions,
+
+    /// Uploaded input files and run artifacts.
+    pub files: FileStore,
+
+    /// Per-namespace and per-API-key limits and output usage.
+    pub quotas: Quotas,
+
+    /// Maintenance mode, scheduling freeze and other runtime toggles.
+    pub toggles: Toggles,
+
+    /// Fault injection flags (inert unless enabled with `--enable-chaos`).
+    pub chaos: Chaos,
+
+    /// HTTP request totals by route.
+    pub http: HttpStats,
+
+    /// Whether this instance is the leader (always, unless HA is enabled).
+    pub leadership: Leadership,
+
+    /// Time tasks waited in Pending before they were assigned.
+    pub scheduling_latency: Histogram,
 }
 
 impl AppState {
@@ -163,13 +391,28 @@ impl AppState {
         Arc::new(Self {
             workers: RwLock::new(HashMap::new()),
             tasks: RwLock::new(HashMap::new()),
-            events: RwLock::new(HashMap::new()),
-            outputs: RwLock::new(HashMap::new()),
+            events: Arc::new(InMemoryEventStore::new()),
+            outputs: RunOutputs::in_memory(),
+            output_index: OutputIndex::default(),
             chat_messages: RwLock::new(HashMap::new()),
             stream_channels: RwLock::new(HashMap::new()),
             bootstrap_tokens: RwLock::new(HashMap::new()),
             ca: None,
             ui_tx: None,
+            worker_samples: RwLock::new(WorkerSamples::new()),
+            audit: AuditLog::in_memory(),
+            retention: RetentionStats::default(),
+            approvals: ApprovalQueue::default(),
+            assignments: Assignments::default(),
+            conversations: Conversations::default(),
+            completions: TaskCompletions::default(),
+            files: FileStore::default(),
+            quotas: Quotas::default(),
+            toggles: Toggles::default(),
+            chaos: Chaos::default(),
+            http: HttpStats::default(),
+            leadership: Leadership::default(),
+            scheduling_latency: Histogram::new(SCHEDULING_LATENCY_BUCKETS),
         })
     }
 
@@ -178,13 +421,28 @@ impl AppState {
         Arc::new(Self {
             workers: RwLock::new(HashMap::new()),
             tasks: RwLock::new(HashMap::new()),
-            events: RwLock::new(HashMap::new()),
-            outputs: RwLock::new(HashMap::new()),
+            events: Arc::new(InMemoryEventStore::new()),
+            outputs: RunOutputs::in_memory(),
+            output_index: OutputIndex::default(),
             chat_messages: RwLock::new(HashMap::new()),
             stream_channels: RwLock::new(HashMap::new()),
             bootstrap_tokens: RwLock::new(HashMap::new()),
             ca: Some(ca),
             ui_tx: None,
+            worker_samples: RwLock::new(WorkerSamples::new()),
+            audit: AuditLog::in_memory(),
+            retention: RetentionStats::default(),
+            approvals: ApprovalQueue::default(),
+            assignments: Assignments::default(),
+            conversations: Conversations::default(),
+            completions: TaskCompletions::default(),
+            files: FileStore::default(),
+            quotas: Quotas::default(),
+            toggles: Toggles::default(),
+            chaos: Chaos::default(),
+            http: HttpStats::default(),
+            leadership: Leadership::default(),
+            scheduling_latency: Histogram::new(SCHEDULING_LATENCY_BUCKETS),
         })
     }
 
@@ -193,24 +451,79 @@ impl AppState {
     #[allow(dead_code)] // Used by taskrun-server TUI
     pub fn with_ui_channel(
         ca: Option<CertificateAuthority>,
+        events: Arc<dyn EventStore>,
+        outputs: RunOutputs,
+        audit: Arc<AuditLog>,
     ) -> (Arc<Self>, broadcast::Receiver<UiNotification>) {
         let (tx, rx) = broadcast::channel(256);
+        // Artifacts go to the store run output spills to
+        let files = FileStore::default().with_store(outputs.store());
         let state = Arc::new(Self {
             workers: RwLock::new(HashMap::new()),
             tasks: RwLock::new(HashMap::new()),
-            events: RwLock::new(HashMap::new()),
-            outputs: RwLock::new(HashMap::new()),
+            events,
+            outputs,
+            output_index: OutputIndex::default(),
             chat_messages: RwLock::new(HashMap::new()),
             stream_channels: RwLock::new(HashMap::new()),
             bootstrap_tokens: RwLock::new(HashMap::new()),
             ca,
             ui_tx: Some(tx),
+            worker_samples: RwLock::new(WorkerSamples::new()),
+            audit,
+            retention: RetentionStats::default(),
+            approvals: ApprovalQueue::default(),
+            assignments: Assignments::default(),
+            conversations: Conversations::default(),
+            completions: TaskCompletions::default(),
+            files,
+            quotas: Quotas::default(),
+            toggles: Toggles::default(),
+            chaos: Chaos::default(),
+            http: HttpStats::default(),
+            leadership: Leadership::default(),
+            scheduling_latency: Histogram::new(SCHEDULING_LATENCY_BUCKETS),
         });
         (state, rx)
     }
 
+    /// Record a mutating action in the audit log and show it in the UI.
+    pub async fn audit(
+        &self,
+        actor: &str,
+        action: AuditAction,
+        target: &str,
+        payload: &impl serde::Serialize,
+    ) {
+        self.audit_from(actor, None, action, target, payload).await;
+    }
+
+    /// Record an audited action of an HTTP client at `client_ip`.
+    pub async fn audit_from(
+        &self,
+        actor: &str,
+        client_ip: Option<&str>,
+        action: AuditAction,
+        target: &str,
+        payload: &impl serde::Serialize,
+    ) {
+        let record = self
+            .audit
+            .record_from(actor, client_ip, action, target, payload)
+            .await;
+        self.notify_ui(UiNotification::AuditRecorded { record });
+    }
+
+    /// Subscribe to the UI notification feed (None = no channel configured).
+    pub fn subscribe_ui(&self) -> Option<broadcast::Receiver<UiNotification>> {
+        self.ui_tx.as_ref().map(|tx| tx.subscribe())
+    }
+
     /// Send a notification to the UI if a channel is configured.
     pub fn notify_ui(&self, notification: UiNotification) {
+        if let UiNotification::TaskStatusChanged { task_id, .. } = &notification {
+            self.completions.notify(task_id);
+        }
         if let Some(ref tx) = self.ui_tx {
             // Ignore send errors (no subscribers = ok to drop)
             let _ = tx.send(notification);
@@ -229,64 +542,111 @@ impl AppState {
         self.tasks.read().await.len()
     }
 
+    /// Resolve a full or unique-prefix task ID among the tasks in
+    /// `namespace` (None = all). Tasks of other namespaces are not found.
+    pub async fn resolve_task_id(
+        &self,
+        prefix: &str,
+        namespace: Option<&str>,
+    ) -> Result<TaskId, IdLookupError> {
+        let tasks = self.tasks.read().await;
+        let task_ids = tasks
+            .values()
+            .filter(|t| namespace.map_or(true, |ns| t.namespace == ns))
+            .map(|t| t.id.as_str());
+        resolve_prefix("Task", prefix, task_ids).map(TaskId::new)
+    }
+
+    /// Resolve a full or unique-prefix run ID among the runs of tasks in
+    /// `namespace` (None = all).
+    pub async fn resolve_run_id(
+        &self,
+        prefix: &str,
+        namespace: Option<&str>,
+    ) -> Result<RunId, IdLookupError> {
+        let tasks = self.tasks.read().await;
+        let run_ids = tasks
+            .values()
+            .filter(|t| namespace.map_or(true, |ns| t.namespace == ns))
+            .flat_map(|t| t.runs.iter().map(|r| r.run_id.as_str()));
+        resolve_prefix("Run", prefix, run_ids).map(RunId::new)
+    }
+
+    /// Resolve a full or unique-prefix worker ID among the workers in
+    /// `namespace` (None = all).
+    pub async fn resolve_worker_id(
+        &self,
+        prefix: &str,
+        namespace: Option<&str>,
+    ) -> Result<WorkerId, IdLookupError> {
+        let workers = self.workers.read().await;
+        let worker_ids = workers
+            .values()
+            .filter(|w| namespace.map_or(true, |ns| w.info.namespace == ns))
+            .map(|w| w.info.worker_id.as_str());
+        resolve_prefix("Worker", prefix, worker_ids).map(WorkerId::new)
+    }
+
     /// Store a run event.
     pub async fn store_event(&self, event: RunEvent) {
-        let run_id = event.run_id.clone();
-        let mut events = self.events.write().await;
-        events.entry(run_id).or_default().push(event);
+        if let Err(e) = self.events.append(event).await {
+            warn!(error = %e, "Failed to store run event");
+        }
     }
 
     /// Get all events for a run.
     #[allow(dead_code)]
     pub async fn get_events_by_run(&self, run_id: &RunId) -> Vec<RunEvent> {
-        let events = self.events.read().await;
-        events.get(run_id).cloned().unwrap_or_default()
+        self.events.list_by_run(run_id).await.unwrap_or_else(|e| {
+            warn!(run_id = %run_id, error = %e, "Failed to list run events");
+            Vec::new()
+        })
     }
 
-    /// Get all events for a task (across all runs).
+    /// Get all events for a task (across all runs), sorted by timestamp.
     pub async fn get_events_by_task(&self, task_id: &TaskId) -> Vec<RunEvent> {
-        let events = self.events.read().await;
-        let mut result = Vec::new();
-        for run_events in events.values() {
-            for event in run_events {
-                if &event.task_id == task_id {
-                    result.push(event.clone());
-                }
-            }
-        }
-        // Sort by timestamp
-        result.sort_by_key(|e| e.timestamp_ms);
-        result
+        self.events.list_by_task(task_id).await.unwrap_or_else(|e| {
+            warn!(task_id = %task_id, error = %e, "Failed to list task events");
+            Vec::new()
+        })
     }
 
-    /// Append output content to a run.
-    pub async fn append_output(&self, run_id: &RunId, content: &str) {
-        let mut outputs = self.outputs.write().await;
-        outputs.entry(run_id.clone()).or_default().push_str(content);
+    /// Append output chunk `seq` to a run.
+    pub async fn append_output(&self, run_id: &RunId, seq: u64, content: &str) {
+        self.outputs.append(run_id, seq, content).await;
+        self.output_index.add(run_id, content).await;
     }
 
     /// Get output for a run.
     pub async fn get_output(&self, run_id: &RunId) -> Option<String> {
-        let outputs = self.outputs.read().await;
-        outputs.get(run_id).cloned()
+        self.outputs.read_all(run_id).await
     }
 
-    /// Get output for a task (finds the first run with output).
-    pub async fn get_output_by_task(&self, task_id: &TaskId) -> Option<String> {
-        // Get the task to find its runs
-        let tasks = self.tasks.read().await;
-        if let Some(task) = tasks.get(task_id) {
-            let outputs = self.outputs.read().await;
-            // Return output from the first run that has output
-            for run in &task.runs {
-                if let Some(output) = outputs.get(&run.run_id) {
-                    return Some(output.clone());
-                }
+    /// Get the run whose output represents a task (the first run with output).
+    pub async fn output_run_for_task(&self, task_id: &TaskId) -> Option<RunId> {
+        let run_ids: Vec<RunId> = {
+            let tasks = self.tasks.read().await;
+            tasks
+                .get(task_id)?
+                .runs
+                .iter()
+                .map(|r| r.run_id.clone())
+                .collect()
+        };
+        for run_id in run_ids {
+            if self.outputs.len(&run_id).await.is_some() {
+                return Some(run_id);
             }
         }
         None
     }
 
+    /// Get output for a task (finds the first run with output).
+    pub async fn get_output_by_task(&self, task_id: &TaskId) -> Option<String> {
+        let run_id = self.output_run_for_task(task_id).await?;
+        self.get_output(&run_id).await
+    }
+
     // ========================================================================
     // Chat Message Methods
     // ========================================================================
@@ -305,7 +665,6 @@ impl AppState {
     }
 
     /// Get chat messages for a task (across all runs, combined).
-    #[allow(dead_code)]
     pub async fn get_chat_messages_by_task(&self, task_id: &TaskId) -> Vec<ChatMessage> {
         let tasks = self.tasks.read().await;
         if let Some(task) = tasks.get(task_id) {
@@ -323,6 +682,123 @@ impl AppState {
         Vec::new()
     }
 
+    // ========================================================================
+    // Run Finalization
+    // ========================================================================
+
+    /// Move an active run (and its task) to a terminal status on the control
+    /// plane's own authority, e.g. when reaping or when a cancel goes unacknowledged.
+    ///
+    /// Frees the worker slot, notifies stream subscribers and the UI, and schedules
+    /// stream channel cleanup. Returns false if the run is unknown or already terminal.
+    pub async fn finalize_run(
+        self: &Arc<Self>,
+        run_id: &RunId,
+        status: RunStatus,
+        failure_reason: Option<FailureReason>,
+        error_message: Option<String>,
+    ) -> bool {
+        let task_status = match status {
+            RunStatus::Completed => TaskStatus::Completed,
+            RunStatus::Failed => TaskStatus::Failed,
+            RunStatus::Cancelled => TaskStatus::Cancelled,
+            _ => return false,
+        };
+        self.finish_run(run_id, status, failure_reason, error_message, task_status)
+            .await
+    }
+
+    /// Fail an active run whose worker was lost and put its task straight
+    /// back in Pending, to be retried. Unlike `finalize_run`, the task never
+    /// passes through Failed, so nothing waiting on it sees it finish.
+    /// Returns false if the run is unknown or already terminal.
+    pub async fn requeue_lost_run(self: &Arc<Self>, run_id: &RunId, error_message: String) -> bool {
+        self.finish_run(
+            run_id,
+            RunStatus::Failed,
+            Some(FailureReason::WorkerLost),
+            Some(error_message),
+            TaskStatus::Pending,
+        )
+        .await
+    }
+
+    async fn finish_run(
+        self: &Arc<Self>,
+        run_id: &RunId,
+        status: RunStatus,
+        failure_reason: Option<FailureReason>,
+        error_message: Option<String>,
+        task_status: TaskStatus,
+    ) -> bool {
+        let (task_id, worker_id) = {
+            let mut tasks = self.tasks.write().await;
+            let Some((task, idx)) = tasks.values_mut().find_map(|task| {
+                let idx = task.runs.iter().position(|r| &r.run_id == run_id)?;
+                Some((task, idx))
+            }) else {
+                return false;
+            };
+            let run = &mut task.runs[idx];
+            if !run.status.is_active() {
+                return false;
+            }
+            run.status = status;
+            run.finished_at = Some(Utc::now());
+            if error_message.is_some() {
+                run.error_message = error_message.clone();
+            }
+            run.failure_reason = failure_reason;
+            let worker_id = run.worker_id.clone();
+            task.status = task_status;
+            if task_status == TaskStatus::Pending {
+                task.failure_reason = None;
+                task.failure_message = None;
+            } else {
+                task.failure_reason = failure_reason;
+            }
+            (task.id.clone(), worker_id)
+        };
+
+        {
+            let mut workers = self.workers.write().await;
+            if let Some(worker) = workers.get_mut(&worker_id) {
+                worker.active_runs = worker.active_runs.saturating_sub(1);
+            }
+        }
+
+        self.publish_stream_event(
+            run_id,
+            StreamEvent::StatusUpdate {
+                status,
+                error_message,
+                timestamp_ms: Utc::now().timestamp_millis(),
+            },
+        )
+        .await;
+
+        self.notify_ui(UiNotification::RunStatusChanged {
+            run_id: run_id.clone(),
+            task_id: task_id.clone(),
+            worker_id: Some(worker_id),
+            status,
+        });
+        self.notify_ui(UiNotification::TaskStatusChanged {
+            task_id,
+            status: task_status,
+        });
+
+        // Give SSE subscribers a moment to receive the final event
+        let state = self.clone();
+        let run_id = run_id.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+            state.remove_stream_channel(&run_id).await;
+        });
+
+        true
+    }
+
     // ========================================================================
     // Streaming Methods
     // ========================================================================
@@ -361,9 +837,11 @@ impl AppState {
     // ========================================================================
 
     /// Check if any connected worker supports the given agent.
-    pub async fn has_agent(&self, agent_name: &str) -> bool {
+    pub async fn has_agent(&self, agent_name: &str, namespace: &str) -> bool {
         let workers = self.workers.read().await;
-        workers.values().any(|w| w.info.supports_agent(agent_name))
+        workers
+            .values()
+            .any(|w| w.info.namespace == namespace && w.info.supports_agent(agent_name))
     }
 }
 
@@ -372,13 +850,59 @@ impl Default for AppState {
         Self {
             workers: RwLock::new(HashMap::new()),
             tasks: RwLock::new(HashMap::new()),
-            events: RwLock::new(HashMap::new()),
-            outputs: RwLock::new(HashMap::new()),
+            events: Arc::new(InMemoryEventStore::new()),
+            outputs: RunOutputs::in_memory(),
+            output_index: OutputIndex::default(),
             chat_messages: RwLock::new(HashMap::new()),
             stream_channels: RwLock::new(HashMap::new()),
             bootstrap_tokens: RwLock::new(HashMap::new()),
             ca: None,
             ui_tx: None,
+            worker_samples: RwLock::new(WorkerSamples::new()),
+            audit: AuditLog::in_memory(),
+            retention: RetentionStats::default(),
+            approvals: ApprovalQueue::default(),
+            assignments: Assignments::default(),
+            conversations: Conversations::default(),
+            completions: TaskCompletions::default(),
+            files: FileStore::default(),
+            quotas: Quotas::default(),
+            toggles: Toggles::default(),
+            chaos: Chaos::default(),
+            http: HttpStats::default(),
+            leadership: Leadership::default(),
+            scheduling_latency: Histogram::new(SCHEDULING_LATENCY_BUCKETS),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_prefix() {
+        let ids = ["abc12345", "abc99999", "def00000"];
+
+        assert_eq!(
+            resolve_prefix("Task", "def", ids.into_iter()).unwrap(),
+            "def00000"
+        );
+        assert_eq!(
+            resolve_prefix("Task", "abc12345", ids.into_iter()).unwrap(),
+            "abc12345"
+        );
+        assert!(matches!(
+            resolve_prefix("Task", "abc", ids.into_iter()),
+            Err(IdLookupError::Ambiguous { ref matches, .. }) if matches.len() == 2
+        ));
+        assert!(matches!(
+            resolve_prefix("Task", "xyz", ids.into_iter()),
+            Err(IdLookupError::NotFound { .. })
+        ));
+        assert!(matches!(
+            resolve_prefix("Task", "", ids.into_iter()),
+            Err(IdLookupError::NotFound { .. })
+        ));
+    }
+}
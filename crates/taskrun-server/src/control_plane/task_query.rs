@@ -0,0 +1,206 @@
+//! Task listing - filter, sort and paginate tasks.
+//!
+//! Shared by the `ListTasks` RPC and `GET /v1/tasks`. Pages are keyed on the
+//! `(created_at, id)` of the last task returned rather than an offset, so
+//! tasks created while a client pages through the list do not shift entries
+//! between pages or repeat them.
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::Deserialize;
+use thiserror::Error;
+
+use taskrun_core::Task;
+
+use crate::control_plane::bulk::TaskFilter;
+use crate::control_plane::state::AppState;
+
+/// Page size when the caller does not ask for one.
+pub const DEFAULT_PAGE_SIZE: usize = 100;
+
+/// Largest page a caller can ask for.
+pub const MAX_PAGE_SIZE: usize = 1000;
+
+/// Task query errors.
+#[derive(Debug, Error)]
+pub enum TaskQueryError {
+    #[error("Invalid page token")]
+    InvalidPageToken,
+}
+
+/// Order of listed tasks.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskSort {
+    /// Newest first.
+    #[default]
+    CreatedDesc,
+    /// Oldest first.
+    CreatedAsc,
+}
+
+/// Which tasks to list, and which page of them.
+#[derive(Debug, Clone, Default)]
+pub struct TaskQuery {
+    pub filter: TaskFilter,
+    /// Only tasks created at or after this time.
+    pub created_after: Option<DateTime<Utc>>,
+    /// Only tasks created before this time.
+    pub created_before: Option<DateTime<Utc>>,
+    pub sort: TaskSort,
+    /// Page size (0 = `DEFAULT_PAGE_SIZE`, capped at `MAX_PAGE_SIZE`).
+    pub limit: usize,
+    /// Token from the previous page's `next_page_token`.
+    pub page_token: Option<String>,
+}
+
+impl TaskQuery {
+    fn matches(&self, task: &Task) -> bool {
+        self.filter.matches(task)
+            && self.created_after.map_or(true, |t| task.created_at >= t)
+            && self.created_before.map_or(true, |t| task.created_at < t)
+    }
+
+    fn page_size(&self) -> usize {
+        match self.limit {
+            0 => DEFAULT_PAGE_SIZE,
+            n => n.min(MAX_PAGE_SIZE),
+        }
+    }
+}
+
+/// One page of tasks.
+#[derive(Debug, Clone)]
+pub struct TaskPage {
+    pub tasks: Vec<Task>,
+    /// Token for the next page, or None if this is the last one.
+    pub next_page_token: Option<String>,
+}
+
+/// List the page of tasks selected by `query`.
+pub async fn list(state: &AppState, query: &TaskQuery) -> Result<TaskPage, TaskQueryError> {
+    let after = query.page_token.as_deref().map(decode_token).transpose()?;
+
+    let tasks = state.tasks.read().await;
+    let mut matching: Vec<&Task> = tasks
+        .values()
+        .filter(|task| query.matches(task))
+        .filter(|task| {
+            after.as_ref().map_or(true, |(created_at, id)| {
+                let key = (task.created_at, task.id.as_str());
+                let cursor = (*created_at, id.as_str());
+                match query.sort {
+                    TaskSort::CreatedDesc => key < cursor,
+                    TaskSort::CreatedAsc => key > cursor,
+                }
+            })
+        })
+        .collect();
+
+    matching.sort_by(|a, b| {
+        let order = (a.created_at, a.id.as_str()).cmp(&(b.created_at, b.id.as_str()));
+        match query.sort {
+            TaskSort::CreatedDesc => order.reverse(),
+            TaskSort::CreatedAsc => order,
+        }
+    });
+
+    let page_size = query.page_size();
+    let next_page_token =
+        (matching.len() > page_size).then(|| encode_token(matching[page_size - 1]));
+    matching.truncate(page_size);
+
+    Ok(TaskPage {
+        tasks: matching.into_iter().cloned().collect(),
+        next_page_token,
+    })
+}
+
+fn encode_token(task: &Task) -> String {
+    let cursor = format!(
+        "{}|{}",
+        task.created_at.to_rfc3339_opts(SecondsFormat::Nanos, true),
+        task.id
+    );
+    URL_SAFE_NO_PAD.encode(cursor)
+}
+
+fn decode_token(token: &str) -> Result<(DateTime<Utc>, String), TaskQueryError> {
+    let cursor = URL_SAFE_NO_PAD
+        .decode(token)
+        .ok()
+        .and_then(|bytes| String::from_utf8(bytes).ok())
+        .ok_or(TaskQueryError::InvalidPageToken)?;
+    let (created_at, id) = cursor
+        .split_once('|')
+        .ok_or(TaskQueryError::InvalidPageToken)?;
+    let created_at = DateTime::parse_from_rfc3339(created_at)
+        .map_err(|_| TaskQueryError::InvalidPageToken)?
+        .with_timezone(&Utc);
+    Ok((created_at, id.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_pages_cover_all_tasks_once() {
+        let state = AppState::new();
+        let start = Utc::now();
+        {
+            let mut tasks = state.tasks.write().await;
+            for i in 0..5 {
+                let mut task =
+                    Task::new(if i % 2 == 0 { "general" } else { "other" }, "{}", "test");
+                task.created_at = start + chrono::Duration::seconds(i);
+                tasks.insert(task.id.clone(), task);
+            }
+        }
+
+        let mut query = TaskQuery {
+            limit: 2,
+            sort: TaskSort::CreatedAsc,
+            ..TaskQuery::default()
+        };
+        let mut seen = Vec::new();
+        loop {
+            let page = list(&state, &query).await.unwrap();
+            seen.extend(page.tasks.iter().map(|t| t.created_at));
+            match page.next_page_token {
+                Some(token) => query.page_token = Some(token),
+                None => break,
+            }
+        }
+        let expected: Vec<_> = (0..5)
+            .map(|i| start + chrono::Duration::seconds(i))
+            .collect();
+        assert_eq!(seen, expected);
+
+        // Filters combine with the created_at range, newest first
+        let query = TaskQuery {
+            filter: TaskFilter {
+                agent_name: Some("general".to_string()),
+                ..TaskFilter::default()
+            },
+            created_after: Some(start + chrono::Duration::seconds(1)),
+            ..TaskQuery::default()
+        };
+        let page = list(&state, &query).await.unwrap();
+        let created: Vec<_> = page.tasks.iter().map(|t| t.created_at).collect();
+        assert_eq!(
+            created,
+            vec![
+                start + chrono::Duration::seconds(4),
+                start + chrono::Duration::seconds(2)
+            ]
+        );
+        assert!(page.next_page_token.is_none());
+
+        let query = TaskQuery {
+            page_token: Some("not a token".to_string()),
+            ..TaskQuery::default()
+        };
+        assert!(list(&state, &query).await.is_err());
+    }
+}
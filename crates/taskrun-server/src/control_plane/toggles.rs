@@ -0,0 +1,250 @@
+//! Runtime toggles.
+//!
+//! Switches an operator flips on a running control plane, without editing
+//! config files or restarting:
+//!
+//! - `maintenance`: new tasks are refused; queued and running work continues.
+//! - `scheduling_frozen`: queued tasks stay Pending instead of being assigned
+//!   to workers; tasks can still be created.
+//! - `default_priority`: lane of tasks created without one.
+//!
+//! Toggles are changed with the AdminService `UpdateToggles` RPC or the
+//! settings dialog of the server TUI. With `--toggles-file` they are kept in
+//! a JSON file, rewritten on every change and read back at startup.
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio::sync::{Mutex, RwLock};
+use tracing::info;
+
+use taskrun_core::TaskPriority;
+use taskrun_proto::pb;
+
+use crate::control_plane::audit::AuditAction;
+use crate::control_plane::state::{AppState, UiNotification};
+
+/// Errors reading or writing the toggles file.
+#[derive(Debug, Error)]
+pub enum ToggleError {
+    #[error("I/O error on toggles file '{path}': {source}")]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+
+    #[error("Invalid toggles file: {0}")]
+    Serialization(#[from] serde_json::Error),
+}
+
+/// A task was refused because the control plane is in maintenance mode.
+#[derive(Debug, Clone, Error)]
+#[error("The control plane is in maintenance mode and is not accepting new tasks")]
+pub struct MaintenanceMode;
+
+/// Current value of every toggle.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RuntimeToggles {
+    #[serde(default)]
+    pub maintenance: bool,
+    #[serde(default)]
+    pub scheduling_frozen: bool,
+    #[serde(default)]
+    pub default_priority: TaskPriority,
+}
+
+/// A change to some toggles. Unset fields keep their value.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct ToggleUpdate {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub maintenance: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scheduling_frozen: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_priority: Option<TaskPriority>,
+}
+
+impl RuntimeToggles {
+    /// These toggles with `update` applied.
+    pub fn apply(mut self, update: ToggleUpdate) -> Self {
+        if let Some(maintenance) = update.maintenance {
+            self.maintenance = maintenance;
+        }
+        if let Some(frozen) = update.scheduling_frozen {
+            self.scheduling_frozen = frozen;
+        }
+        if let Some(priority) = update.default_priority {
+            self.default_priority = priority;
+        }
+        self
+    }
+}
+
+impl From<RuntimeToggles> for pb::RuntimeToggles {
+    fn from(toggles: RuntimeToggles) -> Self {
+        pb::RuntimeToggles {
+            maintenance: toggles.maintenance,
+            scheduling_frozen: toggles.scheduling_frozen,
+            default_priority: pb::TaskPriority::from(toggles.default_priority).into(),
+        }
+    }
+}
+
+impl From<pb::UpdateTogglesRequest> for ToggleUpdate {
+    fn from(req: pb::UpdateTogglesRequest) -> Self {
+        ToggleUpdate {
+            maintenance: req.maintenance,
+            scheduling_frozen: req.scheduling_frozen,
+            default_priority: match req.default_priority() {
+                pb::TaskPriority::Unspecified => None,
+                priority => Some(priority.into()),
+            },
+        }
+    }
+}
+
+/// The toggles of a control plane and the file they are kept in.
+#[derive(Debug, Default)]
+pub struct Toggles {
+    current: RwLock<RuntimeToggles>,
+    /// Toggles file (None = in memory only). Held while the file is written.
+    file: Mutex<Option<PathBuf>>,
+}
+
+impl Toggles {
+    /// Load toggles from `path` and keep them there from now on. A missing
+    /// or empty file leaves every toggle off.
+    pub async fn open(&self, path: PathBuf) -> Result<RuntimeToggles, ToggleError> {
+        let mut file = self.file.lock().await;
+        let toggles = match tokio::fs::read_to_string(&path).await {
+            Ok(contents) if contents.trim().is_empty() => RuntimeToggles::default(),
+            Ok(contents) => serde_json::from_str(&contents)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => RuntimeToggles::default(),
+            Err(source) => return Err(ToggleError::Io { path, source }),
+        };
+        *self.current.write().await = toggles;
+        *file = Some(path);
+        Ok(toggles)
+    }
+
+    /// Current toggles.
+    pub async fn get(&self) -> RuntimeToggles {
+        *self.current.read().await
+    }
+
+    /// Apply `update` and save the result. On a save error the toggles are
+    /// left as they were.
+    pub async fn update(&self, update: ToggleUpdate) -> Result<RuntimeToggles, ToggleError> {
+        let file = self.file.lock().await;
+        let toggles = self.get().await.apply(update);
+        if let Some(path) = file.as_ref() {
+            save(path, &toggles).await?;
+        }
+        *self.current.write().await = toggles;
+        Ok(toggles)
+    }
+
+    /// Refuse new tasks while in maintenance mode.
+    pub async fn check_accepting(&self) -> Result<(), MaintenanceMode> {
+        if self.current.read().await.maintenance {
+            return Err(MaintenanceMode);
+        }
+        Ok(())
+    }
+
+    /// Whether queued tasks are held back from workers.
+    pub async fn is_frozen(&self) -> bool {
+        self.current.read().await.scheduling_frozen
+    }
+
+    /// Lane of tasks created without a priority.
+    pub async fn default_priority(&self) -> TaskPriority {
+        self.current.read().await.default_priority
+    }
+}
+
+async fn save(path: &Path, toggles: &RuntimeToggles) -> Result<(), ToggleError> {
+    let io_err = |source| ToggleError::Io {
+        path: path.to_path_buf(),
+        source,
+    };
+
+    let contents = serde_json::to_string_pretty(toggles)?;
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        tokio::fs::create_dir_all(parent).await.map_err(io_err)?;
+    }
+
+    let tmp_path = path.with_extension("json.tmp");
+    tokio::fs::write(&tmp_path, contents)
+        .await
+        .map_err(io_err)?;
+    tokio::fs::rename(&tmp_path, path).await.map_err(io_err)
+}
+
+/// Change toggles on behalf of `actor`, notifying the UI and auditing the
+/// change.
+pub async fn update(
+    state: &AppState,
+    actor: &str,
+    update: ToggleUpdate,
+) -> Result<RuntimeToggles, ToggleError> {
+    let toggles = state.toggles.update(update).await?;
+    info!(
+        maintenance = toggles.maintenance,
+        scheduling_frozen = toggles.scheduling_frozen,
+        default_priority = ?toggles.default_priority,
+        changed_by = actor,
+        "Runtime toggles updated"
+    );
+    state.notify_ui(UiNotification::TogglesChanged { toggles });
+    state
+        .audit(actor, AuditAction::TogglesUpdated, "toggles", &update)
+        .await;
+    Ok(toggles)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_updates_are_saved_and_read_back() {
+        let path =
+            std::env::temp_dir().join(format!("taskrun-toggles-{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let toggles = Toggles::default();
+        assert_eq!(
+            toggles.open(path.clone()).await.unwrap(),
+            RuntimeToggles::default()
+        );
+        toggles
+            .update(ToggleUpdate {
+                maintenance: Some(true),
+                default_priority: Some(TaskPriority::Interactive),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert!(toggles.check_accepting().await.is_err());
+
+        // Unset fields keep their value
+        let current = toggles
+            .update(ToggleUpdate {
+                scheduling_frozen: Some(true),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert!(current.maintenance);
+
+        let reopened = Toggles::default();
+        assert_eq!(reopened.open(path.clone()).await.unwrap(), current);
+        assert!(reopened.is_frozen().await);
+        assert_eq!(reopened.default_priority().await, TaskPriority::Interactive);
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,239 @@
+//! Token usage and cost reporting.
+//!
+//! Workers report what a run consumed on its completion event: the
+//! `input_tokens`, `output_tokens` and `cost_usd` metadata. Those are summed
+//! per task, per agent and per namespace for `GET /v1/usage`, and a run's own
+//! usage fills the `usage` of its OpenAI response object.
+
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use taskrun_core::{RunEvent, RunEventType, RunId, Task, TaskId};
+
+use crate::control_plane::state::AppState;
+
+/// What one run consumed.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct RunUsage {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    /// None if the run reported no cost.
+    pub cost_usd: Option<f64>,
+}
+
+impl RunUsage {
+    /// Usage recorded on a completion event, if it carries any.
+    pub fn from_event(event: &RunEvent) -> Option<Self> {
+        if !matches!(
+            event.event_type,
+            RunEventType::ExecutionCompleted | RunEventType::ExecutionFailed
+        ) {
+            return None;
+        }
+        let tokens = |key: &str| event.metadata.get(key).and_then(|v| v.parse().ok());
+        let input_tokens = tokens("input_tokens");
+        let output_tokens = tokens("output_tokens");
+        let cost_usd = event.metadata.get("cost_usd").and_then(|v| v.parse().ok());
+        if input_tokens.is_none() && output_tokens.is_none() && cost_usd.is_none() {
+            return None;
+        }
+        Some(Self {
+            input_tokens: input_tokens.unwrap_or(0),
+            output_tokens: output_tokens.unwrap_or(0),
+            cost_usd,
+        })
+    }
+
+    pub fn total_tokens(&self) -> u64 {
+        self.input_tokens + self.output_tokens
+    }
+}
+
+/// Usage summed over runs.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct UsageTotals {
+    /// Runs that reported usage.
+    pub runs: u64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub total_tokens: u64,
+    pub cost_usd: f64,
+}
+
+impl UsageTotals {
+    fn add(&mut self, usage: &RunUsage) {
+        self.runs += 1;
+        self.input_tokens += usage.input_tokens;
+        self.output_tokens += usage.output_tokens;
+        self.total_tokens += usage.total_tokens();
+        self.cost_usd += usage.cost_usd.unwrap_or(0.0);
+    }
+}
+
+/// Usage of one task.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TaskUsage {
+    pub task_id: TaskId,
+    pub agent_name: String,
+    pub namespace: String,
+    #[serde(flatten)]
+    pub usage: UsageTotals,
+}
+
+/// Usage of the runs finished since a point in time.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct UsageReport {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub since: Option<DateTime<Utc>>,
+    pub total: UsageTotals,
+    pub by_namespace: BTreeMap<String, UsageTotals>,
+    pub by_agent: BTreeMap<String, UsageTotals>,
+    /// Tasks that reported usage, most tokens first.
+    pub by_task: Vec<TaskUsage>,
+}
+
+/// Usage recorded on the run's completion event.
+pub async fn run_usage(state: &AppState, run_id: &RunId) -> Option<RunUsage> {
+    state
+        .get_events_by_run(run_id)
+        .await
+        .iter()
+        .rev()
+        .find_map(RunUsage::from_event)
+}
+
+/// Usage of the runs finished since `since` (None = ever) among the tasks in
+/// `namespace` (None = all).
+pub async fn report(
+    state: &AppState,
+    since: Option<DateTime<Utc>>,
+    namespace: Option<&str>,
+) -> UsageReport {
+    let tasks: Vec<Task> = state
+        .tasks
+        .read()
+        .await
+        .values()
+        .filter(|t| namespace.map_or(true, |ns| t.namespace == ns))
+        .cloned()
+        .collect();
+
+    let mut runs = Vec::new();
+    for task in &tasks {
+        for run in &task.runs {
+            let Some(finished_at) = run.finished_at else {
+                continue;
+            };
+            if since.is_some_and(|since| finished_at < since) {
+                continue;
+            }
+            if let Some(usage) = run_usage(state, &run.run_id).await {
+                runs.push((task, usage));
+            }
+        }
+    }
+    aggregate(since, runs)
+}
+
+fn aggregate(since: Option<DateTime<Utc>>, runs: Vec<(&Task, RunUsage)>) -> UsageReport {
+    let mut total = UsageTotals::default();
+    let mut by_namespace: BTreeMap<String, UsageTotals> = BTreeMap::new();
+    let mut by_agent: BTreeMap<String, UsageTotals> = BTreeMap::new();
+    let mut by_task: HashMap<&TaskId, TaskUsage> = HashMap::new();
+
+    for (task, usage) in runs {
+        total.add(&usage);
+        by_namespace
+            .entry(task.namespace.clone())
+            .or_default()
+            .add(&usage);
+        by_agent
+            .entry(task.agent_name.clone())
+            .or_default()
+            .add(&usage);
+        by_task
+            .entry(&task.id)
+            .or_insert_with(|| TaskUsage {
+                task_id: task.id.clone(),
+                agent_name: task.agent_name.clone(),
+                namespace: task.namespace.clone(),
+                usage: UsageTotals::default(),
+            })
+            .usage
+            .add(&usage);
+    }
+
+    let mut by_task: Vec<TaskUsage> = by_task.into_values().collect();
+    by_task.sort_by_key(|t| std::cmp::Reverse(t.usage.total_tokens));
+
+    UsageReport {
+        since,
+        total,
+        by_namespace,
+        by_agent,
+        by_task,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_usage_is_read_from_completion_events() {
+        let run_id = RunId::generate();
+        let task_id = TaskId::generate();
+        let event = RunEvent::execution_completed(run_id.clone(), task_id.clone(), Some(10))
+            .with_cost_usd(Some(0.25))
+            .with_token_usage(100, 20);
+        assert_eq!(
+            RunUsage::from_event(&event),
+            Some(RunUsage {
+                input_tokens: 100,
+                output_tokens: 20,
+                cost_usd: Some(0.25),
+            })
+        );
+
+        let bare = RunEvent::execution_completed(run_id.clone(), task_id.clone(), Some(10));
+        assert_eq!(RunUsage::from_event(&bare), None);
+        let started = RunEvent::new(
+            run_id,
+            task_id,
+            RunEventType::ExecutionStarted,
+            HashMap::from([("input_tokens".to_string(), "5".to_string())]),
+        );
+        assert_eq!(RunUsage::from_event(&started), None);
+    }
+
+    #[test]
+    fn test_usage_is_summed_per_task_agent_and_namespace() {
+        let a = Task::new("general", "{}", "test").with_namespace("team-a");
+        let b = Task::new("reviewer", "{}", "test").with_namespace("team-b");
+        let usage = |input, output, cost| RunUsage {
+            input_tokens: input,
+            output_tokens: output,
+            cost_usd: cost,
+        };
+
+        let report = aggregate(
+            None,
+            vec![
+                (&a, usage(100, 10, Some(0.5))),
+                (&a, usage(50, 5, None)),
+                (&b, usage(1000, 100, Some(2.0))),
+            ],
+        );
+
+        assert_eq!(report.total.runs, 3);
+        assert_eq!(report.total.total_tokens, 1265);
+        assert_eq!(report.total.cost_usd, 2.5);
+        assert_eq!(report.by_namespace["team-a"].input_tokens, 150);
+        assert_eq!(report.by_agent["reviewer"].output_tokens, 100);
+        assert_eq!(report.by_task.len(), 2);
+        assert_eq!(report.by_task[0].task_id, b.id);
+        assert_eq!(report.by_task[1].usage.runs, 2);
+    }
+}
@@ -0,0 +1,286 @@
+//! Pre-flight validation of a task spec ("lint before submitting").
+//!
+//! Runs the checks task creation and scheduling would, without creating
+//! anything: the agent against what connected workers advertise, the input,
+//! the quotas, and whether a worker could take the task now. Errors mean
+//! creation would be refused; warnings mean the task would be created but
+//! may wait, expire, or not behave as intended.
+
+use std::sync::Arc;
+
+use serde::Serialize;
+
+use taskrun_core::{TaskPriority, TaskStatus};
+use taskrun_proto::pb;
+
+use crate::control_plane::quotas::QuotaResource;
+use crate::control_plane::scheduler::Scheduler;
+use crate::control_plane::state::AppState;
+
+/// How bad a finding is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    /// Creating the task would fail.
+    Error,
+    /// The task would be created, but something looks off.
+    Warning,
+}
+
+/// One problem with a task spec.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Finding {
+    pub severity: Severity,
+    /// Stable identifier, e.g. `no_worker_for_agent`.
+    pub code: &'static str,
+    /// Request field the finding is about (None = the task as a whole).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub field: Option<&'static str>,
+    pub message: String,
+}
+
+impl Finding {
+    fn error(code: &'static str, field: Option<&'static str>, message: String) -> Self {
+        Self {
+            severity: Severity::Error,
+            code,
+            field,
+            message,
+        }
+    }
+
+    fn warning(code: &'static str, field: Option<&'static str>, message: String) -> Self {
+        Self {
+            severity: Severity::Warning,
+            code,
+            field,
+            message,
+        }
+    }
+}
+
+/// Result of validating a task spec.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ValidationReport {
+    /// No errors (warnings allowed).
+    pub valid: bool,
+    pub findings: Vec<Finding>,
+}
+
+/// The parts of a task that are checked.
+#[derive(Debug, Clone)]
+pub struct TaskSpec<'a> {
+    pub agent_name: &'a str,
+    pub input_json: &'a str,
+    pub namespace: &'a str,
+    /// Who would create the task (API key quotas apply to `api-key:<name>`).
+    pub created_by: &'a str,
+    pub priority: TaskPriority,
+}
+
+/// Validate `spec` against the current state.
+pub async fn validate(state: &Arc<AppState>, spec: &TaskSpec<'_>) -> ValidationReport {
+    let mut findings = check_input(spec.input_json);
+
+    if spec.agent_name.is_empty() {
+        findings.push(Finding::error(
+            "agent_required",
+            Some("agent_name"),
+            "agent_name is required".to_string(),
+        ));
+    } else {
+        findings.extend(check_agent(state, spec).await);
+    }
+
+    let tasks = state.tasks.read().await;
+    let quota_checks = [
+        (
+            &[QuotaResource::TasksPerDay, QuotaResource::OutputBytesPerDay][..],
+            Severity::Error,
+        ),
+        (&[QuotaResource::ConcurrentRuns][..], Severity::Warning),
+    ];
+    for (resources, severity) in quota_checks {
+        let Some(exceeded) = state
+            .quotas
+            .exceeded(&tasks, spec.namespace, spec.created_by, resources)
+            .await
+        else {
+            continue;
+        };
+        findings.push(match severity {
+            Severity::Error => Finding::error("quota_exceeded", None, exceeded.to_string()),
+            Severity::Warning => Finding::warning(
+                "quota_exceeded",
+                None,
+                format!("{}; the task would wait in Pending", exceeded),
+            ),
+        });
+    }
+
+    ValidationReport {
+        valid: !findings.iter().any(|f| f.severity == Severity::Error),
+        findings,
+    }
+}
+
+/// Workers run the input through the agent as JSON (falling back to text).
+fn check_input(input_json: &str) -> Vec<Finding> {
+    if input_json.trim().is_empty() {
+        return vec![Finding::warning(
+            "empty_input",
+            Some("input"),
+            "Input is empty".to_string(),
+        )];
+    }
+    match serde_json::from_str::<serde_json::Value>(input_json) {
+        Ok(value) if value.is_object() => Vec::new(),
+        Ok(_) => vec![Finding::warning(
+            "input_not_object",
+            Some("input"),
+            "Input is JSON but not an object".to_string(),
+        )],
+        Err(e) => vec![Finding::warning(
+            "input_not_json",
+            Some("input"),
+            format!("Input is not valid JSON ({}); it would be sent as text", e),
+        )],
+    }
+}
+
+/// The agent catalog is what the namespace's connected workers advertise.
+async fn check_agent(state: &Arc<AppState>, spec: &TaskSpec<'_>) -> Vec<Finding> {
+    let mut known: Vec<String> = {
+        let workers = state.workers.read().await;
+        workers
+            .values()
+            .filter(|w| w.info.namespace == spec.namespace)
+            .flat_map(|w| w.info.agents.iter().map(|a| a.name.clone()))
+            .collect()
+    };
+    known.sort();
+    known.dedup();
+
+    if !known.iter().any(|name| name == spec.agent_name) {
+        let available = if known.is_empty() {
+            "no agents are available".to_string()
+        } else {
+            format!("available: {}", known.join(", "))
+        };
+        return vec![Finding::warning(
+            "no_worker_for_agent",
+            Some("agent_name"),
+            format!(
+                "No connected worker in namespace '{}' runs agent '{}' ({}); the task would wait in Pending",
+                spec.namespace, spec.agent_name, available
+            ),
+        )];
+    }
+
+    let scheduler = Scheduler::new(state.clone());
+    if scheduler
+        .select_worker(spec.agent_name, spec.namespace, spec.priority)
+        .await
+        .is_some()
+    {
+        return Vec::new();
+    }
+    let ahead = state
+        .tasks
+        .read()
+        .await
+        .values()
+        .filter(|t| {
+            t.status == TaskStatus::Pending
+                && t.agent_name == spec.agent_name
+                && t.namespace == spec.namespace
+        })
+        .count();
+    vec![Finding::warning(
+        "no_free_worker",
+        None,
+        format!(
+            "Every worker running '{}' is busy or draining; the task would queue behind {} pending task(s)",
+            spec.agent_name, ahead
+        ),
+    )]
+}
+
+impl From<Finding> for pb::ValidationFinding {
+    fn from(finding: Finding) -> Self {
+        let severity = match finding.severity {
+            Severity::Error => pb::ValidationSeverity::Error,
+            Severity::Warning => pb::ValidationSeverity::Warning,
+        };
+        pb::ValidationFinding {
+            severity: severity.into(),
+            code: finding.code.to_string(),
+            field: finding.field.unwrap_or_default().to_string(),
+            message: finding.message,
+        }
+    }
+}
+
+impl From<ValidationReport> for pb::ValidateTaskResponse {
+    fn from(report: ValidationReport) -> Self {
+        pb::ValidateTaskResponse {
+            valid: report.valid,
+            findings: report.findings.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::control_plane::quotas::{QuotaConfig, QuotaLimits};
+    use taskrun_core::Task;
+
+    fn spec<'a>(agent_name: &'a str, input_json: &'a str) -> TaskSpec<'a> {
+        TaskSpec {
+            agent_name,
+            input_json,
+            namespace: "default",
+            created_by: "test",
+            priority: TaskPriority::Batch,
+        }
+    }
+
+    #[test]
+    fn test_input_checks() {
+        assert!(check_input(r#"{"task":"hi"}"#).is_empty());
+        assert_eq!(check_input("").first().unwrap().code, "empty_input");
+        assert_eq!(check_input("[1]").first().unwrap().code, "input_not_object");
+        assert_eq!(
+            check_input("say hi").first().unwrap().code,
+            "input_not_json"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_unknown_agent_warns_and_quota_errors() {
+        let state = AppState::new();
+        let report = validate(&state, &spec("general", "{}")).await;
+        assert!(report.valid);
+        assert_eq!(report.findings[0].code, "no_worker_for_agent");
+
+        let mut config = QuotaConfig::default();
+        config.namespaces.insert(
+            "default".into(),
+            QuotaLimits {
+                max_tasks_per_day: Some(1),
+                ..QuotaLimits::default()
+            },
+        );
+        state.quotas.configure(config).await;
+        let task = Task::new("general", "{}", "test");
+        state.tasks.write().await.insert(task.id.clone(), task);
+
+        let report = validate(&state, &spec("", "{}")).await;
+        assert!(!report.valid);
+        let codes: Vec<&str> = report.findings.iter().map(|f| f.code).collect();
+        assert_eq!(codes, vec!["agent_required", "quota_exceeded"]);
+        // Validating is not a rejection
+        assert_eq!(state.quotas.rejected(QuotaResource::TasksPerDay), 0);
+    }
+}
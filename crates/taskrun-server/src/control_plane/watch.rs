@@ -0,0 +1,209 @@
+//! Live task updates for `WatchTasks` subscribers.
+//!
+//! A watch is the UI notification feed (what the server TUI renders),
+//! filtered to the tasks a client asked about and converted to
+//! `TaskWatchEvent`s. The feed is a bounded broadcast channel: a subscriber
+//! that falls behind is sent a `lagged` event with the number of skipped
+//! changes and should re-list tasks to catch up.
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use chrono::Utc;
+use tokio::sync::{broadcast, mpsc};
+use tracing::debug;
+
+use taskrun_core::{TaskId, TaskStatus};
+use taskrun_proto::pb;
+use taskrun_proto::pb::task_watch_event::Event;
+
+use crate::control_plane::bulk::TaskFilter;
+use crate::control_plane::state::{AppState, UiNotification};
+
+/// Buffered events per subscriber before the watch stream applies backpressure.
+const WATCH_BUFFER: usize = 64;
+
+/// Which tasks a watch reports on.
+#[derive(Debug, Clone, Default)]
+pub struct WatchFilter {
+    /// Namespace, agent and labels (status is not used: it changes while watched).
+    pub tasks: TaskFilter,
+    /// Only these tasks (empty = any).
+    pub task_ids: HashSet<TaskId>,
+    /// Also report run output chunks.
+    pub include_output: bool,
+}
+
+/// Start a watch. Returns None if the state has no notification feed.
+///
+/// Events are sent until the receiver is dropped.
+pub fn subscribe(
+    state: Arc<AppState>,
+    filter: WatchFilter,
+) -> Option<mpsc::Receiver<pb::TaskWatchEvent>> {
+    let mut feed = state.subscribe_ui()?;
+    let (tx, rx) = mpsc::channel(WATCH_BUFFER);
+
+    tokio::spawn(async move {
+        loop {
+            let event = tokio::select! {
+                received = feed.recv() => match received {
+                    Ok(notification) => match to_event(&state, &filter, notification).await {
+                        Some(event) => event,
+                        None => continue,
+                    },
+                    Err(broadcast::error::RecvError::Lagged(missed)) => {
+                        watch_event(String::new(), Event::Lagged(pb::WatchLagged { missed }))
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                },
+                _ = tx.closed() => break,
+            };
+            if tx.send(event).await.is_err() {
+                break;
+            }
+        }
+        debug!("Task watch ended");
+    });
+
+    Some(rx)
+}
+
+fn watch_event(task_id: String, event: Event) -> pb::TaskWatchEvent {
+    pb::TaskWatchEvent {
+        task_id,
+        timestamp_ms: Utc::now().timestamp_millis(),
+        event: Some(event),
+    }
+}
+
+/// The watch event for `notification`, if it is about a watched task.
+async fn to_event(
+    state: &AppState,
+    filter: &WatchFilter,
+    notification: UiNotification,
+) -> Option<pb::TaskWatchEvent> {
+    let task_id = match &notification {
+        UiNotification::TaskCreated { task_id, .. }
+        | UiNotification::TaskStatusChanged { task_id, .. }
+        | UiNotification::RunStatusChanged { task_id, .. }
+        | UiNotification::RunEvent { task_id, .. } => task_id.clone(),
+        UiNotification::RunOutputChunk { task_id, .. } if filter.include_output => task_id.clone(),
+        _ => return None,
+    };
+    if !filter.task_ids.is_empty() && !filter.task_ids.contains(&task_id) {
+        return None;
+    }
+
+    // Namespace, agent and labels are on the task, not the notification
+    let task = state.tasks.read().await.get(&task_id).cloned()?;
+    if !filter.tasks.matches(&task) {
+        return None;
+    }
+
+    let event = match notification {
+        UiNotification::TaskCreated { .. } => Event::Created(task.into()),
+        UiNotification::TaskStatusChanged { status, .. } => {
+            // The reason only applies to the status it came with
+            let failed = matches!(status, TaskStatus::Failed | TaskStatus::Cancelled);
+            Event::TaskStatus(pb::TaskStatusChange {
+                status: pb::TaskStatus::from(status).into(),
+                failure_reason: task
+                    .failure_reason
+                    .filter(|_| failed)
+                    .map_or(0, |r| pb::FailureReason::from(r).into()),
+                failure_message: task.failure_message.filter(|_| failed).unwrap_or_default(),
+            })
+        }
+        UiNotification::RunStatusChanged {
+            run_id,
+            worker_id,
+            status,
+            ..
+        } => Event::RunStatus(pb::RunStatusChange {
+            run_id: run_id.into_inner(),
+            worker_id: worker_id.map(|w| w.into_inner()).unwrap_or_default(),
+            status: pb::RunStatus::from(status).into(),
+        }),
+        UiNotification::RunOutputChunk {
+            run_id, content, ..
+        } => Event::Output(pb::RunOutputChunkNotice {
+            run_id: run_id.into_inner(),
+            content,
+        }),
+        UiNotification::RunEvent {
+            run_id,
+            task_id,
+            event_type,
+            timestamp,
+            metadata,
+        } => Event::RunEvent(pb::RunEvent {
+            id: String::new(),
+            run_id: run_id.into_inner(),
+            task_id: task_id.into_inner(),
+            event_type: pb::RunEventType::from(event_type).into(),
+            timestamp_ms: timestamp.timestamp_millis(),
+            metadata,
+            artifacts: Vec::new(),
+        }),
+        _ => return None,
+    };
+    Some(watch_event(task_id.into_inner(), event))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::control_plane::audit::AuditLog;
+    use crate::control_plane::event_store::InMemoryEventStore;
+    use crate::control_plane::output_store::RunOutputs;
+    use taskrun_core::{RunId, Task};
+
+    #[tokio::test]
+    async fn test_watch_filters_and_converts() {
+        let (state, _ui_rx) = AppState::with_ui_channel(
+            None,
+            Arc::new(InMemoryEventStore::new()),
+            RunOutputs::in_memory(),
+            AuditLog::in_memory(),
+        );
+        let watched = Task::new("general", "{}", "test").with_namespace("team-a");
+        let other = Task::new("general", "{}", "test");
+        for task in [&watched, &other] {
+            state
+                .tasks
+                .write()
+                .await
+                .insert(task.id.clone(), task.clone());
+        }
+
+        let filter = WatchFilter {
+            tasks: TaskFilter {
+                namespace: Some("team-a".into()),
+                ..TaskFilter::default()
+            },
+            ..WatchFilter::default()
+        };
+        let mut rx = subscribe(state.clone(), filter).unwrap();
+
+        state.notify_ui(UiNotification::task_created(&other));
+        state.notify_ui(UiNotification::RunOutputChunk {
+            run_id: RunId::generate(),
+            task_id: watched.id.clone(),
+            seq: 0,
+            content: "hi".into(),
+        });
+        state.notify_ui(UiNotification::TaskStatusChanged {
+            task_id: watched.id.clone(),
+            status: TaskStatus::Running,
+        });
+
+        // Other namespace and output (not requested) are skipped
+        let event = rx.recv().await.unwrap();
+        assert_eq!(event.task_id, watched.id.as_str());
+        let Some(Event::TaskStatus(change)) = event.event else {
+            panic!("expected a task status change");
+        };
+        assert_eq!(change.status(), pb::TaskStatus::Running);
+    }
+}
@@ -0,0 +1,251 @@
+//! Workflow advancer - dispatches workflow steps as their dependencies finish.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use tracing::{debug, info, warn};
+
+use taskrun_core::workflow::fill_placeholders;
+use taskrun_core::{TaskId, TaskStatus, Workflow, WorkflowStepTask};
+
+use super::WorkflowManager;
+use crate::control_plane::scheduler::{Scheduler, SchedulerError};
+use crate::control_plane::state::UiNotification;
+
+/// What to do with a pending workflow step.
+enum StepAction {
+    /// Dependencies are still running.
+    Wait,
+    /// A dependency failed or was cancelled.
+    Cancel(String),
+    /// Every dependency completed.
+    Dispatch,
+}
+
+/// Periodically dispatches workflow steps whose dependencies have completed.
+pub struct WorkflowAdvancer {
+    manager: Arc<WorkflowManager>,
+    interval: Duration,
+}
+
+impl WorkflowAdvancer {
+    /// Create an advancer that checks workflows once per second.
+    pub fn new(manager: Arc<WorkflowManager>) -> Self {
+        Self {
+            manager,
+            interval: Duration::from_secs(1),
+        }
+    }
+
+    /// Run the advancer loop forever.
+    pub async fn run(self) {
+        info!("Workflow advancer started");
+
+        let mut interval = tokio::time::interval(self.interval);
+        loop {
+            interval.tick().await;
+            self.manager.advance().await;
+        }
+    }
+}
+
+impl WorkflowManager {
+    /// Dispatch every ready step and cancel steps whose dependencies failed.
+    /// Returns the tasks that were assigned to a worker.
+    pub async fn advance(&self) -> Vec<TaskId> {
+        let _guard = self.dispatch.lock().await;
+
+        let workflows = self.unfinished().await;
+        let scheduler = Scheduler::new(self.state().clone());
+        let mut dispatched = Vec::new();
+
+        for workflow in &workflows {
+            for step in &workflow.steps {
+                match self.step_action(step).await {
+                    StepAction::Wait => {}
+                    StepAction::Cancel(reason) => self.cancel_step(workflow, step, &reason).await,
+                    StepAction::Dispatch => {
+                        self.render_step_outputs(workflow, step).await;
+                        match scheduler.assign_task(&step.task_id).await {
+                            Ok(run_id) => {
+                                info!(
+                                    workflow_id = %workflow.id,
+                                    step = %step.step_name,
+                                    task_id = %step.task_id,
+                                    run_id = %run_id,
+                                    "Workflow step dispatched"
+                                );
+                                dispatched.push(step.task_id.clone());
+                            }
+                            // Retried on the next pass
+                            Err(SchedulerError::NoWorkersAvailable(agent)) => {
+                                debug!(
+                                    workflow_id = %workflow.id,
+                                    step = %step.step_name,
+                                    agent = %agent,
+                                    "No worker for workflow step yet"
+                                );
+                            }
+                            Err(SchedulerError::SchedulingFrozen(_)) => {}
+                            Err(e) => {
+                                warn!(
+                                    workflow_id = %workflow.id,
+                                    step = %step.step_name,
+                                    error = %e,
+                                    "Failed to dispatch workflow step"
+                                );
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        dispatched
+    }
+
+    /// Workflows with a step that has not finished. Finished workflows are
+    /// skipped rather than dropped, since retrying a step makes one live again.
+    async fn unfinished(&self) -> Vec<Workflow> {
+        let tasks = self.state().tasks.read().await;
+        self.workflows
+            .read()
+            .await
+            .values()
+            .filter(|w| {
+                w.steps
+                    .iter()
+                    .any(|s| tasks.get(&s.task_id).is_some_and(|t| !t.is_terminal()))
+            })
+            .cloned()
+            .collect()
+    }
+
+    async fn step_action(&self, step: &WorkflowStepTask) -> StepAction {
+        let tasks = self.state().tasks.read().await;
+        let Some(task) = tasks.get(&step.task_id) else {
+            return StepAction::Wait;
+        };
+        if task.status != TaskStatus::Pending || !task.runs.is_empty() {
+            return StepAction::Wait;
+        }
+
+        let mut ready = true;
+        for dep in &task.depends_on {
+            match tasks.get(dep).map(|t| t.status) {
+                Some(TaskStatus::Completed) => {}
+                Some(status @ (TaskStatus::Failed | TaskStatus::Cancelled)) => {
+                    return StepAction::Cancel(format!("dependency {} is {:?}", dep, status));
+                }
+                _ => ready = false,
+            }
+        }
+
+        if ready {
+            StepAction::Dispatch
+        } else {
+            StepAction::Wait
+        }
+    }
+
+    /// Fill `{{steps.<step>.output}}` placeholders from completed dependencies.
+    async fn render_step_outputs(&self, workflow: &Workflow, step: &WorkflowStepTask) {
+        let depends_on = match self.state().tasks.read().await.get(&step.task_id) {
+            Some(task) => task.depends_on.clone(),
+            None => return,
+        };
+
+        let mut outputs = HashMap::new();
+        for upstream in workflow
+            .steps
+            .iter()
+            .filter(|s| depends_on.contains(&s.task_id))
+        {
+            let output = self.state().get_output_by_task(&upstream.task_id).await;
+            outputs.insert(
+                format!("steps.{}.output", upstream.step_name),
+                output.unwrap_or_default(),
+            );
+        }
+
+        let mut tasks = self.state().tasks.write().await;
+        if let Some(task) = tasks.get_mut(&step.task_id) {
+            task.input_json =
+                fill_placeholders(&task.input_json, |name| outputs.get(name).cloned());
+        }
+    }
+
+    async fn cancel_step(&self, workflow: &Workflow, step: &WorkflowStepTask, reason: &str) {
+        if let Some(task) = self.state().tasks.write().await.get_mut(&step.task_id) {
+            task.status = TaskStatus::Cancelled;
+        }
+
+        info!(
+            workflow_id = %workflow.id,
+            step = %step.step_name,
+            task_id = %step.task_id,
+            reason = %reason,
+            "Workflow step cancelled"
+        );
+        self.state().notify_ui(UiNotification::TaskStatusChanged {
+            task_id: step.task_id.clone(),
+            status: TaskStatus::Cancelled,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::control_plane::state::AppState;
+    use taskrun_core::{WorkflowStep, WorkflowTemplate};
+
+    #[tokio::test]
+    async fn test_failed_dependency_cancels_downstream_steps() {
+        let state = AppState::new();
+        let step = |name: &str, deps: &[&str]| WorkflowStep {
+            name: name.to_string(),
+            agent_name: "general".to_string(),
+            input_template: "{}".to_string(),
+            depends_on: deps.iter().map(|d| d.to_string()).collect(),
+            timeout_seconds: None,
+        };
+        let manager = WorkflowManager::new(
+            state.clone(),
+            vec![WorkflowTemplate {
+                name: "chain".to_string(),
+                description: String::new(),
+                params: Vec::new(),
+                steps: vec![step("a", &[]), step("b", &["a"]), step("c", &["b"])],
+            }],
+        )
+        .unwrap();
+        let workflow = manager
+            .create("chain", HashMap::new(), "test")
+            .await
+            .unwrap();
+
+        let first = &workflow.steps[0].task_id;
+        state.tasks.write().await.get_mut(first).unwrap().status = TaskStatus::Failed;
+
+        // Steps are checked in order, so the cancellation cascades in one pass
+        assert!(manager.advance().await.is_empty());
+
+        let tasks = state.tasks.read().await;
+        assert_eq!(
+            tasks[&workflow.steps[1].task_id].status,
+            TaskStatus::Cancelled
+        );
+        assert_eq!(
+            tasks[&workflow.steps[2].task_id].status,
+            TaskStatus::Cancelled
+        );
+        drop(tasks);
+
+        // Finished workflows are no longer scanned, until a step is retried
+        assert!(manager.unfinished().await.is_empty());
+        state.tasks.write().await.get_mut(first).unwrap().status = TaskStatus::Pending;
+        assert_eq!(manager.unfinished().await.len(), 1);
+    }
+}
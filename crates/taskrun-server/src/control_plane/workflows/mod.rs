@@ -0,0 +1,446 @@
+//! Multi-step workflows.
+//!
+//! A `WorkflowTemplate` (loaded from `--workflows-file`) names a set of steps,
+//! each an agent plus an input template, with dependency edges between steps.
+//! `WorkflowManager::create` expands a template into one task per step, linked
+//! through `Task::depends_on`. Steps without dependencies are dispatched
+//! immediately; the `WorkflowAdvancer` dispatches the rest as their
+//! dependencies complete, filling `{{steps.<step>.output}}` placeholders with
+//! the upstream output. If a dependency fails or is cancelled, the steps that
+//! wait on it are cancelled.
+
+mod advancer;
+
+pub use advancer::WorkflowAdvancer;
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::Path;
+use std::sync::Arc;
+
+use chrono::Utc;
+use thiserror::Error;
+use tokio::sync::{Mutex, RwLock};
+use tracing::{info, info_span};
+
+use taskrun_core::workflow::{fill_placeholders, placeholders};
+use taskrun_core::{
+    Task, TaskId, TaskStatus, Workflow, WorkflowId, WorkflowStatus, WorkflowStepTask,
+    WorkflowTemplate,
+};
+use taskrun_proto::trace;
+
+use crate::control_plane::audit::AuditAction;
+use crate::control_plane::scheduler::trace_created;
+use crate::control_plane::state::{resolve_prefix, AppState, IdLookupError, UiNotification};
+use crate::control_plane::toggles::MaintenanceMode;
+
+/// Label set on every task created by a workflow.
+pub const WORKFLOW_ID_LABEL: &str = "workflow_id";
+
+/// Label holding the step name of a workflow task.
+pub const WORKFLOW_STEP_LABEL: &str = "workflow_step";
+
+/// Workflow errors.
+#[derive(Debug, Error)]
+pub enum WorkflowError {
+    #[error("Failed to read workflow templates from '{path}': {source}")]
+    Io {
+        path: String,
+        source: std::io::Error,
+    },
+
+    #[error("Invalid workflow templates file: {0}")]
+    Parse(#[from] serde_json::Error),
+
+    #[error("Invalid workflow template '{template}': {reason}")]
+    InvalidTemplate { template: String, reason: String },
+
+    #[error("Unknown workflow template: {0}")]
+    UnknownTemplate(String),
+
+    #[error("Workflow template '{template}' requires parameter '{param}'")]
+    MissingParam { template: String, param: String },
+
+    #[error("Workflow template '{template}' has no parameter '{param}'")]
+    UnknownParam { template: String, param: String },
+
+    #[error(transparent)]
+    Lookup(#[from] IdLookupError),
+
+    #[error(transparent)]
+    Maintenance(#[from] MaintenanceMode),
+}
+
+/// Load workflow templates from a JSON file containing an array of templates.
+pub fn load_templates(path: &Path) -> Result<Vec<WorkflowTemplate>, WorkflowError> {
+    let json = std::fs::read_to_string(path).map_err(|source| WorkflowError::Io {
+        path: path.display().to_string(),
+        source,
+    })?;
+    Ok(serde_json::from_str(&json)?)
+}
+
+/// Check that a template's steps form a DAG and every placeholder resolves.
+pub fn validate_template(template: &WorkflowTemplate) -> Result<(), WorkflowError> {
+    let invalid = |reason: String| WorkflowError::InvalidTemplate {
+        template: template.name.clone(),
+        reason,
+    };
+
+    if template.name.is_empty() {
+        return Err(invalid("name is required".to_string()));
+    }
+    if template.steps.is_empty() {
+        return Err(invalid("at least one step is required".to_string()));
+    }
+
+    let mut names = HashSet::new();
+    for step in &template.steps {
+        if step.agent_name.is_empty() {
+            return Err(invalid(format!("step '{}' has no agent_name", step.name)));
+        }
+        if !names.insert(step.name.as_str()) {
+            return Err(invalid(format!("duplicate step '{}'", step.name)));
+        }
+    }
+
+    for step in &template.steps {
+        for dep in &step.depends_on {
+            if !names.contains(dep.as_str()) || dep == &step.name {
+                return Err(invalid(format!(
+                    "step '{}' depends on unknown step '{}'",
+                    step.name, dep
+                )));
+            }
+        }
+
+        for name in placeholders(&step.input_template) {
+            let resolves = match name
+                .strip_prefix("steps.")
+                .and_then(|s| s.strip_suffix(".output"))
+            {
+                Some(upstream) => step.depends_on.iter().any(|d| d == upstream),
+                None => template.params.iter().any(|p| p == name),
+            };
+            if !resolves {
+                return Err(invalid(format!(
+                    "step '{}' references '{{{{{}}}}}', which is not a parameter or the output of a dependency",
+                    step.name, name
+                )));
+            }
+        }
+    }
+
+    // Kahn's algorithm: every step must become ready eventually
+    let mut remaining: HashMap<&str, usize> = template
+        .steps
+        .iter()
+        .map(|s| (s.name.as_str(), s.depends_on.len()))
+        .collect();
+    let mut ready: Vec<&str> = remaining
+        .iter()
+        .filter(|(_, &n)| n == 0)
+        .map(|(&name, _)| name)
+        .collect();
+    let mut visited = 0;
+    while let Some(done) = ready.pop() {
+        visited += 1;
+        for step in &template.steps {
+            if step.depends_on.iter().any(|d| d == done) {
+                let n = remaining.get_mut(step.name.as_str()).expect("step exists");
+                *n -= 1;
+                if *n == 0 {
+                    ready.push(step.name.as_str());
+                }
+            }
+        }
+    }
+    if visited != template.steps.len() {
+        return Err(invalid("steps have a dependency cycle".to_string()));
+    }
+
+    Ok(())
+}
+
+/// Owns workflow templates and the workflows created from them.
+pub struct WorkflowManager {
+    state: Arc<AppState>,
+    templates: BTreeMap<String, WorkflowTemplate>,
+    workflows: RwLock<HashMap<WorkflowId, Workflow>>,
+    /// Serializes dispatching so a step is never assigned twice.
+    dispatch: Mutex<()>,
+}
+
+impl WorkflowManager {
+    /// Create a manager after validating every template.
+    pub fn new(
+        state: Arc<AppState>,
+        templates: Vec<WorkflowTemplate>,
+    ) -> Result<Arc<Self>, WorkflowError> {
+        let mut by_name = BTreeMap::new();
+        for template in templates {
+            validate_template(&template)?;
+            if by_name.contains_key(&template.name) {
+                return Err(WorkflowError::InvalidTemplate {
+                    template: template.name,
+                    reason: "defined more than once".to_string(),
+                });
+            }
+            by_name.insert(template.name.clone(), template);
+        }
+
+        if !by_name.is_empty() {
+            info!(count = by_name.len(), "Loaded workflow templates");
+        }
+
+        Ok(Arc::new(Self {
+            state,
+            templates: by_name,
+            workflows: RwLock::new(HashMap::new()),
+            dispatch: Mutex::new(()),
+        }))
+    }
+
+    /// Shared application state used to create tasks.
+    pub(crate) fn state(&self) -> &Arc<AppState> {
+        &self.state
+    }
+
+    /// All templates, ordered by name.
+    pub fn templates(&self) -> Vec<WorkflowTemplate> {
+        self.templates.values().cloned().collect()
+    }
+
+    /// Expand a template into linked tasks and dispatch the steps that are
+    /// ready.
+    pub async fn create(
+        &self,
+        template_name: &str,
+        params: HashMap<String, String>,
+        created_by: &str,
+    ) -> Result<Workflow, WorkflowError> {
+        self.state.toggles.check_accepting().await?;
+        let template = self
+            .templates
+            .get(template_name)
+            .ok_or_else(|| WorkflowError::UnknownTemplate(template_name.to_string()))?;
+
+        if let Some(param) = template.params.iter().find(|p| !params.contains_key(*p)) {
+            return Err(WorkflowError::MissingParam {
+                template: template.name.clone(),
+                param: param.clone(),
+            });
+        }
+        if let Some(param) = params.keys().find(|k| !template.params.contains(k)) {
+            return Err(WorkflowError::UnknownParam {
+                template: template.name.clone(),
+                param: param.clone(),
+            });
+        }
+
+        let workflow_id = WorkflowId::generate();
+        let task_ids: HashMap<&str, TaskId> = template
+            .steps
+            .iter()
+            .map(|s| (s.name.as_str(), TaskId::generate()))
+            .collect();
+
+        // Every step is traced as part of the workflow's trace
+        let span = info_span!(
+            "workflow.create",
+            workflow_id = %workflow_id,
+            template = %template.name,
+        );
+        let parent = trace::inject(&span);
+
+        let mut tasks = Vec::with_capacity(template.steps.len());
+        for step in &template.steps {
+            // Step outputs are filled in when the step is dispatched
+            let input = fill_placeholders(&step.input_template, |name| params.get(name).cloned());
+            let mut task = Task::new(&step.agent_name, input, created_by)
+                .with_id(task_ids[step.name.as_str()].clone())
+                .with_label(WORKFLOW_ID_LABEL, workflow_id.as_str())
+                .with_label(WORKFLOW_STEP_LABEL, &step.name);
+            for dep in &step.depends_on {
+                task = task.with_dependency(task_ids[dep.as_str()].clone());
+            }
+            if let Some(secs) = step.timeout_seconds {
+                task = task.with_timeout_seconds(secs);
+            }
+            tasks.push(trace_created(task, &parent));
+        }
+
+        let workflow = Workflow {
+            id: workflow_id,
+            template_name: template.name.clone(),
+            params,
+            created_by: created_by.to_string(),
+            created_at: Utc::now(),
+            steps: template
+                .steps
+                .iter()
+                .map(|s| WorkflowStepTask {
+                    step_name: s.name.clone(),
+                    task_id: task_ids[s.name.as_str()].clone(),
+                })
+                .collect(),
+        };
+
+        info!(
+            workflow_id = %workflow.id,
+            template = %workflow.template_name,
+            steps = workflow.steps.len(),
+            "Creating workflow"
+        );
+
+        self.state
+            .audit(
+                created_by,
+                AuditAction::WorkflowCreated,
+                workflow.id.as_str(),
+                &workflow,
+            )
+            .await;
+        for task in &tasks {
+            self.state
+                .audit(created_by, AuditAction::TaskCreated, task.id.as_str(), task)
+                .await;
+        }
+        {
+            let mut all = self.state.tasks.write().await;
+            for task in &tasks {
+                all.insert(task.id.clone(), task.clone());
+            }
+        }
+        for task in &tasks {
+            self.state.notify_ui(UiNotification::task_created(task));
+        }
+        self.workflows
+            .write()
+            .await
+            .insert(workflow.id.clone(), workflow.clone());
+
+        self.advance().await;
+        Ok(workflow)
+    }
+
+    /// Get a workflow by ID.
+    pub async fn get(&self, workflow_id: &WorkflowId) -> Result<Workflow, WorkflowError> {
+        self.workflows
+            .read()
+            .await
+            .get(workflow_id)
+            .cloned()
+            .ok_or_else(|| {
+                IdLookupError::NotFound {
+                    kind: "Workflow",
+                    prefix: workflow_id.to_string(),
+                }
+                .into()
+            })
+    }
+
+    /// All workflows, newest first.
+    pub async fn list(&self) -> Vec<Workflow> {
+        let mut list: Vec<Workflow> = self.workflows.read().await.values().cloned().collect();
+        list.sort_by_key(|w| std::cmp::Reverse(w.created_at));
+        list
+    }
+
+    /// Resolve a full or unique-prefix workflow ID.
+    pub async fn resolve_id(&self, prefix: &str) -> Result<WorkflowId, IdLookupError> {
+        let workflows = self.workflows.read().await;
+        resolve_prefix("Workflow", prefix, workflows.keys().map(|id| id.as_str()))
+            .map(WorkflowId::new)
+    }
+
+    /// Overall status of a workflow, derived from its tasks.
+    pub async fn status(&self, workflow: &Workflow) -> WorkflowStatus {
+        let tasks = self.state.tasks.read().await;
+        WorkflowStatus::from_tasks(workflow.steps.iter().map(|s| {
+            tasks
+                .get(&s.task_id)
+                .map_or(TaskStatus::Pending, |t| t.status)
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use taskrun_core::WorkflowStep;
+
+    fn step(name: &str, input: &str, depends_on: &[&str]) -> WorkflowStep {
+        WorkflowStep {
+            name: name.to_string(),
+            agent_name: "general".to_string(),
+            input_template: input.to_string(),
+            depends_on: depends_on.iter().map(|d| d.to_string()).collect(),
+            timeout_seconds: None,
+        }
+    }
+
+    fn template(steps: Vec<WorkflowStep>) -> WorkflowTemplate {
+        WorkflowTemplate {
+            name: "review".to_string(),
+            description: String::new(),
+            params: vec!["repo".to_string()],
+            steps,
+        }
+    }
+
+    #[test]
+    fn test_validate_rejects_cycles_and_unresolved_placeholders() {
+        let ok = template(vec![
+            step("plan", r#"{"prompt":"Plan {{repo}}"}"#, &[]),
+            step("review", r#"{"prompt":"{{steps.plan.output}}"}"#, &["plan"]),
+        ]);
+        assert!(validate_template(&ok).is_ok());
+
+        let cycle = template(vec![step("a", "{}", &["b"]), step("b", "{}", &["a"])]);
+        assert!(matches!(
+            validate_template(&cycle),
+            Err(WorkflowError::InvalidTemplate { .. })
+        ));
+
+        let not_a_dependency = template(vec![
+            step("plan", "{}", &[]),
+            step("review", r#"{"p":"{{steps.plan.output}}"}"#, &[]),
+        ]);
+        assert!(validate_template(&not_a_dependency).is_err());
+
+        let unknown_param = template(vec![step("plan", r#"{"p":"{{branch}}"}"#, &[])]);
+        assert!(validate_template(&unknown_param).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_create_links_tasks_and_checks_params() {
+        let state = AppState::new();
+        let manager = WorkflowManager::new(
+            state.clone(),
+            vec![template(vec![
+                step("plan", r#"{"prompt":"Plan {{repo}}"}"#, &[]),
+                step("review", r#"{"prompt":"{{steps.plan.output}}"}"#, &["plan"]),
+            ])],
+        )
+        .unwrap();
+
+        assert!(matches!(
+            manager.create("review", HashMap::new(), "test").await,
+            Err(WorkflowError::MissingParam { .. })
+        ));
+
+        let params = HashMap::from([("repo".to_string(), "acme/web".to_string())]);
+        let workflow = manager.create("review", params, "test").await.unwrap();
+        assert_eq!(manager.status(&workflow).await, WorkflowStatus::Running);
+
+        let tasks = state.tasks.read().await;
+        let plan = &tasks[&workflow.steps[0].task_id];
+        let review = &tasks[&workflow.steps[1].task_id];
+        assert_eq!(plan.input_json, r#"{"prompt":"Plan acme/web"}"#);
+        assert_eq!(review.depends_on, vec![plan.id.clone()]);
+        assert_eq!(review.labels[WORKFLOW_ID_LABEL], workflow.id.as_str());
+        // No workers are connected, so nothing was dispatched
+        assert_eq!(plan.status, TaskStatus::Pending);
+    }
+}
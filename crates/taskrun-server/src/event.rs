@@ -1,11 +1,25 @@
 //! Server TUI events and commands.
 
+use std::collections::HashMap;
+
 use chrono::{DateTime, Utc};
-use taskrun_core::{ChatRole, RunEventType, RunId, RunStatus, TaskId, TaskStatus, WorkerId, WorkerStatus};
+use taskrun_core::{
+    Artifact, ChatRole, RunEventType, RunId, RunMilestone, RunResultSummary, RunStatus, Schedule,
+    ScheduleId, TaskId, TaskStatus, WorkerId, WorkerStatus,
+};
 use taskrun_proto::pb::RunServerMessage;
+use taskrun_tui_components::TimeFormat;
+
+use crate::control_plane::approvals::Approval;
+use crate::control_plane::audit::AuditRecord;
+use crate::control_plane::bulk::{BulkOperation, TaskFilter};
+use crate::control_plane::debug_stats::DebugStats;
+use crate::control_plane::groups::GroupCounts;
+use crate::control_plane::queue::QueueEntry;
+use crate::control_plane::toggles::{RuntimeToggles, ToggleUpdate};
 
-// Re-export LogLevel from shared components
-pub use taskrun_tui_components::LogLevel;
+// Re-export LogLevel and ValidationMessage from shared components
+pub use taskrun_tui_components::{LogLevel, ValidationMessage};
 
 /// Events sent from backend to UI.
 #[derive(Debug, Clone)]
@@ -24,6 +38,7 @@ pub enum ServerUiEvent {
         worker_id: WorkerId,
         hostname: String,
         agents: Vec<String>,
+        labels: HashMap<String, String>,
     },
 
     /// Worker disconnected.
@@ -35,10 +50,33 @@ pub enum ServerUiEvent {
         status: WorkerStatus,
         active_runs: u32,
         max_concurrent_runs: u32,
+        metrics: HashMap<String, String>,
+    },
+
+    /// Worker status changed without a heartbeat (e.g. went stale).
+    WorkerStatusChanged {
+        worker_id: WorkerId,
+        status: WorkerStatus,
+    },
+
+    /// A draining worker finished its last run.
+    WorkerDrained { worker_id: WorkerId },
+
+    /// Worker changed its agents, labels or run limit in place.
+    WorkerUpdated {
+        worker_id: WorkerId,
+        agents: Vec<String>,
+        labels: HashMap<String, String>,
+        max_concurrent_runs: u32,
     },
 
     /// Task created.
-    TaskCreated { task_id: TaskId, agent: String },
+    TaskCreated {
+        task_id: TaskId,
+        agent: String,
+        created_by: String,
+        client_ip: Option<String>,
+    },
 
     /// Task status changed.
     TaskStatusChanged { task_id: TaskId, status: TaskStatus },
@@ -51,7 +89,18 @@ pub enum ServerUiEvent {
     },
 
     /// Run output chunk.
-    RunOutputChunk { run_id: RunId, content: String },
+    RunOutputChunk {
+        run_id: RunId,
+        seq: u64,
+        content: String,
+    },
+
+    /// Stored output of a run, up to (not including) chunk `next_seq`.
+    RunOutputBacklog {
+        run_id: RunId,
+        content: String,
+        next_seq: u64,
+    },
 
     /// Run execution event (tool calls, etc.).
     RunEvent {
@@ -61,6 +110,18 @@ pub enum ServerUiEvent {
         details: Option<String>,
     },
 
+    /// Milestone reported by a run's agent.
+    RunMilestone {
+        run_id: RunId,
+        milestone: RunMilestone,
+    },
+
+    /// Result summary reported by a run's worker.
+    RunResult {
+        run_id: RunId,
+        result: RunResultSummary,
+    },
+
     /// Chat message (user or assistant message in conversation).
     ChatMessage {
         run_id: RunId,
@@ -68,8 +129,59 @@ pub enum ServerUiEvent {
         task_id: TaskId,
         role: ChatRole,
         content: String,
+        attachments: Vec<Artifact>,
     },
 
+    /// Schedule created, fired, or paused/resumed.
+    ScheduleUpdated { schedule: Schedule },
+
+    /// Schedule deleted.
+    ScheduleDeleted { schedule_id: ScheduleId },
+
+    /// Mutating action appended to the audit log.
+    AuditRecorded { record: AuditRecord },
+
+    /// Dry run of a bulk operation, to be confirmed.
+    BulkPreview {
+        op: BulkOperation,
+        filter: TaskFilter,
+        task_count: usize,
+    },
+
+    /// Validation findings for the new-task dialog's contents.
+    TaskValidated {
+        agent_name: String,
+        input_json: String,
+        messages: Vec<ValidationMessage>,
+    },
+
+    /// Tasks matching a search.
+    SearchResults {
+        query: String,
+        task_ids: Vec<TaskId>,
+    },
+
+    /// A worker asked to approve a tool use.
+    ApprovalRequested { approval: Approval },
+
+    /// A pending approval was decided, expired, or dropped.
+    ApprovalResolved { approval_id: String },
+
+    /// Progress of active groups changed.
+    GroupsUpdated { groups: Vec<(String, GroupCounts)> },
+
+    /// Queued tasks, their positions or why they wait changed.
+    QueueUpdated { entries: Vec<QueueEntry> },
+
+    /// Runtime toggles loaded or changed.
+    TogglesChanged { toggles: RuntimeToggles },
+
+    /// Process and state counters refreshed.
+    DebugStats { stats: DebugStats },
+
+    /// Time zone or locale of timestamps changed in the settings file.
+    TimeFormatChanged { time_format: TimeFormat },
+
     /// Log message.
     LogMessage { level: LogLevel, message: String },
 }
@@ -82,15 +194,59 @@ pub enum ServerCommand {
         input_json: String,
     },
 
+    /// Validate a task without creating it (answered with `TaskValidated`).
+    ValidateTask {
+        agent_name: String,
+        input_json: String,
+    },
+
     /// Cancel a task.
     CancelTask { task_id: TaskId },
 
+    /// Count the tasks a bulk operation would affect (answered with `BulkPreview`).
+    PreviewBulk {
+        op: BulkOperation,
+        filter: TaskFilter,
+    },
+
+    /// Cancel or retry every task matching a filter.
+    RunBulk {
+        op: BulkOperation,
+        filter: TaskFilter,
+    },
+
+    /// Search tasks (answered with `SearchResults`).
+    Search { query: String },
+
+    /// Load a run's stored output (answered with `RunOutputBacklog`).
+    LoadRunOutput { run_id: RunId },
+
     /// Disconnect a worker.
     DisconnectWorker { worker_id: WorkerId },
 
+    /// Drain or undrain a worker.
+    SetWorkerDraining { worker_id: WorkerId, draining: bool },
+
     /// Send a chat message to a run (forwarded to worker).
     SendChatMessage { run_id: RunId, message: String },
 
+    /// Approve or deny a pending tool use; `always_allow` also allows later
+    /// uses of the tool by the same agent.
+    DecideApproval {
+        approval_id: String,
+        allowed: bool,
+        always_allow: bool,
+    },
+
+    /// Change runtime toggles (maintenance mode, scheduling freeze, ...).
+    UpdateToggles { update: ToggleUpdate },
+
+    /// Pause or resume a schedule.
+    SetSchedulePaused {
+        schedule_id: ScheduleId,
+        paused: bool,
+    },
+
     /// Shutdown the server.
     Shutdown,
 }
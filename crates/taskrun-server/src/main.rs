@@ -12,8 +12,11 @@ mod state;
 mod views;
 
 use std::io::{self, stdout};
+use std::path::PathBuf;
 use std::thread;
+use std::time::Duration;
 
+use chrono::{DateTime, Utc};
 use clap::Parser;
 use crossterm::execute;
 use crossterm::terminal::{
@@ -21,20 +24,27 @@ use crossterm::terminal::{
 };
 use ratatui::backend::CrosstermBackend;
 use ratatui::Terminal;
+use taskrun_proto::trace::OtlpExporter;
+use taskrun_tui_components::{DisplayLocale, DisplayZone, TimeFormat};
 use tokio::sync::mpsc;
 use tracing::info;
-use tracing_subscriber::EnvFilter;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
 
 use app::ServerApp;
 use backend::{run_server_backend, ServerConfig};
+use control_plane::event_store::EventStoreConfig;
+use control_plane::http::AccessLogFile;
+use control_plane::leader::{LeaseConfig, DEFAULT_LEASE_TTL};
+use control_plane::output_store::{OutputStoreConfig, S3Config, DEFAULT_SPILL_THRESHOLD};
+use control_plane::reload::{default_log_filter, LogFilterHandle};
+use control_plane::retention::{RetentionConfig, RetentionPolicy};
+use control_plane::schedules::ScheduleStoreConfig;
 use event::{ServerCommand, ServerUiEvent};
 
 /// TaskRun control plane server.
 #[derive(Parser, Debug)]
-#[command(
-    name = "taskrun-server",
-    about = "TaskRun control plane server"
-)]
+#[command(name = "taskrun-server", about = "TaskRun control plane server")]
 struct Args {
     /// Run in headless mode (daemon without TUI)
     #[arg(long)]
@@ -67,6 +77,211 @@ struct Args {
     /// Worker certificate validity in days
     #[arg(long, default_value = "7")]
     worker_cert_validity_days: u32,
+
+    /// CA certificate being rotated out; client certificates it issued stay
+    /// trusted until --previous-ca-until
+    #[arg(long)]
+    previous_ca_cert: Option<String>,
+
+    /// End of the CA rotation window, RFC 3339 (default: the new CA's start
+    /// plus --worker-cert-validity-days)
+    #[arg(long, requires = "previous_ca_cert")]
+    previous_ca_until: Option<DateTime<Utc>>,
+
+    /// Persist revoked certificates to this JSON file (default: in-memory only)
+    #[arg(long)]
+    revocations_file: Option<PathBuf>,
+
+    /// Persist run events to this JSONL file (default: in-memory only)
+    #[arg(long)]
+    events_file: Option<PathBuf>,
+
+    /// Prune stored run events older than this many hours
+    #[arg(long)]
+    event_retention_hours: Option<u64>,
+
+    /// Keep run events only for this many most recently finished runs
+    #[arg(long)]
+    event_retention_runs: Option<usize>,
+
+    /// Drop chat messages of runs finished more than this many hours ago
+    #[arg(long)]
+    chat_retention_hours: Option<u64>,
+
+    /// Keep chat messages only for this many most recently finished runs
+    #[arg(long)]
+    chat_retention_runs: Option<usize>,
+
+    /// Drop output of runs finished more than this many hours ago
+    #[arg(long)]
+    output_retention_hours: Option<u64>,
+
+    /// Keep output only for this many most recently finished runs
+    #[arg(long)]
+    output_retention_runs: Option<usize>,
+
+    /// Seconds between run data compaction passes
+    #[arg(long, default_value = "600")]
+    compaction_interval_secs: u64,
+
+    /// Expected worker heartbeat interval in seconds
+    #[arg(long, default_value = "15")]
+    heartbeat_interval_secs: u64,
+
+    /// Missed heartbeats before a worker's runs are failed
+    #[arg(long, default_value = "3")]
+    max_missed_heartbeats: u32,
+
+    /// Seconds without a heartbeat before a worker is marked degraded
+    #[arg(long, default_value = "30")]
+    worker_stale_secs: u64,
+
+    /// Persist schedules to this JSON file (default: in-memory only)
+    #[arg(long)]
+    schedules_file: Option<PathBuf>,
+
+    /// Load workflow templates from this JSON file
+    #[arg(long)]
+    workflows_file: Option<PathBuf>,
+
+    /// Load per-namespace and per-API-key quotas from this JSON file
+    #[arg(long)]
+    quotas_file: Option<PathBuf>,
+
+    /// Persist runtime toggles (maintenance mode, scheduling freeze) to this JSON file
+    #[arg(long)]
+    toggles_file: Option<PathBuf>,
+
+    /// Allow fault injection through /v1/admin/chaos (testing and staging only)
+    #[arg(long)]
+    enable_chaos: bool,
+
+    /// Persist HTTP API keys to this JSON file (default: in-memory only)
+    #[arg(long)]
+    api_keys_file: Option<PathBuf>,
+
+    /// Reject HTTP API calls without a valid API key
+    #[arg(long)]
+    require_api_key: bool,
+
+    /// Serve the HTTP API over TLS with the gRPC server certificate; clients may
+    /// authenticate with a certificate from the CA instead of an API key
+    #[arg(long)]
+    http_tls: bool,
+
+    /// Allow cross-origin HTTP requests only from these origins (default: any)
+    #[arg(long = "cors-origin", value_delimiter = ',')]
+    cors_origins: Vec<String>,
+
+    /// Append audit records to this JSONL file (default: in-memory only)
+    #[arg(long)]
+    audit_file: Option<PathBuf>,
+
+    /// Cancel a streamed /v1/responses task when its client disconnects
+    #[arg(long)]
+    cancel_on_disconnect: bool,
+
+    /// Believe X-Forwarded-For/Forwarded from these proxy addresses or CIDR ranges
+    #[arg(long, value_delimiter = ',')]
+    trusted_proxies: Vec<String>,
+
+    /// Log this fraction of HTTP requests, 0.0 to 1.0 (server errors are always logged)
+    #[arg(long, default_value = "1.0", value_parser = parse_sample_rate)]
+    http_log_sample_rate: f64,
+
+    /// Write a JSON access log line for every HTTP API request
+    #[arg(long)]
+    access_log: bool,
+
+    /// Write the access log to this file instead of the application log (implies --access-log)
+    #[arg(long)]
+    access_log_file: Option<PathBuf>,
+
+    /// Rotate the access log file when it reaches this size in MB
+    #[arg(long, default_value = "100")]
+    access_log_max_mb: u64,
+
+    /// Rotated access log files to keep
+    #[arg(long, default_value = "5")]
+    access_log_max_files: usize,
+
+    /// Fail tasks still pending after this many seconds (overridable per task)
+    #[arg(long)]
+    pending_ttl_secs: Option<u64>,
+
+    /// Reschedule a task up to this many times when its worker is lost mid-run
+    #[arg(long, default_value = "0")]
+    lost_run_retries: u32,
+
+    /// Reassign a run whose worker did not acknowledge its assignment within this many seconds
+    #[arg(long, default_value = "10")]
+    assignment_ack_timeout_secs: u64,
+
+    /// Spill run output past the threshold to files in this directory
+    #[arg(long, conflicts_with = "output_s3_bucket")]
+    output_dir: Option<PathBuf>,
+
+    /// Spill run output past the threshold to this S3 bucket
+    /// (credentials from AWS_ACCESS_KEY_ID / AWS_SECRET_ACCESS_KEY)
+    #[arg(long)]
+    output_s3_bucket: Option<String>,
+
+    /// S3 API endpoint (default: AWS for the region; set for MinIO, R2, ...)
+    #[arg(long)]
+    output_s3_endpoint: Option<String>,
+
+    /// S3 region
+    #[arg(long, default_value = "us-east-1")]
+    output_s3_region: String,
+
+    /// Key prefix for output objects
+    #[arg(long, default_value = "taskrun/outputs/")]
+    output_s3_prefix: String,
+
+    /// In-memory output (bytes) after which a run spills to the output store
+    #[arg(long, default_value_t = DEFAULT_SPILL_THRESHOLD)]
+    output_spill_bytes: usize,
+
+    /// Run in HA mode, electing a leader through this lease file (on storage
+    /// shared by all instances); term and state files are kept next to it
+    #[arg(long, requires = "advertise_addr")]
+    ha_lease_file: Option<PathBuf>,
+
+    /// gRPC address workers use to reach this instance, handed out when it leads
+    #[arg(long)]
+    advertise_addr: Option<String>,
+
+    /// Seconds a leader lease lasts without renewal
+    #[arg(long, default_value_t = DEFAULT_LEASE_TTL.as_secs())]
+    lease_ttl_secs: u64,
+
+    /// Load settings from this JSON file and reload them when it changes,
+    /// on SIGHUP or via POST /v1/admin/reload (log level, retention,
+    /// timezone, locale)
+    #[arg(long)]
+    config: Option<PathBuf>,
+
+    /// Time zone of TUI timestamps: utc, local or an IANA name (Europe/Berlin)
+    #[arg(long, default_value = "utc")]
+    tz: DisplayZone,
+
+    /// Date order and clock of TUI timestamps: iso, a locale such as en_US
+    /// or de_DE, or auto to follow LC_TIME/LANG
+    #[arg(long, default_value = "iso")]
+    locale: DisplayLocale,
+
+    /// Export traces of task creation, scheduling and delivery to this OTLP
+    /// gRPC endpoint (e.g. http://localhost:4317)
+    #[arg(long)]
+    otlp_endpoint: Option<String>,
+}
+
+/// Build a retention policy from an age in hours and a run count.
+fn retention_policy(hours: Option<u64>, runs: Option<usize>) -> RetentionPolicy {
+    RetentionPolicy {
+        max_age: hours.map(|h| Duration::from_secs(h * 3600)),
+        max_runs: runs,
+    }
 }
 
 fn main() -> io::Result<()> {
@@ -82,24 +297,107 @@ fn main() -> io::Result<()> {
         ca_cert_path: args.ca_cert,
         ca_key_path: args.ca_key,
         worker_cert_validity_days: args.worker_cert_validity_days,
+        previous_ca_cert_path: args.previous_ca_cert,
+        previous_ca_until: args.previous_ca_until,
+        revocations_file: args.revocations_file,
+        event_store: match args.events_file {
+            Some(path) => EventStoreConfig::File { path },
+            None => EventStoreConfig::Memory,
+        },
+        retention: RetentionConfig {
+            interval: Duration::from_secs(args.compaction_interval_secs),
+            events: retention_policy(args.event_retention_hours, args.event_retention_runs),
+            chat_messages: retention_policy(args.chat_retention_hours, args.chat_retention_runs),
+            outputs: retention_policy(args.output_retention_hours, args.output_retention_runs),
+        },
+        heartbeat_interval_secs: args.heartbeat_interval_secs,
+        max_missed_heartbeats: args.max_missed_heartbeats,
+        worker_stale_secs: args.worker_stale_secs,
+        schedule_store: match args.schedules_file {
+            Some(path) => ScheduleStoreConfig::File { path },
+            None => ScheduleStoreConfig::Memory,
+        },
+        workflows_file: args.workflows_file,
+        quotas_file: args.quotas_file,
+        toggles_file: args.toggles_file,
+        enable_chaos: args.enable_chaos,
+        api_keys_file: args.api_keys_file,
+        require_api_key: args.require_api_key,
+        http_tls: args.http_tls,
+        cors_origins: args.cors_origins,
+        audit_file: args.audit_file,
+        cancel_on_disconnect: args.cancel_on_disconnect,
+        trusted_proxies: args.trusted_proxies,
+        http_log_sample_rate: args.http_log_sample_rate,
+        access_log: args.access_log,
+        access_log_file: args.access_log_file.map(|path| AccessLogFile {
+            path,
+            max_bytes: args.access_log_max_mb.max(1) * 1024 * 1024,
+            max_files: args.access_log_max_files,
+        }),
+        pending_ttl_secs: args.pending_ttl_secs,
+        lost_run_retries: args.lost_run_retries,
+        assignment_ack_timeout_secs: args.assignment_ack_timeout_secs,
+        output_store: match (args.output_dir, args.output_s3_bucket) {
+            (Some(dir), _) => OutputStoreConfig::Disk { dir },
+            (None, Some(bucket)) => OutputStoreConfig::S3(S3Config {
+                endpoint: args.output_s3_endpoint.unwrap_or_else(|| {
+                    format!("https://s3.{}.amazonaws.com", args.output_s3_region)
+                }),
+                bucket,
+                region: args.output_s3_region,
+                prefix: args.output_s3_prefix,
+                access_key_id: std::env::var("AWS_ACCESS_KEY_ID").unwrap_or_default(),
+                secret_access_key: std::env::var("AWS_SECRET_ACCESS_KEY").unwrap_or_default(),
+            }),
+            (None, None) => OutputStoreConfig::Memory,
+        },
+        output_spill_bytes: args.output_spill_bytes,
+        ha: args.ha_lease_file.map(|path| LeaseConfig {
+            path,
+            advertise_addr: args.advertise_addr.unwrap_or_default(),
+            ttl: Duration::from_secs(args.lease_ttl_secs.max(3)),
+        }),
+        config_file: args.config,
+        log_filter: None,
+        time_format: TimeFormat::new(args.tz, args.locale),
     };
 
+    let otlp = start_trace_export(args.otlp_endpoint.as_deref());
     if args.headless {
-        run_headless(config)
+        run_headless(config, otlp)
     } else {
-        run_tui(config)
+        run_tui(config, otlp)
+    }
+}
+
+/// Start exporting traces if an OTLP endpoint was given.
+fn start_trace_export(endpoint: Option<&str>) -> Option<OtlpExporter> {
+    let endpoint = endpoint?;
+    match OtlpExporter::start("taskrun-server", endpoint) {
+        Ok(exporter) => Some(exporter),
+        Err(e) => {
+            eprintln!("Failed to start trace export to {}: {}", endpoint, e);
+            None
+        }
     }
 }
 
 /// Run the server in headless mode (daemon without TUI).
-fn run_headless(config: ServerConfig) -> io::Result<()> {
+fn run_headless(mut config: ServerConfig, otlp: Option<OtlpExporter>) -> io::Result<()> {
     // Initialize logging to stderr for headless mode
-    tracing_subscriber::fmt()
-        .with_env_filter(
-            EnvFilter::from_default_env().add_directive("taskrun=info".parse().unwrap()),
-        )
+    let subscriber = tracing_subscriber::fmt()
+        .with_env_filter(default_log_filter())
         .with_target(true)
+        .with_filter_reloading();
+    let handle = subscriber.reload_handle();
+    subscriber
+        .finish()
+        .with(otlp.as_ref().map(|otlp| otlp.layer()))
         .init();
+    config.log_filter = Some(LogFilterHandle::new(move |filter| {
+        handle.reload(filter).map_err(|e| e.to_string())
+    }));
 
     info!("TaskRun Server starting (headless mode)");
 
@@ -116,12 +414,10 @@ fn run_headless(config: ServerConfig) -> io::Result<()> {
 }
 
 /// Run the server with TUI.
-fn run_tui(config: ServerConfig) -> io::Result<()> {
+fn run_tui(mut config: ServerConfig, otlp: Option<OtlpExporter>) -> io::Result<()> {
     // Initialize logging to file for TUI mode (not stderr since we have TUI)
-    tracing_subscriber::fmt()
-        .with_env_filter(
-            EnvFilter::from_default_env().add_directive("taskrun=info".parse().unwrap()),
-        )
+    let subscriber = tracing_subscriber::fmt()
+        .with_env_filter(default_log_filter())
         .with_writer(|| {
             std::fs::OpenOptions::new()
                 .create(true)
@@ -129,13 +425,22 @@ fn run_tui(config: ServerConfig) -> io::Result<()> {
                 .open("taskrun-server.log")
                 .unwrap_or_else(|_| std::fs::File::create("/dev/null").unwrap())
         })
+        .with_filter_reloading();
+    let handle = subscriber.reload_handle();
+    subscriber
+        .finish()
+        .with(otlp.as_ref().map(|otlp| otlp.layer()))
         .init();
+    config.log_filter = Some(LogFilterHandle::new(move |filter| {
+        handle.reload(filter).map_err(|e| e.to_string())
+    }));
 
     info!("TaskRun Server starting");
 
     // Create channels for UI <-> backend communication
     let (ui_tx, ui_rx) = mpsc::channel::<ServerUiEvent>(1000);
     let (cmd_tx, cmd_rx) = mpsc::channel::<ServerCommand>(100);
+    let time_format = config.time_format;
 
     // Spawn backend in a separate thread with its own tokio runtime
     let backend_handle = thread::spawn(move || {
@@ -150,7 +455,9 @@ fn run_tui(config: ServerConfig) -> io::Result<()> {
     let mut terminal = Terminal::new(backend)?;
 
     // Run the TUI app
-    let result = ServerApp::new(ui_rx, cmd_tx).run(&mut terminal);
+    let result = ServerApp::new(ui_rx, cmd_tx)
+        .with_time_format(time_format)
+        .run(&mut terminal);
 
     // Cleanup terminal
     disable_raw_mode()?;
@@ -164,3 +471,10 @@ fn run_tui(config: ServerConfig) -> io::Result<()> {
 
     result
 }
+
+fn parse_sample_rate(s: &str) -> Result<f64, String> {
+    s.parse::<f64>()
+        .ok()
+        .filter(|rate| (0.0..=1.0).contains(rate))
+        .ok_or_else(|| format!("expected a number from 0.0 to 1.0, got '{}'", s))
+}
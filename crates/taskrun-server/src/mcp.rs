@@ -1,16 +1,29 @@
 //! MCP (Model Context Protocol) server implementation.
 //!
-//! Provides MCP tools for interacting with TaskRun:
+//! JSON-RPC over the Streamable HTTP transport at `/mcp` (`initialize`,
+//! `tools/list`, `tools/call` and notifications), so any MCP client can use
+//! TaskRun as a tool provider. Provides MCP tools for interacting with TaskRun:
 //! - `list_workers` - List connected workers
 //! - `start_new_task` - Create and start a new task
-//! - `get_task` - Get task details including status, input, output, and run history
+//! - `get_task` - Get task details including status, input, output, events, and chat
 //! - `continue_task` - Continue an existing task with a follow-up message
+//! - `search_tasks` - Full-text search over tasks and their output
+//! - `get_usage` - Tokens and cost of finished runs
+//! - `list_schedules` / `create_schedule` - Recurring tasks
+//! - `drain_worker` - Stop assigning runs to a worker before maintenance
+//!
+//! Tools check the caller's role as the matching gRPC call does.
+//!
+//! The REST routes under `/mcp/tools/*` are a deprecated alias of these tools.
 
+use std::collections::HashMap;
 use std::sync::Arc;
 
+use axum::http::request::Parts;
 use axum::Router;
+use chrono::{DateTime, Utc};
 use rmcp::{
-    handler::server::wrapper::Parameters,
+    handler::server::{tool::Extension, wrapper::Parameters},
     model::{CallToolResult, Content, ServerCapabilities, ServerInfo},
     tool, tool_handler, tool_router,
     transport::streamable_http_server::{
@@ -23,11 +36,21 @@ use serde::{Deserialize, Serialize};
 use tokio_util::sync::CancellationToken;
 use tracing::info;
 
+use crate::control_plane::audit::AuditAction;
+use crate::control_plane::drain;
+use crate::control_plane::http::client_ip::ClientIp;
+use crate::control_plane::quotas;
+use crate::control_plane::rbac::{self, Caller, Role};
+use crate::control_plane::scheduler::trace_created;
+use crate::control_plane::schedules::ScheduleManager;
+use crate::control_plane::search;
 use crate::control_plane::state::{AppState, UiNotification};
+use crate::control_plane::usage;
 use crate::control_plane::Scheduler;
-use taskrun_core::{ChatRole, Task};
+use taskrun_core::{ChatRole, RunEventType, Schedule, Task, DEFAULT_NAMESPACE};
 use taskrun_proto::pb::run_server_message::Payload as ServerPayload;
 use taskrun_proto::pb::{ContinueRun, RunServerMessage};
+use taskrun_proto::trace::TraceContext;
 
 /// Convert ChatRole to string.
 fn chat_role_to_string(role: &ChatRole) -> String {
@@ -39,10 +62,65 @@ fn chat_role_to_string(role: &ChatRole) -> String {
     .to_string()
 }
 
+/// Check that the caller of a tool has at least `role`.
+///
+/// Returns None for calls without an API key, which the HTTP middleware only
+/// lets through when keys are not required.
+fn authorize_tool(parts: &Parts, role: Role) -> Result<Option<Caller>, McpError> {
+    match parts.extensions.get::<Caller>() {
+        Some(caller) if caller.has_role(role) => Ok(Some(caller.clone())),
+        Some(caller) => Err(McpError::invalid_request(
+            format!(
+                "'{}' has role {}, {} required",
+                caller.identity, caller.role, role
+            ),
+            None,
+        )),
+        None => Ok(None),
+    }
+}
+
+/// Namespace the caller of a tool is bound to (None = all namespaces).
+fn caller_namespace(parts: &Parts) -> Option<String> {
+    parts
+        .extensions
+        .get::<Caller>()
+        .and_then(|c| c.namespace.clone())
+}
+
+/// Namespaces a read tool covers: the requested one, limited to the
+/// caller's (None = all namespaces).
+fn read_scope(parts: &Parts, requested: Option<&str>) -> Result<Option<String>, McpError> {
+    rbac::scope(parts.extensions.get::<Caller>(), requested)
+        .map_err(|e| McpError::invalid_request(e.to_string(), None))
+}
+
+/// Identity recorded for a tool call.
+fn actor(caller: Option<Caller>) -> String {
+    caller.map_or_else(|| "mcp".to_string(), |c| c.identity)
+}
+
+/// Client address of a tool call, as recorded.
+fn client_ip(parts: &Parts) -> Option<String> {
+    parts.extensions.get::<ClientIp>().map(ToString::to_string)
+}
+
+/// A tool result holding `value` as pretty-printed JSON.
+fn json_result<T: Serialize>(value: &T) -> CallToolResult {
+    let text = serde_json::to_string_pretty(value).unwrap_or_else(|_| "{}".to_string());
+    CallToolResult::success(vec![Content::text(text)])
+}
+
+/// A failed tool result; the model sees the message.
+fn tool_error(message: impl ToString) -> CallToolResult {
+    CallToolResult::error(vec![Content::text(message.to_string())])
+}
+
 /// MCP server for TaskRun operations.
 #[derive(Clone)]
 pub struct TaskRunMcpServer {
     state: Arc<AppState>,
+    schedules: Arc<ScheduleManager>,
     tool_router: rmcp::handler::server::router::tool::ToolRouter<Self>,
 }
 
@@ -66,12 +144,16 @@ pub struct StartNewTaskParams {
 
     /// Input for the task (plain text or JSON string).
     pub input: String,
+
+    /// Labels to add to the task.
+    #[serde(default)]
+    pub metadata: HashMap<String, String>,
 }
 
 /// Parameters for continue_task tool.
 #[derive(Debug, Deserialize, JsonSchema)]
 pub struct ContinueTaskParams {
-    /// Task ID to continue.
+    /// Task ID (or unique prefix) to continue.
     pub task_id: String,
 
     /// Follow-up message to send.
@@ -81,10 +163,74 @@ pub struct ContinueTaskParams {
 /// Parameters for get_task tool.
 #[derive(Debug, Deserialize, JsonSchema)]
 pub struct GetTaskParams {
-    /// Task ID to retrieve.
+    /// Task ID (or unique prefix) to retrieve.
     pub task_id: String,
 }
 
+/// Parameters for search_tasks tool.
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct SearchTasksParams {
+    /// Words that must all occur in the task input, labels, agent name or
+    /// output; a trailing `*` matches a prefix.
+    pub query: String,
+
+    /// Only search this namespace.
+    #[serde(default)]
+    pub namespace: Option<String>,
+
+    /// Most tasks to return (default 20).
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+/// Parameters for get_usage tool.
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct GetUsageParams {
+    /// Only count runs finished at or after this time (RFC 3339).
+    #[serde(default)]
+    pub since: Option<String>,
+
+    /// Only count tasks of this namespace.
+    #[serde(default)]
+    pub namespace: Option<String>,
+}
+
+/// Parameters for list_schedules tool.
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ListSchedulesParams {
+    /// Optional filter by agent name.
+    #[serde(default)]
+    pub agent: Option<String>,
+}
+
+/// Parameters for create_schedule tool.
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct CreateScheduleParams {
+    /// Cron expression, 5 fields (or 6 with leading seconds), in UTC.
+    pub cron_expr: String,
+
+    /// Name of the agent to run.
+    pub agent_name: String,
+
+    /// Input of each task; `{{fire_time}}`, `{{fire_time_ms}}` and `{{schedule_id}}` are filled in.
+    pub input_template: String,
+
+    /// Labels to add to every task.
+    #[serde(default)]
+    pub labels: HashMap<String, String>,
+
+    /// Run timeout of every task, in seconds.
+    #[serde(default)]
+    pub timeout_seconds: Option<u32>,
+}
+
+/// Parameters for drain_worker tool.
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct DrainWorkerParams {
+    /// Worker ID (or unique prefix) to drain.
+    pub worker_id: String,
+}
+
 // ============================================================================
 // Response Types
 // ============================================================================
@@ -116,6 +262,18 @@ pub struct ContinueTaskResult {
     pub status: String,
 }
 
+/// Run event of a task.
+#[derive(Debug, Serialize)]
+pub struct TaskEvent {
+    #[serde(rename = "type")]
+    pub event_type: RunEventType,
+    pub timestamp_ms: i64,
+
+    /// Additional metadata from the event.
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub metadata: HashMap<String, String>,
+}
+
 /// Chat message in the conversation.
 #[derive(Debug, Serialize)]
 pub struct ChatMessageInfo {
@@ -137,8 +295,15 @@ pub struct TaskDetails {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub output: Option<String>,
 
+    /// Run events, oldest first.
+    pub events: Vec<TaskEvent>,
+
     /// Chat messages in the conversation.
     pub chat_messages: Vec<ChatMessageInfo>,
+
+    /// Latest session ID (for continuation).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
 }
 
 // ============================================================================
@@ -148,9 +313,10 @@ pub struct TaskDetails {
 #[tool_router]
 impl TaskRunMcpServer {
     /// Create a new MCP server with the given AppState.
-    pub fn new(state: Arc<AppState>) -> Self {
+    pub fn new(state: Arc<AppState>, schedules: Arc<ScheduleManager>) -> Self {
         Self {
             state,
+            schedules,
             tool_router: Self::tool_router(),
         }
     }
@@ -161,12 +327,19 @@ impl TaskRunMcpServer {
     )]
     async fn list_workers(
         &self,
+        Extension(parts): Extension<Parts>,
         Parameters(params): Parameters<ListWorkersParams>,
     ) -> Result<CallToolResult, McpError> {
+        let namespace = caller_namespace(&parts);
         let workers = self.state.workers.read().await;
 
         let workers_list: Vec<WorkerInfo> = workers
             .values()
+            .filter(|w| {
+                namespace
+                    .as_ref()
+                    .map_or(true, |ns| &w.info.namespace == ns)
+            })
             .filter(|w| {
                 if let Some(ref agent) = params.agent {
                     w.info.supports_agent(agent)
@@ -198,10 +371,17 @@ impl TaskRunMcpServer {
     )]
     async fn start_new_task(
         &self,
+        Extension(parts): Extension<Parts>,
         Parameters(params): Parameters<StartNewTaskParams>,
     ) -> Result<CallToolResult, McpError> {
+        let caller = authorize_tool(&parts, Role::Operator)?;
+        let namespace = caller
+            .as_ref()
+            .and_then(|c| c.namespace.clone())
+            .unwrap_or_else(|| DEFAULT_NAMESPACE.to_string());
+
         // Check if any worker supports this agent
-        if !self.state.has_agent(&params.agent_name).await {
+        if !self.state.has_agent(&params.agent_name, &namespace).await {
             return Ok(CallToolResult::error(vec![Content::text(format!(
                 "No worker supports agent: {}",
                 params.agent_name
@@ -209,17 +389,38 @@ impl TaskRunMcpServer {
         }
 
         // Create task
-        let task = Task::new(&params.agent_name, &params.input, "mcp");
+        let created_by = caller.map_or_else(|| "mcp".to_string(), |c| c.identity);
+        if let Err(e) = self.state.toggles.check_accepting().await {
+            return Ok(CallToolResult::error(vec![Content::text(e.to_string())]));
+        }
+        if let Err(e) = quotas::check_create(&self.state, &namespace, &created_by).await {
+            return Ok(CallToolResult::error(vec![Content::text(e.to_string())]));
+        }
+        let client_ip = client_ip(&parts);
+        let mut task = Task::new(&params.agent_name, &params.input, created_by)
+            .with_namespace(namespace)
+            .with_priority(self.state.toggles.default_priority().await)
+            .with_client_ip(client_ip);
+        task.labels.insert("source".to_string(), "mcp".to_string());
+        task.labels.extend(params.metadata);
+        let task = trace_created(task, &TraceContext::new());
         let task_id = task.id.clone();
 
         // Store task
+        self.state
+            .audit_from(
+                &task.created_by,
+                task.client_ip.as_deref(),
+                AuditAction::TaskCreated,
+                task_id.as_str(),
+                &task,
+            )
+            .await;
+        let created = UiNotification::task_created(&task);
         self.state.tasks.write().await.insert(task_id.clone(), task);
 
         // Notify UI
-        self.state.notify_ui(UiNotification::TaskCreated {
-            task_id: task_id.clone(),
-            agent: params.agent_name.clone(),
-        });
+        self.state.notify_ui(created);
 
         info!(
             task_id = %task_id,
@@ -257,28 +458,44 @@ impl TaskRunMcpServer {
 
     /// Get details about a task.
     #[tool(
-        description = "Get details about a task including its status, input, output, and run history."
+        description = "Get details about a task including its status, input, output, run events, and chat history."
     )]
     async fn get_task(
         &self,
+        Extension(parts): Extension<Parts>,
         Parameters(params): Parameters<GetTaskParams>,
     ) -> Result<CallToolResult, McpError> {
-        let task_id = taskrun_core::TaskId::new(&params.task_id);
-
-        // Verify task exists
+        let namespace = caller_namespace(&parts);
+        let task_id = match self
+            .state
+            .resolve_task_id(&params.task_id, namespace.as_deref())
+            .await
         {
-            let tasks = self.state.tasks.read().await;
-            if !tasks.contains_key(&task_id) {
-                return Ok(CallToolResult::error(vec![Content::text(format!(
-                    "Task not found: {}",
-                    params.task_id
-                ))]));
-            }
-        }
+            Ok(id) => id,
+            Err(e) => return Ok(CallToolResult::error(vec![Content::text(e.to_string())])),
+        };
 
         // Get output
         let output = self.state.get_output_by_task(&task_id).await;
 
+        // Get events; the latest session is the one a continuation resumes
+        let events: Vec<TaskEvent> = self
+            .state
+            .get_events_by_task(&task_id)
+            .await
+            .into_iter()
+            .map(|e| TaskEvent {
+                event_type: e.event_type,
+                timestamp_ms: e.timestamp_ms,
+                metadata: e.metadata,
+            })
+            .collect();
+        let session_id = events
+            .iter()
+            .rev()
+            .filter(|e| e.event_type == RunEventType::SessionInitialized)
+            .find_map(|e| e.metadata.get("session_id").cloned());
+
         // Get chat messages
         let chat_messages: Vec<ChatMessageInfo> = self
             .state
@@ -308,7 +525,9 @@ impl TaskRunMcpServer {
             input: task.input_json.clone(),
             created_at: task.created_at.to_rfc3339(),
             output,
+            events,
             chat_messages,
+            session_id,
         };
 
         let response = serde_json::to_string_pretty(&details).unwrap_or_else(|_| "{}".to_string());
@@ -324,9 +543,20 @@ impl TaskRunMcpServer {
     )]
     async fn continue_task(
         &self,
+        Extension(parts): Extension<Parts>,
         Parameters(params): Parameters<ContinueTaskParams>,
     ) -> Result<CallToolResult, McpError> {
-        let task_id = taskrun_core::TaskId::new(&params.task_id);
+        authorize_tool(&parts, Role::Operator)?;
+
+        let namespace = caller_namespace(&parts);
+        let task_id = match self
+            .state
+            .resolve_task_id(&params.task_id, namespace.as_deref())
+            .await
+        {
+            Ok(id) => id,
+            Err(e) => return Ok(CallToolResult::error(vec![Content::text(e.to_string())])),
+        };
 
         // Get task and its latest run
         let (run_id, worker_id) = {
@@ -396,6 +626,141 @@ impl TaskRunMcpServer {
 
         Ok(CallToolResult::success(vec![Content::text(response)]))
     }
+
+    /// Search tasks by input, labels, agent name and output.
+    #[tool(
+        description = "Search tasks by words in their input, labels, agent name or output. Returns matching tasks, newest first, with the field that matched and a snippet."
+    )]
+    async fn search_tasks(
+        &self,
+        Extension(parts): Extension<Parts>,
+        Parameters(params): Parameters<SearchTasksParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let namespace = read_scope(&parts, params.namespace.as_deref())?;
+        let limit = params.limit.unwrap_or_default();
+        match search::search(&self.state, &params.query, namespace.as_deref(), limit).await {
+            Ok(hits) => Ok(json_result(&hits)),
+            Err(e) => Ok(tool_error(e)),
+        }
+    }
+
+    /// Report tokens and cost of finished runs.
+    #[tool(
+        description = "Report tokens and cost of finished runs, in total and per namespace, agent and task. Optionally only since a time (RFC 3339)."
+    )]
+    async fn get_usage(
+        &self,
+        Extension(parts): Extension<Parts>,
+        Parameters(params): Parameters<GetUsageParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let namespace = read_scope(&parts, params.namespace.as_deref())?;
+        let since = match params.since.as_deref().map(DateTime::parse_from_rfc3339) {
+            None => None,
+            Some(Ok(since)) => Some(since.with_timezone(&Utc)),
+            Some(Err(e)) => return Ok(tool_error(format!("Invalid since: {}", e))),
+        };
+        let report = usage::report(&self.state, since, namespace.as_deref()).await;
+        Ok(json_result(&report))
+    }
+
+    /// List schedules.
+    #[tool(
+        description = "List schedules that create a task for an agent whenever their cron expression fires, with their next fire time. Optionally filter by agent name."
+    )]
+    async fn list_schedules(
+        &self,
+        Parameters(params): Parameters<ListSchedulesParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let schedules: Vec<Schedule> = self
+            .schedules
+            .list()
+            .await
+            .into_iter()
+            .filter(|s| params.agent.as_ref().map_or(true, |a| &s.agent_name == a))
+            .collect();
+        Ok(json_result(&schedules))
+    }
+
+    /// Create a schedule.
+    #[tool(
+        description = "Create a schedule that starts a task for an agent every time a cron expression (UTC) fires."
+    )]
+    async fn create_schedule(
+        &self,
+        Extension(parts): Extension<Parts>,
+        Parameters(params): Parameters<CreateScheduleParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let caller = authorize_tool(&parts, Role::Operator)?;
+        // Scheduled tasks are created in the default namespace
+        if let Some(caller) = &caller {
+            caller
+                .require_unbound()
+                .map_err(|e| McpError::invalid_request(e.to_string(), None))?;
+        }
+
+        let mut schedule = Schedule::new(
+            params.cron_expr,
+            params.agent_name,
+            params.input_template,
+            actor(caller),
+        );
+        schedule.labels = params.labels;
+        if let Some(timeout_seconds) = params.timeout_seconds.filter(|t| *t > 0) {
+            schedule = schedule.with_timeout_seconds(timeout_seconds);
+        }
+        let schedule = match self.schedules.create(schedule).await {
+            Ok(schedule) => schedule,
+            Err(e) => return Ok(tool_error(e)),
+        };
+        self.state
+            .audit_from(
+                &schedule.created_by,
+                client_ip(&parts).as_deref(),
+                AuditAction::ScheduleCreated,
+                schedule.id.as_str(),
+                &schedule,
+            )
+            .await;
+
+        info!(schedule_id = %schedule.id, "Created schedule via MCP");
+        Ok(json_result(&schedule))
+    }
+
+    /// Drain a worker.
+    #[tool(
+        description = "Drain a worker before maintenance: it gets no new runs but finishes its current ones. Returns the runs it is still executing; the worker is drained once there are none."
+    )]
+    async fn drain_worker(
+        &self,
+        Extension(parts): Extension<Parts>,
+        Parameters(params): Parameters<DrainWorkerParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let caller = authorize_tool(&parts, Role::Admin)?;
+        let namespace = caller.as_ref().and_then(|c| c.namespace.clone());
+        let worker_id = match self
+            .state
+            .resolve_worker_id(&params.worker_id, namespace.as_deref())
+            .await
+        {
+            Ok(id) => id,
+            Err(e) => return Ok(tool_error(e)),
+        };
+        let Some(progress) = drain::set_draining(&self.state, &worker_id, true).await else {
+            return Ok(tool_error(format!("Worker {} not found", worker_id)));
+        };
+        self.state
+            .audit_from(
+                &actor(caller),
+                client_ip(&parts).as_deref(),
+                AuditAction::WorkerDrained,
+                worker_id.as_str(),
+                &progress,
+            )
+            .await;
+
+        info!(worker_id = %worker_id, "Drained worker via MCP");
+        Ok(json_result(&progress))
+    }
 }
 
 // ============================================================================
@@ -418,7 +783,8 @@ impl ServerHandler for TaskRunMcpServer {
             instructions: Some(
                 "TaskRun MCP Server - Control AI agent tasks on remote workers. \
                  Use list_workers to see available workers, start_new_task to create tasks, \
-                 and continue_task to send follow-up messages."
+                 and continue_task to send follow-up messages. search_tasks, get_usage, \
+                 list_schedules, create_schedule and drain_worker administer the cluster."
                     .to_string(),
             ),
         }
@@ -433,10 +799,13 @@ impl ServerHandler for TaskRunMcpServer {
 ///
 /// This router handles MCP protocol requests over HTTP using the Streamable HTTP transport.
 /// Mount this at `/mcp` on your existing HTTP server or run it standalone.
-pub fn create_mcp_router(state: Arc<AppState>, ct: CancellationToken) -> Router {
-    let state_clone = state.clone();
+pub fn create_mcp_router(
+    state: Arc<AppState>,
+    schedules: Arc<ScheduleManager>,
+    ct: CancellationToken,
+) -> Router {
     let service = StreamableHttpService::new(
-        move || Ok(TaskRunMcpServer::new(state_clone.clone())),
+        move || Ok(TaskRunMcpServer::new(state.clone(), schedules.clone())),
         LocalSessionManager::default().into(),
         StreamableHttpServerConfig {
             cancellation_token: ct,
@@ -448,3 +817,35 @@ pub fn create_mcp_router(state: Arc<AppState>, ct: CancellationToken) -> Router
 
     Router::new().nest_service("/mcp", service)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::control_plane::schedules::store::InMemoryScheduleStore;
+
+    #[tokio::test]
+    async fn test_admin_tools_have_input_schemas() {
+        let state = AppState::new();
+        let schedules =
+            ScheduleManager::open(state.clone(), Arc::new(InMemoryScheduleStore::new()))
+                .await
+                .unwrap();
+        let server = TaskRunMcpServer::new(state, schedules);
+        let tools = server.tool_router.list_all();
+
+        for name in [
+            "list_schedules",
+            "create_schedule",
+            "drain_worker",
+            "get_usage",
+            "search_tasks",
+        ] {
+            assert!(tools.iter().any(|t| t.name == name), "missing {name}");
+        }
+        let create = tools.iter().find(|t| t.name == "create_schedule").unwrap();
+        let required = create.input_schema["required"].as_array().unwrap();
+        for field in ["cron_expr", "agent_name", "input_template"] {
+            assert!(required.iter().any(|r| r == field), "{field} not required");
+        }
+    }
+}
@@ -7,10 +7,14 @@ use taskrun_tui_components::{Footer, Header, HeaderStat, StatusIndicator};
 
 use crate::state::{ServerStatus, ServerUiState, ServerView};
 use crate::views::dialogs::{
-    render_cancel_confirm, render_disconnect_confirm, render_new_task_dialog, render_quit_confirm,
+    render_approval_dialog, render_bulk_confirm, render_cancel_confirm, render_disconnect_confirm,
+    render_new_task_dialog, render_quit_confirm, render_search_input, render_settings_dialog,
+    render_toast,
 };
 use crate::views::{
-    render_logs_view, render_run_detail_view, render_tasks_view, render_workers_view,
+    render_approvals_view, render_audit_view, render_fleet_view, render_logs_view,
+    render_queue_view, render_run_detail_view, render_schedules_view, render_tasks_view,
+    render_workers_view,
 };
 
 /// Main render function.
@@ -28,6 +32,10 @@ pub fn render(f: &mut Frame, state: &ServerUiState) {
     render_main_content(f, state, chunks[1]);
     render_footer(f, state, chunks[2]);
 
+    if let Some(toast) = &state.toast {
+        render_toast(f, &toast.message);
+    }
+
     // Render dialogs on top
     if state.show_quit_confirm {
         render_quit_confirm(f);
@@ -41,6 +49,20 @@ pub fn render(f: &mut Frame, state: &ServerUiState) {
     if state.show_disconnect_confirm {
         render_disconnect_confirm(f, state);
     }
+    if let Some(bulk) = &state.bulk_confirm {
+        render_bulk_confirm(f, bulk);
+    }
+    if let Some(input) = &state.search_input {
+        render_search_input(f, input);
+    }
+    if state.show_approval_dialog {
+        if let Some(approval) = state.pending_approvals.front() {
+            render_approval_dialog(f, approval, state.pending_approvals.len());
+        }
+    }
+    if state.show_settings_dialog {
+        render_settings_dialog(f, state);
+    }
 }
 
 fn render_header(f: &mut Frame, state: &ServerUiState, area: ratatui::layout::Rect) {
@@ -64,18 +86,27 @@ fn render_header(f: &mut Frame, state: &ServerUiState, area: ratatui::layout::Re
         uptime.as_secs() % 60
     );
 
+    let mut stats = vec![
+        HeaderStat::new("Workers", state.workers.len().to_string()),
+        HeaderStat::new("Tasks", state.total_tasks.to_string()),
+        HeaderStat::new("Done", state.completed_tasks.to_string())
+            .color(ratatui::style::Color::Green),
+        HeaderStat::new("Failed", state.failed_tasks.to_string()).color(ratatui::style::Color::Red),
+        HeaderStat::new("Approvals", state.pending_approvals.len().to_string())
+            .color(ratatui::style::Color::Yellow),
+        HeaderStat::new("Up", uptime_str),
+    ];
+    if state.toggles.maintenance {
+        stats.push(HeaderStat::new("Mode", "maintenance").color(ratatui::style::Color::Red));
+    }
+    if state.toggles.scheduling_frozen {
+        stats.push(HeaderStat::new("Scheduling", "frozen").color(ratatui::style::Color::Red));
+    }
+
     Header::new("TaskRun Server")
         .status(status)
         .tabs(tabs, selected)
-        .stats(vec![
-            HeaderStat::new("Workers", state.workers.len().to_string()),
-            HeaderStat::new("Tasks", state.total_tasks.to_string()),
-            HeaderStat::new("Done", state.completed_tasks.to_string())
-                .color(ratatui::style::Color::Green),
-            HeaderStat::new("Failed", state.failed_tasks.to_string())
-                .color(ratatui::style::Color::Red),
-            HeaderStat::new("Up", uptime_str),
-        ])
+        .stats(stats)
         .render(f, area);
 }
 
@@ -84,19 +115,76 @@ fn render_main_content(f: &mut Frame, state: &ServerUiState, area: ratatui::layo
         ServerView::Workers => render_workers_view(f, state, area),
         ServerView::Tasks => render_tasks_view(f, state, area),
         ServerView::Logs => render_logs_view(f, state, area),
+        ServerView::Schedules => render_schedules_view(f, state, area),
+        ServerView::Audit => render_audit_view(f, state, area),
+        ServerView::Approvals => render_approvals_view(f, state, area),
+        ServerView::Fleet => render_fleet_view(f, state, area),
+        ServerView::Queue => render_queue_view(f, state, area),
         ServerView::RunDetail => render_run_detail_view(f, state, area),
     }
 }
 
 fn render_footer(f: &mut Frame, state: &ServerUiState, area: ratatui::layout::Rect) {
     let help_text = match state.current_view {
-        ServerView::Workers => "j/k: Navigate | d: Disconnect | Tab: Next view | q: Quit",
+        ServerView::Workers => "j/k: Navigate | d: Disconnect | D: Drain/Undrain | S: Settings | Tab: Next view | q: Quit",
+        ServerView::Tasks if state.task_search.is_some() => {
+            "j/k: Navigate | /: Search | c: Cancel | Enter: Details | Esc: Clear search"
+        }
         ServerView::Tasks => {
-            "j/k: Navigate | n: New task | c: Cancel | Enter: Details | Tab: Next view | q: Quit"
+            "j/k: Navigate | n: New task | /: Search | c: Cancel | C/R: Cancel/retry similar | Enter: Details | q: Quit"
+        }
+        ServerView::Logs => "j/k: Scroll | g/G: Top/Bottom | T: Relative times | Tab: Next view | q: Quit",
+        ServerView::Schedules => "j/k: Navigate | p: Pause/Resume | T: Relative times | Tab: Next view | q: Quit",
+        ServerView::Audit => "j/k: Navigate | g/G: Newest/Oldest | T: Relative times | Tab: Next view | q: Quit",
+        ServerView::Approvals => {
+            "j/k: Navigate | y: Approve | n: Deny | A: Always allow | Tab: Next view | q: Quit"
+        }
+        ServerView::Fleet if state.fleet_group.is_some() => {
+            "j/k: Navigate | Esc: Back to groups | Tab: Next view | q: Quit"
+        }
+        ServerView::Fleet => {
+            "j/k: Navigate | l: Group by next label | Enter: Workers | Tab: Next view | q: Quit"
+        }
+        ServerView::Queue => {
+            "j/k: Navigate | g/G: First/Last | S: Settings | Tab: Next view | q: Quit"
+        }
+        ServerView::RunDetail => {
+            "PgUp/PgDn: Scroll | Ctrl+O: Open attachment | Ctrl+R: Raw/markdown | Enter: Send | Esc: Back"
         }
-        ServerView::Logs => "j/k: Scroll | g/G: Top/Bottom | Tab: Next view | q: Quit",
-        ServerView::RunDetail => "j/k: Scroll | g/G: Top/Bottom | Esc: Back | q: Quit",
     };
 
-    Footer::new(help_text).render(f, area);
+    // Resource readout on the right, when there is room for it
+    let area = match &state.debug_stats {
+        Some(stats) => {
+            let readout = stats.summary();
+            let width = readout.chars().count() as u16;
+            if area.width > width * 2 {
+                let chunks = Layout::default()
+                    .direction(Direction::Horizontal)
+                    .constraints([Constraint::Min(0), Constraint::Length(width)])
+                    .split(area);
+                Footer::new(&readout).render(f, chunks[1]);
+                chunks[0]
+            } else {
+                area
+            }
+        }
+        None => area,
+    };
+
+    if state.pending_approvals.is_empty()
+        || matches!(
+            state.current_view,
+            ServerView::RunDetail | ServerView::Approvals
+        )
+    {
+        Footer::new(help_text).render(f, area);
+    } else {
+        let help_text = format!(
+            "a: Approvals ({}) | {}",
+            state.pending_approvals.len(),
+            help_text
+        );
+        Footer::new(&help_text).render(f, area);
+    }
 }
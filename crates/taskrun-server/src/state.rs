@@ -1,11 +1,33 @@
 //! Server TUI state types.
 
 use std::collections::{HashMap, VecDeque};
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use chrono::{DateTime, Utc};
-use taskrun_core::{ChatRole, RunEventType, RunId, RunStatus, TaskId, TaskStatus, WorkerId, WorkerStatus};
-use taskrun_tui_components::{LogEntry, LogLevel};
+use taskrun_core::{
+    Artifact, ChatRole, RunEventType, RunId, RunMilestone, RunResultSummary, RunStatus, Schedule,
+    ScheduleId, TaskId, TaskPriority, TaskStatus, WorkerId, WorkerStatus,
+};
+use taskrun_tui_components::{GroupProgress, LogEntry, LogLevel, TimeFormat, ValidationMessage};
+
+use crate::control_plane::approvals::Approval;
+use crate::control_plane::audit::AuditRecord;
+use crate::control_plane::bulk::{BulkOperation, TaskFilter};
+use crate::control_plane::debug_stats::DebugStats;
+use crate::control_plane::queue::{QueueEntry, QueuePosition};
+use crate::control_plane::toggles::{RuntimeToggles, ToggleUpdate};
+
+/// Audit records kept for the Audit view.
+pub const MAX_AUDIT_RECORDS: usize = 500;
+
+/// How long a toast stays on screen.
+pub const TOAST_DURATION: Duration = Duration::from_secs(5);
+
+/// Fleet group of workers without the grouping label.
+pub const UNLABELED_GROUP: &str = "(none)";
+
+/// Rows of the settings dialog: maintenance, scheduling freeze, default priority.
+pub const SETTING_ROWS: usize = 3;
 
 /// Server views.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -13,13 +35,27 @@ pub enum ServerView {
     Workers,
     Tasks,
     Logs,
+    Schedules,
+    Audit,
+    Approvals,
+    Fleet,
+    Queue,
     RunDetail,
 }
 
 impl ServerView {
     /// Views shown in the tab bar (excludes RunDetail which is a drill-down).
     pub fn all() -> &'static [ServerView] {
-        &[ServerView::Workers, ServerView::Tasks, ServerView::Logs]
+        &[
+            ServerView::Workers,
+            ServerView::Tasks,
+            ServerView::Logs,
+            ServerView::Schedules,
+            ServerView::Audit,
+            ServerView::Approvals,
+            ServerView::Fleet,
+            ServerView::Queue,
+        ]
     }
 
     pub fn name(&self) -> &'static str {
@@ -27,6 +63,11 @@ impl ServerView {
             ServerView::Workers => "Workers",
             ServerView::Tasks => "Tasks",
             ServerView::Logs => "Logs",
+            ServerView::Schedules => "Schedules",
+            ServerView::Audit => "Audit",
+            ServerView::Approvals => "Approvals",
+            ServerView::Fleet => "Fleet",
+            ServerView::Queue => "Queue",
             ServerView::RunDetail => "Run Detail",
         }
     }
@@ -35,16 +76,26 @@ impl ServerView {
         match self {
             ServerView::Workers => ServerView::Tasks,
             ServerView::Tasks => ServerView::Logs,
-            ServerView::Logs => ServerView::Workers,
+            ServerView::Logs => ServerView::Schedules,
+            ServerView::Schedules => ServerView::Audit,
+            ServerView::Audit => ServerView::Approvals,
+            ServerView::Approvals => ServerView::Fleet,
+            ServerView::Fleet => ServerView::Queue,
+            ServerView::Queue => ServerView::Workers,
             ServerView::RunDetail => ServerView::Tasks,
         }
     }
 
     pub fn prev(&self) -> ServerView {
         match self {
-            ServerView::Workers => ServerView::Logs,
+            ServerView::Workers => ServerView::Queue,
             ServerView::Tasks => ServerView::Workers,
             ServerView::Logs => ServerView::Tasks,
+            ServerView::Schedules => ServerView::Logs,
+            ServerView::Audit => ServerView::Schedules,
+            ServerView::Approvals => ServerView::Audit,
+            ServerView::Fleet => ServerView::Approvals,
+            ServerView::Queue => ServerView::Fleet,
             ServerView::RunDetail => ServerView::Tasks,
         }
     }
@@ -57,11 +108,47 @@ pub struct WorkerDisplayInfo {
     pub worker_id: WorkerId,
     pub hostname: String,
     pub agents: Vec<String>,
+    pub labels: HashMap<String, String>,
     pub status: WorkerStatus,
     pub active_runs: u32,
     pub max_concurrent_runs: u32,
     pub connected_at: DateTime<Utc>,
     pub last_heartbeat: DateTime<Utc>,
+    /// Metrics of the last heartbeat.
+    pub metrics: HashMap<String, String>,
+}
+
+/// Workers sharing a label value, shown as one row of the Fleet view.
+#[derive(Debug, Clone)]
+pub struct FleetGroup<'a> {
+    /// Label value (`UNLABELED_GROUP` for workers without the label).
+    pub name: String,
+    /// Workers in the group, in Workers view order.
+    pub workers: Vec<&'a WorkerDisplayInfo>,
+}
+
+impl FleetGroup<'_> {
+    /// Runs executing across the group.
+    pub fn active_runs(&self) -> u32 {
+        self.workers.iter().map(|w| w.active_runs).sum()
+    }
+
+    /// Run slots across the group.
+    pub fn max_runs(&self) -> u32 {
+        self.workers.iter().map(|w| w.max_concurrent_runs).sum()
+    }
+
+    /// Workers in `status`.
+    pub fn count(&self, status: WorkerStatus) -> usize {
+        self.workers.iter().filter(|w| w.status == status).count()
+    }
+
+    /// Workers that are degraded, in error or offline.
+    pub fn unhealthy(&self) -> usize {
+        self.count(WorkerStatus::Degraded)
+            + self.count(WorkerStatus::Error)
+            + self.count(WorkerStatus::Offline)
+    }
 }
 
 /// Cached task info for display.
@@ -69,6 +156,9 @@ pub struct WorkerDisplayInfo {
 pub struct TaskDisplayInfo {
     pub task_id: TaskId,
     pub agent_name: String,
+    pub created_by: String,
+    /// Address of the HTTP client that created the task.
+    pub client_ip: Option<String>,
     pub status: TaskStatus,
     pub created_at: DateTime<Utc>,
     pub run_count: usize,
@@ -76,6 +166,43 @@ pub struct TaskDisplayInfo {
     pub latest_run_status: Option<RunStatus>,
 }
 
+/// Bulk operation shown in the confirm dialog.
+#[derive(Debug, Clone)]
+pub struct BulkConfirm {
+    pub op: BulkOperation,
+    pub filter: TaskFilter,
+    /// Tasks affected according to the dry run.
+    pub task_count: usize,
+}
+
+/// Search results shown in place of the full task list.
+#[derive(Debug, Clone)]
+pub struct TaskSearch {
+    pub query: String,
+    /// Matching tasks, newest first.
+    pub task_ids: Vec<TaskId>,
+}
+
+/// Short-lived notice drawn over the current view.
+#[derive(Debug, Clone)]
+pub struct Toast {
+    pub message: String,
+    pub shown_at: Instant,
+}
+
+impl Toast {
+    pub fn new(message: String) -> Self {
+        Self {
+            message,
+            shown_at: Instant::now(),
+        }
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.shown_at.elapsed() >= TOAST_DURATION
+    }
+}
+
 /// Chat message entry for display.
 #[derive(Debug, Clone)]
 #[allow(dead_code)] // timestamp for future display use
@@ -83,6 +210,7 @@ pub struct ChatEntry {
     pub timestamp: DateTime<Utc>,
     pub role: ChatRole,
     pub content: String,
+    pub attachments: Vec<Artifact>,
 }
 
 /// Run event entry for display.
@@ -117,16 +245,40 @@ pub struct ServerUiState {
     pub workers: HashMap<WorkerId, WorkerDisplayInfo>,
     pub selected_worker_index: usize,
 
+    // Fleet view
+    /// Label key workers are grouped by (None = the first key any worker has).
+    pub fleet_label_key: Option<String>,
+    pub selected_fleet_group_index: usize,
+    /// Group drilled into, listing its workers.
+    pub fleet_group: Option<String>,
+    pub selected_fleet_worker_index: usize,
+
     // Tasks view
     pub tasks: HashMap<TaskId, TaskDisplayInfo>,
     pub task_list: Vec<TaskId>, // Sorted list for display
+    /// Progress of unfinished or recently finished groups.
+    pub groups: Vec<GroupProgress>,
+    /// Position and ETA of queued tasks.
+    pub queue_positions: HashMap<TaskId, QueuePosition>,
+
+    // Queue view (dispatch order)
+    pub queue: Vec<QueueEntry>,
+    pub selected_queue_index: usize,
     pub selected_task_index: usize,
+    /// Search prompt text while the prompt is open.
+    pub search_input: Option<String>,
+    /// Active search filtering the task list.
+    pub task_search: Option<TaskSearch>,
 
     // Run detail view
     pub viewing_task_id: Option<TaskId>,
     pub run_output: HashMap<RunId, String>,
-    pub run_chat: HashMap<RunId, Vec<ChatEntry>>,     // Chat messages per run
-    pub run_events: HashMap<RunId, Vec<EventEntry>>,  // Events per run
+    /// Next output chunk expected per run; earlier chunks are already shown.
+    pub run_output_next_seq: HashMap<RunId, u64>,
+    pub run_chat: HashMap<RunId, Vec<ChatEntry>>, // Chat messages per run
+    pub run_events: HashMap<RunId, Vec<EventEntry>>, // Events per run
+    pub run_milestones: HashMap<RunId, Vec<RunMilestone>>, // Reported milestones per run
+    pub run_results: HashMap<RunId, RunResultSummary>, // Result summaries of finished runs
     pub run_scroll: usize,
     pub events_scroll: usize,
     pub chat_input: String,       // Current chat input text
@@ -136,16 +288,46 @@ pub struct ServerUiState {
     pub log_messages: VecDeque<LogEntry>,
     pub log_scroll: usize,
 
+    // Schedules view
+    pub schedules: HashMap<ScheduleId, Schedule>,
+    pub selected_schedule_index: usize,
+
+    // Audit view (oldest first)
+    pub audit_records: VecDeque<AuditRecord>,
+    pub selected_audit_index: usize,
+
     // Dialogs
     pub show_new_task_dialog: bool,
     pub new_task_agent: String,
     pub new_task_input: String,
     pub new_task_cursor: usize,
     pub new_task_field: usize, // 0 = agent, 1 = input
+    /// Findings of the latest validation of the dialog's contents.
+    pub new_task_validation: Vec<ValidationMessage>,
 
     pub show_cancel_confirm: bool,
     pub show_disconnect_confirm: bool,
     pub show_quit_confirm: bool,
+    /// Bulk operation awaiting confirmation.
+    pub bulk_confirm: Option<BulkConfirm>,
+
+    // Settings dialog (runtime toggles)
+    pub toggles: RuntimeToggles,
+    /// Latest process and state counters, shown in the footer.
+    pub debug_stats: Option<DebugStats>,
+    /// How timestamps are shown; `T` toggles relative times.
+    pub time_format: TimeFormat,
+    /// Show agent responses as raw text instead of rendered markdown;
+    /// `Ctrl+R` toggles it in run detail.
+    pub raw_chat: bool,
+    pub show_settings_dialog: bool,
+    pub selected_setting_index: usize,
+
+    // Tool approvals (oldest first)
+    pub pending_approvals: VecDeque<Approval>,
+    pub selected_approval_index: usize,
+    pub show_approval_dialog: bool,
+    pub toast: Option<Toast>,
 
     // Stats
     pub total_tasks: u64,
@@ -167,14 +349,28 @@ impl ServerUiState {
             workers: HashMap::new(),
             selected_worker_index: 0,
 
+            fleet_label_key: None,
+            selected_fleet_group_index: 0,
+            fleet_group: None,
+            selected_fleet_worker_index: 0,
+
             tasks: HashMap::new(),
             task_list: Vec::new(),
+            groups: Vec::new(),
+            queue_positions: HashMap::new(),
+            queue: Vec::new(),
+            selected_queue_index: 0,
             selected_task_index: 0,
+            search_input: None,
+            task_search: None,
 
             viewing_task_id: None,
             run_output: HashMap::new(),
+            run_output_next_seq: HashMap::new(),
             run_chat: HashMap::new(),
             run_events: HashMap::new(),
+            run_milestones: HashMap::new(),
+            run_results: HashMap::new(),
             run_scroll: 0,
             events_scroll: 0,
             chat_input: String::new(),
@@ -183,15 +379,34 @@ impl ServerUiState {
             log_messages: VecDeque::with_capacity(1000),
             log_scroll: 0,
 
+            schedules: HashMap::new(),
+            selected_schedule_index: 0,
+            audit_records: VecDeque::new(),
+            selected_audit_index: 0,
+
             show_new_task_dialog: false,
             new_task_agent: String::new(),
             new_task_input: String::new(),
             new_task_cursor: 0,
             new_task_field: 0,
+            new_task_validation: Vec::new(),
 
             show_cancel_confirm: false,
             show_disconnect_confirm: false,
             show_quit_confirm: false,
+            bulk_confirm: None,
+
+            toggles: RuntimeToggles::default(),
+            debug_stats: None,
+            time_format: TimeFormat::default(),
+            raw_chat: false,
+            show_settings_dialog: false,
+            selected_setting_index: 0,
+
+            pending_approvals: VecDeque::new(),
+            selected_approval_index: 0,
+            show_approval_dialog: false,
+            toast: None,
 
             total_tasks: 0,
             completed_tasks: 0,
@@ -199,6 +414,39 @@ impl ServerUiState {
         }
     }
 
+    /// Change flipping the settings dialog row at `index`.
+    pub fn flip_setting(&self, index: usize) -> Option<ToggleUpdate> {
+        let toggles = self.toggles;
+        let update = match index {
+            0 => ToggleUpdate {
+                maintenance: Some(!toggles.maintenance),
+                ..Default::default()
+            },
+            1 => ToggleUpdate {
+                scheduling_frozen: Some(!toggles.scheduling_frozen),
+                ..Default::default()
+            },
+            2 => ToggleUpdate {
+                default_priority: Some(match toggles.default_priority {
+                    TaskPriority::Batch => TaskPriority::Interactive,
+                    TaskPriority::Interactive => TaskPriority::Batch,
+                }),
+                ..Default::default()
+            },
+            _ => return None,
+        };
+        Some(update)
+    }
+
+    /// Input of the task in the new-task dialog (`{}` when left empty).
+    pub fn new_task_input_json(&self) -> String {
+        if self.new_task_input.is_empty() {
+            "{}".to_string()
+        } else {
+            self.new_task_input.clone()
+        }
+    }
+
     pub fn uptime(&self) -> std::time::Duration {
         self.start_time.elapsed()
     }
@@ -214,9 +462,16 @@ impl ServerUiState {
         }
     }
 
+    pub fn add_audit_record(&mut self, record: AuditRecord) {
+        self.audit_records.push_back(record);
+        while self.audit_records.len() > MAX_AUDIT_RECORDS {
+            self.audit_records.pop_front();
+        }
+    }
+
     pub fn worker_list(&self) -> Vec<&WorkerDisplayInfo> {
         let mut workers: Vec<_> = self.workers.values().collect();
-        workers.sort_by(|a, b| a.worker_id.to_string().cmp(&b.worker_id.to_string()));
+        workers.sort_by_key(|a| a.worker_id.to_string());
         workers
     }
 
@@ -224,17 +479,117 @@ impl ServerUiState {
         self.worker_list().get(self.selected_worker_index).copied()
     }
 
+    /// Label keys of connected workers, sorted.
+    pub fn fleet_label_keys(&self) -> Vec<&str> {
+        let mut keys: Vec<&str> = self
+            .workers
+            .values()
+            .flat_map(|w| w.labels.keys().map(String::as_str))
+            .collect();
+        keys.sort_unstable();
+        keys.dedup();
+        keys
+    }
+
+    /// Label key the Fleet view groups by.
+    pub fn fleet_key(&self) -> Option<&str> {
+        self.fleet_label_key
+            .as_deref()
+            .or_else(|| self.fleet_label_keys().first().copied())
+    }
+
+    /// Group by the next label key, back to the group list.
+    pub fn cycle_fleet_label_key(&mut self) {
+        let keys = self.fleet_label_keys();
+        let next = match self.fleet_key() {
+            Some(current) => keys
+                .iter()
+                .position(|k| *k == current)
+                .map_or(0, |i| (i + 1) % keys.len()),
+            None => 0,
+        };
+        self.fleet_label_key = keys.get(next).map(|k| k.to_string());
+        self.fleet_group = None;
+        self.selected_fleet_group_index = 0;
+    }
+
+    /// Workers grouped by the Fleet view's label key, by label value
+    /// (workers without the label last).
+    pub fn fleet_groups(&self) -> Vec<FleetGroup<'_>> {
+        let key = self.fleet_key();
+        let mut groups: Vec<FleetGroup> = Vec::new();
+        for worker in self.worker_list() {
+            let name = key
+                .and_then(|k| worker.labels.get(k))
+                .map_or(UNLABELED_GROUP, String::as_str);
+            match groups.iter_mut().find(|g| g.name == name) {
+                Some(group) => group.workers.push(worker),
+                None => groups.push(FleetGroup {
+                    name: name.to_string(),
+                    workers: vec![worker],
+                }),
+            }
+        }
+        groups.sort_by(|a, b| {
+            (a.name == UNLABELED_GROUP, &a.name).cmp(&(b.name == UNLABELED_GROUP, &b.name))
+        });
+        groups
+    }
+
+    pub fn get_selected_fleet_group(&self) -> Option<FleetGroup<'_>> {
+        self.fleet_groups()
+            .into_iter()
+            .nth(self.selected_fleet_group_index)
+    }
+
+    /// Workers of the group drilled into in the Fleet view.
+    pub fn fleet_group_workers(&self) -> Vec<&WorkerDisplayInfo> {
+        let Some(name) = &self.fleet_group else {
+            return Vec::new();
+        };
+        self.fleet_groups()
+            .into_iter()
+            .find(|g| &g.name == name)
+            .map(|g| g.workers)
+            .unwrap_or_default()
+    }
+
+    /// Tasks shown in the Tasks view: the search results, or every task.
+    pub fn visible_task_ids(&self) -> &[TaskId] {
+        match &self.task_search {
+            Some(search) => &search.task_ids,
+            None => &self.task_list,
+        }
+    }
+
     pub fn task_display_list(&self) -> Vec<&TaskDisplayInfo> {
-        self.task_list
+        self.visible_task_ids()
             .iter()
             .filter_map(|id| self.tasks.get(id))
             .collect()
     }
 
     pub fn get_selected_task(&self) -> Option<&TaskDisplayInfo> {
-        self.task_list
+        self.task_display_list()
             .get(self.selected_task_index)
-            .and_then(|id| self.tasks.get(id))
+            .copied()
+    }
+
+    /// Schedules ordered by next fire time (paused schedules last).
+    pub fn schedule_list(&self) -> Vec<&Schedule> {
+        let mut schedules: Vec<_> = self.schedules.values().collect();
+        schedules.sort_by_key(|s| (s.next_fire_at.is_none(), s.next_fire_at, s.created_at));
+        schedules
+    }
+
+    pub fn get_selected_schedule(&self) -> Option<&Schedule> {
+        self.schedule_list()
+            .get(self.selected_schedule_index)
+            .copied()
+    }
+
+    pub fn get_selected_approval(&self) -> Option<&Approval> {
+        self.pending_approvals.get(self.selected_approval_index)
     }
 
     pub fn get_viewing_task(&self) -> Option<&TaskDisplayInfo> {
@@ -242,6 +597,16 @@ impl ServerUiState {
             .as_ref()
             .and_then(|id| self.tasks.get(id))
     }
+
+    /// The most recent attachment in the chat of the viewed task's latest run.
+    pub fn latest_viewing_attachment(&self) -> Option<&Artifact> {
+        let run_id = self.get_viewing_task()?.latest_run_id.as_ref()?;
+        self.run_chat
+            .get(run_id)?
+            .iter()
+            .rev()
+            .find_map(|entry| entry.attachments.last())
+    }
 }
 
 impl Default for ServerUiState {
@@ -249,3 +614,58 @@ impl Default for ServerUiState {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn worker(
+        labels: &[(&str, &str)],
+        status: WorkerStatus,
+        active_runs: u32,
+    ) -> WorkerDisplayInfo {
+        WorkerDisplayInfo {
+            worker_id: WorkerId::generate(),
+            hostname: "host".to_string(),
+            agents: vec!["general".to_string()],
+            labels: labels
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+            status,
+            active_runs,
+            max_concurrent_runs: 4,
+            connected_at: Utc::now(),
+            last_heartbeat: Utc::now(),
+            metrics: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_fleet_groups_by_label_value() {
+        let mut state = ServerUiState::new();
+        for w in [
+            worker(&[("region", "us"), ("gpu", "a100")], WorkerStatus::Busy, 3),
+            worker(&[("region", "eu")], WorkerStatus::Idle, 0),
+            worker(&[("region", "us")], WorkerStatus::Degraded, 1),
+            worker(&[], WorkerStatus::Offline, 0),
+        ] {
+            state.workers.insert(w.worker_id.clone(), w);
+        }
+
+        assert_eq!(state.fleet_label_keys(), vec!["gpu", "region"]);
+        assert_eq!(state.fleet_key(), Some("gpu"));
+        state.cycle_fleet_label_key();
+        assert_eq!(state.fleet_key(), Some("region"));
+
+        let groups = state.fleet_groups();
+        let names: Vec<&str> = groups.iter().map(|g| g.name.as_str()).collect();
+        assert_eq!(names, vec!["eu", "us", UNLABELED_GROUP]);
+        assert_eq!((groups[1].active_runs(), groups[1].max_runs()), (4, 8));
+        assert_eq!(groups[1].unhealthy(), 1);
+        assert_eq!(groups[2].unhealthy(), 1);
+
+        state.fleet_group = Some("us".to_string());
+        assert_eq!(state.fleet_group_workers().len(), 2);
+    }
+}
@@ -0,0 +1,59 @@
+//! Approvals inbox view.
+
+use chrono::Utc;
+use ratatui::layout::Rect;
+use ratatui::style::Color;
+use ratatui::Frame;
+
+use taskrun_tui_components::{format_duration, DataTable, TableCell, TableColumn, TableRow};
+
+use crate::state::ServerUiState;
+
+pub fn render_approvals_view(f: &mut Frame, state: &ServerUiState, area: Rect) {
+    let now = Utc::now();
+
+    let columns = vec![
+        TableColumn::new("Approval", 10),
+        TableColumn::new("Run", 10),
+        TableColumn::new("Agent", 16),
+        TableColumn::new("Tool", 12),
+        TableColumn::new("Waiting", 9),
+        TableColumn::new("Expires", 9),
+        TableColumn::flex("Input", 20),
+    ];
+
+    // Oldest first, like the queue
+    let rows: Vec<TableRow> = state
+        .pending_approvals
+        .iter()
+        .map(|a| {
+            let waiting = (now - a.requested_at).num_seconds().max(0);
+            let remaining = (a.expires_at - now).num_seconds().max(0);
+            let expires_color = if remaining < 30 {
+                Color::Red
+            } else {
+                Color::Yellow
+            };
+            let input: String = a.input_json.chars().take(120).collect();
+
+            TableRow::new(vec![
+                TableCell::new(short(&a.id)),
+                TableCell::new(short(a.run_id.as_str())),
+                TableCell::new(a.agent_name.clone()),
+                TableCell::new(a.tool_name.clone()).color(Color::Yellow),
+                TableCell::new(format_duration(waiting)),
+                TableCell::new(format_duration(remaining)).color(expires_color),
+                TableCell::muted(input),
+            ])
+        })
+        .collect();
+
+    DataTable::new(&columns, &rows)
+        .title(format!(" Approvals ({}) ", state.pending_approvals.len()))
+        .selected(state.selected_approval_index)
+        .render(f, area);
+}
+
+fn short(id: &str) -> String {
+    id.chars().take(8).collect()
+}
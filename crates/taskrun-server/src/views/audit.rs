@@ -0,0 +1,66 @@
+//! Audit log view.
+
+use ratatui::layout::Rect;
+use ratatui::style::Color;
+use ratatui::Frame;
+
+use taskrun_tui_components::{DataTable, TableCell, TableColumn, TableRow};
+
+use crate::control_plane::audit::AuditAction;
+use crate::state::ServerUiState;
+
+pub fn render_audit_view(f: &mut Frame, state: &ServerUiState, area: Rect) {
+    let columns = vec![
+        TableColumn::new("Seq", 6),
+        TableColumn::new("Time", 23),
+        TableColumn::new("Actor", 24),
+        TableColumn::new("Client", 16),
+        TableColumn::new("Action", 20),
+        TableColumn::new("Target", 14),
+        TableColumn::flex("Digest", 12),
+    ];
+
+    // Newest first
+    let rows: Vec<TableRow> = state
+        .audit_records
+        .iter()
+        .rev()
+        .map(|r| {
+            TableRow::new(vec![
+                TableCell::muted(r.seq.to_string()),
+                TableCell::new(state.time_format.date_time(r.timestamp)),
+                TableCell::new(r.actor.clone()),
+                TableCell::muted(r.client_ip.clone().unwrap_or_else(|| "-".to_string())),
+                TableCell::new(r.action.as_str()).color(action_color(r.action)),
+                TableCell::new(r.target.chars().take(12).collect::<String>()),
+                TableCell::muted(r.payload_digest.chars().take(12).collect::<String>()),
+            ])
+        })
+        .collect();
+
+    DataTable::new(&columns, &rows)
+        .title(format!(" Audit ({}) ", state.audit_records.len()))
+        .selected(state.selected_audit_index)
+        .render(f, area);
+}
+
+fn action_color(action: AuditAction) -> Color {
+    match action {
+        AuditAction::TaskCancelled
+        | AuditAction::WorkerDisconnected
+        | AuditAction::ScheduleDeleted
+        | AuditAction::ApiKeyRevoked
+        | AuditAction::ToolDenied
+        | AuditAction::FileDeleted => Color::Red,
+        AuditAction::TaskRetried
+        | AuditAction::SchedulePaused
+        | AuditAction::ScheduleResumed
+        | AuditAction::WorkerDrained
+        | AuditAction::WorkerUndrained
+        | AuditAction::StateImported
+        | AuditAction::ConfigReloaded
+        | AuditAction::TogglesUpdated
+        | AuditAction::ChaosUpdated => Color::Yellow,
+        _ => Color::Green,
+    }
+}
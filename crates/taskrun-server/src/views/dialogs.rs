@@ -8,7 +8,9 @@ use ratatui::Frame;
 
 use taskrun_tui_components::{centered_rect, ConfirmDialog};
 
-use crate::state::ServerUiState;
+use crate::control_plane::approvals::Approval;
+use crate::control_plane::bulk::BulkOperation;
+use crate::state::{BulkConfirm, ServerUiState};
 
 /// Render the quit confirmation dialog.
 pub fn render_quit_confirm(f: &mut Frame) {
@@ -17,7 +19,8 @@ pub fn render_quit_confirm(f: &mut Frame) {
 
 /// Render the new task dialog.
 pub fn render_new_task_dialog(f: &mut Frame, state: &ServerUiState) {
-    let area = centered_rect(60, 12, f.area());
+    let findings = state.new_task_validation.len() as u16;
+    let area = centered_rect(60, 12 + findings, f.area());
 
     f.render_widget(Clear, area);
 
@@ -25,15 +28,16 @@ pub fn render_new_task_dialog(f: &mut Frame, state: &ServerUiState) {
         .direction(Direction::Vertical)
         .margin(1)
         .constraints([
-            Constraint::Length(1), // Title
-            Constraint::Length(1), // Spacing
-            Constraint::Length(1), // Agent label
-            Constraint::Length(1), // Agent input
-            Constraint::Length(1), // Spacing
-            Constraint::Length(1), // Input label
-            Constraint::Length(1), // Input field
-            Constraint::Length(1), // Spacing
-            Constraint::Length(1), // Help
+            Constraint::Length(1),        // Title
+            Constraint::Length(1),        // Spacing
+            Constraint::Length(1),        // Agent label
+            Constraint::Length(1),        // Agent input
+            Constraint::Length(1),        // Spacing
+            Constraint::Length(1),        // Input label
+            Constraint::Length(1),        // Input field
+            Constraint::Length(findings), // Validation findings
+            Constraint::Length(1),        // Spacing
+            Constraint::Length(1),        // Help
         ])
         .split(area);
 
@@ -82,6 +86,14 @@ pub fn render_new_task_dialog(f: &mut Frame, state: &ServerUiState) {
     );
     f.render_widget(input_value, chunks[6]);
 
+    // Validation findings (errors block Enter)
+    let lines: Vec<Line> = state
+        .new_task_validation
+        .iter()
+        .map(|m| m.to_line())
+        .collect();
+    f.render_widget(Paragraph::new(lines), chunks[7]);
+
     // Help
     let help = Paragraph::new(Line::from(vec![
         Span::styled("Tab", Style::default().fg(Color::Yellow)),
@@ -92,7 +104,7 @@ pub fn render_new_task_dialog(f: &mut Frame, state: &ServerUiState) {
         Span::raw(": Cancel"),
     ]))
     .alignment(Alignment::Center);
-    f.render_widget(help, chunks[8]);
+    f.render_widget(help, chunks[9]);
 }
 
 fn render_input_field(value: &str, focused: bool, cursor: usize) -> Paragraph<'static> {
@@ -118,6 +130,240 @@ fn render_input_field(value: &str, focused: bool, cursor: usize) -> Paragraph<'s
     Paragraph::new(display_value).style(style)
 }
 
+/// Render the task search prompt.
+pub fn render_search_input(f: &mut Frame, input: &str) {
+    let area = centered_rect(60, 6, f.area());
+
+    f.render_widget(Clear, area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints([
+            Constraint::Length(1), // Input field
+            Constraint::Length(1), // Spacing
+            Constraint::Length(1), // Help
+        ])
+        .split(area);
+
+    let block = Block::default()
+        .title(" Search Tasks ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan));
+    f.render_widget(block, area);
+
+    let field = render_input_field(input, true, input.chars().count());
+    f.render_widget(field, chunks[0]);
+
+    let help = Paragraph::new(Line::from(vec![
+        Span::styled("Enter", Style::default().fg(Color::Green)),
+        Span::raw(": Search (empty clears)  "),
+        Span::styled("Esc", Style::default().fg(Color::Red)),
+        Span::raw(": Cancel"),
+    ]))
+    .alignment(Alignment::Center);
+    f.render_widget(help, chunks[2]);
+}
+
+/// Render the oldest pending tool approval.
+pub fn render_approval_dialog(f: &mut Frame, approval: &Approval, pending: usize) {
+    let area = centered_rect(70, 12, f.area());
+
+    f.render_widget(Clear, area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints([
+            Constraint::Length(1), // Tool
+            Constraint::Length(1), // Run / worker
+            Constraint::Length(1), // Spacing
+            Constraint::Length(3), // Input
+            Constraint::Length(1), // Expiry
+            Constraint::Length(1), // Spacing
+            Constraint::Length(1), // Help
+        ])
+        .split(area);
+
+    let title = if pending > 1 {
+        format!(" Tool Approval (1 of {}) ", pending)
+    } else {
+        " Tool Approval ".to_string()
+    };
+    let block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Yellow));
+    f.render_widget(block, area);
+
+    let tool = Paragraph::new(Line::from(vec![
+        Span::raw("Tool: "),
+        Span::styled(
+            approval.tool_name.clone(),
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        ),
+    ]));
+    f.render_widget(tool, chunks[0]);
+
+    let run_id = approval.run_id.as_str();
+    let worker_id = approval.worker_id.as_str();
+    let origin = Paragraph::new(format!(
+        "Run: {}  Worker: {}",
+        &run_id[..8.min(run_id.len())],
+        &worker_id[..8.min(worker_id.len())]
+    ))
+    .style(Style::default().fg(Color::DarkGray));
+    f.render_widget(origin, chunks[1]);
+
+    let input: String = approval.input_json.chars().take(200).collect();
+    let input = Paragraph::new(input).wrap(ratatui::widgets::Wrap { trim: false });
+    f.render_widget(input, chunks[3]);
+
+    let remaining = (approval.expires_at - chrono::Utc::now())
+        .num_seconds()
+        .max(0);
+    let default = if approval.default_allow {
+        "allowed"
+    } else {
+        "denied"
+    };
+    let expiry = Paragraph::new(format!(
+        "Expires in {}s, then {} by default",
+        remaining, default
+    ))
+    .style(Style::default().fg(Color::DarkGray));
+    f.render_widget(expiry, chunks[4]);
+
+    let help = Paragraph::new(Line::from(vec![
+        Span::styled("y", Style::default().fg(Color::Green)),
+        Span::raw(": Approve | "),
+        Span::styled("n", Style::default().fg(Color::Red)),
+        Span::raw(": Deny | "),
+        Span::styled("A", Style::default().fg(Color::Green)),
+        Span::raw(": Always allow | "),
+        Span::styled("Esc", Style::default().fg(Color::Yellow)),
+        Span::raw(": Later"),
+    ]))
+    .alignment(Alignment::Center);
+    f.render_widget(help, chunks[6]);
+}
+
+/// Render the settings dialog (runtime toggles).
+pub fn render_settings_dialog(f: &mut Frame, state: &ServerUiState) {
+    let area = centered_rect(64, 11, f.area());
+
+    f.render_widget(Clear, area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints([
+            Constraint::Length(1), // Maintenance
+            Constraint::Length(1), // Scheduling
+            Constraint::Length(1), // Default priority
+            Constraint::Length(1), // Spacing
+            Constraint::Length(2), // Description
+            Constraint::Length(1), // Spacing
+            Constraint::Length(1), // Help
+        ])
+        .split(area);
+
+    let block = Block::default()
+        .title(" Settings ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan));
+    f.render_widget(block, area);
+
+    let toggles = state.toggles;
+    let on_off = |on: bool| {
+        if on {
+            ("on", Color::Red)
+        } else {
+            ("off", Color::Green)
+        }
+    };
+    let priority = if toggles.default_priority.is_interactive() {
+        ("interactive", Color::Yellow)
+    } else {
+        ("batch", Color::Green)
+    };
+    let rows = [
+        (
+            "Maintenance mode",
+            on_off(toggles.maintenance),
+            "New tasks are refused; queued and running work continues.",
+        ),
+        (
+            "Scheduling frozen",
+            on_off(toggles.scheduling_frozen),
+            "Queued tasks stay pending instead of going to workers.",
+        ),
+        (
+            "Default priority",
+            priority,
+            "Lane of tasks created without a priority.",
+        ),
+    ];
+
+    for (i, (label, (value, color), _)) in rows.iter().enumerate() {
+        let selected = i == state.selected_setting_index;
+        let label_style = if selected {
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default()
+        };
+        let line = Line::from(vec![
+            Span::styled(if selected { "> " } else { "  " }, label_style),
+            Span::styled(format!("{:<20}", label), label_style),
+            Span::styled(value.to_string(), Style::default().fg(*color)),
+        ]);
+        f.render_widget(Paragraph::new(line), chunks[i]);
+    }
+
+    let description = rows
+        .get(state.selected_setting_index)
+        .map_or("", |(_, _, description)| *description);
+    let description = Paragraph::new(description)
+        .style(Style::default().fg(Color::DarkGray))
+        .wrap(ratatui::widgets::Wrap { trim: true });
+    f.render_widget(description, chunks[4]);
+
+    let help = Paragraph::new(Line::from(vec![
+        Span::styled("j/k", Style::default().fg(Color::Yellow)),
+        Span::raw(": Navigate  "),
+        Span::styled("Space", Style::default().fg(Color::Green)),
+        Span::raw(": Change  "),
+        Span::styled("Esc", Style::default().fg(Color::Red)),
+        Span::raw(": Close"),
+    ]))
+    .alignment(Alignment::Center);
+    f.render_widget(help, chunks[6]);
+}
+
+/// Render a toast in the top-right corner, below the header.
+pub fn render_toast(f: &mut Frame, message: &str) {
+    let screen = f.area();
+    let width = (message.chars().count() as u16 + 4).min(screen.width);
+    let area = ratatui::layout::Rect {
+        x: screen.width - width,
+        y: 3.min(screen.height),
+        width,
+        height: 3.min(screen.height.saturating_sub(3)),
+    };
+
+    f.render_widget(Clear, area);
+    let toast = Paragraph::new(message).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Yellow)),
+    );
+    f.render_widget(toast, area);
+}
+
 /// Render the cancel task confirmation dialog.
 pub fn render_cancel_confirm(f: &mut Frame, state: &ServerUiState) {
     let task_id = state
@@ -146,3 +392,31 @@ pub fn render_disconnect_confirm(f: &mut Frame, state: &ServerUiState) {
     .size(50, 9)
     .render(f);
 }
+
+/// Render the bulk cancel/retry confirmation dialog.
+pub fn render_bulk_confirm(f: &mut Frame, bulk: &BulkConfirm) {
+    let (title, verb, secondary) = match bulk.op {
+        BulkOperation::Cancel => (
+            "Bulk Cancel",
+            "Cancel",
+            "Running executions will be stopped.",
+        ),
+        BulkOperation::Retry => ("Bulk Retry", "Retry", "Each task gets a new run."),
+    };
+
+    let mut scope = Vec::new();
+    if let Some(status) = bulk.filter.status {
+        scope.push(format!("{:?}", status).to_lowercase());
+    }
+    if let Some(agent) = &bulk.filter.agent_name {
+        scope.push(agent.clone());
+    }
+
+    ConfirmDialog::new(
+        title,
+        &format!("{} {} {} task(s)?", verb, bulk.task_count, scope.join(" ")),
+    )
+    .secondary(secondary)
+    .size(56, 9)
+    .render(f);
+}
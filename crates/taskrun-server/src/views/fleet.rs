@@ -0,0 +1,104 @@
+//! Fleet view: workers grouped by a label.
+
+use ratatui::layout::Rect;
+use ratatui::style::Color;
+use ratatui::Frame;
+
+use taskrun_core::WorkerStatus;
+use taskrun_tui_components::{DataTable, TableCell, TableColumn, TableRow};
+
+use super::workers::render_worker_table;
+use crate::state::{FleetGroup, ServerUiState};
+
+/// Width of the capacity bars, in cells.
+const BAR_WIDTH: u32 = 20;
+
+pub fn render_fleet_view(f: &mut Frame, state: &ServerUiState, area: Rect) {
+    let key = state.fleet_key().unwrap_or("-");
+
+    if let Some(group) = &state.fleet_group {
+        let workers = state.fleet_group_workers();
+        render_worker_table(
+            f,
+            &workers,
+            format!(" Fleet: {}={} ({}) ", key, group, workers.len()),
+            state.selected_fleet_worker_index,
+            area,
+        );
+        return;
+    }
+
+    let columns = vec![
+        TableColumn::new(key.to_string(), 20),
+        TableColumn::new("Workers", 8),
+        TableColumn::flex("Capacity", BAR_WIDTH as u16 + 10),
+        TableColumn::new("Idle", 6),
+        TableColumn::new("Busy", 6),
+        TableColumn::new("Draining", 9),
+        TableColumn::new("Unhealthy", 10),
+    ];
+
+    let groups = state.fleet_groups();
+    let rows: Vec<TableRow> = groups
+        .iter()
+        .map(|g| {
+            // Red when workers are down, light red when only degraded
+            let health_color = if g.count(WorkerStatus::Offline) + g.count(WorkerStatus::Error) > 0
+            {
+                Some(Color::Red)
+            } else if g.unhealthy() > 0 {
+                Some(Color::LightRed)
+            } else {
+                None
+            };
+            let (bar, bar_color) = capacity_bar(g);
+            let highlight = |cell: TableCell| match health_color {
+                Some(color) => cell.color(color),
+                None => cell,
+            };
+
+            TableRow::new(vec![
+                highlight(TableCell::new(g.name.clone())),
+                TableCell::new(g.workers.len().to_string()),
+                TableCell::new(bar).color(bar_color),
+                TableCell::muted(g.count(WorkerStatus::Idle).to_string()),
+                TableCell::new(g.count(WorkerStatus::Busy).to_string()),
+                TableCell::new(g.count(WorkerStatus::Draining).to_string()),
+                highlight(TableCell::new(g.unhealthy().to_string())),
+            ])
+        })
+        .collect();
+
+    DataTable::new(&columns, &rows)
+        .title(format!(
+            " Fleet by {} ({} groups, {} workers) ",
+            key,
+            groups.len(),
+            state.workers.len()
+        ))
+        .selected(state.selected_fleet_group_index)
+        .render(f, area);
+}
+
+/// Bar of the group's busy run slots, with its color: yellow when nearly
+/// full, red when the group has no slots left.
+fn capacity_bar(group: &FleetGroup) -> (String, Color) {
+    let active = group.active_runs();
+    let max = group.max_runs();
+    let filled = (active.min(max) * BAR_WIDTH).checked_div(max).unwrap_or(0) as usize;
+    let bar = format!(
+        "{}{} {}/{}",
+        "█".repeat(filled),
+        "░".repeat(BAR_WIDTH as usize - filled),
+        active,
+        max
+    );
+    let color = if max == 0 || active >= max {
+        Color::Red
+    } else if active * 10 >= max * 8 {
+        Color::Yellow
+    } else {
+        Color::Cyan
+    };
+    (bar, color)
+}
@@ -13,5 +13,6 @@ pub fn render_logs_view(f: &mut Frame, state: &ServerUiState, area: Rect) {
 
     LogsWidget::new(&entries)
         .scroll(state.log_scroll)
+        .time_format(state.time_format)
         .render(f, area);
 }
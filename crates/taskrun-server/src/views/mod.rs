@@ -1,12 +1,22 @@
 //! View implementations.
 
+mod approvals;
+mod audit;
 pub mod dialogs;
+mod fleet;
 mod logs;
+mod queue;
 mod run_detail;
+mod schedules;
 mod tasks;
 mod workers;
 
+pub use approvals::render_approvals_view;
+pub use audit::render_audit_view;
+pub use fleet::render_fleet_view;
 pub use logs::render_logs_view;
+pub use queue::render_queue_view;
 pub use run_detail::render_run_detail_view;
+pub use schedules::render_schedules_view;
 pub use tasks::render_tasks_view;
 pub use workers::render_workers_view;
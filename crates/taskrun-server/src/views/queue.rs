@@ -0,0 +1,83 @@
+//! Queue view: pending tasks in dispatch order and why they wait.
+
+use chrono::Utc;
+use ratatui::layout::Rect;
+use ratatui::style::Color;
+use ratatui::Frame;
+
+use taskrun_core::TaskPriority;
+use taskrun_tui_components::{format_duration, DataTable, TableCell, TableColumn, TableRow};
+
+use crate::control_plane::queue::BlockReason;
+use crate::state::ServerUiState;
+
+pub fn render_queue_view(f: &mut Frame, state: &ServerUiState, area: Rect) {
+    let now = Utc::now();
+
+    let columns = vec![
+        TableColumn::new("#", 4),
+        TableColumn::new("Task", 10),
+        TableColumn::new("Agent", 16),
+        TableColumn::new("Namespace", 12),
+        TableColumn::new("Priority", 12),
+        TableColumn::new("Waiting", 9),
+        TableColumn::new("ETA", 9),
+        TableColumn::flex("Blocked by", 20),
+    ];
+
+    let rows: Vec<TableRow> = state
+        .queue
+        .iter()
+        .map(|e| {
+            let waiting = (now - e.queued_at).num_seconds().max(0);
+            let eta = e
+                .queue
+                .eta_secs
+                .map_or_else(|| "-".to_string(), |eta| format_duration(eta as i64));
+            let priority = match e.priority {
+                TaskPriority::Interactive => TableCell::new("interactive").color(Color::Cyan),
+                TaskPriority::Batch => TableCell::muted("batch"),
+            };
+            let blocked_by = match e.blocked_by {
+                Some(reason) => TableCell::new(reason.as_str()).color(block_color(reason)),
+                None => TableCell::muted("next dispatch"),
+            };
+
+            TableRow::new(vec![
+                TableCell::new(e.queue.position.to_string()),
+                TableCell::new(e.task_id.as_str().chars().take(8).collect::<String>()),
+                TableCell::new(e.agent_name.clone()),
+                TableCell::new(e.namespace.clone()),
+                priority,
+                TableCell::new(format_duration(waiting)),
+                TableCell::new(eta),
+                blocked_by,
+            ])
+        })
+        .collect();
+
+    let oldest = state
+        .queue
+        .iter()
+        .map(|e| e.queued_at)
+        .min()
+        .map(|queued_at| format_duration((now - queued_at).num_seconds().max(0)));
+    let title = match oldest {
+        Some(oldest) => format!(" Queue ({}, oldest waiting {}) ", state.queue.len(), oldest),
+        None => " Queue (empty) ".to_string(),
+    };
+
+    DataTable::new(&columns, &rows)
+        .title(title)
+        .selected(state.selected_queue_index)
+        .render(f, area);
+}
+
+/// Red when nothing can take the task, yellow when it waits its turn.
+fn block_color(reason: BlockReason) -> Color {
+    match reason {
+        BlockReason::AgentMissing | BlockReason::WorkersUnavailable => Color::Red,
+        BlockReason::NoCapacity | BlockReason::QuotaExceeded => Color::Yellow,
+        BlockReason::SchedulingFrozen => Color::Cyan,
+    }
+}
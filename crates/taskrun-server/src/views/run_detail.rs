@@ -5,9 +5,12 @@ use ratatui::style::{Color, Style};
 use ratatui::widgets::{Block, Borders, Paragraph};
 use ratatui::Frame;
 
-use taskrun_core::{ChatRole, RunEventType, RunStatus, TaskStatus};
+use taskrun_core::{
+    Artifact, ChatRole, RunEventType, RunMilestone, RunResultSummary, RunStatus, TaskStatus,
+};
 use taskrun_tui_components::{
-    DetailPane, MessageRole, RunDetailInfo, RunDetailStatus, RunDetailView, RunEvent, RunMessage,
+    DetailPane, MessageRole, RunDetailInfo, RunDetailMilestone, RunDetailStatus, RunDetailView,
+    RunEvent, RunMessage,
 };
 
 use crate::state::ServerUiState;
@@ -47,6 +50,8 @@ pub fn render_run_detail_view(f: &mut Frame, state: &ServerUiState, area: Rect)
         .chat_scroll(state.run_scroll)
         .events_scroll(state.events_scroll)
         .input(&state.chat_input, state.chat_input_cursor)
+        .time_format(state.time_format)
+        .markdown(!state.raw_chat)
         .render(f, area);
 }
 
@@ -70,6 +75,7 @@ fn convert_to_run_detail(
                     },
                     content: msg.content.clone(),
                     timestamp: msg.timestamp,
+                    attachments: msg.attachments.iter().map(Artifact::marker).collect(),
                 })
                 .collect()
         })
@@ -90,28 +96,32 @@ fn convert_to_run_detail(
         })
         .unwrap_or_default();
 
+    let milestones: Vec<RunDetailMilestone> = state
+        .run_milestones
+        .get(run_id)
+        .map(|milestones| milestones.iter().map(milestone_to_detail).collect())
+        .unwrap_or_default();
+
     // Convert status from task/run status
     let status = match task.latest_run_status {
         Some(RunStatus::Running) => RunDetailStatus::Running,
         Some(RunStatus::Completed) => RunDetailStatus::Completed,
-        Some(RunStatus::Failed) | Some(RunStatus::Cancelled) => RunDetailStatus::Failed,
+        Some(RunStatus::Failed) => RunDetailStatus::Failed,
+        Some(RunStatus::Cancelled) => RunDetailStatus::Cancelled,
         Some(RunStatus::Pending) | Some(RunStatus::Assigned) | None => {
             // Map task status if no run status
             match task.status {
                 TaskStatus::Running => RunDetailStatus::Running,
                 TaskStatus::Completed => RunDetailStatus::Completed,
-                TaskStatus::Failed | TaskStatus::Cancelled => RunDetailStatus::Failed,
+                TaskStatus::Failed => RunDetailStatus::Failed,
+                TaskStatus::Cancelled => RunDetailStatus::Cancelled,
                 TaskStatus::Pending => RunDetailStatus::Running,
             }
         }
     };
 
     // Get streaming output if any
-    let current_output = state
-        .run_output
-        .get(run_id)
-        .cloned()
-        .unwrap_or_default();
+    let current_output = state.run_output.get(run_id).cloned().unwrap_or_default();
 
     RunDetailInfo {
         run_id: run_id.to_string(),
@@ -124,6 +134,20 @@ fn convert_to_run_detail(
         events,
         current_output,
         queued_input: None, // Server doesn't queue inputs
+        created_by: Some(match &task.client_ip {
+            Some(ip) => format!("{} from {}", task.created_by, ip),
+            None => task.created_by.clone(),
+        }),
+        milestones,
+        result: state.run_results.get(run_id).map(RunResultSummary::preview),
+    }
+}
+
+fn milestone_to_detail(milestone: &RunMilestone) -> RunDetailMilestone {
+    RunDetailMilestone {
+        name: milestone.name.clone(),
+        percent: milestone.percent,
+        note: milestone.note.clone(),
     }
 }
 
@@ -137,5 +161,8 @@ fn event_type_to_string(event_type: &RunEventType) -> String {
         RunEventType::OutputGenerated => "Output Generated".to_string(),
         RunEventType::ExecutionCompleted => "Execution Completed".to_string(),
         RunEventType::ExecutionFailed => "Execution Failed".to_string(),
+        RunEventType::Milestone => "Milestone".to_string(),
+        RunEventType::Log => "Log".to_string(),
+        RunEventType::RunSummary => "Run Summary".to_string(),
     }
 }
@@ -0,0 +1,66 @@
+//! Schedules view.
+
+use chrono::{DateTime, Utc};
+use ratatui::layout::Rect;
+use ratatui::style::Color;
+use ratatui::Frame;
+
+use taskrun_tui_components::{format_relative, DataTable, TableCell, TableColumn, TableRow};
+
+use crate::state::ServerUiState;
+
+pub fn render_schedules_view(f: &mut Frame, state: &ServerUiState, area: Rect) {
+    let schedules = state.schedule_list();
+    let now = Utc::now();
+
+    let columns = vec![
+        TableColumn::new("Schedule ID", 12),
+        TableColumn::new("Cron", 16),
+        TableColumn::new("Agent", 20),
+        TableColumn::new("Status", 8),
+        TableColumn::new("Next Fire", 22),
+        TableColumn::flex("Last Fired", 12),
+    ];
+
+    let rows: Vec<TableRow> = schedules
+        .iter()
+        .map(|s| {
+            let (status_str, status_color) = if s.paused {
+                ("Paused", Color::DarkGray)
+            } else {
+                ("Active", Color::Green)
+            };
+
+            let next_str = match s.next_fire_at {
+                Some(at) if state.time_format.relative => format_relative(at, now),
+                Some(at) => format!(
+                    "{} ({})",
+                    state.time_format.time_at(at, now),
+                    format_relative(at, now)
+                ),
+                None => "-".to_string(),
+            };
+
+            TableRow::new(vec![
+                TableCell::new(s.id.to_string()[..8].to_string()),
+                TableCell::new(s.cron_expr.clone()),
+                TableCell::new(s.agent_name.clone()),
+                TableCell::new(status_str).color(status_color),
+                TableCell::new(next_str),
+                TableCell::muted(last_fired(s.last_fired_at, now)),
+            ])
+        })
+        .collect();
+
+    DataTable::new(&columns, &rows)
+        .title(format!(" Schedules ({}) ", state.schedules.len()))
+        .selected(state.selected_schedule_index)
+        .render(f, area);
+}
+
+fn last_fired(at: Option<DateTime<Utc>>, now: DateTime<Utc>) -> String {
+    match at {
+        Some(at) => format_relative(at, now),
+        None => "never".to_string(),
+    }
+}
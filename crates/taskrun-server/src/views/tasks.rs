@@ -1,24 +1,41 @@
 //! Tasks view.
 
-use ratatui::layout::Rect;
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
 use ratatui::style::Color;
 use ratatui::Frame;
 
 use taskrun_core::TaskStatus;
-use taskrun_tui_components::{DataTable, TableCell, TableColumn, TableRow};
+use taskrun_tui_components::{
+    format_duration, DataTable, GroupProgressList, TableCell, TableColumn, TableRow,
+};
 
 use crate::state::ServerUiState;
 
 pub fn render_tasks_view(f: &mut Frame, state: &ServerUiState, area: Rect) {
+    // Group bars above the table, while any group is active
+    let area = if state.groups.is_empty() {
+        area
+    } else {
+        let groups = GroupProgressList::new(&state.groups);
+        let height = groups.height().min(area.height / 3);
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(height), Constraint::Min(0)])
+            .split(area);
+        groups.render(f, chunks[0]);
+        chunks[1]
+    };
+
     let tasks = state.task_display_list();
 
     let columns = vec![
         TableColumn::new("Task ID", 10),
         TableColumn::new("Agent", 20),
-        TableColumn::new("Status", 12),
+        TableColumn::new("Status", 20),
         TableColumn::new("Created", 12),
         TableColumn::new("Runs", 6),
-        TableColumn::flex("Latest Run", 12),
+        TableColumn::new("Latest Run", 12),
+        TableColumn::flex("Result", 20),
     ];
 
     let rows: Vec<TableRow> = tasks
@@ -32,15 +49,19 @@ pub fn render_tasks_view(f: &mut Frame, state: &ServerUiState, area: Rect) {
                 TaskStatus::Cancelled => Color::DarkGray,
             };
 
-            let created_ago = chrono::Utc::now()
-                .signed_duration_since(t.created_at)
-                .num_seconds();
-            let created_str = if created_ago < 60 {
-                format!("{}s ago", created_ago)
-            } else if created_ago < 3600 {
-                format!("{}m ago", created_ago / 60)
-            } else {
-                format!("{}h ago", created_ago / 3600)
+            let created_str = state.time_format.time(t.created_at);
+
+            // Queued tasks show where they stand: "Pending #2 ~1m 30s"
+            let status_str = match state.queue_positions.get(&t.task_id) {
+                Some(queue) if t.status == TaskStatus::Pending => match queue.eta_secs {
+                    Some(eta) => format!(
+                        "Pending #{} ~{}",
+                        queue.position,
+                        format_duration(eta as i64)
+                    ),
+                    None => format!("Pending #{}", queue.position),
+                },
+                _ => format!("{:?}", t.status),
             };
 
             let latest_run_str = match &t.latest_run_status {
@@ -48,19 +69,30 @@ pub fn render_tasks_view(f: &mut Frame, state: &ServerUiState, area: Rect) {
                 None => "-".to_string(),
             };
 
+            // What the latest run produced, once its worker reported it
+            let result_str = t
+                .latest_run_id
+                .as_ref()
+                .and_then(|run_id| state.run_results.get(run_id))
+                .map_or_else(|| "-".to_string(), |result| result.preview());
+
             TableRow::new(vec![
                 TableCell::new(t.task_id.to_string()[..8].to_string()),
                 TableCell::new(t.agent_name.clone()),
-                TableCell::new(format!("{:?}", t.status)).color(status_color),
+                TableCell::new(status_str).color(status_color),
                 TableCell::muted(created_str),
                 TableCell::new(format!("{}", t.run_count)),
                 TableCell::new(latest_run_str),
+                TableCell::muted(result_str),
             ])
         })
         .collect();
 
     DataTable::new(&columns, &rows)
-        .title(format!(" Tasks ({}) ", state.task_list.len()))
+        .title(match &state.task_search {
+            Some(search) => format!(" Tasks matching '{}' ({}) ", search.query, tasks.len()),
+            None => format!(" Tasks ({}) ", state.task_list.len()),
+        })
         .selected(state.selected_task_index)
         .render(f, area);
 }
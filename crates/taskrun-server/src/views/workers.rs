@@ -1,17 +1,118 @@
-//! Workers view.
+//! Workers view, with the selected worker's heartbeat metrics below the
+//! table.
 
-use ratatui::layout::Rect;
-use ratatui::style::Color;
+use std::collections::HashMap;
+
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::style::{Color, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Paragraph};
 use ratatui::Frame;
 
+use taskrun_core::artifact::format_size;
 use taskrun_core::WorkerStatus;
-use taskrun_tui_components::{DataTable, TableCell, TableColumn, TableRow};
+use taskrun_tui_components::{format_duration, DataTable, TableCell, TableColumn, TableRow};
+
+use crate::state::{ServerUiState, WorkerDisplayInfo};
 
-use crate::state::ServerUiState;
+/// Height of the worker detail pane, borders included.
+const DETAIL_HEIGHT: u16 = 6;
 
 pub fn render_workers_view(f: &mut Frame, state: &ServerUiState, area: Rect) {
-    let workers = state.worker_list();
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(DETAIL_HEIGHT)])
+        .split(area);
+
+    render_worker_table(
+        f,
+        &state.worker_list(),
+        format!(" Workers ({}) ", state.workers.len()),
+        state.selected_worker_index,
+        chunks[0],
+    );
+    render_worker_detail(f, state.get_selected_worker(), chunks[1]);
+}
+
+/// The selected worker's host and run metrics, from its last heartbeat.
+fn render_worker_detail(f: &mut Frame, worker: Option<&WorkerDisplayInfo>, area: Rect) {
+    let Some(worker) = worker else {
+        let empty = Paragraph::new("No worker selected")
+            .style(Style::default().fg(Color::DarkGray))
+            .block(Block::default().borders(Borders::ALL).title(" Worker "));
+        f.render_widget(empty, area);
+        return;
+    };
+
+    let lines = if worker.metrics.is_empty() {
+        vec![Line::styled(
+            "No metrics reported",
+            Style::default().fg(Color::DarkGray),
+        )]
+    } else {
+        metric_lines(&worker.metrics)
+    };
+    let detail = Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(format!(" Worker {} ", worker.worker_id)),
+    );
+    f.render_widget(detail, area);
+}
+
+/// Lines of the metrics a worker sent: host, then runs.
+fn metric_lines(metrics: &HashMap<String, String>) -> Vec<Line<'static>> {
+    let get = |key: &str| metrics.get(key).map(String::as_str);
+    let bytes = |key: &str| get(key).and_then(|v| v.parse().ok()).map(format_size);
+
+    let mut host = Vec::new();
+    if let Some(load) = get("load_1m") {
+        host.push(("Load", load.to_string()));
+    }
+    if let (Some(used), Some(total)) = (bytes("memory_used_bytes"), bytes("memory_total_bytes")) {
+        host.push(("Memory", format!("{} / {}", used, total)));
+    }
+    if let Some(free) = bytes("disk_free_bytes") {
+        host.push(("Disk free", free));
+    }
 
+    let mut runs = Vec::new();
+    if let (Some(completed), Some(failed)) = (get("runs_completed"), get("runs_failed")) {
+        runs.push(("Completed", completed.to_string()));
+        runs.push(("Failed", failed.to_string()));
+    }
+    if let Some(avg_ms) = get("avg_run_ms").and_then(|v| v.parse::<i64>().ok()) {
+        runs.push(("Avg run", format_duration(avg_ms / 1000)));
+    }
+
+    [host, runs]
+        .into_iter()
+        .filter(|fields| !fields.is_empty())
+        .map(|fields| {
+            let mut spans = Vec::new();
+            for (label, value) in fields {
+                if !spans.is_empty() {
+                    spans.push(Span::raw("   "));
+                }
+                spans.push(Span::styled(
+                    format!("{}: ", label),
+                    Style::default().fg(Color::DarkGray),
+                ));
+                spans.push(Span::raw(value));
+            }
+            Line::from(spans)
+        })
+        .collect()
+}
+
+/// Table of `workers`, as in the Workers view.
+pub(super) fn render_worker_table(
+    f: &mut Frame,
+    workers: &[&WorkerDisplayInfo],
+    title: String,
+    selected: usize,
+    area: Rect,
+) {
     let columns = vec![
         TableColumn::new("Worker ID", 10),
         TableColumn::new("Hostname", 20),
@@ -24,13 +125,6 @@ pub fn render_workers_view(f: &mut Frame, state: &ServerUiState, area: Rect) {
     let rows: Vec<TableRow> = workers
         .iter()
         .map(|w| {
-            let status_color = match w.status {
-                WorkerStatus::Idle => Color::Green,
-                WorkerStatus::Busy => Color::Yellow,
-                WorkerStatus::Draining => Color::Magenta,
-                WorkerStatus::Error => Color::Red,
-            };
-
             let agents_str = if w.agents.len() <= 2 {
                 w.agents.join(", ")
             } else {
@@ -50,7 +144,7 @@ pub fn render_workers_view(f: &mut Frame, state: &ServerUiState, area: Rect) {
                 TableCell::new(w.worker_id.to_string()[..8].to_string()),
                 TableCell::new(w.hostname.clone()),
                 TableCell::new(agents_str),
-                TableCell::new(format!("{:?}", w.status)).color(status_color),
+                TableCell::new(format!("{:?}", w.status)).color(status_color(w.status)),
                 TableCell::new(format!("{}/{}", w.active_runs, w.max_concurrent_runs)),
                 TableCell::muted(hb_str),
             ])
@@ -58,7 +152,18 @@ pub fn render_workers_view(f: &mut Frame, state: &ServerUiState, area: Rect) {
         .collect();
 
     DataTable::new(&columns, &rows)
-        .title(format!(" Workers ({}) ", state.workers.len()))
-        .selected(state.selected_worker_index)
+        .title(title)
+        .selected(selected)
         .render(f, area);
 }
+
+fn status_color(status: WorkerStatus) -> Color {
+    match status {
+        WorkerStatus::Idle => Color::Green,
+        WorkerStatus::Busy => Color::Yellow,
+        WorkerStatus::Draining => Color::Magenta,
+        WorkerStatus::Error => Color::Red,
+        WorkerStatus::Degraded => Color::LightRed,
+        WorkerStatus::Offline => Color::DarkGray,
+    }
+}
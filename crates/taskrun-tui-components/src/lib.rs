@@ -6,29 +6,41 @@
 //! # Architecture
 //!
 //! The crate is organized into:
-//! - `widgets` - Reusable ratatui widgets (header, footer, table, chat, events, logs, dialogs)
+//! - `widgets` - Reusable ratatui widgets (header, footer, table, chat, events, logs, dialogs,
+//!   group progress bars)
+//! - `markdown` - Markdown rendering for chat panes
 //! - `theme` - Colors, styles, and visual constants
-//! - `utils` - Text wrapping, formatting utilities
+//! - `time_format` - Timestamp display in a configured time zone and locale
+//! - `utils` - Text wrapping, formatting utilities, opening files externally
 //!
 //! # Usage
 //!
 //! Components are designed to be data-agnostic. Pass data through trait
 //! implementations or simple structs rather than depending on domain types.
 
+pub mod markdown;
 pub mod theme;
+pub mod time_format;
 pub mod utils;
 pub mod widgets;
 
+pub use markdown::markdown_lines;
 pub use theme::Theme;
-pub use utils::{format_duration, truncate, wrap_text, wrap_text_indented};
+pub use time_format::{format_relative, DisplayLocale, DisplayZone, TimeFormat, UnknownZone};
+pub use utils::{
+    format_duration, is_web_url, open_external, truncate, wrap_text, wrap_text_indented,
+};
 pub use widgets::chat::{ChatMessage, ChatRole, ChatWidget};
-pub use widgets::dialogs::{centered_rect, ConfirmDialog, InputDialog, InputField};
+pub use widgets::dialogs::{
+    centered_rect, ConfirmDialog, InputDialog, InputField, ValidationMessage,
+};
 pub use widgets::events::{EventInfo, EventsWidget};
 pub use widgets::footer::Footer;
 pub use widgets::header::{Header, HeaderStat, StatusIndicator};
 pub use widgets::logs::{LogEntry, LogLevel, LogsWidget};
+pub use widgets::progress::{GroupProgress, GroupProgressList};
 pub use widgets::run_detail::{
     DetailPane, MessageRole, RunDetailView, RunEvent, RunInfo as RunDetailInfo, RunMessage,
-    RunStatus as RunDetailStatus,
+    RunMilestone as RunDetailMilestone, RunStatus as RunDetailStatus,
 };
 pub use widgets::table::{DataTable, TableCell, TableColumn, TableRow};
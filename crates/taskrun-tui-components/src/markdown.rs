@@ -0,0 +1,517 @@
+//! Markdown rendering for chat panes.
+//!
+//! Turns an agent's markdown answer into styled, wrapped lines: headings,
+//! bold/italic/strikethrough, inline code, links, block quotes, (nested)
+//! lists, rules, and fenced code blocks with keyword highlighting for common
+//! languages. Line breaks in the source are kept, as agents lay out plain
+//! text (and tables) line by line.
+
+use pulldown_cmark::{CodeBlockKind, Event, HeadingLevel, Options, Parser, Tag, TagEnd};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use unicode_width::UnicodeWidthStr;
+
+/// Render `text` as markdown, wrapped to `width` and indented by `indent`.
+pub fn markdown_lines(text: &str, width: usize, indent: &str) -> Vec<Line<'static>> {
+    let options = Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TASKLISTS;
+    let mut renderer = Renderer {
+        width,
+        indent: indent.to_string(),
+        ..Renderer::default()
+    };
+    for event in Parser::new_ext(text, options) {
+        renderer.event(event);
+    }
+    renderer.flush();
+
+    let mut lines = renderer.lines;
+    while lines.len() > 1 && lines.last().is_some_and(|l| l.width() <= indent.len()) {
+        lines.pop();
+    }
+    if lines.is_empty() {
+        lines.push(Line::from(indent.to_string()));
+    }
+    lines
+}
+
+#[derive(Default)]
+struct Renderer {
+    width: usize,
+    indent: String,
+    lines: Vec<Line<'static>>,
+    /// Inline content of the current block.
+    spans: Vec<Span<'static>>,
+    /// Inline styles in effect, innermost last.
+    styles: Vec<Style>,
+    /// Open lists, with the next number of ordered ones.
+    lists: Vec<Option<u64>>,
+    /// Marker of the list item whose first line is not out yet.
+    item_marker: Option<String>,
+    quote_depth: usize,
+    /// Language and text of the code block being read.
+    code: Option<(String, String)>,
+    /// Destination of the link being read.
+    link: Option<String>,
+}
+
+impl Renderer {
+    fn event(&mut self, event: Event) {
+        if let Some((_, code)) = &mut self.code {
+            match event {
+                Event::Text(text) => code.push_str(&text),
+                Event::End(TagEnd::CodeBlock) => self.end_code_block(),
+                _ => {}
+            }
+            return;
+        }
+
+        match event {
+            Event::Start(tag) => self.start(tag),
+            Event::End(tag) => self.end(tag),
+            Event::Text(text) | Event::InlineHtml(text) => self.push_text(&text),
+            Event::Html(html) => {
+                self.push_text(html.trim_end_matches('\n'));
+                self.flush();
+            }
+            Event::Code(code) => self.spans.push(Span::styled(
+                code.to_string(),
+                self.style().fg(Color::Yellow),
+            )),
+            Event::SoftBreak | Event::HardBreak => self.flush(),
+            Event::Rule => {
+                self.flush();
+                let width = self.width.saturating_sub(self.prefix().width()).max(1);
+                self.lines.push(Line::from(vec![
+                    Span::raw(self.prefix()),
+                    Span::styled("─".repeat(width), Style::default().fg(Color::DarkGray)),
+                ]));
+                self.blank_line();
+            }
+            Event::TaskListMarker(done) => {
+                self.spans
+                    .push(Span::raw(if done { "[x] " } else { "[ ] " }));
+            }
+            Event::FootnoteReference(name) => self.push_text(&format!("[^{name}]")),
+            _ => {}
+        }
+    }
+
+    fn start(&mut self, tag: Tag) {
+        match tag {
+            Tag::Heading { level, .. } => {
+                self.flush();
+                let mut style = Style::default()
+                    .fg(Color::Cyan)
+                    .add_modifier(Modifier::BOLD);
+                if level == HeadingLevel::H1 {
+                    style = style.add_modifier(Modifier::UNDERLINED);
+                }
+                self.styles.push(style);
+            }
+            Tag::BlockQuote(_) => {
+                self.flush();
+                self.quote_depth += 1;
+            }
+            Tag::CodeBlock(kind) => {
+                self.flush();
+                let lang = match kind {
+                    CodeBlockKind::Fenced(info) => info
+                        .split_whitespace()
+                        .next()
+                        .unwrap_or_default()
+                        .to_string(),
+                    CodeBlockKind::Indented => String::new(),
+                };
+                self.code = Some((lang, String::new()));
+            }
+            Tag::List(start) => {
+                self.flush();
+                self.lists.push(start);
+            }
+            Tag::Item => {
+                self.flush();
+                let marker = match self.lists.last_mut() {
+                    Some(Some(number)) => {
+                        *number += 1;
+                        format!("{}. ", *number - 1)
+                    }
+                    _ => "• ".to_string(),
+                };
+                self.item_marker = Some(marker);
+            }
+            Tag::Emphasis => self.push_style(Modifier::ITALIC),
+            Tag::Strong => self.push_style(Modifier::BOLD),
+            Tag::Strikethrough => self.push_style(Modifier::CROSSED_OUT),
+            Tag::Link { dest_url, .. } => {
+                self.link = Some(dest_url.to_string());
+                self.styles.push(
+                    self.style()
+                        .fg(Color::Blue)
+                        .add_modifier(Modifier::UNDERLINED),
+                );
+            }
+            _ => {}
+        }
+    }
+
+    fn end(&mut self, tag: TagEnd) {
+        match tag {
+            TagEnd::Paragraph => {
+                self.flush();
+                if self.lists.is_empty() {
+                    self.blank_line();
+                }
+            }
+            TagEnd::Heading(_) => {
+                self.flush();
+                self.styles.pop();
+                self.blank_line();
+            }
+            TagEnd::BlockQuote(_) => {
+                self.flush();
+                self.quote_depth = self.quote_depth.saturating_sub(1);
+            }
+            TagEnd::List(_) => {
+                self.flush();
+                self.lists.pop();
+                if self.lists.is_empty() {
+                    self.blank_line();
+                }
+            }
+            TagEnd::Item => self.flush(),
+            TagEnd::Emphasis | TagEnd::Strong | TagEnd::Strikethrough => {
+                self.styles.pop();
+            }
+            TagEnd::Link => {
+                self.styles.pop();
+                let text_is_url = self
+                    .spans
+                    .last()
+                    .zip(self.link.as_ref())
+                    .is_some_and(|(span, url)| span.content == url.as_str());
+                if let Some(url) = self.link.take().filter(|_| !text_is_url) {
+                    self.spans.push(Span::styled(
+                        format!(" ({url})"),
+                        Style::default().fg(Color::DarkGray),
+                    ));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn end_code_block(&mut self) {
+        let Some((lang, code)) = self.code.take() else {
+            return;
+        };
+        let prefix = format!("{}  ", self.prefix());
+        for line in code.trim_end_matches('\n').lines() {
+            let mut spans = vec![Span::raw(prefix.clone())];
+            spans.extend(highlight(line, &lang));
+            self.lines.push(Line::from(spans));
+        }
+        if self.lists.is_empty() {
+            self.blank_line();
+        }
+    }
+
+    fn style(&self) -> Style {
+        self.styles.last().copied().unwrap_or_default()
+    }
+
+    fn push_style(&mut self, modifier: Modifier) {
+        self.styles.push(self.style().add_modifier(modifier));
+    }
+
+    fn push_text(&mut self, text: &str) {
+        self.spans
+            .push(Span::styled(text.to_string(), self.style()));
+    }
+
+    /// Indent, quote bars and list indentation of the current block.
+    fn prefix(&self) -> String {
+        format!(
+            "{}{}{}",
+            self.indent,
+            "│ ".repeat(self.quote_depth),
+            "  ".repeat(self.lists.len().saturating_sub(1))
+        )
+    }
+
+    fn blank_line(&mut self) {
+        if self
+            .lines
+            .last()
+            .is_some_and(|l| l.width() > self.indent.len())
+        {
+            self.lines.push(Line::from(self.indent.clone()));
+        }
+    }
+
+    /// Wrap the current block's inline content into lines.
+    fn flush(&mut self) {
+        let marker = self.item_marker.take();
+        if self.spans.is_empty() && marker.is_none() {
+            return;
+        }
+        let spans = std::mem::take(&mut self.spans);
+        let prefix = self.prefix();
+        let quote = Style::default().fg(Color::DarkGray);
+        let (first, rest) = match &marker {
+            Some(marker) => (
+                format!("{prefix}{marker}"),
+                format!("{prefix}{}", " ".repeat(marker.width())),
+            ),
+            // Items continue below their marker
+            None if !self.lists.is_empty() => (format!("{prefix}  "), format!("{prefix}  ")),
+            None => (prefix.clone(), prefix),
+        };
+        let styled_prefix = |text: &str| {
+            if self.quote_depth > 0 {
+                Span::styled(text.to_string(), quote)
+            } else {
+                Span::raw(text.to_string())
+            }
+        };
+
+        let mut line = vec![styled_prefix(&first)];
+        let mut line_width = first.width();
+        let mut has_words = false;
+        for span in spans {
+            let style = if self.quote_depth > 0 {
+                span.style.add_modifier(Modifier::ITALIC)
+            } else {
+                span.style
+            };
+            for word in span.content.split_inclusive(' ') {
+                let word_width = word.trim_end().width();
+                if has_words && line_width + word_width > self.width {
+                    trim_line_end(&mut line);
+                    self.lines.push(Line::from(std::mem::take(&mut line)));
+                    line.push(styled_prefix(&rest));
+                    line_width = rest.width();
+                    has_words = false;
+                }
+                let word = if has_words { word } else { word.trim_start() };
+                if word.is_empty() {
+                    continue;
+                }
+                line_width += word.width();
+                line.push(Span::styled(word.to_string(), style));
+                has_words = true;
+            }
+        }
+        trim_line_end(&mut line);
+        self.lines.push(Line::from(line));
+    }
+}
+
+fn trim_line_end(line: &mut [Span<'static>]) {
+    // The first span is the prefix, which keeps its spaces
+    if let [_, .., last] = line {
+        let trimmed = last.content.trim_end().to_string();
+        last.content = trimmed.into();
+    }
+}
+
+/// Keywords of the languages code blocks are highlighted for.
+fn keywords(lang: &str) -> &'static [&'static str] {
+    match lang {
+        "rust" | "rs" => &[
+            "as", "async", "await", "break", "const", "continue", "crate", "else", "enum", "false",
+            "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub",
+            "ref", "return", "self", "Self", "static", "struct", "super", "trait", "true", "type",
+            "unsafe", "use", "where", "while",
+        ],
+        "python" | "py" => &[
+            "and", "as", "async", "await", "break", "class", "continue", "def", "elif", "else",
+            "except", "False", "finally", "for", "from", "if", "import", "in", "is", "lambda",
+            "None", "not", "or", "pass", "raise", "return", "True", "try", "while", "with",
+            "yield",
+        ],
+        "javascript" | "js" | "typescript" | "ts" | "tsx" | "jsx" => &[
+            "async",
+            "await",
+            "break",
+            "case",
+            "catch",
+            "class",
+            "const",
+            "continue",
+            "default",
+            "else",
+            "export",
+            "extends",
+            "false",
+            "finally",
+            "for",
+            "from",
+            "function",
+            "if",
+            "import",
+            "interface",
+            "let",
+            "new",
+            "null",
+            "return",
+            "switch",
+            "this",
+            "throw",
+            "true",
+            "try",
+            "type",
+            "undefined",
+            "var",
+            "while",
+        ],
+        "go" => &[
+            "break",
+            "case",
+            "chan",
+            "const",
+            "continue",
+            "default",
+            "defer",
+            "else",
+            "false",
+            "for",
+            "func",
+            "go",
+            "if",
+            "import",
+            "interface",
+            "map",
+            "nil",
+            "package",
+            "range",
+            "return",
+            "select",
+            "struct",
+            "switch",
+            "true",
+            "type",
+            "var",
+        ],
+        "sh" | "bash" | "shell" | "zsh" | "console" => &[
+            "case", "do", "done", "echo", "elif", "else", "esac", "exit", "export", "fi", "for",
+            "function", "if", "in", "local", "then", "while",
+        ],
+        "json" | "toml" | "yaml" | "yml" => &["false", "null", "true"],
+        _ => &[],
+    }
+}
+
+/// Comment marker of a language, if it has line comments.
+fn line_comment(lang: &str) -> Option<&'static str> {
+    match lang {
+        "rust" | "rs" | "javascript" | "js" | "typescript" | "ts" | "tsx" | "jsx" | "go" => {
+            Some("//")
+        }
+        "python" | "py" | "sh" | "bash" | "shell" | "zsh" | "toml" | "yaml" | "yml" => Some("#"),
+        _ => None,
+    }
+}
+
+/// Spans of a code line with keywords, strings, numbers and comments
+/// coloured; plain for languages without keywords.
+fn highlight(line: &str, lang: &str) -> Vec<Span<'static>> {
+    let code = Style::default().fg(Color::Gray);
+    let keywords = keywords(lang);
+    if keywords.is_empty() {
+        return vec![Span::styled(line.to_string(), code)];
+    }
+    let comment = line_comment(lang);
+
+    let mut spans = Vec::new();
+    let mut rest = line;
+    while !rest.is_empty() {
+        if comment.is_some_and(|marker| rest.starts_with(marker)) {
+            spans.push(Span::styled(
+                rest.to_string(),
+                Style::default().fg(Color::DarkGray),
+            ));
+            break;
+        }
+        let first = rest.chars().next().unwrap_or_default();
+        let len = if first == '"' || first == '\'' || first == '`' {
+            // A string, up to its unescaped closing quote
+            let mut escaped = false;
+            rest[1..]
+                .char_indices()
+                .find(|&(_, c)| {
+                    let closes = c == first && !escaped;
+                    escaped = c == '\\' && !escaped;
+                    closes
+                })
+                .map_or(rest.len(), |(i, _)| i + 2)
+        } else if first.is_alphanumeric() || first == '_' {
+            rest.find(|c: char| !(c.is_alphanumeric() || c == '_'))
+                .unwrap_or(rest.len())
+        } else {
+            first.len_utf8()
+        };
+        let token = &rest[..len];
+        let style = if first == '"' || first == '\'' || first == '`' {
+            Style::default().fg(Color::Green)
+        } else if first.is_ascii_digit() {
+            Style::default().fg(Color::Yellow)
+        } else if keywords.contains(&token) {
+            Style::default()
+                .fg(Color::Magenta)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            code
+        };
+        spans.push(Span::styled(token.to_string(), style));
+        rest = &rest[len..];
+    }
+    spans
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(lines: &[Line]) -> Vec<String> {
+        lines.iter().map(|l| l.to_string()).collect()
+    }
+
+    #[test]
+    fn test_markdown_blocks_and_inline_styles() {
+        let md = "# Done\n\nFixed the **flaky** test in `lib.rs`.\n\n- one\n- two\n  1. nested\n\n```rust\nfn main() {} // entry\n```\n";
+        let lines = markdown_lines(md, 40, "  ");
+        assert_eq!(
+            text(&lines),
+            [
+                "  Done",
+                "  ",
+                "  Fixed the flaky test in lib.rs.",
+                "  ",
+                "  • one",
+                "  • two",
+                "    1. nested",
+                "  ",
+                "    fn main() {} // entry",
+            ]
+        );
+        assert!(lines[0].spans[1]
+            .style
+            .add_modifier
+            .contains(Modifier::BOLD | Modifier::UNDERLINED));
+        let bold = lines[2]
+            .spans
+            .iter()
+            .find(|s| s.content == "flaky")
+            .unwrap();
+        assert!(bold.style.add_modifier.contains(Modifier::BOLD));
+        let keyword = lines[8].spans.iter().find(|s| s.content == "fn").unwrap();
+        assert_eq!(keyword.style.fg, Some(Color::Magenta));
+        assert_eq!(lines[8].spans.last().unwrap().content, "// entry");
+    }
+
+    #[test]
+    fn test_markdown_wraps_list_items_under_their_text() {
+        let lines = markdown_lines("- a long item that wraps", 14, "");
+        assert_eq!(text(&lines), ["• a long item", "  that wraps"]);
+    }
+}
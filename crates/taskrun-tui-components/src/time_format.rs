@@ -0,0 +1,250 @@
+//! Timestamp display shared by the TUIs and the CLI.
+//!
+//! Timestamps are kept in UTC. A [`TimeFormat`] shows them in a configured
+//! time zone (`utc`, `local` or an IANA name such as `Europe/Berlin`), with
+//! the date order and clock of a locale, either as absolute times or
+//! relative to now ("5m ago").
+
+use std::convert::Infallible;
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{DateTime, Local, Utc};
+use chrono_tz::Tz;
+
+/// Time zone timestamps are shown in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DisplayZone {
+    #[default]
+    Utc,
+    /// The system time zone.
+    Local,
+    /// An IANA time zone.
+    Named(Tz),
+}
+
+/// A time zone name that is neither `utc`, `local` nor an IANA zone.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("unknown time zone '{0}' (use utc, local or an IANA name like Europe/Berlin)")]
+pub struct UnknownZone(pub String);
+
+impl FromStr for DisplayZone {
+    type Err = UnknownZone;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.eq_ignore_ascii_case("utc") || s.eq_ignore_ascii_case("z") {
+            Ok(Self::Utc)
+        } else if s.eq_ignore_ascii_case("local") {
+            Ok(Self::Local)
+        } else {
+            s.parse::<Tz>()
+                .map(Self::Named)
+                .map_err(|_| UnknownZone(s.to_string()))
+        }
+    }
+}
+
+impl fmt::Display for DisplayZone {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Utc => f.write_str("utc"),
+            Self::Local => f.write_str("local"),
+            Self::Named(tz) => f.write_str(tz.name()),
+        }
+    }
+}
+
+/// Date order and clock of a locale.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DisplayLocale {
+    /// `2025-05-01 14:30:00`
+    #[default]
+    Iso,
+    /// `05/01/2025 02:30:00 PM`
+    MonthFirst,
+    /// `01/05/2025 14:30:00`
+    DayFirst,
+}
+
+impl DisplayLocale {
+    /// Locale from a tag such as `en_US.UTF-8` or `de-DE`. `iso`, `C` and
+    /// `POSIX` keep ISO 8601; US English is month-first with a 12-hour
+    /// clock, any other locale day-first with a 24-hour clock.
+    pub fn from_tag(tag: &str) -> Self {
+        let tag = tag.split(['.', '@']).next().unwrap_or_default().trim();
+        if tag.is_empty() || ["iso", "c", "posix"].contains(&tag.to_ascii_lowercase().as_str()) {
+            return Self::Iso;
+        }
+        let mut parts = tag.split(['_', '-']);
+        let _language = parts.next();
+        match parts.next() {
+            Some(region) if region.eq_ignore_ascii_case("us") => Self::MonthFirst,
+            _ => Self::DayFirst,
+        }
+    }
+
+    /// Locale of the environment (`LC_ALL`, `LC_TIME`, then `LANG`).
+    pub fn from_env() -> Self {
+        ["LC_ALL", "LC_TIME", "LANG"]
+            .iter()
+            .filter_map(|var| std::env::var(var).ok())
+            .find(|value| !value.is_empty())
+            .map_or(Self::Iso, |tag| Self::from_tag(&tag))
+    }
+
+    fn date_format(self) -> &'static str {
+        match self {
+            Self::Iso => "%Y-%m-%d",
+            Self::MonthFirst => "%m/%d/%Y",
+            Self::DayFirst => "%d/%m/%Y",
+        }
+    }
+
+    fn time_format(self) -> &'static str {
+        match self {
+            Self::MonthFirst => "%I:%M:%S %p",
+            Self::Iso | Self::DayFirst => "%H:%M:%S",
+        }
+    }
+}
+
+/// `auto` reads the environment; anything else is a locale tag.
+impl FromStr for DisplayLocale {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.trim().eq_ignore_ascii_case("auto") {
+            Ok(Self::from_env())
+        } else {
+            Ok(Self::from_tag(s))
+        }
+    }
+}
+
+/// How timestamps are displayed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TimeFormat {
+    pub zone: DisplayZone,
+    pub locale: DisplayLocale,
+    /// Show how long ago (or how far ahead) instead of the time.
+    pub relative: bool,
+}
+
+impl TimeFormat {
+    /// Absolute times in `zone` with the conventions of `locale`.
+    pub fn new(zone: DisplayZone, locale: DisplayLocale) -> Self {
+        Self {
+            zone,
+            locale,
+            relative: false,
+        }
+    }
+
+    /// Show relative instead of absolute times.
+    pub fn with_relative(mut self, relative: bool) -> Self {
+        self.relative = relative;
+        self
+    }
+
+    /// Switch between absolute and relative times.
+    pub fn toggle_relative(&mut self) {
+        self.relative = !self.relative;
+    }
+
+    /// Time of day, for chat messages, events and log lines.
+    pub fn time(&self, at: DateTime<Utc>) -> String {
+        self.time_at(at, Utc::now())
+    }
+
+    /// Date and time, for listings.
+    pub fn date_time(&self, at: DateTime<Utc>) -> String {
+        self.date_time_at(at, Utc::now())
+    }
+
+    /// [`time`](Self::time) as seen at `now`.
+    pub fn time_at(&self, at: DateTime<Utc>, now: DateTime<Utc>) -> String {
+        if self.relative {
+            return format_relative(at, now);
+        }
+        self.render(at, self.locale.time_format())
+    }
+
+    /// [`date_time`](Self::date_time) as seen at `now`.
+    pub fn date_time_at(&self, at: DateTime<Utc>, now: DateTime<Utc>) -> String {
+        if self.relative {
+            return format_relative(at, now);
+        }
+        let format = format!(
+            "{} {}",
+            self.locale.date_format(),
+            self.locale.time_format()
+        );
+        self.render(at, &format)
+    }
+
+    fn render(&self, at: DateTime<Utc>, format: &str) -> String {
+        match self.zone {
+            DisplayZone::Utc => at.format(format).to_string(),
+            DisplayZone::Local => at.with_timezone(&Local).format(format).to_string(),
+            DisplayZone::Named(tz) => at.with_timezone(&tz).format(format).to_string(),
+        }
+    }
+}
+
+/// `at` relative to `now`: `45s ago`, `3m ago`, `2h ago`, `4d ago`, or
+/// `in 5m` for times still ahead.
+pub fn format_relative(at: DateTime<Utc>, now: DateTime<Utc>) -> String {
+    let secs = now.signed_duration_since(at).num_seconds();
+    let amount = match secs.unsigned_abs() {
+        s if s < 60 => format!("{}s", s),
+        s if s < 3600 => format!("{}m", s / 60),
+        s if s < 86400 => format!("{}h", s / 3600),
+        s => format!("{}d", s / 86400),
+    };
+    if secs < 0 {
+        format!("in {}", amount)
+    } else {
+        format!("{} ago", amount)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_time_format_zone_and_locale() {
+        let at = Utc.with_ymd_and_hms(2025, 5, 1, 14, 30, 0).unwrap();
+        assert_eq!(TimeFormat::default().date_time(at), "2025-05-01 14:30:00");
+
+        let zone: DisplayZone = "America/New_York".parse().unwrap();
+        let us = TimeFormat::new(zone, DisplayLocale::from_tag("en_US.UTF-8"));
+        assert_eq!(us.date_time(at), "05/01/2025 10:30:00 AM");
+        assert_eq!(us.time(at), "10:30:00 AM");
+
+        let berlin = TimeFormat::new("Europe/Berlin".parse().unwrap(), "de-DE".parse().unwrap());
+        assert_eq!(berlin.date_time(at), "01/05/2025 16:30:00");
+
+        assert!("Mars/Olympus".parse::<DisplayZone>().is_err());
+    }
+
+    #[test]
+    fn test_relative_times() {
+        let now = Utc.with_ymd_and_hms(2025, 5, 1, 14, 30, 0).unwrap();
+        let format = TimeFormat::default().with_relative(true);
+        assert_eq!(
+            format.time_at(now - chrono::Duration::seconds(45), now),
+            "45s ago"
+        );
+        assert_eq!(
+            format.date_time_at(now - chrono::Duration::hours(50), now),
+            "2d ago"
+        );
+        assert_eq!(
+            format_relative(now + chrono::Duration::minutes(5), now),
+            "in 5m"
+        );
+    }
+}
@@ -132,6 +132,51 @@ pub fn format_duration(seconds: i64) -> String {
     }
 }
 
+/// Whether `uri` is an http(s) URL.
+pub fn is_web_url(uri: &str) -> bool {
+    has_scheme(uri, "http://") || has_scheme(uri, "https://")
+}
+
+fn has_scheme(uri: &str, scheme: &str) -> bool {
+    uri.get(..scheme.len())
+        .is_some_and(|s| s.eq_ignore_ascii_case(scheme))
+}
+
+/// Open an http(s) or `file://` URI with the platform's default application.
+/// Other targets, such as bare paths an opener could take for options, are
+/// refused.
+///
+/// The opener is run directly, never through a shell, detached with its
+/// output discarded so it cannot draw over the terminal UI.
+pub fn open_external(uri: &str) -> std::io::Result<()> {
+    use std::process::{Command, Stdio};
+
+    if !is_web_url(uri) && !has_scheme(uri, "file://") {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("not an http(s) or file:// URI: {}", uri),
+        ));
+    }
+
+    let mut command = if cfg!(target_os = "macos") {
+        Command::new("open")
+    } else if cfg!(target_os = "windows") {
+        let mut command = Command::new("rundll32");
+        command.arg("url.dll,FileProtocolHandler");
+        command
+    } else {
+        Command::new("xdg-open")
+    };
+
+    command
+        .arg(uri)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map(|_| ())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -143,6 +188,21 @@ mod tests {
         assert_eq!(wrapped, vec!["Hello", " worl", "d"]);
     }
 
+    #[test]
+    fn test_open_external_only_opens_uris() {
+        assert!(is_web_url("HTTPS://example.com/report.pdf"));
+        assert!(!is_web_url("file:///tmp/report.pdf"));
+        for target in [
+            "--help",
+            "calc.exe",
+            "/tmp/report.pdf",
+            "javascript:alert(1)",
+        ] {
+            let err = open_external(target).unwrap_err();
+            assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+        }
+    }
+
     #[test]
     fn test_wrap_text_empty() {
         let wrapped = wrap_text("", 10);
@@ -7,7 +7,9 @@ use ratatui::text::{Line, Span};
 use ratatui::widgets::{Block, Borders, Paragraph};
 use ratatui::Frame;
 
+use crate::markdown::markdown_lines;
 use crate::theme::Theme;
+use crate::time_format::TimeFormat;
 use crate::utils::wrap_text_indented;
 
 /// Role of a chat message participant.
@@ -41,6 +43,10 @@ pub struct ChatWidget<'a> {
     title: Option<String>,
     /// Theme for styling.
     theme: Theme,
+    /// How timestamps are shown.
+    time_format: TimeFormat,
+    /// Render assistant messages as markdown (false = raw text).
+    markdown: bool,
 }
 
 impl<'a> ChatWidget<'a> {
@@ -53,6 +59,8 @@ impl<'a> ChatWidget<'a> {
             focused: false,
             title: None,
             theme: Theme::default(),
+            time_format: TimeFormat::default(),
+            markdown: true,
         }
     }
 
@@ -88,6 +96,18 @@ impl<'a> ChatWidget<'a> {
         self
     }
 
+    /// Set how timestamps are shown.
+    pub fn time_format(mut self, time_format: TimeFormat) -> Self {
+        self.time_format = time_format;
+        self
+    }
+
+    /// Set whether assistant messages are rendered as markdown.
+    pub fn markdown(mut self, markdown: bool) -> Self {
+        self.markdown = markdown;
+        self
+    }
+
     /// Render the widget.
     pub fn render(self, frame: &mut Frame, area: Rect) {
         let border_style = if self.focused {
@@ -122,14 +142,18 @@ impl<'a> ChatWidget<'a> {
             all_lines.push(Line::from(vec![
                 Span::styled(prefix, style),
                 Span::styled(
-                    msg.timestamp.format("%H:%M:%S").to_string(),
+                    self.time_format.time(msg.timestamp),
                     self.theme.muted_style(),
                 ),
             ]));
 
             // Add message content with word wrapping
-            for wrapped_line in wrap_text_indented(&msg.content, text_width, "  ") {
-                all_lines.push(Line::from(Span::raw(wrapped_line)));
+            if self.markdown && msg.role == ChatRole::Assistant {
+                all_lines.extend(markdown_lines(&msg.content, text_width, "  "));
+            } else {
+                for wrapped_line in wrap_text_indented(&msg.content, text_width, "  ") {
+                    all_lines.push(Line::from(Span::raw(wrapped_line)));
+                }
             }
 
             // Add blank line between messages
@@ -145,8 +169,12 @@ impl<'a> ChatWidget<'a> {
                 ),
                 Span::styled("(streaming...)", self.theme.muted_style()),
             ]));
-            for wrapped_line in wrap_text_indented(streaming, text_width, "  ") {
-                all_lines.push(Line::from(Span::raw(wrapped_line)));
+            if self.markdown {
+                all_lines.extend(markdown_lines(streaming, text_width, "  "));
+            } else {
+                for wrapped_line in wrap_text_indented(streaming, text_width, "  ") {
+                    all_lines.push(Line::from(Span::raw(wrapped_line)));
+                }
             }
         }
 
@@ -15,6 +15,29 @@ pub fn centered_rect(width: u16, height: u16, area: Rect) -> Rect {
     Rect::new(x, y, width.min(area.width), height.min(area.height))
 }
 
+/// A problem with what was typed into a dialog, e.g. from task validation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationMessage {
+    /// Submitting would fail (otherwise a warning).
+    pub error: bool,
+    pub text: String,
+}
+
+impl ValidationMessage {
+    /// Line showing the message, red for errors and yellow for warnings.
+    pub fn to_line(&self) -> Line<'static> {
+        let (marker, color) = if self.error {
+            ("✗", Color::Red)
+        } else {
+            ("!", Color::Yellow)
+        };
+        Line::from(Span::styled(
+            format!("  {} {}", marker, self.text),
+            Style::default().fg(color),
+        ))
+    }
+}
+
 /// A simple confirmation dialog.
 #[derive(Debug, Clone)]
 pub struct ConfirmDialog<'a> {
@@ -200,6 +223,8 @@ pub struct InputDialog<'a> {
     value: &'a str,
     /// Cursor position.
     cursor: usize,
+    /// Validation messages shown under the input.
+    messages: &'a [ValidationMessage],
     /// Width of the dialog.
     width: u16,
     /// Theme for styling.
@@ -214,6 +239,7 @@ impl<'a> InputDialog<'a> {
             prompt,
             value,
             cursor: value.chars().count(),
+            messages: &[],
             width: 60,
             theme: Theme::default(),
         }
@@ -225,6 +251,12 @@ impl<'a> InputDialog<'a> {
         self
     }
 
+    /// Show validation messages under the input.
+    pub fn messages(mut self, messages: &'a [ValidationMessage]) -> Self {
+        self.messages = messages;
+        self
+    }
+
     /// Set the dialog width.
     pub fn width(mut self, width: u16) -> Self {
         self.width = width;
@@ -239,7 +271,8 @@ impl<'a> InputDialog<'a> {
 
     /// Render the dialog.
     pub fn render(self, frame: &mut Frame) {
-        let area = centered_rect(self.width, 7, frame.area());
+        let height = 7 + self.messages.len() as u16;
+        let area = centered_rect(self.width, height, frame.area());
 
         frame.render_widget(Clear, area);
 
@@ -250,7 +283,7 @@ impl<'a> InputDialog<'a> {
         let after: String = self.value.chars().skip(cursor_pos).collect();
         let input_display = format!("  {}|{}", before, after);
 
-        let lines = vec![
+        let mut lines = vec![
             Line::from(""),
             Line::from(Span::styled(
                 format!("  {}", self.prompt),
@@ -261,11 +294,12 @@ impl<'a> InputDialog<'a> {
                 input_display,
                 Style::default().fg(Color::White),
             )),
-            Line::from(Span::styled(
-                "  [Enter] Submit  [Esc] Cancel",
-                self.theme.muted_style(),
-            )),
         ];
+        lines.extend(self.messages.iter().map(ValidationMessage::to_line));
+        lines.push(Line::from(Span::styled(
+            "  [Enter] Submit  [Esc] Cancel",
+            self.theme.muted_style(),
+        )));
 
         let paragraph = Paragraph::new(lines).block(
             Block::default()
@@ -8,6 +8,7 @@ use ratatui::widgets::{Block, Borders, List, ListItem};
 use ratatui::Frame;
 
 use crate::theme::Theme;
+use crate::time_format::TimeFormat;
 
 /// Information about an event.
 #[derive(Debug, Clone)]
@@ -30,6 +31,8 @@ pub struct EventsWidget<'a> {
     title: Option<String>,
     /// Theme for styling.
     theme: Theme,
+    /// How timestamps are shown.
+    time_format: TimeFormat,
 }
 
 impl<'a> EventsWidget<'a> {
@@ -41,6 +44,7 @@ impl<'a> EventsWidget<'a> {
             focused: false,
             title: None,
             theme: Theme::default(),
+            time_format: TimeFormat::default(),
         }
     }
 
@@ -68,6 +72,12 @@ impl<'a> EventsWidget<'a> {
         self
     }
 
+    /// Set how timestamps are shown.
+    pub fn time_format(mut self, time_format: TimeFormat) -> Self {
+        self.time_format = time_format;
+        self
+    }
+
     /// Render the widget.
     pub fn render(self, frame: &mut Frame, area: Rect) {
         let border_style = if self.focused {
@@ -89,7 +99,7 @@ impl<'a> EventsWidget<'a> {
             .skip(scroll_offset)
             .take(visible_height)
             .map(|event| {
-                let timestamp = event.timestamp.format("%H:%M:%S");
+                let timestamp = self.time_format.time(event.timestamp);
                 let event_style = Self::style_for_event(&event.event_type, &self.theme);
 
                 let mut spans = vec![
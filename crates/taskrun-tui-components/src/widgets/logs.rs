@@ -8,6 +8,7 @@ use ratatui::widgets::{Block, Borders, List, ListItem};
 use ratatui::Frame;
 
 use crate::theme::Theme;
+use crate::time_format::TimeFormat;
 
 /// Log level for messages.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -50,6 +51,8 @@ pub struct LogsWidget<'a> {
     title: Option<String>,
     /// Theme for styling.
     theme: Theme,
+    /// How timestamps are shown.
+    time_format: TimeFormat,
 }
 
 impl<'a> LogsWidget<'a> {
@@ -61,6 +64,7 @@ impl<'a> LogsWidget<'a> {
             focused: false,
             title: None,
             theme: Theme::default(),
+            time_format: TimeFormat::default(),
         }
     }
 
@@ -88,6 +92,12 @@ impl<'a> LogsWidget<'a> {
         self
     }
 
+    /// Set how timestamps are shown.
+    pub fn time_format(mut self, time_format: TimeFormat) -> Self {
+        self.time_format = time_format;
+        self
+    }
+
     /// Render the widget.
     pub fn render(self, frame: &mut Frame, area: Rect) {
         let border_style = if self.focused {
@@ -110,7 +120,7 @@ impl<'a> LogsWidget<'a> {
             .take(visible_height)
             .map(|entry| {
                 let level_style = self.style_for_level(entry.level);
-                let timestamp = entry.timestamp.format("%H:%M:%S").to_string();
+                let timestamp = self.time_format.time(entry.timestamp);
 
                 ListItem::new(Line::from(vec![
                     Span::styled(timestamp, self.theme.muted_style()),
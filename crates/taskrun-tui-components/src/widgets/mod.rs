@@ -6,5 +6,6 @@ pub mod events;
 pub mod footer;
 pub mod header;
 pub mod logs;
+pub mod progress;
 pub mod run_detail;
 pub mod table;
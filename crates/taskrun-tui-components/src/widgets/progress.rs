@@ -0,0 +1,142 @@
+//! Grouped progress bars (workflows, batches, fan-outs).
+
+use ratatui::layout::Rect;
+use ratatui::style::{Color, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Paragraph};
+use ratatui::Frame;
+
+use crate::theme::Theme;
+
+/// Task counts of one group, by status.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GroupProgress {
+    /// Group name shown before the bar.
+    pub label: String,
+    pub completed: u32,
+    pub failed: u32,
+    pub cancelled: u32,
+    pub running: u32,
+    pub pending: u32,
+}
+
+impl GroupProgress {
+    /// Number of tasks in the group.
+    pub fn total(&self) -> u32 {
+        self.completed + self.failed + self.cancelled + self.running + self.pending
+    }
+
+    /// Number of tasks that reached a terminal status.
+    pub fn finished(&self) -> u32 {
+        self.completed + self.failed + self.cancelled
+    }
+
+    /// Width of each segment (completed, failed, cancelled, running) in a bar
+    /// of `width` cells; the rest of the bar is pending.
+    fn segments(&self, width: u16) -> [u16; 4] {
+        let total = self.total().max(1) as u64;
+        let cells = |count: u32| (count as u64 * width as u64 / total) as u16;
+        [
+            cells(self.completed),
+            cells(self.failed),
+            cells(self.cancelled),
+            cells(self.running),
+        ]
+    }
+}
+
+/// One bar per group, each split by status.
+#[derive(Debug, Clone)]
+pub struct GroupProgressList<'a> {
+    groups: &'a [GroupProgress],
+    title: String,
+    theme: Theme,
+}
+
+impl<'a> GroupProgressList<'a> {
+    /// Create a list of group bars.
+    pub fn new(groups: &'a [GroupProgress]) -> Self {
+        Self {
+            groups,
+            title: " Groups ".to_string(),
+            theme: Theme::default(),
+        }
+    }
+
+    /// Set the block title.
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = title.into();
+        self
+    }
+
+    /// Set the theme.
+    pub fn theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    /// Height needed to show every group (borders included).
+    pub fn height(&self) -> u16 {
+        self.groups.len() as u16 + 2
+    }
+
+    /// Render the bars.
+    pub fn render(self, frame: &mut Frame, area: Rect) {
+        const LABEL_WIDTH: usize = 16;
+        const COUNT_WIDTH: u16 = 12;
+        let bar_width = area
+            .width
+            .saturating_sub(LABEL_WIDTH as u16 + COUNT_WIDTH + 4);
+
+        let lines: Vec<Line> = self
+            .groups
+            .iter()
+            .map(|group| {
+                let [completed, failed, cancelled, running] = group.segments(bar_width);
+                let pending = bar_width - completed - failed - cancelled - running;
+                let segment = |cells: u16, color: Color| {
+                    Span::styled("█".repeat(cells as usize), Style::default().fg(color))
+                };
+                let label: String = group.label.chars().take(LABEL_WIDTH).collect();
+                Line::from(vec![
+                    Span::raw(format!("{:<width$} ", label, width = LABEL_WIDTH)),
+                    segment(completed, self.theme.success),
+                    segment(failed, self.theme.error),
+                    segment(cancelled, self.theme.muted),
+                    segment(running, Color::Cyan),
+                    Span::styled(
+                        "░".repeat(pending as usize),
+                        Style::default().fg(self.theme.muted),
+                    ),
+                    Span::raw(format!(" {}/{}", group.finished(), group.total())),
+                ])
+            })
+            .collect();
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(self.theme.unfocused_border())
+            .title(self.title);
+        frame.render_widget(Paragraph::new(lines).block(block), area);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_segments_fit_the_bar() {
+        let group = GroupProgress {
+            label: "nightly".to_string(),
+            completed: 5,
+            failed: 1,
+            running: 2,
+            pending: 2,
+            ..GroupProgress::default()
+        };
+        assert_eq!(group.finished(), 6);
+        assert_eq!(group.segments(20), [10, 2, 0, 4]);
+        assert!(group.segments(7).iter().sum::<u16>() <= 7);
+    }
+}
@@ -7,7 +7,9 @@ use ratatui::text::{Line, Span};
 use ratatui::widgets::{Block, Borders, List, ListItem, Paragraph};
 use ratatui::Frame;
 
+use crate::markdown::markdown_lines;
 use crate::theme::Theme;
+use crate::time_format::TimeFormat;
 use crate::utils::wrap_text_indented;
 
 /// Status of a run.
@@ -16,6 +18,7 @@ pub enum RunStatus {
     Running,
     Completed,
     Failed,
+    Cancelled,
 }
 
 /// Role in a chat message.
@@ -31,6 +34,8 @@ pub struct RunMessage {
     pub role: MessageRole,
     pub content: String,
     pub timestamp: DateTime<Utc>,
+    /// Attachment markers shown under the content, e.g. `[attachment: shot.png (12.3 KB)]`.
+    pub attachments: Vec<String>,
 }
 
 /// An event in the run.
@@ -41,6 +46,18 @@ pub struct RunEvent {
     pub details: Option<String>,
 }
 
+/// A milestone the run's agent reported.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RunMilestone {
+    pub name: String,
+    /// Overall progress estimate (0-100).
+    pub percent: Option<u8>,
+    pub note: Option<String>,
+}
+
+/// Most milestones listed in the header; earlier ones are summarized.
+const MAX_MILESTONE_LINES: usize = 4;
+
 /// Information about a run to display.
 #[derive(Debug, Clone)]
 pub struct RunInfo {
@@ -54,6 +71,12 @@ pub struct RunInfo {
     pub events: Vec<RunEvent>,
     pub current_output: String,
     pub queued_input: Option<String>,
+    /// Who created the task, shown in the header when known.
+    pub created_by: Option<String>,
+    /// Milestones reported so far, in the order first reached.
+    pub milestones: Vec<RunMilestone>,
+    /// One-line preview of what the run produced, once it finished.
+    pub result: Option<String>,
 }
 
 /// Which pane is focused.
@@ -63,6 +86,8 @@ pub enum DetailPane {
     Chat,
     Events,
     Input,
+    /// Focus is on a pane the caller renders beside the view.
+    Outside,
 }
 
 /// Run detail view combining header, chat, events, and input.
@@ -75,12 +100,18 @@ pub struct RunDetailView<'a> {
     chat_scroll: usize,
     /// Events scroll offset.
     events_scroll: usize,
+    /// Highlighted event, kept in view (None = no selection).
+    selected_event: Option<usize>,
     /// Current input text.
     input_text: &'a str,
     /// Input cursor position.
     input_cursor: usize,
     /// Theme for styling.
     theme: Theme,
+    /// How timestamps are shown.
+    time_format: TimeFormat,
+    /// Render assistant messages as markdown (false = raw text).
+    markdown: bool,
 }
 
 impl<'a> RunDetailView<'a> {
@@ -91,9 +122,12 @@ impl<'a> RunDetailView<'a> {
             focused_pane: DetailPane::Chat,
             chat_scroll: usize::MAX,
             events_scroll: 0,
+            selected_event: None,
             input_text: "",
             input_cursor: 0,
             theme: Theme::default(),
+            time_format: TimeFormat::default(),
+            markdown: true,
         }
     }
 
@@ -115,6 +149,12 @@ impl<'a> RunDetailView<'a> {
         self
     }
 
+    /// Highlight an event in the events pane, scrolling it into view.
+    pub fn selected_event(mut self, index: usize) -> Self {
+        self.selected_event = Some(index);
+        self
+    }
+
     /// Set the input text and cursor.
     pub fn input(mut self, text: &'a str, cursor: usize) -> Self {
         self.input_text = text;
@@ -128,15 +168,28 @@ impl<'a> RunDetailView<'a> {
         self
     }
 
+    /// Set how timestamps are shown.
+    pub fn time_format(mut self, time_format: TimeFormat) -> Self {
+        self.time_format = time_format;
+        self
+    }
+
+    /// Set whether assistant messages are rendered as markdown.
+    pub fn markdown(mut self, markdown: bool) -> Self {
+        self.markdown = markdown;
+        self
+    }
+
     /// Render the view.
     pub fn render(self, frame: &mut Frame, area: Rect) {
         // Layout: header + chat/events split + input box
+        let extra_lines = (self.milestone_lines().len() + self.run.result.iter().count()) as u16;
         let chunks = Layout::default()
             .direction(Direction::Vertical)
             .constraints([
-                Constraint::Length(3), // Status header
-                Constraint::Min(0),    // Chat + events
-                Constraint::Length(3), // Input box
+                Constraint::Length(3 + extra_lines), // Status header, milestones and result
+                Constraint::Min(0),                  // Chat + events
+                Constraint::Length(3),               // Input box
             ])
             .split(area);
 
@@ -162,6 +215,7 @@ impl<'a> RunDetailView<'a> {
             RunStatus::Running => ("● Running", Color::Yellow),
             RunStatus::Completed => ("✓ Completed", Color::Green),
             RunStatus::Failed => ("✗ Failed", Color::Red),
+            RunStatus::Cancelled => ("⊘ Cancelled", Color::DarkGray),
         };
 
         let duration = if let Some(completed) = self.run.completed_at {
@@ -172,11 +226,18 @@ impl<'a> RunDetailView<'a> {
             format!("{}s", dur.num_seconds())
         };
 
-        let header = Paragraph::new(Line::from(vec![
+        let mut spans = vec![
             Span::styled(status_str, Style::default().fg(status_color)),
             Span::raw(" | "),
             Span::raw("Agent: "),
             Span::styled(&self.run.agent, Style::default().fg(Color::Cyan)),
+        ];
+        if let Some(created_by) = &self.run.created_by {
+            spans.push(Span::raw(" | "));
+            spans.push(Span::raw("By: "));
+            spans.push(Span::raw(created_by.as_str()));
+        }
+        spans.extend([
             Span::raw(" | "),
             Span::styled(duration, Style::default().fg(Color::DarkGray)),
             Span::raw(" | "),
@@ -184,12 +245,58 @@ impl<'a> RunDetailView<'a> {
                 format!("{} messages", self.run.messages.len()),
                 Style::default().fg(Color::DarkGray),
             ),
-        ]))
-        .block(Block::default().borders(Borders::ALL));
+        ]);
+        let mut lines = vec![Line::from(spans)];
+        lines.extend(self.milestone_lines());
+        if let Some(result) = &self.run.result {
+            lines.push(Line::from(vec![
+                Span::styled("→ ", Style::default().fg(Color::Green)),
+                Span::styled(result.as_str(), self.theme.muted_style()),
+            ]));
+        }
+        let header = Paragraph::new(lines).block(Block::default().borders(Borders::ALL));
 
         frame.render_widget(header, area);
     }
 
+    /// Milestone checklist lines of the header, latest last.
+    fn milestone_lines(&self) -> Vec<Line<'a>> {
+        let milestones = &self.run.milestones;
+        let mut lines = Vec::new();
+        let hidden = milestones.len().saturating_sub(MAX_MILESTONE_LINES);
+        let shown = if hidden > 0 {
+            // Keep the summary line within the limit
+            let hidden = hidden + 1;
+            lines.push(Line::from(Span::styled(
+                format!("✓ {} earlier milestones", hidden),
+                self.theme.muted_style(),
+            )));
+            &milestones[hidden..]
+        } else {
+            &milestones[..]
+        };
+        for milestone in shown {
+            let mut spans = vec![
+                Span::styled("✓ ", Style::default().fg(Color::Green)),
+                Span::raw(milestone.name.clone()),
+            ];
+            if let Some(percent) = milestone.percent {
+                spans.push(Span::styled(
+                    format!(" {}%", percent),
+                    Style::default().fg(Color::Cyan),
+                ));
+            }
+            if let Some(note) = &milestone.note {
+                spans.push(Span::styled(
+                    format!(" - {}", note),
+                    self.theme.muted_style(),
+                ));
+            }
+            lines.push(Line::from(spans));
+        }
+        lines
+    }
+
     /// Render chat messages.
     fn render_chat(&self, frame: &mut Frame, area: Rect) {
         let is_focused = self.focused_pane == DetailPane::Chat;
@@ -215,14 +322,25 @@ impl<'a> RunDetailView<'a> {
             all_lines.push(Line::from(vec![
                 Span::styled(prefix, style.add_modifier(Modifier::BOLD)),
                 Span::styled(
-                    msg.timestamp.format("%H:%M:%S").to_string(),
+                    self.time_format.time(msg.timestamp),
                     self.theme.muted_style(),
                 ),
             ]));
 
             // Add message content with word wrapping
-            for wrapped_line in wrap_text_indented(&msg.content, text_width, "  ") {
-                all_lines.push(Line::from(Span::raw(wrapped_line)));
+            if self.markdown && msg.role == MessageRole::Assistant {
+                all_lines.extend(markdown_lines(&msg.content, text_width, "  "));
+            } else {
+                for wrapped_line in wrap_text_indented(&msg.content, text_width, "  ") {
+                    all_lines.push(Line::from(Span::raw(wrapped_line)));
+                }
+            }
+
+            for marker in &msg.attachments {
+                all_lines.push(Line::from(Span::styled(
+                    format!("  {}", marker),
+                    Style::default().fg(Color::Magenta),
+                )));
             }
 
             // Add blank line between messages
@@ -240,8 +358,12 @@ impl<'a> RunDetailView<'a> {
                 ),
                 Span::styled("(streaming...)", self.theme.muted_style()),
             ]));
-            for wrapped_line in wrap_text_indented(&self.run.current_output, text_width, "  ") {
-                all_lines.push(Line::from(Span::raw(wrapped_line)));
+            if self.markdown {
+                all_lines.extend(markdown_lines(&self.run.current_output, text_width, "  "));
+            } else {
+                for wrapped_line in wrap_text_indented(&self.run.current_output, text_width, "  ") {
+                    all_lines.push(Line::from(Span::raw(wrapped_line)));
+                }
             }
         }
 
@@ -263,7 +385,13 @@ impl<'a> RunDetailView<'a> {
 
         let first_line = scroll_offset + 1;
         let last_line = (scroll_offset + visible_height).min(total_lines);
-        let title = format!(" Chat [{}-{}/{}] ", first_line, last_line, total_lines);
+        let title = format!(
+            " Chat{} [{}-{}/{}] ",
+            if self.markdown { "" } else { " (raw)" },
+            first_line,
+            last_line,
+            total_lines
+        );
 
         let chat = Paragraph::new(lines).block(
             Block::default()
@@ -287,23 +415,32 @@ impl<'a> RunDetailView<'a> {
         let visible_height = area.height.saturating_sub(2) as usize;
         let total_events = self.run.events.len();
 
-        // Clamp scroll offset
+        // Clamp scroll offset; a selected event stays in view
         let max_scroll = total_events.saturating_sub(visible_height);
-        let scroll_offset = self.events_scroll.min(max_scroll);
+        let selected = self
+            .selected_event
+            .map(|index| index.min(total_events.saturating_sub(1)));
+        let scroll_offset = match selected {
+            Some(index) => index.saturating_sub(visible_height.saturating_sub(1)),
+            None => self.events_scroll,
+        }
+        .min(max_scroll);
 
         let items: Vec<ListItem> = self
             .run
             .events
             .iter()
+            .enumerate()
             .skip(scroll_offset)
             .take(visible_height)
-            .map(|event| {
-                let timestamp = event.timestamp.format("%H:%M:%S");
+            .map(|(index, event)| {
+                let timestamp = self.time_format.time(event.timestamp);
                 let event_style = match event.event_type.as_str() {
                     s if s.contains("Started") => Style::default().fg(Color::Green),
                     s if s.contains("Completed") => Style::default().fg(Color::Green),
                     s if s.contains("Failed") => Style::default().fg(Color::Red),
                     s if s.contains("Tool") => Style::default().fg(Color::Cyan),
+                    "Log" => Style::default().fg(Color::Yellow),
                     _ => Style::default().fg(Color::White),
                 };
 
@@ -317,7 +454,12 @@ impl<'a> RunDetailView<'a> {
                     spans.push(Span::styled(details, Style::default().fg(Color::Gray)));
                 }
 
-                ListItem::new(Line::from(spans))
+                let item = ListItem::new(Line::from(spans));
+                if is_focused && selected == Some(index) {
+                    item.style(Style::default().add_modifier(Modifier::REVERSED))
+                } else {
+                    item
+                }
             })
             .collect();
 
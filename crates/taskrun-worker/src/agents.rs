@@ -0,0 +1,692 @@
+//! Agents a worker serves, configured per agent.
+//!
+//! By default a worker serves the one `--agent`. Agent definitions give it
+//! several, each advertised over the same connection and run with its own
+//! system prompt, prompt template, input schema, model and tools. They come
+//! from `--agents-file`, a JSON file listing them:
+//!
+//! ```json
+//! {
+//!   "agents": [
+//!     { "name": "general" },
+//!     {
+//!       "name": "support_triage",
+//!       "description": "Classifies and triages support tickets",
+//!       "prompt_template": "Triage this support ticket:\n\n{input}",
+//!       "model": "haiku",
+//!       "allowed_tools": ["Read", "Grep"]
+//!     }
+//!   ]
+//! }
+//! ```
+//!
+//! or from `--agents-dir`, a directory with one YAML (`.yaml`, `.yml`) or TOML
+//! (`.toml`) file per agent, named after the file unless it sets `name`:
+//!
+//! ```yaml
+//! # agents/support_triage.yaml
+//! description: Classifies and triages support tickets
+//! system_prompt: You triage support tickets for the billing team.
+//! prompt_template: "Triage this support ticket:\n\n{input}"
+//! input_schema:
+//!   type: object
+//!   required: [task]
+//!   properties:
+//!     task: { type: string }
+//! model: haiku
+//! allowed_tools: [Read, Grep]
+//! ```
+//!
+//! `artifacts` lists glob patterns, relative to the run's workspace, of files
+//! the agent produces (`reports/*.md`, `out/**/*.png`); with
+//! `changed_artifacts: true` every file it changed in the workspace counts
+//! too. Either way the files are uploaded as artifacts when the run completes.
+//!
+//! `secrets` maps environment variables of the Claude process to secret
+//! names, resolved through `--secrets-provider` before each run (see
+//! [`crate::secrets`]). `allowed_paths` confines the agent's file tools and
+//! shell commands to a list of roots (see [`crate::path_scope`]).
+//!
+//! The directory is re-read when a file in it changes; a set that fails to
+//! load is logged and the current definitions stay in place.
+//!
+//! `{input}` in a template is replaced by the task (the input's `task` field,
+//! or the whole input). Inputs are checked against `input_schema` before the
+//! run starts, for the `type`, `required`, `properties`, `items` and `enum`
+//! keywords of JSON Schema. Settings an agent leaves out fall back to the
+//! command line (`--model`, `--allow-tools`, `--deny-tools`).
+
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock, Weak};
+use std::time::{Duration, SystemTime};
+
+use serde::Deserialize;
+use serde_json::Value;
+use taskrun_core::AgentSpec;
+use thiserror::Error;
+use tokio::sync::watch;
+use tracing::{info, warn};
+
+use crate::artifacts;
+use crate::config;
+use crate::manifest;
+
+/// Placeholder a prompt template puts the task in.
+pub const INPUT_PLACEHOLDER: &str = "{input}";
+
+/// How often the agents directory is checked for changes.
+const WATCH_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Agent definition errors.
+#[derive(Debug, Error)]
+pub enum AgentsError {
+    #[error("Failed to read agents from '{path}': {source}")]
+    Io {
+        path: String,
+        source: std::io::Error,
+    },
+
+    #[error("Invalid agents file: {0}")]
+    Parse(#[from] serde_json::Error),
+
+    #[error("Invalid agent definition '{path}': {source}")]
+    Yaml {
+        path: String,
+        source: serde_yaml::Error,
+    },
+
+    #[error("Invalid agent definition '{path}': {source}")]
+    Toml {
+        path: String,
+        source: toml::de::Error,
+    },
+
+    #[error("Invalid agent definitions: {0}")]
+    Invalid(String),
+}
+
+/// Contents of the agents file.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AgentsFile {
+    pub agents: Vec<AgentDefinition>,
+}
+
+/// One agent, from the agents file or a file in the agents directory.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AgentDefinition {
+    /// Defaults to the file name in the agents directory.
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Replaces Claude Code's system prompt.
+    #[serde(default)]
+    pub system_prompt: Option<String>,
+    /// Prompt with `{input}` where the task goes.
+    #[serde(default)]
+    pub prompt_template: Option<String>,
+    /// JSON Schema the run input must match.
+    #[serde(default)]
+    pub input_schema: Option<Value>,
+    /// Model, as for `--model`.
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub allowed_tools: Option<Vec<String>>,
+    #[serde(default)]
+    pub denied_tools: Option<Vec<String>>,
+    /// Glob patterns of files the agent produces, relative to the workspace.
+    #[serde(default)]
+    pub artifacts: Vec<String>,
+    /// Upload the files a run changed in the workspace.
+    #[serde(default)]
+    pub changed_artifacts: bool,
+    /// Environment variable to the name of the secret it is set to.
+    #[serde(default)]
+    pub secrets: BTreeMap<String, String>,
+    /// Roots the agent's file tools and commands are confined to, relative
+    /// to the workspace.
+    #[serde(default)]
+    pub allowed_paths: Option<Vec<String>>,
+}
+
+/// Settings for what a definition leaves out.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AgentDefaults {
+    pub model_provider: String,
+    pub model_name: String,
+    pub allowed_tools: Option<Vec<String>>,
+    pub denied_tools: Option<Vec<String>>,
+}
+
+/// How the worker runs one agent.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AgentProfile {
+    pub name: String,
+    pub description: Option<String>,
+    pub system_prompt: Option<String>,
+    pub prompt_template: Option<String>,
+    pub input_schema: Option<Value>,
+    pub model_provider: String,
+    pub model_name: String,
+    pub allowed_tools: Option<Vec<String>>,
+    pub denied_tools: Option<Vec<String>>,
+    pub artifacts: Vec<String>,
+    pub changed_artifacts: bool,
+    pub secrets: BTreeMap<String, String>,
+    pub allowed_paths: Option<Vec<String>>,
+}
+
+/// The agent definitions in use, shared by the executors and replaced when
+/// the agents directory changes.
+#[derive(Debug, Clone)]
+pub struct AgentRegistry {
+    inner: Arc<RegistryInner>,
+}
+
+#[derive(Debug)]
+struct RegistryInner {
+    definitions: RwLock<Arc<Vec<AgentDefinition>>>,
+    changes: watch::Sender<()>,
+}
+
+impl AgentsFile {
+    /// Load and check the agents file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, AgentsError> {
+        let json = read(path.as_ref())?;
+        let file: Self = serde_json::from_str(&json)?;
+        validate(&file.agents)?;
+        Ok(file)
+    }
+}
+
+impl AgentDefinition {
+    /// Load one definition from a YAML or TOML file, named after the file
+    /// if it does not say otherwise.
+    pub fn load(path: &Path) -> Result<Self, AgentsError> {
+        let text = read(path)?;
+        let mut definition: Self = if is_toml(path) {
+            toml::from_str(&text).map_err(|source| AgentsError::Toml {
+                path: path.display().to_string(),
+                source,
+            })?
+        } else {
+            serde_yaml::from_str(&text).map_err(|source| AgentsError::Yaml {
+                path: path.display().to_string(),
+                source,
+            })?
+        };
+        if definition.name.is_empty() {
+            if let Some(stem) = path.file_stem() {
+                definition.name = stem.to_string_lossy().into_owned();
+            }
+        }
+        Ok(definition)
+    }
+}
+
+impl AgentProfile {
+    /// How to run `name`, from its definition (if any) and the defaults.
+    pub fn resolve(
+        name: &str,
+        definition: Option<&AgentDefinition>,
+        defaults: &AgentDefaults,
+    ) -> Self {
+        let definition = definition.cloned().unwrap_or_default();
+        let (model_provider, model_name) = match &definition.model {
+            Some(model) => config::parse_model_string(model),
+            None => (defaults.model_provider.clone(), defaults.model_name.clone()),
+        };
+        Self {
+            name: name.to_string(),
+            description: definition.description,
+            system_prompt: definition.system_prompt,
+            prompt_template: definition.prompt_template,
+            input_schema: definition.input_schema,
+            model_provider,
+            model_name,
+            allowed_tools: definition
+                .allowed_tools
+                .or_else(|| defaults.allowed_tools.clone()),
+            denied_tools: definition
+                .denied_tools
+                .or_else(|| defaults.denied_tools.clone()),
+            artifacts: definition.artifacts,
+            changed_artifacts: definition.changed_artifacts,
+            secrets: definition.secrets,
+            allowed_paths: definition.allowed_paths,
+        }
+    }
+
+    /// The specification advertised for this agent.
+    pub fn spec(&self) -> AgentSpec {
+        let spec = manifest::agent_spec(&self.name, &self.model_provider, &self.model_name);
+        match &self.description {
+            Some(description) => spec.with_description(description),
+            None => spec,
+        }
+    }
+
+    /// The prompt for a run of `input_json`: its task put in the template.
+    pub fn prompt(&self, input_json: &str) -> String {
+        let task = task(input_json);
+        match &self.prompt_template {
+            Some(template) => template.replace(INPUT_PLACEHOLDER, &task),
+            None => task,
+        }
+    }
+
+    /// Check `input_json` against the input schema, if the agent has one.
+    pub fn check_input(&self, input_json: &str) -> Result<(), String> {
+        let Some(schema) = &self.input_schema else {
+            return Ok(());
+        };
+        let input: Value = serde_json::from_str(input_json)
+            .map_err(|e| format!("input is not valid JSON: {}", e))?;
+        check_value(schema, &input, "input")
+    }
+
+    /// What the agent adds to the run template beyond its spec, for the
+    /// manifest's template hash.
+    pub fn template(&self) -> Option<String> {
+        if self.system_prompt.is_none()
+            && self.prompt_template.is_none()
+            && self.input_schema.is_none()
+            && self.allowed_paths.is_none()
+        {
+            return None;
+        }
+        let mut template = serde_json::json!({
+            "system_prompt": self.system_prompt,
+            "prompt_template": self.prompt_template,
+            "input_schema": self.input_schema,
+        });
+        if let Some(paths) = &self.allowed_paths {
+            template["allowed_paths"] = serde_json::json!(paths);
+        }
+        Some(template.to_string())
+    }
+}
+
+impl Default for AgentRegistry {
+    fn default() -> Self {
+        Self::new(Vec::new())
+    }
+}
+
+impl AgentRegistry {
+    fn new(definitions: Vec<AgentDefinition>) -> Self {
+        let (changes, _) = watch::channel(());
+        Self {
+            inner: Arc::new(RegistryInner {
+                definitions: RwLock::new(Arc::new(definitions)),
+                changes,
+            }),
+        }
+    }
+
+    /// Definitions from the agents file.
+    pub fn load_file(path: impl AsRef<Path>) -> Result<Self, AgentsError> {
+        Ok(Self::new(AgentsFile::load(path)?.agents))
+    }
+
+    /// Definitions from the agents directory.
+    pub fn load_dir(path: impl AsRef<Path>) -> Result<Self, AgentsError> {
+        Ok(Self::new(load_dir(path.as_ref())?))
+    }
+
+    /// The current definitions.
+    pub fn definitions(&self) -> Arc<Vec<AgentDefinition>> {
+        self.inner
+            .definitions
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// The current definition of `name`.
+    pub fn get(&self, name: &str) -> Option<AgentDefinition> {
+        self.definitions()
+            .iter()
+            .find(|definition| definition.name == name)
+            .cloned()
+    }
+
+    /// Notified whenever the definitions are reloaded.
+    pub fn subscribe(&self) -> watch::Receiver<()> {
+        self.inner.changes.subscribe()
+    }
+
+    /// Re-read `path` when files in it change, while the registry is alive.
+    /// Must be called within a tokio runtime.
+    pub fn watch_dir(&self, path: PathBuf) {
+        let registry = Arc::downgrade(&self.inner);
+        tokio::spawn(async move {
+            info!(path = %path.display(), "Watching agents directory");
+            let mut last_seen = fingerprint(&path);
+            let mut interval = tokio::time::interval(WATCH_INTERVAL);
+            loop {
+                interval.tick().await;
+                let Some(inner) = Weak::upgrade(&registry) else {
+                    return;
+                };
+                let seen = fingerprint(&path);
+                if seen == last_seen {
+                    continue;
+                }
+                last_seen = seen;
+                match load_dir(&path) {
+                    Ok(definitions) => replace(&inner, definitions),
+                    Err(e) => {
+                        warn!(path = %path.display(), error = %e, "Agent definitions reload rejected")
+                    }
+                }
+            }
+        });
+    }
+}
+
+fn replace(inner: &RegistryInner, definitions: Vec<AgentDefinition>) {
+    let names: Vec<String> = definitions.iter().map(|d| d.name.clone()).collect();
+    {
+        let mut current = inner.definitions.write().unwrap_or_else(|e| e.into_inner());
+        if **current == definitions {
+            return;
+        }
+        *current = Arc::new(definitions);
+    }
+    info!(agents = ?names, "Agent definitions reloaded");
+    inner.changes.send_replace(());
+}
+
+/// The task in `input_json`: its `task` field if it is a JSON object with
+/// one, otherwise the input as it is.
+pub fn task(input_json: &str) -> String {
+    serde_json::from_str::<Value>(input_json)
+        .ok()
+        .and_then(|parsed| parsed.get("task")?.as_str().map(str::to_string))
+        .unwrap_or_else(|| input_json.to_string())
+}
+
+fn read(path: &Path) -> Result<String, AgentsError> {
+    std::fs::read_to_string(path).map_err(|source| AgentsError::Io {
+        path: path.display().to_string(),
+        source,
+    })
+}
+
+fn is_toml(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "toml")
+}
+
+fn is_definition(path: &Path) -> bool {
+    path.is_file()
+        && path
+            .extension()
+            .is_some_and(|ext| ext == "yaml" || ext == "yml" || ext == "toml")
+}
+
+/// Definition files in `dir`, in name order.
+fn definition_files(dir: &Path) -> Result<Vec<PathBuf>, AgentsError> {
+    let entries = std::fs::read_dir(dir).map_err(|source| AgentsError::Io {
+        path: dir.display().to_string(),
+        source,
+    })?;
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| is_definition(path))
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+fn load_dir(dir: &Path) -> Result<Vec<AgentDefinition>, AgentsError> {
+    let definitions = definition_files(dir)?
+        .iter()
+        .map(|path| AgentDefinition::load(path))
+        .collect::<Result<Vec<_>, _>>()?;
+    validate(&definitions)?;
+    Ok(definitions)
+}
+
+/// Definition files and when they were last changed.
+fn fingerprint(dir: &Path) -> Vec<(PathBuf, Option<SystemTime>)> {
+    definition_files(dir)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|path| {
+            let modified = std::fs::metadata(&path).and_then(|m| m.modified()).ok();
+            (path, modified)
+        })
+        .collect()
+}
+
+fn validate(definitions: &[AgentDefinition]) -> Result<(), AgentsError> {
+    if definitions.is_empty() {
+        return Err(AgentsError::Invalid("no agents".to_string()));
+    }
+    let mut names = HashSet::new();
+    for agent in definitions {
+        if agent.name.trim().is_empty() {
+            return Err(AgentsError::Invalid("agent without a name".to_string()));
+        }
+        if !names.insert(agent.name.as_str()) {
+            return Err(AgentsError::Invalid(format!(
+                "agent '{}' is declared twice",
+                agent.name
+            )));
+        }
+        if let Some(template) = &agent.prompt_template {
+            if !template.contains(INPUT_PLACEHOLDER) {
+                return Err(AgentsError::Invalid(format!(
+                    "prompt_template of agent '{}' has no {} placeholder",
+                    agent.name, INPUT_PLACEHOLDER
+                )));
+            }
+        }
+        if let Some(schema) = &agent.input_schema {
+            if !schema.is_object() {
+                return Err(AgentsError::Invalid(format!(
+                    "input_schema of agent '{}' is not an object",
+                    agent.name
+                )));
+            }
+        }
+        for pattern in &agent.artifacts {
+            if let Err(e) = artifacts::pattern(pattern) {
+                return Err(AgentsError::Invalid(format!(
+                    "artifact pattern '{}' of agent '{}': {}",
+                    pattern, agent.name, e
+                )));
+            }
+        }
+        for (var, name) in &agent.secrets {
+            if !is_env_var_name(var) {
+                return Err(AgentsError::Invalid(format!(
+                    "secret variable '{}' of agent '{}' is not a valid environment variable name",
+                    var, agent.name
+                )));
+            }
+            if name.trim().is_empty() {
+                return Err(AgentsError::Invalid(format!(
+                    "secret variable '{}' of agent '{}' names no secret",
+                    var, agent.name
+                )));
+            }
+        }
+        if let Some(paths) = &agent.allowed_paths {
+            if paths.is_empty() || paths.iter().any(|p| p.trim().is_empty()) {
+                return Err(AgentsError::Invalid(format!(
+                    "allowed_paths of agent '{}' needs at least one non-empty path",
+                    agent.name
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Whether `name` is a portable environment variable name.
+fn is_env_var_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Check `value` at `path` against `schema`.
+fn check_value(schema: &Value, value: &Value, path: &str) -> Result<(), String> {
+    if let Some(expected) = schema.get("type").and_then(Value::as_str) {
+        if !has_type(value, expected) {
+            return Err(format!("{} must be of type {}", path, expected));
+        }
+    }
+    if let Some(options) = schema.get("enum").and_then(Value::as_array) {
+        if !options.contains(value) {
+            return Err(format!(
+                "{} must be one of {}",
+                path,
+                Value::from(options.clone())
+            ));
+        }
+    }
+    if let Some(object) = value.as_object() {
+        let required = schema.get("required").and_then(Value::as_array);
+        for name in required.into_iter().flatten().filter_map(Value::as_str) {
+            if !object.contains_key(name) {
+                return Err(format!("{} is missing required property '{}'", path, name));
+            }
+        }
+        let properties = schema.get("properties").and_then(Value::as_object);
+        for (name, property) in properties.into_iter().flatten() {
+            if let Some(value) = object.get(name) {
+                check_value(property, value, &format!("{}.{}", path, name))?;
+            }
+        }
+    }
+    if let (Some(items), Some(array)) = (schema.get("items"), value.as_array()) {
+        for (index, item) in array.iter().enumerate() {
+            check_value(items, item, &format!("{}[{}]", path, index))?;
+        }
+    }
+    Ok(())
+}
+
+fn has_type(value: &Value, expected: &str) -> bool {
+    match expected {
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "number" => value.is_number(),
+        "integer" => value.is_i64() || value.is_u64(),
+        "boolean" => value.is_boolean(),
+        "null" => value.is_null(),
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profiles_fall_back_to_defaults() {
+        let file: AgentsFile = serde_json::from_str(
+            r#"{"agents": [
+                {"name": "general"},
+                {"name": "triage", "prompt_template": "Triage:\n{input}",
+                 "model": "haiku", "allowed_tools": ["Read"]}
+            ]}"#,
+        )
+        .unwrap();
+        validate(&file.agents).unwrap();
+        let defaults = AgentDefaults {
+            model_provider: "anthropic".to_string(),
+            model_name: "claude-sonnet-4-5".to_string(),
+            allowed_tools: None,
+            denied_tools: Some(vec!["Bash".to_string()]),
+        };
+
+        let general = AgentProfile::resolve("general", Some(&file.agents[0]), &defaults);
+        assert_eq!(general, AgentProfile::resolve("general", None, &defaults));
+        let triage = AgentProfile::resolve("triage", Some(&file.agents[1]), &defaults);
+        assert_eq!(triage.model_name, "claude-haiku-4-5");
+        assert_eq!(triage.allowed_tools, Some(vec!["Read".to_string()]));
+        assert_eq!(triage.denied_tools, Some(vec!["Bash".to_string()]));
+        assert_eq!(
+            triage.prompt(r#"{"task": "Refund not received"}"#),
+            "Triage:\nRefund not received"
+        );
+        assert_eq!(general.prompt("plain text"), "plain text");
+    }
+
+    #[test]
+    fn test_invalid_agents_rejected() {
+        for json in [
+            r#"{"agents": []}"#,
+            r#"{"agents": [{"name": "a"}, {"name": "a"}]}"#,
+            r#"{"agents": [{"name": "a", "prompt_template": "no placeholder"}]}"#,
+            r#"{"agents": [{"name": "a", "input_schema": "string"}]}"#,
+            r#"{"agents": [{"name": "a", "artifacts": ["../secrets/*"]}]}"#,
+        ] {
+            let file: AgentsFile = serde_json::from_str(json).unwrap();
+            assert!(
+                matches!(validate(&file.agents), Err(AgentsError::Invalid(_))),
+                "{}",
+                json
+            );
+        }
+    }
+
+    #[test]
+    fn test_dir_definitions_and_input_schema() {
+        let dir = std::env::temp_dir().join(format!("taskrun-agents-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("triage.yaml"),
+            "system_prompt: You triage tickets.\n\
+             input_schema:\n  type: object\n  required: [task]\n  properties:\n    \
+             task: { type: string }\n    priority: { enum: [low, high] }\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("review.toml"),
+            "name = \"code_review\"\nmodel = \"opus\"\n",
+        )
+        .unwrap();
+        std::fs::write(dir.join("notes.txt"), "ignored").unwrap();
+
+        let registry = AgentRegistry::load_dir(&dir).unwrap();
+        let names: Vec<_> = registry
+            .definitions()
+            .iter()
+            .map(|d| d.name.clone())
+            .collect();
+        assert_eq!(names, ["code_review", "triage"]);
+
+        let triage = AgentProfile::resolve(
+            "triage",
+            registry.get("triage").as_ref(),
+            &AgentDefaults::default(),
+        );
+        assert_eq!(triage.system_prompt.as_deref(), Some("You triage tickets."));
+        assert!(triage
+            .check_input(r#"{"task": "Refund", "priority": "high"}"#)
+            .is_ok());
+        assert_eq!(
+            triage.check_input(r#"{"priority": "high"}"#).unwrap_err(),
+            "input is missing required property 'task'"
+        );
+        assert_eq!(
+            triage.check_input(r#"{"task": 3}"#).unwrap_err(),
+            "input.task must be of type string"
+        );
+        assert!(triage
+            .check_input(r#"{"task": "x", "priority": "urgent"}"#)
+            .is_err());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,235 @@
+//! Tool-use approvals escalated to the control plane.
+//!
+//! Tools listed in `--ask-tools` are not run on the worker's own authority.
+//! Claude's permission prompt for such a tool is forwarded to the control
+//! plane as a `ToolApprovalRequest`, and the run waits until an operator
+//! approves or denies it there. If nobody decides within the approval
+//! timeout, the configured default applies.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde_json::Value;
+use tokio::sync::{mpsc, oneshot};
+use tracing::{info, warn};
+
+use taskrun_proto::pb::run_client_message::Payload as ClientPayload;
+use taskrun_proto::pb::{RunClientMessage, ToolApprovalDecision, ToolApprovalRequest};
+
+/// Which tools need approval, and what happens when nobody answers.
+#[derive(Debug, Clone)]
+pub struct ApprovalPolicy {
+    /// Tools that need an operator's approval.
+    pub ask_tools: Vec<String>,
+    /// How long to wait for a decision.
+    pub timeout: Duration,
+    /// Decision applied when the timeout passes.
+    pub default_allow: bool,
+}
+
+impl ApprovalPolicy {
+    /// Whether `tool_name` needs an operator's approval.
+    pub fn asks(&self, tool_name: &str) -> bool {
+        self.ask_tools.iter().any(|t| t == tool_name)
+    }
+}
+
+/// An operator's answer (or the default, on timeout).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApprovalOutcome {
+    pub allowed: bool,
+    pub reason: String,
+}
+
+/// Registry of approvals waiting for a decision, keyed by approval ID.
+#[derive(Clone, Default)]
+pub struct PendingApprovals {
+    waiting: Arc<Mutex<HashMap<String, oneshot::Sender<ApprovalOutcome>>>>,
+    next_id: Arc<AtomicU64>,
+}
+
+impl PendingApprovals {
+    /// Create an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a new approval for `run_id` and return its ID and receiver.
+    fn register(&self, run_id: &str) -> (String, oneshot::Receiver<ApprovalOutcome>) {
+        let seq = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let approval_id = format!("{}-{}", run_id, seq);
+        let (tx, rx) = oneshot::channel();
+        self.waiting
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(approval_id.clone(), tx);
+        (approval_id, rx)
+    }
+
+    fn unregister(&self, approval_id: &str) {
+        self.waiting
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(approval_id);
+    }
+
+    /// Deliver a decision from the control plane. Returns false if the
+    /// approval is not waiting (already decided or timed out).
+    pub fn decide(&self, decision: ToolApprovalDecision) -> bool {
+        let waiting = self
+            .waiting
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&decision.approval_id);
+        match waiting {
+            Some(tx) => tx
+                .send(ApprovalOutcome {
+                    allowed: decision.allowed,
+                    reason: decision.reason,
+                })
+                .is_ok(),
+            None => false,
+        }
+    }
+}
+
+/// Asks the control plane about one run's tool uses.
+#[derive(Clone)]
+pub struct ApprovalGate {
+    policy: Arc<ApprovalPolicy>,
+    pending: PendingApprovals,
+    tx: mpsc::Sender<RunClientMessage>,
+    run_id: String,
+}
+
+impl ApprovalGate {
+    /// Create a gate for `run_id` that sends requests on `tx`.
+    pub fn new(
+        policy: Arc<ApprovalPolicy>,
+        pending: PendingApprovals,
+        tx: mpsc::Sender<RunClientMessage>,
+        run_id: impl Into<String>,
+    ) -> Self {
+        Self {
+            policy,
+            pending,
+            tx,
+            run_id: run_id.into(),
+        }
+    }
+
+    /// Whether `tool_name` needs an operator's approval.
+    pub fn asks(&self, tool_name: &str) -> bool {
+        self.policy.asks(tool_name)
+    }
+
+    /// Request approval for a tool use and wait for the decision.
+    pub async fn request(&self, tool_name: &str, input: &Value) -> ApprovalOutcome {
+        let (approval_id, rx) = self.pending.register(&self.run_id);
+        let request = ToolApprovalRequest {
+            approval_id: approval_id.clone(),
+            run_id: self.run_id.clone(),
+            tool_name: tool_name.to_string(),
+            input_json: input.to_string(),
+            timeout_ms: self.policy.timeout.as_millis() as i64,
+            default_allow: self.policy.default_allow,
+        };
+        let msg = RunClientMessage {
+            payload: Some(ClientPayload::ToolApproval(request)),
+        };
+
+        info!(
+            run_id = %self.run_id,
+            approval_id = %approval_id,
+            tool = %tool_name,
+            "Requesting tool approval from control plane"
+        );
+        if self.tx.send(msg).await.is_err() {
+            self.pending.unregister(&approval_id);
+            warn!(approval_id = %approval_id, "Control plane unreachable, applying default");
+            return self.default_outcome("control plane unreachable");
+        }
+
+        match tokio::time::timeout(self.policy.timeout, rx).await {
+            Ok(Ok(outcome)) => outcome,
+            Ok(Err(_)) => self.default_outcome("approval dropped"),
+            Err(_) => {
+                self.pending.unregister(&approval_id);
+                warn!(approval_id = %approval_id, "Tool approval timed out, applying default");
+                self.default_outcome("no decision before timeout")
+            }
+        }
+    }
+
+    fn default_outcome(&self, why: &str) -> ApprovalOutcome {
+        ApprovalOutcome {
+            allowed: self.policy.default_allow,
+            reason: format!(
+                "{} ({} by default)",
+                why,
+                if self.policy.default_allow {
+                    "allowed"
+                } else {
+                    "denied"
+                }
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gate(timeout: Duration) -> (ApprovalGate, mpsc::Receiver<RunClientMessage>) {
+        let policy = Arc::new(ApprovalPolicy {
+            ask_tools: vec!["Bash".to_string()],
+            timeout,
+            default_allow: false,
+        });
+        let (tx, rx) = mpsc::channel(4);
+        (
+            ApprovalGate::new(policy, PendingApprovals::new(), tx, "run-1"),
+            rx,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_decision_reaches_waiting_request() {
+        let (gate, mut rx) = gate(Duration::from_secs(5));
+        assert!(gate.asks("Bash"));
+        assert!(!gate.asks("Read"));
+
+        let pending = gate.pending.clone();
+        let waiter = tokio::spawn(async move {
+            gate.request("Bash", &serde_json::json!({"command": "ls"}))
+                .await
+        });
+
+        let Some(ClientPayload::ToolApproval(request)) = rx.recv().await.unwrap().payload else {
+            panic!("expected a tool approval request");
+        };
+        assert_eq!(request.tool_name, "Bash");
+        assert!(pending.decide(ToolApprovalDecision {
+            approval_id: request.approval_id.clone(),
+            run_id: request.run_id,
+            allowed: true,
+            reason: "looks fine".to_string(),
+            decided_by: "ops".to_string(),
+        }));
+
+        let outcome = waiter.await.unwrap();
+        assert!(outcome.allowed);
+        assert_eq!(outcome.reason, "looks fine");
+    }
+
+    #[tokio::test]
+    async fn test_timeout_applies_default() {
+        let (gate, _rx) = gate(Duration::from_millis(10));
+        let outcome = gate.request("Bash", &Value::Null).await;
+        assert!(!outcome.allowed);
+        assert!(gate.pending.waiting.lock().unwrap().is_empty());
+    }
+}
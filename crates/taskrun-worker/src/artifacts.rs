@@ -0,0 +1,344 @@
+//! Artifact detection for tool calls and workspaces.
+//!
+//! Files an agent writes or a tool saves (screenshots, generated reports)
+//! become artifact references on the run's events and on the assistant chat
+//! message. Paths are collected when a tool is requested and resolved once
+//! the tool has run, so only files that exist are attached. Only file types
+//! with a known artifact MIME type are considered; source edits are not.
+//!
+//! When a run completes, its workspace is searched for the files its agent
+//! declares (`artifacts` patterns) and, with `changed_artifacts`, the files
+//! the run changed: in a git repository the modified and untracked files git
+//! does not ignore, otherwise any file. Both are named by their path in the
+//! workspace and may be of any type.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::SystemTime;
+
+use glob::{MatchOptions, Pattern};
+use serde_json::Value;
+use taskrun_claude_sdk::ToolData;
+use taskrun_core::artifact::mime_type_for;
+use taskrun_core::Artifact;
+use tracing::warn;
+
+/// Input fields of unknown (e.g. MCP) tools that may name an output file.
+const PATH_FIELDS: [&str; 5] = ["file_path", "path", "filename", "output_path", "save_path"];
+
+/// Directories of the workspace never searched for artifacts.
+const SKIPPED_DIRS: [&str; 2] = [".git", ".taskrun"];
+
+/// Most files looked at in a workspace.
+const MAX_SCANNED_FILES: usize = 50_000;
+
+/// Most artifacts taken from a workspace.
+const MAX_WORKSPACE_ARTIFACTS: usize = 100;
+
+/// `*` and `?` stop at `/`; `**` crosses directories.
+const MATCH_OPTIONS: MatchOptions = MatchOptions {
+    case_sensitive: true,
+    require_literal_separator: true,
+    require_literal_leading_dot: false,
+};
+
+/// Tracks artifact candidates across the tool calls of one execution.
+#[derive(Debug)]
+pub struct ArtifactTracker {
+    /// Directory the agent runs in; relative tool paths are resolved
+    /// against it.
+    working_dir: PathBuf,
+    /// Paths from tool calls whose results have not arrived yet.
+    pending: Vec<PathBuf>,
+    /// Artifacts found so far, in discovery order (one per URI).
+    found: Vec<Artifact>,
+}
+
+impl ArtifactTracker {
+    /// Create a tracker for an execution in `working_dir`.
+    pub fn new(working_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            working_dir: working_dir.into(),
+            pending: Vec::new(),
+            found: Vec::new(),
+        }
+    }
+
+    /// Record the artifact candidates of a requested tool call.
+    pub fn on_tool_use(&mut self, tool: &ToolData) {
+        self.pending.extend(candidate_paths(tool));
+    }
+
+    /// Resolve pending candidates after a tool result. Returns the artifacts
+    /// found for it; a failed tool produces none.
+    pub fn on_tool_result(&mut self, is_error: bool) -> Vec<Artifact> {
+        if is_error {
+            self.pending.clear();
+            return Vec::new();
+        }
+        self.resolve()
+    }
+
+    /// Resolve any remaining candidates. Returns `(unreported, all)`: the
+    /// artifacts not yet returned by `on_tool_result`, and every artifact of
+    /// the execution.
+    pub fn finish(&mut self) -> (Vec<Artifact>, Vec<Artifact>) {
+        let late = self.resolve();
+        (late, self.found.clone())
+    }
+
+    fn resolve(&mut self) -> Vec<Artifact> {
+        let mut resolved = Vec::new();
+        for path in std::mem::take(&mut self.pending) {
+            // The same call may be reported as a message and as content
+            let Some(artifact) = artifact_for(&self.working_dir.join(path)) else {
+                continue;
+            };
+            if resolved.iter().any(|a: &Artifact| a.uri == artifact.uri) {
+                continue;
+            }
+            match self.found.iter_mut().find(|a| a.uri == artifact.uri) {
+                // Rewritten file: keep one entry with the latest size
+                Some(existing) => *existing = artifact.clone(),
+                None => self.found.push(artifact.clone()),
+            }
+            resolved.push(artifact);
+        }
+        resolved
+    }
+}
+
+/// Output file paths named by a tool call.
+fn candidate_paths(tool: &ToolData) -> Vec<PathBuf> {
+    let paths: Vec<&str> = match tool {
+        ToolData::Write { file_path, .. } => vec![file_path.as_str()],
+        ToolData::Unknown(data) => match data.get("input") {
+            Some(Value::Object(input)) => PATH_FIELDS
+                .iter()
+                .filter_map(|field| input.get(*field).and_then(Value::as_str))
+                .collect(),
+            _ => Vec::new(),
+        },
+        _ => Vec::new(),
+    };
+
+    paths
+        .into_iter()
+        .filter(|p| mime_type_for(p).is_some())
+        .map(PathBuf::from)
+        .collect()
+}
+
+/// Artifact reference for an existing file.
+fn artifact_for(path: &Path) -> Option<Artifact> {
+    let metadata = std::fs::metadata(path).ok().filter(|m| m.is_file())?;
+    let name = path.file_name()?.to_string_lossy().into_owned();
+    let mime_type = mime_type_for(&name)?;
+
+    Some(
+        Artifact::new(name, format!("file://{}", path.display()))
+            .with_size(metadata.len())
+            .with_mime_type(mime_type),
+    )
+}
+
+/// Parse an artifact pattern, which must stay within the workspace.
+pub fn pattern(pattern: &str) -> Result<Pattern, String> {
+    let path = Path::new(pattern);
+    if path.is_absolute() || path.components().any(|c| c.as_os_str() == "..") {
+        return Err("must be relative to the workspace".to_string());
+    }
+    Pattern::new(pattern).map_err(|e| e.to_string())
+}
+
+/// Artifacts in `workspace` at the end of a run: files matching `patterns`,
+/// and files changed since `changed_since` if given.
+pub fn workspace_artifacts(
+    workspace: &Path,
+    patterns: &[String],
+    changed_since: Option<SystemTime>,
+) -> Vec<Artifact> {
+    let patterns: Vec<Pattern> = patterns.iter().filter_map(|p| pattern(p).ok()).collect();
+    if patterns.is_empty() && changed_since.is_none() {
+        return Vec::new();
+    }
+    let Ok(workspace) = workspace.canonicalize() else {
+        return Vec::new();
+    };
+    let changed_in_git = changed_since.and_then(|_| git_changed_files(&workspace));
+
+    let mut files = Vec::new();
+    collect_files(&workspace, Path::new(""), &mut files);
+    if files.len() >= MAX_SCANNED_FILES {
+        warn!(workspace = %workspace.display(), max = MAX_SCANNED_FILES, "Workspace too large to search fully for artifacts");
+    }
+
+    let mut artifacts = Vec::new();
+    for relative in files {
+        let name = relative.to_string_lossy().replace('\\', "/");
+        let declared = patterns
+            .iter()
+            .any(|p| p.matches_with(&name, MATCH_OPTIONS));
+        let path = workspace.join(&relative);
+        let Ok(metadata) = std::fs::metadata(&path) else {
+            continue;
+        };
+        let changed = changed_since.is_some_and(|since| {
+            let candidate = changed_in_git
+                .as_ref()
+                .map_or(true, |changed| changed.contains(&name));
+            candidate && metadata.modified().is_ok_and(|modified| modified >= since)
+        });
+        if !declared && !changed {
+            continue;
+        }
+        if artifacts.len() == MAX_WORKSPACE_ARTIFACTS {
+            warn!(workspace = %workspace.display(), max = MAX_WORKSPACE_ARTIFACTS, "Too many workspace artifacts, skipping the rest");
+            break;
+        }
+        let mut artifact = Artifact::new(name.as_str(), format!("file://{}", path.display()))
+            .with_size(metadata.len());
+        if let Some(mime_type) = mime_type_for(&name) {
+            artifact = artifact.with_mime_type(mime_type);
+        }
+        artifacts.push(artifact);
+    }
+    artifacts
+}
+
+/// Files under `root.join(dir)`, relative to `root`, in name order.
+fn collect_files(root: &Path, dir: &Path, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(root.join(dir)) else {
+        return;
+    };
+    let mut entries: Vec<_> = entries.flatten().collect();
+    entries.sort_by_key(|entry| entry.file_name());
+    for entry in entries {
+        if files.len() >= MAX_SCANNED_FILES {
+            return;
+        }
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        let relative = dir.join(entry.file_name());
+        if file_type.is_dir() {
+            if !SKIPPED_DIRS
+                .iter()
+                .any(|skipped| entry.file_name() == *skipped)
+            {
+                collect_files(root, &relative, files);
+            }
+        } else if file_type.is_file() {
+            files.push(relative);
+        }
+    }
+}
+
+/// Modified and untracked files git does not ignore, if `workspace` is in a
+/// git repository. Paths are relative to the workspace.
+fn git_changed_files(workspace: &Path) -> Option<HashSet<String>> {
+    let output = Command::new("git")
+        .args([
+            "ls-files",
+            "-z",
+            "--modified",
+            "--others",
+            "--exclude-standard",
+        ])
+        .current_dir(workspace)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+    Some(
+        output
+            .stdout
+            .split(|&b| b == 0)
+            .filter(|path| !path.is_empty())
+            .map(|path| String::from_utf8_lossy(path).into_owned())
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_written_report_becomes_artifact() {
+        let dir = std::env::temp_dir().join(format!("taskrun-artifacts-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let report = dir.join("report.md");
+        let source = dir.join("main.rs");
+
+        let mut tracker = ArtifactTracker::new("/elsewhere");
+        for path in [&report, &source] {
+            tracker.on_tool_use(&ToolData::Write {
+                file_path: path.display().to_string(),
+                content: "# Report".to_string(),
+            });
+            std::fs::write(path, "# Report").unwrap();
+        }
+
+        let found = tracker.on_tool_result(false);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].name, "report.md");
+        assert_eq!(found[0].size_bytes, Some(8));
+        assert_eq!(found[0].mime_type.as_deref(), Some("text/markdown"));
+
+        // A failed tool call attaches nothing
+        tracker.on_tool_use(&ToolData::Write {
+            file_path: dir.join("missing.png").display().to_string(),
+            content: String::new(),
+        });
+        assert!(tracker.on_tool_result(true).is_empty());
+
+        let (late, all) = tracker.finish();
+        assert!(late.is_empty());
+        assert_eq!(all, found);
+
+        // Relative paths are in the run's working directory
+        let mut tracker = ArtifactTracker::new(&dir);
+        tracker.on_tool_use(&ToolData::Write {
+            file_path: "report.md".to_string(),
+            content: "# Report".to_string(),
+        });
+        let found = tracker.on_tool_result(false);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].uri, format!("file://{}", report.display()));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_declared_and_changed_workspace_files() {
+        let dir = std::env::temp_dir().join(format!("taskrun-workspace-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("out/charts")).unwrap();
+        std::fs::create_dir_all(dir.join(".taskrun")).unwrap();
+        std::fs::write(dir.join("out/summary.csv"), "a,b").unwrap();
+        std::fs::write(dir.join("out/charts/sales.png"), "png").unwrap();
+        std::fs::write(dir.join("notes.txt"), "old").unwrap();
+        // File times come from a coarse clock
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        let started = SystemTime::now();
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        std::fs::write(dir.join("main.rs"), "fn main() {}").unwrap();
+        std::fs::write(dir.join(".taskrun/input.txt"), "staged").unwrap();
+
+        let names = |artifacts: Vec<Artifact>| -> Vec<String> {
+            artifacts.into_iter().map(|a| a.name).collect()
+        };
+        let patterns = ["out/*".to_string()];
+        assert_eq!(
+            names(workspace_artifacts(&dir, &patterns, None)),
+            ["out/summary.csv"]
+        );
+        let patterns = ["out/**/*.png".to_string()];
+        let found = workspace_artifacts(&dir, &patterns, Some(started));
+        assert_eq!(names(found.clone()), ["main.rs", "out/charts/sales.png"]);
+        assert_eq!(found[1].mime_type.as_deref(), Some("image/png"));
+        assert!(pattern("../etc/*").is_err());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,171 @@
+//! Run cancellation tracking.
+//!
+//! Each in-flight run registers here when it starts. A `CancelRun` from the
+//! control plane fires the run's signal; the run drops its executor future,
+//! which kills the Claude Code subprocess, and reports CANCELLED. Runs still
+//! in flight when a shutdown's drain timeout expires are stopped the same way,
+//! but report FAILED with `WORKER_LOST` so the control plane can retry them.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use taskrun_core::FailureReason;
+use taskrun_proto::pb::RunStatus;
+use tokio::sync::oneshot;
+
+/// Why a run was stopped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cancel {
+    pub reason: String,
+    /// Stopped because the worker is shutting down, not cancelled by a user.
+    pub shutdown: bool,
+}
+
+impl Cancel {
+    /// A user cancellation.
+    pub fn new(reason: impl Into<String>) -> Self {
+        Self {
+            reason: reason.into(),
+            shutdown: false,
+        }
+    }
+
+    /// Status and failure reason to report for the stopped run.
+    pub fn outcome(&self) -> (RunStatus, FailureReason) {
+        if self.shutdown {
+            (RunStatus::Failed, FailureReason::WorkerLost)
+        } else {
+            (RunStatus::Cancelled, FailureReason::CancelledByUser)
+        }
+    }
+}
+
+/// Registry of cancel signals for in-flight runs, keyed by run ID.
+#[derive(Clone, Default)]
+pub struct RunCancellations {
+    signals: Arc<Mutex<HashMap<String, oneshot::Sender<Cancel>>>>,
+}
+
+impl RunCancellations {
+    /// Create an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a run and return the receiver that yields why it was stopped.
+    pub fn register(&self, run_id: &str) -> oneshot::Receiver<Cancel> {
+        let (tx, rx) = oneshot::channel();
+        self.signals
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(run_id.to_string(), tx);
+        rx
+    }
+
+    /// Register a run unless one with the same ID is still in flight, like
+    /// an earlier turn of a continued session. Returns None if it is.
+    pub fn try_register(&self, run_id: &str) -> Option<oneshot::Receiver<Cancel>> {
+        let mut signals = self.signals.lock().unwrap_or_else(|e| e.into_inner());
+        if signals.get(run_id).is_some_and(|tx| !tx.is_closed()) {
+            return None;
+        }
+        let (tx, rx) = oneshot::channel();
+        signals.insert(run_id.to_string(), tx);
+        Some(rx)
+    }
+
+    /// Forget a run once it has finished.
+    pub fn unregister(&self, run_id: &str) {
+        self.signals
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(run_id);
+    }
+
+    /// Signal a run to stop. Returns false if the run is not in flight.
+    pub fn cancel(&self, run_id: &str, reason: &str) -> bool {
+        let signal = self
+            .signals
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(run_id);
+        match signal {
+            Some(tx) => tx.send(Cancel::new(reason)).is_ok(),
+            None => false,
+        }
+    }
+
+    /// Stop every run in flight because the worker is shutting down. Returns
+    /// the number of runs signalled.
+    pub fn shutdown_all(&self, reason: &str) -> usize {
+        let signals: Vec<_> = self
+            .signals
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .drain()
+            .collect();
+        signals
+            .into_iter()
+            .filter(|(_, tx)| !tx.is_closed())
+            .map(|(_, tx)| {
+                tx.send(Cancel {
+                    reason: reason.to_string(),
+                    shutdown: true,
+                })
+            })
+            .filter(Result::is_ok)
+            .count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_cancel_delivers_reason_once() {
+        let cancellations = RunCancellations::new();
+        let rx = cancellations.register("run-1");
+
+        assert!(cancellations.cancel("run-1", "user request"));
+        assert_eq!(rx.await.unwrap(), Cancel::new("user request"));
+        assert!(!cancellations.cancel("run-1", "again"));
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_fails_runs_as_worker_lost() {
+        let cancellations = RunCancellations::new();
+        let rx1 = cancellations.register("run-1");
+        let rx2 = cancellations.register("run-2");
+
+        assert_eq!(cancellations.shutdown_all("Worker shut down"), 2);
+        for rx in [rx1, rx2] {
+            let cancel = rx.await.unwrap();
+            assert!(cancel.shutdown);
+            assert_eq!(
+                cancel.outcome(),
+                (RunStatus::Failed, FailureReason::WorkerLost)
+            );
+        }
+        assert!(!cancellations.cancel("run-1", "late"));
+    }
+
+    #[test]
+    fn test_try_register_refuses_a_run_in_flight() {
+        let cancellations = RunCancellations::new();
+        let rx = cancellations.try_register("run-1").unwrap();
+        assert!(cancellations.try_register("run-1").is_none());
+
+        // A finished run that was never unregistered no longer blocks
+        drop(rx);
+        assert!(cancellations.try_register("run-1").is_some());
+    }
+
+    #[test]
+    fn test_unregistered_run_is_not_cancellable() {
+        let cancellations = RunCancellations::new();
+        let _rx = cancellations.register("run-1");
+        cancellations.unregister("run-1");
+        assert!(!cancellations.cancel("run-1", "late"));
+    }
+}
@@ -0,0 +1,269 @@
+//! What the worker advertises to the control plane, and changes to it.
+//!
+//! Agents, labels and `max_concurrent_runs` are sent in `WorkerHello`. With
+//! `--capabilities-file` they can change while connected; the worker sends a
+//! `WorkerUpdate` with what changed instead of reconnecting:
+//!
+//! ```json
+//! {
+//!   "agents": ["general", "support_triage"],
+//!   "labels": { "zone": "eu-1" },
+//!   "max_concurrent_runs": 4
+//! }
+//! ```
+//!
+//! Fields missing from the file fall back to the command line. The file is
+//! read at startup and again when it changes or on SIGHUP; a file that fails
+//! to parse is logged and the current capabilities stay in place. Agents
+//! reloaded from `--agents-dir` are sent the same way.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use serde::Deserialize;
+use thiserror::Error;
+use tokio::sync::watch;
+use tracing::{info, warn};
+
+use taskrun_core::AgentSpec;
+use taskrun_proto::pb;
+
+use crate::agents::AgentProfile;
+use crate::config::Config;
+
+/// How often the capabilities file is checked for changes.
+const WATCH_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Capabilities file errors.
+#[derive(Debug, Error)]
+pub enum CapabilitiesError {
+    #[error("Failed to read capabilities from '{path}': {source}")]
+    Io {
+        path: String,
+        source: std::io::Error,
+    },
+
+    #[error("Invalid capabilities file: {0}")]
+    Parse(#[from] serde_json::Error),
+}
+
+/// Contents of the capabilities file.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CapabilitiesFile {
+    /// Names of the agents to advertise; agents in `--agents-file` keep their
+    /// settings there.
+    #[serde(default)]
+    pub agents: Option<Vec<String>>,
+    #[serde(default)]
+    pub labels: Option<HashMap<String, String>>,
+    #[serde(default)]
+    pub max_concurrent_runs: Option<u32>,
+}
+
+impl CapabilitiesFile {
+    /// Load the capabilities file.
+    pub fn load(path: &Path) -> Result<Self, CapabilitiesError> {
+        let json = std::fs::read_to_string(path).map_err(|source| CapabilitiesError::Io {
+            path: path.display().to_string(),
+            source,
+        })?;
+        Ok(serde_json::from_str(&json)?)
+    }
+}
+
+/// Agents, labels and run limit the worker advertises.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Capabilities {
+    pub agents: Vec<AgentSpec>,
+    pub labels: HashMap<String, String>,
+    pub max_concurrent_runs: u32,
+}
+
+impl Capabilities {
+    /// Capabilities given on the command line.
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            agents: config.agents().iter().map(AgentProfile::spec).collect(),
+            labels: config.labels.iter().cloned().collect(),
+            max_concurrent_runs: config.max_concurrent_runs,
+        }
+    }
+
+    /// These capabilities with the fields set in `file` replaced.
+    pub fn with_file(mut self, config: &Config, file: CapabilitiesFile) -> Self {
+        if let Some(agents) = file.agents {
+            self.agents = agents
+                .iter()
+                .filter(|name| !config.unready_agents.contains(name))
+                .map(|name| agent_spec(config, name))
+                .collect();
+        }
+        if let Some(labels) = file.labels {
+            self.labels = labels;
+        }
+        if let Some(max) = file.max_concurrent_runs {
+            self.max_concurrent_runs = max.max(1);
+        }
+        self
+    }
+
+    /// The update telling the control plane what changed since `previous`,
+    /// or None if nothing did.
+    pub fn update_since(&self, previous: &Capabilities) -> Option<pb::WorkerUpdate> {
+        let update = pb::WorkerUpdate {
+            agents: (self.agents != previous.agents).then(|| pb::WorkerAgents {
+                agents: self.agents.iter().cloned().map(Into::into).collect(),
+            }),
+            labels: (self.labels != previous.labels).then(|| pb::WorkerLabels {
+                labels: self.labels.clone(),
+            }),
+            max_concurrent_runs: (self.max_concurrent_runs != previous.max_concurrent_runs)
+                .then_some(self.max_concurrent_runs),
+        };
+        (update != pb::WorkerUpdate::default()).then_some(update)
+    }
+}
+
+fn agent_spec(config: &Config, name: &str) -> AgentSpec {
+    config.agent(name).spec()
+}
+
+/// Capabilities from `path` (if any) on top of the command line and the
+/// agent definitions, kept current while the returned receiver is alive.
+/// Must be called within a tokio runtime.
+pub fn watch(
+    config: Arc<Config>,
+    path: Option<PathBuf>,
+) -> Result<watch::Receiver<Capabilities>, CapabilitiesError> {
+    let mut file = match &path {
+        Some(path) => CapabilitiesFile::load(path)?,
+        None => CapabilitiesFile::default(),
+    };
+    let initial = Capabilities::from_config(&config).with_file(&config, file.clone());
+    let (tx, rx) = watch::channel(initial);
+    let mut agent_changes = config.agents.subscribe();
+
+    tokio::spawn(async move {
+        if let Some(path) = &path {
+            info!(path = %path.display(), "Watching capabilities file");
+        }
+        let mut hangups = hangup_signals(path.is_some());
+        let mut last_modified = path.as_deref().and_then(modified);
+        let mut interval = tokio::time::interval(WATCH_INTERVAL);
+        loop {
+            let reread = tokio::select! {
+                _ = interval.tick(), if path.is_some() => {
+                    let now = path.as_deref().and_then(modified);
+                    if now == last_modified {
+                        continue;
+                    }
+                    last_modified = now;
+                    true
+                }
+                Some(()) = recv_hangup(&mut hangups) => true,
+                Ok(()) = agent_changes.changed() => false,
+                _ = tx.closed() => return,
+            };
+            if let (true, Some(path)) = (reread, &path) {
+                match CapabilitiesFile::load(path) {
+                    Ok(loaded) => file = loaded,
+                    Err(e) => {
+                        warn!(path = %path.display(), error = %e, "Capabilities reload rejected");
+                        continue;
+                    }
+                }
+            }
+            let capabilities = Capabilities::from_config(&config).with_file(&config, file.clone());
+            tx.send_if_modified(|current| {
+                let changed = *current != capabilities;
+                if changed {
+                    info!(
+                        agents = ?capabilities.agents.iter().map(|a| &a.name).collect::<Vec<_>>(),
+                        labels = ?capabilities.labels,
+                        max_concurrent_runs = capabilities.max_concurrent_runs,
+                        "Capabilities reloaded"
+                    );
+                    *current = capabilities;
+                }
+                changed
+            });
+        }
+    });
+    Ok(rx)
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+#[cfg(unix)]
+type Hangups = Option<tokio::signal::unix::Signal>;
+#[cfg(not(unix))]
+type Hangups = ();
+
+/// SIGHUP, if `listen`; otherwise SIGHUP keeps its default action.
+#[cfg(unix)]
+fn hangup_signals(listen: bool) -> Hangups {
+    use tokio::signal::unix::{signal, SignalKind};
+    if !listen {
+        return None;
+    }
+    match signal(SignalKind::hangup()) {
+        Ok(signals) => Some(signals),
+        Err(e) => {
+            warn!(error = %e, "Failed to listen for SIGHUP");
+            None
+        }
+    }
+}
+
+#[cfg(not(unix))]
+fn hangup_signals(_: bool) -> Hangups {}
+
+#[cfg(unix)]
+async fn recv_hangup(hangups: &mut Hangups) -> Option<()> {
+    match hangups {
+        Some(signals) => signals.recv().await,
+        None => std::future::pending().await,
+    }
+}
+
+#[cfg(not(unix))]
+async fn recv_hangup(_: &mut Hangups) -> Option<()> {
+    std::future::pending().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_update_carries_only_what_changed() {
+        let before = Capabilities {
+            agents: vec![AgentSpec::new("general")],
+            labels: HashMap::from([("zone".to_string(), "a".to_string())]),
+            max_concurrent_runs: 2,
+        };
+        assert!(before.update_since(&before).is_none());
+
+        let after = Capabilities {
+            max_concurrent_runs: 4,
+            ..before.clone()
+        };
+        let update = after.update_since(&before).unwrap();
+        assert_eq!(update.max_concurrent_runs, Some(4));
+        assert!(update.agents.is_none());
+        assert!(update.labels.is_none());
+
+        let after = Capabilities {
+            agents: vec![AgentSpec::new("general"), AgentSpec::new("reviewer")],
+            ..before.clone()
+        };
+        let update = after.update_since(&before).unwrap();
+        assert_eq!(update.agents.unwrap().agents.len(), 2);
+        assert!(update.max_concurrent_runs.is_none());
+    }
+}
@@ -0,0 +1,165 @@
+//! Startup check of the Claude Code CLI, one of the pre-flight checks.
+//!
+//! Without it a worker with a missing or logged-out `claude` binary connects
+//! and accepts tasks, and only the first run fails. The check resolves the
+//! binary (a path, or a name looked up on `PATH`), runs `claude --version`,
+//! and looks for credentials: an `ANTHROPIC_API_KEY` or
+//! `CLAUDE_CODE_OAUTH_TOKEN`, or a login stored under the Claude config
+//! directory. Credentials kept only in a system keychain are not visible to
+//! it; `--skip-claude-check` bypasses the check for such setups.
+
+use std::env;
+use std::path::{Path, PathBuf};
+
+use thiserror::Error;
+
+use crate::manifest;
+
+/// Environment variables that authenticate the CLI on their own.
+const AUTH_ENV_VARS: &[&str] = &["ANTHROPIC_API_KEY", "CLAUDE_CODE_OAUTH_TOKEN"];
+
+/// A usable Claude Code CLI.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClaudeCli {
+    pub path: PathBuf,
+    pub version: String,
+}
+
+/// Why the Claude Code CLI can't be used.
+#[derive(Debug, Error)]
+pub enum ClaudeCheckError {
+    #[error("Claude Code CLI '{0}' not found")]
+    NotFound(String),
+
+    #[error("Claude Code CLI at {} is not executable", .0.display())]
+    NotExecutable(PathBuf),
+
+    #[error("'{} --version' failed", .0.display())]
+    VersionFailed(PathBuf),
+
+    #[error("Claude Code CLI is not logged in")]
+    NotAuthenticated,
+}
+
+impl ClaudeCheckError {
+    /// Steps that fix the problem, for the user.
+    pub fn remediation(&self) -> &'static [&'static str] {
+        match self {
+            ClaudeCheckError::NotFound(_) => &[
+                "Install Claude Code: npm install -g @anthropic-ai/claude-code",
+                "Or pass the binary's location with --claude-path",
+            ],
+            ClaudeCheckError::NotExecutable(_) => &[
+                "Make the binary executable (chmod +x), or reinstall Claude Code",
+                "Or pass another binary with --claude-path",
+            ],
+            ClaudeCheckError::VersionFailed(_) => &[
+                "Run 'claude --version' to see the error",
+                "Reinstall or update Claude Code: npm install -g @anthropic-ai/claude-code",
+            ],
+            ClaudeCheckError::NotAuthenticated => &[
+                "Run 'claude' once and log in, or set ANTHROPIC_API_KEY",
+                "If the login is in the system keychain, pass --skip-claude-check",
+            ],
+        }
+    }
+}
+
+/// Check that `claude_path` resolves to a working, logged-in CLI.
+pub fn check(claude_path: &str) -> Result<ClaudeCli, ClaudeCheckError> {
+    let path = resolve(claude_path, env::var_os("PATH").as_deref())
+        .ok_or_else(|| ClaudeCheckError::NotFound(claude_path.to_string()))?;
+    if !is_executable(&path) {
+        return Err(ClaudeCheckError::NotExecutable(path));
+    }
+    let version = manifest::detect_claude_version(&path.to_string_lossy())
+        .ok_or_else(|| ClaudeCheckError::VersionFailed(path.clone()))?;
+    if !has_credentials() {
+        return Err(ClaudeCheckError::NotAuthenticated);
+    }
+    Ok(ClaudeCli { path, version })
+}
+
+/// Resolve a binary the way a shell would: a name without a separator is
+/// looked up on `path_var`.
+pub fn resolve(claude_path: &str, path_var: Option<&std::ffi::OsStr>) -> Option<PathBuf> {
+    let candidate = Path::new(claude_path);
+    if candidate.components().count() > 1 {
+        return candidate.is_file().then(|| candidate.to_path_buf());
+    }
+    env::split_paths(path_var?)
+        .map(|dir| dir.join(claude_path))
+        .find(|path| path.is_file())
+}
+
+#[cfg(unix)]
+pub fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata()
+        .map(|m| m.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+#[cfg(not(unix))]
+pub fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+/// Whether the CLI has credentials it can find without a keychain.
+fn has_credentials() -> bool {
+    if AUTH_ENV_VARS
+        .iter()
+        .any(|var| env::var(var).is_ok_and(|v| !v.is_empty()))
+    {
+        return true;
+    }
+    let Some(home) = env::var_os("HOME").map(PathBuf::from) else {
+        return false;
+    };
+    let config_dir = env::var_os("CLAUDE_CONFIG_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| home.join(".claude"));
+    if config_dir.join(".credentials.json").is_file() {
+        return true;
+    }
+    // A login records the account in ~/.claude.json
+    std::fs::read_to_string(home.join(".claude.json"))
+        .is_ok_and(|config| config.contains("\"oauthAccount\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolves_names_on_path_and_paths_as_given() {
+        let dir = env::temp_dir().join(format!("taskrun-claude-check-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let binary = dir.join("claude");
+        std::fs::write(&binary, "").unwrap();
+        let path_var = env::join_paths([Path::new("/nonexistent"), dir.as_path()]).unwrap();
+
+        assert_eq!(resolve("claude", Some(&path_var)), Some(binary.clone()));
+        assert_eq!(resolve("claude", None), None);
+        assert_eq!(resolve("missing", Some(&path_var)), None);
+        assert_eq!(
+            resolve(binary.to_str().unwrap(), None),
+            Some(binary.clone())
+        );
+        // Not executable yet
+        assert!(matches!(
+            check(binary.to_str().unwrap()),
+            Err(ClaudeCheckError::NotExecutable(_)) | Err(ClaudeCheckError::VersionFailed(_))
+        ));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_errors_list_remediation_steps() {
+        let error = ClaudeCheckError::NotFound("claude".to_string());
+        assert_eq!(error.to_string(), "Claude Code CLI 'claude' not found");
+        assert!(error.remediation()[0].starts_with("Install"));
+        assert!(error.remediation()[1].contains("--claude-path"));
+    }
+}
@@ -1,14 +1,46 @@
 //! Worker configuration.
 
-use clap::Parser;
-use taskrun_core::WorkerId;
+use std::sync::Arc;
+use std::time::Duration;
+
+use clap::{Args, Parser, Subcommand};
+use taskrun_claude_sdk::{ResourceLimits, Sandbox, SandboxRuntime};
+use taskrun_core::{RunLimits, WorkerId};
+
+use crate::agents::{AgentDefaults, AgentProfile, AgentRegistry};
+use crate::approvals::ApprovalPolicy;
+use crate::journal::RunJournal;
+use crate::key_store::{self, KeyPassphrase};
+use crate::metrics::{HeartbeatMetric, RunStats};
+use crate::output_filter::OutputFilters;
+use crate::reconnect::ReconnectPolicy;
+use crate::resource_limits::{self, ResourceLimitConfig};
+use crate::run_logs::RunLogs;
+use crate::secrets::{Secrets, SecretsBackend};
+use crate::spool::OutputSpool;
+use crate::worktrees::Worktrees;
 
 /// CLI arguments for the worker.
 #[derive(Parser)]
 #[command(name = "taskrun-worker")]
 #[command(about = "TaskRun Worker - connects to control plane and executes agent tasks")]
 #[command(version)]
+#[command(args_override_self = true)]
 pub struct Cli {
+    /// Worker config file with named profiles (default:
+    /// ~/.config/taskrun/worker.toml, if it exists)
+    #[arg(
+        long,
+        env = "TASKRUN_WORKER_CONFIG",
+        value_name = "FILE",
+        global = true
+    )]
+    pub config: Option<String>,
+
+    /// Profile of the config file to apply; command line flags override it
+    #[arg(long, env = "TASKRUN_WORKER_PROFILE", global = true)]
+    pub profile: Option<String>,
+
     /// Run in headless mode (daemon without TUI)
     #[arg(long)]
     pub headless: bool,
@@ -18,11 +50,28 @@ pub struct Cli {
     pub json: bool,
 
     /// Agent name to run (e.g., general, support_triage)
-    #[arg(short, long, default_value = "general")]
+    #[arg(short, long, default_value = "general", global = true)]
     pub agent: String,
 
+    /// Namespace the worker serves (only tasks in this namespace are assigned to it)
+    #[arg(long, default_value = "default")]
+    pub namespace: String,
+
+    /// Worker label as key=value, e.g. region=eu-west (repeatable; shown in the server's Fleet view)
+    #[arg(
+        long = "label",
+        env = "TASKRUN_WORKER_LABELS",
+        value_delimiter = ',',
+        value_parser = parse_label
+    )]
+    pub labels: Vec<(String, String)>,
+
+    /// Do not add detected labels (os, arch, hostname, claude_version)
+    #[arg(long)]
+    pub no_detect_labels: bool,
+
     /// Model to use (e.g., claude-opus-4-5, claude-sonnet-4-5, claude-haiku-4-5)
-    #[arg(short, long, default_value = "claude-sonnet-4-5")]
+    #[arg(short, long, default_value = "claude-sonnet-4-5", global = true)]
     pub model: String,
 
     /// Control plane gRPC endpoint
@@ -41,29 +90,273 @@ pub struct Cli {
     #[arg(long, default_value = "certs/worker.key")]
     pub client_key: String,
 
+    /// Bootstrap token to enroll with when there is no client certificate
+    /// yet (from `taskrun token create`); the issued certificate is stored
+    /// at --client-cert and --client-key
+    #[arg(long, env = "TASKRUN_ENROLL_TOKEN", hide_env_values = true)]
+    pub enroll_token: Option<String>,
+
+    /// Control plane HTTP address to enroll at
+    #[arg(long, default_value = "http://[::1]:50052")]
+    pub enroll_url: String,
+
     /// Tools to allow (comma-separated, e.g., "Read,Write,Bash")
-    #[arg(long)]
+    #[arg(long, global = true)]
     pub allow_tools: Option<String>,
 
     /// Tools to deny (comma-separated, e.g., "WebSearch,Bash")
-    #[arg(long)]
+    #[arg(long, global = true)]
     pub deny_tools: Option<String>,
 
+    /// Tools that need an operator's approval from the control plane (comma-separated)
+    #[arg(long)]
+    pub ask_tools: Option<String>,
+
+    /// Seconds to wait for an approval decision before applying the default
+    #[arg(long, default_value = "300")]
+    pub approval_timeout: u64,
+
+    /// Decision applied when an approval times out
+    #[arg(long, default_value = "deny", value_parser = ["allow", "deny"])]
+    pub approval_default: String,
+
     /// Log level (e.g., info, debug, warn)
-    #[arg(long, default_value = "info")]
+    #[arg(long, default_value = "info", global = true)]
     pub log_level: String,
 
     /// Heartbeat interval in seconds
     #[arg(long, default_value = "15")]
     pub heartbeat_interval: u64,
 
+    /// Metrics to send with heartbeats (headless and JSON modes)
+    #[arg(
+        long,
+        value_enum,
+        value_delimiter = ',',
+        default_value = "load,memory,disk,runs"
+    )]
+    pub heartbeat_metrics: Vec<HeartbeatMetric>,
+
+    /// Send heartbeats without metrics
+    #[arg(long, conflicts_with = "heartbeat_metrics")]
+    pub no_heartbeat_metrics: bool,
+
+    /// Seconds to wait for active runs to finish on SIGTERM/SIGINT before
+    /// failing them (headless and JSON modes)
+    #[arg(long, default_value = "300")]
+    pub shutdown_timeout: u64,
+
+    /// Seconds to wait before reconnecting after the first failure; doubles
+    /// with each failed attempt
+    #[arg(long, value_name = "SECS", default_value = "1")]
+    pub reconnect_initial_secs: u64,
+
+    /// Longest wait between reconnect attempts, in seconds
+    #[arg(long, value_name = "SECS", default_value = "60")]
+    pub reconnect_max_secs: u64,
+
+    /// Give up after this many failed reconnect attempts in a row (default:
+    /// retry forever)
+    #[arg(long, value_name = "N")]
+    pub reconnect_max_retries: Option<u32>,
+
     /// Maximum concurrent runs
     #[arg(long, default_value = "10")]
     pub max_concurrent_runs: u32,
 
-    /// Working directory for agent execution (TUI mode)
-    #[arg(short = 'd', long, default_value = ".")]
+    /// Extra run slots reserved for interactive work (streaming chat, continuations)
+    #[arg(long, default_value = "2")]
+    pub interactive_slots: u32,
+
+    /// Working directory for agent execution (TUI mode and `run`)
+    #[arg(short = 'd', long, default_value = ".", global = true)]
     pub working_dir: String,
+
+    /// Output filter config (JSON file with per-agent masks)
+    #[arg(long, global = true)]
+    pub output_filters: Option<String>,
+
+    /// Include Claude's thinking in run output and transcripts
+    #[arg(long, global = true)]
+    pub include_thinking: bool,
+
+    /// Do not give agents the report_progress tool for run milestones
+    #[arg(long, global = true)]
+    pub no_progress_tool: bool,
+
+    /// Time zone of TUI timestamps: utc, local or an IANA name (Europe/Berlin)
+    #[arg(long, default_value = "utc")]
+    pub tz: String,
+
+    /// Date order and clock of TUI timestamps: iso, a locale such as en_US
+    /// or de_DE, or auto to follow LC_TIME/LANG
+    #[arg(long, default_value = "iso")]
+    pub locale: String,
+
+    /// Claude Code CLI binary (a path, or a name looked up on PATH)
+    #[arg(long, default_value = "claude", global = true)]
+    pub claude_path: String,
+
+    /// Start without checking that the Claude Code CLI is installed and logged in
+    #[arg(long, global = true)]
+    pub skip_claude_check: bool,
+
+    /// Resource limits on the Claude process (JSON file with per-agent limits)
+    #[arg(long, global = true)]
+    pub resource_limits: Option<String>,
+
+    /// Where the secrets agent definitions name come from
+    #[arg(
+        long,
+        value_enum,
+        env = "TASKRUN_SECRETS_PROVIDER",
+        default_value = "env",
+        global = true
+    )]
+    pub secrets_provider: SecretsBackend,
+
+    /// Directory of secret files, one per secret (--secrets-provider file)
+    #[arg(long, global = true)]
+    pub secrets_dir: Option<String>,
+
+    /// Vault server address (--secrets-provider vault)
+    #[arg(long, env = "VAULT_ADDR", global = true)]
+    pub vault_addr: Option<String>,
+
+    /// Vault token
+    #[arg(long, env = "VAULT_TOKEN", hide_env_values = true, global = true)]
+    pub vault_token: Option<String>,
+
+    /// Mount path of Vault's KV v2 secrets engine
+    #[arg(long, default_value = "secret", global = true)]
+    pub vault_mount: String,
+
+    /// CA certificate (PEM) to trust for the Vault server
+    #[arg(long, env = "VAULT_CACERT", global = true)]
+    pub vault_ca_cert: Option<String>,
+
+    /// AWS region of Secrets Manager (--secrets-provider aws; default: the
+    /// aws CLI's)
+    #[arg(long, env = "AWS_REGION", global = true)]
+    pub aws_region: Option<String>,
+
+    /// Run Claude in a container of --sandbox-image with this runtime
+    /// (docker or podman; headless and JSON modes)
+    #[arg(
+        long,
+        value_name = "RUNTIME",
+        requires = "sandbox_image",
+        global = true
+    )]
+    pub sandbox: Option<SandboxRuntime>,
+
+    /// Image of sandbox containers, with the Claude Code CLI installed
+    #[arg(long, value_name = "IMAGE", global = true)]
+    pub sandbox_image: Option<String>,
+
+    /// Agents to serve, each with its own prompt template, model and tools
+    /// (JSON file; replaces --agent in headless and JSON modes)
+    #[arg(long, conflicts_with = "agents_dir", global = true)]
+    pub agents_file: Option<String>,
+
+    /// Directory of agent definitions, one YAML or TOML file per agent
+    /// (re-read when it changes; replaces --agent in headless and JSON modes)
+    #[arg(long, global = true)]
+    pub agents_dir: Option<String>,
+
+    /// Run each assignment in its own git worktree and branch of the
+    /// repository the worker starts in (headless and JSON modes)
+    #[arg(long)]
+    pub worktrees: bool,
+
+    /// Push run branches to this remote when their runs complete
+    #[arg(long, value_name = "REMOTE", requires = "worktrees")]
+    pub push_worktrees: Option<String>,
+
+    /// Keep a journal of runs in this directory, so a restarted worker keeps
+    /// its ID, reports runs it lost as failed and can continue completed
+    /// sessions (headless and JSON modes)
+    #[arg(long, value_name = "DIR")]
+    pub journal_dir: Option<String>,
+
+    /// Spool output the control plane stream does not take to this directory
+    /// and replay it after reconnecting (default: a directory per worker ID
+    /// under the system temp directory; headless and JSON modes)
+    #[arg(long, value_name = "DIR")]
+    pub spool_dir: Option<String>,
+
+    /// Size bound of the output spool in MiB
+    #[arg(long, value_name = "MB", default_value = "64")]
+    pub spool_max_mb: u64,
+
+    /// Write a JSON log file per run to this directory (headless and JSON
+    /// modes)
+    #[arg(long, value_name = "DIR")]
+    pub run_log_dir: Option<String>,
+
+    /// Size in MiB at which a run log file is rotated
+    #[arg(long, value_name = "MB", default_value = "10")]
+    pub run_log_max_mb: u64,
+
+    /// Delete run log files not written to for this many days
+    #[arg(long, value_name = "DAYS", default_value = "7")]
+    pub run_log_retention_days: u64,
+
+    /// Send run log records at this level or more severe to the control
+    /// plane as run events (error, warn, info, debug; headless and JSON modes)
+    #[arg(long, value_name = "LEVEL")]
+    pub ship_run_logs: Option<tracing::Level>,
+
+    /// Agents, labels and run limit to advertise (JSON file, re-read when it
+    /// changes; headless and JSON modes)
+    #[arg(long)]
+    pub capabilities_file: Option<String>,
+
+    /// Run a canned prompt through each agent before connecting and
+    /// advertise the ones that fail as degraded (headless and JSON modes)
+    #[arg(long)]
+    pub smoke_test: bool,
+
+    /// Prompt of the agent smoke test
+    #[arg(long, default_value = crate::smoke_test::DEFAULT_PROMPT)]
+    pub smoke_test_prompt: String,
+
+    /// Seconds an agent has to answer the smoke test
+    #[arg(long, default_value = "60")]
+    pub smoke_test_timeout_secs: u64,
+
+    /// Seconds between smoke tests of degraded agents
+    #[arg(long, default_value = "300")]
+    pub smoke_test_retry_secs: u64,
+
+    /// Export traces of run execution and tool calls to this OTLP gRPC
+    /// endpoint (e.g. http://localhost:4317; headless and JSON modes)
+    #[arg(long)]
+    pub otlp_endpoint: Option<String>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+/// Worker subcommands.
+#[derive(Subcommand)]
+pub enum Command {
+    /// Run one task locally, without a control plane, streaming its output
+    /// to stdout
+    Run(RunArgs),
+}
+
+/// Arguments of `taskrun-worker run`.
+#[derive(Args)]
+pub struct RunArgs {
+    /// Input JSON for the agent
+    #[arg(short, long)]
+    pub input: String,
+
+    /// Directory to write the run's events, transcript and result to
+    /// (default: .taskrun/runs/<run ID> in the working directory)
+    #[arg(short, long, value_name = "DIR")]
+    pub output_dir: Option<String>,
 }
 
 /// Worker configuration.
@@ -77,12 +370,24 @@ pub struct Config {
     /// Heartbeat interval in seconds.
     pub heartbeat_interval_secs: u64,
 
-    /// Reconnection delay on connection loss (seconds).
-    pub reconnect_delay_secs: u64,
+    /// Metrics sent with heartbeats.
+    pub heartbeat_metrics: Vec<HeartbeatMetric>,
+
+    /// Runs finished since the worker started, for heartbeat metrics.
+    pub run_stats: Arc<RunStats>,
+
+    /// Backoff between connection attempts.
+    pub reconnect: ReconnectPolicy,
+
+    /// How long a shutdown waits for active runs to finish (seconds).
+    pub shutdown_timeout_secs: u64,
 
     /// Maximum concurrent runs this worker can handle.
     pub max_concurrent_runs: u32,
 
+    /// Extra run slots reserved for interactive work.
+    pub interactive_slots: u32,
+
     /// Path to CA certificate for verifying control plane (CA pinning).
     pub tls_ca_cert_path: String,
 
@@ -92,6 +397,9 @@ pub struct Config {
     /// Path to worker client private key for mTLS (PEM format).
     pub tls_key_path: String,
 
+    /// Passphrase of an encrypted client key.
+    pub tls_key_passphrase: Option<KeyPassphrase>,
+
     /// Path to Claude Code CLI binary.
     pub claude_path: String,
 
@@ -99,6 +407,19 @@ pub struct Config {
     /// Agent name to advertise and handle.
     pub agent_name: String,
 
+    /// Agent definitions from `--agents-file` or `--agents-dir`; when empty,
+    /// the worker serves `agent_name`.
+    pub agents: AgentRegistry,
+
+    /// Agents the pre-flight checks found unable to run; not advertised.
+    pub unready_agents: Vec<String>,
+
+    /// Namespace the worker serves.
+    pub namespace: String,
+
+    /// Labels advertised to the control plane.
+    pub labels: Vec<(String, String)>,
+
     /// Model provider (e.g., "anthropic").
     pub model_provider: String,
 
@@ -110,6 +431,42 @@ pub struct Config {
 
     /// Tools to deny (if specified).
     pub denied_tools: Option<Vec<String>>,
+
+    /// Tools escalated to the control plane for approval (if specified).
+    pub approval_policy: Option<Arc<ApprovalPolicy>>,
+
+    /// Output filters applied to run output before it is sent.
+    pub output_filters: Option<Arc<OutputFilters>>,
+
+    /// Stream thinking blocks into run output.
+    pub include_thinking: bool,
+
+    /// Give runs the report_progress MCP tool.
+    pub progress_tool: bool,
+
+    /// Limits on the Claude process of each run, per agent.
+    pub resource_limits: Option<Arc<ResourceLimitConfig>>,
+
+    /// Provider of the secrets agents name.
+    pub secrets: Secrets,
+
+    /// Repository runs get worktrees of, with `--worktrees`.
+    pub worktrees: Option<Arc<Worktrees>>,
+
+    /// Run journal, with `--journal-dir`.
+    pub journal: Option<Arc<RunJournal>>,
+
+    /// Spool of output the stream did not take.
+    pub spool: Option<Arc<OutputSpool>>,
+
+    /// Run log files and shipping, with `--run-log-dir` or `--ship-run-logs`.
+    pub run_logs: Option<Arc<RunLogs>>,
+
+    /// Container runtime to run Claude with, with `--sandbox`.
+    pub sandbox: Option<SandboxRuntime>,
+
+    /// Default image of sandbox containers.
+    pub sandbox_image: Option<String>,
 }
 
 impl Config {
@@ -121,19 +478,110 @@ impl Config {
             control_plane_addr: cli.endpoint.clone(),
             worker_id: WorkerId::generate(),
             heartbeat_interval_secs: cli.heartbeat_interval,
-            reconnect_delay_secs: 5,
+            heartbeat_metrics: if cli.no_heartbeat_metrics {
+                Vec::new()
+            } else {
+                cli.heartbeat_metrics.clone()
+            },
+            run_stats: Arc::default(),
+            reconnect: reconnect_policy(cli),
+            shutdown_timeout_secs: cli.shutdown_timeout,
             max_concurrent_runs: cli.max_concurrent_runs,
+            interactive_slots: cli.interactive_slots,
             tls_ca_cert_path: cli.ca_cert.clone(),
             tls_cert_path: cli.client_cert.clone(),
             tls_key_path: cli.client_key.clone(),
-            claude_path: "claude".to_string(),
+            tls_key_passphrase: key_store::passphrase_from_env(),
+            claude_path: cli.claude_path.clone(),
             agent_name: cli.agent.clone(),
+            agents: AgentRegistry::default(),
+            unready_agents: Vec::new(),
+            namespace: cli.namespace.clone(),
+            labels: cli.labels.clone(),
             model_provider: provider,
             model_name: model,
             allowed_tools: cli.allow_tools.as_ref().map(|s| parse_tools(s)),
             denied_tools: cli.deny_tools.as_ref().map(|s| parse_tools(s)),
+            approval_policy: approval_policy(cli),
+            output_filters: None,
+            include_thinking: cli.include_thinking,
+            progress_tool: !cli.no_progress_tool,
+            resource_limits: None,
+            secrets: Secrets::default(),
+            worktrees: None,
+            journal: None,
+            spool: None,
+            run_logs: None,
+            sandbox: cli.sandbox,
+            sandbox_image: cli.sandbox_image.clone(),
+        }
+    }
+
+    /// The agents to advertise.
+    pub fn agents(&self) -> Vec<AgentProfile> {
+        self.configured_agents()
+            .into_iter()
+            .filter(|agent| !self.unready_agents.contains(&agent.name))
+            .collect()
+    }
+
+    /// The agents given on the command line or in definitions, ready or not.
+    pub fn configured_agents(&self) -> Vec<AgentProfile> {
+        let definitions = self.agents.definitions();
+        if definitions.is_empty() {
+            return vec![self.agent(&self.agent_name)];
+        }
+        definitions
+            .iter()
+            .map(|definition| {
+                AgentProfile::resolve(&definition.name, Some(definition), &self.agent_defaults())
+            })
+            .collect()
+    }
+
+    /// How to run `agent_name`: its definition, or the command line settings.
+    pub fn agent(&self, agent_name: &str) -> AgentProfile {
+        AgentProfile::resolve(
+            agent_name,
+            self.agents.get(agent_name).as_ref(),
+            &self.agent_defaults(),
+        )
+    }
+
+    /// Command line settings for what agent definitions leave out.
+    fn agent_defaults(&self) -> AgentDefaults {
+        AgentDefaults {
+            model_provider: self.model_provider.clone(),
+            model_name: self.model_name.clone(),
+            allowed_tools: self.allowed_tools.clone(),
+            denied_tools: self.denied_tools.clone(),
         }
     }
+
+    /// Limits on the Claude process of a run of `agent_name`.
+    pub fn resource_limits(&self, agent_name: &str) -> ResourceLimits {
+        self.resource_limits
+            .as_ref()
+            .map(|limits| limits.for_agent(agent_name))
+            .unwrap_or_default()
+    }
+
+    /// Limits on the Claude process of a run of `agent_name`, tightened by
+    /// its task's `run` limits.
+    pub fn run_resource_limits(&self, agent_name: &str, run: &RunLimits) -> ResourceLimits {
+        resource_limits::with_run_limits(self.resource_limits(agent_name), run)
+    }
+
+    /// Container to run a run of `agent_name` in, with `--sandbox`.
+    pub fn sandbox(&self, agent_name: &str) -> Option<Sandbox> {
+        let runtime = self.sandbox?;
+        let image = self
+            .resource_limits
+            .as_ref()
+            .and_then(|limits| limits.sandbox_image(agent_name))
+            .or(self.sandbox_image.as_deref())?;
+        Some(Sandbox::new(runtime, image))
+    }
 }
 
 /// Parse a model string into (provider, model_name).
@@ -142,7 +590,7 @@ impl Config {
 /// - Full names: "claude-opus-4-5", "claude-sonnet-4-5", "claude-haiku-4-5"
 /// - Short names: "opus", "sonnet", "haiku"
 /// - Provider prefix: "anthropic/claude-opus-4-5"
-fn parse_model_string(model: &str) -> (String, String) {
+pub(crate) fn parse_model_string(model: &str) -> (String, String) {
     // Check for provider prefix
     if let Some((provider, model_name)) = model.split_once('/') {
         return (provider.to_string(), model_name.to_string());
@@ -160,6 +608,39 @@ fn parse_model_string(model: &str) -> (String, String) {
     ("anthropic".to_string(), model_name.to_string())
 }
 
+/// Approval policy from `--ask-tools`, `--approval-timeout` and `--approval-default`.
+pub fn approval_policy(cli: &Cli) -> Option<Arc<ApprovalPolicy>> {
+    let ask_tools = parse_tools(cli.ask_tools.as_deref()?);
+    if ask_tools.is_empty() {
+        return None;
+    }
+    Some(Arc::new(ApprovalPolicy {
+        ask_tools,
+        timeout: Duration::from_secs(cli.approval_timeout),
+        default_allow: cli.approval_default == "allow",
+    }))
+}
+
+/// Reconnect backoff from `--reconnect-initial-secs`, `--reconnect-max-secs`
+/// and `--reconnect-max-retries`.
+pub fn reconnect_policy(cli: &Cli) -> ReconnectPolicy {
+    ReconnectPolicy {
+        initial: Duration::from_secs(cli.reconnect_initial_secs),
+        max: Duration::from_secs(cli.reconnect_max_secs.max(cli.reconnect_initial_secs)),
+        max_retries: cli.reconnect_max_retries,
+    }
+}
+
+/// Parse a `key=value` worker label.
+pub fn parse_label(s: &str) -> Result<(String, String), String> {
+    match s.split_once('=') {
+        Some((key, value)) if !key.trim().is_empty() => {
+            Ok((key.trim().to_string(), value.trim().to_string()))
+        }
+        _ => Err(format!("expected key=value, got '{}'", s)),
+    }
+}
+
 /// Parse a comma-separated list of tools.
 fn parse_tools(tools: &str) -> Vec<String> {
     tools
@@ -175,17 +656,38 @@ impl Default for Config {
             control_plane_addr: "https://[::1]:50051".to_string(),
             worker_id: WorkerId::generate(),
             heartbeat_interval_secs: 15,
-            reconnect_delay_secs: 5,
+            heartbeat_metrics: Vec::new(),
+            run_stats: Arc::default(),
+            reconnect: ReconnectPolicy::default(),
+            shutdown_timeout_secs: 300,
             max_concurrent_runs: 10,
+            interactive_slots: 2,
             tls_ca_cert_path: "certs/ca.crt".to_string(),
             tls_cert_path: "certs/worker.crt".to_string(),
             tls_key_path: "certs/worker.key".to_string(),
+            tls_key_passphrase: None,
             claude_path: "claude".to_string(),
             agent_name: "general".to_string(),
+            agents: AgentRegistry::default(),
+            unready_agents: Vec::new(),
+            namespace: taskrun_core::DEFAULT_NAMESPACE.to_string(),
+            labels: Vec::new(),
             model_provider: "anthropic".to_string(),
             model_name: "claude-sonnet-4-5".to_string(),
             allowed_tools: None,
             denied_tools: None,
+            approval_policy: None,
+            output_filters: None,
+            include_thinking: false,
+            progress_tool: true,
+            resource_limits: None,
+            secrets: Secrets::default(),
+            worktrees: None,
+            journal: None,
+            spool: None,
+            run_logs: None,
+            sandbox: None,
+            sandbox_image: None,
         }
     }
 }
@@ -234,4 +736,18 @@ mod tests {
         let tools = parse_tools("");
         assert!(tools.is_empty());
     }
+
+    #[test]
+    fn test_parse_label() {
+        assert_eq!(
+            parse_label("region = eu-west").unwrap(),
+            ("region".to_string(), "eu-west".to_string())
+        );
+        assert_eq!(
+            parse_label("gpu=").unwrap(),
+            ("gpu".to_string(), String::new())
+        );
+        assert!(parse_label("region").is_err());
+        assert!(parse_label("=eu-west").is_err());
+    }
 }
@@ -0,0 +1,302 @@
+//! Worker config file with named profiles.
+//!
+//! Settings can live in a TOML file (`--config`, by default
+//! `~/.config/taskrun/worker.toml` if it exists) instead of on the command
+//! line. Keys are the long flag names, with `-` or `_`:
+//!
+//! ```toml
+//! # Applied with every profile
+//! [defaults]
+//! ca-cert = "/etc/taskrun/ca.crt"
+//!
+//! [profiles.prod]
+//! endpoint = "https://taskrun.internal:50051"
+//! client-cert = "/etc/taskrun/worker.crt"
+//! client-key = "/etc/taskrun/worker.key"
+//! agents-dir = "/etc/taskrun/agents"
+//! allow-tools = ["Read", "Grep", "Glob"]
+//! label = { region = "eu", gpu = "true" }
+//! headless = true
+//! ```
+//!
+//! `[defaults]` applies on its own, and under the profile chosen with
+//! `--profile`. Settings become flags ahead of the command line, so flags
+//! given on the command line or through their environment variables win;
+//! repeatable flags (`label`) add to the profile's. Arrays of a flag that
+//! takes a comma-separated list are joined, tables become `key=value` pairs.
+
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+use clap::parser::ValueSource;
+use clap::{ArgAction, ArgMatches, CommandFactory, FromArgMatches, Parser};
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::config::Cli;
+
+/// Flags that choose the file and profile, or only make sense on the
+/// command line.
+const COMMAND_LINE_ONLY: &[&str] = &["config", "profile", "help", "version"];
+
+/// Config file errors.
+#[derive(Debug, Error)]
+pub enum ConfigFileError {
+    #[error("Failed to read config file '{path}': {source}")]
+    Read {
+        path: String,
+        source: std::io::Error,
+    },
+
+    #[error("Invalid config file '{path}': {source}")]
+    Parse {
+        path: String,
+        source: toml::de::Error,
+    },
+
+    #[error("No profile '{name}' in '{path}' (profiles: {available})")]
+    UnknownProfile {
+        name: String,
+        path: String,
+        available: String,
+    },
+
+    #[error("--profile {0} given, but there is no config file")]
+    NoFile(String),
+
+    #[error("Unknown setting '{0}' in config file")]
+    UnknownSetting(String),
+
+    #[error("Setting '{key}' in config file: {reason}")]
+    InvalidValue { key: String, reason: String },
+}
+
+/// A parsed config file.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigFile {
+    #[serde(default)]
+    defaults: toml::Table,
+    #[serde(default)]
+    profiles: BTreeMap<String, toml::Table>,
+}
+
+impl ConfigFile {
+    /// Load the config file at `path`.
+    pub fn load(path: &Path) -> Result<Self, ConfigFileError> {
+        let toml = std::fs::read_to_string(path).map_err(|source| ConfigFileError::Read {
+            path: path.display().to_string(),
+            source,
+        })?;
+        toml::from_str(&toml).map_err(|source| ConfigFileError::Parse {
+            path: path.display().to_string(),
+            source,
+        })
+    }
+
+    /// `[defaults]` with `profile`'s settings on top.
+    fn settings(&self, profile: Option<&str>, path: &Path) -> Result<toml::Table, ConfigFileError> {
+        let mut settings = self.defaults.clone();
+        if let Some(name) = profile {
+            let overrides =
+                self.profiles
+                    .get(name)
+                    .ok_or_else(|| ConfigFileError::UnknownProfile {
+                        name: name.to_string(),
+                        path: path.display().to_string(),
+                        available: self.profiles.keys().cloned().collect::<Vec<_>>().join(", "),
+                    })?;
+            settings.extend(overrides.clone());
+        }
+        Ok(settings)
+    }
+}
+
+/// `~/.config/taskrun/worker.toml`, under `$XDG_CONFIG_HOME` if set.
+pub fn default_path() -> Option<PathBuf> {
+    let config_home = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(config_home.join("taskrun").join("worker.toml"))
+}
+
+/// Parse the command line, with the config file's settings for the chosen
+/// profile under it.
+pub fn parse_cli() -> Result<Cli, ConfigFileError> {
+    let args: Vec<OsString> = std::env::args_os().collect();
+    let matches = Cli::command().get_matches_from(&args);
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+
+    let path = match &cli.config {
+        Some(path) => PathBuf::from(path),
+        None => match default_path().filter(|path| path.is_file()) {
+            Some(path) => path,
+            None => match cli.profile {
+                Some(name) => return Err(ConfigFileError::NoFile(name)),
+                None => return Ok(cli),
+            },
+        },
+    };
+    let settings = ConfigFile::load(&path)?.settings(cli.profile.as_deref(), &path)?;
+    let settings = settings_args(&settings, &matches)?;
+    if settings.is_empty() {
+        return Ok(cli);
+    }
+
+    // Ahead of the command line, so its flags override the file's
+    let mut args = args.into_iter();
+    let full: Vec<OsString> = args
+        .next()
+        .into_iter()
+        .chain(settings)
+        .chain(args)
+        .collect();
+    Ok(Cli::parse_from(full))
+}
+
+/// Flags for `settings`, leaving out the ones set through their
+/// environment variables.
+fn settings_args(
+    settings: &toml::Table,
+    matches: &ArgMatches,
+) -> Result<Vec<OsString>, ConfigFileError> {
+    let command = Cli::command();
+    let mut args = Vec::new();
+    for (key, value) in settings {
+        let long = key.replace('_', "-");
+        let arg = command
+            .get_arguments()
+            .find(|arg| {
+                arg.get_long() == Some(long.as_str())
+                    && !COMMAND_LINE_ONLY.contains(&arg.get_id().as_str())
+            })
+            .ok_or_else(|| ConfigFileError::UnknownSetting(key.clone()))?;
+        if matches.value_source(arg.get_id().as_str()) == Some(ValueSource::EnvVariable) {
+            continue;
+        }
+
+        let flag = OsString::from(format!("--{}", long));
+        let invalid = |reason: &str| ConfigFileError::InvalidValue {
+            key: key.clone(),
+            reason: reason.to_string(),
+        };
+        let repeatable = matches!(arg.get_action(), ArgAction::Append);
+        match (arg.get_action(), value) {
+            (ArgAction::SetTrue, toml::Value::Boolean(set)) => {
+                if *set {
+                    args.push(flag);
+                }
+            }
+            (ArgAction::SetTrue, _) => return Err(invalid("expected true or false")),
+            (_, toml::Value::Array(items)) if repeatable => {
+                for item in items {
+                    args.push(flag.clone());
+                    args.push(scalar(item).ok_or_else(|| invalid("expected a list of values"))?);
+                }
+            }
+            (_, toml::Value::Array(items)) => {
+                let items: Option<Vec<OsString>> = items.iter().map(scalar).collect();
+                let items = items.ok_or_else(|| invalid("expected a list of values"))?;
+                args.push(flag);
+                args.push(items.join(",".as_ref()));
+            }
+            (_, toml::Value::Table(pairs)) if repeatable => {
+                for (name, value) in pairs {
+                    let value =
+                        scalar(value).ok_or_else(|| invalid("expected a table of values"))?;
+                    let mut pair = OsString::from(format!("{}=", name));
+                    pair.push(value);
+                    args.push(flag.clone());
+                    args.push(pair);
+                }
+            }
+            (_, value) => {
+                args.push(flag);
+                args.push(scalar(value).ok_or_else(|| invalid("expected a single value"))?);
+            }
+        }
+    }
+    Ok(args)
+}
+
+/// A string, number or boolean as a flag value.
+fn scalar(value: &toml::Value) -> Option<OsString> {
+    let value = match value {
+        toml::Value::String(s) => s.clone(),
+        toml::Value::Integer(n) => n.to_string(),
+        toml::Value::Float(n) => n.to_string(),
+        toml::Value::Boolean(b) => b.to_string(),
+        _ => return None,
+    };
+    Some(value.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FILE: &str = r#"
+        [defaults]
+        endpoint = "https://defaults:50051"
+        max_concurrent_runs = 4
+
+        [profiles.prod]
+        endpoint = "https://prod:50051"
+        allow-tools = ["Read", "Grep"]
+        label = { region = "eu" }
+        headless = true
+    "#;
+
+    fn parse(profile: Option<&str>, command_line: &[&str]) -> Cli {
+        let file: ConfigFile = toml::from_str(FILE).unwrap();
+        let settings = file.settings(profile, Path::new("worker.toml")).unwrap();
+        let argv: Vec<&str> = std::iter::once("taskrun-worker")
+            .chain(command_line.iter().copied())
+            .collect();
+        let matches = Cli::command().get_matches_from(&argv);
+        let settings = settings_args(&settings, &matches).unwrap();
+        let full: Vec<OsString> = std::iter::once(OsString::from("taskrun-worker"))
+            .chain(settings)
+            .chain(command_line.iter().map(OsString::from))
+            .collect();
+        Cli::parse_from(full)
+    }
+
+    #[test]
+    fn test_profile_settings_sit_under_command_line_flags() {
+        let cli = parse(None, &[]);
+        assert_eq!(cli.endpoint, "https://defaults:50051");
+        assert_eq!(cli.max_concurrent_runs, 4);
+        assert!(!cli.headless);
+
+        let cli = parse(
+            Some("prod"),
+            &["--endpoint", "https://flag:50051", "--label", "gpu=true"],
+        );
+        assert_eq!(cli.endpoint, "https://flag:50051");
+        assert_eq!(cli.max_concurrent_runs, 4);
+        assert_eq!(cli.allow_tools.as_deref(), Some("Read,Grep"));
+        assert!(cli.headless);
+        assert_eq!(
+            cli.labels,
+            [
+                ("region".to_string(), "eu".to_string()),
+                ("gpu".to_string(), "true".to_string())
+            ]
+        );
+
+        let file: ConfigFile = toml::from_str(FILE).unwrap();
+        assert!(matches!(
+            file.settings(Some("staging"), Path::new("worker.toml")),
+            Err(ConfigFileError::UnknownProfile { .. })
+        ));
+        let bad: toml::Table = toml::from_str("no_such_flag = 1").unwrap();
+        let matches = Cli::command().get_matches_from(["taskrun-worker"]);
+        assert!(matches!(
+            settings_args(&bad, &matches),
+            Err(ConfigFileError::UnknownSetting(_))
+        ));
+    }
+}
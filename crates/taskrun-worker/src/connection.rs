@@ -1,66 +1,197 @@
 //! Connection management for the worker.
 
 use std::collections::HashMap;
-use std::sync::atomic::{AtomicU32, Ordering};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, SystemTime};
 
-use tokio::sync::{mpsc, Mutex};
+use tokio::sync::{mpsc, oneshot, watch, Mutex};
 use tokio_stream::wrappers::ReceiverStream;
 use tokio_stream::StreamExt;
 use tonic::transport::{Certificate, Channel, ClientTlsConfig, Identity};
-use tracing::{error, info, warn};
+use tonic::Streaming;
+use tracing::{error, info, info_span, warn, Instrument};
 
-use taskrun_core::{AgentSpec, ModelBackend, RunEvent, RunId, TaskId, WorkerInfo};
+use taskrun_core::{
+    Artifact, FailureReason, RunEvent, RunId, RunLimits, RunManifest, TaskId, TaskPriority,
+    WorkerInfo,
+};
 use taskrun_proto::pb::run_client_message::Payload as ClientPayload;
 use taskrun_proto::pb::run_server_message::Payload as ServerPayload;
 use taskrun_proto::pb::{
-    ChatMessage, ChatRole as ProtoChatRole, ContinueRun, RunAssignment, RunChatMessage,
-    RunClientMessage, RunEvent as ProtoRunEvent, RunOutputChunk, RunStatusUpdate, WorkerHeartbeat,
-    WorkerHello,
+    AssignmentAck, ChatMessage, ChatRole as ProtoChatRole, ContinueRun, RunAssignment,
+    RunChatMessage, RunClientMessage, RunEvent as ProtoRunEvent, RunOutputChunk, RunStatusUpdate,
+    WorkerHeartbeat, WorkerHello,
 };
+use taskrun_proto::trace;
 use taskrun_proto::RunServiceClient;
 
+use crate::cancel::{Cancel, RunCancellations};
+use crate::capabilities::Capabilities;
 use crate::config::Config;
 use crate::executor::ClaudeCodeExecutor;
+use crate::files;
+use crate::journal::{JournalEntry, RunRecord};
 use crate::json_output;
+use crate::key_store::{self, KeyStoreError};
+use crate::labels;
+use crate::metrics;
+use crate::secrets::Redactor;
+use crate::shutdown::{self, Shutdown};
+use crate::spool::OutputSpool;
+use crate::worktrees::{RunWorktree, Worktrees};
+
+/// gRPC metadata key a control plane that is not the leader names it in.
+const LEADER_HEADER: &str = "x-taskrun-leader";
+
+/// How often a drain checks whether the active runs have finished.
+const DRAIN_POLL: Duration = Duration::from_millis(250);
+
+/// How long runs stopped at the end of a drain get to report their status.
+const STOP_GRACE: Duration = Duration::from_secs(10);
+
+/// How long to wait for the control plane to close the stream after we
+/// close ours on shutdown.
+const CLOSE_GRACE: Duration = Duration::from_secs(5);
+
+/// How long a run waits for its spooled output to be replayed before it
+/// reports its status.
+const SPOOL_GRACE: Duration = Duration::from_secs(30);
+
+/// Failure reported for a ContinueRun of a run whose session is unknown,
+/// e.g. because the worker restarted since.
+pub(crate) const NO_SESSION: &str = "No session to continue; the worker no longer has this run";
+
+/// The leader's address, if `err` is a control plane in HA mode refusing us
+/// because it is not the leader.
+pub fn leader_redirect(err: &(dyn std::error::Error + 'static)) -> Option<String> {
+    let mut current = Some(err);
+    while let Some(e) = current {
+        if let Some(status) = e.downcast_ref::<tonic::Status>() {
+            return status
+                .metadata()
+                .get(LEADER_HEADER)
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_string);
+        }
+        current = e.source();
+    }
+    None
+}
 
 /// Session info stored for each run.
 #[derive(Debug, Clone)]
 struct SessionInfo {
     session_id: String,
     task_id: String,
+    agent_name: String,
+    /// The run's worktree, with `--worktrees`.
+    worktree: Option<RunWorktree>,
+}
+
+impl SessionInfo {
+    /// The session a journaled run can be continued in.
+    fn from_journal(entry: JournalEntry) -> Option<(String, Self)> {
+        let session = Self {
+            session_id: entry.session_id?,
+            task_id: entry.task_id,
+            agent_name: entry.agent_name,
+            worktree: entry.worktree,
+        };
+        Some((entry.run_id, session))
+    }
 }
 
 /// Manages connection to the control plane.
 pub struct WorkerConnection {
     config: Arc<Config>,
+    /// Control plane address to connect to (the configured one, or the
+    /// leader another instance pointed us at).
+    endpoint: String,
     outbound_tx: Option<mpsc::Sender<RunClientMessage>>,
     active_run_count: Arc<AtomicU32>,
     executor: Arc<ClaudeCodeExecutor>,
     /// Maps run_id -> session info for session continuation.
     sessions: Arc<Mutex<HashMap<String, SessionInfo>>>,
+    /// Cancel signals for in-flight runs.
+    cancellations: RunCancellations,
+    /// What to advertise; changes are sent as `WorkerUpdate`s.
+    capabilities: watch::Receiver<Capabilities>,
+    /// Shutdown requests; the connection drains and returns on one.
+    shutdown: watch::Receiver<Shutdown>,
+    /// Set while draining: heartbeats say DRAINING and assignments are refused.
+    draining: Arc<AtomicBool>,
+    /// Whether the stream to the control plane came up.
+    connected: bool,
 }
 
 impl WorkerConnection {
     /// Create a new WorkerConnection.
     pub fn new(config: Arc<Config>) -> Self {
         let executor = Arc::new(ClaudeCodeExecutor::new(config.clone()));
+        let (_, capabilities) = watch::channel(Capabilities::from_config(&config));
+        let (_, shutdown) = watch::channel(Shutdown::Running);
+        // Sessions outlive the connection, and with a journal the process
+        let sessions = config
+            .journal
+            .as_ref()
+            .map(|journal| {
+                journal
+                    .sessions()
+                    .into_iter()
+                    .filter_map(SessionInfo::from_journal)
+                    .collect()
+            })
+            .unwrap_or_default();
         Self {
+            endpoint: config.control_plane_addr.clone(),
             config,
             outbound_tx: None,
             active_run_count: Arc::new(AtomicU32::new(0)),
             executor,
-            sessions: Arc::new(Mutex::new(HashMap::new())),
+            sessions: Arc::new(Mutex::new(sessions)),
+            cancellations: RunCancellations::new(),
+            capabilities,
+            shutdown,
+            draining: Arc::new(AtomicBool::new(false)),
+            connected: false,
         }
     }
 
+    /// Drain and return when `shutdown` asks for it.
+    pub fn with_shutdown(mut self, shutdown: watch::Receiver<Shutdown>) -> Self {
+        self.shutdown = shutdown;
+        self
+    }
+
+    /// Advertise `capabilities` instead of the command line ones, following
+    /// their changes while connected.
+    pub fn with_capabilities(mut self, capabilities: watch::Receiver<Capabilities>) -> Self {
+        self.executor
+            .resize_lanes(capabilities.borrow().max_concurrent_runs);
+        self.capabilities = capabilities;
+        self
+    }
+
+    /// Whether the stream to the control plane came up, so the reconnect
+    /// backoff can start over.
+    pub fn was_connected(&self) -> bool {
+        self.connected
+    }
+
+    /// Connect to `endpoint` instead of the configured address.
+    pub fn with_endpoint(mut self, endpoint: String) -> Self {
+        self.endpoint = endpoint;
+        self
+    }
+
     /// Connect to control plane and run the main loop.
     /// Returns on disconnect (caller should handle reconnection).
     pub async fn connect_and_run(
         &mut self,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        info!(addr = %self.config.control_plane_addr, "Connecting to control plane with mTLS");
+        info!(addr = %self.endpoint, "Connecting to control plane with mTLS");
 
         // Load CA certificate for pinned trust
         let ca_cert = std::fs::read(&self.config.tls_ca_cert_path).map_err(|e| {
@@ -77,11 +208,15 @@ impl WorkerConnection {
                 self.config.tls_cert_path, e
             )
         })?;
-        let client_key = std::fs::read(&self.config.tls_key_path).map_err(|e| {
-            format!(
-                "Failed to read worker key from '{}': {}. Run scripts/gen-worker-cert.sh first.",
-                self.config.tls_key_path, e
-            )
+        let client_key = key_store::read_key(
+            &self.config.tls_key_path,
+            self.config.tls_key_passphrase.as_ref(),
+        )
+        .map_err(|e| match e {
+            KeyStoreError::Read { .. } => {
+                format!("{}. Run scripts/gen-worker-cert.sh first.", e)
+            }
+            e => e.to_string(),
         })?;
 
         let tls_config = ClientTlsConfig::new()
@@ -89,7 +224,7 @@ impl WorkerConnection {
             .identity(Identity::from_pem(client_cert, client_key))
             .domain_name("localhost");
 
-        let channel = Channel::from_shared(self.config.control_plane_addr.clone())?
+        let channel = Channel::from_shared(self.endpoint.clone())?
             .tls_config(tls_config)?
             .connect()
             .await?;
@@ -106,51 +241,200 @@ impl WorkerConnection {
         // Start streaming connection
         let response = client.stream_connect(outbound_stream).await?;
         let mut inbound = response.into_inner();
+        self.connected = true;
 
         info!("Connected to control plane, sending WorkerHello");
 
         // Emit JSON event for worker connected
-        json_output::emit_worker_connected(
-            self.config.worker_id.as_str(),
-            &self.config.control_plane_addr,
-        );
+        json_output::emit_worker_connected(self.config.worker_id.as_str(), &self.endpoint);
 
         // Send WorkerHello
         self.send_hello().await?;
 
+        // Settle runs an earlier worker process lost
+        self.report_interrupted(&tx).await;
+
+        // Replay output spooled while the stream was slow or down
+        let replay_handle = self.config.spool.clone().map(|spool| {
+            let replay_tx = tx.clone();
+            tokio::spawn(async move { spool.replay(replay_tx).await })
+        });
+
         // Start heartbeat task
         let heartbeat_tx = tx.clone();
         let heartbeat_config = self.config.clone();
         let heartbeat_run_count = self.active_run_count.clone();
+        let heartbeat_capabilities = self.capabilities.clone();
+        let heartbeat_draining = self.draining.clone();
         let heartbeat_handle = tokio::spawn(async move {
-            run_heartbeat_loop(heartbeat_tx, heartbeat_config, heartbeat_run_count).await;
+            run_heartbeat_loop(
+                heartbeat_tx,
+                heartbeat_config,
+                heartbeat_run_count,
+                heartbeat_capabilities,
+                heartbeat_draining,
+            )
+            .await;
         });
 
-        // Process incoming messages
-        while let Some(result) = inbound.next().await {
-            match result {
-                Ok(msg) => {
-                    self.handle_server_message(msg).await;
-                }
-                Err(e) => {
-                    warn!(error = %e, "Stream error");
-                    break;
-                }
+        // Send capability changes as they happen
+        let update_handle = tokio::spawn(send_capability_updates(
+            tx.clone(),
+            self.capabilities.clone(),
+            self.executor.clone(),
+        ));
+
+        // Process incoming messages until the stream ends or we shut down
+        let mut shutdown = self.shutdown.clone();
+        let shutting_down = loop {
+            tokio::select! {
+                result = inbound.next() => match result {
+                    Some(Ok(msg)) => self.handle_server_message(msg).await,
+                    Some(Err(e)) => {
+                        warn!(error = %e, "Stream error");
+                        break false;
+                    }
+                    None => break false,
+                },
+                _ = shutdown::reached(&mut shutdown, Shutdown::Drain) => break true,
             }
+        };
+        if shutting_down {
+            self.drain(&mut inbound, &tx, &mut shutdown).await;
         }
 
         // Clean up
         heartbeat_handle.abort();
+        update_handle.abort();
+        if let Some(handle) = replay_handle {
+            handle.abort();
+        }
         self.outbound_tx = None;
 
+        if shutting_down {
+            // Closing our side flushes what is queued; the control plane then
+            // ends the stream
+            drop(tx);
+            let closed = async { while inbound.next().await.is_some() {} };
+            if tokio::time::timeout(CLOSE_GRACE, closed).await.is_err() {
+                warn!("Control plane did not close the stream, exiting anyway");
+            }
+        }
+
         info!("Disconnected from control plane");
         Ok(())
     }
 
+    /// Drain before shutting down: heartbeat DRAINING, refuse new assignments
+    /// and wait for the active runs, stopping those still going at the
+    /// shutdown timeout. Messages keep being handled meanwhile, so approvals
+    /// and cancels reach the draining runs.
+    async fn drain(
+        &self,
+        inbound: &mut Streaming<taskrun_proto::pb::RunServerMessage>,
+        tx: &mpsc::Sender<RunClientMessage>,
+        shutdown: &mut watch::Receiver<Shutdown>,
+    ) {
+        self.draining.store(true, Ordering::SeqCst);
+        let timeout_secs = self.config.shutdown_timeout_secs;
+        info!(
+            active_runs = self.active_run_count.load(Ordering::SeqCst),
+            timeout_secs, "Draining before shutdown"
+        );
+        json_output::emit_worker_draining(
+            self.config.worker_id.as_str(),
+            self.active_run_count.load(Ordering::SeqCst),
+            timeout_secs,
+        );
+        // Tell the control plane now rather than at the next heartbeat
+        let _ = tx
+            .send(heartbeat_message(
+                &self.config,
+                &self.active_run_count,
+                &self.capabilities,
+                &self.draining,
+            ))
+            .await;
+
+        let deadline = tokio::time::sleep(Duration::from_secs(timeout_secs));
+        tokio::pin!(deadline);
+        let mut poll = tokio::time::interval(DRAIN_POLL);
+        let mut stopped = false;
+        loop {
+            tokio::select! {
+                _ = poll.tick() => {
+                    if self.active_run_count.load(Ordering::SeqCst) == 0 {
+                        info!("All runs finished, shutting down");
+                        return;
+                    }
+                }
+                result = inbound.next() => match result {
+                    Some(Ok(msg)) => self.handle_server_message(msg).await,
+                    _ => {
+                        warn!("Control plane stream ended while draining");
+                        return;
+                    }
+                },
+                _ = &mut deadline => {
+                    if stopped {
+                        warn!(
+                            active_runs = self.active_run_count.load(Ordering::SeqCst),
+                            "Runs did not stop in time, shutting down anyway"
+                        );
+                        return;
+                    }
+                    self.stop_runs();
+                    stopped = true;
+                    deadline.as_mut().reset(tokio::time::Instant::now() + STOP_GRACE);
+                }
+                _ = shutdown::reached(shutdown, Shutdown::Now), if !stopped => {
+                    self.stop_runs();
+                    stopped = true;
+                    deadline.as_mut().reset(tokio::time::Instant::now() + STOP_GRACE);
+                }
+            }
+        }
+    }
+
+    /// Stop the runs still active at shutdown; they report FAILED with
+    /// `WORKER_LOST`.
+    fn stop_runs(&self) {
+        let runs = self
+            .cancellations
+            .shutdown_all("Worker shut down before the run finished");
+        warn!(runs, "Stopping runs still active at shutdown");
+    }
+
+    /// Report runs the journal has as interrupted by a restart as FAILED
+    /// with `WORKER_LOST`, then settle them in the journal.
+    async fn report_interrupted(&self, tx: &mpsc::Sender<RunClientMessage>) {
+        let Some(journal) = &self.config.journal else {
+            return;
+        };
+        for entry in journal.interrupted() {
+            let error = format!(
+                "Run interrupted by a worker restart after {} output chunks",
+                entry.output_seq
+            );
+            info!(run_id = %entry.run_id, task_id = %entry.task_id, follow_up = entry.follow_up, "Reporting run interrupted by a restart");
+            json_output::emit_task_failed(&entry.run_id, &error);
+            send_status_update_with_error(
+                tx,
+                &entry.run_id,
+                taskrun_proto::pb::RunStatus::Failed,
+                FailureReason::WorkerLost,
+                error,
+            )
+            .await;
+            journal.resolve_interrupted(&entry);
+        }
+    }
+
     async fn send_hello(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         let info = self.build_worker_info();
         let hello = WorkerHello {
             info: Some(info.into()),
+            acks_assignments: true,
         };
 
         let msg = RunClientMessage {
@@ -164,32 +448,19 @@ impl WorkerConnection {
     }
 
     fn build_worker_info(&self) -> WorkerInfo {
-        // Model backend from config
-        let backend = ModelBackend::new(&self.config.model_provider, &self.config.model_name)
-            .with_context_window(200_000)
-            .with_modalities(vec!["text".to_string()]);
+        let capabilities = self.capabilities.borrow();
 
-        // Agent from config
-        let description = get_agent_description(&self.config.agent_name);
-        let agent = AgentSpec::new(&self.config.agent_name)
-            .with_description(&description)
-            .with_backend(backend);
-
-        // Get hostname
-        let hostname = std::env::var("HOSTNAME").unwrap_or_else(|_| "unknown".to_string());
-
-        WorkerInfo::new(self.config.worker_id.clone(), hostname)
-            .with_agent(agent)
-            .with_label("env", "development")
-    }
-}
+        let hostname = labels::hostname();
 
-/// Get the description for a known agent, or a generic description for custom agents.
-fn get_agent_description(agent_name: &str) -> String {
-    match agent_name {
-        "general" => "General-purpose agent that executes any task".to_string(),
-        "support_triage" => "Classifies and triages support tickets".to_string(),
-        _ => format!("Custom agent: {}", agent_name),
+        let info = capabilities.agents.iter().cloned().fold(
+            WorkerInfo::new(self.config.worker_id.clone(), hostname)
+                .with_namespace(&self.config.namespace),
+            WorkerInfo::with_agent,
+        );
+        capabilities
+            .labels
+            .iter()
+            .fold(info, |info, (key, value)| info.with_label(key, value))
     }
 }
 
@@ -204,6 +475,28 @@ impl WorkerConnection {
                         agent = %assignment.agent_name,
                         "Received run assignment"
                     );
+                    if let Some(tx) = &self.outbound_tx {
+                        send_assignment_ack(tx, &assignment.run_id).await;
+                    }
+
+                    if self.draining.load(Ordering::SeqCst) {
+                        warn!(run_id = %assignment.run_id, "Refusing run assignment, worker is shutting down");
+                        json_output::emit_task_failed(
+                            &assignment.run_id,
+                            "Worker is shutting down",
+                        );
+                        if let Some(tx) = &self.outbound_tx {
+                            send_status_update_with_error(
+                                tx,
+                                &assignment.run_id,
+                                taskrun_proto::pb::RunStatus::Failed,
+                                FailureReason::WorkerLost,
+                                "Worker is shutting down".to_string(),
+                            )
+                            .await;
+                        }
+                        return;
+                    }
 
                     // Emit JSON event for task assignment
                     json_output::emit_task_assigned(
@@ -218,11 +511,34 @@ impl WorkerConnection {
                         let active_count = self.active_run_count.clone();
                         let executor = self.executor.clone();
                         let sessions = self.sessions.clone();
-
-                        tokio::spawn(async move {
-                            execute_real_run(executor, tx, assignment, active_count, sessions)
+                        let cancellations = self.cancellations.clone();
+                        // Before the run starts, so a cancel right behind the assignment finds it
+                        let cancel_rx = cancellations.register(&assignment.run_id);
+
+                        // Execution span, in the trace the control plane scheduled the run in
+                        let span = info_span!(
+                            "run.execute",
+                            run_id = %assignment.run_id,
+                            task_id = %assignment.task_id,
+                            agent = %assignment.agent_name,
+                        );
+                        trace::set_parent(&span, &assignment.trace_context);
+
+                        tokio::spawn(
+                            async move {
+                                execute_real_run(
+                                    executor,
+                                    tx,
+                                    assignment,
+                                    active_count,
+                                    sessions,
+                                    cancellations,
+                                    cancel_rx,
+                                )
                                 .await;
-                        });
+                            }
+                            .instrument(span),
+                        );
                     }
                 }
                 ServerPayload::CancelRun(cancel) => {
@@ -235,11 +551,42 @@ impl WorkerConnection {
                     // Emit JSON event for task cancellation
                     json_output::emit_task_cancelled(&cancel.run_id, &cancel.reason);
 
-                    // TODO: Cancel the run (would need to track JoinHandles)
+                    if !self.cancellations.cancel(&cancel.run_id, &cancel.reason) {
+                        // Not running here (already finished or lost on reconnect);
+                        // report CANCELLED so the control plane can settle the run.
+                        warn!(run_id = %cancel.run_id, "Cancel requested for run that is not in flight");
+                        if let Some(tx) = &self.outbound_tx {
+                            send_status_update_with_error(
+                                tx,
+                                &cancel.run_id,
+                                taskrun_proto::pb::RunStatus::Cancelled,
+                                FailureReason::CancelledByUser,
+                                cancel.reason,
+                            )
+                            .await;
+                        }
+                    }
                 }
                 ServerPayload::Ack(ack) => {
                     info!(ack_type = %ack.ack_type, ref_id = %ack.ref_id, "Received ack");
                 }
+                ServerPayload::Disconnect(disconnect) => {
+                    // The control plane ends the stream next; we reconnect as usual
+                    warn!(reason = %disconnect.reason, "Disconnected by the control plane");
+                }
+                ServerPayload::ApprovalDecision(decision) => {
+                    info!(
+                        approval_id = %decision.approval_id,
+                        run_id = %decision.run_id,
+                        allowed = decision.allowed,
+                        decided_by = %decision.decided_by,
+                        "Received tool approval decision"
+                    );
+                    let approval_id = decision.approval_id.clone();
+                    if !self.executor.decide_approval(decision) {
+                        warn!(approval_id = %approval_id, "Decision for approval that is no longer waiting");
+                    }
+                }
                 ServerPayload::ContinueRun(continue_run) => {
                     info!(
                         run_id = %continue_run.run_id,
@@ -259,6 +606,7 @@ impl WorkerConnection {
                         let sessions = self.sessions.clone();
                         let executor = self.executor.clone();
                         let active_count = self.active_run_count.clone();
+                        let cancellations = self.cancellations.clone();
 
                         tokio::spawn(async move {
                             execute_continue_run(
@@ -267,6 +615,7 @@ impl WorkerConnection {
                                 continue_run,
                                 sessions,
                                 active_count,
+                                cancellations,
                             )
                             .await;
                         });
@@ -277,6 +626,40 @@ impl WorkerConnection {
     }
 }
 
+/// Build a heartbeat with the worker's current status.
+fn heartbeat_message(
+    config: &Config,
+    active_count: &AtomicU32,
+    capabilities: &watch::Receiver<Capabilities>,
+    draining: &AtomicBool,
+) -> RunClientMessage {
+    let runs = active_count.load(Ordering::SeqCst);
+    let (status, status_str) = if draining.load(Ordering::SeqCst) {
+        (taskrun_proto::pb::WorkerStatus::Draining, "draining")
+    } else if runs > 0 {
+        (taskrun_proto::pb::WorkerStatus::Busy, "busy")
+    } else {
+        (taskrun_proto::pb::WorkerStatus::Idle, "idle")
+    };
+
+    // Emit JSON event for heartbeat
+    json_output::emit_heartbeat(config.worker_id.as_str(), status_str, runs);
+
+    let heartbeat = WorkerHeartbeat {
+        worker_id: config.worker_id.as_str().to_string(),
+        status: status as i32,
+        active_runs: runs,
+        max_concurrent_runs: capabilities.borrow().max_concurrent_runs,
+        interactive_slots: config.interactive_slots,
+        metrics: metrics::collect(&config.heartbeat_metrics, &config.run_stats, Path::new(".")),
+        timestamp_ms: chrono::Utc::now().timestamp_millis(),
+    };
+
+    RunClientMessage {
+        payload: Some(ClientPayload::Heartbeat(heartbeat)),
+    }
+}
+
 /// Execute a real run via Claude Code subprocess.
 async fn execute_real_run(
     executor: Arc<ClaudeCodeExecutor>,
@@ -284,17 +667,44 @@ async fn execute_real_run(
     assignment: RunAssignment,
     active_count: Arc<AtomicU32>,
     sessions: Arc<Mutex<HashMap<String, SessionInfo>>>,
+    cancellations: RunCancellations,
+    mut cancel_rx: oneshot::Receiver<Cancel>,
 ) {
     let run_id = assignment.run_id.clone();
     let task_id = assignment.task_id.clone();
+    let mut record = RunRecord::assigned(
+        executor.journal(),
+        &run_id,
+        &task_id,
+        &assignment.agent_name,
+    );
 
-    // Increment active run count
+    // Increment active run count (queued runs count, so the control plane sees them)
     active_count.fetch_add(1, Ordering::SeqCst);
 
-    info!(run_id = %run_id, agent = %assignment.agent_name, "Starting real execution via Claude Code");
+    // Wait for a slot in the run's lane; a cancel while queued ends the run here
+    let priority = assignment.priority().into();
+    let _permit = tokio::select! {
+        permit = executor.acquire_lane(priority) => permit,
+        cancel = &mut cancel_rx => {
+            let cancel = cancel.unwrap_or_else(|_| Cancel::new("Run cancelled"));
+            info!(run_id = %run_id, reason = %cancel.reason, "Queued run cancelled");
+            cancellations.unregister(&run_id);
+            record.ended();
+            let (status, failure_reason) = cancel.outcome();
+            send_status_update_with_error(&tx, &run_id, status, failure_reason, cancel.reason)
+            .await;
+            active_count.fetch_sub(1, Ordering::SeqCst);
+            return;
+        }
+    };
 
-    // Send RUNNING status
-    send_status_update(&tx, &run_id, taskrun_proto::pb::RunStatus::Running, None).await;
+    info!(run_id = %run_id, agent = %assignment.agent_name, priority = ?priority, "Starting real execution via Claude Code");
+
+    // Send RUNNING status with the environment the run executes in
+    let manifest = executor.run_manifest(&assignment);
+    let limits: RunLimits = assignment.limits.map(Into::into).unwrap_or_default();
+    send_run_started(&tx, &run_id, manifest.clone()).await;
 
     // Emit JSON event for task running
     json_output::emit_task_running(&run_id);
@@ -305,52 +715,173 @@ async fn execute_real_run(
     // Create channel for events from executor
     let (event_tx, mut event_rx) = mpsc::channel::<RunEvent>(32);
 
-    // Spawn event forwarder to send events via gRPC
+    // Masks the run's secret values once the executor resolves them
+    let redactor = Redactor::default();
+
+    // Spawn event forwarder to send events via gRPC, tagged with the run's trace
     let event_tx_grpc = tx.clone();
+    let trace_id = trace::trace_id(&tracing::Span::current());
+    let event_redactor = redactor.clone();
     let event_handle = tokio::spawn(async move {
         while let Some(event) = event_rx.recv().await {
+            let mut event = event_redactor.redact_event(event);
+            if let Some(trace_id) = &trace_id {
+                event
+                    .metadata
+                    .insert("trace_id".to_string(), trace_id.clone());
+            }
             send_event(&event_tx_grpc, event).await;
         }
     });
 
+    // Give the run a worktree of its own, if configured
+    let mut worktree = match executor.worktrees() {
+        Some(worktrees) => match worktrees.create(&run_id).await {
+            Ok(worktree) => Some(worktree),
+            Err(e) => {
+                error!(run_id = %run_id, error = %e, "Failed to create run worktree");
+                cancellations.unregister(&run_id);
+                record.ended();
+                send_status_update_with_error(
+                    &tx,
+                    &run_id,
+                    taskrun_proto::pb::RunStatus::Failed,
+                    FailureReason::ProcessCrash,
+                    format!("Failed to create run worktree: {}", e),
+                )
+                .await;
+                active_count.fetch_sub(1, Ordering::SeqCst);
+                return;
+            }
+        },
+        None => None,
+    };
+    let working_dir = worktree
+        .as_ref()
+        .map_or_else(|| PathBuf::from("."), |worktree| worktree.path.clone());
+    record.running(worktree.as_ref());
+
+    // Stage input files in the workspace and point the task at them
+    let input_json = match files::stage_inputs(&working_dir, &run_id, &assignment.input_files) {
+        Ok(paths) => files::with_input_files(&assignment.input_json, &paths),
+        Err(e) => {
+            error!(run_id = %run_id, error = %e, "Failed to stage input files");
+            cancellations.unregister(&run_id);
+            record.ended();
+            send_status_update_with_error(
+                &tx,
+                &run_id,
+                taskrun_proto::pb::RunStatus::Failed,
+                FailureReason::ProcessCrash,
+                format!("Failed to stage input files: {}", e),
+            )
+            .await;
+            active_count.fetch_sub(1, Ordering::SeqCst);
+            return;
+        }
+    };
+
+    // Files changed from here on are the run's
+    let started = SystemTime::now();
+
     // Spawn executor in background
     let executor_clone = executor.clone();
-    let agent_name = assignment.agent_name.clone();
-    let input_json = assignment.input_json.clone();
+    let working_dir_clone = working_dir.clone();
     let run_id_clone = RunId::new(&run_id);
     let task_id_clone = TaskId::new(&task_id);
-    let executor_handle = tokio::spawn(async move {
-        executor_clone
-            .execute(
-                &agent_name,
-                &input_json,
-                chunk_tx,
-                event_tx,
-                run_id_clone,
-                task_id_clone,
-            )
-            .await
+    let approvals = executor.approval_gate(&tx, &run_id);
+    let log_shipper = ship_run_logs(&executor, &tx, &run_id, &redactor);
+    let executor_redactor = redactor.clone();
+    let executor_handle = tokio::spawn(
+        async move {
+            executor_clone
+                .execute(
+                    &working_dir_clone,
+                    &input_json,
+                    &manifest,
+                    &limits,
+                    chunk_tx,
+                    event_tx,
+                    run_id_clone,
+                    task_id_clone,
+                    approvals,
+                    &executor_redactor,
+                )
+                .await
+        }
+        .in_current_span(),
+    );
+
+    // Abort the executor (killing the Claude process) if a cancel arrives
+    let abort_handle = executor_handle.abort_handle();
+    let cancel_watcher = tokio::spawn(async move {
+        let cancel = cancel_rx.await.ok()?;
+        abort_handle.abort();
+        Some(cancel)
     });
 
     // Stream chunks as they arrive
+    let mut output_filter = executor.output_filter(&assignment.agent_name);
     let mut seq = 0u64;
-    while let Some(chunk) = chunk_rx.recv().await {
-        if !chunk.is_final && !chunk.content.is_empty() {
+    let mut output_redactor = redactor.chunks();
+    let mut open = true;
+    while open {
+        let content = match chunk_rx.recv().await {
+            Some(chunk) if chunk.is_final => continue,
+            Some(chunk) => output_redactor.push(&chunk.content),
+            None => {
+                open = false;
+                output_redactor.finish()
+            }
+        };
+        if !content.is_empty() {
+            let content = output_filter.filter(content);
             // Emit JSON event for output chunk
-            json_output::emit_output_chunk(&run_id, seq, &chunk.content, false);
-            send_output_chunk(&tx, &run_id, seq, chunk.content, false).await;
+            json_output::emit_output_chunk(&run_id, seq, &content, false);
+            send_output_chunk(&tx, executor.spool(), &run_id, seq, content, false).await;
             seq += 1;
+            record.output(seq);
         }
     }
 
+    // Status updates go straight to the stream, so let spooled output go first
+    wait_for_spool(executor.spool(), &run_id).await;
+
+    // Record masked span counts in the run's event stream
+    if let Some(event) = output_filter.into_event(RunId::new(&run_id), TaskId::new(&task_id)) {
+        send_event(&tx, event).await;
+    }
+
     // Wait for executor to complete and get result
     let result = executor_handle.await;
+    if let Some(completed) = finished_ok(&result) {
+        let duration = started.elapsed().unwrap_or_default();
+        executor.run_stats().record(completed, duration);
+    }
+    stop_shipping_run_logs(&executor, &run_id, log_shipper).await;
+
+    // Dropping the registration ends the watcher if no cancel arrived
+    cancellations.unregister(&run_id);
+    let cancel = cancel_watcher.await.ok().flatten();
 
     // Wait for event forwarder to finish
     let _ = event_handle.await;
 
     match result {
+        Err(e) if e.is_cancelled() => {
+            let cancel = cancel.unwrap_or_else(|| Cancel::new("Run cancelled"));
+            info!(run_id = %run_id, reason = %cancel.reason, "Execution cancelled");
+            let (status, failure_reason) = cancel.outcome();
+            send_status_update_with_error(&tx, &run_id, status, failure_reason, cancel.reason)
+                .await;
+        }
         Ok(Ok(exec_result)) => {
+            // Commit the run's changes to its branch
+            if let (Some(worktrees), Some(worktree)) = (executor.worktrees(), &worktree) {
+                let message = format!("TaskRun run {}\n\nTask: {}", run_id, task_id);
+                commit_worktree(worktrees, worktree, &tx, &run_id, &task_id, &message).await;
+            }
+
             // Store session ID for future continuation
             if let Some(ref session_id) = exec_result.session_id {
                 info!(
@@ -363,13 +894,26 @@ async fn execute_real_run(
                     SessionInfo {
                         session_id: session_id.clone(),
                         task_id: task_id.clone(),
+                        agent_name: assignment.agent_name.clone(),
+                        worktree: worktree.clone(),
                     },
                 );
+                record.completed(session_id, worktree.take().as_ref());
             }
 
+            let artifacts = files::completed_artifacts(
+                &exec_result.artifacts,
+                &working_dir,
+                &executor.agent(&assignment.agent_name),
+                started,
+            )
+            .await;
+            files::upload_artifacts(&tx, &run_id, &artifacts).await;
+
             // Send final chunk and emit JSON event
             json_output::emit_output_chunk(&run_id, seq, "", true);
-            send_output_chunk(&tx, &run_id, seq, String::new(), true).await;
+            send_output_chunk(&tx, executor.spool(), &run_id, seq, String::new(), true).await;
+            wait_for_spool(executor.spool(), &run_id).await;
 
             // Build the backend info that was used
             let backend_used = taskrun_proto::pb::ModelBackend {
@@ -402,26 +946,29 @@ async fn execute_real_run(
         }
         Ok(Err(e)) => {
             // Executor returned an error
-            error!(run_id = %run_id, error = %e, "Execution failed");
+            let message = redactor.redact(&e.to_string());
+            error!(run_id = %run_id, error = %message, "Execution failed");
             send_status_update_with_error(
                 &tx,
                 &run_id,
                 taskrun_proto::pb::RunStatus::Failed,
-                e.to_string(),
+                e.failure_reason(),
+                message.clone(),
             )
             .await;
 
             // Emit JSON event for task failed
-            json_output::emit_task_failed(&run_id, &e.to_string());
+            json_output::emit_task_failed(&run_id, &message);
         }
         Err(e) => {
-            // Task panicked or was cancelled
+            // Executor task panicked
             error!(run_id = %run_id, error = %e, "Executor task failed");
             let error_msg = format!("Executor task failed: {}", e);
             send_status_update_with_error(
                 &tx,
                 &run_id,
                 taskrun_proto::pb::RunStatus::Failed,
+                FailureReason::ProcessCrash,
                 error_msg.clone(),
             )
             .await;
@@ -431,6 +978,13 @@ async fn execute_real_run(
         }
     }
 
+    // A run that cannot be continued is done with its worktree and its
+    // journal entry
+    if let (Some(worktrees), Some(worktree)) = (executor.worktrees(), worktree) {
+        worktrees.remove(&worktree).await;
+    }
+    record.ended();
+
     // Decrement active run count
     active_count.fetch_sub(1, Ordering::SeqCst);
 }
@@ -442,6 +996,7 @@ async fn execute_continue_run(
     continue_run: ContinueRun,
     sessions: Arc<Mutex<HashMap<String, SessionInfo>>>,
     active_count: Arc<AtomicU32>,
+    cancellations: RunCancellations,
 ) {
     let run_id = continue_run.run_id.clone();
     let message = continue_run.message.clone();
@@ -456,6 +1011,15 @@ async fn execute_continue_run(
         Some(info) => info,
         None => {
             warn!(run_id = %run_id, "No session found for continue request");
+            // Tell the control plane, which may be waiting on a reply
+            send_status_update_with_error(
+                &tx,
+                &run_id,
+                taskrun_proto::pb::RunStatus::Failed,
+                FailureReason::SdkError,
+                NO_SESSION.to_string(),
+            )
+            .await;
             return;
         }
     };
@@ -466,11 +1030,45 @@ async fn execute_continue_run(
         "Continuing run with existing session"
     );
 
+    let mut cancel_rx = cancellations.register(&run_id);
+    let mut record = RunRecord::follow_up(
+        executor.journal(),
+        &run_id,
+        &session_info.task_id,
+        &session_info.agent_name,
+        &session_info.session_id,
+        session_info.worktree.clone(),
+    );
+
     // Increment active run count
     active_count.fetch_add(1, Ordering::SeqCst);
 
+    // Continuations are interactive: a user is waiting on the reply. A cancel
+    // while queued ends the turn here.
+    let _permit = tokio::select! {
+        permit = executor.acquire_lane(TaskPriority::Interactive) => permit,
+        cancel = &mut cancel_rx => {
+            let cancel = cancel.unwrap_or_else(|_| Cancel::new("Run cancelled"));
+            info!(run_id = %run_id, reason = %cancel.reason, "Queued continuation cancelled");
+            cancellations.unregister(&run_id);
+            record.ended();
+            let (status, failure_reason) = cancel.outcome();
+            send_status_update_with_error(&tx, &run_id, status, failure_reason, cancel.reason)
+            .await;
+            active_count.fetch_sub(1, Ordering::SeqCst);
+            return;
+        }
+    };
+
     // Send user message as ChatMessage
-    send_chat_message(&tx, &run_id, ProtoChatRole::User, message.clone()).await;
+    send_chat_message(
+        &tx,
+        &run_id,
+        ProtoChatRole::User,
+        message.clone(),
+        Vec::new(),
+    )
+    .await;
 
     // Send RUNNING status
     send_status_update(&tx, &run_id, taskrun_proto::pb::RunStatus::Running, None).await;
@@ -484,31 +1082,60 @@ async fn execute_continue_run(
     // Create channel for events from executor
     let (event_tx, mut event_rx) = mpsc::channel::<RunEvent>(32);
 
+    // Masks the run's secret values once the executor resolves them
+    let redactor = Redactor::default();
+
     // Spawn event forwarder to send events via gRPC
     let event_tx_grpc = tx.clone();
+    let event_redactor = redactor.clone();
     let event_handle = tokio::spawn(async move {
         while let Some(event) = event_rx.recv().await {
-            send_event(&event_tx_grpc, event).await;
+            send_event(&event_tx_grpc, event_redactor.redact_event(event)).await;
         }
     });
 
     // Spawn executor in background with session continuation
+    let started = SystemTime::now();
     let executor_clone = executor.clone();
     let session_id = session_info.session_id.clone();
+    let agent_name = session_info.agent_name.clone();
     let task_id = session_info.task_id.clone();
+    let working_dir = session_info
+        .worktree
+        .as_ref()
+        .map_or_else(|| PathBuf::from("."), |worktree| worktree.path.clone());
     let run_id_clone = RunId::new(&run_id);
     let task_id_clone = TaskId::new(&task_id);
-    let executor_handle = tokio::spawn(async move {
-        executor_clone
-            .execute_follow_up(
-                &session_id,
-                &message,
-                chunk_tx,
-                event_tx,
-                run_id_clone,
-                task_id_clone,
-            )
-            .await
+    let approvals = executor.approval_gate(&tx, &run_id);
+    let log_shipper = ship_run_logs(&executor, &tx, &run_id, &redactor);
+    let executor_redactor = redactor.clone();
+    let span = info_span!("run.continue", run_id = %run_id, task_id = %task_id);
+    let executor_handle = tokio::spawn(
+        async move {
+            executor_clone
+                .execute_follow_up(
+                    &agent_name,
+                    &working_dir,
+                    &session_id,
+                    &message,
+                    chunk_tx,
+                    event_tx,
+                    run_id_clone,
+                    task_id_clone,
+                    approvals,
+                    &executor_redactor,
+                )
+                .await
+        }
+        .instrument(span),
+    );
+
+    // Abort the executor (killing the Claude process) if a cancel arrives
+    let abort_handle = executor_handle.abort_handle();
+    let cancel_watcher = tokio::spawn(async move {
+        let cancel = cancel_rx.await.ok()?;
+        abort_handle.abort();
+        Some(cancel)
     });
 
     // Stream chunks as they arrive
@@ -517,24 +1144,83 @@ async fn execute_continue_run(
     let run_id_for_chat = run_id.clone();
     let mut full_response = String::new();
 
-    while let Some(chunk) = chunk_rx.recv().await {
-        if !chunk.is_final && !chunk.content.is_empty() {
-            full_response.push_str(&chunk.content);
+    let mut output_filter = executor.output_filter(&session_info.agent_name);
+
+    let mut output_redactor = redactor.chunks();
+    let mut open = true;
+    while open {
+        let content = match chunk_rx.recv().await {
+            Some(chunk) if chunk.is_final => continue,
+            Some(chunk) => output_redactor.push(&chunk.content),
+            None => {
+                open = false;
+                output_redactor.finish()
+            }
+        };
+        if !content.is_empty() {
+            let content = output_filter.filter(content);
+            full_response.push_str(&content);
             // Emit JSON event for output chunk
-            json_output::emit_output_chunk(&run_id, seq, &chunk.content, false);
-            send_output_chunk(&tx, &run_id, seq, chunk.content, false).await;
+            json_output::emit_output_chunk(&run_id, seq, &content, false);
+            send_output_chunk(&tx, executor.spool(), &run_id, seq, content, false).await;
             seq += 1;
+            record.output(seq);
         }
     }
 
+    // Status updates go straight to the stream, so let spooled output go first
+    wait_for_spool(executor.spool(), &run_id).await;
+
+    // Record masked span counts in the run's event stream
+    if let Some(event) =
+        output_filter.into_event(RunId::new(&run_id), TaskId::new(&session_info.task_id))
+    {
+        send_event(&tx, event).await;
+    }
+
     // Wait for executor to complete and get result
     let result = executor_handle.await;
+    if let Some(completed) = finished_ok(&result) {
+        let duration = started.elapsed().unwrap_or_default();
+        executor.run_stats().record(completed, duration);
+    }
+    stop_shipping_run_logs(&executor, &run_id, log_shipper).await;
+
+    // Dropping the registration ends the watcher if no cancel arrived
+    cancellations.unregister(&run_id);
+    let cancel = cancel_watcher.await.ok().flatten();
 
     // Wait for event forwarder to finish
     let _ = event_handle.await;
 
     match result {
+        Err(e) if e.is_cancelled() => {
+            let cancel = cancel.unwrap_or_else(|| Cancel::new("Run cancelled"));
+            info!(run_id = %run_id, reason = %cancel.reason, "Continuation cancelled");
+            let (status, failure_reason) = cancel.outcome();
+            send_status_update_with_error(&tx, &run_id, status, failure_reason, cancel.reason)
+                .await;
+        }
         Ok(Ok(exec_result)) => {
+            // Commit the turn's changes to the run's branch
+            if let (Some(worktrees), Some(worktree)) =
+                (executor.worktrees(), &session_info.worktree)
+            {
+                let message = format!(
+                    "TaskRun run {} (follow-up)\n\nTask: {}",
+                    run_id, session_info.task_id
+                );
+                commit_worktree(
+                    worktrees,
+                    worktree,
+                    &tx,
+                    &run_id,
+                    &session_info.task_id,
+                    &message,
+                )
+                .await;
+            }
+
             // Update session ID if it changed
             if let Some(ref new_session_id) = exec_result.session_id {
                 sessions.lock().await.insert(
@@ -542,24 +1228,29 @@ async fn execute_continue_run(
                     SessionInfo {
                         session_id: new_session_id.clone(),
                         task_id: session_info.task_id.clone(),
+                        agent_name: session_info.agent_name.clone(),
+                        worktree: session_info.worktree.clone(),
                     },
                 );
+                record.completed(new_session_id, session_info.worktree.as_ref());
             }
 
-            // Send assistant response as ChatMessage
-            if !full_response.is_empty() {
+            // Send assistant response (and the files it produced) as ChatMessage
+            if !full_response.is_empty() || !exec_result.artifacts.is_empty() {
                 send_chat_message(
                     &tx_for_chat,
                     &run_id_for_chat,
                     ProtoChatRole::Assistant,
                     full_response,
+                    exec_result.artifacts.clone(),
                 )
                 .await;
             }
 
             // Send final chunk and emit JSON event
             json_output::emit_output_chunk(&run_id, seq, "", true);
-            send_output_chunk(&tx, &run_id, seq, String::new(), true).await;
+            send_output_chunk(&tx, executor.spool(), &run_id, seq, String::new(), true).await;
+            wait_for_spool(executor.spool(), &run_id).await;
 
             // Build the backend info that was used
             let backend_used = taskrun_proto::pb::ModelBackend {
@@ -591,17 +1282,19 @@ async fn execute_continue_run(
             info!(run_id = %run_id, "Continue execution completed successfully");
         }
         Ok(Err(e)) => {
-            error!(run_id = %run_id, error = %e, "Continue execution failed");
+            let message = redactor.redact(&e.to_string());
+            error!(run_id = %run_id, error = %message, "Continue execution failed");
             send_status_update_with_error(
                 &tx,
                 &run_id,
                 taskrun_proto::pb::RunStatus::Failed,
-                e.to_string(),
+                e.failure_reason(),
+                message.clone(),
             )
             .await;
 
             // Emit JSON event for task failed
-            json_output::emit_task_failed(&run_id, &e.to_string());
+            json_output::emit_task_failed(&run_id, &message);
         }
         Err(e) => {
             error!(run_id = %run_id, error = %e, "Continue executor task failed");
@@ -610,6 +1303,7 @@ async fn execute_continue_run(
                 &tx,
                 &run_id,
                 taskrun_proto::pb::RunStatus::Failed,
+                FailureReason::ProcessCrash,
                 error_msg.clone(),
             )
             .await;
@@ -618,6 +1312,7 @@ async fn execute_continue_run(
             json_output::emit_task_failed(&run_id, &error_msg);
         }
     }
+    record.ended();
 
     // Decrement active run count
     active_count.fetch_sub(1, Ordering::SeqCst);
@@ -636,6 +1331,8 @@ async fn send_status_update(
         error_message: String::new(),
         backend_used,
         timestamp_ms: chrono::Utc::now().timestamp_millis(),
+        manifest: None,
+        failure_reason: 0,
     };
 
     let msg = RunClientMessage {
@@ -647,11 +1344,37 @@ async fn send_status_update(
     }
 }
 
-/// Send a status update with an error message to the control plane.
+/// Send the RUNNING status update, reporting the run's manifest.
+async fn send_run_started(
+    tx: &mpsc::Sender<RunClientMessage>,
+    run_id: &str,
+    manifest: RunManifest,
+) {
+    let update = RunStatusUpdate {
+        run_id: run_id.to_string(),
+        status: taskrun_proto::pb::RunStatus::Running as i32,
+        error_message: String::new(),
+        backend_used: None,
+        timestamp_ms: chrono::Utc::now().timestamp_millis(),
+        manifest: Some(manifest.into()),
+        failure_reason: 0,
+    };
+
+    let msg = RunClientMessage {
+        payload: Some(ClientPayload::StatusUpdate(update)),
+    };
+
+    if tx.send(msg).await.is_err() {
+        warn!(run_id = %run_id, "Failed to send status update");
+    }
+}
+
+/// Send a failed or cancelled status update to the control plane.
 async fn send_status_update_with_error(
     tx: &mpsc::Sender<RunClientMessage>,
     run_id: &str,
     status: taskrun_proto::pb::RunStatus,
+    failure_reason: FailureReason,
     error_message: String,
 ) {
     let update = RunStatusUpdate {
@@ -660,6 +1383,8 @@ async fn send_status_update_with_error(
         error_message,
         backend_used: None,
         timestamp_ms: chrono::Utc::now().timestamp_millis(),
+        manifest: None,
+        failure_reason: taskrun_proto::pb::FailureReason::from(failure_reason) as i32,
     };
 
     let msg = RunClientMessage {
@@ -671,9 +1396,33 @@ async fn send_status_update_with_error(
     }
 }
 
-/// Send an output chunk to the control plane.
+/// Acknowledge a run assignment to the control plane.
+async fn send_assignment_ack(tx: &mpsc::Sender<RunClientMessage>, run_id: &str) {
+    let msg = RunClientMessage {
+        payload: Some(ClientPayload::AssignmentAck(AssignmentAck {
+            run_id: run_id.to_string(),
+        })),
+    };
+
+    if tx.send(msg).await.is_err() {
+        warn!(run_id = %run_id, "Failed to acknowledge run assignment");
+    }
+}
+
+/// Whether a run's execution completed, or `None` if it was cancelled.
+fn finished_ok<T, E>(result: &Result<Result<T, E>, tokio::task::JoinError>) -> Option<bool> {
+    match result {
+        Err(e) if e.is_cancelled() => None,
+        Ok(Ok(_)) => Some(true),
+        _ => Some(false),
+    }
+}
+
+/// Send an output chunk to the control plane, through the spool if there is
+/// one.
 async fn send_output_chunk(
     tx: &mpsc::Sender<RunClientMessage>,
+    spool: Option<&OutputSpool>,
     run_id: &str,
     seq: u64,
     content: String,
@@ -688,6 +1437,11 @@ async fn send_output_chunk(
         timestamp_ms: chrono::Utc::now().timestamp_millis(),
     };
 
+    if let Some(spool) = spool {
+        spool.send(tx, chunk).await;
+        return;
+    }
+
     let msg = RunClientMessage {
         payload: Some(ClientPayload::OutputChunk(chunk)),
     };
@@ -697,6 +1451,48 @@ async fn send_output_chunk(
     }
 }
 
+/// Wait for the run's spooled output, if any, to be replayed.
+async fn wait_for_spool(spool: Option<&OutputSpool>, run_id: &str) {
+    if let Some(spool) = spool {
+        if !spool.wait_drained(run_id, SPOOL_GRACE).await {
+            warn!(run_id = %run_id, "Spooled output not replayed yet, reporting status anyway");
+        }
+    }
+}
+
+/// Forward the run's log records to the control plane as run events, with
+/// `--ship-run-logs`, until `stop_shipping_run_logs`. Secret values are
+/// masked with `redactor`.
+fn ship_run_logs(
+    executor: &ClaudeCodeExecutor,
+    tx: &mpsc::Sender<RunClientMessage>,
+    run_id: &str,
+    redactor: &Redactor,
+) -> Option<tokio::task::JoinHandle<()>> {
+    let mut records = executor.run_logs()?.ship(run_id)?;
+    let tx = tx.clone();
+    let redactor = redactor.clone();
+    Some(tokio::spawn(async move {
+        while let Some(event) = records.recv().await {
+            send_event(&tx, redactor.redact_event(event)).await;
+        }
+    }))
+}
+
+/// Stop shipping the run's log records, once those already taken are sent.
+async fn stop_shipping_run_logs(
+    executor: &ClaudeCodeExecutor,
+    run_id: &str,
+    shipper: Option<tokio::task::JoinHandle<()>>,
+) {
+    if let Some(run_logs) = executor.run_logs() {
+        run_logs.stop_shipping(run_id);
+    }
+    if let Some(shipper) = shipper {
+        let _ = shipper.await;
+    }
+}
+
 /// Send a run event to the control plane.
 async fn send_event(tx: &mpsc::Sender<RunClientMessage>, event: RunEvent) {
     use taskrun_core::RunEventType;
@@ -710,6 +1506,9 @@ async fn send_event(tx: &mpsc::Sender<RunClientMessage>, event: RunEvent) {
         RunEventType::OutputGenerated => taskrun_proto::pb::RunEventType::OutputGenerated,
         RunEventType::ExecutionCompleted => taskrun_proto::pb::RunEventType::ExecutionCompleted,
         RunEventType::ExecutionFailed => taskrun_proto::pb::RunEventType::ExecutionFailed,
+        RunEventType::Milestone => taskrun_proto::pb::RunEventType::Milestone,
+        RunEventType::Log => taskrun_proto::pb::RunEventType::Log,
+        RunEventType::RunSummary => taskrun_proto::pb::RunEventType::RunSummary,
     };
 
     let proto_event = ProtoRunEvent {
@@ -719,6 +1518,7 @@ async fn send_event(tx: &mpsc::Sender<RunClientMessage>, event: RunEvent) {
         event_type: proto_event_type as i32,
         timestamp_ms: event.timestamp_ms,
         metadata: event.metadata,
+        artifacts: event.artifacts.into_iter().map(Into::into).collect(),
     };
 
     let msg = RunClientMessage {
@@ -731,11 +1531,36 @@ async fn send_event(tx: &mpsc::Sender<RunClientMessage>, event: RunEvent) {
 }
 
 /// Send a chat message to the control plane.
+/// Commit a run's changes to its branch and report the branch in the run's
+/// events.
+async fn commit_worktree(
+    worktrees: &Worktrees,
+    worktree: &RunWorktree,
+    tx: &mpsc::Sender<RunClientMessage>,
+    run_id: &str,
+    task_id: &str,
+    message: &str,
+) {
+    match worktrees.commit(worktree, message).await {
+        Ok(Some(report)) => {
+            json_output::emit_branch_updated(run_id, &report.branch, &report.commit);
+            send_event(
+                tx,
+                report.into_event(RunId::new(run_id), TaskId::new(task_id)),
+            )
+            .await;
+        }
+        Ok(None) => {}
+        Err(e) => warn!(run_id = %run_id, error = %e, "Failed to commit run changes"),
+    }
+}
+
 async fn send_chat_message(
     tx: &mpsc::Sender<RunClientMessage>,
     run_id: &str,
     role: ProtoChatRole,
     content: String,
+    attachments: Vec<Artifact>,
 ) {
     let chat_msg = RunChatMessage {
         run_id: run_id.to_string(),
@@ -743,6 +1568,7 @@ async fn send_chat_message(
             role: role as i32,
             content,
             timestamp_ms: chrono::Utc::now().timestamp_millis(),
+            attachments: attachments.into_iter().map(Into::into).collect(),
         }),
     };
 
@@ -755,10 +1581,44 @@ async fn send_chat_message(
     }
 }
 
+/// Send a `WorkerUpdate` whenever the capabilities change, and resize the
+/// batch lane to match.
+async fn send_capability_updates(
+    tx: mpsc::Sender<RunClientMessage>,
+    mut capabilities: watch::Receiver<Capabilities>,
+    executor: Arc<ClaudeCodeExecutor>,
+) {
+    let mut previous = capabilities.borrow_and_update().clone();
+    while capabilities.changed().await.is_ok() {
+        let current = capabilities.borrow_and_update().clone();
+        let Some(update) = current.update_since(&previous) else {
+            continue;
+        };
+        if update.max_concurrent_runs.is_some() {
+            executor.resize_lanes(current.max_concurrent_runs);
+        }
+        info!(
+            agents = update.agents.is_some(),
+            labels = update.labels.is_some(),
+            max_concurrent_runs = ?update.max_concurrent_runs,
+            "Sending worker update"
+        );
+        let msg = RunClientMessage {
+            payload: Some(ClientPayload::Update(update)),
+        };
+        if tx.send(msg).await.is_err() {
+            break;
+        }
+        previous = current;
+    }
+}
+
 async fn run_heartbeat_loop(
     tx: mpsc::Sender<RunClientMessage>,
     config: Arc<Config>,
     active_count: Arc<AtomicU32>,
+    capabilities: watch::Receiver<Capabilities>,
+    draining: Arc<AtomicBool>,
 ) {
     let interval = Duration::from_secs(config.heartbeat_interval_secs);
     let mut interval_timer = tokio::time::interval(interval);
@@ -766,31 +1626,7 @@ async fn run_heartbeat_loop(
     loop {
         interval_timer.tick().await;
 
-        let runs = active_count.load(Ordering::SeqCst);
-        let status = if runs > 0 {
-            taskrun_proto::pb::WorkerStatus::Busy
-        } else {
-            taskrun_proto::pb::WorkerStatus::Idle
-        };
-
-        let status_str = if runs > 0 { "busy" } else { "idle" };
-
-        // Emit JSON event for heartbeat
-        json_output::emit_heartbeat(config.worker_id.as_str(), status_str, runs);
-
-        let heartbeat = WorkerHeartbeat {
-            worker_id: config.worker_id.as_str().to_string(),
-            status: status as i32,
-            active_runs: runs,
-            max_concurrent_runs: config.max_concurrent_runs,
-            metrics: HashMap::new(),
-            timestamp_ms: chrono::Utc::now().timestamp_millis(),
-        };
-
-        let msg = RunClientMessage {
-            payload: Some(ClientPayload::Heartbeat(heartbeat)),
-        };
-
+        let msg = heartbeat_message(&config, &active_count, &capabilities, &draining);
         if tx.send(msg).await.is_err() {
             // Channel closed, connection lost
             break;
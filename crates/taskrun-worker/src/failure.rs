@@ -0,0 +1,57 @@
+//! Classification of execution failures into `FailureReason`s.
+//!
+//! Claude Code reports a failed execution as a `result` message with
+//! `is_error` set. Its `subtype` tells a turn or budget limit apart from a
+//! generic error, and `permission_denials` lists tool uses that were refused.
+
+use taskrun_claude_sdk::SdkError;
+use taskrun_core::FailureReason;
+
+/// Reason for an error `result` message.
+pub fn result_failure_reason(subtype: Option<&str>, permission_denials: usize) -> FailureReason {
+    match subtype {
+        Some("error_max_turns") | Some("error_max_budget_usd") => FailureReason::BudgetExceeded,
+        _ if permission_denials > 0 => FailureReason::ToolDenied,
+        _ => FailureReason::SdkError,
+    }
+}
+
+/// Reason for an error returned by the SDK itself.
+pub fn sdk_failure_reason(error: &SdkError) -> FailureReason {
+    match error {
+        SdkError::Timeout => FailureReason::Timeout,
+        SdkError::ClaudeNotFound(_)
+        | SdkError::SpawnError(_)
+        | SdkError::ProcessError(_)
+        | SdkError::LimitSetup(_) => FailureReason::ProcessCrash,
+        SdkError::ResourceLimit(_) => FailureReason::ResourceLimit,
+        _ => FailureReason::SdkError,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use taskrun_claude_sdk::LimitKind;
+
+    #[test]
+    fn test_result_failure_reason() {
+        assert_eq!(
+            result_failure_reason(Some("error_max_turns"), 1),
+            FailureReason::BudgetExceeded
+        );
+        assert_eq!(
+            result_failure_reason(Some("error_during_execution"), 2),
+            FailureReason::ToolDenied
+        );
+        assert_eq!(result_failure_reason(None, 0), FailureReason::SdkError);
+        assert_eq!(
+            sdk_failure_reason(&SdkError::ProcessError("exit 1".to_string())),
+            FailureReason::ProcessCrash
+        );
+        assert_eq!(
+            sdk_failure_reason(&SdkError::ResourceLimit(LimitKind::Memory)),
+            FailureReason::ResourceLimit
+        );
+    }
+}
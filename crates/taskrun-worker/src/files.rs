@@ -0,0 +1,222 @@
+//! Input files and artifact uploads.
+//!
+//! Files uploaded for a task come with its run assignment. They are written
+//! to `.taskrun/inputs/<run_id>/` in the run's workspace before the run
+//! starts, and the prompt tells the agent where to find them. When the run
+//! completes, the contents of the artifacts it reported or its agent declares
+//! are uploaded to the control plane, which serves them at
+//! `/v1/tasks/:id/artifacts`. Files larger than one message go in chunks.
+
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use serde_json::Value;
+use tokio::sync::mpsc;
+use tracing::{info, warn};
+
+use taskrun_core::Artifact;
+use taskrun_proto::pb::run_client_message::Payload as ClientPayload;
+use taskrun_proto::pb::{ArtifactUpload, InputFile, RunClientMessage};
+
+use crate::agents::AgentProfile;
+use crate::artifacts;
+
+/// Largest artifact uploaded, in bytes (the control plane's artifact limit).
+const MAX_UPLOAD_BYTES: u64 = 64 * 1024 * 1024;
+
+/// Largest upload message, in bytes; larger artifacts are sent in chunks.
+const UPLOAD_CHUNK_BYTES: usize = 1024 * 1024;
+
+/// Where input files are staged, relative to the workspace.
+const INPUTS_DIR: &str = ".taskrun/inputs";
+
+/// Write the run's input files into `workspace`. Returns their paths
+/// relative to the workspace, in order.
+pub fn stage_inputs(
+    workspace: &Path,
+    run_id: &str,
+    files: &[InputFile],
+) -> io::Result<Vec<PathBuf>> {
+    if files.is_empty() {
+        return Ok(Vec::new());
+    }
+    let dir = Path::new(INPUTS_DIR).join(run_id);
+    std::fs::create_dir_all(workspace.join(&dir))?;
+
+    let mut staged = Vec::with_capacity(files.len());
+    for file in files {
+        // Only the file name; never write outside the inputs directory
+        let Some(name) = Path::new(&file.name).file_name() else {
+            warn!(run_id = %run_id, name = %file.name, "Skipping input file without a usable name");
+            continue;
+        };
+        let path = dir.join(name);
+        std::fs::write(workspace.join(&path), &file.content)?;
+        staged.push(path);
+    }
+    Ok(staged)
+}
+
+/// `input_json` with a note listing the staged input files appended to its
+/// task (or to the raw input, if it has no `task` field).
+pub fn with_input_files(input_json: &str, paths: &[PathBuf]) -> String {
+    if paths.is_empty() {
+        return input_json.to_string();
+    }
+    let mut note = String::from("\n\nInput files for this task:");
+    for path in paths {
+        note.push_str(&format!("\n- {}", path.display()));
+    }
+
+    if let Ok(mut parsed) = serde_json::from_str::<Value>(input_json) {
+        if let Some(Value::String(task)) = parsed.get_mut("task") {
+            task.push_str(&note);
+            return parsed.to_string();
+        }
+    }
+    format!("{}{}", input_json, note)
+}
+
+/// The artifacts of a completed run: those it reported, then the files in
+/// `workspace` its agent declares or, with `changed_artifacts`, changed since
+/// the run `started`.
+pub async fn completed_artifacts(
+    reported: &[Artifact],
+    workspace: &Path,
+    agent: &AgentProfile,
+    started: SystemTime,
+) -> Vec<Artifact> {
+    let workspace = workspace.to_path_buf();
+    let patterns = agent.artifacts.clone();
+    let changed_since = agent.changed_artifacts.then_some(started);
+    let found = tokio::task::spawn_blocking(move || {
+        artifacts::workspace_artifacts(&workspace, &patterns, changed_since)
+    })
+    .await
+    .unwrap_or_default();
+
+    let mut all = reported.to_vec();
+    for artifact in found {
+        if !all.iter().any(|a| a.uri == artifact.uri) {
+            all.push(artifact);
+        }
+    }
+    all
+}
+
+/// Upload the contents of a completed run's artifacts. Artifacts that are
+/// too large or no longer readable are skipped.
+pub async fn upload_artifacts(
+    tx: &mpsc::Sender<RunClientMessage>,
+    run_id: &str,
+    artifacts: &[Artifact],
+) {
+    for artifact in artifacts {
+        let Some(path) = artifact.uri.strip_prefix("file://") else {
+            continue;
+        };
+        let content = match tokio::fs::metadata(path).await {
+            Ok(metadata) if metadata.len() > MAX_UPLOAD_BYTES => {
+                warn!(run_id = %run_id, artifact = %artifact.name, size_bytes = metadata.len(), "Artifact too large to upload");
+                continue;
+            }
+            Ok(_) => tokio::fs::read(path).await,
+            Err(e) => Err(e),
+        };
+        let content = match content {
+            Ok(content) => content,
+            Err(e) => {
+                warn!(run_id = %run_id, artifact = %artifact.name, error = %e, "Failed to read artifact");
+                continue;
+            }
+        };
+
+        info!(run_id = %run_id, artifact = %artifact.name, size_bytes = content.len(), "Uploading artifact");
+        for msg in upload_messages(run_id, artifact, &content) {
+            if let Err(e) = tx.send(msg).await {
+                warn!(run_id = %run_id, error = %e, "Failed to upload artifact");
+                return;
+            }
+        }
+    }
+}
+
+/// The messages uploading `content`: one, or one per chunk.
+fn upload_messages(run_id: &str, artifact: &Artifact, content: &[u8]) -> Vec<RunClientMessage> {
+    let chunks: Vec<&[u8]> = if content.is_empty() {
+        vec![content]
+    } else {
+        content.chunks(UPLOAD_CHUNK_BYTES).collect()
+    };
+    let last = chunks.len() - 1;
+    chunks
+        .into_iter()
+        .enumerate()
+        .map(|(i, chunk)| RunClientMessage {
+            payload: Some(ClientPayload::ArtifactUpload(ArtifactUpload {
+                run_id: run_id.to_string(),
+                artifact: Some(artifact.clone().into()),
+                content: chunk.to_vec(),
+                offset: (i * UPLOAD_CHUNK_BYTES) as u64,
+                more: i < last,
+            })),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inputs_are_staged_and_listed_in_task() {
+        let workspace = std::env::temp_dir().join(format!("taskrun-inputs-{}", std::process::id()));
+        let files = vec![InputFile {
+            name: "../../data.csv".to_string(),
+            content: b"a,b\n1,2\n".to_vec(),
+        }];
+
+        let paths = stage_inputs(&workspace, "run-1", &files).unwrap();
+        assert_eq!(paths, vec![PathBuf::from(".taskrun/inputs/run-1/data.csv")]);
+        assert_eq!(
+            std::fs::read(workspace.join(&paths[0])).unwrap(),
+            files[0].content
+        );
+
+        let input = with_input_files(r#"{"task":"Sum the columns"}"#, &paths);
+        let task = serde_json::from_str::<Value>(&input).unwrap()["task"].clone();
+        assert_eq!(
+            task,
+            "Sum the columns\n\nInput files for this task:\n- .taskrun/inputs/run-1/data.csv"
+        );
+        assert_eq!(with_input_files("plain", &[]), "plain");
+
+        std::fs::remove_dir_all(&workspace).unwrap();
+    }
+
+    #[test]
+    fn test_large_artifacts_upload_in_chunks() {
+        let artifact = Artifact::new("build.log", "file:///tmp/build.log");
+        let uploads = |content: &[u8]| -> Vec<(u64, usize, bool)> {
+            upload_messages("run-1", &artifact, content)
+                .into_iter()
+                .map(|msg| match msg.payload {
+                    Some(ClientPayload::ArtifactUpload(u)) => (u.offset, u.content.len(), u.more),
+                    other => panic!("expected an artifact upload, got {:?}", other),
+                })
+                .collect()
+        };
+
+        let chunk = UPLOAD_CHUNK_BYTES;
+        assert_eq!(
+            uploads(&vec![0; chunk * 2 + 10]),
+            [
+                (0, chunk, true),
+                (chunk as u64, chunk, true),
+                (2 * chunk as u64, 10, false)
+            ]
+        );
+        assert_eq!(uploads(b""), [(0, 0, false)]);
+    }
+}
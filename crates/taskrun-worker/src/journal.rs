@@ -0,0 +1,383 @@
+//! Persistent run journal (headless and JSON modes).
+//!
+//! With `--journal-dir`, the worker writes a JSON file per run to
+//! `<dir>/runs/` as the run progresses: its assignment, status, how much
+//! output it has sent and, once it completes, the Claude session it can be
+//! continued in. The worker ID is kept in `<dir>/worker_id`, so a restarted
+//! worker reconnects as the same worker and continuations of its runs still
+//! reach it.
+//!
+//! At startup, runs the journal still has as assigned or running were lost
+//! with the previous process. They are marked interrupted and, once connected,
+//! reported as FAILED with `WORKER_LOST` instead of waiting for the control
+//! plane to give up on them. Completed runs keep their sessions across
+//! restarts and reconnects, along with their worktrees.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use taskrun_core::WorkerId;
+use thiserror::Error;
+use tracing::{info, warn};
+
+use crate::worktrees::RunWorktree;
+
+/// Output offsets are recorded every this many chunks.
+const OUTPUT_RECORD_INTERVAL: u64 = 20;
+
+/// Journal errors.
+#[derive(Debug, Error)]
+pub enum JournalError {
+    #[error("Failed to access run journal '{path}': {source}")]
+    Io {
+        path: String,
+        source: std::io::Error,
+    },
+}
+
+/// Where a journaled run is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JournalStatus {
+    Assigned,
+    Running,
+    /// Done; can be continued in its session.
+    Completed,
+    /// Assigned or running when the previous worker process ended.
+    Interrupted,
+}
+
+/// What the journal keeps of one run.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub run_id: String,
+    pub task_id: String,
+    pub agent_name: String,
+    pub status: JournalStatus,
+    /// Session to continue the run in, once a turn completed.
+    #[serde(default)]
+    pub session_id: Option<String>,
+    /// Whether the turn in progress is a follow-up.
+    #[serde(default)]
+    pub follow_up: bool,
+    /// Output chunks the turn in progress has sent.
+    #[serde(default)]
+    pub output_seq: u64,
+    #[serde(default)]
+    pub worktree: Option<RunWorktree>,
+    pub updated_at_ms: i64,
+}
+
+/// The journal directory.
+#[derive(Debug)]
+pub struct RunJournal {
+    dir: PathBuf,
+}
+
+impl JournalEntry {
+    /// Entry of a newly assigned run.
+    pub fn assigned(run_id: &str, task_id: &str, agent_name: &str) -> Self {
+        Self {
+            run_id: run_id.to_string(),
+            task_id: task_id.to_string(),
+            agent_name: agent_name.to_string(),
+            status: JournalStatus::Assigned,
+            session_id: None,
+            follow_up: false,
+            output_seq: 0,
+            worktree: None,
+            updated_at_ms: 0,
+        }
+    }
+}
+
+impl RunJournal {
+    /// Open the journal in `dir`, creating it if needed, and mark runs the
+    /// previous process left unfinished as interrupted.
+    pub fn open(dir: impl AsRef<Path>) -> Result<Self, JournalError> {
+        let journal = Self {
+            dir: dir.as_ref().to_path_buf(),
+        };
+        let runs = journal.runs_dir();
+        std::fs::create_dir_all(&runs).map_err(|source| io_error(&runs, source))?;
+
+        for mut entry in journal.entries() {
+            if matches!(
+                entry.status,
+                JournalStatus::Assigned | JournalStatus::Running
+            ) {
+                warn!(run_id = %entry.run_id, output_seq = entry.output_seq, "Run was interrupted by a worker restart");
+                entry.status = JournalStatus::Interrupted;
+                journal.record(&mut entry);
+            }
+        }
+        Ok(journal)
+    }
+
+    /// The worker's ID, generated on first use.
+    pub fn worker_id(&self) -> Result<WorkerId, JournalError> {
+        let path = self.dir.join("worker_id");
+        match std::fs::read_to_string(&path) {
+            Ok(id) if !id.trim().is_empty() => return Ok(WorkerId::new(id.trim())),
+            Ok(_) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(source) => return Err(io_error(&path, source)),
+        }
+        let id = WorkerId::generate();
+        std::fs::write(&path, id.as_str()).map_err(|source| io_error(&path, source))?;
+        info!(worker_id = %id, "Generated worker ID");
+        Ok(id)
+    }
+
+    /// All entries, in no particular order. Unreadable ones are skipped.
+    pub fn entries(&self) -> Vec<JournalEntry> {
+        let Ok(files) = std::fs::read_dir(self.runs_dir()) else {
+            return Vec::new();
+        };
+        files
+            .flatten()
+            .map(|file| file.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .filter_map(|path| {
+                let json = std::fs::read_to_string(&path).ok()?;
+                serde_json::from_str(&json)
+                    .map_err(|e| warn!(path = %path.display(), error = %e, "Skipping unreadable journal entry"))
+                    .ok()
+            })
+            .collect()
+    }
+
+    /// Runs lost with the previous worker process, to report as failed.
+    pub fn interrupted(&self) -> Vec<JournalEntry> {
+        self.entries()
+            .into_iter()
+            .filter(|entry| entry.status == JournalStatus::Interrupted)
+            .collect()
+    }
+
+    /// Runs that can be continued: completed ones, and follow-ups that were
+    /// interrupted, whose earlier session is still there.
+    pub fn sessions(&self) -> Vec<JournalEntry> {
+        self.entries()
+            .into_iter()
+            .filter(|entry| entry.session_id.is_some())
+            .collect()
+    }
+
+    /// Worktrees of runs that can be continued.
+    pub fn worktrees(&self) -> Vec<PathBuf> {
+        self.sessions()
+            .into_iter()
+            .filter_map(|entry| entry.worktree.map(|worktree| worktree.path))
+            .collect()
+    }
+
+    /// Settle an interrupted run once it has been reported: a follow-up
+    /// goes back to its last completed turn, anything else is forgotten.
+    pub fn resolve_interrupted(&self, entry: &JournalEntry) {
+        if entry.follow_up && entry.session_id.is_some() {
+            let mut entry = entry.clone();
+            entry.status = JournalStatus::Completed;
+            entry.follow_up = false;
+            entry.output_seq = 0;
+            self.record(&mut entry);
+        } else {
+            self.remove(&entry.run_id);
+        }
+    }
+
+    /// Write `entry`, replacing the run's previous one. Failures are logged;
+    /// a run goes on without its journal entry.
+    pub fn record(&self, entry: &mut JournalEntry) {
+        entry.updated_at_ms = chrono::Utc::now().timestamp_millis();
+        let path = self.entry_path(&entry.run_id);
+        let tmp = path.with_extension("json.tmp");
+        // Written aside and renamed, so a crash never leaves half an entry
+        let written = serde_json::to_vec_pretty(entry)
+            .map_err(std::io::Error::from)
+            .and_then(|json| std::fs::write(&tmp, json))
+            .and_then(|()| std::fs::rename(&tmp, &path));
+        if let Err(e) = written {
+            warn!(run_id = %entry.run_id, path = %path.display(), error = %e, "Failed to write run journal entry");
+        }
+    }
+
+    /// Forget a run.
+    pub fn remove(&self, run_id: &str) {
+        let path = self.entry_path(run_id);
+        match std::fs::remove_file(&path) {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => {
+                warn!(run_id = %run_id, path = %path.display(), error = %e, "Failed to remove run journal entry")
+            }
+        }
+    }
+
+    fn runs_dir(&self) -> PathBuf {
+        self.dir.join("runs")
+    }
+
+    fn entry_path(&self, run_id: &str) -> PathBuf {
+        // Run IDs are UUIDs; keep anything else from leaving the directory
+        let name: String = run_id
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '-' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        self.runs_dir().join(format!("{}.json", name))
+    }
+}
+
+/// The journal entry of one turn of a run, written as the turn progresses.
+/// Without a journal it does nothing.
+pub struct RunRecord {
+    journal: Option<Arc<RunJournal>>,
+    entry: JournalEntry,
+}
+
+impl RunRecord {
+    /// Start recording a newly assigned run.
+    pub fn assigned(
+        journal: Option<Arc<RunJournal>>,
+        run_id: &str,
+        task_id: &str,
+        agent_name: &str,
+    ) -> Self {
+        let mut record = Self {
+            journal,
+            entry: JournalEntry::assigned(run_id, task_id, agent_name),
+        };
+        record.write();
+        record
+    }
+
+    /// Start recording a follow-up turn of a run last continued in
+    /// `session_id`.
+    pub fn follow_up(
+        journal: Option<Arc<RunJournal>>,
+        run_id: &str,
+        task_id: &str,
+        agent_name: &str,
+        session_id: &str,
+        worktree: Option<RunWorktree>,
+    ) -> Self {
+        let mut entry = JournalEntry::assigned(run_id, task_id, agent_name);
+        entry.status = JournalStatus::Running;
+        entry.session_id = Some(session_id.to_string());
+        entry.follow_up = true;
+        entry.worktree = worktree;
+        let mut record = Self { journal, entry };
+        record.write();
+        record
+    }
+
+    /// The run started executing, in `worktree` if it has one.
+    pub fn running(&mut self, worktree: Option<&RunWorktree>) {
+        self.entry.status = JournalStatus::Running;
+        self.entry.worktree = worktree.cloned();
+        self.write();
+    }
+
+    /// The turn has sent `seq` output chunks.
+    pub fn output(&mut self, seq: u64) {
+        self.entry.output_seq = seq;
+        if seq % OUTPUT_RECORD_INTERVAL == 0 {
+            self.write();
+        }
+    }
+
+    /// The turn completed; the run can be continued in `session_id`.
+    pub fn completed(&mut self, session_id: &str, worktree: Option<&RunWorktree>) {
+        self.entry.status = JournalStatus::Completed;
+        self.entry.session_id = Some(session_id.to_string());
+        self.entry.follow_up = false;
+        self.entry.output_seq = 0;
+        self.entry.worktree = worktree.cloned();
+        self.write();
+    }
+
+    /// The turn ended. Unless it completed, a follow-up goes back to the
+    /// session of its last completed turn and anything else is forgotten.
+    pub fn ended(&mut self) {
+        if self.entry.status == JournalStatus::Completed {
+            return;
+        }
+        match self.entry.session_id.clone() {
+            Some(session_id) if self.entry.follow_up => {
+                let worktree = self.entry.worktree.clone();
+                self.completed(&session_id, worktree.as_ref());
+            }
+            _ => {
+                if let Some(journal) = &self.journal {
+                    journal.remove(&self.entry.run_id);
+                }
+            }
+        }
+    }
+
+    fn write(&mut self) {
+        if let Some(journal) = &self.journal {
+            journal.record(&mut self.entry);
+        }
+    }
+}
+
+fn io_error(path: &Path, source: std::io::Error) -> JournalError {
+    JournalError::Io {
+        path: path.display().to_string(),
+        source,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_restart_interrupts_unfinished_runs() {
+        let dir = std::env::temp_dir().join(format!("taskrun-journal-{}", std::process::id()));
+        let journal = Arc::new(RunJournal::open(&dir).unwrap());
+        let worker_id = journal.worker_id().unwrap();
+
+        let mut done = RunRecord::assigned(Some(journal.clone()), "run-1", "task-1", "general");
+        done.running(None);
+        done.completed("session-1", None);
+        let mut lost = RunRecord::assigned(Some(journal.clone()), "run-2", "task-2", "general");
+        lost.running(None);
+        lost.output(OUTPUT_RECORD_INTERVAL);
+        let _follow_up = RunRecord::follow_up(
+            Some(journal.clone()),
+            "run-1",
+            "task-1",
+            "general",
+            "session-1",
+            None,
+        );
+
+        // As if the worker crashed here
+        let journal = RunJournal::open(&dir).unwrap();
+        assert_eq!(journal.worker_id().unwrap(), worker_id);
+        let mut interrupted = journal.interrupted();
+        interrupted.sort_by(|a, b| a.run_id.cmp(&b.run_id));
+        assert_eq!(interrupted.len(), 2);
+        assert!(interrupted[0].follow_up);
+        assert_eq!(interrupted[1].output_seq, OUTPUT_RECORD_INTERVAL);
+
+        for entry in &interrupted {
+            journal.resolve_interrupted(entry);
+        }
+        let entries = journal.entries();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].status, JournalStatus::Completed);
+        assert_eq!(entries[0].session_id.as_deref(), Some("session-1"));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -5,6 +5,8 @@ use std::collections::HashMap;
 use std::io::{self, Write};
 use std::sync::atomic::{AtomicBool, Ordering};
 
+use crate::preflight::ReadinessReport;
+
 /// Global flag to enable JSON output mode.
 static JSON_MODE_ENABLED: AtomicBool = AtomicBool::new(false);
 
@@ -24,14 +26,18 @@ pub fn is_json_mode() -> bool {
 pub enum JsonEventType {
     WorkerConnected,
     WorkerDisconnected,
+    ReconnectScheduled,
+    WorkerDraining,
     TaskAssigned,
     TaskRunning,
     OutputChunk,
     TaskCompleted,
     TaskFailed,
     TaskCancelled,
+    BranchUpdated,
     Heartbeat,
     ContinueReceived,
+    Readiness,
     Error,
 }
 
@@ -90,6 +96,43 @@ pub fn emit_worker_disconnected(worker_id: &str, reason: Option<&str>) {
     .emit();
 }
 
+/// Emit a reconnect_scheduled event: the next connection attempt, `delay`
+/// from now, after `failures` failed attempts in a row. `reason` is
+/// `backoff`, or `network_change` for an attempt made at once.
+pub fn emit_reconnect_scheduled(
+    worker_id: &str,
+    failures: u32,
+    delay: std::time::Duration,
+    reason: &str,
+) {
+    let next_attempt_at = chrono::Utc::now()
+        + chrono::Duration::from_std(delay).unwrap_or_else(|_| chrono::Duration::zero());
+    JsonEvent::new(
+        JsonEventType::ReconnectScheduled,
+        serde_json::json!({
+            "worker_id": worker_id,
+            "failures": failures,
+            "delay_ms": delay.as_millis() as u64,
+            "next_attempt_at": next_attempt_at.to_rfc3339(),
+            "reason": reason,
+        }),
+    )
+    .emit();
+}
+
+/// Emit a worker_draining event.
+pub fn emit_worker_draining(worker_id: &str, active_runs: u32, timeout_secs: u64) {
+    JsonEvent::new(
+        JsonEventType::WorkerDraining,
+        serde_json::json!({
+            "worker_id": worker_id,
+            "active_runs": active_runs,
+            "timeout_secs": timeout_secs,
+        }),
+    )
+    .emit();
+}
+
 /// Emit a task_assigned event.
 pub fn emit_task_assigned(run_id: &str, task_id: &str, agent_name: &str) {
     JsonEvent::new(
@@ -165,6 +208,19 @@ pub fn emit_task_cancelled(run_id: &str, reason: &str) {
     .emit();
 }
 
+/// Emit a branch_updated event.
+pub fn emit_branch_updated(run_id: &str, branch: &str, commit: &str) {
+    JsonEvent::new(
+        JsonEventType::BranchUpdated,
+        serde_json::json!({
+            "run_id": run_id,
+            "branch": branch,
+            "commit": commit,
+        }),
+    )
+    .emit();
+}
+
 /// Emit a heartbeat event.
 pub fn emit_heartbeat(worker_id: &str, status: &str, active_runs: u32) {
     JsonEvent::new(
@@ -178,6 +234,20 @@ pub fn emit_heartbeat(worker_id: &str, status: &str, active_runs: u32) {
     .emit();
 }
 
+/// Emit the pre-flight readiness report.
+pub fn emit_readiness(report: &ReadinessReport) {
+    JsonEvent::new(
+        JsonEventType::Readiness,
+        serde_json::json!({
+            "ready": report.is_ready(),
+            "checks": report.checks,
+            "ready_agents": report.ready_agents,
+            "unready_agents": report.unready_agents,
+        }),
+    )
+    .emit();
+}
+
 /// Emit a continue_received event.
 pub fn emit_continue_received(run_id: &str, message_len: usize) {
     JsonEvent::new(
@@ -0,0 +1,101 @@
+//! Worker labels.
+//!
+//! A worker registers with the labels given with `--label key=value`
+//! (repeatable) or in `TASKRUN_WORKER_LABELS` (comma-separated), plus labels
+//! it detects about its host:
+//!
+//! | Label | Value |
+//! |-------|-------|
+//! | `os` | Operating system, e.g. `linux` or `macos` |
+//! | `arch` | CPU architecture, e.g. `x86_64` or `aarch64` |
+//! | `hostname` | Host name of the machine |
+//! | `claude_version` | `claude --version` output, when the CLI runs on the host |
+//!
+//! Given labels take precedence over detected ones with the same key.
+//! `--no-detect-labels` turns detection off.
+
+use crate::manifest;
+
+/// The worker's labels: `given` ones, then detected ones whose key was not
+/// given. `claude_path` is `None` when Claude does not run on the host.
+pub fn resolve(
+    given: &[(String, String)],
+    detect: bool,
+    claude_path: Option<&str>,
+) -> Vec<(String, String)> {
+    let detected = if detect {
+        detected(claude_path.and_then(manifest::detect_claude_version))
+    } else {
+        Vec::new()
+    };
+    merge(given, detected)
+}
+
+/// Host name of the machine, falling back to `$HOSTNAME`.
+pub fn hostname() -> String {
+    system_hostname()
+        .or_else(|| std::env::var("HOSTNAME").ok())
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+fn detected(claude_version: Option<String>) -> Vec<(String, String)> {
+    let mut labels = vec![
+        ("os".to_string(), std::env::consts::OS.to_string()),
+        ("arch".to_string(), std::env::consts::ARCH.to_string()),
+        ("hostname".to_string(), hostname()),
+    ];
+    if let Some(version) = claude_version {
+        labels.push(("claude_version".to_string(), version));
+    }
+    labels
+}
+
+fn merge(given: &[(String, String)], detected: Vec<(String, String)>) -> Vec<(String, String)> {
+    let mut labels = given.to_vec();
+    for (key, value) in detected {
+        if !given.iter().any(|(k, _)| *k == key) {
+            labels.push((key, value));
+        }
+    }
+    labels
+}
+
+#[cfg(unix)]
+fn system_hostname() -> Option<String> {
+    let mut buf = [0u8; 256];
+    // SAFETY: buf is valid for buf.len() bytes
+    if unsafe { libc::gethostname(buf.as_mut_ptr().cast(), buf.len()) } != 0 {
+        return None;
+    }
+    let len = buf.iter().position(|&b| b == 0).unwrap_or(buf.len());
+    Some(String::from_utf8_lossy(&buf[..len]).into_owned())
+}
+
+#[cfg(not(unix))]
+fn system_hostname() -> Option<String> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn label(key: &str, value: &str) -> (String, String) {
+        (key.to_string(), value.to_string())
+    }
+
+    #[test]
+    fn test_given_labels_override_detected_ones() {
+        let given = [label("region", "eu"), label("os", "custom")];
+        let labels = merge(&given, detected(Some("2.0.1 (Claude Code)".to_string())));
+
+        assert_eq!(labels[..2], given);
+        assert!(labels.contains(&label("arch", std::env::consts::ARCH)));
+        assert!(labels.contains(&label("claude_version", "2.0.1 (Claude Code)")));
+        assert_eq!(labels.iter().filter(|(k, _)| k == "os").count(), 1);
+        assert!(labels.iter().any(|(k, v)| k == "hostname" && !v.is_empty()));
+
+        assert_eq!(resolve(&given, false, None), given);
+    }
+}
@@ -0,0 +1,137 @@
+//! Execution lanes for interactive and batch runs.
+//!
+//! Batch runs share `max_concurrent_runs` slots; interactive runs (streaming
+//! chat, session continuation) get their own `interactive_slots` so a chat
+//! turn never waits behind queued batch work. An interactive run may also
+//! borrow an idle batch slot.
+//!
+//! The batch lane can be resized while runs hold slots; a shrink takes
+//! effect as those runs finish.
+
+use std::sync::{Arc, Mutex};
+
+use taskrun_core::TaskPriority;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+/// Semaphores bounding concurrent runs per lane.
+#[derive(Clone)]
+pub struct RunLanes {
+    batch: Arc<Semaphore>,
+    batch_slots: Arc<Mutex<u32>>,
+    interactive: Arc<Semaphore>,
+    interactive_slots: u32,
+}
+
+impl RunLanes {
+    /// Create lanes with `batch_slots` batch and `interactive_slots` interactive runs.
+    pub fn new(batch_slots: u32, interactive_slots: u32) -> Self {
+        let batch_slots = batch_slots.max(1);
+        Self {
+            batch: Arc::new(Semaphore::new(batch_slots as usize)),
+            batch_slots: Arc::new(Mutex::new(batch_slots)),
+            interactive: Arc::new(Semaphore::new(interactive_slots as usize)),
+            interactive_slots,
+        }
+    }
+
+    /// Wait for a slot in the lane for `priority`. The slot is released when
+    /// the permit is dropped.
+    pub async fn acquire(&self, priority: TaskPriority) -> OwnedSemaphorePermit {
+        let permit = match priority {
+            TaskPriority::Batch => self.batch.clone().acquire_owned().await,
+            TaskPriority::Interactive => {
+                if let Ok(permit) = self.interactive.clone().try_acquire_owned() {
+                    return permit;
+                }
+                if let Ok(permit) = self.batch.clone().try_acquire_owned() {
+                    return permit;
+                }
+                // Both full: queue in the interactive lane (or batch if it has none)
+                let lane = if self.interactive_slots > 0 {
+                    &self.interactive
+                } else {
+                    &self.batch
+                };
+                lane.clone().acquire_owned().await
+            }
+        };
+        permit.expect("run lane semaphores are never closed")
+    }
+
+    /// Change the number of batch slots. Extra slots are usable at once;
+    /// removed slots are taken back as soon as they are free.
+    pub fn resize_batch(&self, slots: u32) {
+        let slots = slots.max(1);
+        let mut current = self.batch_slots.lock().unwrap();
+        if slots > *current {
+            self.batch.add_permits((slots - *current) as usize);
+        } else if slots < *current {
+            let excess = (*current - slots) as usize;
+            let pending = excess - self.batch.forget_permits(excess);
+            if pending > 0 {
+                let batch = self.batch.clone();
+                tokio::spawn(async move {
+                    if let Ok(permits) = batch.acquire_many_owned(pending as u32).await {
+                        permits.forget();
+                    }
+                });
+            }
+        }
+        *current = slots;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_interactive_bypasses_full_batch_lane() {
+        let lanes = RunLanes::new(1, 1);
+        let _batch = lanes.acquire(TaskPriority::Batch).await;
+
+        // Batch lane is full, but interactive work still starts
+        let interactive = tokio::time::timeout(
+            Duration::from_millis(50),
+            lanes.acquire(TaskPriority::Interactive),
+        )
+        .await;
+        assert!(interactive.is_ok());
+
+        // Another batch run waits for the batch slot
+        let queued = tokio::time::timeout(
+            Duration::from_millis(50),
+            lanes.acquire(TaskPriority::Batch),
+        )
+        .await;
+        assert!(queued.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_batch_lane_resizes_around_running_work() {
+        let lanes = RunLanes::new(1, 0);
+        let running = lanes.acquire(TaskPriority::Batch).await;
+
+        lanes.resize_batch(2);
+        let second = tokio::time::timeout(
+            Duration::from_millis(50),
+            lanes.acquire(TaskPriority::Batch),
+        )
+        .await
+        .expect("grown lane has a free slot");
+
+        // Shrinking back to one waits for both runs to finish
+        lanes.resize_batch(1);
+        drop(running);
+        drop(second);
+        tokio::task::yield_now().await;
+        let _only = lanes.acquire(TaskPriority::Batch).await;
+        let queued = tokio::time::timeout(
+            Duration::from_millis(50),
+            lanes.acquire(TaskPriority::Batch),
+        )
+        .await;
+        assert!(queued.is_err());
+    }
+}
@@ -0,0 +1,304 @@
+//! Local task execution (`taskrun-worker run`).
+//!
+//! Runs one task through an agent in the working directory without a control
+//! plane, for trying agent definitions before deploying them. The run goes
+//! through the same executor, manifest and output filters as an assigned
+//! run. Its output streams to stdout, and the run is recorded in the output
+//! directory (`--output-dir`, by default `.taskrun/runs/<run ID>` in the
+//! working directory):
+//!
+//! | File | Contents |
+//! |------|----------|
+//! | `transcript.txt` | The run's output, as streamed |
+//! | `events.jsonl` | Run events, one JSON object per line |
+//! | `result.json` | Status, error, model, duration, manifest and artifacts |
+//!
+//! Ctrl-C stops the Claude process and records the run as cancelled.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Instant, SystemTime};
+
+use serde::Serialize;
+use taskrun_core::{Artifact, FailureReason, RunEvent, RunId, RunLimits, RunManifest, TaskId};
+use taskrun_proto::pb::RunAssignment;
+use thiserror::Error;
+use tokio::sync::mpsc;
+
+use crate::config::Config;
+use crate::executor::{ClaudeCodeExecutor, OutputChunk};
+use crate::files;
+use crate::secrets::Redactor;
+
+/// Local run errors.
+#[derive(Debug, Error)]
+pub enum LocalRunError {
+    #[error("Failed to write run record '{path}': {source}")]
+    Io {
+        path: String,
+        source: std::io::Error,
+    },
+}
+
+/// How a local run ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LocalRunStatus {
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+/// Contents of `result.json`.
+#[derive(Debug, Serialize)]
+struct RunResult<'a> {
+    run_id: &'a str,
+    task_id: &'a str,
+    agent_name: &'a str,
+    status: LocalRunStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    failure_reason: Option<FailureReason>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    model_used: Option<String>,
+    duration_ms: u64,
+    manifest: &'a RunManifest,
+    artifacts: Vec<Artifact>,
+}
+
+/// A finished local run.
+#[derive(Debug)]
+pub struct LocalRun {
+    pub run_id: String,
+    pub status: LocalRunStatus,
+    pub error: Option<String>,
+    pub output_dir: PathBuf,
+}
+
+/// Run `input_json` through `agent_name` in `working_dir`, recording the run
+/// in `output_dir` (or the default directory).
+pub async fn run(
+    config: Arc<Config>,
+    agent_name: &str,
+    input_json: &str,
+    working_dir: &Path,
+    output_dir: Option<&Path>,
+) -> Result<LocalRun, LocalRunError> {
+    let run_id = RunId::generate();
+    let task_id = TaskId::generate();
+    let output_dir = output_dir.map_or_else(
+        || {
+            working_dir
+                .join(".taskrun")
+                .join("runs")
+                .join(run_id.as_str())
+        },
+        Path::to_path_buf,
+    );
+    std::fs::create_dir_all(&output_dir).map_err(|source| io_error(&output_dir, source))?;
+    let mut transcript = create(&output_dir.join("transcript.txt"))?;
+    let mut events = create(&output_dir.join("events.jsonl"))?;
+
+    let executor = ClaudeCodeExecutor::new(config);
+    let assignment = RunAssignment {
+        run_id: run_id.to_string(),
+        task_id: task_id.to_string(),
+        agent_name: agent_name.to_string(),
+        input_json: input_json.to_string(),
+        ..Default::default()
+    };
+    let manifest = executor.run_manifest(&assignment);
+    let limits = RunLimits::default();
+    let redactor = Redactor::default();
+    let mut output_filter = executor.output_filter(agent_name);
+    let (chunk_tx, mut chunk_rx) = mpsc::channel::<OutputChunk>(100);
+    let (event_tx, mut event_rx) = mpsc::channel::<RunEvent>(100);
+
+    let started = SystemTime::now();
+    let clock = Instant::now();
+    let execution = executor.execute(
+        working_dir,
+        input_json,
+        &manifest,
+        &limits,
+        chunk_tx,
+        event_tx,
+        run_id.clone(),
+        task_id.clone(),
+        None,
+        &redactor,
+    );
+    let mut line_open = false;
+    let stream_output = async {
+        let mut output_redactor = redactor.chunks();
+        let mut open = true;
+        while open {
+            let content = match chunk_rx.recv().await {
+                Some(chunk) if chunk.is_final => continue,
+                Some(chunk) => output_redactor.push(&chunk.content),
+                None => {
+                    open = false;
+                    output_redactor.finish()
+                }
+            };
+            if content.is_empty() {
+                continue;
+            }
+            let content = output_filter.filter(content);
+            print!("{}", content);
+            let _ = std::io::stdout().flush();
+            transcript.write_all(content.as_bytes())?;
+            line_open = !content.ends_with('\n');
+        }
+        Ok::<_, std::io::Error>(())
+    };
+    let record_events = async {
+        while let Some(event) = event_rx.recv().await {
+            write_event(&mut events, &redactor.redact_event(event))?;
+        }
+        Ok::<_, std::io::Error>(())
+    };
+
+    let outcome = tokio::select! {
+        (result, streamed, recorded) = async { tokio::join!(execution, stream_output, record_events) } => {
+            streamed.map_err(|source| io_error(&output_dir.join("transcript.txt"), source))?;
+            recorded.map_err(|source| io_error(&output_dir.join("events.jsonl"), source))?;
+            Some(result)
+        }
+        _ = tokio::signal::ctrl_c() => None,
+    };
+    let duration_ms = clock.elapsed().as_millis() as u64;
+    if line_open {
+        println!();
+    }
+
+    if let Some(event) = output_filter.into_event(run_id.clone(), task_id.clone()) {
+        write_event(&mut events, &event)
+            .map_err(|source| io_error(&output_dir.join("events.jsonl"), source))?;
+    }
+
+    let agent = executor.agent(agent_name);
+    let (status, error, failure_reason, model_used, artifacts) = match outcome {
+        Some(Ok(result)) => {
+            let artifacts =
+                files::completed_artifacts(&result.artifacts, working_dir, &agent, started).await;
+            (
+                LocalRunStatus::Completed,
+                None,
+                None,
+                Some(result.model_used),
+                artifacts,
+            )
+        }
+        Some(Err(e)) => (
+            LocalRunStatus::Failed,
+            Some(redactor.redact(&e.to_string())),
+            Some(e.failure_reason()),
+            None,
+            Vec::new(),
+        ),
+        None => (
+            LocalRunStatus::Cancelled,
+            Some("Interrupted".to_string()),
+            None,
+            None,
+            Vec::new(),
+        ),
+    };
+
+    let result = RunResult {
+        run_id: run_id.as_str(),
+        task_id: task_id.as_str(),
+        agent_name,
+        status,
+        error: error.clone(),
+        failure_reason,
+        model_used,
+        duration_ms,
+        manifest: &manifest,
+        artifacts,
+    };
+    let result_path = output_dir.join("result.json");
+    let json = serde_json::to_string_pretty(&result).unwrap_or_default();
+    std::fs::write(&result_path, json).map_err(|source| io_error(&result_path, source))?;
+
+    Ok(LocalRun {
+        run_id: run_id.to_string(),
+        status,
+        error,
+        output_dir,
+    })
+}
+
+fn create(path: &Path) -> Result<std::fs::File, LocalRunError> {
+    std::fs::File::create(path).map_err(|source| io_error(path, source))
+}
+
+fn write_event(file: &mut std::fs::File, event: &RunEvent) -> std::io::Result<()> {
+    let line = serde_json::to_string(event).map_err(std::io::Error::other)?;
+    writeln!(file, "{}", line)
+}
+
+fn io_error(path: &Path, source: std::io::Error) -> LocalRunError {
+    LocalRunError::Io {
+        path: path.display().to_string(),
+        source,
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+
+    const FAKE_CLAUDE: &str = r#"#!/bin/sh
+printf '%s\n' '{"type":"system","subtype":"init","session_id":"s1","model":"claude-sonnet-4-5","tools":[],"cwd":"."}'
+printf '%s\n' '{"type":"assistant","message":{"id":"m1","role":"assistant","model":"claude-sonnet-4-5","content":[{"type":"text","text":"hello"}]},"session_id":"s1"}'
+printf '%s\n' '{"type":"result","subtype":"success","result":"hello","session_id":"s1","is_error":false,"duration_ms":1,"num_turns":1}'
+"#;
+
+    #[tokio::test]
+    async fn test_run_records_output_events_and_result() {
+        let dir = std::env::temp_dir().join(format!("taskrun-local-run-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let claude = dir.join("claude");
+        std::fs::write(&claude, FAKE_CLAUDE).unwrap();
+        std::fs::set_permissions(&claude, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let config = Config {
+            claude_path: claude.display().to_string(),
+            progress_tool: false,
+            ..Config::default()
+        };
+
+        let output_dir = dir.join("out");
+        let run = run(
+            Arc::new(config),
+            "general",
+            r#"{"task": "say hello"}"#,
+            &dir,
+            Some(&output_dir),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(run.status, LocalRunStatus::Completed);
+        let transcript = std::fs::read_to_string(output_dir.join("transcript.txt")).unwrap();
+        assert_eq!(transcript, "hello");
+        let events = std::fs::read_to_string(output_dir.join("events.jsonl")).unwrap();
+        assert!(events.lines().count() >= 2);
+        let summary = events
+            .lines()
+            .find(|line| line.contains(r#""event_type":"run_summary""#))
+            .unwrap();
+        assert!(summary.contains(r#""answer":"hello""#));
+        let result: serde_json::Value =
+            serde_json::from_slice(&std::fs::read(output_dir.join("result.json")).unwrap())
+                .unwrap();
+        assert_eq!(result["status"], "completed");
+        assert_eq!(result["manifest"]["agent_name"], "general");
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
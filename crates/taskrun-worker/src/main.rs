@@ -1,26 +1,84 @@
 //! TaskRun Worker Daemon
 
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::Duration;
 
-use clap::Parser;
+use taskrun_proto::trace::OtlpExporter;
+use tokio::sync::watch;
 use tracing::{error, info};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
 use tracing_subscriber::EnvFilter;
 
+mod agents;
+mod approvals;
+mod artifacts;
+mod cancel;
+mod capabilities;
+mod claude_check;
 mod config;
+mod config_file;
 mod connection;
+mod enroll;
 mod executor;
+mod failure;
+mod files;
+mod journal;
 mod json_output;
+mod key_store;
+mod labels;
+mod lanes;
+mod local_run;
+mod manifest;
+mod metrics;
+mod output_filter;
+mod path_scope;
+mod preflight;
+mod progress_tool;
+mod reconnect;
+mod resource_limits;
+mod result_summary;
+mod run_logs;
+mod secrets;
+mod shutdown;
+mod smoke_test;
+mod spool;
+mod thinking;
+mod worktrees;
 
 #[cfg(feature = "tui")]
 mod tui;
 
-use config::{Cli, Config};
+use agents::AgentRegistry;
+use capabilities::Capabilities;
+use config::{Cli, Command, Config, RunArgs};
 use connection::WorkerConnection;
+use journal::RunJournal;
+use local_run::LocalRunStatus;
+use output_filter::OutputFilters;
+use preflight::{CheckStatus, Preflight};
+use reconnect::Backoff;
+use resource_limits::ResourceLimitConfig;
+use run_logs::{RunLogConfig, RunLogs};
+use secrets::Secrets;
+use shutdown::Shutdown;
+use spool::OutputSpool;
+use worktrees::Worktrees;
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
-    // Parse CLI arguments
-    let cli = Cli::parse();
+    // Parse CLI arguments over the config file's profile
+    let mut cli = match config_file::parse_cli() {
+        Ok(cli) => cli,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+
+    if let Some(Command::Run(args)) = cli.command.take() {
+        return run_local_mode(cli, args);
+    }
 
     // --json implies headless mode
     if cli.json {
@@ -50,51 +108,218 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 fn run_headless_mode(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
     // Initialize tracing with log level from CLI
     let filter = EnvFilter::try_new(&cli.log_level).unwrap_or_else(|_| EnvFilter::new("info"));
+    let otlp = start_trace_export(cli.otlp_endpoint.as_deref());
+    let run_logs = load_run_logs(&cli)?;
     tracing_subscriber::fmt()
         .with_env_filter(filter)
         .with_target(true)
+        .finish()
+        .with(otlp.as_ref().map(|otlp| otlp.layer()))
+        .with(run_logs.as_ref().map(|logs| logs.layer()))
         .init();
 
     // Build config from CLI
-    let config = Arc::new(Config::from_cli(&cli));
+    let mut config = Config::from_cli(&cli);
+    config.output_filters = load_output_filters(&cli)?;
+    config.resource_limits = load_resource_limits(&cli)?.map(Arc::new);
+    config.secrets = Secrets::from_cli(&cli)?;
+    config.agents = load_agents(&cli)?;
+    config.journal = load_journal(&cli)?;
+    if let Some(journal) = &config.journal {
+        config.worker_id = journal.worker_id()?;
+    }
+    config.worktrees = load_worktrees(&cli, config.journal.as_deref())?;
+    config.spool = load_spool(&cli, &config);
+    config.run_logs = run_logs;
+    config.labels = load_labels(&cli, config.sandbox.is_some());
+    if config.worktrees.is_some() {
+        config.claude_path = worktrees::program_path(&config.claude_path);
+    }
+
+    let rt = tokio::runtime::Runtime::new()?;
+    if let Err(e) = rt.block_on(enroll::enroll_if_needed(&cli, config.worker_id.as_str())) {
+        error!(error = %e, "Enrollment failed");
+        std::process::exit(1);
+    }
+    // Fail now rather than on the first run
+    preflight(&cli, &mut config);
+    let config = Arc::new(config);
 
     info!(
         worker_id = %config.worker_id,
         control_plane = %config.control_plane_addr,
-        agent = %config.agent_name,
+        agents = ?agent_names(&config),
         model = format!("{}/{}", config.model_provider, config.model_name),
         allowed_tools = ?config.allowed_tools,
         denied_tools = ?config.denied_tools,
+        ask_tools = ?config.approval_policy.as_ref().map(|p| &p.ask_tools),
         "Starting TaskRun worker"
     );
 
-    // Create tokio runtime and run
-    let rt = tokio::runtime::Runtime::new()?;
+    let advertised = watch_capabilities(&rt, &cli, &config)?;
     rt.block_on(async {
+        let mut shutdown = shutdown::watch_signals();
+
         // Reconnection loop
+        let mut endpoint = config.control_plane_addr.clone();
+        let mut backoff = Backoff::new(config.reconnect);
         loop {
-            let mut connection = WorkerConnection::new(config.clone());
+            let mut connection = WorkerConnection::new(config.clone())
+                .with_endpoint(endpoint.clone())
+                .with_shutdown(shutdown.clone());
+            if let Some(advertised) = &advertised {
+                connection = connection.with_capabilities(advertised.clone());
+            }
 
-            match connection.connect_and_run().await {
+            let result = connection.connect_and_run().await;
+            if connection.was_connected() {
+                backoff.reset();
+            }
+            if *shutdown.borrow() != Shutdown::Running {
+                info!("Worker shut down");
+                return Ok(());
+            }
+            match result {
                 Ok(_) => {
                     info!("Connection closed normally");
+                    endpoint = config.control_plane_addr.clone();
                 }
-                Err(e) => {
-                    // Extract root cause from error chain
-                    let root_cause = get_root_cause(&*e);
-                    error!(error = %root_cause, "Connection failed");
-                }
+                Err(e) => match connection::leader_redirect(&*e) {
+                    Some(leader) => {
+                        info!(leader = %leader, "Control plane is not the leader, reconnecting to the leader");
+                        // Follow at once, unless we were already redirected
+                        let redirected = endpoint != config.control_plane_addr;
+                        endpoint = leader;
+                        if !redirected {
+                            continue;
+                        }
+                    }
+                    None => {
+                        // Extract root cause from error chain
+                        let root_cause = get_root_cause(&*e);
+                        error!(error = %root_cause, "Connection failed");
+                        // The leader may be gone; start over from the configured address
+                        endpoint = config.control_plane_addr.clone();
+                    }
+                },
             }
 
-            info!(
-                delay_secs = config.reconnect_delay_secs,
-                "Reconnecting in {} seconds...", config.reconnect_delay_secs
-            );
-            tokio::time::sleep(Duration::from_secs(config.reconnect_delay_secs)).await;
+            if !wait_to_reconnect(&config, &mut backoff, &mut shutdown).await {
+                info!("Worker shut down");
+                return Ok(());
+            }
         }
     })
 }
 
+/// Wait out the reconnect backoff, or less if the network changes. Returns
+/// false on shutdown, and exits once the reconnect retries are used up.
+async fn wait_to_reconnect(
+    config: &Config,
+    backoff: &mut Backoff,
+    shutdown: &mut watch::Receiver<Shutdown>,
+) -> bool {
+    let Some(delay) = backoff.next_delay() else {
+        error!(
+            attempts = backoff.failures(),
+            "Giving up reconnecting to the control plane"
+        );
+        json_output::emit_error(
+            &format!(
+                "Giving up after {} failed reconnect attempts",
+                backoff.failures()
+            ),
+            None,
+        );
+        std::process::exit(1);
+    };
+    info!(
+        attempt = backoff.failures(),
+        delay_ms = delay.as_millis() as u64,
+        "Reconnecting in {:.1} seconds...",
+        delay.as_secs_f64()
+    );
+    let worker_id = config.worker_id.as_str();
+    json_output::emit_reconnect_scheduled(worker_id, backoff.failures(), delay, "backoff");
+
+    let network = reconnect::interface_addresses();
+    tokio::select! {
+        _ = tokio::time::sleep(delay) => true,
+        _ = reconnect::network_change(network) => {
+            info!("Network changed, reconnecting now");
+            json_output::emit_reconnect_scheduled(worker_id, backoff.failures(), Duration::ZERO, "network_change");
+            backoff.reset();
+            true
+        }
+        _ = shutdown::reached(shutdown, Shutdown::Drain) => false,
+    }
+}
+
+/// Run one task locally, without a control plane.
+fn run_local_mode(cli: Cli, args: RunArgs) -> Result<(), Box<dyn std::error::Error>> {
+    // Logs go to stderr, leaving stdout to the run's output
+    let filter = EnvFilter::try_new(&cli.log_level).unwrap_or_else(|_| EnvFilter::new("info"));
+    tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_target(true)
+        .with_writer(std::io::stderr)
+        .init();
+
+    let mut config = Config::from_cli(&cli);
+    config.output_filters = load_output_filters(&cli)?;
+    config.resource_limits = load_resource_limits(&cli)?.map(Arc::new);
+    config.secrets = Secrets::from_cli(&cli)?;
+    config.agents = load_agents(&cli)?;
+    let definitions = config.agents.definitions();
+    if !definitions.is_empty() && !definitions.iter().any(|d| d.name == cli.agent) {
+        error!(agent = %cli.agent, agents = ?agent_names(&config), "Agent is not defined");
+        std::process::exit(1);
+    }
+
+    let working_dir = PathBuf::from(&cli.working_dir);
+    let sandboxed = config.sandbox(&cli.agent).is_some();
+    let report = Preflight {
+        claude_path: &cli.claude_path,
+        skip_claude_check: cli.skip_claude_check,
+        sandbox: config.sandbox,
+        working_dir: &working_dir,
+        client_cert: None,
+        agents: vec![(cli.agent.clone(), sandboxed)],
+    }
+    .run();
+    report.log();
+    if !report.is_ready() {
+        error!("Can't run the task, fix the failed pre-flight checks");
+        std::process::exit(1);
+    }
+
+    let rt = tokio::runtime::Runtime::new()?;
+    let run = rt.block_on(local_run::run(
+        Arc::new(config),
+        &cli.agent,
+        &args.input,
+        &working_dir,
+        args.output_dir.as_deref().map(Path::new),
+    ))?;
+    let output_dir = run.output_dir.display().to_string();
+    match run.status {
+        LocalRunStatus::Completed => {
+            info!(run_id = %run.run_id, output_dir = %output_dir, "Run completed");
+            Ok(())
+        }
+        status => {
+            error!(
+                run_id = %run.run_id,
+                status = ?status,
+                error = run.error.as_deref().unwrap_or_default(),
+                output_dir = %output_dir,
+                "Run did not complete"
+            );
+            std::process::exit(1);
+        }
+    }
+}
+
 /// Run the worker in JSON mode (headless with JSON line output to stdout).
 fn run_json_mode(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
     // Enable JSON output mode
@@ -102,50 +327,113 @@ fn run_json_mode(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
 
     // Initialize tracing with log level from CLI, output to stderr
     let filter = EnvFilter::try_new(&cli.log_level).unwrap_or_else(|_| EnvFilter::new("info"));
+    let otlp = start_trace_export(cli.otlp_endpoint.as_deref());
+    let run_logs = load_run_logs(&cli)?;
     tracing_subscriber::fmt()
         .with_env_filter(filter)
         .with_target(true)
         .with_writer(std::io::stderr)
+        .finish()
+        .with(otlp.as_ref().map(|otlp| otlp.layer()))
+        .with(run_logs.as_ref().map(|logs| logs.layer()))
         .init();
 
     // Build config from CLI
-    let config = Arc::new(Config::from_cli(&cli));
+    let mut config = Config::from_cli(&cli);
+    config.output_filters = load_output_filters(&cli)?;
+    config.resource_limits = load_resource_limits(&cli)?.map(Arc::new);
+    config.secrets = Secrets::from_cli(&cli)?;
+    config.agents = load_agents(&cli)?;
+    config.journal = load_journal(&cli)?;
+    if let Some(journal) = &config.journal {
+        config.worker_id = journal.worker_id()?;
+    }
+    config.worktrees = load_worktrees(&cli, config.journal.as_deref())?;
+    config.spool = load_spool(&cli, &config);
+    config.run_logs = run_logs;
+    config.labels = load_labels(&cli, config.sandbox.is_some());
+    if config.worktrees.is_some() {
+        config.claude_path = worktrees::program_path(&config.claude_path);
+    }
+
+    let rt = tokio::runtime::Runtime::new()?;
+    if let Err(e) = rt.block_on(enroll::enroll_if_needed(&cli, config.worker_id.as_str())) {
+        error!(error = %e, "Enrollment failed");
+        json_output::emit_error(&format!("Enrollment failed: {}", e), None);
+        std::process::exit(1);
+    }
+    // Fail now rather than on the first run
+    preflight(&cli, &mut config);
+    let config = Arc::new(config);
 
     info!(
         worker_id = %config.worker_id,
         control_plane = %config.control_plane_addr,
-        agent = %config.agent_name,
+        agents = ?agent_names(&config),
         model = format!("{}/{}", config.model_provider, config.model_name),
         json_mode = true,
         "Starting TaskRun worker in JSON mode"
     );
 
-    // Create tokio runtime and run
-    let rt = tokio::runtime::Runtime::new()?;
+    let advertised = watch_capabilities(&rt, &cli, &config)?;
     rt.block_on(async {
+        let mut shutdown = shutdown::watch_signals();
+
         // Reconnection loop
+        let mut endpoint = config.control_plane_addr.clone();
+        let mut backoff = Backoff::new(config.reconnect);
         loop {
-            let mut connection = WorkerConnection::new(config.clone());
+            let mut connection = WorkerConnection::new(config.clone())
+                .with_endpoint(endpoint.clone())
+                .with_shutdown(shutdown.clone());
+            if let Some(advertised) = &advertised {
+                connection = connection.with_capabilities(advertised.clone());
+            }
 
-            match connection.connect_and_run().await {
+            let result = connection.connect_and_run().await;
+            if connection.was_connected() {
+                backoff.reset();
+            }
+            if *shutdown.borrow() != Shutdown::Running {
+                info!("Worker shut down");
+                json_output::emit_worker_disconnected(
+                    config.worker_id.as_str(),
+                    Some("Worker shut down"),
+                );
+                return Ok(());
+            }
+            match result {
                 Ok(_) => {
                     info!("Connection closed normally");
                     json_output::emit_worker_disconnected(
                         config.worker_id.as_str(),
                         Some("Connection closed normally"),
                     );
+                    endpoint = config.control_plane_addr.clone();
                 }
-                Err(e) => {
-                    error!(error = %e, "Connection error");
-                    json_output::emit_error(&format!("Connection error: {}", e), None);
-                }
+                Err(e) => match connection::leader_redirect(&*e) {
+                    Some(leader) => {
+                        info!(leader = %leader, "Control plane is not the leader, reconnecting to the leader");
+                        // Follow at once, unless we were already redirected
+                        let redirected = endpoint != config.control_plane_addr;
+                        endpoint = leader;
+                        if !redirected {
+                            continue;
+                        }
+                    }
+                    None => {
+                        error!(error = %e, "Connection error");
+                        json_output::emit_error(&format!("Connection error: {}", e), None);
+                        // The leader may be gone; start over from the configured address
+                        endpoint = config.control_plane_addr.clone();
+                    }
+                },
             }
 
-            info!(
-                delay_secs = config.reconnect_delay_secs,
-                "Reconnecting in {} seconds...", config.reconnect_delay_secs
-            );
-            tokio::time::sleep(Duration::from_secs(config.reconnect_delay_secs)).await;
+            if !wait_to_reconnect(&config, &mut backoff, &mut shutdown).await {
+                info!("Worker shut down");
+                return Ok(());
+            }
         }
     })
 }
@@ -159,18 +447,47 @@ fn run_tui_mode(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
         .to_string_lossy()
         .to_string();
 
+    // Before the TUI takes over the terminal, so errors stay readable
+    let worker_id = taskrun_core::WorkerId::generate();
+    tokio::runtime::Runtime::new()?.block_on(enroll::enroll_if_needed(&cli, worker_id.as_str()))?;
+
+    let output_filters = load_output_filters(&cli)?;
+    let approval_policy = config::approval_policy(&cli);
+    let resource_limits = load_resource_limits(&cli)?.map(Arc::new);
+    let secrets = Secrets::from_cli(&cli)?;
+    let time_format = taskrun_tui_components::TimeFormat::new(cli.tz.parse()?, cli.locale.parse()?);
+
+    let agents = load_agents(&cli)?;
+    let labels = load_labels(&cli, false);
+    let reconnect = config::reconnect_policy(&cli);
     let config = tui::WorkerConfig {
         agent_name: cli.agent,
+        agents,
+        agents_dir: cli.agents_dir,
+        namespace: cli.namespace,
+        labels,
         model_name: cli.model,
         endpoint: cli.endpoint,
         ca_cert_path: cli.ca_cert,
         client_cert_path: cli.client_cert,
         client_key_path: cli.client_key,
+        client_key_passphrase: key_store::passphrase_from_env(),
         allowed_tools: cli.allow_tools.map(|s| parse_tools(&s)),
         denied_tools: cli.deny_tools.map(|s| parse_tools(&s)),
         max_concurrent_runs: cli.max_concurrent_runs,
+        interactive_slots: cli.interactive_slots,
         working_dir,
         skip_permissions: true,
+        approval_policy,
+        output_filters,
+        include_thinking: cli.include_thinking,
+        progress_tool: !cli.no_progress_tool,
+        time_format,
+        claude_path: cli.claude_path,
+        skip_claude_check: cli.skip_claude_check,
+        resource_limits,
+        secrets,
+        reconnect,
     };
 
     tui::run_worker_tui(config)
@@ -186,6 +503,192 @@ fn parse_tools(tools: &str) -> Vec<String> {
         .collect()
 }
 
+/// Start exporting traces if an OTLP endpoint was given.
+fn start_trace_export(endpoint: Option<&str>) -> Option<OtlpExporter> {
+    let endpoint = endpoint?;
+    match OtlpExporter::start("taskrun-worker", endpoint) {
+        Ok(exporter) => Some(exporter),
+        Err(e) => {
+            eprintln!("Failed to start trace export to {}: {}", endpoint, e);
+            None
+        }
+    }
+}
+
+/// Follow `--capabilities-file` and `--agents-dir`, if given, and smoke test
+/// the agents with `--smoke-test`.
+fn watch_capabilities(
+    rt: &tokio::runtime::Runtime,
+    cli: &Cli,
+    config: &Arc<Config>,
+) -> Result<Option<watch::Receiver<Capabilities>>, Box<dyn std::error::Error>> {
+    let _guard = rt.enter();
+    if let Some(path) = &cli.agents_dir {
+        config.agents.watch_dir(path.into());
+    }
+    let advertised = if cli.capabilities_file.is_some() || cli.agents_dir.is_some() {
+        let path = cli.capabilities_file.as_ref().map(PathBuf::from);
+        Some(capabilities::watch(config.clone(), path)?)
+    } else {
+        None
+    };
+    if !cli.smoke_test {
+        return Ok(advertised);
+    }
+    let source = advertised.unwrap_or_else(|| watch::channel(Capabilities::from_config(config)).1);
+    let smoke = smoke_test::SmokeTestConfig {
+        prompt: cli.smoke_test_prompt.clone(),
+        timeout: Duration::from_secs(cli.smoke_test_timeout_secs),
+        retry_interval: Duration::from_secs(cli.smoke_test_retry_secs.max(1)),
+    };
+    Ok(Some(rt.block_on(smoke_test::watch(
+        config.clone(),
+        smoke,
+        source,
+    ))))
+}
+
+/// Load output filters from `--output-filters`, if given.
+fn load_resource_limits(
+    cli: &Cli,
+) -> Result<Option<ResourceLimitConfig>, Box<dyn std::error::Error>> {
+    match &cli.resource_limits {
+        Some(path) => Ok(Some(ResourceLimitConfig::load(path)?)),
+        None => Ok(None),
+    }
+}
+
+fn load_agents(cli: &Cli) -> Result<AgentRegistry, Box<dyn std::error::Error>> {
+    if let Some(path) = &cli.agents_dir {
+        return Ok(AgentRegistry::load_dir(path)?);
+    }
+    match &cli.agents_file {
+        Some(path) => Ok(AgentRegistry::load_file(path)?),
+        None => Ok(AgentRegistry::default()),
+    }
+}
+
+/// Run the pre-flight checks, leaving out agents that can't run, and exit
+/// if the worker can't serve at all.
+fn preflight(cli: &Cli, config: &mut Config) {
+    let agents = config
+        .configured_agents()
+        .into_iter()
+        .map(|agent| {
+            let sandboxed = config.sandbox(&agent.name).is_some();
+            (agent.name, sandboxed)
+        })
+        .collect();
+    let report = Preflight {
+        claude_path: &cli.claude_path,
+        skip_claude_check: cli.skip_claude_check,
+        sandbox: config.sandbox,
+        working_dir: Path::new("."),
+        client_cert: Some(Path::new(&config.tls_cert_path)),
+        agents,
+    }
+    .run();
+    report.log();
+    json_output::emit_readiness(&report);
+    if !report.is_ready() {
+        let failed: Vec<&str> = report
+            .checks
+            .iter()
+            .filter(|check| check.status == CheckStatus::Fail)
+            .map(|check| check.name)
+            .collect();
+        error!(checks = ?failed, "Worker is not ready, fix the failed pre-flight checks");
+        json_output::emit_error("Worker is not ready", None);
+        std::process::exit(1);
+    }
+    config.unready_agents = report
+        .unready_agents
+        .into_iter()
+        .map(|agent| agent.name)
+        .collect();
+}
+
+/// Open the run journal for `--journal-dir`, if given.
+fn load_journal(cli: &Cli) -> Result<Option<Arc<RunJournal>>, Box<dyn std::error::Error>> {
+    match &cli.journal_dir {
+        Some(dir) => Ok(Some(Arc::new(RunJournal::open(dir)?))),
+        None => Ok(None),
+    }
+}
+
+/// Open the output spool in `--spool-dir`, or in a directory per worker ID
+/// under the temp directory. Runs go without one if it can't be opened.
+fn load_spool(cli: &Cli, config: &Config) -> Option<Arc<OutputSpool>> {
+    let dir = match &cli.spool_dir {
+        Some(dir) => PathBuf::from(dir),
+        None => std::env::temp_dir().join(format!("taskrun-spool-{}", config.worker_id)),
+    };
+    match OutputSpool::open(&dir, cli.spool_max_mb * 1024 * 1024) {
+        Ok(spool) => Some(Arc::new(spool)),
+        Err(e) => {
+            error!("{}; output the stream does not take will be dropped", e);
+            None
+        }
+    }
+}
+
+/// Open run logs with `--run-log-dir` or `--ship-run-logs`.
+fn load_run_logs(cli: &Cli) -> Result<Option<Arc<RunLogs>>, Box<dyn std::error::Error>> {
+    if cli.run_log_dir.is_none() && cli.ship_run_logs.is_none() {
+        return Ok(None);
+    }
+    let logs = RunLogs::open(RunLogConfig {
+        dir: cli.run_log_dir.as_ref().map(PathBuf::from),
+        max_bytes: cli.run_log_max_mb * 1024 * 1024,
+        retention: Duration::from_secs(cli.run_log_retention_days * 24 * 60 * 60),
+        ship_level: cli.ship_run_logs,
+    })?;
+    Ok(Some(Arc::new(logs)))
+}
+
+/// `--label` labels plus detected ones. Sandboxed runs use the image's
+/// Claude, so its host version is not a label then.
+fn load_labels(cli: &Cli, sandboxed: bool) -> Vec<(String, String)> {
+    let claude_path = (!sandboxed).then_some(cli.claude_path.as_str());
+    labels::resolve(&cli.labels, !cli.no_detect_labels, claude_path)
+}
+
+/// Open the repository for `--worktrees`, if given, keeping the worktrees
+/// of runs the journal can still continue.
+fn load_worktrees(
+    cli: &Cli,
+    journal: Option<&RunJournal>,
+) -> Result<Option<Arc<Worktrees>>, Box<dyn std::error::Error>> {
+    if !cli.worktrees {
+        return Ok(None);
+    }
+    let keep = journal.map(RunJournal::worktrees).unwrap_or_default();
+    let worktrees = tokio::runtime::Runtime::new()?.block_on(Worktrees::open(
+        Path::new("."),
+        cli.push_worktrees.clone(),
+        &keep,
+    ))?;
+    info!(repository = %worktrees.root().display(), "Running each assignment in its own worktree");
+    Ok(Some(Arc::new(worktrees)))
+}
+
+fn agent_names(config: &Config) -> Vec<String> {
+    config
+        .agents()
+        .into_iter()
+        .map(|agent| agent.name)
+        .collect()
+}
+
+fn load_output_filters(
+    cli: &Cli,
+) -> Result<Option<Arc<OutputFilters>>, Box<dyn std::error::Error>> {
+    match &cli.output_filters {
+        Some(path) => Ok(Some(Arc::new(OutputFilters::load(path)?))),
+        None => Ok(None),
+    }
+}
+
 /// Extract a user-friendly error message from an error chain.
 /// Looks for common TLS/connection errors and provides actionable messages.
 pub fn get_root_cause(err: &(dyn std::error::Error + 'static)) -> String {
@@ -201,7 +704,8 @@ pub fn get_root_cause(err: &(dyn std::error::Error + 'static)) -> String {
             return "Certificate expired. Run: scripts/gen-worker-cert.sh".to_string();
         }
         if msg.contains("CertificateRequired") {
-            return "Server requires client certificate. Check --client-cert and --client-key".to_string();
+            return "Server requires client certificate. Check --client-cert and --client-key"
+                .to_string();
         }
         if msg.contains("CertificateUnknown") || msg.contains("UnknownCA") {
             return "Certificate not trusted. Check --ca-cert matches server's CA".to_string();
@@ -0,0 +1,149 @@
+//! Run reproducibility manifests.
+//!
+//! When a run starts, the worker reports everything that determines how it
+//! executes: agent template, model, tool policy, binary versions, feature
+//! flags and a hash of the input. A run assigned with a pinned manifest (from
+//! `POST /v1/runs/:id/reproduce`) uses the pinned model and tool policy;
+//! anything else that differs is logged, since it cannot be changed per run.
+
+use std::collections::BTreeMap;
+use std::process::Command;
+
+use sha2::{Digest, Sha256};
+use taskrun_core::{AgentSpec, ModelBackend, RunManifest};
+use tracing::warn;
+
+/// Fields a pinned manifest can override for a single run.
+const PINNABLE_FIELDS: &[&str] = &[
+    "model_provider",
+    "model_name",
+    "allowed_tools",
+    "denied_tools",
+    "input_hash",
+];
+
+/// Build the agent specification advertised for `agent_name`.
+pub fn agent_spec(agent_name: &str, model_provider: &str, model_name: &str) -> AgentSpec {
+    let backend = ModelBackend::new(model_provider, model_name)
+        .with_context_window(200_000)
+        .with_modalities(vec!["text".to_string()]);
+
+    AgentSpec::new(agent_name)
+        .with_description(agent_description(agent_name))
+        .with_backend(backend)
+}
+
+/// Get the description for a known agent, or a generic description for custom agents.
+fn agent_description(agent_name: &str) -> String {
+    match agent_name {
+        "general" => "General-purpose agent that executes any task".to_string(),
+        "support_triage" => "Classifies and triages support tickets".to_string(),
+        _ => format!("Custom agent: {}", agent_name),
+    }
+}
+
+/// Run `claude --version`, returning None if the CLI is missing or fails.
+pub fn detect_claude_version(claude_path: &str) -> Option<String> {
+    let output = Command::new(claude_path).arg("--version").output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let version = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!version.is_empty()).then_some(version)
+}
+
+/// The parts of a manifest shared by every run of an agent on this worker.
+/// The agent's prompts and input schema are part of the agent template hash.
+pub fn base_manifest(
+    agent: &AgentSpec,
+    agent_template: Option<&str>,
+    allowed_tools: Option<Vec<String>>,
+    denied_tools: Option<Vec<String>>,
+    claude_cli_version: Option<String>,
+    feature_flags: BTreeMap<String, String>,
+) -> RunManifest {
+    let backend = agent.backends.first();
+    RunManifest {
+        agent_name: agent.name.clone(),
+        agent_template_hash: agent_template_hash(agent, agent_template),
+        model_provider: backend.map(|b| b.provider.clone()).unwrap_or_default(),
+        model_name: backend.map(|b| b.model_name.clone()).unwrap_or_default(),
+        allowed_tools,
+        denied_tools,
+        claude_cli_version,
+        worker_version: env!("CARGO_PKG_VERSION").to_string(),
+        feature_flags,
+        input_hash: String::new(),
+    }
+}
+
+/// Manifest for a run of `input_json` on a worker with manifest `base`.
+///
+/// A `pinned` manifest's model and tool policy replace the worker's.
+pub fn run_manifest(
+    base: &RunManifest,
+    input_json: &str,
+    pinned: Option<RunManifest>,
+) -> RunManifest {
+    let mut manifest = RunManifest {
+        input_hash: sha256_hex(input_json.as_bytes()),
+        ..base.clone()
+    };
+
+    if let Some(pinned) = pinned {
+        manifest.model_provider = pinned.model_provider.clone();
+        manifest.model_name = pinned.model_name.clone();
+        manifest.allowed_tools = pinned.allowed_tools.clone();
+        manifest.denied_tools = pinned.denied_tools.clone();
+
+        let drift: Vec<_> = pinned
+            .diff(&manifest)
+            .into_iter()
+            .filter(|field| !PINNABLE_FIELDS.contains(field))
+            .collect();
+        if !drift.is_empty() {
+            warn!(
+                fields = ?drift,
+                "Worker environment differs from the pinned manifest"
+            );
+        }
+    }
+    manifest
+}
+
+fn agent_template_hash(agent: &AgentSpec, agent_template: Option<&str>) -> String {
+    let mut template = serde_json::to_vec(agent).unwrap_or_default();
+    if let Some(agent_template) = agent_template {
+        template.extend_from_slice(agent_template.as_bytes());
+    }
+    sha256_hex(&template)
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    hex::encode(Sha256::digest(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pinned_manifest_overrides_model_and_tools() {
+        let agent = agent_spec("general", "anthropic", "claude-sonnet-4-5");
+        let base = base_manifest(&agent, None, None, None, None, BTreeMap::new());
+        assert_eq!(base.agent_template_hash.len(), 64);
+
+        let first = run_manifest(&base, r#"{"task":"hi"}"#, None);
+        assert_eq!(first.model_name, "claude-sonnet-4-5");
+        assert_eq!(first.allowed_tools, None);
+
+        let pinned = RunManifest {
+            model_name: "claude-opus-4-5".to_string(),
+            allowed_tools: Some(vec!["Read".to_string()]),
+            ..first.clone()
+        };
+        let second = run_manifest(&base, r#"{"task":"hi"}"#, Some(pinned.clone()));
+        assert_eq!(second, pinned);
+        assert_eq!(second.input_hash, first.input_hash);
+    }
+}
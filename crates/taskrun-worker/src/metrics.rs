@@ -0,0 +1,166 @@
+//! Heartbeat metrics (headless and JSON modes).
+//!
+//! Each heartbeat carries a snapshot of the worker host and of the runs the
+//! worker finished since it started, in the heartbeat's `metrics` map. Which
+//! groups are sent is set with `--heartbeat-metrics`:
+//!
+//! | Group | Keys |
+//! |-------|------|
+//! | `load` | `load_1m`: one-minute load average |
+//! | `memory` | `memory_used_bytes`, `memory_total_bytes` |
+//! | `disk` | `disk_free_bytes`: free space where the worker runs |
+//! | `runs` | `runs_completed`, `runs_failed`, `avg_run_ms` |
+//!
+//! Values are decimal strings. Host values the platform does not provide
+//! (load and memory come from `/proc`) are left out.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use clap::ValueEnum;
+
+/// A group of heartbeat metrics.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum HeartbeatMetric {
+    Load,
+    Memory,
+    Disk,
+    Runs,
+}
+
+/// Outcomes of the runs the worker finished since it started.
+#[derive(Debug, Default)]
+pub struct RunStats {
+    completed: AtomicU64,
+    failed: AtomicU64,
+    total_ms: AtomicU64,
+}
+
+impl RunStats {
+    /// Count a finished run. Cancelled runs are not counted.
+    pub fn record(&self, completed: bool, duration: Duration) {
+        let counter = if completed {
+            &self.completed
+        } else {
+            &self.failed
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        self.total_ms
+            .fetch_add(duration.as_millis() as u64, Ordering::Relaxed);
+    }
+
+    fn insert_into(&self, metrics: &mut HashMap<String, String>) {
+        let completed = self.completed.load(Ordering::Relaxed);
+        let failed = self.failed.load(Ordering::Relaxed);
+        metrics.insert("runs_completed".to_string(), completed.to_string());
+        metrics.insert("runs_failed".to_string(), failed.to_string());
+        if let Some(avg) = self
+            .total_ms
+            .load(Ordering::Relaxed)
+            .checked_div(completed + failed)
+        {
+            metrics.insert("avg_run_ms".to_string(), avg.to_string());
+        }
+    }
+}
+
+/// The `selected` metrics, with disk space measured at `working_dir`.
+pub fn collect(
+    selected: &[HeartbeatMetric],
+    stats: &RunStats,
+    working_dir: &Path,
+) -> HashMap<String, String> {
+    let mut metrics = HashMap::new();
+    for metric in selected {
+        match metric {
+            HeartbeatMetric::Load => {
+                if let Some(load) = load_average() {
+                    metrics.insert("load_1m".to_string(), load);
+                }
+            }
+            HeartbeatMetric::Memory => {
+                if let Some((used, total)) = memory() {
+                    metrics.insert("memory_used_bytes".to_string(), used.to_string());
+                    metrics.insert("memory_total_bytes".to_string(), total.to_string());
+                }
+            }
+            HeartbeatMetric::Disk => {
+                if let Some(free) = disk_free(working_dir) {
+                    metrics.insert("disk_free_bytes".to_string(), free.to_string());
+                }
+            }
+            HeartbeatMetric::Runs => stats.insert_into(&mut metrics),
+        }
+    }
+    metrics
+}
+
+/// One-minute load average, as `/proc/loadavg` has it.
+fn load_average() -> Option<String> {
+    let loadavg = std::fs::read_to_string("/proc/loadavg").ok()?;
+    loadavg.split_whitespace().next().map(str::to_string)
+}
+
+/// Used and total memory in bytes, from `/proc/meminfo`.
+fn memory() -> Option<(u64, u64)> {
+    let meminfo = std::fs::read_to_string("/proc/meminfo").ok()?;
+    let field = |name: &str| -> Option<u64> {
+        let line = meminfo.lines().find(|line| line.starts_with(name))?;
+        let kb: u64 = line[name.len()..]
+            .trim()
+            .trim_end_matches("kB")
+            .trim()
+            .parse()
+            .ok()?;
+        Some(kb * 1024)
+    };
+    let total = field("MemTotal:")?;
+    let available = field("MemAvailable:")?;
+    Some((total.saturating_sub(available), total))
+}
+
+/// Bytes available to the worker on the filesystem of `path`.
+#[cfg(unix)]
+fn disk_free(path: &Path) -> Option<u64> {
+    use std::os::unix::ffi::OsStrExt;
+
+    let path = std::ffi::CString::new(path.as_os_str().as_bytes()).ok()?;
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    // SAFETY: path is NUL-terminated and stat is a valid statvfs to fill in
+    if unsafe { libc::statvfs(path.as_ptr(), &mut stat) } != 0 {
+        return None;
+    }
+    #[allow(clippy::unnecessary_cast)]
+    Some(stat.f_bavail as u64 * stat.f_frsize as u64)
+}
+
+#[cfg(not(unix))]
+fn disk_free(_path: &Path) -> Option<u64> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_selected_groups_are_collected() {
+        let stats = RunStats::default();
+        let runs = collect(&[HeartbeatMetric::Runs], &stats, Path::new("."));
+        assert_eq!(runs["runs_completed"], "0");
+        assert!(!runs.contains_key("avg_run_ms"));
+
+        stats.record(true, Duration::from_millis(3000));
+        stats.record(false, Duration::from_millis(1000));
+        let runs = collect(&[HeartbeatMetric::Runs], &stats, Path::new("."));
+        assert_eq!(runs["runs_completed"], "1");
+        assert_eq!(runs["runs_failed"], "1");
+        assert_eq!(runs["avg_run_ms"], "2000");
+
+        let disk = collect(&[HeartbeatMetric::Disk], &stats, Path::new("."));
+        assert_eq!(disk.keys().collect::<Vec<_>>(), ["disk_free_bytes"]);
+        assert!(collect(&[], &stats, Path::new(".")).is_empty());
+    }
+}
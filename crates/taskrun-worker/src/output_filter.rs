@@ -0,0 +1,356 @@
+//! Output post-filters applied to agent output before it leaves the worker.
+//!
+//! Filters are configured per agent in a JSON file (`--output-filters`):
+//!
+//! ```json
+//! {
+//!   "default": { "words": ["darn"] },
+//!   "agents": {
+//!     "support_triage": {
+//!       "mask": "[REDACTED]",
+//!       "regex": [{ "name": "email", "pattern": "[\\w.+-]+@[\\w-]+\\.[\\w.]+" }],
+//!       "words": ["darn", "heck"],
+//!       "max_token_entropy": 4.0
+//!     }
+//!   }
+//! }
+//! ```
+//!
+//! An agent entry replaces `default` entirely. Filters run on each output chunk
+//! independently, so a span split across two chunks is not masked.
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::sync::Arc;
+
+use regex::{Regex, RegexBuilder};
+use serde::Deserialize;
+use thiserror::Error;
+
+use taskrun_core::{RunEvent, RunId, TaskId};
+
+/// Default replacement for masked spans.
+pub const DEFAULT_MASK: &str = "[REDACTED]";
+
+/// Default minimum length of a token checked for entropy.
+pub const DEFAULT_MIN_ENTROPY_TOKEN_LEN: usize = 20;
+
+/// Output filter errors.
+#[derive(Debug, Error)]
+pub enum OutputFilterError {
+    #[error("Failed to read output filters from '{path}': {source}")]
+    Io {
+        path: String,
+        source: std::io::Error,
+    },
+
+    #[error("Invalid output filter config: {0}")]
+    Parse(#[from] serde_json::Error),
+
+    #[error("Invalid pattern for filter '{name}': {source}")]
+    InvalidPattern { name: String, source: regex::Error },
+}
+
+/// On-disk filter configuration.
+#[derive(Debug, Default, Deserialize)]
+pub struct OutputFilterFile {
+    /// Filters for agents without their own entry.
+    #[serde(default)]
+    pub default: Option<AgentFilterConfig>,
+
+    /// Filters keyed by agent name.
+    #[serde(default)]
+    pub agents: HashMap<String, AgentFilterConfig>,
+}
+
+/// Filters for one agent.
+#[derive(Debug, Default, Deserialize)]
+pub struct AgentFilterConfig {
+    /// Replacement text (defaults to `[REDACTED]`).
+    #[serde(default)]
+    pub mask: Option<String>,
+
+    /// Named regex masks.
+    #[serde(default)]
+    pub regex: Vec<RegexMaskConfig>,
+
+    /// Words masked case-insensitively on word boundaries.
+    #[serde(default)]
+    pub words: Vec<String>,
+
+    /// Mask tokens whose Shannon entropy (bits per char) is at least this value.
+    #[serde(default)]
+    pub max_token_entropy: Option<f64>,
+
+    /// Only tokens at least this long are checked for entropy.
+    #[serde(default)]
+    pub min_entropy_token_len: Option<usize>,
+}
+
+/// A named regex mask.
+#[derive(Debug, Deserialize)]
+pub struct RegexMaskConfig {
+    pub name: String,
+    pub pattern: String,
+}
+
+/// A compiled filter rule.
+#[derive(Debug)]
+enum Rule {
+    Regex { name: String, regex: Regex },
+    Entropy { threshold: f64, tokens: Regex },
+}
+
+/// Compiled filter chain for one agent.
+#[derive(Debug)]
+pub struct OutputFilterChain {
+    mask: String,
+    rules: Vec<Rule>,
+}
+
+/// Masked span counts keyed by filter name (`words`, `entropy`, or a regex name).
+pub type MaskCounts = BTreeMap<String, usize>;
+
+impl OutputFilterChain {
+    /// Compile an agent's filter config.
+    pub fn compile(config: &AgentFilterConfig) -> Result<Self, OutputFilterError> {
+        let mut rules = Vec::new();
+
+        for mask in &config.regex {
+            let regex =
+                Regex::new(&mask.pattern).map_err(|source| OutputFilterError::InvalidPattern {
+                    name: mask.name.clone(),
+                    source,
+                })?;
+            rules.push(Rule::Regex {
+                name: mask.name.clone(),
+                regex,
+            });
+        }
+
+        let words: Vec<String> = config
+            .words
+            .iter()
+            .map(|w| w.trim())
+            .filter(|w| !w.is_empty())
+            .map(regex::escape)
+            .collect();
+        if !words.is_empty() {
+            let regex = RegexBuilder::new(&format!(r"\b(?:{})\b", words.join("|")))
+                .case_insensitive(true)
+                .build()
+                .map_err(|source| OutputFilterError::InvalidPattern {
+                    name: "words".to_string(),
+                    source,
+                })?;
+            rules.push(Rule::Regex {
+                name: "words".to_string(),
+                regex,
+            });
+        }
+
+        if let Some(threshold) = config.max_token_entropy {
+            let min_len = config
+                .min_entropy_token_len
+                .unwrap_or(DEFAULT_MIN_ENTROPY_TOKEN_LEN)
+                .max(1);
+            // Characters typical of API keys, tokens and base64 blobs
+            let tokens = Regex::new(&format!(r"[A-Za-z0-9+/=_\-]{{{},}}", min_len))
+                .expect("entropy token pattern is valid");
+            rules.push(Rule::Entropy { threshold, tokens });
+        }
+
+        Ok(Self {
+            mask: config
+                .mask
+                .clone()
+                .unwrap_or_else(|| DEFAULT_MASK.to_string()),
+            rules,
+        })
+    }
+
+    /// Mask `text`, adding the number of masked spans per filter to `counts`.
+    pub fn apply(&self, text: &str, counts: &mut MaskCounts) -> String {
+        let mut output = text.to_string();
+
+        for rule in &self.rules {
+            let (name, regex, threshold) = match rule {
+                Rule::Regex { name, regex } => (name.as_str(), regex, None),
+                Rule::Entropy { threshold, tokens } => ("entropy", tokens, Some(*threshold)),
+            };
+
+            let mut masked = 0;
+            let replaced = regex.replace_all(&output, |caps: &regex::Captures| {
+                let span = &caps[0];
+                match threshold {
+                    Some(t) if shannon_entropy(span) < t => span.to_string(),
+                    _ => {
+                        masked += 1;
+                        self.mask.clone()
+                    }
+                }
+            });
+
+            if masked > 0 {
+                output = replaced.into_owned();
+                *counts.entry(name.to_string()).or_default() += masked;
+            }
+        }
+
+        output
+    }
+}
+
+/// Shannon entropy of `s` in bits per character.
+fn shannon_entropy(s: &str) -> f64 {
+    let mut freq: HashMap<char, usize> = HashMap::new();
+    let mut len = 0usize;
+    for c in s.chars() {
+        *freq.entry(c).or_default() += 1;
+        len += 1;
+    }
+    if len == 0 {
+        return 0.0;
+    }
+    freq.values()
+        .map(|&n| {
+            let p = n as f64 / len as f64;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+/// Compiled filters for every configured agent.
+#[derive(Debug, Default)]
+pub struct OutputFilters {
+    default: Option<Arc<OutputFilterChain>>,
+    agents: HashMap<String, Arc<OutputFilterChain>>,
+}
+
+impl OutputFilters {
+    /// Load and compile filters from a JSON file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, OutputFilterError> {
+        let path = path.as_ref();
+        let json = std::fs::read_to_string(path).map_err(|source| OutputFilterError::Io {
+            path: path.display().to_string(),
+            source,
+        })?;
+        Self::from_json(&json)
+    }
+
+    /// Parse and compile filters from a JSON string.
+    pub fn from_json(json: &str) -> Result<Self, OutputFilterError> {
+        let file: OutputFilterFile = serde_json::from_str(json)?;
+
+        let default = file
+            .default
+            .as_ref()
+            .map(OutputFilterChain::compile)
+            .transpose()?
+            .map(Arc::new);
+        let agents = file
+            .agents
+            .iter()
+            .map(|(agent, config)| {
+                OutputFilterChain::compile(config).map(|chain| (agent.clone(), Arc::new(chain)))
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self { default, agents })
+    }
+
+    /// Filter chain for `agent_name`, falling back to the default.
+    pub fn chain_for(&self, agent_name: &str) -> Option<Arc<OutputFilterChain>> {
+        self.agents
+            .get(agent_name)
+            .or(self.default.as_ref())
+            .cloned()
+    }
+}
+
+/// Filter state for a single run: applies the agent's chain to each chunk and
+/// tallies masked spans.
+#[derive(Debug, Default)]
+pub struct RunOutputFilter {
+    chain: Option<Arc<OutputFilterChain>>,
+    counts: MaskCounts,
+}
+
+impl RunOutputFilter {
+    /// Create the filter for a run of `agent_name`.
+    pub fn new(filters: Option<&OutputFilters>, agent_name: &str) -> Self {
+        Self {
+            chain: filters.and_then(|f| f.chain_for(agent_name)),
+            counts: MaskCounts::new(),
+        }
+    }
+
+    /// Mask a chunk of output (returned unchanged when no filters apply).
+    pub fn filter(&mut self, chunk: String) -> String {
+        match &self.chain {
+            Some(chain) => chain.apply(&chunk, &mut self.counts),
+            None => chunk,
+        }
+    }
+
+    /// Event recording masked span counts, if filters were applied.
+    pub fn into_event(self, run_id: RunId, task_id: TaskId) -> Option<RunEvent> {
+        self.chain?;
+        Some(RunEvent::output_filtered(run_id, task_id, &self.counts))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"{
+        "default": { "words": ["darn"] },
+        "agents": {
+            "support": {
+                "mask": "***",
+                "regex": [{ "name": "email", "pattern": "[\\w.+-]+@[\\w-]+\\.[\\w.]+" }],
+                "words": ["Darn", "heck"],
+                "max_token_entropy": 4.0
+            }
+        }
+    }"#;
+
+    #[test]
+    fn test_chain_masks_regex_words_and_secrets() {
+        let filters = OutputFilters::from_json(CONFIG).unwrap();
+        let mut run = RunOutputFilter::new(Some(&filters), "support");
+
+        let out = run.filter(
+            "Mail jo@example.com, darn! Key: sk9Xq2Lm7Vb4Tz1Rw8Yp3Kd6Hf0Ng5Cj please_do_not_share_this"
+                .to_string(),
+        );
+        assert_eq!(out, "Mail ***, ***! Key: *** please_do_not_share_this");
+
+        let out = run.filter("What the HECK".to_string());
+        assert_eq!(out, "What the ***");
+
+        let event = run.into_event(RunId::new("r"), TaskId::new("t")).unwrap();
+        assert_eq!(event.metadata["masked_spans"], "4");
+        assert_eq!(event.metadata["masked.email"], "1");
+        assert_eq!(event.metadata["masked.words"], "2");
+        assert_eq!(event.metadata["masked.entropy"], "1");
+    }
+
+    #[test]
+    fn test_agents_fall_back_to_default() {
+        let filters = OutputFilters::from_json(CONFIG).unwrap();
+
+        let mut run = RunOutputFilter::new(Some(&filters), "general");
+        assert_eq!(run.filter("darn heck".to_string()), "[REDACTED] heck");
+
+        let mut run = RunOutputFilter::new(None, "general");
+        assert_eq!(run.filter("darn".to_string()), "darn");
+        assert!(run.into_event(RunId::new("r"), TaskId::new("t")).is_none());
+
+        assert!(matches!(
+            OutputFilters::from_json(r#"{"default":{"regex":[{"name":"bad","pattern":"("}]}}"#),
+            Err(OutputFilterError::InvalidPattern { .. })
+        ));
+    }
+}
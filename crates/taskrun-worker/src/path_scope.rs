@@ -0,0 +1,390 @@
+//! Filesystem scopes of agents.
+//!
+//! An agent definition with `allowed_paths` confines its runs' file tools
+//! and shell commands to those roots:
+//!
+//! ```yaml
+//! # agents/docs_writer.yaml
+//! allowed_paths: [".", /tmp]
+//! ```
+//!
+//! Relative roots are taken from the run's workspace, and `~` is the
+//! worker's home. Each root is resolved through symlinks when the run
+//! starts, and each checked path before it is compared with the roots, so
+//! a symlink in the workspace doesn't lead out of it.
+//!
+//! The worker turns the agent's file tools into permission rules on the
+//! roots (`Read` becomes `Read(//workspace/**)`, `Edit` and `Write` become
+//! `Edit(//workspace/**)`), and checks every use of a file tool or `Bash`
+//! in a PreToolUse hook, which runs whatever the permission mode:
+//!
+//! - `Read`, `Write`, `Edit`, `MultiEdit`, `NotebookEdit`, `Glob`, `Grep`
+//!   and `LS` are denied a path outside the roots.
+//! - `Bash` is denied a command with a word that names a path outside the
+//!   roots: an absolute or `~` path, or one going up with `..`. `$HOME`,
+//!   `$PWD` and `$TMPDIR` are expanded; other variables are not, so this is
+//!   a guard against mistakes rather than a sandbox (see `--sandbox`).
+//!
+//! `/dev/null` and the standard streams are always allowed.
+
+use std::path::{Component, Path, PathBuf};
+
+use serde_json::{json, Value};
+
+use crate::agents::AgentProfile;
+
+/// Tools whose uses the hook checks.
+pub const HOOK_MATCHER: &str = "Read|Write|Edit|MultiEdit|NotebookEdit|Glob|Grep|LS|Bash";
+
+/// Tools the `Read` permission rule covers.
+const READ_TOOLS: &[&str] = &["Read", "Glob", "Grep", "LS"];
+
+/// Tools the `Edit` permission rule covers.
+const EDIT_TOOLS: &[&str] = &["Edit", "Write", "MultiEdit", "NotebookEdit"];
+
+/// Input fields of file tools that hold a path.
+const PATH_FIELDS: &[&str] = &["file_path", "notebook_path", "path"];
+
+/// Paths outside any root that commands may still use.
+const ALWAYS_ALLOWED: &[&str] = &[
+    "/dev/null",
+    "/dev/zero",
+    "/dev/stdin",
+    "/dev/stdout",
+    "/dev/stderr",
+    "/dev/random",
+    "/dev/urandom",
+];
+
+/// Characters that end a word in a shell command.
+const SHELL_SEPARATORS: &[char] = &[
+    ' ', '\t', '\n', ';', '|', '&', '<', '>', '(', ')', '`', '=', '"', '\'',
+];
+
+/// The roots an agent's run may touch.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PathScope {
+    working_dir: PathBuf,
+    roots: Vec<PathBuf>,
+}
+
+impl PathScope {
+    /// The scope of `allowed_paths`, for a run in `working_dir`.
+    pub fn new(working_dir: &Path, allowed_paths: &[String]) -> Self {
+        let working_dir = resolve(&std::env::current_dir().unwrap_or_default(), working_dir);
+        let roots = allowed_paths
+            .iter()
+            .map(|path| resolve(&working_dir, &expand_home(path)))
+            .collect();
+        Self { working_dir, roots }
+    }
+
+    /// Whether `path`, relative to the workspace, is inside a root.
+    pub fn contains(&self, path: &Path) -> bool {
+        let joined = self.working_dir.join(path);
+        let named = normalize(PathBuf::new(), &joined.components().collect::<Vec<_>>());
+        if ALWAYS_ALLOWED
+            .iter()
+            .any(|allowed| named == Path::new(allowed))
+        {
+            return true;
+        }
+        let path = resolve(&self.working_dir, path);
+        self.roots.iter().any(|root| path.starts_with(root))
+    }
+
+    /// `allowed_tools` with the file tools confined to the roots; without
+    /// a tool list, `Read` and `Edit` rules on the roots.
+    pub fn permission_rules(&self, allowed_tools: Option<&[String]>) -> Vec<String> {
+        let default = ["Read".to_string(), "Edit".to_string()];
+        let mut rules = Vec::new();
+        for tool in allowed_tools.unwrap_or(&default) {
+            let rule = if READ_TOOLS.contains(&tool.as_str()) {
+                "Read"
+            } else if EDIT_TOOLS.contains(&tool.as_str()) {
+                "Edit"
+            } else {
+                rules.push(tool.clone());
+                continue;
+            };
+            for root in &self.roots {
+                let scoped = format!("{}(/{}/**)", rule, root.display()).replace("///", "//");
+                if !rules.contains(&scoped) {
+                    rules.push(scoped);
+                }
+            }
+        }
+        rules
+    }
+
+    /// Why a use of `tool_name` with `input` is out of scope, if it is.
+    pub fn check(&self, tool_name: &str, input: &Value) -> Result<(), String> {
+        if tool_name == "Bash" {
+            let command = input.get("command").and_then(Value::as_str).unwrap_or("");
+            return match self.outside_in_command(command) {
+                Some(path) => Err(self.denial(&path)),
+                None => Ok(()),
+            };
+        }
+        for field in PATH_FIELDS {
+            if let Some(path) = input.get(*field).and_then(Value::as_str) {
+                if !self.contains(&expand_home(path)) {
+                    return Err(self.denial(path));
+                }
+            }
+        }
+        // An absolute glob reaches as far as its literal prefix
+        if tool_name == "Glob" {
+            if let Some(pattern) = input.get("pattern").and_then(Value::as_str) {
+                let pattern = expand_home(pattern);
+                let literal: PathBuf = pattern
+                    .components()
+                    .take_while(|c| {
+                        !c.as_os_str()
+                            .to_string_lossy()
+                            .contains(['*', '?', '[', '{'])
+                    })
+                    .collect();
+                if pattern.is_absolute() && !self.contains(&literal) {
+                    return Err(self.denial(&pattern.to_string_lossy()));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Hook output for a use of `tool_name` with `input`: `{}` to go on, or
+    /// a denial naming the path.
+    pub fn hook_output(&self, tool_name: &str, input: &Value) -> Value {
+        match self.check(tool_name, input) {
+            Ok(()) => json!({}),
+            Err(reason) => json!({
+                "hookSpecificOutput": {
+                    "hookEventName": "PreToolUse",
+                    "permissionDecision": "deny",
+                    "permissionDecisionReason": reason,
+                }
+            }),
+        }
+    }
+
+    /// The first word of `command` naming a path outside the roots.
+    fn outside_in_command(&self, command: &str) -> Option<String> {
+        command
+            .split(SHELL_SEPARATORS)
+            .map(|word| self.expand_vars(word))
+            .find(|word| names_path(word) && !self.contains(&expand_home(word)))
+    }
+
+    /// `word` with `$HOME`, `$PWD` and `$TMPDIR` expanded.
+    fn expand_vars(&self, word: &str) -> String {
+        let mut word = word.to_string();
+        let pwd = self.working_dir.to_string_lossy().into_owned();
+        let vars = [
+            ("HOME", std::env::var("HOME").ok()),
+            ("PWD", Some(pwd)),
+            ("TMPDIR", std::env::var("TMPDIR").ok()),
+        ];
+        for (name, value) in vars {
+            if let Some(value) = value {
+                word = word
+                    .replace(&format!("${{{}}}", name), &value)
+                    .replace(&format!("${}", name), &value);
+            }
+        }
+        word
+    }
+
+    fn denial(&self, path: &str) -> String {
+        let roots: Vec<_> = self.roots.iter().map(|r| r.display().to_string()).collect();
+        format!(
+            "'{}' is outside the agent's allowed paths ({})",
+            path,
+            roots.join(", ")
+        )
+    }
+}
+
+/// The scope of `agent` for a run in `working_dir`, if it has allowed
+/// paths, and `allowed_tools` confined to it.
+pub fn scoped_tools(
+    agent: &AgentProfile,
+    working_dir: &Path,
+    allowed_tools: Option<&[String]>,
+) -> (Option<PathScope>, Option<Vec<String>>) {
+    match &agent.allowed_paths {
+        Some(paths) => {
+            let scope = PathScope::new(working_dir, paths);
+            let rules = scope.permission_rules(allowed_tools);
+            (Some(scope), Some(rules))
+        }
+        None => (None, allowed_tools.map(<[String]>::to_vec)),
+    }
+}
+
+/// Whether a command word names a path that may leave the workspace.
+fn names_path(word: &str) -> bool {
+    word.starts_with('/')
+        || word.starts_with('~')
+        || Path::new(word)
+            .components()
+            .any(|c| c == Component::ParentDir)
+}
+
+/// `path` with a leading `~` replaced by the worker's home.
+fn expand_home(path: &str) -> PathBuf {
+    let home = std::env::var_os("HOME").map(PathBuf::from);
+    match (path.strip_prefix('~'), home) {
+        (Some(rest), Some(home)) if rest.is_empty() || rest.starts_with('/') => {
+            home.join(rest.trim_start_matches('/'))
+        }
+        _ => PathBuf::from(path),
+    }
+}
+
+/// `path` made absolute from `base`, through the symlinks of the part that
+/// exists, and without `.` and `..`.
+///
+/// The existing part is canonicalized as a whole, so `link/..` is the
+/// parent of where `link` points, not the directory holding `link`.
+fn resolve(base: &Path, path: &Path) -> PathBuf {
+    let joined = base.join(path);
+    let components: Vec<_> = joined.components().collect();
+    // Canonicalize the longest existing ancestor and keep the rest
+    for existing in (1..=components.len()).rev() {
+        let ancestor: PathBuf = components[..existing].iter().collect();
+        if let Ok(real) = ancestor.canonicalize() {
+            return normalize(real, &components[existing..]);
+        }
+    }
+    normalize(PathBuf::new(), &components)
+}
+
+/// `components` pushed onto `path`, without `.` and `..`.
+fn normalize(mut path: PathBuf, components: &[Component]) -> PathBuf {
+    for component in components {
+        match component {
+            Component::ParentDir => {
+                path.pop();
+            }
+            Component::CurDir => {}
+            c => path.push(c),
+        }
+    }
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scope_confines_file_tools_and_commands() {
+        let dir = std::env::temp_dir().join(format!("taskrun-scope-{}", std::process::id()));
+        let workspace = dir.join("workspace");
+        std::fs::create_dir_all(workspace.join("src")).unwrap();
+        std::fs::create_dir_all(dir.join("shared")).unwrap();
+        let shared = dir.join("shared").canonicalize().unwrap();
+        let scope = PathScope::new(&workspace, &[".".to_string(), shared.display().to_string()]);
+
+        assert!(scope
+            .check("Read", &json!({"file_path": "src/main.rs"}))
+            .is_ok());
+        assert!(scope
+            .check("Write", &json!({"file_path": shared.join("new/out.txt")}))
+            .is_ok());
+        assert!(scope
+            .check("Read", &json!({"file_path": "/etc/passwd"}))
+            .is_err());
+        assert!(scope
+            .check("Edit", &json!({"file_path": "../secret.txt"}))
+            .is_err());
+        assert!(scope
+            .check("Glob", &json!({"pattern": "/etc/**/*.conf"}))
+            .is_err());
+        assert!(scope.check("Grep", &json!({"pattern": "x"})).is_ok());
+        assert!(scope
+            .check("WebFetch", &json!({"url": "https://x/etc"}))
+            .is_ok());
+
+        assert!(scope
+            .check("Bash", &json!({"command": "cargo test > /dev/null 2>&1"}))
+            .is_ok());
+        assert!(scope
+            .check("Bash", &json!({"command": "cat src/main.rs | grep fn"}))
+            .is_ok());
+        assert!(scope
+            .check("Bash", &json!({"command": "cat /etc/passwd"}))
+            .is_err());
+        assert!(scope
+            .check("Bash", &json!({"command": "cd .. && ls"}))
+            .is_err());
+        assert!(scope
+            .check(
+                "Bash",
+                &json!({"command": "tar -cf out.tar --directory=/root ."})
+            )
+            .is_err());
+        let denied = scope.hook_output("Bash", &json!({"command": "ls ~/.ssh"}));
+        assert_eq!(denied["hookSpecificOutput"]["permissionDecision"], "deny");
+
+        // A symlink out of the workspace doesn't widen it
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink("/etc", workspace.join("etc")).unwrap();
+            assert!(scope
+                .check("Read", &json!({"file_path": "etc/passwd"}))
+                .is_err());
+        }
+
+        let workspace = workspace.canonicalize().unwrap();
+        assert_eq!(
+            scope.permission_rules(Some(&[
+                "Read".to_string(),
+                "Grep".to_string(),
+                "Bash".to_string()
+            ])),
+            [
+                format!("Read(/{}/**)", workspace.display()),
+                format!("Read(/{}/**)", shared.display()),
+                "Bash".to_string(),
+            ]
+        );
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlinks_are_followed_before_the_prefix_check() {
+        let dir = std::env::temp_dir().join(format!("taskrun-scope-links-{}", std::process::id()));
+        let workspace = dir.join("workspace");
+        std::fs::create_dir_all(&workspace).unwrap();
+        std::fs::create_dir_all(dir.join("outside/inner")).unwrap();
+        std::fs::write(dir.join("outside/secret.txt"), "x").unwrap();
+        std::os::unix::fs::symlink(dir.join("outside/inner"), workspace.join("inner")).unwrap();
+        std::os::unix::fs::symlink(dir.join("outside/secret.txt"), workspace.join("secret.txt"))
+            .unwrap();
+        let scope = PathScope::new(&workspace, &[".".to_string()]);
+
+        // A symlinked file, a new file under a symlinked directory, and `..`
+        // out of where a symlink points
+        for path in ["secret.txt", "inner/new.txt", "inner/../secret.txt"] {
+            assert!(
+                scope.check("Write", &json!({ "file_path": path })).is_err(),
+                "{path}"
+            );
+        }
+        assert!(scope
+            .check("Bash", &json!({"command": "cat inner/../secret.txt"}))
+            .is_err());
+        assert!(scope
+            .check("Write", &json!({"file_path": "new/../notes.txt"}))
+            .is_ok());
+        assert!(scope
+            .check("Bash", &json!({"command": "echo hi > /dev/stdout"}))
+            .is_ok());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,368 @@
+//! Pre-flight checks on worker startup.
+//!
+//! Before connecting, the worker checks what its runs depend on and reports
+//! the result as a readiness report:
+//!
+//! | Check | Fails when |
+//! |-------|------------|
+//! | `claude` | The Claude Code CLI is missing, broken or logged out (see `claude_check`) |
+//! | `sandbox` | The `--sandbox` runtime is not on `PATH` |
+//! | `working_dir` | Runs can't write to the working directory |
+//! | `client_cert` | The client certificate can't be read or has expired; warns `EXPIRY_WARNING` ahead (skipped by `taskrun-worker run`) |
+//!
+//! Agents that need a failed check (the Claude Code CLI for runs on the host,
+//! the container runtime for sandboxed runs) are not registered. A failed
+//! working directory or certificate check makes the worker not ready, as
+//! does having no agent left to register.
+
+use std::env;
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use taskrun_claude_sdk::SandboxRuntime;
+use tracing::{error, info, warn};
+use x509_parser::pem::parse_x509_pem;
+
+use crate::claude_check;
+
+/// How long before the client certificate expires the check warns.
+const EXPIRY_WARNING: chrono::Duration = chrono::Duration::days(7);
+
+/// Outcome of one check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    Pass,
+    Warn,
+    Fail,
+    Skipped,
+}
+
+/// One check of the report.
+#[derive(Debug, Clone, Serialize)]
+pub struct CheckResult {
+    pub name: &'static str,
+    pub status: CheckStatus,
+    pub detail: String,
+    /// Steps that fix a failure or warning, for the user.
+    pub remediation: Vec<String>,
+}
+
+impl CheckResult {
+    /// The detail and its remediation steps, one per line.
+    pub fn report(&self) -> String {
+        let mut report = self.detail.clone();
+        for step in &self.remediation {
+            report.push_str("\n  - ");
+            report.push_str(step);
+        }
+        report
+    }
+}
+
+/// Results of the pre-flight checks.
+#[derive(Debug, Clone, Serialize)]
+pub struct ReadinessReport {
+    pub checks: Vec<CheckResult>,
+    /// Agents to register.
+    pub ready_agents: Vec<String>,
+    /// Agents left out.
+    pub unready_agents: Vec<UnreadyAgent>,
+}
+
+/// An agent left out, and the failed check it needs.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct UnreadyAgent {
+    pub name: String,
+    pub needs: &'static str,
+}
+
+/// What the worker is about to run with.
+#[derive(Debug, Clone)]
+pub struct Preflight<'a> {
+    pub claude_path: &'a str,
+    pub skip_claude_check: bool,
+    pub sandbox: Option<SandboxRuntime>,
+    pub working_dir: &'a Path,
+    /// None when the worker does not connect to a control plane.
+    pub client_cert: Option<&'a Path>,
+    /// Agents to register, each with whether its runs are sandboxed.
+    pub agents: Vec<(String, bool)>,
+}
+
+impl Preflight<'_> {
+    /// Run the checks.
+    pub fn run(&self) -> ReadinessReport {
+        let on_host = self.agents.iter().any(|(_, sandboxed)| !sandboxed);
+        let checks = vec![
+            self.check_claude(on_host),
+            self.check_sandbox(),
+            check_working_dir(self.working_dir),
+            match self.client_cert {
+                Some(path) => check_client_cert(path, Utc::now()),
+                None => skipped("client_cert", "no control plane"),
+            },
+        ];
+
+        let failed = |name: &str| {
+            checks
+                .iter()
+                .any(|check| check.name == name && check.status == CheckStatus::Fail)
+        };
+        let mut ready_agents = Vec::new();
+        let mut unready_agents = Vec::new();
+        for (agent, sandboxed) in &self.agents {
+            let needs = if *sandboxed { "sandbox" } else { "claude" };
+            if failed(needs) {
+                unready_agents.push(UnreadyAgent {
+                    name: agent.clone(),
+                    needs,
+                });
+            } else {
+                ready_agents.push(agent.clone());
+            }
+        }
+        ReadinessReport {
+            checks,
+            ready_agents,
+            unready_agents,
+        }
+    }
+
+    fn check_claude(&self, on_host: bool) -> CheckResult {
+        if self.skip_claude_check {
+            return skipped("claude", "--skip-claude-check");
+        }
+        if !on_host {
+            return skipped("claude", "every run is sandboxed and uses the image's CLI");
+        }
+        match claude_check::check(self.claude_path) {
+            Ok(cli) => pass(
+                "claude",
+                format!("{} ({})", cli.version, cli.path.display()),
+            ),
+            Err(e) => CheckResult {
+                name: "claude",
+                status: CheckStatus::Fail,
+                detail: e.to_string(),
+                remediation: e.remediation().iter().map(|s| s.to_string()).collect(),
+            },
+        }
+    }
+
+    fn check_sandbox(&self) -> CheckResult {
+        let Some(runtime) = self.sandbox else {
+            return skipped("sandbox", "no --sandbox");
+        };
+        let program = runtime.program();
+        match claude_check::resolve(program, env::var_os("PATH").as_deref())
+            .filter(|path| claude_check::is_executable(path))
+        {
+            Some(path) => pass("sandbox", path.display().to_string()),
+            None => fail(
+                "sandbox",
+                format!("Container runtime '{}' not found", program),
+                &[
+                    &format!("Install {} or put it on PATH", program),
+                    "Or pick the other runtime with --sandbox",
+                ],
+            ),
+        }
+    }
+}
+
+impl ReadinessReport {
+    /// Whether the worker can serve: no worker-wide check failed and some
+    /// agent is left to register.
+    pub fn is_ready(&self) -> bool {
+        let worker_failed = self.checks.iter().any(|check| {
+            check.status == CheckStatus::Fail && matches!(check.name, "working_dir" | "client_cert")
+        });
+        !worker_failed && !self.ready_agents.is_empty()
+    }
+
+    /// Checks that failed or warned.
+    pub fn problems(&self) -> impl Iterator<Item = &CheckResult> {
+        self.checks
+            .iter()
+            .filter(|check| matches!(check.status, CheckStatus::Fail | CheckStatus::Warn))
+    }
+
+    /// Log every check, with remediation for failures and warnings.
+    pub fn log(&self) {
+        for check in &self.checks {
+            match check.status {
+                CheckStatus::Pass => {
+                    info!(check = check.name, detail = %check.detail, "Pre-flight check passed")
+                }
+                CheckStatus::Skipped => {
+                    info!(check = check.name, reason = %check.detail, "Pre-flight check skipped")
+                }
+                CheckStatus::Warn => warn!(check = check.name, "{}", check.report()),
+                CheckStatus::Fail => error!(check = check.name, "{}", check.report()),
+            }
+        }
+        for agent in &self.unready_agents {
+            warn!(agent = %agent.name, check = agent.needs, "Not registering agent, a check it needs failed");
+        }
+    }
+}
+
+/// Write and remove a probe file in `dir`.
+fn check_working_dir(dir: &Path) -> CheckResult {
+    let probe = dir.join(format!(".taskrun-preflight-{}", std::process::id()));
+    match std::fs::write(&probe, b"") {
+        Ok(()) => {
+            let _ = std::fs::remove_file(&probe);
+            pass("working_dir", dir.display().to_string())
+        }
+        Err(e) => fail(
+            "working_dir",
+            format!("Can't write to {}: {}", dir.display(), e),
+            &[
+                "Fix the directory's permissions, or start the worker in a writable directory",
+                "In the TUI, pass another directory with --working-dir",
+            ],
+        ),
+    }
+}
+
+/// Read the validity of the PEM certificate at `path` as of `now`.
+fn check_client_cert(path: &Path, now: DateTime<Utc>) -> CheckResult {
+    let regenerate =
+        "Generate a new one (scripts/gen-worker-cert.sh) or enroll with --enroll-token";
+    let pem = match std::fs::read(path) {
+        Ok(pem) => pem,
+        Err(e) => {
+            return fail(
+                "client_cert",
+                format!("Can't read {}: {}", path.display(), e),
+                &["Pass the worker certificate with --client-cert", regenerate],
+            )
+        }
+    };
+    let validity = parse_x509_pem(&pem)
+        .map_err(|e| e.to_string())
+        .and_then(|(_, pem)| {
+            let cert = pem.parse_x509().map_err(|e| e.to_string())?;
+            let timestamp = |t: x509_parser::time::ASN1Time| {
+                DateTime::from_timestamp(t.timestamp(), 0).unwrap_or_default()
+            };
+            Ok((
+                timestamp(cert.validity().not_before),
+                timestamp(cert.validity().not_after),
+            ))
+        });
+    let (not_before, not_after) = match validity {
+        Ok(validity) => validity,
+        Err(e) => {
+            return fail(
+                "client_cert",
+                format!("{} is not a PEM certificate: {}", path.display(), e),
+                &["Pass the worker certificate with --client-cert", regenerate],
+            )
+        }
+    };
+
+    let date = |t: DateTime<Utc>| t.format("%Y-%m-%d %H:%M UTC").to_string();
+    if now < not_before {
+        fail(
+            "client_cert",
+            format!("Not valid until {}", date(not_before)),
+            &["Check the system clock", regenerate],
+        )
+    } else if now >= not_after {
+        fail(
+            "client_cert",
+            format!("Expired on {}", date(not_after)),
+            &[regenerate],
+        )
+    } else if not_after - now < EXPIRY_WARNING {
+        CheckResult {
+            name: "client_cert",
+            status: CheckStatus::Warn,
+            detail: format!("Expires on {}", date(not_after)),
+            remediation: vec![regenerate.to_string()],
+        }
+    } else {
+        pass("client_cert", format!("Valid until {}", date(not_after)))
+    }
+}
+
+fn pass(name: &'static str, detail: String) -> CheckResult {
+    CheckResult {
+        name,
+        status: CheckStatus::Pass,
+        detail,
+        remediation: Vec::new(),
+    }
+}
+
+fn skipped(name: &'static str, reason: &str) -> CheckResult {
+    CheckResult {
+        name,
+        status: CheckStatus::Skipped,
+        detail: reason.to_string(),
+        remediation: Vec::new(),
+    }
+}
+
+fn fail(name: &'static str, detail: String, remediation: &[&str]) -> CheckResult {
+    CheckResult {
+        name,
+        status: CheckStatus::Fail,
+        detail,
+        remediation: remediation.iter().map(|s| s.to_string()).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rcgen::{CertificateParams, KeyPair};
+
+    #[test]
+    fn test_certificate_expiry_and_agent_requirements() {
+        let dir = std::env::temp_dir().join(format!("taskrun-preflight-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let cert_path = dir.join("worker.crt");
+        let mut params = CertificateParams::new(vec!["worker".to_string()]).unwrap();
+        params.not_before = rcgen::date_time_ymd(2026, 1, 1);
+        params.not_after = rcgen::date_time_ymd(2026, 3, 1);
+        let cert = params.self_signed(&KeyPair::generate().unwrap()).unwrap();
+        std::fs::write(&cert_path, cert.pem()).unwrap();
+
+        let at = |day: &str| format!("{}T00:00:00Z", day).parse().unwrap();
+        let status = |day: &str| check_client_cert(&cert_path, at(day)).status;
+        assert_eq!(status("2026-02-01"), CheckStatus::Pass);
+        assert_eq!(status("2026-02-25"), CheckStatus::Warn);
+        assert_eq!(status("2026-03-02"), CheckStatus::Fail);
+        assert_eq!(status("2025-12-31"), CheckStatus::Fail);
+
+        // A missing CLI keeps host agents from registering, not sandboxed ones
+        let preflight = Preflight {
+            claude_path: "/nonexistent/claude",
+            skip_claude_check: false,
+            sandbox: None,
+            working_dir: &dir,
+            client_cert: Some(&cert_path),
+            agents: vec![("host".to_string(), false), ("boxed".to_string(), true)],
+        };
+        let report = preflight.run();
+        assert_eq!(report.ready_agents, ["boxed"]);
+        assert_eq!(
+            report.unready_agents,
+            [UnreadyAgent {
+                name: "host".to_string(),
+                needs: "claude"
+            }]
+        );
+        assert!(report
+            .checks
+            .iter()
+            .any(|c| c.name == "working_dir" && c.status == CheckStatus::Pass));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,314 @@
+//! Run-scoped `report_progress` MCP tool.
+//!
+//! The worker serves a small MCP server on a loopback port and points each
+//! Claude process at it with `--mcp-config`. The config carries a token for
+//! the run, so a `report_progress` call becomes a `Milestone` event of that
+//! run and reaches the control plane like any other run event.
+
+use std::collections::HashMap;
+use std::net::Ipv4Addr;
+use std::sync::{Arc, Mutex};
+
+use axum::http::request::Parts;
+use axum::Router;
+use rmcp::{
+    handler::server::{tool::Extension, wrapper::Parameters},
+    model::{CallToolResult, Content, ServerCapabilities, ServerInfo},
+    tool, tool_handler, tool_router,
+    transport::streamable_http_server::{
+        session::local::LocalSessionManager, StreamableHttpServerConfig, StreamableHttpService,
+    },
+    ErrorData as McpError, ServerHandler,
+};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use taskrun_claude_sdk::ClaudeExecutor;
+use taskrun_core::{RunEvent, RunId, RunMilestone, TaskId};
+use tokio::net::TcpListener;
+use tokio::sync::{mpsc, OnceCell};
+use tracing::{info, warn};
+
+/// Name Claude knows the tool by, for `--allowedTools`.
+pub const TOOL_NAME: &str = "mcp__taskrun__report_progress";
+
+/// Header carrying the token of the calling run.
+const RUN_HEADER: &str = "x-taskrun-run";
+
+/// Where the milestones of a run go.
+struct RunSink {
+    run_id: RunId,
+    task_id: TaskId,
+    event_tx: mpsc::Sender<RunEvent>,
+}
+
+/// Registered runs by token.
+type Runs = Arc<Mutex<HashMap<String, RunSink>>>;
+
+/// The worker's progress tool, started on the first run that uses it.
+#[derive(Clone)]
+pub struct ProgressTool {
+    enabled: bool,
+    /// URL of the MCP endpoint, or None if the server failed to start.
+    url: Arc<OnceCell<Option<String>>>,
+    runs: Runs,
+}
+
+impl ProgressTool {
+    /// Create the tool; a disabled one registers no runs.
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            url: Arc::new(OnceCell::new()),
+            runs: Arc::default(),
+        }
+    }
+
+    /// Register a run, starting the server if needed.
+    ///
+    /// Returns None when the tool is disabled or could not be started; the
+    /// run then goes without it.
+    pub async fn register(
+        &self,
+        run_id: RunId,
+        task_id: TaskId,
+        event_tx: mpsc::Sender<RunEvent>,
+    ) -> Option<RunRegistration> {
+        if !self.enabled {
+            return None;
+        }
+        let url = self
+            .url
+            .get_or_init(|| async {
+                match serve(self.runs.clone()).await {
+                    Ok(url) => Some(url),
+                    Err(e) => {
+                        warn!(error = %e, "Failed to start the progress tool");
+                        None
+                    }
+                }
+            })
+            .await
+            .clone()?;
+
+        let token = uuid::Uuid::new_v4().to_string();
+        self.runs.lock().unwrap().insert(
+            token.clone(),
+            RunSink {
+                run_id,
+                task_id,
+                event_tx,
+            },
+        );
+        Some(RunRegistration {
+            token,
+            url,
+            runs: self.runs.clone(),
+        })
+    }
+}
+
+/// A run's access to the tool, revoked when dropped.
+pub struct RunRegistration {
+    token: String,
+    url: String,
+    runs: Runs,
+}
+
+impl RunRegistration {
+    /// `--mcp-config` JSON pointing Claude at the tool with this run's token.
+    pub fn mcp_config(&self) -> String {
+        serde_json::json!({
+            "mcpServers": {
+                "taskrun": {
+                    "type": "http",
+                    "url": self.url,
+                    "headers": { RUN_HEADER: self.token },
+                }
+            }
+        })
+        .to_string()
+    }
+
+    /// Give `executor` the tool. When tools are allow-listed, the tool is
+    /// added to `allowed_tools` so the list does not lock it out.
+    pub fn apply(
+        &self,
+        executor: ClaudeExecutor,
+        allowed_tools: Option<&[String]>,
+    ) -> ClaudeExecutor {
+        let executor = executor.with_mcp_config(self.mcp_config());
+        match allowed_tools {
+            Some(allowed) => {
+                let mut allowed = allowed.to_vec();
+                allowed.push(TOOL_NAME.to_string());
+                executor.with_allowed_tools(allowed)
+            }
+            None => executor,
+        }
+    }
+}
+
+impl Drop for RunRegistration {
+    fn drop(&mut self) {
+        self.runs.lock().unwrap().remove(&self.token);
+    }
+}
+
+/// Serve the MCP endpoint on a loopback port, returning its URL.
+async fn serve(runs: Runs) -> std::io::Result<String> {
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await?;
+    let addr = listener.local_addr()?;
+    let service = StreamableHttpService::new(
+        move || Ok(ProgressServer::new(runs.clone())),
+        LocalSessionManager::default().into(),
+        StreamableHttpServerConfig::default(),
+    );
+    let router = Router::new().nest_service("/mcp", service);
+    tokio::spawn(async move {
+        if let Err(e) = axum::serve(listener, router).await {
+            warn!(error = %e, "Progress tool stopped");
+        }
+    });
+    info!(%addr, "Progress tool listening");
+    Ok(format!("http://{}/mcp", addr))
+}
+
+/// Parameters for the report_progress tool.
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ReportProgressParams {
+    /// Short name of the milestone reached, e.g. "tests pass".
+    pub name: String,
+
+    /// How far along the whole task is, 0-100.
+    #[serde(default)]
+    pub percent: Option<u32>,
+
+    /// Optional detail about the milestone.
+    #[serde(default)]
+    pub note: Option<String>,
+}
+
+/// MCP server for the progress tool.
+#[derive(Clone)]
+struct ProgressServer {
+    runs: Runs,
+    tool_router: rmcp::handler::server::router::tool::ToolRouter<Self>,
+}
+
+#[tool_router]
+impl ProgressServer {
+    fn new(runs: Runs) -> Self {
+        Self {
+            runs,
+            tool_router: Self::tool_router(),
+        }
+    }
+
+    /// Record a milestone of the calling run.
+    #[tool(
+        description = "Report a milestone of the current task, e.g. after finishing a step. Shown to the people following the task."
+    )]
+    async fn report_progress(
+        &self,
+        Extension(parts): Extension<Parts>,
+        Parameters(params): Parameters<ReportProgressParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let token = parts
+            .headers
+            .get(RUN_HEADER)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or_default();
+        report(&self.runs, token, params).await?;
+        Ok(CallToolResult::success(vec![Content::text(
+            "Progress recorded.",
+        )]))
+    }
+}
+
+/// Send the milestone in `params` as an event of the run holding `token`.
+async fn report(runs: &Runs, token: &str, params: ReportProgressParams) -> Result<(), McpError> {
+    let name = params.name.trim();
+    if name.is_empty() {
+        return Err(McpError::invalid_params("name must not be empty", None));
+    }
+    let milestone = RunMilestone::new(name)
+        .with_percent(params.percent.map(|p| p.min(100) as u8))
+        .with_note(params.note);
+
+    let (event, event_tx) = {
+        let runs = runs.lock().unwrap();
+        let sink = runs
+            .get(token)
+            .ok_or_else(|| McpError::invalid_request("not called from an active run", None))?;
+        (
+            RunEvent::milestone(sink.run_id.clone(), sink.task_id.clone(), &milestone),
+            sink.event_tx.clone(),
+        )
+    };
+    event_tx
+        .send(event)
+        .await
+        .map_err(|_| McpError::invalid_request("the run has finished", None))
+}
+
+#[tool_handler]
+impl ServerHandler for ProgressServer {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            protocol_version: Default::default(),
+            capabilities: ServerCapabilities::builder().enable_tools().build(),
+            server_info: rmcp::model::Implementation {
+                name: "taskrun-worker".to_string(),
+                version: env!("CARGO_PKG_VERSION").to_string(),
+                title: None,
+                website_url: None,
+                icons: None,
+            },
+            instructions: Some(
+                "Use report_progress to tell TaskRun about milestones of the current task."
+                    .to_string(),
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use taskrun_core::RunEventType;
+
+    #[tokio::test]
+    async fn test_report_sends_milestone_of_registered_run() {
+        let tool = ProgressTool::new(true);
+        let (event_tx, mut event_rx) = mpsc::channel(4);
+        let run_id = RunId::generate();
+        let registration = tool
+            .register(run_id.clone(), TaskId::generate(), event_tx)
+            .await
+            .unwrap();
+        assert!(registration.mcp_config().contains(&registration.token));
+
+        let params = ReportProgressParams {
+            name: "tests pass".to_string(),
+            percent: Some(250),
+            note: None,
+        };
+        report(&tool.runs, &registration.token, params)
+            .await
+            .unwrap();
+        let event = event_rx.recv().await.unwrap();
+        assert_eq!(event.event_type, RunEventType::Milestone);
+        assert_eq!(event.run_id, run_id);
+        let milestone = RunMilestone::from_event(&event).unwrap();
+        assert_eq!(milestone.percent, Some(100));
+
+        let token = registration.token.clone();
+        drop(registration);
+        let params = ReportProgressParams {
+            name: "late".to_string(),
+            percent: None,
+            note: None,
+        };
+        assert!(report(&tool.runs, &token, params).await.is_err());
+    }
+}
@@ -0,0 +1,197 @@
+//! Reconnect backoff.
+//!
+//! After a failed or lost connection the worker waits before reconnecting:
+//! `--reconnect-initial-secs` at first, doubling with each failed attempt up
+//! to `--reconnect-max-secs`. Each wait is drawn at random between half the
+//! delay and the delay, so workers cut off together don't reconnect
+//! together. The delay starts over once a connection is up. With
+//! `--reconnect-max-retries`, the worker gives up after that many failed
+//! attempts in a row.
+//!
+//! While waiting, the worker watches the addresses of the host's network
+//! interfaces and reconnects at once when they change, starting the delay
+//! over.
+
+use std::collections::BTreeSet;
+use std::time::Duration;
+
+use rand::Rng;
+
+/// How often the network interfaces are checked for changes while waiting.
+const NETWORK_POLL: Duration = Duration::from_secs(2);
+
+/// How long to wait between connection attempts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReconnectPolicy {
+    /// Delay after the first failure.
+    pub initial: Duration,
+    /// Ceiling of the delay.
+    pub max: Duration,
+    /// Failed attempts in a row to give up after; None retries forever.
+    pub max_retries: Option<u32>,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            initial: Duration::from_secs(1),
+            max: Duration::from_secs(60),
+            max_retries: None,
+        }
+    }
+}
+
+/// Exponential backoff with jitter between connection attempts.
+#[derive(Debug, Clone)]
+pub struct Backoff {
+    policy: ReconnectPolicy,
+    failures: u32,
+}
+
+impl Backoff {
+    pub fn new(policy: ReconnectPolicy) -> Self {
+        Self {
+            policy,
+            failures: 0,
+        }
+    }
+
+    /// Count a failed attempt and return how long to wait before the next,
+    /// or None once `max_retries` attempts in a row have failed.
+    pub fn next_delay(&mut self) -> Option<Duration> {
+        if self
+            .policy
+            .max_retries
+            .is_some_and(|max| self.failures >= max)
+        {
+            return None;
+        }
+        let delay = self.delay(self.failures);
+        self.failures += 1;
+        Some(jitter(delay))
+    }
+
+    /// Failed attempts in a row so far.
+    pub fn failures(&self) -> u32 {
+        self.failures
+    }
+
+    /// Start over, after a connection was up or the network changed.
+    pub fn reset(&mut self) {
+        self.failures = 0;
+    }
+
+    /// Delay after `failures` earlier failures, before jitter.
+    fn delay(&self, failures: u32) -> Duration {
+        self.policy
+            .initial
+            .saturating_mul(2u32.saturating_pow(failures))
+            .min(self.policy.max)
+    }
+}
+
+/// A random delay between half of `delay` and `delay`.
+fn jitter(delay: Duration) -> Duration {
+    if delay.is_zero() {
+        return delay;
+    }
+    rand::thread_rng().gen_range(delay / 2..=delay)
+}
+
+/// Addresses of the host's network interfaces (`<interface>=<address>`), or
+/// None where they can't be listed.
+pub fn interface_addresses() -> Option<BTreeSet<String>> {
+    system_interface_addresses()
+}
+
+/// Resolve once the interface addresses differ from `before`; never, if
+/// they can't be listed.
+pub async fn network_change(before: Option<BTreeSet<String>>) {
+    let Some(before) = before else {
+        return std::future::pending().await;
+    };
+    loop {
+        tokio::time::sleep(NETWORK_POLL).await;
+        if interface_addresses().is_some_and(|now| now != before) {
+            return;
+        }
+    }
+}
+
+#[cfg(unix)]
+fn system_interface_addresses() -> Option<BTreeSet<String>> {
+    use std::ffi::CStr;
+    use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+    let mut list: *mut libc::ifaddrs = std::ptr::null_mut();
+    // SAFETY: getifaddrs fills `list`, freed with freeifaddrs below
+    if unsafe { libc::getifaddrs(&mut list) } != 0 {
+        return None;
+    }
+    let mut addresses = BTreeSet::new();
+    let mut entry = list;
+    while !entry.is_null() {
+        // SAFETY: entries of the list stay valid until freeifaddrs
+        let ifa = unsafe { &*entry };
+        entry = ifa.ifa_next;
+        if ifa.ifa_addr.is_null() || ifa.ifa_name.is_null() {
+            continue;
+        }
+        // SAFETY: ifa_addr points at a sockaddr of the family it states
+        let address = unsafe {
+            match i32::from((*ifa.ifa_addr).sa_family) {
+                libc::AF_INET => {
+                    let addr = &*(ifa.ifa_addr as *const libc::sockaddr_in);
+                    IpAddr::V4(Ipv4Addr::from(u32::from_be(addr.sin_addr.s_addr)))
+                }
+                libc::AF_INET6 => {
+                    let addr = &*(ifa.ifa_addr as *const libc::sockaddr_in6);
+                    IpAddr::V6(Ipv6Addr::from(addr.sin6_addr.s6_addr))
+                }
+                _ => continue,
+            }
+        };
+        // SAFETY: ifa_name is a NUL-terminated string
+        let name = unsafe { CStr::from_ptr(ifa.ifa_name) };
+        addresses.insert(format!("{}={}", name.to_string_lossy(), address));
+    }
+    // SAFETY: `list` came from getifaddrs
+    unsafe { libc::freeifaddrs(list) };
+    Some(addresses)
+}
+
+#[cfg(not(unix))]
+fn system_interface_addresses() -> Option<BTreeSet<String>> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_delays_double_up_to_the_ceiling_and_give_up() {
+        let mut backoff = Backoff::new(ReconnectPolicy {
+            initial: Duration::from_secs(1),
+            max: Duration::from_secs(5),
+            max_retries: Some(5),
+        });
+        let ceilings = [1, 2, 4, 5, 5].map(Duration::from_secs);
+        for ceiling in ceilings {
+            let delay = backoff.next_delay().unwrap();
+            assert!(delay >= ceiling / 2 && delay <= ceiling, "{:?}", delay);
+        }
+        assert_eq!(backoff.failures(), 5);
+        assert_eq!(backoff.next_delay(), None);
+
+        backoff.reset();
+        assert!(backoff.next_delay().unwrap() <= Duration::from_secs(1));
+
+        // The loopback interface is always there
+        #[cfg(unix)]
+        assert!(interface_addresses()
+            .unwrap()
+            .iter()
+            .any(|a| a.ends_with("=127.0.0.1")));
+    }
+}
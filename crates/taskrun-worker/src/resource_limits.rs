@@ -0,0 +1,239 @@
+//! Resource limits on the Claude process, configured per agent.
+//!
+//! Limits are read from a JSON file (`--resource-limits`):
+//!
+//! ```json
+//! {
+//!   "cgroup_parent": "/sys/fs/cgroup/taskrun",
+//!   "default": { "max_memory_mb": 4096, "max_processes": 256 },
+//!   "agents": {
+//!     "support_triage": { "max_memory_mb": 1024, "cpu_weight": 50, "no_network": true },
+//!     "code_review": { "max_cpus": 2.0, "sandbox_image": "ghcr.io/acme/claude-go:1" },
+//!     "batch_report": { "max_cpu_seconds": 600, "max_wall_seconds": 1800 }
+//!   }
+//! }
+//! ```
+//!
+//! An agent entry replaces `default` entirely. Without `cgroup_parent` the
+//! limits are applied as rlimits (see `taskrun_claude_sdk::ResourceLimits`).
+//! With `--sandbox` they are applied to the run's container, and
+//! `sandbox_image` picks the image instead of `--sandbox-image`.
+//!
+//! A task can set its own memory, CPU time and wall-clock limits; they apply
+//! where they are tighter than the agent's (see `with_run_limits`).
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::Deserialize;
+use taskrun_claude_sdk::ResourceLimits;
+use taskrun_core::RunLimits;
+use thiserror::Error;
+
+/// Resource limit config errors.
+#[derive(Debug, Error)]
+pub enum ResourceLimitError {
+    #[error("Failed to read resource limits from '{path}': {source}")]
+    Io {
+        path: String,
+        source: std::io::Error,
+    },
+
+    #[error("Invalid resource limit config: {0}")]
+    Parse(#[from] serde_json::Error),
+}
+
+/// On-disk limit configuration.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ResourceLimitConfig {
+    /// Delegated cgroup v2 directory to create execution cgroups in.
+    #[serde(default)]
+    pub cgroup_parent: Option<PathBuf>,
+
+    /// Limits for agents without their own entry.
+    #[serde(default)]
+    pub default: Option<AgentLimits>,
+
+    /// Limits keyed by agent name.
+    #[serde(default)]
+    pub agents: HashMap<String, AgentLimits>,
+}
+
+/// Limits for one agent.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AgentLimits {
+    /// Memory cap in MiB.
+    #[serde(default)]
+    pub max_memory_mb: Option<u64>,
+
+    /// CPU weight (1 to 10000, 100 is the default share; needs a cgroup).
+    #[serde(default)]
+    pub cpu_weight: Option<u32>,
+
+    /// CPU cap in CPUs (needs a cgroup or a sandbox).
+    #[serde(default)]
+    pub max_cpus: Option<f64>,
+
+    /// Maximum number of processes.
+    #[serde(default)]
+    pub max_processes: Option<u64>,
+
+    /// CPU time cap of each process in seconds.
+    #[serde(default)]
+    pub max_cpu_seconds: Option<u64>,
+
+    /// Wall-clock time after which the run is killed, in seconds.
+    #[serde(default)]
+    pub max_wall_seconds: Option<u64>,
+
+    /// Run without network access (Linux only).
+    #[serde(default)]
+    pub no_network: bool,
+
+    /// Container image to run the agent in, with `--sandbox`.
+    #[serde(default)]
+    pub sandbox_image: Option<String>,
+}
+
+impl ResourceLimitConfig {
+    /// Load limits from a JSON file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ResourceLimitError> {
+        let path = path.as_ref();
+        let json = std::fs::read_to_string(path).map_err(|source| ResourceLimitError::Io {
+            path: path.display().to_string(),
+            source,
+        })?;
+        Ok(serde_json::from_str(&json)?)
+    }
+
+    /// Limits for `agent_name`.
+    pub fn for_agent(&self, agent_name: &str) -> ResourceLimits {
+        let Some(agent) = self.agents.get(agent_name).or(self.default.as_ref()) else {
+            return ResourceLimits::default();
+        };
+        let mut limits = ResourceLimits::default().with_no_network(agent.no_network);
+        if let Some(mb) = agent.max_memory_mb {
+            limits = limits.with_max_memory(mb * 1024 * 1024);
+        }
+        if let Some(weight) = agent.cpu_weight {
+            limits = limits.with_cpu_weight(weight);
+        }
+        if let Some(cpus) = agent.max_cpus {
+            limits = limits.with_max_cpus(cpus);
+        }
+        if let Some(processes) = agent.max_processes {
+            limits = limits.with_max_processes(processes);
+        }
+        if let Some(secs) = agent.max_cpu_seconds {
+            limits = limits.with_max_cpu_time(Duration::from_secs(secs));
+        }
+        if let Some(secs) = agent.max_wall_seconds {
+            limits = limits.with_max_wall_time(Duration::from_secs(secs));
+        }
+        if let Some(parent) = &self.cgroup_parent {
+            limits = limits.with_cgroup_parent(parent);
+        }
+        limits
+    }
+
+    /// Sandbox image for `agent_name`, if its limits name one.
+    pub fn sandbox_image(&self, agent_name: &str) -> Option<&str> {
+        self.agents
+            .get(agent_name)
+            .or(self.default.as_ref())?
+            .sandbox_image
+            .as_deref()
+    }
+}
+
+/// `limits` with a task's `run` limits where they are tighter.
+pub fn with_run_limits(mut limits: ResourceLimits, run: &RunLimits) -> ResourceLimits {
+    if let Some(bytes) = run.max_memory_mb.map(|mb| mb.saturating_mul(1024 * 1024)) {
+        if !matches!(limits.max_memory(), Some(max) if max <= bytes) {
+            limits = limits.with_max_memory(bytes);
+        }
+    }
+    if let Some(time) = run.max_cpu_seconds.map(Duration::from_secs) {
+        if !matches!(limits.max_cpu_time(), Some(max) if max <= time) {
+            limits = limits.with_max_cpu_time(time);
+        }
+    }
+    if let Some(time) = run.max_wall_seconds.map(Duration::from_secs) {
+        if !matches!(limits.max_wall_time(), Some(max) if max <= time) {
+            limits = limits.with_max_wall_time(time);
+        }
+    }
+    limits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_agent_entry_replaces_default() {
+        let config: ResourceLimitConfig = serde_json::from_str(
+            r#"{
+                "default": { "max_memory_mb": 4096, "max_processes": 256 },
+                "agents": { "triage": { "max_memory_mb": 1024 } }
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.for_agent("triage"),
+            ResourceLimits::default().with_max_memory(1024 * 1024 * 1024)
+        );
+        assert_eq!(
+            config.for_agent("general"),
+            ResourceLimits::default()
+                .with_max_memory(4096 * 1024 * 1024)
+                .with_max_processes(256)
+        );
+        assert!(ResourceLimitConfig::default()
+            .for_agent("general")
+            .is_empty());
+    }
+
+    #[test]
+    fn test_run_limits_only_tighten_agent_limits() {
+        let config: ResourceLimitConfig = serde_json::from_str(
+            r#"{ "default": { "max_memory_mb": 1024, "max_wall_seconds": 600 } }"#,
+        )
+        .unwrap();
+        let run = RunLimits {
+            max_memory_mb: Some(2048),
+            max_cpu_seconds: Some(60),
+            max_wall_seconds: Some(300),
+        };
+
+        assert_eq!(
+            with_run_limits(config.for_agent("general"), &run),
+            ResourceLimits::default()
+                .with_max_memory(1024 * 1024 * 1024)
+                .with_max_wall_time(Duration::from_secs(300))
+                .with_max_cpu_time(Duration::from_secs(60))
+        );
+    }
+
+    #[test]
+    fn test_sandbox_image_follows_agent_entry() {
+        let config: ResourceLimitConfig = serde_json::from_str(
+            r#"{
+                "default": { "sandbox_image": "claude:base" },
+                "agents": { "review": { "max_cpus": 1.5, "sandbox_image": "claude:go" } }
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(config.sandbox_image("review"), Some("claude:go"));
+        assert_eq!(config.sandbox_image("general"), Some("claude:base"));
+        assert_eq!(
+            config.for_agent("review"),
+            ResourceLimits::default().with_max_cpus(1.5)
+        );
+    }
+}
@@ -0,0 +1,131 @@
+//! Result summaries of finished runs.
+//!
+//! While a run executes, the worker counts its tool calls, notes the files
+//! its agent writes or edits, and keeps the final answer and token usage
+//! Claude reports. When the run completes, these become a `RunSummary`
+//! event, so run lists can preview what a run produced without fetching its
+//! output. The answer excerpt goes through the agent's output filters, like
+//! the output itself.
+
+use std::path::Path;
+use std::time::Duration;
+
+use serde_json::Value;
+use taskrun_claude_sdk::{ResultUsage, ToolData};
+use taskrun_core::RunResultSummary;
+
+use crate::output_filter::RunOutputFilter;
+
+/// Tools outside [`ToolData`]'s variants that change files, and the input
+/// field naming the file.
+const EDIT_TOOLS: [(&str, &str); 2] = [
+    ("MultiEdit", "file_path"),
+    ("NotebookEdit", "notebook_path"),
+];
+
+/// Collects the summary of one execution.
+#[derive(Debug, Default)]
+pub struct ResultTracker {
+    tools_used: u32,
+    /// Files written or edited, in the order first changed.
+    files: Vec<String>,
+    answer: Option<String>,
+    input_tokens: Option<u64>,
+    output_tokens: Option<u64>,
+}
+
+impl ResultTracker {
+    /// Record a requested tool call.
+    pub fn on_tool_use(&mut self, tool: &ToolData) {
+        self.tools_used += 1;
+        let path = match tool {
+            ToolData::Write { file_path, .. } | ToolData::Edit { file_path, .. } => {
+                Some(file_path.as_str())
+            }
+            ToolData::Unknown(data) => EDIT_TOOLS
+                .iter()
+                .find(|(name, _)| tool.name() == *name)
+                .and_then(|(_, field)| data.get("input")?.get(*field)?.as_str()),
+            _ => None,
+        };
+        if let Some(path) = path.filter(|p| !self.files.iter().any(|f| f == p)) {
+            self.files.push(path.to_string());
+        }
+    }
+
+    /// Record Claude's final result and token usage.
+    pub fn on_result(&mut self, result: Option<&Value>, usage: Option<&ResultUsage>) {
+        self.answer = result.and_then(Value::as_str).map(str::to_string);
+        if let Some(usage) = usage {
+            self.input_tokens = Some(usage.total_input_tokens());
+            self.output_tokens = Some(usage.output_tokens);
+        }
+    }
+
+    /// The summary of an execution in `working_dir` that took `duration`,
+    /// its answer masked by `filter`.
+    pub fn summary(
+        &self,
+        working_dir: &Path,
+        duration: Duration,
+        mut filter: RunOutputFilter,
+    ) -> RunResultSummary {
+        let files = self
+            .files
+            .iter()
+            .map(|file| {
+                Path::new(file)
+                    .strip_prefix(working_dir)
+                    .map_or_else(|_| file.clone(), |p| p.display().to_string())
+            })
+            .collect();
+        let summary = RunResultSummary {
+            tools_used: self.tools_used,
+            duration_ms: Some(duration.as_millis() as u64),
+            input_tokens: self.input_tokens,
+            output_tokens: self.output_tokens,
+            ..Default::default()
+        }
+        .with_files(files);
+        match &self.answer {
+            Some(answer) => summary.with_answer(&filter.filter(answer.clone())),
+            None => summary,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_tracker_counts_tools_and_changed_files() {
+        let mut tracker = ResultTracker::default();
+        let tools = [
+            json!({"name": "Read", "input": {"file_path": "/work/src/lib.rs"}}),
+            json!({"name": "Edit", "input": {"file_path": "/work/src/lib.rs", "old_string": "a", "new_string": "b"}}),
+            json!({"name": "Write", "input": {"file_path": "notes.md", "content": "x"}}),
+            json!({"name": "MultiEdit", "input": {"file_path": "/work/src/lib.rs", "edits": []}}),
+            json!({"name": "Bash", "input": {"command": "cargo test"}}),
+        ];
+        for tool in tools {
+            tracker.on_tool_use(&serde_json::from_value(tool).unwrap());
+        }
+        let usage: ResultUsage =
+            serde_json::from_value(json!({"input_tokens": 10, "output_tokens": 5})).unwrap();
+        tracker.on_result(Some(&json!("All\n tests pass.")), Some(&usage));
+
+        let summary = tracker.summary(
+            Path::new("/work"),
+            Duration::from_secs(3),
+            RunOutputFilter::default(),
+        );
+        assert_eq!(summary.tools_used, 5);
+        assert_eq!(summary.files, ["src/lib.rs", "notes.md"]);
+        assert_eq!(summary.files_changed, 2);
+        assert_eq!(summary.answer.as_deref(), Some("All tests pass."));
+        assert_eq!(summary.duration_ms, Some(3000));
+        assert_eq!(summary.input_tokens, Some(10));
+    }
+}
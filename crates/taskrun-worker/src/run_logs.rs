@@ -0,0 +1,413 @@
+//! Per-run log files and log shipping (headless and JSON modes).
+//!
+//! Log records emitted while a run executes (inside its `run.execute` or
+//! `run.continue` span, Claude's stderr included) are written as JSON lines
+//! to `<run ID>.jsonl` in `--run-log-dir`. A file that reaches
+//! `--run-log-max-mb` is rotated to `<run ID>.1.jsonl` (up to
+//! `ROTATED_FILES` generations), and files untouched for
+//! `--run-log-retention-days` are deleted when the worker starts and as runs
+//! finish.
+//!
+//! With `--ship-run-logs <level>`, records at that level or more severe are
+//! also sent to the control plane as Log run events, so they show in the
+//! server TUI's run detail.
+
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+use chrono::Utc;
+use serde_json::{Map, Value};
+use taskrun_core::{RunEvent, RunId, TaskId};
+use thiserror::Error;
+use tokio::sync::mpsc;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::Layer;
+
+/// Rotated generations kept of a run's log file.
+const ROTATED_FILES: usize = 3;
+
+/// Run log errors.
+#[derive(Debug, Error)]
+pub enum RunLogError {
+    #[error("Failed to access run log directory '{path}': {source}")]
+    Io {
+        path: String,
+        source: std::io::Error,
+    },
+}
+
+/// Where run logs go.
+#[derive(Debug, Clone)]
+pub struct RunLogConfig {
+    /// Directory of the log files; None writes no files.
+    pub dir: Option<PathBuf>,
+    /// Size a log file is rotated at.
+    pub max_bytes: u64,
+    /// How long log files are kept after their last write.
+    pub retention: Duration,
+    /// Least severe level shipped to the control plane; None ships nothing.
+    pub ship_level: Option<Level>,
+}
+
+/// Per-run log files and shipping, shared by the tracing layer and the
+/// worker's runs.
+#[derive(Debug)]
+pub struct RunLogs {
+    config: RunLogConfig,
+    /// Open log files by run ID.
+    files: Mutex<HashMap<String, RunLogFile>>,
+    /// Where records of runs being shipped go, by run ID.
+    shipped: Mutex<HashMap<String, mpsc::UnboundedSender<RunEvent>>>,
+}
+
+#[derive(Debug)]
+struct RunLogFile {
+    file: File,
+    bytes: u64,
+}
+
+/// IDs of the run a span executes, kept in the span's extensions.
+struct RunSpan {
+    run_id: String,
+    task_id: String,
+}
+
+/// One line of a run log file.
+#[derive(Debug, serde::Serialize)]
+struct RunLogRecord<'a> {
+    timestamp: String,
+    level: &'a str,
+    target: &'a str,
+    run_id: &'a str,
+    message: String,
+    #[serde(skip_serializing_if = "Map::is_empty")]
+    fields: Map<String, Value>,
+}
+
+impl RunLogs {
+    /// Create the log directory, if any, and delete logs past retention.
+    pub fn open(config: RunLogConfig) -> Result<Self, RunLogError> {
+        if let Some(dir) = &config.dir {
+            std::fs::create_dir_all(dir).map_err(|source| RunLogError::Io {
+                path: dir.display().to_string(),
+                source,
+            })?;
+        }
+        let logs = Self {
+            config,
+            files: Mutex::new(HashMap::new()),
+            shipped: Mutex::new(HashMap::new()),
+        };
+        logs.prune();
+        Ok(logs)
+    }
+
+    /// The tracing layer recording run logs.
+    pub fn layer(self: &Arc<Self>) -> RunLogLayer {
+        RunLogLayer { logs: self.clone() }
+    }
+
+    /// Start shipping the records of `run_id`, if shipping is on. The
+    /// receiver ends after `stop_shipping`.
+    pub fn ship(&self, run_id: &str) -> Option<mpsc::UnboundedReceiver<RunEvent>> {
+        self.config.ship_level?;
+        let (tx, rx) = mpsc::unbounded_channel();
+        self.shipped.lock().unwrap().insert(run_id.to_string(), tx);
+        Some(rx)
+    }
+
+    /// Stop shipping the records of `run_id`.
+    pub fn stop_shipping(&self, run_id: &str) {
+        self.shipped.lock().unwrap().remove(run_id);
+    }
+
+    /// Delete log files not written to within the retention period.
+    pub fn prune(&self) {
+        let Some(dir) = &self.config.dir else {
+            return;
+        };
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return;
+        };
+        let now = SystemTime::now();
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("jsonl") {
+                continue;
+            }
+            let expired = entry
+                .metadata()
+                .and_then(|m| m.modified())
+                .ok()
+                .and_then(|modified| now.duration_since(modified).ok())
+                .is_some_and(|age| age > self.config.retention);
+            if expired {
+                let _ = std::fs::remove_file(path);
+            }
+        }
+    }
+
+    fn record(&self, run: &RunSpan, event: &Event<'_>) {
+        let metadata = event.metadata();
+        let mut visitor = FieldVisitor::default();
+        event.record(&mut visitor);
+
+        if let Some(dir) = &self.config.dir {
+            let record = RunLogRecord {
+                timestamp: Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+                level: metadata.level().as_str(),
+                target: metadata.target(),
+                run_id: &run.run_id,
+                message: visitor.message.clone(),
+                fields: visitor.fields.clone(),
+            };
+            if let Ok(line) = serde_json::to_string(&record) {
+                // Errors can't be logged from inside the logger; the record is lost
+                let _ = self.write(dir, &run.run_id, &line);
+            }
+        }
+
+        let shipped = self
+            .config
+            .ship_level
+            .is_some_and(|level| *metadata.level() <= level);
+        if shipped {
+            if let Some(tx) = self.shipped.lock().unwrap().get(&run.run_id) {
+                let _ = tx.send(RunEvent::log(
+                    RunId::new(&run.run_id),
+                    TaskId::new(&run.task_id),
+                    metadata.level().as_str(),
+                    metadata.target(),
+                    visitor.line(),
+                ));
+            }
+        }
+    }
+
+    fn write(&self, dir: &Path, run_id: &str, line: &str) -> std::io::Result<()> {
+        let mut files = self.files.lock().unwrap();
+        let path = dir.join(format!("{}.jsonl", run_id));
+        let needed = line.len() as u64 + 1;
+        if let Some(open) = files.get(run_id) {
+            if open.bytes > 0 && open.bytes + needed > self.config.max_bytes {
+                files.remove(run_id);
+                rotate(dir, run_id)?;
+            }
+        }
+        let open = match files.entry(run_id.to_string()) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                let file = OpenOptions::new().create(true).append(true).open(&path)?;
+                let bytes = file.metadata()?.len();
+                entry.insert(RunLogFile { file, bytes })
+            }
+        };
+        writeln!(open.file, "{}", line)?;
+        open.bytes += needed;
+        Ok(())
+    }
+
+    /// Close the log file of a run whose span closed.
+    fn close(&self, run_id: &str) {
+        if self.files.lock().unwrap().remove(run_id).is_some() {
+            self.prune();
+        }
+    }
+}
+
+/// Shift `<run ID>.jsonl` and its rotated generations up by one, dropping
+/// the oldest.
+fn rotate(dir: &Path, run_id: &str) -> std::io::Result<()> {
+    let generation = |n: usize| match n {
+        0 => dir.join(format!("{}.jsonl", run_id)),
+        n => dir.join(format!("{}.{}.jsonl", run_id, n)),
+    };
+    for n in (0..ROTATED_FILES).rev() {
+        let from = generation(n);
+        if from.exists() {
+            std::fs::rename(from, generation(n + 1))?;
+        }
+    }
+    Ok(())
+}
+
+/// Tracing layer writing the records of run spans to their run's log.
+pub struct RunLogLayer {
+    logs: Arc<RunLogs>,
+}
+
+impl<S> Layer<S> for RunLogLayer
+where
+    S: Subscriber + for<'span> LookupSpan<'span>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let mut visitor = FieldVisitor::default();
+        attrs.record(&mut visitor);
+        let Some(Value::String(run_id)) = visitor.fields.remove("run_id") else {
+            return;
+        };
+        let task_id = match visitor.fields.remove("task_id") {
+            Some(Value::String(task_id)) => task_id,
+            _ => String::new(),
+        };
+        if let Some(span) = ctx.span(id) {
+            span.extensions_mut().insert(RunSpan { run_id, task_id });
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let Some(scope) = ctx.event_scope(event) else {
+            return;
+        };
+        for span in scope {
+            if let Some(run) = span.extensions().get::<RunSpan>() {
+                self.logs.record(run, event);
+                return;
+            }
+        }
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(&id) {
+            if let Some(run) = span.extensions().get::<RunSpan>() {
+                self.logs.close(&run.run_id);
+            }
+        }
+    }
+}
+
+/// Collects a record's message and fields.
+#[derive(Default)]
+struct FieldVisitor {
+    message: String,
+    fields: Map<String, Value>,
+}
+
+impl FieldVisitor {
+    /// The message followed by `key=value` fields, as the console shows it.
+    fn line(&self) -> String {
+        let mut line = self.message.clone();
+        for (key, value) in &self.fields {
+            let value = match value {
+                Value::String(s) => s.clone(),
+                other => other.to_string(),
+            };
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(&format!("{}={}", key, value));
+        }
+        line
+    }
+
+    fn insert(&mut self, field: &Field, value: Value) {
+        if field.name() == "message" {
+            self.message = match value {
+                Value::String(s) => s,
+                other => other.to_string(),
+            };
+        } else {
+            self.fields.insert(field.name().to_string(), value);
+        }
+    }
+}
+
+impl Visit for FieldVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.insert(field, Value::from(value));
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.insert(field, Value::from(value));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.insert(field, Value::from(value));
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.insert(field, Value::from(value));
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.insert(field, Value::from(value));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.insert(field, Value::from(format!("{:?}", value)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing::{info, info_span, warn};
+    use tracing_subscriber::layer::SubscriberExt;
+
+    #[test]
+    fn test_run_records_go_to_run_file_and_shipping() {
+        let dir = std::env::temp_dir().join(format!("taskrun-run-logs-{}", std::process::id()));
+        let logs = Arc::new(
+            RunLogs::open(RunLogConfig {
+                dir: Some(dir.clone()),
+                max_bytes: 800,
+                retention: Duration::from_secs(3600),
+                ship_level: Some(Level::WARN),
+            })
+            .unwrap(),
+        );
+        let mut shipped = logs.ship("run-1").unwrap();
+        let subscriber = tracing_subscriber::registry().with(logs.layer());
+
+        tracing::subscriber::with_default(subscriber, || {
+            info!("outside any run");
+            let span = info_span!("run.execute", run_id = %"run-1", task_id = %"task-1");
+            let _entered = span.enter();
+            for attempt in 0..4 {
+                info!(attempt, "Starting attempt");
+            }
+            warn!(stderr = "rate limited", "Claude stderr");
+        });
+
+        let lines = |path: PathBuf| {
+            std::fs::read_to_string(path)
+                .unwrap()
+                .lines()
+                .map(|line| serde_json::from_str::<Value>(line).unwrap())
+                .collect::<Vec<_>>()
+        };
+        let current = lines(dir.join("run-1.jsonl"));
+        let rotated = lines(dir.join("run-1.1.jsonl"));
+        // The warning did not fit, so the four info records were rotated
+        assert_eq!(rotated.len(), 4);
+        assert_eq!(rotated[0]["message"], "Starting attempt");
+        assert_eq!(rotated[0]["fields"]["attempt"], 0);
+        assert_eq!(current.len(), 1);
+        let last = &current[0];
+        assert_eq!(last["level"], "WARN");
+        assert_eq!(last["run_id"], "run-1");
+        assert_eq!(last["fields"]["stderr"], "rate limited");
+
+        // Only the warning is shipped
+        let event = shipped.try_recv().unwrap();
+        assert_eq!(event.metadata["level"], "WARN");
+        assert_eq!(
+            event.metadata["message"],
+            "Claude stderr stderr=rate limited"
+        );
+        assert_eq!(event.task_id.as_str(), "task-1");
+        assert!(shipped.try_recv().is_err());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,616 @@
+//! Secrets injected into the Claude process.
+//!
+//! Agent definitions name the secrets their runs get, by environment
+//! variable:
+//!
+//! ```yaml
+//! # agents/release_notes.yaml
+//! secrets:
+//!   GITHUB_TOKEN: github-token
+//!   JIRA_API_KEY: jira/api#key
+//! ```
+//!
+//! Before each execution the worker resolves the named secrets through the
+//! provider chosen with `--secrets-provider`, and sets them in the Claude
+//! process's environment (and so in what it starts, tools included):
+//!
+//! | Provider | Secret `name` is |
+//! |----------|------------------|
+//! | `env` | The worker's `TASKRUN_SECRET_<NAME>` variable (uppercased, `-`, `/` and `.` as `_`) |
+//! | `file` | The file `<--secrets-dir>/<name>`, without its trailing newline |
+//! | `vault` | Field `value` (or `name#field`) of KV v2 secret `name` under `--vault-mount` |
+//! | `aws` | AWS Secrets Manager secret `name` (or JSON key `name#key`), through the `aws` CLI |
+//!
+//! Secrets are resolved again for every execution, so rotated values are
+//! picked up. A run whose secrets can't be resolved fails before Claude
+//! starts. The worker's own `TASKRUN_SECRET_*` variables and the providers'
+//! credentials (`VAULT_TOKEN`, `AWS_ACCESS_KEY_ID`, `AWS_SECRET_ACCESS_KEY`,
+//! `AWS_SESSION_TOKEN` and `AWS_PROFILE`) are never inherited by Claude, so
+//! an agent only sees the secrets it names.
+//!
+//! Resolved values are masked in the run's output, events, error messages
+//! and shipped log records. Output is masked as a stream: the end of each
+//! chunk that could start a value waits for the next chunk, so a value split
+//! across chunks is masked too. Events and records are masked one at a
+//! time. Values shorter than `MIN_REDACTED_LEN` are not masked, as they
+//! would mask ordinary text.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, RwLock};
+
+use async_trait::async_trait;
+use clap::ValueEnum;
+use serde_json::Value;
+use taskrun_claude_sdk::ClaudeExecutor;
+use taskrun_core::RunEvent;
+use thiserror::Error;
+use tokio::process::Command;
+use tracing::warn;
+
+use crate::config::Cli;
+use crate::output_filter::DEFAULT_MASK;
+
+/// Shortest secret value masked in run output.
+pub const MIN_REDACTED_LEN: usize = 4;
+
+/// Prefix of the worker's environment variables the `env` provider reads.
+const ENV_PREFIX: &str = "TASKRUN_SECRET_";
+
+/// Worker environment variables holding provider credentials.
+const CREDENTIAL_VARS: &[&str] = &[
+    "VAULT_TOKEN",
+    "AWS_ACCESS_KEY_ID",
+    "AWS_SECRET_ACCESS_KEY",
+    "AWS_SESSION_TOKEN",
+    "AWS_PROFILE",
+];
+
+/// Field of a Vault secret read when the name doesn't pick one.
+const DEFAULT_VAULT_FIELD: &str = "value";
+
+/// Secrets errors.
+#[derive(Debug, Error)]
+pub enum SecretsError {
+    #[error("Secret '{0}' not found")]
+    NotFound(String),
+
+    #[error("Invalid secret name '{0}'")]
+    InvalidName(String),
+
+    #[error("Failed to read secret '{name}': {reason}")]
+    Provider { name: String, reason: String },
+
+    #[error("--secrets-provider {provider} needs {setting}")]
+    MissingSetting {
+        provider: &'static str,
+        setting: &'static str,
+    },
+
+    #[error("Failed to set up the secrets provider: {0}")]
+    Setup(String),
+}
+
+/// Where secrets come from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum SecretsBackend {
+    /// The worker's own `TASKRUN_SECRET_*` environment variables.
+    #[default]
+    Env,
+    /// One file per secret in `--secrets-dir`.
+    File,
+    /// HashiCorp Vault's KV v2 engine.
+    Vault,
+    /// AWS Secrets Manager, through the `aws` CLI.
+    Aws,
+}
+
+/// Resolves secret names to their values.
+#[async_trait]
+pub trait SecretsProvider: Send + Sync + fmt::Debug {
+    /// The current value of secret `name`.
+    async fn get(&self, name: &str) -> Result<String, SecretsError>;
+}
+
+/// Secrets from `TASKRUN_SECRET_<NAME>` variables.
+#[derive(Debug, Default)]
+pub struct EnvProvider;
+
+#[async_trait]
+impl SecretsProvider for EnvProvider {
+    async fn get(&self, name: &str) -> Result<String, SecretsError> {
+        let var = env_var_name(name);
+        std::env::var(&var).map_err(|_| SecretsError::NotFound(name.to_string()))
+    }
+}
+
+/// `TASKRUN_SECRET_` followed by `name` uppercased, with `-`, `/` and `.`
+/// replaced by `_`.
+fn env_var_name(name: &str) -> String {
+    let suffix: String = name
+        .chars()
+        .map(|c| match c {
+            '-' | '/' | '.' => '_',
+            c => c.to_ascii_uppercase(),
+        })
+        .collect();
+    format!("{}{}", ENV_PREFIX, suffix)
+}
+
+/// Secrets from files in a directory.
+#[derive(Debug)]
+pub struct FileProvider {
+    dir: PathBuf,
+}
+
+impl FileProvider {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+}
+
+#[async_trait]
+impl SecretsProvider for FileProvider {
+    async fn get(&self, name: &str) -> Result<String, SecretsError> {
+        // Names stay inside the directory
+        let relative = Path::new(name);
+        if name.is_empty()
+            || !relative
+                .components()
+                .all(|c| matches!(c, Component::Normal(_)))
+        {
+            return Err(SecretsError::InvalidName(name.to_string()));
+        }
+        match tokio::fs::read_to_string(self.dir.join(relative)).await {
+            Ok(value) => Ok(value.trim_end_matches(['\r', '\n']).to_string()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                Err(SecretsError::NotFound(name.to_string()))
+            }
+            Err(e) => Err(provider_error(name, e)),
+        }
+    }
+}
+
+/// Secrets from Vault's KV v2 engine.
+#[derive(Debug)]
+pub struct VaultProvider {
+    addr: String,
+    token: String,
+    mount: String,
+    client: reqwest::Client,
+}
+
+impl VaultProvider {
+    /// Read secrets from `addr` with `token`, trusting `ca_cert` (PEM) if
+    /// given.
+    pub fn new(
+        addr: &str,
+        token: &str,
+        mount: &str,
+        ca_cert: Option<&Path>,
+    ) -> Result<Self, SecretsError> {
+        let mut client = reqwest::Client::builder();
+        if let Some(path) = ca_cert {
+            let pem = std::fs::read(path)
+                .map_err(|e| SecretsError::Setup(format!("{}: {}", path.display(), e)))?;
+            let cert = reqwest::Certificate::from_pem(&pem)
+                .map_err(|e| SecretsError::Setup(format!("{}: {}", path.display(), e)))?;
+            client = client.add_root_certificate(cert);
+        }
+        Ok(Self {
+            addr: addr.trim_end_matches('/').to_string(),
+            token: token.to_string(),
+            mount: mount.trim_matches('/').to_string(),
+            client: client
+                .build()
+                .map_err(|e| SecretsError::Setup(e.to_string()))?,
+        })
+    }
+}
+
+#[async_trait]
+impl SecretsProvider for VaultProvider {
+    async fn get(&self, name: &str) -> Result<String, SecretsError> {
+        let (path, field) = split_field(name);
+        let path = path.trim_matches('/');
+        if path.is_empty() {
+            return Err(SecretsError::InvalidName(name.to_string()));
+        }
+        let response = self
+            .client
+            .get(format!("{}/v1/{}/data/{}", self.addr, self.mount, path))
+            .header("X-Vault-Token", &self.token)
+            .send()
+            .await
+            .map_err(|e| provider_error(name, e))?;
+        let status = response.status();
+        if status == reqwest::StatusCode::NOT_FOUND {
+            return Err(SecretsError::NotFound(name.to_string()));
+        }
+        if !status.is_success() {
+            return Err(provider_error(name, format!("Vault answered {}", status)));
+        }
+        let body: Value = response.json().await.map_err(|e| provider_error(name, e))?;
+        let field = field.unwrap_or(DEFAULT_VAULT_FIELD);
+        string_field(&body["data"]["data"], field)
+            .ok_or_else(|| SecretsError::NotFound(name.to_string()))
+    }
+}
+
+/// Secrets from AWS Secrets Manager, read with the `aws` CLI and its
+/// credentials.
+#[derive(Debug)]
+pub struct AwsProvider {
+    program: String,
+    region: Option<String>,
+}
+
+impl AwsProvider {
+    pub fn new(region: Option<String>) -> Self {
+        Self {
+            program: "aws".to_string(),
+            region,
+        }
+    }
+}
+
+#[async_trait]
+impl SecretsProvider for AwsProvider {
+    async fn get(&self, name: &str) -> Result<String, SecretsError> {
+        let (id, key) = split_field(name);
+        if id.is_empty() {
+            return Err(SecretsError::InvalidName(name.to_string()));
+        }
+        let mut cmd = Command::new(&self.program);
+        cmd.args(["secretsmanager", "get-secret-value", "--secret-id", id])
+            .args(["--query", "SecretString", "--output", "text"]);
+        if let Some(region) = &self.region {
+            cmd.arg("--region").arg(region);
+        }
+        let output = cmd.output().await.map_err(|e| provider_error(name, e))?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            if stderr.contains("ResourceNotFoundException") {
+                return Err(SecretsError::NotFound(name.to_string()));
+            }
+            return Err(provider_error(name, stderr.trim()));
+        }
+        let value = String::from_utf8_lossy(&output.stdout)
+            .trim_end_matches(['\r', '\n'])
+            .to_string();
+        match key {
+            None => Ok(value),
+            Some(key) => {
+                let json: Value = serde_json::from_str(&value)
+                    .map_err(|_| provider_error(name, "secret is not a JSON object"))?;
+                string_field(&json, key).ok_or_else(|| SecretsError::NotFound(name.to_string()))
+            }
+        }
+    }
+}
+
+/// `name#field` as `(name, Some(field))`.
+fn split_field(name: &str) -> (&str, Option<&str>) {
+    match name.split_once('#') {
+        Some((name, field)) => (name, Some(field)),
+        None => (name, None),
+    }
+}
+
+/// `object[key]` as a string, numbers and booleans included.
+fn string_field(object: &Value, key: &str) -> Option<String> {
+    match object.get(key)? {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+fn provider_error(name: &str, reason: impl fmt::Display) -> SecretsError {
+    SecretsError::Provider {
+        name: name.to_string(),
+        reason: reason.to_string(),
+    }
+}
+
+/// The worker's secrets provider.
+#[derive(Debug, Clone)]
+pub struct Secrets {
+    provider: Arc<dyn SecretsProvider>,
+}
+
+impl Default for Secrets {
+    fn default() -> Self {
+        Self::new(EnvProvider)
+    }
+}
+
+impl Secrets {
+    pub fn new(provider: impl SecretsProvider + 'static) -> Self {
+        Self {
+            provider: Arc::new(provider),
+        }
+    }
+
+    /// The provider chosen with `--secrets-provider` and its settings.
+    pub fn from_cli(cli: &Cli) -> Result<Self, SecretsError> {
+        let missing = |provider, setting| SecretsError::MissingSetting { provider, setting };
+        Ok(match cli.secrets_provider {
+            SecretsBackend::Env => Self::new(EnvProvider),
+            SecretsBackend::File => {
+                let dir = cli
+                    .secrets_dir
+                    .as_deref()
+                    .ok_or_else(|| missing("file", "--secrets-dir"))?;
+                Self::new(FileProvider::new(dir))
+            }
+            SecretsBackend::Vault => {
+                let addr = cli
+                    .vault_addr
+                    .as_deref()
+                    .ok_or_else(|| missing("vault", "--vault-addr"))?;
+                let token = cli
+                    .vault_token
+                    .as_deref()
+                    .ok_or_else(|| missing("vault", "--vault-token"))?;
+                Self::new(VaultProvider::new(
+                    addr,
+                    token,
+                    &cli.vault_mount,
+                    cli.vault_ca_cert.as_deref().map(Path::new),
+                )?)
+            }
+            SecretsBackend::Aws => Self::new(AwsProvider::new(cli.aws_region.clone())),
+        })
+    }
+
+    /// `executor` with `secrets` (environment variable to secret name) set in
+    /// its environment and the worker's secret variables left out, adding the
+    /// values to `redactor`.
+    pub async fn apply(
+        &self,
+        mut executor: ClaudeExecutor,
+        secrets: &BTreeMap<String, String>,
+        redactor: &Redactor,
+    ) -> Result<ClaudeExecutor, SecretsError> {
+        for (key, _) in std::env::vars_os() {
+            let key = key.to_string_lossy();
+            if is_worker_secret(&key) {
+                executor = executor.without_env(key);
+            }
+        }
+        for (var, value) in self.resolve(secrets, redactor).await? {
+            executor = executor.with_env(var, value);
+        }
+        Ok(executor)
+    }
+
+    /// Resolve `secrets` (environment variable to secret name), adding the
+    /// values to `redactor`.
+    pub async fn resolve(
+        &self,
+        secrets: &BTreeMap<String, String>,
+        redactor: &Redactor,
+    ) -> Result<Vec<(String, String)>, SecretsError> {
+        let mut env = Vec::with_capacity(secrets.len());
+        for (var, name) in secrets {
+            let value = self.provider.get(name).await?;
+            if value.len() < MIN_REDACTED_LEN {
+                warn!(secret = %name, "Secret is too short to be masked in run output");
+            }
+            redactor.add(&value);
+            env.push((var.clone(), value));
+        }
+        Ok(env)
+    }
+}
+
+/// Whether the worker's environment variable `key` holds a secret or a
+/// provider credential, which Claude doesn't inherit.
+fn is_worker_secret(key: &str) -> bool {
+    key.starts_with(ENV_PREFIX) || CREDENTIAL_VARS.contains(&key)
+}
+
+/// Masks a run's secret values. Clones share the values, so a handle taken
+/// before the secrets are resolved masks them too.
+#[derive(Debug, Clone, Default)]
+pub struct Redactor {
+    values: Arc<RwLock<Vec<String>>>,
+}
+
+impl Redactor {
+    /// Mask `value` from now on.
+    pub fn add(&self, value: &str) {
+        if value.len() < MIN_REDACTED_LEN {
+            return;
+        }
+        let mut values = self.values.write().unwrap_or_else(|e| e.into_inner());
+        if !values.iter().any(|v| v == value) {
+            values.push(value.to_string());
+            // Longest first, so a secret containing another is masked whole
+            values.sort_by_key(|v| std::cmp::Reverse(v.len()));
+        }
+    }
+
+    /// A masker for a stream of output chunks.
+    pub fn chunks(&self) -> ChunkRedactor {
+        ChunkRedactor {
+            redactor: self.clone(),
+            pending: String::new(),
+        }
+    }
+
+    /// `text` with the secret values masked.
+    pub fn redact(&self, text: &str) -> String {
+        let values = self.values.read().unwrap_or_else(|e| e.into_inner());
+        let mut text = text.to_string();
+        for value in values.iter() {
+            if text.contains(value.as_str()) {
+                text = text.replace(value.as_str(), DEFAULT_MASK);
+            }
+        }
+        text
+    }
+
+    /// Mask the secret values in `event`'s metadata.
+    pub fn redact_event(&self, mut event: RunEvent) -> RunEvent {
+        if self.is_empty() {
+            return event;
+        }
+        for value in event.metadata.values_mut() {
+            *value = self.redact(value);
+        }
+        event
+    }
+
+    fn is_empty(&self) -> bool {
+        self.values
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .is_empty()
+    }
+}
+
+/// Masks secret values in a stream of chunks, including values split
+/// across chunks.
+#[derive(Debug)]
+pub struct ChunkRedactor {
+    redactor: Redactor,
+    /// Text received but not yet sent, shorter than the longest value
+    /// unless a value runs through it.
+    pending: String,
+}
+
+impl ChunkRedactor {
+    /// The masked text that can be sent once `chunk` arrives. Text that
+    /// could start a value is held back until the next chunk.
+    pub fn push(&mut self, chunk: &str) -> String {
+        self.pending.push_str(chunk);
+        let values = self
+            .redactor
+            .values
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone();
+        let Some(longest) = values.first().map(String::len) else {
+            return std::mem::take(&mut self.pending);
+        };
+
+        let mut end = self.pending.len().saturating_sub(longest - 1);
+        while !self.pending.is_char_boundary(end) {
+            end -= 1;
+        }
+        // Send whole any value running through the cut
+        while let Some(value_end) = values
+            .iter()
+            .flat_map(|v| self.pending.match_indices(v.as_str()))
+            .filter(|(start, v)| *start < end && start + v.len() > end)
+            .map(|(start, v)| start + v.len())
+            .max()
+        {
+            end = value_end;
+        }
+
+        let held = self.pending.split_off(end);
+        let ready = std::mem::replace(&mut self.pending, held);
+        self.redactor.redact(&ready)
+    }
+
+    /// The masked text held back, once the stream ends.
+    pub fn finish(&mut self) -> String {
+        self.redactor.redact(&std::mem::take(&mut self.pending))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use taskrun_core::{RunEventType, RunId, TaskId};
+
+    #[tokio::test]
+    async fn test_file_secrets_are_injected_and_masked() {
+        let dir = std::env::temp_dir().join(format!("taskrun-secrets-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("jira")).unwrap();
+        std::fs::write(dir.join("github-token"), "ghp_abc123def456\n").unwrap();
+        std::fs::write(dir.join("jira").join("api"), "jira-secret-key").unwrap();
+        let secrets = Secrets::new(FileProvider::new(&dir));
+        let wanted: BTreeMap<String, String> = [
+            ("GITHUB_TOKEN".to_string(), "github-token".to_string()),
+            ("JIRA_API_KEY".to_string(), "jira/api".to_string()),
+        ]
+        .into();
+
+        let redactor = Redactor::default();
+        let env = secrets.resolve(&wanted, &redactor).await.unwrap();
+        assert_eq!(
+            env,
+            [
+                ("GITHUB_TOKEN".to_string(), "ghp_abc123def456".to_string()),
+                ("JIRA_API_KEY".to_string(), "jira-secret-key".to_string()),
+            ]
+        );
+        assert_eq!(
+            redactor.redact("token ghp_abc123def456 and jira-secret-key"),
+            "token [REDACTED] and [REDACTED]"
+        );
+        let event = RunEvent::new(
+            RunId::new("r"),
+            TaskId::new("t"),
+            RunEventType::ToolRequested,
+            [("input".to_string(), "curl -H ghp_abc123def456".to_string())].into(),
+        );
+        assert_eq!(
+            redactor.redact_event(event).metadata["input"],
+            "curl -H [REDACTED]"
+        );
+
+        let missing: BTreeMap<String, String> = [("X".to_string(), "nope".to_string())].into();
+        assert!(matches!(
+            secrets.resolve(&missing, &redactor).await,
+            Err(SecretsError::NotFound(_))
+        ));
+        let escaping: BTreeMap<String, String> =
+            [("X".to_string(), "../etc/passwd".to_string())].into();
+        assert!(matches!(
+            secrets.resolve(&escaping, &redactor).await,
+            Err(SecretsError::InvalidName(_))
+        ));
+        assert_eq!(env_var_name("jira/api-key"), "TASKRUN_SECRET_JIRA_API_KEY");
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_values_split_across_chunks_are_masked() {
+        let redactor = Redactor::default();
+        redactor.add("ghp_abc123def456");
+        redactor.add("short-ü-key");
+
+        let mut chunks = redactor.chunks();
+        let mut output = String::new();
+        for chunk in [
+            "token gh",
+            "p_abc123",
+            "def456 and sho",
+            "rt-ü",
+            "-key!",
+            " done",
+        ] {
+            let sent = chunks.push(chunk);
+            assert!(!sent.contains("ghp_") && !sent.contains("-ü"), "{sent}");
+            output.push_str(&sent);
+        }
+        output.push_str(&chunks.finish());
+        assert_eq!(output, "token [REDACTED] and [REDACTED]! done");
+
+        // Without values nothing is held back
+        assert_eq!(Redactor::default().chunks().push("text"), "text");
+
+        for var in ["AWS_SECRET_ACCESS_KEY", "VAULT_TOKEN", "TASKRUN_SECRET_X"] {
+            assert!(is_worker_secret(var), "{var}");
+        }
+        assert!(!is_worker_secret("AWS_REGION"));
+    }
+}
@@ -0,0 +1,127 @@
+//! Graceful shutdown on SIGTERM/SIGINT (headless and JSON modes).
+//!
+//! The first signal starts a drain: the worker heartbeats DRAINING, refuses
+//! new assignments and waits up to `--shutdown-timeout` seconds for its
+//! active runs to finish. Runs still going at the deadline are stopped and
+//! reported FAILED with `WORKER_LOST`, then the stream is closed and the
+//! worker exits. A second signal skips the wait.
+
+use tokio::sync::watch;
+use tracing::{info, warn};
+
+/// How far a shutdown has gone.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Shutdown {
+    /// No shutdown requested.
+    Running,
+    /// Wait for active runs, up to the shutdown timeout.
+    Drain,
+    /// Stop active runs now.
+    Now,
+}
+
+/// Listen for shutdown signals. Must be called within a Tokio runtime.
+pub fn watch_signals() -> watch::Receiver<Shutdown> {
+    let (tx, rx) = watch::channel(Shutdown::Running);
+    tokio::spawn(async move {
+        let mut signals = Signals::new();
+        signals.recv().await;
+        info!("Shutdown requested, draining active runs (signal again to stop them now)");
+        let _ = tx.send(Shutdown::Drain);
+        signals.recv().await;
+        warn!("Shutdown requested again, stopping active runs");
+        let _ = tx.send(Shutdown::Now);
+        // Keep the sender, so waiters see the final state rather than a close
+        std::future::pending::<()>().await;
+    });
+    rx
+}
+
+/// Resolve once the shutdown has reached `stage`. Never resolves if nothing
+/// can request a shutdown any more.
+pub async fn reached(shutdown: &mut watch::Receiver<Shutdown>, stage: Shutdown) {
+    if shutdown
+        .wait_for(|current| *current >= stage)
+        .await
+        .is_err()
+    {
+        std::future::pending::<()>().await;
+    }
+}
+
+#[cfg(unix)]
+struct Signals {
+    terminate: Option<tokio::signal::unix::Signal>,
+    interrupt: Option<tokio::signal::unix::Signal>,
+}
+
+#[cfg(unix)]
+impl Signals {
+    fn new() -> Self {
+        use tokio::signal::unix::{signal, SignalKind};
+        let listen = |kind: SignalKind, name: &str| match signal(kind) {
+            Ok(signals) => Some(signals),
+            Err(e) => {
+                warn!(error = %e, "Failed to listen for {}", name);
+                None
+            }
+        };
+        Self {
+            terminate: listen(SignalKind::terminate(), "SIGTERM"),
+            interrupt: listen(SignalKind::interrupt(), "SIGINT"),
+        }
+    }
+
+    async fn recv(&mut self) {
+        async fn next(signals: &mut Option<tokio::signal::unix::Signal>) {
+            if let Some(signals) = signals {
+                if signals.recv().await.is_some() {
+                    return;
+                }
+            }
+            std::future::pending().await
+        }
+        tokio::select! {
+            _ = next(&mut self.terminate) => {}
+            _ = next(&mut self.interrupt) => {}
+        }
+    }
+}
+
+#[cfg(not(unix))]
+struct Signals;
+
+#[cfg(not(unix))]
+impl Signals {
+    fn new() -> Self {
+        Self
+    }
+
+    async fn recv(&mut self) {
+        if tokio::signal::ctrl_c().await.is_err() {
+            std::future::pending::<()>().await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_reached_waits_for_stage() {
+        let (tx, mut rx) = watch::channel(Shutdown::Running);
+        tx.send(Shutdown::Drain).unwrap();
+        reached(&mut rx, Shutdown::Drain).await;
+
+        let now = tokio::time::timeout(
+            std::time::Duration::from_millis(20),
+            reached(&mut rx, Shutdown::Now),
+        );
+        assert!(now.await.is_err());
+
+        tx.send(Shutdown::Now).unwrap();
+        reached(&mut rx, Shutdown::Drain).await;
+        reached(&mut rx, Shutdown::Now).await;
+    }
+}
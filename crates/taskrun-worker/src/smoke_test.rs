@@ -0,0 +1,232 @@
+//! Startup smoke test of the advertised agents.
+//!
+//! With `--smoke-test`, the worker runs a tiny canned prompt through each
+//! agent before it connects. The prompt runs with one agentic turn, tool
+//! uses denied and a timeout, so a test costs a single short completion.
+//! Agents that fail are advertised as degraded in `WorkerHello`; the control
+//! plane does not schedule them. Degraded agents are tested again every
+//! `--smoke-test-retry-secs` and a `WorkerUpdate` marks them ready once they
+//! pass. Agents that appear later (from `--capabilities-file`) are tested
+//! when they appear.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use taskrun_claude_sdk::{ClaudeExecutor, ClaudeMessage, DenyAllHandler, PermissionMode};
+use thiserror::Error;
+use tokio::sync::watch;
+use tracing::{info, warn};
+
+use crate::capabilities::Capabilities;
+use crate::config::Config;
+
+/// Default canned prompt.
+pub const DEFAULT_PROMPT: &str = "Reply with the single word: ready";
+
+/// Why an agent failed its smoke test.
+#[derive(Debug, Error)]
+pub enum SmokeTestError {
+    #[error("no answer within {0}s")]
+    Timeout(u64),
+
+    #[error("{0}")]
+    Execution(#[from] taskrun_claude_sdk::SdkError),
+
+    #[error("Claude reported an error: {0}")]
+    Failed(String),
+
+    #[error("no result received")]
+    NoResult,
+}
+
+/// Smoke test settings.
+#[derive(Debug, Clone)]
+pub struct SmokeTestConfig {
+    pub prompt: String,
+    pub timeout: Duration,
+    /// How often degraded agents are tested again.
+    pub retry_interval: Duration,
+}
+
+/// Run the canned prompt through `agent_name`.
+pub async fn run(
+    config: &Config,
+    smoke: &SmokeTestConfig,
+    agent_name: &str,
+) -> Result<(), SmokeTestError> {
+    let agent = config.agent(agent_name);
+    let mut executor = ClaudeExecutor::new(&config.claude_path)
+        .with_resource_limits(config.resource_limits(agent_name))
+        .with_permission_mode(PermissionMode::Default)
+        .with_permission_prompts(true)
+        .with_max_turns(1);
+    if !agent.model_name.is_empty() {
+        executor = executor.with_model(&agent.model_name);
+    }
+    if let Some(sandbox) = config.sandbox(agent_name) {
+        executor = executor.with_sandbox(sandbox);
+    }
+    let (handler, mut messages) = DenyAllHandler::new("Tools are not available in a smoke test");
+
+    let test = async {
+        executor
+            .execute(Path::new("."), &smoke.prompt, Arc::new(handler))
+            .await?;
+        // The handler is dropped once Claude's output is read, closing the channel
+        let mut outcome = Err(SmokeTestError::NoResult);
+        while let Some(message) = messages.recv().await {
+            if let ClaudeMessage::Result {
+                is_error,
+                error,
+                subtype,
+                ..
+            } = message
+            {
+                outcome = match is_error {
+                    Some(true) => Err(SmokeTestError::Failed(
+                        error
+                            .or(subtype)
+                            .unwrap_or_else(|| "unknown error".to_string()),
+                    )),
+                    _ => Ok(()),
+                };
+            }
+        }
+        outcome
+    };
+
+    info!(agent = %agent_name, "Running agent smoke test");
+    match tokio::time::timeout(smoke.timeout, test).await {
+        Ok(result) => result,
+        Err(_) => Err(SmokeTestError::Timeout(smoke.timeout.as_secs())),
+    }
+}
+
+/// Readiness of each agent tested so far: None if it passed, or why it
+/// failed.
+type Readiness = HashMap<String, Option<String>>;
+
+/// `capabilities` with each agent's readiness applied.
+fn with_readiness(mut capabilities: Capabilities, readiness: &Readiness) -> Capabilities {
+    for agent in &mut capabilities.agents {
+        agent.degraded = readiness.get(&agent.name).cloned().flatten();
+    }
+    capabilities
+}
+
+/// Test the agents in `capabilities` that `readiness` has no result for, or
+/// every degraded one too with `retry_degraded`.
+async fn test_agents(
+    config: &Config,
+    smoke: &SmokeTestConfig,
+    capabilities: &Capabilities,
+    readiness: &mut Readiness,
+    retry_degraded: bool,
+) {
+    for agent in &capabilities.agents {
+        let due = match readiness.get(&agent.name) {
+            None => true,
+            Some(degraded) => retry_degraded && degraded.is_some(),
+        };
+        if !due {
+            continue;
+        }
+        let result = match run(config, smoke, &agent.name).await {
+            Ok(()) => {
+                info!(agent = %agent.name, "Agent passed its smoke test");
+                None
+            }
+            Err(e) => {
+                warn!(agent = %agent.name, error = %e, "Agent failed its smoke test, advertising it as degraded");
+                Some(e.to_string())
+            }
+        };
+        readiness.insert(agent.name.clone(), result);
+    }
+}
+
+/// Smoke test the agents of `source` and keep testing new and degraded
+/// ones. The returned receiver carries `source` with each agent's readiness.
+pub async fn watch(
+    config: Arc<Config>,
+    smoke: SmokeTestConfig,
+    mut source: watch::Receiver<Capabilities>,
+) -> watch::Receiver<Capabilities> {
+    let mut readiness = Readiness::new();
+    let current = source.borrow_and_update().clone();
+    test_agents(&config, &smoke, &current, &mut readiness, false).await;
+    let (tx, rx) = watch::channel(with_readiness(current, &readiness));
+
+    tokio::spawn(async move {
+        let mut retry = tokio::time::interval(smoke.retry_interval);
+        retry.tick().await;
+        let mut source_open = true;
+        loop {
+            let retry_degraded = tokio::select! {
+                changed = source.changed(), if source_open => {
+                    if changed.is_err() {
+                        source_open = false;
+                        continue;
+                    }
+                    false
+                }
+                _ = retry.tick() => {
+                    if readiness.values().all(Option::is_none) {
+                        continue;
+                    }
+                    true
+                }
+                _ = tx.closed() => return,
+            };
+            let current = source.borrow_and_update().clone();
+            test_agents(&config, &smoke, &current, &mut readiness, retry_degraded).await;
+            tx.send_if_modified(|advertised| {
+                let updated = with_readiness(current, &readiness);
+                let changed = *advertised != updated;
+                *advertised = updated;
+                changed
+            });
+        }
+    });
+    rx
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use taskrun_core::AgentSpec;
+
+    #[test]
+    fn test_failed_agents_are_advertised_degraded() {
+        let capabilities = Capabilities {
+            agents: vec![AgentSpec::new("general"), AgentSpec::new("reviewer")],
+            labels: HashMap::new(),
+            max_concurrent_runs: 1,
+        };
+        let readiness = Readiness::from([
+            ("general".to_string(), None),
+            (
+                "reviewer".to_string(),
+                Some("no answer within 60s".to_string()),
+            ),
+        ]);
+
+        let advertised = with_readiness(capabilities.clone(), &readiness);
+        assert!(advertised.agents[0].is_ready());
+        assert_eq!(
+            advertised.agents[1].degraded.as_deref(),
+            Some("no answer within 60s")
+        );
+
+        // Passing later sends the agents again
+        let readiness = Readiness::from([
+            ("general".to_string(), None),
+            ("reviewer".to_string(), None),
+        ]);
+        let recovered = with_readiness(capabilities, &readiness);
+        let update = recovered.update_since(&advertised).unwrap();
+        assert!(update.agents.unwrap().agents.iter().all(|a| !a.degraded));
+    }
+}
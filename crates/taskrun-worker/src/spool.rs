@@ -0,0 +1,327 @@
+//! Output spool (headless and JSON modes).
+//!
+//! Output chunks the control plane stream does not take, because it is
+//! disconnected or has not made room within `SEND_TIMEOUT`, are appended to a
+//! file per run in the spool directory (`--spool-dir`) instead of being
+//! dropped. Once a run has spooled chunks, the rest of its output goes to the
+//! spool too, so chunks reach the control plane in order.
+//!
+//! Each connection replays the spool as soon as it is up, with the chunks'
+//! original sequence numbers, and keeps replaying while runs spool. A run
+//! waits for its spooled output to be replayed before reporting its final
+//! status. The spool is bounded (`--spool-max-mb`); chunks past the bound are
+//! dropped with a warning. Spool files left by an earlier process in the
+//! same directory are replayed too.
+
+use std::collections::HashMap;
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use taskrun_proto::pb::run_client_message::Payload as ClientPayload;
+use taskrun_proto::pb::{RunClientMessage, RunOutputChunk};
+use thiserror::Error;
+use tokio::sync::{mpsc, Mutex, Notify};
+use tracing::{info, warn};
+
+/// How long a chunk waits for room in the stream before it is spooled.
+const SEND_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// How often replay retries when nothing new was spooled.
+const REPLAY_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Spool errors.
+#[derive(Debug, Error)]
+pub enum SpoolError {
+    #[error("Failed to access output spool '{path}': {source}")]
+    Io {
+        path: String,
+        source: std::io::Error,
+    },
+}
+
+/// A spooled chunk, one JSON line in its run's spool file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct SpooledChunk {
+    seq: u64,
+    content: String,
+    is_final: bool,
+    timestamp_ms: i64,
+}
+
+/// Bounded on-disk spool of output chunks, shared by the worker's
+/// connections.
+#[derive(Debug)]
+pub struct OutputSpool {
+    dir: PathBuf,
+    max_bytes: u64,
+    /// Bytes spooled per run; a run is in here while it has spooled chunks.
+    runs: Mutex<HashMap<String, u64>>,
+    /// Woken when a chunk is spooled.
+    spooled: Notify,
+    /// Woken when a run's spool has been replayed.
+    drained: Notify,
+}
+
+impl OutputSpool {
+    /// Open the spool in `dir`, creating it if needed. Spool files already
+    /// there are replayed on the next connection.
+    pub fn open(dir: impl AsRef<Path>, max_bytes: u64) -> Result<Self, SpoolError> {
+        let dir = dir.as_ref().to_path_buf();
+        std::fs::create_dir_all(&dir).map_err(|source| io_error(&dir, source))?;
+        let entries = std::fs::read_dir(&dir).map_err(|source| io_error(&dir, source))?;
+        let mut runs = HashMap::new();
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().is_some_and(|ext| ext == "jsonl") {
+                if let (Some(run_id), Ok(meta)) = (path.file_stem(), entry.metadata()) {
+                    runs.insert(run_id.to_string_lossy().into_owned(), meta.len());
+                }
+            }
+        }
+        if !runs.is_empty() {
+            info!(runs = runs.len(), dir = %dir.display(), "Found spooled output to replay");
+        }
+        Ok(Self {
+            dir,
+            max_bytes,
+            runs: Mutex::new(runs),
+            spooled: Notify::new(),
+            drained: Notify::new(),
+        })
+    }
+
+    /// Send `chunk` on `tx`, or spool it if the run is already spooling or
+    /// the stream does not take it in time.
+    pub async fn send(&self, tx: &mpsc::Sender<RunClientMessage>, chunk: RunOutputChunk) {
+        if self.runs.lock().await.contains_key(&chunk.run_id) {
+            self.append(chunk).await;
+            return;
+        }
+        match tokio::time::timeout(SEND_TIMEOUT, tx.reserve()).await {
+            Ok(Ok(permit)) => permit.send(output_message(chunk)),
+            Ok(Err(_)) => {
+                warn!(run_id = %chunk.run_id, seq = chunk.seq, "Stream closed, spooling output");
+                self.append(chunk).await;
+            }
+            Err(_) => {
+                warn!(run_id = %chunk.run_id, seq = chunk.seq, "Stream is slow, spooling output");
+                self.append(chunk).await;
+            }
+        }
+    }
+
+    /// Replay spooled chunks on `tx` until the stream closes.
+    pub async fn replay(&self, tx: mpsc::Sender<RunClientMessage>) {
+        loop {
+            let spooled = self.spooled.notified();
+            if !self.drain(&tx).await {
+                return;
+            }
+            tokio::select! {
+                _ = spooled => {}
+                _ = tokio::time::sleep(REPLAY_INTERVAL) => {}
+            }
+        }
+    }
+
+    /// Wait up to `timeout` for `run_id`'s spooled chunks to be replayed.
+    /// Returns whether none are left.
+    pub async fn wait_drained(&self, run_id: &str, timeout: Duration) -> bool {
+        let wait = async {
+            loop {
+                let drained = self.drained.notified();
+                if !self.runs.lock().await.contains_key(run_id) {
+                    return;
+                }
+                tokio::select! {
+                    _ = drained => {}
+                    _ = tokio::time::sleep(REPLAY_INTERVAL) => {}
+                }
+            }
+        };
+        tokio::time::timeout(timeout, wait).await.is_ok()
+    }
+
+    /// Append `chunk` to its run's spool file, unless the spool is full.
+    async fn append(&self, chunk: RunOutputChunk) {
+        let mut runs = self.runs.lock().await;
+        let spooled = SpooledChunk {
+            seq: chunk.seq,
+            content: chunk.content,
+            is_final: chunk.is_final,
+            timestamp_ms: chunk.timestamp_ms,
+        };
+        let mut line = match serde_json::to_string(&spooled) {
+            Ok(line) => line,
+            Err(e) => {
+                warn!(run_id = %chunk.run_id, error = %e, "Failed to spool output chunk");
+                return;
+            }
+        };
+        line.push('\n');
+
+        let total: u64 = runs.values().sum();
+        if total + line.len() as u64 > self.max_bytes {
+            warn!(run_id = %chunk.run_id, seq = spooled.seq, max_bytes = self.max_bytes, "Output spool is full, dropping chunk");
+            return;
+        }
+        let path = self.path(&chunk.run_id);
+        let written = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .and_then(|mut file| file.write_all(line.as_bytes()));
+        if let Err(e) = written {
+            warn!(run_id = %chunk.run_id, path = %path.display(), error = %e, "Failed to spool output chunk");
+            return;
+        }
+        *runs.entry(chunk.run_id).or_default() += line.len() as u64;
+        self.spooled.notify_waiters();
+    }
+
+    /// Send every spooled chunk on `tx`. Returns false if the stream closed.
+    async fn drain(&self, tx: &mpsc::Sender<RunClientMessage>) -> bool {
+        // Held throughout, so runs append behind what is being replayed
+        let mut runs = self.runs.lock().await;
+        let run_ids: Vec<String> = runs.keys().cloned().collect();
+        for run_id in run_ids {
+            let path = self.path(&run_id);
+            let chunks = match read_chunks(&path) {
+                Ok(chunks) => chunks,
+                Err(e) => {
+                    warn!(run_id = %run_id, path = %path.display(), error = %e, "Dropping unreadable output spool");
+                    Vec::new()
+                }
+            };
+            let count = chunks.len();
+            for (sent, chunk) in chunks.iter().enumerate() {
+                let message = output_message(RunOutputChunk {
+                    run_id: run_id.clone(),
+                    seq: chunk.seq,
+                    content: chunk.content.clone(),
+                    is_final: chunk.is_final,
+                    metadata: HashMap::new(),
+                    timestamp_ms: chunk.timestamp_ms,
+                });
+                if tx.send(message).await.is_err() {
+                    // Keep what was not sent for the next connection
+                    let bytes = rewrite_chunks(&path, &chunks[sent..]);
+                    runs.insert(run_id, bytes);
+                    return false;
+                }
+            }
+            let _ = std::fs::remove_file(&path);
+            runs.remove(&run_id);
+            info!(run_id = %run_id, chunks = count, "Replayed spooled output");
+        }
+        self.drained.notify_waiters();
+        true
+    }
+
+    fn path(&self, run_id: &str) -> PathBuf {
+        // Run IDs are UUIDs; keep anything else from leaving the directory
+        let name: String = run_id
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '-' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        self.dir.join(format!("{}.jsonl", name))
+    }
+}
+
+fn output_message(chunk: RunOutputChunk) -> RunClientMessage {
+    RunClientMessage {
+        payload: Some(ClientPayload::OutputChunk(chunk)),
+    }
+}
+
+fn read_chunks(path: &Path) -> std::io::Result<Vec<SpooledChunk>> {
+    let file = std::fs::File::open(path)?;
+    let mut chunks = Vec::new();
+    for line in std::io::BufReader::new(file).lines() {
+        let line = line?;
+        // A torn last line from a crash mid-write is skipped
+        if let Ok(chunk) = serde_json::from_str(&line) {
+            chunks.push(chunk);
+        }
+    }
+    Ok(chunks)
+}
+
+/// Replace the spool file at `path` with `chunks`, returning its size.
+fn rewrite_chunks(path: &Path, chunks: &[SpooledChunk]) -> u64 {
+    let contents: String = chunks
+        .iter()
+        .filter_map(|chunk| serde_json::to_string(chunk).ok())
+        .map(|line| line + "\n")
+        .collect();
+    if let Err(e) = std::fs::write(path, &contents) {
+        warn!(path = %path.display(), error = %e, "Failed to rewrite output spool");
+    }
+    contents.len() as u64
+}
+
+fn io_error(path: &Path, source: std::io::Error) -> SpoolError {
+    SpoolError::Io {
+        path: path.display().to_string(),
+        source,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunk(seq: u64) -> RunOutputChunk {
+        RunOutputChunk {
+            run_id: "run-1".to_string(),
+            seq,
+            content: format!("chunk {}", seq),
+            is_final: false,
+            metadata: HashMap::new(),
+            timestamp_ms: 0,
+        }
+    }
+
+    fn seq(message: RunClientMessage) -> u64 {
+        match message.payload {
+            Some(ClientPayload::OutputChunk(chunk)) => chunk.seq,
+            other => panic!("expected an output chunk, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_chunks_spooled_while_disconnected_are_replayed_in_order() {
+        let dir = std::env::temp_dir().join(format!("taskrun-spool-{}", std::process::id()));
+        let spool = OutputSpool::open(&dir, 1024 * 1024).unwrap();
+
+        // The stream is gone: chunks go to the spool
+        let (closed_tx, closed_rx) = mpsc::channel(4);
+        drop(closed_rx);
+        spool.send(&closed_tx, chunk(0)).await;
+        spool.send(&closed_tx, chunk(1)).await;
+
+        // Back on a new stream, later chunks queue behind the spooled ones
+        let (tx, mut rx) = mpsc::channel(4);
+        spool.send(&tx, chunk(2)).await;
+        assert!(rx.try_recv().is_err());
+
+        assert!(spool.drain(&tx).await);
+        let replayed: Vec<u64> = std::iter::from_fn(|| rx.try_recv().ok()).map(seq).collect();
+        assert_eq!(replayed, [0, 1, 2]);
+        assert!(spool.wait_drained("run-1", Duration::ZERO).await);
+
+        // Drained runs go straight to the stream again
+        spool.send(&tx, chunk(3)).await;
+        assert_eq!(seq(rx.try_recv().unwrap()), 3);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,85 @@
+//! Thinking (extended reasoning) in run output.
+//!
+//! With `--include-thinking`, Claude's reasoning is streamed into the run
+//! output (and so the chat transcript) between `<thinking>` tags, so readers
+//! can tell it apart from the answer. Redacted thinking is never included.
+
+use std::sync::Mutex;
+
+use taskrun_claude_sdk::{ContentDelta, ContentItem, StreamEvent};
+
+const OPEN_TAG: &str = "<thinking>\n";
+const CLOSE_TAG: &str = "\n</thinking>\n";
+
+/// Turns thinking blocks and stream events into output text.
+#[derive(Debug, Default)]
+pub struct ThinkingStream {
+    /// Index of the thinking block being streamed, if any.
+    open_block: Mutex<Option<usize>>,
+}
+
+impl ThinkingStream {
+    /// Output text for a complete thinking block.
+    pub fn block(thinking: &str) -> String {
+        format!("{OPEN_TAG}{thinking}{CLOSE_TAG}")
+    }
+
+    /// Output text for a stream event, if it is part of a thinking block.
+    pub fn on_event(&self, event: &StreamEvent) -> Option<String> {
+        let mut open_block = self.open_block.lock().unwrap();
+        match event {
+            StreamEvent::ContentBlockStart {
+                index,
+                content_block: ContentItem::Thinking { thinking, .. },
+            } => {
+                *open_block = Some(*index);
+                Some(format!("{OPEN_TAG}{thinking}"))
+            }
+            StreamEvent::ContentBlockDelta {
+                index,
+                delta: ContentDelta::ThinkingDelta { thinking },
+            } if *open_block == Some(*index) => Some(thinking.clone()),
+            StreamEvent::ContentBlockStop { index } if *open_block == Some(*index) => {
+                *open_block = None;
+                Some(CLOSE_TAG.to_string())
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_streamed_thinking_is_tagged() {
+        let stream = ThinkingStream::default();
+        let events = [
+            StreamEvent::ContentBlockStart {
+                index: 0,
+                content_block: ContentItem::Thinking {
+                    thinking: String::new(),
+                    signature: None,
+                },
+            },
+            StreamEvent::ContentBlockDelta {
+                index: 0,
+                delta: ContentDelta::ThinkingDelta {
+                    thinking: "2 + 2".to_string(),
+                },
+            },
+            StreamEvent::ContentBlockDelta {
+                index: 0,
+                delta: ContentDelta::SignatureDelta {
+                    signature: "sig".to_string(),
+                },
+            },
+            StreamEvent::ContentBlockStop { index: 0 },
+            StreamEvent::ContentBlockStop { index: 1 },
+        ];
+
+        let output: String = events.iter().filter_map(|e| stream.on_event(e)).collect();
+        assert_eq!(output, ThinkingStream::block("2 + 2"));
+    }
+}
@@ -1,6 +1,7 @@
 //! Worker TUI application and main event loop.
 
 use std::error::Error;
+use std::path::Path;
 use std::time::Duration;
 
 use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
@@ -11,10 +12,13 @@ use super::backend::run_worker_backend;
 use super::connection::ConnectionConfig;
 use super::event::{WorkerCommand, WorkerUiEvent};
 use super::render;
-use super::setup::{render_setup, SetupState};
+use super::setup::{render_readiness, render_setup, SetupState};
 use super::state::{
-    ConnectionState, DetailPane, LogLevel, RunInfo, WorkerConfig, WorkerUiState, WorkerView,
+    ConnectionState, DetailPane, LogLevel, RunInfo, RunStatus, ToolPrompt, WorkerConfig,
+    WorkerUiState, WorkerView,
 };
+use super::tool_prompts::ToolDecision;
+use crate::preflight::Preflight;
 
 /// Main entry point for the worker TUI.
 pub fn run_worker_tui(config: WorkerConfig) -> Result<(), Box<dyn Error>> {
@@ -35,6 +39,11 @@ fn run_app_with_setup(
     mut config: WorkerConfig,
     mut terminal: DefaultTerminal,
 ) -> Result<(), Box<dyn Error>> {
+    // A missing or logged-out Claude Code CLI would fail every run
+    if !wait_for_readiness(&config, &mut terminal)? {
+        return Ok(());
+    }
+
     // Setup phase - pre-fill based on config defaults
     let model_index = super::setup::MODEL_OPTIONS
         .iter()
@@ -81,6 +90,47 @@ fn run_app_with_setup(
     run_worker_app(config, terminal)
 }
 
+/// Show failed and warned pre-flight checks until a recheck passes.
+/// Warnings alone can be continued past.
+///
+/// Returns false if the user quit instead.
+fn wait_for_readiness(
+    config: &WorkerConfig,
+    terminal: &mut DefaultTerminal,
+) -> Result<bool, Box<dyn Error>> {
+    loop {
+        let report = Preflight {
+            claude_path: &config.claude_path,
+            skip_claude_check: config.skip_claude_check,
+            sandbox: None,
+            working_dir: Path::new(&config.working_dir),
+            client_cert: Some(Path::new(&config.client_cert_path)),
+            agents: vec![(config.agent_name.clone(), false)],
+        }
+        .run();
+        if report.problems().next().is_none() {
+            return Ok(true);
+        }
+        loop {
+            terminal.draw(|frame| render_readiness(frame, &report))?;
+
+            if event::poll(Duration::from_millis(50))? {
+                if let Event::Key(key) = event::read()? {
+                    if key.kind != KeyEventKind::Press {
+                        continue;
+                    }
+                    match key.code {
+                        KeyCode::Esc | KeyCode::Char('q') => return Ok(false),
+                        KeyCode::Enter if report.is_ready() => return Ok(true),
+                        KeyCode::Enter | KeyCode::Char('r') => break,
+                        _ => {}
+                    }
+                }
+            }
+        }
+    }
+}
+
 /// Run the worker app after setup is complete.
 fn run_worker_app(
     config: WorkerConfig,
@@ -226,27 +276,46 @@ impl WorkerApp {
                 run_id,
                 event_type,
                 details,
+                artifacts,
+                milestone,
+                result,
+                payload,
             } => {
-                // Add event to the run
+                // Add event to the run; its files attach to the next assistant message
                 if let Some(run) = self
                     .state
                     .active_runs
                     .iter_mut()
                     .find(|r| r.run_id == run_id)
                 {
-                    run.add_event(event_type, details);
+                    run.add_event(event_type, details, payload.map(|p| *p));
+                    run.pending_attachments.extend(artifacts);
+                    if let Some(milestone) = milestone {
+                        milestone.record(&mut run.milestones);
+                    }
+                    if result.is_some() {
+                        run.result = result;
+                    }
                 } else if let Some(run) = self
                     .state
                     .completed_runs
                     .iter_mut()
                     .find(|r| r.run_id == run_id)
                 {
-                    run.add_event(event_type, details);
+                    run.add_event(event_type, details, payload.map(|p| *p));
+                    run.pending_attachments.extend(artifacts);
+                    if let Some(milestone) = milestone {
+                        milestone.record(&mut run.milestones);
+                    }
+                    if result.is_some() {
+                        run.result = result;
+                    }
                 }
             }
             WorkerUiEvent::RunCompleted {
                 run_id,
                 success,
+                cancelled,
                 error_message,
             } => {
                 // Finalize streaming output as assistant message before completing
@@ -258,7 +327,16 @@ impl WorkerApp {
                 {
                     run.finalize_output();
                 }
-                self.state.complete_run(&run_id, success);
+                let status = if success {
+                    RunStatus::Completed
+                } else if cancelled {
+                    RunStatus::Cancelled
+                } else {
+                    RunStatus::Failed
+                };
+                self.state.complete_run(&run_id, status);
+                // Prompts of a finished run can no longer be answered
+                self.state.tool_prompts.retain(|p| p.run_id != run_id);
                 if let Some(error) = error_message {
                     self.state
                         .add_log(LogLevel::Error, format!("Run {} failed: {}", run_id, error));
@@ -268,6 +346,20 @@ impl WorkerApp {
             WorkerUiEvent::LogMessage { level, message } => {
                 self.state.add_log(level, message);
             }
+            WorkerUiEvent::TaskValidated { messages } => {
+                self.state.new_run_validation = messages;
+            }
+            WorkerUiEvent::GroupUpdated { progress } => {
+                match self
+                    .state
+                    .groups
+                    .iter_mut()
+                    .find(|g| g.label == progress.label)
+                {
+                    Some(group) => *group = progress,
+                    None => self.state.groups.push(progress),
+                }
+            }
             WorkerUiEvent::StatsUpdated { active_runs: _ } => {
                 // Update active run count - already tracked via RunStarted/RunCompleted
                 // This is a fallback for any discrepancy
@@ -310,6 +402,28 @@ impl WorkerApp {
                 {
                     run.finalize_output();
                 }
+                self.state.tool_prompts.retain(|p| p.run_id != run_id);
+            }
+            WorkerUiEvent::ToolApprovalRequested {
+                prompt_id,
+                run_id,
+                tool_name,
+                input,
+            } => {
+                self.state.add_log(
+                    LogLevel::Warn,
+                    format!(
+                        "Run {} asks to use {} - open it to answer",
+                        &run_id[..8.min(run_id.len())],
+                        tool_name
+                    ),
+                );
+                self.state.tool_prompts.push_back(ToolPrompt {
+                    prompt_id,
+                    run_id,
+                    tool_name,
+                    input,
+                });
             }
             WorkerUiEvent::UserMessageAdded { run_id, message } => {
                 // Add user message to the run's chat history
@@ -350,10 +464,23 @@ impl WorkerApp {
                     self.state.stats.failed_runs
                 )
             }
-            ConnectionState::Disconnected { retry_in } => {
+            ConnectionState::Disconnected {
+                retry_in: Some(retry_in),
+                attempt,
+            } => {
                 format!(
-                    "Disconnected - reconnecting in {}s (press 'r' to retry now)",
-                    retry_in.as_secs()
+                    "Disconnected - reconnecting in {:.1}s, attempt {} (press 'r' to retry now)",
+                    retry_in.as_secs_f64(),
+                    attempt
+                )
+            }
+            ConnectionState::Disconnected {
+                retry_in: None,
+                attempt,
+            } => {
+                format!(
+                    "Disconnected - gave up after {} attempts (press 'r' to retry)",
+                    attempt
                 )
             }
         });
@@ -373,6 +500,23 @@ impl WorkerApp {
             return self.handle_new_run_dialog_key(code);
         }
 
+        // Handle cancel run confirmation dialog
+        if self.state.cancel_confirm.is_some() {
+            return self.handle_cancel_confirm_key(code);
+        }
+
+        // Handle a tool prompt of the viewed run
+        if let Some((prompt, _)) = self.state.viewing_tool_prompt() {
+            let prompt_id = prompt.prompt_id.clone();
+            return self.handle_tool_prompt_key(prompt_id, code);
+        }
+
+        // Handle the event inspector
+        if self.state.inspecting_event.is_some() {
+            self.handle_inspector_key(code);
+            return false;
+        }
+
         // Handle detail view specially
         if self.state.current_view == WorkerView::RunDetail {
             return self.handle_detail_key(code);
@@ -385,12 +529,20 @@ impl WorkerApp {
             }
 
             // New run (in Runs view)
-            KeyCode::Char('n') => {
-                if self.state.current_view == WorkerView::Runs {
-                    self.state.show_new_run_dialog = true;
-                    self.state.new_run_prompt.clear();
-                    self.state.new_run_cursor = 0;
-                }
+            KeyCode::Char('n') if self.state.current_view == WorkerView::Runs => {
+                self.state.show_new_run_dialog = true;
+                self.state.new_run_prompt.clear();
+                self.state.new_run_cursor = 0;
+                // Agent, quotas and capacity don't depend on the prompt
+                self.state.new_run_validation.clear();
+                let _ = self.cmd_tx.blocking_send(WorkerCommand::ValidateTask {
+                    prompt: String::new(),
+                });
+            }
+
+            // Cancel the selected run (in Runs view)
+            KeyCode::Char('c') if self.state.current_view == WorkerView::Runs => {
+                self.state.cancel_confirm = running_run_id(self.state.get_selected_run());
             }
 
             // View switching with number keys
@@ -416,23 +568,17 @@ impl WorkerApp {
             }
 
             // Enter to select run (enter detail view)
-            KeyCode::Enter => {
-                if self.state.current_view == WorkerView::Runs {
-                    self.state.enter_run_detail();
-                }
+            KeyCode::Enter if self.state.current_view == WorkerView::Runs => {
+                self.state.enter_run_detail();
             }
 
             // Up/Down or j/k navigation
             KeyCode::Up | KeyCode::Char('k') => match self.state.current_view {
-                WorkerView::Runs => {
-                    if self.state.selected_run_index > 0 {
-                        self.state.selected_run_index -= 1;
-                    }
+                WorkerView::Runs if self.state.selected_run_index > 0 => {
+                    self.state.selected_run_index -= 1;
                 }
-                WorkerView::Logs => {
-                    if self.state.log_scroll_offset > 0 {
-                        self.state.log_scroll_offset -= 1;
-                    }
+                WorkerView::Logs if self.state.log_scroll_offset > 0 => {
+                    self.state.log_scroll_offset -= 1;
                 }
                 _ => {}
             },
@@ -452,6 +598,21 @@ impl WorkerApp {
                 _ => {}
             },
 
+            // Run limit (in Config view); applied without reconnecting
+            KeyCode::Char('+') | KeyCode::Char('=')
+                if self.state.current_view == WorkerView::Config =>
+            {
+                self.set_max_concurrent_runs(self.state.config.max_concurrent_runs + 1);
+            }
+            KeyCode::Char('-') if self.state.current_view == WorkerView::Config => {
+                self.set_max_concurrent_runs(
+                    self.state.config.max_concurrent_runs.saturating_sub(1),
+                );
+            }
+
+            // Absolute or relative timestamps
+            KeyCode::Char('T') => self.state.time_format.toggle_relative(),
+
             // Reconnect
             KeyCode::Char('r') => {
                 if matches!(
@@ -467,6 +628,18 @@ impl WorkerApp {
         false
     }
 
+    /// Change the run limit and tell the backend.
+    fn set_max_concurrent_runs(&mut self, max: u32) {
+        let max = max.max(1);
+        if max == self.state.config.max_concurrent_runs {
+            return;
+        }
+        self.state.config.max_concurrent_runs = max;
+        let _ = self
+            .cmd_tx
+            .blocking_send(WorkerCommand::SetMaxConcurrentRuns { max });
+    }
+
     /// Handle key press in quit confirmation dialog.
     fn handle_quit_confirm_key(&mut self, code: KeyCode) -> bool {
         match code {
@@ -483,6 +656,67 @@ impl WorkerApp {
         false
     }
 
+    /// Handle key press in cancel run confirmation dialog.
+    fn handle_cancel_confirm_key(&mut self, code: KeyCode) -> bool {
+        match code {
+            KeyCode::Char('y') | KeyCode::Char('Y') | KeyCode::Enter => {
+                if let Some(run_id) = self.state.cancel_confirm.take() {
+                    self.state.add_log(
+                        LogLevel::Info,
+                        format!("Cancelling run {}", &run_id[..8.min(run_id.len())]),
+                    );
+                    let _ = self
+                        .cmd_tx
+                        .blocking_send(WorkerCommand::CancelRun { run_id });
+                }
+            }
+            KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
+                self.state.cancel_confirm = None;
+            }
+            _ => {}
+        }
+        false
+    }
+
+    /// Handle key press in the event inspector.
+    fn handle_inspector_key(&mut self, code: KeyCode) {
+        let Some((_, scroll)) = self.state.inspecting_event.as_mut() else {
+            return;
+        };
+        match code {
+            KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q') => {
+                self.state.inspecting_event = None;
+            }
+            KeyCode::Up | KeyCode::Char('k') => *scroll = scroll.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => *scroll = scroll.saturating_add(1),
+            KeyCode::PageUp => *scroll = scroll.saturating_sub(20),
+            KeyCode::PageDown => *scroll = scroll.saturating_add(20),
+            KeyCode::Home | KeyCode::Char('g') => *scroll = 0,
+            _ => {}
+        }
+    }
+
+    /// Handle key press in the tool prompt dialog.
+    fn handle_tool_prompt_key(&mut self, prompt_id: String, code: KeyCode) -> bool {
+        let decision = match code {
+            KeyCode::Char('y') | KeyCode::Char('Y') => ToolDecision::Allow,
+            KeyCode::Char('n') | KeyCode::Char('N') => ToolDecision::Deny,
+            KeyCode::Char('a') | KeyCode::Char('A') => ToolDecision::AlwaysAllow,
+            // Answer later; the run keeps waiting
+            KeyCode::Esc => {
+                self.state.exit_run_detail();
+                return false;
+            }
+            _ => return false,
+        };
+        self.state.tool_prompts.retain(|p| p.prompt_id != prompt_id);
+        let _ = self.cmd_tx.blocking_send(WorkerCommand::ToolDecision {
+            prompt_id,
+            decision,
+        });
+        false
+    }
+
     /// Handle key press in new run dialog.
     fn handle_new_run_dialog_key(&mut self, code: KeyCode) -> bool {
         match code {
@@ -492,20 +726,21 @@ impl WorkerApp {
                 self.state.new_run_prompt.clear();
             }
             // Submit
-            KeyCode::Enter => {
-                if !self.state.new_run_prompt.is_empty() {
-                    let prompt = self.state.new_run_prompt.clone();
-                    self.state.show_new_run_dialog = false;
-                    self.state.new_run_prompt.clear();
-                    self.state.new_run_cursor = 0;
-
-                    // Send command to create task
-                    let _ = self
-                        .cmd_tx
-                        .blocking_send(WorkerCommand::CreateTask { prompt });
-                    self.state
-                        .add_log(LogLevel::Info, "Creating new task...".to_string());
-                }
+            KeyCode::Enter
+                if !self.state.new_run_prompt.is_empty()
+                    && !self.state.new_run_validation.iter().any(|m| m.error) =>
+            {
+                let prompt = self.state.new_run_prompt.clone();
+                self.state.show_new_run_dialog = false;
+                self.state.new_run_prompt.clear();
+                self.state.new_run_cursor = 0;
+
+                // Send command to create task
+                let _ = self
+                    .cmd_tx
+                    .blocking_send(WorkerCommand::CreateTask { prompt });
+                self.state
+                    .add_log(LogLevel::Info, "Creating new task...".to_string());
             }
             // Character input (unicode-safe)
             KeyCode::Char(c) => {
@@ -520,19 +755,17 @@ impl WorkerApp {
                 self.state.new_run_cursor += 1;
             }
             // Backspace (unicode-safe)
-            KeyCode::Backspace => {
-                if self.state.new_run_cursor > 0 {
-                    self.state.new_run_cursor -= 1;
-                    if let Some((byte_idx, ch)) = self
-                        .state
+            KeyCode::Backspace if self.state.new_run_cursor > 0 => {
+                self.state.new_run_cursor -= 1;
+                if let Some((byte_idx, ch)) = self
+                    .state
+                    .new_run_prompt
+                    .char_indices()
+                    .nth(self.state.new_run_cursor)
+                {
+                    self.state
                         .new_run_prompt
-                        .char_indices()
-                        .nth(self.state.new_run_cursor)
-                    {
-                        self.state
-                            .new_run_prompt
-                            .replace_range(byte_idx..byte_idx + ch.len_utf8(), "");
-                    }
+                        .replace_range(byte_idx..byte_idx + ch.len_utf8(), "");
                 }
             }
             // Delete (unicode-safe)
@@ -552,10 +785,8 @@ impl WorkerApp {
                 }
             }
             // Cursor movement (unicode-safe)
-            KeyCode::Left => {
-                if self.state.new_run_cursor > 0 {
-                    self.state.new_run_cursor -= 1;
-                }
+            KeyCode::Left if self.state.new_run_cursor > 0 => {
+                self.state.new_run_cursor -= 1;
             }
             KeyCode::Right => {
                 let char_count = self.state.new_run_prompt.chars().count();
@@ -586,54 +817,52 @@ impl WorkerApp {
                 }
 
                 // Enter sends the message if session exists, or queues it
-                KeyCode::Enter => {
-                    if !self.state.chat_input.is_empty() {
-                        let message = self.state.chat_input.clone();
+                KeyCode::Enter if !self.state.chat_input.is_empty() => {
+                    let message = self.state.chat_input.clone();
 
-                        // Check if we can send immediately (have session_id)
-                        let can_send = self
-                            .state
-                            .get_viewing_run()
-                            .map(|r| r.session_id.is_some())
-                            .unwrap_or(false);
-
-                        if can_send {
-                            // Get run info for sending
-                            if let Some(run) = self.state.get_viewing_run() {
-                                let run_id = run.run_id.clone();
-                                let session_id = run.session_id.clone().unwrap();
-
-                                // Add user message to chat immediately
-                                if let Some(run) = self.state.get_viewing_run_mut() {
-                                    run.add_user_message(message.clone());
-                                }
-
-                                // Clear input
-                                self.state.chat_input.clear();
-                                self.state.chat_input_cursor = 0;
-
-                                // Send the command
-                                let _ = self.cmd_tx.blocking_send(WorkerCommand::ContinueRun {
-                                    run_id,
-                                    session_id: session_id.clone(),
-                                    message,
-                                });
-                                self.state.add_log(
-                                    LogLevel::Info,
-                                    format!(
-                                        "Continuing session {}",
-                                        &session_id[..8.min(session_id.len())]
-                                    ),
-                                );
+                    // Check if we can send immediately (have session_id)
+                    let can_send = self
+                        .state
+                        .get_viewing_run()
+                        .map(|r| r.session_id.is_some())
+                        .unwrap_or(false);
+
+                    if can_send {
+                        // Get run info for sending
+                        if let Some(run) = self.state.get_viewing_run() {
+                            let run_id = run.run_id.clone();
+                            let session_id = run.session_id.clone().unwrap();
+
+                            // Add user message to chat immediately
+                            if let Some(run) = self.state.get_viewing_run_mut() {
+                                run.add_user_message(message.clone());
                             }
-                        } else {
-                            // No session yet - queue for later
-                            self.state.queue_chat_message();
+
+                            // Clear input
+                            self.state.chat_input.clear();
+                            self.state.chat_input_cursor = 0;
+
+                            // Send the command
+                            let _ = self.cmd_tx.blocking_send(WorkerCommand::ContinueRun {
+                                run_id,
+                                session_id: session_id.clone(),
+                                message,
+                            });
                             self.state.add_log(
-                                LogLevel::Warn,
-                                "No session ID yet - message queued".to_string(),
+                                LogLevel::Info,
+                                format!(
+                                    "Continuing session {}",
+                                    &session_id[..8.min(session_id.len())]
+                                ),
                             );
                         }
+                    } else {
+                        // No session yet - queue for later
+                        self.state.queue_chat_message();
+                        self.state.add_log(
+                            LogLevel::Warn,
+                            "No session ID yet - message queued".to_string(),
+                        );
                     }
                 }
 
@@ -657,19 +886,17 @@ impl WorkerApp {
                 }
 
                 // Backspace (unicode-safe)
-                KeyCode::Backspace => {
-                    if self.state.chat_input_cursor > 0 {
-                        self.state.chat_input_cursor -= 1;
-                        if let Some((byte_idx, ch)) = self
-                            .state
+                KeyCode::Backspace if self.state.chat_input_cursor > 0 => {
+                    self.state.chat_input_cursor -= 1;
+                    if let Some((byte_idx, ch)) = self
+                        .state
+                        .chat_input
+                        .char_indices()
+                        .nth(self.state.chat_input_cursor)
+                    {
+                        self.state
                             .chat_input
-                            .char_indices()
-                            .nth(self.state.chat_input_cursor)
-                        {
-                            self.state
-                                .chat_input
-                                .replace_range(byte_idx..byte_idx + ch.len_utf8(), "");
-                        }
+                            .replace_range(byte_idx..byte_idx + ch.len_utf8(), "");
                     }
                 }
 
@@ -691,10 +918,8 @@ impl WorkerApp {
                 }
 
                 // Cursor movement (unicode-safe)
-                KeyCode::Left => {
-                    if self.state.chat_input_cursor > 0 {
-                        self.state.chat_input_cursor -= 1;
-                    }
+                KeyCode::Left if self.state.chat_input_cursor > 0 => {
+                    self.state.chat_input_cursor -= 1;
                 }
                 KeyCode::Right => {
                     let char_count = self.state.chat_input.chars().count();
@@ -720,10 +945,8 @@ impl WorkerApp {
                     }
                 }
                 // Down arrow scrolls chat
-                KeyCode::Down => {
-                    if self.state.chat_scroll != usize::MAX {
-                        self.state.chat_scroll = self.state.chat_scroll.saturating_add(1);
-                    }
+                KeyCode::Down if self.state.chat_scroll != usize::MAX => {
+                    self.state.chat_scroll = self.state.chat_scroll.saturating_add(1);
                 }
 
                 // Page up/down for chat scroll
@@ -734,10 +957,8 @@ impl WorkerApp {
                         self.state.chat_scroll = self.state.chat_scroll.saturating_sub(10);
                     }
                 }
-                KeyCode::PageDown => {
-                    if self.state.chat_scroll != usize::MAX {
-                        self.state.chat_scroll = self.state.chat_scroll.saturating_add(10);
-                    }
+                KeyCode::PageDown if self.state.chat_scroll != usize::MAX => {
+                    self.state.chat_scroll = self.state.chat_scroll.saturating_add(10);
                 }
 
                 _ => {}
@@ -757,12 +978,58 @@ impl WorkerApp {
                 DetailPane::Output => {
                     self.state.detail_pane = DetailPane::Events;
                 }
-                DetailPane::Events => {
+                DetailPane::Events
+                    if self
+                        .state
+                        .get_viewing_run()
+                        .is_some_and(|run| !run.diffs.is_empty()) =>
+                {
+                    self.state.detail_pane = DetailPane::Diff;
+                }
+                DetailPane::Events | DetailPane::Diff => {
                     self.state.detail_pane = DetailPane::Output;
                     self.state.input_focused = true;
                 }
             },
 
+            // Open the latest attachment externally
+            KeyCode::Char('o') => {
+                let artifact = self
+                    .state
+                    .get_viewing_run()
+                    .and_then(|run| run.latest_attachment())
+                    .cloned();
+                match artifact {
+                    Some(artifact) => match taskrun_tui_components::open_external(&artifact.uri) {
+                        Ok(()) => self
+                            .state
+                            .add_log(LogLevel::Info, format!("Opened {}", artifact.name)),
+                        Err(e) => self.state.add_log(
+                            LogLevel::Error,
+                            format!("Failed to open {}: {}", artifact.name, e),
+                        ),
+                    },
+                    None => self
+                        .state
+                        .add_log(LogLevel::Warn, "No attachment to open".to_string()),
+                }
+            }
+
+            // Cancel the run
+            KeyCode::Char('c') => {
+                self.state.cancel_confirm = running_run_id(self.state.get_viewing_run());
+            }
+
+            // Switch between rendered markdown and raw text
+            KeyCode::Char('m') => {
+                self.state.raw_chat = !self.state.raw_chat;
+            }
+
+            // Enter inspects the selected event
+            KeyCode::Enter if self.state.detail_pane == DetailPane::Events => {
+                self.state.inspect_selected_event();
+            }
+
             // Enter focuses input (or 'i' like vim)
             KeyCode::Enter | KeyCode::Char('i') => {
                 self.state.detail_pane = DetailPane::Output;
@@ -780,11 +1047,8 @@ impl WorkerApp {
                             self.state.chat_scroll -= 1;
                         }
                     }
-                    DetailPane::Events => {
-                        if self.state.events_scroll > 0 {
-                            self.state.events_scroll -= 1;
-                        }
-                    }
+                    DetailPane::Events => self.state.move_event_selection(-1),
+                    DetailPane::Diff => self.state.scroll_diff(-1),
                 }
             }
 
@@ -795,9 +1059,8 @@ impl WorkerApp {
                         self.state.chat_scroll = self.state.chat_scroll.saturating_add(1);
                     }
                 }
-                DetailPane::Events => {
-                    self.state.events_scroll += 1;
-                }
+                DetailPane::Events => self.state.move_event_selection(1),
+                DetailPane::Diff => self.state.scroll_diff(1),
             },
 
             // Page up
@@ -809,9 +1072,8 @@ impl WorkerApp {
                         self.state.chat_scroll = self.state.chat_scroll.saturating_sub(20);
                     }
                 }
-                DetailPane::Events => {
-                    self.state.events_scroll = self.state.events_scroll.saturating_sub(20);
-                }
+                DetailPane::Events => self.state.move_event_selection(-20),
+                DetailPane::Diff => self.state.scroll_diff(-20),
             },
 
             // Page down
@@ -821,9 +1083,8 @@ impl WorkerApp {
                         self.state.chat_scroll = self.state.chat_scroll.saturating_add(20);
                     }
                 }
-                DetailPane::Events => {
-                    self.state.events_scroll += 20;
-                }
+                DetailPane::Events => self.state.move_event_selection(20),
+                DetailPane::Diff => self.state.scroll_diff(20),
             },
 
             // Home - scroll to top
@@ -832,7 +1093,10 @@ impl WorkerApp {
                     self.state.chat_scroll = 0;
                 }
                 DetailPane::Events => {
-                    self.state.events_scroll = 0;
+                    self.state.selected_event = 0;
+                }
+                DetailPane::Diff => {
+                    self.state.diff_scroll = 0;
                 }
             },
 
@@ -842,9 +1106,8 @@ impl WorkerApp {
                     DetailPane::Output => {
                         self.state.chat_scroll = usize::MAX; // Re-enable auto-scroll
                     }
-                    DetailPane::Events => {
-                        self.state.events_scroll = usize::MAX / 2; // Events doesn't use auto-scroll
-                    }
+                    DetailPane::Events => self.state.move_event_selection(isize::MAX),
+                    DetailPane::Diff => self.state.scroll_diff(isize::MAX),
                 }
             }
 
@@ -853,3 +1116,84 @@ impl WorkerApp {
         false
     }
 }
+
+/// ID of `run` if it can be cancelled, i.e. it is still running.
+fn running_run_id(run: Option<&RunInfo>) -> Option<String> {
+    run.filter(|r| r.status == RunStatus::Running)
+        .map(|r| r.run_id.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn app() -> (WorkerApp, mpsc::Receiver<WorkerCommand>) {
+        let (_ui_tx, ui_rx) = mpsc::channel(16);
+        let (cmd_tx, cmd_rx) = mpsc::channel(16);
+        let app = WorkerApp::new(WorkerConfig::for_tests(), "w-1".to_string(), ui_rx, cmd_tx);
+        (app, cmd_rx)
+    }
+
+    #[test]
+    fn test_runs_view_keys_do_nothing_elsewhere() {
+        let (mut app, mut cmd_rx) = app();
+        app.state.active_runs.push(RunInfo::new(
+            "run-1".into(),
+            "task-1".into(),
+            "general".into(),
+            "hi".into(),
+        ));
+        app.state.current_view = WorkerView::Status;
+
+        for code in [KeyCode::Enter, KeyCode::Char('n'), KeyCode::Char('c')] {
+            assert!(!app.handle_key(code));
+        }
+        assert_eq!(app.state.current_view, WorkerView::Status);
+        assert!(app.state.viewing_run_id.is_none());
+        assert!(!app.state.show_new_run_dialog);
+        assert!(app.state.cancel_confirm.is_none());
+        assert!(cmd_rx.try_recv().is_err());
+
+        app.state.current_view = WorkerView::Runs;
+        app.handle_key(KeyCode::Enter);
+        assert_eq!(app.state.current_view, WorkerView::RunDetail);
+        assert_eq!(app.state.viewing_run_id.as_deref(), Some("run-1"));
+    }
+
+    #[test]
+    fn test_cancel_asks_before_cancelling_the_selected_run() {
+        let (mut app, mut cmd_rx) = app();
+        app.state.active_runs.push(RunInfo::new(
+            "run-1".into(),
+            "task-1".into(),
+            "general".into(),
+            "hi".into(),
+        ));
+        app.state.current_view = WorkerView::Runs;
+
+        // Declining leaves the run alone
+        for decline in [KeyCode::Char('n'), KeyCode::Esc] {
+            app.handle_key(KeyCode::Char('c'));
+            assert_eq!(app.state.cancel_confirm.as_deref(), Some("run-1"));
+            // Other keys go to the dialog, not the view
+            app.handle_key(KeyCode::Char('1'));
+            assert_eq!(app.state.current_view, WorkerView::Runs);
+            app.handle_key(decline);
+            assert!(app.state.cancel_confirm.is_none());
+        }
+        assert!(cmd_rx.try_recv().is_err());
+
+        app.handle_key(KeyCode::Char('c'));
+        app.handle_key(KeyCode::Char('y'));
+        assert!(app.state.cancel_confirm.is_none());
+        assert!(matches!(
+            cmd_rx.try_recv(),
+            Ok(WorkerCommand::CancelRun { run_id }) if run_id == "run-1"
+        ));
+
+        // Finished runs can't be cancelled
+        app.state.active_runs[0].status = RunStatus::Completed;
+        app.handle_key(KeyCode::Char('c'));
+        assert!(app.state.cancel_confirm.is_none());
+    }
+}
@@ -2,6 +2,7 @@
 //!
 //! Manages the connection to the control plane with automatic reconnection.
 
+use std::collections::BTreeSet;
 use std::time::Duration;
 
 use tokio::sync::mpsc;
@@ -10,20 +11,16 @@ use tracing::{error, info};
 use super::connection::{ConnectionConfig, WorkerConnection};
 use super::event::{disconnected_event, WorkerCommand, WorkerUiEvent};
 use super::state::{ConnectionState, LogLevel, WorkerConfig};
+use super::tool_prompts::{ToolDecision, ToolPrompts};
+use crate::reconnect::{self, Backoff};
 
-/// Backoff configuration.
-const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
-const MAX_BACKOFF: Duration = Duration::from_secs(30);
-const BACKOFF_FACTOR: u32 = 2;
-
-/// Calculate the next backoff duration.
-fn next_backoff(current: Duration) -> Duration {
-    let next = current.saturating_mul(BACKOFF_FACTOR);
-    if next > MAX_BACKOFF {
-        MAX_BACKOFF
-    } else {
-        next
-    }
+/// Why the wait between connection attempts ended.
+enum Wakeup {
+    Quit,
+    /// The delay elapsed.
+    Retry,
+    /// A forced reconnect or a network change; the backoff starts over.
+    RetryNow,
 }
 
 /// Run the worker backend with automatic reconnection.
@@ -37,8 +34,17 @@ pub async fn run_worker_backend(
     ui_tx: mpsc::Sender<WorkerUiEvent>,
     mut cmd_rx: mpsc::Receiver<WorkerCommand>,
 ) {
-    let mut backoff = INITIAL_BACKOFF;
-    let conn_config = ConnectionConfig::from_with_id(&config, worker_id);
+    if let Some(dir) = &config.agents_dir {
+        config.agents.watch_dir(dir.into());
+    }
+
+    let mut backoff = Backoff::new(config.reconnect);
+    let mut conn_config = ConnectionConfig::from_with_id(&config, worker_id);
+    if !config.skip_permissions {
+        // Runs outlive connections, so prompts do too
+        conn_config.tool_prompts = Some(ToolPrompts::new(ui_tx.clone()));
+    }
+    let configured_addr = conn_config.control_plane_addr.clone();
 
     // Log initial configuration
     log_to_ui(
@@ -75,7 +81,13 @@ pub async fn run_worker_backend(
         let mut connection = WorkerConnection::new(conn_config.clone(), ui_tx.clone());
 
         // Try to connect and run (pass cmd_rx for handling ContinueRun commands)
-        match connection.connect_and_run(&mut cmd_rx).await {
+        let result = connection.connect_and_run(&mut cmd_rx).await;
+        if connection.was_connected() {
+            backoff.reset();
+        }
+        // Keep a limit changed while connected for the next connection
+        conn_config.max_concurrent_runs = connection.max_concurrent_runs();
+        match result {
             Ok(quit_requested) => {
                 if quit_requested {
                     info!("Quit requested, shutting down backend");
@@ -89,12 +101,32 @@ pub async fn run_worker_backend(
                     "Connection closed by server".to_string(),
                 )
                 .await;
+                conn_config.control_plane_addr = configured_addr.clone();
             }
-            Err(e) => {
-                let root_cause = crate::get_root_cause(&*e);
-                error!(error = %root_cause, "Connection failed");
-                log_to_ui(&ui_tx, LogLevel::Error, root_cause).await;
-            }
+            Err(e) => match crate::connection::leader_redirect(&*e) {
+                Some(leader) => {
+                    info!(leader = %leader, "Control plane is not the leader, reconnecting to the leader");
+                    log_to_ui(
+                        &ui_tx,
+                        LogLevel::Info,
+                        format!("Control plane is not the leader, redirected to {}", leader),
+                    )
+                    .await;
+                    // Follow at once, unless we were already redirected
+                    let redirected = conn_config.control_plane_addr != configured_addr;
+                    conn_config.control_plane_addr = leader;
+                    if !redirected {
+                        continue;
+                    }
+                }
+                None => {
+                    let root_cause = crate::get_root_cause(&*e);
+                    error!(error = %root_cause, "Connection failed");
+                    log_to_ui(&ui_tx, LogLevel::Error, root_cause).await;
+                    // The leader may be gone; start over from the configured address
+                    conn_config.control_plane_addr = configured_addr.clone();
+                }
+            },
         }
 
         // Check if quit was requested (commands may have been consumed by connection)
@@ -106,12 +138,34 @@ pub async fn run_worker_backend(
                 }
                 WorkerCommand::ForceReconnect => {
                     // Reset backoff on force reconnect
-                    backoff = INITIAL_BACKOFF;
+                    backoff.reset();
                     continue;
                 }
                 WorkerCommand::ContinueRun { .. } => {
                     // Ignore - can't continue while not connected
                 }
+                WorkerCommand::ValidateTask { .. } => {
+                    // Ignore - the dialog shows no findings
+                }
+                WorkerCommand::SetMaxConcurrentRuns { max } => {
+                    // Sent in the next WorkerHello
+                    conn_config.max_concurrent_runs = max;
+                }
+                WorkerCommand::CancelRun { .. } => {
+                    // Ignore - the control plane can't be told while not connected
+                    log_to_ui(
+                        &ui_tx,
+                        LogLevel::Warn,
+                        "Cannot cancel run: not connected".to_string(),
+                    )
+                    .await;
+                }
+                WorkerCommand::ToolDecision {
+                    prompt_id,
+                    decision,
+                } => {
+                    decide_tool_prompt(conn_config.tool_prompts.as_ref(), &prompt_id, decision);
+                }
                 WorkerCommand::CreateTask { .. } => {
                     // Ignore - can't create task while not connected
                     log_to_ui(
@@ -125,59 +179,104 @@ pub async fn run_worker_backend(
         }
 
         // Notify UI of disconnected state with retry time
-        let _ = ui_tx.send(disconnected_event(backoff)).await;
+        let delay = backoff.next_delay();
+        let _ = ui_tx
+            .send(disconnected_event(delay, backoff.failures()))
+            .await;
 
-        log_to_ui(
-            &ui_tx,
-            LogLevel::Info,
-            format!(
-                "Reconnecting in {}s... (press 'r' to retry now)",
-                backoff.as_secs()
+        let message = match delay {
+            Some(delay) => format!(
+                "Reconnecting in {:.1}s, attempt {}... (press 'r' to retry now)",
+                delay.as_secs_f64(),
+                backoff.failures()
             ),
-        )
-        .await;
+            None => format!(
+                "Gave up reconnecting after {} attempts (press 'r' to retry)",
+                backoff.failures()
+            ),
+        };
+        info!("{}", message);
+        log_to_ui(&ui_tx, LogLevel::Info, message).await;
 
         // Wait with backoff, but check for commands (Quit or ForceReconnect)
-        if wait_with_commands(&mut cmd_rx, backoff).await {
-            // Quit command received
-            info!("Received quit command during backoff, shutting down");
-            return;
+        let network = reconnect::interface_addresses();
+        match wait_with_commands(
+            &mut cmd_rx,
+            delay,
+            network,
+            &mut conn_config.max_concurrent_runs,
+            conn_config.tool_prompts.as_ref(),
+        )
+        .await
+        {
+            Wakeup::Quit => {
+                info!("Received quit command during backoff, shutting down");
+                return;
+            }
+            Wakeup::Retry => {}
+            Wakeup::RetryNow => backoff.reset(),
         }
-
-        // Increase backoff for next attempt
-        backoff = next_backoff(backoff);
     }
 }
 
-/// Wait for the specified duration, but respond to Quit and ForceReconnect commands.
+/// Wait for the specified duration (forever if None), but respond to Quit
+/// and ForceReconnect commands and to network changes.
 ///
-/// Returns `true` if Quit was received, `false` if timeout elapsed or ForceReconnect received.
+/// A new run limit is stored in `max_concurrent_runs` for the next connection.
+/// Tool decisions still reach `tool_prompts`, since runs keep executing.
 async fn wait_with_commands(
     cmd_rx: &mut mpsc::Receiver<WorkerCommand>,
-    duration: Duration,
-) -> bool {
-    let sleep = tokio::time::sleep(duration);
+    duration: Option<Duration>,
+    network: Option<BTreeSet<String>>,
+    max_concurrent_runs: &mut u32,
+    tool_prompts: Option<&ToolPrompts>,
+) -> Wakeup {
+    let sleep = async {
+        match duration {
+            Some(duration) => tokio::time::sleep(duration).await,
+            None => std::future::pending().await,
+        }
+    };
     tokio::pin!(sleep);
+    let network_change = reconnect::network_change(network);
+    tokio::pin!(network_change);
 
     loop {
         tokio::select! {
             _ = &mut sleep => {
                 // Timeout elapsed, continue with reconnect
-                return false;
+                return Wakeup::Retry;
+            }
+            _ = &mut network_change => {
+                info!("Network changed, reconnecting now");
+                return Wakeup::RetryNow;
             }
             Some(cmd) = cmd_rx.recv() => {
                 match cmd {
                     WorkerCommand::Quit => {
-                        return true;
+                        return Wakeup::Quit;
                     }
                     WorkerCommand::ForceReconnect => {
                         info!("Force reconnect requested");
-                        return false;
+                        return Wakeup::RetryNow;
                     }
                     WorkerCommand::ContinueRun { .. } => {
                         // Can't continue runs while disconnected, ignore
                         info!("Ignoring ContinueRun command while disconnected");
                     }
+                    WorkerCommand::ValidateTask { .. } => {
+                        // Can't validate tasks while disconnected, ignore
+                    }
+                    WorkerCommand::SetMaxConcurrentRuns { max } => {
+                        *max_concurrent_runs = max;
+                    }
+                    WorkerCommand::CancelRun { .. } => {
+                        // Can't cancel runs while disconnected, ignore
+                        info!("Ignoring CancelRun command while disconnected");
+                    }
+                    WorkerCommand::ToolDecision { prompt_id, decision } => {
+                        decide_tool_prompt(tool_prompts, &prompt_id, decision);
+                    }
                     WorkerCommand::CreateTask { .. } => {
                         // Can't create tasks while disconnected, ignore
                         info!("Ignoring CreateTask command while disconnected");
@@ -188,6 +287,13 @@ async fn wait_with_commands(
     }
 }
 
+/// Deliver a tool decision made while disconnected.
+fn decide_tool_prompt(tool_prompts: Option<&ToolPrompts>, prompt_id: &str, decision: ToolDecision) {
+    if let Some(prompts) = tool_prompts {
+        prompts.decide(prompt_id, decision);
+    }
+}
+
 /// Helper to log a message to the UI.
 async fn log_to_ui(ui_tx: &mpsc::Sender<WorkerUiEvent>, level: LogLevel, message: String) {
     let _ = ui_tx
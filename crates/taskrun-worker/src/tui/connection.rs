@@ -2,30 +2,48 @@
 //!
 //! Adapted from taskrun-worker to forward events to the UI.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 
-use tokio::sync::mpsc;
+use tokio::sync::{mpsc, oneshot};
 use tokio_stream::wrappers::ReceiverStream;
 use tokio_stream::StreamExt;
 use tonic::transport::{Certificate, Channel, ClientTlsConfig, Identity};
 use tracing::{error, info, warn};
 
-use taskrun_core::{AgentSpec, ModelBackend, RunEvent, RunId, TaskId, WorkerId, WorkerInfo};
+use taskrun_claude_sdk::ResourceLimits;
+use taskrun_core::{
+    Artifact, FailureReason, RunEvent, RunId, RunLimits, RunManifest, RunMilestone,
+    RunResultSummary, TaskId, TaskPriority, WorkerId, WorkerInfo,
+};
 use taskrun_proto::pb::run_client_message::Payload as ClientPayload;
 use taskrun_proto::pb::run_server_message::Payload as ServerPayload;
+use taskrun_proto::pb::task_watch_event::Event as WatchEvent;
 use taskrun_proto::pb::{
-    ChatMessage as ProtoChatMessage, ChatRole as ProtoChatRole, CreateTaskRequest, RunAssignment,
-    RunChatMessage, RunClientMessage, RunEvent as ProtoRunEvent, RunStatusUpdate, WorkerHeartbeat,
-    WorkerHello,
+    AssignmentAck, ChatMessage as ProtoChatMessage, ChatRole as ProtoChatRole, CreateTaskRequest,
+    GetGroupSummaryRequest, RunAssignment, RunChatMessage, RunClientMessage,
+    RunEvent as ProtoRunEvent, RunStatusUpdate, ValidationSeverity, WatchTasksRequest,
+    WorkerHeartbeat, WorkerHello, WorkerUpdate,
 };
 use taskrun_proto::{RunServiceClient, TaskServiceClient};
+use taskrun_tui_components::{format_duration, GroupProgress, ValidationMessage};
 
 use super::event::{WorkerCommand, WorkerUiEvent};
 use super::executor::ClaudeCodeExecutor;
 use super::state::{ConnectionState, LogLevel, WorkerConfig};
+use super::tool_payload::ToolPayload;
+use super::tool_prompts::ToolPrompts;
+use crate::agents::{AgentDefaults, AgentProfile, AgentRegistry};
+use crate::approvals::ApprovalPolicy;
+use crate::cancel::{Cancel, RunCancellations};
+use crate::connection::NO_SESSION;
+use crate::files;
+use crate::key_store::{self, KeyPassphrase};
+use crate::labels;
+use crate::output_filter::OutputFilters;
+use crate::secrets::{Redactor, Secrets};
 
 /// Internal config used by the connection.
 #[derive(Debug, Clone)]
@@ -35,16 +53,30 @@ pub struct ConnectionConfig {
     pub tls_ca_cert_path: String,
     pub tls_cert_path: String,
     pub tls_key_path: String,
+    pub tls_key_passphrase: Option<KeyPassphrase>,
     pub agent_name: String,
+    pub agents: AgentRegistry,
+    pub namespace: String,
+    pub labels: Vec<(String, String)>,
     pub model_provider: String,
     pub model_name: String,
     pub heartbeat_interval_secs: u64,
     pub max_concurrent_runs: u32,
+    pub interactive_slots: u32,
     pub allowed_tools: Option<Vec<String>>,
     pub denied_tools: Option<Vec<String>>,
     pub claude_path: String,
     pub working_dir: String,
     pub skip_permissions: bool,
+    pub approval_policy: Option<Arc<ApprovalPolicy>>,
+    pub output_filters: Option<Arc<OutputFilters>>,
+    pub include_thinking: bool,
+    pub progress_tool: bool,
+    pub resource_limits: ResourceLimits,
+    pub secrets: Secrets,
+    /// Asks the operator about tool uses (None = tool uses are not prompted
+    /// in the TUI).
+    pub tool_prompts: Option<ToolPrompts>,
 }
 
 impl ConnectionConfig {
@@ -57,19 +89,46 @@ impl ConnectionConfig {
             tls_ca_cert_path: config.ca_cert_path.clone(),
             tls_cert_path: config.client_cert_path.clone(),
             tls_key_path: config.client_key_path.clone(),
+            tls_key_passphrase: config.client_key_passphrase.clone(),
             agent_name: config.agent_name.clone(),
+            agents: config.agents.clone(),
+            namespace: config.namespace.clone(),
+            labels: config.labels.clone(),
             model_provider: provider,
             model_name: model,
             heartbeat_interval_secs: 30,
             max_concurrent_runs: config.max_concurrent_runs,
+            interactive_slots: config.interactive_slots,
             allowed_tools: config.allowed_tools.clone(),
             denied_tools: config.denied_tools.clone(),
-            claude_path: "claude".to_string(),
+            claude_path: config.claude_path.clone(),
             working_dir: config.working_dir.clone(),
             skip_permissions: config.skip_permissions,
+            approval_policy: config.approval_policy.clone(),
+            output_filters: config.output_filters.clone(),
+            resource_limits: config
+                .resource_limits
+                .as_ref()
+                .map(|limits| limits.for_agent(&config.agent_name))
+                .unwrap_or_default(),
+            secrets: config.secrets.clone(),
+            include_thinking: config.include_thinking,
+            progress_tool: config.progress_tool,
+            tool_prompts: None,
         }
     }
 
+    /// How to run `agent_name`: its definition, or the TUI's settings.
+    pub fn agent(&self, agent_name: &str) -> AgentProfile {
+        let defaults = AgentDefaults {
+            model_provider: self.model_provider.clone(),
+            model_name: self.model_name.clone(),
+            allowed_tools: self.allowed_tools.clone(),
+            denied_tools: self.denied_tools.clone(),
+        };
+        AgentProfile::resolve(agent_name, self.agents.get(agent_name).as_ref(), &defaults)
+    }
+
     /// Generate a new unique worker ID.
     pub fn generate_worker_id() -> String {
         uuid::Uuid::new_v4().to_string()
@@ -91,14 +150,26 @@ pub struct WorkerConnection {
     ui_tx: mpsc::Sender<WorkerUiEvent>,
     /// Session IDs for each run (for continuation support).
     sessions: Arc<tokio::sync::Mutex<HashMap<String, String>>>,
+    /// Cancel signals for in-flight runs.
+    cancellations: RunCancellations,
+    /// Groups whose progress is being followed.
+    watched_groups: Arc<std::sync::Mutex<HashSet<String>>>,
+    /// Run limit, changed from the Config view while connected.
+    max_concurrent_runs: Arc<AtomicU32>,
+    /// Whether the stream to the control plane came up.
+    connected: bool,
 }
 
+/// How often the progress of a run's group is fetched from servers without WatchTasks.
+const GROUP_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
 #[allow(dead_code)] // worker_id is for API completeness
 impl WorkerConnection {
     /// Create a new WorkerConnection.
     pub fn new(config: ConnectionConfig, ui_tx: mpsc::Sender<WorkerUiEvent>) -> Self {
         let config = Arc::new(config);
         let executor = Arc::new(ClaudeCodeExecutor::new(config.clone()));
+        let max_concurrent_runs = Arc::new(AtomicU32::new(config.max_concurrent_runs));
         Self {
             config,
             outbound_tx: None,
@@ -106,9 +177,24 @@ impl WorkerConnection {
             executor,
             ui_tx,
             sessions: Arc::new(tokio::sync::Mutex::new(HashMap::new())),
+            cancellations: RunCancellations::new(),
+            watched_groups: Arc::new(std::sync::Mutex::new(HashSet::new())),
+            max_concurrent_runs,
+            connected: false,
         }
     }
 
+    /// Whether the stream to the control plane came up, so the reconnect
+    /// backoff can start over.
+    pub fn was_connected(&self) -> bool {
+        self.connected
+    }
+
+    /// Current run limit, including changes made while connected.
+    pub fn max_concurrent_runs(&self) -> u32 {
+        self.max_concurrent_runs.load(Ordering::SeqCst)
+    }
+
     /// Get the worker ID.
     pub fn worker_id(&self) -> &str {
         &self.config.worker_id
@@ -144,12 +230,10 @@ impl WorkerConnection {
                 self.config.tls_cert_path, e
             )
         })?;
-        let client_key = std::fs::read(&self.config.tls_key_path).map_err(|e| {
-            format!(
-                "Failed to read worker key from '{}': {}",
-                self.config.tls_key_path, e
-            )
-        })?;
+        let client_key = key_store::read_key(
+            &self.config.tls_key_path,
+            self.config.tls_key_passphrase.as_ref(),
+        )?;
 
         let tls_config = ClientTlsConfig::new()
             .ca_certificate(Certificate::from_pem(ca_cert))
@@ -173,6 +257,7 @@ impl WorkerConnection {
         // Start streaming connection
         let response = client.stream_connect(outbound_stream).await?;
         let mut inbound = response.into_inner();
+        self.connected = true;
 
         self.log(
             LogLevel::Info,
@@ -194,8 +279,15 @@ impl WorkerConnection {
         let heartbeat_tx = tx.clone();
         let heartbeat_config = self.config.clone();
         let heartbeat_run_count = self.active_run_count.clone();
+        let heartbeat_max_runs = self.max_concurrent_runs.clone();
         let heartbeat_handle = tokio::spawn(async move {
-            run_heartbeat_loop(heartbeat_tx, heartbeat_config, heartbeat_run_count).await;
+            run_heartbeat_loop(
+                heartbeat_tx,
+                heartbeat_config,
+                heartbeat_run_count,
+                heartbeat_max_runs,
+            )
+            .await;
         });
 
         // Process incoming messages and UI commands
@@ -236,6 +328,18 @@ impl WorkerConnection {
                         WorkerCommand::CreateTask { prompt } => {
                             self.handle_create_task(prompt).await;
                         }
+                        WorkerCommand::ValidateTask { prompt } => {
+                            self.handle_validate_task(prompt).await;
+                        }
+                        WorkerCommand::SetMaxConcurrentRuns { max } => {
+                            self.handle_set_max_concurrent_runs(max, &tx).await;
+                        }
+                        WorkerCommand::CancelRun { run_id } => {
+                            self.handle_cancel_run(&run_id);
+                        }
+                        WorkerCommand::ToolDecision { prompt_id, decision } => {
+                            self.executor.decide_tool_prompt(&prompt_id, decision);
+                        }
                     }
                 }
             }
@@ -256,6 +360,7 @@ impl WorkerConnection {
         let info = self.build_worker_info();
         let hello = WorkerHello {
             info: Some(info.into()),
+            acks_assignments: true,
         };
 
         let msg = RunClientMessage {
@@ -268,7 +373,42 @@ impl WorkerConnection {
         Ok(())
     }
 
+    /// Cancel an in-flight run at the operator's request. The run reports
+    /// CANCELLED to the control plane like one cancelled there.
+    fn handle_cancel_run(&self, run_id: &str) {
+        info!(run_id = %run_id, "Cancelling run from the TUI");
+        if !self
+            .cancellations
+            .cancel(run_id, "Cancelled from the worker TUI")
+        {
+            self.log(LogLevel::Warn, format!("Run {} is not in flight", run_id));
+        }
+    }
+
+    /// Apply a new run limit locally and tell the control plane in place.
+    async fn handle_set_max_concurrent_runs(&self, max: u32, tx: &mpsc::Sender<RunClientMessage>) {
+        self.max_concurrent_runs.store(max, Ordering::SeqCst);
+        self.executor.resize_lanes(max);
+
+        let update = WorkerUpdate {
+            max_concurrent_runs: Some(max),
+            ..Default::default()
+        };
+        let msg = RunClientMessage {
+            payload: Some(ClientPayload::Update(update)),
+        };
+        if tx.send(msg).await.is_err() {
+            warn!("Failed to send worker update");
+            return;
+        }
+        self.log(
+            LogLevel::Info,
+            format!("Max concurrent runs set to {}", max),
+        );
+    }
+
     /// Handle a ContinueRun command - resume a session with a follow-up message.
+    /// The turn runs in the background, so a cancel can reach it.
     async fn handle_continue_run(
         &self,
         run_id: String,
@@ -284,128 +424,73 @@ impl WorkerConnection {
                 &session_id[..8.min(session_id.len())]
             ),
         );
+        // Before the turn starts, so a cancel right behind it finds it
+        let Some(cancel_rx) = self.cancellations.try_register(&run_id) else {
+            self.log(
+                LogLevel::Warn,
+                format!("Run {} already has a turn in progress", run_id),
+            );
+            return;
+        };
+        tokio::spawn(execute_continue_run(
+            self.executor.clone(),
+            self.ui_tx.clone(),
+            self.config.agent_name.clone(),
+            self.cancellations.clone(),
+            cancel_rx,
+            tx,
+            run_id,
+            session_id,
+            message,
+        ));
+    }
 
-        // Send user message to server as ChatMessage
-        send_chat_message(&tx, &run_id, ProtoChatRole::User, message.clone()).await;
-
-        // Notify UI that run is active again
-        let _ = self
-            .ui_tx
-            .send(WorkerUiEvent::RunProgress {
-                run_id: run_id.clone(),
-                output: String::new(), // Will be populated by streaming
-            })
-            .await;
-
-        // Create channels for output streaming
-        let (output_tx, mut output_rx) = mpsc::channel::<super::executor::OutputChunk>(32);
-        let (event_tx, mut event_rx) = mpsc::channel::<RunEvent>(32);
-
-        // Spawn output forwarder to UI, accumulate for server ChatMessage
-        let ui_tx_clone = self.ui_tx.clone();
-        let run_id_clone = run_id.clone();
-        let output_handle = tokio::spawn(async move {
-            let mut accumulated_output = String::new();
-            while let Some(chunk) = output_rx.recv().await {
-                if !chunk.content.is_empty() {
-                    // Send to UI for real-time streaming
-                    let _ = ui_tx_clone
-                        .send(WorkerUiEvent::RunProgress {
-                            run_id: run_id_clone.clone(),
-                            output: chunk.content.clone(),
-                        })
-                        .await;
-
-                    // Accumulate for final assistant message
-                    accumulated_output.push_str(&chunk.content);
-                }
-            }
-            accumulated_output
-        });
-
-        // Spawn event forwarder to both UI and server
-        let ui_tx_clone2 = self.ui_tx.clone();
-        let server_tx_clone2 = tx.clone();
-        let run_id_clone2 = run_id.clone();
-        let event_handle = tokio::spawn(async move {
-            while let Some(event) = event_rx.recv().await {
-                // Send to UI
-                let _ = ui_tx_clone2
-                    .send(WorkerUiEvent::RunEvent {
-                        run_id: run_id_clone2.clone(),
-                        event_type: format!("{:?}", event.event_type),
-                        details: event.metadata.get("tool_name").cloned(),
-                    })
-                    .await;
-
-                // Send to server
-                send_event(&server_tx_clone2, event).await;
-            }
-        });
-
-        // Execute the follow-up
-        let executor = self.executor.clone();
-        let run_id_for_exec = RunId::new(&run_id);
-        // Use the same task_id (we don't have it, use run_id as placeholder)
-        let task_id_for_exec = TaskId::new(&run_id);
-
-        let result = executor
-            .execute_follow_up(
-                &session_id,
-                &message,
-                output_tx,
-                event_tx,
-                run_id_for_exec,
-                task_id_for_exec,
-            )
-            .await;
-
-        // Wait for handlers
-        let accumulated_output = output_handle.await.unwrap_or_default();
-        let _ = event_handle.await;
-
-        // Send accumulated assistant output as a single ChatMessage
-        if !accumulated_output.is_empty() {
-            send_chat_message(&tx, &run_id, ProtoChatRole::Assistant, accumulated_output).await;
+    /// Request creating a task for `prompt` on this worker's agent.
+    fn new_task_request(&self, prompt: &str) -> CreateTaskRequest {
+        CreateTaskRequest {
+            agent_name: self.config.agent_name.clone(),
+            input_json: serde_json::json!({ "prompt": prompt }).to_string(),
+            labels: std::collections::HashMap::new(),
+            created_by: "worker-tui".to_string(),
+            timeout_seconds: 0,
+            // Typed into the TUI, so someone is waiting on the output
+            priority: taskrun_proto::pb::TaskPriority::Interactive.into(),
+            pending_ttl_seconds: 0,
+            namespace: self.config.namespace.clone(),
+            input_files: Vec::new(),
+            limits: None,
         }
+    }
 
-        match result {
-            Ok(exec_result) => {
-                // Send new session_id if changed
-                if let Some(new_session_id) = &exec_result.session_id {
-                    let _ = self
-                        .ui_tx
-                        .send(WorkerUiEvent::SessionCaptured {
-                            run_id: run_id.clone(),
-                            session_id: new_session_id.clone(),
-                        })
-                        .await;
-                }
-
-                // Notify UI that turn is complete (finalize output as assistant message)
-                let _ = self
-                    .ui_tx
-                    .send(WorkerUiEvent::TurnCompleted {
-                        run_id: run_id.clone(),
-                    })
-                    .await;
-
-                self.log(
-                    LogLevel::Info,
-                    format!("Continuation completed for run {}", run_id),
-                );
+    /// Handle a ValidateTask command - lint the new-task dialog's task.
+    async fn handle_validate_task(&self, prompt: String) {
+        let request = self.new_task_request(&prompt);
+        let response = match self.create_task_client().await {
+            Ok(mut client) => client.validate_task(request).await,
+            Err(e) => {
+                self.log(LogLevel::Warn, format!("Cannot validate task: {}", e));
+                return;
             }
+        };
+        let messages = match response {
+            Ok(response) => response
+                .into_inner()
+                .findings
+                .into_iter()
+                .map(|finding| ValidationMessage {
+                    error: finding.severity() == ValidationSeverity::Error,
+                    text: finding.message,
+                })
+                .collect(),
             Err(e) => {
-                self.log(LogLevel::Error, format!("Continuation failed: {}", e));
-                let _ = self
-                    .ui_tx
-                    .send(WorkerUiEvent::LogMessage {
-                        level: LogLevel::Error,
-                        message: format!("Failed to continue session: {}", e),
-                    })
-                    .await;
+                self.log(LogLevel::Warn, format!("Cannot validate task: {}", e));
+                return;
             }
-        }
+        };
+        let _ = self
+            .ui_tx
+            .send(WorkerUiEvent::TaskValidated { messages })
+            .await;
     }
 
     /// Handle a CreateTask command - create a new task via the TaskService API.
@@ -418,19 +503,7 @@ impl WorkerConnection {
             ),
         );
 
-        // Build JSON input
-        let input_json = serde_json::json!({
-            "prompt": prompt
-        })
-        .to_string();
-
-        // Create the request
-        let request = CreateTaskRequest {
-            agent_name: self.config.agent_name.clone(),
-            input_json,
-            labels: std::collections::HashMap::new(),
-            created_by: "worker-tui".to_string(),
-        };
+        let request = self.new_task_request(&prompt);
 
         // Connect to TaskService (reuse TLS config)
         match self.create_task_client().await {
@@ -441,6 +514,18 @@ impl WorkerConnection {
                         LogLevel::Info,
                         format!("Task created: id={}, agent={}", task.id, task.agent_name),
                     );
+                    // No free worker yet: tell the user how long the wait may be
+                    if let Some(queue) = task.queue {
+                        let eta = if queue.eta_seconds > 0 {
+                            format!(", ETA ~{}", format_duration(queue.eta_seconds as i64))
+                        } else {
+                            String::new()
+                        };
+                        self.log(
+                            LogLevel::Info,
+                            format!("Task queued at position {}{}", queue.position, eta),
+                        );
+                    }
                 }
                 Err(e) => {
                     self.log(LogLevel::Error, format!("Failed to create task: {}", e));
@@ -455,13 +540,104 @@ impl WorkerConnection {
         }
     }
 
+    /// Follow a group's summary until every task of it finished. The summary
+    /// is fetched again whenever one of the group's tasks (labeled
+    /// `label=group_id`) is created or changes status.
+    async fn watch_group(&self, label: &str, group_id: String) {
+        if !self.watched_groups.lock().unwrap().insert(group_id.clone()) {
+            return;
+        }
+        let mut client = match self.create_task_client().await {
+            Ok(client) => client,
+            Err(e) => {
+                self.log(
+                    LogLevel::Warn,
+                    format!("Cannot follow group {}: {}", group_id, e),
+                );
+                self.watched_groups.lock().unwrap().remove(&group_id);
+                return;
+            }
+        };
+
+        let request = GetGroupSummaryRequest {
+            group_id: group_id.clone(),
+            namespace: self.config.namespace.clone(),
+        };
+        let watch = WatchTasksRequest {
+            namespace: self.config.namespace.clone(),
+            labels: HashMap::from([(label.to_string(), group_id.clone())]),
+            ..WatchTasksRequest::default()
+        };
+        let ui_tx = self.ui_tx.clone();
+        let watched = self.watched_groups.clone();
+        tokio::spawn(async move {
+            let mut updates = client.watch_tasks(watch).await.ok().map(|r| r.into_inner());
+            let mut interval = tokio::time::interval(GROUP_POLL_INTERVAL);
+            loop {
+                let Ok(summary) = client.get_group_summary(request.clone()).await else {
+                    break;
+                };
+                let summary = summary.into_inner();
+                let progress = GroupProgress {
+                    label: summary.group_id,
+                    completed: summary.completed,
+                    failed: summary.failed,
+                    cancelled: summary.cancelled,
+                    running: summary.running,
+                    pending: summary.pending,
+                };
+                let finished = progress.finished() == progress.total();
+                if ui_tx
+                    .send(WorkerUiEvent::GroupUpdated { progress })
+                    .await
+                    .is_err()
+                    || finished
+                {
+                    break;
+                }
+
+                match updates.as_mut() {
+                    Some(stream) => loop {
+                        match stream.message().await {
+                            Ok(Some(update)) => {
+                                if matches!(
+                                    update.event,
+                                    Some(
+                                        WatchEvent::Created(_)
+                                            | WatchEvent::TaskStatus(_)
+                                            | WatchEvent::Lagged(_)
+                                    )
+                                ) {
+                                    break;
+                                }
+                            }
+                            // Stream ended: fall back to polling
+                            _ => {
+                                updates = None;
+                                interval.reset();
+                                break;
+                            }
+                        }
+                    },
+                    None => {
+                        interval.tick().await;
+                    }
+                }
+            }
+            watched.lock().unwrap().remove(&request.group_id);
+        });
+    }
+
     /// Create a TaskService client with the same TLS config.
     async fn create_task_client(
         &self,
     ) -> Result<TaskServiceClient<Channel>, Box<dyn std::error::Error + Send + Sync>> {
         let ca_cert = std::fs::read(&self.config.tls_ca_cert_path)?;
         let client_cert = std::fs::read(&self.config.tls_cert_path)?;
-        let client_key = std::fs::read(&self.config.tls_key_path)?;
+        let client_key = key_store::read_key(
+            &self.config.tls_key_path,
+            self.config.tls_key_passphrase.as_ref(),
+        )?;
 
         let tls_config = ClientTlsConfig::new()
             .ca_certificate(Certificate::from_pem(ca_cert))
@@ -477,23 +653,16 @@ impl WorkerConnection {
     }
 
     fn build_worker_info(&self) -> WorkerInfo {
-        // Model backend from config
-        let backend = ModelBackend::new(&self.config.model_provider, &self.config.model_name)
-            .with_context_window(200_000)
-            .with_modalities(vec!["text".to_string()]);
+        let agent = self.config.agent(&self.config.agent_name).spec();
 
-        // Agent from config
-        let description = get_agent_description(&self.config.agent_name);
-        let agent = AgentSpec::new(&self.config.agent_name)
-            .with_description(&description)
-            .with_backend(backend);
+        let hostname = labels::hostname();
 
-        // Get hostname
-        let hostname = std::env::var("HOSTNAME").unwrap_or_else(|_| "unknown".to_string());
-
-        WorkerInfo::new(WorkerId::new(&self.config.worker_id), hostname)
-            .with_agent(agent)
-            .with_label("env", "development")
+        self.config.labels.iter().fold(
+            WorkerInfo::new(WorkerId::new(&self.config.worker_id), hostname)
+                .with_namespace(&self.config.namespace)
+                .with_agent(agent),
+            |info, (key, value)| info.with_label(key, value),
+        )
     }
 
     async fn handle_server_message(
@@ -511,6 +680,7 @@ impl WorkerConnection {
                             assignment.run_id, assignment.agent_name
                         ),
                     );
+                    send_assignment_ack(&tx, &assignment.run_id).await;
 
                     // Notify UI of run start
                     let _ = self
@@ -523,15 +693,35 @@ impl WorkerConnection {
                         })
                         .await;
 
+                    // Workflow steps and batch members: show the group's progress
+                    let group = ["group", "workflow_id"].into_iter().find_map(|label| {
+                        assignment.labels.get(label).map(|id| (label, id.clone()))
+                    });
+                    if let Some((label, group_id)) = group {
+                        self.watch_group(label, group_id).await;
+                    }
+
                     // Spawn real execution via Claude Code
                     let active_count = self.active_run_count.clone();
                     let executor = self.executor.clone();
                     let ui_tx = self.ui_tx.clone();
                     let sessions = self.sessions.clone();
+                    let cancellations = self.cancellations.clone();
+                    // Before the run starts, so a cancel right behind the assignment finds it
+                    let cancel_rx = cancellations.register(&assignment.run_id);
 
                     tokio::spawn(async move {
-                        execute_real_run(executor, tx, assignment, active_count, ui_tx, sessions)
-                            .await;
+                        execute_real_run(
+                            executor,
+                            tx,
+                            assignment,
+                            active_count,
+                            ui_tx,
+                            sessions,
+                            cancellations,
+                            cancel_rx,
+                        )
+                        .await;
                     });
                 }
                 ServerPayload::CancelRun(cancel) => {
@@ -542,6 +732,22 @@ impl WorkerConnection {
                             cancel.run_id, cancel.reason
                         ),
                     );
+
+                    if !self.cancellations.cancel(&cancel.run_id, &cancel.reason) {
+                        // Not running here; report CANCELLED so the control plane can settle it
+                        self.log(
+                            LogLevel::Warn,
+                            format!("Run {} is not in flight", cancel.run_id),
+                        );
+                        send_status_update_with_error(
+                            &tx,
+                            &cancel.run_id,
+                            taskrun_proto::pb::RunStatus::Cancelled,
+                            FailureReason::CancelledByUser,
+                            cancel.reason,
+                        )
+                        .await;
+                    }
                 }
                 ServerPayload::Ack(ack) => {
                     self.log(
@@ -549,6 +755,35 @@ impl WorkerConnection {
                         format!("Received ack: type={}, ref_id={}", ack.ack_type, ack.ref_id),
                     );
                 }
+                ServerPayload::Disconnect(disconnect) => {
+                    // The control plane ends the stream next; we reconnect as usual
+                    self.log(
+                        LogLevel::Warn,
+                        format!("Disconnected by the control plane: {}", disconnect.reason),
+                    );
+                }
+                ServerPayload::ApprovalDecision(decision) => {
+                    self.log(
+                        LogLevel::Info,
+                        format!(
+                            "Tool approval {} {} by {}",
+                            decision.approval_id,
+                            if decision.allowed {
+                                "approved"
+                            } else {
+                                "denied"
+                            },
+                            decision.decided_by
+                        ),
+                    );
+                    let approval_id = decision.approval_id.clone();
+                    if !self.executor.decide_approval(decision) {
+                        self.log(
+                            LogLevel::Warn,
+                            format!("Approval {} is no longer waiting", approval_id),
+                        );
+                    }
+                }
                 ServerPayload::ContinueRun(continue_run) => {
                     self.log(
                         LogLevel::Info,
@@ -592,6 +827,15 @@ impl WorkerConnection {
                                     &continue_run.run_id[..8.min(continue_run.run_id.len())]
                                 ),
                             );
+                            // Tell the control plane, which may be waiting on a reply
+                            send_status_update_with_error(
+                                &tx,
+                                &continue_run.run_id,
+                                taskrun_proto::pb::RunStatus::Failed,
+                                FailureReason::SdkError,
+                                NO_SESSION.to_string(),
+                            )
+                            .await;
                         }
                     }
                 }
@@ -600,31 +844,254 @@ impl WorkerConnection {
     }
 
     fn log(&self, level: LogLevel, message: String) {
-        // Also log via tracing
-        match level {
-            LogLevel::Debug => tracing::debug!("{}", message),
-            LogLevel::Info => tracing::info!("{}", message),
-            LogLevel::Warn => tracing::warn!("{}", message),
-            LogLevel::Error => tracing::error!("{}", message),
-        }
+        log_to_ui(&self.ui_tx, level, message);
+    }
+}
 
-        // Send to UI (non-blocking)
-        let _ = self
-            .ui_tx
-            .try_send(WorkerUiEvent::LogMessage { level, message });
+/// Log via tracing and to the UI log panel (non-blocking).
+fn log_to_ui(ui_tx: &mpsc::Sender<WorkerUiEvent>, level: LogLevel, message: String) {
+    match level {
+        LogLevel::Debug => tracing::debug!("{}", message),
+        LogLevel::Info => tracing::info!("{}", message),
+        LogLevel::Warn => tracing::warn!("{}", message),
+        LogLevel::Error => tracing::error!("{}", message),
     }
+    let _ = ui_tx.try_send(WorkerUiEvent::LogMessage { level, message });
 }
 
-/// Get the description for a known agent, or a generic description for custom agents.
-fn get_agent_description(agent_name: &str) -> String {
-    match agent_name {
-        "general" => "General-purpose agent that executes any task".to_string(),
-        "support_triage" => "Classifies and triages support tickets".to_string(),
-        _ => format!("Custom agent: {}", agent_name),
+/// Run a follow-up turn of `run_id`'s session. A cancel stops the turn and
+/// kills its Claude process.
+#[allow(clippy::too_many_arguments)]
+async fn execute_continue_run(
+    executor: Arc<ClaudeCodeExecutor>,
+    ui_tx: mpsc::Sender<WorkerUiEvent>,
+    agent_name: String,
+    cancellations: RunCancellations,
+    mut cancel_rx: oneshot::Receiver<Cancel>,
+    tx: mpsc::Sender<RunClientMessage>,
+    run_id: String,
+    session_id: String,
+    message: String,
+) {
+    // Continuations are interactive: the user is waiting on the reply. A
+    // cancel while queued ends the turn here.
+    let _permit = tokio::select! {
+        permit = executor.acquire_lane(TaskPriority::Interactive) => permit,
+        cancel = &mut cancel_rx => {
+            let cancel = cancel.unwrap_or_else(|_| Cancel::new("Run cancelled"));
+            cancellations.unregister(&run_id);
+            finish_cancelled_turn(&tx, &ui_tx, &run_id, cancel).await;
+            return;
+        }
+    };
+
+    // Send user message to server as ChatMessage
+    send_chat_message(
+        &tx,
+        &run_id,
+        ProtoChatRole::User,
+        message.clone(),
+        Vec::new(),
+    )
+    .await;
+
+    // Notify UI that run is active again
+    let _ = ui_tx
+        .send(WorkerUiEvent::RunProgress {
+            run_id: run_id.clone(),
+            output: String::new(), // Will be populated by streaming
+        })
+        .await;
+
+    // Create channels for output streaming
+    let (output_tx, mut output_rx) = mpsc::channel::<super::executor::OutputChunk>(32);
+    let (event_tx, mut event_rx) = mpsc::channel::<RunEvent>(32);
+
+    // Masks the run's secret values once the executor resolves them
+    let redactor = Redactor::default();
+
+    // Spawn output forwarder to UI, accumulate for server ChatMessage
+    let ui_tx_clone = ui_tx.clone();
+    let run_id_clone = run_id.clone();
+    let mut output_filter = executor.output_filter(&agent_name);
+    let mut output_redactor = redactor.chunks();
+    let output_handle = tokio::spawn(async move {
+        let mut accumulated_output = String::new();
+        let mut open = true;
+        while open {
+            let content = match output_rx.recv().await {
+                Some(chunk) => output_redactor.push(&chunk.content),
+                None => {
+                    open = false;
+                    output_redactor.finish()
+                }
+            };
+            if !content.is_empty() {
+                let content = output_filter.filter(content);
+                // Send to UI for real-time streaming
+                let _ = ui_tx_clone
+                    .send(WorkerUiEvent::RunProgress {
+                        run_id: run_id_clone.clone(),
+                        output: content.clone(),
+                    })
+                    .await;
+
+                // Accumulate for final assistant message
+                accumulated_output.push_str(&content);
+            }
+        }
+        (accumulated_output, output_filter)
+    });
+
+    // Spawn event forwarder to both UI and server
+    let ui_tx_clone2 = ui_tx.clone();
+    let server_tx_clone2 = tx.clone();
+    let run_id_clone2 = run_id.clone();
+    let event_redactor = redactor.clone();
+    let event_handle = tokio::spawn(async move {
+        while let Some(event) = event_rx.recv().await {
+            let mut event = event_redactor.redact_event(event);
+            // Full tool input and output stay on the worker
+            let payload = ToolPayload::take(&mut event).map(Box::new);
+            // Send to UI
+            let _ = ui_tx_clone2
+                .send(WorkerUiEvent::RunEvent {
+                    run_id: run_id_clone2.clone(),
+                    event_type: format!("{:?}", event.event_type),
+                    details: event.metadata.get("tool_name").cloned(),
+                    artifacts: event.artifacts.clone(),
+                    milestone: RunMilestone::from_event(&event),
+                    result: RunResultSummary::from_event(&event),
+                    payload,
+                })
+                .await;
+
+            // Send to server
+            send_event(&server_tx_clone2, event).await;
+        }
+    });
+
+    // Execute the follow-up
+    let run_id_for_exec = RunId::new(&run_id);
+    // Use the same task_id (we don't have it, use run_id as placeholder)
+    let task_id_for_exec = TaskId::new(&run_id);
+    let approvals = executor.approval_gate(&tx, &run_id);
+
+    // Dropping the execution future on cancel kills the Claude process
+    let result = tokio::select! {
+        result = executor.execute_follow_up(
+            &session_id,
+            &message,
+            output_tx,
+            event_tx,
+            run_id_for_exec,
+            task_id_for_exec,
+            approvals,
+            &redactor,
+        ) => Some(result),
+        Ok(cancel) = &mut cancel_rx => {
+            finish_cancelled_turn(&tx, &ui_tx, &run_id, cancel).await;
+            None
+        }
+    };
+    cancellations.unregister(&run_id);
+
+    // Wait for handlers
+    let (accumulated_output, output_filter) = output_handle.await.unwrap_or_default();
+    let _ = event_handle.await;
+
+    // Record masked span counts in the run's event stream
+    if let Some(event) = output_filter.into_event(RunId::new(&run_id), TaskId::new(&run_id)) {
+        send_event(&tx, event).await;
+    }
+    let Some(result) = result else {
+        return;
+    };
+
+    // Send accumulated assistant output (and the files it produced) as a single ChatMessage
+    let attachments = result
+        .as_ref()
+        .map(|r| r.artifacts.clone())
+        .unwrap_or_default();
+    if !accumulated_output.is_empty() || !attachments.is_empty() {
+        send_chat_message(
+            &tx,
+            &run_id,
+            ProtoChatRole::Assistant,
+            accumulated_output,
+            attachments,
+        )
+        .await;
     }
+
+    match result {
+        Ok(exec_result) => {
+            // Send new session_id if changed
+            if let Some(new_session_id) = &exec_result.session_id {
+                let _ = ui_tx
+                    .send(WorkerUiEvent::SessionCaptured {
+                        run_id: run_id.clone(),
+                        session_id: new_session_id.clone(),
+                    })
+                    .await;
+            }
+
+            // Notify UI that turn is complete (finalize output as assistant message)
+            let _ = ui_tx
+                .send(WorkerUiEvent::TurnCompleted {
+                    run_id: run_id.clone(),
+                })
+                .await;
+
+            log_to_ui(
+                &ui_tx,
+                LogLevel::Info,
+                format!("Continuation completed for run {}", run_id),
+            );
+        }
+        Err(e) => {
+            let e = redactor.redact(&e.to_string());
+            log_to_ui(
+                &ui_tx,
+                LogLevel::Error,
+                format!("Continuation failed: {}", e),
+            );
+            let _ = ui_tx
+                .send(WorkerUiEvent::LogMessage {
+                    level: LogLevel::Error,
+                    message: format!("Failed to continue session: {}", e),
+                })
+                .await;
+        }
+    }
+}
+
+/// Report a cancelled follow-up turn to the control plane and the UI.
+async fn finish_cancelled_turn(
+    tx: &mpsc::Sender<RunClientMessage>,
+    ui_tx: &mpsc::Sender<WorkerUiEvent>,
+    run_id: &str,
+    cancel: Cancel,
+) {
+    log_to_ui(
+        ui_tx,
+        LogLevel::Info,
+        format!(
+            "Continuation of run {} cancelled: {}",
+            run_id, cancel.reason
+        ),
+    );
+    let (status, failure_reason) = cancel.outcome();
+    send_status_update_with_error(tx, run_id, status, failure_reason, cancel.reason).await;
+    let _ = ui_tx
+        .send(WorkerUiEvent::TurnCompleted {
+            run_id: run_id.to_string(),
+        })
+        .await;
 }
 
 /// Execute a real run via Claude Code subprocess.
+#[allow(clippy::too_many_arguments)]
 async fn execute_real_run(
     executor: Arc<ClaudeCodeExecutor>,
     tx: mpsc::Sender<RunClientMessage>,
@@ -632,17 +1099,79 @@ async fn execute_real_run(
     active_count: Arc<AtomicU32>,
     ui_tx: mpsc::Sender<WorkerUiEvent>,
     sessions: Arc<tokio::sync::Mutex<HashMap<String, String>>>,
+    cancellations: RunCancellations,
+    mut cancel_rx: oneshot::Receiver<Cancel>,
 ) {
     let run_id = assignment.run_id.clone();
     let task_id = assignment.task_id.clone();
 
-    // Increment active run count
+    // Increment active run count (queued runs count, so the control plane sees them)
     let count = active_count.fetch_add(1, Ordering::SeqCst) + 1;
     let _ = ui_tx
         .send(WorkerUiEvent::StatsUpdated { active_runs: count })
         .await;
 
-    info!(run_id = %run_id, agent = %assignment.agent_name, "Starting real execution via Claude Code");
+    // Wait for a slot in the run's lane; a cancel while queued ends the run here
+    let priority = assignment.priority().into();
+    let _permit = tokio::select! {
+        permit = executor.acquire_lane(priority) => permit,
+        cancel = &mut cancel_rx => {
+            let cancel = cancel.unwrap_or_else(|_| Cancel::new("Run cancelled"));
+            let reason = &cancel.reason;
+            info!(run_id = %run_id, reason = %reason, "Queued run cancelled");
+            cancellations.unregister(&run_id);
+            let (status, failure_reason) = cancel.outcome();
+            send_status_update_with_error(&tx, &run_id, status, failure_reason, reason.clone())
+                .await;
+            let _ = ui_tx
+                .send(WorkerUiEvent::RunCompleted {
+                    run_id: run_id.clone(),
+                    success: false,
+                    cancelled: status == taskrun_proto::pb::RunStatus::Cancelled,
+                    error_message: Some(format!("Cancelled: {}", reason)),
+                })
+                .await;
+            let count = active_count.fetch_sub(1, Ordering::SeqCst) - 1;
+            let _ = ui_tx
+                .send(WorkerUiEvent::StatsUpdated { active_runs: count })
+                .await;
+            return;
+        }
+    };
+
+    info!(run_id = %run_id, agent = %assignment.agent_name, priority = ?priority, "Starting real execution via Claude Code");
+
+    // Stage input files in the workspace and point the task at them
+    let staged = files::stage_inputs(executor.working_dir(), &run_id, &assignment.input_files);
+    let input_json = match staged {
+        Ok(paths) => files::with_input_files(&assignment.input_json, &paths),
+        Err(e) => {
+            let error_message = format!("Failed to stage input files: {}", e);
+            error!(run_id = %run_id, error = %e, "Failed to stage input files");
+            cancellations.unregister(&run_id);
+            send_status_update_with_error(
+                &tx,
+                &run_id,
+                taskrun_proto::pb::RunStatus::Failed,
+                FailureReason::ProcessCrash,
+                error_message.clone(),
+            )
+            .await;
+            let _ = ui_tx
+                .send(WorkerUiEvent::RunCompleted {
+                    run_id: run_id.clone(),
+                    success: false,
+                    cancelled: false,
+                    error_message: Some(error_message),
+                })
+                .await;
+            let count = active_count.fetch_sub(1, Ordering::SeqCst) - 1;
+            let _ = ui_tx
+                .send(WorkerUiEvent::StatsUpdated { active_runs: count })
+                .await;
+            return;
+        }
+    };
 
     // Send the initial user message (input_json) as a ChatMessage
     // Extract prompt from input_json if possible, otherwise use the raw JSON
@@ -656,10 +1185,12 @@ async fn execute_real_run(
         } else {
             assignment.input_json.clone()
         };
-    send_chat_message(&tx, &run_id, ProtoChatRole::User, user_message).await;
+    send_chat_message(&tx, &run_id, ProtoChatRole::User, user_message, Vec::new()).await;
 
-    // Send RUNNING status
-    send_status_update(&tx, &run_id, taskrun_proto::pb::RunStatus::Running, None).await;
+    // Send RUNNING status with the environment the run executes in
+    let manifest = executor.run_manifest(&assignment);
+    let limits: RunLimits = assignment.limits.map(Into::into).unwrap_or_default();
+    send_run_started(&tx, &run_id, manifest.clone()).await;
 
     // Create channel for streaming output from executor
     let (chunk_tx, mut chunk_rx) = mpsc::channel::<super::executor::OutputChunk>(32);
@@ -667,24 +1198,36 @@ async fn execute_real_run(
     // Create channel for events from executor
     let (event_tx, mut event_rx) = mpsc::channel::<RunEvent>(32);
 
+    // Masks the run's secret values once the executor resolves them
+    let redactor = Redactor::default();
+
     // Spawn event forwarder to send events via gRPC and UI
     let event_tx_grpc = tx.clone();
     let event_ui_tx = ui_tx.clone();
     let event_run_id = run_id.clone();
+    let event_redactor = redactor.clone();
     let event_handle = tokio::spawn(async move {
         while let Some(event) = event_rx.recv().await {
+            let mut event = event_redactor.redact_event(event);
+            // Full tool input and output stay on the worker
+            let payload = ToolPayload::take(&mut event).map(Box::new);
             // Forward to UI
             let event_type = format!("{:?}", event.event_type);
             let details = event
                 .metadata
                 .get("tool_name")
                 .cloned()
-                .or_else(|| event.metadata.get("error").cloned());
+                .or_else(|| event.metadata.get("error").cloned())
+                .or_else(|| event.metadata.get("milestone").cloned());
             let _ = event_ui_tx
                 .send(WorkerUiEvent::RunEvent {
                     run_id: event_run_id.clone(),
                     event_type: event_type.clone(),
                     details,
+                    artifacts: event.artifacts.clone(),
+                    milestone: RunMilestone::from_event(&event),
+                    result: RunResultSummary::from_event(&event),
+                    payload,
                 })
                 .await;
             // Forward to gRPC
@@ -693,55 +1236,105 @@ async fn execute_real_run(
     });
 
     // Spawn output forwarder to UI (accumulates for assistant ChatMessage)
+    let mut output_filter = executor.output_filter(&assignment.agent_name);
     let ui_tx_output = ui_tx.clone();
     let run_id_output = run_id.clone();
+    let mut output_redactor = redactor.chunks();
     let output_handle = tokio::spawn(async move {
         let mut accumulated_output = String::new();
-        while let Some(chunk) = chunk_rx.recv().await {
-            if !chunk.is_final && !chunk.content.is_empty() {
+        let mut open = true;
+        while open {
+            let content = match chunk_rx.recv().await {
+                Some(chunk) if chunk.is_final => continue,
+                Some(chunk) => output_redactor.push(&chunk.content),
+                None => {
+                    open = false;
+                    output_redactor.finish()
+                }
+            };
+            if !content.is_empty() {
+                let content = output_filter.filter(content);
                 // Send to UI for real-time streaming
                 let _ = ui_tx_output
                     .send(WorkerUiEvent::RunProgress {
                         run_id: run_id_output.clone(),
-                        output: chunk.content.clone(),
+                        output: content.clone(),
                     })
                     .await;
                 // Accumulate for final assistant message
-                accumulated_output.push_str(&chunk.content);
+                accumulated_output.push_str(&content);
             }
         }
-        accumulated_output
+        (accumulated_output, output_filter)
     });
 
     // Execute
     let executor_clone = executor.clone();
-    let agent_name = assignment.agent_name.clone();
-    let input_json = assignment.input_json.clone();
     let run_id_clone = RunId::new(&run_id);
     let task_id_clone = TaskId::new(&task_id);
+    let approvals = executor.approval_gate(&tx, &run_id);
 
-    let result = executor_clone
-        .execute(
-            &agent_name,
+    // Dropping the execution future on cancel kills the Claude process
+    let result = tokio::select! {
+        result = executor_clone.execute(
             &input_json,
+            &manifest,
+            &limits,
             chunk_tx,
             event_tx,
             run_id_clone,
             task_id_clone,
-        )
-        .await;
+            approvals,
+            &redactor,
+        ) => Some(result),
+        Ok(cancel) = &mut cancel_rx => {
+            let reason = &cancel.reason;
+            info!(run_id = %run_id, reason = %reason, "Execution cancelled");
+            let (status, failure_reason) = cancel.outcome();
+            send_status_update_with_error(&tx, &run_id, status, failure_reason, reason.clone())
+                .await;
+            let _ = ui_tx
+                .send(WorkerUiEvent::RunCompleted {
+                    run_id: run_id.clone(),
+                    success: false,
+                    cancelled: status == taskrun_proto::pb::RunStatus::Cancelled,
+                    error_message: Some(format!("Cancelled: {}", reason)),
+                })
+                .await;
+            None
+        }
+    };
+    cancellations.unregister(&run_id);
 
     // Wait for all handlers
-    let accumulated_output = output_handle.await.unwrap_or_default();
+    let (accumulated_output, output_filter) = output_handle.await.unwrap_or_default();
     let _ = event_handle.await;
 
-    // Send accumulated assistant output as a single ChatMessage
-    if !accumulated_output.is_empty() {
-        send_chat_message(&tx, &run_id, ProtoChatRole::Assistant, accumulated_output).await;
+    // Record masked span counts in the run's event stream
+    if let Some(event) = output_filter.into_event(RunId::new(&run_id), TaskId::new(&task_id)) {
+        send_event(&tx, event).await;
+    }
+
+    // Send accumulated assistant output (and the files it produced) as a single ChatMessage
+    let attachments = match &result {
+        Some(Ok(exec_result)) => exec_result.artifacts.clone(),
+        _ => Vec::new(),
+    };
+    files::upload_artifacts(&tx, &run_id, &attachments).await;
+    if !accumulated_output.is_empty() || !attachments.is_empty() {
+        send_chat_message(
+            &tx,
+            &run_id,
+            ProtoChatRole::Assistant,
+            accumulated_output,
+            attachments,
+        )
+        .await;
     }
 
     match result {
-        Ok(exec_result) => {
+        None => {}
+        Some(Ok(exec_result)) => {
             // Build the backend info that was used
             let backend_used = taskrun_proto::pb::ModelBackend {
                 provider: exec_result.provider,
@@ -784,20 +1377,23 @@ async fn execute_real_run(
                 .send(WorkerUiEvent::RunCompleted {
                     run_id: run_id.clone(),
                     success: true,
+                    cancelled: false,
                     error_message: None,
                 })
                 .await;
 
             info!(run_id = %run_id, "Real execution completed successfully");
         }
-        Err(e) => {
+        Some(Err(e)) => {
             // Executor returned an error
-            error!(run_id = %run_id, error = %e, "Execution failed");
+            let message = redactor.redact(&e.to_string());
+            error!(run_id = %run_id, error = %message, "Execution failed");
             send_status_update_with_error(
                 &tx,
                 &run_id,
                 taskrun_proto::pb::RunStatus::Failed,
-                e.to_string(),
+                e.failure_reason(),
+                message.clone(),
             )
             .await;
 
@@ -806,7 +1402,8 @@ async fn execute_real_run(
                 .send(WorkerUiEvent::RunCompleted {
                     run_id: run_id.clone(),
                     success: false,
-                    error_message: Some(e.to_string()),
+                    cancelled: false,
+                    error_message: Some(message),
                 })
                 .await;
         }
@@ -832,6 +1429,8 @@ async fn send_status_update(
         error_message: String::new(),
         backend_used,
         timestamp_ms: chrono::Utc::now().timestamp_millis(),
+        manifest: None,
+        failure_reason: 0,
     };
 
     let msg = RunClientMessage {
@@ -843,11 +1442,37 @@ async fn send_status_update(
     }
 }
 
-/// Send a status update with an error message to the control plane.
+/// Send the RUNNING status update, reporting the run's manifest.
+async fn send_run_started(
+    tx: &mpsc::Sender<RunClientMessage>,
+    run_id: &str,
+    manifest: RunManifest,
+) {
+    let update = RunStatusUpdate {
+        run_id: run_id.to_string(),
+        status: taskrun_proto::pb::RunStatus::Running as i32,
+        error_message: String::new(),
+        backend_used: None,
+        timestamp_ms: chrono::Utc::now().timestamp_millis(),
+        manifest: Some(manifest.into()),
+        failure_reason: 0,
+    };
+
+    let msg = RunClientMessage {
+        payload: Some(ClientPayload::StatusUpdate(update)),
+    };
+
+    if tx.send(msg).await.is_err() {
+        warn!(run_id = %run_id, "Failed to send status update");
+    }
+}
+
+/// Send a failed or cancelled status update to the control plane.
 async fn send_status_update_with_error(
     tx: &mpsc::Sender<RunClientMessage>,
     run_id: &str,
     status: taskrun_proto::pb::RunStatus,
+    failure_reason: FailureReason,
     error_message: String,
 ) {
     let update = RunStatusUpdate {
@@ -856,6 +1481,8 @@ async fn send_status_update_with_error(
         error_message,
         backend_used: None,
         timestamp_ms: chrono::Utc::now().timestamp_millis(),
+        manifest: None,
+        failure_reason: taskrun_proto::pb::FailureReason::from(failure_reason) as i32,
     };
 
     let msg = RunClientMessage {
@@ -867,12 +1494,26 @@ async fn send_status_update_with_error(
     }
 }
 
+/// Acknowledge a run assignment to the control plane.
+async fn send_assignment_ack(tx: &mpsc::Sender<RunClientMessage>, run_id: &str) {
+    let msg = RunClientMessage {
+        payload: Some(ClientPayload::AssignmentAck(AssignmentAck {
+            run_id: run_id.to_string(),
+        })),
+    };
+
+    if tx.send(msg).await.is_err() {
+        warn!(run_id = %run_id, "Failed to acknowledge run assignment");
+    }
+}
+
 /// Send a chat message to the control plane.
 async fn send_chat_message(
     tx: &mpsc::Sender<RunClientMessage>,
     run_id: &str,
     role: ProtoChatRole,
     content: String,
+    attachments: Vec<Artifact>,
 ) {
     let chat_msg = RunChatMessage {
         run_id: run_id.to_string(),
@@ -880,6 +1521,7 @@ async fn send_chat_message(
             role: role as i32,
             content,
             timestamp_ms: chrono::Utc::now().timestamp_millis(),
+            attachments: attachments.into_iter().map(Into::into).collect(),
         }),
     };
 
@@ -905,6 +1547,9 @@ async fn send_event(tx: &mpsc::Sender<RunClientMessage>, event: RunEvent) {
         RunEventType::OutputGenerated => taskrun_proto::pb::RunEventType::OutputGenerated,
         RunEventType::ExecutionCompleted => taskrun_proto::pb::RunEventType::ExecutionCompleted,
         RunEventType::ExecutionFailed => taskrun_proto::pb::RunEventType::ExecutionFailed,
+        RunEventType::Milestone => taskrun_proto::pb::RunEventType::Milestone,
+        RunEventType::Log => taskrun_proto::pb::RunEventType::Log,
+        RunEventType::RunSummary => taskrun_proto::pb::RunEventType::RunSummary,
     };
 
     let proto_event = ProtoRunEvent {
@@ -914,6 +1559,7 @@ async fn send_event(tx: &mpsc::Sender<RunClientMessage>, event: RunEvent) {
         event_type: proto_event_type as i32,
         timestamp_ms: event.timestamp_ms,
         metadata: event.metadata,
+        artifacts: event.artifacts.into_iter().map(Into::into).collect(),
     };
 
     let msg = RunClientMessage {
@@ -929,6 +1575,7 @@ async fn run_heartbeat_loop(
     tx: mpsc::Sender<RunClientMessage>,
     config: Arc<ConnectionConfig>,
     active_count: Arc<AtomicU32>,
+    max_concurrent_runs: Arc<AtomicU32>,
 ) {
     let interval = Duration::from_secs(config.heartbeat_interval_secs);
     let mut interval_timer = tokio::time::interval(interval);
@@ -947,7 +1594,8 @@ async fn run_heartbeat_loop(
             worker_id: config.worker_id.clone(),
             status: status as i32,
             active_runs: runs,
-            max_concurrent_runs: config.max_concurrent_runs,
+            max_concurrent_runs: max_concurrent_runs.load(Ordering::SeqCst),
+            interactive_slots: config.interactive_slots,
             metrics: HashMap::new(),
             timestamp_ms: chrono::Utc::now().timestamp_millis(),
         };
@@ -962,3 +1610,94 @@ async fn run_heartbeat_loop(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_cancelled_continuation_reports_cancelled_and_kills_claude() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = std::env::temp_dir().join(format!("taskrun-continue-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let pid_file = dir.join("claude.pid");
+        // Stands in for a Claude process that never answers
+        let claude = dir.join("claude");
+        std::fs::write(
+            &claude,
+            format!(
+                "#!/bin/sh\n[ \"$1\" = --version ] && echo 1.0.0 && exit\necho $$ > {}\nexec sleep 600\n",
+                pid_file.display()
+            ),
+        )
+        .unwrap();
+        std::fs::set_permissions(&claude, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let mut config = WorkerConfig::for_tests();
+        config.claude_path = claude.display().to_string();
+        config.working_dir = dir.display().to_string();
+        config.progress_tool = false;
+        let config = ConnectionConfig::from_with_id(&config, "worker-1".to_string());
+        let executor = Arc::new(ClaudeCodeExecutor::new(Arc::new(config)));
+        let (ui_tx, _ui_rx) = mpsc::channel(256);
+        let (tx, mut rx) = mpsc::channel(256);
+        let cancellations = RunCancellations::new();
+
+        let cancel_rx = cancellations.try_register("run-1").unwrap();
+        let turn = tokio::spawn(execute_continue_run(
+            executor,
+            ui_tx,
+            "general".to_string(),
+            cancellations.clone(),
+            cancel_rx,
+            tx,
+            "run-1".to_string(),
+            "session-1".to_string(),
+            "And the tests?".to_string(),
+        ));
+
+        let pid = tokio::time::timeout(Duration::from_secs(10), async {
+            loop {
+                if let Ok(pid) = std::fs::read_to_string(&pid_file) {
+                    if let Ok(pid) = pid.trim().parse::<u32>() {
+                        return pid;
+                    }
+                }
+                tokio::time::sleep(Duration::from_millis(20)).await;
+            }
+        })
+        .await
+        .expect("Claude was started");
+
+        // A second turn of the same run is refused while this one runs
+        assert!(cancellations.try_register("run-1").is_none());
+
+        assert!(cancellations.cancel("run-1", "Cancelled from the worker TUI"));
+        tokio::time::timeout(Duration::from_secs(10), turn)
+            .await
+            .expect("turn ended")
+            .unwrap();
+
+        let mut statuses = Vec::new();
+        while let Ok(msg) = rx.try_recv() {
+            if let Some(ClientPayload::StatusUpdate(update)) = msg.payload {
+                statuses.push(update.status);
+            }
+        }
+        assert_eq!(statuses, [taskrun_proto::pb::RunStatus::Cancelled as i32]);
+
+        // The Claude process was killed with the execution
+        let proc = format!("/proc/{}/stat", pid);
+        tokio::time::timeout(Duration::from_secs(10), async {
+            while std::fs::read_to_string(&proc).is_ok_and(|stat| !stat.contains(") Z ")) {
+                tokio::time::sleep(Duration::from_millis(20)).await;
+            }
+        })
+        .await
+        .expect("Claude was killed");
+        assert!(cancellations.try_register("run-1").is_some());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -3,8 +3,12 @@
 use std::time::Duration;
 
 use crossterm::event::KeyEvent;
+use taskrun_core::{Artifact, RunMilestone, RunResultSummary};
+use taskrun_tui_components::{GroupProgress, ValidationMessage};
 
 use super::state::{ConnectionState, LogLevel};
+use super::tool_payload::ToolPayload;
+use super::tool_prompts::ToolDecision;
 
 /// Events sent from the backend to the UI.
 #[derive(Debug)]
@@ -29,6 +33,8 @@ pub enum WorkerUiEvent {
     RunCompleted {
         run_id: String,
         success: bool,
+        /// Stopped by a cancel, from the control plane or the TUI.
+        cancelled: bool,
         error_message: Option<String>,
     },
     /// An event occurred during a run (tool use, execution lifecycle, etc).
@@ -36,6 +42,13 @@ pub enum WorkerUiEvent {
         run_id: String,
         event_type: String,
         details: Option<String>,
+        artifacts: Vec<Artifact>,
+        /// Milestone reported by the agent, for `Milestone` events.
+        milestone: Option<RunMilestone>,
+        /// What the run produced, for `RunSummary` events.
+        result: Option<RunResultSummary>,
+        /// Full tool input and output, for tool events.
+        payload: Option<Box<ToolPayload>>,
     },
     /// Log message from the worker.
     LogMessage { level: LogLevel, message: String },
@@ -47,6 +60,19 @@ pub enum WorkerUiEvent {
     TurnCompleted { run_id: String },
     /// A user message was added to a run (from server or local input).
     UserMessageAdded { run_id: String, message: String },
+    /// Validation findings for the new-task dialog.
+    TaskValidated { messages: Vec<ValidationMessage> },
+    /// Progress of the group (workflow, batch) a run belongs to.
+    GroupUpdated { progress: GroupProgress },
+    /// Claude asks whether a run may use a tool; answered with
+    /// `WorkerCommand::ToolDecision`.
+    ToolApprovalRequested {
+        prompt_id: String,
+        run_id: String,
+        tool_name: String,
+        /// Tool input as JSON.
+        input: String,
+    },
     /// Request to quit.
     Quit,
 }
@@ -64,11 +90,22 @@ pub enum WorkerCommand {
     },
     /// Create a new task.
     CreateTask { prompt: String },
+    /// Validate the task the new-task dialog would create.
+    ValidateTask { prompt: String },
+    /// Change the run limit without reconnecting.
+    SetMaxConcurrentRuns { max: u32 },
+    /// Cancel an active run.
+    CancelRun { run_id: String },
+    /// Answer a tool prompt.
+    ToolDecision {
+        prompt_id: String,
+        decision: ToolDecision,
+    },
     /// Quit the worker.
     Quit,
 }
 
 /// Helper to create a disconnect retry event.
-pub fn disconnected_event(retry_in: Duration) -> WorkerUiEvent {
-    WorkerUiEvent::ConnectionStateChanged(ConnectionState::Disconnected { retry_in })
+pub fn disconnected_event(retry_in: Option<Duration>, attempt: u32) -> WorkerUiEvent {
+    WorkerUiEvent::ConnectionStateChanged(ConnectionState::Disconnected { retry_in, attempt })
 }
@@ -2,21 +2,42 @@
 //!
 //! Adapted from taskrun-worker for use in the TUI.
 
+use std::collections::{BTreeMap, VecDeque};
 use std::path::Path;
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 use async_trait::async_trait;
 use serde_json::{json, Value};
 use taskrun_claude_sdk::{
     ClaudeExecutor, ClaudeMessage, ContentDelta, ContentItem, ControlHandler, PermissionMode,
-    PermissionResult, SdkError, StreamEvent,
+    PermissionResult, SdkError, StreamEvent, PRE_TOOL_USE_HOOK,
 };
-use taskrun_core::{RunEvent, RunId, TaskId};
+use taskrun_core::{
+    Artifact, FailureReason, RunEvent, RunId, RunLimits, RunManifest, TaskId, TaskPriority,
+};
+use taskrun_proto::pb::{RunAssignment, RunClientMessage, ToolApprovalDecision};
 use thiserror::Error;
-use tokio::sync::mpsc;
+use tokio::sync::{mpsc, OwnedSemaphorePermit};
 use tracing::{debug, info, warn};
 
 use super::connection::ConnectionConfig;
+use super::file_diff;
+use super::tool_payload;
+use super::tool_prompts::{ToolDecision, ToolPrompter};
+use crate::agents::AgentProfile;
+use crate::approvals::{ApprovalGate, PendingApprovals};
+use crate::artifacts::ArtifactTracker;
+use crate::failure;
+use crate::lanes::RunLanes;
+use crate::manifest;
+use crate::output_filter::RunOutputFilter;
+use crate::path_scope::{self, PathScope};
+use crate::progress_tool::ProgressTool;
+use crate::resource_limits;
+use crate::result_summary::ResultTracker;
+use crate::secrets::{Redactor, SecretsError};
+use crate::thinking::ThinkingStream;
 
 /// Errors that can occur during agent execution.
 #[derive(Debug, Error)]
@@ -33,6 +54,44 @@ pub enum ExecutorError {
 
     #[error("SDK error: {0}")]
     SdkError(String),
+
+    #[error("Input rejected by agent '{0}': {1}")]
+    InvalidInput(String, String),
+
+    #[error(transparent)]
+    Secrets(#[from] SecretsError),
+
+    #[error("{message}")]
+    Failed {
+        reason: FailureReason,
+        message: String,
+    },
+}
+
+impl ExecutorError {
+    /// Why the run failed, as reported to the control plane.
+    pub fn failure_reason(&self) -> FailureReason {
+        match self {
+            ExecutorError::ClaudeNotFound(_)
+            | ExecutorError::SpawnError(_)
+            | ExecutorError::ProcessError(_)
+            | ExecutorError::Secrets(_) => FailureReason::ProcessCrash,
+            ExecutorError::SdkError(_) | ExecutorError::InvalidInput(..) => FailureReason::SdkError,
+            ExecutorError::Failed { reason, .. } => *reason,
+        }
+    }
+}
+
+impl From<SdkError> for ExecutorError {
+    fn from(e: SdkError) -> Self {
+        match failure::sdk_failure_reason(&e) {
+            FailureReason::SdkError => ExecutorError::SdkError(e.to_string()),
+            reason => ExecutorError::Failed {
+                reason,
+                message: format!("SDK error: {}", e),
+            },
+        }
+    }
 }
 
 /// Output chunk from Claude Code execution.
@@ -50,14 +109,35 @@ struct StreamingHandler {
     task_id: TaskId,
     session_id: Arc<Mutex<Option<String>>>,
     model_used: Arc<Mutex<Option<String>>>,
+    artifacts: Mutex<ArtifactTracker>,
+    /// Tool calls, changed files and the final answer, for the result summary.
+    results: Mutex<ResultTracker>,
+    /// Set when Claude reports an error result.
+    failure: Mutex<Option<ExecutorError>>,
+    /// Escalates tool uses the approval policy asks about.
+    approvals: Option<ApprovalGate>,
+    /// Asks the operator about other tool uses (None = they are allowed).
+    prompter: Option<ToolPrompter>,
+    /// Denies tool uses outside the agent's allowed paths.
+    scope: Option<PathScope>,
+    /// Streams thinking into the output (None = thinking is left out).
+    thinking: Option<ThinkingStream>,
+    /// ToolRequested events of tool uses waiting for their result, oldest first.
+    tool_calls: Mutex<VecDeque<RunEvent>>,
 }
 
 impl StreamingHandler {
+    #[allow(clippy::too_many_arguments)]
     fn new(
         output_tx: mpsc::Sender<OutputChunk>,
         event_tx: mpsc::Sender<RunEvent>,
         run_id: RunId,
         task_id: TaskId,
+        working_dir: &Path,
+        approvals: Option<ApprovalGate>,
+        prompter: Option<ToolPrompter>,
+        scope: Option<PathScope>,
+        include_thinking: bool,
     ) -> Self {
         Self {
             output_tx,
@@ -66,6 +146,14 @@ impl StreamingHandler {
             task_id,
             session_id: Arc::new(Mutex::new(None)),
             model_used: Arc::new(Mutex::new(None)),
+            artifacts: Mutex::new(ArtifactTracker::new(working_dir)),
+            results: Mutex::new(ResultTracker::default()),
+            failure: Mutex::new(None),
+            approvals,
+            prompter,
+            scope,
+            thinking: include_thinking.then(ThinkingStream::default),
+            tool_calls: Mutex::new(VecDeque::new()),
         }
     }
 
@@ -73,10 +161,61 @@ impl StreamingHandler {
         self.session_id.lock().unwrap().clone()
     }
 
+    /// The error result Claude reported, if any.
+    fn take_failure(&self) -> Option<ExecutorError> {
+        self.failure.lock().unwrap().take()
+    }
+
     fn model_used(&self) -> Option<String> {
         self.model_used.lock().unwrap().clone()
     }
 
+    /// Resolve the remaining artifact candidates, reporting late ones in an
+    /// event. Returns every artifact of the execution.
+    async fn finish_artifacts(&self) -> Vec<Artifact> {
+        let (late, all) = self.artifacts.lock().unwrap().finish();
+        if !late.is_empty() {
+            self.emit_event(RunEvent::artifacts_attached(
+                self.run_id.clone(),
+                self.task_id.clone(),
+                late,
+            ))
+            .await;
+        }
+        all
+    }
+
+    /// Report the result summary of an execution in `working_dir` that took
+    /// `duration`, its answer masked by `filter`.
+    async fn finish_summary(
+        &self,
+        working_dir: &Path,
+        duration: Duration,
+        filter: RunOutputFilter,
+    ) {
+        let summary = self
+            .results
+            .lock()
+            .unwrap()
+            .summary(working_dir, duration, filter);
+        self.emit_event(RunEvent::run_summary(
+            self.run_id.clone(),
+            self.task_id.clone(),
+            &summary,
+        ))
+        .await;
+    }
+
+    async fn send_output(&self, content: String) {
+        let chunk = OutputChunk {
+            content,
+            is_final: false,
+        };
+        if self.output_tx.send(chunk).await.is_err() {
+            warn!("Failed to send output chunk - receiver dropped");
+        }
+    }
+
     async fn emit_event(&self, event: RunEvent) {
         if self.event_tx.send(event).await.is_err() {
             warn!("Failed to send event - receiver dropped");
@@ -91,7 +230,24 @@ impl ControlHandler for StreamingHandler {
         tool_name: String,
         input: Value,
     ) -> Result<PermissionResult, SdkError> {
-        info!(tool = %tool_name, "Auto-approving tool use");
+        let outcome = if let Some(gate) = self.approvals.as_ref().filter(|g| g.asks(&tool_name)) {
+            Some(gate.request(&tool_name, &input).await)
+        } else if let Some(prompter) = &self.prompter {
+            Some(prompter.request(&tool_name, &input).await)
+        } else {
+            None
+        };
+        match outcome {
+            Some(outcome) if !outcome.allowed => {
+                info!(tool = %tool_name, reason = %outcome.reason, "Tool use denied");
+                return Ok(PermissionResult::Deny {
+                    message: format!("Tool use denied: {}", outcome.reason),
+                    interrupt: None,
+                });
+            }
+            Some(_) => info!(tool = %tool_name, "Tool use approved"),
+            None => info!(tool = %tool_name, "Auto-approving tool use"),
+        }
         Ok(PermissionResult::Allow {
             updated_input: input,
             updated_permissions: None,
@@ -101,9 +257,22 @@ impl ControlHandler for StreamingHandler {
     async fn on_hook_callback(
         &self,
         callback_id: String,
-        _input: Value,
+        input: Value,
         _tool_use_id: Option<String>,
     ) -> Result<Value, SdkError> {
+        if let Some(scope) = self
+            .scope
+            .as_ref()
+            .filter(|_| callback_id == PRE_TOOL_USE_HOOK)
+        {
+            let tool_name = input["tool_name"].as_str().unwrap_or_default();
+            let output = scope.hook_output(tool_name, &input["tool_input"]);
+            if let Some(reason) = output["hookSpecificOutput"]["permissionDecisionReason"].as_str()
+            {
+                info!(tool = %tool_name, reason = %reason, "Tool use outside allowed paths denied");
+            }
+            return Ok(output);
+        }
         info!(callback = %callback_id, "Auto-approving hook callback");
         Ok(json!({
             "hookSpecificOutput": {
@@ -162,19 +331,27 @@ impl ControlHandler for StreamingHandler {
                     "Assistant message received"
                 );
                 for content in message.content {
-                    if let ContentItem::Text { text } = content {
-                        debug!(text_len = text.len(), "Streaming assistant text chunk");
-                        let chunk = OutputChunk {
-                            content: text,
-                            is_final: false,
-                        };
-                        if self.output_tx.send(chunk).await.is_err() {
-                            warn!("Failed to send output chunk - receiver dropped");
+                    match content {
+                        ContentItem::Text { text } => {
+                            debug!(text_len = text.len(), "Streaming assistant text chunk");
+                            self.send_output(text).await;
+                        }
+                        ContentItem::Thinking { thinking, .. } if self.thinking.is_some() => {
+                            self.send_output(ThinkingStream::block(&thinking)).await;
+                        }
+                        ContentItem::ToolUse { tool_data, .. } => {
+                            self.artifacts.lock().unwrap().on_tool_use(&tool_data);
+                            self.results.lock().unwrap().on_tool_use(&tool_data);
                         }
+                        _ => {}
                     }
                 }
             }
             ClaudeMessage::StreamEvent { event, .. } => {
+                if let Some(text) = self.thinking.as_ref().and_then(|t| t.on_event(&event)) {
+                    self.send_output(text).await;
+                }
+
                 // Handle streaming delta events for real-time token output
                 if let StreamEvent::ContentBlockDelta {
                     delta: ContentDelta::TextDelta { text },
@@ -186,65 +363,110 @@ impl ControlHandler for StreamingHandler {
                         block_index = index,
                         "Streaming text delta"
                     );
-                    let chunk = OutputChunk {
-                        content: text,
-                        is_final: false,
-                    };
-                    if self.output_tx.send(chunk).await.is_err() {
-                        warn!("Failed to send output chunk - receiver dropped");
-                    }
+                    self.send_output(text).await;
                 }
             }
             ClaudeMessage::Result {
                 is_error,
                 duration_ms,
+                total_cost_usd,
+                usage,
+                result,
                 error,
+                subtype,
+                permission_denials,
                 ..
             } => {
                 info!(
                     is_error = ?is_error,
                     duration_ms = ?duration_ms,
+                    subtype = ?subtype,
                     "Execution result received"
                 );
+                self.results
+                    .lock()
+                    .unwrap()
+                    .on_result(result.as_ref(), usage.as_ref());
+                let with_usage = |event: RunEvent| {
+                    let event = event.with_cost_usd(total_cost_usd);
+                    match usage {
+                        Some(usage) => {
+                            event.with_token_usage(usage.total_input_tokens(), usage.output_tokens)
+                        }
+                        None => event,
+                    }
+                };
 
                 // Emit ExecutionCompleted or ExecutionFailed event
                 if is_error == Some(true) {
-                    self.emit_event(RunEvent::execution_failed(
+                    let reason = failure::result_failure_reason(
+                        subtype.as_deref(),
+                        permission_denials.len(),
+                    );
+                    let message = error
+                        .clone()
+                        .or(subtype)
+                        .unwrap_or_else(|| "Claude reported an error".to_string());
+                    *self.failure.lock().unwrap() = Some(ExecutorError::Failed { reason, message });
+
+                    self.emit_event(with_usage(RunEvent::execution_failed(
                         self.run_id.clone(),
                         self.task_id.clone(),
                         error,
-                    ))
+                    )))
                     .await;
                 } else {
-                    self.emit_event(RunEvent::execution_completed(
+                    self.emit_event(with_usage(RunEvent::execution_completed(
                         self.run_id.clone(),
                         self.task_id.clone(),
                         duration_ms.map(|d| d as i64),
-                    ))
+                    )))
                     .await;
                 }
             }
-            ClaudeMessage::ToolUse { tool_name, .. } => {
+            ClaudeMessage::ToolUse {
+                tool_name,
+                tool_data,
+                ..
+            } => {
                 debug!(tool = %tool_name, "Tool use message");
+                self.artifacts.lock().unwrap().on_tool_use(&tool_data);
+                self.results.lock().unwrap().on_tool_use(&tool_data);
 
                 // Emit ToolRequested event
-                self.emit_event(RunEvent::tool_requested(
+                let mut event = RunEvent::tool_requested(
                     self.run_id.clone(),
                     self.task_id.clone(),
                     &tool_name,
-                ))
-                .await;
+                    tool_data.summary(),
+                );
+                let input = tool_data.input();
+                tool_payload::attach_input(&mut event, &input);
+                // The diff rides along with the call until it succeeds
+                let mut call = event.clone();
+                if let Some(diff) = file_diff::for_tool(&tool_name, &input) {
+                    tool_payload::attach_diff(&mut call, diff);
+                }
+                self.tool_calls.lock().unwrap().push_back(call);
+                self.emit_event(event).await;
             }
-            ClaudeMessage::ToolResult { is_error, .. } => {
+            ClaudeMessage::ToolResult {
+                result, is_error, ..
+            } => {
                 debug!(is_error = ?is_error, "Tool result message");
 
-                // Emit ToolCompleted event
-                self.emit_event(RunEvent::tool_completed(
-                    self.run_id.clone(),
-                    self.task_id.clone(),
-                    is_error.unwrap_or(false),
-                ))
-                .await;
+                // Emit ToolCompleted event with any files the tool produced
+                let is_error = is_error.unwrap_or(false);
+                let artifacts = self.artifacts.lock().unwrap().on_tool_result(is_error);
+                let mut event =
+                    RunEvent::tool_completed(self.run_id.clone(), self.task_id.clone(), is_error)
+                        .with_artifacts(artifacts);
+                let requested = self.tool_calls.lock().unwrap().pop_front();
+                if let Some(requested) = &requested {
+                    event = event.for_call(requested);
+                }
+                tool_payload::attach_output(&mut event, requested.as_ref(), &result);
+                self.emit_event(event).await;
             }
             ClaudeMessage::Unknown(ref value) => {
                 // Log the full unknown message for debugging
@@ -267,6 +489,10 @@ impl ControlHandler for StreamingHandler {
         }
         Ok(())
     }
+
+    fn wants_thinking(&self) -> bool {
+        self.thinking.is_some()
+    }
 }
 
 /// Executes agents via Claude Code SDK.
@@ -274,35 +500,205 @@ impl ControlHandler for StreamingHandler {
 pub struct ClaudeCodeExecutor {
     /// Worker configuration including claude path and tool permissions.
     config: Arc<ConnectionConfig>,
+    /// Concurrency limits for interactive and batch runs.
+    lanes: RunLanes,
+    /// Claude CLI version, recorded in run manifests.
+    claude_cli_version: Option<String>,
+    /// Feature flags, recorded in run manifests.
+    feature_flags: BTreeMap<String, String>,
+    /// Tool approvals waiting for the control plane.
+    approvals: PendingApprovals,
+    /// The report_progress tool given to runs.
+    progress: ProgressTool,
 }
 
 impl ClaudeCodeExecutor {
     /// Create a new executor with the given configuration.
     pub fn new(config: Arc<ConnectionConfig>) -> Self {
-        Self { config }
+        let lanes = RunLanes::new(config.max_concurrent_runs, config.interactive_slots);
+        let feature_flags = BTreeMap::from([
+            (
+                "permission_mode".to_string(),
+                if config.approval_policy.is_some() {
+                    "ask"
+                } else if config.skip_permissions {
+                    "bypass"
+                } else if config.tool_prompts.is_some() {
+                    "prompt"
+                } else {
+                    "default"
+                }
+                .to_string(),
+            ),
+            (
+                "thinking".to_string(),
+                if config.include_thinking { "on" } else { "off" }.to_string(),
+            ),
+            (
+                "output_filters".to_string(),
+                if config.output_filters.is_some() {
+                    "on"
+                } else {
+                    "off"
+                }
+                .to_string(),
+            ),
+            (
+                "progress_tool".to_string(),
+                if config.progress_tool { "on" } else { "off" }.to_string(),
+            ),
+        ]);
+        let claude_cli_version = manifest::detect_claude_version(&config.claude_path);
+        let progress = ProgressTool::new(config.progress_tool);
+        Self {
+            config,
+            lanes,
+            claude_cli_version,
+            feature_flags,
+            approvals: PendingApprovals::new(),
+            progress,
+        }
+    }
+
+    /// Change the number of batch slots, e.g. after `max_concurrent_runs`
+    /// changed.
+    pub fn resize_lanes(&self, max_concurrent_runs: u32) {
+        self.lanes.resize_batch(max_concurrent_runs);
+    }
+
+    /// Wait for an execution slot in the lane for `priority`.
+    pub async fn acquire_lane(&self, priority: TaskPriority) -> OwnedSemaphorePermit {
+        self.lanes.acquire(priority).await
+    }
+
+    /// Manifest for an assigned run, from its agent's settings and its
+    /// pinned manifest if any.
+    pub fn run_manifest(&self, assignment: &RunAssignment) -> RunManifest {
+        let agent = self.config.agent(&assignment.agent_name);
+        let base = manifest::base_manifest(
+            &agent.spec(),
+            agent.template().as_deref(),
+            agent.allowed_tools.clone(),
+            agent.denied_tools.clone(),
+            self.claude_cli_version.clone(),
+            self.feature_flags.clone(),
+        );
+        manifest::run_manifest(
+            &base,
+            &assignment.input_json,
+            assignment.pinned_manifest.clone().map(Into::into),
+        )
+    }
+
+    /// Directory runs execute in.
+    pub fn working_dir(&self) -> &Path {
+        Path::new(&self.config.working_dir)
+    }
+
+    /// Output filter for a new run of `agent_name`.
+    pub fn output_filter(&self, agent_name: &str) -> RunOutputFilter {
+        RunOutputFilter::new(self.config.output_filters.as_deref(), agent_name)
+    }
+
+    /// Approval gate for a run, if the worker has an approval policy.
+    pub fn approval_gate(
+        &self,
+        tx: &mpsc::Sender<RunClientMessage>,
+        run_id: &str,
+    ) -> Option<ApprovalGate> {
+        let policy = self.config.approval_policy.clone()?;
+        Some(ApprovalGate::new(
+            policy,
+            self.approvals.clone(),
+            tx.clone(),
+            run_id,
+        ))
     }
 
-    /// Execute an agent with the given input, streaming output and events via channels.
+    /// Deliver an operator's decision to the run waiting on it.
+    pub fn decide_approval(&self, decision: ToolApprovalDecision) -> bool {
+        self.approvals.decide(decision)
+    }
+
+    /// Deliver a decision made in the TUI to the run waiting on it.
+    pub fn decide_tool_prompt(&self, prompt_id: &str, decision: ToolDecision) -> bool {
+        self.config
+            .tool_prompts
+            .as_ref()
+            .is_some_and(|prompts| prompts.decide(prompt_id, decision))
+    }
+
+    /// Prompter for the tool uses of a run of `agent_name`, unless tool
+    /// uses are not prompted in the TUI.
+    fn tool_prompter(&self, run_id: &RunId, agent_name: &str) -> Option<ToolPrompter> {
+        self.config
+            .tool_prompts
+            .as_ref()
+            .map(|prompts| prompts.for_run(run_id.as_str(), agent_name))
+    }
+
+    /// SDK executor for one execution of `agent` under its task's
+    /// `limits`, with the agent's secrets in its environment (their values
+    /// added to `redactor`). With an approval gate, Claude asks before tool
+    /// uses so the gate can escalate the ones its policy names; with TUI
+    /// prompts, so the operator can answer.
+    async fn sdk_executor(
+        &self,
+        agent: &AgentProfile,
+        limits: &RunLimits,
+        approvals: bool,
+        redactor: &Redactor,
+    ) -> Result<ClaudeExecutor, ExecutorError> {
+        let permission_mode = if self.config.skip_permissions && !approvals {
+            PermissionMode::BypassPermissions
+        } else {
+            PermissionMode::Default
+        };
+        let executor = ClaudeExecutor::new(&self.config.claude_path)
+            .with_permission_mode(permission_mode)
+            .with_permission_prompts(approvals || self.config.tool_prompts.is_some())
+            .with_resource_limits(resource_limits::with_run_limits(
+                self.config.resource_limits.clone(),
+                limits,
+            ));
+        Ok(self
+            .config
+            .secrets
+            .apply(executor, &agent.secrets, redactor)
+            .await?)
+    }
+
+    /// Execute an agent with the given input under the task's `limits`,
+    /// streaming output and events via channels. The agent's secret values
+    /// are added to `redactor`.
     ///
     /// Returns when execution completes (successfully or with error).
+    #[allow(clippy::too_many_arguments)]
     pub async fn execute(
         &self,
-        agent_name: &str,
         input_json: &str,
+        manifest: &RunManifest,
+        limits: &RunLimits,
         output_tx: mpsc::Sender<OutputChunk>,
         event_tx: mpsc::Sender<RunEvent>,
         run_id: RunId,
         task_id: TaskId,
+        approvals: Option<ApprovalGate>,
+        redactor: &Redactor,
     ) -> Result<ExecutionResult, ExecutorError> {
+        let agent_name = manifest.agent_name.as_str();
         info!(
             agent = %agent_name,
             claude_path = %self.config.claude_path,
             input_len = input_json.len(),
-            allowed_tools = ?self.config.allowed_tools,
-            denied_tools = ?self.config.denied_tools,
+            model = %manifest.model_name,
+            allowed_tools = ?manifest.allowed_tools,
+            denied_tools = ?manifest.denied_tools,
             "Starting agent execution"
         );
 
+        let started = Instant::now();
+
         // Emit ExecutionStarted event
         if event_tx
             .send(RunEvent::execution_started(run_id.clone(), task_id.clone()))
@@ -312,7 +708,11 @@ impl ClaudeCodeExecutor {
             warn!("Failed to send ExecutionStarted event");
         }
 
-        // Build the prompt based on agent type
+        // Check the input and build the prompt from the agent's definition
+        let agent = self.config.agent(agent_name);
+        agent
+            .check_input(input_json)
+            .map_err(|e| ExecutorError::InvalidInput(agent_name.to_string(), e))?;
         let prompt = self.build_prompt(agent_name, input_json);
         info!(prompt_len = prompt.len(), "Built prompt for agent");
 
@@ -320,38 +720,67 @@ impl ClaudeCodeExecutor {
         debug!(prompt = %prompt, "Full prompt");
 
         // Create SDK executor with permission mode based on config
-        let permission_mode = if self.config.skip_permissions {
-            PermissionMode::BypassPermissions
-        } else {
-            PermissionMode::Default
-        };
-        let mut sdk_executor =
-            ClaudeExecutor::new(&self.config.claude_path).with_permission_mode(permission_mode);
-
-        // Apply tool permissions from config
-        if let Some(ref allowed) = self.config.allowed_tools {
+        let mut sdk_executor = self
+            .sdk_executor(&agent, limits, approvals.is_some(), redactor)
+            .await?;
+
+        // Apply the agent's system prompt, and the model and tool permissions
+        // recorded in the manifest
+        if let Some(ref system_prompt) = agent.system_prompt {
+            sdk_executor = sdk_executor.with_system_prompt(system_prompt);
+        }
+        if !manifest.model_name.is_empty() {
+            sdk_executor = sdk_executor.with_model(&manifest.model_name);
+        }
+        let (scope, allowed_tools) = path_scope::scoped_tools(
+            &agent,
+            self.working_dir(),
+            manifest.allowed_tools.as_deref(),
+        );
+        if let Some(ref allowed) = allowed_tools {
             sdk_executor = sdk_executor.with_allowed_tools(allowed.clone());
             info!(allowed_tools = ?allowed, "Applying allowed tools filter");
         }
-        if let Some(ref denied) = self.config.denied_tools {
+        if let Some(ref denied) = manifest.denied_tools {
             sdk_executor = sdk_executor.with_disallowed_tools(denied.clone());
             info!(denied_tools = ?denied, "Applying denied tools filter");
         }
+        if scope.is_some() {
+            sdk_executor = sdk_executor.with_pre_tool_use_hook(path_scope::HOOK_MATCHER);
+            info!(allowed_paths = ?agent.allowed_paths, "Confining tools to allowed paths");
+        }
+
+        // Let the agent report milestones for the rest of the run
+        let progress = self
+            .progress
+            .register(run_id.clone(), task_id.clone(), event_tx.clone())
+            .await;
+        if let Some(ref progress) = progress {
+            sdk_executor = progress.apply(sdk_executor, allowed_tools.as_deref());
+        }
 
         // Create streaming handler with event support
+        let prompter = self.tool_prompter(&run_id, &agent.name);
         let handler = Arc::new(StreamingHandler::new(
             output_tx.clone(),
             event_tx,
             run_id,
             task_id,
+            self.working_dir(),
+            approvals,
+            prompter,
+            scope,
+            self.config.include_thinking,
         ));
 
         // Execute via SDK in the configured working directory
         let working_dir = Path::new(&self.config.working_dir);
         let result = sdk_executor
             .execute(working_dir, &prompt, handler.clone())
-            .await
-            .map_err(|e| ExecutorError::SdkError(e.to_string()))?;
+            .await?;
+        if let Some(failure) = handler.take_failure() {
+            return Err(failure);
+        }
 
         // Send final marker
         let _ = output_tx
@@ -362,6 +791,14 @@ impl ClaudeCodeExecutor {
             .await;
 
         let session_id = handler.session_id();
+        let artifacts = handler.finish_artifacts().await;
+        handler
+            .finish_summary(
+                working_dir,
+                started.elapsed(),
+                self.output_filter(&agent.name),
+            )
+            .await;
         // Use the real model from Claude's System message, fallback to SDK's placeholder
         let model_used = handler.model_used().unwrap_or(result.model_used);
         info!(
@@ -374,12 +811,15 @@ impl ClaudeCodeExecutor {
             model_used,
             provider: "anthropic".to_string(),
             session_id,
+            artifacts,
         })
     }
 
-    /// Execute a follow-up message in an existing session.
+    /// Execute a follow-up message in an existing session. The agent's
+    /// secret values are added to `redactor`.
     ///
     /// Uses --resume <session_id> to continue the conversation.
+    #[allow(clippy::too_many_arguments)]
     pub async fn execute_follow_up(
         &self,
         session_id: &str,
@@ -388,6 +828,8 @@ impl ClaudeCodeExecutor {
         event_tx: mpsc::Sender<RunEvent>,
         run_id: RunId,
         task_id: TaskId,
+        approvals: Option<ApprovalGate>,
+        redactor: &Redactor,
     ) -> Result<ExecutionResult, ExecutorError> {
         info!(
             session_id = %session_id,
@@ -395,6 +837,8 @@ impl ClaudeCodeExecutor {
             "Starting session continuation"
         );
 
+        let started = Instant::now();
+
         // Emit ExecutionStarted event
         if event_tx
             .send(RunEvent::execution_started(run_id.clone(), task_id.clone()))
@@ -405,36 +849,58 @@ impl ClaudeCodeExecutor {
         }
 
         // Create SDK executor with permission mode based on config
-        let permission_mode = if self.config.skip_permissions {
-            PermissionMode::BypassPermissions
-        } else {
-            PermissionMode::Default
-        };
-        let mut sdk_executor =
-            ClaudeExecutor::new(&self.config.claude_path).with_permission_mode(permission_mode);
-
-        // Apply tool permissions from config
-        if let Some(ref allowed) = self.config.allowed_tools {
+        let agent = self.config.agent(&self.config.agent_name);
+        let mut sdk_executor = self
+            .sdk_executor(&agent, &RunLimits::default(), approvals.is_some(), redactor)
+            .await?;
+
+        // Apply the agent's system prompt and tool permissions
+        if let Some(ref system_prompt) = agent.system_prompt {
+            sdk_executor = sdk_executor.with_system_prompt(system_prompt);
+        }
+        let (scope, allowed_tools) =
+            path_scope::scoped_tools(&agent, self.working_dir(), agent.allowed_tools.as_deref());
+        if let Some(ref allowed) = allowed_tools {
             sdk_executor = sdk_executor.with_allowed_tools(allowed.clone());
         }
-        if let Some(ref denied) = self.config.denied_tools {
+        if let Some(ref denied) = agent.denied_tools {
             sdk_executor = sdk_executor.with_disallowed_tools(denied.clone());
         }
+        if scope.is_some() {
+            sdk_executor = sdk_executor.with_pre_tool_use_hook(path_scope::HOOK_MATCHER);
+        }
+
+        // Let the agent report milestones for the rest of the run
+        let progress = self
+            .progress
+            .register(run_id.clone(), task_id.clone(), event_tx.clone())
+            .await;
+        if let Some(ref progress) = progress {
+            sdk_executor = progress.apply(sdk_executor, allowed_tools.as_deref());
+        }
 
         // Create streaming handler
+        let prompter = self.tool_prompter(&run_id, &agent.name);
         let handler = Arc::new(StreamingHandler::new(
             output_tx.clone(),
             event_tx,
             run_id,
             task_id,
+            self.working_dir(),
+            approvals,
+            prompter,
+            scope,
+            self.config.include_thinking,
         ));
 
         // Execute follow-up via SDK
         let working_dir = Path::new(&self.config.working_dir);
         let result = sdk_executor
             .execute_follow_up(working_dir, message, session_id, handler.clone())
-            .await
-            .map_err(|e| ExecutorError::SdkError(e.to_string()))?;
+            .await?;
+        if let Some(failure) = handler.take_failure() {
+            return Err(failure);
+        }
 
         // Send final marker
         let _ = output_tx
@@ -445,6 +911,14 @@ impl ClaudeCodeExecutor {
             .await;
 
         let new_session_id = handler.session_id();
+        let artifacts = handler.finish_artifacts().await;
+        handler
+            .finish_summary(
+                working_dir,
+                started.elapsed(),
+                self.output_filter(&agent.name),
+            )
+            .await;
         let model_used = handler.model_used().unwrap_or(result.model_used);
         info!(
             session_id = ?new_session_id,
@@ -456,20 +930,15 @@ impl ClaudeCodeExecutor {
             model_used,
             provider: "anthropic".to_string(),
             session_id: new_session_id,
+            artifacts,
         })
     }
 
     /// Build the prompt for a given agent and input.
-    /// Any agent name is accepted - the input is passed directly to Claude.
-    fn build_prompt(&self, _agent_name: &str, input_json: &str) -> String {
-        // Try to parse as JSON to extract "task" field, otherwise use as-is
-        if let Ok(parsed) = serde_json::from_str::<Value>(input_json) {
-            if let Some(task) = parsed.get("task").and_then(|t| t.as_str()) {
-                return task.to_string();
-            }
-        }
-        // If not JSON or no "task" field, use input directly
-        input_json.to_string()
+    /// Any agent name is accepted - agents without a prompt template get the
+    /// input passed directly to Claude.
+    fn build_prompt(&self, agent_name: &str, input_json: &str) -> String {
+        self.config.agent(agent_name).prompt(input_json)
     }
 }
 
@@ -483,4 +952,6 @@ pub struct ExecutionResult {
     pub provider: String,
     /// The session ID for continuation (if available).
     pub session_id: Option<String>,
+    /// Files produced during the execution.
+    pub artifacts: Vec<Artifact>,
 }
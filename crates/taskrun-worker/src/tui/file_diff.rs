@@ -0,0 +1,265 @@
+//! Unified diffs of the file edits a run makes.
+//!
+//! When Claude asks for an Edit, MultiEdit or Write call, the TUI's executor
+//! applies the call's input to the file as it is on disk at that moment and
+//! diffs the two, so the operator can review the run's changes in the run
+//! detail view's Diff pane. If the edit no longer applies (the file changed,
+//! or the edit already happened) the diff falls back to the edited snippets.
+
+use std::fmt::Write as _;
+
+use serde_json::Value;
+
+/// Unchanged lines shown around each change.
+const CONTEXT_LINES: usize = 3;
+
+/// Largest changed region, in old lines times new lines, diffed line by
+/// line; larger ones are shown as removed and then added.
+const MAX_LCS_CELLS: usize = 1 << 22;
+
+/// Unified diff of the file change a tool call makes; None for tools that
+/// don't edit files, and for calls that change nothing.
+pub fn for_tool(tool_name: &str, input: &Value) -> Option<String> {
+    let path = input
+        .get("file_path")
+        .or_else(|| input.get("path"))
+        .and_then(Value::as_str)?;
+    let current = std::fs::read_to_string(path).ok();
+
+    let (before, after) = match tool_name {
+        "Write" => (current, input.get("content")?.as_str()?.to_string()),
+        "Edit" => edited(current, &[edit(input)?]),
+        "MultiEdit" => {
+            let edits = input.get("edits")?.as_array()?;
+            edited(
+                current,
+                &edits.iter().map(edit).collect::<Option<Vec<_>>>()?,
+            )
+        }
+        _ => return None,
+    };
+    unified(path, before.as_deref(), &after)
+}
+
+/// One replacement of an Edit or MultiEdit call.
+struct Edit<'a> {
+    old: &'a str,
+    new: &'a str,
+    all: bool,
+}
+
+fn edit(input: &Value) -> Option<Edit<'_>> {
+    let text = |key: &str, alias: &str| {
+        input
+            .get(key)
+            .or_else(|| input.get(alias))
+            .and_then(Value::as_str)
+    };
+    Some(Edit {
+        old: text("old_string", "old_str").unwrap_or_default(),
+        new: text("new_string", "new_str")?,
+        all: input.get("replace_all").and_then(Value::as_bool) == Some(true),
+    })
+}
+
+/// File contents before and after `edits`.
+fn edited(current: Option<String>, edits: &[Edit]) -> (Option<String>, String) {
+    let Some(current) = current else {
+        return (None, snippets(edits, |e| e.new));
+    };
+    if let Some(after) = apply(&current, edits) {
+        return (Some(current), after);
+    }
+    // Already applied: undo the edits to recover the old contents
+    let undo: Vec<_> = edits
+        .iter()
+        .rev()
+        .map(|e| Edit {
+            old: e.new,
+            new: e.old,
+            all: e.all,
+        })
+        .collect();
+    match apply(&current, &undo) {
+        Some(before) => (Some(before), current),
+        None => (Some(snippets(edits, |e| e.old)), snippets(edits, |e| e.new)),
+    }
+}
+
+fn apply(text: &str, edits: &[Edit]) -> Option<String> {
+    edits.iter().try_fold(text.to_string(), |text, e| {
+        if e.old.is_empty() || !text.contains(e.old) {
+            return None;
+        }
+        Some(if e.all {
+            text.replace(e.old, e.new)
+        } else {
+            text.replacen(e.old, e.new, 1)
+        })
+    })
+}
+
+fn snippets<'a>(edits: &[Edit<'a>], side: impl Fn(&Edit<'a>) -> &'a str) -> String {
+    edits.iter().map(side).collect::<Vec<_>>().join("\n")
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Same,
+    Removed,
+    Added,
+}
+
+/// Unified diff of `path` from `before` (None for a new file) to `after`;
+/// None if they are the same.
+fn unified(path: &str, before: Option<&str>, after: &str) -> Option<String> {
+    let old: Vec<&str> = before.unwrap_or_default().lines().collect();
+    let new: Vec<&str> = after.lines().collect();
+    let ops = line_ops(&old, &new);
+
+    // Changed lines with their context, merged where they overlap
+    let mut hunks: Vec<(usize, usize)> = Vec::new();
+    for (i, _) in ops
+        .iter()
+        .enumerate()
+        .filter(|(_, (op, _))| *op != Op::Same)
+    {
+        let start = i.saturating_sub(CONTEXT_LINES);
+        let end = (i + 1 + CONTEXT_LINES).min(ops.len());
+        match hunks.last_mut() {
+            Some(last) if start <= last.1 => last.1 = end,
+            _ => hunks.push((start, end)),
+        }
+    }
+    if hunks.is_empty() {
+        return None;
+    }
+
+    let mut diff = format!(
+        "--- {}\n+++ {}\n",
+        if before.is_some() { path } else { "/dev/null" },
+        path
+    );
+    // Lines of the old (without additions) or new (without removals) file
+    let lines =
+        |ops: &[(Op, &str)], without: Op| ops.iter().filter(|(op, _)| *op != without).count();
+    for (start, end) in hunks {
+        let (before, hunk) = (&ops[..start], &ops[start..end]);
+        let _ = writeln!(
+            diff,
+            "@@ -{} +{} @@",
+            range(lines(before, Op::Added), lines(hunk, Op::Added)),
+            range(lines(before, Op::Removed), lines(hunk, Op::Removed)),
+        );
+        for (op, line) in &ops[start..end] {
+            let marker = match op {
+                Op::Same => ' ',
+                Op::Removed => '-',
+                Op::Added => '+',
+            };
+            let _ = writeln!(diff, "{marker}{line}");
+        }
+    }
+    Some(diff)
+}
+
+/// Hunk range of `len` lines after the first `skipped`.
+fn range(skipped: usize, len: usize) -> String {
+    match len {
+        0 => format!("{skipped},0"),
+        1 => format!("{}", skipped + 1),
+        _ => format!("{},{}", skipped + 1, len),
+    }
+}
+
+/// Line-by-line edit script from `old` to `new`, by longest common
+/// subsequence of the region between their common prefix and suffix.
+fn line_ops<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<(Op, &'a str)> {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let old_mid = &old[prefix..old.len() - suffix];
+    let new_mid = &new[prefix..new.len() - suffix];
+
+    let mut ops: Vec<(Op, &str)> = old[..prefix].iter().map(|l| (Op::Same, *l)).collect();
+    let (n, m) = (old_mid.len(), new_mid.len());
+    if n.saturating_mul(m) > MAX_LCS_CELLS {
+        ops.extend(old_mid.iter().map(|l| (Op::Removed, *l)));
+        ops.extend(new_mid.iter().map(|l| (Op::Added, *l)));
+    } else {
+        // lcs[i * (m + 1) + j]: common subsequence length of old_mid[i..]
+        // and new_mid[j..]
+        let at = |i: usize, j: usize| i * (m + 1) + j;
+        let mut lcs = vec![0u32; (n + 1) * (m + 1)];
+        for i in (0..n).rev() {
+            for j in (0..m).rev() {
+                lcs[at(i, j)] = if old_mid[i] == new_mid[j] {
+                    lcs[at(i + 1, j + 1)] + 1
+                } else {
+                    lcs[at(i + 1, j)].max(lcs[at(i, j + 1)])
+                };
+            }
+        }
+        let (mut i, mut j) = (0, 0);
+        while i < n && j < m {
+            if old_mid[i] == new_mid[j] {
+                ops.push((Op::Same, old_mid[i]));
+                i += 1;
+                j += 1;
+            } else if lcs[at(i + 1, j)] >= lcs[at(i, j + 1)] {
+                ops.push((Op::Removed, old_mid[i]));
+                i += 1;
+            } else {
+                ops.push((Op::Added, new_mid[j]));
+                j += 1;
+            }
+        }
+        ops.extend(old_mid[i..].iter().map(|l| (Op::Removed, *l)));
+        ops.extend(new_mid[j..].iter().map(|l| (Op::Added, *l)));
+    }
+    ops.extend(old[old.len() - suffix..].iter().map(|l| (Op::Same, *l)));
+    ops
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_edit_is_diffed_against_the_file_before_and_after_it_applies() {
+        let dir = std::env::temp_dir().join(format!("taskrun-file-diff-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("lib.rs");
+        let lines: Vec<String> = (1..=10).map(|i| format!("line {i}")).collect();
+        std::fs::write(&path, lines.join("\n")).unwrap();
+        let path = path.to_str().unwrap();
+
+        let input =
+            json!({"file_path": path, "old_string": "line 5", "new_string": "five\nfive b"});
+        let expected = format!(
+            "--- {path}\n+++ {path}\n@@ -2,7 +2,8 @@\n line 2\n line 3\n line 4\n-line 5\n+five\n+five b\n line 6\n line 7\n line 8\n"
+        );
+        assert_eq!(for_tool("Edit", &input).unwrap(), expected);
+
+        // Once the edit happened, the same diff is recovered by undoing it
+        std::fs::write(path, lines.join("\n").replace("line 5", "five\nfive b")).unwrap();
+        assert_eq!(for_tool("Edit", &input).unwrap(), expected);
+
+        let created = json!({"file_path": dir.join("new.rs"), "content": "fn main() {}\n"});
+        assert_eq!(
+            for_tool("Write", &created).unwrap(),
+            format!(
+                "--- /dev/null\n+++ {0}\n@@ -0,0 +1 @@\n+fn main() {{}}\n",
+                dir.join("new.rs").display()
+            )
+        );
+        assert!(for_tool("Bash", &json!({"command": "ls"})).is_none());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
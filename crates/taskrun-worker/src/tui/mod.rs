@@ -7,9 +7,12 @@ mod backend;
 mod connection;
 mod event;
 mod executor;
+mod file_diff;
 mod render;
 mod setup;
 mod state;
+mod tool_payload;
+mod tool_prompts;
 
 pub use app::run_worker_tui;
 pub use state::WorkerConfig;
@@ -1,19 +1,22 @@
 //! UI rendering for the worker TUI.
 
-use ratatui::layout::{Constraint, Direction, Layout, Rect};
-use ratatui::style::{Color, Style};
+use ratatui::layout::{Alignment, Constraint, Direction, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
 use ratatui::text::{Line, Span};
-use ratatui::widgets::{Block, Borders, Paragraph};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph, Wrap};
 use ratatui::Frame;
 
+use taskrun_core::RunResultSummary;
 use taskrun_tui_components::{
-    ConfirmDialog, DataTable, DetailPane as SharedDetailPane, Footer, Header, HeaderStat,
-    InputDialog, LogsWidget, MessageRole, RunDetailInfo, RunDetailStatus, RunDetailView, RunEvent,
-    RunMessage, StatusIndicator, TableCell, TableColumn, TableRow,
+    centered_rect, ConfirmDialog, DataTable, DetailPane as SharedDetailPane, Footer,
+    GroupProgressList, Header, HeaderStat, InputDialog, LogsWidget, MessageRole, RunDetailInfo,
+    RunDetailMilestone, RunDetailStatus, RunDetailView, RunEvent, RunMessage, StatusIndicator,
+    TableCell, TableColumn, TableRow, Theme, TimeFormat,
 };
 
 use super::state::{
-    ChatRole, ConnectionState, DetailPane, RunInfo, RunStatus, WorkerUiState, WorkerView,
+    ChatRole, ConnectionState, DetailPane, RunEventInfo, RunInfo, RunStatus, ToolPrompt,
+    WorkerUiState, WorkerView,
 };
 
 /// Main render function for the worker TUI.
@@ -38,6 +41,15 @@ pub fn render(frame: &mut Frame, state: &WorkerUiState) {
     if state.show_new_run_dialog {
         render_new_run_dialog(frame, state);
     }
+    if let Some((event, scroll)) = &state.inspecting_event {
+        render_event_inspector(frame, event, *scroll, state.time_format);
+    }
+    if let Some((prompt, pending)) = state.viewing_tool_prompt() {
+        render_tool_prompt_dialog(frame, prompt, pending);
+    }
+    if let Some(run_id) = &state.cancel_confirm {
+        render_cancel_confirm(frame, run_id);
+    }
 }
 
 /// Render the header with tabs and stats.
@@ -45,9 +57,11 @@ fn render_header(frame: &mut Frame, area: Rect, state: &WorkerUiState) {
     let status = match &state.connection_state {
         ConnectionState::Connecting => StatusIndicator::warning("Connecting..."),
         ConnectionState::Connected => StatusIndicator::success("Connected"),
-        ConnectionState::Disconnected { retry_in } => {
-            StatusIndicator::error(format!("Retry {}s", retry_in.as_secs()))
-        }
+        ConnectionState::Disconnected {
+            retry_in: Some(retry_in),
+            ..
+        } => StatusIndicator::error(format!("Retry {:.1}s", retry_in.as_secs_f64())),
+        ConnectionState::Disconnected { retry_in: None, .. } => StatusIndicator::error("Gave up"),
     };
 
     let tabs: Vec<&str> = WorkerView::all().iter().map(|v| v.name()).collect();
@@ -99,10 +113,16 @@ fn render_main_content(frame: &mut Frame, area: Rect, state: &WorkerUiState) {
 fn render_footer(frame: &mut Frame, area: Rect, state: &WorkerUiState) {
     let help_text = match state.current_view {
         WorkerView::Status => "Tab: Next view | q: Quit",
-        WorkerView::Runs => "j/k: Navigate | n: New | Enter: Details | Tab: Next view | q: Quit",
-        WorkerView::RunDetail => "j/k: Scroll | Tab: Switch pane | g/G: Top/Bottom | Esc: Back",
-        WorkerView::Logs => "j/k: Scroll | g/G: Top/Bottom | Tab: Next view | q: Quit",
-        WorkerView::Config => "Tab: Next view | q: Quit",
+        WorkerView::Runs => {
+            "j/k: Navigate | n: New | c: Cancel | Enter: Details | T: Relative times | Tab: Next view | q: Quit"
+        }
+        WorkerView::RunDetail => {
+            "j/k: Scroll | Tab: Switch pane (chat, events, diff) | Enter: Inspect event | g/G: Top/Bottom | o: Open attachment | m: Raw/markdown | c: Cancel | Esc: Back"
+        }
+        WorkerView::Logs => {
+            "j/k: Scroll | g/G: Top/Bottom | T: Relative times | Tab: Next view | q: Quit"
+        }
+        WorkerView::Config => "+/-: Max concurrent runs | Tab: Next view | q: Quit",
     };
 
     Footer::new(help_text).render(frame, area);
@@ -215,13 +235,29 @@ fn render_runs_view(frame: &mut Frame, area: Rect, state: &WorkerUiState) {
         return;
     }
 
+    let area = if state.groups.is_empty() {
+        area
+    } else {
+        let groups = GroupProgressList::new(&state.groups);
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(groups.height().min(area.height / 3)),
+                Constraint::Min(0),
+            ])
+            .split(area);
+        groups.render(frame, chunks[0]);
+        chunks[1]
+    };
+
     let columns = vec![
         TableColumn::new("Status", 10),
         TableColumn::new("Run ID", 10),
         TableColumn::new("Task ID", 10),
         TableColumn::new("Agent", 15),
         TableColumn::new("Started", 10),
-        TableColumn::flex("Duration", 10),
+        TableColumn::new("Duration", 10),
+        TableColumn::flex("Result", 20),
     ];
 
     let rows: Vec<TableRow> = all_runs
@@ -231,6 +267,7 @@ fn render_runs_view(frame: &mut Frame, area: Rect, state: &WorkerUiState) {
                 RunStatus::Running => ("Running", Color::Yellow),
                 RunStatus::Completed => ("Done", Color::Green),
                 RunStatus::Failed => ("Failed", Color::Red),
+                RunStatus::Cancelled => ("Cancelled", Color::DarkGray),
             };
 
             let duration = if let Some(completed) = run.completed_at {
@@ -246,8 +283,13 @@ fn render_runs_view(frame: &mut Frame, area: Rect, state: &WorkerUiState) {
                 TableCell::new(run.run_id.chars().take(8).collect::<String>()),
                 TableCell::new(run.task_id.chars().take(8).collect::<String>()),
                 TableCell::new(run.agent.clone()),
-                TableCell::muted(run.started_at.format("%H:%M:%S").to_string()),
+                TableCell::muted(state.time_format.time(run.started_at)),
                 TableCell::new(duration),
+                TableCell::muted(
+                    run.result
+                        .as_ref()
+                        .map_or_else(|| "-".to_string(), RunResultSummary::preview),
+                ),
             ])
         })
         .collect();
@@ -288,17 +330,78 @@ fn render_run_detail_view(frame: &mut Frame, area: Rect, state: &WorkerUiState)
             }
         }
         DetailPane::Events => SharedDetailPane::Events,
+        DetailPane::Diff => SharedDetailPane::Outside,
+    };
+
+    // Once the run changed files, their diffs go below the chat and events
+    let area = if run.diffs.is_empty() {
+        area
+    } else {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
+            .split(area);
+        render_diff_pane(
+            frame,
+            chunks[1],
+            &run.diffs,
+            state.diff_scroll,
+            state.detail_pane == DetailPane::Diff,
+        );
+        chunks[0]
     };
 
     // Render using the shared component
     RunDetailView::new(&run_detail_info)
         .focused_pane(focused_pane)
         .chat_scroll(state.chat_scroll)
-        .events_scroll(state.events_scroll)
+        .selected_event(state.selected_event)
         .input(&state.chat_input, state.chat_input_cursor)
+        .time_format(state.time_format)
+        .markdown(!state.raw_chat)
         .render(frame, area);
 }
 
+/// Render the unified diffs of the files a run changed, one after another.
+fn render_diff_pane(frame: &mut Frame, area: Rect, diffs: &[String], scroll: usize, focused: bool) {
+    let lines: Vec<Line> = diffs
+        .iter()
+        .flat_map(|diff| diff.lines().map(diff_line).chain([Line::from("")]))
+        .collect();
+    let theme = Theme::default();
+    let border = if focused {
+        theme.focused_border()
+    } else {
+        theme.unfocused_border()
+    };
+
+    let pane = Paragraph::new(lines)
+        .scroll((scroll.min(u16::MAX as usize) as u16, 0))
+        .block(
+            Block::default()
+                .title(format!(" Diff ({} file changes) ", diffs.len()))
+                .borders(Borders::ALL)
+                .border_style(border),
+        );
+    frame.render_widget(pane, area);
+}
+
+/// A line of a unified diff, coloured by what it is.
+fn diff_line(line: &str) -> Line<'static> {
+    let style = if line.starts_with("+++") || line.starts_with("---") {
+        Style::default().add_modifier(Modifier::BOLD)
+    } else if line.starts_with("@@") {
+        Style::default().fg(Color::Cyan)
+    } else if line.starts_with('+') {
+        Style::default().fg(Color::Green)
+    } else if line.starts_with('-') {
+        Style::default().fg(Color::Red)
+    } else {
+        Style::default()
+    };
+    Line::styled(line.to_string(), style)
+}
+
 /// Convert worker's RunInfo to shared RunDetailInfo.
 fn convert_run_info_to_detail(run: &RunInfo) -> RunDetailInfo {
     // Convert messages
@@ -312,6 +415,7 @@ fn convert_run_info_to_detail(run: &RunInfo) -> RunDetailInfo {
             },
             content: msg.content.clone(),
             timestamp: msg.timestamp,
+            attachments: msg.attachments.iter().map(|a| a.marker()).collect(),
         })
         .collect();
 
@@ -331,6 +435,7 @@ fn convert_run_info_to_detail(run: &RunInfo) -> RunDetailInfo {
         RunStatus::Running => RunDetailStatus::Running,
         RunStatus::Completed => RunDetailStatus::Completed,
         RunStatus::Failed => RunDetailStatus::Failed,
+        RunStatus::Cancelled => RunDetailStatus::Cancelled,
     };
 
     RunDetailInfo {
@@ -344,6 +449,18 @@ fn convert_run_info_to_detail(run: &RunInfo) -> RunDetailInfo {
         events,
         current_output: run.current_output.clone(),
         queued_input: run.queued_input.clone(),
+        // Assignments do not say who created the task
+        created_by: None,
+        milestones: run
+            .milestones
+            .iter()
+            .map(|m| RunDetailMilestone {
+                name: m.name.clone(),
+                percent: m.percent,
+                note: m.note.clone(),
+            })
+            .collect(),
+        result: run.result.as_ref().map(RunResultSummary::preview),
     }
 }
 
@@ -353,6 +470,7 @@ fn render_logs_view(frame: &mut Frame, area: Rect, state: &WorkerUiState) {
 
     LogsWidget::new(&entries)
         .scroll(state.log_scroll_offset)
+        .time_format(state.time_format)
         .render(frame, area);
 }
 
@@ -385,6 +503,10 @@ fn render_config_view(frame: &mut Frame, area: Rect, state: &WorkerUiState) {
             Span::styled("Max Concurrent:    ", Style::default().fg(Color::DarkGray)),
             Span::raw(state.config.max_concurrent_runs.to_string()),
         ]),
+        Line::from(vec![
+            Span::styled("Time Zone:         ", Style::default().fg(Color::DarkGray)),
+            Span::raw(state.time_format.zone.to_string()),
+        ]),
         Line::from(""),
         Line::from(vec![
             Span::styled("CA Certificate:    ", Style::default().fg(Color::DarkGray)),
@@ -440,6 +562,17 @@ fn render_quit_confirm(frame: &mut Frame) {
     ConfirmDialog::new("Confirm", "Quit worker?").render(frame);
 }
 
+/// Render cancel run confirmation dialog.
+fn render_cancel_confirm(frame: &mut Frame, run_id: &str) {
+    ConfirmDialog::new(
+        "Cancel Run",
+        &format!("Cancel run {}?", &run_id[..8.min(run_id.len())]),
+    )
+    .secondary("This will stop its Claude process.")
+    .size(50, 9)
+    .render(frame);
+}
+
 /// Render new run dialog.
 fn render_new_run_dialog(frame: &mut Frame, state: &WorkerUiState) {
     InputDialog::new(
@@ -448,5 +581,133 @@ fn render_new_run_dialog(frame: &mut Frame, state: &WorkerUiState) {
         &state.new_run_prompt,
     )
     .cursor(state.new_run_cursor)
+    .messages(&state.new_run_validation)
     .render(frame);
 }
+
+/// Render the inspector showing an event's full tool input and output.
+fn render_event_inspector(
+    frame: &mut Frame,
+    event: &RunEventInfo,
+    scroll: u16,
+    time_format: TimeFormat,
+) {
+    let screen = frame.area();
+    let area = centered_rect(
+        screen.width.saturating_sub(8),
+        screen.height.saturating_sub(4),
+        screen,
+    );
+
+    frame.render_widget(Clear, area);
+
+    let heading = Style::default()
+        .fg(Color::Cyan)
+        .add_modifier(Modifier::BOLD);
+    let mut lines = vec![Line::from(vec![
+        Span::styled(
+            format!("{} ", time_format.time(event.timestamp)),
+            Style::default().fg(Color::DarkGray),
+        ),
+        Span::styled(
+            event.event_type.clone(),
+            Style::default().add_modifier(Modifier::BOLD),
+        ),
+        Span::raw(
+            event
+                .details
+                .as_ref()
+                .map(|d| format!(" → {}", d))
+                .unwrap_or_default(),
+        ),
+    ])];
+    let payload = event.payload.clone().unwrap_or_default();
+    for (title, text) in [("Input", payload.input), ("Output", payload.output)] {
+        if let Some(text) = text {
+            lines.push(Line::from(""));
+            lines.push(Line::styled(title, heading));
+            lines.extend(text.lines().map(|line| Line::from(line.to_string())));
+        }
+    }
+    if let Some(diff) = payload.diff {
+        lines.push(Line::from(""));
+        lines.push(Line::styled("Diff", heading));
+        lines.extend(diff.lines().map(diff_line));
+    }
+    if event.payload.is_none() {
+        lines.push(Line::from(""));
+        lines.push(Line::styled(
+            "No tool input or output recorded for this event",
+            Style::default().fg(Color::DarkGray),
+        ));
+    }
+
+    let inspector = Paragraph::new(lines)
+        .wrap(Wrap { trim: false })
+        .scroll((scroll, 0))
+        .block(
+            Block::default()
+                .title(" Event [j/k: Scroll | Esc: Close] ")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Cyan)),
+        );
+    frame.render_widget(inspector, area);
+}
+
+/// Render the dialog asking whether the viewed run may use a tool.
+fn render_tool_prompt_dialog(frame: &mut Frame, prompt: &ToolPrompt, pending: usize) {
+    let area = centered_rect(70, 10, frame.area());
+
+    frame.render_widget(Clear, area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints([
+            Constraint::Length(1), // Tool
+            Constraint::Length(1), // Spacing
+            Constraint::Length(3), // Input
+            Constraint::Length(1), // Spacing
+            Constraint::Length(1), // Help
+        ])
+        .split(area);
+
+    let title = if pending > 1 {
+        format!(" Allow Tool? (1 of {}) ", pending)
+    } else {
+        " Allow Tool? ".to_string()
+    };
+    let block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Yellow));
+    frame.render_widget(block, area);
+
+    let tool = Paragraph::new(Line::from(vec![
+        Span::raw("Tool: "),
+        Span::styled(
+            prompt.tool_name.clone(),
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        ),
+    ]));
+    frame.render_widget(tool, chunks[0]);
+
+    let input: String = prompt.input.chars().take(200).collect();
+    let input = Paragraph::new(input).wrap(Wrap { trim: false });
+    frame.render_widget(input, chunks[2]);
+
+    let help = Paragraph::new(Line::from(vec![
+        Span::styled("y", Style::default().fg(Color::Green)),
+        Span::raw(": Allow | "),
+        Span::styled("n", Style::default().fg(Color::Red)),
+        Span::raw(": Deny | "),
+        Span::styled("a", Style::default().fg(Color::Green)),
+        Span::raw(": Always allow | "),
+        Span::styled("Esc", Style::default().fg(Color::Yellow)),
+        Span::raw(": Later"),
+    ]))
+    .alignment(Alignment::Center);
+    frame.render_widget(help, chunks[4]);
+}
@@ -4,9 +4,11 @@ use ratatui::crossterm::event::KeyCode;
 use ratatui::layout::{Constraint, Direction, Layout, Rect};
 use ratatui::style::{Color, Modifier, Style};
 use ratatui::text::{Line, Span};
-use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph, Wrap};
 use ratatui::Frame;
 
+use crate::preflight::{CheckStatus, ReadinessReport};
+
 /// Predefined model options.
 pub const MODEL_OPTIONS: &[&str] = &["sonnet", "opus", "haiku"];
 
@@ -124,28 +126,23 @@ impl SetupState {
                     self.skip_permissions = !self.skip_permissions;
                 }
             }
-            KeyCode::Backspace => {
-                if self.current_field == SetupField::Agent && self.agent_cursor > 0 {
-                    self.agent_cursor -= 1;
-                    self.agent_name.remove(self.agent_cursor);
-                }
+            KeyCode::Backspace
+                if self.current_field == SetupField::Agent && self.agent_cursor > 0 =>
+            {
+                self.agent_cursor -= 1;
+                self.agent_name.remove(self.agent_cursor);
             }
-            KeyCode::Delete => {
+            KeyCode::Delete
                 if self.current_field == SetupField::Agent
-                    && self.agent_cursor < self.agent_name.len()
-                {
-                    self.agent_name.remove(self.agent_cursor);
-                }
+                    && self.agent_cursor < self.agent_name.len() =>
+            {
+                self.agent_name.remove(self.agent_cursor);
             }
-            KeyCode::Home => {
-                if self.current_field == SetupField::Agent {
-                    self.agent_cursor = 0;
-                }
+            KeyCode::Home if self.current_field == SetupField::Agent => {
+                self.agent_cursor = 0;
             }
-            KeyCode::End => {
-                if self.current_field == SetupField::Agent {
-                    self.agent_cursor = self.agent_name.len();
-                }
+            KeyCode::End if self.current_field == SetupField::Agent => {
+                self.agent_cursor = self.agent_name.len();
             }
             KeyCode::Enter => {
                 if self.current_field == SetupField::Start {
@@ -277,6 +274,69 @@ pub fn render_setup(frame: &mut Frame, state: &mut SetupState) {
     }
 }
 
+/// Render the screen shown when pre-flight checks fail or warn.
+pub fn render_readiness(frame: &mut Frame, report: &ReadinessReport) {
+    let ready = report.is_ready();
+    let color = if ready { Color::Yellow } else { Color::Red };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(" Pre-flight checks ")
+        .title_style(Style::default().fg(color).add_modifier(Modifier::BOLD))
+        .border_style(Style::default().fg(color));
+
+    let mut lines = Vec::new();
+    for check in &report.checks {
+        let (mark, style) = match check.status {
+            CheckStatus::Pass => ("✓", Style::default().fg(Color::Green)),
+            CheckStatus::Warn => ("!", Style::default().fg(Color::Yellow)),
+            CheckStatus::Fail => ("✗", Style::default().fg(Color::Red)),
+            CheckStatus::Skipped => ("-", Style::default().fg(Color::DarkGray)),
+        };
+        lines.push(Line::from(vec![
+            Span::styled(format!("{} {:<12}", mark, check.name), style),
+            Span::styled(check.detail.clone(), Style::default().fg(Color::Gray)),
+        ]));
+        lines.extend(
+            check
+                .remediation
+                .iter()
+                .map(|step| Line::from(format!("    • {}", step))),
+        );
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        if ready {
+            "The worker can start, but fix the warnings soon."
+        } else {
+            "Runs would fail until the failed checks are fixed."
+        },
+        Style::default().fg(Color::Gray),
+    )));
+    lines.push(Line::from(""));
+    let mut help = vec![
+        Span::styled("r", Style::default().fg(Color::Cyan)),
+        Span::raw(" check again  "),
+    ];
+    if ready {
+        help.push(Span::styled("Enter", Style::default().fg(Color::Cyan)));
+        help.push(Span::raw(" continue  "));
+    }
+    help.push(Span::styled("Esc", Style::default().fg(Color::Cyan)));
+    help.push(Span::raw(" quit"));
+    lines.push(Line::from(help));
+
+    let area = frame.area();
+    let popup_width = 80.min(area.width.saturating_sub(4));
+    let popup_height = (lines.len() as u16 + 2).min(area.height.saturating_sub(4));
+    let popup_area = centered_rect(popup_width, popup_height, area);
+
+    frame.render_widget(Clear, popup_area);
+    let paragraph = Paragraph::new(lines)
+        .block(block)
+        .wrap(Wrap { trim: false });
+    frame.render_widget(paragraph, popup_area);
+}
+
 /// Render a text input row.
 fn render_text_input_row(
     frame: &mut Frame,
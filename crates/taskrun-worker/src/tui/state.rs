@@ -1,27 +1,57 @@
 //! Worker TUI state types.
 
 use std::collections::VecDeque;
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 use chrono::{DateTime, Utc};
+use taskrun_core::{Artifact, RunMilestone, RunResultSummary};
+
+use super::tool_payload::ToolPayload;
+use crate::agents::AgentRegistry;
+use crate::approvals::ApprovalPolicy;
+use crate::key_store::KeyPassphrase;
+use crate::output_filter::OutputFilters;
+use crate::reconnect::ReconnectPolicy;
+use crate::resource_limits::ResourceLimitConfig;
+use crate::secrets::Secrets;
 
 // Re-export shared types
-pub use taskrun_tui_components::{LogEntry, LogLevel};
+use taskrun_tui_components::TimeFormat;
+pub use taskrun_tui_components::{GroupProgress, LogEntry, LogLevel, ValidationMessage};
 
 /// Worker configuration from CLI arguments.
 #[derive(Debug, Clone)]
 pub struct WorkerConfig {
     pub agent_name: String,
+    /// Agent definitions; the one named `agent_name` applies to its runs.
+    pub agents: AgentRegistry,
+    /// Directory the definitions are reloaded from.
+    pub agents_dir: Option<String>,
+    pub namespace: String,
+    pub labels: Vec<(String, String)>,
     pub model_name: String,
     pub endpoint: String,
     pub ca_cert_path: String,
     pub client_cert_path: String,
     pub client_key_path: String,
+    pub client_key_passphrase: Option<KeyPassphrase>,
     pub allowed_tools: Option<Vec<String>>,
     pub denied_tools: Option<Vec<String>>,
     pub max_concurrent_runs: u32,
+    pub interactive_slots: u32,
     pub working_dir: String,
     pub skip_permissions: bool,
+    pub approval_policy: Option<Arc<ApprovalPolicy>>,
+    pub output_filters: Option<Arc<OutputFilters>>,
+    pub include_thinking: bool,
+    pub progress_tool: bool,
+    pub time_format: TimeFormat,
+    pub claude_path: String,
+    pub skip_claude_check: bool,
+    pub resource_limits: Option<Arc<ResourceLimitConfig>>,
+    pub secrets: Secrets,
+    pub reconnect: ReconnectPolicy,
 }
 
 impl WorkerConfig {
@@ -42,6 +72,40 @@ impl WorkerConfig {
 
         ("anthropic".to_string(), model_name.to_string())
     }
+
+    /// A configuration for tests that never connects anywhere.
+    #[cfg(test)]
+    pub fn for_tests() -> Self {
+        Self {
+            agent_name: "general".to_string(),
+            agents: AgentRegistry::default(),
+            agents_dir: None,
+            namespace: taskrun_core::DEFAULT_NAMESPACE.to_string(),
+            labels: Vec::new(),
+            model_name: "sonnet".to_string(),
+            endpoint: "https://[::1]:50051".to_string(),
+            ca_cert_path: "certs/ca.crt".to_string(),
+            client_cert_path: "certs/worker.crt".to_string(),
+            client_key_path: "certs/worker.key".to_string(),
+            client_key_passphrase: None,
+            allowed_tools: None,
+            denied_tools: None,
+            max_concurrent_runs: 1,
+            interactive_slots: 1,
+            working_dir: ".".to_string(),
+            skip_permissions: true,
+            approval_policy: None,
+            output_filters: None,
+            include_thinking: false,
+            progress_tool: true,
+            time_format: TimeFormat::default(),
+            claude_path: "claude".to_string(),
+            skip_claude_check: true,
+            resource_limits: None,
+            secrets: Secrets::default(),
+            reconnect: ReconnectPolicy::default(),
+        }
+    }
 }
 
 /// Available views in the worker TUI.
@@ -101,7 +165,12 @@ impl WorkerView {
 pub enum ConnectionState {
     Connecting,
     Connected,
-    Disconnected { retry_in: Duration },
+    /// Waiting to reconnect after `attempt` failed attempts in a row;
+    /// `retry_in` is None once the worker gave up.
+    Disconnected {
+        retry_in: Option<Duration>,
+        attempt: u32,
+    },
 }
 
 /// Status of a run.
@@ -110,6 +179,7 @@ pub enum RunStatus {
     Running,
     Completed,
     Failed,
+    Cancelled,
 }
 
 /// Event that occurred during a run.
@@ -118,6 +188,8 @@ pub struct RunEventInfo {
     pub timestamp: DateTime<Utc>,
     pub event_type: String,
     pub details: Option<String>,
+    /// Full tool input and output, for tool events.
+    pub payload: Option<ToolPayload>,
 }
 
 /// Role in a chat message.
@@ -133,6 +205,7 @@ pub struct ChatMessage {
     pub role: ChatRole,
     pub content: String,
     pub timestamp: DateTime<Utc>,
+    pub attachments: Vec<Artifact>,
 }
 
 impl ChatMessage {
@@ -141,14 +214,16 @@ impl ChatMessage {
             role: ChatRole::User,
             content,
             timestamp: Utc::now(),
+            attachments: Vec::new(),
         }
     }
 
-    pub fn assistant(content: String) -> Self {
+    pub fn assistant(content: String, attachments: Vec<Artifact>) -> Self {
         Self {
             role: ChatRole::Assistant,
             content,
             timestamp: Utc::now(),
+            attachments,
         }
     }
 }
@@ -159,6 +234,9 @@ const MAX_OUTPUT_SIZE: usize = 50_000;
 /// Maximum events per run.
 const MAX_EVENTS_PER_RUN: usize = 100;
 
+/// Maximum file diffs per run.
+const MAX_DIFFS_PER_RUN: usize = 100;
+
 /// Maximum messages in chat history.
 const MAX_CHAT_MESSAGES: usize = 100;
 
@@ -174,8 +252,16 @@ pub struct RunInfo {
     pub messages: Vec<ChatMessage>,
     pub current_output: String,
     pub events: Vec<RunEventInfo>,
+    /// Unified diffs of the files the run changed, oldest first.
+    pub diffs: Vec<String>,
     pub queued_input: Option<String>,
     pub session_id: Option<String>,
+    /// Artifacts from this turn's events, attached when the output is finalized.
+    pub pending_attachments: Vec<Artifact>,
+    /// Milestones reported by the agent, in the order first reached.
+    pub milestones: Vec<RunMilestone>,
+    /// What the run produced, once the executor reported it.
+    pub result: Option<RunResultSummary>,
 }
 
 impl RunInfo {
@@ -192,8 +278,12 @@ impl RunInfo {
             messages,
             current_output: String::new(),
             events: Vec::new(),
+            diffs: Vec::new(),
             queued_input: None,
             session_id: None,
+            pending_attachments: Vec::new(),
+            milestones: Vec::new(),
+            result: None,
         }
     }
 
@@ -209,16 +299,25 @@ impl RunInfo {
 
     /// Finalize current output as an assistant message.
     pub fn finalize_output(&mut self) {
-        if !self.current_output.is_empty() {
+        if !self.current_output.is_empty() || !self.pending_attachments.is_empty() {
             if self.messages.len() >= MAX_CHAT_MESSAGES {
                 self.messages.remove(0);
             }
-            self.messages
-                .push(ChatMessage::assistant(self.current_output.clone()));
-            self.current_output.clear();
+            self.messages.push(ChatMessage::assistant(
+                std::mem::take(&mut self.current_output),
+                std::mem::take(&mut self.pending_attachments),
+            ));
         }
     }
 
+    /// The most recent attachment in the chat history.
+    pub fn latest_attachment(&self) -> Option<&Artifact> {
+        self.messages
+            .iter()
+            .rev()
+            .find_map(|m| m.attachments.last())
+    }
+
     /// Add a new user message (for continuation).
     pub fn add_user_message(&mut self, content: String) {
         if self.messages.len() >= MAX_CHAT_MESSAGES {
@@ -227,15 +326,28 @@ impl RunInfo {
         self.messages.push(ChatMessage::user(content));
     }
 
-    /// Add an event, keeping under the limit.
-    pub fn add_event(&mut self, event_type: String, details: Option<String>) {
+    /// Add an event, keeping under the limit, and keep the diff of a file
+    /// change it carries.
+    pub fn add_event(
+        &mut self,
+        event_type: String,
+        details: Option<String>,
+        payload: Option<ToolPayload>,
+    ) {
         if self.events.len() >= MAX_EVENTS_PER_RUN {
             self.events.remove(0);
         }
+        if let Some(diff) = payload.as_ref().and_then(|p| p.diff.clone()) {
+            if self.diffs.len() >= MAX_DIFFS_PER_RUN {
+                self.diffs.remove(0);
+            }
+            self.diffs.push(diff);
+        }
         self.events.push(RunEventInfo {
             timestamp: Utc::now(),
             event_type,
             details,
+            payload,
         });
     }
 
@@ -263,6 +375,18 @@ pub enum DetailPane {
     #[default]
     Output,
     Events,
+    /// Diffs of the files the run changed; only shown once it changed some.
+    Diff,
+}
+
+/// A tool use Claude asks the operator about.
+#[derive(Debug, Clone)]
+pub struct ToolPrompt {
+    pub prompt_id: String,
+    pub run_id: String,
+    pub tool_name: String,
+    /// Tool input as JSON.
+    pub input: String,
 }
 
 /// Main UI state for the worker TUI.
@@ -273,6 +397,8 @@ pub struct WorkerUiState {
     pub current_view: WorkerView,
     pub active_runs: Vec<RunInfo>,
     pub completed_runs: VecDeque<RunInfo>,
+    /// Groups of the runs this worker executed.
+    pub groups: Vec<GroupProgress>,
     pub log_messages: VecDeque<LogEntry>,
     pub stats: WorkerStats,
     pub start_time: Instant,
@@ -283,7 +409,12 @@ pub struct WorkerUiState {
     pub viewing_run_id: Option<String>,
     pub detail_pane: DetailPane,
     pub chat_scroll: usize,
-    pub events_scroll: usize,
+    /// Selected event in the events pane.
+    pub selected_event: usize,
+    /// First line shown in the diff pane.
+    pub diff_scroll: usize,
+    /// Event shown in the inspector, with the inspector's scroll offset.
+    pub inspecting_event: Option<(RunEventInfo, u16)>,
     // Chat input state
     pub chat_input: String,
     pub chat_input_cursor: usize,
@@ -294,10 +425,22 @@ pub struct WorkerUiState {
     pub show_new_run_dialog: bool,
     pub new_run_prompt: String,
     pub new_run_cursor: usize,
+    /// Findings of validating the new run's task when the dialog opened.
+    pub new_run_validation: Vec<ValidationMessage>,
+    /// How timestamps are shown; `T` toggles relative times.
+    pub time_format: TimeFormat,
+    /// Show agent responses as raw text instead of rendered markdown; `m`
+    /// toggles it in run detail.
+    pub raw_chat: bool,
+    /// Run awaiting confirmation to cancel it.
+    pub cancel_confirm: Option<String>,
+    /// Tool prompts waiting for a decision, oldest first.
+    pub tool_prompts: VecDeque<ToolPrompt>,
 }
 
 impl WorkerUiState {
     pub fn new(config: WorkerConfig, worker_id: String) -> Self {
+        let time_format = config.time_format;
         Self {
             config,
             worker_id,
@@ -305,6 +448,7 @@ impl WorkerUiState {
             current_view: WorkerView::Status,
             active_runs: Vec::new(),
             completed_runs: VecDeque::with_capacity(100),
+            groups: Vec::new(),
             log_messages: VecDeque::with_capacity(1000),
             stats: WorkerStats::default(),
             start_time: Instant::now(),
@@ -314,7 +458,9 @@ impl WorkerUiState {
             viewing_run_id: None,
             detail_pane: DetailPane::default(),
             chat_scroll: usize::MAX, // usize::MAX means auto-scroll to bottom
-            events_scroll: 0,
+            selected_event: 0,
+            diff_scroll: 0,
+            inspecting_event: None,
             chat_input: String::new(),
             chat_input_cursor: 0,
             input_focused: true,
@@ -322,6 +468,11 @@ impl WorkerUiState {
             show_new_run_dialog: false,
             new_run_prompt: String::new(),
             new_run_cursor: 0,
+            new_run_validation: Vec::new(),
+            time_format,
+            raw_chat: false,
+            cancel_confirm: None,
+            tool_prompts: VecDeque::new(),
         }
     }
 
@@ -351,16 +502,70 @@ impl WorkerUiState {
             self.current_view = WorkerView::RunDetail;
             self.detail_pane = DetailPane::Output;
             self.chat_scroll = 0;
-            self.events_scroll = 0;
+            self.selected_event = 0;
+            self.diff_scroll = 0;
             self.chat_input.clear();
             self.chat_input_cursor = 0;
             self.input_focused = true;
         }
     }
 
+    /// The oldest tool prompt of the viewed run, and how many the run has
+    /// waiting.
+    pub fn viewing_tool_prompt(&self) -> Option<(&ToolPrompt, usize)> {
+        if self.current_view != WorkerView::RunDetail {
+            return None;
+        }
+        let run_id = self.viewing_run_id.as_ref()?;
+        let mut prompts = self.tool_prompts.iter().filter(|p| &p.run_id == run_id);
+        let first = prompts.next()?;
+        Some((first, 1 + prompts.count()))
+    }
+
+    /// Move the selection in the events pane by `delta`, within the viewed
+    /// run's events.
+    pub fn move_event_selection(&mut self, delta: isize) {
+        let last = self
+            .get_viewing_run()
+            .map_or(0, |run| run.events.len().saturating_sub(1));
+        self.selected_event = self
+            .selected_event
+            .min(last)
+            .saturating_add_signed(delta)
+            .min(last);
+    }
+
+    /// Scroll the diff pane by `delta` lines, within the viewed run's diffs.
+    pub fn scroll_diff(&mut self, delta: isize) {
+        let last = self.get_viewing_run().map_or(0, |run| {
+            run.diffs
+                .iter()
+                .map(|diff| diff.lines().count() + 1)
+                .sum::<usize>()
+                .saturating_sub(1)
+        });
+        self.diff_scroll = self
+            .diff_scroll
+            .min(last)
+            .saturating_add_signed(delta)
+            .min(last);
+    }
+
+    /// Open the inspector on the selected event of the viewed run.
+    pub fn inspect_selected_event(&mut self) {
+        self.inspecting_event = self
+            .get_viewing_run()
+            .and_then(|run| {
+                run.events
+                    .get(self.selected_event.min(run.events.len().saturating_sub(1)))
+            })
+            .map(|event| (event.clone(), 0));
+    }
+
     /// Exit detail view and return to runs list.
     pub fn exit_run_detail(&mut self) {
         self.viewing_run_id = None;
+        self.inspecting_event = None;
         self.current_view = WorkerView::Runs;
         self.chat_input.clear();
     }
@@ -408,23 +613,19 @@ impl WorkerUiState {
         self.active_runs.push(run);
     }
 
-    pub fn complete_run(&mut self, run_id: &str, success: bool) {
+    pub fn complete_run(&mut self, run_id: &str, status: RunStatus) {
         if let Some(pos) = self.active_runs.iter().position(|r| r.run_id == run_id) {
             let mut run = self.active_runs.remove(pos);
-            run.status = if success {
-                RunStatus::Completed
-            } else {
-                RunStatus::Failed
-            };
             run.completed_at = Some(Utc::now());
 
-            // Update stats
+            // Update stats; cancelled runs count toward neither outcome
             self.stats.total_runs += 1;
-            if success {
-                self.stats.successful_runs += 1;
-            } else {
-                self.stats.failed_runs += 1;
+            match status {
+                RunStatus::Completed => self.stats.successful_runs += 1,
+                RunStatus::Failed => self.stats.failed_runs += 1,
+                RunStatus::Running | RunStatus::Cancelled => {}
             }
+            run.status = status;
 
             // Add to completed runs (keep last 100)
             self.completed_runs.push_front(run);
@@ -0,0 +1,123 @@
+//! Full tool inputs and outputs for the event inspector.
+//!
+//! Run events only carry a one-line summary of a tool call. The TUI's
+//! executor also attaches the call's full input and output to its events,
+//! under metadata keys of their own; the event forwarder redacts them with
+//! the rest of the event, then takes them out before the event goes to the
+//! control plane, so they are only ever shown locally. Edits to files also
+//! carry a unified diff of the change, for the run detail view's Diff pane.
+
+use serde_json::Value;
+use taskrun_core::RunEvent;
+
+/// Metadata key of a tool call's full input.
+const TOOL_INPUT: &str = "tui.tool_input";
+
+/// Metadata key of a tool call's full output.
+const TOOL_OUTPUT: &str = "tui.tool_output";
+
+/// Metadata key of the diff of a tool call's file change.
+const TOOL_DIFF: &str = "tui.file_diff";
+
+/// Longest input, output or diff kept, in bytes.
+const MAX_PAYLOAD_BYTES: usize = 64 * 1024;
+
+/// Full input and output of a tool call, pretty-printed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ToolPayload {
+    pub input: Option<String>,
+    pub output: Option<String>,
+    /// Unified diff of the file the call changed, on ToolCompleted events
+    /// of successful edits.
+    pub diff: Option<String>,
+}
+
+impl ToolPayload {
+    /// Take the payload out of `event`; None if it carries none.
+    pub fn take(event: &mut RunEvent) -> Option<Self> {
+        let payload = Self {
+            input: event.metadata.remove(TOOL_INPUT),
+            output: event.metadata.remove(TOOL_OUTPUT),
+            diff: event.metadata.remove(TOOL_DIFF),
+        };
+        (payload != Self::default()).then_some(payload)
+    }
+}
+
+/// Attach a tool call's input to its ToolRequested event.
+pub fn attach_input(event: &mut RunEvent, input: &Value) {
+    event.metadata.insert(TOOL_INPUT.to_string(), pretty(input));
+}
+
+/// Attach the diff of a tool call's file change to the call's ToolRequested
+/// event, to be carried over to its ToolCompleted event.
+pub fn attach_diff(event: &mut RunEvent, diff: String) {
+    event
+        .metadata
+        .insert(TOOL_DIFF.to_string(), pretty(&Value::String(diff)));
+}
+
+/// Attach a tool call's output to its ToolCompleted event, with the input
+/// and, unless the call failed, the diff from its ToolRequested event.
+pub fn attach_output(event: &mut RunEvent, requested: Option<&RunEvent>, output: &Value) {
+    let failed = event.metadata.get("is_error").is_some_and(|e| e == "true");
+    let carried = |key: &str| requested.and_then(|r| r.metadata.get(key)).cloned();
+    if let Some(input) = carried(TOOL_INPUT) {
+        event.metadata.insert(TOOL_INPUT.to_string(), input);
+    }
+    if let Some(diff) = carried(TOOL_DIFF).filter(|_| !failed) {
+        event.metadata.insert(TOOL_DIFF.to_string(), diff);
+    }
+    event
+        .metadata
+        .insert(TOOL_OUTPUT.to_string(), pretty(output));
+}
+
+/// Text as is, anything else as pretty-printed JSON, cut to
+/// `MAX_PAYLOAD_BYTES`.
+fn pretty(value: &Value) -> String {
+    let mut text = match value {
+        Value::String(text) => text.clone(),
+        value => serde_json::to_string_pretty(value).unwrap_or_default(),
+    };
+    if text.len() > MAX_PAYLOAD_BYTES {
+        let mut end = MAX_PAYLOAD_BYTES;
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        text.truncate(end);
+        text.push_str("\n… (truncated)");
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use taskrun_core::{RunId, TaskId};
+
+    #[test]
+    fn test_payload_follows_the_call_and_is_taken_out() {
+        let mut requested =
+            RunEvent::tool_requested(RunId::generate(), TaskId::generate(), "Bash", None);
+        attach_input(&mut requested, &json!({"command": "ls"}));
+        attach_diff(&mut requested, "+a\n".to_string());
+        let mut completed = RunEvent::tool_completed(RunId::generate(), TaskId::generate(), false);
+        attach_output(&mut completed, Some(&requested), &json!("a\nb"));
+        let mut failed = RunEvent::tool_completed(RunId::generate(), TaskId::generate(), true);
+        attach_output(&mut failed, Some(&requested), &json!("error"));
+
+        let payload = ToolPayload::take(&mut completed).unwrap();
+        assert_eq!(
+            payload.input.as_deref(),
+            Some("{\n  \"command\": \"ls\"\n}")
+        );
+        assert_eq!(payload.output.as_deref(), Some("a\nb"));
+        assert_eq!(payload.diff.as_deref(), Some("+a\n"));
+        assert!(ToolPayload::take(&mut failed).unwrap().diff.is_none());
+        assert!(ToolPayload::take(&mut completed).is_none());
+
+        assert!(pretty(&json!("é".repeat(MAX_PAYLOAD_BYTES))).ends_with("(truncated)"));
+    }
+}
@@ -0,0 +1,188 @@
+//! Tool-use prompts answered in the worker TUI.
+//!
+//! With "Skip Permissions" turned off in setup, Claude asks before tool uses
+//! its permissions don't already allow. The TUI shows each question as a
+//! dialog in the run's detail view, and the run waits until the operator
+//! allows or denies it.
+//! "Always allow" answers later uses of the same tool by the same agent
+//! until the worker exits.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use serde_json::Value;
+use tokio::sync::{mpsc, oneshot};
+use tracing::info;
+
+use super::event::WorkerUiEvent;
+use crate::approvals::ApprovalOutcome;
+
+/// The operator's answer to a tool prompt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ToolDecision {
+    Allow,
+    Deny,
+    /// Allow, and stop asking about the tool for this agent.
+    AlwaysAllow,
+}
+
+/// Prompts waiting for the operator, keyed by prompt ID.
+#[derive(Clone)]
+pub struct ToolPrompts {
+    ui_tx: mpsc::Sender<WorkerUiEvent>,
+    waiting: Arc<Mutex<HashMap<String, oneshot::Sender<ToolDecision>>>>,
+    /// (agent, tool) pairs the operator always allows.
+    always_allowed: Arc<Mutex<HashSet<(String, String)>>>,
+    next_id: Arc<AtomicU64>,
+}
+
+impl fmt::Debug for ToolPrompts {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ToolPrompts").finish_non_exhaustive()
+    }
+}
+
+impl ToolPrompts {
+    /// Create a registry that shows prompts through `ui_tx`.
+    pub fn new(ui_tx: mpsc::Sender<WorkerUiEvent>) -> Self {
+        Self {
+            ui_tx,
+            waiting: Arc::default(),
+            always_allowed: Arc::default(),
+            next_id: Arc::default(),
+        }
+    }
+
+    /// Prompter for the tool uses of one run of `agent_name`.
+    pub fn for_run(&self, run_id: impl Into<String>, agent_name: &str) -> ToolPrompter {
+        ToolPrompter {
+            prompts: self.clone(),
+            run_id: run_id.into(),
+            agent_name: agent_name.to_string(),
+        }
+    }
+
+    /// Deliver the operator's decision. Returns false if the prompt is not
+    /// waiting (already decided, or its run ended).
+    pub fn decide(&self, prompt_id: &str, decision: ToolDecision) -> bool {
+        let waiting = self
+            .waiting
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(prompt_id);
+        waiting.is_some_and(|tx| tx.send(decision).is_ok())
+    }
+
+    fn always_allows(&self, agent_name: &str, tool_name: &str) -> bool {
+        self.always_allowed
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .contains(&(agent_name.to_string(), tool_name.to_string()))
+    }
+}
+
+/// Asks the operator about one run's tool uses.
+#[derive(Debug, Clone)]
+pub struct ToolPrompter {
+    prompts: ToolPrompts,
+    run_id: String,
+    agent_name: String,
+}
+
+impl ToolPrompter {
+    /// Show a prompt for a tool use and wait for the operator's decision.
+    pub async fn request(&self, tool_name: &str, input: &Value) -> ApprovalOutcome {
+        let prompts = &self.prompts;
+        if prompts.always_allows(&self.agent_name, tool_name) {
+            return outcome(true, "always allowed in the worker TUI");
+        }
+
+        let seq = prompts.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let prompt_id = format!("{}-{}", self.run_id, seq);
+        let (tx, rx) = oneshot::channel();
+        prompts
+            .waiting
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(prompt_id.clone(), tx);
+
+        info!(
+            run_id = %self.run_id,
+            prompt_id = %prompt_id,
+            tool = %tool_name,
+            "Asking operator about tool use"
+        );
+        let event = WorkerUiEvent::ToolApprovalRequested {
+            prompt_id: prompt_id.clone(),
+            run_id: self.run_id.clone(),
+            tool_name: tool_name.to_string(),
+            input: input.to_string(),
+        };
+        if prompts.ui_tx.send(event).await.is_err() {
+            prompts.decide(&prompt_id, ToolDecision::Deny);
+            return outcome(false, "worker TUI closed");
+        }
+
+        match rx.await {
+            Ok(ToolDecision::Allow) => outcome(true, "allowed in the worker TUI"),
+            Ok(ToolDecision::AlwaysAllow) => {
+                prompts
+                    .always_allowed
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .insert((self.agent_name.clone(), tool_name.to_string()));
+                outcome(true, "always allowed in the worker TUI")
+            }
+            Ok(ToolDecision::Deny) => outcome(false, "denied in the worker TUI"),
+            Err(_) => outcome(false, "prompt dismissed"),
+        }
+    }
+}
+
+fn outcome(allowed: bool, reason: &str) -> ApprovalOutcome {
+    ApprovalOutcome {
+        allowed,
+        reason: reason.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_always_allow_stops_asking_for_the_agent() {
+        let (ui_tx, mut ui_rx) = mpsc::channel(8);
+        let prompts = ToolPrompts::new(ui_tx);
+        let prompter = prompts.for_run("run-1", "coder");
+
+        let request = tokio::spawn({
+            let prompter = prompter.clone();
+            async move { prompter.request("Bash", &json!({"command": "ls"})).await }
+        });
+        let Some(WorkerUiEvent::ToolApprovalRequested {
+            prompt_id, input, ..
+        }) = ui_rx.recv().await
+        else {
+            panic!("expected a tool prompt");
+        };
+        assert_eq!(input, r#"{"command":"ls"}"#);
+        assert!(prompts.decide(&prompt_id, ToolDecision::AlwaysAllow));
+        assert!(request.await.unwrap().allowed);
+        assert!(!prompts.decide(&prompt_id, ToolDecision::Deny));
+
+        // Later uses by the agent are allowed without a prompt; other agents
+        // are still asked
+        assert!(prompter.request("Bash", &json!({})).await.allowed);
+        assert!(ui_rx.try_recv().is_err());
+        let other = prompts.for_run("run-2", "reviewer");
+        tokio::spawn(async move { other.request("Bash", &json!({})).await });
+        assert!(matches!(
+            ui_rx.recv().await,
+            Some(WorkerUiEvent::ToolApprovalRequested { .. })
+        ));
+    }
+}
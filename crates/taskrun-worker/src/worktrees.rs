@@ -0,0 +1,314 @@
+//! Per-run git worktrees (headless and JSON modes).
+//!
+//! With `--worktrees`, a worker started in a git repository runs each
+//! assignment in a worktree of its own, on a new branch `taskrun/<run_id>`
+//! from the repository's HEAD, so concurrent coding runs do not edit the same
+//! checkout. Worktrees live in `.taskrun/worktrees/` of the repository, which
+//! is added to `.git/info/exclude` along with the rest of `.taskrun/`.
+//!
+//! When a run or follow-up turn completes, what the agent changed is committed
+//! to the run's branch and reported in the run's events with the branch,
+//! commit and diff stat against the base. With `--push-worktrees <remote>` the
+//! branch is pushed too. A worktree is kept while its run can be continued and
+//! removed otherwise; branches that got no commits are deleted with it. Worktrees
+//! left by an earlier worker process are removed at startup, except those of
+//! runs the run journal can still continue.
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use taskrun_core::{RunEvent, RunId, TaskId};
+use thiserror::Error;
+use tokio::process::Command;
+use tracing::{info, warn};
+
+/// Where worktrees are created, relative to the repository root.
+const WORKTREES_DIR: &str = ".taskrun/worktrees";
+
+/// Branch prefix of run branches.
+const BRANCH_PREFIX: &str = "taskrun/";
+
+/// Committer used when the repository has no identity configured.
+const FALLBACK_IDENTITY: [&str; 4] = [
+    "-c",
+    "user.name=TaskRun worker",
+    "-c",
+    "user.email=worker@taskrun.invalid",
+];
+
+/// Worktree errors.
+#[derive(Debug, Error)]
+pub enum WorktreeError {
+    #[error("Failed to run git: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("git {command} failed: {message}")]
+    Git { command: String, message: String },
+}
+
+/// The repository runs get worktrees of.
+#[derive(Debug)]
+pub struct Worktrees {
+    /// Root of the main checkout.
+    root: PathBuf,
+    /// Remote to push run branches to, if any.
+    push_remote: Option<String>,
+    /// Whether commits need the fallback identity.
+    fallback_identity: bool,
+}
+
+/// The worktree of one run.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RunWorktree {
+    pub path: PathBuf,
+    pub branch: String,
+    /// Commit the branch started from.
+    pub base: String,
+}
+
+/// What a run's branch holds after a commit.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BranchReport {
+    pub branch: String,
+    pub commit: String,
+    /// `git diff --stat` of the branch against its base.
+    pub diff_stat: String,
+    /// Remote the branch was pushed to.
+    pub pushed_to: Option<String>,
+    /// Why pushing failed.
+    pub push_error: Option<String>,
+}
+
+impl Worktrees {
+    /// Use the repository containing `dir`, removing worktrees an earlier
+    /// worker left behind other than `keep`.
+    pub async fn open(
+        dir: &Path,
+        push_remote: Option<String>,
+        keep: &[PathBuf],
+    ) -> Result<Self, WorktreeError> {
+        let root = PathBuf::from(git(dir, &["rev-parse", "--show-toplevel"]).await?);
+        let fallback_identity = git(&root, &["config", "user.email"]).await.is_err();
+        let worktrees = Self {
+            root,
+            push_remote,
+            fallback_identity,
+        };
+        worktrees.exclude_taskrun_dir().await?;
+        worktrees.remove_stale(keep).await;
+        Ok(worktrees)
+    }
+
+    /// Root of the main checkout.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Create the worktree and branch of `run_id` from HEAD.
+    pub async fn create(&self, run_id: &str) -> Result<RunWorktree, WorktreeError> {
+        let base = git(&self.root, &["rev-parse", "HEAD"]).await?;
+        let branch = format!("{}{}", BRANCH_PREFIX, run_id);
+        let path = self.root.join(WORKTREES_DIR).join(run_id);
+        let path_arg = path.to_string_lossy();
+        git(
+            &self.root,
+            &["worktree", "add", "-q", "-b", &branch, &path_arg, &base],
+        )
+        .await?;
+        info!(run_id = %run_id, branch = %branch, path = %path.display(), "Created run worktree");
+        Ok(RunWorktree { path, branch, base })
+    }
+
+    /// Commit what the run changed in its worktree, push the branch if
+    /// configured, and report it. None if the branch has no changes.
+    pub async fn commit(
+        &self,
+        worktree: &RunWorktree,
+        message: &str,
+    ) -> Result<Option<BranchReport>, WorktreeError> {
+        let dir = &worktree.path;
+        git(dir, &["add", "-A"]).await?;
+        if git(dir, &["diff", "--cached", "--quiet"]).await.is_err() {
+            let mut args: Vec<&str> = Vec::new();
+            if self.fallback_identity {
+                args.extend(FALLBACK_IDENTITY);
+            }
+            args.extend(["commit", "-q", "-m", message]);
+            git(dir, &args).await?;
+        }
+
+        let commit = git(dir, &["rev-parse", "HEAD"]).await?;
+        if commit == worktree.base {
+            return Ok(None);
+        }
+        let range = format!("{}..{}", worktree.base, commit);
+        let diff_stat = git(dir, &["diff", "--stat", &range]).await?;
+
+        let mut report = BranchReport {
+            branch: worktree.branch.clone(),
+            commit,
+            diff_stat,
+            pushed_to: None,
+            push_error: None,
+        };
+        if let Some(remote) = &self.push_remote {
+            match git(dir, &["push", "-q", remote, &worktree.branch]).await {
+                Ok(_) => report.pushed_to = Some(remote.clone()),
+                Err(e) => {
+                    warn!(branch = %worktree.branch, remote = %remote, error = %e, "Failed to push run branch");
+                    report.push_error = Some(e.to_string());
+                }
+            }
+        }
+        info!(branch = %report.branch, commit = %report.commit, pushed_to = ?report.pushed_to, "Committed run changes");
+        Ok(Some(report))
+    }
+
+    /// Remove the run's worktree, and its branch if it got no commits.
+    pub async fn remove(&self, worktree: &RunWorktree) {
+        let path = worktree.path.to_string_lossy();
+        if let Err(e) = git(&self.root, &["worktree", "remove", "--force", &path]).await {
+            warn!(path = %path, error = %e, "Failed to remove run worktree");
+            return;
+        }
+        let unchanged = git(&self.root, &["rev-parse", &worktree.branch])
+            .await
+            .is_ok_and(|head| head == worktree.base);
+        if unchanged {
+            let _ = git(&self.root, &["branch", "-q", "-D", &worktree.branch]).await;
+        }
+    }
+
+    /// Keep `.taskrun/` (worktrees, staged inputs) out of commits.
+    async fn exclude_taskrun_dir(&self) -> Result<(), WorktreeError> {
+        let exclude = git(&self.root, &["rev-parse", "--git-path", "info/exclude"]).await?;
+        let exclude = self.root.join(exclude);
+        let current = std::fs::read_to_string(&exclude).unwrap_or_default();
+        if current.lines().any(|line| line.trim() == "/.taskrun/") {
+            return Ok(());
+        }
+        if let Some(dir) = exclude.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let mut updated = current;
+        if !updated.is_empty() && !updated.ends_with('\n') {
+            updated.push('\n');
+        }
+        updated.push_str("/.taskrun/\n");
+        std::fs::write(&exclude, updated)?;
+        Ok(())
+    }
+
+    /// Remove worktrees in the worktrees directory other than `keep`; their
+    /// runs ended with the process that created them.
+    async fn remove_stale(&self, keep: &[PathBuf]) {
+        let dir = self.root.join(WORKTREES_DIR);
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            return;
+        };
+        for entry in entries
+            .flatten()
+            .filter(|entry| !keep.contains(&entry.path()))
+        {
+            let path = entry.path().to_string_lossy().into_owned();
+            match git(&self.root, &["worktree", "remove", "--force", &path]).await {
+                Ok(_) => info!(path = %path, "Removed stale run worktree"),
+                Err(e) => warn!(path = %path, error = %e, "Failed to remove stale run worktree"),
+            }
+        }
+        let _ = git(&self.root, &["worktree", "prune"]).await;
+    }
+}
+
+impl BranchReport {
+    /// Event recording the branch in the run's event stream.
+    pub fn into_event(self, run_id: RunId, task_id: TaskId) -> RunEvent {
+        let mut event =
+            RunEvent::branch_updated(run_id, task_id, &self.branch, &self.commit, &self.diff_stat);
+        if let Some(remote) = self.pushed_to {
+            event.metadata.insert("pushed_to".to_string(), remote);
+        }
+        if let Some(error) = self.push_error {
+            event.metadata.insert("push_error".to_string(), error);
+        }
+        event
+    }
+}
+
+/// `program` made absolute if it is a relative path, since runs start in
+/// their worktree rather than the worker's directory.
+pub fn program_path(program: &str) -> String {
+    let path = Path::new(program);
+    if path.is_absolute() || path.components().count() < 2 {
+        return program.to_string();
+    }
+    match std::env::current_dir() {
+        Ok(dir) => dir.join(path).to_string_lossy().into_owned(),
+        Err(_) => program.to_string(),
+    }
+}
+
+/// Run git in `dir`, returning its trimmed stdout.
+async fn git(dir: &Path, args: &[&str]) -> Result<String, WorktreeError> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .kill_on_drop(true)
+        .output()
+        .await?;
+    if !output.status.success() {
+        return Err(WorktreeError::Git {
+            command: args.join(" "),
+            message: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        });
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_run_changes_committed_to_run_branch() {
+        let repo = std::env::temp_dir().join(format!("taskrun-worktrees-{}", std::process::id()));
+        std::fs::create_dir_all(&repo).unwrap();
+        git(&repo, &["init", "-q"]).await.unwrap();
+        std::fs::write(repo.join("README.md"), "hello\n").unwrap();
+        git(&repo, &["add", "-A"]).await.unwrap();
+        let mut commit = FALLBACK_IDENTITY.to_vec();
+        commit.extend(["commit", "-q", "-m", "init"]);
+        git(&repo, &commit).await.unwrap();
+
+        let worktrees = Worktrees::open(&repo, None, &[]).await.unwrap();
+        let idle = worktrees.create("run-1").await.unwrap();
+        let busy = worktrees.create("run-2").await.unwrap();
+        assert_ne!(idle.path, busy.path);
+
+        std::fs::write(busy.path.join("README.md"), "hello\nworld\n").unwrap();
+        assert_eq!(worktrees.commit(&idle, "run-1").await.unwrap(), None);
+        let report = worktrees.commit(&busy, "run-2").await.unwrap().unwrap();
+        assert_eq!(report.branch, "taskrun/run-2");
+        assert!(
+            report.diff_stat.contains("README.md"),
+            "{}",
+            report.diff_stat
+        );
+
+        // The unchanged branch goes with its worktree; the other one stays
+        worktrees.remove(&idle).await;
+        worktrees.remove(&busy).await;
+        assert!(git(&repo, &["rev-parse", "taskrun/run-1"]).await.is_err());
+        assert_eq!(
+            git(&repo, &["rev-parse", "taskrun/run-2"]).await.unwrap(),
+            report.commit
+        );
+        assert_eq!(
+            std::fs::read_to_string(repo.join("README.md")).unwrap(),
+            "hello\n"
+        );
+
+        std::fs::remove_dir_all(repo).unwrap();
+    }
+}
@@ -14,6 +14,9 @@ echo ""
 # Generate certificates if needed
 echo -e "${GREEN}Checking certificates...${NC}"
 "$SCRIPT_DIR/gen-dev-certs.sh"
+if [[ ! -f "$SCRIPT_DIR/../certs/client.crt" ]]; then
+    "$SCRIPT_DIR/gen-client-cert.sh" operator demo
+fi
 echo ""
 
 # Build
@@ -24,19 +27,20 @@ cargo build --workspace --quiet
 cleanup() {
     echo ""
     echo -e "${GREEN}Cleaning up...${NC}"
-    pkill -f taskrun-control-plane 2>/dev/null || true
+    pkill -f taskrun-server 2>/dev/null || true
     pkill -f taskrun-worker 2>/dev/null || true
 }
 trap cleanup EXIT
 
-# Start control plane
+# Start control plane (the standalone taskrun-control-plane crate was folded
+# into taskrun-server; --headless runs it without the TUI)
 echo -e "${GREEN}Starting control plane...${NC}"
-cargo run -p taskrun-control-plane --quiet &
+cargo run -p taskrun-server --quiet -- --headless &
 sleep 2
 
 # Start worker
 echo -e "${GREEN}Starting worker...${NC}"
-cargo run -p taskrun-worker --quiet &
+cargo run -p taskrun-worker --quiet -- --headless &
 sleep 2
 
 # List workers
@@ -0,0 +1,74 @@
+#!/usr/bin/env bash
+# Generate a CLI client certificate for a role, for development/testing.
+# The CN is "<role>:<name>", which the control plane maps to the caller's role.
+#
+# Usage: ./scripts/gen-client-cert.sh [role] [name]
+# Example: ./scripts/gen-client-cert.sh operator alice
+
+set -e
+
+SCRIPT_DIR="$(cd "$(dirname "${BASH_SOURCE[0]}")" && pwd)"
+PROJECT_DIR="$(dirname "$SCRIPT_DIR")"
+CERTS_DIR="$PROJECT_DIR/certs"
+
+# Role (default: admin) and name (default: current user)
+ROLE="${1:-admin}"
+NAME="${2:-${USER:-cli}}"
+
+# Colors
+GREEN='\033[0;32m'
+BLUE='\033[0;34m'
+RED='\033[0;31m'
+NC='\033[0m'
+
+case "$ROLE" in
+    readonly|operator|admin) ;;
+    *)
+        echo -e "${RED}Error: role must be readonly, operator or admin, got '$ROLE'${NC}"
+        exit 1
+        ;;
+esac
+
+echo -e "${BLUE}=== Generating Client Certificate ===${NC}"
+echo -e "Identity: ${GREEN}${ROLE}:${NAME}${NC}"
+
+# Check that CA exists
+if [[ ! -f "$CERTS_DIR/ca.crt" || ! -f "$CERTS_DIR/ca.key" ]]; then
+    echo -e "${RED}Error: CA certificate/key not found in $CERTS_DIR${NC}"
+    echo "Run scripts/gen-dev-certs.sh first to generate the CA."
+    exit 1
+fi
+
+cd "$CERTS_DIR"
+
+# Generate client key (PKCS#8 format required by rustls)
+echo -e "${GREEN}Generating client private key...${NC}"
+openssl ecparam -genkey -name prime256v1 -out client.key.ec 2>/dev/null
+openssl pkcs8 -topk8 -nocrypt -in client.key.ec -out client.key
+rm client.key.ec
+
+# Generate CSR with CN=<role>:<name>
+echo -e "${GREEN}Generating certificate signing request...${NC}"
+openssl req -new -key client.key -out client.csr \
+    -subj "/CN=$ROLE:$NAME/O=TaskRun Client" 2>/dev/null
+
+# Sign with CA (valid for 7 days)
+echo -e "${GREEN}Signing certificate with CA...${NC}"
+openssl x509 -req -days 7 -in client.csr -CA ca.crt -CAkey ca.key \
+    -CAcreateserial -out client.crt 2>/dev/null
+
+# Clean up
+rm -f client.csr ca.srl
+
+# Set permissions
+chmod 600 client.key
+chmod 644 client.crt
+
+echo ""
+echo -e "${GREEN}Client certificate generated successfully:${NC}"
+echo "  Certificate: $CERTS_DIR/client.crt"
+echo "  Private Key: $CERTS_DIR/client.key"
+echo "  Identity:    $ROLE:$NAME"
+echo "  Valid for:   7 days"
+echo ""
+echo "The CLI presents this certificate by default (--client-cert/--client-key)."
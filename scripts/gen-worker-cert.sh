@@ -2,8 +2,8 @@
 # Generate a worker certificate for development/testing.
 # This bypasses the enrollment endpoint for quick local testing.
 #
-# Usage: ./scripts/gen-worker-cert.sh [worker-id]
-# Example: ./scripts/gen-worker-cert.sh worker-dev
+# Usage: ./scripts/gen-worker-cert.sh [worker-id] [agent]
+# Example: ./scripts/gen-worker-cert.sh worker-dev general
 
 set -e
 
@@ -14,6 +14,12 @@ CERTS_DIR="$PROJECT_DIR/certs"
 # Worker ID (default: worker-dev)
 WORKER_ID="${1:-worker-dev}"
 
+# SPIFFE ID (URI SAN), narrowed to an agent if one is given
+SPIFFE_ID="spiffe://taskrun/worker/$WORKER_ID"
+if [[ -n "$2" ]]; then
+    SPIFFE_ID="$SPIFFE_ID/agent/$2"
+fi
+
 # Colors
 GREEN='\033[0;32m'
 BLUE='\033[0;34m'
@@ -22,6 +28,7 @@ NC='\033[0m'
 
 echo -e "${BLUE}=== Generating Worker Certificate ===${NC}"
 echo -e "Worker ID: ${GREEN}${WORKER_ID}${NC}"
+echo -e "SPIFFE ID: ${GREEN}${SPIFFE_ID}${NC}"
 
 # Check that CA exists
 if [[ ! -f "$CERTS_DIR/ca.crt" || ! -f "$CERTS_DIR/ca.key" ]]; then
@@ -45,11 +52,12 @@ openssl req -new -key worker.key -out worker.csr \
 
 # Sign with CA (valid for 7 days)
 echo -e "${GREEN}Signing certificate with CA...${NC}"
+echo "subjectAltName=URI:$SPIFFE_ID" > worker-san.ext
 openssl x509 -req -days 7 -in worker.csr -CA ca.crt -CAkey ca.key \
-    -CAcreateserial -out worker.crt 2>/dev/null
+    -CAcreateserial -out worker.crt -extfile worker-san.ext 2>/dev/null
 
 # Clean up
-rm -f worker.csr ca.srl
+rm -f worker.csr worker-san.ext ca.srl
 
 # Set permissions
 chmod 600 worker.key
@@ -60,6 +68,7 @@ echo -e "${GREEN}Worker certificate generated successfully:${NC}"
 echo "  Certificate: $CERTS_DIR/worker.crt"
 echo "  Private Key: $CERTS_DIR/worker.key"
 echo "  Worker ID:   $WORKER_ID"
+echo "  SPIFFE ID:   $SPIFFE_ID"
 echo "  Valid for:   7 days"
 echo ""
 echo "The worker will present this certificate when connecting to the control plane."
@@ -0,0 +1,78 @@
+#!/usr/bin/env bash
+# Rotate the development CA with a dual-trust window
+#
+#   rotate-ca.sh start   Keep the current CA as certs/ca-previous.crt and
+#                        generate a new certs/ca.crt and certs/ca.key
+#   rotate-ca.sh finish  Re-sign the server certificate with the new CA,
+#                        once every worker has re-enrolled
+#
+# Between the two steps, run the control plane with
+# --previous-ca-cert certs/ca-previous.crt and re-enroll every worker.
+
+set -e
+
+SCRIPT_DIR="$(cd "$(dirname "${BASH_SOURCE[0]}")" && pwd)"
+PROJECT_DIR="$(dirname "$SCRIPT_DIR")"
+CERTS_DIR="$PROJECT_DIR/certs"
+
+# Colors
+GREEN='\033[0;32m'
+BLUE='\033[0;34m'
+RED='\033[0;31m'
+NC='\033[0m'
+
+cd "$CERTS_DIR"
+
+case "$1" in
+    start)
+        if [[ -f "ca-previous.crt" ]]; then
+            echo -e "${RED}A rotation is already in progress (ca-previous.crt exists)${NC}"
+            exit 1
+        fi
+        echo -e "${BLUE}=== Starting CA Rotation ===${NC}"
+        mv ca.crt ca-previous.crt
+        mv ca.key ca-previous.key
+
+        echo -e "${GREEN}Generating new CA key and certificate...${NC}"
+        openssl ecparam -genkey -name prime256v1 -out ca.key.ec 2>/dev/null
+        openssl pkcs8 -topk8 -nocrypt -in ca.key.ec -out ca.key
+        rm ca.key.ec
+        openssl req -new -x509 -days 365 -key ca.key -out ca.crt \
+            -subj "/CN=TaskRun Dev CA" 2>/dev/null
+        # The server keeps its old certificate until the rotation finishes
+        cat ca.crt ca-previous.crt > ca-bundle.crt
+        chmod 600 ca.key ca-previous.key
+        chmod 644 ca.crt ca-previous.crt ca-bundle.crt
+
+        echo ""
+        echo "Restart the control plane with:"
+        echo "  --previous-ca-cert certs/ca-previous.crt"
+        echo "then re-enroll every worker before the window ends. Until the rotation"
+        echo "finishes, CLI clients verify the server with --ca-cert certs/ca-bundle.crt."
+        ;;
+    finish)
+        if [[ ! -f "ca-previous.crt" ]]; then
+            echo -e "${RED}No rotation in progress (ca-previous.crt missing)${NC}"
+            exit 1
+        fi
+        echo -e "${BLUE}=== Finishing CA Rotation ===${NC}"
+        echo -e "${GREEN}Re-signing server certificate with the new CA...${NC}"
+        openssl req -new -key server.key -out server.csr \
+            -subj "/CN=localhost" 2>/dev/null
+        cat > san.ext << EXT
+subjectAltName=DNS:localhost,IP:127.0.0.1,IP:::1
+EXT
+        openssl x509 -req -days 365 -in server.csr -CA ca.crt -CAkey ca.key \
+            -CAcreateserial -out server.crt -extfile san.ext 2>/dev/null
+        rm -f server.csr san.ext ca.srl
+        rm ca-previous.key ca-bundle.crt
+        mv ca-previous.crt "ca-retired-$(date +%Y%m%d).crt"
+
+        echo ""
+        echo "Restart the control plane without --previous-ca-cert."
+        ;;
+    *)
+        echo "Usage: $0 start|finish"
+        exit 1
+        ;;
+esac